@@ -0,0 +1,27 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Helpers for `berth` runtimes: services built into the Berth binary, such
+//! as the registry API, that install and run like any other server.
+//!
+//! A `berth` runtime's command is resolved to the running Berth executable at
+//! install and update time, so the service runs the Berth that installed it.
+//! Its args are a Berth subcommand. These services do not speak MCP, so they
+//! are neither linked into clients nor proxied.
+
+use berth_registry::config::InstalledServer;
+
+/// Runtime type of services built into the Berth binary.
+pub const BUILTIN_RUNTIME: &str = "berth";
+
+/// Returns whether an installed server is a built-in Berth service.
+pub fn is_builtin_service(installed: &InstalledServer) -> bool {
+    installed.runtime.runtime_type == BUILTIN_RUNTIME
+}
+
+/// Returns the path of the running Berth executable.
+pub fn berth_executable() -> Result<String, String> {
+    std::env::current_exe()
+        .map(|exe| exe.to_string_lossy().to_string())
+        .map_err(|e| format!("Failed to resolve berth executable path: {e}"))
+}
@@ -0,0 +1,148 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Timestamped snapshots of client config files taken before `berth link` and
+//! `berth unlink` rewrite them, restored by `berth link <client> --undo`.
+//!
+//! Snapshots live in `<state>/backups/clients/<client>/` as `<epoch_ms>.json`;
+//! a `<epoch_ms>.absent` marker records that the file did not exist yet, so
+//! undoing the first link removes it again. Only the newest
+//! [`MAX_SNAPSHOTS`] are kept.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::paths;
+use crate::time_format::now_epoch_ms;
+
+/// Snapshots kept per client.
+pub const MAX_SNAPSHOTS: usize = 10;
+
+const COPY_EXT: &str = "json";
+const ABSENT_EXT: &str = "absent";
+
+/// Snapshots `config_path` before it is modified; returns the snapshot path.
+pub fn snapshot(client: &str, config_path: &Path) -> Result<PathBuf, String> {
+    snapshot_in(&backups_dir(client)?, config_path, now_epoch_ms())
+}
+
+/// Restores the newest snapshot over `config_path` and drops it from the
+/// history, so repeated undos step further back. Returns `None` when there
+/// is nothing to undo.
+pub fn restore_latest(client: &str, config_path: &Path) -> Result<Option<PathBuf>, String> {
+    restore_latest_in(&backups_dir(client)?, config_path)
+}
+
+fn backups_dir(client: &str) -> Result<PathBuf, String> {
+    paths::client_backups_dir(client).ok_or("Could not determine home directory.".to_string())
+}
+
+fn snapshot_in(dir: &Path, config_path: &Path, now_ms: u64) -> Result<PathBuf, String> {
+    fs::create_dir_all(dir)
+        .map_err(|e| format!("Failed to create backup directory {}: {e}", dir.display()))?;
+    // Two snapshots within one millisecond still need distinct, ordered names.
+    let stamp = list(dir)?
+        .last()
+        .map_or(now_ms, |(last, _)| now_ms.max(last + 1));
+    let path = if config_path.exists() {
+        let path = dir.join(format!("{stamp}.{COPY_EXT}"));
+        fs::copy(config_path, &path)
+            .map_err(|e| format!("Failed to create backup {}: {e}", path.display()))?;
+        path
+    } else {
+        let path = dir.join(format!("{stamp}.{ABSENT_EXT}"));
+        fs::write(&path, "")
+            .map_err(|e| format!("Failed to create backup {}: {e}", path.display()))?;
+        path
+    };
+    prune(dir)?;
+    Ok(path)
+}
+
+fn restore_latest_in(dir: &Path, config_path: &Path) -> Result<Option<PathBuf>, String> {
+    let Some((_, latest)) = list(dir)?.pop() else {
+        return Ok(None);
+    };
+    if latest.extension().is_some_and(|ext| ext == ABSENT_EXT) {
+        if config_path.exists() {
+            fs::remove_file(config_path)
+                .map_err(|e| format!("Failed to remove {}: {e}", config_path.display()))?;
+        }
+    } else {
+        fs::copy(&latest, config_path)
+            .map_err(|e| format!("Failed to restore {}: {e}", config_path.display()))?;
+    }
+    fs::remove_file(&latest)
+        .map_err(|e| format!("Failed to remove backup {}: {e}", latest.display()))?;
+    Ok(Some(latest))
+}
+
+/// Returns a client's snapshots, oldest first.
+fn list(dir: &Path) -> Result<Vec<(u64, PathBuf)>, String> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to read {}: {e}", dir.display())),
+    };
+    let mut snapshots: Vec<(u64, PathBuf)> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| ext == COPY_EXT || ext == ABSENT_EXT)
+        })
+        .filter_map(|path| {
+            let stamp = path.file_stem()?.to_str()?.parse().ok()?;
+            Some((stamp, path))
+        })
+        .collect();
+    snapshots.sort();
+    Ok(snapshots)
+}
+
+fn prune(dir: &Path) -> Result<(), String> {
+    let snapshots = list(dir)?;
+    let excess = snapshots.len().saturating_sub(MAX_SNAPSHOTS);
+    for (_, path) in &snapshots[..excess] {
+        fs::remove_file(path)
+            .map_err(|e| format!("Failed to prune backup {}: {e}", path.display()))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn undo_steps_back_through_snapshots_to_a_missing_file() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("backups");
+        let config = tmp.path().join("mcp.json");
+
+        snapshot_in(&dir, &config, 1_000).unwrap();
+        fs::write(&config, "first").unwrap();
+        snapshot_in(&dir, &config, 1_000).unwrap();
+        fs::write(&config, "second").unwrap();
+
+        assert!(restore_latest_in(&dir, &config).unwrap().is_some());
+        assert_eq!(fs::read_to_string(&config).unwrap(), "first");
+        assert!(restore_latest_in(&dir, &config).unwrap().is_some());
+        assert!(!config.exists());
+        assert!(restore_latest_in(&dir, &config).unwrap().is_none());
+    }
+
+    #[test]
+    fn history_is_bounded() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("backups");
+        let config = tmp.path().join("mcp.json");
+        for i in 0..(MAX_SNAPSHOTS as u64 + 5) {
+            fs::write(&config, i.to_string()).unwrap();
+            snapshot_in(&dir, &config, 10 + i).unwrap();
+        }
+        let kept = list(&dir).unwrap();
+        assert_eq!(kept.len(), MAX_SNAPSHOTS);
+        assert_eq!(kept[0].0, 15);
+    }
+}
@@ -0,0 +1,471 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Per-client MCP protocol compatibility shims, stdio framing, and config
+//! overrides applied by `berth proxy`.
+
+use berth_registry::config::InstalledServer;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use std::fs;
+
+use crate::commands::link::CLIENTS;
+use crate::framing::Framing;
+use crate::paths;
+
+/// First protocol revision with tool annotations and audio content.
+const REV_2025_03_26: &str = "2025-03-26";
+/// First protocol revision with structured tool output and resource links.
+const REV_2025_06_18: &str = "2025-06-18";
+
+/// How the proxy adapts messages for one linked client.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CompatMode {
+    /// Shim based on the protocol revision negotiated in `initialize`.
+    Auto,
+    /// Relay messages unchanged.
+    Off,
+    /// Shim as if the client only understood this protocol revision.
+    Pinned(String),
+}
+
+impl CompatMode {
+    /// Parses `auto`, `off`, or a `YYYY-MM-DD` protocol revision.
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let value = value.trim();
+        match value {
+            "auto" => Ok(CompatMode::Auto),
+            "off" => Ok(CompatMode::Off),
+            _ if is_revision(value) => Ok(CompatMode::Pinned(value.to_string())),
+            _ => Err(format!(
+                "Invalid compat mode `{value}`. Expected `auto`, `off`, or a protocol revision like `{REV_2025_03_26}`."
+            )),
+        }
+    }
+
+    /// Returns the canonical config-file spelling of this mode.
+    pub fn as_config_value(&self) -> String {
+        match self {
+            CompatMode::Auto => "auto".to_string(),
+            CompatMode::Off => "off".to_string(),
+            CompatMode::Pinned(rev) => rev.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CompatFile {
+    #[serde(default)]
+    clients: BTreeMap<String, String>,
+    /// Stdio framing per client, when it is not autodetected.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    framing: BTreeMap<String, String>,
+    /// Config overrides per client, then per server.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    overrides: BTreeMap<String, BTreeMap<String, BTreeMap<String, String>>>,
+}
+
+/// One `<server>.<key>=<value>` config override given to `berth link --set`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientOverride {
+    pub server: String,
+    pub key: String,
+    /// An empty value removes the override.
+    pub value: String,
+}
+
+impl ClientOverride {
+    /// Parses `<server>.<key>=<value>`.
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let invalid = || format!("Invalid override `{input}`. Expected `<server>.<key>=<value>`.");
+        let (target, value) = input.split_once('=').ok_or_else(invalid)?;
+        let (server, key) = target.trim().split_once('.').ok_or_else(invalid)?;
+        if server.is_empty() || key.is_empty() {
+            return Err(invalid());
+        }
+        Ok(ClientOverride {
+            server: server.to_string(),
+            key: key.to_string(),
+            value: value.trim().to_string(),
+        })
+    }
+}
+
+/// Loads the compat mode configured for a client, if any.
+pub fn load_client_compat(client: &str) -> Result<Option<CompatMode>, String> {
+    let file = read_compat_file()?;
+    file.clients
+        .get(client)
+        .map(|value| CompatMode::parse(value))
+        .transpose()
+}
+
+/// Persists the compat mode for a client.
+pub fn set_client_compat(client: &str, mode: &CompatMode) -> Result<(), String> {
+    let mut file = read_compat_file()?;
+    file.clients
+        .insert(client.to_string(), mode.as_config_value());
+    write_compat_file(&file)
+}
+
+/// Loads the stdio framing configured for a client, if any.
+pub fn load_client_framing(client: &str) -> Result<Option<Framing>, String> {
+    let file = read_compat_file()?;
+    file.framing
+        .get(client)
+        .map(|value| Framing::parse(value))
+        .transpose()
+}
+
+/// Persists the stdio framing for a client.
+pub fn set_client_framing(client: &str, framing: Framing) -> Result<(), String> {
+    let mut file = read_compat_file()?;
+    file.framing
+        .insert(client.to_string(), framing.as_str().to_string());
+    write_compat_file(&file)
+}
+
+/// Persists one config override for a client; an empty value removes it.
+pub fn set_client_override(client: &str, entry: &ClientOverride) -> Result<(), String> {
+    let mut file = read_compat_file()?;
+    let servers = file.overrides.entry(client.to_string()).or_default();
+    let keys = servers.entry(entry.server.clone()).or_default();
+    if entry.value.is_empty() {
+        keys.remove(&entry.key);
+    } else {
+        keys.insert(entry.key.clone(), entry.value.clone());
+    }
+    if keys.is_empty() {
+        servers.remove(&entry.server);
+    }
+    if servers.is_empty() {
+        file.overrides.remove(client);
+    }
+    write_compat_file(&file)
+}
+
+/// Returns the config overrides a client has for each server.
+pub fn load_client_overrides(
+    client: &str,
+) -> Result<BTreeMap<String, BTreeMap<String, String>>, String> {
+    Ok(read_compat_file()?
+        .overrides
+        .remove(client)
+        .unwrap_or_default())
+}
+
+/// Returns whether any client has config overrides for `server`.
+pub fn server_has_client_overrides(server: &str) -> Result<bool, String> {
+    Ok(read_compat_file()?
+        .overrides
+        .values()
+        .any(|servers| servers.contains_key(server)))
+}
+
+/// Applies a client's config overrides for `server` on top of its installed config.
+pub fn apply_client_overrides(
+    client: &str,
+    server: &str,
+    installed: &mut InstalledServer,
+) -> Result<(), String> {
+    if let Some(overrides) = load_client_overrides(client)?.remove(server) {
+        tracing::info!(client, server, keys = ?overrides.keys(), "applying client overrides");
+        installed.config.extend(overrides);
+    }
+    Ok(())
+}
+
+/// Maps the `clientInfo.name` a client sends in `initialize` to a linkable client.
+pub fn client_from_info(name: &str) -> Option<&'static str> {
+    let normalized: String = name
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .collect::<String>()
+        .to_ascii_lowercase();
+    let known = [
+        ("claudeai", "claude-desktop"),
+        ("claudedesktop", "claude-desktop"),
+        ("cursor", "cursor"),
+        ("windsurf", "windsurf"),
+        ("continue", "continue"),
+        ("visualstudiocode", "vscode"),
+        ("vscode", "vscode"),
+    ];
+    CLIENTS
+        .into_iter()
+        .find(|client| *client == name)
+        .or_else(|| {
+            known
+                .into_iter()
+                .find(|(prefix, _)| normalized.starts_with(prefix))
+                .map(|(_, client)| client)
+        })
+}
+
+fn write_compat_file(file: &CompatFile) -> Result<(), String> {
+    let path = paths::client_compat_path().ok_or("Could not determine home directory.")?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {e}", parent.display()))?;
+    }
+    let rendered = toml::to_string_pretty(file)
+        .map_err(|e| format!("Failed to serialize compat settings: {e}"))?;
+    fs::write(&path, rendered).map_err(|e| format!("Failed to write {}: {e}", path.display()))
+}
+
+fn read_compat_file() -> Result<CompatFile, String> {
+    let path = paths::client_compat_path().ok_or("Could not determine home directory.")?;
+    if !path.exists() {
+        return Ok(CompatFile::default());
+    }
+    let content =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    toml::from_str(&content).map_err(|e| format!("Failed to parse {}: {e}", path.display()))
+}
+
+/// Returns the revision the client should be treated as, if shimming applies.
+pub fn effective_client_revision(mode: &CompatMode, requested: Option<&str>) -> Option<String> {
+    match mode {
+        CompatMode::Off => None,
+        CompatMode::Pinned(rev) => Some(rev.clone()),
+        CompatMode::Auto => requested.map(ToString::to_string),
+    }
+}
+
+/// Adapts one server->client message for a client revision; returns true when changed.
+///
+/// Downgrades newer result shapes for older clients and, when the server
+/// negotiated an older revision than the client, upgrades JSON text tool
+/// output into `structuredContent`.
+pub fn shim_server_message(
+    message: &mut Value,
+    client_rev: &str,
+    server_rev: Option<&str>,
+) -> bool {
+    let Some(result) = message.get_mut("result").and_then(Value::as_object_mut) else {
+        return false;
+    };
+    let mut changed = false;
+
+    if let Some(tools) = result.get_mut("tools").and_then(Value::as_array_mut) {
+        for tool in tools.iter_mut().filter_map(Value::as_object_mut) {
+            if client_rev < REV_2025_06_18 {
+                changed |= tool.remove("outputSchema").is_some();
+                changed |= tool.remove("title").is_some();
+            }
+            if client_rev < REV_2025_03_26 {
+                changed |= tool.remove("annotations").is_some();
+            }
+        }
+    }
+
+    if client_rev < REV_2025_06_18 {
+        if let Some(structured) = result.remove("structuredContent") {
+            let has_content = result
+                .get("content")
+                .and_then(Value::as_array)
+                .is_some_and(|c| !c.is_empty());
+            if !has_content {
+                result.insert(
+                    "content".to_string(),
+                    json!([{"type": "text", "text": structured.to_string()}]),
+                );
+            }
+            changed = true;
+        }
+    } else if server_rev.is_some_and(|rev| rev < REV_2025_06_18) {
+        changed |= upgrade_structured_content(result);
+    }
+
+    if let Some(content) = result.get_mut("content").and_then(Value::as_array_mut) {
+        for item in content.iter_mut() {
+            changed |= downgrade_content_item(item, client_rev);
+        }
+    }
+    changed
+}
+
+/// Rewrites content types unknown to older clients into text items.
+fn downgrade_content_item(item: &mut Value, client_rev: &str) -> bool {
+    let kind = item.get("type").and_then(Value::as_str).unwrap_or_default();
+    let text = match kind {
+        "resource_link" if client_rev < REV_2025_06_18 => {
+            let uri = item.get("uri").and_then(Value::as_str).unwrap_or_default();
+            format!("Resource: {uri}")
+        }
+        "audio" if client_rev < REV_2025_03_26 => {
+            let mime = item
+                .get("mimeType")
+                .and_then(Value::as_str)
+                .unwrap_or("audio");
+            format!("[{mime} content omitted for this client]")
+        }
+        _ => return false,
+    };
+    *item = json!({"type": "text", "text": text});
+    true
+}
+
+/// Adds `structuredContent` when an older server returned a single JSON object as text.
+fn upgrade_structured_content(result: &mut Map<String, Value>) -> bool {
+    if result.contains_key("structuredContent") {
+        return false;
+    }
+    let parsed = match result.get("content").and_then(Value::as_array) {
+        Some(items) if items.len() == 1 => items[0]
+            .get("text")
+            .and_then(Value::as_str)
+            .and_then(|t| serde_json::from_str::<Value>(t).ok())
+            .filter(Value::is_object),
+        _ => None,
+    };
+    match parsed {
+        Some(structured) => {
+            result.insert("structuredContent".to_string(), structured);
+            true
+        }
+        None => false,
+    }
+}
+
+fn is_revision(value: &str) -> bool {
+    let bytes = value.as_bytes();
+    bytes.len() == 10
+        && bytes[4] == b'-'
+        && bytes[7] == b'-'
+        && bytes
+            .iter()
+            .enumerate()
+            .all(|(i, b)| i == 4 || i == 7 || b.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_accepts_modes_and_revisions() {
+        assert_eq!(CompatMode::parse("auto").unwrap(), CompatMode::Auto);
+        assert_eq!(CompatMode::parse("off").unwrap(), CompatMode::Off);
+        assert_eq!(
+            CompatMode::parse("2024-11-05").unwrap(),
+            CompatMode::Pinned("2024-11-05".to_string())
+        );
+        assert!(CompatMode::parse("latest").is_err());
+        assert!(CompatMode::parse("2024-1-05").is_err());
+    }
+
+    #[test]
+    fn client_overrides_parse_and_clients_map_from_handshake_names() {
+        assert_eq!(
+            ClientOverride::parse("github.max_results=10").unwrap(),
+            ClientOverride {
+                server: "github".to_string(),
+                key: "max_results".to_string(),
+                value: "10".to_string(),
+            }
+        );
+        assert_eq!(ClientOverride::parse("github.token=").unwrap().value, "");
+        assert!(ClientOverride::parse("github=10").is_err());
+        assert!(ClientOverride::parse(".key=10").is_err());
+
+        assert_eq!(client_from_info("claude-ai"), Some("claude-desktop"));
+        assert_eq!(client_from_info("Visual Studio Code"), Some("vscode"));
+        assert_eq!(client_from_info("cursor-vscode"), Some("cursor"));
+        assert_eq!(client_from_info("windsurf"), Some("windsurf"));
+        assert_eq!(client_from_info("claude-code"), None);
+    }
+
+    #[test]
+    fn effective_revision_respects_mode() {
+        assert_eq!(
+            effective_client_revision(&CompatMode::Auto, Some("2025-03-26")),
+            Some("2025-03-26".to_string())
+        );
+        assert_eq!(
+            effective_client_revision(&CompatMode::Off, Some("2025-03-26")),
+            None
+        );
+        assert_eq!(
+            effective_client_revision(&CompatMode::Pinned("2024-11-05".to_string()), None),
+            Some("2024-11-05".to_string())
+        );
+    }
+
+    #[test]
+    fn structured_content_becomes_text_for_older_clients() {
+        let mut msg = json!({"id": 1, "result": {"structuredContent": {"temp": 21}}});
+        assert!(shim_server_message(&mut msg, "2025-03-26", None));
+        assert!(msg["result"].get("structuredContent").is_none());
+        assert_eq!(msg["result"]["content"][0]["type"], "text");
+        assert_eq!(msg["result"]["content"][0]["text"], r#"{"temp":21}"#);
+    }
+
+    #[test]
+    fn structured_content_keeps_existing_text_content() {
+        let mut msg = json!({"id": 1, "result": {
+            "structuredContent": {"temp": 21},
+            "content": [{"type": "text", "text": "21 degrees"}]
+        }});
+        assert!(shim_server_message(&mut msg, "2024-11-05", None));
+        assert_eq!(msg["result"]["content"][0]["text"], "21 degrees");
+    }
+
+    #[test]
+    fn tool_listing_drops_fields_unknown_to_client() {
+        let mut msg = json!({"id": 1, "result": {"tools": [{
+            "name": "t",
+            "title": "T",
+            "outputSchema": {},
+            "annotations": {"readOnlyHint": true}
+        }]}});
+        assert!(shim_server_message(&mut msg, "2024-11-05", None));
+        let tool = &msg["result"]["tools"][0];
+        assert!(tool.get("outputSchema").is_none());
+        assert!(tool.get("annotations").is_none());
+        assert_eq!(tool["name"], "t");
+    }
+
+    #[test]
+    fn newer_content_types_are_downgraded_to_text() {
+        let mut msg = json!({"id": 1, "result": {"content": [
+            {"type": "resource_link", "uri": "file:///a"},
+            {"type": "audio", "mimeType": "audio/wav", "data": "AAAA"}
+        ]}});
+        assert!(shim_server_message(&mut msg, "2024-11-05", None));
+        assert_eq!(msg["result"]["content"][0]["text"], "Resource: file:///a");
+        assert_eq!(
+            msg["result"]["content"][1]["text"],
+            "[audio/wav content omitted for this client]"
+        );
+    }
+
+    #[test]
+    fn json_text_is_upgraded_for_newer_clients_of_older_servers() {
+        let mut msg = json!({"id": 1, "result": {"content": [
+            {"type": "text", "text": "{\"ok\":true}"}
+        ]}});
+        assert!(shim_server_message(
+            &mut msg,
+            "2025-06-18",
+            Some("2024-11-05")
+        ));
+        assert_eq!(msg["result"]["structuredContent"]["ok"], true);
+
+        let mut current = json!({"id": 1, "result": {"content": [
+            {"type": "text", "text": "{\"ok\":true}"}
+        ]}});
+        assert!(!shim_server_message(
+            &mut current,
+            "2025-06-18",
+            Some("2025-06-18")
+        ));
+    }
+
+    #[test]
+    fn non_result_messages_are_untouched() {
+        let mut msg = json!({"method": "notifications/progress"});
+        assert!(!shim_server_message(&mut msg, "2024-11-05", None));
+    }
+}
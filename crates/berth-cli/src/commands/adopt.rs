@@ -0,0 +1,230 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Command handler for `berth adopt`.
+//!
+//! Brings a server process started outside Berth (by hand, or by a client
+//! that spawns servers itself) under management without restarting it.
+//! Without `--pid`, the process is found by its command line: the installed
+//! command's file name followed by its fixed arguments in order.
+
+use colored::Colorize;
+use std::path::Path;
+use std::process::{self, Command, Stdio};
+
+use berth_registry::config::RuntimeInfo;
+use berth_runtime::{output_files, process_table, StartOutcome, SOCKET_PLACEHOLDER};
+
+use crate::commands::start::read_installed;
+use crate::paths;
+use crate::runtime_policy::parse_shutdown_settings;
+
+/// Hidden subcommand that copies an adopted process's output into its log.
+pub const RELAY_COMMAND: &str = "__relay-output";
+
+/// Executes the `berth adopt` command.
+pub fn execute(server: &str, pid: Option<u32>) {
+    let Some(config_path) = paths::server_config_path(server) else {
+        fail("Could not determine home directory.");
+    };
+    if !config_path.exists() {
+        fail(&format!("Server {} is not installed.", server.cyan()));
+    }
+    let Ok(installed) = read_installed(server, &config_path) else {
+        process::exit(1);
+    };
+    let Some(runtime) = paths::runtime_manager() else {
+        fail("Could not determine home directory.");
+    };
+    let (signal, grace_period_secs) = match parse_shutdown_settings(&installed.config) {
+        Ok(settings) => settings,
+        Err(msg) => fail(&msg),
+    };
+
+    let pid = match pid {
+        Some(pid) => pid,
+        None => {
+            let managed = runtime.managed_pids().unwrap_or_default();
+            let own = process::id();
+            let candidates: Vec<u32> = process_table()
+                .into_iter()
+                .filter(|(pid, args)| {
+                    *pid != own && !managed.contains_key(pid) && matches(&installed.runtime, args)
+                })
+                .map(|(pid, _)| pid)
+                .collect();
+            match candidates.as_slice() {
+                [pid] => *pid,
+                [] => fail(&format!(
+                    "No running process looks like {} (`{}`). Pass {} to adopt one by pid.",
+                    server.cyan(),
+                    fingerprint(&installed.runtime).join(" "),
+                    "--pid".bold()
+                )),
+                many => fail(&format!(
+                    "Several processes look like {}: pids {}. Pass {} to choose one.",
+                    server.cyan(),
+                    many.iter()
+                        .map(u32::to_string)
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    "--pid".bold()
+                )),
+            }
+        }
+    };
+
+    match runtime.adopt(server, pid, signal, grace_period_secs) {
+        Ok(StartOutcome::Started) => {
+            println!(
+                "{} Adopted {} (pid {}).",
+                "✓".green().bold(),
+                server.cyan(),
+                pid
+            );
+            let outputs = output_files(pid);
+            if outputs.is_empty() {
+                println!(
+                    "  {}",
+                    "Its output goes to a terminal or pipe, so berth logs only shows lifecycle events."
+                        .dimmed()
+                );
+            }
+            for output in outputs {
+                match spawn_relay(server, pid, &output) {
+                    Ok(()) => println!(
+                        "  Capturing output from {} into {}.",
+                        output.display(),
+                        format!("berth logs {server}").bold()
+                    ),
+                    Err(e) => eprintln!(
+                        "{} Could not capture output from {}: {e}",
+                        "!".yellow().bold(),
+                        output.display()
+                    ),
+                }
+            }
+            println!(
+                "  {} and {} now manage it.",
+                format!("berth stop {server}").bold(),
+                format!("berth restart {server}").bold()
+            );
+        }
+        Ok(StartOutcome::AlreadyRunning) => println!(
+            "{} {} already manages pid {}.",
+            "!".yellow().bold(),
+            server.cyan(),
+            pid
+        ),
+        Err(e) => fail(&format!("Failed to adopt {}: {e}", server.cyan())),
+    }
+}
+
+/// Returns the command-line words that identify a server's processes: the
+/// command's file name and the arguments that do not vary per launch.
+fn fingerprint(runtime: &RuntimeInfo) -> Vec<String> {
+    std::iter::once(file_name(&runtime.command).to_string())
+        .chain(
+            runtime
+                .args
+                .iter()
+                .filter(|arg| !arg.contains(SOCKET_PLACEHOLDER))
+                .cloned(),
+        )
+        .collect()
+}
+
+/// Returns whether `args` runs the server: some word names the command, and
+/// the fixed arguments follow it in order. Interpreters launching the command
+/// as a script (`node /usr/bin/npx ...`) match too.
+fn matches(runtime: &RuntimeInfo, args: &[String]) -> bool {
+    let command = file_name(&runtime.command);
+    let Some(start) = args.iter().position(|arg| file_name(arg) == command) else {
+        return false;
+    };
+    let mut rest = args[start + 1..].iter();
+    fingerprint(runtime)
+        .iter()
+        .skip(1)
+        .all(|expected| rest.any(|arg| arg == expected))
+}
+
+fn file_name(path: &str) -> &str {
+    let name = Path::new(path)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(path);
+    name.strip_suffix(".exe").unwrap_or(name)
+}
+
+/// Starts a detached `__relay-output` that outlives this command.
+fn spawn_relay(server: &str, pid: u32, output: &Path) -> Result<(), String> {
+    let exe = std::env::current_exe().map_err(|e| format!("failed to locate current exe: {e}"))?;
+    Command::new(exe)
+        .arg(RELAY_COMMAND)
+        .arg(server)
+        .arg(pid.to_string())
+        .arg(output)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map(|_| ())
+        .map_err(|e| format!("failed to spawn output relay: {e}"))
+}
+
+fn fail(msg: &str) -> ! {
+    eprintln!("{} {}", "✗".red().bold(), msg);
+    process::exit(1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn runtime(command: &str, args: &[&str]) -> RuntimeInfo {
+        RuntimeInfo {
+            runtime_type: "node".to_string(),
+            command: command.to_string(),
+            args: args.iter().map(ToString::to_string).collect(),
+            transport: "stdio".to_string(),
+            syscalls: Vec::new(),
+            health_check: None,
+            schedule: None,
+            limits: None,
+            socket: None,
+        }
+    }
+
+    fn words(line: &str) -> Vec<String> {
+        line.split_whitespace().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn matches_the_command_and_its_fixed_arguments_in_order() {
+        let npx = runtime("npx", &["-y", "@modelcontextprotocol/server-github"]);
+        assert!(matches(
+            &npx,
+            &words("npx -y @modelcontextprotocol/server-github")
+        ));
+        assert!(matches(
+            &npx,
+            &words("node /usr/local/bin/npx -y @modelcontextprotocol/server-github --verbose")
+        ));
+        assert!(!matches(
+            &npx,
+            &words("npx -y @modelcontextprotocol/server-slack")
+        ));
+        assert!(!matches(
+            &npx,
+            &words("node @modelcontextprotocol/server-github")
+        ));
+
+        let socket = runtime("/opt/db/server", &["--listen", "{socket}"]);
+        assert_eq!(fingerprint(&socket), ["server", "--listen"]);
+        assert!(matches(
+            &socket,
+            &words("/opt/db/server --listen /run/user/1000/berth/db.sock")
+        ));
+    }
+}
@@ -4,22 +4,15 @@
 //! Command handler for `berth analytics`.
 
 use colored::Colorize;
-use serde::{Deserialize, Serialize};
+use serde::Serialize;
 use std::collections::{BTreeMap, BTreeSet};
-use std::fs;
 use std::path::Path;
 use std::process;
-use std::time::{SystemTime, UNIX_EPOCH};
 
-use crate::paths;
+use berth_runtime::{AuditLog, AuditQuery, AuditRecord};
 
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct AuditEvent {
-    timestamp_epoch_secs: u64,
-    server: String,
-    action: String,
-}
+use crate::paths;
+use crate::time_format::{now_epoch_ms, TimestampFormatter};
 
 #[derive(Debug, Serialize, PartialEq, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -36,12 +29,16 @@ pub struct AnalyticsSummary {
     pub estimated_cost_usd: f64,
     pub earliest_event_epoch_secs: Option<u64>,
     pub latest_event_epoch_secs: Option<u64>,
+    pub earliest_event_epoch_ms: Option<u64>,
+    pub latest_event_epoch_ms: Option<u64>,
+    pub proxy_queued_requests: u64,
+    pub proxy_queue_timeouts: u64,
     pub top_actions: Vec<CountStat>,
     pub top_servers: Vec<CountStat>,
 }
 
 /// Executes the `berth analytics` command.
-pub fn execute(server: Option<&str>, since: Option<&str>, top: u32, json: bool) {
+pub fn execute(server: Option<&str>, since: Option<&str>, top: u32, json: bool, utc: bool) {
     let since_secs = match since {
         Some(raw) => match parse_since(raw) {
             Ok(v) => Some(v),
@@ -117,10 +114,22 @@ pub fn execute(server: Option<&str>, since: Option<&str>, top: u32, json: bool)
         summary.estimated_cost_usd
     );
     if let (Some(first), Some(last)) = (
-        summary.earliest_event_epoch_secs,
-        summary.latest_event_epoch_secs,
+        summary.earliest_event_epoch_ms,
+        summary.latest_event_epoch_ms,
     ) {
-        println!("  time range: {first} .. {last}");
+        let formatter = TimestampFormatter::new(utc);
+        println!(
+            "  time range: {} .. {}",
+            formatter.format(first),
+            formatter.format(last)
+        );
+    }
+    if summary.proxy_queued_requests > 0 || summary.proxy_queue_timeouts > 0 {
+        println!(
+            "  proxy queue: {} queued, {} timed out",
+            summary.proxy_queued_requests.to_string().bold(),
+            summary.proxy_queue_timeouts.to_string().bold()
+        );
     }
 
     println!("\n  {}", "Top actions".bold());
@@ -145,12 +154,16 @@ pub fn empty_summary(top: usize) -> AnalyticsSummary {
         estimated_cost_usd: 0.0,
         earliest_event_epoch_secs: None,
         latest_event_epoch_secs: None,
+        earliest_event_epoch_ms: None,
+        latest_event_epoch_ms: None,
+        proxy_queued_requests: 0,
+        proxy_queue_timeouts: 0,
         top_actions: Vec::new(),
         top_servers: Vec::new(),
     }
 }
 
-fn summarize_events(events: &[AuditEvent], top: usize) -> AnalyticsSummary {
+fn summarize_events(events: &[AuditRecord], top: usize) -> AnalyticsSummary {
     if events.is_empty() {
         return empty_summary(top);
     }
@@ -167,8 +180,8 @@ fn summarize_events(events: &[AuditEvent], top: usize) -> AnalyticsSummary {
         *server_counts.entry(event.server.clone()).or_insert(0) += 1;
         servers.insert(event.server.clone());
 
-        earliest = earliest.min(event.timestamp_epoch_secs);
-        latest = latest.max(event.timestamp_epoch_secs);
+        earliest = earliest.min(event.epoch_ms());
+        latest = latest.max(event.epoch_ms());
         estimated_cost_usd += action_cost_estimate_usd(&event.action);
     }
 
@@ -179,8 +192,18 @@ fn summarize_events(events: &[AuditEvent], top: usize) -> AnalyticsSummary {
         total_events: events.len() as u64,
         unique_servers: servers.len() as u64,
         estimated_cost_usd,
-        earliest_event_epoch_secs: Some(earliest),
-        latest_event_epoch_secs: Some(latest),
+        earliest_event_epoch_secs: Some(earliest / 1_000),
+        latest_event_epoch_secs: Some(latest / 1_000),
+        earliest_event_epoch_ms: Some(earliest),
+        latest_event_epoch_ms: Some(latest),
+        proxy_queued_requests: action_counts
+            .get("mcp-request-queued")
+            .copied()
+            .unwrap_or(0),
+        proxy_queue_timeouts: action_counts
+            .get("mcp-request-queue-timeout")
+            .copied()
+            .unwrap_or(0),
         top_actions,
         top_servers,
     }
@@ -205,7 +228,9 @@ fn top_counts(map: &BTreeMap<String, u64>, top: usize) -> Vec<CountStat> {
 
 fn action_cost_estimate_usd(action: &str) -> f64 {
     match action {
-        "proxy-start" | "proxy-end" | "proxy-error" => 0.0020,
+        "proxy-start" | "proxy-end" | "proxy-error" | "wrap-start" | "wrap-end" | "wrap-error" => {
+            0.0020
+        }
         "start" | "stop" | "restart" | "exit" | "auto-restart" => 0.0005,
         _ => 0.0,
     }
@@ -223,13 +248,6 @@ fn print_count_stats(stats: &[CountStat]) {
     }
 }
 
-fn now_epoch_secs() -> u64 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_secs()
-}
-
 /// Parses `--since` values like `30s`, `10m`, `24h`, `7d`.
 pub(crate) fn parse_since(raw: &str) -> Result<u64, String> {
     if raw.len() < 2 {
@@ -257,44 +275,29 @@ pub fn summarize_audit_log(
     since_secs: Option<u64>,
     top: usize,
 ) -> Result<(AnalyticsSummary, usize), String> {
-    let content = fs::read_to_string(path)
+    let query = AuditQuery {
+        server: server.map(str::to_string),
+        since_epoch_ms: since_secs
+            .map(|seconds| now_epoch_ms().saturating_sub(seconds.saturating_mul(1_000))),
+        ..AuditQuery::default()
+    };
+    let page = AuditLog::new(path)
+        .query(&query)
         .map_err(|e| format!("Failed to read audit log {}: {e}", path.display()))?;
 
-    let cutoff = since_secs.map(|seconds| now_epoch_secs().saturating_sub(seconds));
-    let mut events = Vec::new();
-    let mut skipped = 0usize;
-
-    for line in content.lines().filter(|line| !line.trim().is_empty()) {
-        match serde_json::from_str::<AuditEvent>(line) {
-            Ok(event) => {
-                if let Some(server_name) = server {
-                    if event.server != server_name {
-                        continue;
-                    }
-                }
-                if let Some(epoch_cutoff) = cutoff {
-                    if event.timestamp_epoch_secs < epoch_cutoff {
-                        continue;
-                    }
-                }
-                events.push(event);
-            }
-            Err(_) => skipped += 1,
-        }
-    }
-
-    Ok((summarize_events(&events, top), skipped))
+    Ok((summarize_events(&page.entries, top), page.malformed_lines))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    fn ev(ts: u64, server: &str, action: &str) -> AuditEvent {
-        AuditEvent {
+    fn ev(ts: u64, server: &str, action: &str) -> AuditRecord {
+        AuditRecord {
             timestamp_epoch_secs: ts,
             server: server.to_string(),
             action: action.to_string(),
+            ..AuditRecord::default()
         }
     }
 
@@ -327,6 +330,7 @@ mod tests {
         assert_eq!(summary.unique_servers, 2);
         assert_eq!(summary.earliest_event_epoch_secs, Some(100));
         assert_eq!(summary.latest_event_epoch_secs, Some(120));
+        assert_eq!(summary.latest_event_epoch_ms, Some(120_000));
         assert!((summary.estimated_cost_usd - 0.0035).abs() < 0.00001);
         assert_eq!(
             summary.top_servers[0],
@@ -344,4 +348,26 @@ mod tests {
             4
         );
     }
+
+    #[test]
+    fn summarize_events_prefers_millisecond_timestamps() {
+        let mut precise = ev(200, "github", "start");
+        precise.timestamp_epoch_ms = Some(200_250);
+        let summary = summarize_events(&[ev(200, "github", "stop"), precise], 5);
+        assert_eq!(summary.earliest_event_epoch_ms, Some(200_000));
+        assert_eq!(summary.latest_event_epoch_ms, Some(200_250));
+        assert_eq!(summary.latest_event_epoch_secs, Some(200));
+    }
+
+    #[test]
+    fn summarize_events_counts_proxy_queue_metrics() {
+        let events = vec![
+            ev(100, "github", "mcp-request-queued"),
+            ev(101, "github", "mcp-request-queued"),
+            ev(102, "github", "mcp-request-queue-timeout"),
+        ];
+        let summary = summarize_events(&events, 5);
+        assert_eq!(summary.proxy_queued_requests, 2);
+        assert_eq!(summary.proxy_queue_timeouts, 1);
+    }
 }
@@ -4,35 +4,24 @@
 //! Command handler for `berth audit`.
 
 use colored::Colorize;
-use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::Path;
 use std::process;
-use std::time::{SystemTime, UNIX_EPOCH};
 
-use crate::paths;
+use berth_runtime::{AuditLog, AuditQuery, AuditRecord, PERMISSION_CHANGE_ACTION};
 
-#[derive(Debug, Deserialize, Serialize)]
-#[serde(rename_all = "camelCase")]
-struct AuditEvent {
-    timestamp_epoch_secs: u64,
-    server: String,
-    action: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pid: Option<u32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    command: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    args: Option<Vec<String>>,
-}
+use crate::paths;
+use crate::time_format::{now_epoch_ms, TimestampFormatter};
 
 /// Executes the `berth audit` command.
 pub fn execute(
     server: Option<&str>,
     since: Option<&str>,
     action: Option<&str>,
+    pid: Option<u32>,
     json: bool,
     export: Option<&str>,
+    utc: bool,
 ) {
     let since_secs = match since {
         Some(raw) => match parse_since(raw) {
@@ -66,8 +55,16 @@ pub fn execute(
         return;
     }
 
-    let content = match fs::read_to_string(&path) {
-        Ok(c) => c,
+    let now = now_epoch_ms();
+    let query = AuditQuery {
+        server: server.map(str::to_string),
+        action: action.map(str::to_string),
+        pid,
+        since_epoch_ms: since_secs.map(|s| now.saturating_sub(s.saturating_mul(1_000))),
+        ..AuditQuery::default()
+    };
+    let page = match AuditLog::new(&path).query(&query) {
+        Ok(page) => page,
         Err(e) => {
             eprintln!(
                 "{} Failed to read audit log {}: {}",
@@ -78,35 +75,8 @@ pub fn execute(
             process::exit(1);
         }
     };
-
-    let now = now_epoch_secs();
-    let cutoff = since_secs.map(|s| now.saturating_sub(s));
-    let mut events = Vec::new();
-    let mut skipped = 0usize;
-
-    for line in content.lines().filter(|l| !l.trim().is_empty()) {
-        match serde_json::from_str::<AuditEvent>(line) {
-            Ok(ev) => {
-                if let Some(name) = server {
-                    if ev.server != name {
-                        continue;
-                    }
-                }
-                if let Some(filter_action) = action {
-                    if ev.action != filter_action {
-                        continue;
-                    }
-                }
-                if let Some(c) = cutoff {
-                    if ev.timestamp_epoch_secs < c {
-                        continue;
-                    }
-                }
-                events.push(ev);
-            }
-            Err(_) => skipped += 1,
-        }
-    }
+    let events = page.entries;
+    let skipped = page.malformed_lines;
 
     if events.is_empty() {
         if let Some(export_path) = export {
@@ -147,7 +117,7 @@ pub fn execute(
     }
 
     println!(
-        "{} Audit entries{}{}:\n",
+        "{} Audit entries{}{}{}:\n",
         "✓".green().bold(),
         server
             .map(|s| format!(" for {}", s.cyan()))
@@ -155,29 +125,37 @@ pub fn execute(
         action
             .map(|a| format!(" (action={})", a.bold()))
             .unwrap_or_default(),
+        pid.map(|p| format!(" (pid={})", p.to_string().bold()))
+            .unwrap_or_default(),
     );
 
     println!(
-        "  {:<24} {:<20} {:<22} {}",
+        "  {:<12} {:<24} {:<20} {:<42} {}",
+        "ID".bold(),
         "ACTION".bold(),
         "SERVER".bold(),
         "TIME".bold(),
         "PID".bold()
     );
-    println!("  {}", "─".repeat(80));
+    println!("  {}", "─".repeat(113));
+    let formatter = TimestampFormatter::new(utc);
     for ev in &events {
         let pid = ev
             .pid
             .map(|p| p.to_string())
             .unwrap_or_else(|| "-".to_string());
-        let ts = format_timestamp(ev.timestamp_epoch_secs, now);
+        let ts = format_timestamp(&formatter, ev.epoch_ms(), now);
         println!(
-            "  {:<24} {:<20} {:<22} {}",
+            "  {:<12} {:<24} {:<20} {:<42} {}",
+            ev.id.as_deref().unwrap_or("-").dimmed(),
             ev.action.as_str(),
             ev.server.cyan(),
             ts,
             pid
         );
+        if let Some(detail) = permission_change_detail(ev) {
+            println!("  {:<12} {}", "", detail);
+        }
     }
 
     if skipped > 0 {
@@ -189,6 +167,62 @@ pub fn execute(
     }
 }
 
+/// Executes `berth audit --verify`.
+pub fn verify() {
+    let path = match paths::audit_log_path() {
+        Some(p) => p,
+        None => {
+            eprintln!("{} Could not determine home directory.", "✗".red().bold());
+            process::exit(1);
+        }
+    };
+    let report = match AuditLog::new(&path).verify() {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!(
+                "{} Failed to read audit log {}: {}",
+                "✗".red().bold(),
+                path.display(),
+                e
+            );
+            process::exit(1);
+        }
+    };
+    if let Some(broken) = report.first_break {
+        eprintln!(
+            "{} Audit hash chain broken at {}:{}: {}. The entry there, or the one before it, was edited, removed, or written without chaining.",
+            "✗".red().bold(),
+            broken.path.display(),
+            broken.line,
+            broken.reason
+        );
+        process::exit(1);
+    }
+    if report.chained == 0 {
+        println!(
+            "{} No audit entry is hash-chained yet. Set `hash_chain = true` under `[audit]` in policy.toml to start the chain.",
+            "!".yellow().bold()
+        );
+        return;
+    }
+    let unchained = report.entries - report.chained;
+    println!(
+        "{} Audit hash chain intact: {} chained entr{} across {} file(s){}.",
+        "✓".green().bold(),
+        report.chained,
+        if report.chained == 1 { "y" } else { "ies" },
+        report.segments,
+        if unchained > 0 {
+            format!(
+                "; {unchained} earlier entr{} predate chaining",
+                if unchained == 1 { "y" } else { "ies" }
+            )
+        } else {
+            String::new()
+        }
+    );
+}
+
 /// Parses `--since` strings like `30s`, `5m`, `1h`, `7d`.
 fn parse_since(raw: &str) -> Result<u64, String> {
     if raw.len() < 2 {
@@ -210,18 +244,14 @@ fn parse_since(raw: &str) -> Result<u64, String> {
     Ok(n.saturating_mul(mult))
 }
 
-/// Returns current unix timestamp in seconds.
-fn now_epoch_secs() -> u64 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_secs()
-}
-
-/// Formats epoch seconds with a relative-age suffix.
-fn format_timestamp(epoch_secs: u64, now_epoch_secs: u64) -> String {
-    let age = now_epoch_secs.saturating_sub(epoch_secs);
-    format!("{epoch_secs} ({})", format_age(age))
+/// Formats an event time as RFC3339 with a relative-age suffix.
+pub(crate) fn format_timestamp(
+    formatter: &TimestampFormatter,
+    epoch_ms: u64,
+    now_epoch_ms: u64,
+) -> String {
+    let age = now_epoch_ms.saturating_sub(epoch_ms) / 1_000;
+    format!("{} ({})", formatter.format(epoch_ms), format_age(age))
 }
 
 /// Formats age in compact form, e.g. `12s ago`, `5m ago`.
@@ -238,8 +268,44 @@ fn format_age(seconds: u64) -> String {
     format!("{}d ago", seconds / 86_400)
 }
 
+/// Summarizes a permission change: the operation, who made it, and how the
+/// effective permissions changed.
+fn permission_change_detail(ev: &AuditRecord) -> Option<String> {
+    if ev.action != PERMISSION_CHANGE_ACTION {
+        return None;
+    }
+    let change = ev.args.as_deref().unwrap_or_default().join(" ");
+    let actor = ev
+        .actor
+        .as_ref()
+        .map(|actor| actor.describe())
+        .unwrap_or_else(|| "unknown user".to_string());
+    let before = ev.permissions_before.as_deref().unwrap_or_default();
+    let after = ev.permissions_after.as_deref().unwrap_or_default();
+    let mut diff: Vec<String> = after
+        .iter()
+        .filter(|entry| !before.contains(entry))
+        .map(|entry| format!("+{entry}").green().to_string())
+        .collect();
+    diff.extend(
+        before
+            .iter()
+            .filter(|entry| !after.contains(entry))
+            .map(|entry| format!("-{entry}").red().to_string()),
+    );
+    if diff.is_empty() {
+        diff.push("no effective change".dimmed().to_string());
+    }
+    Some(format!(
+        "{} by {}: {}",
+        change.bold(),
+        actor,
+        diff.join(" ")
+    ))
+}
+
 /// Writes matching audit events to a file as JSON or JSONL.
-fn write_export(path: &str, events: &[AuditEvent], json: bool) -> Result<(), String> {
+fn write_export(path: &str, events: &[AuditRecord], json: bool) -> Result<(), String> {
     let out = Path::new(path);
     if let Some(parent) = out.parent() {
         if !parent.as_os_str().is_empty() {
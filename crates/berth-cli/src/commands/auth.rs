@@ -0,0 +1,189 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Command handler for `berth auth`.
+
+use berth_common::atomic_write_private;
+use colored::Colorize;
+use std::io::{self, IsTerminal};
+use std::net::TcpListener;
+use std::process;
+use std::time::Duration;
+
+use crate::commands::registry_init::generate_token;
+use crate::commands::start::read_installed;
+use crate::oauth::{
+    authorization_url, client_credentials, exchange_code, redirect_uri, store_refresh_token,
+    validate_spec, wait_for_code,
+};
+use crate::paths;
+use crate::platform;
+use crate::suggest;
+
+/// How long to wait for the user to finish signing in.
+const REDIRECT_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Executes the `berth auth` command.
+pub fn execute(server: &str, no_browser: bool) {
+    let config_path = match paths::server_config_path(server) {
+        Some(p) => p,
+        None => {
+            eprintln!("{} Could not determine home directory.", "✗".red().bold());
+            process::exit(1);
+        }
+    };
+    let installed = if config_path.exists() {
+        match read_installed(server, &config_path) {
+            Ok(i) => Some(i),
+            Err(()) => process::exit(1),
+        }
+    } else {
+        None
+    };
+    // Aliased installs look up the OAuth flow under their registry name.
+    let registry_name = installed
+        .as_ref()
+        .map_or(server, |i| i.server.name.as_str());
+
+    let registry = super::load_registry();
+    let spec = match registry.get(registry_name) {
+        Some(meta) => match &meta.oauth {
+            Some(spec) => spec.clone(),
+            None => {
+                eprintln!(
+                    "{} {} does not declare an OAuth flow.",
+                    "✗".red().bold(),
+                    server.cyan()
+                );
+                process::exit(1);
+            }
+        },
+        None => {
+            eprintln!(
+                "{} Server {} not found in the registry.",
+                "✗".red().bold(),
+                registry_name.cyan()
+            );
+            suggest::print_server_suggestions(registry_name, &registry);
+            process::exit(1);
+        }
+    };
+    if let Err(msg) = validate_spec(&spec) {
+        eprintln!("{} {}", "✗".red().bold(), msg);
+        process::exit(1);
+    }
+
+    let Some(mut installed) = installed else {
+        eprintln!(
+            "{} Server {} is not installed.",
+            "✗".red().bold(),
+            server.cyan()
+        );
+        process::exit(1);
+    };
+    let client = match client_credentials(server, &installed, &spec) {
+        Ok(client) => client,
+        Err(msg) => {
+            eprintln!("{} {}", "✗".red().bold(), msg);
+            process::exit(1);
+        }
+    };
+
+    let listener = match TcpListener::bind(("127.0.0.1", 0)) {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!(
+                "{} Failed to start redirect listener: {}",
+                "✗".red().bold(),
+                e
+            );
+            process::exit(1);
+        }
+    };
+    let port = match listener.local_addr() {
+        Ok(addr) => addr.port(),
+        Err(e) => {
+            eprintln!(
+                "{} Failed to start redirect listener: {}",
+                "✗".red().bold(),
+                e
+            );
+            process::exit(1);
+        }
+    };
+    let redirect = redirect_uri(port);
+    let state = match generate_token() {
+        Ok(state) => state,
+        Err(e) => {
+            eprintln!("{} {}", "✗".red().bold(), e);
+            process::exit(1);
+        }
+    };
+    let verifier = match generate_token() {
+        Ok(verifier) => verifier,
+        Err(e) => {
+            eprintln!("{} {}", "✗".red().bold(), e);
+            process::exit(1);
+        }
+    };
+    let url = authorization_url(&spec, &client, &redirect, &state, &verifier);
+
+    println!("Authorize {} in your browser:", server.cyan());
+    println!("  {}", url.bold());
+    let open_browser =
+        !no_browser && io::stdout().is_terminal() && std::env::var_os("BERTH_NO_BROWSER").is_none();
+    if open_browser {
+        if let Err(e) = platform::open_url(&url) {
+            println!("{} Could not open browser: {}", "!".yellow().bold(), e);
+        }
+    }
+    println!(
+        "{}",
+        format!("Waiting for the redirect on {redirect} ...").dimmed()
+    );
+
+    let tokens = match wait_for_code(&listener, &state, REDIRECT_TIMEOUT)
+        .and_then(|code| exchange_code(&spec, &client, &code, &redirect, &verifier))
+    {
+        Ok(tokens) => tokens,
+        Err(msg) => {
+            eprintln!("{} {}", "✗".red().bold(), msg);
+            process::exit(1);
+        }
+    };
+    let Some(refresh_token) = tokens.refresh_token else {
+        eprintln!(
+            "{} The provider did not return a refresh token; check that the OAuth app allows offline access.",
+            "✗".red().bold()
+        );
+        process::exit(1);
+    };
+    if let Err(msg) = store_refresh_token(server, &mut installed, &refresh_token) {
+        eprintln!(
+            "{} Failed to store refresh token: {}",
+            "✗".red().bold(),
+            msg
+        );
+        process::exit(1);
+    }
+
+    let rendered = match toml::to_string_pretty(&installed) {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("{} Failed to serialize config: {}", "✗".red().bold(), e);
+            process::exit(1);
+        }
+    };
+    // The config references the refresh token, so keep it owner-only.
+    if let Err(e) = atomic_write_private(&config_path, rendered) {
+        eprintln!("{} Failed to write config: {}", "✗".red().bold(), e);
+        process::exit(1);
+    }
+
+    println!(
+        "{} Authorized {}. {} is refreshed on every start.",
+        "✓".green().bold(),
+        server.cyan(),
+        spec.env.bold()
+    );
+}
@@ -11,19 +11,60 @@ use std::io::{self, Write};
 use std::path::{Path, PathBuf};
 use std::process;
 
+use berth_common::now_epoch_secs;
 use berth_registry::config::InstalledServer;
 use berth_registry::Registry;
+use berth_runtime::DEFAULT_SHUTDOWN_GRACE_PERIOD;
 
+use crate::commands::start::build_process_spec;
+use crate::credential_expiry::{
+    classify, credential_expiry, is_credential_expiry_key, validate_credential_expiry_value,
+    CredentialState, KEY_CREDENTIAL_EXPIRES_AT,
+};
+use crate::dependency_policy::{
+    is_dependency_key, parse_depends_on, validate_dependency_value, KEY_DEPENDS_ON,
+};
+use crate::egress_monitor::{
+    is_egress_monitor_key, parse_egress_monitor, validate_egress_monitor_value, KEY_EGRESS_MONITOR,
+};
+use crate::env_isolation::dropped_env_vars;
+use crate::error_budget::{
+    is_error_budget_key, parse_error_budget, validate_error_budget_value,
+    KEY_ERROR_BUDGET_ERROR_RATE, KEY_ERROR_BUDGET_RESTARTS,
+};
+use crate::framing::Framing;
+use crate::job_policy::{
+    is_job, is_schedule_key, parse_schedule, validate_schedule_value, KEY_SCHEDULE,
+};
 use crate::paths;
+use crate::permission_prompt::{
+    is_permission_prompt_key, parse_permission_prompt, validate_permission_prompt_value,
+    PermissionPrompt, KEY_PERMISSION_PROMPT,
+};
+use crate::policy_engine::load_global_policy;
+use crate::proxy_policy::{
+    is_proxy_policy_key, parse_proxy_policy, validate_proxy_policy_value, UpdateStrategy,
+    KEY_FRAMING, KEY_MAX_IN_FLIGHT, KEY_QUEUE_TIMEOUT_MS, KEY_UPDATE_STRATEGY,
+};
+use crate::readiness_policy::{
+    is_readiness_key, parse_readiness, validate_readiness_value, KEY_READINESS,
+    KEY_READINESS_TIMEOUT_MS,
+};
+use crate::replica_policy::{
+    is_replica_key, parse_replica_policy, validate_replica_value, KEY_PORT, KEY_REPLICAS,
+};
 use crate::runtime_policy::{
-    is_runtime_policy_key, parse_runtime_policy, validate_runtime_policy_value, KEY_AUTO_RESTART,
-    KEY_MAX_RESTARTS,
+    capture_core_enabled, is_runtime_policy_key, parse_runtime_policy, parse_shutdown_settings,
+    validate_runtime_policy_value, KEY_AUTO_RESTART, KEY_CAPTURE_CORE, KEY_MAX_RESTARTS,
+    KEY_RESTART_ON_EXIT_CODES, KEY_SHUTDOWN_GRACE_PERIOD_SECS, KEY_SHUTDOWN_SIGNAL,
 };
 use crate::sandbox_policy::{
-    is_sandbox_policy_key, parse_sandbox_policy, validate_sandbox_policy_value, KEY_SANDBOX,
-    KEY_SANDBOX_NETWORK,
+    is_sandbox_policy_key, parse_run_as, parse_sandbox_policy, parse_seccomp,
+    validate_sandbox_policy_value, SandboxNetwork, KEY_RUN_AS, KEY_SANDBOX, KEY_SANDBOX_NETWORK,
+    KEY_SECCOMP,
 };
-use crate::secrets::store_secret;
+use crate::secrets::{plaintext_sensitive_keys, sensitive_config_keys, store_secret};
+use crate::suggest;
 
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -84,13 +125,33 @@ pub fn execute(
         return;
     }
 
+    if server == "migrate-secrets" {
+        if set.is_some() || env || interactive || secure {
+            eprintln!(
+                "{} `config migrate-secrets` does not support {}, {}, {}, or {}.",
+                "✗".red().bold(),
+                "--set".bold(),
+                "--env".bold(),
+                "--interactive".bold(),
+                "--secure".bold()
+            );
+            process::exit(1);
+        }
+        match path {
+            Some(target) => migrate_secrets(target),
+            None => report_plaintext_secrets(),
+        }
+        return;
+    }
+
     if path.is_some() {
         eprintln!(
-            "{} Unexpected extra argument for {}. Use only with {} or {}.",
+            "{} Unexpected extra argument for {}. Use only with {}, {} or {}.",
             "✗".red().bold(),
             server.cyan(),
             "berth config export [file]".bold(),
-            "berth config import <file>".bold()
+            "berth config import <file>".bold(),
+            "berth config migrate-secrets [server]".bold()
         );
         process::exit(1);
     }
@@ -132,7 +193,7 @@ pub fn execute(
             );
             process::exit(1);
         }
-        show_env(server);
+        show_env(server, &config_path);
         return;
     }
 
@@ -187,14 +248,15 @@ fn prompt_config(server: &str, config_path: &Path) {
     };
 
     let registry = Registry::from_seed();
-    let meta = match registry.get(server) {
+    let meta = match registry.get(&installed.server.name) {
         Some(m) => m,
         None => {
             eprintln!(
                 "{} Server {} not found in the registry.",
                 "✗".red().bold(),
-                server.cyan()
+                installed.server.name.cyan()
             );
+            suggest::print_server_suggestions(&installed.server.name, &registry);
             process::exit(1);
         }
     };
@@ -368,6 +430,120 @@ fn show_config(server: &str, config_path: &std::path::Path) {
             KEY_MAX_RESTARTS,
             format!("{}", policy.max_restarts).dimmed()
         );
+        println!(
+            "    {:<24} [{}]",
+            KEY_RESTART_ON_EXIT_CODES,
+            match &policy.restart_on_exit_codes {
+                Some(codes) => codes
+                    .iter()
+                    .map(i32::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+                    .normal(),
+                None => "any exit".dimmed(),
+            }
+        );
+        if installed.runtime.runtime_type == "binary" {
+            println!(
+                "    {:<24} [{}]",
+                KEY_CAPTURE_CORE,
+                if capture_core_enabled(&installed).unwrap_or(false) {
+                    "true".green().to_string()
+                } else {
+                    "false".dimmed().to_string()
+                }
+            );
+        }
+        if let Ok((signal, grace_period_secs)) = parse_shutdown_settings(&installed.config) {
+            println!(
+                "    {:<24} [{}]",
+                KEY_SHUTDOWN_SIGNAL,
+                match signal {
+                    Some(signal) => signal.as_str().normal(),
+                    None => "SIGTERM".dimmed(),
+                }
+            );
+            println!(
+                "    {:<24} [{}]",
+                KEY_SHUTDOWN_GRACE_PERIOD_SECS,
+                match grace_period_secs {
+                    Some(secs) => secs.to_string().normal(),
+                    None => DEFAULT_SHUTDOWN_GRACE_PERIOD.as_secs().to_string().dimmed(),
+                }
+            );
+        }
+        if let Ok(budget) = parse_error_budget(&installed.config) {
+            println!(
+                "    {:<24} [{}]",
+                KEY_ERROR_BUDGET_RESTARTS,
+                format!("{} per 24h", budget.max_restarts).dimmed()
+            );
+            println!(
+                "    {:<24} [{}]",
+                KEY_ERROR_BUDGET_ERROR_RATE,
+                format!("{}%", budget.max_error_rate_percent).dimmed()
+            );
+        }
+        if is_job(&installed) {
+            if let Ok(Some(schedule)) = parse_schedule(&installed) {
+                println!(
+                    "    {:<24} [{}]",
+                    KEY_SCHEDULE,
+                    if installed.config.contains_key(KEY_SCHEDULE) {
+                        schedule.to_string().normal()
+                    } else {
+                        schedule.to_string().dimmed()
+                    }
+                );
+            }
+        }
+    }
+
+    if let Ok(readiness) = parse_readiness(&installed.config) {
+        println!();
+        println!("  {}", "Readiness:".bold());
+        match readiness {
+            Some(check) => {
+                println!(
+                    "    {:<24} [{}]",
+                    KEY_READINESS,
+                    check.probe.to_string().green()
+                );
+                println!(
+                    "    {:<24} [{}]",
+                    KEY_READINESS_TIMEOUT_MS,
+                    format!("{}", check.timeout_ms).dimmed()
+                );
+            }
+            None => println!(
+                "    {:<24} [{}]",
+                KEY_READINESS,
+                "none (running once spawned)".dimmed()
+            ),
+        }
+        let depends_on = parse_depends_on(&installed.config);
+        if !depends_on.is_empty() {
+            println!(
+                "    {:<24} [{}]",
+                KEY_DEPENDS_ON,
+                depends_on.join(", ").cyan()
+            );
+        }
+    }
+
+    if let Ok(Some(policy)) = parse_replica_policy(&installed) {
+        println!();
+        println!("  {}", "HTTP:".bold());
+        println!("    {:<24} [{}]", KEY_PORT, policy.port.to_string().green());
+        println!(
+            "    {:<24} [{}]",
+            KEY_REPLICAS,
+            if installed.config.contains_key(KEY_REPLICAS) {
+                policy.replicas.to_string().normal()
+            } else {
+                policy.replicas.to_string().dimmed()
+            }
+        );
     }
 
     if let Ok(policy) = parse_sandbox_policy(&installed.config) {
@@ -385,15 +561,123 @@ fn show_config(server: &str, config_path: &std::path::Path) {
         println!(
             "    {:<24} [{}]",
             KEY_SANDBOX_NETWORK,
-            if policy.network_deny_all {
-                "deny-all".yellow().to_string()
+            match policy.network {
+                SandboxNetwork::Inherit => "inherit".dimmed().to_string(),
+                SandboxNetwork::DenyAll => "deny-all".yellow().to_string(),
+                SandboxNetwork::Allowlist => "allowlist".green().to_string(),
+            }
+        );
+        println!(
+            "    {:<24} [{}]",
+            KEY_RUN_AS,
+            match parse_run_as(&installed.config) {
+                Ok(Some(user)) => user.green().to_string(),
+                Ok(None) => "current user".dimmed().to_string(),
+                Err(_) => "unsupported".red().to_string(),
+            }
+        );
+        println!(
+            "    {:<24} [{}]",
+            KEY_SECCOMP,
+            match parse_seccomp(&installed.config, &installed.runtime.syscalls) {
+                Ok(Some(profile)) if profile.allow.is_empty() => "default".green().to_string(),
+                Ok(Some(profile)) => format!("default, allows {}", profile.allow.join(", "))
+                    .green()
+                    .to_string(),
+                Ok(None) => "off".dimmed().to_string(),
+                Err(_) => "unsupported".red().to_string(),
+            }
+        );
+        println!(
+            "    {:<24} [{}]",
+            KEY_EGRESS_MONITOR,
+            if parse_egress_monitor(&installed.config).unwrap_or(false) {
+                "log".green().to_string()
             } else {
-                "inherit".dimmed().to_string()
+                "off".dimmed().to_string()
+            }
+        );
+    }
+
+    if let Ok(policy) = parse_proxy_policy(&installed.config) {
+        println!();
+        println!("  {}", "Proxy:".bold());
+        println!(
+            "    {:<24} [{}]",
+            KEY_MAX_IN_FLIGHT,
+            match policy.max_in_flight {
+                Some(max) => max.to_string().green().to_string(),
+                None => "unlimited".dimmed().to_string(),
+            }
+        );
+        println!(
+            "    {:<24} [{}]",
+            KEY_QUEUE_TIMEOUT_MS,
+            format!("{}", policy.queue_timeout_ms).dimmed()
+        );
+        println!(
+            "    {:<24} [{}]",
+            KEY_UPDATE_STRATEGY,
+            match policy.update_strategy {
+                UpdateStrategy::BlueGreen => policy.update_strategy.as_str().green().to_string(),
+                UpdateStrategy::Restart => policy.update_strategy.as_str().dimmed().to_string(),
+            }
+        );
+        println!(
+            "    {:<24} [{}]",
+            KEY_FRAMING,
+            match policy.framing {
+                Framing::Auto => policy.framing.as_str().dimmed().to_string(),
+                _ => policy.framing.as_str().green().to_string(),
+            }
+        );
+        println!(
+            "    {:<24} [{}]",
+            KEY_PERMISSION_PROMPT,
+            match parse_permission_prompt(&installed.config) {
+                Ok(PermissionPrompt::Off) => "off".dimmed().to_string(),
+                Ok(prompt) => prompt.as_str().green().to_string(),
+                Err(_) => "invalid".red().to_string(),
+            }
+        );
+    }
+
+    if let Some(expires_at) = credential_expiry(server, &installed.config) {
+        let state = classify(expires_at, now_epoch_secs());
+        let label = state.describe();
+        println!();
+        println!("  {}", "Credentials:".bold());
+        println!(
+            "    {:<24} [{}]",
+            KEY_CREDENTIAL_EXPIRES_AT,
+            match state {
+                CredentialState::Valid { .. } => label.green().to_string(),
+                CredentialState::ExpiringSoon { .. } => label.yellow().to_string(),
+                CredentialState::Expired { .. } => label.red().to_string(),
             }
         );
     }
 
     println!();
+    warn_plaintext_secrets(server, &installed, &Registry::from_seed());
+}
+
+/// Warns when sensitive values of `installed` are stored in plaintext.
+fn warn_plaintext_secrets(server: &str, installed: &InstalledServer, registry: &Registry) {
+    let plaintext = plaintext_sensitive_keys(installed, registry);
+    if plaintext.is_empty() {
+        return;
+    }
+    eprintln!(
+        "{} Sensitive config for {} is stored in plaintext: {}",
+        "!".yellow().bold(),
+        server.cyan(),
+        plaintext.join(", ").yellow()
+    );
+    eprintln!(
+        "  Run {} to move it into the secure backend.",
+        format!("berth config migrate-secrets {server}").bold()
+    );
 }
 
 /// Sets a single config value (`key=value`) for an installed server.
@@ -436,7 +720,17 @@ fn set_config(server: &str, kv: &str, secure: bool, config_path: &Path) {
             .optional_keys
             .contains(&key.to_string())
         || is_runtime_policy_key(key);
-    let is_known = is_known || is_sandbox_policy_key(key);
+    let is_known = is_known
+        || is_readiness_key(key)
+        || is_sandbox_policy_key(key)
+        || is_egress_monitor_key(key)
+        || is_proxy_policy_key(key)
+        || is_permission_prompt_key(key)
+        || is_credential_expiry_key(key)
+        || is_error_budget_key(key)
+        || is_schedule_key(key)
+        || is_dependency_key(key)
+        || is_replica_key(key);
 
     if !is_known {
         eprintln!("{} Unknown config key: {}", "✗".red().bold(), key.cyan());
@@ -449,8 +743,29 @@ fn set_config(server: &str, kv: &str, secure: bool, config_path: &Path) {
             .collect();
         all_keys.push(KEY_AUTO_RESTART);
         all_keys.push(KEY_MAX_RESTARTS);
+        all_keys.push(KEY_RESTART_ON_EXIT_CODES);
+        all_keys.push(KEY_CAPTURE_CORE);
+        all_keys.push(KEY_SHUTDOWN_SIGNAL);
+        all_keys.push(KEY_SHUTDOWN_GRACE_PERIOD_SECS);
+        all_keys.push(KEY_ERROR_BUDGET_RESTARTS);
+        all_keys.push(KEY_ERROR_BUDGET_ERROR_RATE);
+        all_keys.push(KEY_READINESS);
+        all_keys.push(KEY_READINESS_TIMEOUT_MS);
         all_keys.push(KEY_SANDBOX);
         all_keys.push(KEY_SANDBOX_NETWORK);
+        all_keys.push(KEY_RUN_AS);
+        all_keys.push(KEY_SECCOMP);
+        all_keys.push(KEY_EGRESS_MONITOR);
+        all_keys.push(KEY_MAX_IN_FLIGHT);
+        all_keys.push(KEY_QUEUE_TIMEOUT_MS);
+        all_keys.push(KEY_UPDATE_STRATEGY);
+        all_keys.push(KEY_FRAMING);
+        all_keys.push(KEY_PERMISSION_PROMPT);
+        all_keys.push(KEY_CREDENTIAL_EXPIRES_AT);
+        all_keys.push(KEY_SCHEDULE);
+        all_keys.push(KEY_DEPENDS_ON);
+        all_keys.push(KEY_PORT);
+        all_keys.push(KEY_REPLICAS);
         all_keys.sort_unstable();
         eprintln!("  Known keys: {}", all_keys.join(", "));
         process::exit(1);
@@ -462,12 +777,91 @@ fn set_config(server: &str, kv: &str, secure: bool, config_path: &Path) {
             process::exit(1);
         }
     }
+    if is_readiness_key(key) {
+        if let Err(msg) = validate_readiness_value(key, value) {
+            eprintln!("{} {}", "✗".red().bold(), msg);
+            process::exit(1);
+        }
+    }
     if is_sandbox_policy_key(key) {
         if let Err(msg) = validate_sandbox_policy_value(key, value) {
             eprintln!("{} {}", "✗".red().bold(), msg);
             process::exit(1);
         }
     }
+    if is_egress_monitor_key(key) {
+        if let Err(msg) = validate_egress_monitor_value(key, value) {
+            eprintln!("{} {}", "✗".red().bold(), msg);
+            process::exit(1);
+        }
+    }
+    if is_proxy_policy_key(key) {
+        if let Err(msg) = validate_proxy_policy_value(key, value) {
+            eprintln!("{} {}", "✗".red().bold(), msg);
+            process::exit(1);
+        }
+    }
+    if is_permission_prompt_key(key) {
+        if let Err(msg) = validate_permission_prompt_value(key, value) {
+            eprintln!("{} {}", "✗".red().bold(), msg);
+            process::exit(1);
+        }
+    }
+    if is_credential_expiry_key(key) {
+        if let Err(msg) = validate_credential_expiry_value(key, value) {
+            eprintln!("{} {}", "✗".red().bold(), msg);
+            process::exit(1);
+        }
+    }
+    if is_error_budget_key(key) {
+        if let Err(msg) = validate_error_budget_value(key, value) {
+            eprintln!("{} {}", "✗".red().bold(), msg);
+            process::exit(1);
+        }
+    }
+    if is_schedule_key(key) {
+        if let Err(msg) = validate_schedule_value(key, value) {
+            eprintln!("{} {}", "✗".red().bold(), msg);
+            process::exit(1);
+        }
+    }
+    if is_dependency_key(key) {
+        if let Err(msg) = validate_dependency_value(server, key, value) {
+            eprintln!("{} {}", "✗".red().bold(), msg);
+            process::exit(1);
+        }
+    }
+    if is_replica_key(key) {
+        if let Err(msg) = validate_replica_value(key, value) {
+            eprintln!("{} {}", "✗".red().bold(), msg);
+            process::exit(1);
+        }
+    }
+
+    let sensitive = sensitive_config_keys(&installed, &Registry::from_seed())
+        .iter()
+        .any(|k| k == key);
+    if sensitive && !secure && !value.is_empty() {
+        let forbidden = match load_global_policy() {
+            Ok(policy) => policy.secrets.forbid_plaintext,
+            Err(msg) => {
+                eprintln!("{} {}", "✗".red().bold(), msg);
+                process::exit(1);
+            }
+        };
+        if forbidden {
+            eprintln!(
+                "{} Org policy forbids storing sensitive key {} in plaintext.",
+                "✗".red().bold(),
+                key.cyan()
+            );
+            eprintln!(
+                "  Run {} instead.",
+                format!("berth config {server} --set {key}=<value> --secure").bold()
+            );
+            process::exit(1);
+        }
+    }
 
     let persisted_value = if secure {
         match store_secret(server, key, value) {
@@ -503,6 +897,19 @@ fn set_config(server: &str, kv: &str, secure: bool, config_path: &Path) {
             key.bold(),
             server.cyan()
         );
+    } else if sensitive {
+        println!(
+            "{} Set {} for {}.",
+            "✓".green().bold(),
+            key.bold(),
+            server.cyan()
+        );
+        eprintln!(
+            "{} {} is sensitive and was stored in plaintext; pass {} to use the secure backend.",
+            "!".yellow().bold(),
+            key.bold(),
+            "--secure".bold()
+        );
     } else {
         println!(
             "{} Set {} = {} for {}.",
@@ -514,18 +921,150 @@ fn set_config(server: &str, kv: &str, secure: bool, config_path: &Path) {
     }
 }
 
-/// Prints environment-variable mapping for a registry server definition.
-fn show_env(server: &str) {
+/// Moves plaintext sensitive values of one server into the secure backend.
+fn migrate_secrets(server: &str) {
+    let config_path = match paths::server_config_path(server) {
+        Some(p) => p,
+        None => {
+            eprintln!("{} Could not determine home directory.", "✗".red().bold());
+            process::exit(1);
+        }
+    };
+    if !config_path.exists() {
+        eprintln!(
+            "{} Server {} is not installed.",
+            "✗".red().bold(),
+            server.cyan()
+        );
+        process::exit(1);
+    }
+
+    let mut installed = match read_installed(&config_path) {
+        Ok(v) => v,
+        Err(msg) => {
+            eprintln!("{} {}", "✗".red().bold(), msg);
+            process::exit(1);
+        }
+    };
+
+    let plaintext = plaintext_sensitive_keys(&installed, &Registry::from_seed());
+    if plaintext.is_empty() {
+        println!(
+            "{} No plaintext sensitive config for {}.",
+            "✓".green().bold(),
+            server.cyan()
+        );
+        return;
+    }
+
+    for key in &plaintext {
+        let value = installed.config[key].clone();
+        match store_secret(server, key, &value) {
+            Ok(reference) => {
+                installed.config.insert(key.clone(), reference);
+            }
+            Err(msg) => {
+                eprintln!(
+                    "{} Failed to store {} securely: {}",
+                    "✗".red().bold(),
+                    key.cyan(),
+                    msg
+                );
+                eprintln!("  The config file was not changed.");
+                process::exit(1);
+            }
+        }
+    }
+
+    let rendered = match toml::to_string_pretty(&installed) {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("{} Failed to serialize config: {}", "✗".red().bold(), e);
+            process::exit(1);
+        }
+    };
+    if let Err(e) = fs::write(&config_path, rendered) {
+        eprintln!("{} Failed to write config: {}", "✗".red().bold(), e);
+        process::exit(1);
+    }
+
+    println!(
+        "{} Moved {} sensitive value(s) for {} into the secure backend: {}",
+        "✓".green().bold(),
+        plaintext.len(),
+        server.cyan(),
+        plaintext.join(", ")
+    );
+}
+
+/// Lists installed servers that store sensitive values in plaintext.
+fn report_plaintext_secrets() {
+    let entries = match installed_server_entries() {
+        Ok(v) => v,
+        Err(msg) => {
+            eprintln!("{} {}", "✗".red().bold(), msg);
+            process::exit(1);
+        }
+    };
+
+    let registry = Registry::from_seed();
+    let mut found = 0usize;
+    for (name, config_path) in &entries {
+        let installed = match read_installed(config_path) {
+            Ok(v) => v,
+            Err(msg) => {
+                eprintln!("{} Skipping {}: {}", "!".yellow().bold(), name.cyan(), msg);
+                continue;
+            }
+        };
+        let plaintext = plaintext_sensitive_keys(&installed, &registry);
+        if plaintext.is_empty() {
+            continue;
+        }
+        if found == 0 {
+            println!(
+                "{} Sensitive config stored in plaintext:\n",
+                "!".yellow().bold()
+            );
+        }
+        found += 1;
+        println!("  {:<24} {}", name.cyan(), plaintext.join(", ").yellow());
+    }
+
+    if found == 0 {
+        println!(
+            "{} No plaintext sensitive config in {} installed server(s).",
+            "✓".green().bold(),
+            entries.len()
+        );
+        return;
+    }
+    println!(
+        "\n  Run {} for each server to move the values into the secure backend.",
+        "berth config migrate-secrets <server>".bold()
+    );
+}
+
+/// Prints environment-variable mapping and the launch environment for a server.
+fn show_env(server: &str, config_path: &Path) {
     let registry = Registry::from_seed();
+    let installed = match read_installed(config_path) {
+        Ok(i) => i,
+        Err(msg) => {
+            eprintln!("{} {}", "✗".red().bold(), msg);
+            process::exit(1);
+        }
+    };
 
-    let meta = match registry.get(server) {
+    let meta = match registry.get(&installed.server.name) {
         Some(m) => m,
         None => {
             eprintln!(
                 "{} Server {} not found in the registry.",
                 "✗".red().bold(),
-                server.cyan()
+                installed.server.name.cyan()
             );
+            suggest::print_server_suggestions(&installed.server.name, &registry);
             process::exit(1);
         }
     };
@@ -558,6 +1097,52 @@ fn show_env(server: &str) {
     }
 
     println!();
+    show_launch_env(server, &installed, &registry);
+}
+
+/// Prints which variables an isolated launch passes and which parent variables it drops.
+fn show_launch_env(server: &str, installed: &InstalledServer, registry: &Registry) {
+    let spec = load_global_policy()
+        .and_then(|policy| build_process_spec(server, installed, registry, &policy, false));
+    let (spec, _) = match spec {
+        Ok(v) => v,
+        Err(msg) => {
+            println!(
+                "  {} Launch environment unavailable: {}",
+                "!".yellow().bold(),
+                msg
+            );
+            return;
+        }
+    };
+
+    if !spec.clean_env {
+        println!(
+            "  {} inherited from the parent environment (`env:*` granted)",
+            "Launch environment:".bold()
+        );
+        println!();
+        return;
+    }
+    let passed: Vec<&str> = spec.env.keys().map(String::as_str).collect();
+    let dropped = dropped_env_vars(&spec.env, std::env::vars());
+    println!("  {} isolated", "Launch environment:".bold());
+    println!("    {:<10} {}", "passed:", passed.join(", "));
+    if dropped.is_empty() {
+        println!("    {:<10} {}", "dropped:", "none".dimmed());
+    } else {
+        println!(
+            "    {:<10} {} parent variable(s): {}",
+            "dropped:",
+            dropped.len(),
+            dropped.join(", ").dimmed()
+        );
+    }
+    println!(
+        "\n  Pass {} to start, restart, or proxy to inherit the full parent environment.",
+        "--inherit-env".bold()
+    );
+    println!();
 }
 
 /// Exports all installed non-empty server config values as a TOML bundle.
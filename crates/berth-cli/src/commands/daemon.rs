@@ -0,0 +1,178 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Command handler for `berth daemon`.
+
+use colored::Colorize;
+use std::process::{self, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::daemon::{self, Reply, Request};
+use crate::{paths, platform};
+
+/// How long `berth daemon start` waits for the daemon to answer.
+const START_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Executes the `berth daemon` command.
+pub fn execute(action: Option<&str>) {
+    match action.unwrap_or("run") {
+        "run" => run(),
+        "start" => start(),
+        "stop" => stop(),
+        "status" => status(),
+        "install" => install(),
+        "uninstall" => uninstall(),
+        other => fail(&format!(
+            "Unknown daemon action `{other}`. Use {}, {}, {}, {}, {} or {}.",
+            "run".bold(),
+            "start".bold(),
+            "stop".bold(),
+            "status".bold(),
+            "install".bold(),
+            "uninstall".bold()
+        )),
+    }
+}
+
+/// Serves the control socket in the foreground.
+fn run() {
+    let Some(runtime) = paths::runtime_manager() else {
+        fail("Could not determine home directory.");
+    };
+    if let Err(msg) = daemon::serve(runtime) {
+        fail(&msg);
+    }
+}
+
+/// Starts a detached daemon and waits until it answers.
+fn start() {
+    if let Some((pid, _)) = daemon::ping() {
+        println!(
+            "{} A daemon already runs for this Berth home (pid {pid}).",
+            "!".yellow().bold()
+        );
+        return;
+    }
+    let exe = match std::env::current_exe() {
+        Ok(exe) => exe,
+        Err(e) => fail(&format!("Failed to locate current exe: {e}")),
+    };
+    if let Err(e) = Command::new(exe)
+        .args(["daemon", "run"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+    {
+        fail(&format!("Failed to spawn daemon: {e}"));
+    }
+    let deadline = Instant::now() + START_TIMEOUT;
+    while Instant::now() < deadline {
+        if let Some((pid, _)) = daemon::ping() {
+            println!(
+                "{} Started the Berth daemon (pid {pid}).",
+                "✓".green().bold()
+            );
+            return;
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+    fail(&format!(
+        "The daemon did not answer within {}s. Run {} to see why.",
+        START_TIMEOUT.as_secs(),
+        "berth daemon run".bold()
+    ));
+}
+
+/// Stops the servers the daemon supervises, then the daemon.
+fn stop() {
+    match daemon::request(&Request::Shutdown) {
+        None => println!(
+            "{} No daemon runs for this Berth home.",
+            "!".yellow().bold()
+        ),
+        Some(Ok(Reply::ShuttingDown { stopped })) => {
+            for server in &stopped {
+                println!("{} Stopped {}.", "✓".green().bold(), server.cyan());
+            }
+            println!("{} Stopped the Berth daemon.", "✓".green().bold());
+        }
+        Some(Ok(other)) => fail(&format!("Unexpected reply from the daemon: {other:?}")),
+        Some(Err(msg)) => fail(&format!("Failed to stop the daemon: {msg}")),
+    }
+}
+
+fn status() {
+    let Some((pid, supervised)) = daemon::ping() else {
+        println!("No daemon runs for this Berth home; each started server has its own supervisor.");
+        return;
+    };
+    let endpoint = daemon::endpoint()
+        .map(|e| e.to_string())
+        .unwrap_or_default();
+    println!(
+        "{} Berth daemon running (pid {pid}) on {}.",
+        "✓".green().bold(),
+        endpoint.bold()
+    );
+    if supervised.is_empty() {
+        println!("  Supervising no servers.");
+    } else {
+        let names: Vec<String> = supervised.iter().map(|s| s.cyan().to_string()).collect();
+        println!("  Supervising {}.", names.join(", "));
+    }
+}
+
+/// Registers a Windows logon task that runs `berth start`.
+///
+/// The control socket needs unix domain sockets, so on Windows the task
+/// starts every server with its own supervisor rather than a daemon.
+fn install() {
+    if !cfg!(windows) {
+        fail(&format!(
+            "{} registers a Windows logon task. Here, run {} under systemd or launchd.",
+            "berth daemon install".bold(),
+            "berth daemon run".bold()
+        ));
+    }
+    if std::env::var_os("BERTH_HOME").is_some() {
+        fail("The logon task starts the default Berth home; unset BERTH_HOME to install it.");
+    }
+    let exe = match std::env::current_exe() {
+        Ok(exe) => exe,
+        Err(e) => fail(&format!("Failed to locate current exe: {e}")),
+    };
+    if let Err(msg) = platform::schtasks(&platform::logon_task_create_args(&exe)) {
+        fail(&format!("Failed to register the logon task: {msg}"));
+    }
+    println!(
+        "{} Registered the {} logon task; it runs {} when you sign in.",
+        "✓".green().bold(),
+        platform::LOGON_TASK.bold(),
+        "berth start".bold()
+    );
+}
+
+/// Removes the logon task registered by `berth daemon install`.
+fn uninstall() {
+    if !cfg!(windows) {
+        fail(&format!(
+            "{} removes a Windows logon task; there is none on this platform.",
+            "berth daemon uninstall".bold()
+        ));
+    }
+    if let Err(msg) = platform::schtasks(&platform::logon_task_delete_args()) {
+        fail(&format!("Failed to remove the logon task: {msg}"));
+    }
+    println!(
+        "{} Removed the {} logon task.",
+        "✓".green().bold(),
+        platform::LOGON_TASK.bold()
+    );
+}
+
+fn fail(msg: &str) -> ! {
+    eprintln!("{} {}", "✗".red().bold(), msg);
+    process::exit(1);
+}
@@ -0,0 +1,479 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Command handler for `berth doctor`.
+//!
+//! Checks change nothing apart from the storage write probe; problems come
+//! with the command or change that fixes them, and any failed check makes the
+//! command exit 1.
+
+use colored::Colorize;
+use serde::Serialize;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use std::process;
+
+use berth_registry::config::InstalledServer;
+use berth_registry::Registry;
+use berth_runtime::{RuntimeManager, StaleState};
+
+use crate::commands::install::read_installed_servers;
+use crate::commands::link::CLIENTS;
+use crate::commands::unlink::installed_server_names;
+use crate::sandbox_runtime::path_has_binary;
+use crate::wasm_runtime::{WASMTIME, WASM_RUNTIME};
+use crate::{jsonc, paths, secrets};
+
+/// Outcome of one check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+/// One line of the doctor report.
+#[derive(Debug, Serialize)]
+struct Check {
+    name: &'static str,
+    status: CheckStatus,
+    detail: String,
+    /// What to run or change; set for warnings and failures.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fix: Option<String>,
+}
+
+impl Check {
+    fn pass(name: &'static str, detail: impl Into<String>) -> Self {
+        Check {
+            name,
+            status: CheckStatus::Pass,
+            detail: detail.into(),
+            fix: None,
+        }
+    }
+
+    fn warn(name: &'static str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Check {
+            name,
+            status: CheckStatus::Warn,
+            detail: detail.into(),
+            fix: Some(fix.into()),
+        }
+    }
+
+    fn fail(name: &'static str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Check {
+            name,
+            status: CheckStatus::Fail,
+            detail: detail.into(),
+            fix: Some(fix.into()),
+        }
+    }
+}
+
+/// `berth doctor --json` output.
+#[derive(Debug, Serialize)]
+struct Report {
+    ok: bool,
+    checks: Vec<Check>,
+}
+
+/// Runtimes installed servers launch through, with how to get them.
+const RUNTIMES: [(&str, &str, &str); 4] = [
+    ("node", "npx", "Install Node.js, which provides `npx`"),
+    ("python", "uvx", "Install uv, which provides `uvx`"),
+    (
+        "docker",
+        "docker",
+        "Install Docker or another engine with a `docker` CLI",
+    ),
+    (
+        WASM_RUNTIME,
+        WASMTIME,
+        "Install wasmtime (https://wasmtime.dev)",
+    ),
+];
+
+/// Executes the `berth doctor` command.
+pub fn execute(json: bool) {
+    let (Some(layout), Some(runtime)) = (paths::berth_layout(), paths::runtime_manager()) else {
+        eprintln!("{} Could not determine home directory.", "✗".red().bold());
+        process::exit(1);
+    };
+    let installed = read_installed_servers();
+    let known = installed_server_names().unwrap_or_default();
+
+    let mut checks = check_home(&layout);
+    checks.extend(check_runtime_state(&runtime));
+    checks.extend(check_runtimes(&installed));
+    checks.push(check_registry_cache());
+    checks.push(check_orphaned_logs(&runtime, &known));
+    checks.extend(check_client_links(&known));
+    checks.push(check_keyring(&installed));
+
+    let ok = checks.iter().all(|c| c.status != CheckStatus::Fail);
+    if json {
+        let report = Report { ok, checks };
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&report).unwrap_or_default()
+        );
+    } else {
+        print_report(&checks, ok);
+    }
+    if !ok {
+        process::exit(1);
+    }
+}
+
+fn print_report(checks: &[Check], ok: bool) {
+    for check in checks {
+        let mark = match check.status {
+            CheckStatus::Pass => "✓".green().bold(),
+            CheckStatus::Warn => "!".yellow().bold(),
+            CheckStatus::Fail => "✗".red().bold(),
+        };
+        println!("{mark} {:<15} {}", check.name, check.detail);
+        if let Some(fix) = &check.fix {
+            println!("  {:<15} {} {fix}", "", "fix:".dimmed());
+        }
+    }
+    println!();
+    let failed = checks
+        .iter()
+        .filter(|c| c.status == CheckStatus::Fail)
+        .count();
+    let warned = checks
+        .iter()
+        .filter(|c| c.status == CheckStatus::Warn)
+        .count();
+    if ok {
+        println!(
+            "{} All checks passed ({warned} warning(s)).",
+            "✓".green().bold()
+        );
+    } else {
+        println!(
+            "{} {failed} check(s) failed, {warned} warning(s).",
+            "✗".red().bold()
+        );
+    }
+}
+
+/// Checks that the config and state directories take writes and are private.
+fn check_home(layout: &paths::BerthLayout) -> Vec<Check> {
+    let mut checks = Vec::new();
+    match paths::check_storage() {
+        Ok(()) => checks.push(Check::pass(
+            "berth home",
+            format!("{} is writable", layout.config.display()),
+        )),
+        Err(problem) => checks.push(Check::fail(
+            "berth home",
+            problem.to_string(),
+            format!(
+                "Fix the permissions or free space on {}, or pick another home with `--home`",
+                problem.path().display()
+            ),
+        )),
+    }
+    if let Some(mode) = group_or_world_writable(&layout.config) {
+        checks.push(Check::warn(
+            "berth home",
+            format!(
+                "{} is writable by other users (mode {mode:o})",
+                layout.config.display()
+            ),
+            format!("chmod 700 {}", layout.config.display()),
+        ));
+    }
+    checks
+}
+
+#[cfg(unix)]
+fn group_or_world_writable(dir: &Path) -> Option<u32> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mode = fs::metadata(dir).ok()?.permissions().mode() & 0o777;
+    (mode & 0o022 != 0).then_some(mode)
+}
+
+#[cfg(not(unix))]
+fn group_or_world_writable(_dir: &Path) -> Option<u32> {
+    None
+}
+
+/// Reports state files that record a dead process or cannot be parsed.
+fn check_runtime_state(runtime: &RuntimeManager) -> Vec<Check> {
+    let stale = match runtime.stale_states() {
+        Ok(stale) => stale,
+        Err(e) => {
+            return vec![Check::fail(
+                "runtime state",
+                format!("Failed to read runtime state: {e}"),
+                "Check the permissions of the runtime directory in the Berth home",
+            )]
+        }
+    };
+    if stale.is_empty() {
+        return vec![Check::pass("runtime state", "No stale runtime state files")];
+    }
+    stale
+        .into_iter()
+        .map(|state| match state {
+            StaleState::DeadPid { server, pid } => Check::warn(
+                "runtime state",
+                format!("{server} is recorded as running but pid {pid} is gone"),
+                format!("berth status records the exit; `berth start {server}` restarts it"),
+            ),
+            StaleState::Unreadable { server, error } => Check::fail(
+                "runtime state",
+                format!(
+                    "{} cannot be read: {}",
+                    runtime.state_path(&server).display(),
+                    error.lines().next().unwrap_or_default()
+                ),
+                format!(
+                    "Remove {} and run `berth start {server}`",
+                    runtime.state_path(&server).display()
+                ),
+            ),
+        })
+        .collect()
+}
+
+/// Checks that every installed server's command resolves and reports which
+/// of the common runtimes are available.
+fn check_runtimes(installed: &[(String, InstalledServer)]) -> Vec<Check> {
+    let mut checks = Vec::new();
+    for (runtime_type, command, install_hint) in RUNTIMES {
+        let users: Vec<&str> = installed
+            .iter()
+            .filter(|(_, server)| {
+                server.runtime.command == command || server.runtime.runtime_type == runtime_type
+            })
+            .map(|(name, _)| name.as_str())
+            .collect();
+        if path_has_binary(command) {
+            checks.push(Check::pass(
+                "runtime",
+                format!("{runtime_type}: `{command}` found on PATH"),
+            ));
+        } else if users.is_empty() {
+            checks.push(Check::warn(
+                "runtime",
+                format!(
+                    "{runtime_type}: `{command}` not found on PATH (no installed server needs it)"
+                ),
+                install_hint,
+            ));
+        } else {
+            checks.push(Check::fail(
+                "runtime",
+                format!(
+                    "{runtime_type}: `{command}` not found on PATH, needed by {}",
+                    users.join(", ")
+                ),
+                install_hint,
+            ));
+        }
+    }
+    let known_commands: Vec<&str> = RUNTIMES.iter().map(|(_, command, _)| *command).collect();
+    for (name, server) in installed {
+        let command = &server.runtime.command;
+        if known_commands.contains(&command.as_str()) || command_resolves(command) {
+            continue;
+        }
+        checks.push(Check::fail(
+            "runtime",
+            format!("{name}: command `{command}` does not exist"),
+            format!("berth uninstall {name} && berth install {name}"),
+        ));
+    }
+    checks
+}
+
+fn command_resolves(command: &str) -> bool {
+    let path = Path::new(command);
+    if path.components().count() > 1 {
+        path.is_file()
+    } else {
+        path_has_binary(command)
+    }
+}
+
+/// Checks that a cached registry index, if any, can be read and parsed.
+fn check_registry_cache() -> Check {
+    let Some(cache) = berth_registry::cache_path() else {
+        return Check::pass("registry cache", "No registry cache configured");
+    };
+    if !cache.exists() {
+        return Check::pass(
+            "registry cache",
+            "Not cached yet; the embedded seed registry is used",
+        );
+    }
+    match Registry::from_index_file(&cache) {
+        Ok(registry) => Check::pass(
+            "registry cache",
+            format!(
+                "{} ({} servers)",
+                cache.display(),
+                registry.list_all().len()
+            ),
+        ),
+        Err(e) => Check::fail(
+            "registry cache",
+            e,
+            format!(
+                "Remove {}; the next registry fetch rewrites it",
+                cache.display()
+            ),
+        ),
+    }
+}
+
+/// Reports state and log files left behind by uninstalled servers.
+fn check_orphaned_logs(runtime: &RuntimeManager, known: &[String]) -> Check {
+    match runtime.orphaned_artifacts(known) {
+        Ok(orphaned) if orphaned.is_empty() => {
+            Check::pass("orphaned logs", "No logs or state of uninstalled servers")
+        }
+        Ok(orphaned) => {
+            let files: usize = orphaned.values().map(Vec::len).sum();
+            let servers: Vec<&str> = orphaned.keys().map(String::as_str).collect();
+            Check::warn(
+                "orphaned logs",
+                format!(
+                    "{files} file(s) of uninstalled servers: {}",
+                    servers.join(", ")
+                ),
+                "berth gc",
+            )
+        }
+        Err(e) => Check::fail(
+            "orphaned logs",
+            format!("Failed to scan runtime artifacts: {e}"),
+            "Check the permissions of the runtime and logs directories",
+        ),
+    }
+}
+
+/// Checks client configs for `berth proxy` entries that can no longer start.
+fn check_client_links(known: &[String]) -> Vec<Check> {
+    let mut checks = Vec::new();
+    for client in CLIENTS {
+        let Some(path) = paths::client_config_path(client) else {
+            continue;
+        };
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        let root = match jsonc::parse(&content) {
+            Ok(root) => root,
+            Err(e) => {
+                checks.push(Check::fail(
+                    "client links",
+                    format!("{client}: {} is not valid JSON: {e}", path.display()),
+                    format!(
+                        "Fix {}, or run `berth link {client} --undo`",
+                        path.display()
+                    ),
+                ));
+                continue;
+            }
+        };
+        let problems = broken_links(&root, known);
+        if problems.is_empty() {
+            continue;
+        }
+        checks.push(Check::fail(
+            "client links",
+            format!("{client}: {}", problems.join("; ")),
+            format!("berth unlink {client} && berth link {client}"),
+        ));
+    }
+    if checks.is_empty() {
+        checks.push(Check::pass("client links", "No broken client links"));
+    }
+    checks
+}
+
+/// Describes every Berth proxy entry in a client config that cannot start.
+fn broken_links(root: &Value, known: &[String]) -> Vec<String> {
+    let Some(servers) = root.get("mcpServers").and_then(Value::as_object) else {
+        return Vec::new();
+    };
+    let mut problems = Vec::new();
+    for (name, entry) in servers {
+        let args: Vec<&str> = entry
+            .get("args")
+            .and_then(Value::as_array)
+            .map(|args| args.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default();
+        let ["proxy", server, ..] = args.as_slice() else {
+            continue;
+        };
+        if !args.contains(&"--client") {
+            continue;
+        }
+        if !known.iter().any(|k| k == server) {
+            problems.push(format!("{name} points at uninstalled server {server}"));
+            continue;
+        }
+        let command = entry.get("command").and_then(Value::as_str).unwrap_or("");
+        if !command_resolves(command) {
+            problems.push(format!("{name} launches missing executable {command}"));
+        }
+    }
+    problems
+}
+
+/// Checks that secret references in installed configs can be resolved.
+fn check_keyring(installed: &[(String, InstalledServer)]) -> Check {
+    let users: Vec<&str> = installed
+        .iter()
+        .filter(|(_, server)| server.config.values().any(|v| secrets::is_secret_ref(v)))
+        .map(|(name, _)| name.as_str())
+        .collect();
+    match secrets::backend_status() {
+        Ok(backend) => Check::pass("keyring", format!("Secrets use the {backend}")),
+        Err(reason) if users.is_empty() => Check::warn(
+            "keyring",
+            format!("{reason}; no installed server stores secrets yet"),
+            "Install the OS keychain tools, or set BERTH_SECRET_BACKEND=file",
+        ),
+        Err(reason) => Check::fail(
+            "keyring",
+            format!("{reason}; needed by {}", users.join(", ")),
+            "Install the OS keychain tools, or set BERTH_SECRET_BACKEND=file",
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn broken_links_flag_uninstalled_servers_and_missing_executables() {
+        let root: Value = serde_json::json!({
+            "mcpServers": {
+                "github": {"command": "/nonexistent/berth", "args": ["proxy", "github", "--client", "cursor"]},
+                "slack": {"command": "/nonexistent/berth", "args": ["proxy", "slack", "--client", "cursor"]},
+                "manual": {"command": "node", "args": ["server.js"]}
+            }
+        });
+        let problems = broken_links(&root, &["github".to_string()]);
+        assert_eq!(
+            problems,
+            vec![
+                "github launches missing executable /nonexistent/berth".to_string(),
+                "slack points at uninstalled server slack".to_string(),
+            ]
+        );
+    }
+}
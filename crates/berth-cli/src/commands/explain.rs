@@ -0,0 +1,405 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Command handler for `berth explain`.
+//!
+//! `berth explain audit <entry>` expands one audit entry into a narrative for
+//! incident review: what triggered it, the server and process involved, the
+//! entries recorded for the same server around it, and the server log lines
+//! written at the same time. The entry is picked by its position in
+//! `berth audit`, by its id, or by pasting the entry's JSON.
+
+use colored::Colorize;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use std::process;
+
+use berth_runtime::{AuditQuery, AuditRecord};
+
+use crate::commands::audit::format_timestamp;
+use crate::paths;
+use crate::time_format::{now_epoch_ms, TimestampFormatter};
+
+/// Topics `berth explain` can expand.
+const TOPICS: [&str; 1] = ["audit"];
+
+/// How far a log line's timestamp may be from the event and still be shown.
+const LOG_WINDOW_SECS: u64 = 2;
+
+/// Most log lines printed for one entry.
+const MAX_LOG_LINES: usize = 8;
+
+/// Executes the `berth explain` command.
+pub fn execute(topic: &str, entry: &str, context: usize, utc: bool) {
+    if topic != "audit" {
+        eprintln!(
+            "{} Unknown explain topic `{}`. Supported: {}.",
+            "✗".red().bold(),
+            topic,
+            TOPICS.join(", ")
+        );
+        process::exit(1);
+    }
+    let Some(runtime) = paths::runtime_manager() else {
+        eprintln!("{} Could not determine home directory.", "✗".red().bold());
+        process::exit(1);
+    };
+    let entries = match runtime.audit_log().query(&AuditQuery::default()) {
+        Ok(page) => page.entries,
+        Err(e) => {
+            eprintln!("{} Failed to read audit log: {}", "✗".red().bold(), e);
+            process::exit(1);
+        }
+    };
+    let index = match find_entry(&entries, entry) {
+        Ok(index) => index,
+        Err(msg) => {
+            eprintln!("{} {}", "✗".red().bold(), msg);
+            process::exit(1);
+        }
+    };
+
+    let event = &entries[index];
+    let now = now_epoch_ms();
+    let formatter = TimestampFormatter::new(utc);
+    println!(
+        "{} Audit entry #{} (id {})\n",
+        "✓".green().bold(),
+        index + 1,
+        event.id.as_deref().unwrap_or("-")
+    );
+    println!("  {:<9}{}", "What:".bold(), describe(event));
+    println!("  {:<9}{}", "Actor:".bold(), actor(&event.action));
+    println!(
+        "  {:<9}{}",
+        "When:".bold(),
+        format_timestamp(&formatter, event.epoch_ms(), now)
+    );
+    println!("  {:<9}{}", "Server:".bold(), event.server.cyan());
+    if let Some(pid) = event.pid {
+        println!("  {:<9}{}", "PID:".bold(), pid);
+    }
+    if let Some(command) = &event.command {
+        println!("  {:<9}{}", "Command:".bold(), command);
+    }
+    if let Some(args) = event.args.as_deref().filter(|a| !a.is_empty()) {
+        println!("  {:<9}{}", "Args:".bold(), args.join(" "));
+    }
+    let details = details(event);
+    if !details.is_empty() {
+        println!("  {:<9}{}", "Details:".bold(), details.join(", "));
+    }
+
+    let related: Vec<usize> = (0..entries.len())
+        .filter(|&i| i != index && entries[i].server == event.server)
+        .collect();
+    let split = related.partition_point(|&i| i < index);
+    let before = &related[split.saturating_sub(context)..split];
+    let after = &related[split..(split + context).min(related.len())];
+    for (label, indices) in [("Before", before), ("After", after)] {
+        if indices.is_empty() {
+            continue;
+        }
+        println!("\n  {}:", label.bold());
+        for &i in indices {
+            let other = &entries[i];
+            println!(
+                "    {:<6} {:<24} {}",
+                format!("#{}", i + 1).dimmed(),
+                other.action,
+                format_timestamp(&formatter, other.epoch_ms(), now)
+            );
+        }
+    }
+
+    let log_path = runtime.log_path(&event.server);
+    match log_lines_near(&log_path, event.epoch_ms() / 1_000) {
+        Some((first, lines)) => {
+            println!(
+                "\n  {} {}:{}-{}",
+                "Logs:".bold(),
+                log_path.display(),
+                first,
+                first + lines.len() - 1
+            );
+            for line in lines.iter().take(MAX_LOG_LINES) {
+                println!("    {line}");
+            }
+        }
+        None => println!(
+            "\n  {} no lines in {} within {}s of this entry.",
+            "Logs:".bold(),
+            log_path.display(),
+            LOG_WINDOW_SECS
+        ),
+    }
+}
+
+/// Resolves `entry` to an index into `entries`.
+///
+/// Accepts a 1-based position as listed by `berth audit` (`7` or `#7`, with
+/// negative numbers counting back from the newest entry), an id or a unique
+/// prefix of one, or an entry's JSON as printed by `berth audit --json`.
+fn find_entry(entries: &[AuditRecord], entry: &str) -> Result<usize, String> {
+    let entry = entry.trim();
+    if entries.is_empty() {
+        return Err("No audit entries yet.".to_string());
+    }
+    if entry.starts_with('{') {
+        return find_pasted(entries, entry);
+    }
+    let position = entry.strip_prefix('#').unwrap_or(entry);
+    if let Ok(position) = position.parse::<i64>() {
+        let len = entries.len() as i64;
+        let index = if position < 0 {
+            len + position
+        } else {
+            position - 1
+        };
+        if (0..len).contains(&index) {
+            return Ok(index as usize);
+        }
+        if entry.starts_with('#') || position <= 0 {
+            return Err(format!(
+                "No audit entry #{position}; the log holds {len} entries."
+            ));
+        }
+    }
+    let id = entry.to_ascii_lowercase();
+    let matches: Vec<usize> = (0..entries.len())
+        .filter(|&i| entries[i].id.as_deref().is_some_and(|e| e.starts_with(&id)))
+        .collect();
+    match matches.as_slice() {
+        [index] => Ok(*index),
+        [] => Err(format!(
+            "No audit entry matches `{entry}`. Pass a position from `berth audit`, an entry id, or the entry's JSON."
+        )),
+        _ => Err(format!(
+            "Audit entry id `{entry}` is ambiguous ({} matches); use more digits.",
+            matches.len()
+        )),
+    }
+}
+
+/// Finds a pasted entry by its id, or by time, server, action, and pid.
+fn find_pasted(entries: &[AuditRecord], json: &str) -> Result<usize, String> {
+    let value: Value =
+        serde_json::from_str(json).map_err(|e| format!("Pasted audit entry is not JSON: {e}"))?;
+    if let Some(id) = value.get("id").and_then(Value::as_str) {
+        return find_entry(entries, id);
+    }
+    let pasted: AuditRecord = serde_json::from_value(value)
+        .map_err(|e| format!("Pasted JSON is not an audit entry: {e}"))?;
+    entries
+        .iter()
+        .position(|e| {
+            e.epoch_ms() == pasted.epoch_ms()
+                && e.server == pasted.server
+                && e.action == pasted.action
+                && e.pid == pasted.pid
+        })
+        .ok_or_else(|| "The pasted entry is not in the audit log.".to_string())
+}
+
+/// One-sentence summary of what the entry records.
+fn describe(event: &AuditRecord) -> String {
+    let server = event.server.as_str();
+    let target = event
+        .args
+        .as_deref()
+        .and_then(<[String]>::first)
+        .map_or("(unknown)", String::as_str);
+    let version = event.version.as_deref().unwrap_or("?");
+    match event.action.as_str() {
+        "install" => match &event.previous_version {
+            Some(from) => format!("{server} was reinstalled, replacing v{from} with v{version}."),
+            None => format!("{server} v{version} was installed."),
+        },
+        "update" => format!(
+            "{server} was updated from v{} to v{version}.",
+            event.previous_version.as_deref().unwrap_or("?")
+        ),
+        "uninstall" => format!("{server} was uninstalled."),
+        "start" => format!("{server} was started."),
+        "adopt" => format!("{server} was started outside Berth and adopted."),
+        "stop" => format!("{server} was stopped."),
+        "restart" => format!("{server} was restarted."),
+        "exit" => match event.exit_code {
+            Some(0) => format!("{server} exited cleanly."),
+            Some(code) => format!("{server} exited with code {code}."),
+            None => format!("{server} exited."),
+        },
+        "auto-restart" => format!("{server} was restarted after it exited."),
+        "crash-loop" => format!("{server} kept crashing, so restarts stopped."),
+        "refresh" => format!("{server} was relaunched with refreshed credentials."),
+        "ready" => format!("{server} passed its readiness probe."),
+        "readiness-timeout" => format!("{server} did not become ready in time."),
+        "health-failed" => format!("{server} failed its health check."),
+        "health-recovered" => format!("{server} passed its health check again."),
+        "schedule" => format!("The schedule of job {server} was armed."),
+        "job-start" => format!("A run of job {server} started."),
+        "job-exit" => format!("A run of job {server} finished."),
+        "proxy-start" | "wrap-start" => format!("A client session with {server} began."),
+        "proxy-end" | "wrap-end" => format!("A client session with {server} ended."),
+        "proxy-error" | "wrap-error" => format!("A client session with {server} failed."),
+        "proxy-restart" => format!("{server} was restarted during a client session."),
+        "proxy-blue-green-switch" => format!("The session moved to the updated {server}."),
+        "proxy-blue-green-failed" => {
+            format!("The updated {server} failed; the session kept the old one.")
+        }
+        "mcp-tool-call" => format!("The client called tool `{target}`."),
+        "mcp-tool-result" => format!("Tool `{target}` answered."),
+        "mcp-resource-read" => format!("The client read resource `{target}`."),
+        "mcp-resource-result" => format!("Resource `{target}` was returned."),
+        "mcp-resource-subscribe" => format!("The client subscribed to `{target}`."),
+        "mcp-resource-unsubscribe" => format!("The client unsubscribed from `{target}`."),
+        "mcp-prompt-get" => format!("The client fetched prompt `{target}`."),
+        "mcp-prompt-result" => format!("Prompt `{target}` was returned."),
+        "mcp-request-queued" => format!("A `{target}` request waited for a free slot."),
+        "mcp-request-queue-timeout" => format!("A `{target}` request timed out in the queue."),
+        "permission-denied" => format!("A call to tool `{target}` was rejected."),
+        "permission-prompt" => {
+            let args = event.args.as_deref().unwrap_or_default();
+            format!(
+                "Asked whether tool `{target}` may use {}; the answer was `{}`.",
+                args.get(2..)
+                    .map_or(String::new(), |needs| needs.join(", ")),
+                args.get(1).map_or("?", String::as_str)
+            )
+        }
+        "permission-network-denied" => {
+            format!("Launching {server} was refused: all network access is revoked.")
+        }
+        "permission-network-warning" => {
+            format!("{server} was launched with network grants it does not declare.")
+        }
+        "policy-denied" => format!("Org policy blocked launching {server}."),
+        other => format!("{server} recorded `{other}`."),
+    }
+}
+
+/// Who or what writes entries with this action.
+fn actor(action: &str) -> String {
+    let actor = match action {
+        "install" => "`berth install`",
+        "update" => "`berth update`",
+        "uninstall" => "`berth uninstall` (or a failed install rolling back)",
+        "start" => "`berth start`, or the supervisor when it relaunches a server",
+        "stop" => "`berth stop`",
+        "adopt" => "`berth adopt`",
+        "restart" => "`berth restart`",
+        "exit" | "auto-restart" | "crash-loop" | "refresh" | "health-failed"
+        | "health-recovered" | "schedule" | "job-start" | "job-exit" => {
+            "the Berth supervisor watching the server"
+        }
+        "permission-prompt" => {
+            "the user, on the terminal or through the `berth.permission-prompt` hook"
+        }
+        "ready" | "readiness-timeout" => "the readiness probe after a start",
+        "wrap-start" | "wrap-end" | "wrap-error" => "`berth wrap`, spawned by an MCP client",
+        "permission-network-denied" | "permission-network-warning" | "policy-denied" => {
+            "the launch checks of `berth start`, `berth restart`, `berth proxy`, or the supervisor"
+        }
+        action if action.starts_with("proxy-") => "`berth proxy`, spawned by an MCP client",
+        action if action.starts_with("mcp-") || action == "permission-denied" => {
+            "an MCP client, through `berth proxy` or `berth wrap`"
+        }
+        _ => "unknown; this action is not one Berth documents",
+    };
+    actor.to_string()
+}
+
+/// Outcome fields of the entry, in display form.
+fn details(event: &AuditRecord) -> Vec<String> {
+    let mut details = Vec::new();
+    if let Some(code) = event.exit_code {
+        details.push(format!("exit code {code}"));
+    }
+    if let Some(ms) = event.duration_ms {
+        details.push(format!("took {ms}ms"));
+    }
+    if let Some(error) = event.error {
+        details.push(if error { "failed" } else { "succeeded" }.to_string());
+    }
+    if let Some(hash) = &event.prev_hash {
+        details.push(format!("follows entry {}", &hash[..hash.len().min(12)]));
+    }
+    details
+}
+
+/// Returns the 1-based number of the first timestamped log line written
+/// within [`LOG_WINDOW_SECS`] of `at_secs`, with that run of lines.
+///
+/// Server output between Berth's timestamped lines is kept in the run.
+fn log_lines_near(path: &Path, at_secs: u64) -> Option<(usize, Vec<String>)> {
+    let content = fs::read_to_string(path).ok()?;
+    let stamp =
+        |line: &str| -> Option<u64> { line.strip_prefix('[')?.split_once(']')?.0.parse().ok() };
+    let near = |secs: u64| secs.abs_diff(at_secs) <= LOG_WINDOW_SECS;
+    let lines: Vec<&str> = content.lines().collect();
+    let first = lines.iter().position(|l| stamp(l).is_some_and(near))?;
+    let mut last = first;
+    for (i, line) in lines.iter().enumerate().skip(first + 1) {
+        match stamp(line) {
+            Some(secs) if !near(secs) => break,
+            _ => last = i,
+        }
+    }
+    let run = lines[first..=last].iter().map(|l| l.to_string()).collect();
+    Some((first + 1, run))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(ms: u64, action: &str, id: &str) -> AuditRecord {
+        AuditRecord {
+            timestamp_epoch_ms: Some(ms),
+            server: "github".to_string(),
+            action: action.to_string(),
+            pid: Some(7),
+            id: Some(id.to_string()),
+            ..AuditRecord::default()
+        }
+    }
+
+    #[test]
+    fn entries_are_found_by_position_id_and_pasted_json() {
+        let entries = vec![
+            entry(1_000, "start", "aa11"),
+            entry(2_000, "stop", "ab22"),
+            entry(3_000, "start", "cc33"),
+        ];
+        assert_eq!(find_entry(&entries, "2"), Ok(1));
+        assert_eq!(find_entry(&entries, "#3"), Ok(2));
+        assert_eq!(find_entry(&entries, "-1"), Ok(2));
+        assert!(find_entry(&entries, "#4").is_err());
+        assert_eq!(find_entry(&entries, "CC"), Ok(2));
+        assert!(find_entry(&entries, "a").unwrap_err().contains("ambiguous"));
+        assert_eq!(find_entry(&entries, r#"{"id":"ab22"}"#), Ok(1));
+        assert_eq!(
+            find_entry(
+                &entries,
+                r#"{"timestampEpochMs":3000,"server":"github","action":"start","pid":7}"#
+            ),
+            Ok(2)
+        );
+    }
+
+    #[test]
+    fn log_lines_near_keeps_untimestamped_output_in_the_run() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("github.log");
+        fs::write(
+            &path,
+            "[100] START pid=1\n[200] START pid=2\nlistening\n[201] READY\n[300] STOP\n",
+        )
+        .unwrap();
+        let (first, lines) = log_lines_near(&path, 199).unwrap();
+        assert_eq!(first, 2);
+        assert_eq!(lines, ["[200] START pid=2", "listening", "[201] READY"]);
+        assert!(log_lines_near(&path, 150).is_none());
+    }
+}
@@ -0,0 +1,145 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Internal command handler for `berth __gateway`.
+//!
+//! Runs in front of the replicas of an `http` server with `berth.replicas`
+//! above 1. It listens on the server's port on loopback and hands each
+//! connection to the next replica in turn, skipping replicas that refuse it,
+//! e.g. one being restarted by a rolling update. A connection stays with its
+//! replica until either side closes it.
+
+use colored::Colorize;
+use std::io;
+use std::net::{Ipv4Addr, Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// How long one replica gets to accept a connection before the next is tried.
+const CONNECT_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Executes the hidden gateway command.
+pub fn execute(port: u16, upstreams: &[u16]) {
+    if upstreams.is_empty() {
+        fail("The gateway needs at least one replica port.");
+    }
+    let listener = match TcpListener::bind((Ipv4Addr::LOCALHOST, port)) {
+        Ok(listener) => listener,
+        Err(e) => fail(&format!("Failed to listen on 127.0.0.1:{port}: {e}")),
+    };
+    eprintln!(
+        "Gateway listening on 127.0.0.1:{port}, balancing across {} replicas.",
+        upstreams.len()
+    );
+    serve(listener, upstreams.to_vec());
+}
+
+/// Accepts connections forever, relaying each to a replica on its own threads.
+fn serve(listener: TcpListener, upstreams: Vec<u16>) {
+    let upstreams = Arc::new(upstreams);
+    let next = Arc::new(AtomicUsize::new(0));
+    for client in listener.incoming() {
+        let Ok(client) = client else {
+            continue;
+        };
+        let upstreams = Arc::clone(&upstreams);
+        let next = Arc::clone(&next);
+        thread::spawn(move || {
+            let first = next.fetch_add(1, Ordering::Relaxed);
+            match connect_upstream(&upstreams, first) {
+                Ok(upstream) => relay(client, upstream),
+                Err(e) => {
+                    eprintln!("No replica accepted a connection: {e}");
+                    let _ = client.shutdown(Shutdown::Both);
+                }
+            }
+        });
+    }
+}
+
+/// Connects to the first replica from `first` on, round robin, that accepts.
+fn connect_upstream(upstreams: &[u16], first: usize) -> io::Result<TcpStream> {
+    let mut last_error = None;
+    for offset in 0..upstreams.len() {
+        let port = upstreams[(first + offset) % upstreams.len()];
+        let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+        match TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT) {
+            Ok(stream) => return Ok(stream),
+            Err(e) => last_error = Some(e),
+        }
+    }
+    Err(last_error.unwrap_or_else(|| io::Error::other("no replicas")))
+}
+
+/// Copies bytes both ways until each side has finished sending.
+fn relay(client: TcpStream, upstream: TcpStream) {
+    let (Ok(mut client_reader), Ok(mut upstream_reader)) =
+        (client.try_clone(), upstream.try_clone())
+    else {
+        return;
+    };
+    let mut upstream_writer = upstream;
+    let requests = thread::spawn(move || {
+        let _ = io::copy(&mut client_reader, &mut upstream_writer);
+        let _ = upstream_writer.shutdown(Shutdown::Write);
+    });
+    let mut client_writer = client;
+    let _ = io::copy(&mut upstream_reader, &mut client_writer);
+    let _ = client_writer.shutdown(Shutdown::Write);
+    let _ = requests.join();
+}
+
+fn fail(msg: &str) -> ! {
+    eprintln!("{} {}", "✗".red().bold(), msg);
+    process::exit(1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+
+    /// Starts a replica that answers every connection with `name` and closes.
+    fn replica(name: &'static str) -> u16 {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut request = [0u8; 4];
+                stream.read_exact(&mut request).unwrap();
+                stream.write_all(name.as_bytes()).unwrap();
+            }
+        });
+        port
+    }
+
+    fn ask(port: u16) -> String {
+        let mut stream = TcpStream::connect((Ipv4Addr::LOCALHOST, port)).unwrap();
+        stream.write_all(b"ping").unwrap();
+        let mut reply = String::new();
+        stream.read_to_string(&mut reply).unwrap();
+        reply
+    }
+
+    #[test]
+    fn balances_round_robin_and_skips_refusing_replicas() {
+        // Bound then dropped, so nothing listens there.
+        let down = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let upstreams = vec![replica("a"), down, replica("b")];
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        thread::spawn(move || serve(listener, upstreams));
+
+        let replies: Vec<String> = (0..3).map(|_| ask(port)).collect();
+        assert_eq!(replies, ["a", "b", "b"]);
+        assert_eq!(ask(port), "a");
+    }
+}
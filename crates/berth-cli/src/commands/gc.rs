@@ -0,0 +1,102 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Command handler for `berth gc`.
+
+use colored::Colorize;
+use std::process;
+
+use berth_runtime::PruneMode;
+
+use crate::commands::unlink::installed_server_names;
+use crate::paths;
+
+/// Executes the `berth gc` command.
+pub fn execute(archive: bool, dry_run: bool) {
+    let runtime = match paths::runtime_manager() {
+        Some(r) => r,
+        None => {
+            eprintln!("{} Could not determine home directory.", "✗".red().bold());
+            process::exit(1);
+        }
+    };
+    let known = match installed_server_names() {
+        Ok(names) => names,
+        Err(msg) => {
+            eprintln!("{} {}", "✗".red().bold(), msg);
+            process::exit(1);
+        }
+    };
+
+    if dry_run {
+        let orphaned = match runtime.orphaned_artifacts(&known) {
+            Ok(v) => v,
+            Err(e) => {
+                eprintln!(
+                    "{} Failed to scan runtime artifacts: {}",
+                    "✗".red().bold(),
+                    e
+                );
+                process::exit(1);
+            }
+        };
+        if orphaned.is_empty() {
+            println!("{} No runtime artifacts to prune.", "✓".green().bold());
+            return;
+        }
+        let verb = if archive { "archive" } else { "remove" };
+        println!("{} Would {verb}:", "!".yellow().bold());
+        for (server, files) in &orphaned {
+            for file in files {
+                println!("  {} {}", server.cyan(), file.display());
+            }
+        }
+        return;
+    }
+
+    let mode = if archive {
+        PruneMode::Archive
+    } else {
+        PruneMode::Remove
+    };
+    let report = match runtime.prune(&known, mode) {
+        Ok(r) => r,
+        Err(e) => {
+            eprintln!(
+                "{} Failed to prune runtime artifacts: {}",
+                "✗".red().bold(),
+                e
+            );
+            process::exit(1);
+        }
+    };
+
+    for server in &report.skipped_running {
+        println!(
+            "{} Kept artifacts for {}: it is no longer installed but still running.",
+            "!".yellow().bold(),
+            server.cyan()
+        );
+    }
+    if report.servers.is_empty() {
+        println!("{} No runtime artifacts to prune.", "✓".green().bold());
+        return;
+    }
+    let action = match &report.archive_dir {
+        Some(dir) => format!("Archived to {}", dir.display()),
+        None => "Removed".to_string(),
+    };
+    println!(
+        "{} {} {} file(s) for {} uninstalled server(s): {}",
+        "✓".green().bold(),
+        action,
+        report.files.len(),
+        report.servers.len(),
+        report
+            .servers
+            .iter()
+            .map(|s| s.cyan().to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+}
@@ -0,0 +1,336 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Command handler for `berth history`.
+//!
+//! Renders a server's lifecycle from the audit log as a timeline: installs,
+//! updates, starts, crashes, restarts, and stops, with the time elapsed
+//! between them. Per-request proxy events are left out, and repeated crash and
+//! auto-restart cycles are folded into one row.
+
+use berth_runtime::{AuditLog, AuditQuery, AuditRecord};
+use colored::Colorize;
+use serde::Serialize;
+use std::process;
+
+use crate::commands::analytics::parse_since;
+use crate::observer;
+use crate::paths;
+use crate::time_format::{now_epoch_ms, TimestampFormatter};
+
+/// Describes an event for the timeline; `None` for per-request noise.
+fn describe(event: &AuditRecord) -> Option<String> {
+    let versions = |verb: &str| match (&event.previous_version, &event.version) {
+        (Some(from), Some(to)) => format!("{verb} {from} → {to}"),
+        (None, Some(to)) => format!("{verb} v{to}"),
+        _ => verb.to_string(),
+    };
+    let described = match event.action.as_str() {
+        "install" if event.previous_version.is_some() => versions("reinstalled"),
+        "install" => versions("installed"),
+        "update" => versions("updated"),
+        "uninstall" => "uninstalled".to_string(),
+        "start" => with_pid("started", event.pid),
+        "adopt" => with_pid("adopted", event.pid),
+        "proxy-start" => with_pid("started by proxy", event.pid),
+        "proxy-end" => "proxy ended".to_string(),
+        "proxy-error" => "proxy failed".to_string(),
+        "wrap-start" => with_pid("started by client (wrap)", event.pid),
+        "wrap-end" => "wrapped server ended".to_string(),
+        "wrap-error" => "wrap failed".to_string(),
+        "stop" => "stopped".to_string(),
+        "restart" => "restarted".to_string(),
+        "exit" => describe_exit(event.exit_code),
+        "auto-restart" => "auto-restarted".to_string(),
+        "crash-loop" => "gave up restarting (crash loop)".to_string(),
+        "ready" => "passed readiness probe".to_string(),
+        "readiness-timeout" => "failed readiness probe".to_string(),
+        "health-failed" => "failed its health check".to_string(),
+        "health-recovered" => "passed its health check again".to_string(),
+        "schedule" => "schedule armed".to_string(),
+        // A run is one row, at its end, once its outcome is known.
+        "job-start" => return None,
+        "job-exit" => describe_job_run(event.exit_code),
+        "refresh" => "relaunched with refreshed credentials".to_string(),
+        "proxy-restart" => "restarted by proxy".to_string(),
+        "proxy-blue-green-switch" => "proxy switched to the new version".to_string(),
+        "proxy-blue-green-failed" => "proxy kept the old version".to_string(),
+        action if action.starts_with("mcp-") => return None,
+        action => action.to_string(),
+    };
+    Some(described)
+}
+
+/// One timeline row, covering one event or a run of identical ones.
+#[derive(Debug, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+struct Entry {
+    timestamp_epoch_ms: u64,
+    /// Time of the last folded event, when the row covers more than one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_epoch_ms: Option<u64>,
+    event: String,
+    count: u32,
+}
+
+impl Entry {
+    fn end_ms(&self) -> u64 {
+        self.last_epoch_ms.unwrap_or(self.timestamp_epoch_ms)
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct History<'a> {
+    server: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status: Option<String>,
+    events: Vec<Entry>,
+}
+
+/// Executes the `berth history` command.
+pub fn execute(server: &str, since: Option<&str>, json: bool, utc: bool) {
+    let since_secs = match since.map(parse_since).transpose() {
+        Ok(v) => v,
+        Err(msg) => {
+            eprintln!("{} {}", "✗".red().bold(), msg);
+            process::exit(1);
+        }
+    };
+    let (path, runtime) = match (paths::audit_log_path(), paths::runtime_manager()) {
+        (Some(p), Some(r)) => (p, r),
+        _ => {
+            eprintln!("{} Could not determine home directory.", "✗".red().bold());
+            process::exit(1);
+        }
+    };
+
+    let now = now_epoch_ms();
+    let query = AuditQuery {
+        server: Some(server.to_string()),
+        since_epoch_ms: since_secs.map(|s| now.saturating_sub(s.saturating_mul(1_000))),
+        ..AuditQuery::default()
+    };
+    // Rotated segments included, in time order with ties in file order.
+    let events = match AuditLog::new(&path).query(&query) {
+        Ok(page) => page.entries,
+        Err(e) => {
+            eprintln!(
+                "{} Failed to read audit log {}: {}",
+                "✗".red().bold(),
+                path.display(),
+                e
+            );
+            process::exit(1);
+        }
+    };
+    let entries = timeline(&events);
+
+    let status = paths::server_config_path(server)
+        .filter(|p| p.exists())
+        .and_then(|_| {
+            if observer::enabled() {
+                runtime.peek_status(server).ok()
+            } else {
+                runtime.status(server).ok()
+            }
+        })
+        .map(|s| s.to_string());
+
+    if json {
+        let history = History {
+            server,
+            status,
+            events: entries,
+        };
+        match serde_json::to_string_pretty(&history) {
+            Ok(rendered) => println!("{rendered}"),
+            Err(e) => {
+                eprintln!("{} Failed to serialize history: {}", "✗".red().bold(), e);
+                process::exit(1);
+            }
+        }
+        return;
+    }
+
+    if entries.is_empty() {
+        println!(
+            "{} No history for {}{}.",
+            "!".yellow().bold(),
+            server.cyan(),
+            since
+                .map(|s| format!(" in the last {s}"))
+                .unwrap_or_default()
+        );
+        return;
+    }
+
+    println!("{} History of {}:\n", "✓".green().bold(), server.cyan());
+    let formatter = TimestampFormatter::new(utc);
+    let mut previous_ms: Option<u64> = None;
+    for entry in &entries {
+        let gap = previous_ms
+            .map(|prev| {
+                format!(
+                    "+{}",
+                    format_span(entry.timestamp_epoch_ms.saturating_sub(prev))
+                )
+            })
+            .unwrap_or_default();
+        let event = if entry.count > 1 {
+            format!(
+                "{} ×{} over {}",
+                entry.event,
+                entry.count,
+                format_span(entry.end_ms() - entry.timestamp_epoch_ms)
+            )
+        } else {
+            entry.event.clone()
+        };
+        println!(
+            "  {:<30} {:>10}  {}",
+            formatter.format(entry.timestamp_epoch_ms),
+            gap.dimmed(),
+            event
+        );
+        previous_ms = Some(entry.end_ms());
+    }
+    if let (Some(status), Some(last)) = (status, previous_ms) {
+        println!(
+            "  {:<30} {:>10}  {}",
+            "now",
+            format!("+{}", format_span(now.saturating_sub(last))).dimmed(),
+            status.bold()
+        );
+    }
+}
+
+/// Builds timeline rows from time-ordered events.
+///
+/// A crash that triggers an auto-restart shares its row, so a crash loop reads
+/// as one `crashed (exit code 1), auto-restarted ×3` row.
+fn timeline(events: &[AuditRecord]) -> Vec<Entry> {
+    let mut entries: Vec<Entry> = Vec::new();
+    for ev in events {
+        let Some(mut event) = describe(ev) else {
+            continue;
+        };
+        let at = ev.epoch_ms();
+        if ev.action == "auto-restart" {
+            if let Some(crash) = entries.pop_if(|last| last.count == 1 && is_exit(&last.event)) {
+                event = format!("{}, {event}", crash.event);
+            }
+        }
+        match entries.last_mut() {
+            Some(last) if last.event == event => {
+                last.count += 1;
+                last.last_epoch_ms = Some(at);
+            }
+            _ => entries.push(Entry {
+                timestamp_epoch_ms: at,
+                last_epoch_ms: None,
+                event,
+                count: 1,
+            }),
+        }
+    }
+    entries
+}
+
+fn is_exit(event: &str) -> bool {
+    event.starts_with("crashed") || event.starts_with("exited")
+}
+
+fn with_pid(verb: &str, pid: Option<u32>) -> String {
+    match pid {
+        Some(pid) => format!("{verb} (pid {pid})"),
+        None => verb.to_string(),
+    }
+}
+
+/// Describes an exit, decoding `128 + n` as death by signal `n`.
+fn describe_exit(code: Option<i32>) -> String {
+    match code {
+        Some(0) => "exited cleanly".to_string(),
+        Some(code) if code > 128 => format!("crashed (signal {})", code - 128),
+        Some(code) => format!("crashed (exit code {code})"),
+        None => "exited".to_string(),
+    }
+}
+
+fn describe_job_run(code: Option<i32>) -> String {
+    match code {
+        Some(0) => "job ran successfully".to_string(),
+        Some(code) => format!("job run failed (exit code {code})"),
+        None => "job run was killed".to_string(),
+    }
+}
+
+/// Formats a duration compactly with its two largest units, e.g. `2h 14m`.
+fn format_span(ms: u64) -> String {
+    let secs = ms / 1_000;
+    match secs {
+        0 => format!("{ms}ms"),
+        1..=59 => format!("{secs}s"),
+        60..=3_599 => format!("{}m {}s", secs / 60, secs % 60),
+        3_600..=86_399 => format!("{}h {}m", secs / 3_600, secs % 3_600 / 60),
+        _ => format!("{}d {}h", secs / 86_400, secs % 86_400 / 3_600),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(ms: u64, action: &str, exit_code: Option<i32>) -> AuditRecord {
+        AuditRecord {
+            timestamp_epoch_ms: Some(ms),
+            timestamp_epoch_secs: ms / 1_000,
+            server: "github".to_string(),
+            action: action.to_string(),
+            exit_code,
+            ..AuditRecord::default()
+        }
+    }
+
+    #[test]
+    fn timeline_folds_crash_loops_and_skips_requests() {
+        let mut update = event(9_000, "update", None);
+        update.version = Some("1.3.0".to_string());
+        update.previous_version = Some("1.2.0".to_string());
+        let events = vec![
+            event(1_000, "start", None),
+            event(2_000, "mcp-tool-call", None),
+            event(3_000, "exit", Some(1)),
+            event(3_100, "auto-restart", None),
+            event(4_000, "exit", Some(1)),
+            event(4_100, "auto-restart", None),
+            event(5_000, "exit", Some(137)),
+            event(5_100, "auto-restart", None),
+            update,
+            event(10_000, "stop", None),
+        ];
+        let rows: Vec<(String, u32)> = timeline(&events)
+            .into_iter()
+            .map(|e| (e.event, e.count))
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                ("started".to_string(), 1),
+                ("crashed (exit code 1), auto-restarted".to_string(), 2),
+                ("crashed (signal 9), auto-restarted".to_string(), 1),
+                ("updated 1.2.0 → 1.3.0".to_string(), 1),
+                ("stopped".to_string(), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn format_span_uses_two_largest_units() {
+        assert_eq!(format_span(250), "250ms");
+        assert_eq!(format_span(42_000), "42s");
+        assert_eq!(format_span(8_040_000), "2h 14m");
+        assert_eq!(format_span(90_000_000), "1d 1h");
+    }
+}
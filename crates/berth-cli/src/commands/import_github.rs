@@ -13,9 +13,11 @@ use std::process::{self, Command};
 use berth_registry::config::{
     ConfigMeta, InstalledServer, PermissionsInfo, RuntimeInfo, ServerInfo, SourceInfo,
 };
+use berth_registry::types::{HealthCheck, ResourceLimits};
 
 use crate::paths;
 use crate::permission_filter::validate_permission_syntax;
+use crate::platform::is_portable_file_name;
 
 /// Executes the `berth import-github` command.
 pub fn execute(repo: &str, git_ref: &str, manifest_path: &str, dry_run: bool) {
@@ -305,6 +307,11 @@ fn validate_manifest(manifest: &GithubManifest) -> Vec<String> {
         .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
     {
         errors.push("server.name must use lowercase letters, digits, and dashes only.".to_string());
+    } else if !is_portable_file_name(&manifest.server.name) {
+        errors.push(
+            "server.name must not be a reserved Windows device name (con, nul, com1, ...)."
+                .to_string(),
+        );
     }
     if !is_basic_semver(&manifest.server.version) {
         errors.push("server.version must look like semantic version `x.y.z`.".to_string());
@@ -320,9 +327,10 @@ fn validate_manifest(manifest: &GithubManifest) -> Vec<String> {
 
     validate_non_empty("runtime.type", &manifest.runtime.runtime_type, &mut errors);
     validate_non_empty("runtime.command", &manifest.runtime.command, &mut errors);
-    if manifest.runtime.transport.trim() != "stdio" {
-        errors.push("runtime.transport must be `stdio`.".to_string());
-    }
+    errors.extend(berth_runtime::template_problems(
+        manifest.runtime.transport.trim(),
+        manifest.runtime.socket.as_deref(),
+    ));
 
     for entry in &manifest.permissions.network {
         if let Err(err) = validate_permission_syntax(&format!("network:{entry}")) {
@@ -344,6 +352,11 @@ fn validate_manifest(manifest: &GithubManifest) -> Vec<String> {
             errors.push(err);
         }
     }
+    for entry in manifest.permissions.tools.values().flatten() {
+        if let Err(err) = validate_permission_syntax(entry) {
+            errors.push(err);
+        }
+    }
 
     let mut config_keys = BTreeSet::new();
     for field in &manifest.config.required {
@@ -408,6 +421,14 @@ fn to_installed_server(manifest: &GithubManifest) -> InstalledServer {
             f.key.clone()
         })
         .collect();
+    let sensitive_keys: Vec<String> = manifest
+        .config
+        .required
+        .iter()
+        .chain(manifest.config.optional.iter())
+        .filter(|f| f.sensitive)
+        .map(|f| f.key.clone())
+        .collect();
 
     InstalledServer {
         server: ServerInfo {
@@ -418,28 +439,39 @@ fn to_installed_server(manifest: &GithubManifest) -> InstalledServer {
             category: manifest.server.category.clone(),
             maintainer: manifest.server.maintainer.clone(),
             trust_level: manifest.server.trust_level.clone(),
+            tags: Vec::new(),
+            version_constraint: None,
         },
         source: SourceInfo {
             source_type: manifest.source.source_type.clone(),
             package: manifest.source.package.clone(),
             repository: manifest.source.repository.clone(),
+            registry: None,
+            sha256: None,
         },
         runtime: RuntimeInfo {
             runtime_type: manifest.runtime.runtime_type.clone(),
             command: manifest.runtime.command.clone(),
             args: manifest.runtime.args.clone(),
             transport: manifest.runtime.transport.clone(),
+            syscalls: manifest.runtime.syscalls.clone(),
+            health_check: manifest.runtime.health_check.clone(),
+            schedule: manifest.runtime.schedule.clone(),
+            limits: manifest.runtime.limits,
+            socket: manifest.runtime.socket.clone(),
         },
         permissions: PermissionsInfo {
             network: manifest.permissions.network.clone(),
             env: manifest.permissions.env.clone(),
             filesystem: manifest.permissions.filesystem.clone(),
             exec: manifest.permissions.exec.clone(),
+            tools: manifest.permissions.tools.clone(),
         },
         config,
         config_meta: ConfigMeta {
             required_keys,
             optional_keys,
+            sensitive_keys,
         },
     }
 }
@@ -504,6 +536,16 @@ struct ManifestRuntime {
     #[serde(default)]
     args: Vec<String>,
     transport: String,
+    #[serde(default)]
+    syscalls: Vec<String>,
+    #[serde(default)]
+    health_check: Option<HealthCheck>,
+    #[serde(default)]
+    schedule: Option<String>,
+    #[serde(default)]
+    limits: Option<ResourceLimits>,
+    #[serde(default)]
+    socket: Option<String>,
 }
 
 #[derive(Debug, Clone, Default, Deserialize)]
@@ -516,6 +558,8 @@ struct ManifestPermissions {
     filesystem: Vec<String>,
     #[serde(default)]
     exec: Vec<String>,
+    #[serde(default)]
+    tools: BTreeMap<String, Vec<String>>,
 }
 
 #[derive(Debug, Clone, Default, Deserialize)]
@@ -533,7 +577,6 @@ struct ManifestConfigField {
     #[serde(default)]
     env: Option<String>,
     description: String,
-    #[allow(dead_code)]
     #[serde(default)]
     sensitive: bool,
     #[serde(default)]
@@ -592,7 +635,7 @@ repository = "https://github.com/acme/demo"
 type = "node"
 command = "npx"
 args = ["demo"]
-transport = "http"
+transport = "sse"
 
 [permissions]
 env = ["bad-var"]
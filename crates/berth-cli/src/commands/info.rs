@@ -3,14 +3,29 @@
 
 //! Command handler for `berth info`.
 
-use berth_registry::types::TrustLevel;
-use berth_registry::Registry;
+use berth_registry::types::{ServerMetadata, TrustLevel};
+use berth_registry::versions::{at_version, published_versions};
 use colored::Colorize;
+use serde::Serialize;
 use std::process;
 
-/// Executes the `berth info` command.
-pub fn execute(server_name: &str) {
-    let registry = Registry::from_seed();
+use crate::commands::link::linked_clients;
+use crate::suggest;
+
+/// One published version in `berth info --versions --json`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct VersionEntry<'a> {
+    version: &'a str,
+    current: bool,
+    source: &'a berth_registry::types::ServerSource,
+    runtime: &'a berth_registry::types::ServerRuntime,
+    permissions: &'a berth_registry::types::ServerPermissions,
+}
+
+/// Executes the `berth info` command; `json` prints the registry metadata as is.
+pub fn execute(server_name: &str, json: bool, versions: bool) {
+    let registry = super::load_registry();
 
     let server = match registry.get(server_name) {
         Some(s) => s,
@@ -20,6 +35,7 @@ pub fn execute(server_name: &str) {
                 "✗".red().bold(),
                 server_name
             );
+            suggest::print_server_suggestions(server_name, &registry);
             eprintln!(
                 "  Run {} to search for available servers.",
                 "berth search <query>".bold()
@@ -28,6 +44,27 @@ pub fn execute(server_name: &str) {
         }
     };
 
+    if versions {
+        print_versions(server, json);
+        return;
+    }
+
+    if json {
+        match serde_json::to_string_pretty(server) {
+            Ok(rendered) => println!("{rendered}"),
+            Err(e) => {
+                eprintln!(
+                    "{} Failed to serialize {}: {}",
+                    "✗".red().bold(),
+                    server_name,
+                    e
+                );
+                process::exit(1);
+            }
+        }
+        return;
+    }
+
     // Header
     println!();
     println!(
@@ -172,6 +209,25 @@ pub fn execute(server_name: &str) {
         "Platforms:".dimmed(),
         server.compatibility.platforms.join(", ")
     );
+    // Unreadable client configs only cost this line, not the whole view.
+    let linked = linked_clients().unwrap_or_default();
+    if !linked.is_empty() {
+        let rendered: Vec<String> = linked
+            .iter()
+            .map(|client| {
+                if server.compatibility.supports_client(client) {
+                    format!("{client} {}", "✓".green())
+                } else {
+                    format!("{client} {}", "✗ not supported".red())
+                }
+            })
+            .collect();
+        println!(
+            "  {:<18} {}",
+            "Linked clients:".dimmed(),
+            rendered.join(", ")
+        );
+    }
     println!();
 
     // Quality
@@ -203,3 +259,81 @@ pub fn execute(server_name: &str) {
     );
     println!();
 }
+
+/// Prints every published version, newest first, with what differs from
+/// the current one.
+fn print_versions(server: &ServerMetadata, json: bool) {
+    let resolved: Vec<ServerMetadata> = published_versions(server)
+        .into_iter()
+        .filter_map(|version| at_version(server, version))
+        .collect();
+
+    if json {
+        let entries: Vec<VersionEntry<'_>> = resolved
+            .iter()
+            .map(|meta| VersionEntry {
+                version: &meta.version,
+                current: meta.version == server.version,
+                source: &meta.source,
+                runtime: &meta.runtime,
+                permissions: &meta.permissions,
+            })
+            .collect();
+        match serde_json::to_string_pretty(&entries) {
+            Ok(rendered) => println!("{rendered}"),
+            Err(e) => {
+                eprintln!("{} Failed to serialize versions: {}", "✗".red().bold(), e);
+                process::exit(1);
+            }
+        }
+        return;
+    }
+
+    println!();
+    println!(
+        "  {} {}",
+        server.display_name.bold(),
+        "published versions".dimmed()
+    );
+    println!();
+    for meta in &resolved {
+        let marker = if meta.version == server.version {
+            " (current)".green().to_string()
+        } else {
+            String::new()
+        };
+        println!("  {}{}", format!("v{}", meta.version).bold(), marker);
+        if meta.source.package != server.source.package {
+            println!("    {:<16} {}", "Package:".dimmed(), meta.source.package);
+        }
+        if meta.runtime.args != server.runtime.args
+            || meta.runtime.command != server.runtime.command
+        {
+            println!(
+                "    {:<16} {} {}",
+                "Command:".dimmed(),
+                meta.runtime.command,
+                meta.runtime.args.join(" ")
+            );
+        }
+        let permissions = &meta.permissions;
+        let classes = [
+            ("Network:", &permissions.network),
+            ("Environment:", &permissions.env),
+            ("Filesystem:", &permissions.filesystem),
+            ("Exec:", &permissions.exec),
+        ];
+        for (label, entries) in classes {
+            if !entries.is_empty() {
+                println!("    {:<16} {}", label.dimmed(), entries.join(", "));
+            }
+        }
+    }
+    println!();
+    println!(
+        "  Install one with {}, or a range with {}.",
+        format!("berth install {}@<version>", server.name).bold(),
+        format!("berth install {}@^<major>", server.name).bold()
+    );
+    println!();
+}
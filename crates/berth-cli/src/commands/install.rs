@@ -5,109 +5,359 @@
 
 use colored::Colorize;
 use std::fs;
+use std::io::IsTerminal;
 use std::path::{Path, PathBuf};
 use std::process;
 use std::process::Command;
 
+use berth_common::sha256_file;
 use berth_registry::config::InstalledServer;
-use berth_registry::config::RuntimeInfo;
+use berth_registry::config::{RuntimeInfo, SourceInfo};
 use berth_registry::types::ServerMetadata;
-use berth_registry::Registry;
+use berth_registry::versions::resolve_version;
+use berth_registry::{mirror_dir, Registry, MIRROR_INDEX_FILE};
 
+use crate::builtin_runtime::{berth_executable, BUILTIN_RUNTIME};
+use crate::commands::registry_mirror::{apply_mirror_artifact, load_mirror_manifest};
+use crate::disk_usage::{check_quota, estimate_install};
+use crate::dry_run::{join_or_none, DryRunPlan};
+use crate::install_hooks::{describe_step, run_post_install, validate_step};
 use crate::paths;
+use crate::permission_filter::PermissionOverrides;
+use crate::platform::is_portable_file_name;
+use crate::policy_engine::{enforce_global_policy, load_global_policy};
+use crate::progress::{say, Progress, ProgressMode};
+use crate::receipts::{self, FileRecord, PolicyDecision, Receipt};
+use crate::shell_preview::command_line;
+use crate::suggest;
+use crate::wasm_runtime::{is_wasm, module_file_name, WASM_RUNTIME};
 
 /// Executes the `berth install` command.
-pub fn execute(server_spec: &str) {
+pub fn execute(
+    server_spec: &str,
+    dry_run: bool,
+    mirror: Option<&str>,
+    no_hooks: bool,
+    replace: bool,
+    alias: Option<&str>,
+    progress: ProgressMode,
+) {
+    let mut progress = Progress::new(progress, "install", if dry_run { 1 } else { 4 });
+    progress.start("resolve", Some(alias.unwrap_or(server_spec)));
     let (server, requested_version) = match parse_server_spec(server_spec) {
         Ok(v) => v,
         Err(msg) => {
-            eprintln!("{} {}", "✗".red().bold(), msg);
-            process::exit(1);
+            progress.exit_with_error(&msg);
         }
     };
+    // The local instance name; config, secrets, permissions, and runtime state
+    // are keyed by it while registry lookups keep using `server`.
+    let name = alias.unwrap_or(server);
+    if let Err(msg) = alias.map_or(Ok(()), validate_alias) {
+        progress.exit_with_error(&msg);
+    }
 
-    let registry = Registry::from_seed();
+    // Absolute so mirrored npm tarball paths stay valid in the installed runtime args.
+    let mirror = mirror
+        .map(PathBuf::from)
+        .or_else(mirror_dir)
+        .map(|dir| fs::canonicalize(&dir).unwrap_or(dir));
+    let registry = match &mirror {
+        Some(dir) => match Registry::from_index_file(&dir.join(MIRROR_INDEX_FILE)) {
+            Ok(r) => r,
+            Err(msg) => {
+                progress.exit_with_error(&msg);
+            }
+        },
+        None => super::load_registry(),
+    };
 
-    let meta = match registry.get(server) {
-        Some(m) => m,
+    let mut meta = match registry.get(server) {
+        Some(m) => m.clone(),
         None => {
             eprintln!(
                 "{} Server {} not found in the registry.",
                 "✗".red().bold(),
                 server.cyan()
             );
+            suggest::print_server_suggestions(server, &registry);
+            progress.fail(&format!("Server {server} not found in the registry."));
+            progress.finish();
             process::exit(1);
         }
     };
-    if let Some(version) = requested_version {
-        if meta.version != version {
-            eprintln!(
-                "{} Version {} for {} is not available in the seed registry (available: {}).",
-                "✗".red().bold(),
-                version.bold(),
-                server.cyan(),
-                meta.version
-            );
-            process::exit(1);
-        }
+    if let Some(constraint) = requested_version {
+        meta = match resolve_version(&meta, constraint) {
+            Ok(resolved) => resolved,
+            Err(msg) => {
+                progress.exit_with_error(&msg);
+            }
+        };
     }
 
-    let config_path = match paths::server_config_path(server) {
+    let mirrored = match &mirror {
+        Some(dir) => match load_mirror_manifest(dir) {
+            Ok(manifest) => manifest.servers.get(server).map(|artifact| {
+                apply_mirror_artifact(&mut meta, dir, artifact);
+                dir.join(&artifact.path)
+            }),
+            Err(msg) => {
+                progress.exit_with_error(&msg);
+            }
+        },
+        None => None,
+    };
+    let meta = &meta;
+
+    let config_path = match paths::server_config_path(name) {
         Some(p) => p,
-        None => {
-            eprintln!("{} Could not determine home directory.", "✗".red().bold());
-            process::exit(1);
-        }
+        None => progress.exit_with_error("Could not determine home directory."),
     };
 
-    if config_path.exists() {
-        println!(
+    let mut incoming_source = InstalledServer::from_metadata(meta).source;
+    incoming_source.registry = Some(registry.origin().to_string());
+    let installed_servers = read_installed_servers();
+    let collisions = find_collisions(name, &incoming_source, &installed_servers);
+    if collisions.is_empty() && config_path.exists() {
+        say!(
             "{} {} is already installed.",
             "!".yellow().bold(),
-            server.cyan()
+            name.cyan()
         );
+        progress.complete();
+        progress.finish();
         return;
     }
+    if !collisions.is_empty() && !replace {
+        eprintln!(
+            "{} {} collides with an installed server from a different source:",
+            "✗".red().bold(),
+            name.cyan()
+        );
+        for (replaced_name, installed) in &collisions {
+            eprintln!(
+                "  {:<10} {} {}",
+                "installed".dimmed(),
+                replaced_name.cyan(),
+                describe_provenance(
+                    &installed.server.version,
+                    &installed.server.trust_level,
+                    &installed.source
+                )
+            );
+        }
+        eprintln!(
+            "  {:<10} {} {}",
+            "incoming".dimmed(),
+            name.cyan(),
+            describe_provenance(
+                &meta.version,
+                &meta.trust_level.to_string(),
+                &incoming_source
+            )
+        );
+        eprintln!(
+            "  Re-run with {} to replace the installed server, or {} to install it alongside.",
+            "--replace".bold(),
+            "--as <name>".bold()
+        );
+        progress.fail(&format!(
+            "{name} collides with an installed server from a different source."
+        ));
+        progress.finish();
+        process::exit(1);
+    }
+
+    let estimate = estimate_install(meta, mirrored.as_deref());
+    let quota = home_quota_verdict(estimate.home_bytes);
+
+    if dry_run {
+        let plan = install_plan(
+            name,
+            meta,
+            &config_path,
+            mirrored.as_deref(),
+            no_hooks,
+            &registry,
+        )
+        .map(|mut plan| {
+            plan.add("estimated size", estimate.describe());
+            if let Some(verdict) = &quota {
+                plan.add(
+                    "home quota",
+                    match verdict {
+                        Ok(()) => "within quota".to_string(),
+                        Err(msg) => format!("would be refused ({msg})"),
+                    },
+                );
+            }
+            for (replaced_name, installed) in &collisions {
+                plan.add(
+                    "replaces",
+                    format!(
+                        "{replaced_name} {}",
+                        describe_provenance(
+                            &installed.server.version,
+                            &installed.server.trust_level,
+                            &installed.source
+                        )
+                    ),
+                );
+            }
+            plan
+        });
+        match plan {
+            Ok(plan) => plan.print(),
+            Err(msg) => {
+                progress.exit_with_error(&msg);
+            }
+        }
+        progress.complete();
+        progress.finish();
+        return;
+    }
+
+    say!("  Estimated size: {}", estimate.describe().dimmed());
+    if let Some(Err(msg)) = quota {
+        progress.exit_with_error(&msg);
+    }
+
+    for (replaced_name, _) in &collisions {
+        if let Err(msg) = ensure_stopped(replaced_name) {
+            progress.exit_with_error(&msg);
+        }
+    }
 
     // Create the servers directory if needed
     if let Some(parent) = config_path.parent() {
         if let Err(e) = fs::create_dir_all(parent) {
-            eprintln!(
-                "{} Failed to create directory {}: {}",
-                "✗".red().bold(),
-                parent.display(),
-                e
-            );
-            process::exit(1);
+            progress.exit_with_error(&format!(
+                "Failed to create directory {}: {e}",
+                parent.display()
+            ));
         }
     }
+    progress.complete();
 
-    let installed = match prepare_installed_server(server, meta) {
-        Ok(v) => v,
+    progress.start("fetch", Some(name));
+    let installed = match prepare_installed_server(name, meta, mirrored.as_deref()) {
+        Ok(mut v) => {
+            v.source.registry = incoming_source.registry.clone();
+            v.server.version_constraint = requested_version.map(str::to_string);
+            v
+        }
         Err(msg) => {
-            eprintln!("{} {}", "✗".red().bold(), msg);
-            process::exit(1);
+            progress.exit_with_error(&msg);
         }
     };
+    progress.complete();
+
+    progress.start("configure", Some(name));
     let toml_str = match toml::to_string_pretty(&installed) {
         Ok(s) => s,
-        Err(e) => {
-            eprintln!("{} Failed to serialize config: {}", "✗".red().bold(), e);
-            process::exit(1);
-        }
+        Err(e) => progress.exit_with_error(&format!("Failed to serialize config: {e}")),
     };
 
     if let Err(e) = fs::write(&config_path, &toml_str) {
-        eprintln!("{} Failed to write config file: {}", "✗".red().bold(), e);
-        process::exit(1);
+        progress.exit_with_error(&format!("Failed to write config file: {e}"));
+    }
+    if let Some(runtime) = paths::runtime_manager() {
+        let previous = collisions
+            .iter()
+            .find(|(replaced_name, _)| replaced_name == name)
+            .map(|(_, replaced)| replaced.server.version.as_str());
+        let _ = runtime.record_version_event(name, "install", &meta.version, previous);
     }
 
-    println!(
-        "{} Installed {} (v{}).",
-        "✓".green().bold(),
-        server.cyan(),
-        meta.version
-    );
+    if name == server {
+        say!(
+            "{} Installed {} (v{}).",
+            "✓".green().bold(),
+            name.cyan(),
+            meta.version
+        );
+    } else {
+        say!(
+            "{} Installed {} as {} (v{}).",
+            "✓".green().bold(),
+            server.cyan(),
+            name.cyan(),
+            meta.version
+        );
+    }
+    if let Some(digest) = &installed.source.sha256 {
+        say!("  Verified sha256 {}", digest.dimmed());
+    }
+    for (replaced_name, replaced) in &collisions {
+        // A same-name install was overwritten above; look-alikes under another
+        // name are uninstalled so only the new server remains.
+        if replaced_name != name {
+            if let Err(msg) = uninstall_replaced(replaced_name) {
+                progress.exit_with_error(&msg);
+            }
+        }
+        say!(
+            "  Replaced {} {}",
+            replaced_name.cyan(),
+            describe_provenance(
+                &replaced.server.version,
+                &replaced.server.trust_level,
+                &replaced.source
+            )
+            .dimmed()
+        );
+    }
+
+    progress.complete();
+
+    progress.start("hooks", Some(name));
+    let hooks = if no_hooks {
+        if !meta.post_install.is_empty() {
+            say!(
+                "  Skipped {} post-install step(s) (--no-hooks).",
+                meta.post_install.len()
+            );
+        }
+        "skipped (--no-hooks)".to_string()
+    } else {
+        let open_browser =
+            std::io::stdout().is_terminal() && std::env::var_os("BERTH_NO_BROWSER").is_none();
+        let skipped = run_post_install(
+            &meta.post_install,
+            &installed.permissions,
+            dirs::home_dir().as_deref(),
+            open_browser,
+        );
+        format!(
+            "ran {} step(s), skipped {skipped}",
+            meta.post_install.len() - skipped
+        )
+    };
+
+    let mut receipt = Receipt::new(name, "install", &meta.version, &installed.source);
+    receipt.previous_version = collisions
+        .iter()
+        .find(|(replaced_name, _)| replaced_name == name)
+        .map(|(_, replaced)| replaced.server.version.clone());
+    if let Err(msg) = fill_install_receipt(
+        &mut receipt,
+        &installed,
+        &config_path,
+        mirrored.as_deref(),
+        &collisions,
+        hooks,
+        &registry,
+    )
+    .and_then(|()| receipts::write(&receipt))
+    {
+        say!(
+            "{} Could not write install receipt: {msg}",
+            "!".yellow().bold()
+        );
+    }
+
+    progress.complete();
+    progress.finish();
 
     // Suggest berth config if there are required config fields
     if !meta.config.required.is_empty() {
@@ -117,32 +367,308 @@ pub fn execute(server_spec: &str) {
             .iter()
             .map(|f| f.key.as_str())
             .collect();
-        println!(
+        say!(
             "\n  This server requires configuration: {}",
             keys.join(", ").yellow()
         );
-        println!(
+        say!(
             "  Run {} to configure it.",
-            format!("berth config {server}").bold()
+            format!("berth config {name}").bold()
         );
     }
 }
 
+/// Records the files an install wrote and the policy decisions it took.
+fn fill_install_receipt(
+    receipt: &mut Receipt,
+    installed: &InstalledServer,
+    config_path: &Path,
+    mirrored: Option<&Path>,
+    collisions: &[&(String, InstalledServer)],
+    hooks: String,
+    registry: &Registry,
+) -> Result<(), String> {
+    receipt.artifact = mirrored
+        .filter(|path| path.is_file())
+        .map(|path| FileRecord::hashed(path, "artifact"))
+        .transpose()?;
+    receipt
+        .files
+        .push(FileRecord::hashed(config_path, "config")?);
+    if matches!(
+        installed.runtime.runtime_type.as_str(),
+        "binary" | WASM_RUNTIME
+    ) {
+        receipt.files.push(FileRecord::hashed(
+            Path::new(&installed.runtime.command),
+            "binary",
+        )?);
+    }
+    receipt.policy.push(PolicyDecision::new(
+        "global",
+        global_policy_verdict(&receipt.server, installed, registry)?,
+    ));
+    for (replaced_name, replaced) in collisions {
+        receipt.policy.push(PolicyDecision::new(
+            "collision",
+            format!(
+                "replaced {replaced_name} {}",
+                describe_provenance(
+                    &replaced.server.version,
+                    &replaced.server.trust_level,
+                    &replaced.source
+                )
+            ),
+        ));
+    }
+    receipt
+        .policy
+        .push(PolicyDecision::new("post-install", hooks));
+    Ok(())
+}
+
+/// Reads every installed server config, skipping unreadable ones.
+pub(crate) fn read_installed_servers() -> Vec<(String, InstalledServer)> {
+    let Some(dir) = paths::berth_servers_dir() else {
+        return Vec::new();
+    };
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut servers = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+        .filter_map(|path| {
+            let name = path.file_stem()?.to_string_lossy().to_string();
+            let content = fs::read_to_string(&path).ok()?;
+            Some((name, toml::from_str::<InstalledServer>(&content).ok()?))
+        })
+        .collect::<Vec<_>>();
+    servers.sort_by(|a, b| a.0.cmp(&b.0));
+    servers
+}
+
+/// Returns installed servers an incoming install would shadow or duplicate.
+///
+/// A server with the same name collides unless it was installed from the same
+/// package. One under another name collides when it is the same package and
+/// repository installed from a different registry. Repository matches alone
+/// do not count, since monorepos publish many servers from one repository.
+fn find_collisions<'a>(
+    server: &str,
+    incoming: &SourceInfo,
+    installed: &'a [(String, InstalledServer)],
+) -> Vec<&'a (String, InstalledServer)> {
+    installed
+        .iter()
+        .filter(|(name, existing)| {
+            if name == server {
+                return !same_artifact(&existing.source, incoming);
+            }
+            same_artifact(&existing.source, incoming)
+                && matches!(
+                    (&existing.source.registry, &incoming.registry),
+                    (Some(a), Some(b)) if a != b
+                )
+        })
+        .collect()
+}
+
+/// Returns whether two sources name the same package and repository.
+fn same_artifact(existing: &SourceInfo, incoming: &SourceInfo) -> bool {
+    existing.source_type == incoming.source_type
+        && existing.package == incoming.package
+        && normalize_repository(&existing.repository) == normalize_repository(&incoming.repository)
+}
+
+/// Normalizes a repository URL for comparison (case, trailing `/` and `.git`).
+fn normalize_repository(url: &str) -> String {
+    let url = url.trim().trim_end_matches('/').to_ascii_lowercase();
+    url.strip_suffix(".git").unwrap_or(&url).to_string()
+}
+
+/// One-line provenance of an install, e.g. `v1.2.0 · npm pkg · repo · official · from bundled seed`.
+fn describe_provenance(version: &str, trust_level: &str, source: &SourceInfo) -> String {
+    format!(
+        "v{version} · {} {} · {} · {trust_level} · from {}",
+        source.source_type,
+        source.package,
+        if source.repository.is_empty() {
+            "no repository"
+        } else {
+            &source.repository
+        },
+        source.registry.as_deref().unwrap_or("unrecorded registry")
+    )
+}
+
+/// Refuses to replace a server whose process is still running.
+fn ensure_stopped(name: &str) -> Result<(), String> {
+    let runtime = paths::runtime_manager().ok_or("Could not determine home directory.")?;
+    match runtime.running_pid(name) {
+        Ok(Some(pid)) => Err(format!(
+            "Server {} is running (PID {pid}). Stop it first with {}.",
+            name.cyan(),
+            format!("berth stop {name}").bold()
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Removes a replaced server's config, runtime state, and logs like `berth uninstall`.
+fn uninstall_replaced(name: &str) -> Result<(), String> {
+    let config_path =
+        paths::server_config_path(name).ok_or("Could not determine home directory.")?;
+    fs::remove_file(&config_path)
+        .map_err(|e| format!("Failed to remove {}: {e}", config_path.display()))?;
+    if let Some(runtime) = paths::runtime_manager() {
+        let _ = runtime.remove_server_artifacts(name);
+        let _ = runtime.record_audit_event(name, "uninstall", None, None, None);
+    }
+    Ok(())
+}
+
+/// Describes what `berth install` would do without fetching or writing anything.
+fn install_plan(
+    server: &str,
+    meta: &ServerMetadata,
+    config_path: &Path,
+    mirrored: Option<&Path>,
+    no_hooks: bool,
+    registry: &Registry,
+) -> Result<DryRunPlan, String> {
+    let mut installed = InstalledServer::from_metadata(meta);
+    let mut plan = DryRunPlan::new("install", server);
+    plan.add("outcome", format!("would install v{}", meta.version));
+    if meta.name != server {
+        plan.add("alias of", meta.name.clone());
+    }
+    plan.add("runtime", installed.runtime.runtime_type.clone());
+    if let Some(artifact) = mirrored {
+        plan.add("mirror artifact", artifact.display().to_string());
+    }
+    plan.add("integrity", integrity_plan(meta, mirrored));
+
+    match installed.runtime.runtime_type.as_str() {
+        "node" | "job" => {}
+        "python" => ensure_python_runtime(&mut installed.runtime, &installed.source.package),
+        "binary" | WASM_RUNTIME => {
+            let bin_dir = paths::berth_bin_dir()
+                .ok_or_else(|| "Could not determine home directory.".to_string())?;
+            let destination = bin_dir.join(artifact_file_name(server, &installed));
+            let package = &installed.source.package;
+            if package.starts_with("http://") || package.starts_with("https://") {
+                plan.add("download", package.clone());
+            } else {
+                plan.add(
+                    "copy",
+                    package
+                        .strip_prefix("file://")
+                        .unwrap_or(package)
+                        .to_string(),
+                );
+            }
+            installed.runtime.command = destination.to_string_lossy().to_string();
+            plan.add_write(&destination);
+        }
+        BUILTIN_RUNTIME => installed.runtime.command = berth_executable()?,
+        other => {
+            return Err(format!(
+                "Unsupported runtime type `{other}` for {}.",
+                server.cyan()
+            ))
+        }
+    }
+
+    plan.add(
+        "command",
+        command_line(&installed.runtime.command, &installed.runtime.args),
+    );
+    plan.add_permissions(&installed.permissions);
+    plan.add(
+        "required config",
+        join_or_none(&installed.config_meta.required_keys),
+    );
+    for step in &meta.post_install {
+        let status = match validate_step(step, &installed.permissions) {
+            _ if no_hooks => "skipped (--no-hooks)".to_string(),
+            Ok(()) => "would run".to_string(),
+            Err(reason) => format!("would skip ({reason})"),
+        };
+        plan.add(
+            "post-install",
+            format!("{} [{status}]", describe_step(step)),
+        );
+    }
+
+    plan.add(
+        "policy global",
+        global_policy_verdict(server, &installed, registry)?,
+    );
+    plan.add_write(config_path);
+    Ok(plan)
+}
+
+/// Describes how org policy treats a server once it is started.
+fn global_policy_verdict(
+    server: &str,
+    installed: &InstalledServer,
+    registry: &Registry,
+) -> Result<String, String> {
+    let policy = load_global_policy()?;
+    let overrides = PermissionOverrides::default();
+    Ok(
+        match enforce_global_policy(server, installed, &overrides, &policy, registry) {
+            Ok(()) => "allowed at start".to_string(),
+            Err(msg) => format!("would be denied at start ({msg})"),
+        },
+    )
+}
+
+/// Checks an install of `incoming` bytes against the org policy's Berth home
+/// quota; `None` when no quota is configured.
+fn home_quota_verdict(incoming: u64) -> Option<Result<(), String>> {
+    let quota_mb = match load_global_policy() {
+        Ok(policy) => policy.storage.quota_mb?,
+        Err(msg) => return Some(Err(msg)),
+    };
+    let home = paths::berth_home().ok_or_else(|| "Could not determine home directory.".to_string());
+    Some(home.and_then(|home| check_quota(&home, quota_mb, incoming)))
+}
+
 /// Builds installed config from metadata and prepares runtime artifacts when needed.
-fn prepare_installed_server(
+///
+/// Artifacts Berth fetches itself, binaries and mirrored npm tarballs, are
+/// verified against the registry's SHA-256; the verified digest is kept in
+/// the installed config.
+pub(crate) fn prepare_installed_server(
     server: &str,
     meta: &ServerMetadata,
+    mirrored: Option<&Path>,
 ) -> Result<InstalledServer, String> {
     let mut installed = InstalledServer::from_metadata(meta);
+    let expected = meta.source.sha256.as_deref();
     match installed.runtime.runtime_type.as_str() {
-        "node" => Ok(installed),
-        "python" => {
-            ensure_python_runtime(&mut installed.runtime, &installed.source.package);
+        "node" | "job" | "python" => {
+            if installed.runtime.runtime_type == "python" {
+                ensure_python_runtime(&mut installed.runtime, &installed.source.package);
+            }
+            if let (Some(expected), Some(artifact)) = (expected, mirrored) {
+                installed.source.sha256 = Some(verify_sha256(server, artifact, expected)?);
+            }
             Ok(installed)
         }
-        "binary" => {
-            let binary_path = install_binary_artifact(server, &installed.source.package)?;
+        "binary" | WASM_RUNTIME => {
+            let file_name = artifact_file_name(server, &installed);
+            let (binary_path, digest) =
+                install_binary_artifact(&file_name, server, &installed.source.package, expected)?;
             installed.runtime.command = binary_path.to_string_lossy().to_string();
+            installed.source.sha256 = digest;
+            Ok(installed)
+        }
+        BUILTIN_RUNTIME => {
+            installed.runtime.command = berth_executable()?;
             Ok(installed)
         }
         other => Err(format!(
@@ -152,6 +678,50 @@ fn prepare_installed_server(
     }
 }
 
+/// Checks that `path` has the SHA-256 `expected` and returns its digest.
+pub(crate) fn verify_sha256(server: &str, path: &Path, expected: &str) -> Result<String, String> {
+    let expected = normalize_sha256(expected)?;
+    let actual =
+        sha256_file(path).map_err(|e| format!("Failed to hash {}: {e}", path.display()))?;
+    if actual != expected {
+        return Err(format!(
+            "Integrity check failed for {}: {} has sha256 {actual}, but the registry expects {expected}. The artifact may be corrupted or tampered with.",
+            server.cyan(),
+            path.display()
+        ));
+    }
+    Ok(actual)
+}
+
+/// Strips an optional `sha256:` prefix and checks for 64 hex digits.
+pub(crate) fn normalize_sha256(digest: &str) -> Result<String, String> {
+    let digest = digest.trim();
+    let hex = digest.strip_prefix("sha256:").unwrap_or(digest);
+    if hex.len() != 64 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!(
+            "`{digest}` is not a SHA-256 digest (expected 64 hex digits)."
+        ));
+    }
+    Ok(hex.to_ascii_lowercase())
+}
+
+/// Describes how the install of `meta` will be checked for integrity.
+pub(crate) fn integrity_plan(meta: &ServerMetadata, mirrored: Option<&Path>) -> String {
+    match &meta.source.sha256 {
+        None => "no sha256 declared".to_string(),
+        Some(digest)
+            if matches!(meta.runtime.runtime_type.as_str(), "binary" | WASM_RUNTIME)
+                || mirrored.is_some() =>
+        {
+            format!("verify sha256 {digest}")
+        }
+        Some(_) => format!(
+            "sha256 declared; package is fetched by {} at launch and not verified",
+            meta.runtime.command
+        ),
+    }
+}
+
 /// Ensures python runtimes default to `uvx <package>` when command/args are missing.
 fn ensure_python_runtime(runtime: &mut RuntimeInfo, package: &str) {
     if runtime.command.trim().is_empty() {
@@ -163,37 +733,44 @@ fn ensure_python_runtime(runtime: &mut RuntimeInfo, package: &str) {
 }
 
 /// Installs a binary artifact from local path/file URL/http URL into Berth's bin directory.
-fn install_binary_artifact(server: &str, package: &str) -> Result<PathBuf, String> {
+///
+/// With an `expected` digest the artifact is fetched next to the destination
+/// and only moved into place once it matches; the verified digest is returned.
+fn install_binary_artifact(
+    file_name: &str,
+    server: &str,
+    package: &str,
+    expected: Option<&str>,
+) -> Result<(PathBuf, Option<String>), String> {
     let bin_dir =
         paths::berth_bin_dir().ok_or_else(|| "Could not determine home directory.".to_string())?;
     fs::create_dir_all(&bin_dir)
         .map_err(|e| format!("failed to create {}: {e}", bin_dir.display()))?;
 
-    let mut file_name = server.to_string();
-    if cfg!(windows) && !file_name.to_ascii_lowercase().ends_with(".exe") {
-        file_name.push_str(".exe");
-    }
     let destination = bin_dir.join(file_name);
-
-    if package.starts_with("http://") || package.starts_with("https://") {
-        download_binary(package, &destination)?;
-    } else {
-        let source = package.strip_prefix("file://").unwrap_or(package);
-        let source_path = Path::new(source);
-        if !source_path.exists() {
-            return Err(format!(
-                "Binary source {} does not exist.",
-                source_path.display()
-            ));
+    let digest = match expected {
+        Some(expected) => {
+            let download = bin_dir.join(format!("{file_name}.download"));
+            let verified = fetch_binary(package, &download)
+                .and_then(|()| verify_sha256(server, &download, expected));
+            if verified.is_err() {
+                let _ = fs::remove_file(&download);
+            }
+            let digest = verified?;
+            fs::rename(&download, &destination).map_err(|e| {
+                format!(
+                    "failed to move binary {} -> {}: {e}",
+                    download.display(),
+                    destination.display()
+                )
+            })?;
+            Some(digest)
         }
-        fs::copy(source_path, &destination).map_err(|e| {
-            format!(
-                "failed to copy binary {} -> {}: {e}",
-                source_path.display(),
-                destination.display()
-            )
-        })?;
-    }
+        None => {
+            fetch_binary(package, &destination)?;
+            None
+        }
+    };
 
     #[cfg(unix)]
     {
@@ -215,7 +792,48 @@ fn install_binary_artifact(server: &str, package: &str) -> Result<PathBuf, Strin
         })?;
     }
 
-    Ok(destination)
+    Ok((destination, digest))
+}
+
+/// Copies or downloads a binary artifact from a local path, file URL, or http URL.
+pub(crate) fn fetch_binary(package: &str, destination: &Path) -> Result<(), String> {
+    if package.starts_with("http://") || package.starts_with("https://") {
+        return download_binary(package, destination);
+    }
+    let source = package.strip_prefix("file://").unwrap_or(package);
+    let source_path = Path::new(source);
+    if !source_path.exists() {
+        return Err(format!(
+            "Binary source {} does not exist.",
+            source_path.display()
+        ));
+    }
+    fs::copy(source_path, destination).map_err(|e| {
+        format!(
+            "failed to copy binary {} -> {}: {e}",
+            source_path.display(),
+            destination.display()
+        )
+    })?;
+    Ok(())
+}
+
+/// Returns the file a server's binary or module is installed as.
+fn artifact_file_name(server: &str, installed: &InstalledServer) -> String {
+    if is_wasm(installed) {
+        module_file_name(server)
+    } else {
+        binary_file_name(server)
+    }
+}
+
+/// Returns the installed binary file name for a server on this platform.
+fn binary_file_name(server: &str) -> String {
+    let mut file_name = server.to_string();
+    if cfg!(windows) && !file_name.to_ascii_lowercase().ends_with(".exe") {
+        file_name.push_str(".exe");
+    }
+    file_name
 }
 
 /// Downloads a binary artifact using `curl` or `wget`.
@@ -240,15 +858,35 @@ fn download_binary(url: &str, destination: &Path) -> Result<(), String> {
     ))
 }
 
-/// Parses `server` or `server@version` install specs.
-fn parse_server_spec(spec: &str) -> Result<(&str, Option<&str>), String> {
+/// Parses `server`, `server@version` or `server@<range>` specs.
+pub(crate) fn parse_server_spec(spec: &str) -> Result<(&str, Option<&str>), String> {
     if let Some((server, version)) = spec.rsplit_once('@') {
-        if server.is_empty() || version.is_empty() {
+        if server.is_empty() || version.trim().is_empty() {
             return Err(
-                "Invalid server format. Use `<server>` or `<server>@<version>`.".to_string(),
+                "Invalid server format. Use `<server>`, `<server>@<version>` or `<server>@<range>` (e.g. `^1.2`)."
+                    .to_string(),
             );
         }
         return Ok((server, Some(version)));
     }
     Ok((spec, None))
 }
+
+/// Validates a local instance name given with `--as`.
+fn validate_alias(alias: &str) -> Result<(), String> {
+    if alias.is_empty()
+        || !alias
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+    {
+        return Err(format!(
+            "Invalid alias `{alias}`. Use lowercase letters, digits, and dashes only."
+        ));
+    }
+    if !is_portable_file_name(alias) {
+        return Err(format!(
+            "Invalid alias `{alias}`. Reserved Windows device names (con, nul, com1, ...) are not allowed."
+        ));
+    }
+    Ok(())
+}
@@ -8,12 +8,21 @@ use serde::Serialize;
 use serde_json::{Map, Value};
 use std::collections::BTreeMap;
 use std::fs;
-use std::path::{Path, PathBuf};
+use std::path::Path;
 use std::process;
 
 use berth_registry::config::InstalledServer;
 use berth_registry::Registry;
 
+use crate::builtin_runtime::is_builtin_service;
+use crate::client_backups;
+use crate::client_compat::{
+    load_client_compat, load_client_framing, load_client_overrides, set_client_compat,
+    set_client_framing, set_client_override, ClientOverride, CompatMode,
+};
+use crate::commands::unlink::installed_server_names;
+use crate::framing::Framing;
+use crate::jsonc;
 use crate::paths;
 use crate::permission_filter::{filter_env_map, load_permission_overrides};
 use crate::policy_engine::{enforce_global_policy, load_global_policy};
@@ -31,10 +40,25 @@ struct ClientServerConfig {
 struct LinkableServers {
     servers: Vec<(String, ClientServerConfig)>,
     skipped_by_policy: Vec<String>,
+    /// Servers whose manifest does not list the client.
+    unsupported: Vec<String>,
 }
 
+/// Clients `berth link` can write configs for.
+pub const CLIENTS: [&str; 5] = ["claude-desktop", "cursor", "windsurf", "continue", "vscode"];
+
 /// Executes the `berth link` command.
-pub fn execute(client: &str) {
+///
+/// Servers whose manifest does not list `client` are linked with a warning,
+/// or refused with `strict`. `overrides` are `<server>.<key>=<value>` config
+/// overrides the proxy applies when this client connects.
+pub fn execute(
+    client: &str,
+    compat: Option<&str>,
+    framing: Option<&str>,
+    overrides: &[String],
+    strict: bool,
+) {
     let config_path = match paths::client_config_path(client) {
         Some(p) => p,
         None => {
@@ -51,18 +75,134 @@ pub fn execute(client: &str) {
             process::exit(1);
         }
     };
-    link_client(client, &config_path);
+    if let Some(value) = compat {
+        let mode = match CompatMode::parse(value) {
+            Ok(mode) => mode,
+            Err(msg) => {
+                eprintln!("{} {}", "✗".red().bold(), msg);
+                process::exit(1);
+            }
+        };
+        if let Err(msg) = set_client_compat(client, &mode) {
+            eprintln!("{} {}", "✗".red().bold(), msg);
+            process::exit(1);
+        }
+    }
+    if let Some(value) = framing {
+        let framing = match Framing::parse(value) {
+            Ok(framing) => framing,
+            Err(msg) => {
+                eprintln!("{} {}", "✗".red().bold(), msg);
+                process::exit(1);
+            }
+        };
+        if let Err(msg) = set_client_framing(client, framing) {
+            eprintln!("{} {}", "✗".red().bold(), msg);
+            process::exit(1);
+        }
+    }
+    for raw in overrides {
+        if let Err(msg) = set_override(client, raw) {
+            eprintln!("{} {}", "✗".red().bold(), msg);
+            process::exit(1);
+        }
+    }
+    let via_proxy = match load_client_compat(client).and_then(|mode| {
+        Ok(mode.is_some()
+            || load_client_framing(client)?.is_some()
+            || !load_client_overrides(client)?.is_empty())
+    }) {
+        Ok(via_proxy) => via_proxy,
+        Err(msg) => {
+            eprintln!("{} {}", "✗".red().bold(), msg);
+            process::exit(1);
+        }
+    };
+    link_client(client, &config_path, via_proxy, strict);
+}
+
+/// Validates and persists one `--set <server>.<key>=<value>` override.
+fn set_override(client: &str, raw: &str) -> Result<(), String> {
+    let entry = ClientOverride::parse(raw)?;
+    let path =
+        paths::server_config_path(&entry.server).ok_or("Could not determine home directory.")?;
+    if !path.exists() {
+        return Err(format!("Server {} is not installed.", entry.server.cyan()));
+    }
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read config for {}: {e}", entry.server))?;
+    let installed: InstalledServer = toml::from_str(&content)
+        .map_err(|e| format!("Failed to parse config for {}: {e}", entry.server))?;
+    let is_known = installed.config_meta.required_keys.contains(&entry.key)
+        || installed.config_meta.optional_keys.contains(&entry.key);
+    if !is_known {
+        return Err(format!(
+            "Unknown config key `{}` for {}.",
+            entry.key,
+            entry.server.cyan()
+        ));
+    }
+    set_client_override(client, &entry)
+}
+
+/// Executes `berth link <client> --undo`.
+pub fn undo(client: &str) {
+    let Some(config_path) = paths::client_config_path(client) else {
+        eprintln!(
+            "{} Unsupported client {}. Supported: {}.",
+            "✗".red().bold(),
+            client.cyan(),
+            CLIENTS.join(", ").bold()
+        );
+        process::exit(1);
+    };
+    match client_backups::restore_latest(client, &config_path) {
+        Ok(Some(snapshot)) => {
+            println!(
+                "{} Restored {} config from before the last link/unlink.",
+                "✓".green().bold(),
+                client.cyan()
+            );
+            println!("  Config: {}", config_path.display());
+            println!("  From:   {}", snapshot.display());
+        }
+        Ok(None) => {
+            println!(
+                "{} No {} config backups to restore.",
+                "!".yellow().bold(),
+                client.cyan()
+            );
+        }
+        Err(msg) => {
+            eprintln!("{} {}", "✗".red().bold(), msg);
+            process::exit(1);
+        }
+    }
 }
 
 /// Links all installable Berth servers into a supported client config file.
-fn link_client(client: &str, config_path: &Path) {
-    let linked = match load_linkable_servers() {
+fn link_client(client: &str, config_path: &Path, via_proxy: bool, strict: bool) {
+    let linked = match load_linkable_servers(client, via_proxy) {
         Ok(servers) => servers,
         Err(msg) => {
             eprintln!("{} {}", "✗".red().bold(), msg);
             process::exit(1);
         }
     };
+    if strict && !linked.unsupported.is_empty() {
+        eprintln!(
+            "{} Not supported by {} according to their manifest: {}",
+            "✗".red().bold(),
+            client.cyan(),
+            linked.unsupported.join(", ").cyan()
+        );
+        eprintln!(
+            "  Run {} without {} to link them anyway.",
+            format!("berth link {client}").bold(),
+            "--strict".bold()
+        );
+        process::exit(1);
+    }
 
     if let Some(parent) = config_path.parent() {
         if let Err(e) = fs::create_dir_all(parent) {
@@ -76,9 +216,9 @@ fn link_client(client: &str, config_path: &Path) {
         }
     }
 
-    let (mut root, backup_path) = if config_path.exists() {
-        let content = match fs::read_to_string(config_path) {
-            Ok(c) => c,
+    let existing = if config_path.exists() {
+        match fs::read_to_string(config_path) {
+            Ok(c) => Some(c),
             Err(e) => {
                 eprintln!(
                     "{} Failed to read existing client config {}: {}",
@@ -88,9 +228,14 @@ fn link_client(client: &str, config_path: &Path) {
                 );
                 process::exit(1);
             }
-        };
+        }
+    } else {
+        None
+    };
 
-        let parsed = match serde_json::from_str::<Value>(&content) {
+    let mut root = match &existing {
+        None => Value::Object(Map::new()),
+        Some(existing) => match jsonc::parse(existing) {
             Ok(v) => v,
             Err(e) => {
                 eprintln!(
@@ -101,22 +246,7 @@ fn link_client(client: &str, config_path: &Path) {
                 );
                 process::exit(1);
             }
-        };
-
-        let backup = backup_path(config_path);
-        if let Err(e) = fs::copy(config_path, &backup) {
-            eprintln!(
-                "{} Failed to create backup {}: {}",
-                "✗".red().bold(),
-                backup.display(),
-                e
-            );
-            process::exit(1);
-        }
-
-        (parsed, Some(backup))
-    } else {
-        (Value::Object(Map::new()), None)
+        },
     };
 
     if !root.is_object() {
@@ -161,7 +291,18 @@ fn link_client(client: &str, config_path: &Path) {
         mcp_servers.insert(name.clone(), value);
     }
 
-    let rendered = match serde_json::to_string_pretty(&root) {
+    // Files with comments or trailing commas are edited in place so those
+    // survive; plain JSON is re-rendered as before.
+    let rendered = match existing.filter(|text| jsonc::needs_tolerant_edit(text)) {
+        Some(existing) => jsonc::Document::parse(&existing).and_then(|mut doc| {
+            for (name, _) in &linked.servers {
+                doc.set(&["mcpServers"], name, &root["mcpServers"][name])?;
+            }
+            Ok(doc.into_string())
+        }),
+        None => serde_json::to_string_pretty(&root).map_err(|e| e.to_string()),
+    };
+    let rendered = match rendered {
         Ok(s) => s,
         Err(e) => {
             eprintln!(
@@ -173,6 +314,13 @@ fn link_client(client: &str, config_path: &Path) {
         }
     };
 
+    let backup = match client_backups::snapshot(client, config_path) {
+        Ok(path) => path,
+        Err(msg) => {
+            eprintln!("{} {}", "✗".red().bold(), msg);
+            process::exit(1);
+        }
+    };
     if let Err(e) = fs::write(config_path, rendered) {
         eprintln!(
             "{} Failed to write client config {}: {}",
@@ -197,14 +345,34 @@ fn link_client(client: &str, config_path: &Path) {
             linked.skipped_by_policy.join(", ").cyan()
         );
     }
-    println!("  Config: {}", config_path.display());
-    if let Some(backup) = backup_path {
-        println!("  Backup: {}", backup.display());
+    if !linked.unsupported.is_empty() {
+        eprintln!(
+            "{} Manifest does not list {} as a supported client: {}",
+            "!".yellow().bold(),
+            client.cyan(),
+            linked.unsupported.join(", ").cyan()
+        );
+    }
+    if let Ok(overrides) = load_client_overrides(client) {
+        for (server, keys) in &overrides {
+            let keys: Vec<&str> = keys.keys().map(String::as_str).collect();
+            println!(
+                "  {} {} ({})",
+                "Overrides:".dimmed(),
+                server.cyan(),
+                keys.join(", ")
+            );
+        }
     }
+    println!("  Config: {}", config_path.display());
+    println!("  Backup: {}", backup.display());
 }
 
 /// Loads installed server definitions and converts them to client entries.
-fn load_linkable_servers() -> Result<LinkableServers, String> {
+///
+/// With `via_proxy`, entries launch `berth proxy <server> --client <client>`
+/// so the client's compat settings and overrides apply.
+fn load_linkable_servers(client: &str, via_proxy: bool) -> Result<LinkableServers, String> {
     let servers_dir = paths::berth_servers_dir().ok_or("Could not determine home directory.")?;
 
     if !servers_dir.exists() {
@@ -226,6 +394,7 @@ fn load_linkable_servers() -> Result<LinkableServers, String> {
     let policy = load_global_policy()?;
     let mut out = Vec::new();
     let mut skipped_by_policy = Vec::new();
+    let mut unsupported = Vec::new();
 
     for entry in &entries {
         let path = entry.path();
@@ -239,6 +408,10 @@ fn load_linkable_servers() -> Result<LinkableServers, String> {
             .map_err(|e| format!("Failed to read config for {name}: {e}"))?;
         let installed: InstalledServer = toml::from_str(&content)
             .map_err(|e| format!("Failed to parse config for {name}: {e}"))?;
+        // Berth services such as the registry API are not MCP servers.
+        if is_builtin_service(&installed) {
+            continue;
+        }
 
         let missing_required: Vec<String> = installed
             .config_meta
@@ -257,7 +430,8 @@ fn load_linkable_servers() -> Result<LinkableServers, String> {
         }
 
         let mut env = BTreeMap::new();
-        if let Some(meta) = registry.get(&name) {
+        let meta = registry.get(&installed.server.name);
+        if let Some(meta) = meta {
             for field in meta
                 .config
                 .required
@@ -282,12 +456,19 @@ fn load_linkable_servers() -> Result<LinkableServers, String> {
             }
         }
         let overrides = load_permission_overrides(&name)?;
-        if enforce_global_policy(&name, &installed.permissions, &overrides, &policy).is_err() {
+        if enforce_global_policy(&name, &installed, &overrides, &policy, &registry).is_err() {
             skipped_by_policy.push(name);
             continue;
         }
         filter_env_map(&mut env, &installed.permissions.env, &overrides);
+        if meta.is_some_and(|meta| !meta.compatibility.supports_client(client)) {
+            unsupported.push(name.clone());
+        }
 
+        if via_proxy {
+            out.push((name.clone(), proxy_entry(&name, client)?));
+            continue;
+        }
         out.push((
             name,
             ClientServerConfig {
@@ -301,15 +482,59 @@ fn load_linkable_servers() -> Result<LinkableServers, String> {
     Ok(LinkableServers {
         servers: out,
         skipped_by_policy,
+        unsupported,
     })
 }
 
-/// Returns a deterministic backup path next to the client config file.
-fn backup_path(config_path: &Path) -> PathBuf {
-    let file_name = config_path
-        .file_name()
-        .unwrap_or_default()
-        .to_string_lossy()
-        .to_string();
-    config_path.with_file_name(format!("{file_name}.bak"))
+/// Returns the clients whose config currently holds an installed Berth server.
+pub fn linked_clients() -> Result<Vec<&'static str>, String> {
+    let installed = installed_server_names()?;
+    let mut linked = Vec::new();
+    for client in CLIENTS {
+        let Some(path) = paths::client_config_path(client) else {
+            continue;
+        };
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        let Ok(root) = jsonc::parse(&content) else {
+            continue;
+        };
+        let has_berth_server = root
+            .get("mcpServers")
+            .and_then(Value::as_object)
+            .is_some_and(|servers| installed.iter().any(|name| servers.contains_key(name)));
+        if has_berth_server {
+            linked.push(client);
+        }
+    }
+    Ok(linked)
+}
+
+/// Builds a client entry that launches the server through `berth proxy`.
+fn proxy_entry(name: &str, client: &str) -> Result<ClientServerConfig, String> {
+    let exe = std::env::current_exe()
+        .map_err(|e| format!("Failed to resolve berth executable path: {e}"))?;
+    let mut env = BTreeMap::new();
+    // Clients rarely inherit the shell's env; pin the layout the proxy resolves.
+    for var in [
+        "BERTH_HOME",
+        "XDG_CONFIG_HOME",
+        "XDG_STATE_HOME",
+        "XDG_CACHE_HOME",
+    ] {
+        if let Ok(value) = std::env::var(var) {
+            env.insert(var.to_string(), value);
+        }
+    }
+    Ok(ClientServerConfig {
+        command: exe.to_string_lossy().to_string(),
+        args: vec![
+            "proxy".to_string(),
+            name.to_string(),
+            "--client".to_string(),
+            client.to_string(),
+        ],
+        env,
+    })
 }
@@ -4,16 +4,41 @@
 //! Command handler for `berth list`.
 
 use colored::Colorize;
+use serde::Serialize;
+use std::collections::BTreeMap;
 use std::fs;
+use std::process;
 
+use berth_common::storage::format_bytes;
 use berth_registry::config::InstalledServer;
-use berth_registry::Registry;
 use berth_runtime::{RuntimeManager, ServerStatus};
 
+use crate::commands::status::status_label;
+use crate::disk_usage::server_disk_usage;
+use crate::observer;
 use crate::paths;
 
+/// One installed server in `berth list --json` output.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ListedServer {
+    name: String,
+    /// Installed version; `None` when the config cannot be read.
+    version: Option<String>,
+    /// Runtime status; `None` when the runtime state cannot be read.
+    status: Option<ServerStatus>,
+    /// Registry version, when the server is in the registry.
+    latest_version: Option<String>,
+    /// Bytes used in the Berth home; only with `--sizes`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    disk_bytes: Option<u64>,
+    /// Why the config could not be read, for the table.
+    #[serde(skip)]
+    problem: Option<&'static str>,
+}
+
 /// Executes the `berth list` command.
-pub fn execute() {
+pub fn execute(json: bool, sizes: bool) {
     let servers_dir = match paths::berth_servers_dir() {
         Some(d) => d,
         None => {
@@ -22,85 +47,133 @@ pub fn execute() {
         }
     };
 
-    if !servers_dir.exists() {
+    let mut entries: Vec<_> = if servers_dir.exists() {
+        fs::read_dir(&servers_dir)
+            .map(|rd| {
+                rd.filter_map(|e| e.ok())
+                    .filter(|e| e.path().extension().is_some_and(|ext| ext == "toml"))
+                    .collect()
+            })
+            .unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+
+    if entries.is_empty() && !json {
         print_no_servers();
         return;
     }
-
-    let entries: Vec<_> = match fs::read_dir(&servers_dir) {
-        Ok(rd) => rd
-            .filter_map(|e| e.ok())
-            .filter(|e| e.path().extension().is_some_and(|ext| ext == "toml"))
-            .collect(),
-        Err(_) => {
-            print_no_servers();
-            return;
-        }
+    entries.sort_by_key(|e| e.path());
+
+    let registry = super::load_registry();
+    let runtime = paths::runtime_manager().unwrap_or_else(|| RuntimeManager::new(&servers_dir));
+    let artifacts = if sizes {
+        runtime.artifact_usage().unwrap_or_default()
+    } else {
+        BTreeMap::new()
     };
 
-    if entries.is_empty() {
-        print_no_servers();
+    let servers: Vec<ListedServer> = entries
+        .iter()
+        .map(|entry| {
+            let path = entry.path();
+            let name = path
+                .file_stem()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string();
+            let installed = match fs::read_to_string(&path) {
+                Ok(content) => {
+                    toml::from_str::<InstalledServer>(&content).map_err(|_| "parse error")
+                }
+                Err(_) => Err("read error"),
+            };
+            ListedServer {
+                version: installed.as_ref().ok().map(|i| i.server.version.clone()),
+                latest_version: installed
+                    .as_ref()
+                    .ok()
+                    .and_then(|i| registry.get(&i.server.name))
+                    .map(|meta| meta.version.clone()),
+                status: if observer::enabled() {
+                    runtime.peek_status(&name)
+                } else {
+                    runtime.status(&name)
+                }
+                .ok(),
+                disk_bytes: installed
+                    .as_ref()
+                    .ok()
+                    .filter(|_| sizes)
+                    .map(|i| server_disk_usage(&name, i, &artifacts)),
+                problem: installed.err(),
+                name,
+            }
+        })
+        .collect();
+
+    if json {
+        match serde_json::to_string_pretty(&servers) {
+            Ok(rendered) => println!("{rendered}"),
+            Err(e) => {
+                eprintln!("{} Failed to serialize servers: {}", "✗".red().bold(), e);
+                process::exit(1);
+            }
+        }
         return;
     }
 
-    let registry = Registry::from_seed();
-    let runtime = RuntimeManager::new(paths::berth_home().unwrap_or_else(|| servers_dir.clone()));
-
     println!(
         "{} {} server(s) installed:\n",
         "✓".green().bold(),
-        entries.len()
+        servers.len()
     );
 
+    let size_header = if sizes {
+        format!("{:<11} ", "SIZE".bold())
+    } else {
+        String::new()
+    };
     println!(
-        "  {:<20} {:<12} {:<12} {}",
+        "  {:<20} {:<12} {:<14} {size_header}{}",
         "NAME".bold(),
         "VERSION".bold(),
         "STATUS".bold(),
         "UPDATE".bold(),
     );
-    println!("  {}", "─".repeat(60));
-
-    for entry in &entries {
-        let path = entry.path();
-        let name = path
-            .file_stem()
-            .unwrap_or_default()
-            .to_string_lossy()
-            .to_string();
-
-        let (version, update) = match fs::read_to_string(&path) {
-            Ok(content) => match toml::from_str::<InstalledServer>(&content) {
-                Ok(installed) => {
-                    let ver = installed.server.version.clone();
-                    let upd = match registry.get(&name) {
-                        Some(meta) if meta.version != ver => {
-                            format!("{} available", meta.version).yellow().to_string()
-                        }
-                        Some(_) => "up to date".green().to_string(),
-                        None => "unknown".dimmed().to_string(),
-                    };
-                    (ver, upd)
-                }
-                Err(_) => ("?".to_string(), "parse error".red().to_string()),
-            },
-            Err(_) => ("?".to_string(), "read error".red().to_string()),
+    println!("  {}", "─".repeat(if sizes { 74 } else { 62 }));
+
+    for server in &servers {
+        let update = match (&server.version, &server.latest_version) {
+            (None, _) => server.problem.unwrap_or_default().red().to_string(),
+            (Some(installed), Some(latest)) if latest != installed => {
+                format!("{latest} available").yellow().to_string()
+            }
+            (Some(_), Some(_)) => "up to date".green().to_string(),
+            (Some(_), None) => "unknown".dimmed().to_string(),
         };
-
-        let status = match runtime.status(&name) {
-            Ok(ServerStatus::Running) => "running".green().to_string(),
-            Ok(ServerStatus::Stopped) => "stopped".dimmed().to_string(),
-            Err(_) => "error".red().to_string(),
+        let status = match server.status {
+            Some(status) => status_label(status),
+            None => "error".red().to_string(),
         };
 
+        let size = match (sizes, server.disk_bytes) {
+            (false, _) => String::new(),
+            (true, Some(bytes)) => format!("{:<11} ", format_bytes(bytes)),
+            (true, None) => format!("{:<11} ", "?"),
+        };
         println!(
-            "  {:<20} {:<12} {:<12} {}",
-            name.cyan(),
-            version,
+            "  {:<20} {:<12} {:<14} {size}{}",
+            server.name.cyan(),
+            server.version.as_deref().unwrap_or("?"),
             status,
             update,
         );
     }
+    if sizes {
+        let total: u64 = servers.iter().filter_map(|s| s.disk_bytes).sum();
+        println!("\n  Total: {}", format_bytes(total));
+    }
     println!();
 }
 
@@ -4,15 +4,20 @@
 //! Command handler for `berth logs`.
 
 use colored::Colorize;
+use std::io::{self, Write};
 use std::process;
 
-use berth_runtime::RuntimeManager;
-
+use crate::daemon;
+use crate::log_stream::{self, LogTail, FOLLOW_BUFFER_LINES};
 use crate::paths;
+use crate::replica_policy::base_server;
 
-/// Executes the `berth logs` command.
-pub fn execute(server: &str, tail: u32) {
-    let config_path = match paths::server_config_path(server) {
+/// Executes the `berth logs` command; `server` may name a replica, e.g. `github@2`.
+///
+/// With `raw`, the log's original bytes are written to stdout without any
+/// decoding or decoration, e.g. to export binary server output.
+pub fn execute(server: &str, tail: u32, follow: bool, raw: bool) {
+    let config_path = match paths::server_config_path(base_server(server)) {
         Some(p) => p,
         None => {
             eprintln!("{} Could not determine home directory.", "✗".red().bold());
@@ -29,16 +34,46 @@ pub fn execute(server: &str, tail: u32) {
         process::exit(1);
     }
 
-    let berth_home = match paths::berth_home() {
-        Some(h) => h,
+    let runtime = match paths::runtime_manager() {
+        Some(r) => r,
         None => {
             eprintln!("{} Could not determine home directory.", "✗".red().bold());
             process::exit(1);
         }
     };
-    let runtime = RuntimeManager::new(berth_home);
 
-    let lines = match runtime.tail_logs(server, tail as usize) {
+    if raw {
+        let log_tail = LogTail::from_end(&runtime.log_path(server));
+        let exported = runtime
+            .tail_logs_raw(server, tail as usize)
+            .and_then(|bytes| {
+                let mut out = io::stdout().lock();
+                out.write_all(&bytes)?;
+                out.flush()
+            })
+            .and_then(|()| {
+                if follow {
+                    log_stream::follow_raw(log_tail, io::stdout())
+                } else {
+                    Ok(())
+                }
+            });
+        match exported {
+            Ok(()) => return,
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => return,
+            Err(e) => {
+                eprintln!(
+                    "{} Failed to read logs for {}: {}",
+                    "✗".red().bold(),
+                    server.cyan(),
+                    e
+                );
+                process::exit(1);
+            }
+        }
+    }
+
+    let lines = match daemon::tail_logs(&runtime, server, tail as usize) {
         Ok(l) => l,
         Err(e) => {
             eprintln!(
@@ -57,17 +92,33 @@ pub fn execute(server: &str, tail: u32) {
             "!".yellow().bold(),
             server.cyan()
         );
-        return;
+    } else {
+        println!(
+            "{} Last {} log line(s) for {}:\n",
+            "✓".green().bold(),
+            lines.len(),
+            server.cyan()
+        );
+        for line in lines {
+            println!("  {}", line);
+        }
     }
 
-    println!(
-        "{} Last {} log line(s) for {}:\n",
-        "✓".green().bold(),
-        lines.len(),
-        server.cyan()
-    );
-
-    for line in lines {
-        println!("  {}", line);
+    if follow {
+        let log_tail = LogTail::from_end(&runtime.log_path(server));
+        println!(
+            "\n{} Following {} (Ctrl-C to stop)...",
+            "!".yellow().bold(),
+            server.cyan()
+        );
+        if let Err(e) = log_stream::follow(log_tail, FOLLOW_BUFFER_LINES, io::stdout()) {
+            eprintln!(
+                "{} Failed to follow logs for {}: {}",
+                "✗".red().bold(),
+                server.cyan(),
+                e
+            );
+            process::exit(1);
+        }
     }
 }
@@ -0,0 +1,199 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Command handler for `berth migrate` and the upgrade run before other commands.
+
+use colored::Colorize;
+use std::path::Path;
+use std::process;
+
+use crate::migrations::{self, Plan, CURRENT_FORMAT};
+use crate::observer;
+use crate::paths;
+
+/// Executes the `berth migrate` command.
+pub fn execute(dry_run: bool) {
+    let Some(layout) = paths::berth_layout() else {
+        eprintln!("{} Could not determine home directory.", "✗".red().bold());
+        process::exit(1);
+    };
+    let plan = match migrations::plan(&layout) {
+        Ok(plan) => plan,
+        Err(msg) => {
+            eprintln!("{} {}", "✗".red().bold(), msg);
+            process::exit(1);
+        }
+    };
+    if plan.is_newer() {
+        report_newer(&plan);
+        process::exit(1);
+    }
+    if plan.file_count() == 0 {
+        if !dry_run && plan.is_outdated() && layout.state.is_dir() {
+            if let Err(msg) = migrations::apply(&layout, &plan) {
+                eprintln!("{} {}", "✗".red().bold(), msg);
+                process::exit(1);
+            }
+        }
+        println!(
+            "{} Berth data is at format {CURRENT_FORMAT}; nothing to migrate.",
+            "✓".green().bold()
+        );
+        return;
+    }
+
+    if dry_run {
+        println!(
+            "{} Would upgrade Berth data from format {} to {CURRENT_FORMAT}:",
+            "!".yellow().bold(),
+            plan.from
+        );
+        for line in step_lines(&plan, true) {
+            println!("{line}");
+        }
+        println!(
+            "  Files are copied to {} first.",
+            layout.state.join("backups").join("migrations").display()
+        );
+        return;
+    }
+
+    match migrations::apply(&layout, &plan) {
+        Ok(backup) => {
+            for line in report_lines(&plan, backup.as_deref()) {
+                println!("{line}");
+            }
+        }
+        Err(msg) => {
+            eprintln!("{} {}", "✗".red().bold(), msg);
+            process::exit(1);
+        }
+    }
+}
+
+/// Brings an older Berth home to the current format before a command runs,
+/// reporting on stderr so command output stays parseable.
+///
+/// A home in a newer format stops `mutating` commands and warns otherwise.
+pub fn upgrade_on_first_run(mutating: bool) {
+    let Some(layout) = paths::berth_layout() else {
+        return;
+    };
+    if !layout.state.is_dir() {
+        return;
+    }
+    let plan = match migrations::plan(&layout) {
+        Ok(plan) => plan,
+        Err(msg) => {
+            eprintln!("{} {}", "!".yellow().bold(), msg);
+            return;
+        }
+    };
+    if plan.is_newer() {
+        report_newer(&plan);
+        if mutating {
+            process::exit(1);
+        }
+        return;
+    }
+    if !plan.is_outdated() {
+        return;
+    }
+    if observer::enabled() || paths::check_storage().is_err() {
+        if plan.file_count() > 0 {
+            eprintln!(
+                "{} Berth data is at format {}; run {} to upgrade it to format {CURRENT_FORMAT}.",
+                "!".yellow().bold(),
+                plan.from,
+                "berth migrate".bold()
+            );
+        }
+        return;
+    }
+    match migrations::apply(&layout, &plan) {
+        Ok(backup) => {
+            let upgraded_release = plan
+                .recorded
+                .as_ref()
+                .is_some_and(|marker| marker.berth != env!("CARGO_PKG_VERSION"));
+            if plan.file_count() > 0 || upgraded_release {
+                for line in report_lines(&plan, backup.as_deref()) {
+                    eprintln!("{line}");
+                }
+            }
+        }
+        Err(msg) => {
+            eprintln!(
+                "{} Could not upgrade Berth data: {}",
+                "!".yellow().bold(),
+                msg
+            );
+            eprintln!("  Run {} to retry.", "berth migrate".bold());
+        }
+    }
+}
+
+fn report_lines(plan: &Plan, backup: Option<&Path>) -> Vec<String> {
+    let release = match &plan.recorded {
+        Some(marker) if marker.berth != env!("CARGO_PKG_VERSION") => {
+            format!(" (berth {} → {})", marker.berth, env!("CARGO_PKG_VERSION"))
+        }
+        _ => String::new(),
+    };
+    let mut lines = vec![if plan.from == CURRENT_FORMAT {
+        format!(
+            "{} Upgraded Berth{release}; data format {CURRENT_FORMAT} is unchanged.",
+            "✓".green().bold()
+        )
+    } else {
+        format!(
+            "{} Upgraded Berth data from format {} to {CURRENT_FORMAT}{release}.",
+            "✓".green().bold(),
+            plan.from
+        )
+    }];
+    lines.extend(step_lines(plan, false));
+    if let Some(backup) = backup {
+        lines.push(format!("  Backup: {}", backup.display()));
+    }
+    lines
+}
+
+/// Describes the steps that rewrite files, with each file when `list_files` is set.
+fn step_lines(plan: &Plan, list_files: bool) -> Vec<String> {
+    let mut lines = Vec::new();
+    for step in plan.steps.iter().filter(|step| !step.files.is_empty()) {
+        lines.push(format!(
+            "  format {}: {} ({} file(s))",
+            step.migration.to,
+            step.migration.title,
+            step.files.len()
+        ));
+        if list_files {
+            lines.extend(
+                step.files
+                    .iter()
+                    .map(|file| format!("    {}", file.display())),
+            );
+        }
+        if let Some(note) = step.migration.breaking {
+            lines.push(format!("    {} Breaking: {note}", "!".yellow().bold()));
+        }
+    }
+    lines
+}
+
+fn report_newer(plan: &Plan) {
+    let writer = plan
+        .recorded
+        .as_ref()
+        .map(|marker| format!(" by berth {}", marker.berth))
+        .unwrap_or_default();
+    eprintln!(
+        "{} Berth data was written{writer} in format {}; berth {} only knows format {CURRENT_FORMAT}.",
+        "✗".red().bold(),
+        plan.from,
+        env!("CARGO_PKG_VERSION")
+    );
+    eprintln!("  Upgrade berth, or restore a backup from before the upgrade, to change anything.");
+}
@@ -0,0 +1,414 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Command handler for `berth migrate-layout`.
+
+use colored::Colorize;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use berth_common::atomic_write;
+use berth_runtime::RuntimeManager;
+
+use crate::paths::{self, BerthLayout};
+
+/// Top-level entries of a single-directory home that belong in the config directory.
+const CONFIG_ENTRIES: [&str; 7] = [
+    "servers",
+    "permissions",
+    "credentials",
+    "plugins",
+    "policy.toml",
+    "compat.toml",
+    "plugins.toml",
+];
+
+/// One file or directory relocation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Move {
+    from: PathBuf,
+    to: PathBuf,
+}
+
+/// Executes the `berth migrate-layout` command.
+pub fn execute(dry_run: bool) {
+    if std::env::var_os("BERTH_HOME").is_some() {
+        eprintln!(
+            "{} BERTH_HOME pins a single-directory layout; unset it before migrating.",
+            "✗".red().bold()
+        );
+        process::exit(1);
+    }
+    let (Some(home), Some(legacy)) = (dirs::home_dir(), paths::legacy_berth_home()) else {
+        eprintln!("{} Could not determine home directory.", "✗".red().bold());
+        process::exit(1);
+    };
+    if !legacy.is_dir() {
+        println!(
+            "{} Nothing to migrate: {} does not exist.",
+            "✓".green().bold(),
+            legacy.display()
+        );
+        return;
+    }
+    let target = BerthLayout::xdg(&home);
+
+    let moves = match plan_moves(&legacy, &target) {
+        Ok(m) => m,
+        Err(msg) => {
+            eprintln!("{} {}", "✗".red().bold(), msg);
+            process::exit(1);
+        }
+    };
+    let conflicts: Vec<&Move> = moves.iter().filter(|m| m.to.exists()).collect();
+    if !conflicts.is_empty() {
+        eprintln!(
+            "{} Migration would overwrite existing files:",
+            "✗".red().bold()
+        );
+        for conflict in conflicts {
+            eprintln!("  {}", conflict.to.display());
+        }
+        process::exit(1);
+    }
+
+    if dry_run {
+        println!(
+            "{} Would migrate {} to the XDG layout:",
+            "!".yellow().bold(),
+            legacy.display()
+        );
+        for m in &moves {
+            println!("  {} -> {}", m.from.display(), m.to.display());
+        }
+        return;
+    }
+
+    let running = running_servers(&legacy);
+    if !running.is_empty() {
+        eprintln!(
+            "{} Stop running servers before migrating: {}",
+            "✗".red().bold(),
+            running.join(", ")
+        );
+        process::exit(1);
+    }
+
+    for m in &moves {
+        if let Err(e) = move_path(&m.from, &m.to) {
+            eprintln!(
+                "{} Failed to move {} to {}: {}",
+                "✗".red().bold(),
+                m.from.display(),
+                m.to.display(),
+                e
+            );
+            process::exit(1);
+        }
+    }
+    let rewritten = match rewrite_config_paths(&target.config, &moves) {
+        Ok(n) => n,
+        Err(msg) => {
+            eprintln!("{} {}", "✗".red().bold(), msg);
+            process::exit(1);
+        }
+    };
+    let _ = fs::remove_dir(legacy.join("registry"));
+    if let Err(e) = fs::remove_dir(&legacy) {
+        println!(
+            "{} Left {} in place: {}",
+            "!".yellow().bold(),
+            legacy.display(),
+            e
+        );
+    }
+
+    println!(
+        "{} Migrated {} to the XDG layout.",
+        "✓".green().bold(),
+        legacy.display()
+    );
+    println!("  config: {}", target.config.display());
+    println!("  state:  {}", target.state.display());
+    println!("  cache:  {}", target.cache.display());
+    println!(
+        "  Moved {} item(s), updated paths in {} config file(s).",
+        moves.len(),
+        rewritten
+    );
+}
+
+/// Maps every top-level entry of a single-directory home onto `target`.
+///
+/// Config entries go to the config directory, the registry index cache to the
+/// cache directory, and everything else to the state directory.
+fn plan_moves(legacy: &Path, target: &BerthLayout) -> Result<Vec<Move>, String> {
+    let mut moves = Vec::new();
+    for name in sorted_entries(legacy)? {
+        let from = legacy.join(&name);
+        if CONFIG_ENTRIES.contains(&name.as_str()) {
+            moves.push(Move {
+                to: target.config.join(&name),
+                from,
+            });
+        } else if name == "registry" && from.is_dir() {
+            for child in sorted_entries(&from)? {
+                let root = if child == "index.json" {
+                    &target.cache
+                } else {
+                    &target.state
+                };
+                moves.push(Move {
+                    from: from.join(&child),
+                    to: root.join("registry").join(&child),
+                });
+            }
+        } else {
+            moves.push(Move {
+                to: target.state.join(&name),
+                from,
+            });
+        }
+    }
+    Ok(moves)
+}
+
+fn sorted_entries(dir: &Path) -> Result<Vec<String>, String> {
+    let mut names: Vec<String> = fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read {}: {e}", dir.display()))?
+        .filter_map(|e| e.ok())
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        .collect();
+    names.sort();
+    Ok(names)
+}
+
+/// Lists servers whose tracked process is still alive in the legacy home.
+fn running_servers(legacy: &Path) -> Vec<String> {
+    let runtime = RuntimeManager::new(legacy);
+    let Ok(entries) = fs::read_dir(legacy.join("runtime")) else {
+        return Vec::new();
+    };
+    let mut running: Vec<String> = entries
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let path = e.path();
+            let server = path.file_stem()?.to_string_lossy().to_string();
+            let is_state = path.extension().is_some_and(|ext| ext == "toml");
+            (is_state && runtime.running_pid(&server).ok().flatten().is_some()).then_some(server)
+        })
+        .collect();
+    running.sort();
+    running
+}
+
+/// Renames a path, falling back to copy-and-delete across filesystems.
+fn move_path(from: &Path, to: &Path) -> io::Result<()> {
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    copy_recursive(from, to)?;
+    if from.is_dir() {
+        fs::remove_dir_all(from)
+    } else {
+        fs::remove_file(from)
+    }
+}
+
+fn copy_recursive(from: &Path, to: &Path) -> io::Result<()> {
+    if !from.is_dir() {
+        fs::copy(from, to)?;
+        return Ok(());
+    }
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        copy_recursive(&entry.path(), &to.join(entry.file_name()))?;
+    }
+    Ok(())
+}
+
+/// Rewrites moved absolute paths inside every TOML file below `config_dir`.
+///
+/// Returns the number of files changed.
+fn rewrite_config_paths(config_dir: &Path, moves: &[Move]) -> Result<usize, String> {
+    let mut files = Vec::new();
+    collect_toml_files(config_dir, &mut files)
+        .map_err(|e| format!("Failed to scan {}: {e}", config_dir.display()))?;
+    let mut changed = 0;
+    for file in files {
+        let content = fs::read_to_string(&file)
+            .map_err(|e| format!("Failed to read {}: {e}", file.display()))?;
+        let updated = rewrite_paths(&content, moves);
+        if updated != content {
+            atomic_write(&file, updated)
+                .map_err(|e| format!("Failed to write {}: {e}", file.display()))?;
+            changed += 1;
+        }
+    }
+    Ok(changed)
+}
+
+fn collect_toml_files(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    if !dir.is_dir() {
+        return Ok(());
+    }
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_toml_files(&path, files)?;
+        } else if path.extension().is_some_and(|ext| ext == "toml") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Replaces old paths with their new locations in one pass, preferring the
+/// longest match. A path only matches as whole components: `/a/bin` is
+/// rewritten in `/a/bin/tool` but not in `/a/bin2` or `/x/a/bin`.
+fn rewrite_paths(content: &str, moves: &[Move]) -> String {
+    let mut ordered: Vec<(String, String)> = moves
+        .iter()
+        .map(|m| {
+            (
+                m.from.to_string_lossy().into_owned(),
+                m.to.to_string_lossy().into_owned(),
+            )
+        })
+        .filter(|(from, _)| !from.is_empty())
+        .collect();
+    ordered.sort_by_key(|(from, _)| std::cmp::Reverse(from.len()));
+
+    let mut out = String::with_capacity(content.len());
+    let mut rest = content;
+    let mut previous = None;
+    while let Some(c) = rest.chars().next() {
+        let at_boundary = !previous.is_some_and(continues_path);
+        let matched = ordered.iter().find(|(from, _)| {
+            at_boundary
+                && rest.starts_with(from.as_str())
+                && rest[from.len()..]
+                    .chars()
+                    .next()
+                    .is_none_or(|next| matches!(next, '/' | '\\') || !continues_path(next))
+        });
+        if let Some((from, to)) = matched {
+            out.push_str(to);
+            rest = &rest[from.len()..];
+            previous = from.chars().last();
+        } else {
+            out.push(c);
+            rest = &rest[c.len_utf8()..];
+            previous = Some(c);
+        }
+    }
+    out
+}
+
+/// Returns whether `c` can be part of a path next to the one being matched.
+fn continues_path(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '.' | '_' | '-' | '~' | '/' | '\\')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target(root: &Path) -> BerthLayout {
+        BerthLayout {
+            config: root.join("config/berth"),
+            state: root.join("state/berth"),
+            cache: root.join("cache/berth"),
+        }
+    }
+
+    #[test]
+    fn plan_moves_splits_config_state_and_cache() {
+        let tmp = tempfile::tempdir().unwrap();
+        let legacy = tmp.path().join(".berth");
+        for dir in ["servers", "runtime", "logs", "bin", "registry/community"] {
+            fs::create_dir_all(legacy.join(dir)).unwrap();
+        }
+        fs::write(legacy.join("policy.toml"), "").unwrap();
+        fs::write(legacy.join("registry/index.json"), "[]").unwrap();
+
+        let layout = target(tmp.path());
+        let moves = plan_moves(&legacy, &layout).unwrap();
+        let to = |from: &str| {
+            moves
+                .iter()
+                .find(|m| m.from == legacy.join(from))
+                .map(|m| m.to.clone())
+                .unwrap()
+        };
+        assert_eq!(to("servers"), layout.config.join("servers"));
+        assert_eq!(to("policy.toml"), layout.config.join("policy.toml"));
+        assert_eq!(to("runtime"), layout.state.join("runtime"));
+        assert_eq!(to("bin"), layout.state.join("bin"));
+        assert_eq!(
+            to("registry/index.json"),
+            layout.cache.join("registry/index.json")
+        );
+        assert_eq!(
+            to("registry/community"),
+            layout.state.join("registry/community")
+        );
+    }
+
+    #[test]
+    fn rewrite_paths_prefers_longest_prefix() {
+        let moves = vec![
+            Move {
+                from: PathBuf::from("/home/dev/.berth/bin"),
+                to: PathBuf::from("/home/dev/.local/state/berth/bin"),
+            },
+            Move {
+                from: PathBuf::from("/home/dev/.berth/bin/github"),
+                to: PathBuf::from("/elsewhere/github"),
+            },
+        ];
+        let content =
+            "command = \"/home/dev/.berth/bin/tool\"\nargs = [\"/home/dev/.berth/bin/github\"]\n";
+        assert_eq!(
+            rewrite_paths(content, &moves),
+            "command = \"/home/dev/.local/state/berth/bin/tool\"\nargs = [\"/elsewhere/github\"]\n"
+        );
+    }
+
+    #[test]
+    fn rewrite_paths_matches_whole_components_only() {
+        let moves = vec![
+            Move {
+                from: PathBuf::from("/home/dev/.berth/bin"),
+                to: PathBuf::from("/home/dev/.local/state/berth/bin"),
+            },
+            Move {
+                from: PathBuf::from("/home/dev/.berth/policy.toml"),
+                to: PathBuf::from("/home/dev/.config/berth/policy.toml"),
+            },
+        ];
+        let content = concat!(
+            "a = \"/home/dev/.berth/bin\"\n",
+            "b = \"/home/dev/.berth/bin2/tool\"\n",
+            "c = \"/mnt/home/dev/.berth/bin/tool\"\n",
+            "d = \"/home/dev/.berth/policy.toml.bak\"\n",
+            "e = [\"/home/dev/.berth/policy.toml\", \"/home/dev/.berth/bin/x\"]\n",
+        );
+        assert_eq!(
+            rewrite_paths(content, &moves),
+            concat!(
+                "a = \"/home/dev/.local/state/berth/bin\"\n",
+                "b = \"/home/dev/.berth/bin2/tool\"\n",
+                "c = \"/mnt/home/dev/.berth/bin/tool\"\n",
+                "d = \"/home/dev/.berth/policy.toml.bak\"\n",
+                "e = [\"/home/dev/.config/berth/policy.toml\", \"/home/dev/.local/state/berth/bin/x\"]\n",
+            )
+        );
+    }
+}
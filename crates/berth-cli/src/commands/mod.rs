@@ -3,31 +3,65 @@
 
 //! CLI subcommand declarations and dispatch.
 
+pub mod adopt;
 pub mod analytics;
 pub mod audit;
+pub mod auth;
 pub mod config;
+pub mod daemon;
+pub mod doctor;
+pub mod explain;
+pub mod gateway;
+pub mod gc;
+pub mod history;
 pub mod import_github;
 pub mod info;
 pub mod install;
 pub mod link;
 pub mod list;
 pub mod logs;
+pub mod migrate;
+pub mod migrate_layout;
 pub mod permissions;
+pub mod plugins;
 pub mod policy;
 pub mod proxy;
+pub mod prune;
 pub mod publish;
+pub mod receipts;
 pub mod registry_api;
+pub mod registry_init;
+pub mod registry_mirror;
+pub mod registry_promote;
+pub mod registry_seed_check;
+pub mod relay_output;
 pub mod restart;
 pub mod search;
+pub mod show_command;
+pub mod socket_bridge;
 pub mod start;
 pub mod status;
 pub mod stop;
 pub mod supervise;
+pub mod test_server;
+pub mod top;
+pub mod trust;
 pub mod uninstall;
 pub mod unlink;
 pub mod update;
+pub mod warnings;
+pub mod wrap;
 
 use clap::Subcommand;
+use colored::Colorize;
+use std::process;
+
+use berth_registry::signing::IndexTrust;
+use berth_registry::Registry;
+
+use crate::paths;
+use crate::progress::ProgressMode;
+use crate::workspace_trust;
 
 /// Top-level CLI subcommands supported by `berth`.
 #[derive(Subcommand)]
@@ -36,21 +70,73 @@ pub enum Commands {
     Search {
         /// Search query
         query: String,
+
+        /// Print matching servers as JSON
+        #[arg(long)]
+        json: bool,
+
+        /// Show at most this many results and print a cursor for the rest
+        #[arg(long)]
+        limit: Option<usize>,
+
+        /// Continue from a cursor printed by an earlier `--limit` search
+        #[arg(long, value_name = "TOKEN")]
+        cursor: Option<String>,
     },
 
     /// Show detailed info about an MCP server
     Info {
         /// Server name
         server: String,
+
+        /// Print the registry metadata as JSON
+        #[arg(long)]
+        json: bool,
+
+        /// List published versions with their source and permissions
+        #[arg(long)]
+        versions: bool,
     },
 
     /// List installed MCP servers
-    List,
+    List {
+        /// Print installed servers as JSON
+        #[arg(long)]
+        json: bool,
+
+        /// Show the disk space each server uses in the Berth home
+        #[arg(long)]
+        sizes: bool,
+    },
 
     /// Install an MCP server
     Install {
-        /// Server name (optionally with @version)
+        /// Server name, optionally with @version or @<range> (e.g. `github@^1.2`)
         server: String,
+
+        /// Print what would be installed without writing anything
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Resolve the server and its artifact from a `registry-mirror` directory
+        #[arg(long)]
+        mirror: Option<String>,
+
+        /// Skip manifest-declared post-install steps
+        #[arg(long)]
+        no_hooks: bool,
+
+        /// Replace an installed server with the same name or package from a different source
+        #[arg(long)]
+        replace: bool,
+
+        /// Install under a local alias so one registry server can be installed more than once
+        #[arg(long = "as", value_name = "NAME")]
+        alias: Option<String>,
+
+        /// Progress output: `text`, or `json` for line-delimited events on stdout
+        #[arg(long, value_enum, default_value_t = ProgressMode::Text)]
+        progress: ProgressMode,
     },
 
     /// Auto-import an MCP server from a GitHub repo containing `berth.toml`
@@ -79,20 +165,29 @@ pub enum Commands {
 
     /// Update an MCP server (or all with --all)
     Update {
-        /// Server name (omit for --all)
+        /// Server name, optionally with @version or @<range> (omit for --all)
         server: Option<String>,
 
         /// Update all installed servers
         #[arg(long)]
         all: bool,
+
+        /// Print what would change without writing anything
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Progress output: `text`, or `json` for line-delimited events on stdout
+        #[arg(long, value_enum, default_value_t = ProgressMode::Text)]
+        progress: ProgressMode,
     },
 
     /// Configure an MCP server
     Config {
-        /// Server name, or 'export'/'import' for config sharing
+        /// Server name, 'export'/'import' for config sharing, or 'migrate-secrets'
         server: String,
 
-        /// Path for `config export` output or `config import` input
+        /// Path for `config export` output or `config import` input, or the
+        /// server for `config migrate-secrets`
         path: Option<String>,
 
         /// Set a config value (key=value)
@@ -112,26 +207,85 @@ pub enum Commands {
         interactive: bool,
     },
 
+    /// Sign in to a server's OAuth provider and store the refresh token
+    Auth {
+        /// Server name
+        server: String,
+
+        /// Print the authorization URL without opening a browser
+        #[arg(long)]
+        no_browser: bool,
+    },
+
     /// Start MCP server(s)
     Start {
         /// Server name (omit to start all)
         server: Option<String>,
+
+        /// Print the resolved launch plan without starting anything
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Inherit the full parent environment instead of only declared/granted variables
+        #[arg(long)]
+        inherit_env: bool,
+
+        /// Progress output: `text`, or `json` for line-delimited events on stdout
+        #[arg(long, value_enum, default_value_t = ProgressMode::Text)]
+        progress: ProgressMode,
     },
 
     /// Stop MCP server(s)
     Stop {
         /// Server name (omit to stop all)
         server: Option<String>,
+
+        /// Print what would be stopped without signaling processes
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Bring a server process started outside Berth under management
+    Adopt {
+        /// Server name
+        server: String,
+
+        /// Process to adopt (default: find it by its command line)
+        #[arg(long)]
+        pid: Option<u32>,
     },
 
     /// Restart an MCP server
     Restart {
         /// Server name
         server: String,
+
+        /// Inherit the full parent environment instead of only declared/granted variables
+        #[arg(long)]
+        inherit_env: bool,
+    },
+
+    /// Run one supervisor for all servers that start, stop, status, and logs talk to
+    Daemon {
+        /// `run` (default, in the foreground), `start`, `stop`, `status`, or
+        /// `install`/`uninstall` (Windows logon task)
+        action: Option<String>,
     },
 
     /// Show status of MCP servers
-    Status,
+    Status {
+        /// List scheduled jobs instead of servers
+        #[arg(long)]
+        jobs: bool,
+
+        /// Print status as JSON
+        #[arg(long)]
+        json: bool,
+
+        /// Print the path of the machine-wide status summary file for prompts and editors
+        #[arg(long, conflicts_with_all = ["jobs", "json"])]
+        summary_file: bool,
+    },
 
     /// Stream logs from an MCP server
     Logs {
@@ -141,6 +295,63 @@ pub enum Commands {
         /// Number of lines to show
         #[arg(long, default_value = "50")]
         tail: u32,
+
+        /// Keep streaming new lines; a slow terminal skips old lines instead of buffering them all
+        #[arg(long, short = 'f')]
+        follow: bool,
+
+        /// Write the log's original bytes without decoding or line formatting
+        #[arg(long)]
+        raw: bool,
+    },
+
+    /// Remove or archive runtime state and logs of uninstalled servers
+    Gc {
+        /// Move artifacts under the state directory's archive/ instead of deleting them
+        #[arg(long)]
+        archive: bool,
+
+        /// Print artifacts that would be pruned without changing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Check the Berth home, runtime state, runtimes, registry cache, client links, and keyring
+    Doctor {
+        /// Print the checks as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Remove state of uninstalled servers and logs, audit entries, and reviewed publish submissions past retention
+    Prune {
+        /// Retention period, e.g. 30d, 12h
+        #[arg(long, default_value = "30d")]
+        older_than: String,
+
+        /// Print what would be removed and the space it would free without changing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Upgrade Berth data written by an older release to this release's format
+    Migrate {
+        /// Print the migrations and files they would rewrite without changing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Move a single-directory ~/.berth into XDG config, state, and cache directories
+    MigrateLayout {
+        /// Print planned moves without changing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Print the resolved launch command for a server in shell form
+    ShowCommand {
+        /// Server name
+        server: String,
     },
 
     /// Show or manage permissions for an MCP server
@@ -161,15 +372,22 @@ pub enum Commands {
         reset: bool,
 
         /// Export declared/overrides/effective permissions as JSON
-        #[arg(long = "export")]
+        #[arg(long = "export", visible_alias = "json")]
         export_json: bool,
+
+        /// Show hosts the server contacted (needs berth.egress-monitor=log)
+        #[arg(long)]
+        usage: bool,
     },
 
-    /// Show, update, or validate global org policy
+    /// Show, update, validate, or test global org policy
     Policy {
-        /// Optional installed server name to validate against policy
+        /// Optional installed server name to validate against policy, or `test`
         server: Option<String>,
 
+        /// Scenario directory for `berth policy test <dir>`
+        dir: Option<String>,
+
         /// Update one policy key (key=value)
         #[arg(long)]
         set: Option<String>,
@@ -183,6 +401,31 @@ pub enum Commands {
         json: bool,
     },
 
+    /// List, grant, or revoke trust in workspaces whose Berth home defines servers
+    Trust {
+        /// `list` (default), `grant`, or `revoke`
+        action: Option<String>,
+
+        /// Workspace path (defaults to the checkout of the active home or current directory)
+        path: Option<String>,
+
+        /// Render timestamps in UTC instead of local time
+        #[arg(long)]
+        utc: bool,
+    },
+
+    /// List repeated warnings, or snooze or unsnooze one by ID
+    Warnings {
+        /// `list` (default), `snooze`, or `unsnooze`
+        action: Option<String>,
+
+        /// Warning ID, e.g. `plaintext-secrets`
+        id: Option<String>,
+
+        /// How long to snooze, e.g. `7d` (m, h, d, or w)
+        duration: Option<String>,
+    },
+
     /// Show audit log of MCP tool calls
     Audit {
         /// Server name (omit for all)
@@ -196,6 +439,10 @@ pub enum Commands {
         #[arg(long)]
         action: Option<String>,
 
+        /// Filter to entries recorded for a process ID
+        #[arg(long)]
+        pid: Option<u32>,
+
         /// Print matching audit entries as JSON
         #[arg(long)]
         json: bool,
@@ -203,6 +450,85 @@ pub enum Commands {
         /// Export matching audit entries to a file
         #[arg(long, value_name = "FILE")]
         export: Option<String>,
+
+        /// Render timestamps in UTC instead of local time
+        #[arg(long)]
+        utc: bool,
+
+        /// Check the audit hash chain and report the first broken link
+        #[arg(long, conflicts_with_all = ["server", "since", "action", "pid", "json", "export"])]
+        verify: bool,
+    },
+
+    /// List, enable, disable, or test plugins that receive audit events
+    Plugins {
+        /// Plugin name (omit to list discovered plugins)
+        name: Option<String>,
+
+        /// Deliver events to the plugin
+        #[arg(long, conflicts_with = "disable")]
+        enable: bool,
+
+        /// Stop delivering events to the plugin
+        #[arg(long)]
+        disable: bool,
+
+        /// Seconds the plugin may run per event before it is stopped
+        #[arg(long, value_name = "SECS")]
+        timeout: Option<u64>,
+
+        /// Send the plugin a test event and report whether it succeeded
+        #[arg(long)]
+        test: bool,
+    },
+
+    /// Explain an audit entry: trigger, process, related entries, and log lines
+    Explain {
+        /// What to explain (currently only `audit`)
+        topic: String,
+
+        /// Position from `berth audit` (e.g. 7, #7, -1), entry id, or pasted entry JSON
+        entry: String,
+
+        /// Related entries to show before and after
+        #[arg(long, default_value_t = 3)]
+        context: usize,
+
+        /// Render timestamps in UTC instead of local time
+        #[arg(long)]
+        utc: bool,
+    },
+
+    /// Show install and update receipts for a server
+    Receipts {
+        /// Server name
+        server: String,
+
+        /// Print receipts as JSON
+        #[arg(long)]
+        json: bool,
+
+        /// Render timestamps in UTC instead of local time
+        #[arg(long)]
+        utc: bool,
+    },
+
+    /// Show a server's lifecycle timeline from the audit log
+    History {
+        /// Server name
+        server: String,
+
+        /// Show events since duration (e.g. 12h, 7d)
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Print the timeline as JSON
+        #[arg(long)]
+        json: bool,
+
+        /// Render timestamps in UTC instead of local time
+        #[arg(long)]
+        utc: bool,
     },
 
     /// Summarize audit usage and estimated runtime cost
@@ -221,12 +547,54 @@ pub enum Commands {
         /// Print analytics summary as JSON
         #[arg(long)]
         json: bool,
+
+        /// Render timestamps in UTC instead of local time
+        #[arg(long)]
+        utc: bool,
+    },
+
+    /// Show per-tool usage from proxied requests: top tools, latency, error rates
+    Top {
+        /// Server name (omit for all)
+        server: Option<String>,
+
+        /// Only count requests since duration (e.g. 1h, 24h)
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Number of tools/servers to display
+        #[arg(long, default_value = "10")]
+        limit: u32,
+
+        /// Print the usage report as JSON
+        #[arg(long)]
+        json: bool,
     },
 
     /// Link Berth to an AI client (e.g. claude-desktop, cursor, continue, vscode)
     Link {
         /// Client name
         client: String,
+
+        /// Protocol compat mode for this client (auto, off, or a revision like 2024-11-05)
+        #[arg(long)]
+        compat: Option<String>,
+
+        /// Stdio framing the proxy uses towards this client (auto, line, or content-length)
+        #[arg(long)]
+        framing: Option<String>,
+
+        /// Refuse to link servers whose manifest does not list this client
+        #[arg(long)]
+        strict: bool,
+
+        /// Config override for this client only (an empty value removes it)
+        #[arg(long = "set", value_name = "SERVER.KEY=VALUE")]
+        overrides: Vec<String>,
+
+        /// Restore the client config as it was before the last link or unlink
+        #[arg(long, conflicts_with_all = ["compat", "framing", "strict", "overrides"])]
+        undo: bool,
     },
 
     /// Unlink Berth from an AI client
@@ -239,6 +607,28 @@ pub enum Commands {
     Proxy {
         /// Server name
         server: String,
+
+        /// Linked client name whose compat settings apply
+        #[arg(long)]
+        client: Option<String>,
+
+        /// Inherit the full parent environment instead of only declared/granted variables
+        #[arg(long)]
+        inherit_env: bool,
+    },
+
+    /// Run a server for a client that spawns it directly, applying Berth policy and audit
+    Wrap {
+        /// Server name
+        server: String,
+
+        /// Linked client name whose compat settings apply
+        #[arg(long)]
+        client: Option<String>,
+
+        /// Inherit the full parent environment instead of only declared/granted variables
+        #[arg(long)]
+        inherit_env: bool,
     },
 
     /// Publish an MCP server manifest to the registry review queue
@@ -249,34 +639,295 @@ pub enum Commands {
         /// Validate only; do not submit
         #[arg(long)]
         dry_run: bool,
+
+        /// Show the review status of a submission by its ID instead of publishing
+        #[arg(long, value_name = "ID", conflicts_with_all = ["manifest", "dry_run"])]
+        status: Option<String>,
     },
 
     /// Serve local registry REST API endpoints
     RegistryApi {
-        /// Bind address (host:port)
-        #[arg(long, default_value = "127.0.0.1:8787")]
-        bind: String,
+        /// Bind address (host:port) [default: $BERTH_REGISTRY_API_BIND or 127.0.0.1:8787]
+        #[arg(long)]
+        bind: Option<String>,
 
         /// Exit after serving this many requests (for tests/automation)
         #[arg(long)]
         max_requests: Option<u32>,
     },
 
+    /// Scaffold a self-hosted registry directory served by `registry-api`
+    RegistryInit {
+        /// Directory to create the registry in
+        dir: String,
+    },
+
+    /// Mirror the registry index and server artifacts for offline installs
+    RegistryMirror {
+        /// Destination directory for the mirror
+        #[arg(long)]
+        dest: String,
+    },
+
+    /// Record a reviewed trust-level change for a server in the local registry
+    RegistryPromote {
+        /// Server name
+        server: String,
+
+        /// New trust level (untrusted, community, verified, or official)
+        #[arg(long)]
+        to: String,
+
+        /// Identity of the reviewer making the change
+        #[arg(long)]
+        reviewer: String,
+
+        /// Why the trust level changes
+        #[arg(long)]
+        reason: String,
+    },
+
+    /// Validate the embedded seed registry and diff it against another index
+    RegistrySeedCheck {
+        /// Flag entries whose lastVerified date is older than this many days
+        #[arg(long, default_value_t = 180)]
+        max_age_days: u32,
+
+        /// Also check that repository and npm package URLs resolve (network)
+        #[arg(long)]
+        online: bool,
+
+        /// Index file path or URL to diff the seed dataset against
+        #[arg(long)]
+        diff: Option<String>,
+    },
+
+    /// Run a fake MCP server on stdio for testing against Berth (hidden)
+    #[command(hide = true, name = "test-server")]
+    TestServer {
+        /// Server name reported in `serverInfo`
+        #[arg(long)]
+        name: Option<String>,
+
+        /// Tool to list and answer (repeatable) [default: echo]
+        #[arg(long = "tool")]
+        tools: Vec<String>,
+
+        /// Wait this long before answering anything
+        #[arg(long, default_value_t = 0)]
+        startup_delay_ms: u64,
+
+        /// Exit after answering this many tool calls
+        #[arg(long)]
+        crash_after: Option<u32>,
+
+        /// Exit code used by --crash-after
+        #[arg(long, default_value_t = 1, allow_negative_numbers = true)]
+        exit_code: i32,
+
+        /// Answer tool calls with this many bytes of text instead of echoing
+        #[arg(long, default_value_t = 0)]
+        output_bytes: usize,
+
+        /// Break tool call answers: malformed-json, wrong-id, missing-version, or stdout-noise
+        #[arg(long)]
+        violate: Option<String>,
+    },
+
     /// Internal process supervisor loop (hidden).
     #[command(hide = true, name = "__supervise")]
     Supervise {
         /// Server name
         server: String,
     },
+
+    /// Internal stdio relay to a socket or named-pipe server (hidden).
+    #[command(hide = true, name = "__socket-bridge")]
+    SocketBridge {
+        /// `unix-socket` or `named-pipe`
+        transport: String,
+
+        /// Socket path or pipe name
+        endpoint: String,
+
+        /// Server command to start when nothing listens on the endpoint
+        #[arg(last = true)]
+        command: Vec<String>,
+    },
+
+    /// Internal load balancer in front of an HTTP server's replicas (hidden).
+    #[command(hide = true, name = "__gateway")]
+    Gateway {
+        /// Port to listen on
+        port: u16,
+
+        /// Replica ports to balance across
+        #[arg(required = true)]
+        upstreams: Vec<u16>,
+    },
+
+    /// Internal copy of an adopted process's output into its log (hidden).
+    #[command(hide = true, name = "__relay-output")]
+    RelayOutput {
+        /// Server name
+        server: String,
+
+        /// Adopted process
+        pid: u32,
+
+        /// File the process writes its output to
+        source: String,
+    },
+}
+
+impl Commands {
+    /// Returns whether the command writes Berth config or state, as opposed
+    /// to informational commands that keep working on a read-only home.
+    fn writes_state(&self) -> bool {
+        match self {
+            Commands::Install { dry_run, .. }
+            | Commands::Update { dry_run, .. }
+            | Commands::Start { dry_run, .. }
+            | Commands::Stop { dry_run, .. }
+            | Commands::Gc { dry_run, .. }
+            | Commands::Prune { dry_run, .. }
+            | Commands::Migrate { dry_run }
+            | Commands::MigrateLayout { dry_run } => !dry_run,
+            Commands::Uninstall { .. }
+            | Commands::Adopt { .. }
+            | Commands::RelayOutput { .. }
+            | Commands::Restart { .. }
+            | Commands::Link { .. }
+            | Commands::Unlink { .. } => true,
+            Commands::Daemon { action } => action.as_deref() != Some("status"),
+            Commands::Warnings { action, .. } => {
+                matches!(action.as_deref(), Some("snooze" | "unsnooze"))
+            }
+            Commands::Plugins {
+                enable,
+                disable,
+                timeout,
+                test,
+                ..
+            } => *enable || *disable || timeout.is_some() || *test,
+            _ => false,
+        }
+    }
+
+    /// Returns whether observer mode refuses the command: anything that
+    /// writes Berth config or state, launches or signals servers, changes
+    /// permissions or policy, or edits client configs.
+    pub fn mutates(&self) -> bool {
+        if self.writes_state() {
+            return true;
+        }
+        match self {
+            Commands::Config {
+                server,
+                set,
+                interactive,
+                ..
+            } => set.is_some() || *interactive || server == "import" || server == "migrate-secrets",
+            Commands::Permissions {
+                grant,
+                revoke,
+                reset,
+                ..
+            } => grant.is_some() || revoke.is_some() || *reset,
+            Commands::Policy { set, init, .. } => set.is_some() || *init,
+            Commands::Trust { action, .. } => {
+                matches!(action.as_deref(), Some("grant" | "revoke"))
+            }
+            Commands::ImportGithub { dry_run, .. } => !dry_run,
+            Commands::Publish {
+                dry_run, status, ..
+            } => !dry_run && status.is_none(),
+            Commands::Auth { .. }
+            | Commands::Proxy { .. }
+            | Commands::Wrap { .. }
+            | Commands::RegistryApi { .. }
+            | Commands::RegistryInit { .. }
+            | Commands::RegistryMirror { .. }
+            | Commands::RegistryPromote { .. }
+            | Commands::Supervise { .. }
+            | Commands::SocketBridge { .. }
+            | Commands::Gateway { .. } => true,
+            _ => false,
+        }
+    }
+
+    /// Returns whether the command runs server commands or plugins defined in
+    /// the active Berth home, which workspace trust must allow first.
+    fn runs_home_commands(&self) -> bool {
+        match self {
+            Commands::Start { dry_run, .. } => !dry_run,
+            Commands::Plugins { test, .. } => *test,
+            Commands::Daemon { action } => {
+                matches!(action.as_deref(), None | Some("run" | "start"))
+            }
+            Commands::Restart { .. }
+            | Commands::Proxy { .. }
+            | Commands::Wrap { .. }
+            | Commands::Supervise { .. } => true,
+            _ => false,
+        }
+    }
 }
 
 /// Dispatches a parsed CLI command to its command module.
 pub fn execute(command: Commands) {
+    if command.writes_state() {
+        if let Err(problem) = paths::check_storage() {
+            eprintln!("{} Cannot write Berth files: {problem}.", "✗".red().bold());
+            eprintln!(
+                "  Informational commands such as {}, {}, {} and {} still work.",
+                "berth status".bold(),
+                "berth list".bold(),
+                "berth search".bold(),
+                "berth logs".bold()
+            );
+            process::exit(1);
+        }
+    }
+    if !matches!(
+        command,
+        Commands::Migrate { .. } | Commands::MigrateLayout { .. } | Commands::TestServer { .. }
+    ) {
+        migrate::upgrade_on_first_run(command.mutates());
+    }
+    if command.runs_home_commands() {
+        workspace_trust::ensure();
+    }
     match command {
-        Commands::Search { query } => search::execute(&query),
-        Commands::Info { server } => info::execute(&server),
-        Commands::List => list::execute(),
-        Commands::Install { server } => install::execute(&server),
+        Commands::Search {
+            query,
+            json,
+            limit,
+            cursor,
+        } => search::execute(&query, json, limit, cursor.as_deref()),
+        Commands::Info {
+            server,
+            json,
+            versions,
+        } => info::execute(&server, json, versions),
+        Commands::List { json, sizes } => list::execute(json, sizes),
+        Commands::Install {
+            server,
+            dry_run,
+            mirror,
+            no_hooks,
+            replace,
+            alias,
+            progress,
+        } => install::execute(
+            &server,
+            dry_run,
+            mirror.as_deref(),
+            no_hooks,
+            replace,
+            alias.as_deref(),
+            progress,
+        ),
         Commands::ImportGithub {
             repo,
             git_ref,
@@ -284,7 +935,12 @@ pub fn execute(command: Commands) {
             dry_run,
         } => import_github::execute(&repo, &git_ref, &manifest_path, dry_run),
         Commands::Uninstall { server } => uninstall::execute(&server),
-        Commands::Update { server, all } => update::execute(server.as_deref(), all),
+        Commands::Update {
+            server,
+            all,
+            dry_run,
+            progress,
+        } => update::execute(server.as_deref(), all, dry_run, progress),
         Commands::Config {
             server,
             path,
@@ -300,54 +956,279 @@ pub fn execute(command: Commands) {
             env,
             interactive,
         ),
-        Commands::Start { server } => start::execute(server.as_deref()),
-        Commands::Stop { server } => stop::execute(server.as_deref()),
-        Commands::Restart { server } => restart::execute(&server),
-        Commands::Status => status::execute(),
-        Commands::Logs { server, tail } => logs::execute(&server, tail),
+        Commands::Auth { server, no_browser } => auth::execute(&server, no_browser),
+        Commands::Start {
+            server,
+            dry_run,
+            inherit_env,
+            progress,
+        } => start::execute(server.as_deref(), dry_run, inherit_env, progress),
+        Commands::Stop { server, dry_run } => stop::execute(server.as_deref(), dry_run),
+        Commands::Adopt { server, pid } => adopt::execute(&server, pid),
+        Commands::Restart {
+            server,
+            inherit_env,
+        } => restart::execute(&server, inherit_env),
+        Commands::Daemon { action } => daemon::execute(action.as_deref()),
+        Commands::Status {
+            jobs,
+            json,
+            summary_file,
+        } => {
+            if summary_file {
+                status::print_summary_file()
+            } else {
+                status::execute(jobs, json)
+            }
+        }
+        Commands::Logs {
+            server,
+            tail,
+            follow,
+            raw,
+        } => logs::execute(&server, tail, follow, raw),
+        Commands::Gc { archive, dry_run } => gc::execute(archive, dry_run),
+        Commands::Prune {
+            older_than,
+            dry_run,
+        } => prune::execute(&older_than, dry_run),
+        Commands::Doctor { json } => doctor::execute(json),
+        Commands::Migrate { dry_run } => migrate::execute(dry_run),
+        Commands::MigrateLayout { dry_run } => migrate_layout::execute(dry_run),
+        Commands::ShowCommand { server } => show_command::execute(&server),
         Commands::Permissions {
             server,
             grant,
             revoke,
             reset,
             export_json,
+            usage,
         } => permissions::execute(
             &server,
             grant.as_deref(),
             revoke.as_deref(),
             reset,
             export_json,
+            usage,
         ),
         Commands::Policy {
             server,
+            dir,
             set,
             init,
             json,
-        } => policy::execute(server.as_deref(), set.as_deref(), init, json),
+        } => policy::execute(
+            server.as_deref(),
+            dir.as_deref(),
+            set.as_deref(),
+            init,
+            json,
+        ),
+        Commands::Trust { action, path, utc } => {
+            trust::execute(action.as_deref(), path.as_deref(), utc)
+        }
+        Commands::Warnings {
+            action,
+            id,
+            duration,
+        } => warnings::execute(action.as_deref(), id.as_deref(), duration.as_deref()),
         Commands::Audit {
             server,
             since,
             action,
+            pid,
             json,
             export,
-        } => audit::execute(
-            server.as_deref(),
-            since.as_deref(),
-            action.as_deref(),
+            utc,
+            verify,
+        } => {
+            if verify {
+                audit::verify();
+            } else {
+                audit::execute(
+                    server.as_deref(),
+                    since.as_deref(),
+                    action.as_deref(),
+                    pid,
+                    json,
+                    export.as_deref(),
+                    utc,
+                )
+            }
+        }
+        Commands::Plugins {
+            name,
+            enable,
+            disable,
+            timeout,
+            test,
+        } => plugins::execute(name.as_deref(), enable, disable, timeout, test),
+        Commands::Explain {
+            topic,
+            entry,
+            context,
+            utc,
+        } => explain::execute(&topic, &entry, context, utc),
+        Commands::Receipts { server, json, utc } => receipts::execute(&server, json, utc),
+        Commands::History {
+            server,
+            since,
             json,
-            export.as_deref(),
-        ),
+            utc,
+        } => history::execute(&server, since.as_deref(), json, utc),
         Commands::Analytics {
             server,
             since,
             top,
             json,
-        } => analytics::execute(server.as_deref(), since.as_deref(), top, json),
-        Commands::Link { client } => link::execute(&client),
+            utc,
+        } => analytics::execute(server.as_deref(), since.as_deref(), top, json, utc),
+        Commands::Top {
+            server,
+            since,
+            limit,
+            json,
+        } => top::execute(server.as_deref(), since.as_deref(), limit, json),
+        Commands::Link {
+            client,
+            compat,
+            framing,
+            strict,
+            overrides,
+            undo,
+        } => {
+            if undo {
+                link::undo(&client)
+            } else {
+                link::execute(
+                    &client,
+                    compat.as_deref(),
+                    framing.as_deref(),
+                    &overrides,
+                    strict,
+                )
+            }
+        }
         Commands::Unlink { client } => unlink::execute(&client),
-        Commands::Proxy { server } => proxy::execute(&server),
-        Commands::Publish { manifest, dry_run } => publish::execute(manifest.as_deref(), dry_run),
-        Commands::RegistryApi { bind, max_requests } => registry_api::execute(&bind, max_requests),
+        Commands::Proxy {
+            server,
+            client,
+            inherit_env,
+        } => proxy::execute(&server, client.as_deref(), inherit_env),
+        Commands::Wrap {
+            server,
+            client,
+            inherit_env,
+        } => wrap::execute(&server, client.as_deref(), inherit_env),
+        Commands::Publish {
+            manifest,
+            dry_run,
+            status,
+        } => match status {
+            Some(id) => publish::status(&id),
+            None => publish::execute(manifest.as_deref(), dry_run),
+        },
+        Commands::RegistryApi { bind, max_requests } => {
+            registry_api::execute(bind.as_deref(), max_requests)
+        }
+        Commands::RegistryInit { dir } => registry_init::execute(&dir),
+        Commands::RegistryMirror { dest } => registry_mirror::execute(&dest),
+        Commands::RegistryPromote {
+            server,
+            to,
+            reviewer,
+            reason,
+        } => registry_promote::execute(&server, &to, &reviewer, &reason),
+        Commands::RegistrySeedCheck {
+            max_age_days,
+            online,
+            diff,
+        } => registry_seed_check::execute(max_age_days, online, diff.as_deref()),
+        Commands::TestServer {
+            name,
+            tools,
+            startup_delay_ms,
+            crash_after,
+            exit_code,
+            output_bytes,
+            violate,
+        } => test_server::execute(
+            name.as_deref(),
+            &tools,
+            startup_delay_ms,
+            crash_after,
+            exit_code,
+            output_bytes,
+            violate.as_deref(),
+        ),
         Commands::Supervise { server } => supervise::execute(&server),
+        Commands::SocketBridge {
+            transport,
+            endpoint,
+            command,
+        } => socket_bridge::execute(&transport, &endpoint, &command),
+        Commands::Gateway { port, upstreams } => gateway::execute(port, &upstreams),
+        Commands::RelayOutput {
+            server,
+            pid,
+            source,
+        } => relay_output::execute(&server, pid, &source),
+    }
+}
+
+/// Loads the configured registry, exiting when a remote index fails its
+/// signature or pinned-key check instead of quietly falling back to the seed.
+pub(crate) fn load_registry() -> Registry {
+    let registry = match Registry::load() {
+        Ok(registry) => registry,
+        Err(msg) => {
+            eprintln!("{} {}", "✗".red().bold(), msg);
+            process::exit(1);
+        }
+    };
+    if let IndexTrust::NewlyPinned { key, previous } = registry.trust() {
+        match previous {
+            Some(previous) => eprintln!(
+                "{} Replaced the pinned signing key {previous} of registry {} with {key}.",
+                "!".yellow().bold(),
+                registry.origin()
+            ),
+            None => eprintln!(
+                "{} Pinned signing key {key} for registry {} (trust on first use).",
+                "!".yellow().bold(),
+                registry.origin()
+            ),
+        }
+    }
+    warn_about_old_cache(&registry);
+    registry
+}
+
+/// Age in days from which a registry index read from the cache is worth a warning.
+const REGISTRY_CACHE_WARN_DAYS: u64 = 30;
+
+/// Warns when the index came from a cache that has not been refreshed for
+/// [`REGISTRY_CACHE_WARN_DAYS`].
+fn warn_about_old_cache(registry: &Registry) {
+    if !registry.origin().starts_with("cache ") {
+        return;
+    }
+    let Some(cache) = berth_registry::cache_path() else {
+        return;
+    };
+    let age_days = std::fs::metadata(&cache)
+        .and_then(|meta| meta.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+        .map_or(0, |age| age.as_secs() / 86_400);
+    if age_days >= REGISTRY_CACHE_WARN_DAYS {
+        crate::warnings::emit(
+            crate::warnings::REGISTRY_CACHE_AGE,
+            None,
+            &format!(
+                "Registry index cache {} is {age_days} days old; search and install may miss newer servers.",
+                cache.display()
+            ),
+        );
     }
 }
@@ -10,11 +10,14 @@ use std::fs;
 use std::path::Path;
 use std::process;
 
+use crate::egress_monitor::{parse_egress_monitor, read_usage, KEY_EGRESS_MONITOR};
 use crate::paths;
 use crate::permission_filter::{
-    clear_permission_overrides, effective_permissions, load_permission_overrides,
-    validate_permission_syntax, write_permission_overrides,
+    audit_permission_change, clear_permission_overrides, effective_permissions,
+    load_permission_overrides, validate_permission_syntax, write_permission_overrides,
+    PermissionOverrides,
 };
+use crate::time_format::TimestampFormatter;
 
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -48,17 +51,22 @@ pub fn execute(
     revoke: Option<&str>,
     reset: bool,
     export_json: bool,
+    usage: bool,
 ) {
-    let action_count =
-        grant.is_some() as u8 + revoke.is_some() as u8 + reset as u8 + export_json as u8;
+    let action_count = grant.is_some() as u8
+        + revoke.is_some() as u8
+        + reset as u8
+        + export_json as u8
+        + usage as u8;
     if action_count > 1 {
         eprintln!(
-            "{} Use only one of {}, {}, {}, or {}.",
+            "{} Use only one of {}, {}, {}, {}, or {}.",
             "✗".red().bold(),
             "--grant".bold(),
             "--revoke".bold(),
             "--reset".bold(),
-            "--export".bold()
+            "--export".bold(),
+            "--usage".bold()
         );
         process::exit(1);
     }
@@ -99,12 +107,20 @@ pub fn execute(
                 process::exit(1);
             }
         };
+        let before = overrides.clone();
         upsert_permission(&mut overrides.grant, perm);
         remove_permission(&mut overrides.revoke, perm);
         if let Err(msg) = write_permission_overrides(server, &overrides) {
             eprintln!("{} {}", "✗".red().bold(), msg);
             process::exit(1);
         }
+        audit_permission_change(
+            server,
+            &["grant", perm],
+            &installed.permissions,
+            &before,
+            &overrides,
+        );
         println!(
             "{} Granted override {} for {}.",
             "✓".green().bold(),
@@ -126,12 +142,20 @@ pub fn execute(
                 process::exit(1);
             }
         };
+        let before = overrides.clone();
         upsert_permission(&mut overrides.revoke, perm);
         remove_permission(&mut overrides.grant, perm);
         if let Err(msg) = write_permission_overrides(server, &overrides) {
             eprintln!("{} {}", "✗".red().bold(), msg);
             process::exit(1);
         }
+        audit_permission_change(
+            server,
+            &["revoke", perm],
+            &installed.permissions,
+            &before,
+            &overrides,
+        );
         println!(
             "{} Revoked override {} for {}.",
             "✓".green().bold(),
@@ -142,10 +166,24 @@ pub fn execute(
     }
 
     if reset {
+        let before = match load_permission_overrides(server) {
+            Ok(o) => o,
+            Err(msg) => {
+                eprintln!("{} {}", "✗".red().bold(), msg);
+                process::exit(1);
+            }
+        };
         if let Err(msg) = clear_permission_overrides(server) {
             eprintln!("{} {}", "✗".red().bold(), msg);
             process::exit(1);
         }
+        audit_permission_change(
+            server,
+            &["reset"],
+            &installed.permissions,
+            &before,
+            &PermissionOverrides::default(),
+        );
         println!(
             "{} Cleared permission overrides for {}.",
             "✓".green().bold(),
@@ -169,6 +207,11 @@ pub fn execute(
         effective_permissions("filesystem", &installed.permissions.filesystem, &overrides);
     let effective_exec = effective_permissions("exec", &installed.permissions.exec, &overrides);
 
+    if usage {
+        print_usage(server, &installed, &effective_network);
+        return;
+    }
+
     if export_json {
         let export = PermissionExport {
             server: server.to_string(),
@@ -255,6 +298,76 @@ pub fn execute(
     );
 }
 
+/// Prints the destinations recorded by the egress monitor.
+fn print_usage(server: &str, installed: &InstalledServer, effective_network: &[String]) {
+    let Some(log) = paths::egress_log_path(server) else {
+        eprintln!("{} Could not determine home directory.", "✗".red().bold());
+        process::exit(1);
+    };
+    let destinations = match read_usage(&log, effective_network) {
+        Ok(d) => d,
+        Err(msg) => {
+            eprintln!("{} {}", "✗".red().bold(), msg);
+            process::exit(1);
+        }
+    };
+    let monitoring = parse_egress_monitor(&installed.config).unwrap_or(false);
+    if destinations.is_empty() {
+        println!("No network usage recorded for {}.", server.cyan());
+        if !monitoring {
+            println!(
+                "  Enable it with {} and restart the server.",
+                format!("berth config {server} --set {KEY_EGRESS_MONITOR}=log").bold()
+            );
+        }
+        return;
+    }
+
+    println!(
+        "{} Network usage for {}:\n",
+        "✓".green().bold(),
+        server.cyan()
+    );
+    let formatter = TimestampFormatter::new(false);
+    let width = destinations
+        .iter()
+        .map(|d| d.permission().len())
+        .max()
+        .unwrap_or(0);
+    for dest in &destinations {
+        let status = if dest.blocked {
+            format!("{:<12}", "blocked").red().bold()
+        } else if dest.declared {
+            format!("{:<12}", "declared").green()
+        } else {
+            format!("{:<12}", "undeclared").yellow().bold()
+        };
+        println!(
+            "  {:<width$}  {}  {}",
+            dest.permission(),
+            status,
+            format!("last seen {}", formatter.format(dest.last_seen_ms)).dimmed()
+        );
+    }
+
+    let undeclared = destinations.iter().filter(|d| !d.declared).count();
+    if undeclared > 0 {
+        println!();
+        println!(
+            "{} {} destination(s) not covered by network permissions. Review the server, or allow one with {}.",
+            "!".yellow().bold(),
+            undeclared,
+            format!("berth permissions {server} --grant network:<host>:<port>").bold()
+        );
+    }
+    if !monitoring {
+        println!(
+            "{}",
+            format!("Monitoring is off; set {KEY_EGRESS_MONITOR}=log to keep recording.").dimmed()
+        );
+    }
+}
+
 /// Reads and parses an installed server config file.
 fn read_installed(path: &Path) -> Result<InstalledServer, String> {
     let content = fs::read_to_string(path).map_err(|e| format!("Failed to read config: {e}"))?;
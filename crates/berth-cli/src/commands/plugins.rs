@@ -0,0 +1,148 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Command handler for `berth plugins`.
+
+use colored::Colorize;
+use std::process;
+
+use berth_runtime::AuditRecord;
+
+use crate::paths::{self, BerthLayout};
+use crate::plugins::{self, Plugin};
+use crate::time_format::now_epoch_ms;
+
+/// Executes the `berth plugins` command.
+pub fn execute(name: Option<&str>, enable: bool, disable: bool, timeout: Option<u64>, test: bool) {
+    let Some(layout) = paths::berth_layout() else {
+        eprintln!("{} Could not determine home directory.", "✗".red().bold());
+        process::exit(1);
+    };
+    let Some(name) = name else {
+        if enable || disable || timeout.is_some() || test {
+            eprintln!(
+                "{} Name the plugin, e.g. {}.",
+                "✗".red().bold(),
+                "berth plugins <name> --enable".bold()
+            );
+            process::exit(1);
+        }
+        list(&layout);
+        return;
+    };
+
+    let enabled = if enable {
+        Some(true)
+    } else if disable {
+        Some(false)
+    } else {
+        None
+    };
+    let plugin = if enabled.is_some() || timeout.is_some() {
+        match plugins::configure(&layout, name, enabled, timeout) {
+            Ok(plugin) => {
+                println!(
+                    "{} Plugin {} is {} (timeout {}s).",
+                    "✓".green().bold(),
+                    plugin.name.cyan(),
+                    state_label(&plugin),
+                    plugin.timeout.as_secs()
+                );
+                plugin
+            }
+            Err(msg) => {
+                eprintln!("{} {}", "✗".red().bold(), msg);
+                process::exit(1);
+            }
+        }
+    } else {
+        match plugins::find(&layout, name) {
+            Ok(plugin) => plugin,
+            Err(msg) => {
+                eprintln!("{} {}", "✗".red().bold(), msg);
+                process::exit(1);
+            }
+        }
+    };
+
+    if test {
+        send_test_event(&layout, &plugin);
+    } else if enabled.is_none() && timeout.is_none() {
+        print_plugin(&layout, &plugin);
+    }
+}
+
+fn list(layout: &BerthLayout) {
+    let dir = plugins::plugins_dir(layout);
+    let found = match plugins::discover(layout) {
+        Ok(found) => found,
+        Err(msg) => {
+            eprintln!("{} {}", "✗".red().bold(), msg);
+            process::exit(1);
+        }
+    };
+    if found.is_empty() {
+        println!("No plugins in {}.", dir.display());
+        println!(
+            "  Add an executable there, then run {}.",
+            "berth plugins <name> --enable".bold()
+        );
+        return;
+    }
+    println!("Plugins in {}:", dir.display());
+    let width = found.iter().map(|p| p.name.len()).max().unwrap_or(0);
+    for plugin in &found {
+        println!(
+            "  {:<width$}  {:<8}  timeout {}s",
+            plugin.name.cyan(),
+            state_label(plugin),
+            plugin.timeout.as_secs()
+        );
+    }
+}
+
+fn print_plugin(layout: &BerthLayout, plugin: &Plugin) {
+    println!("{}", plugin.name.cyan().bold());
+    println!("  Path:    {}", plugin.path.display());
+    println!("  State:   {}", state_label(plugin));
+    println!("  Timeout: {}s", plugin.timeout.as_secs());
+    println!(
+        "  Log:     {}",
+        plugins::log_path(layout, &plugin.name).display()
+    );
+}
+
+/// Runs the plugin once in the foreground with a `test` event.
+fn send_test_event(layout: &BerthLayout, plugin: &Plugin) {
+    let now = now_epoch_ms();
+    let event = AuditRecord {
+        timestamp_epoch_ms: Some(now),
+        timestamp_epoch_secs: now / 1_000,
+        server: "berth".to_string(),
+        action: "plugin-test".to_string(),
+        ..AuditRecord::default()
+    };
+    match plugins::deliver(layout, plugin, &plugins::envelope("test", &event)) {
+        Ok(()) => println!(
+            "{} Plugin {} accepted a test event.",
+            "✓".green().bold(),
+            plugin.name.cyan()
+        ),
+        Err(msg) => {
+            eprintln!("{} {}", "✗".red().bold(), msg);
+            eprintln!(
+                "  Output: {}",
+                plugins::log_path(layout, &plugin.name).display()
+            );
+            process::exit(1);
+        }
+    }
+}
+
+fn state_label(plugin: &Plugin) -> colored::ColoredString {
+    if plugin.enabled {
+        "enabled".green()
+    } else {
+        "disabled".dimmed()
+    }
+}
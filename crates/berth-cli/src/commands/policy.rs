@@ -4,6 +4,7 @@
 //! Command handler for `berth policy`.
 
 use berth_registry::config::InstalledServer;
+use berth_registry::Registry;
 use colored::Colorize;
 use std::fs;
 use std::path::Path;
@@ -11,10 +12,25 @@ use std::process;
 
 use crate::paths;
 use crate::permission_filter::load_permission_overrides;
-use crate::policy_engine::{enforce_global_policy, load_global_policy, GlobalPolicy};
+use crate::policy_engine::{
+    enforce_global_policy, load_global_policy, validate_scoped_policies, GlobalPolicy,
+    PermissionPolicy,
+};
+use crate::policy_scenarios::{run_scenarios, Expectation, ScenarioResult};
 
 /// Executes the `berth policy` command.
-pub fn execute(server: Option<&str>, set: Option<&str>, init: bool, json: bool) {
+pub fn execute(server: Option<&str>, dir: Option<&str>, set: Option<&str>, init: bool, json: bool) {
+    if let Some(dir) = dir {
+        if server != Some("test") || set.is_some() || init {
+            eprintln!(
+                "{} Unexpected argument `{dir}`. Use `berth policy test <dir>` to run policy scenarios.",
+                "✗".red().bold()
+            );
+            process::exit(1);
+        }
+        run_policy_tests(Path::new(dir), json);
+        return;
+    }
     if server.is_some() && (set.is_some() || init) {
         eprintln!(
             "{} `berth policy <server>` cannot be combined with `--set` or `--init`.",
@@ -88,8 +104,13 @@ pub fn execute(server: Option<&str>, set: Option<&str>, init: bool, json: bool)
             }
         };
 
-        let validation =
-            enforce_global_policy(server_name, &installed.permissions, &overrides, &policy);
+        let validation = enforce_global_policy(
+            server_name,
+            &installed,
+            &overrides,
+            &policy,
+            &Registry::from_seed(),
+        );
         if json {
             let payload = match &validation {
                 Ok(()) => serde_json::json!({
@@ -152,6 +173,71 @@ pub fn execute(server: Option<&str>, set: Option<&str>, init: bool, json: bool)
     print_policy(&policy, &policy_path);
 }
 
+/// Runs `berth policy test <dir>` and exits non-zero when any scenario fails.
+fn run_policy_tests(dir: &Path, json: bool) {
+    let results = match run_scenarios(dir) {
+        Ok(results) => results,
+        Err(msg) => {
+            eprintln!("{} {}", "✗".red().bold(), msg);
+            process::exit(1);
+        }
+    };
+    let failed = results.iter().filter(|r| !r.passed()).count();
+
+    if json {
+        let payload = serde_json::json!({
+            "passed": results.len() - failed,
+            "failed": failed,
+            "scenarios": results,
+        });
+        match serde_json::to_string_pretty(&payload) {
+            Ok(rendered) => println!("{rendered}"),
+            Err(e) => {
+                eprintln!(
+                    "{} Failed to serialize policy test JSON: {}",
+                    "✗".red().bold(),
+                    e
+                );
+                process::exit(1);
+            }
+        }
+    } else {
+        for result in &results {
+            print_scenario_result(result);
+        }
+        println!();
+        let summary = format!("{} passed, {} failed", results.len() - failed, failed);
+        if failed == 0 {
+            println!("{} Policy scenarios: {summary}.", "✓".green().bold());
+        } else {
+            eprintln!("{} Policy scenarios: {summary}.", "✗".red().bold());
+        }
+    }
+    if failed > 0 {
+        process::exit(1);
+    }
+}
+
+fn print_scenario_result(result: &ScenarioResult) {
+    let expected = match result.expected {
+        Some(Expectation::Allow) => "allow",
+        Some(Expectation::Deny) => "deny",
+        None => "?",
+    };
+    let label = format!(
+        "{} {}",
+        result.name.cyan(),
+        format!("(expect {expected})").dimmed()
+    );
+    match &result.failure {
+        None => println!("  {} {label}", "✓".green().bold()),
+        Some(failure) => {
+            println!("  {} {label}", "✗".red().bold());
+            println!("      {failure}");
+        }
+    }
+}
+
 fn initialize_policy_file(path: &Path) -> Result<(), String> {
     if path.exists() {
         return Ok(());
@@ -183,6 +269,52 @@ fn parse_bool(value: &str) -> Result<bool, String> {
     }
 }
 
+fn parse_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(ToOwned::to_owned)
+        .collect()
+}
+
+/// Sets one wildcard/write guard by field name; returns false for unknown fields.
+fn set_guard(guards: &mut PermissionPolicy, field: &str, value: &str) -> Result<bool, String> {
+    let slot = match field {
+        "deny_network_wildcard" => &mut guards.deny_network_wildcard,
+        "deny_env_wildcard" => &mut guards.deny_env_wildcard,
+        "deny_filesystem_write" => &mut guards.deny_filesystem_write,
+        "deny_exec_wildcard" => &mut guards.deny_exec_wildcard,
+        _ => return Ok(false),
+    };
+    *slot = parse_bool(value)?;
+    Ok(true)
+}
+
+/// Updates one field of a named scoped policy (`policy.<name>.<field>`).
+fn apply_scoped_set(policy: &mut GlobalPolicy, path: &str, value: &str) -> Result<(), String> {
+    let (name, field) = path
+        .rsplit_once('.')
+        .filter(|(name, field)| !name.is_empty() && !field.is_empty())
+        .ok_or("Invalid scoped policy key. Use policy.<name>.<field>=value.")?;
+    let scope = policy.policy.entry(name.to_string()).or_default();
+    match field {
+        "categories" => scope.categories = parse_list(value),
+        "tags" => scope.tags = parse_list(value),
+        "permissions" => scope.permissions = parse_list(value),
+        "require_trust" => scope.require_trust = parse_list(value),
+        "deny" => scope.deny = parse_bool(value)?,
+        _ => {
+            if !set_guard(&mut scope.guards, field, value)? {
+                return Err(format!(
+                    "Unknown scoped policy field `{field}`. Supported: categories, tags, permissions, require_trust, deny, deny_network_wildcard, deny_env_wildcard, deny_filesystem_write, deny_exec_wildcard."
+                ));
+            }
+        }
+    }
+    validate_scoped_policies(policy)
+}
+
 fn apply_policy_set(policy: &mut GlobalPolicy, expr: &str) -> Result<(), String> {
     let (key, value) = expr
         .split_once('=')
@@ -193,32 +325,21 @@ fn apply_policy_set(policy: &mut GlobalPolicy, expr: &str) -> Result<(), String>
         return Err("Invalid --set format. Key is required.".to_string());
     }
 
-    match key {
-        "servers.deny" => {
-            policy.servers.deny = value
-                .split(',')
-                .map(str::trim)
-                .filter(|entry| !entry.is_empty())
-                .map(ToOwned::to_owned)
-                .collect::<Vec<_>>();
-        }
-        "permissions.deny_network_wildcard" => {
-            policy.permissions.deny_network_wildcard = parse_bool(value)?;
-        }
-        "permissions.deny_env_wildcard" => {
-            policy.permissions.deny_env_wildcard = parse_bool(value)?;
-        }
-        "permissions.deny_filesystem_write" => {
-            policy.permissions.deny_filesystem_write = parse_bool(value)?;
-        }
-        "permissions.deny_exec_wildcard" => {
-            policy.permissions.deny_exec_wildcard = parse_bool(value)?;
-        }
-        _ => {
-            return Err(format!(
-                "Unknown policy key `{key}`. Supported: servers.deny, permissions.deny_network_wildcard, permissions.deny_env_wildcard, permissions.deny_filesystem_write, permissions.deny_exec_wildcard."
-            ));
-        }
+    if key == "servers.deny" {
+        policy.servers.deny = parse_list(value);
+        return Ok(());
+    }
+    if let Some(path) = key.strip_prefix("policy.") {
+        return apply_scoped_set(policy, path, value);
+    }
+    let known = match key.strip_prefix("permissions.") {
+        Some(field) => set_guard(&mut policy.permissions, field, value)?,
+        None => false,
+    };
+    if !known {
+        return Err(format!(
+            "Unknown policy key `{key}`. Supported: servers.deny, permissions.deny_network_wildcard, permissions.deny_env_wildcard, permissions.deny_filesystem_write, permissions.deny_exec_wildcard, policy.<name>.<field>."
+        ));
     }
     Ok(())
 }
@@ -274,6 +395,36 @@ fn print_policy(policy: &GlobalPolicy, path: &Path) {
         "deny_exec_wildcard:".dimmed(),
         policy.permissions.deny_exec_wildcard
     );
+
+    if policy.policy.is_empty() {
+        return;
+    }
+    println!();
+    println!("  {}", "Scoped Policies".bold());
+    for (name, scope) in &policy.policy {
+        println!("    {}", name.cyan());
+        for (label, values) in [
+            ("categories:", &scope.categories),
+            ("tags:", &scope.tags),
+            ("permissions:", &scope.permissions),
+            ("require_trust:", &scope.require_trust),
+        ] {
+            if !values.is_empty() {
+                println!("      {} {}", label.dimmed(), values.join(", "));
+            }
+        }
+        for (label, enabled) in [
+            ("deny:", scope.deny),
+            ("deny_network_wildcard:", scope.guards.deny_network_wildcard),
+            ("deny_env_wildcard:", scope.guards.deny_env_wildcard),
+            ("deny_filesystem_write:", scope.guards.deny_filesystem_write),
+            ("deny_exec_wildcard:", scope.guards.deny_exec_wildcard),
+        ] {
+            if enabled {
+                println!("      {} true", label.dimmed());
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -298,5 +449,24 @@ mod tests {
         assert!(apply_policy_set(&mut policy, "bad").is_err());
         assert!(apply_policy_set(&mut policy, "permissions.deny_exec_wildcard=maybe").is_err());
         assert!(apply_policy_set(&mut policy, "permissions.unknown=true").is_err());
+        assert!(apply_policy_set(&mut policy, "policy.exec-servers.permissions=sockets").is_err());
+        assert!(apply_policy_set(&mut policy, "policy.exec-servers.colour=red").is_err());
+    }
+
+    #[test]
+    fn apply_policy_set_updates_scoped_policies() {
+        let mut policy = GlobalPolicy::default();
+        apply_policy_set(&mut policy, "policy.exec-servers.permissions=exec").unwrap();
+        apply_policy_set(
+            &mut policy,
+            "policy.exec-servers.require_trust=official,verified",
+        )
+        .unwrap();
+        apply_policy_set(&mut policy, "policy.exec-servers.deny_exec_wildcard=true").unwrap();
+
+        let scope = &policy.policy["exec-servers"];
+        assert_eq!(scope.permissions, vec!["exec"]);
+        assert_eq!(scope.require_trust, vec!["official", "verified"]);
+        assert!(scope.guards.deny_exec_wildcard);
     }
 }
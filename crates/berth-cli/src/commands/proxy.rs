@@ -5,27 +5,187 @@
 
 use berth_registry::config::InstalledServer;
 use berth_registry::Registry;
-use berth_runtime::{ProcessSpec, RuntimeManager};
+use berth_runtime::{
+    attach_limits, exit_code, process_command, ProcessSpec, RuntimeManager, HTTP, STDIO,
+};
 use colored::Colorize;
 use std::collections::BTreeMap;
 use std::fs;
-use std::path::Path;
-use std::process::{self, Command, Stdio};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{self, Child, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
 
+use crate::builtin_runtime::is_builtin_service;
+use crate::client_compat::{
+    apply_client_overrides, client_from_info, load_client_compat, load_client_framing,
+    server_has_client_overrides, CompatMode,
+};
+use crate::egress_allowlist::{
+    apply_egress_allowlist, route_through_proxy, warn_cooperative_egress,
+};
+use crate::egress_monitor::{apply_egress_monitor, parse_egress_monitor};
+use crate::env_isolation::apply_env_isolation;
+use crate::framing::{self, Detected, Framing};
+use crate::hints::{self, Failure};
+use crate::job_policy::is_job;
+use crate::limits_policy::resource_limits;
+use crate::mcp_proxy::{self, AuditedRequest, InFlightLimit, RelayOptions, RestartPolicy};
+use crate::oauth::inject_access_token;
 use crate::paths;
 use crate::permission_filter::{
-    filter_env_map, load_permission_overrides, undeclared_network_grants,
-    validate_network_permissions, NETWORK_PERMISSION_DENIED_PREFIX,
+    effective_permissions, filter_env_map, load_permission_overrides, missing_tool_permissions,
+    undeclared_network_grants, validate_network_permissions, NETWORK_PERMISSION_DENIED_PREFIX,
+    TOOL_PERMISSION_DENIED_PREFIX,
 };
+use crate::permission_prompt::{self, parse_permission_prompt, Decision, PermissionPrompt};
 use crate::policy_engine::{
     enforce_global_policy, load_global_policy, GlobalPolicy, POLICY_DENIED_PREFIX,
 };
-use crate::sandbox_policy::{parse_sandbox_policy, KEY_SANDBOX_NETWORK};
+use crate::proxy_policy::{parse_proxy_policy, UpdateStrategy};
+use crate::replica_policy::KEY_PORT;
+use crate::runtime_policy::parse_runtime_policy;
+use crate::sandbox_policy::{
+    parse_run_as, parse_sandbox_policy, parse_seccomp, SandboxNetwork, KEY_SANDBOX_NETWORK,
+};
 use crate::sandbox_runtime::apply_sandbox_runtime;
 use crate::secrets::resolve_config_value;
+use crate::socket_transport::{apply_socket_endpoint, bridge_command};
+use crate::warnings;
+use crate::wasm_runtime::apply_wasm_runtime;
+use crate::workspace_trust;
+
+/// Bounded wait for a restarted upstream to answer the replayed `initialize`.
+const RESTART_INIT_WAIT: Duration = Duration::from_secs(10);
+
+/// How often a blue/green proxy checks its server config for a new version.
+const UPGRADE_POLL: Duration = Duration::from_secs(1);
+
+/// A configured server that passed policy checks, ready to launch behind the relay.
+pub(crate) struct Upstream {
+    pub config_path: PathBuf,
+    pub installed: InstalledServer,
+    pub spec: ProcessSpec,
+    pub runtime: RuntimeManager,
+    pub options: RelayOptions,
+}
 
 /// Executes the `berth proxy` command.
-pub fn execute(server: &str) {
+///
+/// Without `--client`, a server with per-client overrides identifies the
+/// client from the `clientInfo` of its `initialize` before launching.
+pub fn execute(server: &str, client: Option<&str>, inherit_env: bool) {
+    let (client, prelude) = match client {
+        Some(client) => (Some(client.to_string()), Vec::new()),
+        None => identify_client(server),
+    };
+    let Upstream {
+        config_path,
+        installed,
+        spec,
+        runtime,
+        mut options,
+    } = prepare(server, client.as_deref(), inherit_env, "proxy");
+    options.client_prelude = prelude;
+
+    let child = match spawn_upstream(&spec) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("{} Failed to start proxy process: {}", "✗".red().bold(), e);
+            if let Some(failure) = Failure::from_io(&e) {
+                failure.print(server, &spec.command);
+            }
+            process::exit(1);
+        }
+    };
+
+    let pid = child.id();
+    let _ = runtime.record_audit_event(
+        server,
+        "proxy-start",
+        Some(pid),
+        Some(&spec.command),
+        Some(&spec.args),
+    );
+
+    // Restarts launch whatever a blue/green upgrade last switched to.
+    let current_spec = Arc::new(Mutex::new(spec.clone()));
+    let respawn_spec = Arc::clone(&current_spec);
+    let upgrade_server = server.to_string();
+    let mut watch = VersionWatch::new(config_path, &installed.server.version);
+    let relayed = mcp_proxy::relay(
+        child,
+        move || {
+            let spec = respawn_spec
+                .lock()
+                .map_err(|_| io::Error::other("proxy spec poisoned"))?
+                .clone();
+            spawn_upstream(&spec)
+        },
+        move || {
+            let mut updated = watch.poll()?;
+            tracing::info!(version = %updated.server.version, "starting updated upstream");
+            let spawned = client
+                .as_deref()
+                .map_or(Ok(()), |client| {
+                    apply_client_overrides(client, &upgrade_server, &mut updated)
+                })
+                .and_then(|()| upgraded_spec(&upgrade_server, &updated, inherit_env))
+                .map_err(io::Error::other)
+                .and_then(|spec| {
+                    let child = spawn_upstream(&spec)?;
+                    if let Ok(mut current) = current_spec.lock() {
+                        *current = spec;
+                    }
+                    Ok(child)
+                });
+            Some(spawned)
+        },
+        options,
+        audit_requests(server),
+        tool_gate(server, &installed),
+    );
+
+    let status = match relayed {
+        Ok(s) => s,
+        Err(e) => {
+            let _ = runtime.record_audit_event(
+                server,
+                "proxy-error",
+                Some(pid),
+                Some(&spec.command),
+                Some(&spec.args),
+            );
+            eprintln!(
+                "{} Failed while proxying {}: {}",
+                "✗".red().bold(),
+                server.cyan(),
+                e
+            );
+            process::exit(1);
+        }
+    };
+
+    let _ = runtime.record_audit_event(
+        server,
+        "proxy-end",
+        Some(pid),
+        Some(&spec.command),
+        Some(&spec.args),
+    );
+
+    process::exit(exit_code(&status).unwrap_or(1));
+}
+
+/// Loads a server and applies env, secrets, permission, and sandbox policy for
+/// `berth proxy` and `berth wrap`, exiting with an error when it cannot launch.
+pub(crate) fn prepare(
+    server: &str,
+    client: Option<&str>,
+    inherit_env: bool,
+    verb: &str,
+) -> Upstream {
     let config_path = match paths::server_config_path(server) {
         Some(p) => p,
         None => {
@@ -42,7 +202,7 @@ pub fn execute(server: &str) {
         process::exit(1);
     }
 
-    let installed = match read_installed(&config_path) {
+    let mut installed = match read_installed(&config_path) {
         Ok(i) => i,
         Err(msg) => {
             eprintln!("{} {}", "✗".red().bold(), msg);
@@ -50,18 +210,69 @@ pub fn execute(server: &str) {
         }
     };
 
+    if is_job(&installed) {
+        eprintln!(
+            "{} Cannot {} {}. It is a scheduled job, not an MCP server.",
+            "✗".red().bold(),
+            verb,
+            server.cyan()
+        );
+        eprintln!(
+            "  Run {} to arm its schedule.",
+            format!("berth start {server}").bold()
+        );
+        process::exit(1);
+    }
+    if is_builtin_service(&installed) {
+        eprintln!(
+            "{} Cannot {} {}. It is a Berth service, not an MCP server.",
+            "✗".red().bold(),
+            verb,
+            server.cyan()
+        );
+        eprintln!(
+            "  Run {} to start it in the background.",
+            format!("berth start {server}").bold()
+        );
+        process::exit(1);
+    }
+    if installed.runtime.transport.trim() == HTTP {
+        eprintln!(
+            "{} Cannot {} {}. It speaks MCP over HTTP, not stdio.",
+            "✗".red().bold(),
+            verb,
+            server.cyan()
+        );
+        let url = match installed.config.get(KEY_PORT) {
+            Some(port) => format!("http://127.0.0.1:{}", port.trim())
+                .bold()
+                .to_string(),
+            None => "the address it listens on".to_string(),
+        };
+        eprintln!(
+            "  Run {} and point clients at {}.",
+            format!("berth start {server}").bold(),
+            url
+        );
+        process::exit(1);
+    }
+    if let Some(client) = client {
+        if let Err(msg) = apply_client_overrides(client, server, &mut installed) {
+            eprintln!("{} {}", "✗".red().bold(), msg);
+            process::exit(1);
+        }
+    }
+
     let missing = missing_required_keys(&installed);
     if !missing.is_empty() {
         eprintln!(
-            "{} Cannot proxy {}. Missing required config: {}",
+            "{} Cannot {} {}. Missing required config: {}",
             "✗".red().bold(),
+            verb,
             server.cyan(),
             missing.join(", ").yellow()
         );
-        eprintln!(
-            "  Run {} to configure.",
-            format!("berth config {server} --set <key>=<value>").bold()
-        );
+        Failure::MissingConfig.print(server, &installed.runtime.command);
         process::exit(1);
     }
 
@@ -73,18 +284,17 @@ pub fn execute(server: &str) {
             process::exit(1);
         }
     };
-    let (spec, undeclared_network) =
-        match build_process_spec(server, &installed, &registry, &global_policy) {
+    let (mut spec, undeclared_network) =
+        match build_process_spec(server, &installed, &registry, &global_policy, inherit_env) {
             Ok(spec) => spec,
             Err(msg) => {
-                let berth_home = match paths::berth_home() {
-                    Some(h) => h,
+                let runtime = match paths::runtime_manager() {
+                    Some(r) => r,
                     None => {
                         eprintln!("{} Could not determine home directory.", "✗".red().bold());
                         process::exit(1);
                     }
                 };
-                let runtime = RuntimeManager::new(berth_home);
                 if msg.starts_with(NETWORK_PERMISSION_DENIED_PREFIX) {
                     let _ = runtime.record_audit_event(
                         server,
@@ -103,25 +313,52 @@ pub fn execute(server: &str) {
                     );
                 }
                 eprintln!("{} {}", "✗".red().bold(), msg);
+                hints::print_for_message(&msg, server, &installed.runtime.command);
                 process::exit(1);
             }
         };
 
-    let berth_home = match paths::berth_home() {
-        Some(h) => h,
+    if let Err(msg) = inject_access_token(server, &installed, &registry, &mut spec)
+        .and_then(|()| route_through_proxy(&mut spec.env))
+    {
+        eprintln!("{} {}", "✗".red().bold(), msg);
+        process::exit(1);
+    }
+    warn_cooperative_egress(server, &spec);
+
+    let compat = match client.map(load_client_compat).transpose() {
+        Ok(mode) => mode.flatten().unwrap_or(CompatMode::Auto),
+        Err(msg) => {
+            eprintln!("{} {}", "✗".red().bold(), msg);
+            process::exit(1);
+        }
+    };
+
+    let client_framing = match client.map(load_client_framing).transpose() {
+        Ok(framing) => framing.flatten().unwrap_or(Framing::Auto),
+        Err(msg) => {
+            eprintln!("{} {}", "✗".red().bold(), msg);
+            process::exit(1);
+        }
+    };
+
+    let runtime = match paths::runtime_manager() {
+        Some(r) => r,
         None => {
             eprintln!("{} Could not determine home directory.", "✗".red().bold());
             process::exit(1);
         }
     };
-    let runtime = RuntimeManager::new(berth_home);
     if !undeclared_network.is_empty() {
-        println!(
-            "{} {} has undeclared network grant override(s): {} (log-only).",
-            "!".yellow().bold(),
-            server.cyan(),
-            undeclared_network.join(", ")
-        );
+        if warnings::due(warnings::UNDECLARED_NETWORK, Some(server)) {
+            println!(
+                "{} {} has undeclared network grant override(s): {} (log-only). {}",
+                "!".yellow().bold(),
+                server.cyan(),
+                undeclared_network.join(", "),
+                warnings::snooze_hint(warnings::UNDECLARED_NETWORK)
+            );
+        }
         let _ = runtime.record_audit_event(
             server,
             "permission-network-warning",
@@ -131,64 +368,238 @@ pub fn execute(server: &str) {
         );
     }
 
-    let mut child = match Command::new(&spec.command)
-        .args(&spec.args)
-        .envs(&spec.env)
-        .stdin(Stdio::inherit())
-        .stdout(Stdio::inherit())
-        .stderr(Stdio::inherit())
-        .spawn()
-    {
-        Ok(c) => c,
-        Err(e) => {
-            eprintln!("{} Failed to start proxy process: {}", "✗".red().bold(), e);
+    let restart = match parse_runtime_policy(&installed.config) {
+        Ok(policy) if policy.enabled => Some(RestartPolicy {
+            max_restarts: policy.max_restarts,
+            restart_on_exit_codes: policy.restart_on_exit_codes,
+            wait: RESTART_INIT_WAIT,
+        }),
+        Ok(_) => None,
+        Err(msg) => {
+            eprintln!("{} {}", "✗".red().bold(), msg);
             process::exit(1);
         }
     };
 
-    let pid = child.id();
-    let _ = runtime.record_audit_event(
-        server,
-        "proxy-start",
-        Some(pid),
-        Some(&spec.command),
-        Some(&spec.args),
-    );
-
-    let status = match child.wait() {
-        Ok(s) => s,
-        Err(e) => {
-            let _ = runtime.record_audit_event(
-                server,
-                "proxy-error",
-                Some(pid),
-                Some(&spec.command),
-                Some(&spec.args),
-            );
-            eprintln!(
-                "{} Failed while proxying {}: {}",
-                "✗".red().bold(),
-                server.cyan(),
-                e
-            );
+    let proxy_policy = match parse_proxy_policy(&installed.config) {
+        Ok(policy) => policy,
+        Err(msg) => {
+            eprintln!("{} {}", "✗".red().bold(), msg);
             process::exit(1);
         }
     };
+    let limit = proxy_policy.max_in_flight.map(|max| InFlightLimit {
+        max_in_flight: max as usize,
+        queue_timeout: Duration::from_millis(proxy_policy.queue_timeout_ms),
+    });
+    // A replacement cannot listen on the endpoint the current server holds.
+    let upgrade_wait = match proxy_policy.update_strategy {
+        UpdateStrategy::BlueGreen if installed.runtime.transport.trim() == STDIO => {
+            Some(RESTART_INIT_WAIT)
+        }
+        UpdateStrategy::BlueGreen | UpdateStrategy::Restart => None,
+    };
 
-    let _ = runtime.record_audit_event(
-        server,
-        "proxy-end",
-        Some(pid),
-        Some(&spec.command),
-        Some(&spec.args),
-    );
+    Upstream {
+        config_path,
+        installed,
+        spec,
+        runtime,
+        options: RelayOptions {
+            compat,
+            restart,
+            limit,
+            upgrade_wait,
+            client_framing,
+            server_framing: proxy_policy.framing,
+            client_prelude: Vec::new(),
+        },
+    }
+}
 
-    match status.code() {
-        Some(code) => process::exit(code),
-        None => process::exit(1),
+/// Records each audited client request against `server` in the audit log,
+/// with its target first and the argument summary after it.
+pub(crate) fn audit_requests(server: &str) -> impl Fn(&AuditedRequest, u32) + Send + 'static {
+    let runtime = paths::runtime_manager();
+    let server = server.to_string();
+    move |request, pid| {
+        let args: Vec<String> = request
+            .target
+            .iter()
+            .chain(&request.arguments)
+            .cloned()
+            .collect();
+        let Some(runtime) = &runtime else {
+            return;
+        };
+        let _ = match request.outcome {
+            Some(outcome) => runtime.record_timed_audit_event(
+                &server,
+                request.action,
+                Some(pid),
+                Some(&args),
+                outcome.duration_ms,
+                outcome.error,
+            ),
+            None => {
+                runtime.record_audit_event(&server, request.action, Some(pid), None, Some(&args))
+            }
+        };
+    }
+}
+
+/// Checks each tool call against org policy and the permissions the tool
+/// declares. Policy and overrides are reloaded per call, so
+/// `berth permissions --revoke` and policy edits apply to running sessions.
+///
+/// With `berth.permission-prompt` set, a call the permissions do not cover is
+/// put to the user first; the answer is audited as `permission-prompt`. Org
+/// policy denials are never prompted.
+pub(crate) fn tool_gate(
+    server: &str,
+    installed: &InstalledServer,
+) -> impl Fn(&str) -> Result<(), String> + Send + 'static {
+    let server = server.to_string();
+    let installed = installed.clone();
+    let runtime = paths::runtime_manager();
+    let registry = Registry::from_seed();
+    move |tool| {
+        let overrides = load_permission_overrides(&server)?;
+        let policy = load_global_policy()?;
+        enforce_global_policy(&server, &installed, &overrides, &policy, &registry)?;
+        let missing = missing_tool_permissions(&installed.permissions, tool, &overrides);
+        if missing.is_empty() {
+            return Ok(());
+        }
+        let denied = format!(
+            "{TOOL_PERMISSION_DENIED_PREFIX} for {server}: tool `{tool}` needs {}, which is not in the effective permissions.",
+            missing.join(", ")
+        );
+        let prompt = parse_permission_prompt(&installed.config)?;
+        if prompt == PermissionPrompt::Off {
+            return Err(denied);
+        }
+        let decision = permission_prompt::ask(&prompt, &server, tool, &missing)
+            .map_err(|e| format!("{denied} {e}"))?;
+        if let Some(runtime) = &runtime {
+            let args: Vec<String> = [tool, decision.as_str()]
+                .into_iter()
+                .map(String::from)
+                .chain(missing.iter().cloned())
+                .collect();
+            let _ =
+                runtime.record_audit_event(&server, "permission-prompt", None, None, Some(&args));
+        }
+        match decision {
+            Decision::Once => Ok(()),
+            Decision::Always => {
+                permission_prompt::remember(&server, &installed.permissions, &missing)
+                    .map_err(|e| format!("{denied} Could not save the grant: {e}"))
+            }
+            Decision::Deny => Err(denied),
+        }
     }
 }
 
+/// Detects `berth update` rewriting a server config with a new version.
+struct VersionWatch {
+    path: PathBuf,
+    version: String,
+    modified: Option<SystemTime>,
+    checked: Instant,
+}
+
+impl VersionWatch {
+    fn new(path: PathBuf, version: &str) -> Self {
+        let modified = fs::metadata(&path).and_then(|m| m.modified()).ok();
+        VersionWatch {
+            path,
+            version: version.to_string(),
+            modified,
+            checked: Instant::now(),
+        }
+    }
+
+    /// Returns the updated config once per version change, checking at most every [`UPGRADE_POLL`].
+    fn poll(&mut self) -> Option<InstalledServer> {
+        if self.checked.elapsed() < UPGRADE_POLL {
+            return None;
+        }
+        self.checked = Instant::now();
+        let modified = fs::metadata(&self.path).and_then(|m| m.modified()).ok();
+        if modified == self.modified {
+            return None;
+        }
+        self.modified = modified;
+        let installed = read_installed(&self.path).ok()?;
+        if installed.server.version == self.version {
+            return None;
+        }
+        self.version = installed.server.version.clone();
+        Some(installed)
+    }
+}
+
+/// Builds the launch spec for an updated config, applying the same policy checks as startup.
+fn upgraded_spec(
+    server: &str,
+    installed: &InstalledServer,
+    inherit_env: bool,
+) -> Result<ProcessSpec, String> {
+    let missing = missing_required_keys(installed);
+    if !missing.is_empty() {
+        return Err(format!(
+            "updated version needs config: {}",
+            missing.join(", ")
+        ));
+    }
+    let registry = Registry::from_seed();
+    let global_policy = load_global_policy()?;
+    let (mut spec, _) =
+        build_process_spec(server, installed, &registry, &global_policy, inherit_env)?;
+    inject_access_token(server, installed, &registry, &mut spec)?;
+    route_through_proxy(&mut spec.env)?;
+    Ok(spec)
+}
+
+/// Reads the client's first message to find out which linked client it is,
+/// when any client has overrides for `server`.
+///
+/// Returns the client, if recognized, and the message re-encoded for relaying.
+fn identify_client(server: &str) -> (Option<String>, Vec<u8>) {
+    if !server_has_client_overrides(server).unwrap_or(false) {
+        return (None, Vec::new());
+    }
+    let seen = Detected::default();
+    let Ok(Some(message)) = framing::read_message(&mut io::stdin().lock(), &seen) else {
+        return (None, Vec::new());
+    };
+    let client = serde_json::from_slice::<serde_json::Value>(&message)
+        .ok()
+        .filter(|m| m.get("method").and_then(serde_json::Value::as_str) == Some("initialize"))
+        .and_then(|m| {
+            m.pointer("/params/clientInfo/name")
+                .and_then(serde_json::Value::as_str)
+                .and_then(client_from_info)
+        });
+    tracing::debug!(?client, "identified client from initialize");
+    let prelude = framing::encode(&message, seen.get().unwrap_or(Framing::Line));
+    (client.map(str::to_string), prelude)
+}
+
+/// Spawns the upstream server with piped stdio for relaying.
+pub(crate) fn spawn_upstream(spec: &ProcessSpec) -> io::Result<Child> {
+    let child = process_command(spec)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()?;
+    if let Some(limits) = &spec.limits {
+        attach_limits(limits, child.id());
+    }
+    Ok(child)
+}
+
 /// Reads and parses an installed server config file.
 fn read_installed(path: &Path) -> Result<InstalledServer, String> {
     let content = fs::read_to_string(path).map_err(|e| format!("Failed to read config: {e}"))?;
@@ -212,10 +623,11 @@ fn build_process_spec(
     installed: &InstalledServer,
     registry: &Registry,
     global_policy: &GlobalPolicy,
+    inherit_env: bool,
 ) -> Result<(ProcessSpec, Vec<String>), String> {
     let mut env = BTreeMap::new();
 
-    if let Some(meta) = registry.get(name) {
+    if let Some(meta) = registry.get(&installed.server.name) {
         for field in meta
             .config
             .required
@@ -241,24 +653,53 @@ fn build_process_spec(
     }
 
     let overrides = load_permission_overrides(name)?;
-    enforce_global_policy(name, &installed.permissions, &overrides, global_policy)?;
+    enforce_global_policy(name, installed, &overrides, global_policy, registry)?;
+    workspace_trust::check()?;
     validate_network_permissions(name, &installed.permissions.network, &overrides)?;
     let undeclared_network = undeclared_network_grants(&installed.permissions.network, &overrides);
     let sandbox_policy = parse_sandbox_policy(&installed.config)?;
-    if sandbox_policy.network_deny_all {
+    let run_as = parse_run_as(&installed.config)?;
+    let seccomp = parse_seccomp(&installed.config, &installed.runtime.syscalls)?;
+    if sandbox_policy.network == SandboxNetwork::DenyAll {
         return Err(format!(
             "{NETWORK_PERMISSION_DENIED_PREFIX}Server {} blocked by sandbox policy: set `{KEY_SANDBOX_NETWORK}=inherit` or relax network constraints.",
             name.cyan()
         ));
     }
     filter_env_map(&mut env, &installed.permissions.env, &overrides);
+    let network = effective_permissions("network", &installed.permissions.network, &overrides);
+    let clean_env = apply_env_isolation(
+        &mut env,
+        &installed.permissions.env,
+        &overrides,
+        inherit_env,
+    );
+    if sandbox_policy.network == SandboxNetwork::Allowlist {
+        apply_egress_allowlist(name, &mut env, clean_env, &network)?;
+    } else if parse_egress_monitor(&installed.config)? {
+        apply_egress_monitor(name, &mut env, clean_env)?;
+    }
+    // Wrapped last, so the sandbox can mount what egress setup points the env at.
+    let filesystem =
+        effective_permissions("filesystem", &installed.permissions.filesystem, &overrides);
+    let mut args = installed.runtime.args.clone();
+    let endpoint = apply_socket_endpoint(name, &installed.runtime, &mut env, &mut args)?;
+    let (command, args) =
+        apply_wasm_runtime(&installed.runtime, &args, &mut env, &filesystem, &network);
     let (command, args) = apply_sandbox_runtime(
-        &installed.runtime.command,
-        &installed.runtime.args,
+        name,
+        &command,
+        &args,
         &mut env,
         sandbox_policy,
-        &installed.permissions.filesystem,
+        &filesystem,
+        &network,
     );
+    // Outermost, so the bridge can reach the socket the sandboxed server binds.
+    let (command, args) = match &endpoint {
+        Some(endpoint) => bridge_command(endpoint, command, args)?,
+        None => (command, args),
+    };
 
     Ok((
         ProcessSpec {
@@ -266,6 +707,17 @@ fn build_process_spec(
             args,
             env,
             auto_restart: None,
+            clean_env,
+            refresh_at_epoch_secs: None,
+            capture_core: false,
+            readiness: None,
+            run_as,
+            seccomp,
+            shutdown_signal: None,
+            shutdown_grace_period_secs: None,
+            health_check: None,
+            schedule: None,
+            limits: resource_limits(&installed.runtime),
         },
         undeclared_network,
     ))
@@ -0,0 +1,201 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Command handler for `berth prune`.
+
+use colored::Colorize;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::Duration;
+
+use berth_common::now_epoch_secs;
+use berth_common::storage::format_bytes;
+use berth_runtime::RetentionPolicy;
+
+use crate::commands::analytics::parse_since;
+use crate::commands::unlink::installed_server_names;
+use crate::paths;
+use crate::publish_queue::PENDING_STATUS;
+
+/// Executes the `berth prune` command.
+pub fn execute(older_than: &str, dry_run: bool) {
+    let max_age_secs = match parse_since(older_than) {
+        Ok(secs) => secs,
+        Err(msg) => {
+            eprintln!(
+                "{} {}",
+                "✗".red().bold(),
+                msg.replace("--since", "--older-than")
+            );
+            process::exit(1);
+        }
+    };
+    let runtime = match paths::runtime_manager() {
+        Some(r) => r,
+        None => {
+            eprintln!("{} Could not determine home directory.", "✗".red().bold());
+            process::exit(1);
+        }
+    };
+    let known = match installed_server_names() {
+        Ok(names) => names,
+        Err(msg) => {
+            eprintln!("{} {}", "✗".red().bold(), msg);
+            process::exit(1);
+        }
+    };
+
+    let policy = RetentionPolicy {
+        max_age: Duration::from_secs(max_age_secs),
+        dry_run,
+    };
+    let report = match runtime.prune_stale(&known, policy) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("{} Failed to prune runtime data: {}", "✗".red().bold(), e);
+            process::exit(1);
+        }
+    };
+    let cutoff_secs = now_epoch_secs().saturating_sub(max_age_secs);
+    let submissions = match paths::publish_queue_dir() {
+        Some(dir) => match prune_publish_queue(&dir, cutoff_secs, dry_run) {
+            Ok(removed) => removed,
+            Err(msg) => {
+                eprintln!("{} {}", "✗".red().bold(), msg);
+                process::exit(1);
+            }
+        },
+        None => Vec::new(),
+    };
+    let submission_bytes: u64 = submissions.iter().map(|(_, bytes)| bytes).sum();
+    let freed = report.freed_bytes + submission_bytes;
+
+    for server in &report.skipped_running {
+        println!(
+            "{} Kept files of {}: it is no longer installed but still running.",
+            "!".yellow().bold(),
+            server.cyan()
+        );
+    }
+    let removed = report.orphaned_files.len()
+        + report.expired_logs.len()
+        + report.expired_audit_entries
+        + submissions.len();
+    if removed == 0 {
+        println!(
+            "{} Nothing older than {older_than} or left by uninstalled servers.",
+            "✓".green().bold()
+        );
+        return;
+    }
+
+    if dry_run {
+        println!("{} Would remove:", "!".yellow().bold());
+        let files = report
+            .orphaned_files
+            .iter()
+            .chain(&report.expired_logs)
+            .chain(submissions.iter().map(|(path, _)| path));
+        for file in files {
+            println!("  {}", file.display());
+        }
+    }
+    let verb = if dry_run { "Would free" } else { "Freed" };
+    println!(
+        "{} {verb} {}: {} file(s) of uninstalled servers, {} log file(s), {} audit entr{} and {} reviewed publish submission(s) older than {older_than}.",
+        if dry_run {
+            "!".yellow().bold()
+        } else {
+            "✓".green().bold()
+        },
+        format_bytes(freed),
+        report.orphaned_files.len(),
+        report.expired_logs.len(),
+        report.expired_audit_entries,
+        if report.expired_audit_entries == 1 {
+            "y"
+        } else {
+            "ies"
+        },
+        submissions.len()
+    );
+}
+
+/// Removes reviewed submissions submitted before `cutoff_secs`, returning
+/// their paths and sizes; with `dry_run` only lists them. Submissions still
+/// waiting for review are never pruned, and the queue index drops entries of
+/// removed files the next time it is read.
+fn prune_publish_queue(
+    dir: &Path,
+    cutoff_secs: u64,
+    dry_run: bool,
+) -> Result<Vec<(PathBuf, u64)>, String> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let entries = fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read publish queue {}: {e}", dir.display()))?;
+    let mut removed = Vec::new();
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if path.extension().is_none_or(|ext| ext != "json") {
+            continue;
+        }
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        let Ok(submission) = serde_json::from_str::<Value>(&content) else {
+            continue;
+        };
+        let status = submission
+            .get("status")
+            .and_then(Value::as_str)
+            .unwrap_or(PENDING_STATUS);
+        let submitted = submission
+            .get("submitted_at_epoch_secs")
+            .and_then(Value::as_u64)
+            .unwrap_or(u64::MAX);
+        if status == PENDING_STATUS || submitted >= cutoff_secs {
+            continue;
+        }
+        if !dry_run {
+            fs::remove_file(&path)
+                .map_err(|e| format!("Failed to remove {}: {e}", path.display()))?;
+        }
+        removed.push((path, content.len() as u64));
+    }
+    removed.sort();
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn publish_queue_keeps_pending_and_recent_submissions() {
+        let tmp = tempfile::tempdir().unwrap();
+        let write = |name: &str, status: &str, submitted: u64| {
+            fs::write(
+                tmp.path().join(name),
+                format!(r#"{{"status": "{status}", "submitted_at_epoch_secs": {submitted}}}"#),
+            )
+            .unwrap();
+        };
+        write("old-approved.json", "approved", 100);
+        write("old-pending.json", PENDING_STATUS, 100);
+        write("new-rejected.json", "rejected", 5_000);
+
+        let planned = prune_publish_queue(tmp.path(), 1_000, true).unwrap();
+        assert_eq!(planned.len(), 1);
+        assert!(planned[0].0.ends_with("old-approved.json"));
+        assert!(planned[0].0.exists());
+
+        prune_publish_queue(tmp.path(), 1_000, false).unwrap();
+        assert!(!tmp.path().join("old-approved.json").exists());
+        assert!(tmp.path().join("old-pending.json").exists());
+        assert!(tmp.path().join("new-rejected.json").exists());
+    }
+}
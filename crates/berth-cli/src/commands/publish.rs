@@ -3,14 +3,20 @@
 
 //! Command handler for `berth publish`.
 
+use berth_common::{new_ulid, now_epoch_secs};
+use berth_registry::types::{HealthCheck, ResourceLimits};
 use colored::Colorize;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::process;
-use std::time::{SystemTime, UNIX_EPOCH};
 
+use crate::commands::install::normalize_sha256;
+use crate::job_policy::{parse_schedule_value, JOB_RUNTIME};
 use crate::paths;
 use crate::permission_filter::validate_permission_syntax;
+use crate::publish_queue::{self, PENDING_STATUS};
+use crate::time_format::TimestampFormatter;
 
 /// Executes the `berth publish` command.
 pub fn execute(manifest_path: Option<&str>, dry_run: bool) {
@@ -52,8 +58,8 @@ pub fn execute(manifest_path: Option<&str>, dry_run: bool) {
         return;
     }
 
-    let output_path = match write_submission(&manifest, &checks) {
-        Ok(path) => path,
+    let (id, output_path) = match write_submission(&manifest, &checks) {
+        Ok(written) => written,
         Err(msg) => {
             eprintln!("{} {}", "✗".red().bold(), msg);
             process::exit(1);
@@ -65,7 +71,70 @@ pub fn execute(manifest_path: Option<&str>, dry_run: bool) {
         "✓".green().bold(),
         manifest.server.name.cyan()
     );
+    println!("  Submission ID: {}", id.bold());
     println!("  Queue entry: {}", output_path.display());
+    println!("  Check progress with `berth publish --status {id}`.");
+}
+
+/// Executes `berth publish --status <id>`: shows where a submission stands
+/// in the review queue.
+pub fn status(id: &str) {
+    let Some(queue_dir) = paths::publish_queue_dir() else {
+        fail("Could not determine home directory.");
+    };
+    let (document, summary) = match publish_queue::read(&queue_dir, id) {
+        Ok(Some(found)) => found,
+        Ok(None) => fail(&format!("No publish submission with ID `{id}`.")),
+        Err(msg) => fail(&format!("Failed to read submission `{id}`: {msg}")),
+    };
+
+    let timestamps = TimestampFormatter::new(false);
+    println!("{} {}", "Submission".bold(), summary.id.bold());
+    println!(
+        "  Server:    {} {} ({})",
+        summary.server.name.cyan(),
+        summary.server.version,
+        summary.server.display_name
+    );
+    println!("  Status:    {}", summary.status);
+    println!(
+        "  Submitted: {}",
+        timestamps.format(summary.submitted_at_epoch_secs * 1_000)
+    );
+    println!(
+        "  Checks:    {}/{} passed",
+        summary.quality_checks_passed, summary.quality_checks_total
+    );
+
+    let history = document
+        .get("reviewHistory")
+        .and_then(|value| value.as_array())
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    if history.is_empty() {
+        println!("  Not reviewed yet.");
+        return;
+    }
+    println!("  Review history:");
+    for event in history {
+        let at = event
+            .get("timestampEpochSecs")
+            .and_then(|value| value.as_u64())
+            .unwrap_or_default();
+        let status = event
+            .get("status")
+            .and_then(|value| value.as_str())
+            .unwrap_or("unknown");
+        match event.get("note").and_then(|value| value.as_str()) {
+            Some(note) => println!("    {}  {status}: {note}", timestamps.format(at * 1_000)),
+            None => println!("    {}  {status}", timestamps.format(at * 1_000)),
+        }
+    }
+}
+
+fn fail(msg: &str) -> ! {
+    eprintln!("{} {}", "✗".red().bold(), msg);
+    process::exit(1);
 }
 
 /// Loads and parses a publish manifest from disk.
@@ -124,11 +193,49 @@ fn validate_manifest(manifest: &PublishManifest) -> Vec<String> {
         &manifest.source.repository,
         &mut errors,
     );
+    if let Some(digest) = &manifest.source.sha256 {
+        if let Err(msg) = normalize_sha256(digest) {
+            errors.push(format!("source.sha256: {msg}"));
+        }
+    }
 
     validate_non_empty("runtime.type", &manifest.runtime.runtime_type, &mut errors);
     validate_non_empty("runtime.command", &manifest.runtime.command, &mut errors);
-    if manifest.runtime.transport.trim() != "stdio" {
-        errors.push("runtime.transport must be `stdio`.".to_string());
+    errors.extend(berth_runtime::template_problems(
+        manifest.runtime.transport.trim(),
+        manifest.runtime.socket.as_deref(),
+    ));
+    for syscall in &manifest.runtime.syscalls {
+        if !berth_runtime::is_filtered_syscall(syscall) {
+            errors.push(format!(
+                "runtime.syscalls entry `{syscall}` is not blocked by the default seccomp profile."
+            ));
+        }
+    }
+    if let Some(check) = &manifest.runtime.health_check {
+        for problem in check.problems() {
+            errors.push(format!("runtime.health_check: {problem}."));
+        }
+    }
+    if let Some(limits) = &manifest.runtime.limits {
+        for problem in limits.problems() {
+            errors.push(format!("runtime.limits: {problem}."));
+        }
+    }
+    match (
+        &manifest.runtime.schedule,
+        manifest.runtime.runtime_type.as_str(),
+    ) {
+        (Some(schedule), JOB_RUNTIME) => {
+            if let Err(msg) = parse_schedule_value(schedule) {
+                errors.push(format!("runtime.schedule: {msg}"));
+            }
+        }
+        (None, JOB_RUNTIME) => errors.push("runtime.schedule is required for jobs.".to_string()),
+        (Some(_), _) => {
+            errors.push("runtime.schedule is only valid for `job` runtimes.".to_string())
+        }
+        (None, _) => {}
     }
 
     let mut config_keys = std::collections::BTreeSet::new();
@@ -175,6 +282,11 @@ fn validate_manifest(manifest: &PublishManifest) -> Vec<String> {
             errors.push(err);
         }
     }
+    for permission in manifest.permissions.tools.values().flatten() {
+        if let Err(err) = validate_permission_syntax(permission) {
+            errors.push(err);
+        }
+    }
 
     if manifest.compatibility.clients.is_empty() {
         errors.push("compatibility.clients must include at least one client.".to_string());
@@ -270,55 +382,21 @@ fn print_quality_checks(checks: &[QualityCheck]) {
 fn write_submission(
     manifest: &PublishManifest,
     checks: &[QualityCheck],
-) -> Result<std::path::PathBuf, String> {
+) -> Result<(String, std::path::PathBuf), String> {
     let queue_dir =
         paths::publish_queue_dir().ok_or("Could not determine home directory.".to_string())?;
-    fs::create_dir_all(&queue_dir)
-        .map_err(|e| format!("Failed to create publish queue directory: {e}"))?;
-
-    let file_name = format!(
-        "{}-{}.json",
-        sanitize_name(&manifest.server.name),
-        now_epoch_secs()
-    );
-    let path = queue_dir.join(file_name);
     let submission = PublishSubmission {
+        id: new_ulid(),
         submitted_at_epoch_secs: now_epoch_secs(),
-        status: "pending-manual-review".to_string(),
+        status: PENDING_STATUS.to_string(),
         manifest: manifest.clone(),
         quality_checks: checks.to_vec(),
     };
-    let payload = serde_json::to_string_pretty(&submission)
+    let document = serde_json::to_value(&submission)
         .map_err(|e| format!("Failed to serialize submission payload: {e}"))?;
-    fs::write(&path, payload).map_err(|e| format!("Failed to write submission: {e}"))?;
-    Ok(path)
-}
-
-/// Returns current unix timestamp in seconds.
-fn now_epoch_secs() -> u64 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_secs()
-}
-
-/// Replaces non-safe filename characters with '-'.
-fn sanitize_name(name: &str) -> String {
-    let out: String = name
-        .chars()
-        .map(|c| {
-            if c.is_ascii_alphanumeric() || c == '-' {
-                c
-            } else {
-                '-'
-            }
-        })
-        .collect();
-    if out.is_empty() {
-        "submission".to_string()
-    } else {
-        out
-    }
+    let path = publish_queue::add(&queue_dir, &document)
+        .map_err(|e| format!("Failed to write submission: {e}"))?;
+    Ok((submission.id, path))
 }
 
 /// Pushes a non-empty validation error when `value` is blank.
@@ -329,7 +407,7 @@ fn validate_non_empty(field: &str, value: &str, errors: &mut Vec<String>) {
 }
 
 /// Performs a lightweight semantic-version check (`x.y.z`).
-fn is_basic_semver(version: &str) -> bool {
+pub(crate) fn is_basic_semver(version: &str) -> bool {
     let mut parts = version.split('.');
     let (Some(a), Some(b), Some(c), None) =
         (parts.next(), parts.next(), parts.next(), parts.next())
@@ -346,6 +424,7 @@ fn is_basic_semver(version: &str) -> bool {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct PublishSubmission {
+    id: String,
     submitted_at_epoch_secs: u64,
     status: String,
     manifest: PublishManifest,
@@ -390,6 +469,8 @@ struct ManifestSource {
     source_type: String,
     package: String,
     repository: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sha256: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -400,6 +481,16 @@ struct ManifestRuntime {
     #[serde(default)]
     args: Vec<String>,
     transport: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    syscalls: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    health_check: Option<HealthCheck>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    schedule: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    limits: Option<ResourceLimits>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    socket: Option<String>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -412,6 +503,9 @@ struct ManifestPermissions {
     filesystem: Vec<String>,
     #[serde(default)]
     exec: Vec<String>,
+    /// Permissions each listed tool needs, in prefixed form.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    tools: BTreeMap<String, Vec<String>>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -488,18 +582,25 @@ mod tests {
                 source_type: "npm".to_string(),
                 package: "@acme/mcp-github".to_string(),
                 repository: "https://github.com/acme/mcp-github".to_string(),
+                sha256: None,
             },
             runtime: ManifestRuntime {
                 runtime_type: "node".to_string(),
                 command: "npx".to_string(),
                 args: vec!["-y".to_string(), "@acme/mcp-github".to_string()],
                 transport: "stdio".to_string(),
+                syscalls: vec![],
+                health_check: None,
+                schedule: None,
+                limits: None,
+                socket: None,
             },
             permissions: ManifestPermissions {
                 network: vec!["api.github.com:443".to_string()],
                 env: vec!["GITHUB_TOKEN".to_string()],
                 filesystem: vec!["read:/workspace".to_string()],
                 exec: vec!["git".to_string()],
+                tools: BTreeMap::new(),
             },
             config: ManifestConfig {
                 required: vec![ManifestConfigField {
@@ -534,7 +635,7 @@ mod tests {
     #[test]
     fn validate_manifest_rejects_bad_permissions_and_transport() {
         let mut manifest = valid_manifest();
-        manifest.runtime.transport = "http".to_string();
+        manifest.runtime.transport = "sse".to_string();
         manifest.permissions.env = vec!["bad-var".to_string()];
         let errors = validate_manifest(&manifest);
         assert!(errors.iter().any(|e| e.contains("runtime.transport")));
@@ -543,6 +644,74 @@ mod tests {
             .any(|e| e.contains("Invalid permission format")));
     }
 
+    #[test]
+    fn validate_manifest_rejects_syscalls_the_profile_allows() {
+        let mut manifest = valid_manifest();
+        manifest.runtime.syscalls = vec!["ptrace".to_string()];
+        assert!(validate_manifest(&manifest).is_empty());
+        manifest.runtime.syscalls.push("read".to_string());
+        let errors = validate_manifest(&manifest);
+        assert!(errors.iter().any(|e| e.contains("`read`")));
+    }
+
+    #[test]
+    fn validate_manifest_checks_sha256_format() {
+        let mut manifest = valid_manifest();
+        manifest.source.sha256 = Some(format!("sha256:{}", "ab".repeat(32)));
+        assert!(validate_manifest(&manifest).is_empty());
+        manifest.source.sha256 = Some("abc123".to_string());
+        let errors = validate_manifest(&manifest);
+        assert!(errors.iter().any(|e| e.contains("source.sha256")));
+    }
+
+    #[test]
+    fn validate_manifest_rejects_incomplete_health_checks() {
+        let mut manifest = valid_manifest();
+        manifest.runtime.health_check =
+            Some(serde_json::from_str(r#"{"probe": "mcp-ping"}"#).unwrap());
+        assert!(validate_manifest(&manifest).is_empty());
+        manifest.runtime.health_check = Some(
+            serde_json::from_str(r#"{"probe": "exec", "command": " ", "failureThreshold": 0}"#)
+                .unwrap(),
+        );
+        let errors = validate_manifest(&manifest);
+        assert!(errors
+            .iter()
+            .any(|e| e.contains("exec probe needs a command")));
+        assert!(errors.iter().any(|e| e.contains("failureThreshold")));
+    }
+
+    #[test]
+    fn validate_manifest_rejects_zero_resource_limits() {
+        let mut manifest = valid_manifest();
+        manifest.runtime.limits = Some(ResourceLimits {
+            memory_mb: Some(256),
+            ..ResourceLimits::default()
+        });
+        assert!(validate_manifest(&manifest).is_empty());
+        manifest.runtime.limits = Some(ResourceLimits {
+            max_open_files: Some(0),
+            ..ResourceLimits::default()
+        });
+        let errors = validate_manifest(&manifest);
+        assert!(errors.iter().any(|e| e.contains("maxOpenFiles")));
+    }
+
+    #[test]
+    fn validate_manifest_requires_schedules_on_jobs_only() {
+        let mut manifest = valid_manifest();
+        manifest.runtime.schedule = Some("every 1h".to_string());
+        assert!(validate_manifest(&manifest)
+            .iter()
+            .any(|e| e.contains("only valid for `job`")));
+        manifest.runtime.runtime_type = JOB_RUNTIME.to_string();
+        assert!(validate_manifest(&manifest).is_empty());
+        manifest.runtime.schedule = None;
+        assert!(validate_manifest(&manifest)
+            .iter()
+            .any(|e| e.contains("runtime.schedule is required")));
+    }
+
     #[test]
     fn quality_checks_fail_when_permissions_missing() {
         let mut manifest = valid_manifest();
@@ -0,0 +1,76 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Command handler for `berth receipts`.
+
+use colored::Colorize;
+use std::process;
+
+use crate::receipts;
+use crate::time_format::TimestampFormatter;
+
+/// Executes the `berth receipts` command.
+pub fn execute(server: &str, json: bool, utc: bool) {
+    let receipts = match receipts::list(server) {
+        Ok(receipts) => receipts,
+        Err(msg) => {
+            eprintln!("{} {}", "✗".red().bold(), msg);
+            process::exit(1);
+        }
+    };
+
+    if json {
+        match serde_json::to_string_pretty(&receipts) {
+            Ok(rendered) => println!("{rendered}"),
+            Err(e) => {
+                eprintln!("{} Failed to serialize receipts: {}", "✗".red().bold(), e);
+                process::exit(1);
+            }
+        }
+        return;
+    }
+
+    if receipts.is_empty() {
+        println!("{} No receipts for {}.", "!".yellow().bold(), server.cyan());
+        return;
+    }
+
+    println!("{} Receipts for {}:", "✓".green().bold(), server.cyan());
+    let formatter = TimestampFormatter::new(utc);
+    for receipt in &receipts {
+        let version = match &receipt.previous_version {
+            Some(previous) => format!("{previous} -> {}", receipt.version),
+            None => format!("v{}", receipt.version),
+        };
+        println!(
+            "\n  {} {}  {}",
+            receipt.action.bold(),
+            version,
+            formatter.format(receipt.timestamp_epoch_ms).dimmed()
+        );
+        let source = &receipt.source;
+        println!(
+            "    {:<10} {} {} ({})",
+            "source".dimmed(),
+            source.source_type,
+            source.package,
+            source.registry.as_deref().unwrap_or("seed")
+        );
+        for file in receipt.artifact.iter().chain(&receipt.files) {
+            println!(
+                "    {:<10} {}  sha256:{}",
+                file.kind.dimmed(),
+                file.path.display(),
+                file.sha256
+            );
+        }
+        for decision in &receipt.policy {
+            println!(
+                "    {:<10} {}: {}",
+                "policy".dimmed(),
+                decision.check,
+                decision.decision
+            );
+        }
+    }
+}
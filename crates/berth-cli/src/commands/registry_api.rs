@@ -7,25 +7,47 @@ use colored::Colorize;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::collections::BTreeSet;
+use std::env;
 use std::fs;
 use std::io::{self, Read, Write};
 use std::net::{TcpListener, TcpStream};
 use std::path::PathBuf;
 use std::process;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::time::Duration;
 
+use berth_common::{atomic_write, jsonl, now_epoch_ms, now_epoch_secs};
+use berth_registry::permissions::{grants_filesystem_write, FsPermission, NetworkPermission};
+use berth_registry::search::{paginate, Cursor};
 use berth_registry::types::{ServerMetadata, TrustLevel};
 use berth_registry::Registry;
+use berth_runtime::{AuditLog, AuditQuery};
 
 use crate::commands::analytics::{empty_summary, parse_since, summarize_audit_log};
+use crate::commands::registry_init::secrets_match;
 use crate::paths;
+use crate::platform::safe_file_name;
+use crate::publish_queue::{self, PublishSubmissionSummary};
 
 const MAX_REQUEST_BYTES: usize = 16 * 1024;
+/// Request size accepted by `POST /reports/batch`, which carries many reports.
+const MAX_BATCH_REQUEST_BYTES: usize = 256 * 1024;
+/// Reports accepted in one `POST /reports/batch`; larger batches are refused
+/// with 413 so syncing tools split them.
+const MAX_REPORT_BATCH_ITEMS: usize = 100;
+/// How far ahead of this instance's clock a synced report may be dated.
+const MAX_REPORT_CLOCK_SKEW_SECS: u64 = 300;
+/// Env var holding the bearer token required for POST requests.
+pub const API_TOKEN_ENV: &str = "BERTH_REGISTRY_API_TOKEN";
+/// Env var naming the bind address when `--bind` is not given.
+pub const API_BIND_ENV: &str = "BERTH_REGISTRY_API_BIND";
+/// Bind address used when neither `--bind` nor [`API_BIND_ENV`] is set.
+pub const DEFAULT_API_BIND: &str = "127.0.0.1:8787";
 
 #[derive(Debug)]
 struct ApiState {
     community_dir: PathBuf,
     publish_queue_dir: PathBuf,
+    auth_token: Option<String>,
 }
 
 #[derive(Debug, Default, Serialize, Deserialize)]
@@ -36,6 +58,9 @@ struct CommunitySnapshot {
     reports: std::collections::BTreeMap<String, u64>,
     #[serde(default)]
     verified_publishers: Vec<String>,
+    /// Trust levels set by reviewers, overriding the index.
+    #[serde(default)]
+    trust_levels: std::collections::BTreeMap<String, TrustLevel>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -59,6 +84,28 @@ struct PublishReviewEvent {
     note: Option<String>,
 }
 
+/// One reviewed trust-level change of a server.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TrustChangeEvent {
+    pub timestamp_epoch_secs: u64,
+    pub server: String,
+    pub previous_level: TrustLevel,
+    pub level: TrustLevel,
+    pub reviewer: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TrustChangePayload {
+    #[serde(default)]
+    level: String,
+    #[serde(default)]
+    reviewer: String,
+    #[serde(default)]
+    reason: String,
+}
+
 #[derive(Debug, Deserialize)]
 struct ReportPayload {
     #[serde(default)]
@@ -67,6 +114,26 @@ struct ReportPayload {
     details: String,
 }
 
+#[derive(Debug, Deserialize)]
+struct ReportBatchPayload {
+    reports: Vec<Value>,
+}
+
+/// One item of a `POST /reports/batch` body.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BatchReportPayload {
+    #[serde(default)]
+    server: String,
+    #[serde(default)]
+    reason: String,
+    #[serde(default)]
+    details: String,
+    /// When the report was filed on the instance it is synced from.
+    #[serde(default)]
+    timestamp_epoch_secs: Option<u64>,
+}
+
 #[derive(Debug, Deserialize)]
 struct PublisherPayload {
     #[serde(default)]
@@ -77,6 +144,7 @@ struct PublisherPayload {
 struct HttpRequest {
     method: String,
     target: String,
+    authorization: Option<String>,
     body: String,
 }
 
@@ -173,34 +241,6 @@ struct SiteReviewEventsUrlParams<'a> {
     offset: usize,
 }
 
-#[derive(Debug, Deserialize)]
-struct QueueSubmissionFile {
-    submitted_at_epoch_secs: u64,
-    status: String,
-    manifest: QueueManifest,
-    #[serde(default)]
-    quality_checks: Vec<QueueQualityCheck>,
-}
-
-#[derive(Debug, Deserialize)]
-struct QueueManifest {
-    server: QueueServer,
-}
-
-#[derive(Debug, Deserialize)]
-struct QueueServer {
-    name: String,
-    display_name: String,
-    version: String,
-    maintainer: String,
-    category: String,
-}
-
-#[derive(Debug, Deserialize)]
-struct QueueQualityCheck {
-    passed: bool,
-}
-
 #[derive(Debug, Deserialize)]
 struct PublishSubmissionStatusPayload {
     status: String,
@@ -208,27 +248,6 @@ struct PublishSubmissionStatusPayload {
     note: String,
 }
 
-#[derive(Debug, Serialize)]
-#[serde(rename_all = "camelCase")]
-struct PublishSubmissionSummary {
-    id: String,
-    submitted_at_epoch_secs: u64,
-    status: String,
-    server: PublishSubmissionServerSummary,
-    quality_checks_passed: usize,
-    quality_checks_total: usize,
-}
-
-#[derive(Debug, Serialize)]
-#[serde(rename_all = "camelCase")]
-struct PublishSubmissionServerSummary {
-    name: String,
-    display_name: String,
-    version: String,
-    maintainer: String,
-    category: String,
-}
-
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 struct PublisherSummary {
@@ -274,9 +293,27 @@ impl ApiState {
         Self {
             community_dir,
             publish_queue_dir,
+            auth_token: None,
         }
     }
 
+    /// Requires `Authorization: Bearer <token>` on mutating requests.
+    fn with_auth_token(mut self, token: Option<String>) -> Self {
+        self.auth_token = token.filter(|t| !t.trim().is_empty());
+        self
+    }
+
+    fn is_authorized(&self, request: &HttpRequest) -> bool {
+        let Some(token) = &self.auth_token else {
+            return true;
+        };
+        request
+            .authorization
+            .as_deref()
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .is_some_and(|presented| secrets_match(presented.trim(), token))
+    }
+
     fn snapshot_path(&self) -> PathBuf {
         self.community_dir.join("snapshot.json")
     }
@@ -286,7 +323,12 @@ impl ApiState {
     }
 
     fn report_path(&self, server: &str) -> PathBuf {
-        self.reports_dir().join(format!("{server}.jsonl"))
+        self.reports_dir()
+            .join(format!("{}.jsonl", safe_file_name(server)))
+    }
+
+    fn trust_events_path(&self) -> PathBuf {
+        self.community_dir.join("trust-events.jsonl")
     }
 
     fn berth_root_dir(&self) -> PathBuf {
@@ -337,7 +379,7 @@ impl ApiState {
         let path = self.snapshot_path();
         let payload = serde_json::to_string_pretty(snapshot)
             .map_err(|e| format!("failed to serialize community snapshot: {e}"))?;
-        fs::write(&path, payload)
+        atomic_write(&path, payload)
             .map_err(|e| format!("failed to write community snapshot {}: {e}", path.display()))
     }
 
@@ -364,13 +406,6 @@ impl ApiState {
         let report_count = *reports;
         self.save_snapshot(&snapshot)?;
 
-        let reports_dir = self.reports_dir();
-        fs::create_dir_all(&reports_dir).map_err(|e| {
-            format!(
-                "failed to create reports directory {}: {e}",
-                reports_dir.display()
-            )
-        })?;
         let report_path = self.report_path(server);
         let event = ReportEvent {
             timestamp_epoch_secs: now_epoch_secs(),
@@ -378,18 +413,57 @@ impl ApiState {
             reason: reason.to_string(),
             details: details.to_string(),
         };
-        let line = serde_json::to_string(&event)
-            .map_err(|e| format!("failed to serialize report: {e}"))?;
-        let mut file = fs::OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&report_path)
-            .map_err(|e| format!("failed to open report file {}: {e}", report_path.display()))?;
-        writeln!(file, "{line}")
+        jsonl::append(&report_path, &event)
             .map_err(|e| format!("failed to append report {}: {e}", report_path.display()))?;
         Ok(report_count)
     }
 
+    /// Appends `events` to their servers' report logs with one write per
+    /// server and bumps the report counts once, returning the new totals.
+    ///
+    /// All or nothing: if any append or the snapshot update fails, the logs
+    /// already appended to are truncated back to where the batch started.
+    fn record_reports(
+        &self,
+        events: &[&ReportEvent],
+    ) -> Result<std::collections::BTreeMap<String, u64>, String> {
+        let mut by_server = std::collections::BTreeMap::<&str, Vec<&ReportEvent>>::new();
+        for event in events {
+            by_server.entry(&event.server).or_default().push(*event);
+        }
+        let mut appended = Vec::new();
+        let recorded = (|| {
+            for (server, batch) in &by_server {
+                let report_path = self.report_path(server);
+                let len = jsonl::append_all(&report_path, batch).map_err(|e| {
+                    format!("failed to append reports {}: {e}", report_path.display())
+                })?;
+                appended.push((report_path, len));
+            }
+
+            let mut snapshot = self.load_snapshot()?;
+            let mut totals = std::collections::BTreeMap::new();
+            for (server, batch) in &by_server {
+                let reports = snapshot.reports.entry(server.to_string()).or_insert(0);
+                *reports += batch.len() as u64;
+                totals.insert(server.to_string(), *reports);
+            }
+            self.save_snapshot(&snapshot)?;
+            Ok(totals)
+        })();
+        if let Err(e) = &recorded {
+            for (report_path, len) in &appended {
+                if let Err(rollback) = jsonl::truncate(report_path, *len) {
+                    return Err(format!(
+                        "{e}; failed to roll back reports {}: {rollback}",
+                        report_path.display()
+                    ));
+                }
+            }
+        }
+        recorded
+    }
+
     fn list_reports(&self, server: &str) -> Result<Vec<ReportEvent>, String> {
         let report_path = self.report_path(server);
         if !report_path.exists() {
@@ -473,28 +547,8 @@ impl ApiState {
     }
 
     fn append_publish_review_event(&self, event: &PublishReviewEvent) -> Result<(), String> {
-        let publish_dir = self.publish_root_dir();
-        fs::create_dir_all(&publish_dir).map_err(|e| {
-            format!(
-                "failed to create publish directory {}: {e}",
-                publish_dir.display()
-            )
-        })?;
-
         let path = self.publish_review_events_path();
-        let line = serde_json::to_string(event)
-            .map_err(|e| format!("failed to serialize publish review event: {e}"))?;
-        let mut file = fs::OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&path)
-            .map_err(|e| {
-                format!(
-                    "failed to open publish review events file {}: {e}",
-                    path.display()
-                )
-            })?;
-        writeln!(file, "{line}").map_err(|e| {
+        jsonl::append(&path, event).map_err(|e| {
             format!(
                 "failed to append publish review event {}: {e}",
                 path.display()
@@ -539,52 +593,7 @@ impl ApiState {
     }
 
     fn list_publish_submissions(&self) -> Result<Vec<PublishSubmissionSummary>, String> {
-        let queue_dir = self.publish_queue_dir();
-        if !queue_dir.exists() {
-            return Ok(Vec::new());
-        }
-        let entries = fs::read_dir(&queue_dir).map_err(|e| {
-            format!(
-                "failed to read publish queue directory {}: {e}",
-                queue_dir.display()
-            )
-        })?;
-
-        let mut submissions = Vec::new();
-        for entry in entries {
-            let entry = entry.map_err(|e| {
-                format!(
-                    "failed to enumerate publish queue directory {}: {e}",
-                    queue_dir.display()
-                )
-            })?;
-            let path = entry.path();
-            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
-                continue;
-            }
-            let content = fs::read_to_string(&path)
-                .map_err(|e| format!("failed to read queue file {}: {e}", path.display()))?;
-            let payload = serde_json::from_str::<QueueSubmissionFile>(&content)
-                .map_err(|e| format!("failed to parse queue file {}: {e}", path.display()))?;
-
-            let id = path
-                .file_name()
-                .and_then(|value| value.to_str())
-                .unwrap_or_default()
-                .to_string();
-            if id.is_empty() {
-                continue;
-            }
-            submissions.push(publish_submission_summary_from_queue_file(id, payload));
-        }
-
-        submissions.sort_by(|left, right| {
-            right
-                .submitted_at_epoch_secs
-                .cmp(&left.submitted_at_epoch_secs)
-                .then_with(|| left.id.cmp(&right.id))
-        });
-        Ok(submissions)
+        publish_queue::list(&self.publish_queue_dir())
     }
 
     fn set_publish_submission_status(
@@ -593,92 +602,61 @@ impl ApiState {
         status: &str,
         note: Option<&str>,
     ) -> Result<Option<PublishSubmissionSummary>, String> {
-        if !is_safe_submission_id(submission_id) {
-            return Err("invalid submission id".to_string());
-        }
-        let path = self.publish_queue_dir().join(submission_id);
-        if !path.exists() {
-            return Ok(None);
-        }
-
-        let content = fs::read_to_string(&path)
-            .map_err(|e| format!("failed to read queue file {}: {e}", path.display()))?;
-        let mut value = serde_json::from_str::<Value>(&content)
-            .map_err(|e| format!("failed to parse queue file {}: {e}", path.display()))?;
         let timestamp = now_epoch_secs();
-        let previous_status = value
-            .get("status")
-            .and_then(Value::as_str)
-            .map(str::trim)
-            .filter(|current| !current.is_empty())
-            .unwrap_or("unknown")
-            .to_string();
         let normalized_note = note
             .map(str::trim)
             .filter(|value| !value.is_empty())
             .map(str::to_string);
-        value["status"] = Value::String(status.to_string());
-        value["reviewedAtEpochSecs"] = json!(timestamp);
-
-        if value
-            .get("reviewHistory")
-            .and_then(Value::as_array)
-            .is_none()
-        {
-            value["reviewHistory"] = Value::Array(Vec::new());
-        }
-        if let Some(history) = value["reviewHistory"].as_array_mut() {
-            let mut event = json!({
-                "timestampEpochSecs": timestamp,
-                "status": status
-            });
-            if let Some(note) = normalized_note.as_deref() {
-                event["note"] = Value::String(note.to_string());
+        let mut previous_status = String::new();
+        let updated = publish_queue::update(&self.publish_queue_dir(), submission_id, |value| {
+            previous_status = value
+                .get("status")
+                .and_then(Value::as_str)
+                .map(str::trim)
+                .filter(|current| !current.is_empty())
+                .unwrap_or("unknown")
+                .to_string();
+            value["status"] = Value::String(status.to_string());
+            value["reviewedAtEpochSecs"] = json!(timestamp);
+
+            if value
+                .get("reviewHistory")
+                .and_then(Value::as_array)
+                .is_none()
+            {
+                value["reviewHistory"] = Value::Array(Vec::new());
             }
-            history.push(event);
-        }
-
-        let payload = serde_json::to_string_pretty(&value)
-            .map_err(|e| format!("failed to serialize queue file {}: {e}", path.display()))?;
-        fs::write(&path, payload)
-            .map_err(|e| format!("failed to write queue file {}: {e}", path.display()))?;
+            if let Some(history) = value["reviewHistory"].as_array_mut() {
+                let mut event = json!({
+                    "timestampEpochSecs": timestamp,
+                    "status": status
+                });
+                if let Some(note) = normalized_note.as_deref() {
+                    event["note"] = Value::String(note.to_string());
+                }
+                history.push(event);
+            }
+        })?;
+        let Some((_, summary)) = updated else {
+            return Ok(None);
+        };
 
-        let normalized = serde_json::from_value::<QueueSubmissionFile>(value)
-            .map_err(|e| format!("failed to normalize queue file {}: {e}", path.display()))?;
         self.append_publish_review_event(&PublishReviewEvent {
             timestamp_epoch_secs: timestamp,
-            submission_id: submission_id.to_string(),
-            server: normalized.manifest.server.name.clone(),
+            submission_id: summary.id.clone(),
+            server: summary.server.name.clone(),
             previous_status,
             status: status.to_string(),
             note: normalized_note,
         })?;
-        Ok(Some(publish_submission_summary_from_queue_file(
-            submission_id.to_string(),
-            normalized,
-        )))
+        Ok(Some(summary))
     }
 
     fn get_publish_submission(
         &self,
         submission_id: &str,
     ) -> Result<Option<(Value, PublishSubmissionSummary)>, String> {
-        if !is_safe_submission_id(submission_id) {
-            return Err("invalid submission id".to_string());
-        }
-        let path = self.publish_queue_dir().join(submission_id);
-        if !path.exists() {
-            return Ok(None);
-        }
-        let content = fs::read_to_string(&path)
-            .map_err(|e| format!("failed to read queue file {}: {e}", path.display()))?;
-        let value = serde_json::from_str::<Value>(&content)
-            .map_err(|e| format!("failed to parse queue file {}: {e}", path.display()))?;
-        let normalized = serde_json::from_value::<QueueSubmissionFile>(value.clone())
-            .map_err(|e| format!("failed to normalize queue file {}: {e}", path.display()))?;
-        let summary =
-            publish_submission_summary_from_queue_file(submission_id.to_string(), normalized);
-        Ok(Some((value, summary)))
+        publish_queue::read(&self.publish_queue_dir(), submission_id)
     }
 
     fn list_verified_publishers(&self) -> Result<Vec<String>, String> {
@@ -720,6 +698,100 @@ impl ApiState {
         self.list_verified_publishers()
     }
 
+    /// Returns the trust level of `server`, including reviewed changes.
+    fn trust_level(&self, server: &ServerMetadata) -> Result<TrustLevel, String> {
+        let snapshot = self.load_snapshot()?;
+        Ok(snapshot
+            .trust_levels
+            .get(&server.name)
+            .cloned()
+            .unwrap_or_else(|| server.trust_level.clone()))
+    }
+
+    /// Sets the trust level of `server` and appends the change to the trust
+    /// event log; callers validate it with [`validate_trust_change`] first.
+    fn change_trust_level(
+        &self,
+        server: &ServerMetadata,
+        level: TrustLevel,
+        reviewer: &str,
+        reason: &str,
+    ) -> Result<TrustChangeEvent, String> {
+        let mut snapshot = self.load_snapshot()?;
+        let previous_level = snapshot
+            .trust_levels
+            .get(&server.name)
+            .cloned()
+            .unwrap_or_else(|| server.trust_level.clone());
+        validate_trust_change(&previous_level, &level, reviewer, reason)?;
+        if level == server.trust_level {
+            snapshot.trust_levels.remove(&server.name);
+        } else {
+            snapshot
+                .trust_levels
+                .insert(server.name.clone(), level.clone());
+        }
+        self.save_snapshot(&snapshot)?;
+
+        let event = TrustChangeEvent {
+            timestamp_epoch_secs: now_epoch_secs(),
+            server: server.name.clone(),
+            previous_level,
+            level,
+            reviewer: reviewer.trim().to_string(),
+            reason: reason.trim().to_string(),
+        };
+        let path = self.trust_events_path();
+        jsonl::append(&path, &event)
+            .map_err(|e| format!("failed to append trust event {}: {e}", path.display()))?;
+        Ok(event)
+    }
+
+    /// Lists the trust changes of `server`, newest first.
+    fn list_trust_events(&self, server: &str) -> Result<Vec<TrustChangeEvent>, String> {
+        let path = self.trust_events_path();
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let content = fs::read_to_string(&path)
+            .map_err(|e| format!("failed to read trust events {}: {e}", path.display()))?;
+        let mut events = Vec::new();
+        for (idx, line) in content.lines().enumerate() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() {
+                continue;
+            }
+            let event = serde_json::from_str::<TrustChangeEvent>(trimmed).map_err(|e| {
+                format!(
+                    "failed to parse trust events {} at line {}: {e}",
+                    path.display(),
+                    idx + 1
+                )
+            })?;
+            if event.server == server {
+                events.push(event);
+            }
+        }
+        events.reverse();
+        Ok(events)
+    }
+
+    /// Returns the index served at `/index.json`, with reviewed trust levels applied.
+    fn index_servers(&self, registry: &Registry) -> Result<Vec<ServerMetadata>, String> {
+        let snapshot = self.load_snapshot()?;
+        Ok(registry
+            .list_all()
+            .iter()
+            .map(|server| {
+                let mut server = server.clone();
+                if let Some(level) = snapshot.trust_levels.get(&server.name) {
+                    server.trust_level = level.clone();
+                }
+                server
+            })
+            .collect())
+    }
+
     fn is_publisher_verified(&self, maintainer: &str) -> Result<bool, String> {
         let normalized = normalize_maintainer(maintainer);
         if normalized.is_empty() {
@@ -731,7 +803,9 @@ impl ApiState {
 }
 
 /// Executes the `berth registry-api` command.
-pub fn execute(bind: &str, max_requests: Option<u32>) {
+pub fn execute(bind: Option<&str>, max_requests: Option<u32>) {
+    let bind_env = env::var(API_BIND_ENV).ok().filter(|v| !v.trim().is_empty());
+    let bind = bind.or(bind_env.as_deref()).unwrap_or(DEFAULT_API_BIND);
     let listener = match TcpListener::bind(bind) {
         Ok(listener) => listener,
         Err(e) => {
@@ -755,13 +829,8 @@ pub fn execute(bind: &str, max_requests: Option<u32>) {
     println!("Registry API listening on http://{local_addr}");
     let _ = io::stdout().flush();
 
-    let registry = Registry::from_seed();
-    let community_dir = paths::berth_home()
-        .map(|home| home.join("registry").join("community"))
-        .unwrap_or_else(|| PathBuf::from(".berth/registry/community"));
-    let publish_queue_dir =
-        paths::publish_queue_dir().unwrap_or_else(|| PathBuf::from(".berth/publish/queue"));
-    let state = ApiState::new(community_dir, publish_queue_dir);
+    let registry = super::load_registry();
+    let state = local_state().with_auth_token(env::var(API_TOKEN_ENV).ok());
     let mut handled: u32 = 0;
     for stream in listener.incoming() {
         let mut stream = match stream {
@@ -790,6 +859,50 @@ pub fn execute(bind: &str, max_requests: Option<u32>) {
     }
 }
 
+/// Returns the state of the registry served from the current Berth home.
+fn local_state() -> ApiState {
+    let community_dir = paths::berth_home()
+        .map(|home| home.join("registry").join("community"))
+        .unwrap_or_else(|| PathBuf::from(".berth/registry/community"));
+    let publish_queue_dir =
+        paths::publish_queue_dir().unwrap_or_else(|| PathBuf::from(".berth/publish/queue"));
+    ApiState::new(community_dir, publish_queue_dir)
+}
+
+/// Records a trust-level change in the community data `registry-api` serves
+/// from the current Berth home, as `POST /servers/<name>/trust` does.
+pub(crate) fn record_trust_change(
+    server: &str,
+    level: TrustLevel,
+    reviewer: &str,
+    reason: &str,
+) -> Result<TrustChangeEvent, String> {
+    let registry = super::load_registry();
+    let meta = registry
+        .get(server)
+        .ok_or_else(|| format!("Server {server} is not in the registry."))?;
+    local_state().change_trust_level(meta, level, reviewer, reason)
+}
+
+/// Checks that a trust change names a new level, a reviewer, and a reason.
+fn validate_trust_change(
+    previous: &TrustLevel,
+    level: &TrustLevel,
+    reviewer: &str,
+    reason: &str,
+) -> Result<(), String> {
+    if reviewer.trim().is_empty() {
+        return Err("reviewer is required".to_string());
+    }
+    if reason.trim().is_empty() {
+        return Err("reason is required".to_string());
+    }
+    if previous == level {
+        return Err(format!("trust level is already {level}"));
+    }
+    Ok(())
+}
+
 /// Handles one HTTP connection and writes a JSON response.
 fn handle_connection(
     stream: &mut TcpStream,
@@ -797,7 +910,21 @@ fn handle_connection(
     state: &ApiState,
 ) -> io::Result<()> {
     stream.set_read_timeout(Some(Duration::from_secs(2)))?;
-    let request = read_http_request(stream)?;
+    let request = match read_http_request(stream) {
+        Ok(request) => request,
+        // `read_http_request` reports bodies over the route's limit this way.
+        Err(e) if e.kind() == io::ErrorKind::InvalidData => {
+            return write_json_response(
+                stream,
+                413,
+                &json!({
+                    "error": "request too large",
+                    "detail": e.to_string()
+                }),
+            );
+        }
+        Err(e) => return Err(e),
+    };
     if let Some((status, body)) = route_website_request(&request, registry, state) {
         return write_html_response(stream, status, &body);
     }
@@ -827,14 +954,36 @@ fn read_http_request(stream: &mut TcpStream) -> io::Result<HttpRequest> {
         return Ok(HttpRequest {
             method: String::new(),
             target: String::new(),
+            authorization: None,
             body: String::new(),
         });
     };
 
     let headers_str = String::from_utf8_lossy(&buf[..header_end]).to_string();
+    let request_line = headers_str.lines().next().unwrap_or_default();
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let target = parts.next().unwrap_or_default().to_string();
+
     let content_length = parse_content_length(&headers_str).unwrap_or(0);
     let body_start = header_end + 4;
-    while buf.len().saturating_sub(body_start) < content_length && buf.len() < MAX_REQUEST_BYTES {
+    // Batch uploads get a larger limit and are refused outright beyond it,
+    // rather than truncated into a body that fails to parse.
+    let is_batch = split_path_query(&target).0 == "/reports/batch";
+    let max_bytes = if is_batch {
+        MAX_BATCH_REQUEST_BYTES
+    } else {
+        MAX_REQUEST_BYTES
+    };
+    // A huge Content-Length must not wrap around past the limit.
+    let request_bytes = body_start.saturating_add(content_length);
+    if is_batch && request_bytes > max_bytes {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("request of {request_bytes} bytes exceeds the limit of {max_bytes} bytes"),
+        ));
+    }
+    while buf.len().saturating_sub(body_start) < content_length && buf.len() < max_bytes {
         let n = stream.read(&mut chunk)?;
         if n == 0 {
             break;
@@ -842,11 +991,6 @@ fn read_http_request(stream: &mut TcpStream) -> io::Result<HttpRequest> {
         buf.extend_from_slice(&chunk[..n]);
     }
 
-    let request_line = headers_str.lines().next().unwrap_or_default();
-    let mut parts = request_line.split_whitespace();
-    let method = parts.next().unwrap_or_default().to_string();
-    let target = parts.next().unwrap_or_default().to_string();
-
     let body_bytes = if body_start <= buf.len() {
         &buf[body_start..]
     } else {
@@ -857,9 +1001,16 @@ fn read_http_request(stream: &mut TcpStream) -> io::Result<HttpRequest> {
         .take(content_length)
         .collect::<String>();
 
+    let authorization = headers_str.lines().skip(1).find_map(|line| {
+        line.split_once(':')
+            .filter(|(name, _)| name.trim().eq_ignore_ascii_case("authorization"))
+            .map(|(_, value)| value.trim().to_string())
+    });
+
     Ok(HttpRequest {
         method,
         target,
+        authorization,
         body,
     })
 }
@@ -879,13 +1030,6 @@ fn parse_content_length(headers: &str) -> Option<usize> {
     None
 }
 
-fn now_epoch_secs() -> u64 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_secs()
-}
-
 /// Routes browser-facing local website paths (`/site`, `/site/reports`, `/site/submissions`, `/site/review-events`, `/site/publishers`, `/site/analytics`, `/site/publishers/<maintainer>`, `/site/submissions/<id>`, `/site/servers/<name>`).
 fn route_website_request(
     request: &HttpRequest,
@@ -2240,6 +2384,8 @@ fn render_site_analytics_page(query: Option<&str>, state: &ApiState) -> String {
     let earliest = summary["earliestEventEpochSecs"].as_u64().unwrap_or(0);
     let latest = summary["latestEventEpochSecs"].as_u64().unwrap_or(0);
     let malformed_lines = payload["malformedLines"].as_u64().unwrap_or(0);
+    let queued = summary["proxyQueuedRequests"].as_u64().unwrap_or(0);
+    let queue_timeouts = summary["proxyQueueTimeouts"].as_u64().unwrap_or(0);
     let top_actions = summary["topActions"]
         .as_array()
         .cloned()
@@ -2263,6 +2409,12 @@ fn render_site_analytics_page(query: Option<&str>, state: &ApiState) -> String {
             earliest, latest
         ));
     }
+    if queued > 0 || queue_timeouts > 0 {
+        content.push_str(&format!(
+            "<p class=\"meta\"><span>proxy queued {}</span><span>queue timeouts {}</span></p>",
+            queued, queue_timeouts
+        ));
+    }
     content.push_str("</section>");
 
     content.push_str("<section class=\"panel\">");
@@ -2811,9 +2963,10 @@ fn render_site_detail_page(
     content.push_str("<h2>Security Summary</h2>");
     content.push_str("<p class=\"meta\">");
     content.push_str(&format!(
-        "<span>permissions {}</span><span>network wildcard {}</span><span>filesystem write {}</span>",
+        "<span>permissions {}</span><span>network wildcard {}</span><span>broad network {}</span><span>filesystem write {}</span>",
         permissions["total"].as_u64().unwrap_or(0),
         permissions["network"]["wildcard"].as_bool().unwrap_or(false),
+        permissions["network"]["broad"].as_u64().unwrap_or(0),
         permissions["filesystem"]["hasWriteAccess"]
             .as_bool()
             .unwrap_or(false)
@@ -2823,6 +2976,27 @@ fn render_site_detail_page(
     content.push_str("</section>");
 
     let escaped_server_name = html_escape(&server.name);
+    content.push_str(&format!(
+        "<section class=\"panel\" data-detail-tabs=\"{escaped_server_name}\">"
+    ));
+    content.push_str("<h2>Inspect</h2>");
+    content.push_str("<div class=\"tab-list\" role=\"tablist\">");
+    for (tab, label) in [
+        ("manifest", "Manifest (TOML)"),
+        ("json", "JSON API"),
+        ("versions", "Version History"),
+    ] {
+        content.push_str(&format!(
+            "<button type=\"button\" class=\"tab-btn\" role=\"tab\" data-detail-tab=\"{tab}\">{label}</button>"
+        ));
+    }
+    content.push_str("</div>");
+    content.push_str(
+        "<p class=\"meta\" data-detail-status>Choose a view to load it from the registry API.</p>",
+    );
+    content.push_str("<pre class=\"detail-view\" data-detail-view hidden><code></code></pre>");
+    content.push_str("</section>");
+
     content.push_str("<section class=\"panel\">");
     content.push_str("<h2>Community</h2>");
     content.push_str(&format!(
@@ -2923,7 +3097,20 @@ fn render_site_shell(title: &str, content: &str) -> String {
     page.push_str("<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">");
     page.push_str("<title>");
     page.push_str(&html_escape(title));
-    page.push_str("</title><style>");
+    page.push_str("</title><script>");
+    // Applied before first paint so a saved dark theme does not flash light.
+    page.push_str(
+        r#"
+try {
+  const savedTheme = localStorage.getItem("berth-site-theme");
+  const prefersDark = window.matchMedia("(prefers-color-scheme: dark)").matches;
+  if (savedTheme === "dark" || (!savedTheme && prefersDark)) {
+    document.documentElement.setAttribute("data-theme", "dark");
+  }
+} catch (_) {}
+"#,
+    );
+    page.push_str("</script><style>");
     page.push_str(
         r#"
 :root {
@@ -2935,6 +3122,16 @@ fn render_site_shell(title: &str, content: &str) -> String {
   --accent-soft: #d6efdf;
   --line: #c3d6c9;
 }
+:root[data-theme="dark"] {
+  --bg: #0e1712;
+  --surface: #16231b;
+  --ink: #dcebe1;
+  --muted: #93ab9d;
+  --accent: #2f9e63;
+  --accent-soft: #1f4a33;
+  --line: #2d4236;
+  color-scheme: dark;
+}
 * { box-sizing: border-box; }
 body {
   margin: 0;
@@ -3134,23 +3331,153 @@ textarea {
 .perm-list li { margin: 0.2rem 0; }
 .related-list { padding-left: 1rem; }
 .related-list li { margin: 0.7rem 0; }
+.tab-list {
+  display: flex;
+  gap: 0.45rem;
+  flex-wrap: wrap;
+}
+.tab-btn {
+  background: var(--accent-soft);
+  color: var(--ink);
+  font-weight: 400;
+}
+.tab-btn.active {
+  background: var(--accent);
+  color: #f4fff8;
+  font-weight: 700;
+}
+.detail-view {
+  margin: 0.7rem 0 0;
+  max-height: 32rem;
+  overflow: auto;
+}
+.detail-view code {
+  display: block;
+  white-space: pre;
+}
 .empty {
   padding: 1rem;
   border-radius: 12px;
   border: 1px dashed var(--line);
   background: #f8fef6;
 }
-@media (max-width: 720px) {
+.theme-toggle {
+  float: right;
+  margin: 0 0 0.6rem 0.6rem;
+  background: var(--surface);
+  color: var(--ink);
+  border: 1px solid var(--line);
+  font-weight: 400;
+}
+[data-theme="dark"] body {
+  background: radial-gradient(circle at 20% 0%, #16261c 0, var(--bg) 40%, #09110c 100%);
+}
+[data-theme="dark"] a { color: #7fd3a3; }
+[data-theme="dark"] .hero {
+  background: linear-gradient(140deg, #0b1f14 0%, #0d3b23 50%, #17603b 100%);
+  box-shadow: 0 16px 40px rgba(0, 0, 0, 0.4);
+}
+[data-theme="dark"] .hero a { color: #b8f0cf; }
+[data-theme="dark"] .metric,
+[data-theme="dark"] .empty,
+[data-theme="dark"] code {
+  background: #111d16;
+  border-color: var(--line);
+}
+[data-theme="dark"] input,
+[data-theme="dark"] select,
+[data-theme="dark"] textarea {
+  background: #111d16;
+  color: var(--ink);
+}
+[data-theme="dark"] .description { color: #c2d8ca; }
+[data-theme="dark"] .badge { background: #22303f; }
+[data-theme="dark"] .badge-verified { color: #a6e9c2; }
+[data-theme="dark"] .pagination a { background: #1f3a2a; }
+[data-theme="dark"] .pagination-disabled {
+  color: #66806f;
+  background: #131f18;
+}
+[data-theme="dark"] .card { box-shadow: 0 6px 18px rgba(0, 0, 0, 0.35); }
+@media (max-width: 720px) {
   .install-row { flex-direction: column; align-items: stretch; }
   button { width: 100%; }
 }
 "#,
     );
     page.push_str("</style></head><body><main class=\"page\">");
+    page.push_str(
+        "<button type=\"button\" class=\"theme-toggle\" data-theme-toggle>Dark mode</button>",
+    );
     page.push_str(content);
     page.push_str("</main><script>");
     page.push_str(
         r#"
+function readPreference(key) {
+  try {
+    return localStorage.getItem(key);
+  } catch (_) {
+    return null;
+  }
+}
+
+function writePreference(key, value) {
+  try {
+    localStorage.setItem(key, value);
+  } catch (_) {}
+}
+
+const themeToggle = document.querySelector("[data-theme-toggle]");
+if (themeToggle) {
+  const syncThemeLabel = () => {
+    const dark = document.documentElement.getAttribute("data-theme") === "dark";
+    themeToggle.textContent = dark ? "Light mode" : "Dark mode";
+  };
+  syncThemeLabel();
+  themeToggle.addEventListener("click", () => {
+    const dark = document.documentElement.getAttribute("data-theme") !== "dark";
+    if (dark) {
+      document.documentElement.setAttribute("data-theme", "dark");
+    } else {
+      document.documentElement.removeAttribute("data-theme");
+    }
+    writePreference("berth-site-theme", dark ? "dark" : "light");
+    syncThemeLabel();
+  });
+}
+
+// Saved sort and page size only fill in what the URL leaves unset, so shared
+// links keep rendering exactly what they name.
+const preferenceFields = { sortBy: "berth-site-sort", order: "berth-site-order", limit: "berth-site-page-size" };
+const filterForm = document.querySelector("form.filters");
+if (filterForm) {
+  const params = new URLSearchParams(window.location.search);
+  let changed = false;
+  for (const [name, key] of Object.entries(preferenceFields)) {
+    const field = filterForm.querySelector(`[name='${name}']`);
+    const saved = readPreference(key);
+    if (!field || !saved || params.has(name)) continue;
+    // Searches rank by relevance unless the user picks a sort explicitly.
+    if (name !== "limit" && params.get("q")) continue;
+    if (field.tagName === "SELECT" && !field.querySelector(`option[value='${CSS.escape(saved)}']`)) continue;
+    if (name === "limit" && !(Number(saved) >= 1 && Number(saved) <= Number(field.max || saved))) continue;
+    params.set(name, saved);
+    changed = true;
+  }
+  if (changed) {
+    window.location.replace(`${window.location.pathname}?${params.toString()}`);
+  }
+  filterForm.addEventListener("submit", () => {
+    const queryField = filterForm.querySelector("[name='q']");
+    const searching = Boolean(queryField && queryField.value.trim());
+    for (const [name, key] of Object.entries(preferenceFields)) {
+      const field = filterForm.querySelector(`[name='${name}']`);
+      if (name !== "limit" && searching) continue;
+      if (field && field.value) writePreference(key, field.value);
+    }
+  });
+}
+
 for (const button of document.querySelectorAll(".copy-btn")) {
   button.addEventListener("click", async () => {
     const text = button.getAttribute("data-copy") || "";
@@ -3282,6 +3609,55 @@ if (reportForm) {
   });
 }
 
+const detailTabs = document.querySelector("[data-detail-tabs]");
+if (detailTabs) {
+  const server = encodeURIComponent(detailTabs.getAttribute("data-detail-tabs") || "");
+  const view = detailTabs.querySelector("[data-detail-view]");
+  const viewCode = view ? view.querySelector("code") : null;
+  const status = detailTabs.querySelector("[data-detail-status]");
+  const sources = {
+    manifest: { path: `/servers/${server}/manifest`, render: (payload) => payload.manifest || "" },
+    json: { path: `/servers/${server}`, render: (payload) => JSON.stringify(payload, null, 2) },
+    versions: {
+      path: `/servers/${server}/versions`,
+      render: (payload) => {
+        const lines = [`${payload.current}  published`];
+        for (const entry of payload.submissions || []) {
+          const submitted = new Date(Number(entry.submittedAtEpochSecs) * 1000).toISOString();
+          lines.push(`${entry.version}  ${entry.status}  submitted ${submitted}  ${entry.submissionId}`);
+        }
+        return lines.join("\n");
+      }
+    }
+  };
+  const loaded = {};
+  for (const tab of detailTabs.querySelectorAll("[data-detail-tab]")) {
+    tab.addEventListener("click", async () => {
+      const name = tab.getAttribute("data-detail-tab");
+      const source = sources[name];
+      if (!source || !view || !viewCode) return;
+      for (const other of detailTabs.querySelectorAll("[data-detail-tab]")) {
+        other.classList.toggle("active", other === tab);
+        other.setAttribute("aria-selected", other === tab ? "true" : "false");
+      }
+      try {
+        if (loaded[name] === undefined) {
+          if (status) status.textContent = `Loading ${source.path} ...`;
+          const response = await fetch(source.path);
+          if (!response.ok) throw new Error();
+          loaded[name] = source.render(await response.json());
+        }
+        viewCode.textContent = loaded[name];
+        view.hidden = false;
+        if (status) status.textContent = `Source: GET ${source.path}`;
+      } catch (_) {
+        view.hidden = true;
+        if (status) status.textContent = `Failed to load ${source.path}`;
+      }
+    });
+  }
+}
+
 for (const button of document.querySelectorAll(".submission-status-btn")) {
   button.addEventListener("click", async () => {
     const submissionId = button.getAttribute("data-submission-id");
@@ -3579,6 +3955,15 @@ fn route_request(request: &HttpRequest, registry: &Registry, state: &ApiState) -
         );
     }
 
+    if method == "POST" && !state.is_authorized(request) {
+        return (
+            401,
+            json!({
+                "error": "unauthorized"
+            }),
+        );
+    }
+
     let (path, query) = split_path_query(target);
     if method == "OPTIONS" {
         return (
@@ -3721,6 +4106,17 @@ fn route_request(request: &HttpRequest, registry: &Registry, state: &ApiState) -
             }
             route_report_filters(state)
         }
+        "/reports/batch" => {
+            if method != "POST" {
+                return (
+                    405,
+                    json!({
+                        "error": "method not allowed"
+                    }),
+                );
+            }
+            route_reports_batch(request.body.trim(), registry, state)
+        }
         "/reports" => {
             if method != "GET" {
                 return (
@@ -3743,6 +4139,17 @@ fn route_request(request: &HttpRequest, registry: &Registry, state: &ApiState) -
             }
             route_analytics(query, state)
         }
+        "/audit" => {
+            if method != "GET" {
+                return (
+                    405,
+                    json!({
+                        "error": "method not allowed"
+                    }),
+                );
+            }
+            route_audit(query, state)
+        }
         "/stats" => {
             if method != "GET" {
                 return (
@@ -3842,6 +4249,26 @@ fn route_request(request: &HttpRequest, registry: &Registry, state: &ApiState) -
             }
             route_unverify_publisher(request.body.trim(), state)
         }
+        "/index.json" => {
+            if method != "GET" {
+                return (
+                    405,
+                    json!({
+                        "error": "method not allowed"
+                    }),
+                );
+            }
+            match state.index_servers(registry) {
+                Ok(servers) => (200, json!(servers)),
+                Err(e) => (
+                    500,
+                    json!({
+                        "error": "internal error",
+                        "detail": e
+                    }),
+                ),
+            }
+        }
         "/servers" => {
             if method != "GET" {
                 return (
@@ -3861,6 +4288,22 @@ fn route_request(request: &HttpRequest, registry: &Registry, state: &ApiState) -
                 .filter(|v| !v.trim().is_empty());
             let offset = parse_usize_param(query, "offset").unwrap_or(0);
             let limit = parse_usize_param(query, "limit");
+            let after = match query_param(query, "cursor")
+                .filter(|v| !v.trim().is_empty())
+                .map(Cursor::decode)
+                .transpose()
+            {
+                Ok(after) => after,
+                Err(detail) => {
+                    return (
+                        400,
+                        json!({
+                            "error": "invalid cursor",
+                            "detail": detail
+                        }),
+                    );
+                }
+            };
             let sort_by = match parse_sort_by(query, query_value) {
                 Ok(sort_by) => sort_by,
                 Err(detail) => {
@@ -3934,16 +4377,30 @@ fn route_request(request: &HttpRequest, registry: &Registry, state: &ApiState) -
                 .collect::<Vec<_>>();
             listed.sort_by(|left, right| compare_listed_servers(left, right, sort_by, sort_order));
             let total = listed.len();
-            let sliced = if let Some(limit) = limit {
-                listed
-                    .into_iter()
-                    .skip(offset)
-                    .take(limit)
-                    .collect::<Vec<_>>()
-            } else {
-                listed.into_iter().skip(offset).collect::<Vec<_>>()
-            };
-            let servers = sliced
+            let ordering = format!("{}:{}", sort_by.as_str(), sort_order.as_str());
+            if after
+                .as_ref()
+                .is_some_and(|after| after.ordering != ordering)
+            {
+                return (
+                    400,
+                    json!({
+                        "error": "invalid cursor",
+                        "detail": format!("cursor was not issued for ordering {ordering}")
+                    }),
+                );
+            }
+            let offset = if after.is_some() { 0 } else { offset };
+            let page = paginate(
+                listed.into_iter().skip(offset).collect(),
+                after.as_ref(),
+                limit.unwrap_or(usize::MAX),
+                |entry| listed_server_cursor(entry, sort_by, &ordering),
+                |left, right| compare_cursors(left, right, sort_order),
+            );
+            let next_cursor = page.next_cursor.as_ref().map(Cursor::encode);
+            let servers = page
+                .items
                 .into_iter()
                 .map(|entry| {
                     let mut summary = server_summary(
@@ -3981,6 +4438,7 @@ fn route_request(request: &HttpRequest, registry: &Registry, state: &ApiState) -
                     "count": count,
                     "offset": offset,
                     "limit": limit,
+                    "nextCursor": next_cursor,
                     "servers": servers
                 }),
             )
@@ -4280,6 +4738,135 @@ fn route_reports(query: Option<&str>, state: &ApiState) -> (u16, Value) {
     }
 }
 
+/// Records up to [`MAX_REPORT_BATCH_ITEMS`] reports at once, e.g. synced from
+/// another registry instance. Every item is validated first and nothing is
+/// recorded unless all of them pass; the response has one result per item.
+fn route_reports_batch(body: &str, registry: &Registry, state: &ApiState) -> (u16, Value) {
+    let payload = match serde_json::from_str::<ReportBatchPayload>(body) {
+        Ok(payload) => payload,
+        Err(e) => {
+            return (
+                400,
+                json!({
+                    "error": "invalid json body",
+                    "detail": e.to_string()
+                }),
+            );
+        }
+    };
+    if payload.reports.is_empty() {
+        return (
+            400,
+            json!({
+                "error": "report batch is empty"
+            }),
+        );
+    }
+    if payload.reports.len() > MAX_REPORT_BATCH_ITEMS {
+        return (
+            413,
+            json!({
+                "error": "report batch too large",
+                "detail": format!(
+                    "{} reports exceed the limit of {MAX_REPORT_BATCH_ITEMS} per batch",
+                    payload.reports.len()
+                ),
+                "maxItems": MAX_REPORT_BATCH_ITEMS
+            }),
+        );
+    }
+
+    let now = now_epoch_secs();
+    let validated = payload
+        .reports
+        .into_iter()
+        .map(|item| validate_batch_report(item, registry, now))
+        .collect::<Vec<_>>();
+    let rejected = validated.iter().filter(|item| item.is_err()).count();
+    let results = |valid_status: &str| {
+        validated
+            .iter()
+            .enumerate()
+            .map(|(index, item)| match item {
+                Ok(event) => json!({
+                    "index": index,
+                    "status": valid_status,
+                    "server": event.server
+                }),
+                Err(detail) => json!({
+                    "index": index,
+                    "status": "invalid",
+                    "error": detail
+                }),
+            })
+            .collect::<Vec<_>>()
+    };
+    if rejected > 0 {
+        return (
+            400,
+            json!({
+                "error": "invalid report batch",
+                "accepted": 0,
+                "rejected": rejected,
+                "results": results("valid")
+            }),
+        );
+    }
+
+    let events = validated.iter().flatten().collect::<Vec<_>>();
+    match state.record_reports(&events) {
+        Ok(totals) => (
+            200,
+            json!({
+                "status": "received",
+                "accepted": events.len(),
+                "rejected": 0,
+                "results": results("recorded"),
+                "reports": totals
+            }),
+        ),
+        Err(e) => (
+            500,
+            json!({
+                "error": "internal error",
+                "detail": e
+            }),
+        ),
+    }
+}
+
+/// Checks one batch item and turns it into the report event to record.
+fn validate_batch_report(
+    item: Value,
+    registry: &Registry,
+    now: u64,
+) -> Result<ReportEvent, String> {
+    let payload = serde_json::from_value::<BatchReportPayload>(item)
+        .map_err(|e| format!("invalid report: {e}"))?;
+    let server_name = payload.server.trim();
+    if server_name.is_empty() {
+        return Err("server is required".to_string());
+    }
+    let Some(server) = registry.get(server_name) else {
+        return Err(format!("unknown server `{server_name}`"));
+    };
+    let timestamp_epoch_secs = payload.timestamp_epoch_secs.unwrap_or(now);
+    if timestamp_epoch_secs > now + MAX_REPORT_CLOCK_SKEW_SECS {
+        return Err("timestampEpochSecs is in the future".to_string());
+    }
+    let reason = if payload.reason.trim().is_empty() {
+        "unspecified".to_string()
+    } else {
+        payload.reason.trim().to_string()
+    };
+    Ok(ReportEvent {
+        timestamp_epoch_secs,
+        server: server.name.clone(),
+        reason,
+        details: payload.details.trim().to_string(),
+    })
+}
+
 fn route_report_filters(state: &ApiState) -> (u16, Value) {
     match state.list_all_reports() {
         Ok(reports) => {
@@ -4618,6 +5205,90 @@ fn route_analytics(query: Option<&str>, state: &ApiState) -> (u16, Value) {
     }
 }
 
+/// Pages through local audit entries with optional `server`, `action`,
+/// `pid`, and `since`/`until` (durations before now) filters.
+fn route_audit(query: Option<&str>, state: &ApiState) -> (u16, Value) {
+    let text_param = |key: &str| {
+        query_param(query, key)
+            .map(url_decode)
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+    let server_filter = text_param("server");
+    let action_filter = text_param("action");
+    let limit = parse_usize_param(query, "limit").unwrap_or(50).min(500);
+    let offset = parse_usize_param(query, "offset").unwrap_or(0);
+    let pid = match text_param("pid").map(|raw| raw.parse::<u32>()) {
+        Some(Ok(pid)) => Some(pid),
+        Some(Err(_)) => {
+            return (
+                400,
+                json!({
+                    "error": "invalid pid filter",
+                    "detail": "pid must be a process ID"
+                }),
+            );
+        }
+        None => None,
+    };
+    let now = now_epoch_ms();
+    let mut bounds = [None, None];
+    for (bound, key) in bounds.iter_mut().zip(["since", "until"]) {
+        if let Some(raw) = text_param(key) {
+            match parse_since(&raw) {
+                Ok(secs) => *bound = Some(now.saturating_sub(secs.saturating_mul(1_000))),
+                Err(detail) => {
+                    return (
+                        400,
+                        json!({
+                            "error": format!("invalid {key} filter"),
+                            "detail": detail
+                        }),
+                    );
+                }
+            }
+        }
+    }
+    let [since_epoch_ms, until_epoch_ms] = bounds;
+
+    let audit_query = AuditQuery {
+        server: server_filter.clone(),
+        action: action_filter.clone(),
+        pid,
+        since_epoch_ms,
+        until_epoch_ms,
+        offset,
+        limit: Some(limit),
+    };
+    match AuditLog::new(state.audit_log_path()).query(&audit_query) {
+        Ok(page) => (
+            200,
+            json!({
+                "filters": {
+                    "server": server_filter,
+                    "action": action_filter,
+                    "pid": pid,
+                    "since": text_param("since"),
+                    "until": text_param("until")
+                },
+                "total": page.total,
+                "offset": offset,
+                "limit": limit,
+                "count": page.entries.len(),
+                "entries": page.entries,
+                "malformedLines": page.malformed_lines
+            }),
+        ),
+        Err(e) => (
+            500,
+            json!({
+                "error": "internal error",
+                "detail": e.to_string()
+            }),
+        ),
+    }
+}
+
 fn route_publishers(query: Option<&str>, registry: &Registry, state: &ApiState) -> (u16, Value) {
     let limit = parse_usize_param(query, "limit").unwrap_or(25).min(200);
     let offset = parse_usize_param(query, "offset").unwrap_or(0);
@@ -4765,7 +5436,7 @@ fn route_publisher_detail(
 
 fn route_publish_submission_detail(raw_submission_id: &str, state: &ApiState) -> (u16, Value) {
     let submission_id = url_decode(raw_submission_id);
-    if !is_safe_submission_id(&submission_id) {
+    if !publish_queue::is_valid_id(&submission_id) {
         return (
             400,
             json!({
@@ -4805,7 +5476,7 @@ fn route_update_publish_submission_status(
     state: &ApiState,
 ) -> (u16, Value) {
     let submission_id = url_decode(raw_submission_id);
-    if !is_safe_submission_id(&submission_id) {
+    if !publish_queue::is_valid_id(&submission_id) {
         return (
             400,
             json!({
@@ -5378,7 +6049,37 @@ fn compare_listed_servers(
     }
 }
 
-/// Routes `/servers/<name>` detail/community/star/report/reports/related paths.
+/// Returns the pagination cursor of a list entry under `sort_by`.
+fn listed_server_cursor(entry: &ListedServer<'_>, sort_by: SortBy, ordering: &str) -> Cursor {
+    let key = match sort_by {
+        SortBy::Relevance => u64::from(entry.search_score.unwrap_or(0)),
+        SortBy::Name => 0,
+        SortBy::Downloads => entry.server.quality.downloads,
+        SortBy::Stars => entry.stars,
+        SortBy::Reports => entry.reports,
+        SortBy::QualityScore => u64::from(entry.quality_score),
+    };
+    Cursor {
+        ordering: ordering.to_string(),
+        key,
+        name: entry.server.name.clone(),
+    }
+}
+
+/// Compares cursors the same way [`compare_listed_servers`] orders entries.
+fn compare_cursors(left: &Cursor, right: &Cursor, order: SortOrder) -> std::cmp::Ordering {
+    let asc = left
+        .key
+        .cmp(&right.key)
+        .then_with(|| left.name.cmp(&right.name));
+    if order == SortOrder::Asc {
+        asc
+    } else {
+        asc.reverse()
+    }
+}
+
+/// Routes `/servers/<name>` detail/community/star/report/reports/related/trust paths.
 fn route_server_detail(
     method: &str,
     path: &str,
@@ -5482,6 +6183,28 @@ fn route_server_detail(
                 }),
             )
         }
+        Some("manifest") => {
+            if method != "GET" {
+                return (
+                    405,
+                    json!({
+                        "error": "method not allowed"
+                    }),
+                );
+            }
+            route_server_manifest(server)
+        }
+        Some("versions") => {
+            if method != "GET" {
+                return (
+                    405,
+                    json!({
+                        "error": "method not allowed"
+                    }),
+                );
+            }
+            route_server_versions(server, state)
+        }
         Some("community") => {
             if method != "GET" {
                 return (
@@ -5537,6 +6260,16 @@ fn route_server_detail(
             }
             route_server_reports(server, query, state)
         }
+        Some("trust") => match method {
+            "GET" => route_server_trust(server, state),
+            "POST" => route_server_change_trust(server, body, state),
+            _ => (
+                405,
+                json!({
+                    "error": "method not allowed"
+                }),
+            ),
+        },
         _ => (
             404,
             json!({
@@ -5546,6 +6279,63 @@ fn route_server_detail(
     }
 }
 
+/// Renders the registry entry as the TOML manifest `berth install` reads.
+fn route_server_manifest(server: &ServerMetadata) -> (u16, Value) {
+    match toml::to_string_pretty(server) {
+        Ok(manifest) => (
+            200,
+            json!({
+                "server": server.name,
+                "format": "toml",
+                "manifest": manifest
+            }),
+        ),
+        Err(e) => (
+            500,
+            json!({
+                "error": "internal error",
+                "detail": format!("failed to render manifest: {e}")
+            }),
+        ),
+    }
+}
+
+/// Lists the published version alongside every submitted version, newest first.
+fn route_server_versions(server: &ServerMetadata, state: &ApiState) -> (u16, Value) {
+    match state.list_publish_submissions() {
+        Ok(submissions) => {
+            let submitted = submissions
+                .into_iter()
+                .filter(|submission| submission.server.name == server.name)
+                .map(|submission| {
+                    json!({
+                        "version": submission.server.version,
+                        "status": submission.status,
+                        "submittedAtEpochSecs": submission.submitted_at_epoch_secs,
+                        "submissionId": submission.id
+                    })
+                })
+                .collect::<Vec<_>>();
+            (
+                200,
+                json!({
+                    "server": server.name,
+                    "current": server.version,
+                    "count": submitted.len(),
+                    "submissions": submitted
+                }),
+            )
+        }
+        Err(e) => (
+            500,
+            json!({
+                "error": "internal error",
+                "detail": e
+            }),
+        ),
+    }
+}
+
 fn route_server_community(server_name: &str, state: &ApiState) -> (u16, Value) {
     match state.community_counts(server_name) {
         Ok((stars, reports)) => (
@@ -5781,6 +6571,104 @@ fn route_server_reports(
     }
 }
 
+/// Returns the current trust level of a server and its reviewed changes.
+fn route_server_trust(server: &ServerMetadata, state: &ApiState) -> (u16, Value) {
+    match (
+        state.trust_level(server),
+        state.list_trust_events(&server.name),
+    ) {
+        (Ok(level), Ok(events)) => (
+            200,
+            json!({
+                "server": server.name,
+                "trustLevel": level,
+                "indexTrustLevel": server.trust_level,
+                "count": events.len(),
+                "events": events
+            }),
+        ),
+        (Err(e), _) | (_, Err(e)) => (
+            500,
+            json!({
+                "error": "internal error",
+                "detail": e
+            }),
+        ),
+    }
+}
+
+/// Records a reviewed trust-level change from a `level`/`reviewer`/`reason` body.
+fn route_server_change_trust(
+    server: &ServerMetadata,
+    body: &str,
+    state: &ApiState,
+) -> (u16, Value) {
+    let payload = match serde_json::from_str::<TrustChangePayload>(body) {
+        Ok(payload) => payload,
+        Err(e) => {
+            return (
+                400,
+                json!({
+                    "error": "invalid json body",
+                    "detail": e.to_string()
+                }),
+            );
+        }
+    };
+    let level = match payload.level.parse::<TrustLevel>() {
+        Ok(level) => level,
+        Err(detail) => {
+            return (
+                400,
+                json!({
+                    "error": "invalid trust level",
+                    "detail": detail
+                }),
+            );
+        }
+    };
+    let previous = match state.trust_level(server) {
+        Ok(previous) => previous,
+        Err(e) => {
+            return (
+                500,
+                json!({
+                    "error": "internal error",
+                    "detail": e
+                }),
+            );
+        }
+    };
+    if let Err(detail) =
+        validate_trust_change(&previous, &level, &payload.reviewer, &payload.reason)
+    {
+        return (
+            400,
+            json!({
+                "error": "invalid trust change",
+                "detail": detail
+            }),
+        );
+    }
+    match state.change_trust_level(server, level, &payload.reviewer, &payload.reason) {
+        Ok(event) => (
+            200,
+            json!({
+                "server": server.name,
+                "status": "recorded",
+                "event": event
+            }),
+        ),
+        Err(e) => (
+            500,
+            json!({
+                "error": "internal error",
+                "detail": e
+            }),
+        ),
+    }
+}
+
 /// Splits request target into path and optional query string.
 fn split_path_query(target: &str) -> (&str, Option<&str>) {
     if let Some((path, query)) = target.split_once('?') {
@@ -5802,36 +6690,6 @@ fn query_param<'a>(query: Option<&'a str>, key: &str) -> Option<&'a str> {
     None
 }
 
-fn is_safe_submission_id(value: &str) -> bool {
-    !value.is_empty()
-        && value.ends_with(".json")
-        && !value.contains('/')
-        && !value.contains('\\')
-        && !value.contains("..")
-}
-
-fn publish_submission_summary_from_queue_file(
-    id: String,
-    payload: QueueSubmissionFile,
-) -> PublishSubmissionSummary {
-    let quality_checks_passed = payload.quality_checks.iter().filter(|c| c.passed).count();
-    let quality_checks_total = payload.quality_checks.len();
-    PublishSubmissionSummary {
-        id,
-        submitted_at_epoch_secs: payload.submitted_at_epoch_secs,
-        status: payload.status,
-        server: PublishSubmissionServerSummary {
-            name: payload.manifest.server.name,
-            display_name: payload.manifest.server.display_name,
-            version: payload.manifest.server.version,
-            maintainer: payload.manifest.server.maintainer,
-            category: payload.manifest.server.category,
-        },
-        quality_checks_passed,
-        quality_checks_total,
-    }
-}
-
 /// Produces a normalized maintainer identifier for reliable comparisons.
 fn normalize_maintainer(maintainer: &str) -> String {
     maintainer
@@ -6043,16 +6901,35 @@ fn related_server_score(
 /// Builds a normalized permission summary for website rendering.
 fn permissions_summary(server: &ServerMetadata) -> Value {
     let has_wildcard = |entries: &[String]| entries.iter().any(|entry| entry.trim() == "*");
-    let has_filesystem_write = server
+    let has_filesystem_write = grants_filesystem_write(&server.permissions.filesystem);
+    let broad_network = server
+        .permissions
+        .network
+        .iter()
+        .filter(|entry| {
+            entry
+                .trim()
+                .parse::<NetworkPermission>()
+                .is_ok_and(|permission| permission.is_broad())
+        })
+        .count();
+    let broad_filesystem = server
         .permissions
         .filesystem
         .iter()
-        .any(|entry| entry.trim() == "*" || entry.trim_start().starts_with("write:"));
+        .filter(|entry| {
+            entry
+                .trim()
+                .parse::<FsPermission>()
+                .is_ok_and(|permission| permission.is_broad())
+        })
+        .count();
 
     json!({
         "network": {
             "count": server.permissions.network.len(),
-            "wildcard": has_wildcard(&server.permissions.network)
+            "wildcard": has_wildcard(&server.permissions.network),
+            "broad": broad_network
         },
         "env": {
             "count": server.permissions.env.len(),
@@ -6061,6 +6938,7 @@ fn permissions_summary(server: &ServerMetadata) -> Value {
         "filesystem": {
             "count": server.permissions.filesystem.len(),
             "wildcard": has_wildcard(&server.permissions.filesystem),
+            "broad": broad_filesystem,
             "hasWriteAccess": has_filesystem_write
         },
         "exec": {
@@ -6227,8 +7105,10 @@ fn write_http_response(
     let reason = match status {
         200 => "OK",
         400 => "Bad Request",
+        401 => "Unauthorized",
         404 => "Not Found",
         405 => "Method Not Allowed",
+        413 => "Payload Too Large",
         _ => "Internal Server Error",
     };
 
@@ -6250,6 +7130,7 @@ fn write_http_response(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::time::{SystemTime, UNIX_EPOCH};
 
     fn test_state() -> ApiState {
         let unique = format!(
@@ -6318,9 +7199,75 @@ mod tests {
         HttpRequest {
             method: method.to_string(),
             target: target.to_string(),
+            authorization: None,
             body: String::new(),
         }
     }
+    #[test]
+    fn auth_token_guards_post_requests_only() {
+        let tmp = tempfile::tempdir().unwrap();
+        let registry = Registry::from_seed();
+        let state = ApiState::new(tmp.path().join("community"), tmp.path().join("queue"))
+            .with_auth_token(Some("secret".to_string()));
+        let mut star = req("POST", "/servers/github/star");
+        let (status, _) = route_request(&star, &registry, &state);
+        assert_eq!(status, 401);
+
+        star.authorization = Some("Bearer secret".to_string());
+        let (status, _) = route_request(&star, &registry, &state);
+        assert_eq!(status, 200);
+
+        let (status, body) = route_request(&req("GET", "/index.json"), &registry, &state);
+        assert_eq!(status, 200);
+        assert_eq!(
+            body.as_array().map(Vec::len),
+            Some(registry.list_all().len())
+        );
+    }
+
+    #[test]
+    fn trust_changes_are_recorded_and_reflected_in_the_index() {
+        let tmp = tempfile::tempdir().unwrap();
+        let registry = Registry::from_seed();
+        let state = ApiState::new(tmp.path().join("community"), tmp.path().join("queue"));
+        let original = registry.get("github").unwrap().trust_level.clone();
+        let mut change = req("POST", "/servers/github/trust");
+
+        change.body = r#"{"level": "verified", "reviewer": "alice"}"#.to_string();
+        let (status, body) = route_request(&change, &registry, &state);
+        assert_eq!(status, 400, "{body}");
+        assert_eq!(body["detail"], "reason is required");
+
+        change.body = r#"{"level": "trusted", "reviewer": "alice", "reason": "x"}"#.to_string();
+        assert_eq!(route_request(&change, &registry, &state).0, 400);
+
+        change.body =
+            r#"{"level": "untrusted", "reviewer": "alice", "reason": "leaked token"}"#.to_string();
+        let (status, body) = route_request(&change, &registry, &state);
+        assert_eq!(status, 200, "{body}");
+        assert_eq!(body["event"]["previousLevel"], json!(original));
+        assert_eq!(body["event"]["reviewer"], "alice");
+
+        let (_, index) = route_request(&req("GET", "/index.json"), &registry, &state);
+        let entry = index
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|server| server["name"] == "github")
+            .unwrap();
+        assert_eq!(entry["trustLevel"], "untrusted");
+
+        let (status, body) = route_request(&change, &registry, &state);
+        assert_eq!(status, 400);
+        assert_eq!(body["detail"], "trust level is already untrusted");
+
+        let (status, trust) =
+            route_request(&req("GET", "/servers/github/trust"), &registry, &state);
+        assert_eq!(status, 200);
+        assert_eq!(trust["trustLevel"], "untrusted");
+        assert_eq!(trust["count"], 1);
+        assert_eq!(trust["events"][0]["reason"], "leaked token");
+    }
 
     #[test]
     fn split_path_query_parses_query() {
@@ -6361,6 +7308,10 @@ mod tests {
         assert!(catalog.contains("/site/review-events"));
         assert!(catalog.contains("/site/publishers"));
         assert!(catalog.contains("/site/analytics"));
+        assert!(catalog.contains("data-theme-toggle"));
+        assert!(catalog.contains(":root[data-theme=\"dark\"]"));
+        assert!(catalog.contains("berth-site-theme"));
+        assert!(catalog.contains("berth-site-page-size"));
 
         let (detail_status, detail) =
             route_website_request(&req("GET", "/site/servers/github"), &registry, &state).unwrap();
@@ -6371,10 +7322,14 @@ mod tests {
         assert!(detail.contains("Star this server"));
         assert!(detail.contains("Recent Reports"));
         assert!(detail.contains("data-report-list"));
+        assert!(detail.contains("data-detail-tabs=\"github\""));
+        assert!(detail.contains("data-detail-tab=\"manifest\""));
+        assert!(detail.contains("/servers/${server}/versions"));
 
         let report_req = HttpRequest {
             method: "POST".to_string(),
             target: "/servers/github/report".to_string(),
+            authorization: None,
             body: "{\"reason\":\"spam\",\"details\":\"broken output\"}".to_string(),
         };
         let (report_status, _) = route_request(&report_req, &registry, &state);
@@ -6446,6 +7401,7 @@ mod tests {
         let verify_req = HttpRequest {
             method: "POST".to_string(),
             target: "/publishers/verify".to_string(),
+            authorization: None,
             body: "{\"maintainer\":\"Anthropic\"}".to_string(),
         };
         assert_eq!(route_request(&verify_req, &registry, &state).0, 200);
@@ -6472,6 +7428,7 @@ mod tests {
         let review_update_req = HttpRequest {
             method: "POST".to_string(),
             target: "/publish/submissions/github-400.json/status".to_string(),
+            authorization: None,
             body: "{\"status\":\"approved\",\"note\":\"queue review ok\"}".to_string(),
         };
         assert_eq!(route_request(&review_update_req, &registry, &state).0, 200);
@@ -6692,6 +7649,62 @@ mod tests {
         assert_eq!(first["trustLevel"].as_str(), Some("official"));
     }
 
+    #[test]
+    fn route_request_pages_servers_with_cursors() {
+        let registry = Registry::from_seed();
+        let state = test_state();
+        let (_, all) = route_request(
+            &req("GET", "/servers?sortBy=downloads&order=desc"),
+            &registry,
+            &state,
+        );
+        assert!(all["nextCursor"].is_null());
+
+        let mut names = Vec::new();
+        let mut target = "/servers?sortBy=downloads&order=desc&limit=2".to_string();
+        loop {
+            let (status, body) = route_request(&req("GET", &target), &registry, &state);
+            assert_eq!(status, 200);
+            for server in body["servers"].as_array().unwrap() {
+                names.push(server["name"].clone());
+            }
+            let Some(cursor) = body["nextCursor"].as_str() else {
+                break;
+            };
+            target = format!("/servers?sortBy=downloads&order=desc&limit=2&cursor={cursor}");
+        }
+        let expected = all["servers"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|server| server["name"].clone())
+            .collect::<Vec<_>>();
+        assert_eq!(names, expected);
+
+        let first_name = all["servers"][0]["name"].as_str().unwrap();
+        let wrong_order = Cursor {
+            ordering: "name:asc".to_string(),
+            key: 0,
+            name: first_name.to_string(),
+        };
+        let (status, body) = route_request(
+            &req(
+                "GET",
+                &format!(
+                    "/servers?sortBy=downloads&order=desc&cursor={}",
+                    wrong_order.encode()
+                ),
+            ),
+            &registry,
+            &state,
+        );
+        assert_eq!(status, 400);
+        assert_eq!(body["error"].as_str(), Some("invalid cursor"));
+
+        let (status, _) = route_request(&req("GET", "/servers?cursor=zz"), &registry, &state);
+        assert_eq!(status, 400);
+    }
+
     #[test]
     fn route_request_supports_sorting_with_metadata() {
         let registry = Registry::from_seed();
@@ -6837,11 +7850,13 @@ mod tests {
         let report_one = HttpRequest {
             method: "POST".to_string(),
             target: "/servers/github/report".to_string(),
+            authorization: None,
             body: "{\"reason\":\"spam\",\"details\":\"bad output\"}".to_string(),
         };
         let report_two = HttpRequest {
             method: "POST".to_string(),
             target: "/servers/filesystem/report".to_string(),
+            authorization: None,
             body: "{\"reason\":\"abuse\",\"details\":\"unsafe behavior\"}".to_string(),
         };
         assert_eq!(route_request(&report_one, &registry, &state).0, 200);
@@ -6908,6 +7923,42 @@ mod tests {
         );
     }
 
+    #[test]
+    fn route_request_pages_through_audit_entries() {
+        let registry = Registry::from_seed();
+        let state = test_state();
+        seed_audit_event(&state, "github", "start", 1000);
+        seed_audit_event(&state, "slack", "start", 1001);
+        seed_audit_event(&state, "github", "stop", 1002);
+
+        let (status, body) = route_request(
+            &req("GET", "/audit?server=github&limit=1&offset=1"),
+            &registry,
+            &state,
+        );
+        assert_eq!(status, 200);
+        assert_eq!(body["total"].as_u64(), Some(2));
+        assert_eq!(body["count"].as_u64(), Some(1));
+        assert_eq!(body["entries"][0]["action"].as_str(), Some("stop"));
+        assert_eq!(
+            body["entries"][0]["timestampEpochMs"].as_u64(),
+            Some(1_002_000)
+        );
+
+        let (_, body) = route_request(
+            &req("GET", "/audit?action=start&until=1h"),
+            &registry,
+            &state,
+        );
+        assert_eq!(body["total"].as_u64(), Some(2));
+        let (_, body) = route_request(&req("GET", "/audit?since=1h"), &registry, &state);
+        assert_eq!(body["total"].as_u64(), Some(0));
+
+        let (status, body) = route_request(&req("GET", "/audit?pid=abc"), &registry, &state);
+        assert_eq!(status, 400);
+        assert_eq!(body["error"].as_str(), Some("invalid pid filter"));
+    }
+
     #[test]
     fn route_request_supports_publish_submissions_endpoint() {
         let registry = Registry::from_seed();
@@ -7012,6 +8063,41 @@ mod tests {
         assert!(body["submission"]["manifest"]["server"]["name"].is_string());
     }
 
+    #[test]
+    fn route_request_supports_server_manifest_and_versions() {
+        let registry = Registry::from_seed();
+        let state = test_state();
+        seed_publish_submission(&state, "github-100.json", 100, "approved", "github");
+        seed_publish_submission(&state, "github-200.json", 200, "pending", "github");
+        seed_publish_submission(&state, "slack-300.json", 300, "pending", "slack");
+
+        let (status, body) =
+            route_request(&req("GET", "/servers/github/manifest"), &registry, &state);
+        assert_eq!(status, 200);
+        assert_eq!(body["format"].as_str(), Some("toml"));
+        let manifest = body["manifest"].as_str().unwrap();
+        let parsed = toml::from_str::<ServerMetadata>(manifest).unwrap();
+        assert_eq!(parsed.name, "github");
+
+        let (status, body) =
+            route_request(&req("GET", "/servers/github/versions"), &registry, &state);
+        assert_eq!(status, 200);
+        assert_eq!(
+            body["current"].as_str(),
+            Some(registry.get("github").unwrap().version.as_str())
+        );
+        assert_eq!(body["count"].as_u64(), Some(2));
+        assert_eq!(
+            body["submissions"][0]["submissionId"].as_str(),
+            Some("github-200.json")
+        );
+        assert_eq!(body["submissions"][1]["status"].as_str(), Some("approved"));
+
+        let (status, _) =
+            route_request(&req("POST", "/servers/github/versions"), &registry, &state);
+        assert_eq!(status, 405);
+    }
+
     #[test]
     fn route_request_supports_publish_submission_status_updates() {
         let registry = Registry::from_seed();
@@ -7027,6 +8113,7 @@ mod tests {
         let update_request = HttpRequest {
             method: "POST".to_string(),
             target: "/publish/submissions/github-500.json/status".to_string(),
+            authorization: None,
             body: "{\"status\":\"approved\",\"note\":\"looks good\"}".to_string(),
         };
         let (update_status, update_body) = route_request(&update_request, &registry, &state);
@@ -7112,6 +8199,7 @@ mod tests {
         let invalid_request = HttpRequest {
             method: "POST".to_string(),
             target: "/publish/submissions/github-500.json/status".to_string(),
+            authorization: None,
             body: "{\"status\":\"unknown\"}".to_string(),
         };
         let (invalid_status, invalid_body) = route_request(&invalid_request, &registry, &state);
@@ -7121,6 +8209,7 @@ mod tests {
         let missing_request = HttpRequest {
             method: "POST".to_string(),
             target: "/publish/submissions/nope.json/status".to_string(),
+            authorization: None,
             body: "{\"status\":\"approved\"}".to_string(),
         };
         let (missing_status, missing_body) = route_request(&missing_request, &registry, &state);
@@ -7198,6 +8287,134 @@ mod tests {
             .any(|m| m.as_str() == Some("OPTIONS")));
     }
 
+    #[test]
+    fn report_batch_records_all_items_or_none() {
+        let registry = Registry::from_seed();
+        let state = test_state();
+        let batch = |body: Value| HttpRequest {
+            method: "POST".to_string(),
+            target: "/reports/batch".to_string(),
+            authorization: None,
+            body: body.to_string(),
+        };
+
+        let (status, body) = route_request(
+            &batch(json!({"reports": [
+                {"server": "github", "reason": "spam"},
+                {"server": "no-such-server", "reason": "spam"},
+                {"reason": "abuse"},
+                {"server": "github", "timestampEpochSecs": now_epoch_secs() + 3_600}
+            ]})),
+            &registry,
+            &state,
+        );
+        assert_eq!(status, 400);
+        assert_eq!(body["rejected"].as_u64(), Some(3));
+        assert_eq!(body["results"][0]["status"].as_str(), Some("valid"));
+        assert!(body["results"][1]["error"]
+            .as_str()
+            .unwrap()
+            .contains("unknown server"));
+        assert_eq!(
+            body["results"][2]["error"].as_str(),
+            Some("server is required")
+        );
+        assert!(body["results"][3]["error"]
+            .as_str()
+            .unwrap()
+            .contains("future"));
+        let (_, reports) = route_request(&req("GET", "/reports"), &registry, &state);
+        assert_eq!(reports["total"].as_u64(), Some(0));
+
+        let (status, body) = route_request(
+            &batch(json!({"reports": [
+                {"server": "github", "reason": "spam", "timestampEpochSecs": 1_000},
+                {"server": "github", "reason": "abuse", "details": "leaks tokens"},
+                {"server": "filesystem"}
+            ]})),
+            &registry,
+            &state,
+        );
+        assert_eq!(status, 200);
+        assert_eq!(body["accepted"].as_u64(), Some(3));
+        assert_eq!(body["results"][2]["status"].as_str(), Some("recorded"));
+        assert_eq!(body["reports"]["github"].as_u64(), Some(2));
+        assert_eq!(body["reports"]["filesystem"].as_u64(), Some(1));
+
+        let (_, reports) = route_request(&req("GET", "/reports?server=github"), &registry, &state);
+        assert_eq!(reports["total"].as_u64(), Some(2));
+        assert_eq!(
+            reports["reports"][1]["timestampEpochSecs"].as_u64(),
+            Some(1_000)
+        );
+        let (_, reports) = route_request(
+            &req("GET", "/reports?reason=unspecified"),
+            &registry,
+            &state,
+        );
+        assert_eq!(reports["reports"][0]["server"].as_str(), Some("filesystem"));
+        assert_eq!(state.community_counts("github").unwrap(), (0, 2));
+
+        // A failure after some logs were appended to rolls those back too.
+        let logs = |state: &ApiState| {
+            ["filesystem", "github"].map(|server| fs::read(state.report_path(server)).unwrap())
+        };
+        let before = logs(&state);
+        let second_batch = || {
+            batch(json!({"reports": [
+                {"server": "filesystem", "reason": "spam"},
+                {"server": "github", "reason": "spam"}
+            ]}))
+        };
+        let snapshot = fs::read(state.snapshot_path()).unwrap();
+        fs::remove_file(state.snapshot_path()).unwrap();
+        fs::create_dir(state.snapshot_path()).unwrap();
+        let (status, _) = route_request(&second_batch(), &registry, &state);
+        assert_eq!(status, 500);
+        assert_eq!(logs(&state), before);
+        fs::remove_dir(state.snapshot_path()).unwrap();
+        fs::write(state.snapshot_path(), snapshot).unwrap();
+
+        let github_log = state.report_path("github");
+        fs::rename(&github_log, github_log.with_extension("bak")).unwrap();
+        fs::create_dir(&github_log).unwrap();
+        let (status, _) = route_request(&second_batch(), &registry, &state);
+        assert_eq!(status, 500);
+        assert_eq!(
+            fs::read(state.report_path("filesystem")).unwrap(),
+            before[0]
+        );
+        assert_eq!(state.community_counts("filesystem").unwrap(), (0, 1));
+    }
+
+    #[test]
+    fn report_batch_refuses_empty_and_oversized_batches() {
+        let registry = Registry::from_seed();
+        let state = test_state();
+        let batch = |reports: usize| HttpRequest {
+            method: "POST".to_string(),
+            target: "/reports/batch".to_string(),
+            authorization: None,
+            body: json!({"reports": vec![json!({"server": "github"}); reports]}).to_string(),
+        };
+
+        assert_eq!(route_request(&batch(0), &registry, &state).0, 400);
+        let (status, body) = route_request(&batch(MAX_REPORT_BATCH_ITEMS + 1), &registry, &state);
+        assert_eq!(status, 413);
+        assert_eq!(
+            body["maxItems"].as_u64(),
+            Some(MAX_REPORT_BATCH_ITEMS as u64)
+        );
+        assert_eq!(
+            route_request(&batch(MAX_REPORT_BATCH_ITEMS), &registry, &state).0,
+            200
+        );
+        assert_eq!(
+            route_request(&req("GET", "/reports/batch"), &registry, &state).0,
+            405
+        );
+    }
+
     #[test]
     fn route_request_handles_star_and_report_endpoints() {
         let registry = Registry::from_seed();
@@ -7211,6 +8428,7 @@ mod tests {
         let report_req = HttpRequest {
             method: "POST".to_string(),
             target: "/servers/github/report".to_string(),
+            authorization: None,
             body: "{\"reason\":\"spam\",\"details\":\"bad output\"}".to_string(),
         };
         let (report_status, report_body) = route_request(&report_req, &registry, &state);
@@ -7221,6 +8439,7 @@ mod tests {
         let second_report_req = HttpRequest {
             method: "POST".to_string(),
             target: "/servers/github/report".to_string(),
+            authorization: None,
             body: "{\"reason\":\"abuse\",\"details\":\"unsafe behavior\"}".to_string(),
         };
         let (second_report_status, second_report_body) =
@@ -7260,6 +8479,7 @@ mod tests {
         let verify_request = HttpRequest {
             method: "POST".to_string(),
             target: "/publishers/verify".to_string(),
+            authorization: None,
             body: "{\"maintainer\":\"Anthropic\"}".to_string(),
         };
         let (verify_status, verify_body) = route_request(&verify_request, &registry, &state);
@@ -7344,6 +8564,7 @@ mod tests {
         let invalid_method_request = HttpRequest {
             method: "POST".to_string(),
             target: "/publishers/anthropic".to_string(),
+            authorization: None,
             body: "{}".to_string(),
         };
         let (publisher_method_status, publisher_method_body) =
@@ -7393,6 +8614,7 @@ mod tests {
         let unverify_request = HttpRequest {
             method: "POST".to_string(),
             target: "/publishers/unverify".to_string(),
+            authorization: None,
             body: "{\"maintainer\":\"Anthropic\"}".to_string(),
         };
         let (unverify_status, unverify_body) = route_request(&unverify_request, &registry, &state);
@@ -0,0 +1,148 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Command handler for `berth registry-init`.
+
+use colored::Colorize;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use berth_registry::MIRROR_INDEX_FILE;
+
+use crate::commands::registry_api::API_TOKEN_ENV;
+use crate::shell_preview::quote;
+
+/// Token file name inside a scaffolded registry directory.
+const AUTH_TOKEN_FILE: &str = "auth-token";
+
+/// Executes the `berth registry-init` command.
+pub fn execute(dir: &str) {
+    let root = PathBuf::from(dir);
+    let index_path = root.join(MIRROR_INDEX_FILE);
+    if index_path.exists() {
+        eprintln!(
+            "{} A registry already exists at {}.",
+            "✗".red().bold(),
+            root.display()
+        );
+        process::exit(1);
+    }
+
+    if let Err(msg) = scaffold(&root) {
+        eprintln!("{} {}", "✗".red().bold(), msg);
+        process::exit(1);
+    }
+    let root = fs::canonicalize(&root).unwrap_or(root);
+    let root_arg = quote(&root.to_string_lossy());
+    let token_path = quote(&root.join(AUTH_TOKEN_FILE).to_string_lossy());
+
+    println!(
+        "{} Initialized private registry at {}.",
+        "✓".green().bold(),
+        root.display()
+    );
+    println!("\n  {}", "Serve it:".bold());
+    println!(
+        "    BERTH_HOME={root_arg} BERTH_REGISTRY_INDEX_FILE={} {API_TOKEN_ENV}=\"$(cat {token_path})\" berth registry-api --bind 0.0.0.0:8787",
+        quote(&root.join(MIRROR_INDEX_FILE).to_string_lossy())
+    );
+    println!("\n  {}", "Point clients at it:".bold());
+    println!("    export BERTH_REGISTRY_INDEX_URL=http://<host>:8787/index.json");
+    println!("\n  {}", "Authorize publishing and moderation:".bold());
+    println!("    send `Authorization: Bearer <token>` with POST requests (token in {token_path})");
+}
+
+/// Creates the index, community storage, publish queue, and auth token.
+fn scaffold(root: &Path) -> Result<(), String> {
+    for dir in [
+        root.join("registry").join("community"),
+        root.join("publish").join("queue"),
+    ] {
+        fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create directory {}: {e}", dir.display()))?;
+    }
+
+    let registry = super::load_registry();
+    let index = serde_json::to_string_pretty(registry.list_all())
+        .map_err(|e| format!("Failed to serialize index: {e}"))?;
+    let index_path = root.join(MIRROR_INDEX_FILE);
+    fs::write(&index_path, index)
+        .map_err(|e| format!("Failed to write {}: {e}", index_path.display()))?;
+
+    let token_path = root.join(AUTH_TOKEN_FILE);
+    let token = generate_token()?;
+    // Created owner-only, so the token is never readable under the umask.
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options
+        .open(&token_path)
+        .and_then(|mut file| writeln!(file, "{token}"))
+        .map_err(|e| format!("Failed to write {}: {e}", token_path.display()))?;
+    Ok(())
+}
+
+/// Generates a 256-bit hex token from the operating system's CSPRNG.
+pub(crate) fn generate_token() -> Result<String, String> {
+    let mut bytes = [0u8; 32];
+    getrandom::getrandom(&mut bytes)
+        .map_err(|e| format!("Failed to read random bytes for a token: {e}"))?;
+    Ok(bytes.iter().map(|b| format!("{b:02x}")).collect())
+}
+
+/// Compares a presented secret with the expected one in constant time.
+pub(crate) fn secrets_match(presented: &str, expected: &str) -> bool {
+    use subtle::ConstantTimeEq;
+    presented.as_bytes().ct_eq(expected.as_bytes()).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generate_token_is_hex_and_unique() {
+        let first = generate_token().unwrap();
+        assert_eq!(first.len(), 64);
+        assert!(first.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(first, generate_token().unwrap());
+    }
+
+    #[test]
+    fn secrets_match_only_identical_strings() {
+        assert!(secrets_match("abc123", "abc123"));
+        assert!(!secrets_match("abc124", "abc123"));
+        assert!(!secrets_match("abc12", "abc123"));
+        assert!(!secrets_match("", "abc123"));
+    }
+
+    #[test]
+    fn scaffold_creates_registry_layout() {
+        let tmp = tempfile::tempdir().unwrap();
+        scaffold(tmp.path()).unwrap();
+        assert!(tmp.path().join("index.json").exists());
+        assert!(tmp.path().join("registry/community").is_dir());
+        assert!(tmp.path().join("publish/queue").is_dir());
+        let token = fs::read_to_string(tmp.path().join(AUTH_TOKEN_FILE)).unwrap();
+        assert_eq!(token.trim().len(), 64);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn scaffold_creates_the_token_owner_only() {
+        use std::os::unix::fs::PermissionsExt;
+        let tmp = tempfile::tempdir().unwrap();
+        scaffold(tmp.path()).unwrap();
+        let mode = fs::metadata(tmp.path().join(AUTH_TOKEN_FILE))
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+}
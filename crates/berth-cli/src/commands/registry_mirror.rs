@@ -0,0 +1,272 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Command handler for `berth registry-mirror`.
+
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::process::Command;
+
+use berth_registry::types::ServerMetadata;
+use berth_registry::{MIRROR_ENV, MIRROR_INDEX_FILE};
+
+use crate::commands::install::fetch_binary;
+
+/// Manifest file name inside a registry mirror directory.
+pub const MIRROR_MANIFEST_FILE: &str = "mirror.json";
+
+/// Artifact kind for npm package tarballs.
+const KIND_NPM: &str = "npm";
+/// Artifact kind for prebuilt binaries.
+const KIND_BINARY: &str = "binary";
+
+/// Artifacts stored in a mirror, keyed by server name.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MirrorManifest {
+    pub servers: BTreeMap<String, MirrorArtifact>,
+}
+
+/// One mirrored artifact with a path relative to the mirror root.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MirrorArtifact {
+    pub kind: String,
+    pub version: String,
+    pub path: String,
+}
+
+/// Executes the `berth registry-mirror` command.
+pub fn execute(dest: &str) {
+    let dest = PathBuf::from(dest);
+    let registry = super::load_registry();
+    let servers = registry.list_all();
+
+    for dir in [dest.join("artifacts/npm"), dest.join("artifacts/bin")] {
+        if let Err(e) = fs::create_dir_all(&dir) {
+            eprintln!(
+                "{} Failed to create directory {}: {}",
+                "✗".red().bold(),
+                dir.display(),
+                e
+            );
+            process::exit(1);
+        }
+    }
+
+    let mut manifest = MirrorManifest::default();
+    let mut failed = 0usize;
+    let mut skipped = 0usize;
+    for server in servers {
+        match mirror_artifact(server, &dest) {
+            Ok(Some(artifact)) => {
+                manifest.servers.insert(server.name.clone(), artifact);
+            }
+            Ok(None) => {
+                skipped += 1;
+                println!(
+                    "{} {} uses the {} runtime; no artifact mirrored.",
+                    "!".yellow().bold(),
+                    server.name.cyan(),
+                    server.runtime.runtime_type
+                );
+            }
+            Err(msg) => {
+                failed += 1;
+                eprintln!(
+                    "{} Failed to mirror {}: {}",
+                    "✗".red().bold(),
+                    server.name.cyan(),
+                    msg
+                );
+            }
+        }
+    }
+
+    let index = match serde_json::to_string_pretty(servers) {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("{} Failed to serialize index: {}", "✗".red().bold(), e);
+            process::exit(1);
+        }
+    };
+    if let Err(msg) = write_mirror_file(&dest.join(MIRROR_INDEX_FILE), &index) {
+        eprintln!("{} {}", "✗".red().bold(), msg);
+        process::exit(1);
+    }
+    let rendered = match serde_json::to_string_pretty(&manifest) {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!(
+                "{} Failed to serialize mirror manifest: {}",
+                "✗".red().bold(),
+                e
+            );
+            process::exit(1);
+        }
+    };
+    if let Err(msg) = write_mirror_file(&dest.join(MIRROR_MANIFEST_FILE), &rendered) {
+        eprintln!("{} {}", "✗".red().bold(), msg);
+        process::exit(1);
+    }
+
+    println!(
+        "{} Mirrored {} server(s) with {} artifact(s) to {}.",
+        "✓".green().bold(),
+        servers.len(),
+        manifest.servers.len(),
+        dest.display()
+    );
+    if skipped > 0 {
+        println!(
+            "  {} server(s) still need network access at install time.",
+            skipped
+        );
+    }
+    println!(
+        "  Install offline with {} or {}.",
+        format!("berth install <server> --mirror {}", dest.display()).bold(),
+        format!("{MIRROR_ENV}={}", dest.display()).bold()
+    );
+    if failed > 0 {
+        eprintln!(
+            "{} {} artifact(s) could not be mirrored.",
+            "✗".red().bold(),
+            failed
+        );
+        process::exit(1);
+    }
+}
+
+/// Fetches one server's artifact into the mirror, or returns `None` when its runtime has none.
+fn mirror_artifact(server: &ServerMetadata, dest: &Path) -> Result<Option<MirrorArtifact>, String> {
+    let (kind, relative) = match server.runtime.runtime_type.as_str() {
+        "node" => (
+            KIND_NPM,
+            format!("artifacts/npm/{}-{}.tgz", server.name, server.version),
+        ),
+        "binary" => (KIND_BINARY, format!("artifacts/bin/{}", server.name)),
+        _ => return Ok(None),
+    };
+    let destination = dest.join(&relative);
+    if kind == KIND_NPM {
+        pack_npm(&server.source.package, &destination)?;
+    } else {
+        fetch_binary(&server.source.package, &destination)?;
+    }
+    Ok(Some(MirrorArtifact {
+        kind: kind.to_string(),
+        version: server.version.clone(),
+        path: relative,
+    }))
+}
+
+/// Downloads an npm package tarball using `npm pack`.
+fn pack_npm(package: &str, destination: &Path) -> Result<(), String> {
+    let dir = destination
+        .parent()
+        .ok_or_else(|| format!("invalid artifact path {}", destination.display()))?;
+    let output = Command::new("npm")
+        .args(["pack", package, "--silent", "--pack-destination"])
+        .arg(dir)
+        .output()
+        .map_err(|e| format!("failed to run npm pack for {package}: {e}"))?;
+    if !output.status.success() {
+        return Err(format!(
+            "npm pack {package} failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let packed = stdout
+        .lines()
+        .rev()
+        .find(|l| !l.trim().is_empty())
+        .ok_or_else(|| format!("npm pack {package} did not report a tarball"))?;
+    fs::rename(dir.join(packed.trim()), destination)
+        .map_err(|e| format!("failed to store tarball {}: {e}", destination.display()))
+}
+
+fn write_mirror_file(path: &Path, content: &str) -> Result<(), String> {
+    fs::write(path, content).map_err(|e| format!("Failed to write {}: {e}", path.display()))
+}
+
+/// Loads the manifest of a mirror directory.
+pub(crate) fn load_mirror_manifest(mirror: &Path) -> Result<MirrorManifest, String> {
+    let path = mirror.join(MIRROR_MANIFEST_FILE);
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read mirror manifest {}: {e}", path.display()))?;
+    serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse mirror manifest {}: {e}", path.display()))
+}
+
+/// Points server metadata at a mirrored artifact so install needs no network.
+///
+/// Binary sources become the mirrored file; npm package args in the runtime
+/// command are replaced with the mirrored tarball path.
+pub(crate) fn apply_mirror_artifact(
+    meta: &mut ServerMetadata,
+    mirror: &Path,
+    artifact: &MirrorArtifact,
+) {
+    let path = mirror.join(&artifact.path).to_string_lossy().to_string();
+    match artifact.kind.as_str() {
+        KIND_BINARY => meta.source.package = path,
+        KIND_NPM => {
+            for arg in meta
+                .runtime
+                .args
+                .iter_mut()
+                .filter(|a| **a == meta.source.package)
+            {
+                *arg = path.clone();
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use berth_registry::Registry;
+
+    fn seed_server(name: &str) -> ServerMetadata {
+        Registry::from_seed().get(name).unwrap().clone()
+    }
+
+    #[test]
+    fn npm_artifact_replaces_package_arg() {
+        let mut meta = seed_server("github");
+        let artifact = MirrorArtifact {
+            kind: KIND_NPM.to_string(),
+            version: meta.version.clone(),
+            path: "artifacts/npm/github-1.2.0.tgz".to_string(),
+        };
+        apply_mirror_artifact(&mut meta, Path::new("/mirror"), &artifact);
+        assert_eq!(
+            meta.runtime.args,
+            vec![
+                "-y".to_string(),
+                "/mirror/artifacts/npm/github-1.2.0.tgz".to_string()
+            ]
+        );
+        assert_eq!(meta.source.package, "@modelcontextprotocol/server-github");
+    }
+
+    #[test]
+    fn binary_artifact_replaces_source_package() {
+        let mut meta = seed_server("github");
+        let artifact = MirrorArtifact {
+            kind: KIND_BINARY.to_string(),
+            version: meta.version.clone(),
+            path: "artifacts/bin/github".to_string(),
+        };
+        apply_mirror_artifact(&mut meta, Path::new("/mirror"), &artifact);
+        assert_eq!(meta.source.package, "/mirror/artifacts/bin/github");
+    }
+}
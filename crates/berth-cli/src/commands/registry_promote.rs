@@ -0,0 +1,43 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Command handler for `berth registry-promote`.
+
+use colored::Colorize;
+use std::process;
+
+use berth_registry::types::TrustLevel;
+
+use crate::commands::registry_api::record_trust_change;
+
+/// Executes the `berth registry-promote` command.
+pub fn execute(server: &str, to: &str, reviewer: &str, reason: &str) {
+    let level = match to.parse::<TrustLevel>() {
+        Ok(level) => level,
+        Err(msg) => {
+            eprintln!("{} {}", "✗".red().bold(), msg);
+            process::exit(1);
+        }
+    };
+    let event = match record_trust_change(server, level, reviewer, reason) {
+        Ok(event) => event,
+        Err(msg) => {
+            eprintln!("{} {}", "✗".red().bold(), msg);
+            process::exit(1);
+        }
+    };
+    let verb = if event.level > event.previous_level {
+        "Promoted"
+    } else {
+        "Demoted"
+    };
+    println!(
+        "{} {verb} {} from {} to {} (reviewer {}).",
+        "✓".green().bold(),
+        event.server.cyan(),
+        event.previous_level,
+        event.level.to_string().bold(),
+        event.reviewer
+    );
+    println!("  /index.json served by `berth registry-api` now reports the new level.");
+}
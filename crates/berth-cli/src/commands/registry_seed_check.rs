@@ -0,0 +1,419 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Command handler for `berth registry-seed-check`.
+
+use colored::Colorize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use std::process;
+
+use berth_common::now_epoch_secs;
+use berth_common::time::parse_date;
+use berth_registry::config::PermissionsInfo;
+use berth_registry::http;
+use berth_registry::seed::seed_json;
+use berth_registry::types::ServerMetadata;
+use berth_registry::Registry;
+
+use crate::commands::publish::is_basic_semver;
+use crate::install_hooks::validate_step;
+use crate::oauth::validate_spec;
+use crate::permission_filter::validate_permission_syntax;
+
+/// One change between the seed dataset and another index.
+#[derive(Debug, PartialEq, Eq)]
+enum IndexChange {
+    Added {
+        name: String,
+        version: String,
+    },
+    Removed {
+        name: String,
+    },
+    Changed {
+        name: String,
+        field: &'static str,
+        seed: String,
+        other: String,
+    },
+}
+
+/// Executes the `berth registry-seed-check` command.
+pub fn execute(max_age_days: u32, online: bool, diff: Option<&str>) {
+    let servers: Vec<ServerMetadata> = match serde_json::from_str(seed_json()) {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!(
+                "{} Seed registry does not match the metadata schema: {}",
+                "✗".red().bold(),
+                e
+            );
+            process::exit(1);
+        }
+    };
+
+    let mut errors = validate_seed(&servers);
+    if online {
+        errors.extend(check_links(&servers));
+    }
+    let stale = stale_entries(&servers, today_epoch_days(), max_age_days);
+
+    for (server, message) in &errors {
+        println!("  {} {}: {}", "✗".red().bold(), server.cyan(), message);
+    }
+    for (server, verified, age) in &stale {
+        println!(
+            "  {} {}: last verified {} ({} days ago)",
+            "!".yellow().bold(),
+            server.cyan(),
+            verified,
+            age
+        );
+    }
+
+    if let Some(source) = diff {
+        let other = match load_index(source) {
+            Ok(r) => r,
+            Err(msg) => {
+                eprintln!("{} {}", "✗".red().bold(), msg);
+                process::exit(1);
+            }
+        };
+        print_diff(source, &diff_indexes(&servers, other.list_all()));
+    }
+
+    if !errors.is_empty() {
+        eprintln!(
+            "{} Seed registry has {} problem(s) across {} server(s).",
+            "✗".red().bold(),
+            errors.len(),
+            servers.len()
+        );
+        process::exit(1);
+    }
+    println!(
+        "{} Seed registry: {} servers valid{}.",
+        "✓".green().bold(),
+        servers.len(),
+        if stale.is_empty() {
+            String::new()
+        } else {
+            format!(", {} stale", stale.len())
+        }
+    );
+}
+
+/// Validates seed entries beyond what the typed schema enforces.
+fn validate_seed(servers: &[ServerMetadata]) -> Vec<(String, String)> {
+    let mut errors = Vec::new();
+    let mut names = BTreeSet::new();
+    for server in servers {
+        let mut push = |message: String| errors.push((server.name.clone(), message));
+        if !names.insert(server.name.as_str()) {
+            push("duplicate server name".to_string());
+        }
+        if server.name.is_empty()
+            || !server
+                .name
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        {
+            push("name must use lowercase letters, digits, and dashes only".to_string());
+        }
+        for (field, value) in [
+            ("displayName", &server.display_name),
+            ("description", &server.description),
+            ("category", &server.category),
+            ("maintainer", &server.maintainer),
+            ("source.package", &server.source.package),
+        ] {
+            if value.trim().is_empty() {
+                push(format!("{field} is required"));
+            }
+        }
+        if !is_basic_semver(&server.version) {
+            push(format!("version `{}` is not `x.y.z`", server.version));
+        }
+        match (
+            server.runtime.runtime_type.as_str(),
+            server.transport.as_str(),
+        ) {
+            ("berth", "http") => {}
+            ("berth", other) => push(format!("transport `{other}` is not `http`")),
+            (_, transport) => {
+                for problem in
+                    berth_runtime::template_problems(transport, server.runtime.socket.as_deref())
+                {
+                    push(problem.trim_end_matches('.').to_string());
+                }
+            }
+        }
+        match server.runtime.runtime_type.as_str() {
+            "node" | "python" | "binary" | "berth" | "wasm" => {}
+            "job" if server.runtime.schedule.is_none() => {
+                push("job runtimes need a runtime.schedule".to_string())
+            }
+            "job" => {}
+            other => push(format!("unsupported runtime type `{other}`")),
+        }
+        if server.runtime.runtime_type != "python" && server.runtime.command.trim().is_empty() {
+            push("runtime.command is required".to_string());
+        }
+        if !server.source.repository.starts_with("https://") {
+            push(format!(
+                "repository `{}` is not an https URL",
+                server.source.repository
+            ));
+        }
+        if parse_date(&server.quality.last_verified).is_none() {
+            push(format!(
+                "lastVerified `{}` is not a YYYY-MM-DD date",
+                server.quality.last_verified
+            ));
+        }
+        if server.compatibility.clients.is_empty() || server.compatibility.platforms.is_empty() {
+            push("compatibility must list clients and platforms".to_string());
+        }
+        for (kind, values) in [
+            ("network", &server.permissions.network),
+            ("env", &server.permissions.env),
+            ("filesystem", &server.permissions.filesystem),
+            ("exec", &server.permissions.exec),
+        ] {
+            for value in values {
+                if let Err(msg) = validate_permission_syntax(&format!("{kind}:{value}")) {
+                    push(msg);
+                }
+            }
+        }
+        for value in server.permissions.tools.values().flatten() {
+            if let Err(msg) = validate_permission_syntax(value) {
+                push(msg);
+            }
+        }
+        let declared = PermissionsInfo {
+            network: server.permissions.network.clone(),
+            env: server.permissions.env.clone(),
+            filesystem: server.permissions.filesystem.clone(),
+            exec: server.permissions.exec.clone(),
+            tools: server.permissions.tools.clone(),
+        };
+        for step in &server.post_install {
+            if let Err(msg) = validate_step(step, &declared) {
+                push(format!("postInstall step rejected: {msg}"));
+            }
+        }
+        if let Some(spec) = &server.oauth {
+            if let Err(msg) = validate_spec(spec) {
+                push(msg);
+            }
+        }
+        let mut keys = BTreeSet::new();
+        for field in server.config.required.iter().chain(&server.config.optional) {
+            if !keys.insert(field.key.as_str()) {
+                push(format!("duplicate config key `{}`", field.key));
+            }
+            if let Some(env) = &field.env {
+                if env.is_empty()
+                    || !env
+                        .chars()
+                        .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+                {
+                    push(format!(
+                        "config env `{env}` is not an uppercase variable name"
+                    ));
+                }
+                if !server.permissions.env.iter().any(|p| p == env || p == "*") {
+                    push(format!(
+                        "config env `{env}` is not declared in permissions.env"
+                    ));
+                }
+            }
+        }
+    }
+    errors
+}
+
+/// Returns `(server, lastVerified, age_days)` for entries older than `max_age_days`.
+fn stale_entries(
+    servers: &[ServerMetadata],
+    today: i64,
+    max_age_days: u32,
+) -> Vec<(String, String, i64)> {
+    servers
+        .iter()
+        .filter_map(|s| {
+            let verified = parse_date(&s.quality.last_verified)?;
+            let age = today - verified;
+            (age > i64::from(max_age_days))
+                .then(|| (s.name.clone(), s.quality.last_verified.clone(), age))
+        })
+        .collect()
+}
+
+/// Checks that repository and npm package URLs resolve.
+fn check_links(servers: &[ServerMetadata]) -> Vec<(String, String)> {
+    let mut errors = Vec::new();
+    for server in servers {
+        let mut urls = vec![server.source.repository.clone()];
+        if server.source.source_type == "npm" {
+            urls.push(format!(
+                "https://registry.npmjs.org/{}",
+                server.source.package.replace('/', "%2F")
+            ));
+        }
+        for url in urls {
+            if !http::url_resolves(&url) {
+                errors.push((server.name.clone(), format!("{url} did not resolve")));
+            }
+        }
+    }
+    errors
+}
+
+/// Loads another index from a local path or http(s) URL.
+fn load_index(source: &str) -> Result<Registry, String> {
+    if source.starts_with("http://") || source.starts_with("https://") {
+        Registry::from_index_url(source)
+    } else {
+        Registry::from_index_file(Path::new(source))
+    }
+}
+
+/// Compares the seed dataset against another index by server name.
+fn diff_indexes(seed: &[ServerMetadata], other: &[ServerMetadata]) -> Vec<IndexChange> {
+    let seed: BTreeMap<&str, &ServerMetadata> = seed.iter().map(|s| (s.name.as_str(), s)).collect();
+    let other: BTreeMap<&str, &ServerMetadata> =
+        other.iter().map(|s| (s.name.as_str(), s)).collect();
+    let mut changes = Vec::new();
+    for (name, theirs) in &other {
+        let Some(ours) = seed.get(name) else {
+            changes.push(IndexChange::Added {
+                name: name.to_string(),
+                version: theirs.version.clone(),
+            });
+            continue;
+        };
+        for (field, a, b) in [
+            ("version", &ours.version, &theirs.version),
+            ("package", &ours.source.package, &theirs.source.package),
+            (
+                "lastVerified",
+                &ours.quality.last_verified,
+                &theirs.quality.last_verified,
+            ),
+        ] {
+            if a != b {
+                changes.push(IndexChange::Changed {
+                    name: name.to_string(),
+                    field,
+                    seed: a.clone(),
+                    other: b.clone(),
+                });
+            }
+        }
+        if ours.trust_level.to_string() != theirs.trust_level.to_string() {
+            changes.push(IndexChange::Changed {
+                name: name.to_string(),
+                field: "trustLevel",
+                seed: ours.trust_level.to_string(),
+                other: theirs.trust_level.to_string(),
+            });
+        }
+    }
+    for name in seed.keys().filter(|n| !other.contains_key(*n)) {
+        changes.push(IndexChange::Removed {
+            name: name.to_string(),
+        });
+    }
+    changes
+}
+
+fn print_diff(source: &str, changes: &[IndexChange]) {
+    println!("\n  {} {}", "Diff against".bold(), source);
+    if changes.is_empty() {
+        println!("    (no differences)");
+        return;
+    }
+    for change in changes {
+        match change {
+            IndexChange::Added { name, version } => {
+                println!("    {} {} (v{version})", "+".green().bold(), name.cyan())
+            }
+            IndexChange::Removed { name } => println!("    {} {}", "-".red().bold(), name.cyan()),
+            IndexChange::Changed {
+                name,
+                field,
+                seed,
+                other,
+            } => println!(
+                "    {} {}: {field} {seed} -> {other}",
+                "~".yellow().bold(),
+                name.cyan()
+            ),
+        }
+    }
+}
+
+fn today_epoch_days() -> i64 {
+    (now_epoch_secs() / 86_400) as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seed() -> Vec<ServerMetadata> {
+        serde_json::from_str(seed_json()).unwrap()
+    }
+
+    #[test]
+    fn embedded_seed_passes_validation() {
+        assert_eq!(validate_seed(&seed()), Vec::new());
+    }
+
+    #[test]
+    fn validation_flags_bad_entries() {
+        let mut servers = seed();
+        servers[0].version = "latest".to_string();
+        servers[0].quality.last_verified = "2026-02-30".to_string();
+        servers[1].name = servers[0].name.clone();
+        let errors = validate_seed(&servers);
+        let messages: Vec<&str> = errors.iter().map(|(_, m)| m.as_str()).collect();
+        assert!(messages.iter().any(|m| m.contains("not `x.y.z`")));
+        assert!(messages.iter().any(|m| m.contains("YYYY-MM-DD")));
+        assert!(messages.contains(&"duplicate server name"));
+    }
+
+    #[test]
+    fn stale_entries_respect_max_age() {
+        let servers = seed();
+        let verified = parse_date(&servers[0].quality.last_verified).unwrap();
+        assert!(stale_entries(&servers[..1], verified + 10, 10).is_empty());
+        let stale = stale_entries(&servers[..1], verified + 11, 10);
+        assert_eq!(stale[0].2, 11);
+    }
+
+    #[test]
+    fn diff_reports_added_removed_and_changed() {
+        let ours = seed();
+        let mut theirs = ours[..2].to_vec();
+        theirs[0].version = "9.9.9".to_string();
+        theirs[1].name = "brand-new".to_string();
+        let changes = diff_indexes(&ours[..2], &theirs);
+        assert!(changes.contains(&IndexChange::Added {
+            name: "brand-new".to_string(),
+            version: ours[1].version.clone()
+        }));
+        assert!(changes.contains(&IndexChange::Removed {
+            name: ours[1].name.clone()
+        }));
+        assert!(changes.contains(&IndexChange::Changed {
+            name: ours[0].name.clone(),
+            field: "version",
+            seed: ours[0].version.clone(),
+            other: "9.9.9".to_string()
+        }));
+    }
+}
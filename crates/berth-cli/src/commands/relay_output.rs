@@ -0,0 +1,30 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Internal command handler for `berth __relay-output`.
+//!
+//! `berth adopt` starts one relay per file an adopted process writes its
+//! output to. The relay copies what is appended into the server log until the
+//! process exits or is stopped.
+
+use colored::Colorize;
+use std::path::Path;
+use std::process;
+
+use crate::paths;
+
+/// Executes the hidden output relay command.
+pub fn execute(server: &str, pid: u32, source: &str) {
+    let Some(runtime) = paths::runtime_manager() else {
+        eprintln!("{} Could not determine home directory.", "✗".red().bold());
+        process::exit(1);
+    };
+    if let Err(e) = runtime.relay_output(server, pid, Path::new(source)) {
+        eprintln!(
+            "{} Failed to relay output of {} from {source}: {e}",
+            "✗".red().bold(),
+            server.cyan()
+        );
+        process::exit(1);
+    }
+}
@@ -12,22 +12,38 @@ use berth_registry::config::InstalledServer;
 use berth_registry::Registry;
 use berth_runtime::{ProcessSpec, RuntimeManager};
 
-use crate::commands::supervise;
+use crate::commands::start::{await_readiness, launch, needs_supervisor};
+use crate::daemon;
+use crate::egress_allowlist::{apply_egress_allowlist, warn_cooperative_egress};
+use crate::egress_monitor::{apply_egress_monitor, parse_egress_monitor};
+use crate::env_isolation::apply_env_isolation;
+use crate::hints::{self, Failure};
+use crate::job_policy::parse_schedule;
+use crate::limits_policy::resource_limits;
+use crate::oauth::inject_access_token;
 use crate::paths;
 use crate::permission_filter::{
-    filter_env_map, load_permission_overrides, undeclared_network_grants,
+    effective_permissions, filter_env_map, load_permission_overrides, undeclared_network_grants,
     validate_network_permissions, NETWORK_PERMISSION_DENIED_PREFIX,
 };
 use crate::policy_engine::{
     enforce_global_policy, load_global_policy, GlobalPolicy, POLICY_DENIED_PREFIX,
 };
-use crate::runtime_policy::parse_runtime_policy;
-use crate::sandbox_policy::{parse_sandbox_policy, KEY_SANDBOX_NETWORK};
+use crate::readiness_policy::{health_check, parse_readiness};
+use crate::replica_policy::{instances, running_replicas, Instance, Role};
+use crate::runtime_policy::{capture_core_enabled, parse_runtime_policy, parse_shutdown_settings};
+use crate::sandbox_policy::{
+    parse_run_as, parse_sandbox_policy, parse_seccomp, SandboxNetwork, KEY_SANDBOX_NETWORK,
+};
 use crate::sandbox_runtime::apply_sandbox_runtime;
 use crate::secrets::resolve_config_value;
+use crate::socket_transport::apply_socket_endpoint;
+use crate::warnings;
+use crate::wasm_runtime::apply_wasm_runtime;
+use crate::workspace_trust;
 
 /// Executes the `berth restart` command.
-pub fn execute(server: &str) {
+pub fn execute(server: &str, inherit_env: bool) {
     let config_path = match paths::server_config_path(server) {
         Some(p) => p,
         None => {
@@ -79,21 +95,17 @@ pub fn execute(server: &str) {
             server.cyan(),
             missing.join(", ").yellow()
         );
-        eprintln!(
-            "  Run {} to configure.",
-            format!("berth config {server} --set <key>=<value>").bold()
-        );
+        Failure::MissingConfig.print(server, &installed.runtime.command);
         process::exit(1);
     }
 
-    let berth_home = match paths::berth_home() {
-        Some(h) => h,
+    let runtime = match paths::runtime_manager() {
+        Some(r) => r,
         None => {
             eprintln!("{} Could not determine home directory.", "✗".red().bold());
             process::exit(1);
         }
     };
-    let runtime = RuntimeManager::new(berth_home.clone());
     let global_policy = match load_global_policy() {
         Ok(policy) => policy,
         Err(msg) => {
@@ -102,8 +114,8 @@ pub fn execute(server: &str) {
         }
     };
     let registry = Registry::from_seed();
-    let (spec, undeclared_network) =
-        match build_process_spec(server, &installed, &registry, &global_policy) {
+    let (mut spec, undeclared_network) =
+        match build_process_spec(server, &installed, &registry, &global_policy, inherit_env) {
             Ok(spec) => spec,
             Err(msg) => {
                 if msg.starts_with(NETWORK_PERMISSION_DENIED_PREFIX) {
@@ -124,16 +136,24 @@ pub fn execute(server: &str) {
                     );
                 }
                 eprintln!("{} {}", "✗".red().bold(), msg);
+                hints::print_for_message(&msg, server, &installed.runtime.command);
                 process::exit(1);
             }
         };
+    if let Err(msg) = inject_access_token(server, &installed, &registry, &mut spec) {
+        eprintln!("{} {}", "✗".red().bold(), msg);
+        process::exit(1);
+    }
     if !undeclared_network.is_empty() {
-        println!(
-            "{} {} has undeclared network grant override(s): {} (log-only).",
-            "!".yellow().bold(),
-            server.cyan(),
-            undeclared_network.join(", ")
-        );
+        if warnings::due(warnings::UNDECLARED_NETWORK, Some(server)) {
+            println!(
+                "{} {} has undeclared network grant override(s): {} (log-only). {}",
+                "!".yellow().bold(),
+                server.cyan(),
+                undeclared_network.join(", "),
+                warnings::snooze_hint(warnings::UNDECLARED_NETWORK)
+            );
+        }
         let _ = runtime.record_audit_event(
             server,
             "permission-network-warning",
@@ -143,36 +163,120 @@ pub fn execute(server: &str) {
         );
     }
 
-    let supervision_enabled = spec.auto_restart.is_some_and(|policy| policy.enabled);
-    let mut runtime_spec = spec.clone();
-    if supervision_enabled {
-        runtime_spec.auto_restart = None;
-    }
+    warn_cooperative_egress(server, &spec);
+    let instances = match instances(server, &installed, spec) {
+        Ok(instances) => instances,
+        Err(msg) => {
+            eprintln!("{} {}", "✗".red().bold(), msg);
+            process::exit(1);
+        }
+    };
+    let [instance] = instances.as_slice() else {
+        if let Err(msg) = rolling_restart(&runtime, server, &instances) {
+            eprintln!("{} {}", "✗".red().bold(), msg);
+            process::exit(1);
+        }
+        return;
+    };
 
-    if let Err(e) = runtime.restart(server, &runtime_spec) {
+    if let Err(e) = restart_instance(&runtime, server, &instance.spec) {
         eprintln!(
             "{} Failed to restart {}: {}",
             "✗".red().bold(),
             server.cyan(),
             e
         );
+        hints::print_for_message(&e, server, &instance.spec.command);
+        process::exit(1);
+    }
+
+    println!("{} Restarted {}.", "✓".green().bold(), server.cyan());
+    if !await_readiness(&runtime, server, &instance.spec) {
         process::exit(1);
     }
+    retire_replicas(&runtime, server, &instances);
+}
+
+/// Restarts the replicas of a replicated server one at a time, each only once
+/// the one before is ready again, so the gateway always has replicas to hand
+/// connections to.
+///
+/// The gateway itself restarts only when it is not running or its replicas
+/// changed; replicas beyond `berth.replicas` are stopped last. A replica that
+/// does not become ready ends the roll, leaving the rest on their old launch.
+pub(crate) fn rolling_restart(
+    runtime: &RuntimeManager,
+    server: &str,
+    instances: &[Instance],
+) -> Result<(), String> {
+    let configured: Vec<&str> = instances
+        .iter()
+        .filter(|i| matches!(i.role, Role::Replica(_)))
+        .map(|i| i.name.as_str())
+        .collect();
+    let unchanged = running_replicas(runtime, server) == configured;
+    for instance in instances {
+        let (name, spec) = (instance.name.as_str(), &instance.spec);
+        if instance.role == Role::Gateway
+            && unchanged
+            && runtime.running_pid(name).ok().flatten().is_some()
+        {
+            continue;
+        }
+        restart_instance(runtime, name, spec)
+            .map_err(|e| format!("Failed to restart {}: {e}", name.cyan()))?;
+        println!("{} Restarted {}.", "✓".green().bold(), name.cyan());
+        if !await_readiness(runtime, name, spec) {
+            return Err(format!(
+                "Stopped the rolling restart of {}; the remaining replicas keep running.",
+                server.cyan()
+            ));
+        }
+    }
+    retire_replicas(runtime, server, instances);
+    Ok(())
+}
 
-    if supervision_enabled {
-        if let Err(msg) = supervise::spawn_detached(server, &spec, &berth_home) {
-            let _ = runtime.stop(server);
-            eprintln!(
-                "{} Failed to start supervisor for {}: {}",
+/// Stops running replicas of `server` that are not among `instances`, e.g.
+/// after `berth.replicas` was lowered.
+fn retire_replicas(runtime: &RuntimeManager, server: &str, instances: &[Instance]) {
+    for replica in running_replicas(runtime, server) {
+        if instances.iter().any(|i| i.name == replica) {
+            continue;
+        }
+        match daemon::stop(runtime, &replica) {
+            Ok(_) => println!("{} Stopped {}.", "✓".green().bold(), replica.cyan()),
+            Err(e) => eprintln!(
+                "{} Failed to stop {}: {}",
                 "✗".red().bold(),
-                server.cyan(),
-                msg
-            );
-            process::exit(1);
+                replica.cyan(),
+                e
+            ),
         }
     }
+}
 
-    println!("{} Restarted {}.", "✓".green().bold(), server.cyan());
+/// Stops one instance and launches it again from `spec`.
+fn restart_instance(
+    runtime: &RuntimeManager,
+    name: &str,
+    spec: &ProcessSpec,
+) -> Result<(), String> {
+    if needs_supervisor(spec) || daemon::running() {
+        daemon::stop(runtime, name)
+            .and_then(|_| launch(runtime, name, spec))
+            .map(|_| {
+                let _ = runtime.record_audit_event(
+                    name,
+                    "restart",
+                    runtime.running_pid(name).ok().flatten(),
+                    Some(&spec.command),
+                    Some(&spec.args),
+                );
+            })
+    } else {
+        runtime.restart(name, spec).map_err(|e| e.to_string())
+    }
 }
 
 /// Builds a runtime process spec from installed metadata and config values.
@@ -181,10 +285,11 @@ fn build_process_spec(
     installed: &InstalledServer,
     registry: &Registry,
     global_policy: &GlobalPolicy,
+    inherit_env: bool,
 ) -> Result<(ProcessSpec, Vec<String>), String> {
     let mut env = BTreeMap::new();
 
-    if let Some(meta) = registry.get(name) {
+    if let Some(meta) = registry.get(&installed.server.name) {
         for field in meta
             .config
             .required
@@ -210,11 +315,14 @@ fn build_process_spec(
     }
 
     let overrides = load_permission_overrides(name)?;
-    enforce_global_policy(name, &installed.permissions, &overrides, global_policy)?;
+    enforce_global_policy(name, installed, &overrides, global_policy, registry)?;
+    workspace_trust::check()?;
     validate_network_permissions(name, &installed.permissions.network, &overrides)?;
     let undeclared_network = undeclared_network_grants(&installed.permissions.network, &overrides);
     let sandbox_policy = parse_sandbox_policy(&installed.config)?;
-    if sandbox_policy.network_deny_all {
+    let run_as = parse_run_as(&installed.config)?;
+    let seccomp = parse_seccomp(&installed.config, &installed.runtime.syscalls)?;
+    if sandbox_policy.network == SandboxNetwork::DenyAll {
         return Err(format!(
             "{NETWORK_PERMISSION_DENIED_PREFIX}Server {} blocked by sandbox policy: set `{KEY_SANDBOX_NETWORK}=inherit` or relax network constraints.",
             name.cyan()
@@ -222,12 +330,36 @@ fn build_process_spec(
     }
     filter_env_map(&mut env, &installed.permissions.env, &overrides);
     let policy = parse_runtime_policy(&installed.config)?;
+    let (shutdown_signal, shutdown_grace_period_secs) = parse_shutdown_settings(&installed.config)?;
+    let capture_core = capture_core_enabled(installed)?;
+    let readiness = parse_readiness(&installed.config)?;
+    let network = effective_permissions("network", &installed.permissions.network, &overrides);
+    let clean_env = apply_env_isolation(
+        &mut env,
+        &installed.permissions.env,
+        &overrides,
+        inherit_env,
+    );
+    if sandbox_policy.network == SandboxNetwork::Allowlist {
+        apply_egress_allowlist(name, &mut env, clean_env, &network)?;
+    } else if parse_egress_monitor(&installed.config)? {
+        apply_egress_monitor(name, &mut env, clean_env)?;
+    }
+    // Wrapped last, so the sandbox can mount what egress setup points the env at.
+    let filesystem =
+        effective_permissions("filesystem", &installed.permissions.filesystem, &overrides);
+    let mut args = installed.runtime.args.clone();
+    apply_socket_endpoint(name, &installed.runtime, &mut env, &mut args)?;
+    let (command, args) =
+        apply_wasm_runtime(&installed.runtime, &args, &mut env, &filesystem, &network);
     let (command, args) = apply_sandbox_runtime(
-        &installed.runtime.command,
-        &installed.runtime.args,
+        name,
+        &command,
+        &args,
         &mut env,
         sandbox_policy,
-        &installed.permissions.filesystem,
+        &filesystem,
+        &network,
     );
 
     Ok((
@@ -236,6 +368,17 @@ fn build_process_spec(
             args,
             env,
             auto_restart: Some(policy),
+            clean_env,
+            refresh_at_epoch_secs: None,
+            capture_core,
+            readiness,
+            run_as,
+            seccomp,
+            shutdown_signal,
+            shutdown_grace_period_secs,
+            health_check: health_check(&installed.runtime),
+            schedule: parse_schedule(installed)?,
+            limits: resource_limits(&installed.runtime),
         },
         undeclared_network,
     ))
@@ -3,13 +3,103 @@
 
 //! Command handler for `berth search`.
 
+use berth_registry::search::{Cursor, Page, SearchResult};
+use berth_registry::types::TrustLevel;
 use berth_registry::Registry;
 use colored::Colorize;
+use serde::Serialize;
+use std::process;
+
+use crate::shell_preview::quote;
+
+/// One search hit in `berth search --json` output.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchHit<'a> {
+    name: &'a str,
+    display_name: &'a str,
+    version: &'a str,
+    description: &'a str,
+    category: &'a str,
+    trust_level: &'a TrustLevel,
+    downloads: u64,
+    score: u32,
+}
+
+/// One page of `berth search --json --limit/--cursor` output.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchPage<'a> {
+    results: Vec<SearchHit<'a>>,
+    next_cursor: Option<String>,
+}
 
 /// Executes the `berth search` command.
-pub fn execute(query: &str) {
-    let registry = Registry::from_seed();
-    let results = registry.search(query);
+///
+/// With `limit` or `cursor` only one page is shown, followed by the cursor
+/// for the next page when more results remain.
+pub fn execute(query: &str, json: bool, limit: Option<usize>, cursor: Option<&str>) {
+    let registry = super::load_registry();
+    let paginated = limit.is_some() || cursor.is_some();
+    let Page {
+        items: results,
+        next_cursor,
+    } = if paginated {
+        match load_page(&registry, query, limit, cursor) {
+            Ok(page) => page,
+            Err(e) => {
+                eprintln!("{} {}", "✗".red().bold(), e);
+                process::exit(1);
+            }
+        }
+    } else {
+        Page {
+            items: registry.search(query),
+            next_cursor: None,
+        }
+    };
+    let next_token = next_cursor.as_ref().map(Cursor::encode);
+
+    if json {
+        let hits: Vec<SearchHit> = results
+            .iter()
+            .map(|result| SearchHit {
+                name: &result.server.name,
+                display_name: &result.server.display_name,
+                version: &result.server.version,
+                description: &result.server.description,
+                category: &result.server.category,
+                trust_level: &result.server.trust_level,
+                downloads: result.server.quality.downloads,
+                score: result.score,
+            })
+            .collect();
+        let rendered = if paginated {
+            serde_json::to_string_pretty(&SearchPage {
+                results: hits,
+                next_cursor: next_token,
+            })
+        } else {
+            serde_json::to_string_pretty(&hits)
+        };
+        match rendered {
+            Ok(rendered) => println!("{rendered}"),
+            Err(e) => {
+                eprintln!("{} Failed to serialize results: {}", "✗".red().bold(), e);
+                process::exit(1);
+            }
+        }
+        return;
+    }
+
+    if results.is_empty() && cursor.is_some() {
+        println!(
+            "{} No more servers matching '{}'",
+            "!".yellow().bold(),
+            query
+        );
+        return;
+    }
 
     if results.is_empty() {
         println!(
@@ -21,8 +111,9 @@ pub fn execute(query: &str) {
     }
 
     println!(
-        "{} Found {} server(s) matching '{}':\n",
+        "{} {} {} server(s) matching '{}':\n",
         "✓".green().bold(),
+        if paginated { "Showing" } else { "Found" },
         results.len(),
         query
     );
@@ -40,12 +131,10 @@ pub fn execute(query: &str) {
     for result in &results {
         let server = result.server;
         let trust_colored = match server.trust_level {
-            berth_registry::types::TrustLevel::Official => {
-                server.trust_level.to_string().green().bold()
-            }
-            berth_registry::types::TrustLevel::Verified => server.trust_level.to_string().cyan(),
-            berth_registry::types::TrustLevel::Community => server.trust_level.to_string().yellow(),
-            berth_registry::types::TrustLevel::Untrusted => server.trust_level.to_string().red(),
+            TrustLevel::Official => server.trust_level.to_string().green().bold(),
+            TrustLevel::Verified => server.trust_level.to_string().cyan(),
+            TrustLevel::Community => server.trust_level.to_string().yellow(),
+            TrustLevel::Untrusted => server.trust_level.to_string().red(),
         };
 
         let description = if server.description.len() > 48 {
@@ -64,12 +153,37 @@ pub fn execute(query: &str) {
     }
 
     println!();
+    if let Some(token) = next_token {
+        println!(
+            "  More results: {}",
+            format!(
+                "berth search {} --limit {} --cursor {token}",
+                quote(query),
+                results.len()
+            )
+            .bold()
+        );
+    }
     println!(
         "  Run {} for details on a specific server.",
         "berth info <server>".bold()
     );
 }
 
+/// Loads the page of results requested with `--limit`/`--cursor`.
+fn load_page<'a>(
+    registry: &'a Registry,
+    query: &str,
+    limit: Option<usize>,
+    cursor: Option<&str>,
+) -> Result<Page<SearchResult<'a>>, String> {
+    if limit == Some(0) {
+        return Err("--limit must be at least 1".to_string());
+    }
+    let after = cursor.map(Cursor::decode).transpose()?;
+    registry.search_page(query, after.as_ref(), limit.unwrap_or(usize::MAX))
+}
+
 /// Formats a download counter with `K`/`M` suffixes for display.
 fn format_downloads(n: u64) -> String {
     if n >= 1_000_000 {
@@ -0,0 +1,108 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Command handler for `berth show-command`.
+
+use colored::Colorize;
+use std::process;
+
+use berth_registry::Registry;
+
+use crate::commands::start::{build_process_spec, read_installed};
+use crate::egress_monitor::EGRESS_LOG_ENV;
+use crate::paths;
+use crate::policy_engine::load_global_policy;
+use crate::sandbox_policy::parse_sandbox_policy;
+use crate::shell_preview::env_command_line;
+
+/// Executes the `berth show-command` command.
+pub fn execute(server: &str) {
+    let config_path = match paths::server_config_path(server) {
+        Some(p) => p,
+        None => {
+            eprintln!("{} Could not determine home directory.", "✗".red().bold());
+            process::exit(1);
+        }
+    };
+    if !config_path.exists() {
+        eprintln!(
+            "{} Server {} is not installed.",
+            "✗".red().bold(),
+            server.cyan()
+        );
+        process::exit(1);
+    }
+
+    let installed = match read_installed(server, &config_path) {
+        Ok(i) => i,
+        Err(()) => process::exit(1),
+    };
+    let global_policy = match load_global_policy() {
+        Ok(policy) => policy,
+        Err(msg) => {
+            eprintln!("{} {}", "✗".red().bold(), msg);
+            process::exit(1);
+        }
+    };
+    let registry = Registry::from_seed();
+    let (spec, _) = match build_process_spec(server, &installed, &registry, &global_policy, false) {
+        Ok(v) => v,
+        Err(msg) => {
+            eprintln!("{} {}", "✗".red().bold(), msg);
+            process::exit(1);
+        }
+    };
+    let sandbox = match parse_sandbox_policy(&installed.config) {
+        Ok(policy) => policy,
+        Err(msg) => {
+            eprintln!("{} {}", "✗".red().bold(), msg);
+            process::exit(1);
+        }
+    };
+
+    let cwd = std::env::current_dir()
+        .map(|p| p.display().to_string())
+        .unwrap_or_else(|_| "(unknown)".to_string());
+    let wrapper = if spec.command != installed.runtime.command {
+        spec.command.as_str()
+    } else {
+        "none"
+    };
+    let env_names: Vec<&str> = spec.env.keys().map(String::as_str).collect();
+
+    println!("# server: {server}");
+    println!("# cwd: {cwd} (inherited from the calling process)");
+    println!(
+        "# env: {}",
+        if env_names.is_empty() {
+            "(none)".to_string()
+        } else {
+            env_names.join(", ")
+        }
+    );
+    println!(
+        "# env isolation: {}",
+        if spec.clean_env {
+            "on (only the variables above are passed)"
+        } else {
+            "off (inherits the calling environment)"
+        }
+    );
+    println!(
+        "# sandbox: {} (wrapper: {wrapper})",
+        if sandbox.enabled { "basic" } else { "off" }
+    );
+    if let Some(user) = &spec.run_as {
+        println!("# run as: {user} (via runuser when privileged, otherwise systemd-run)");
+    }
+    if let Some(log) = spec.env.get(EGRESS_LOG_ENV) {
+        println!("# egress monitor: node/python hooks log contacted hosts to {log}");
+    }
+    if let Some(profile) = &spec.seccomp {
+        println!("# seccomp: blocks {}", profile.denied().join(", "));
+    }
+    println!(
+        "{}",
+        env_command_line(&spec.env, spec.clean_env, &spec.command, &spec.args)
+    );
+}
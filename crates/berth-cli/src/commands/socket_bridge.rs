@@ -0,0 +1,158 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Internal command handler for `berth __socket-bridge`.
+//!
+//! `berth proxy` and `berth wrap` relay stdio, so servers that listen on a unix
+//! socket or named pipe are launched through this bridge. It connects to the
+//! endpoint, starting the server command first when nothing listens there
+//! (e.g. no `berth start` instance is running), and copies bytes between its
+//! stdio and the connection. A server it started is stopped when it exits.
+
+use colored::Colorize;
+use std::io::{self, Read, Write};
+use std::process::{self, Child, Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use berth_runtime::{bind_to_parent, exit_code, Connection, Endpoint};
+
+/// How long a started server gets to begin listening.
+const LISTEN_WAIT: Duration = Duration::from_secs(30);
+
+const LISTEN_POLL: Duration = Duration::from_millis(50);
+
+/// How long the server may keep answering after the client closed stdin.
+const CLOSE_GRACE: Duration = Duration::from_secs(5);
+
+/// Executes the hidden socket bridge command.
+pub fn execute(transport: &str, endpoint: &str, command: &[String]) {
+    let endpoint = match Endpoint::parse(transport, endpoint) {
+        Ok(endpoint) => endpoint,
+        Err(msg) => fail(&format!("Invalid endpoint: {msg}.")),
+    };
+    let mut started = None;
+    let connection = match endpoint.connect() {
+        Ok(connection) => connection,
+        Err(e) if e.kind() == io::ErrorKind::PermissionDenied => {
+            fail(&format!("Refusing to connect: {e}."))
+        }
+        Err(e) => {
+            let Some((program, args)) = command.split_first() else {
+                fail(&format!("Nothing listens on {endpoint}: {e}."));
+            };
+            if let Err(e) = endpoint.remove_stale() {
+                fail(&format!("Cannot reuse {endpoint}: {e}."));
+            }
+            let mut server = Command::new(program);
+            server
+                .args(args)
+                .stdin(Stdio::null())
+                .stdout(Stdio::from(io::stderr()))
+                .stderr(Stdio::inherit());
+            bind_to_parent(&mut server);
+            let mut child = match server.spawn() {
+                Ok(child) => child,
+                Err(e) => fail(&format!("Failed to start {program}: {e}")),
+            };
+            match wait_for_listener(&endpoint, &mut child) {
+                Ok(connection) => {
+                    started = Some(child);
+                    connection
+                }
+                Err(msg) => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    fail(&msg);
+                }
+            }
+        }
+    };
+
+    let started = Arc::new(Mutex::new(started));
+    let mut writer = match connection.try_clone() {
+        Ok(writer) => writer,
+        Err(e) => fail(&format!("Failed to share connection: {e}")),
+    };
+    let on_close = Arc::clone(&started);
+    thread::spawn(move || {
+        let _ = io::copy(&mut io::stdin().lock(), &mut writer);
+        let _ = writer.shutdown_write();
+        thread::sleep(CLOSE_GRACE);
+        stop(&on_close);
+        process::exit(0);
+    });
+
+    let mut reader = connection;
+    let mut stdout = io::stdout().lock();
+    let mut buf = [0u8; 8192];
+    loop {
+        match reader.read(&mut buf) {
+            Ok(0) | Err(_) => break,
+            Ok(n) => {
+                if stdout
+                    .write_all(&buf[..n])
+                    .and_then(|()| stdout.flush())
+                    .is_err()
+                {
+                    break;
+                }
+            }
+        }
+    }
+    process::exit(stop(&started));
+}
+
+/// Connects once the started server listens, or explains why it never did.
+fn wait_for_listener(endpoint: &Endpoint, child: &mut Child) -> Result<Connection, String> {
+    let deadline = Instant::now() + LISTEN_WAIT;
+    loop {
+        match endpoint.connect() {
+            Ok(connection) => return Ok(connection),
+            Err(e) if e.kind() == io::ErrorKind::PermissionDenied => {
+                return Err(format!("Refusing to connect: {e}."));
+            }
+            Err(_) => {}
+        }
+        if let Ok(Some(status)) = child.try_wait() {
+            return Err(format!(
+                "Server exited ({status}) before listening on {endpoint}."
+            ));
+        }
+        if Instant::now() >= deadline {
+            return Err(format!(
+                "Server did not listen on {endpoint} within {}s.",
+                LISTEN_WAIT.as_secs()
+            ));
+        }
+        thread::sleep(LISTEN_POLL);
+    }
+}
+
+/// Stops the server the bridge started, if any; returns the exit code to report.
+///
+/// A server that closed the connection by exiting reports its own exit code.
+fn stop(started: &Mutex<Option<Child>>) -> i32 {
+    let Ok(mut started) = started.lock() else {
+        return 1;
+    };
+    let Some(child) = started.as_mut() else {
+        return 0;
+    };
+    let deadline = Instant::now() + Duration::from_secs(1);
+    while Instant::now() < deadline {
+        if let Ok(Some(status)) = child.try_wait() {
+            return exit_code(&status).unwrap_or(1);
+        }
+        thread::sleep(LISTEN_POLL);
+    }
+    let _ = child.kill();
+    let _ = child.wait();
+    0
+}
+
+fn fail(msg: &str) -> ! {
+    eprintln!("{} {}", "✗".red().bold(), msg);
+    process::exit(1);
+}
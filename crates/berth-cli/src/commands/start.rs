@@ -4,41 +4,93 @@
 //! Command handler for `berth start`.
 
 use colored::Colorize;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs;
 use std::path::Path;
 use std::process;
+use std::thread;
+use std::time::{Duration, Instant};
 
-use berth_registry::config::InstalledServer;
+use berth_common::redact::redact_args;
+use berth_registry::config::{InstalledServer, PermissionsInfo};
 use berth_registry::Registry;
-use berth_runtime::{ProcessSpec, RuntimeManager, StartOutcome};
+use berth_runtime::{
+    Health, HealthCheck, ProcessSpec, ReadinessOutcome, RuntimeManager, StartOutcome,
+};
 
 use crate::commands::supervise;
+use crate::daemon;
+use crate::dependency_policy::{load_dependencies, parse_depends_on, startup_order};
+use crate::dry_run::{join_or_none, DryRunPlan};
+use crate::egress_allowlist::{apply_egress_allowlist, warn_cooperative_egress, EGRESS_ALLOW_ENV};
+use crate::egress_monitor::{apply_egress_monitor, parse_egress_monitor, EGRESS_LOG_ENV};
+use crate::env_isolation::apply_env_isolation;
+use crate::hints::{self, Failure};
+use crate::job_policy::parse_schedule;
+use crate::limits_policy::resource_limits;
+use crate::oauth::inject_access_token;
 use crate::paths;
 use crate::permission_filter::{
-    filter_env_map, load_permission_overrides, undeclared_network_grants,
+    effective_permissions, filter_env_map, load_permission_overrides, undeclared_network_grants,
     validate_network_permissions, NETWORK_PERMISSION_DENIED_PREFIX,
 };
 use crate::policy_engine::{
     enforce_global_policy, load_global_policy, GlobalPolicy, POLICY_DENIED_PREFIX,
 };
-use crate::runtime_policy::parse_runtime_policy;
-use crate::sandbox_policy::{parse_sandbox_policy, KEY_SANDBOX_NETWORK};
-use crate::sandbox_runtime::apply_sandbox_runtime;
-use crate::secrets::resolve_config_value;
+use crate::progress::{say, Progress, ProgressMode};
+use crate::readiness_policy::{health_check, parse_readiness};
+use crate::replica_policy::{instances, Instance, Role};
+use crate::runtime_policy::{capture_core_enabled, parse_runtime_policy, parse_shutdown_settings};
+use crate::sandbox_policy::{
+    parse_run_as, parse_sandbox_policy, parse_seccomp, SandboxNetwork, KEY_SANDBOX_NETWORK,
+};
+use crate::sandbox_runtime::{apply_sandbox_runtime, BWRAP_BACKEND, SANDBOX_BACKEND_ENV};
+use crate::secrets::{plaintext_sensitive_keys, resolve_config_value};
+use crate::shell_preview::command_line;
+use crate::socket_transport::apply_socket_endpoint;
+use crate::warnings;
+use crate::wasm_runtime::apply_wasm_runtime;
+use crate::workspace_trust;
+
+/// How often a dependency's health is re-read while dependents wait.
+const HEALTH_GATE_POLL: Duration = Duration::from_millis(100);
+
+/// Outcome of starting one server.
+enum StartResult {
+    Started,
+    AlreadyRunning,
+    Planned,
+}
 
 /// Executes the `berth start` command.
-pub fn execute(server: Option<&str>) {
-    let targets = resolve_targets(server);
+///
+/// Without a server name, servers start after the ones they depend on, and a
+/// server others depend on must pass its readiness probe and first health
+/// check before they start.
+pub fn execute(server: Option<&str>, dry_run: bool, inherit_env: bool, progress: ProgressMode) {
+    let mut targets = resolve_targets(server);
+    let dependencies = if server.is_none() {
+        load_dependencies(&targets)
+    } else {
+        BTreeMap::new()
+    };
+    if server.is_none() {
+        targets = match startup_order(&dependencies) {
+            Ok(order) => order,
+            Err(msg) => {
+                eprintln!("{} {}", "✗".red().bold(), msg);
+                process::exit(1);
+            }
+        };
+    }
     let registry = Registry::from_seed();
-    let berth_home = match paths::berth_home() {
-        Some(h) => h,
+    let runtime = match paths::runtime_manager() {
+        Some(r) => r,
         None => {
             eprintln!("{} Could not determine home directory.", "✗".red().bold());
             process::exit(1);
         }
     };
-    let runtime = RuntimeManager::new(berth_home.clone());
     let global_policy = match load_global_policy() {
         Ok(policy) => policy,
         Err(msg) => {
@@ -50,45 +102,142 @@ pub fn execute(server: Option<&str>) {
     let mut started = 0usize;
     let mut already_running = 0usize;
     let mut failed = 0usize;
+    let mut progress = Progress::new(progress, "start", targets.len());
+    let mut unavailable = BTreeSet::new();
 
     for name in &targets {
-        let config_path = match paths::server_config_path(name) {
-            Some(p) => p,
-            None => {
-                eprintln!("{} Could not determine home directory.", "✗".red().bold());
-                failed += 1;
-                continue;
+        progress.start("start", Some(name));
+        let depends_on = dependencies
+            .get(name)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        if let Some(msg) = blocked_by(name, depends_on, &targets, &unavailable) {
+            eprintln!("{} {}", "✗".red().bold(), msg);
+            progress.fail(&msg);
+            unavailable.insert(name.clone());
+            failed += 1;
+            continue;
+        }
+        let has_dependents = dependencies.values().any(|deps| deps.contains(name));
+        match start_one(
+            name,
+            &runtime,
+            &registry,
+            &global_policy,
+            dry_run,
+            inherit_env,
+            has_dependents,
+        ) {
+            Ok(result) => {
+                progress.complete();
+                match result {
+                    StartResult::Started => started += 1,
+                    StartResult::AlreadyRunning => already_running += 1,
+                    StartResult::Planned => {}
+                }
             }
-        };
-
-        let installed = match read_installed(name, &config_path) {
-            Ok(i) => i,
-            Err(()) => {
+            Err(msg) => {
+                progress.fail(&msg);
+                unavailable.insert(name.clone());
                 failed += 1;
-                continue;
             }
-        };
+        }
+    }
+    progress.finish();
 
-        let missing = missing_required_keys(&installed);
-        if !missing.is_empty() {
-            eprintln!(
-                "{} Cannot start {}. Missing required config: {}",
-                "✗".red().bold(),
-                name.cyan(),
-                missing.join(", ").yellow()
-            );
-            eprintln!(
-                "  Run {} to configure.",
-                format!("berth config {name} --set <key>=<value>").bold()
-            );
-            failed += 1;
-            continue;
+    if targets.len() > 1 && !dry_run {
+        say!(
+            "\n{} Started: {}, already running: {}, failed: {}",
+            "•".dimmed(),
+            started,
+            already_running,
+            failed
+        );
+    }
+
+    if failed > 0 {
+        process::exit(1);
+    }
+}
+
+/// Explains why `name` cannot start yet: a dependency is not installed or
+/// did not start.
+fn blocked_by(
+    name: &str,
+    depends_on: &[String],
+    installed: &[String],
+    unavailable: &BTreeSet<String>,
+) -> Option<String> {
+    depends_on.iter().find_map(|dep| {
+        if !installed.contains(dep) {
+            Some(format!("{name} depends on {dep}, which is not installed."))
+        } else if unavailable.contains(dep) {
+            Some(format!(
+                "Skipped {name}: its dependency {dep} did not start."
+            ))
+        } else {
+            None
         }
+    })
+}
 
-        let (spec, undeclared_network) =
-            match build_process_spec(name, &installed, &registry, &global_policy) {
-                Ok(spec) => spec,
-                Err(msg) => {
+/// Starts one installed server, or prints its launch plan on a dry run.
+///
+/// With `has_dependents`, a server with a health check only counts as started
+/// once the check passes. Failures are reported to the user here; the returned
+/// error is a plain summary for progress events.
+fn start_one(
+    name: &str,
+    runtime: &RuntimeManager,
+    registry: &Registry,
+    global_policy: &GlobalPolicy,
+    dry_run: bool,
+    inherit_env: bool,
+    has_dependents: bool,
+) -> Result<StartResult, String> {
+    let config_path = match paths::server_config_path(name) {
+        Some(p) => p,
+        None => {
+            let msg = "Could not determine home directory.".to_string();
+            eprintln!("{} {msg}", "✗".red().bold());
+            return Err(msg);
+        }
+    };
+
+    let installed = read_installed(name, &config_path)
+        .map_err(|()| format!("Failed to load the config of {name}."))?;
+
+    let missing = missing_required_keys(&installed);
+    if !missing.is_empty() {
+        eprintln!(
+            "{} Cannot start {}. Missing required config: {}",
+            "✗".red().bold(),
+            name.cyan(),
+            missing.join(", ").yellow()
+        );
+        Failure::MissingConfig.print(name, &installed.runtime.command);
+        return Err(format!("Missing required config: {}", missing.join(", ")));
+    }
+
+    let plaintext = plaintext_sensitive_keys(&installed, registry);
+    if !plaintext.is_empty() && !global_policy.secrets.forbid_plaintext {
+        warnings::emit(
+            warnings::PLAINTEXT_SECRETS,
+            Some(name),
+            &format!(
+                "{} stores sensitive config in plaintext: {}. Run {} to move it into the secure backend.",
+                name.cyan(),
+                plaintext.join(", ").yellow(),
+                format!("berth config migrate-secrets {name}").bold()
+            ),
+        );
+    }
+
+    let (mut spec, undeclared_network) =
+        match build_process_spec(name, &installed, registry, global_policy, inherit_env) {
+            Ok(spec) => spec,
+            Err(msg) => {
+                if !dry_run {
                     if msg.starts_with(NETWORK_PERMISSION_DENIED_PREFIX) {
                         let _ = runtime.record_audit_event(
                             name,
@@ -106,82 +255,277 @@ pub fn execute(server: Option<&str>) {
                             Some(&installed.runtime.args),
                         );
                     }
-                    eprintln!("{} {}", "✗".red().bold(), msg);
-                    failed += 1;
-                    continue;
                 }
-            };
-        if !undeclared_network.is_empty() {
-            println!(
-                "{} {} has undeclared network grant override(s): {} (log-only).",
+                eprintln!("{} {}", "✗".red().bold(), msg);
+                hints::print_for_message(&msg, name, &installed.runtime.command);
+                return Err(msg);
+            }
+        };
+    if dry_run {
+        let plan = instances(name, &installed, spec)
+            .and_then(|instances| {
+                start_plan(name, &installed, &instances, &undeclared_network, runtime)
+            })
+            .inspect_err(|msg| eprintln!("{} {}", "✗".red().bold(), msg))?;
+        plan.print();
+        return Ok(StartResult::Planned);
+    }
+    if let Err(msg) = inject_access_token(name, &installed, registry, &mut spec) {
+        eprintln!("{} {}", "✗".red().bold(), msg);
+        return Err(msg);
+    }
+    if !undeclared_network.is_empty() {
+        if warnings::due(warnings::UNDECLARED_NETWORK, Some(name)) {
+            say!(
+                "{} {} has undeclared network grant override(s): {} (log-only). {}",
                 "!".yellow().bold(),
                 name.cyan(),
-                undeclared_network.join(", ")
-            );
-            let _ = runtime.record_audit_event(
-                name,
-                "permission-network-warning",
-                None,
-                Some(&installed.runtime.command),
-                Some(&installed.runtime.args),
+                undeclared_network.join(", "),
+                warnings::snooze_hint(warnings::UNDECLARED_NETWORK)
             );
         }
-        let supervision_enabled = spec.auto_restart.is_some_and(|policy| policy.enabled);
-        let mut runtime_spec = spec.clone();
-        if supervision_enabled {
-            runtime_spec.auto_restart = None;
+        let _ = runtime.record_audit_event(
+            name,
+            "permission-network-warning",
+            None,
+            Some(&installed.runtime.command),
+            Some(&installed.runtime.args),
+        );
+    }
+    warn_cooperative_egress(name, &spec);
+    let instances = instances(name, &installed, spec)
+        .inspect_err(|msg| eprintln!("{} {}", "✗".red().bold(), msg))?;
+
+    let mut result = StartResult::AlreadyRunning;
+    for instance in &instances {
+        if let StartResult::Started = start_instance(runtime, instance, has_dependents)? {
+            result = StartResult::Started;
         }
+    }
+    Ok(result)
+}
 
-        match runtime.start(name, &runtime_spec) {
-            Ok(StartOutcome::Started) => {
-                if supervision_enabled {
-                    if let Err(msg) = supervise::spawn_detached(name, &spec, &berth_home) {
-                        let _ = runtime.stop(name);
-                        eprintln!(
-                            "{} Failed to start supervisor for {}: {}",
-                            "✗".red().bold(),
-                            name.cyan(),
-                            msg
-                        );
-                        failed += 1;
-                        continue;
-                    }
+/// Launches one instance of a server and waits for it to become ready.
+fn start_instance(
+    runtime: &RuntimeManager,
+    instance: &Instance,
+    has_dependents: bool,
+) -> Result<StartResult, String> {
+    let (name, spec) = (instance.name.as_str(), &instance.spec);
+    match launch(runtime, name, spec) {
+        Ok(StartOutcome::Started) => {
+            if let Some(schedule) = spec.schedule {
+                say!(
+                    "{} Scheduled {} ({schedule}).",
+                    "✓".green().bold(),
+                    name.cyan()
+                );
+                return Ok(StartResult::Started);
+            }
+            match instance.port {
+                Some(port) => say!(
+                    "{} Started {} on port {port}.",
+                    "✓".green().bold(),
+                    name.cyan()
+                ),
+                None => say!("{} Started {}.", "✓".green().bold(), name.cyan()),
+            }
+            if !await_readiness(runtime, name, spec) {
+                return Err(format!("{name} did not become ready."));
+            }
+            match &spec.health_check {
+                Some(check) if has_dependents && !await_health(runtime, name, check) => {
+                    Err(format!("{name} did not become healthy."))
                 }
-                println!("{} Started {}.", "✓".green().bold(), name.cyan());
-                started += 1;
+                _ => Ok(StartResult::Started),
             }
-            Ok(StartOutcome::AlreadyRunning) => {
-                println!(
-                    "{} {} is already running.",
-                    "!".yellow().bold(),
+        }
+        Ok(StartOutcome::AlreadyRunning) => {
+            say!(
+                "{} {} is already running.",
+                "!".yellow().bold(),
+                name.cyan()
+            );
+            Ok(StartResult::AlreadyRunning)
+        }
+        Err(e) => {
+            eprintln!(
+                "{} Failed to start {}: {}",
+                "✗".red().bold(),
+                name.cyan(),
+                e
+            );
+            hints::print_for_message(&e, name, &spec.command);
+            Err(format!("Failed to start {name}: {e}"))
+        }
+    }
+}
+
+/// Returns whether a server needs a supervisor process of its own when no
+/// daemon runs, i.e. something has to watch it after launch.
+pub(crate) fn needs_supervisor(spec: &ProcessSpec) -> bool {
+    spec.auto_restart.as_ref().is_some_and(|p| p.enabled)
+        || spec.refresh_at_epoch_secs.is_some()
+        || spec.capture_core
+        || spec.seccomp.is_some()
+        || spec.health_check.is_some()
+        || spec.schedule.is_some()
+        || spec.env.contains_key(EGRESS_ALLOW_ENV)
+        || spec
+            .env
+            .get(SANDBOX_BACKEND_ENV)
+            .is_some_and(|backend| backend.contains(BWRAP_BACKEND))
+}
+
+/// Launches a server through the daemon when one runs, under a supervisor
+/// when it needs one, or directly otherwise.
+pub(crate) fn launch(
+    runtime: &RuntimeManager,
+    name: &str,
+    spec: &ProcessSpec,
+) -> Result<StartOutcome, String> {
+    match daemon::start(name, spec) {
+        Some(outcome) => outcome,
+        None if needs_supervisor(spec) => supervise::launch_detached(name, spec),
+        None => runtime.start(name, spec).map_err(|e| e.to_string()),
+    }
+}
+
+/// Waits for a freshly started server to pass its readiness probe, if it has one.
+///
+/// Returns whether the server is running; a server that exits or times out is
+/// reported and, on timeout, stopped.
+pub(crate) fn await_readiness(runtime: &RuntimeManager, name: &str, spec: &ProcessSpec) -> bool {
+    let Some(check) = &spec.readiness else {
+        return true;
+    };
+    say!(
+        "{}",
+        format!(
+            "  Waiting for {} ({}) ...",
+            check.probe,
+            format_ms(check.timeout_ms)
+        )
+        .dimmed()
+    );
+    match runtime.await_ready(name, check) {
+        Ok(ReadinessOutcome::Ready { after_ms }) => {
+            say!(
+                "{} {} is ready after {}.",
+                "✓".green().bold(),
+                name.cyan(),
+                format_ms(after_ms)
+            );
+            true
+        }
+        Ok(ReadinessOutcome::Exited) => {
+            eprintln!(
+                "{} {} exited before becoming ready. Check {}.",
+                "✗".red().bold(),
+                name.cyan(),
+                format!("berth logs {name}").bold()
+            );
+            hints::print_for_logs(runtime, name, &spec.command);
+            false
+        }
+        Ok(ReadinessOutcome::TimedOut) => {
+            eprintln!(
+                "{} {} did not become ready within {}; stopped it.",
+                "✗".red().bold(),
+                name.cyan(),
+                format_ms(check.timeout_ms)
+            );
+            false
+        }
+        Err(e) => {
+            eprintln!(
+                "{} Failed to check readiness of {}: {}",
+                "✗".red().bold(),
+                name.cyan(),
+                e
+            );
+            false
+        }
+    }
+}
+
+/// Waits for a server others depend on to pass its first health check.
+///
+/// Gives up when the server is recorded as unhealthy, exits, or has not passed
+/// after as many intervals as it may fail plus one.
+fn await_health(runtime: &RuntimeManager, name: &str, check: &HealthCheck) -> bool {
+    let timeout_ms = check
+        .interval_secs
+        .saturating_mul(1_000)
+        .saturating_mul(u64::from(check.failure_threshold) + 1)
+        .saturating_add(check.timeout_ms);
+    say!(
+        "{}",
+        format!(
+            "  Waiting for health check ({}) before starting dependents ...",
+            check.probe
+        )
+        .dimmed()
+    );
+    let started = Instant::now();
+    loop {
+        match runtime.health(name) {
+            Ok(Some(Health::Healthy)) => {
+                say!(
+                    "{} {} is healthy after {}.",
+                    "✓".green().bold(),
+                    name.cyan(),
+                    format_ms(started.elapsed().as_millis() as u64)
+                );
+                return true;
+            }
+            Ok(Some(Health::Unhealthy)) => {
+                eprintln!(
+                    "{} {} is unhealthy; servers depending on it are not started.",
+                    "✗".red().bold(),
                     name.cyan()
                 );
-                already_running += 1;
+                return false;
             }
+            Ok(None) => {}
             Err(e) => {
                 eprintln!(
-                    "{} Failed to start {}: {}",
+                    "{} Failed to check health of {}: {}",
                     "✗".red().bold(),
                     name.cyan(),
                     e
                 );
-                failed += 1;
+                return false;
             }
         }
+        if runtime.running_pid(name).ok().flatten().is_none() {
+            eprintln!(
+                "{} {} exited before passing its health check. Check {}.",
+                "✗".red().bold(),
+                name.cyan(),
+                format!("berth logs {name}").bold()
+            );
+            return false;
+        }
+        if started.elapsed() >= Duration::from_millis(timeout_ms) {
+            eprintln!(
+                "{} {} did not pass its health check within {}.",
+                "✗".red().bold(),
+                name.cyan(),
+                format_ms(timeout_ms)
+            );
+            return false;
+        }
+        thread::sleep(HEALTH_GATE_POLL);
     }
+}
 
-    if targets.len() > 1 {
-        println!(
-            "\n{} Started: {}, already running: {}, failed: {}",
-            "•".dimmed(),
-            started,
-            already_running,
-            failed
-        );
-    }
-
-    if failed > 0 {
-        process::exit(1);
+/// Formats milliseconds as `850ms` or `2.5s`.
+fn format_ms(ms: u64) -> String {
+    if ms < 1_000 {
+        format!("{ms}ms")
+    } else {
+        format!("{}s", ms as f64 / 1_000.0)
     }
 }
 
@@ -251,7 +595,7 @@ fn resolve_targets(server: Option<&str>) -> Vec<String> {
 }
 
 /// Loads and parses an installed server config file.
-fn read_installed(name: &str, config_path: &Path) -> Result<InstalledServer, ()> {
+pub(crate) fn read_installed(name: &str, config_path: &Path) -> Result<InstalledServer, ()> {
     let content = match fs::read_to_string(config_path) {
         Ok(c) => c,
         Err(e) => {
@@ -290,16 +634,188 @@ fn missing_required_keys(installed: &InstalledServer) -> Vec<String> {
         .collect()
 }
 
+/// Describes what `berth start` would do for one server without side effects.
+///
+/// Replicated servers show the launch of their first replica.
+fn start_plan(
+    name: &str,
+    installed: &InstalledServer,
+    instances: &[Instance],
+    undeclared_network: &[String],
+    runtime: &RuntimeManager,
+) -> Result<DryRunPlan, String> {
+    let spec = &instances[0].spec;
+    let mut plan = DryRunPlan::new("start", name);
+    if let Some(pid) = runtime
+        .running_pid(name)
+        .map_err(|e| format!("Failed to read runtime state for {}: {e}", name.cyan()))?
+    {
+        plan.add(
+            "outcome",
+            format!("already running (pid {pid}); nothing to do"),
+        );
+        return Ok(plan);
+    }
+
+    plan.add(
+        "outcome",
+        if spec.schedule.is_some() {
+            "would schedule"
+        } else {
+            "would start"
+        },
+    );
+    if let Some(schedule) = spec.schedule {
+        plan.add("schedule", format!("{schedule} (supervisor process)"));
+    }
+    let depends_on = parse_depends_on(&installed.config);
+    if !depends_on.is_empty() {
+        plan.add("depends on", depends_on.join(", "));
+    }
+    for instance in instances {
+        match (instance.role, instance.port) {
+            (Role::Replica(_), Some(port)) => {
+                plan.add("replica", format!("{} on port {port}", instance.name))
+            }
+            (Role::Gateway, Some(port)) => plan.add(
+                "gateway",
+                format!(
+                    "{} on port {port} (round robin per connection)",
+                    instance.name
+                ),
+            ),
+            (_, Some(port)) => plan.add("port", port.to_string()),
+            _ => {}
+        }
+    }
+    plan.add(
+        "command",
+        command_line(&spec.command, &redact_args(&spec.args)),
+    );
+    plan.add(
+        "env",
+        join_or_none(&spec.env.keys().cloned().collect::<Vec<_>>()),
+    );
+    plan.add(
+        "env isolation",
+        if spec.clean_env {
+            "on (parent environment not inherited)"
+        } else {
+            "off (inherits parent environment)"
+        },
+    );
+
+    let overrides = load_permission_overrides(name)?;
+    let declared = &installed.permissions;
+    plan.add_permissions(&PermissionsInfo {
+        network: effective_permissions("network", &declared.network, &overrides),
+        env: effective_permissions("env", &declared.env, &overrides),
+        filesystem: effective_permissions("filesystem", &declared.filesystem, &overrides),
+        exec: effective_permissions("exec", &declared.exec, &overrides),
+        tools: declared.tools.clone(),
+    });
+    if !undeclared_network.is_empty() {
+        plan.add(
+            "warning",
+            format!(
+                "undeclared network grant override(s): {}",
+                undeclared_network.join(", ")
+            ),
+        );
+    }
+
+    let sandbox = parse_sandbox_policy(&installed.config)?;
+    plan.add("policy global", "allowed");
+    plan.add(
+        "policy sandbox",
+        if sandbox.enabled {
+            let backend = spec
+                .env
+                .get(SANDBOX_BACKEND_ENV)
+                .map_or("none", String::as_str);
+            format!("basic ({backend})")
+        } else {
+            "off".to_string()
+        },
+    );
+    let auto_restart = spec.auto_restart.as_ref().filter(|p| p.enabled);
+    plan.add(
+        "policy auto-restart",
+        match auto_restart {
+            Some(p) => match &p.restart_on_exit_codes {
+                Some(codes) => format!(
+                    "enabled (max {} restarts on exit codes {}, supervisor process)",
+                    p.max_restarts,
+                    codes
+                        .iter()
+                        .map(i32::to_string)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+                None => format!(
+                    "enabled (max {} restarts, supervisor process)",
+                    p.max_restarts
+                ),
+            },
+            None => "disabled".to_string(),
+        },
+    );
+    if let Some(check) = &spec.readiness {
+        plan.add(
+            "readiness",
+            format!("{} (timeout {})", check.probe, format_ms(check.timeout_ms)),
+        );
+    }
+    if let Some(check) = &spec.health_check {
+        plan.add(
+            "health check",
+            format!(
+                "{} every {}s (unhealthy after {} failures, supervisor process)",
+                check.probe, check.interval_secs, check.failure_threshold
+            ),
+        );
+    }
+    if spec.capture_core {
+        plan.add(
+            "crash capture",
+            "core dumps enabled, reports in logs/ (supervisor process)",
+        );
+    }
+    if let Some(log) = spec.env.get(EGRESS_LOG_ENV) {
+        plan.add(
+            "egress monitor",
+            format!("node/python hooks log hosts to {log}"),
+        );
+    }
+    if let Some(profile) = &spec.seccomp {
+        plan.add(
+            "seccomp",
+            format!(
+                "blocks {} syscalls (supervisor process)",
+                profile.denied().len()
+            ),
+        );
+    }
+
+    for instance in instances {
+        plan.add_write(&runtime.state_path(&instance.name));
+        plan.add_write(&runtime.log_path(&instance.name));
+    }
+    plan.add_write(&runtime.audit_log_path());
+    Ok(plan)
+}
+
 /// Builds a runtime process spec from installed metadata and config values.
-fn build_process_spec(
+pub(crate) fn build_process_spec(
     name: &str,
     installed: &InstalledServer,
     registry: &Registry,
     global_policy: &GlobalPolicy,
+    inherit_env: bool,
 ) -> Result<(ProcessSpec, Vec<String>), String> {
     let mut env = BTreeMap::new();
 
-    if let Some(meta) = registry.get(name) {
+    if let Some(meta) = registry.get(&installed.server.name) {
         for field in meta
             .config
             .required
@@ -325,11 +841,14 @@ fn build_process_spec(
     }
 
     let overrides = load_permission_overrides(name)?;
-    enforce_global_policy(name, &installed.permissions, &overrides, global_policy)?;
+    enforce_global_policy(name, installed, &overrides, global_policy, registry)?;
+    workspace_trust::check()?;
     validate_network_permissions(name, &installed.permissions.network, &overrides)?;
     let undeclared_network = undeclared_network_grants(&installed.permissions.network, &overrides);
     let sandbox_policy = parse_sandbox_policy(&installed.config)?;
-    if sandbox_policy.network_deny_all {
+    let run_as = parse_run_as(&installed.config)?;
+    let seccomp = parse_seccomp(&installed.config, &installed.runtime.syscalls)?;
+    if sandbox_policy.network == SandboxNetwork::DenyAll {
         return Err(format!(
             "{NETWORK_PERMISSION_DENIED_PREFIX}Server {} blocked by sandbox policy: set `{KEY_SANDBOX_NETWORK}=inherit` or relax network constraints.",
             name.cyan()
@@ -337,12 +856,36 @@ fn build_process_spec(
     }
     filter_env_map(&mut env, &installed.permissions.env, &overrides);
     let policy = parse_runtime_policy(&installed.config)?;
+    let (shutdown_signal, shutdown_grace_period_secs) = parse_shutdown_settings(&installed.config)?;
+    let capture_core = capture_core_enabled(installed)?;
+    let readiness = parse_readiness(&installed.config)?;
+    let network = effective_permissions("network", &installed.permissions.network, &overrides);
+    let clean_env = apply_env_isolation(
+        &mut env,
+        &installed.permissions.env,
+        &overrides,
+        inherit_env,
+    );
+    if sandbox_policy.network == SandboxNetwork::Allowlist {
+        apply_egress_allowlist(name, &mut env, clean_env, &network)?;
+    } else if parse_egress_monitor(&installed.config)? {
+        apply_egress_monitor(name, &mut env, clean_env)?;
+    }
+    // Wrapped last, so the sandbox can mount what egress setup points the env at.
+    let filesystem =
+        effective_permissions("filesystem", &installed.permissions.filesystem, &overrides);
+    let mut args = installed.runtime.args.clone();
+    apply_socket_endpoint(name, &installed.runtime, &mut env, &mut args)?;
+    let (command, args) =
+        apply_wasm_runtime(&installed.runtime, &args, &mut env, &filesystem, &network);
     let (command, args) = apply_sandbox_runtime(
-        &installed.runtime.command,
-        &installed.runtime.args,
+        name,
+        &command,
+        &args,
         &mut env,
         sandbox_policy,
-        &installed.permissions.filesystem,
+        &filesystem,
+        &network,
     );
 
     Ok((
@@ -351,6 +894,17 @@ fn build_process_spec(
             args,
             env,
             auto_restart: Some(policy),
+            clean_env,
+            refresh_at_epoch_secs: None,
+            capture_core,
+            readiness,
+            run_as,
+            seccomp,
+            shutdown_signal,
+            shutdown_grace_period_secs,
+            health_check: health_check(&installed.runtime),
+            schedule: parse_schedule(installed)?,
+            limits: resource_limits(&installed.runtime),
         },
         undeclared_network,
     ))
@@ -371,23 +925,33 @@ mod tests {
                 category: "developer-tools".to_string(),
                 maintainer: "Anthropic".to_string(),
                 trust_level: "official".to_string(),
+                tags: Vec::new(),
+                version_constraint: None,
             },
             source: berth_registry::config::SourceInfo {
                 source_type: "npm".to_string(),
                 package: "@pkg".to_string(),
                 repository: "https://example.com".to_string(),
+                registry: None,
+                sha256: None,
             },
             runtime: berth_registry::config::RuntimeInfo {
                 runtime_type: "node".to_string(),
                 command: "npx".to_string(),
                 args: vec![],
                 transport: "stdio".to_string(),
+                syscalls: vec![],
+                health_check: None,
+                schedule: None,
+                limits: None,
+                socket: None,
             },
             permissions: berth_registry::config::PermissionsInfo {
                 network: vec![],
                 env: vec![],
                 filesystem: vec![],
                 exec: vec![],
+                ..Default::default()
             },
             config: std::collections::BTreeMap::from([
                 ("token".to_string(), "".to_string()),
@@ -396,6 +960,7 @@ mod tests {
             config_meta: berth_registry::config::ConfigMeta {
                 required_keys: vec!["token".to_string()],
                 optional_keys: vec!["enterprise-url".to_string()],
+                sensitive_keys: vec!["token".to_string()],
             },
         };
 
@@ -4,34 +4,104 @@
 //! Command handler for `berth status`.
 
 use colored::Colorize;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
-use std::fs;
+use std::fs::{self, DirEntry};
 use std::process;
 use std::process::Command;
 
+use berth_common::now_epoch_secs;
 use berth_registry::config::InstalledServer;
 use berth_registry::Registry;
-use berth_runtime::{ProcessSpec, RuntimeManager, ServerStatus};
+use berth_runtime::{Health, JobRun, ProcessSpec, ResourceLimits, RuntimeManager, ServerStatus};
 
+use crate::credential_expiry::{
+    classify, credential_expiry, format_span, CredentialState, KEY_CREDENTIAL_EXPIRES_AT,
+};
+use crate::daemon;
+use crate::egress_allowlist::apply_egress_allowlist;
+use crate::egress_monitor::{apply_egress_monitor, parse_egress_monitor};
+use crate::env_isolation::apply_env_isolation;
+use crate::error_budget::{self, overruns, parse_error_budget, Windows};
+use crate::job_policy::{is_job, parse_schedule};
+use crate::limits_policy::resource_limits;
+use crate::observer;
 use crate::paths;
 use crate::permission_filter::{
-    filter_env_map, load_permission_overrides, validate_network_permissions,
+    effective_permissions, filter_env_map, load_permission_overrides, validate_network_permissions,
 };
 use crate::policy_engine::{enforce_global_policy, load_global_policy, GlobalPolicy};
-use crate::runtime_policy::parse_runtime_policy;
-use crate::sandbox_policy::parse_sandbox_policy;
+use crate::readiness_policy::{health_check, parse_readiness};
+use crate::replica_policy::{instances, Role};
+use crate::runtime_policy::{capture_core_enabled, parse_runtime_policy, parse_shutdown_settings};
+use crate::sandbox_policy::{parse_run_as, parse_sandbox_policy, parse_seccomp, SandboxNetwork};
 use crate::sandbox_runtime::apply_sandbox_runtime;
 use crate::secrets::resolve_config_value;
+use crate::socket_transport::apply_socket_endpoint;
+use crate::time_format::now_epoch_ms;
+use crate::wasm_runtime::apply_wasm_runtime;
+use crate::workspace_trust;
 
 #[derive(Debug, Deserialize)]
 struct RuntimeStateSnapshot {
     #[serde(default)]
     pid: Option<u32>,
+    #[serde(default)]
+    clean_env: Option<bool>,
+}
+
+/// One server in `berth status --json` output.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ServerRow {
+    name: String,
+    /// Installed version; `None` when the config cannot be read.
+    version: Option<String>,
+    /// `None` when the config or runtime state cannot be read.
+    status: Option<ServerStatus>,
+    pid: Option<u32>,
+    memory_kib: Option<u64>,
+    /// Whether the server is over its error budget.
+    flaky: bool,
+    /// Resource limits from the manifest, if any.
+    limits: Option<ResourceLimits>,
+    /// CPU usage in percent of one core; only measured for limited servers.
+    cpu_percent: Option<f32>,
+    /// Open file descriptors; only measured for limited servers on Linux.
+    open_files: Option<u64>,
+    /// Server this row is a replica of, e.g. `github` for `github@2`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    replica_of: Option<String>,
+    /// Port an `http` server, replica or gateway listens on.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    port: Option<u16>,
+}
+
+/// One job in `berth status --jobs --json` output.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JobRow {
+    name: String,
+    /// Normalized schedule, e.g. `every 1h`; `None` when it is invalid.
+    schedule: Option<String>,
+    status: Option<ServerStatus>,
+    last_run: Option<JobRun>,
+    next_run_epoch_ms: Option<u64>,
+}
+
+/// Executes `berth status --summary-file`: brings the status summary up to
+/// date and prints its path.
+pub fn print_summary_file() {
+    let Some(runtime) = paths::runtime_manager() else {
+        eprintln!("{} Could not determine home directory.", "✗".red().bold());
+        process::exit(1);
+    };
+    runtime.refresh_status_summary();
+    println!("{}", runtime.status_summary_path().display());
 }
 
-/// Executes the `berth status` command.
-pub fn execute() {
+/// Executes the `berth status` command; `jobs` lists scheduled jobs instead of servers.
+pub fn execute(jobs: bool, json: bool) {
     let servers_dir = match paths::berth_servers_dir() {
         Some(d) => d,
         None => {
@@ -40,6 +110,10 @@ pub fn execute() {
         }
     };
 
+    if !servers_dir.exists() && json {
+        println!("[]");
+        return;
+    }
     if !servers_dir.exists() {
         println!("{} No servers installed.", "!".yellow().bold());
         println!("  Run {} to install one.", "berth install <server>".bold());
@@ -61,6 +135,10 @@ pub fn execute() {
         }
     };
 
+    if entries.is_empty() && json {
+        println!("[]");
+        return;
+    }
     if entries.is_empty() {
         println!("{} No servers installed.", "!".yellow().bold());
         println!("  Run {} to install one.", "berth install <server>".bold());
@@ -69,14 +147,13 @@ pub fn execute() {
 
     entries.sort_by_key(|e| e.path());
 
-    let berth_home = match paths::berth_home() {
-        Some(h) => h,
+    let runtime = match paths::runtime_manager() {
+        Some(r) => r,
         None => {
             eprintln!("{} Could not determine home directory.", "✗".red().bold());
             process::exit(1);
         }
     };
-    let runtime = RuntimeManager::new(berth_home);
     let registry = Registry::from_seed();
     let global_policy = match load_global_policy() {
         Ok(policy) => policy,
@@ -86,18 +163,34 @@ pub fn execute() {
         }
     };
 
-    println!("{} MCP server status:\n", "✓".green().bold());
-    println!(
-        "  {:<20} {:<12} {:<12} {:<8} {:<12}",
-        "NAME".bold(),
-        "VERSION".bold(),
-        "STATUS".bold(),
-        "PID".bold(),
-        "MEMORY".bold(),
-    );
-    println!("  {}", "─".repeat(72));
+    if jobs {
+        if !print_jobs(&entries, &runtime, &registry, &global_policy, json) {
+            process::exit(1);
+        }
+        return;
+    }
+
+    // Health signals are advisory; a status listing never fails over them.
+    let now_ms = now_epoch_ms();
+    let windows = error_budget::refresh(now_ms).ok();
+
+    if !json {
+        println!("{} MCP server status:\n", "✓".green().bold());
+        println!(
+            "  {:<20} {:<12} {:<14} {:<8} {:<12} {}",
+            "NAME".bold(),
+            "VERSION".bold(),
+            "STATUS".bold(),
+            "PID".bold(),
+            "MEMORY".bold(),
+            "LIMITS".bold(),
+        );
+        println!("  {}", "─".repeat(96));
+    }
 
     let mut had_error = false;
+    let mut hidden_jobs = 0usize;
+    let mut rows = Vec::new();
     for entry in &entries {
         let path = entry.path();
         let name = path
@@ -106,97 +199,395 @@ pub fn execute() {
             .to_string_lossy()
             .to_string();
 
-        let installed = match fs::read_to_string(&path) {
-            Ok(content) => match toml::from_str::<InstalledServer>(&content) {
-                Ok(installed) => installed,
-                Err(_) => {
-                    had_error = true;
-                    println!(
-                        "  {:<20} {:<12} {:<12} {:<8} {:<12}",
-                        name.cyan(),
-                        "?",
-                        "error".red(),
-                        "-",
-                        "-"
-                    );
-                    continue;
-                }
-            },
-            Err(_) => {
-                had_error = true;
+        let installed = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| toml::from_str::<InstalledServer>(&content).ok());
+        let Some(installed) = installed else {
+            had_error = true;
+            if json {
+                rows.push(ServerRow {
+                    name,
+                    version: None,
+                    status: None,
+                    pid: None,
+                    memory_kib: None,
+                    flaky: false,
+                    limits: None,
+                    cpu_percent: None,
+                    open_files: None,
+                    replica_of: None,
+                    port: None,
+                });
+            } else {
                 println!(
-                    "  {:<20} {:<12} {:<12} {:<8} {:<12}",
+                    "  {:<20} {:<12} {:<14} {:<8} {:<12} -",
                     name.cyan(),
                     "?",
                     "error".red(),
                     "-",
                     "-"
                 );
-                continue;
             }
+            continue;
         };
+        if is_job(&installed) {
+            hidden_jobs += 1;
+            continue;
+        }
         let version = installed.server.version.clone();
 
-        let spec = match build_process_spec(&name, &installed, &registry, &global_policy) {
-            Ok(spec) => Some(spec),
-            Err(_) => {
+        let inherit_env = launched_with_inherited_env(&name);
+        let spec =
+            match build_process_spec(&name, &installed, &registry, &global_policy, inherit_env) {
+                Ok(spec) => Some(spec),
+                Err(_) => {
+                    had_error = true;
+                    None
+                }
+            };
+        // A replicated server shows its gateway first, then one row per replica.
+        let mut instances = match spec.map(|spec| instances(&name, &installed, spec)) {
+            Some(Ok(instances)) => instances.into_iter().map(Some).collect(),
+            Some(Err(_)) => {
                 had_error = true;
-                None
+                vec![None]
             }
+            None => vec![None],
         };
+        instances.sort_by_key(|i| i.as_ref().is_some_and(|i| i.role != Role::Gateway));
 
-        let (status_display, pid_display, memory_display) = match spec.as_ref().map_or_else(
-            || runtime.status(&name),
-            |s| runtime.status_with_spec(&name, Some(s)),
-        ) {
-            Ok(ServerStatus::Running) => {
-                let pid = read_runtime_pid(&name);
-                let pid_display = pid
-                    .map(|p| p.to_string())
-                    .unwrap_or_else(|| "-".to_string());
-                let memory_display = pid
-                    .and_then(resident_memory_kib)
-                    .map(|kib| format!("{kib} KiB"))
-                    .unwrap_or_else(|| "-".to_string());
-                ("running".green().to_string(), pid_display, memory_display)
-            }
-            Ok(ServerStatus::Stopped) => (
-                "stopped".dimmed().to_string(),
-                "-".to_string(),
-                "-".to_string(),
-            ),
-            Err(_) => {
-                had_error = true;
-                ("error".red().to_string(), "-".to_string(), "-".to_string())
+        for instance in instances {
+            let row_name = instance.as_ref().map_or(name.as_str(), |i| i.name.as_str());
+            let spec = instance.as_ref().map(|i| &i.spec);
+            let port = instance.as_ref().and_then(|i| i.port);
+            let replica_of = instance
+                .as_ref()
+                .filter(|i| matches!(i.role, Role::Replica(_)))
+                .map(|_| name.clone());
+
+            let status = daemon::status(&runtime, row_name, spec);
+            let running = matches!(
+                status,
+                Ok(ServerStatus::Starting | ServerStatus::Running | ServerStatus::Unhealthy)
+            );
+            let pid = read_runtime_pid(row_name).filter(|_| running);
+            let memory_kib = pid.and_then(resident_memory_kib);
+            let limits = spec.and_then(|spec| spec.limits);
+            let (cpu_percent, open_files) = match (pid, limits) {
+                (Some(pid), Some(_)) => (cpu_usage_percent(pid), open_file_count(pid)),
+                _ => (None, None),
+            };
+            let status_display = match status {
+                Ok(status) => status_label(status),
+                Err(_) => {
+                    had_error = true;
+                    "error".red().to_string()
+                }
+            };
+            if json {
+                let flaky = replica_of.is_none()
+                    && windows.as_ref().is_some_and(|windows| {
+                        parse_error_budget(&installed.config).is_ok_and(|budget| {
+                            !overruns(&windows.totals(&name, now_ms), &budget).is_empty()
+                        })
+                    });
+                rows.push(ServerRow {
+                    name: row_name.to_string(),
+                    version: Some(version.clone()),
+                    status: status.ok(),
+                    pid,
+                    memory_kib,
+                    flaky,
+                    limits,
+                    cpu_percent,
+                    open_files,
+                    replica_of,
+                    port,
+                });
+                continue;
             }
-        };
 
+            println!(
+                "  {:<20} {:<12} {:<14} {:<8} {:<12} {}",
+                row_name.cyan(),
+                version,
+                status_display,
+                pid.map_or_else(|| "-".to_string(), |p| p.to_string()),
+                memory_kib.map_or_else(|| "-".to_string(), |kib| format!("{kib} KiB")),
+                limits.map_or_else(
+                    || "-".to_string(),
+                    |limits| limits_usage(&limits, memory_kib, cpu_percent, open_files)
+                )
+            );
+            if replica_of.is_none() {
+                print_credential_warning(&name, &installed, running);
+                if let Some(windows) = &windows {
+                    print_flaky_warning(&name, &installed, &registry, windows, now_ms);
+                }
+            }
+            if let Ok(status @ (ServerStatus::Unhealthy | ServerStatus::CrashLooping)) = status {
+                print_failure_hint(&runtime, row_name, status);
+            }
+        }
+    }
+    if json {
+        match serde_json::to_string_pretty(&rows) {
+            Ok(rendered) => println!("{rendered}"),
+            Err(e) => {
+                eprintln!("{} Failed to serialize status: {}", "✗".red().bold(), e);
+                process::exit(1);
+            }
+        }
+    } else {
+        println!();
+    }
+    if hidden_jobs > 0 && !json {
         println!(
-            "  {:<20} {:<12} {:<12} {:<8} {:<12}",
-            name.cyan(),
-            version,
-            status_display,
-            pid_display,
-            memory_display
+            "  {} scheduled job(s) not shown. Run {} to list them.\n",
+            hidden_jobs,
+            "berth status --jobs".bold()
         );
     }
-    println!();
 
     if had_error {
         process::exit(1);
     }
 }
 
+/// Prints the scheduled jobs table, or JSON rows; returns `false` when a job
+/// could not be read.
+fn print_jobs(
+    entries: &[DirEntry],
+    runtime: &RuntimeManager,
+    registry: &Registry,
+    global_policy: &GlobalPolicy,
+    json: bool,
+) -> bool {
+    let mut ok = true;
+    let rows: Vec<JobRow> = entries
+        .iter()
+        .filter_map(|entry| {
+            let path = entry.path();
+            let installed: InstalledServer =
+                toml::from_str(&fs::read_to_string(&path).ok()?).ok()?;
+            let name = path.file_stem()?.to_string_lossy().to_string();
+            is_job(&installed).then_some((name, installed))
+        })
+        .map(|(name, installed)| {
+            let inherit_env = launched_with_inherited_env(&name);
+            let spec = build_process_spec(&name, &installed, registry, global_policy, inherit_env);
+            let schedule = spec.as_ref().ok().and_then(|s| s.schedule);
+            let status = if observer::enabled() {
+                runtime.peek_status(&name)
+            } else {
+                runtime.status_with_spec(&name, spec.as_ref().ok())
+            }
+            .ok();
+            ok &= schedule.is_some() && status.is_some();
+            JobRow {
+                schedule: schedule.map(|s| s.to_string()),
+                status,
+                last_run: runtime
+                    .job_runs(&name, 1)
+                    .ok()
+                    .and_then(|mut runs| runs.pop()),
+                next_run_epoch_ms: runtime.next_job_run(&name).ok().flatten(),
+                name,
+            }
+        })
+        .collect();
+
+    if json {
+        match serde_json::to_string_pretty(&rows) {
+            Ok(rendered) => println!("{rendered}"),
+            Err(e) => {
+                eprintln!("{} Failed to serialize jobs: {}", "✗".red().bold(), e);
+                process::exit(1);
+            }
+        }
+        return ok;
+    }
+    if rows.is_empty() {
+        println!("{} No scheduled jobs installed.", "!".yellow().bold());
+        return true;
+    }
+
+    let now_secs = now_epoch_ms() / 1_000;
+    println!("{} Scheduled jobs:\n", "✓".green().bold());
+    println!(
+        "  {:<20} {:<12} {:<12} {:<12} {:<10} {:<12}",
+        "NAME".bold(),
+        "SCHEDULE".bold(),
+        "STATUS".bold(),
+        "LAST RUN".bold(),
+        "RESULT".bold(),
+        "NEXT RUN".bold(),
+    );
+    println!("  {}", "─".repeat(82));
+
+    for row in &rows {
+        let status = match row.status {
+            Some(status) => status_label(status),
+            None => "error".red().to_string(),
+        };
+        let last_run = row.last_run.as_ref().map_or_else(
+            || "-".to_string(),
+            |run| {
+                let ago = now_secs.saturating_sub(run.started_at_epoch_ms / 1_000);
+                format!("{} ago", format_span(ago))
+            },
+        );
+        let result = match row
+            .last_run
+            .as_ref()
+            .map(|run| (run.succeeded(), run.exit_code))
+        {
+            Some((true, _)) => "ok".green().to_string(),
+            Some((false, Some(code))) => format!("exit {code}").red().to_string(),
+            Some((false, None)) => "killed".red().to_string(),
+            None => "-".dimmed().to_string(),
+        };
+        let next_run = row.next_run_epoch_ms.map_or_else(
+            || "-".to_string(),
+            |at| format!("in {}", format_span((at / 1_000).saturating_sub(now_secs))),
+        );
+        println!(
+            "  {:<20} {:<12} {:<12} {:<12} {:<10} {:<12}",
+            row.name.cyan(),
+            row.schedule.as_deref().unwrap_or("?"),
+            status,
+            last_run,
+            result,
+            next_run
+        );
+    }
+    println!();
+    ok
+}
+
+/// Returns the colored label for a server status, as shown by `status` and `list`.
+pub(crate) fn status_label(status: ServerStatus) -> String {
+    match status {
+        ServerStatus::Starting => "starting".yellow().to_string(),
+        ServerStatus::Running => "running".green().to_string(),
+        ServerStatus::Unhealthy => "unhealthy".red().to_string(),
+        ServerStatus::CrashLooping => "crash-looping".red().to_string(),
+        ServerStatus::Scheduled => "scheduled".cyan().to_string(),
+        ServerStatus::Stopped => "stopped".dimmed().to_string(),
+    }
+}
+
+/// Points at the log for servers that are unhealthy or crash-looping.
+fn print_failure_hint(runtime: &RuntimeManager, name: &str, status: ServerStatus) {
+    let reason = match status {
+        ServerStatus::Unhealthy
+            if runtime.health(name).ok().flatten() == Some(Health::Unhealthy) =>
+        {
+            "keeps failing its health check"
+        }
+        ServerStatus::Unhealthy => "has not passed its readiness probe",
+        _ => "kept exiting until its restart budget ran out",
+    };
+    println!(
+        "    {} {} {}; see {}.",
+        "!".yellow().bold(),
+        name.cyan(),
+        reason,
+        format!("berth logs {name}").bold()
+    );
+}
+
+/// Flags a server that exceeded its error budget, with suggested next steps.
+fn print_flaky_warning(
+    name: &str,
+    installed: &InstalledServer,
+    registry: &Registry,
+    windows: &Windows,
+    now_ms: u64,
+) {
+    let Ok(budget) = parse_error_budget(&installed.config) else {
+        return;
+    };
+    let reasons = overruns(&windows.totals(name, now_ms), &budget);
+    if reasons.is_empty() {
+        return;
+    }
+    println!(
+        "    {} {} in the last 24h: {}",
+        "!".yellow().bold(),
+        "flaky".red(),
+        reasons.join(", ")
+    );
+    if let Some(meta) = registry.get(&installed.server.name) {
+        if meta.version != installed.server.version {
+            println!(
+                "      update available ({} → {}): run {}",
+                installed.server.version,
+                meta.version,
+                format!("berth update {name}").bold()
+            );
+        }
+    }
+    println!(
+        "      if it persists, report it to the registry: {}",
+        format!("POST /servers/{}/report", installed.server.name).bold()
+    );
+}
+
+/// Warns about credentials that are close to or past their expiry.
+///
+/// Recorded OAuth expiries only matter while the server runs, since every
+/// start issues a fresh token; a configured expiry is always reported.
+fn print_credential_warning(name: &str, installed: &InstalledServer, running: bool) {
+    let configured = installed
+        .config
+        .get(KEY_CREDENTIAL_EXPIRES_AT)
+        .is_some_and(|v| !v.trim().is_empty());
+    if !running && !configured {
+        return;
+    }
+    let Some(expires_at) = credential_expiry(name, &installed.config) else {
+        return;
+    };
+    let hint = if configured {
+        format!("update them and `{KEY_CREDENTIAL_EXPIRES_AT}`")
+    } else {
+        format!("run `berth restart {name}` to refresh")
+    };
+    match classify(expires_at, now_epoch_secs()) {
+        CredentialState::Valid { .. } => {}
+        state @ CredentialState::ExpiringSoon { .. } => {
+            println!(
+                "    {} credentials {}",
+                "!".yellow().bold(),
+                state.describe()
+            );
+        }
+        state @ CredentialState::Expired { .. } => {
+            println!(
+                "    {} credentials {}; {}",
+                "✗".red().bold(),
+                state.describe(),
+                hint
+            );
+        }
+    }
+}
+
 /// Builds a runtime process spec from installed metadata and config values.
+///
+/// `inherit_env` should repeat the running server's launch, since status
+/// checks auto-restart it from this spec.
 fn build_process_spec(
     name: &str,
     installed: &InstalledServer,
     registry: &Registry,
     global_policy: &GlobalPolicy,
+    inherit_env: bool,
 ) -> Result<ProcessSpec, String> {
     let mut env = BTreeMap::new();
 
-    if let Some(meta) = registry.get(name) {
+    if let Some(meta) = registry.get(&installed.server.name) {
         for field in meta
             .config
             .required
@@ -222,20 +613,47 @@ fn build_process_spec(
     }
 
     let overrides = load_permission_overrides(name)?;
-    enforce_global_policy(name, &installed.permissions, &overrides, global_policy)?;
+    enforce_global_policy(name, installed, &overrides, global_policy, registry)?;
+    workspace_trust::check()?;
     validate_network_permissions(name, &installed.permissions.network, &overrides)?;
     filter_env_map(&mut env, &installed.permissions.env, &overrides);
     let mut policy = parse_runtime_policy(&installed.config)?;
+    let (shutdown_signal, shutdown_grace_period_secs) = parse_shutdown_settings(&installed.config)?;
+    let capture_core = capture_core_enabled(installed)?;
+    let readiness = parse_readiness(&installed.config)?;
     let sandbox_policy = parse_sandbox_policy(&installed.config)?;
-    if sandbox_policy.network_deny_all {
+    let run_as = parse_run_as(&installed.config)?;
+    let seccomp = parse_seccomp(&installed.config, &installed.runtime.syscalls)?;
+    if sandbox_policy.network == SandboxNetwork::DenyAll {
         policy.enabled = false;
     }
+    let network = effective_permissions("network", &installed.permissions.network, &overrides);
+    let clean_env = apply_env_isolation(
+        &mut env,
+        &installed.permissions.env,
+        &overrides,
+        inherit_env,
+    );
+    if sandbox_policy.network == SandboxNetwork::Allowlist {
+        apply_egress_allowlist(name, &mut env, clean_env, &network)?;
+    } else if parse_egress_monitor(&installed.config)? {
+        apply_egress_monitor(name, &mut env, clean_env)?;
+    }
+    // Wrapped last, so the sandbox can mount what egress setup points the env at.
+    let filesystem =
+        effective_permissions("filesystem", &installed.permissions.filesystem, &overrides);
+    let mut args = installed.runtime.args.clone();
+    apply_socket_endpoint(name, &installed.runtime, &mut env, &mut args)?;
+    let (command, args) =
+        apply_wasm_runtime(&installed.runtime, &args, &mut env, &filesystem, &network);
     let (command, args) = apply_sandbox_runtime(
-        &installed.runtime.command,
-        &installed.runtime.args,
+        name,
+        &command,
+        &args,
         &mut env,
         sandbox_policy,
-        &installed.permissions.filesystem,
+        &filesystem,
+        &network,
     );
 
     Ok(ProcessSpec {
@@ -243,19 +661,134 @@ fn build_process_spec(
         args,
         env,
         auto_restart: Some(policy),
+        clean_env,
+        refresh_at_epoch_secs: None,
+        capture_core,
+        readiness,
+        run_as,
+        seccomp,
+        shutdown_signal,
+        shutdown_grace_period_secs,
+        health_check: health_check(&installed.runtime),
+        schedule: parse_schedule(installed)?,
+        limits: resource_limits(&installed.runtime),
     })
 }
 
-/// Reads the persisted runtime PID for a server, if present.
-fn read_runtime_pid(server: &str) -> Option<u32> {
-    let berth_home = paths::berth_home()?;
-    let state_path = berth_home.join("runtime").join(format!("{server}.toml"));
+/// Reads the persisted runtime state for a server, if present.
+fn read_runtime_state(server: &str) -> Option<RuntimeStateSnapshot> {
+    let state_home = paths::berth_home()?;
+    let state_path = state_home.join("runtime").join(format!("{server}.toml"));
     if !state_path.exists() {
         return None;
     }
     let content = fs::read_to_string(state_path).ok()?;
-    let state: RuntimeStateSnapshot = toml::from_str(&content).ok()?;
-    state.pid
+    toml::from_str(&content).ok()
+}
+
+/// Reads the persisted runtime PID for a server, if present.
+fn read_runtime_pid(server: &str) -> Option<u32> {
+    read_runtime_state(server)?.pid
+}
+
+/// Returns whether the server was last launched with the inherited env
+/// (`--inherit-env`), as the supervisor's own restarts assume too.
+pub(crate) fn launched_with_inherited_env(server: &str) -> bool {
+    read_runtime_state(server).and_then(|state| state.clean_env) == Some(false)
+}
+
+/// Renders current usage against each set limit, e.g. `mem 120/512 MiB`.
+///
+/// Usage at 90% of a limit or more is highlighted.
+fn limits_usage(
+    limits: &ResourceLimits,
+    memory_kib: Option<u64>,
+    cpu_percent: Option<f32>,
+    open_files: Option<u64>,
+) -> String {
+    let render = |label: &str, used: Option<f64>, limit: f64, unit: &str| {
+        let text = match used {
+            Some(used) => format!("{label} {used:.0}/{limit:.0}{unit}"),
+            None => format!("{label} -/{limit:.0}{unit}"),
+        };
+        if used.is_some_and(|used| used >= limit * 0.9) {
+            text.yellow().to_string()
+        } else {
+            text
+        }
+    };
+    let mut parts = Vec::new();
+    if let Some(mb) = limits.memory_mb {
+        let used = memory_kib.map(|kib| kib as f64 / 1024.0);
+        parts.push(render("mem", used, mb as f64, " MiB"));
+    }
+    if let Some(percent) = limits.cpu_percent {
+        parts.push(render(
+            "cpu",
+            cpu_percent.map(f64::from),
+            f64::from(percent),
+            "%",
+        ));
+    }
+    if let Some(files) = limits.max_open_files {
+        parts.push(render(
+            "files",
+            open_files.map(|n| n as f64),
+            files as f64,
+            "",
+        ));
+    }
+    parts.join(", ")
+}
+
+/// Returns the CPU usage of a process in percent of one core, if available:
+/// its CPU time over its lifetime, as `ps` reports it.
+#[cfg(target_os = "linux")]
+fn cpu_usage_percent(pid: u32) -> Option<f32> {
+    // `/proc` counts in USER_HZ, which the kernel fixes at 100 for userspace.
+    const TICKS_PER_SEC: f64 = 100.0;
+    let stat = fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+    // The command name may contain spaces and parentheses; fields follow the last `)`.
+    let fields: Vec<&str> = stat.rsplit_once(')')?.1.split_whitespace().collect();
+    let ticks = |index: usize| fields.get(index)?.parse::<f64>().ok();
+    let cpu_secs = (ticks(11)? + ticks(12)?) / TICKS_PER_SEC;
+    let started_secs = ticks(19)? / TICKS_PER_SEC;
+    let uptime = fs::read_to_string("/proc/uptime").ok()?;
+    let uptime_secs: f64 = uptime.split_whitespace().next()?.parse().ok()?;
+    let elapsed = uptime_secs - started_secs;
+    (elapsed > 0.0).then(|| (cpu_secs / elapsed * 100.0) as f32)
+}
+
+/// Returns the CPU usage of a process in percent of one core, if available.
+#[cfg(all(unix, not(target_os = "linux")))]
+fn cpu_usage_percent(pid: u32) -> Option<f32> {
+    let output = Command::new("ps")
+        .args(["-o", "%cpu=", "-p", &pid.to_string()])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+}
+
+/// Returns the CPU usage of a process in percent of one core, if available.
+#[cfg(not(unix))]
+fn cpu_usage_percent(_pid: u32) -> Option<f32> {
+    None
+}
+
+/// Returns how many file descriptors a process has open, if available.
+#[cfg(target_os = "linux")]
+fn open_file_count(pid: u32) -> Option<u64> {
+    let entries = fs::read_dir(format!("/proc/{pid}/fd")).ok()?;
+    Some(entries.count() as u64)
+}
+
+/// Returns how many file descriptors a process has open, if available.
+#[cfg(not(target_os = "linux"))]
+fn open_file_count(_pid: u32) -> Option<u64> {
+    None
 }
 
 /// Returns current resident memory (KiB) for a process id, if available.
@@ -9,26 +9,60 @@ use std::process;
 
 use berth_runtime::{RuntimeManager, StopOutcome};
 
+use crate::daemon;
+use crate::dependency_policy::{load_dependencies, startup_order};
+use crate::dry_run::DryRunPlan;
 use crate::paths;
+use crate::replica_policy::running_replicas;
 
 /// Executes the `berth stop` command.
-pub fn execute(server: Option<&str>) {
-    let targets = resolve_targets(server);
-    let berth_home = match paths::berth_home() {
-        Some(h) => h,
+///
+/// Without a server name, servers stop before the ones they depend on. The
+/// replicas of a replicated server stop with it.
+pub fn execute(server: Option<&str>, dry_run: bool) {
+    let mut targets = resolve_targets(server);
+    if server.is_none() {
+        match startup_order(&load_dependencies(&targets)) {
+            Ok(mut order) => {
+                order.reverse();
+                targets = order;
+            }
+            Err(msg) => eprintln!("{} {} Stopping in name order.", "!".yellow().bold(), msg),
+        }
+    }
+    let runtime = match paths::runtime_manager() {
+        Some(r) => r,
         None => {
             eprintln!("{} Could not determine home directory.", "✗".red().bold());
             process::exit(1);
         }
     };
-    let runtime = RuntimeManager::new(berth_home);
 
     let mut stopped = 0usize;
     let mut already_stopped = 0usize;
     let mut failed = 0usize;
 
-    for name in &targets {
-        match runtime.stop(name) {
+    // Replicas stop after their gateway, so it hands out no more connections.
+    let instances: Vec<String> = targets
+        .iter()
+        .flat_map(|name| {
+            let mut instances = vec![name.clone()];
+            instances.extend(running_replicas(&runtime, name));
+            instances
+        })
+        .collect();
+    for name in &instances {
+        if dry_run {
+            match stop_plan(name, &runtime) {
+                Ok(plan) => plan.print(),
+                Err(msg) => {
+                    eprintln!("{} {}", "✗".red().bold(), msg);
+                    failed += 1;
+                }
+            }
+            continue;
+        }
+        match daemon::stop(&runtime, name) {
             Ok(StopOutcome::Stopped) => {
                 println!("{} Stopped {}.", "✓".green().bold(), name.cyan());
                 stopped += 1;
@@ -48,7 +82,7 @@ pub fn execute(server: Option<&str>) {
         }
     }
 
-    if targets.len() > 1 {
+    if instances.len() > 1 && !dry_run {
         println!(
             "\n{} Stopped: {}, already stopped: {}, failed: {}",
             "•".dimmed(),
@@ -63,6 +97,32 @@ pub fn execute(server: Option<&str>) {
     }
 }
 
+/// Describes what `berth stop` would do for one server without side effects.
+fn stop_plan(name: &str, runtime: &RuntimeManager) -> Result<DryRunPlan, String> {
+    let mut plan = DryRunPlan::new("stop", name);
+    let pid = runtime
+        .running_pid(name)
+        .map_err(|e| format!("Failed to read runtime state for {}: {e}", name.cyan()))?;
+    match pid {
+        Some(pid) => {
+            plan.add("outcome", "would stop");
+            plan.add(
+                "signal",
+                format!("terminate pid {pid}, force kill if still alive after grace period"),
+            );
+            plan.add_write(&runtime.state_path(name));
+            plan.add_write(&runtime.log_path(name));
+            plan.add_write(&runtime.audit_log_path());
+        }
+        None => {
+            plan.add("outcome", "already stopped; runtime state would be reset");
+            plan.add_write(&runtime.state_path(name));
+            plan.add_write(&runtime.log_path(name));
+        }
+    }
+    Ok(plan)
+}
+
 /// Resolves target server names from a specific name or all installed servers.
 fn resolve_targets(server: Option<&str>) -> Vec<String> {
     if let Some(name) = server {
@@ -4,18 +4,25 @@
 //! Internal command handler for `berth __supervise`.
 
 use colored::Colorize;
-use std::io::{self, Read, Write};
-use std::path::Path;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Write};
 use std::process::{self, Command, Stdio};
 
-use berth_runtime::{ProcessSpec, RuntimeManager};
+use berth_registry::config::InstalledServer;
+use berth_registry::Registry;
+use berth_runtime::{ProcessSpec, RuntimeManager, StartOutcome};
 
+use crate::commands::start::build_process_spec;
+use crate::egress_allowlist::EgressProxy;
+use crate::oauth::inject_access_token;
 use crate::paths;
+use crate::policy_engine::load_global_policy;
+use crate::replica_policy::{base_server, instance_spec};
 
 /// Executes the hidden supervisor process command.
 pub fn execute(server: &str) {
-    let berth_home = match paths::berth_home() {
-        Some(h) => h,
+    let runtime = match paths::runtime_manager() {
+        Some(r) => r,
         None => {
             eprintln!("{} Could not determine home directory.", "✗".red().bold());
             process::exit(1);
@@ -44,20 +51,133 @@ pub fn execute(server: &str) {
         }
     };
 
-    let runtime = RuntimeManager::new(berth_home);
-    if let Err(e) = runtime.run_supervisor(server, &spec) {
-        eprintln!(
-            "{} Supervisor loop failed for {}: {}",
-            "✗".red().bold(),
-            server.cyan(),
-            e
-        );
+    // Reports the launch on stdout for [`launch_detached`]. The parent may be
+    // gone already; a closed pipe must not kill the supervisor.
+    let report = |outcome: Result<StartOutcome, String>| {
+        let line = match outcome {
+            Ok(StartOutcome::Started) => "started".to_string(),
+            Ok(StartOutcome::AlreadyRunning) => "already-running".to_string(),
+            Err(e) => format!("error {e}"),
+        };
+        let mut stdout = io::stdout();
+        let _ = writeln!(stdout, "{line}").and_then(|()| stdout.flush());
+    };
+    if let Err(e) = run(&runtime, server, spec, false, report) {
+        eprintln!("{} {}: {}", "✗".red().bold(), server.cyan(), e);
         process::exit(1);
     }
 }
 
-/// Spawns a detached supervisor process and sends it the process spec over stdin.
-pub fn spawn_detached(server: &str, spec: &ProcessSpec, berth_home: &Path) -> Result<(), String> {
+/// Launches a server as a child of this process, hands the launch outcome to
+/// `report`, and supervises it until it is stopped.
+///
+/// The allow-listing egress proxy lives in this process, as long as the server
+/// does. With `watch`, servers that need no supervision are watched too, so a
+/// long-lived caller reaps them; see [`RuntimeManager::watch_child`].
+pub fn run(
+    runtime: &RuntimeManager,
+    server: &str,
+    mut spec: ProcessSpec,
+    watch: bool,
+    report: impl FnOnce(Result<StartOutcome, String>),
+) -> Result<(), String> {
+    let egress_proxy = match EgressProxy::start(&spec.env) {
+        Ok(proxy) => proxy,
+        Err(e) => {
+            report(Err(e));
+            return Ok(());
+        }
+    };
+    if let Some(proxy) = &egress_proxy {
+        proxy.apply(&mut spec.env);
+    }
+
+    if spec.schedule.is_some() {
+        return run_schedule(runtime, server, &spec, report);
+    }
+
+    let refresh = || {
+        let mut fresh = refreshed_spec(server, &spec)?;
+        if let Some(proxy) = &egress_proxy {
+            proxy.apply(&mut fresh.env);
+        }
+        Ok(fresh)
+    };
+    // The supervisor handles restarts; `berth status` must not race it.
+    let mut launch_spec = spec.clone();
+    launch_spec.auto_restart = None;
+    let child = match runtime.start_child(server, &launch_spec) {
+        Ok(Some(child)) => {
+            report(Ok(StartOutcome::Started));
+            child
+        }
+        // Already running, so its own supervisor is in charge.
+        Ok(None) => {
+            report(Ok(StartOutcome::AlreadyRunning));
+            return Ok(());
+        }
+        Err(e) => {
+            report(Err(e.to_string()));
+            return Ok(());
+        }
+    };
+    let supervised = if watch {
+        runtime.watch_child(server, &spec, child, refresh)
+    } else {
+        runtime.supervise_child(server, &spec, child, refresh)
+    };
+    supervised.map_err(|e| format!("Supervisor loop failed: {e}"))
+}
+
+/// Arms a job's schedule, reports it like a launch, and runs the job whenever it is due.
+fn run_schedule(
+    runtime: &RuntimeManager,
+    server: &str,
+    spec: &ProcessSpec,
+    report: impl FnOnce(Result<StartOutcome, String>),
+) -> Result<(), String> {
+    let outcome = runtime
+        .schedule_job(server, spec)
+        .map_err(|e| e.to_string());
+    let started = matches!(outcome, Ok(StartOutcome::Started));
+    report(outcome);
+    if !started {
+        return Ok(());
+    }
+    runtime
+        .run_job_schedule(server, spec)
+        .map_err(|e| format!("Scheduler failed: {e}"))
+}
+
+/// Rebuilds the launch spec from current config with freshly issued credentials.
+///
+/// `instance` may be a replica or gateway; its spec is picked from the
+/// rebuilt spec of the server it belongs to.
+fn refreshed_spec(instance: &str, current: &ProcessSpec) -> Result<ProcessSpec, String> {
+    let server = base_server(instance);
+    let config_path =
+        paths::server_config_path(server).ok_or("Could not determine home directory.")?;
+    let content = fs::read_to_string(&config_path)
+        .map_err(|e| format!("failed to read {}: {e}", config_path.display()))?;
+    let installed: InstalledServer = toml::from_str(&content)
+        .map_err(|e| format!("failed to parse {}: {e}", config_path.display()))?;
+    let registry = Registry::from_seed();
+    let policy = load_global_policy()?;
+    let (mut fresh, _) =
+        build_process_spec(server, &installed, &registry, &policy, !current.clean_env)?;
+    inject_access_token(server, &installed, &registry, &mut fresh)?;
+    let mut fresh = instance_spec(instance, &installed, fresh)?;
+    fresh.auto_restart = current.auto_restart.clone();
+    Ok(fresh)
+}
+
+/// Starts a server under a detached supervisor that launches it as its own child.
+///
+/// The supervisor gets the process spec over stdin and inherits this process's
+/// environment, so it resolves the same Berth layout. Owning the server process
+/// lets it see exit codes for `berth.restart-on-exit-codes`. Returns once the
+/// supervisor has launched the server.
+pub fn launch_detached(server: &str, spec: &ProcessSpec) -> Result<StartOutcome, String> {
     let exe = std::env::current_exe().map_err(|e| format!("failed to locate current exe: {e}"))?;
     let payload =
         serde_json::to_string(spec).map_err(|e| format!("failed to serialize spec: {e}"))?;
@@ -65,9 +185,8 @@ pub fn spawn_detached(server: &str, spec: &ProcessSpec, berth_home: &Path) -> Re
     let mut child = Command::new(exe)
         .arg("__supervise")
         .arg(server)
-        .env("BERTH_HOME", berth_home)
         .stdin(Stdio::piped())
-        .stdout(Stdio::null())
+        .stdout(Stdio::piped())
         .stderr(Stdio::null())
         .spawn()
         .map_err(|e| format!("failed to spawn supervisor: {e}"))?;
@@ -80,5 +199,20 @@ pub fn spawn_detached(server: &str, spec: &ProcessSpec, berth_home: &Path) -> Re
         .write_all(payload.as_bytes())
         .map_err(|e| format!("failed to write supervisor spec: {e}"))?;
     drop(stdin);
-    Ok(())
+
+    let Some(stdout) = child.stdout.take() else {
+        return Err("failed to open supervisor stdout".to_string());
+    };
+    let mut report = String::new();
+    BufReader::new(stdout)
+        .read_line(&mut report)
+        .map_err(|e| format!("failed to read supervisor launch report: {e}"))?;
+    match report.trim_end() {
+        "started" => Ok(StartOutcome::Started),
+        "already-running" => Ok(StartOutcome::AlreadyRunning),
+        other => match other.strip_prefix("error ") {
+            Some(msg) => Err(msg.to_string()),
+            None => Err("supervisor exited before launching the server".to_string()),
+        },
+    }
 }
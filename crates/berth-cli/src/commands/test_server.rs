@@ -0,0 +1,57 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Command handler for `berth test-server`.
+//!
+//! Runs a fake MCP server from `berth-testkit` on stdio. Pointing a server's
+//! runtime command at it shows how `berth start`, `berth proxy`, and
+//! `berth wrap` handle slow starts, crashes, huge outputs, and protocol
+//! violations; Berth's own integration tests use it the same way.
+
+use colored::Colorize;
+use std::io;
+use std::process;
+use std::time::Duration;
+
+use berth_testkit::{Exit, FakeServer, Violation};
+
+/// Executes the hidden test server command.
+pub fn execute(
+    name: Option<&str>,
+    tools: &[String],
+    startup_delay_ms: u64,
+    crash_after: Option<u32>,
+    exit_code: i32,
+    output_bytes: usize,
+    violate: Option<&str>,
+) {
+    let mut server = FakeServer::new()
+        .with_startup_delay(Duration::from_millis(startup_delay_ms))
+        .with_output_bytes(output_bytes);
+    if let Some(name) = name {
+        server = server.with_name(name);
+    }
+    if !tools.is_empty() {
+        let tools: Vec<&str> = tools.iter().map(String::as_str).collect();
+        server = server.with_tools(&tools);
+    }
+    if let Some(calls) = crash_after {
+        server = server.crashing_after(calls, exit_code);
+    }
+    if let Some(violation) = violate {
+        match Violation::parse(violation) {
+            Ok(violation) => server = server.violating(violation),
+            Err(msg) => fail(&msg),
+        }
+    }
+    match server.serve(io::stdin().lock(), io::stdout().lock()) {
+        Ok(Exit::InputClosed) => {}
+        Ok(Exit::Crashed { exit_code }) => process::exit(exit_code),
+        Err(e) => fail(&format!("Test server failed: {e}")),
+    }
+}
+
+fn fail(msg: &str) -> ! {
+    eprintln!("{} {}", "✗".red().bold(), msg);
+    process::exit(1);
+}
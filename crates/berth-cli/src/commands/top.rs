@@ -0,0 +1,416 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Command handler for `berth top`.
+
+use berth_runtime::{AuditLog, AuditQuery, AuditRecord};
+use colored::Colorize;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::process;
+
+use crate::commands::analytics::parse_since;
+use crate::paths;
+use crate::time_format::now_epoch_ms;
+
+/// Audit actions recorded by `berth proxy` for client requests.
+const REQUEST_ACTIONS: [&str; 5] = [
+    "mcp-tool-call",
+    "mcp-resource-read",
+    "mcp-resource-subscribe",
+    "mcp-resource-unsubscribe",
+    "mcp-prompt-get",
+];
+
+/// Tool name recorded as the first audit argument.
+fn tool(event: &AuditRecord) -> &str {
+    event
+        .args
+        .as_deref()
+        .and_then(<[String]>::first)
+        .map_or("(unknown)", String::as_str)
+}
+
+/// Latency and error totals over completed tool calls.
+#[derive(Debug, Default, Clone, Copy)]
+struct Completions {
+    count: u64,
+    errors: u64,
+    total_ms: u64,
+}
+
+impl Completions {
+    fn add(&mut self, event: &AuditRecord) {
+        self.count += 1;
+        self.total_ms += event.duration_ms.unwrap_or(0);
+        if event.error == Some(true) {
+            self.errors += 1;
+        }
+    }
+
+    fn error_rate(&self) -> f64 {
+        if self.count == 0 {
+            0.0
+        } else {
+            self.errors as f64 / self.count as f64
+        }
+    }
+
+    fn avg_latency_ms(&self) -> Option<f64> {
+        (self.count > 0).then(|| self.total_ms as f64 / self.count as f64)
+    }
+}
+
+#[derive(Debug, Serialize, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolUsage {
+    pub server: String,
+    pub tool: String,
+    pub calls: u64,
+    /// Calls whose response was observed; latency and errors cover only these.
+    pub completed: u64,
+    pub errors: u64,
+    pub error_rate: f64,
+    pub avg_latency_ms: Option<f64>,
+}
+
+#[derive(Debug, Serialize, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerUsage {
+    pub server: String,
+    pub requests: u64,
+    pub tool_calls: u64,
+    pub completed: u64,
+    pub errors: u64,
+    pub error_rate: f64,
+    pub avg_latency_ms: Option<f64>,
+}
+
+#[derive(Debug, Serialize, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageReport {
+    pub since_secs: Option<u64>,
+    pub total_requests: u64,
+    pub total_tool_calls: u64,
+    pub total_errors: u64,
+    pub tools: Vec<ToolUsage>,
+    pub servers: Vec<ServerUsage>,
+}
+
+/// Executes the `berth top` command.
+pub fn execute(server: Option<&str>, since: Option<&str>, limit: u32, json: bool) {
+    let since_secs = match since.map(parse_since).transpose() {
+        Ok(v) => v,
+        Err(msg) => {
+            eprintln!("{} {}", "✗".red().bold(), msg);
+            process::exit(1);
+        }
+    };
+
+    let path = match paths::audit_log_path() {
+        Some(p) => p,
+        None => {
+            eprintln!("{} Could not determine home directory.", "✗".red().bold());
+            process::exit(1);
+        }
+    };
+
+    let report = match usage_report(&path, server, since_secs, limit as usize) {
+        Ok(report) => report,
+        Err(msg) => {
+            eprintln!("{} {}", "✗".red().bold(), msg);
+            process::exit(1);
+        }
+    };
+
+    if json {
+        match serde_json::to_string_pretty(&report) {
+            Ok(rendered) => println!("{rendered}"),
+            Err(e) => {
+                eprintln!("{} Failed to serialize usage JSON: {}", "✗".red().bold(), e);
+                process::exit(1);
+            }
+        }
+        return;
+    }
+
+    if report.total_requests == 0 {
+        println!(
+            "{} No proxied requests recorded. Usage is audited while clients connect through {}.",
+            "!".yellow().bold(),
+            "berth proxy".bold()
+        );
+        return;
+    }
+
+    println!(
+        "{} Tool usage{}{}:\n",
+        "✓".green().bold(),
+        server
+            .map(|name| format!(" for {}", name.cyan()))
+            .unwrap_or_default(),
+        since
+            .map(|window| format!(" (since {})", window.bold()))
+            .unwrap_or_default()
+    );
+    println!(
+        "  requests: {}   tool calls: {}   errors: {}",
+        report.total_requests.to_string().bold(),
+        report.total_tool_calls.to_string().bold(),
+        report.total_errors.to_string().bold()
+    );
+
+    println!("\n  {}", "Most-called tools".bold());
+    if report.tools.is_empty() {
+        println!("    (none)");
+    } else {
+        println!(
+            "    {:<28} {:<16} {:>7} {:>8} {:>12}",
+            "TOOL".bold(),
+            "SERVER".bold(),
+            "CALLS".bold(),
+            "ERRORS".bold(),
+            "AVG LATENCY".bold()
+        );
+        println!("    {}", "─".repeat(75));
+        for tool in &report.tools {
+            println!(
+                "    {:<28} {:<16} {:>7} {:>8} {:>12}",
+                tool.tool,
+                tool.server,
+                tool.calls,
+                format_rate(tool.error_rate, tool.completed),
+                format_latency(tool.avg_latency_ms)
+            );
+        }
+    }
+
+    println!("\n  {}", "Busiest servers".bold());
+    println!(
+        "    {:<28} {:>9} {:>11} {:>8} {:>12}",
+        "SERVER".bold(),
+        "REQUESTS".bold(),
+        "TOOL CALLS".bold(),
+        "ERRORS".bold(),
+        "AVG LATENCY".bold()
+    );
+    println!("    {}", "─".repeat(72));
+    for usage in &report.servers {
+        println!(
+            "    {:<28} {:>9} {:>11} {:>8} {:>12}",
+            usage.server,
+            usage.requests,
+            usage.tool_calls,
+            format_rate(usage.error_rate, usage.completed),
+            format_latency(usage.avg_latency_ms)
+        );
+    }
+}
+
+/// Reads the audit log, rotated segments included, and builds a usage report
+/// with optional server and time filters.
+pub fn usage_report(
+    path: &Path,
+    server: Option<&str>,
+    since_secs: Option<u64>,
+    limit: usize,
+) -> Result<UsageReport, String> {
+    let query = AuditQuery {
+        server: server.map(str::to_string),
+        since_epoch_ms: since_secs
+            .map(|seconds| now_epoch_ms().saturating_sub(seconds.saturating_mul(1_000))),
+        ..AuditQuery::default()
+    };
+    let page = AuditLog::new(path)
+        .query(&query)
+        .map_err(|e| format!("Failed to read audit log {}: {e}", path.display()))?;
+    Ok(summarize(&page.entries, since_secs, limit))
+}
+
+fn summarize(events: &[AuditRecord], since_secs: Option<u64>, limit: usize) -> UsageReport {
+    let mut tool_calls = BTreeMap::<(&str, &str), u64>::new();
+    let mut tool_results = BTreeMap::<(&str, &str), Completions>::new();
+    let mut server_requests = BTreeMap::<&str, (u64, u64)>::new();
+    let mut server_results = BTreeMap::<&str, Completions>::new();
+
+    for event in events {
+        let server = event.server.as_str();
+        if REQUEST_ACTIONS.contains(&event.action.as_str()) {
+            let entry = server_requests.entry(server).or_default();
+            entry.0 += 1;
+            if event.action == "mcp-tool-call" {
+                entry.1 += 1;
+                *tool_calls.entry((server, tool(event))).or_default() += 1;
+            }
+        } else if event.action == "mcp-tool-result" {
+            tool_results
+                .entry((server, tool(event)))
+                .or_default()
+                .add(event);
+            server_results.entry(server).or_default().add(event);
+        }
+    }
+
+    let mut tools = tool_calls
+        .iter()
+        .map(|(&(server, tool), &calls)| {
+            let results = tool_results
+                .get(&(server, tool))
+                .copied()
+                .unwrap_or_default();
+            ToolUsage {
+                server: server.to_string(),
+                tool: tool.to_string(),
+                calls,
+                completed: results.count,
+                errors: results.errors,
+                error_rate: results.error_rate(),
+                avg_latency_ms: results.avg_latency_ms(),
+            }
+        })
+        .collect::<Vec<_>>();
+    tools.sort_by(|a, b| b.calls.cmp(&a.calls).then_with(|| a.tool.cmp(&b.tool)));
+    tools.truncate(limit);
+
+    let mut servers = server_requests
+        .iter()
+        .map(|(&server, &(requests, calls))| {
+            let results = server_results.get(server).copied().unwrap_or_default();
+            ServerUsage {
+                server: server.to_string(),
+                requests,
+                tool_calls: calls,
+                completed: results.count,
+                errors: results.errors,
+                error_rate: results.error_rate(),
+                avg_latency_ms: results.avg_latency_ms(),
+            }
+        })
+        .collect::<Vec<_>>();
+    servers.sort_by(|a, b| {
+        b.requests
+            .cmp(&a.requests)
+            .then_with(|| a.server.cmp(&b.server))
+    });
+    servers.truncate(limit);
+
+    UsageReport {
+        since_secs,
+        total_requests: server_requests.values().map(|(r, _)| r).sum(),
+        total_tool_calls: tool_calls.values().sum(),
+        total_errors: server_results.values().map(|c| c.errors).sum(),
+        tools,
+        servers,
+    }
+}
+
+/// Formats an error rate, or `-` when no responses were observed.
+fn format_rate(rate: f64, observed: u64) -> String {
+    if observed == 0 {
+        "-".to_string()
+    } else {
+        format!("{:.1}%", rate * 100.0)
+    }
+}
+
+fn format_latency(avg_ms: Option<f64>) -> String {
+    match avg_ms {
+        Some(ms) if ms >= 1_000.0 => format!("{:.2}s", ms / 1_000.0),
+        Some(ms) => format!("{ms:.0}ms"),
+        None => "-".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ev(server: &str, action: &str, tool: Option<&str>) -> AuditRecord {
+        AuditRecord {
+            timestamp_epoch_ms: Some(1_000),
+            timestamp_epoch_secs: 1,
+            server: server.to_string(),
+            action: action.to_string(),
+            args: tool.map(|t| vec![t.to_string()]),
+            ..AuditRecord::default()
+        }
+    }
+
+    fn result(server: &str, tool: &str, duration_ms: u64, error: bool) -> AuditRecord {
+        AuditRecord {
+            duration_ms: Some(duration_ms),
+            error: Some(error),
+            ..ev(server, "mcp-tool-result", Some(tool))
+        }
+    }
+
+    #[test]
+    fn summarize_ranks_tools_and_servers_with_latency_and_errors() {
+        let events = vec![
+            ev("github", "mcp-tool-call", Some("search")),
+            ev("github", "mcp-tool-call", Some("search")),
+            ev("github", "mcp-tool-call", Some("create_issue")),
+            ev("slack", "mcp-resource-read", Some("file:///notes")),
+            ev("github", "start", None),
+            result("github", "search", 100, false),
+            result("github", "search", 300, true),
+        ];
+        let report = summarize(&events, None, 10);
+
+        assert_eq!(report.total_requests, 4);
+        assert_eq!(report.total_tool_calls, 3);
+        assert_eq!(report.total_errors, 1);
+        let search = &report.tools[0];
+        assert_eq!(search.tool, "search");
+        assert_eq!(search.calls, 2);
+        assert_eq!(search.avg_latency_ms, Some(200.0));
+        assert!((search.error_rate - 0.5).abs() < f64::EPSILON);
+        assert_eq!(report.tools[1].avg_latency_ms, None);
+        assert_eq!(report.servers[0].server, "github");
+        assert_eq!(report.servers[0].requests, 3);
+        assert_eq!(report.servers[1].server, "slack");
+        assert_eq!(report.servers[1].tool_calls, 0);
+    }
+
+    #[test]
+    fn summarize_truncates_to_limit() {
+        let events = vec![
+            ev("github", "mcp-tool-call", Some("a")),
+            ev("github", "mcp-tool-call", Some("b")),
+            ev("github", "mcp-tool-call", Some("b")),
+        ];
+        let report = summarize(&events, Some(3_600), 1);
+        assert_eq!(report.tools.len(), 1);
+        assert_eq!(report.tools[0].tool, "b");
+        assert_eq!(report.since_secs, Some(3_600));
+    }
+
+    #[test]
+    fn usage_report_reads_rotated_segments() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("audit.jsonl");
+        let line = |action: &str| {
+            format!(
+                "{{\"timestampEpochMs\":{},\"server\":\"github\",\"action\":\"{action}\",\"args\":[\"search\"]}}\n",
+                now_epoch_ms()
+            )
+        };
+        std::fs::write(tmp.path().join("audit-1.jsonl"), line("mcp-tool-call")).unwrap();
+        let report = usage_report(&path, Some("github"), Some(3_600), 10).unwrap();
+        assert_eq!(report.total_tool_calls, 1);
+
+        std::fs::write(&path, line("mcp-tool-call")).unwrap();
+        let report = usage_report(&path, None, None, 10).unwrap();
+        assert_eq!(report.total_tool_calls, 2);
+    }
+
+    #[test]
+    fn format_helpers_render_missing_data_as_dash() {
+        assert_eq!(format_rate(0.25, 4), "25.0%");
+        assert_eq!(format_rate(0.0, 0), "-");
+        assert_eq!(format_latency(Some(42.4)), "42ms");
+        assert_eq!(format_latency(Some(1_500.0)), "1.50s");
+        assert_eq!(format_latency(None), "-");
+    }
+}
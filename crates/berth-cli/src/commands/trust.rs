@@ -0,0 +1,110 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Command handler for `berth trust`.
+
+use colored::Colorize;
+use std::process;
+
+use crate::time_format::TimestampFormatter;
+use crate::workspace_trust::{self, current_workspace, resolve_workspace};
+
+/// Executes the `berth trust` command.
+pub fn execute(action: Option<&str>, path: Option<&str>, utc: bool) {
+    let Some(trust_path) = workspace_trust::trust_path() else {
+        eprintln!("{} Could not determine home directory.", "✗".red().bold());
+        process::exit(1);
+    };
+    match action.unwrap_or("list") {
+        "list" => list(&trust_path, utc),
+        "grant" => {
+            let workspace = resolve_or_exit(path);
+            if let Err(msg) = workspace_trust::decide(&trust_path, &workspace, true) {
+                eprintln!("{} {}", "✗".red().bold(), msg);
+                process::exit(1);
+            }
+            println!(
+                "{} Trusted workspace {}.",
+                "✓".green().bold(),
+                workspace.display().to_string().bold()
+            );
+        }
+        "revoke" => {
+            let workspace = resolve_or_exit(path);
+            match workspace_trust::revoke(&trust_path, &workspace) {
+                Ok(true) => println!(
+                    "{} Revoked the trust decision for {}; Berth asks again before launching its servers.",
+                    "✓".green().bold(),
+                    workspace.display().to_string().bold()
+                ),
+                Ok(false) => println!(
+                    "No trust decision recorded for {}.",
+                    workspace.display().to_string().bold()
+                ),
+                Err(msg) => {
+                    eprintln!("{} {}", "✗".red().bold(), msg);
+                    process::exit(1);
+                }
+            }
+        }
+        other => {
+            eprintln!(
+                "{} Unknown trust action `{other}`. Use {}, {} or {}.",
+                "✗".red().bold(),
+                "list".bold(),
+                "grant".bold(),
+                "revoke".bold()
+            );
+            process::exit(1);
+        }
+    }
+}
+
+fn resolve_or_exit(path: Option<&str>) -> std::path::PathBuf {
+    match resolve_workspace(path) {
+        Ok(workspace) => workspace,
+        Err(msg) => {
+            eprintln!("{} {}", "✗".red().bold(), msg);
+            process::exit(1);
+        }
+    }
+}
+
+fn list(trust_path: &std::path::Path, utc: bool) {
+    let file = match workspace_trust::load(trust_path) {
+        Ok(file) => file,
+        Err(msg) => {
+            eprintln!("{} {}", "✗".red().bold(), msg);
+            process::exit(1);
+        }
+    };
+    if let Some(workspace) = current_workspace() {
+        let state = match file.workspaces.get(&workspace.display().to_string()) {
+            Some(decision) if decision.trusted => "trusted".green(),
+            Some(_) => "not trusted".red(),
+            None => "undecided".yellow(),
+        };
+        println!(
+            "Active Berth home belongs to workspace {} ({state}).\n",
+            workspace.display().to_string().bold()
+        );
+    }
+    if file.workspaces.is_empty() {
+        println!("No workspace trust decisions recorded.");
+        return;
+    }
+    let formatter = TimestampFormatter::new(utc);
+    for (workspace, decision) in &file.workspaces {
+        let state = if decision.trusted {
+            "trusted".green()
+        } else {
+            "not trusted".red()
+        };
+        println!(
+            "  {:<12} {}  {}",
+            state,
+            workspace,
+            formatter.format(decision.decided_at_epoch_ms).dimmed()
+        );
+    }
+}
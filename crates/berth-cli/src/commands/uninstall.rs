@@ -4,16 +4,21 @@
 //! Command handler for `berth uninstall`.
 
 use colored::Colorize;
+use std::collections::BTreeSet;
 use std::fs;
+use std::path::Path;
 use std::process;
 
 use crate::paths;
+use crate::receipts;
+use crate::replica_policy::running_replicas;
 
 /// Executes the `berth uninstall` command.
 pub fn execute(server: &str) {
-    let config_path = match paths::server_config_path(server) {
-        Some(p) => p,
-        None => {
+    let (config_path, runtime) = match (paths::server_config_path(server), paths::runtime_manager())
+    {
+        (Some(p), Some(r)) => (p, r),
+        _ => {
             eprintln!("{} Could not determine home directory.", "✗".red().bold());
             process::exit(1);
         }
@@ -28,10 +33,85 @@ pub fn execute(server: &str) {
         process::exit(1);
     }
 
+    if let Ok(Some(pid)) = runtime.running_pid(server) {
+        eprintln!(
+            "{} Server {} is running (PID {}). Stop it first with {}.",
+            "✗".red().bold(),
+            server.cyan(),
+            pid,
+            format!("berth stop {server}").bold()
+        );
+        process::exit(1);
+    }
+    if let Some(replica) = running_replicas(&runtime, server).first() {
+        eprintln!(
+            "{} Replica {} of {} is running. Stop it first with {}.",
+            "✗".red().bold(),
+            replica.cyan(),
+            server.cyan(),
+            format!("berth stop {server}").bold()
+        );
+        process::exit(1);
+    }
+
     if let Err(e) = fs::remove_file(&config_path) {
         eprintln!("{} Failed to remove config file: {}", "✗".red().bold(), e);
         process::exit(1);
     }
+    remove_installed_files(server);
+
+    let _ = runtime.record_audit_event(server, "uninstall", None, None, None);
+
+    if let Err(e) = runtime.remove_server_artifacts(server) {
+        println!(
+            "{} Could not remove runtime state and logs: {} (run {} later)",
+            "!".yellow().bold(),
+            e,
+            "berth gc".bold()
+        );
+    }
 
     println!("{} Uninstalled {}.", "✓".green().bold(), server.cyan());
 }
+
+/// Removes the binaries the server's install receipts recorded, keeping any
+/// that were modified since.
+fn remove_installed_files(server: &str) {
+    let installed = match receipts::list(server) {
+        Ok(receipts) => receipts,
+        Err(msg) => {
+            println!(
+                "{} Could not read install receipts: {msg}",
+                "!".yellow().bold()
+            );
+            return;
+        }
+    };
+    let removable = receipts::removable_files(&installed);
+    let recorded: BTreeSet<&Path> = installed
+        .iter()
+        .flat_map(|r| &r.files)
+        .filter(|f| f.kind == "binary")
+        .map(|f| f.path.as_path())
+        .collect();
+    for path in recorded {
+        if !removable.iter().any(|p| p == path) {
+            if path.exists() {
+                println!(
+                    "{} Kept {}: modified since install.",
+                    "!".yellow().bold(),
+                    path.display()
+                );
+            }
+            continue;
+        }
+        match fs::remove_file(path) {
+            Ok(()) => println!("  Removed {}", path.display().to_string().dimmed()),
+            Err(e) => println!(
+                "{} Could not remove {}: {e}",
+                "!".yellow().bold(),
+                path.display()
+            ),
+        }
+    }
+}
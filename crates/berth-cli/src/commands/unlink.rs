@@ -6,9 +6,11 @@
 use colored::Colorize;
 use serde_json::Value;
 use std::fs;
-use std::path::{Path, PathBuf};
+use std::path::Path;
 use std::process;
 
+use crate::client_backups;
+use crate::jsonc;
 use crate::paths;
 
 /// Executes the `berth unlink` command.
@@ -57,7 +59,7 @@ fn unlink_client(client: &str, config_path: &Path) {
         }
     };
 
-    let mut root = match serde_json::from_str::<Value>(&content) {
+    let mut root = match jsonc::parse(&content) {
         Ok(v) => v,
         Err(e) => {
             eprintln!(
@@ -78,16 +80,13 @@ fn unlink_client(client: &str, config_path: &Path) {
         }
     };
 
-    let backup = backup_path(config_path);
-    if let Err(e) = fs::copy(config_path, &backup) {
-        eprintln!(
-            "{} Failed to create backup {}: {}",
-            "✗".red().bold(),
-            backup.display(),
-            e
-        );
-        process::exit(1);
-    }
+    let backup = match client_backups::snapshot(client, config_path) {
+        Ok(path) => path,
+        Err(msg) => {
+            eprintln!("{} {}", "✗".red().bold(), msg);
+            process::exit(1);
+        }
+    };
 
     let mut removed = 0usize;
     if let Some(root_obj) = root.as_object_mut() {
@@ -109,7 +108,17 @@ fn unlink_client(client: &str, config_path: &Path) {
         process::exit(1);
     }
 
-    let rendered = match serde_json::to_string_pretty(&root) {
+    let rendered = if jsonc::needs_tolerant_edit(&content) {
+        jsonc::Document::parse(&content).and_then(|mut doc| {
+            for server in &installed {
+                doc.remove(&["mcpServers"], server)?;
+            }
+            Ok(doc.into_string())
+        })
+    } else {
+        serde_json::to_string_pretty(&root).map_err(|e| e.to_string())
+    };
+    let rendered = match rendered {
         Ok(s) => s,
         Err(e) => {
             eprintln!(
@@ -150,7 +159,7 @@ fn unlink_client(client: &str, config_path: &Path) {
 }
 
 /// Lists installed server names derived from `~/.berth/servers/*.toml`.
-fn installed_server_names() -> Result<Vec<String>, String> {
+pub(crate) fn installed_server_names() -> Result<Vec<String>, String> {
     let servers_dir = paths::berth_servers_dir().ok_or("Could not determine home directory.")?;
     if !servers_dir.exists() {
         return Ok(Vec::new());
@@ -172,13 +181,3 @@ fn installed_server_names() -> Result<Vec<String>, String> {
     names.sort();
     Ok(names)
 }
-
-/// Returns a deterministic backup path next to the client config file.
-fn backup_path(config_path: &Path) -> PathBuf {
-    let file_name = config_path
-        .file_name()
-        .unwrap_or_default()
-        .to_string_lossy()
-        .to_string();
-    config_path.with_file_name(format!("{file_name}.bak"))
-}
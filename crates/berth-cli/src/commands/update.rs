@@ -8,17 +8,39 @@ use colored::Colorize;
 use std::fs;
 use std::process;
 
+use berth_registry::types::ServerMetadata;
+use berth_registry::versions::resolve_version;
 use berth_registry::Registry;
-
+use berth_runtime::HTTP;
+
+use crate::commands::install::{integrity_plan, parse_server_spec, prepare_installed_server};
+use crate::commands::restart::rolling_restart;
+use crate::commands::start::build_process_spec;
+use crate::commands::status::launched_with_inherited_env;
+use crate::dry_run::{join_or_none, DryRunPlan};
+use crate::oauth::inject_access_token;
 use crate::paths;
+use crate::policy_engine::load_global_policy;
+use crate::progress::{say, Progress, ProgressMode};
+use crate::proxy_policy::{parse_proxy_policy, UpdateStrategy};
+use crate::receipts::{self, FileRecord, PolicyDecision, Receipt};
+use crate::replica_policy::{instances, parse_replica_policy};
+use crate::shell_preview::command_line;
 
 enum UpdateResult {
-    Updated { from: String, to: String },
-    UpToDate { version: String },
+    Updated {
+        from: String,
+        to: String,
+        strategy: UpdateStrategy,
+    },
+    UpToDate {
+        version: String,
+    },
+    Planned(DryRunPlan),
 }
 
 /// Executes the `berth update` command.
-pub fn execute(server: Option<&str>, all: bool) {
+pub fn execute(server: Option<&str>, all: bool, dry_run: bool, progress: ProgressMode) {
     if all && server.is_some() {
         eprintln!(
             "{} Use either a server name or {}, not both.",
@@ -37,7 +59,7 @@ pub fn execute(server: Option<&str>, all: bool) {
         process::exit(1);
     }
 
-    let registry = Registry::from_seed();
+    let registry = super::load_registry();
 
     if all {
         let targets = match installed_server_names() {
@@ -51,21 +73,29 @@ pub fn execute(server: Option<&str>, all: bool) {
         let mut updated = 0usize;
         let mut up_to_date = 0usize;
         let mut failed = 0usize;
+        let mut progress = Progress::new(progress, "update", targets.len());
 
         for name in &targets {
-            match update_one(name, &registry) {
-                Ok(UpdateResult::Updated { from, to }) => {
-                    println!(
+            progress.start("update", Some(name));
+            let result = update_one(name, None, &registry, dry_run);
+            match &result {
+                Ok(_) => progress.complete(),
+                Err(msg) => progress.fail(msg),
+            }
+            match result {
+                Ok(UpdateResult::Updated { from, to, strategy }) => {
+                    say!(
                         "{} Updated {} ({} -> {}).",
                         "✓".green().bold(),
                         name.cyan(),
                         from,
                         to
                     );
+                    print_strategy_note(strategy);
                     updated += 1;
                 }
                 Ok(UpdateResult::UpToDate { version }) => {
-                    println!(
+                    say!(
                         "{} {} is already up to date (v{}).",
                         "!".yellow().bold(),
                         name.cyan(),
@@ -73,6 +103,7 @@ pub fn execute(server: Option<&str>, all: bool) {
                     );
                     up_to_date += 1;
                 }
+                Ok(UpdateResult::Planned(plan)) => plan.print(),
                 Err(msg) => {
                     eprintln!("{} {}", "✗".red().bold(), msg);
                     failed += 1;
@@ -80,13 +111,16 @@ pub fn execute(server: Option<&str>, all: bool) {
             }
         }
 
-        println!(
-            "\n{} Updated: {}, up to date: {}, failed: {}",
-            "•".dimmed(),
-            updated,
-            up_to_date,
-            failed
-        );
+        progress.finish();
+        if !dry_run {
+            say!(
+                "\n{} Updated: {}, up to date: {}, failed: {}",
+                "•".dimmed(),
+                updated,
+                up_to_date,
+                failed
+            );
+        }
 
         if failed > 0 {
             process::exit(1);
@@ -95,25 +129,42 @@ pub fn execute(server: Option<&str>, all: bool) {
         return;
     }
 
-    if let Some(name) = server {
-        match update_one(name, &registry) {
-            Ok(UpdateResult::Updated { from, to }) => {
-                println!(
+    if let Some(spec) = server {
+        let (name, constraint) = match parse_server_spec(spec) {
+            Ok(v) => v,
+            Err(msg) => {
+                eprintln!("{} {}", "✗".red().bold(), msg);
+                process::exit(1);
+            }
+        };
+        let mut progress = Progress::new(progress, "update", 1);
+        progress.start("update", Some(name));
+        let result = update_one(name, constraint, &registry, dry_run);
+        match &result {
+            Ok(_) => progress.complete(),
+            Err(msg) => progress.fail(msg),
+        }
+        progress.finish();
+        match result {
+            Ok(UpdateResult::Updated { from, to, strategy }) => {
+                say!(
                     "{} Updated {} ({} -> {}).",
                     "✓".green().bold(),
                     name.cyan(),
                     from,
                     to
                 );
+                print_strategy_note(strategy);
             }
             Ok(UpdateResult::UpToDate { version }) => {
-                println!(
+                say!(
                     "{} {} is already up to date (v{}).",
                     "!".yellow().bold(),
                     name.cyan(),
                     version
                 );
             }
+            Ok(UpdateResult::Planned(plan)) => plan.print(),
             Err(msg) => {
                 eprintln!("{} {}", "✗".red().bold(), msg);
                 process::exit(1);
@@ -122,6 +173,16 @@ pub fn execute(server: Option<&str>, all: bool) {
     }
 }
 
+/// Explains how connected proxies pick up the new version.
+fn print_strategy_note(strategy: UpdateStrategy) {
+    if strategy == UpdateStrategy::BlueGreen {
+        say!(
+            "  {}",
+            "Connected proxies switch to the new version once it answers initialize.".dimmed()
+        );
+    }
+}
+
 /// Lists all installed servers by config file stem.
 fn installed_server_names() -> Result<Vec<String>, String> {
     let servers_dir = paths::berth_servers_dir().ok_or("Could not determine home directory.")?;
@@ -151,7 +212,15 @@ fn installed_server_names() -> Result<Vec<String>, String> {
 }
 
 /// Updates a single installed server from seed registry metadata.
-fn update_one(name: &str, registry: &Registry) -> Result<UpdateResult, String> {
+///
+/// A `constraint` replaces the one recorded at install; without either, the
+/// registry's current version is installed.
+fn update_one(
+    name: &str,
+    constraint: Option<&str>,
+    registry: &Registry,
+    dry_run: bool,
+) -> Result<UpdateResult, String> {
     let config_path =
         paths::server_config_path(name).ok_or("Could not determine home directory.")?;
     if !config_path.exists() {
@@ -164,30 +233,177 @@ fn update_one(name: &str, registry: &Registry) -> Result<UpdateResult, String> {
         toml::from_str(&content).map_err(|e| format!("Failed to parse config file: {e}"))?;
 
     let meta = registry
-        .get(name)
+        .get(&current.server.name)
         .ok_or_else(|| format!("Server {} not found in the registry.", name.cyan()))?;
+    let constraint = constraint
+        .map(str::to_string)
+        .or_else(|| current.server.version_constraint.clone());
+    let meta = match &constraint {
+        Some(constraint) => resolve_version(meta, constraint)?,
+        None => meta.clone(),
+    };
 
     if current.server.version == meta.version {
+        if !dry_run && current.server.version_constraint != constraint {
+            let mut pinned = current.clone();
+            pinned.server.version_constraint = constraint;
+            let rendered = toml::to_string_pretty(&pinned)
+                .map_err(|e| format!("Failed to serialize config: {e}"))?;
+            fs::write(&config_path, rendered)
+                .map_err(|e| format!("Failed to write config file: {e}"))?;
+        }
         return Ok(UpdateResult::UpToDate {
-            version: meta.version.clone(),
+            version: meta.version,
         });
     }
 
     let from_version = current.server.version.clone();
-    let mut updated = InstalledServer::from_metadata(meta);
+    let mut updated = InstalledServer::from_metadata(&meta);
+    updated.source.registry = Some(registry.origin().to_string());
+    updated.server.version_constraint = constraint;
     merge_config_values(&current, &mut updated);
+    let strategy = parse_proxy_policy(&updated.config)?.update_strategy;
+
+    if dry_run {
+        return Ok(UpdateResult::Planned(update_plan(
+            name,
+            &current,
+            &updated,
+            &meta,
+            &config_path,
+        )));
+    }
+
+    // Binaries are fetched again and checked against the new digest before
+    // the config is switched over.
+    let prepared = prepare_installed_server(name, &meta, None)?;
+    updated.runtime = prepared.runtime;
+    updated.source.sha256 = prepared.source.sha256;
 
     let rendered =
         toml::to_string_pretty(&updated).map_err(|e| format!("Failed to serialize config: {e}"))?;
     fs::write(&config_path, rendered).map_err(|e| format!("Failed to write config file: {e}"))?;
+    if let Some(runtime) = paths::runtime_manager() {
+        let _ = runtime.record_version_event(
+            name,
+            "update",
+            &updated.server.version,
+            Some(&from_version),
+        );
+    }
+    let mut receipt = Receipt::new(name, "update", &updated.server.version, &updated.source);
+    receipt.previous_version = Some(from_version.clone());
+    receipt
+        .policy
+        .push(PolicyDecision::new("update strategy", strategy.as_str()));
+    if let Err(msg) = FileRecord::hashed(&config_path, "config").and_then(|record| {
+        receipt.files.push(record);
+        receipts::write(&receipt)
+    }) {
+        say!(
+            "{} Could not write update receipt: {msg}",
+            "!".yellow().bold()
+        );
+    }
+
+    restart_http_server(name, &updated, registry).map_err(|msg| {
+        format!(
+            "Updated {} to {}, but restarting it failed: {msg}",
+            name.cyan(),
+            updated.server.version
+        )
+    })?;
 
     Ok(UpdateResult::Updated {
         from: from_version,
         to: updated.server.version,
+        strategy,
     })
 }
 
+/// Moves a running `http` server onto its updated config: replicas one at a
+/// time behind their gateway, or the server itself when it is not replicated.
+///
+/// No proxy relaunches `http` servers, so without this the old version would
+/// keep serving until the next `berth restart`.
+fn restart_http_server(
+    name: &str,
+    installed: &InstalledServer,
+    registry: &Registry,
+) -> Result<(), String> {
+    if installed.runtime.transport.trim() != HTTP {
+        return Ok(());
+    }
+    let runtime = paths::runtime_manager().ok_or("Could not determine home directory.")?;
+    if runtime.running_pid(name).ok().flatten().is_none() {
+        return Ok(());
+    }
+    let policy = load_global_policy()?;
+    let inherit_env = launched_with_inherited_env(name);
+    let (mut spec, _) = build_process_spec(name, installed, registry, &policy, inherit_env)?;
+    inject_access_token(name, installed, registry, &mut spec)?;
+    rolling_restart(&runtime, name, &instances(name, installed, spec)?)
+}
+
+/// Describes what `berth update` would change for one server.
+fn update_plan(
+    name: &str,
+    current: &InstalledServer,
+    updated: &InstalledServer,
+    meta: &ServerMetadata,
+    config_path: &std::path::Path,
+) -> DryRunPlan {
+    let mut plan = DryRunPlan::new("update", name);
+    plan.add(
+        "outcome",
+        format!(
+            "would update {} -> {}",
+            current.server.version, updated.server.version
+        ),
+    );
+    plan.add(
+        "command",
+        command_line(&updated.runtime.command, &updated.runtime.args),
+    );
+    if let Ok(policy) = parse_proxy_policy(&updated.config) {
+        plan.add("update strategy", policy.update_strategy.as_str());
+    }
+    match parse_replica_policy(updated) {
+        Ok(Some(policy)) if policy.replicas > 1 => plan.add(
+            "restart",
+            format!(
+                "rolling, {} replicas one at a time if running",
+                policy.replicas
+            ),
+        ),
+        Ok(_) if updated.runtime.transport.trim() == HTTP => plan.add("restart", "if running"),
+        _ => {}
+    }
+
+    let preserved: Vec<String> = current
+        .config
+        .iter()
+        .filter(|(k, v)| !v.trim().is_empty() && updated.config.contains_key(*k))
+        .map(|(k, _)| k.clone())
+        .collect();
+    let dropped: Vec<String> = current
+        .config
+        .keys()
+        .filter(|k| !updated.config.contains_key(*k))
+        .cloned()
+        .collect();
+    plan.add("config preserved", join_or_none(&preserved));
+    plan.add("config dropped", join_or_none(&dropped));
+    plan.add_permissions(&updated.permissions);
+    plan.add_permission_changes(&current.permissions, &updated.permissions);
+    plan.add("integrity", integrity_plan(meta, None));
+    plan.add_write(config_path);
+    plan
+}
+
 /// Preserves non-empty existing config values for keys in the new schema.
+///
+/// Berth's own `berth.*` settings are not part of any schema and are always kept.
 fn merge_config_values(current: &InstalledServer, updated: &mut InstalledServer) {
     for (key, old_value) in &current.config {
         if old_value.trim().is_empty() {
@@ -195,6 +411,8 @@ fn merge_config_values(current: &InstalledServer, updated: &mut InstalledServer)
         }
         if let Some(new_value) = updated.config.get_mut(key) {
             *new_value = old_value.clone();
+        } else if key.starts_with("berth.") {
+            updated.config.insert(key.clone(), old_value.clone());
         }
     }
 }
@@ -246,4 +464,27 @@ mod tests {
         );
         assert_eq!(updated.config.get("schema"), Some(&"public".to_string()));
     }
+
+    #[test]
+    fn merge_keeps_berth_settings_outside_the_schema() {
+        let registry = Registry::from_seed();
+        let meta = registry.get("github").unwrap();
+        let mut current = InstalledServer::from_metadata(meta);
+        current.config.insert(
+            "berth.update-strategy".to_string(),
+            "blue-green".to_string(),
+        );
+        current
+            .config
+            .insert("retired-key".to_string(), "value".to_string());
+
+        let mut updated = InstalledServer::from_metadata(meta);
+        merge_config_values(&current, &mut updated);
+
+        assert_eq!(
+            updated.config.get("berth.update-strategy"),
+            Some(&"blue-green".to_string())
+        );
+        assert!(!updated.config.contains_key("retired-key"));
+    }
 }
@@ -0,0 +1,118 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Command handler for `berth warnings`.
+
+use colored::Colorize;
+use std::process;
+
+use crate::time_format::{now_epoch_ms, TimestampFormatter};
+use crate::warnings::{self, CATALOG};
+
+/// Executes the `berth warnings` command.
+pub fn execute(action: Option<&str>, id: Option<&str>, duration: Option<&str>) {
+    let Some(path) = warnings::warnings_path() else {
+        fail("Could not determine home directory.");
+    };
+    match action.unwrap_or("list") {
+        "list" => list(&path),
+        "snooze" => {
+            let id = known_id(id);
+            let Some(duration) = duration else {
+                fail("Name how long to snooze, e.g. `berth warnings snooze <id> 7d`.");
+            };
+            let until = match warnings::parse_duration(duration) {
+                Ok(ms) => now_epoch_ms().saturating_add(ms),
+                Err(msg) => fail(&msg),
+            };
+            if let Err(msg) = warnings::snooze(&path, id, until) {
+                fail(&msg);
+            }
+            println!(
+                "{} Snoozed {} until {}.",
+                "✓".green().bold(),
+                id.bold(),
+                TimestampFormatter::new(false).format(until)
+            );
+        }
+        "unsnooze" => {
+            let id = known_id(id);
+            match warnings::unsnooze(&path, id) {
+                Ok(true) => println!("{} {} is no longer snoozed.", "✓".green().bold(), id.bold()),
+                Ok(false) => println!("{} is not snoozed.", id.bold()),
+                Err(msg) => fail(&msg),
+            }
+        }
+        other => fail(&format!(
+            "Unknown warnings action `{other}`. Use {}, {} or {}.",
+            "list".bold(),
+            "snooze".bold(),
+            "unsnooze".bold()
+        )),
+    }
+}
+
+fn known_id(id: Option<&str>) -> &str {
+    let Some(id) = id else {
+        fail("Name a warning ID; `berth warnings list` shows them.");
+    };
+    if warnings::kind(id).is_none() {
+        let ids: Vec<&str> = CATALOG.iter().map(|kind| kind.id).collect();
+        fail(&format!(
+            "Unknown warning ID `{id}`. Known IDs: {}.",
+            ids.join(", ")
+        ));
+    }
+    id
+}
+
+fn list(path: &std::path::Path) {
+    let file = match warnings::load(path) {
+        Ok(file) => file,
+        Err(msg) => fail(&msg),
+    };
+    let formatter = TimestampFormatter::new(false);
+    let now = now_epoch_ms();
+    for kind in CATALOG {
+        println!("{}  {}", kind.id.bold(), kind.description);
+        match file.snoozed.get(kind.id).filter(|until| **until > now) {
+            Some(until) => println!(
+                "  {}",
+                format!("snoozed until {}", formatter.format(*until)).yellow()
+            ),
+            None => println!(
+                "  {}",
+                format!(
+                    "shown at most once per {}h per subject",
+                    kind.interval_ms / 3_600_000
+                )
+                .dimmed()
+            ),
+        }
+        let prefix = format!("{}:", kind.id);
+        for (key, shown) in &file.shown {
+            let subject = if key == kind.id {
+                "-"
+            } else if let Some(subject) = key.strip_prefix(&prefix) {
+                subject
+            } else {
+                continue;
+            };
+            let suppressed = if shown.suppressed > 0 {
+                format!(", {} repeat(s) held back since", shown.suppressed)
+            } else {
+                String::new()
+            };
+            println!(
+                "  {:<20} last shown {}{suppressed}",
+                subject.cyan(),
+                formatter.format(shown.last_shown_epoch_ms)
+            );
+        }
+    }
+}
+
+fn fail(msg: &str) -> ! {
+    eprintln!("{} {}", "✗".red().bold(), msg);
+    process::exit(1);
+}
@@ -0,0 +1,98 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Command handler for `berth wrap`.
+//!
+//! Some clients insist on spawning server processes themselves. `berth wrap`
+//! is the command such a client runs: it launches the real server with the
+//! same env, secrets, permission, sandbox, and audit handling as
+//! `berth proxy`, but leaves the process lifetime to the client. The server is
+//! never restarted or upgraded in place; it ends when the client closes stdin,
+//! and its exit code becomes the exit code of `berth wrap`.
+
+use berth_runtime::exit_code;
+use colored::Colorize;
+use std::io;
+use std::process;
+
+use crate::commands::proxy::{self, Upstream};
+use crate::hints::Failure;
+use crate::mcp_proxy;
+
+/// Executes the `berth wrap` command.
+pub fn execute(server: &str, client: Option<&str>, inherit_env: bool) {
+    let Upstream {
+        installed,
+        spec,
+        runtime,
+        mut options,
+        ..
+    } = proxy::prepare(server, client, inherit_env, "wrap");
+    // The client decides when the server lives and dies.
+    options.restart = None;
+    options.upgrade_wait = None;
+
+    let child = match proxy::spawn_upstream(&spec) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!(
+                "{} Failed to start {}: {}",
+                "✗".red().bold(),
+                server.cyan(),
+                e
+            );
+            if let Some(failure) = Failure::from_io(&e) {
+                failure.print(server, &spec.command);
+            }
+            process::exit(1);
+        }
+    };
+
+    let pid = child.id();
+    let _ = runtime.record_audit_event(
+        server,
+        "wrap-start",
+        Some(pid),
+        Some(&spec.command),
+        Some(&spec.args),
+    );
+
+    let relayed = mcp_proxy::relay(
+        child,
+        || Err(io::Error::other("wrapped servers are not restarted")),
+        || None,
+        options,
+        proxy::audit_requests(server),
+        proxy::tool_gate(server, &installed),
+    );
+
+    let status = match relayed {
+        Ok(s) => s,
+        Err(e) => {
+            let _ = runtime.record_audit_event(
+                server,
+                "wrap-error",
+                Some(pid),
+                Some(&spec.command),
+                Some(&spec.args),
+            );
+            eprintln!(
+                "{} Failed while wrapping {}: {}",
+                "✗".red().bold(),
+                server.cyan(),
+                e
+            );
+            process::exit(1);
+        }
+    };
+
+    let _ = runtime.record_audit_event(
+        server,
+        "wrap-end",
+        Some(pid),
+        Some(&spec.command),
+        Some(&spec.args),
+    );
+
+    process::exit(exit_code(&status).unwrap_or(1));
+}
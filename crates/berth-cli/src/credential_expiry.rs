@@ -0,0 +1,242 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Expiry tracking for short-lived server credentials.
+//!
+//! OAuth refreshes record when the issued access token expires; other
+//! expiring credentials (e.g. STS session tokens) declare it with
+//! `berth.credential-expires-at`. Supervised servers are relaunched with fresh
+//! credentials before expiry, and `berth status` flags credentials that are
+//! close to or past it.
+
+use berth_common::atomic_write;
+use berth_common::time::parse_date;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::paths;
+
+/// Config key declaring when a manually supplied credential expires.
+pub const KEY_CREDENTIAL_EXPIRES_AT: &str = "berth.credential-expires-at";
+
+/// Credentials closer than this to expiry are reported as expiring.
+pub const EXPIRY_WARNING_SECS: u64 = 300;
+
+/// How close a credential is to its expiry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CredentialState {
+    Valid { remaining_secs: u64 },
+    ExpiringSoon { remaining_secs: u64 },
+    Expired { ago_secs: u64 },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ExpiryRecord {
+    expires_at_epoch_secs: u64,
+}
+
+/// Returns whether a config key belongs to credential expiry settings.
+pub fn is_credential_expiry_key(key: &str) -> bool {
+    key == KEY_CREDENTIAL_EXPIRES_AT
+}
+
+/// Validates a credential expiry config value.
+pub fn validate_credential_expiry_value(key: &str, value: &str) -> Result<(), String> {
+    match key {
+        KEY_CREDENTIAL_EXPIRES_AT => parse_expiry(value).map(|_| ()),
+        _ => Err(format!("Unknown credential expiry key `{key}`.")),
+    }
+}
+
+/// Parses unix seconds or an RFC3339 timestamp such as `2026-10-16T12:00:00Z`.
+pub fn parse_expiry(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    if !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit()) {
+        return value
+            .parse::<u64>()
+            .map_err(|_| format!("Invalid expiry `{value}`."));
+    }
+    parse_rfc3339(value).ok_or_else(|| {
+        format!(
+            "Invalid expiry `{value}`. Use unix seconds or an RFC3339 timestamp (e.g. 2026-10-16T12:00:00Z)."
+        )
+    })
+}
+
+/// Classifies a credential expiring at `expires_at` as seen at `now`.
+pub fn classify(expires_at: u64, now: u64) -> CredentialState {
+    if now >= expires_at {
+        CredentialState::Expired {
+            ago_secs: now - expires_at,
+        }
+    } else if expires_at - now <= EXPIRY_WARNING_SECS {
+        CredentialState::ExpiringSoon {
+            remaining_secs: expires_at - now,
+        }
+    } else {
+        CredentialState::Valid {
+            remaining_secs: expires_at - now,
+        }
+    }
+}
+
+impl CredentialState {
+    /// Describes the state relative to now, e.g. `expires in 4m`.
+    pub fn describe(&self) -> String {
+        match self {
+            CredentialState::Valid { remaining_secs }
+            | CredentialState::ExpiringSoon { remaining_secs } => {
+                format!("expires in {}", format_span(*remaining_secs))
+            }
+            CredentialState::Expired { ago_secs } => {
+                format!("expired {} ago", format_span(*ago_secs))
+            }
+        }
+    }
+}
+
+/// Returns when a credential issued at `issued_at` should be refreshed.
+///
+/// Refreshes [`EXPIRY_WARNING_SECS`] before expiry, but never before half the
+/// lifetime has passed so very short-lived tokens do not relaunch constantly.
+pub fn refresh_at(issued_at: u64, expires_at: u64) -> u64 {
+    let halfway = issued_at + expires_at.saturating_sub(issued_at) / 2;
+    expires_at.saturating_sub(EXPIRY_WARNING_SECS).max(halfway)
+}
+
+/// Returns the known expiry of a server's credentials, if any.
+///
+/// An explicit `berth.credential-expires-at` wins over the recorded OAuth expiry.
+pub fn credential_expiry(server: &str, config: &BTreeMap<String, String>) -> Option<u64> {
+    match config.get(KEY_CREDENTIAL_EXPIRES_AT) {
+        Some(value) if !value.trim().is_empty() => parse_expiry(value).ok(),
+        _ => recorded_expiry(server),
+    }
+}
+
+/// Records when the credentials most recently issued to a server expire.
+pub fn record_expiry(server: &str, expires_at: u64) -> Result<(), String> {
+    let path = record_path(server)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("failed to create {}: {e}", parent.display()))?;
+    }
+    let rendered = toml::to_string(&ExpiryRecord {
+        expires_at_epoch_secs: expires_at,
+    })
+    .map_err(|e| format!("failed to serialize credential expiry: {e}"))?;
+    atomic_write(&path, rendered).map_err(|e| format!("failed to write {}: {e}", path.display()))
+}
+
+/// Returns the recorded expiry for a server, if any.
+pub fn recorded_expiry(server: &str) -> Option<u64> {
+    let content = fs::read_to_string(record_path(server).ok()?).ok()?;
+    toml::from_str::<ExpiryRecord>(&content)
+        .ok()
+        .map(|r| r.expires_at_epoch_secs)
+}
+
+/// Formats a span of seconds compactly, e.g. `45s`, `4m`, `2h 5m`, `3d`.
+pub fn format_span(secs: u64) -> String {
+    match secs {
+        0..=59 => format!("{secs}s"),
+        60..=3_599 => format!("{}m", secs / 60),
+        3_600..=86_399 if secs % 3_600 >= 60 => {
+            format!("{}h {}m", secs / 3_600, secs % 3_600 / 60)
+        }
+        3_600..=86_399 => format!("{}h", secs / 3_600),
+        _ => format!("{}d", secs / 86_400),
+    }
+}
+
+fn record_path(server: &str) -> Result<PathBuf, String> {
+    paths::berth_home()
+        .map(|h| h.join("credential-expiry").join(format!("{server}.toml")))
+        .ok_or("Could not determine home directory.".to_string())
+}
+
+/// Parses `YYYY-MM-DDTHH:MM:SS[.frac](Z|±HH:MM)` into unix seconds.
+fn parse_rfc3339(value: &str) -> Option<u64> {
+    let (date, time) = value.split_once(['T', 't', ' '])?;
+    let days = parse_date(date)?;
+
+    let (clock, offset_secs) = if let Some(clock) = time.strip_suffix(['Z', 'z']) {
+        (clock, 0i64)
+    } else {
+        let split = time.rfind(['+', '-'])?;
+        let (clock, offset) = time.split_at(split);
+        let sign = if offset.starts_with('-') { -1 } else { 1 };
+        let (hours, minutes) = offset[1..].split_once(':')?;
+        let offset = hours.parse::<i64>().ok()? * 3_600 + minutes.parse::<i64>().ok()? * 60;
+        (clock, sign * offset)
+    };
+    let clock = clock.split('.').next()?;
+    let mut clock_parts = clock.splitn(3, ':');
+    let hour: i64 = clock_parts.next()?.parse().ok()?;
+    let minute: i64 = clock_parts.next()?.parse().ok()?;
+    let second: i64 = clock_parts.next()?.parse().ok()?;
+    if hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+
+    let secs = days * 86_400 + hour * 3_600 + minute * 60 + second - offset_secs;
+    u64::try_from(secs).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_expiry_accepts_epoch_and_rfc3339() {
+        assert_eq!(parse_expiry("1792152000").unwrap(), 1_792_152_000);
+        assert_eq!(parse_expiry("1970-01-01T00:00:00Z").unwrap(), 0);
+        assert_eq!(parse_expiry("2026-10-16T12:00:00Z").unwrap(), 1_792_152_000);
+        assert_eq!(
+            parse_expiry("2026-10-16T14:00:00.123+02:00").unwrap(),
+            1_792_152_000
+        );
+        assert!(parse_expiry("tomorrow").is_err());
+        assert!(parse_expiry("2026-13-01T00:00:00Z").is_err());
+        assert!(parse_expiry("2026-02-30T00:00:00Z").is_err());
+    }
+
+    #[test]
+    fn classify_flags_near_and_past_expiry() {
+        assert_eq!(
+            classify(1_000, 100),
+            CredentialState::Valid {
+                remaining_secs: 900
+            }
+        );
+        assert_eq!(
+            classify(1_000, 800),
+            CredentialState::ExpiringSoon {
+                remaining_secs: 200
+            }
+        );
+        assert_eq!(
+            classify(1_000, 1_060),
+            CredentialState::Expired { ago_secs: 60 }
+        );
+        assert_eq!(classify(1_000, 800).describe(), "expires in 3m");
+        assert_eq!(classify(1_000, 1_060).describe(), "expired 1m ago");
+    }
+
+    #[test]
+    fn refresh_at_leaves_headroom_without_thrashing() {
+        assert_eq!(refresh_at(0, 3_600), 3_300);
+        assert_eq!(refresh_at(1_000, 1_120), 1_060);
+    }
+
+    #[test]
+    fn format_span_is_compact() {
+        assert_eq!(format_span(42), "42s");
+        assert_eq!(format_span(299), "4m");
+        assert_eq!(format_span(7_500), "2h 5m");
+        assert_eq!(format_span(7_200), "2h");
+        assert_eq!(format_span(259_200), "3d");
+    }
+}
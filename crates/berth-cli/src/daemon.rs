@@ -0,0 +1,423 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! `berth daemon`: one long-running supervisor for the servers of a Berth home.
+//!
+//! Without a daemon, every supervised `berth start` leaves a hidden
+//! `__supervise` process behind for its server. While a daemon runs, `start`,
+//! `restart`, `stop`, `status`, and `logs` send their request over its control
+//! socket instead: the daemon launches servers as its own children, supervises
+//! each on a thread, and answers from the same state files. Requests and
+//! replies are one JSON object per line. When no daemon answers, commands fall
+//! back to working on the state files themselves.
+
+use serde::{Deserialize, Serialize};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+
+use berth_runtime::{
+    Endpoint, ProcessSpec, RuntimeManager, ServerStatus, StartOutcome, StopOutcome,
+};
+
+use crate::observer;
+use crate::paths;
+
+/// Control socket in the state directory, on unix.
+const SOCKET_FILE: &str = "berthd.sock";
+
+/// A request to the daemon.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "kebab-case")]
+pub enum Request {
+    Ping,
+    Start {
+        server: String,
+        spec: Box<ProcessSpec>,
+    },
+    Stop {
+        server: String,
+    },
+    Status {
+        server: String,
+        spec: Option<Box<ProcessSpec>>,
+    },
+    Logs {
+        server: String,
+        lines: usize,
+    },
+    /// Stops the servers the daemon supervises, then the daemon.
+    Shutdown,
+}
+
+/// The daemon's answer to a [`Request`].
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "reply", rename_all = "kebab-case")]
+pub enum Reply {
+    Pong { pid: u32, supervised: Vec<String> },
+    Started { already_running: bool },
+    Stopped { was_running: bool },
+    Status { status: ServerStatus },
+    Logs { lines: Vec<String> },
+    ShuttingDown { stopped: Vec<String> },
+    Error { message: String },
+}
+
+/// Returns the control endpoint of the active Berth home.
+pub fn endpoint() -> Option<Endpoint> {
+    let state = paths::berth_home()?;
+    endpoint_in(&state).ok()
+}
+
+fn endpoint_in(state: &Path) -> Result<Endpoint, String> {
+    if cfg!(windows) {
+        let digest = berth_common::digest::sha256_hex(state.display().to_string().as_bytes());
+        Endpoint::parse(
+            berth_runtime::NAMED_PIPE,
+            &format!(r"\\.\pipe\berthd-{}", &digest[..16]),
+        )
+    } else {
+        Endpoint::parse(
+            berth_runtime::UNIX_SOCKET,
+            &state.join(SOCKET_FILE).display().to_string(),
+        )
+    }
+}
+
+/// Sends one request; `None` when no daemon answers on the control socket.
+pub fn request(request: &Request) -> Option<Result<Reply, String>> {
+    let mut connection = endpoint()?.connect().ok()?;
+    Some(exchange(&mut connection, request))
+}
+
+fn exchange(
+    connection: &mut berth_runtime::Connection,
+    request: &Request,
+) -> Result<Reply, String> {
+    let line = serde_json::to_string(request)
+        .map_err(|e| format!("failed to encode daemon request: {e}"))?;
+    writeln!(connection, "{line}")
+        .and_then(|()| connection.flush())
+        .map_err(|e| format!("failed to send daemon request: {e}"))?;
+    let _ = connection.shutdown_write();
+    let mut reply = String::new();
+    BufReader::new(connection)
+        .read_line(&mut reply)
+        .map_err(|e| format!("failed to read daemon reply: {e}"))?;
+    match serde_json::from_str(&reply) {
+        Ok(Reply::Error { message }) => Err(message),
+        Ok(reply) => Ok(reply),
+        Err(_) if reply.is_empty() => Err("berth daemon closed the connection".to_string()),
+        Err(e) => Err(format!("invalid daemon reply: {e}")),
+    }
+}
+
+/// Returns the daemon's pid and supervised servers, if one answers.
+pub fn ping() -> Option<(u32, Vec<String>)> {
+    match request(&Request::Ping)? {
+        Ok(Reply::Pong { pid, supervised }) => Some((pid, supervised)),
+        _ => None,
+    }
+}
+
+/// Returns whether a daemon answers for the active Berth home.
+pub fn running() -> bool {
+    ping().is_some()
+}
+
+/// Launches a server under the daemon; `None` when no daemon answers.
+pub fn start(server: &str, spec: &ProcessSpec) -> Option<Result<StartOutcome, String>> {
+    let reply = request(&Request::Start {
+        server: server.to_string(),
+        spec: Box::new(spec.clone()),
+    })?;
+    Some(reply.and_then(|reply| match reply {
+        Reply::Started { already_running } if already_running => Ok(StartOutcome::AlreadyRunning),
+        Reply::Started { .. } => Ok(StartOutcome::Started),
+        other => Err(unexpected(&other)),
+    }))
+}
+
+/// Stops a server through the daemon when one runs, or directly otherwise.
+pub fn stop(runtime: &RuntimeManager, server: &str) -> Result<StopOutcome, String> {
+    let Some(reply) = request(&Request::Stop {
+        server: server.to_string(),
+    }) else {
+        return runtime.stop(server).map_err(|e| e.to_string());
+    };
+    match reply? {
+        Reply::Stopped { was_running: true } => Ok(StopOutcome::Stopped),
+        Reply::Stopped { was_running: false } => Ok(StopOutcome::AlreadyStopped),
+        other => Err(unexpected(&other)),
+    }
+}
+
+/// Reads a server's status through the daemon when one runs, or directly otherwise.
+///
+/// In observer mode the status is only peeked at: neither the daemon nor this
+/// process records an exit or auto-restarts a crashed server.
+pub fn status(
+    runtime: &RuntimeManager,
+    server: &str,
+    spec: Option<&ProcessSpec>,
+) -> io::Result<ServerStatus> {
+    if observer::enabled() {
+        return runtime.peek_status(server);
+    }
+    let Some(reply) = request(&Request::Status {
+        server: server.to_string(),
+        spec: spec.cloned().map(Box::new),
+    }) else {
+        return runtime.status_with_spec(server, spec);
+    };
+    match reply.map_err(io::Error::other)? {
+        Reply::Status { status } => Ok(status),
+        other => Err(io::Error::other(unexpected(&other))),
+    }
+}
+
+/// Reads the last log lines through the daemon when one runs, or directly otherwise.
+pub fn tail_logs(runtime: &RuntimeManager, server: &str, lines: usize) -> io::Result<Vec<String>> {
+    let Some(reply) = request(&Request::Logs {
+        server: server.to_string(),
+        lines,
+    }) else {
+        return runtime.tail_logs(server, lines);
+    };
+    match reply.map_err(io::Error::other)? {
+        Reply::Logs { lines } => Ok(lines),
+        other => Err(io::Error::other(unexpected(&other))),
+    }
+}
+
+fn unexpected(reply: &Reply) -> String {
+    format!("unexpected reply from berth daemon: {reply:?}")
+}
+
+#[cfg(unix)]
+pub use server::serve;
+
+/// Serving the control socket needs unix domain sockets.
+#[cfg(not(unix))]
+pub fn serve(_runtime: RuntimeManager) -> Result<(), String> {
+    Err("`berth daemon` needs unix domain sockets; on this platform every server keeps its own supervisor.".to_string())
+}
+
+#[cfg(unix)]
+mod server {
+    use colored::Colorize;
+    use std::collections::BTreeMap;
+    use std::io::{self, BufRead, BufReader, Write};
+    use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::path::Path;
+    use std::sync::{mpsc, Arc, Mutex};
+    use std::{fs, process, thread};
+
+    use berth_runtime::{Endpoint, RuntimeManager, StartOutcome, StopOutcome};
+
+    use super::{ping, Reply, Request, SOCKET_FILE};
+    use crate::commands::supervise;
+
+    /// Supervision threads per server. A restarted server briefly has two,
+    /// until the old one sees its process replaced.
+    type Supervised = Arc<Mutex<BTreeMap<String, usize>>>;
+
+    /// Listens on the control socket and serves requests until a shutdown
+    /// request arrives.
+    pub fn serve(runtime: RuntimeManager) -> Result<(), String> {
+        let endpoint = super::endpoint().ok_or("Could not determine home directory.")?;
+        let Endpoint::UnixSocket(path) = &endpoint else {
+            return Err(format!("{endpoint} is not a unix socket."));
+        };
+        if let Some((pid, _)) = ping() {
+            return Err(format!(
+                "A daemon already runs for this Berth home (pid {pid})."
+            ));
+        }
+        endpoint
+            .prepare()
+            .and_then(|()| endpoint.remove_stale())
+            .map_err(|e| format!("Failed to prepare {}: {e}", path.display()))?;
+        let listener = bind_private(path)
+            .map_err(|e| format!("Failed to listen on {}: {e}", path.display()))?;
+        println!(
+            "{} Berth daemon listening on {} (pid {}).",
+            "✓".green().bold(),
+            path.display(),
+            process::id()
+        );
+
+        let supervised = Supervised::default();
+        for stream in listener.incoming() {
+            let Ok(stream) = stream else {
+                continue;
+            };
+            let runtime = runtime.clone();
+            let supervised = Arc::clone(&supervised);
+            let path = path.clone();
+            thread::spawn(move || {
+                let shutdown = handle(stream, &runtime, &supervised);
+                if shutdown {
+                    let _ = fs::remove_file(&path);
+                    println!("Berth daemon stopped.");
+                    process::exit(0);
+                }
+            });
+        }
+        Ok(())
+    }
+
+    /// Binds the control socket inside a directory only the owner can enter,
+    /// restricts it to the owner, then moves it into place, so it is never
+    /// reachable with the permissions the umask would give it.
+    pub(super) fn bind_private(path: &Path) -> io::Result<UnixListener> {
+        let dir = path.with_file_name(format!(".berthd-{}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::DirBuilder::new().mode(0o700).create(&dir)?;
+        let staged = dir.join(SOCKET_FILE);
+        let listener = UnixListener::bind(&staged).and_then(|listener| {
+            fs::set_permissions(&staged, fs::Permissions::from_mode(0o600))?;
+            fs::rename(&staged, path)?;
+            Ok(listener)
+        });
+        let _ = fs::remove_dir_all(&dir);
+        listener
+    }
+
+    /// Answers one connection; returns whether it asked the daemon to exit.
+    fn handle(mut stream: UnixStream, runtime: &RuntimeManager, supervised: &Supervised) -> bool {
+        let mut line = String::new();
+        let read = stream
+            .try_clone()
+            .and_then(|reader| BufReader::new(reader).read_line(&mut line));
+        if read.is_err() {
+            return false;
+        }
+        let request: Request = match serde_json::from_str(&line) {
+            Ok(request) => request,
+            Err(e) => {
+                reply(
+                    &mut stream,
+                    &Reply::Error {
+                        message: format!("invalid daemon request: {e}"),
+                    },
+                );
+                return false;
+            }
+        };
+        let shutdown = matches!(request, Request::Shutdown);
+        let answer = answer(request, runtime, supervised);
+        reply(&mut stream, &answer);
+        shutdown
+    }
+
+    fn reply(stream: &mut UnixStream, reply: &Reply) {
+        if let Ok(line) = serde_json::to_string(reply) {
+            // The client may be gone already.
+            let _ = writeln!(stream, "{line}").and_then(|()| stream.flush());
+        }
+    }
+
+    fn answer(request: Request, runtime: &RuntimeManager, supervised: &Supervised) -> Reply {
+        let error = |message: String| Reply::Error { message };
+        match request {
+            Request::Ping => Reply::Pong {
+                pid: process::id(),
+                supervised: lock(supervised).keys().cloned().collect(),
+            },
+            Request::Start { server, spec } => launch(runtime, supervised, server, *spec),
+            Request::Stop { server } => match runtime.stop(&server) {
+                Ok(outcome) => Reply::Stopped {
+                    was_running: outcome == StopOutcome::Stopped,
+                },
+                Err(e) => error(e.to_string()),
+            },
+            Request::Status { server, spec } => {
+                match runtime.status_with_spec(&server, spec.as_deref()) {
+                    Ok(status) => Reply::Status { status },
+                    Err(e) => error(e.to_string()),
+                }
+            }
+            Request::Logs { server, lines } => match runtime.tail_logs(&server, lines) {
+                Ok(lines) => Reply::Logs { lines },
+                Err(e) => error(e.to_string()),
+            },
+            Request::Shutdown => {
+                let servers: Vec<String> = lock(supervised).keys().cloned().collect();
+                let stopped = servers
+                    .into_iter()
+                    .filter(|server| runtime.stop(server).is_ok())
+                    .collect();
+                Reply::ShuttingDown { stopped }
+            }
+        }
+    }
+
+    /// Launches a server on its own supervision thread and waits for the
+    /// launch outcome.
+    fn launch(
+        runtime: &RuntimeManager,
+        supervised: &Supervised,
+        server: String,
+        spec: berth_runtime::ProcessSpec,
+    ) -> Reply {
+        let (sender, outcome) = mpsc::channel();
+        let runtime = runtime.clone();
+        let supervised = Arc::clone(supervised);
+        thread::spawn(move || {
+            let mut started = false;
+            let report = |result: Result<StartOutcome, String>| {
+                if matches!(result, Ok(StartOutcome::Started)) {
+                    started = true;
+                    *lock(&supervised).entry(server.clone()).or_default() += 1;
+                }
+                let _ = sender.send(result);
+            };
+            if let Err(e) = supervise::run(&runtime, &server, spec, true, report) {
+                eprintln!("{} {}: {e}", "✗".red().bold(), server.cyan());
+            }
+            if started {
+                let mut supervised = lock(&supervised);
+                if let Some(threads) = supervised.get_mut(&server) {
+                    *threads -= 1;
+                    if *threads == 0 {
+                        supervised.remove(&server);
+                    }
+                }
+            }
+        });
+        match outcome.recv() {
+            Ok(Ok(outcome)) => Reply::Started {
+                already_running: outcome == StartOutcome::AlreadyRunning,
+            },
+            Ok(Err(message)) => Reply::Error { message },
+            Err(_) => Reply::Error {
+                message: "supervision ended before launching the server".to_string(),
+            },
+        }
+    }
+
+    fn lock(supervised: &Supervised) -> std::sync::MutexGuard<'_, BTreeMap<String, usize>> {
+        supervised
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::server::bind_private;
+    use std::os::unix::fs::PermissionsExt;
+    use std::os::unix::net::UnixStream;
+
+    #[test]
+    fn control_socket_is_bound_private_and_moved_into_place() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join(super::SOCKET_FILE);
+        let _listener = bind_private(&path).unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert_eq!(std::fs::read_dir(tmp.path()).unwrap().count(), 1);
+        UnixStream::connect(&path).unwrap();
+    }
+}
@@ -0,0 +1,157 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Helpers for `berth.depends-on`, the servers a server needs running first.
+//!
+//! `berth start` without a server name starts dependencies before the servers
+//! that declare them, and `berth stop` without one stops in reverse order.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+
+use berth_registry::config::InstalledServer;
+
+use crate::paths;
+
+pub const KEY_DEPENDS_ON: &str = "berth.depends-on";
+
+/// Returns whether a key is reserved for dependency settings.
+pub fn is_dependency_key(key: &str) -> bool {
+    key == KEY_DEPENDS_ON
+}
+
+/// Validates one key/value pair for dependency settings.
+pub fn validate_dependency_value(server: &str, key: &str, value: &str) -> Result<(), String> {
+    if key != KEY_DEPENDS_ON {
+        return Err(format!("Unknown dependency key: {key}"));
+    }
+    for name in parse_list(value) {
+        if name == server {
+            return Err(format!("{server} cannot depend on itself."));
+        }
+        if name.contains(char::is_whitespace) || name.contains('/') {
+            return Err(format!(
+                "Invalid server name `{name}`. Expected a comma-separated list of installed server names."
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Returns the servers an installed server depends on.
+pub fn parse_depends_on(config: &BTreeMap<String, String>) -> Vec<String> {
+    config
+        .get(KEY_DEPENDS_ON)
+        .map(|value| parse_list(value))
+        .unwrap_or_default()
+}
+
+fn parse_list(value: &str) -> Vec<String> {
+    let mut seen = BTreeSet::new();
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty() && seen.insert(*name))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Reads the dependencies of each of `servers`; a config that cannot be read
+/// counts as having none, so its own start reports the problem.
+pub fn load_dependencies(servers: &[String]) -> BTreeMap<String, Vec<String>> {
+    servers
+        .iter()
+        .map(|name| {
+            let depends_on = paths::server_config_path(name)
+                .and_then(|path| fs::read_to_string(path).ok())
+                .and_then(|content| toml::from_str::<InstalledServer>(&content).ok())
+                .map(|installed| parse_depends_on(&installed.config))
+                .unwrap_or_default();
+            (name.clone(), depends_on)
+        })
+        .collect()
+}
+
+/// Orders servers so each comes after the servers it depends on.
+///
+/// Independent servers keep name order. Dependencies outside `graph` do not
+/// affect the order; a cycle is an error naming the servers on it.
+pub fn startup_order(graph: &BTreeMap<String, Vec<String>>) -> Result<Vec<String>, String> {
+    let mut pending: BTreeMap<&str, BTreeSet<&str>> = graph
+        .iter()
+        .map(|(name, depends_on)| {
+            let inside = depends_on
+                .iter()
+                .map(String::as_str)
+                .filter(|dep| graph.contains_key(*dep))
+                .collect();
+            (name.as_str(), inside)
+        })
+        .collect();
+    let mut order = Vec::with_capacity(graph.len());
+    while !pending.is_empty() {
+        let Some(next) = pending
+            .iter()
+            .find(|(_, deps)| deps.is_empty())
+            .map(|(name, _)| *name)
+        else {
+            let cycle: Vec<&str> = pending.keys().copied().collect();
+            return Err(format!(
+                "Cannot order servers by `{KEY_DEPENDS_ON}`; these are part of or wait on a cycle: {}.",
+                cycle.join(", ")
+            ));
+        };
+        pending.remove(next);
+        for deps in pending.values_mut() {
+            deps.remove(next);
+        }
+        order.push(next.to_string());
+    }
+    Ok(order)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph(entries: &[(&str, &[&str])]) -> BTreeMap<String, Vec<String>> {
+        entries
+            .iter()
+            .map(|(name, deps)| {
+                (
+                    name.to_string(),
+                    deps.iter().map(ToString::to_string).collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn orders_dependencies_first_and_keeps_name_order_otherwise() {
+        let order = startup_order(&graph(&[
+            ("api", &["db", "cache"]),
+            ("cache", &[]),
+            ("db", &["missing"]),
+            ("web", &["api"]),
+            ("docs", &[]),
+        ]))
+        .unwrap();
+        assert_eq!(order, ["cache", "db", "api", "docs", "web"]);
+    }
+
+    #[test]
+    fn reports_cycles() {
+        let err = startup_order(&graph(&[("a", &["b"]), ("b", &["a"]), ("c", &[])])).unwrap_err();
+        assert!(err.ends_with(": a, b."));
+    }
+
+    #[test]
+    fn parses_and_validates_lists() {
+        let cfg = BTreeMap::from([(KEY_DEPENDS_ON.to_string(), " db, cache,,db ".to_string())]);
+        assert_eq!(parse_depends_on(&cfg), ["db", "cache"]);
+        assert!(validate_dependency_value("api", KEY_DEPENDS_ON, "db,cache").is_ok());
+        assert!(validate_dependency_value("api", KEY_DEPENDS_ON, "").is_ok());
+        assert!(validate_dependency_value("api", KEY_DEPENDS_ON, "api").is_err());
+        assert!(validate_dependency_value("api", KEY_DEPENDS_ON, "my db").is_err());
+    }
+}
@@ -0,0 +1,175 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Install-time size estimates, the Berth home quota, and per-server usage.
+//!
+//! Only binaries are stored inside the Berth home; npm and PyPI packages live
+//! in the `npx`/`uvx` caches and count toward the quota only through config,
+//! receipts, logs and runtime state.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use berth_common::storage::{dir_size, format_bytes};
+use berth_registry::config::InstalledServer;
+use berth_registry::types::ServerMetadata;
+
+use crate::paths;
+use crate::wasm_runtime::WASM_RUNTIME;
+
+/// Expected cost of installing one server.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InstallEstimate {
+    /// Bytes fetched or copied; `None` when neither the artifact nor the
+    /// manifest tells.
+    pub download_bytes: Option<u64>,
+    /// Bytes the install adds below the Berth home.
+    pub home_bytes: u64,
+}
+
+impl InstallEstimate {
+    /// One-line summary for install output and dry-run plans.
+    pub fn describe(&self) -> String {
+        let download = match self.download_bytes {
+            Some(bytes) => format!("{} download", format_bytes(bytes)),
+            None => "download size unknown".to_string(),
+        };
+        format!(
+            "{download}, {} in Berth home",
+            format_bytes(self.home_bytes)
+        )
+    }
+}
+
+/// Estimates the size of installing `meta`.
+///
+/// Local and mirrored artifacts are measured; remote ones fall back to the
+/// sizes the manifest declares.
+pub fn estimate_install(meta: &ServerMetadata, mirrored: Option<&Path>) -> InstallEstimate {
+    let is_binary = matches!(meta.runtime.runtime_type.as_str(), "binary" | WASM_RUNTIME);
+    let package = &meta.source.package;
+    let local = match mirrored {
+        Some(artifact) => file_size(artifact),
+        None if is_binary && !is_url(package) => file_size(Path::new(
+            package.strip_prefix("file://").unwrap_or(package),
+        )),
+        None => None,
+    };
+    let download_bytes = local.or(meta.source.download_bytes);
+    let home_bytes = if is_binary {
+        local
+            .or(meta.source.installed_bytes)
+            .or(download_bytes)
+            .unwrap_or(0)
+    } else {
+        0
+    };
+    InstallEstimate {
+        download_bytes,
+        home_bytes,
+    }
+}
+
+/// Checks that adding `incoming` bytes keeps `home` within `quota_mb` MiB.
+pub fn check_quota(home: &Path, quota_mb: u64, incoming: u64) -> Result<(), String> {
+    let used = dir_size(home).map_err(|e| format!("Failed to measure {}: {e}", home.display()))?;
+    let quota = quota_mb.saturating_mul(1024 * 1024);
+    if used.saturating_add(incoming) > quota {
+        return Err(format!(
+            "Install needs {} but the Berth home quota is {} and {} is already used. Run `berth gc` or uninstall servers to free space.",
+            format_bytes(incoming),
+            format_bytes(quota),
+            format_bytes(used)
+        ));
+    }
+    Ok(())
+}
+
+/// Returns the bytes one server occupies below the Berth home: its config,
+/// permission overrides, receipts, egress log, installed binary, and the
+/// runtime state and logs accounted by `berth gc`.
+pub fn server_disk_usage(
+    name: &str,
+    installed: &InstalledServer,
+    artifacts: &BTreeMap<String, u64>,
+) -> u64 {
+    let mut total = artifacts.get(name).copied().unwrap_or(0);
+    let owned = [
+        paths::server_config_path(name),
+        paths::permissions_override_path(name),
+        paths::receipts_dir(name),
+        paths::egress_log_path(name),
+    ];
+    for path in owned.into_iter().flatten() {
+        total = total.saturating_add(dir_size(&path).unwrap_or(0));
+    }
+    if let Some(bin_dir) = paths::berth_bin_dir() {
+        let command = Path::new(&installed.runtime.command);
+        if command.starts_with(&bin_dir) {
+            total = total.saturating_add(dir_size(command).unwrap_or(0));
+        }
+    }
+    total
+}
+
+fn file_size(path: &Path) -> Option<u64> {
+    fs::metadata(path)
+        .ok()
+        .filter(|m| m.is_file())
+        .map(|m| m.len())
+}
+
+fn is_url(package: &str) -> bool {
+    package.starts_with("http://") || package.starts_with("https://")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use berth_registry::Registry;
+
+    #[test]
+    fn estimates_measure_local_binaries_and_trust_manifest_otherwise() {
+        let tmp = tempfile::tempdir().unwrap();
+        let binary = tmp.path().join("server-bin");
+        fs::write(&binary, [0u8; 2048]).unwrap();
+
+        let mut meta = Registry::from_seed().get("github").unwrap().clone();
+        meta.runtime.runtime_type = "binary".to_string();
+        meta.source.package = format!("file://{}", binary.display());
+        meta.source.download_bytes = Some(1);
+        let estimate = estimate_install(&meta, None);
+        assert_eq!(estimate.download_bytes, Some(2048));
+        assert_eq!(estimate.home_bytes, 2048);
+
+        meta.source.package = "https://example.com/server-bin".to_string();
+        meta.source.download_bytes = Some(1000);
+        meta.source.installed_bytes = Some(3000);
+        assert_eq!(
+            estimate_install(&meta, None),
+            InstallEstimate {
+                download_bytes: Some(1000),
+                home_bytes: 3000
+            }
+        );
+
+        let node = Registry::from_seed().get("github").unwrap().clone();
+        let estimate = estimate_install(&node, None);
+        assert_eq!(estimate.home_bytes, 0);
+        assert_eq!(
+            estimate.describe(),
+            "download size unknown, 0 B in Berth home"
+        );
+    }
+
+    #[test]
+    fn quota_counts_existing_usage() {
+        let tmp = tempfile::tempdir().unwrap();
+        fs::write(tmp.path().join("state"), vec![0u8; 512 * 1024]).unwrap();
+        check_quota(tmp.path(), 1, 256 * 1024).unwrap();
+        let err = check_quota(tmp.path(), 1, 768 * 1024).unwrap_err();
+        assert!(err.contains("quota is 1.0 MiB"), "{err}");
+        assert!(err.contains("512.0 KiB is already used"), "{err}");
+    }
+}
@@ -0,0 +1,171 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Rendering for `--dry-run` plans printed by mutating commands.
+
+use colored::Colorize;
+use std::path::Path;
+
+use berth_registry::config::PermissionsInfo;
+
+use crate::permission_filter::covers;
+use crate::progress::say;
+
+/// Ordered description of what a command would do for one server.
+pub struct DryRunPlan {
+    action: String,
+    server: String,
+    entries: Vec<(String, String)>,
+}
+
+impl DryRunPlan {
+    /// Creates an empty plan for an action on a server.
+    pub fn new(action: &str, server: &str) -> Self {
+        DryRunPlan {
+            action: action.to_string(),
+            server: server.to_string(),
+            entries: Vec::new(),
+        }
+    }
+
+    /// Appends one labeled line to the plan.
+    pub fn add(&mut self, label: &str, value: impl Into<String>) {
+        self.entries.push((label.to_string(), value.into()));
+    }
+
+    /// Appends a file that would be created or modified.
+    pub fn add_write(&mut self, path: &Path) {
+        self.add("write", path.display().to_string());
+    }
+
+    /// Appends declared permission lines in a stable order.
+    pub fn add_permissions(&mut self, permissions: &PermissionsInfo) {
+        for (kind, values) in [
+            ("network", &permissions.network),
+            ("env", &permissions.env),
+            ("filesystem", &permissions.filesystem),
+            ("exec", &permissions.exec),
+        ] {
+            self.add(&format!("permission {kind}"), join_or_none(values));
+        }
+        for (tool, needs) in &permissions.tools {
+            self.add(&format!("tool {tool} needs"), join_or_none(needs));
+        }
+    }
+
+    /// Appends permission changes between two declarations.
+    ///
+    /// Entries covered by a broader entry on the other side (e.g.
+    /// `api.github.com:443` under `*.github.com:443`) are not reported.
+    pub fn add_permission_changes(&mut self, before: &PermissionsInfo, after: &PermissionsInfo) {
+        let mut changes = Vec::new();
+        for (kind, old, new) in [
+            ("network", &before.network, &after.network),
+            ("env", &before.env, &after.env),
+            ("filesystem", &before.filesystem, &after.filesystem),
+            ("exec", &before.exec, &after.exec),
+        ] {
+            let uncovered = |entries: &[String], by: &[String]| -> Vec<String> {
+                entries
+                    .iter()
+                    .filter(|entry| {
+                        !by.iter()
+                            .any(|other| other == *entry || covers(kind, other, entry))
+                    })
+                    .map(|entry| format!("{kind}:{entry}"))
+                    .collect()
+            };
+            changes.extend(uncovered(new, old).into_iter().map(|e| format!("+{e}")));
+            changes.extend(uncovered(old, new).into_iter().map(|e| format!("-{e}")));
+        }
+        self.add("permission changes", join_or_none(&changes));
+    }
+
+    /// Returns the plan's `label: value` lines in insertion order.
+    pub fn lines(&self) -> Vec<String> {
+        self.entries
+            .iter()
+            .map(|(label, value)| format!("{label}: {value}"))
+            .collect()
+    }
+
+    /// Prints the plan to stdout.
+    pub fn print(&self) {
+        say!(
+            "{} Dry run: {} {}",
+            "•".dimmed(),
+            self.action.bold(),
+            self.server.cyan()
+        );
+        for line in self.lines() {
+            say!("  {line}");
+        }
+    }
+}
+
+/// Joins values with `, `, or returns `(none)` for an empty list.
+pub fn join_or_none(values: &[String]) -> String {
+    if values.is_empty() {
+        "(none)".to_string()
+    } else {
+        values.join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lines_list_entries_in_insertion_order() {
+        let mut plan = DryRunPlan::new("start", "github");
+        plan.add("command", "npx -y pkg");
+        plan.add_write(Path::new("/tmp/state.toml"));
+        assert_eq!(
+            plan.lines(),
+            vec![
+                "command: npx -y pkg".to_string(),
+                "write: /tmp/state.toml".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn add_permissions_marks_empty_lists() {
+        let mut plan = DryRunPlan::new("install", "x");
+        plan.add_permissions(&PermissionsInfo {
+            network: vec!["api.github.com:443".to_string()],
+            env: vec![],
+            filesystem: vec![],
+            exec: vec![],
+            ..Default::default()
+        });
+        let lines = plan.lines();
+        assert!(lines.contains(&"permission network: api.github.com:443".to_string()));
+        assert!(lines.contains(&"permission env: (none)".to_string()));
+    }
+
+    #[test]
+    fn add_permission_changes_ignores_entries_covered_by_wildcards() {
+        let before = PermissionsInfo {
+            network: vec!["api.github.com:443".to_string()],
+            env: vec!["GITHUB_TOKEN".to_string()],
+            filesystem: vec!["read:/workspace".to_string()],
+            exec: vec![],
+            ..Default::default()
+        };
+        let after = PermissionsInfo {
+            network: vec!["*.github.com:443".to_string()],
+            env: vec!["GITHUB_TOKEN".to_string()],
+            filesystem: vec!["read:/workspace/docs/**".to_string()],
+            exec: vec![],
+            ..Default::default()
+        };
+        let mut plan = DryRunPlan::new("update", "x");
+        plan.add_permission_changes(&before, &after);
+        assert_eq!(
+            plan.lines(),
+            vec!["permission changes: +network:*.github.com:443, -filesystem:read:/workspace"]
+        );
+    }
+}
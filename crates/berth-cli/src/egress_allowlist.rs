@@ -0,0 +1,434 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Enforces a server's network permissions with `berth.sandbox-network=allowlist`.
+//!
+//! Two layers keep a server to the hosts its effective `network:` permissions
+//! name. The egress hooks that `berth.egress-monitor` installs switch to
+//! refusing other connections in node and python servers. Every server also
+//! gets `HTTP_PROXY`/`HTTPS_PROXY` pointing at an allow-listing proxy, hosted
+//! by the process that owns the server: the supervisor for `berth start`, the
+//! session for `berth proxy` and `berth wrap`. Refused attempts are written to
+//! the egress log with `"blocked":true`.
+//!
+//! Both layers are cooperative. Unless bubblewrap runs the server without a
+//! network (an empty allow-list), a server that ignores the proxy variables
+//! and opens its own sockets reaches any host, and Berth warns about that at
+//! launch.
+
+use berth_common::jsonl;
+use berth_registry::permissions::NetworkPermission;
+use berth_runtime::ProcessSpec;
+use colored::Colorize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{self, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::egress_monitor::{apply_egress_monitor, EgressEvent, EGRESS_LOG_ENV};
+use crate::time_format::now_epoch_ms;
+use crate::warnings;
+
+/// Env var carrying the allowed `host:port` patterns, comma separated.
+pub const EGRESS_ALLOW_ENV: &str = "BERTH_EGRESS_ALLOW";
+
+/// Env var carrying the proxy address, which the hooks always let through.
+pub const EGRESS_PROXY_ENV: &str = "BERTH_EGRESS_PROXY";
+
+/// Proxy variables set for the server, in the spellings runtimes look for.
+const PROXY_VARS: [&str; 6] = [
+    "HTTP_PROXY",
+    "HTTPS_PROXY",
+    "ALL_PROXY",
+    "http_proxy",
+    "https_proxy",
+    "all_proxy",
+];
+
+/// Largest request head the proxy reads before giving up on a client.
+const MAX_HEAD_BYTES: usize = 64 * 1024;
+
+/// How long a client may take to send its request head.
+const HEAD_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Installs the enforcing egress hooks and records the allow-list in `env`.
+///
+/// `allowed` are the effective `network:` permissions without their prefix.
+pub fn apply_egress_allowlist(
+    server: &str,
+    env: &mut BTreeMap<String, String>,
+    clean_env: bool,
+    allowed: &[String],
+) -> Result<(), String> {
+    apply_egress_monitor(server, env, clean_env)?;
+    env.insert(EGRESS_ALLOW_ENV.to_string(), allowed.join(","));
+    Ok(())
+}
+
+/// Returns whether a launch with an allow-list keeps to it only if the server
+/// honors the proxy variables: always, unless bubblewrap unshares the network.
+pub fn egress_is_cooperative(spec: &ProcessSpec) -> bool {
+    spec.env.contains_key(EGRESS_ALLOW_ENV) && !spec.args.iter().any(|arg| arg == "--unshare-net")
+}
+
+/// Warns on stderr that a server's allow-list is not enforced by the kernel.
+pub fn warn_cooperative_egress(server: &str, spec: &ProcessSpec) {
+    if egress_is_cooperative(spec) {
+        warnings::emit(
+            warnings::UNENFORCED_EGRESS,
+            Some(server),
+            &format!(
+                "{} keeps to its egress allow-list only if it honors HTTP(S)_PROXY; a server that opens its own sockets has unrestricted egress.",
+                server.cyan()
+            ),
+        );
+    }
+}
+
+/// Starts a proxy for a spec with an allow-list and points `env` at it.
+pub fn route_through_proxy(env: &mut BTreeMap<String, String>) -> Result<(), String> {
+    if let Some(proxy) = EgressProxy::start(env)? {
+        proxy.apply(env);
+    }
+    Ok(())
+}
+
+/// A running allow-listing HTTP(S) proxy for one server.
+pub struct EgressProxy {
+    addr: SocketAddr,
+}
+
+impl EgressProxy {
+    /// Starts the proxy when `env` carries an allow-list; `None` otherwise.
+    ///
+    /// The proxy lives until this process exits.
+    pub fn start(env: &BTreeMap<String, String>) -> Result<Option<Self>, String> {
+        let Some(allow) = env.get(EGRESS_ALLOW_ENV) else {
+            return Ok(None);
+        };
+        let allowed: Vec<NetworkPermission> = allow
+            .split(',')
+            .filter_map(|entry| entry.trim().parse().ok())
+            .collect();
+        let log = env.get(EGRESS_LOG_ENV).map(PathBuf::from);
+        let listener = TcpListener::bind(("127.0.0.1", 0))
+            .map_err(|e| format!("Failed to start egress proxy: {e}"))?;
+        let addr = listener
+            .local_addr()
+            .map_err(|e| format!("Failed to start egress proxy: {e}"))?;
+        let shared = Arc::new(Rules {
+            allowed,
+            log,
+            logged: Mutex::new(BTreeSet::new()),
+        });
+        thread::spawn(move || {
+            for client in listener.incoming().flatten() {
+                let rules = Arc::clone(&shared);
+                thread::spawn(move || {
+                    let _ = rules.handle(client);
+                });
+            }
+        });
+        Ok(Some(EgressProxy { addr }))
+    }
+
+    /// Points the server's proxy variables at this proxy.
+    ///
+    /// `NO_PROXY` is cleared so no destination bypasses the proxy by name.
+    pub fn apply(&self, env: &mut BTreeMap<String, String>) {
+        let url = format!("http://{}", self.addr);
+        for var in PROXY_VARS {
+            env.insert(var.to_string(), url.clone());
+        }
+        env.insert("NO_PROXY".to_string(), String::new());
+        env.insert("no_proxy".to_string(), String::new());
+        env.insert(EGRESS_PROXY_ENV.to_string(), self.addr.to_string());
+    }
+}
+
+struct Rules {
+    allowed: Vec<NetworkPermission>,
+    log: Option<PathBuf>,
+    /// Destinations already written to the log by this proxy.
+    logged: Mutex<BTreeSet<(String, u16, bool)>>,
+}
+
+impl Rules {
+    fn handle(&self, mut client: TcpStream) -> io::Result<()> {
+        client.set_read_timeout(Some(HEAD_TIMEOUT))?;
+        let (head, rest) = read_head(&mut client)?;
+        let Some(target) = Target::parse(&head) else {
+            return respond(
+                &mut client,
+                "400 Bad Request",
+                "Berth egress proxy: malformed request.",
+            );
+        };
+        let allowed = self
+            .allowed
+            .iter()
+            .any(|rule| rule.matches(&target.host, target.port));
+        self.record(&target, !allowed);
+        if !allowed {
+            return respond(
+                &mut client,
+                "403 Forbidden",
+                &format!(
+                    "Berth egress proxy: {}:{} is not in the server's network permissions.",
+                    target.host, target.port
+                ),
+            );
+        }
+        let mut upstream = match TcpStream::connect((target.host.as_str(), target.port)) {
+            Ok(upstream) => upstream,
+            Err(e) => {
+                return respond(
+                    &mut client,
+                    "502 Bad Gateway",
+                    &format!("Berth egress proxy: {e}"),
+                )
+            }
+        };
+        match &target.forward {
+            None => client.write_all(b"HTTP/1.1 200 Connection Established\r\n\r\n")?,
+            Some(head) => upstream.write_all(head)?,
+        }
+        upstream.write_all(&rest)?;
+        client.set_read_timeout(None)?;
+        pipe(client, upstream)
+    }
+
+    fn record(&self, target: &Target, blocked: bool) {
+        let Some(log) = &self.log else {
+            return;
+        };
+        let key = (target.host.clone(), target.port, blocked);
+        if !self
+            .logged
+            .lock()
+            .is_ok_and(|mut logged| logged.insert(key))
+        {
+            return;
+        }
+        let event = EgressEvent {
+            ts: now_epoch_ms(),
+            host: target.host.clone(),
+            port: Some(target.port),
+            blocked,
+        };
+        let _ = jsonl::append(log, &event);
+    }
+}
+
+/// Destination of one proxied request.
+#[derive(Debug, PartialEq, Eq)]
+struct Target {
+    host: String,
+    port: u16,
+    /// Head to send upstream for plain HTTP; `None` for a `CONNECT` tunnel.
+    forward: Option<Vec<u8>>,
+}
+
+impl Target {
+    /// Parses a `CONNECT host:port` or absolute-form `GET http://host/...` head.
+    fn parse(head: &[u8]) -> Option<Self> {
+        let text = std::str::from_utf8(head).ok()?;
+        let (request_line, headers) = text.split_once("\r\n")?;
+        let mut parts = request_line.split(' ');
+        let (method, uri, version) = (parts.next()?, parts.next()?, parts.next()?);
+        if method.eq_ignore_ascii_case("CONNECT") {
+            let (host, port) = split_authority(uri, None)?;
+            return Some(Target {
+                host,
+                port,
+                forward: None,
+            });
+        }
+        let rest = uri.strip_prefix("http://")?;
+        let (authority, path) = match rest.find('/') {
+            Some(i) => rest.split_at(i),
+            None => (rest, "/"),
+        };
+        let (host, port) = split_authority(authority, Some(80))?;
+        let forward = format!("{method} {path} {version}\r\n{headers}").into_bytes();
+        Some(Target {
+            host,
+            port,
+            forward: Some(forward),
+        })
+    }
+}
+
+/// Splits `host:port` or `[v6]:port`, falling back to `default` without a port.
+fn split_authority(authority: &str, default: Option<u16>) -> Option<(String, u16)> {
+    let authority = authority.rsplit_once('@').map_or(authority, |(_, a)| a);
+    let (host, port) = match authority.strip_prefix('[') {
+        Some(rest) => {
+            let (host, port) = rest.split_once(']')?;
+            (host, port.strip_prefix(':'))
+        }
+        None => match authority.rsplit_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (authority, None),
+        },
+    };
+    let port = match port {
+        Some(port) => port.parse().ok()?,
+        None => default?,
+    };
+    (!host.is_empty()).then(|| (host.to_ascii_lowercase(), port))
+}
+
+/// Reads up to the end of the request head; returns it and any bytes after it.
+fn read_head(client: &mut TcpStream) -> io::Result<(Vec<u8>, Vec<u8>)> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    loop {
+        if let Some(end) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            let rest = buf.split_off(end + 4);
+            return Ok((buf, rest));
+        }
+        if buf.len() > MAX_HEAD_BYTES {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "request head too large",
+            ));
+        }
+        let read = client.read(&mut chunk)?;
+        if read == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        buf.extend_from_slice(&chunk[..read]);
+    }
+}
+
+fn respond(client: &mut TcpStream, status: &str, body: &str) -> io::Result<()> {
+    write!(
+        client,
+        "HTTP/1.1 {status}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )
+}
+
+/// Copies bytes both ways until either side closes.
+fn pipe(client: TcpStream, upstream: TcpStream) -> io::Result<()> {
+    let (mut client_read, mut upstream_write) = (client.try_clone()?, upstream.try_clone()?);
+    let outbound = thread::spawn(move || {
+        let _ = io::copy(&mut client_read, &mut upstream_write);
+        let _ = upstream_write.shutdown(Shutdown::Write);
+    });
+    let (mut upstream_read, mut client_write) = (upstream, client);
+    let _ = io::copy(&mut upstream_read, &mut client_write);
+    let _ = client_write.shutdown(Shutdown::Write);
+    let _ = outbound.join();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::BufRead;
+
+    #[test]
+    fn parses_connect_and_absolute_form_requests() {
+        let connect =
+            Target::parse(b"CONNECT api.github.com:443 HTTP/1.1\r\nHost: x\r\n\r\n").unwrap();
+        assert_eq!(
+            (connect.host.as_str(), connect.port),
+            ("api.github.com", 443)
+        );
+        assert!(connect.forward.is_none());
+
+        let get =
+            Target::parse(b"GET http://Example.com/a?b HTTP/1.1\r\nHost: example.com\r\n\r\n")
+                .unwrap();
+        assert_eq!((get.host.as_str(), get.port), ("example.com", 80));
+        assert_eq!(
+            get.forward.unwrap(),
+            b"GET /a?b HTTP/1.1\r\nHost: example.com\r\n\r\n".to_vec()
+        );
+
+        let v6 = Target::parse(b"CONNECT [::1]:8443 HTTP/1.1\r\n\r\n").unwrap();
+        assert_eq!((v6.host.as_str(), v6.port), ("::1", 8443));
+        assert!(Target::parse(b"GET /relative HTTP/1.1\r\n\r\n").is_none());
+    }
+
+    #[test]
+    fn only_an_unshared_network_makes_egress_enforced() {
+        let mut spec = ProcessSpec {
+            command: "node".to_string(),
+            ..ProcessSpec::default()
+        };
+        assert!(!egress_is_cooperative(&spec));
+
+        spec.env.insert(
+            EGRESS_ALLOW_ENV.to_string(),
+            "api.github.com:443".to_string(),
+        );
+        assert!(egress_is_cooperative(&spec));
+
+        spec.env.insert(EGRESS_ALLOW_ENV.to_string(), String::new());
+        assert!(egress_is_cooperative(&spec));
+        spec.command = "bwrap".to_string();
+        spec.args = vec!["--unshare-net".to_string(), "node".to_string()];
+        assert!(!egress_is_cooperative(&spec));
+    }
+
+    #[test]
+    fn proxy_tunnels_allowed_hosts_and_refuses_others() {
+        let upstream = TcpListener::bind("127.0.0.1:0").unwrap();
+        let upstream_port = upstream.local_addr().unwrap().port();
+        thread::spawn(move || {
+            let (mut conn, _) = upstream.accept().unwrap();
+            let mut buf = [0u8; 5];
+            conn.read_exact(&mut buf).unwrap();
+            conn.write_all(&buf).unwrap();
+        });
+        let tmp = tempfile::tempdir().unwrap();
+        let log = tmp.path().join("github.jsonl");
+        let env = BTreeMap::from([
+            (
+                EGRESS_ALLOW_ENV.to_string(),
+                format!("127.0.0.1:{upstream_port},api.github.com:443"),
+            ),
+            (EGRESS_LOG_ENV.to_string(), log.display().to_string()),
+        ]);
+        let proxy = EgressProxy::start(&env).unwrap().unwrap();
+        let mut server_env = BTreeMap::new();
+        proxy.apply(&mut server_env);
+        assert_eq!(server_env["HTTPS_PROXY"], format!("http://{}", proxy.addr));
+
+        let mut tunnel = TcpStream::connect(proxy.addr).unwrap();
+        write!(
+            tunnel,
+            "CONNECT 127.0.0.1:{upstream_port} HTTP/1.1\r\n\r\nhello"
+        )
+        .unwrap();
+        let mut reader = io::BufReader::new(tunnel.try_clone().unwrap());
+        let mut status = String::new();
+        reader.read_line(&mut status).unwrap();
+        assert!(status.starts_with("HTTP/1.1 200"), "{status}");
+        let mut blank = String::new();
+        reader.read_line(&mut blank).unwrap();
+        let mut echoed = [0u8; 5];
+        reader.read_exact(&mut echoed).unwrap();
+        assert_eq!(&echoed, b"hello");
+
+        let mut refused = TcpStream::connect(proxy.addr).unwrap();
+        refused
+            .write_all(b"CONNECT exfil.invalid:443 HTTP/1.1\r\n\r\n")
+            .unwrap();
+        let mut response = String::new();
+        refused.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 403"), "{response}");
+
+        let events: Vec<EgressEvent> = jsonl::read(&log).unwrap();
+        assert!(events
+            .iter()
+            .any(|e| e.host == "exfil.invalid" && e.blocked));
+        assert!(events.iter().any(|e| e.host == "127.0.0.1" && !e.blocked));
+    }
+}
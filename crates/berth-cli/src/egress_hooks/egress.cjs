@@ -0,0 +1,110 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+// Preloaded by Berth with `--require` when `berth.egress-monitor=log` is set.
+// Records each host/port a node server connects to, once per process. With
+// `berth.sandbox-network=allowlist`, connections outside BERTH_EGRESS_ALLOW are
+// refused and recorded as blocked.
+"use strict";
+
+const fs = require("fs");
+const net = require("net");
+
+const logPath = process.env.BERTH_EGRESS_LOG;
+const allowList = process.env.BERTH_EGRESS_ALLOW;
+const proxyAddr = process.env.BERTH_EGRESS_PROXY;
+const seen = new Set();
+
+// Parses `host:port` patterns: `*`, `api.example.com:443`, `*.example.com:*`,
+// `10.0.0.0/8:5432`, `[::1]:443`.
+function parseRule(entry) {
+  entry = entry.trim();
+  if (entry === "*") {
+    return { any: true };
+  }
+  const split = entry.lastIndexOf(":");
+  if (split <= 0) {
+    return null;
+  }
+  let host = entry.slice(0, split).toLowerCase();
+  const port = entry.slice(split + 1);
+  if (host.startsWith("[") && host.endsWith("]")) {
+    host = host.slice(1, -1);
+  }
+  const rule = { host: host, port: port === "*" ? null : Number(port) };
+  const slash = host.indexOf("/");
+  if (slash > 0) {
+    const addr = host.slice(0, slash);
+    const type = net.isIPv6(addr) ? "ipv6" : "ipv4";
+    rule.cidr = new net.BlockList();
+    rule.cidr.addSubnet(addr, Number(host.slice(slash + 1)), type);
+  }
+  return rule;
+}
+
+const rules = allowList === undefined ? null : allowList.split(",").map(parseRule).filter(Boolean);
+
+function hostMatches(rule, host) {
+  if (rule.cidr) {
+    const type = net.isIP(host);
+    return type !== 0 && rule.cidr.check(host, type === 6 ? "ipv6" : "ipv4");
+  }
+  if (rule.host === "*") {
+    return true;
+  }
+  if (rule.host.startsWith("*.")) {
+    return host.endsWith(rule.host.slice(1));
+  }
+  return host === rule.host;
+}
+
+function allowed(host, port) {
+  if (rules === null || `${host}:${port}` === proxyAddr) {
+    return true;
+  }
+  host = String(host).toLowerCase();
+  return rules.some(
+    (rule) => rule.any || ((rule.port === null || rule.port === port) && hostMatches(rule, host))
+  );
+}
+
+function record(host, port, blocked) {
+  const key = `${host}:${port}:${blocked}`;
+  if (!logPath || seen.has(key)) {
+    return;
+  }
+  seen.add(key);
+  const event = { ts: Date.now(), host: String(host), port: port };
+  if (blocked) {
+    event.blocked = true;
+  }
+  const line = JSON.stringify(event);
+  try {
+    fs.appendFileSync(logPath, line + "\n");
+  } catch (_) {
+    // Monitoring must never break the server.
+  }
+}
+
+const connect = net.Socket.prototype.connect;
+net.Socket.prototype.connect = function (...args) {
+  let options = Array.isArray(args[0]) ? args[0][0] : args[0];
+  if (typeof options !== "object" || options === null) {
+    options = { port: options, host: typeof args[1] === "string" ? args[1] : undefined };
+  }
+  const port = Number(options.port);
+  if (options.path === undefined && Number.isInteger(port) && port > 0) {
+    const host = options.host || "localhost";
+    const permitted = allowed(host, port);
+    record(host, port, !permitted);
+    if (!permitted) {
+      const err = new Error(
+        `connect EACCES ${host}:${port} (not in the server's network permissions)`
+      );
+      err.code = "EACCES";
+      process.nextTick(() => this.destroy(err));
+      return this;
+    }
+  }
+  return connect.apply(this, args);
+};
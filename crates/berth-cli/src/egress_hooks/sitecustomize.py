@@ -0,0 +1,119 @@
+# SPDX-License-Identifier: Apache-2.0
+# Copyright (c) 2026 Schwimmbeck Dominik
+
+# Put on PYTHONPATH by Berth when `berth.egress-monitor=log` is set.
+# Records each host/port a python server resolves, once per process. With
+# `berth.sandbox-network=allowlist`, hosts outside BERTH_EGRESS_ALLOW fail to
+# resolve with PermissionError and are recorded as blocked.
+
+import errno
+import ipaddress
+import json
+import os
+import socket
+import time
+
+_log_path = os.environ.get("BERTH_EGRESS_LOG")
+_allow_list = os.environ.get("BERTH_EGRESS_ALLOW")
+_proxy_addr = os.environ.get("BERTH_EGRESS_PROXY")
+_seen = set()
+_getaddrinfo = socket.getaddrinfo
+
+
+def _port(port):
+    if isinstance(port, int):
+        return port
+    if isinstance(port, (str, bytes)):
+        port = port.decode() if isinstance(port, bytes) else port
+        if port.isdigit():
+            return int(port)
+        try:
+            return socket.getservbyname(port)
+        except OSError:
+            return None
+    return None
+
+
+def _parse_rule(entry):
+    """Parses `*`, `host:port`, `*.suffix:port`, `[v6]:port`, or `cidr:port`."""
+    entry = entry.strip()
+    if entry == "*":
+        return ("*", None)
+    host, sep, port = entry.rpartition(":")
+    if not sep or not host:
+        return None
+    host = host.lower()
+    if host.startswith("[") and host.endswith("]"):
+        host = host[1:-1]
+    if "/" in host:
+        try:
+            host = ipaddress.ip_network(host, strict=False)
+        except ValueError:
+            return None
+    if port == "*":
+        return (host, None)
+    return (host, int(port)) if port.isdigit() else None
+
+
+_rules = None
+if _allow_list is not None:
+    _rules = [r for r in map(_parse_rule, _allow_list.split(",")) if r is not None]
+
+
+def _host_matches(pattern, host):
+    if isinstance(pattern, (ipaddress.IPv4Network, ipaddress.IPv6Network)):
+        try:
+            return ipaddress.ip_address(host) in pattern
+        except ValueError:
+            return False
+    if pattern == "*":
+        return True
+    if pattern.startswith("*."):
+        return host.endswith(pattern[1:])
+    return host == pattern
+
+
+def _allowed(host, port):
+    if _rules is None or "%s:%s" % (host, port) == _proxy_addr:
+        return True
+    host = host.lower()
+    return any(
+        (rule_port is None or rule_port == port) and _host_matches(pattern, host)
+        for pattern, rule_port in _rules
+    )
+
+
+def _record(host, port, blocked=False):
+    if not _log_path:
+        return
+    if (host, port, blocked) in _seen:
+        return
+    _seen.add((host, port, blocked))
+    event = {"ts": int(time.time() * 1000), "host": host, "port": port}
+    if blocked:
+        event["blocked"] = True
+    line = json.dumps(event, separators=(",", ":"))
+    try:
+        with open(_log_path, "a") as log:
+            log.write(line + "\n")
+    except OSError:
+        # Monitoring must never break the server.
+        pass
+
+
+def getaddrinfo(host, port, *args, **kwargs):
+    if host is None:
+        return _getaddrinfo(host, port, *args, **kwargs)
+    name = host.decode() if isinstance(host, bytes) else str(host)
+    number = _port(port)
+    if not _allowed(name, number):
+        _record(name, number, blocked=True)
+        raise PermissionError(
+            errno.EACCES,
+            "%s:%s is not in the server's network permissions" % (name, number),
+        )
+    _record(name, number)
+    return _getaddrinfo(host, port, *args, **kwargs)
+
+
+socket.getaddrinfo = getaddrinfo
@@ -0,0 +1,242 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Observes which hosts a server contacts, without blocking anything.
+//!
+//! With `berth.egress-monitor=log`, node servers preload a hook through
+//! `NODE_OPTIONS` and python servers import one as `sitecustomize`; both append
+//! each new host/port to `<state>/egress/<server>.jsonl`. `berth permissions
+//! <server> --usage` reads that log back and flags undeclared destinations.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use berth_common::jsonl;
+use serde::{Deserialize, Serialize};
+
+use crate::paths;
+use crate::permission_filter::covers;
+
+pub const KEY_EGRESS_MONITOR: &str = "berth.egress-monitor";
+
+/// Env var the hooks read the log path from.
+pub const EGRESS_LOG_ENV: &str = "BERTH_EGRESS_LOG";
+
+const NODE_HOOK: &str = include_str!("egress_hooks/egress.cjs");
+const PYTHON_HOOK: &str = include_str!("egress_hooks/sitecustomize.py");
+
+/// Returns whether a key is reserved for egress monitoring settings.
+pub fn is_egress_monitor_key(key: &str) -> bool {
+    key == KEY_EGRESS_MONITOR
+}
+
+/// Validates one key/value pair for egress monitoring settings.
+pub fn validate_egress_monitor_value(key: &str, value: &str) -> Result<(), String> {
+    match key {
+        KEY_EGRESS_MONITOR => parse_mode(value).map(|_| ()),
+        _ => Err(format!("Unknown egress monitor key: {key}")),
+    }
+}
+
+/// Parses whether egress monitoring is enabled; it is off by default.
+pub fn parse_egress_monitor(config: &BTreeMap<String, String>) -> Result<bool, String> {
+    match config.get(KEY_EGRESS_MONITOR) {
+        Some(v) => parse_mode(v),
+        None => Ok(false),
+    }
+}
+
+fn parse_mode(value: &str) -> Result<bool, String> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "log" => Ok(true),
+        "off" => Ok(false),
+        _ => Err(format!("Invalid value `{value}`. Expected `log` or `off`.")),
+    }
+}
+
+/// Writes the hooks and points a server's launch env at them.
+///
+/// Existing `NODE_OPTIONS` and `PYTHONPATH` values are kept; parent values are
+/// only merged in when the child inherits the parent environment anyway.
+pub fn apply_egress_monitor(
+    server: &str,
+    env: &mut BTreeMap<String, String>,
+    clean_env: bool,
+) -> Result<(), String> {
+    let (Some(hooks), Some(log)) = (paths::egress_hooks_dir(), paths::egress_log_path(server))
+    else {
+        return Err("Could not determine home directory.".to_string());
+    };
+    let node_hook = hooks.join("node").join("egress.cjs");
+    let python_dir = hooks.join("python");
+    write_if_changed(&node_hook, NODE_HOOK)?;
+    write_if_changed(&python_dir.join("sitecustomize.py"), PYTHON_HOOK)?;
+    if let Some(dir) = log.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create egress log dir: {e}"))?;
+    }
+
+    let inherited = |key: &str| {
+        env.get(key)
+            .cloned()
+            .or_else(|| (!clean_env).then(|| std::env::var(key).ok()).flatten())
+            .filter(|v| !v.is_empty())
+    };
+    let require = format!("--require \"{}\"", node_hook.display());
+    let node_options = match inherited("NODE_OPTIONS") {
+        Some(existing) => format!("{existing} {require}"),
+        None => require,
+    };
+    let python_path = match inherited("PYTHONPATH") {
+        Some(existing) => std::env::join_paths([python_dir.clone(), PathBuf::from(existing)])
+            .map_err(|e| format!("Invalid PYTHONPATH: {e}"))?
+            .to_string_lossy()
+            .into_owned(),
+        None => python_dir.display().to_string(),
+    };
+    env.insert("NODE_OPTIONS".to_string(), node_options);
+    env.insert("PYTHONPATH".to_string(), python_path);
+    env.insert(EGRESS_LOG_ENV.to_string(), log.display().to_string());
+    Ok(())
+}
+
+fn write_if_changed(path: &Path, content: &str) -> Result<(), String> {
+    if fs::read_to_string(path).is_ok_and(|current| current == content) {
+        return Ok(());
+    }
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create hook dir: {e}"))?;
+    }
+    fs::write(path, content).map_err(|e| format!("Failed to write {}: {e}", path.display()))
+}
+
+/// One line of the egress log.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EgressEvent {
+    pub ts: u64,
+    pub host: String,
+    pub port: Option<u16>,
+    /// Set when `berth.sandbox-network=allowlist` refused the connection.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub blocked: bool,
+}
+
+/// A destination a server contacted, aggregated over all its launches.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Destination {
+    pub host: String,
+    pub port: Option<u16>,
+    pub first_seen_ms: u64,
+    pub last_seen_ms: u64,
+    /// Whether an effective `network:` permission covers it.
+    pub declared: bool,
+    /// Whether the allow-list refused at least one attempt.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub blocked: bool,
+}
+
+impl Destination {
+    /// Renders the destination as a `network:` permission value.
+    pub fn permission(&self) -> String {
+        match self.port {
+            Some(port) => format!("{}:{port}", self.host),
+            None => format!("{}:*", self.host),
+        }
+    }
+}
+
+/// Reads a server's egress log and checks each destination against its
+/// effective network permissions.
+///
+/// Loopback and wildcard bind addresses are left out; malformed lines are
+/// skipped so a partly written line cannot hide the rest.
+pub fn read_usage(path: &Path, effective_network: &[String]) -> Result<Vec<Destination>, String> {
+    let events: Vec<EgressEvent> =
+        jsonl::read(path).map_err(|e| format!("Failed to read egress log: {e}"))?;
+    let mut seen: BTreeMap<(String, Option<u16>), (u64, u64, bool)> = BTreeMap::new();
+    for event in events {
+        if is_local(&event.host) {
+            continue;
+        }
+        let host = event.host.trim_end_matches('.').to_ascii_lowercase();
+        let entry = seen
+            .entry((host, event.port))
+            .or_insert((event.ts, event.ts, false));
+        entry.0 = entry.0.min(event.ts);
+        entry.1 = entry.1.max(event.ts);
+        entry.2 |= event.blocked;
+    }
+    Ok(seen
+        .into_iter()
+        .map(|((host, port), (first_seen_ms, last_seen_ms, blocked))| {
+            let mut dest = Destination {
+                host,
+                port,
+                first_seen_ms,
+                last_seen_ms,
+                declared: false,
+                blocked,
+            };
+            dest.declared = is_declared(&dest.permission(), effective_network);
+            dest
+        })
+        .collect())
+}
+
+fn is_declared(destination: &str, effective_network: &[String]) -> bool {
+    effective_network.iter().any(|pattern| {
+        pattern == "*" || pattern == destination || covers("network", pattern, destination)
+    })
+}
+
+fn is_local(host: &str) -> bool {
+    matches!(host, "" | "localhost" | "::1" | "0.0.0.0" | "::") || host.starts_with("127.")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_egress_monitor_defaults_to_off() {
+        assert!(!parse_egress_monitor(&BTreeMap::new()).unwrap());
+        let cfg = BTreeMap::from([(KEY_EGRESS_MONITOR.to_string(), "log".to_string())]);
+        assert!(parse_egress_monitor(&cfg).unwrap());
+        assert!(validate_egress_monitor_value(KEY_EGRESS_MONITOR, "block").is_err());
+    }
+
+    #[test]
+    fn read_usage_flags_undeclared_destinations() {
+        let tmp = tempfile::tempdir().unwrap();
+        let log = tmp.path().join("github.jsonl");
+        fs::write(
+            &log,
+            [
+                r#"{"ts":10,"host":"api.github.com","port":443}"#,
+                r#"{"ts":30,"host":"API.github.com.","port":443}"#,
+                r#"{"ts":20,"host":"evil.example","port":443,"blocked":true}"#,
+                r#"{"ts":25,"host":"127.0.0.1","port":8080}"#,
+                r#"{"ts":26,"host":"truncated"#,
+            ]
+            .join("\n"),
+        )
+        .unwrap();
+        let usage = read_usage(&log, &["api.github.com:443".to_string()]).unwrap();
+        assert_eq!(usage.len(), 2);
+        assert_eq!(usage[0].host, "api.github.com");
+        assert!(usage[0].declared);
+        assert_eq!((usage[0].first_seen_ms, usage[0].last_seen_ms), (10, 30));
+        assert_eq!(usage[1].permission(), "evil.example:443");
+        assert!(!usage[1].declared);
+        assert!(usage[1].blocked && !usage[0].blocked);
+    }
+
+    #[test]
+    fn read_usage_is_empty_without_a_log() {
+        let tmp = tempfile::tempdir().unwrap();
+        assert!(read_usage(&tmp.path().join("none.jsonl"), &[])
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,127 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Helpers for launching servers with a minimal, permission-scoped environment.
+
+use std::collections::BTreeMap;
+
+use crate::permission_filter::{effective_permissions, PermissionOverrides};
+
+/// Parent variables always passed to isolated servers so runtimes can start.
+///
+/// `PATH` resolves the runtime command; the rest are home, temp, and proxy
+/// settings that node/python tooling expects on Unix and Windows.
+pub const BASELINE_ENV_VARS: [&str; 12] = [
+    "PATH",
+    "HOME",
+    "TMPDIR",
+    "TEMP",
+    "TMP",
+    "SYSTEMROOT",
+    "USERPROFILE",
+    "APPDATA",
+    "LOCALAPPDATA",
+    "HTTP_PROXY",
+    "HTTPS_PROXY",
+    "NO_PROXY",
+];
+
+/// Fills an isolated env map with baseline and granted variables from `parent`.
+///
+/// Values already in `env` (resolved config) win. Returns `false` when an
+/// `env:*` grant means the parent environment must be inherited as-is.
+pub fn isolate_env(
+    env: &mut BTreeMap<String, String>,
+    granted: &[String],
+    parent: impl IntoIterator<Item = (String, String)>,
+) -> bool {
+    if granted.iter().any(|g| g == "*") {
+        return false;
+    }
+    for (key, value) in parent {
+        let passed = BASELINE_ENV_VARS.contains(&key.as_str()) || granted.contains(&key);
+        if passed && !env.contains_key(&key) {
+            env.insert(key, value);
+        }
+    }
+    true
+}
+
+/// Applies isolation to a launch env unless `inherit_env` opts out.
+///
+/// Returns whether the child must be started with a cleared environment.
+pub fn apply_env_isolation(
+    env: &mut BTreeMap<String, String>,
+    declared_env: &[String],
+    overrides: &PermissionOverrides,
+    inherit_env: bool,
+) -> bool {
+    if inherit_env {
+        return false;
+    }
+    let granted = effective_permissions("env", declared_env, overrides);
+    isolate_env(env, &granted, std::env::vars())
+}
+
+/// Returns parent variable names an isolated launch would not pass through.
+pub fn dropped_env_vars(
+    env: &BTreeMap<String, String>,
+    parent: impl IntoIterator<Item = (String, String)>,
+) -> Vec<String> {
+    let mut dropped: Vec<String> = parent
+        .into_iter()
+        .map(|(key, _)| key)
+        .filter(|key| !env.contains_key(key))
+        .collect();
+    dropped.sort();
+    dropped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parent() -> Vec<(String, String)> {
+        [
+            ("PATH", "/usr/bin"),
+            ("HOME", "/home/dev"),
+            ("GITHUB_TOKEN", "from-shell"),
+            ("AWS_SECRET_ACCESS_KEY", "secret"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+    }
+
+    #[test]
+    fn isolate_env_keeps_baseline_and_granted_only() {
+        let mut env = BTreeMap::new();
+        assert!(isolate_env(
+            &mut env,
+            &["GITHUB_TOKEN".to_string()],
+            parent()
+        ));
+        assert_eq!(
+            env.keys().cloned().collect::<Vec<_>>(),
+            vec!["GITHUB_TOKEN", "HOME", "PATH"]
+        );
+        assert_eq!(
+            dropped_env_vars(&env, parent()),
+            vec!["AWS_SECRET_ACCESS_KEY"]
+        );
+    }
+
+    #[test]
+    fn isolate_env_prefers_resolved_config_values() {
+        let mut env = BTreeMap::from([("GITHUB_TOKEN".to_string(), "configured".to_string())]);
+        isolate_env(&mut env, &["GITHUB_TOKEN".to_string()], parent());
+        assert_eq!(env["GITHUB_TOKEN"], "configured");
+    }
+
+    #[test]
+    fn isolate_env_inherits_with_wildcard_grant() {
+        let mut env = BTreeMap::new();
+        assert!(!isolate_env(&mut env, &["*".to_string()], parent()));
+        assert!(env.is_empty());
+    }
+}
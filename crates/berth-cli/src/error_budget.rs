@@ -0,0 +1,390 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Error budgets and flaky-server detection.
+//!
+//! Restarts and tool-call errors from the audit log are folded into hourly
+//! buckets per server, persisted in `<state>/error-budget.json` for seven days
+//! so each refresh only reads audit lines appended since the last one. A server
+//! whose last 24 hours exceed its budget is reported as flaky.
+
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use berth_common::atomic_write;
+use berth_runtime::{AuditLog, AuditRecord};
+use serde::{Deserialize, Serialize};
+
+use crate::paths;
+
+pub const KEY_ERROR_BUDGET_RESTARTS: &str = "berth.error-budget-restarts";
+pub const KEY_ERROR_BUDGET_ERROR_RATE: &str = "berth.error-budget-error-rate";
+pub const DEFAULT_MAX_RESTARTS: u32 = 3;
+pub const DEFAULT_MAX_ERROR_RATE_PERCENT: u32 = 20;
+
+/// Window a budget applies to.
+pub const BUDGET_WINDOW_SECS: u64 = 24 * 3_600;
+/// How long hourly buckets are kept.
+const RETENTION_SECS: u64 = 7 * 24 * 3_600;
+/// Completed tool calls needed before the error rate counts.
+const MIN_CALLS_FOR_ERROR_RATE: u32 = 10;
+
+/// Restarts and tool-call error rate a server may reach within [`BUDGET_WINDOW_SECS`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ErrorBudget {
+    pub max_restarts: u32,
+    pub max_error_rate_percent: u32,
+}
+
+/// Returns whether a key is reserved for error budget settings.
+pub fn is_error_budget_key(key: &str) -> bool {
+    matches!(key, KEY_ERROR_BUDGET_RESTARTS | KEY_ERROR_BUDGET_ERROR_RATE)
+}
+
+/// Validates one key/value pair for error budget settings.
+pub fn validate_error_budget_value(key: &str, value: &str) -> Result<(), String> {
+    match key {
+        KEY_ERROR_BUDGET_RESTARTS => parse_restarts(value).map(|_| ()),
+        KEY_ERROR_BUDGET_ERROR_RATE => parse_error_rate(value).map(|_| ()),
+        _ => Err(format!("Unknown error budget key: {key}")),
+    }
+}
+
+/// Parses the error budget from installed config values.
+pub fn parse_error_budget(config: &BTreeMap<String, String>) -> Result<ErrorBudget, String> {
+    Ok(ErrorBudget {
+        max_restarts: match config.get(KEY_ERROR_BUDGET_RESTARTS) {
+            Some(v) => parse_restarts(v)?,
+            None => DEFAULT_MAX_RESTARTS,
+        },
+        max_error_rate_percent: match config.get(KEY_ERROR_BUDGET_ERROR_RATE) {
+            Some(v) => parse_error_rate(v)?,
+            None => DEFAULT_MAX_ERROR_RATE_PERCENT,
+        },
+    })
+}
+
+fn parse_restarts(value: &str) -> Result<u32, String> {
+    value.trim().parse::<u32>().map_err(|_| {
+        format!("Invalid value `{value}`. Expected a number of restarts per 24 hours (>= 0).")
+    })
+}
+
+fn parse_error_rate(value: &str) -> Result<u32, String> {
+    match value.trim().trim_end_matches('%').parse::<u32>() {
+        Ok(percent) if percent <= 100 => Ok(percent),
+        _ => Err(format!(
+            "Invalid value `{value}`. Expected a tool-call error rate in percent (0-100)."
+        )),
+    }
+}
+
+/// Counts for one server and hour.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Bucket {
+    pub hour_epoch_secs: u64,
+    pub restarts: u32,
+    pub tool_calls: u32,
+    pub tool_errors: u32,
+}
+
+/// Persisted rolling windows for every server.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Windows {
+    /// Audit log bytes already folded in.
+    #[serde(default)]
+    audit_offset: u64,
+    /// Time of the log's first event, which changes when the log is replaced.
+    #[serde(default)]
+    audit_first_epoch_ms: Option<u64>,
+    /// Newest event folded in, to skip duplicates when the log is re-read.
+    #[serde(default)]
+    processed_through_epoch_ms: u64,
+    #[serde(default)]
+    servers: BTreeMap<String, Vec<Bucket>>,
+}
+
+impl Windows {
+    /// Sums one server's buckets over the budget window ending at `now_ms`.
+    pub fn totals(&self, server: &str, now_ms: u64) -> Bucket {
+        let since = (now_ms / 1_000).saturating_sub(BUDGET_WINDOW_SECS);
+        let mut total = Bucket::default();
+        for bucket in self.servers.get(server).into_iter().flatten() {
+            // An hour that started before the window still overlaps it.
+            if bucket.hour_epoch_secs + 3_600 > since {
+                total.restarts += bucket.restarts;
+                total.tool_calls += bucket.tool_calls;
+                total.tool_errors += bucket.tool_errors;
+            }
+        }
+        total
+    }
+
+    /// Folds audit lines into the buckets and drops buckets past retention.
+    fn ingest(&mut self, lines: &str, now_ms: u64) {
+        let seen_through = self.processed_through_epoch_ms;
+        for event in lines
+            .lines()
+            .filter_map(|line| serde_json::from_str::<AuditRecord>(line).ok())
+        {
+            let at = event.epoch_ms();
+            if seen_through > 0 && at <= seen_through {
+                continue;
+            }
+            self.processed_through_epoch_ms = self.processed_through_epoch_ms.max(at);
+            let (restarts, tool_calls, tool_errors) = match event.action.as_str() {
+                "auto-restart" | "proxy-restart" => (1, 0, 0),
+                "mcp-tool-result" => (0, 1, u32::from(event.error == Some(true))),
+                _ => continue,
+            };
+            let hour = at / 1_000 / 3_600 * 3_600;
+            let buckets = self.servers.entry(event.server).or_default();
+            let i = match buckets.binary_search_by_key(&hour, |b| b.hour_epoch_secs) {
+                Ok(i) => i,
+                Err(i) => {
+                    buckets.insert(
+                        i,
+                        Bucket {
+                            hour_epoch_secs: hour,
+                            ..Bucket::default()
+                        },
+                    );
+                    i
+                }
+            };
+            buckets[i].restarts += restarts;
+            buckets[i].tool_calls += tool_calls;
+            buckets[i].tool_errors += tool_errors;
+        }
+
+        let cutoff = (now_ms / 1_000).saturating_sub(RETENTION_SECS);
+        for buckets in self.servers.values_mut() {
+            buckets.retain(|b| b.hour_epoch_secs >= cutoff);
+        }
+        self.servers.retain(|_, buckets| !buckets.is_empty());
+    }
+}
+
+/// Folds audit events appended since the last refresh into the persisted
+/// windows and returns them.
+pub fn refresh(now_ms: u64) -> Result<Windows, String> {
+    let (path, audit) = match (paths::error_budget_path(), paths::audit_log_path()) {
+        (Some(p), Some(a)) => (p, a),
+        _ => return Err("Could not determine home directory.".to_string()),
+    };
+    refresh_at(&path, &audit, now_ms)
+}
+
+fn refresh_at(path: &Path, audit: &Path, now_ms: u64) -> Result<Windows, String> {
+    let mut windows: Windows = match fs::read_to_string(path) {
+        Ok(content) => serde_json::from_str(&content).unwrap_or_default(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Windows::default(),
+        Err(e) => return Err(format!("Failed to read {}: {e}", path.display())),
+    };
+
+    let mut appended = String::new();
+    match File::open(audit) {
+        Ok(mut file) => {
+            let len = file
+                .metadata()
+                .map_err(|e| format!("Failed to read {}: {e}", audit.display()))?
+                .len();
+            let mut first_line = String::new();
+            let _ = BufReader::new(&file).read_line(&mut first_line);
+            let first_epoch_ms = serde_json::from_str::<AuditRecord>(&first_line)
+                .ok()
+                .map(|event| event.epoch_ms());
+            // A shorter or replaced log was rotated or truncated; re-read it and
+            // the rotated segments, whose tail may not have been read yet, and
+            // let the timestamp guard skip what was already counted.
+            if len < windows.audit_offset || first_epoch_ms != windows.audit_first_epoch_ms {
+                windows.audit_offset = 0;
+                windows.audit_first_epoch_ms = first_epoch_ms;
+                appended = read_rotated_segments(audit)?;
+            }
+            let mut bytes = Vec::new();
+            file.seek(SeekFrom::Start(windows.audit_offset))
+                .and_then(|_| file.read_to_end(&mut bytes))
+                .map_err(|e| format!("Failed to read {}: {e}", audit.display()))?;
+            // Leave a partially written last line for the next refresh.
+            let complete = bytes.iter().rposition(|b| *b == b'\n').map_or(0, |i| i + 1);
+            bytes.truncate(complete);
+            windows.audit_offset += complete as u64;
+            appended.push_str(&String::from_utf8_lossy(&bytes));
+        }
+        // Rotated away with no entry since; anything newer sits in the segments.
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            appended = read_rotated_segments(audit)?;
+        }
+        Err(e) => return Err(format!("Failed to read {}: {e}", audit.display())),
+    }
+    windows.ingest(&appended, now_ms);
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {e}", parent.display()))?;
+    }
+    let rendered = serde_json::to_string_pretty(&windows)
+        .map_err(|e| format!("Failed to serialize error budget: {e}"))?;
+    atomic_write(path, rendered).map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+    Ok(windows)
+}
+
+/// Reads the rotated segments of the audit log whose active file is `audit`,
+/// oldest first.
+fn read_rotated_segments(audit: &Path) -> Result<String, String> {
+    let segments = AuditLog::new(audit)
+        .segments()
+        .map_err(|e| format!("Failed to list audit segments of {}: {e}", audit.display()))?;
+    let mut content = String::new();
+    for segment in segments.iter().filter(|segment| *segment != audit) {
+        match fs::read_to_string(segment) {
+            Ok(text) => content.push_str(&text),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(format!("Failed to read {}: {e}", segment.display())),
+        }
+    }
+    Ok(content)
+}
+
+/// Returns why a server exceeded its budget, or nothing when it is within it.
+pub fn overruns(totals: &Bucket, budget: &ErrorBudget) -> Vec<String> {
+    let mut reasons = Vec::new();
+    if totals.restarts > budget.max_restarts {
+        reasons.push(format!(
+            "{} restarts (budget {})",
+            totals.restarts, budget.max_restarts
+        ));
+    }
+    if totals.tool_calls >= MIN_CALLS_FOR_ERROR_RATE {
+        let percent = u64::from(totals.tool_errors) * 100 / u64::from(totals.tool_calls);
+        if percent > u64::from(budget.max_error_rate_percent) {
+            reasons.push(format!(
+                "{percent}% tool-call errors over {} calls (budget {}%)",
+                totals.tool_calls, budget.max_error_rate_percent
+            ));
+        }
+    }
+    reasons
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR_MS: u64 = 3_600_000;
+
+    fn line(ms: u64, server: &str, action: &str, error: Option<bool>) -> String {
+        let mut event = serde_json::json!({
+            "timestampEpochMs": ms,
+            "timestampEpochSecs": ms / 1_000,
+            "server": server,
+            "action": action,
+        });
+        if let Some(error) = error {
+            event["error"] = error.into();
+        }
+        format!("{event}\n")
+    }
+
+    #[test]
+    fn refresh_reads_only_new_lines_and_survives_rotation() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (path, audit) = (
+            tmp.path().join("budget.json"),
+            tmp.path().join("audit.jsonl"),
+        );
+        let now = 100 * HOUR_MS;
+        let first = line(now - 2 * HOUR_MS, "github", "auto-restart", None);
+        fs::write(&audit, &first).unwrap();
+        assert_eq!(
+            refresh_at(&path, &audit, now)
+                .unwrap()
+                .totals("github", now)
+                .restarts,
+            1
+        );
+        // Nothing new: counts stay put.
+        assert_eq!(
+            refresh_at(&path, &audit, now)
+                .unwrap()
+                .totals("github", now)
+                .restarts,
+            1
+        );
+
+        // Rotation: the new log repeats nothing old, so only the new event counts.
+        fs::write(&audit, line(now - HOUR_MS, "github", "proxy-restart", None)).unwrap();
+        let windows = refresh_at(&path, &audit, now).unwrap();
+        assert_eq!(windows.totals("github", now).restarts, 2);
+        // Older than the window.
+        assert_eq!(windows.totals("github", now + 30 * HOUR_MS).restarts, 0);
+
+        // An entry appended just before the log was rotated still counts.
+        let mut tail = fs::read_to_string(&audit).unwrap();
+        tail.push_str(&line(now - 30 * 60_000, "github", "auto-restart", None));
+        fs::write(tmp.path().join(format!("audit-{now}.jsonl")), tail).unwrap();
+        fs::remove_file(&audit).unwrap();
+        assert_eq!(
+            refresh_at(&path, &audit, now)
+                .unwrap()
+                .totals("github", now)
+                .restarts,
+            3
+        );
+        fs::write(&audit, line(now - 60_000, "github", "auto-restart", None)).unwrap();
+        assert_eq!(
+            refresh_at(&path, &audit, now)
+                .unwrap()
+                .totals("github", now)
+                .restarts,
+            4
+        );
+    }
+
+    #[test]
+    fn overruns_flag_restarts_and_error_rate() {
+        let mut windows = Windows::default();
+        let now = 50 * HOUR_MS;
+        let mut lines = String::new();
+        for i in 0..4 {
+            lines.push_str(&line(now - HOUR_MS + i, "github", "auto-restart", None));
+        }
+        for i in 0..10 {
+            lines.push_str(&line(
+                now - 10 + i,
+                "github",
+                "mcp-tool-result",
+                Some(i < 3),
+            ));
+            lines.push_str(&line(now - 10 + i, "slack", "mcp-tool-result", Some(false)));
+        }
+        windows.ingest(&lines, now);
+
+        let budget = ErrorBudget {
+            max_restarts: DEFAULT_MAX_RESTARTS,
+            max_error_rate_percent: DEFAULT_MAX_ERROR_RATE_PERCENT,
+        };
+        assert_eq!(
+            overruns(&windows.totals("github", now), &budget),
+            [
+                "4 restarts (budget 3)",
+                "30% tool-call errors over 10 calls (budget 20%)"
+            ]
+        );
+        assert!(overruns(&windows.totals("slack", now), &budget).is_empty());
+    }
+
+    #[test]
+    fn validate_error_budget_rejects_malformed_values() {
+        assert!(validate_error_budget_value(KEY_ERROR_BUDGET_RESTARTS, "-1").is_err());
+        assert!(validate_error_budget_value(KEY_ERROR_BUDGET_ERROR_RATE, "101").is_err());
+        assert!(validate_error_budget_value(KEY_ERROR_BUDGET_ERROR_RATE, "5%").is_ok());
+        let cfg = BTreeMap::from([(KEY_ERROR_BUDGET_RESTARTS.to_string(), "0".to_string())]);
+        assert_eq!(parse_error_budget(&cfg).unwrap().max_restarts, 0);
+    }
+}
@@ -0,0 +1,275 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Stdio message framing for `berth proxy`.
+//!
+//! MCP clients and servers frame messages either as newline-delimited JSON or
+//! with LSP-style `Content-Length` headers. Reading accepts both on every
+//! message; writing uses the framing fixed by configuration or, with `auto`,
+//! the one the peer was last seen sending.
+
+use std::io::{self, BufRead, Write};
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Arc;
+
+/// How messages are delimited on one side of the proxy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Framing {
+    /// Match whatever the peer sends.
+    #[default]
+    Auto,
+    /// One JSON message per line.
+    Line,
+    /// `Content-Length: <n>` headers, a blank line, then `n` bytes of body.
+    ContentLength,
+}
+
+impl Framing {
+    /// Parses `auto`, `line`, or `content-length`.
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.trim() {
+            "auto" => Ok(Framing::Auto),
+            "line" => Ok(Framing::Line),
+            "content-length" => Ok(Framing::ContentLength),
+            _ => Err(format!(
+                "Invalid framing `{value}`. Expected `auto`, `line`, or `content-length`."
+            )),
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Framing::Auto => "auto",
+            Framing::Line => "line",
+            Framing::ContentLength => "content-length",
+        }
+    }
+}
+
+/// Framing last seen from one peer, shared between its reader and writers.
+#[derive(Debug, Clone, Default)]
+pub struct Detected(Arc<AtomicU8>);
+
+impl Detected {
+    pub fn get(&self) -> Option<Framing> {
+        match self.0.load(Ordering::Relaxed) {
+            1 => Some(Framing::Line),
+            2 => Some(Framing::ContentLength),
+            _ => None,
+        }
+    }
+
+    fn set(&self, framing: Framing) {
+        let value = match framing {
+            Framing::Auto => 0,
+            Framing::Line => 1,
+            Framing::ContentLength => 2,
+        };
+        self.0.store(value, Ordering::Relaxed);
+    }
+}
+
+/// Reads one message in either framing, recording which one was used.
+///
+/// Returns the body with a trailing newline, so callers can treat both
+/// framings like lines, or `None` at EOF.
+pub fn read_message<R: BufRead>(reader: &mut R, seen: &Detected) -> io::Result<Option<Vec<u8>>> {
+    let mut line = Vec::new();
+    if reader.read_until(b'\n', &mut line)? == 0 {
+        return Ok(None);
+    }
+    let Some(first) = header(&line) else {
+        if !line.trim_ascii().is_empty() {
+            seen.set(Framing::Line);
+        }
+        return Ok(Some(line));
+    };
+
+    let mut length = first;
+    loop {
+        let mut next = Vec::new();
+        if reader.read_until(b'\n', &mut next)? == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "stream ended inside message headers",
+            ));
+        }
+        if next.trim_ascii().is_empty() {
+            break;
+        }
+        if let Some(Some(value)) = header(&next) {
+            length = Some(value);
+        }
+    }
+    let length = length.ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidData, "message without Content-Length")
+    })?;
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+    seen.set(Framing::ContentLength);
+    if body.last() != Some(&b'\n') {
+        body.push(b'\n');
+    }
+    Ok(Some(body))
+}
+
+/// Returns `Some(content_length)` for a `Content-Length` or `Content-Type`
+/// header line, `None` for anything else.
+fn header(line: &[u8]) -> Option<Option<usize>> {
+    let line = std::str::from_utf8(line).ok()?.trim();
+    let (name, value) = line.split_once(':')?;
+    if name.eq_ignore_ascii_case("content-length") {
+        Some(Some(value.trim().parse().ok()?))
+    } else if name.eq_ignore_ascii_case("content-type") {
+        Some(None)
+    } else {
+        None
+    }
+}
+
+/// Encodes one message (as produced by [`read_message`]) for the wire.
+pub fn encode(message: &[u8], framing: Framing) -> Vec<u8> {
+    match framing {
+        Framing::ContentLength => {
+            let body = message.trim_ascii();
+            if body.is_empty() {
+                return Vec::new();
+            }
+            let mut out = format!("Content-Length: {}\r\n\r\n", body.len()).into_bytes();
+            out.extend_from_slice(body);
+            out
+        }
+        Framing::Line | Framing::Auto => {
+            let body = message.trim_ascii_end();
+            // A multi-line body must be compacted to stay one message.
+            let compact = body
+                .contains(&b'\n')
+                .then(|| serde_json::from_slice::<serde_json::Value>(body).ok())
+                .flatten()
+                .and_then(|value| serde_json::to_vec(&value).ok());
+            let mut out = match compact {
+                Some(compact) => compact,
+                None if message.ends_with(b"\n") => return message.to_vec(),
+                None => message.to_vec(),
+            };
+            out.push(b'\n');
+            out
+        }
+    }
+}
+
+/// Writes each `write` call as one framed message.
+///
+/// Callers must pass a whole message per call, as `write_all` does.
+pub struct FramedWriter<W> {
+    inner: W,
+    framing: Framing,
+    peer: Detected,
+    fallback: Option<Detected>,
+}
+
+impl<W> FramedWriter<W> {
+    /// Frames per `framing`, following `peer` when it is `auto`.
+    pub fn new(inner: W, framing: Framing, peer: Detected) -> Self {
+        FramedWriter {
+            inner,
+            framing,
+            peer,
+            fallback: None,
+        }
+    }
+
+    /// Follows `other` while `peer` has not sent anything yet.
+    pub fn with_fallback(mut self, other: Detected) -> Self {
+        self.fallback = Some(other);
+        self
+    }
+
+    fn effective(&self) -> Framing {
+        match self.framing {
+            Framing::Auto => self
+                .peer
+                .get()
+                .or_else(|| self.fallback.as_ref().and_then(Detected::get))
+                .unwrap_or(Framing::Line),
+            fixed => fixed,
+        }
+    }
+}
+
+impl<W: Write> Write for FramedWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.inner.write_all(&encode(buf, self.effective()))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn reads_both_framings_from_one_stream() {
+        let input = b"{\"id\":1}\r\nContent-Length: 9\r\nContent-Type: application/json\r\n\r\n{\"id\":2}\n{\"id\":3}\n";
+        let mut reader = Cursor::new(&input[..]);
+        let seen = Detected::default();
+
+        assert_eq!(
+            read_message(&mut reader, &seen).unwrap().unwrap(),
+            b"{\"id\":1}\r\n"
+        );
+        assert_eq!(seen.get(), Some(Framing::Line));
+        assert_eq!(
+            read_message(&mut reader, &seen).unwrap().unwrap(),
+            b"{\"id\":2}\n"
+        );
+        assert_eq!(seen.get(), Some(Framing::ContentLength));
+        assert_eq!(
+            read_message(&mut reader, &seen).unwrap().unwrap(),
+            b"{\"id\":3}\n"
+        );
+        assert!(read_message(&mut reader, &seen).unwrap().is_none());
+    }
+
+    #[test]
+    fn truncated_headers_are_an_error() {
+        let mut reader = Cursor::new(&b"Content-Length: 5\r\n"[..]);
+        assert!(read_message(&mut reader, &Detected::default()).is_err());
+    }
+
+    #[test]
+    fn encode_frames_and_compacts_messages() {
+        assert_eq!(
+            encode(b"{\"id\":1}\n", Framing::ContentLength),
+            b"Content-Length: 8\r\n\r\n{\"id\":1}"
+        );
+        assert_eq!(encode(b"\n", Framing::ContentLength), b"");
+        assert_eq!(
+            encode(b"{\n  \"id\": 1\n}\n", Framing::Line),
+            b"{\"id\":1}\n"
+        );
+        assert_eq!(encode(b"not json", Framing::Line), b"not json\n");
+    }
+
+    #[test]
+    fn auto_writer_follows_peer_then_fallback() {
+        let (peer, other) = (Detected::default(), Detected::default());
+        let mut writer =
+            FramedWriter::new(Vec::new(), Framing::Auto, peer.clone()).with_fallback(other.clone());
+        writer.write_all(b"{}\n").unwrap();
+        other.set(Framing::ContentLength);
+        writer.write_all(b"{}\n").unwrap();
+        peer.set(Framing::Line);
+        writer.write_all(b"{}\n").unwrap();
+        assert_eq!(writer.inner, b"{}\nContent-Length: 2\r\n\r\n{}{}\n");
+
+        let mut fixed = FramedWriter::new(Vec::new(), Framing::Line, other);
+        fixed.write_all(b"{}\n").unwrap();
+        assert_eq!(fixed.inner, b"{}\n");
+    }
+}
@@ -0,0 +1,224 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Remediation hints printed after common command failures.
+//!
+//! Errors are classified into a [`Failure`], from an I/O error kind where
+//! one is at hand and from the error text or the server's last log lines
+//! otherwise. [`RULES`] maps each failure to the hint shown under the error.
+
+use berth_runtime::RuntimeManager;
+use colored::Colorize;
+use std::io;
+
+use crate::permission_filter::NETWORK_PERMISSION_DENIED_PREFIX;
+use crate::policy_engine::POLICY_DENIED_PREFIX;
+
+/// Log lines searched for a known failure after a server exits early.
+const LOG_LINES: usize = 20;
+
+/// Failures Berth knows how to suggest a fix for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Failure {
+    /// The runtime command does not exist.
+    CommandNotFound,
+    /// The runtime command exists but may not be executed.
+    CommandNotExecutable,
+    /// Required config keys have no value.
+    MissingConfig,
+    /// The server exited complaining about an unset environment variable.
+    MissingEnv,
+    /// Org policy blocks the server.
+    PolicyDenied,
+    /// A local override revokes all network access.
+    NetworkDenied,
+    /// Another process already listens on the port the server binds.
+    PortInUse,
+}
+
+/// Hint per failure. `{server}` and `{command}` are filled in, and
+/// backticked spans are rendered bold.
+pub const RULES: [(Failure, &str); 7] = [
+    (
+        Failure::CommandNotFound,
+        "`{command}` was not found. Install it or add it to PATH; `berth doctor` checks the runtimes installed servers need.",
+    ),
+    (
+        Failure::CommandNotExecutable,
+        "`{command}` is not executable. Check its permissions, or reinstall with `berth install {server}`.",
+    ),
+    (
+        Failure::MissingConfig,
+        "Run `berth config {server} --set <key>=<value>` to configure.",
+    ),
+    (
+        Failure::MissingEnv,
+        "Run `berth config {server} --env` to see required variables.",
+    ),
+    (
+        Failure::PolicyDenied,
+        "Run `berth policy {server}` to see which org policy rule blocks it.",
+    ),
+    (
+        Failure::NetworkDenied,
+        "Run `berth permissions {server}` to review local overrides, or `berth permissions {server} --reset` to restore the declared grants.",
+    ),
+    (
+        Failure::PortInUse,
+        "Another process already uses the port {server} listens on. Stop it, or pick another port with `berth config {server} --set <key>=<value>`.",
+    ),
+];
+
+/// Log phrases that mean a port is taken, across platforms and runtimes.
+const PORT_IN_USE: [&str; 5] = [
+    "address already in use",
+    "eaddrinuse",
+    "os error 98",
+    "os error 48",
+    "os error 10048",
+];
+
+impl Failure {
+    /// Classifies an I/O error from spawning a command or binding a port.
+    pub fn from_io(err: &io::Error) -> Option<Self> {
+        match err.kind() {
+            io::ErrorKind::NotFound => Some(Failure::CommandNotFound),
+            io::ErrorKind::PermissionDenied => Some(Failure::CommandNotExecutable),
+            io::ErrorKind::AddrInUse => Some(Failure::PortInUse),
+            _ => None,
+        }
+    }
+
+    /// Classifies an error message or log line.
+    pub fn classify(text: &str) -> Option<Self> {
+        if text.starts_with(POLICY_DENIED_PREFIX) {
+            return Some(Failure::PolicyDenied);
+        }
+        if text.starts_with(NETWORK_PERMISSION_DENIED_PREFIX) {
+            return Some(Failure::NetworkDenied);
+        }
+        let text = text.to_lowercase();
+        if text.contains("missing required config") {
+            return Some(Failure::MissingConfig);
+        }
+        if PORT_IN_USE.iter().any(|phrase| text.contains(phrase)) {
+            return Some(Failure::PortInUse);
+        }
+        if text.contains("failed to spawn") {
+            if text.contains("os error 2)") {
+                return Some(Failure::CommandNotFound);
+            }
+            if text.contains("os error 13)") {
+                return Some(Failure::CommandNotExecutable);
+            }
+        }
+        let about_env = text.contains("environment variable") || text.contains("env var");
+        let unset = ["required", "missing", "not set", "undefined"]
+            .iter()
+            .any(|word| text.contains(word));
+        (about_env && unset).then_some(Failure::MissingEnv)
+    }
+
+    /// Renders the hint for this failure.
+    pub fn hint(self, server: &str, command: &str) -> String {
+        let template = RULES
+            .iter()
+            .find(|(failure, _)| *failure == self)
+            .map_or("", |(_, hint)| hint);
+        let filled = template
+            .replace("{server}", server)
+            .replace("{command}", command);
+        filled
+            .split('`')
+            .enumerate()
+            .map(|(i, part)| {
+                if i % 2 == 1 {
+                    part.bold().to_string()
+                } else {
+                    part.to_string()
+                }
+            })
+            .collect()
+    }
+
+    /// Prints the hint below an error.
+    pub fn print(self, server: &str, command: &str) {
+        eprintln!("  {}", self.hint(server, command));
+    }
+}
+
+/// Prints the hint for an error message, if it matches a known failure.
+pub fn print_for_message(message: &str, server: &str, command: &str) {
+    if let Some(failure) = Failure::classify(message) {
+        failure.print(server, command);
+    }
+}
+
+/// Prints the hint for the newest recognizable line of a server's log, for
+/// servers that exited before they became ready.
+pub fn print_for_logs(runtime: &RuntimeManager, server: &str, command: &str) {
+    let Ok(lines) = runtime.tail_logs(server, LOG_LINES) else {
+        return;
+    };
+    if let Some(failure) = lines.iter().rev().find_map(|line| Failure::classify(line)) {
+        failure.print(server, command);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_errors_and_log_lines() {
+        let cases = [
+            (
+                "Policy denied for github: server is blocked by org policy.",
+                Some(Failure::PolicyDenied),
+            ),
+            (
+                "Network permission denied for github: effective network permissions are empty.",
+                Some(Failure::NetworkDenied),
+            ),
+            (
+                "failed to spawn process: No such file or directory (os error 2)",
+                Some(Failure::CommandNotFound),
+            ),
+            (
+                "failed to spawn process: Permission denied (os error 13)",
+                Some(Failure::CommandNotExecutable),
+            ),
+            (
+                "Error: listen EADDRINUSE: address already in use :::3000",
+                Some(Failure::PortInUse),
+            ),
+            (
+                "GITHUB_TOKEN environment variable is required",
+                Some(Failure::MissingEnv),
+            ),
+            (
+                "Missing required config: token",
+                Some(Failure::MissingConfig),
+            ),
+            ("server exited with code 1", None),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(Failure::classify(text), expected, "{text}");
+        }
+        assert_eq!(
+            Failure::from_io(&io::Error::from(io::ErrorKind::AddrInUse)),
+            Some(Failure::PortInUse)
+        );
+    }
+
+    #[test]
+    fn every_failure_has_a_hint() {
+        for (failure, _) in RULES {
+            let hint = failure.hint("github", "npx");
+            assert!(!hint.contains('`') && !hint.contains('{'), "{hint}");
+        }
+        let hint = Failure::MissingEnv.hint("github", "npx");
+        assert!(hint.contains("berth config github --env"), "{hint}");
+        assert!(hint.ends_with(" to see required variables."), "{hint}");
+    }
+}
@@ -0,0 +1,239 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Restricted post-install steps declared by registry manifests.
+//!
+//! Steps never run manifest-supplied commands. Berth interprets each kind
+//! itself and only within the server's declared permissions: directories must
+//! fall under a `filesystem` write grant and URLs must be `https` hosts the
+//! server may already reach.
+
+use berth_registry::config::PermissionsInfo;
+use berth_registry::permissions::{FsMode, FsPermission, NetworkPermission};
+use berth_registry::types::PostInstallStep;
+use colored::Colorize;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use crate::platform;
+use crate::progress::say;
+
+/// Checks one step against the server's declared permissions.
+pub fn validate_step(step: &PostInstallStep, permissions: &PermissionsInfo) -> Result<(), String> {
+    match step {
+        PostInstallStep::CreateDir { path } => {
+            let relative_to_home = path == "~" || path.starts_with("~/");
+            if !relative_to_home && !Path::new(path).is_absolute() {
+                return Err(format!(
+                    "createDir path `{path}` must be absolute or start with `~/`"
+                ));
+            }
+            if Path::new(path)
+                .components()
+                .any(|c| matches!(c, Component::ParentDir))
+            {
+                return Err(format!("createDir path `{path}` must not contain `..`"));
+            }
+            let granted = permissions.filesystem.iter().any(|entry| {
+                entry
+                    .parse::<FsPermission>()
+                    .is_ok_and(|p| p.matches(FsMode::Write, path))
+            });
+            if !granted {
+                return Err(format!(
+                    "createDir path `{path}` is not covered by a declared filesystem write permission"
+                ));
+            }
+            Ok(())
+        }
+        PostInstallStep::Print { message } => {
+            if message.trim().is_empty() {
+                return Err("print message must not be empty".to_string());
+            }
+            Ok(())
+        }
+        PostInstallStep::OpenUrl { url, .. } => {
+            let (host, port) = https_host_port(url)?;
+            let granted = permissions.network.iter().any(|entry| {
+                entry
+                    .parse::<NetworkPermission>()
+                    .is_ok_and(|p| p.matches(&host, port))
+            });
+            if !granted {
+                return Err(format!(
+                    "openUrl host `{host}:{port}` is not covered by a declared network permission"
+                ));
+            }
+            Ok(())
+        }
+    }
+}
+
+/// One-line description of a step for dry-run plans.
+pub fn describe_step(step: &PostInstallStep) -> String {
+    match step {
+        PostInstallStep::CreateDir { path } => format!("create directory {path}"),
+        PostInstallStep::Print { message } => format!("print \"{}\"", sanitize(message)),
+        PostInstallStep::OpenUrl { url, .. } => format!("open {}", sanitize(url)),
+    }
+}
+
+/// Runs steps in order, skipping invalid or failing ones with a warning.
+///
+/// URLs are always printed; they are only opened when `open_browser` is set.
+/// Returns the number of skipped steps.
+pub fn run_post_install(
+    steps: &[PostInstallStep],
+    permissions: &PermissionsInfo,
+    home: Option<&Path>,
+    open_browser: bool,
+) -> usize {
+    if steps.is_empty() {
+        return 0;
+    }
+    say!("\n  {}", "Post-install steps".bold());
+    let mut skipped = 0;
+    for step in steps {
+        if let Err(reason) =
+            validate_step(step, permissions).and_then(|()| run_step(step, home, open_browser))
+        {
+            skipped += 1;
+            say!(
+                "  {} Skipped post-install step: {}",
+                "!".yellow().bold(),
+                sanitize(&reason)
+            );
+        }
+    }
+    skipped
+}
+
+fn run_step(step: &PostInstallStep, home: Option<&Path>, open_browser: bool) -> Result<(), String> {
+    match step {
+        PostInstallStep::CreateDir { path } => {
+            let dir = expand_home(path, home)?;
+            fs::create_dir_all(&dir)
+                .map_err(|e| format!("failed to create {}: {e}", dir.display()))?;
+            say!("  {} Created {}", "✓".green().bold(), dir.display());
+        }
+        PostInstallStep::Print { message } => {
+            for line in message.lines() {
+                say!("  {} {}", "•".dimmed(), sanitize(line));
+            }
+        }
+        PostInstallStep::OpenUrl { url, description } => {
+            let label = description.as_deref().map(sanitize);
+            say!(
+                "  {} {}: {}",
+                "•".dimmed(),
+                label.as_deref().unwrap_or("Open"),
+                sanitize(url).bold()
+            );
+            if open_browser {
+                if let Err(e) = platform::open_url(url) {
+                    say!("  {} Could not open browser: {e}", "!".yellow().bold());
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Extracts the host and effective port of an `https` URL.
+fn https_host_port(url: &str) -> Result<(String, u16), String> {
+    let rest = url
+        .strip_prefix("https://")
+        .ok_or_else(|| format!("openUrl `{url}` must use https"))?;
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    if authority.is_empty() || authority.contains('@') {
+        return Err(format!("openUrl `{url}` must name a plain host"));
+    }
+    // Bracketed IPv6 hosts contain `:`, so split the port after the `]`.
+    let port_start = authority.rfind(']').unwrap_or(0);
+    match authority[port_start..].rfind(':').map(|i| port_start + i) {
+        Some(colon) => {
+            let port = authority[colon + 1..]
+                .parse::<u16>()
+                .map_err(|_| format!("openUrl `{url}` has an invalid port"))?;
+            Ok((authority[..colon].to_string(), port))
+        }
+        None => Ok((authority.to_string(), 443)),
+    }
+}
+
+fn expand_home(path: &str, home: Option<&Path>) -> Result<PathBuf, String> {
+    match path.strip_prefix('~') {
+        Some(rest) => {
+            let home = home.ok_or("Could not determine home directory.")?;
+            Ok(home.join(rest.trim_start_matches('/')))
+        }
+        None => Ok(PathBuf::from(path)),
+    }
+}
+
+/// Drops control characters so manifests cannot inject terminal escapes.
+fn sanitize(text: &str) -> String {
+    text.chars().filter(|c| !c.is_control()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn permissions() -> PermissionsInfo {
+        PermissionsInfo {
+            network: vec!["*.example.com:443".to_string()],
+            env: vec![],
+            filesystem: vec!["write:~/.config/demo".to_string()],
+            exec: vec![],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn validate_step_requires_declared_filesystem_write() {
+        let create = |path: &str| PostInstallStep::CreateDir {
+            path: path.to_string(),
+        };
+        assert!(validate_step(&create("~/.config/demo/cache"), &permissions()).is_ok());
+        assert!(validate_step(&create("~/.ssh"), &permissions()).is_err());
+        assert!(validate_step(&create("~/.config/demo/../../.ssh"), &permissions()).is_err());
+        assert!(validate_step(&create("relative/dir"), &permissions()).is_err());
+    }
+
+    #[test]
+    fn validate_step_requires_https_hosts_covered_by_network_permissions() {
+        let open = |url: &str| PostInstallStep::OpenUrl {
+            url: url.to_string(),
+            description: None,
+        };
+        assert!(validate_step(&open("https://apps.example.com/oauth/new"), &permissions()).is_ok());
+        assert!(validate_step(&open("http://apps.example.com/"), &permissions()).is_err());
+        assert!(validate_step(&open("https://evil.test/"), &permissions()).is_err());
+        assert!(validate_step(&open("https://apps.example.com:8443/"), &permissions()).is_err());
+        assert!(
+            validate_step(&open("https://apps.example.com@evil.test/"), &permissions()).is_err()
+        );
+    }
+
+    #[test]
+    fn run_post_install_creates_directories_and_skips_invalid_steps() {
+        let tmp = tempfile::tempdir().unwrap();
+        let steps = vec![
+            PostInstallStep::CreateDir {
+                path: "~/.config/demo/cache".to_string(),
+            },
+            PostInstallStep::CreateDir {
+                path: "~/outside".to_string(),
+            },
+            PostInstallStep::Print {
+                message: "Run \u{1b}[31mberth config demo\u{1b}[0m".to_string(),
+            },
+        ];
+        let skipped = run_post_install(&steps, &permissions(), Some(tmp.path()), false);
+        assert_eq!(skipped, 1);
+        assert!(tmp.path().join(".config/demo/cache").is_dir());
+        assert!(!tmp.path().join("outside").exists());
+        assert_eq!(sanitize("a\u{1b}[31mb"), "a[31mb");
+    }
+}
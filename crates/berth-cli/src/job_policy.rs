@@ -0,0 +1,104 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Helpers for parsing and validating the schedule of `job` runtimes.
+//!
+//! A job's manifest declares `runtime.schedule`; `berth.schedule` overrides it
+//! per installation.
+
+use berth_registry::config::InstalledServer;
+use berth_runtime::JobSchedule;
+
+pub const KEY_SCHEDULE: &str = "berth.schedule";
+/// Runtime type of scheduled one-shot tasks.
+pub const JOB_RUNTIME: &str = "job";
+
+/// Returns whether an installed server is a scheduled job.
+pub fn is_job(installed: &InstalledServer) -> bool {
+    installed.runtime.runtime_type == JOB_RUNTIME
+}
+
+/// Returns whether a key is reserved for job schedule settings.
+pub fn is_schedule_key(key: &str) -> bool {
+    key == KEY_SCHEDULE
+}
+
+/// Validates one key/value pair for job schedule settings.
+pub fn validate_schedule_value(key: &str, value: &str) -> Result<(), String> {
+    match key {
+        KEY_SCHEDULE if value.trim().is_empty() => Ok(()),
+        KEY_SCHEDULE => parse_schedule_value(value).map(|_| ()),
+        _ => Err(format!("Unknown schedule key: {key}")),
+    }
+}
+
+/// Returns the schedule an installed job runs on, or `None` for servers.
+pub fn parse_schedule(installed: &InstalledServer) -> Result<Option<JobSchedule>, String> {
+    if !is_job(installed) {
+        return Ok(None);
+    }
+    let configured = installed
+        .config
+        .get(KEY_SCHEDULE)
+        .filter(|v| !v.trim().is_empty())
+        .or(installed.runtime.schedule.as_ref());
+    match configured {
+        Some(value) => parse_schedule_value(value).map(Some),
+        None => Err(format!(
+            "Job {} has no schedule. Set `{KEY_SCHEDULE}` (e.g. `every 1h`).",
+            installed.server.name
+        )),
+    }
+}
+
+/// Parses `every <n><s|m|h|d>`, `hourly` or `daily` (`@hourly` / `@daily` work too).
+pub fn parse_schedule_value(value: &str) -> Result<JobSchedule, String> {
+    let invalid = || {
+        format!("Invalid schedule `{value}`. Expected `every <n><s|m|h|d>`, `hourly` or `daily`.")
+    };
+    let trimmed = value.trim();
+    let every_secs = match trimmed.trim_start_matches('@') {
+        "hourly" => 3_600,
+        "daily" => 86_400,
+        _ => {
+            let interval = trimmed.strip_prefix("every").ok_or_else(invalid)?.trim();
+            if interval.len() < 2 {
+                return Err(invalid());
+            }
+            let (count, unit) = interval.split_at(interval.len() - 1);
+            let count: u64 = count.trim().parse().map_err(|_| invalid())?;
+            let unit_secs = match unit {
+                "s" => 1,
+                "m" => 60,
+                "h" => 3_600,
+                "d" => 86_400,
+                _ => return Err(invalid()),
+            };
+            count.saturating_mul(unit_secs)
+        }
+    };
+    if every_secs == 0 {
+        return Err(invalid());
+    }
+    Ok(JobSchedule { every_secs })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_schedule_value_reads_intervals_and_aliases() {
+        assert_eq!(parse_schedule_value("every 15m").unwrap().every_secs, 900);
+        assert_eq!(
+            parse_schedule_value("every 2d").unwrap().every_secs,
+            172_800
+        );
+        assert_eq!(parse_schedule_value("@hourly").unwrap().every_secs, 3_600);
+        assert_eq!(parse_schedule_value("daily").unwrap().every_secs, 86_400);
+        assert!(parse_schedule_value("every 0m").is_err());
+        assert!(parse_schedule_value("every 1w").is_err());
+        assert!(parse_schedule_value("*/5 * * * *").is_err());
+        assert!(validate_schedule_value(KEY_SCHEDULE, "").is_ok());
+    }
+}
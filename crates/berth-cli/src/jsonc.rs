@@ -0,0 +1,447 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Tolerant reading and comment-preserving editing of JSONC client configs.
+//!
+//! Cursor and VS Code settings files may contain `//` and `/* */` comments and
+//! trailing commas. [`parse`] reads such files; [`Document`] edits object
+//! members in place, so everything outside the touched members, comments
+//! included, is written back byte for byte.
+
+use serde_json::Value;
+
+/// Parses JSON, falling back to JSONC (comments and trailing commas).
+pub fn parse(text: &str) -> Result<Value, String> {
+    serde_json::from_str(text)
+        .or_else(|strict| serde_json::from_str(&strip(text)).map_err(|_| strict.to_string()))
+}
+
+/// Returns whether `text` only parses once comments and trailing commas are
+/// dropped, i.e. whether rewriting it through serde would lose anything.
+pub fn needs_tolerant_edit(text: &str) -> bool {
+    serde_json::from_str::<Value>(text).is_err() && parse(text).is_ok()
+}
+
+/// Blanks out comments and trailing commas, keeping byte offsets and newlines.
+fn strip(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = bytes.to_vec();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'"' => i = string_end(bytes, i),
+            b'/' if matches!(bytes.get(i + 1), Some(b'/' | b'*')) => {
+                let end = comment_end(bytes, i);
+                blank(&mut out[i..end]);
+                i = end;
+            }
+            b',' => {
+                let next = skip_ws(bytes, i + 1);
+                if matches!(bytes.get(next), Some(b'}' | b']')) {
+                    out[i] = b' ';
+                }
+                i += 1;
+            }
+            _ => i += 1,
+        }
+    }
+    // Only ASCII bytes were replaced, by ASCII spaces.
+    String::from_utf8(out).expect("blanking keeps utf-8 valid; qed")
+}
+
+fn blank(bytes: &mut [u8]) {
+    for b in bytes.iter_mut().filter(|b| !matches!(**b, b'\n' | b'\r')) {
+        *b = b' ';
+    }
+}
+
+/// A JSONC document edited by splicing its text.
+pub struct Document {
+    text: String,
+}
+
+/// Byte spans of one object member.
+struct Member {
+    key: String,
+    start: usize,
+    value_start: usize,
+    value_end: usize,
+    /// Position of the comma after the value, if any.
+    comma: Option<usize>,
+}
+
+/// Byte spans of one object.
+struct Object {
+    open: usize,
+    close: usize,
+    members: Vec<Member>,
+}
+
+impl Document {
+    /// Wraps a document whose root is an object.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let doc = Document {
+            text: text.to_string(),
+        };
+        doc.object(&[])?;
+        Ok(doc)
+    }
+
+    /// Returns the edited text.
+    pub fn into_string(self) -> String {
+        self.text
+    }
+
+    /// Sets `key` in the object at `path` (keys from the root), creating
+    /// missing objects along the way. An existing value is replaced in place.
+    pub fn set(&mut self, path: &[&str], key: &str, value: &Value) -> Result<(), String> {
+        for depth in 0..path.len() {
+            let parent = self.object(&path[..depth])?;
+            if !parent.members.iter().any(|m| m.key == path[depth]) {
+                self.insert(&parent, path[depth], &Value::Object(Default::default()))?;
+            }
+        }
+        let object = self.object(path)?;
+        match object.members.iter().find(|m| m.key == key) {
+            Some(member) => {
+                let indent = line_indent(&self.text, member.start);
+                let rendered = render(value, &indent)?;
+                self.text
+                    .replace_range(member.value_start..member.value_end, &rendered);
+                Ok(())
+            }
+            None => self.insert(&object, key, value),
+        }
+    }
+
+    /// Removes `key` from the object at `path`; returns whether it was present.
+    pub fn remove(&mut self, path: &[&str], key: &str) -> Result<bool, String> {
+        let object = match self.object(path) {
+            Ok(object) => object,
+            Err(_) if !path.is_empty() => return Ok(false),
+            Err(e) => return Err(e),
+        };
+        let Some(index) = object.members.iter().position(|m| m.key == key) else {
+            return Ok(false);
+        };
+        let member = &object.members[index];
+        let bytes = self.text.as_bytes();
+        let line_start = line_start(bytes, member.start);
+        let start = if self.text[line_start..member.start].trim().is_empty() {
+            line_start
+        } else {
+            member.start
+        };
+        let mut end = member.comma.map_or(member.value_end, |comma| comma + 1);
+        let rest = end
+            + self.text[end..]
+                .find('\n')
+                .map_or(self.text.len() - end, |n| n + 1);
+        if self.text[end..rest].trim().is_empty() && start == line_start {
+            end = rest;
+        }
+        // Dropping the last member must not leave the previous one's comma
+        // dangling, unless the file already uses trailing commas.
+        let dangling = match (member.comma, index.checked_sub(1)) {
+            (None, Some(prev)) => object.members[prev].comma,
+            _ => None,
+        };
+        self.text.replace_range(start..end, "");
+        if let Some(comma) = dangling {
+            self.text.replace_range(comma..comma + 1, "");
+        }
+        Ok(true)
+    }
+
+    /// Inserts a new member after the last one in `object`.
+    fn insert(&mut self, object: &Object, key: &str, value: &Value) -> Result<(), String> {
+        let key = serde_json::to_string(key).map_err(|e| e.to_string())?;
+        match object.members.last() {
+            Some(last) => {
+                let indent = line_indent(&self.text, last.start);
+                let entry = format!("{key}: {}", render(value, &indent)?);
+                // Keep the file's trailing-comma style.
+                let (after, entry) = match last.comma {
+                    Some(comma) => (comma + 1, format!("\n{indent}{entry},")),
+                    None => (last.value_end, format!("\n{indent}{entry}")),
+                };
+                // Insert below a comment that trails the last member's line.
+                let line_end = after
+                    + self.text[after..]
+                        .find('\n')
+                        .unwrap_or(self.text.len() - after);
+                let rest = self.text[after..line_end].trim();
+                let at = if rest.is_empty() || rest.starts_with("//") {
+                    line_end
+                } else {
+                    after
+                };
+                self.text.insert_str(at, &entry);
+                if last.comma.is_none() {
+                    self.text.insert(last.value_end, ',');
+                }
+            }
+            None => {
+                let outer = line_indent(&self.text, object.open);
+                let indent = format!("{outer}  ");
+                let entry = format!("{key}: {}", render(value, &indent)?);
+                let inner = &self.text[object.open + 1..object.close];
+                if inner.trim().is_empty() {
+                    self.text.replace_range(
+                        object.open + 1..object.close,
+                        &format!("\n{indent}{entry}\n{outer}"),
+                    );
+                } else {
+                    // Only comments inside; keep them above the new member.
+                    self.text
+                        .insert_str(object.open + 1, &format!("\n{indent}{entry}"));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Locates the object reached by following `path` from the root.
+    fn object(&self, path: &[&str]) -> Result<Object, String> {
+        let bytes = self.text.as_bytes();
+        let mut open = skip_ws(bytes, 0);
+        if bytes.get(open) != Some(&b'{') {
+            return Err("config root must be a JSON object".to_string());
+        }
+        for (depth, key) in path.iter().enumerate() {
+            let object = scan_object(bytes, open)?;
+            let member = object
+                .members
+                .iter()
+                .find(|m| m.key == *key)
+                .ok_or_else(|| format!("missing `{}`", path[..=depth].join(".")))?;
+            if bytes.get(member.value_start) != Some(&b'{') {
+                return Err(format!(
+                    "`{}` must be a JSON object",
+                    path[..=depth].join(".")
+                ));
+            }
+            open = member.value_start;
+        }
+        scan_object(bytes, open)
+    }
+}
+
+/// Renders `value` for a member whose line starts with `indent`.
+fn render(value: &Value, indent: &str) -> Result<String, String> {
+    let pretty = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+    Ok(pretty.replace('\n', &format!("\n{indent}")))
+}
+
+fn scan_object(bytes: &[u8], open: usize) -> Result<Object, String> {
+    let malformed = |at: usize| format!("malformed JSONC near byte {at}");
+    let mut members = Vec::new();
+    let mut i = open + 1;
+    loop {
+        i = skip_ws(bytes, i);
+        match bytes.get(i) {
+            Some(b'}') => {
+                return Ok(Object {
+                    open,
+                    close: i,
+                    members,
+                })
+            }
+            Some(b'"') => {}
+            _ => return Err(malformed(i)),
+        }
+        let start = i;
+        let key_end = string_end(bytes, i);
+        let key: String = std::str::from_utf8(&bytes[start..key_end])
+            .ok()
+            .and_then(|raw| serde_json::from_str(raw).ok())
+            .ok_or_else(|| malformed(start))?;
+        i = skip_ws(bytes, key_end);
+        if bytes.get(i) != Some(&b':') {
+            return Err(malformed(i));
+        }
+        let value_start = skip_ws(bytes, i + 1);
+        let value_end = value_end(bytes, value_start);
+        if value_end == value_start {
+            return Err(malformed(value_start));
+        }
+        i = skip_ws(bytes, value_end);
+        let comma = (bytes.get(i) == Some(&b',')).then_some(i);
+        if comma.is_some() {
+            i += 1;
+        }
+        members.push(Member {
+            key,
+            start,
+            value_start,
+            value_end,
+            comma,
+        });
+    }
+}
+
+/// Returns the end of the value starting at `i`.
+fn value_end(bytes: &[u8], i: usize) -> usize {
+    match bytes.get(i) {
+        Some(b'"') => string_end(bytes, i),
+        Some(b'{' | b'[') => {
+            let mut depth = 0usize;
+            let mut j = i;
+            while j < bytes.len() {
+                match bytes[j] {
+                    b'"' => {
+                        j = string_end(bytes, j);
+                        continue;
+                    }
+                    b'/' if matches!(bytes.get(j + 1), Some(b'/' | b'*')) => {
+                        j = comment_end(bytes, j);
+                        continue;
+                    }
+                    b'{' | b'[' => depth += 1,
+                    b'}' | b']' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            return j + 1;
+                        }
+                    }
+                    _ => {}
+                }
+                j += 1;
+            }
+            bytes.len()
+        }
+        _ => {
+            let mut j = i;
+            while j < bytes.len()
+                && !matches!(bytes[j], b',' | b'}' | b']' | b'/')
+                && !bytes[j].is_ascii_whitespace()
+            {
+                j += 1;
+            }
+            j
+        }
+    }
+}
+
+/// Returns the position just past the string starting at `i`.
+fn string_end(bytes: &[u8], i: usize) -> usize {
+    let mut j = i + 1;
+    while j < bytes.len() {
+        match bytes[j] {
+            b'\\' => j += 2,
+            b'"' => return j + 1,
+            _ => j += 1,
+        }
+    }
+    bytes.len()
+}
+
+/// Returns the position just past the comment starting at `i`.
+fn comment_end(bytes: &[u8], i: usize) -> usize {
+    if bytes.get(i + 1) == Some(&b'/') {
+        return bytes[i..]
+            .iter()
+            .position(|b| *b == b'\n')
+            .map_or(bytes.len(), |n| i + n);
+    }
+    bytes[i + 2..]
+        .windows(2)
+        .position(|w| w == b"*/")
+        .map_or(bytes.len(), |n| i + 2 + n + 2)
+}
+
+/// Skips whitespace and comments.
+fn skip_ws(bytes: &[u8], mut i: usize) -> usize {
+    loop {
+        match bytes.get(i) {
+            Some(b) if b.is_ascii_whitespace() => i += 1,
+            Some(b'/') if matches!(bytes.get(i + 1), Some(b'/' | b'*')) => {
+                i = comment_end(bytes, i)
+            }
+            _ => return i,
+        }
+    }
+}
+
+fn line_start(bytes: &[u8], i: usize) -> usize {
+    bytes[..i]
+        .iter()
+        .rposition(|b| *b == b'\n')
+        .map_or(0, |n| n + 1)
+}
+
+fn line_indent(text: &str, i: usize) -> String {
+    let start = line_start(text.as_bytes(), i);
+    text[start..]
+        .chars()
+        .take_while(|c| *c == ' ' || *c == '\t')
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const SETTINGS: &str = r#"{
+    // Editor settings
+    "editor.fontSize": 14,
+    "mcpServers": {
+        /* hand-written */
+        "mine": { "command": "x" }, // keep me
+    },
+}
+"#;
+
+    #[test]
+    fn parse_accepts_comments_and_trailing_commas() {
+        let value = parse(SETTINGS).unwrap();
+        assert_eq!(value["mcpServers"]["mine"]["command"], "x");
+        assert!(needs_tolerant_edit(SETTINGS));
+        assert!(!needs_tolerant_edit(r#"{"a": 1}"#));
+        assert!(parse("{ nope").is_err());
+    }
+
+    #[test]
+    fn set_and_remove_keep_comments_and_layout() {
+        let mut doc = Document::parse(SETTINGS).unwrap();
+        doc.set(&["mcpServers"], "github", &json!({ "command": "npx" }))
+            .unwrap();
+        let edited = doc.into_string();
+        assert!(edited.contains("// Editor settings"));
+        assert!(edited.contains("/* hand-written */"));
+        assert!(edited.contains("// keep me"));
+        assert!(
+            edited.contains("\n        \"github\": {\n          \"command\": \"npx\"\n        },")
+        );
+        assert_eq!(
+            parse(&edited).unwrap()["mcpServers"]["github"]["command"],
+            "npx"
+        );
+
+        let mut doc = Document::parse(&edited).unwrap();
+        assert!(doc.remove(&["mcpServers"], "github").unwrap());
+        assert!(!doc.remove(&["mcpServers"], "github").unwrap());
+        assert_eq!(doc.into_string(), SETTINGS);
+    }
+
+    #[test]
+    fn set_creates_missing_objects_and_replaces_values() {
+        let mut doc = Document::parse("{\n  // only a comment\n}\n").unwrap();
+        doc.set(&["mcpServers"], "github", &json!({ "command": "npx" }))
+            .unwrap();
+        doc.set(&["mcpServers"], "github", &json!({ "command": "node" }))
+            .unwrap();
+        let edited = doc.into_string();
+        assert!(edited.contains("// only a comment"));
+        let value = parse(&edited).unwrap();
+        assert_eq!(value["mcpServers"]["github"]["command"], "node");
+    }
+
+    #[test]
+    fn removing_the_last_member_drops_the_dangling_comma() {
+        let mut doc = Document::parse("{\n  \"a\": 1,\n  \"b\": 2\n}").unwrap();
+        doc.remove(&[], "b").unwrap();
+        assert_eq!(doc.into_string(), "{\n  \"a\": 1\n}");
+    }
+}
@@ -0,0 +1,55 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Turns the resource limits a manifest declares into the runtime's.
+
+use berth_registry::config::RuntimeInfo;
+use berth_runtime::ResourceLimits;
+
+/// Returns the limits a server runs under, if its manifest declares any.
+pub fn resource_limits(runtime: &RuntimeInfo) -> Option<ResourceLimits> {
+    let declared = runtime.limits?;
+    let limits = ResourceLimits {
+        memory_mb: declared.memory_mb.filter(|mb| *mb > 0),
+        cpu_percent: declared.cpu_percent.filter(|percent| *percent > 0),
+        max_open_files: declared.max_open_files.filter(|files| *files > 0),
+    };
+    (!limits.is_empty()).then_some(limits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limits_come_from_the_manifest_without_zero_values() {
+        let mut runtime = RuntimeInfo {
+            runtime_type: "node".to_string(),
+            command: "npx".to_string(),
+            args: vec![],
+            transport: "stdio".to_string(),
+            syscalls: vec![],
+            health_check: None,
+            schedule: None,
+            limits: None,
+            socket: None,
+        };
+        assert_eq!(resource_limits(&runtime), None);
+
+        runtime.limits = Some(
+            serde_json::from_str(r#"{"memoryMb": 512, "cpuPercent": 0, "maxOpenFiles": 256}"#)
+                .unwrap(),
+        );
+        assert_eq!(
+            resource_limits(&runtime),
+            Some(ResourceLimits {
+                memory_mb: Some(512),
+                cpu_percent: None,
+                max_open_files: Some(256),
+            })
+        );
+
+        runtime.limits = Some(serde_json::from_str(r#"{"cpuPercent": 0}"#).unwrap());
+        assert_eq!(resource_limits(&runtime), None);
+    }
+}
@@ -0,0 +1,331 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Log following with bounded buffering for slow terminals.
+//!
+//! A reader thread polls the log file and queues lines; the caller's thread
+//! writes them out. When output falls behind, the queue drops its oldest lines
+//! and reports how many were skipped instead of growing without bound. Lines
+//! are decoded with [`sanitize_log_line`]; [`follow_raw`] copies bytes as is.
+//! Both keep following the log across server restarts, truncation, and
+//! rotation.
+
+use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use berth_runtime::sanitize_log_line;
+
+/// Lines held for a slow terminal before the oldest are dropped.
+pub const FOLLOW_BUFFER_LINES: usize = 1_000;
+
+/// How often the reader checks the log file for appended bytes.
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Bounded line queue that drops the oldest lines and coalesces repeats.
+#[derive(Debug)]
+pub struct LineBuffer {
+    lines: VecDeque<(String, u64)>,
+    capacity: usize,
+    skipped: u64,
+}
+
+impl LineBuffer {
+    /// Creates a buffer holding at most `capacity` distinct lines.
+    pub fn new(capacity: usize) -> Self {
+        LineBuffer {
+            lines: VecDeque::new(),
+            capacity: capacity.max(1),
+            skipped: 0,
+        }
+    }
+
+    /// Queues a line, merging it into the previous one when identical.
+    pub fn push(&mut self, line: String) {
+        if let Some((last, repeats)) = self.lines.back_mut() {
+            if *last == line {
+                *repeats += 1;
+                return;
+            }
+        }
+        if self.lines.len() == self.capacity {
+            if let Some((_, repeats)) = self.lines.pop_front() {
+                self.skipped += repeats;
+            }
+        }
+        self.lines.push_back((line, 1));
+    }
+
+    /// Returns whether nothing is waiting to be written.
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty() && self.skipped == 0
+    }
+
+    /// Takes queued output, leading with a skip marker when lines were dropped.
+    pub fn drain(&mut self) -> Vec<String> {
+        let mut out = Vec::with_capacity(self.lines.len() + 1);
+        if self.skipped > 0 {
+            out.push(format!("… {} lines skipped", self.skipped));
+            self.skipped = 0;
+        }
+        out.extend(self.lines.drain(..).map(|(line, repeats)| {
+            if repeats > 1 {
+                format!("{line} (repeated {repeats} times)")
+            } else {
+                line
+            }
+        }));
+        out
+    }
+}
+
+/// Incremental reader for lines appended to a file.
+///
+/// The open file is kept between polls, so when the log is rotated (renamed
+/// and recreated) the rest of the old file is read before switching to the
+/// new one, and no lines are lost across the rotation.
+#[derive(Debug)]
+pub struct LogTail {
+    path: PathBuf,
+    file: Option<File>,
+    offset: u64,
+    partial: Vec<u8>,
+}
+
+impl LogTail {
+    /// Starts reading at the current end of `path` (or its start if missing).
+    pub fn from_end(path: &Path) -> Self {
+        let file = File::open(path).ok();
+        LogTail {
+            path: path.to_path_buf(),
+            offset: file
+                .as_ref()
+                .and_then(|f| f.metadata().ok())
+                .map_or(0, |m| m.len()),
+            file,
+            partial: Vec::new(),
+        }
+    }
+
+    /// Returns complete lines appended since the last poll.
+    pub fn poll(&mut self) -> io::Result<Vec<String>> {
+        let bytes = self.poll_raw()?;
+        self.partial.extend_from_slice(&bytes);
+
+        let mut lines = Vec::new();
+        while let Some(pos) = self.partial.iter().position(|b| *b == b'\n') {
+            let raw: Vec<u8> = self.partial.drain(..=pos).collect();
+            lines.push(sanitize_log_line(&raw[..raw.len() - 1]));
+        }
+        Ok(lines)
+    }
+
+    /// Returns the bytes appended since the last poll.
+    ///
+    /// A file shorter than the last offset was truncated in place, so reading
+    /// restarts from its beginning. A file replaced at the path is drained
+    /// first and then followed from the start of its replacement.
+    pub fn poll_raw(&mut self) -> io::Result<Vec<u8>> {
+        let mut bytes = match self.file.take() {
+            Some(file) => self.read_new(file)?,
+            None => Vec::new(),
+        };
+        let current = match fs::metadata(&self.path) {
+            Ok(meta) => meta,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(bytes),
+            Err(e) => return Err(e),
+        };
+        let replaced = match &self.file {
+            Some(file) => !same_file(&file.metadata()?, &current),
+            None => true,
+        };
+        if replaced {
+            if self.file.is_some() {
+                self.offset = 0;
+            }
+            bytes.extend(self.read_new(File::open(&self.path)?)?);
+        }
+        Ok(bytes)
+    }
+
+    /// Reads `file` from the last offset and keeps it open for the next poll.
+    fn read_new(&mut self, mut file: File) -> io::Result<Vec<u8>> {
+        let len = file.metadata()?.len();
+        if len < self.offset {
+            self.offset = 0;
+            self.partial.clear();
+        }
+        let mut bytes = Vec::new();
+        if len > self.offset {
+            file.seek(SeekFrom::Start(self.offset))?;
+            file.read_to_end(&mut bytes)?;
+            self.offset += bytes.len() as u64;
+        }
+        self.file = Some(file);
+        Ok(bytes)
+    }
+}
+
+/// Returns whether two metadata records describe the same file.
+#[cfg(unix)]
+fn same_file(a: &fs::Metadata, b: &fs::Metadata) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    a.dev() == b.dev() && a.ino() == b.ino()
+}
+
+/// Returns whether two metadata records describe the same file.
+#[cfg(not(unix))]
+fn same_file(a: &fs::Metadata, b: &fs::Metadata) -> bool {
+    match (a.created(), b.created()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => true,
+    }
+}
+
+/// Follows `tail` until writing to `out` fails, buffering at most `capacity` lines.
+///
+/// Callers create the tail before announcing the stream so lines appended in
+/// between are not missed. A closed pipe (e.g. `berth logs --follow | head`)
+/// ends the stream cleanly.
+pub fn follow(mut tail: LogTail, capacity: usize, mut out: impl Write) -> io::Result<()> {
+    let shared = Arc::new((Mutex::new(LineBuffer::new(capacity)), Condvar::new()));
+    let reader_shared = Arc::clone(&shared);
+    thread::spawn(move || loop {
+        if let Ok(lines) = tail.poll() {
+            if !lines.is_empty() {
+                let (buffer, ready) = &*reader_shared;
+                let mut buffer = buffer.lock().unwrap_or_else(|e| e.into_inner());
+                for line in lines {
+                    buffer.push(line);
+                }
+                ready.notify_one();
+            }
+        }
+        thread::sleep(POLL_INTERVAL);
+    });
+
+    let (buffer, ready) = &*shared;
+    loop {
+        let batch = {
+            let mut guard = buffer.lock().unwrap_or_else(|e| e.into_inner());
+            while guard.is_empty() {
+                guard = ready.wait(guard).unwrap_or_else(|e| e.into_inner());
+            }
+            guard.drain()
+        };
+        let written = batch
+            .iter()
+            .try_for_each(|line| writeln!(out, "  {line}"))
+            .and_then(|()| out.flush());
+        match written {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => return Ok(()),
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Copies bytes appended to `tail` to `out` unchanged until writing fails.
+///
+/// Unlike [`follow`] nothing is dropped, so a slow reader slows the copy.
+pub fn follow_raw(mut tail: LogTail, mut out: impl Write) -> io::Result<()> {
+    loop {
+        let bytes = tail.poll_raw()?;
+        let written = if bytes.is_empty() {
+            Ok(())
+        } else {
+            out.write_all(&bytes).and_then(|()| out.flush())
+        };
+        match written {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => return Ok(()),
+            Err(e) => return Err(e),
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_buffer_drops_oldest_and_reports_skips() {
+        let mut buffer = LineBuffer::new(2);
+        for i in 0..5 {
+            buffer.push(format!("line {i}"));
+        }
+        assert_eq!(
+            buffer.drain(),
+            vec!["… 3 lines skipped", "line 3", "line 4"]
+        );
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn line_buffer_coalesces_repeated_lines() {
+        let mut buffer = LineBuffer::new(2);
+        for line in ["ping", "ping", "ping", "pong"] {
+            buffer.push(line.to_string());
+        }
+        buffer.push("done".to_string());
+        assert_eq!(buffer.drain(), vec!["… 3 lines skipped", "pong", "done"]);
+
+        buffer.push("ping".to_string());
+        buffer.push("ping".to_string());
+        assert_eq!(buffer.drain(), vec!["ping (repeated 2 times)"]);
+    }
+
+    #[test]
+    fn log_tail_reads_appended_lines_and_restarts_after_truncation() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("server.log");
+        fs::write(&path, "old\n").unwrap();
+        let mut tail = LogTail::from_end(&path);
+        assert!(tail.poll().unwrap().is_empty());
+
+        let mut file = fs::OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"first\nsec").unwrap();
+        assert_eq!(tail.poll().unwrap(), vec!["first"]);
+        file.write_all(b"ond\r\n\xff\x1b\n").unwrap();
+        assert_eq!(tail.poll().unwrap(), vec!["second", "\u{fffd}\u{fffd}"]);
+
+        fs::write(&path, "new\n").unwrap();
+        assert_eq!(tail.poll().unwrap(), vec!["new"]);
+    }
+
+    #[test]
+    fn log_tail_drains_rotated_file_before_following_the_new_one() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("server.log");
+        fs::write(&path, "old\n").unwrap();
+        let mut tail = LogTail::from_end(&path);
+
+        let mut file = fs::OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"before rotation\n").unwrap();
+        fs::rename(&path, tmp.path().join("server.log.1")).unwrap();
+        // The server keeps writing to the rotated file until it reopens its log.
+        file.write_all(b"late write\n").unwrap();
+        fs::write(&path, "after rotation, and longer than the old file\n").unwrap();
+
+        assert_eq!(
+            tail.poll().unwrap(),
+            vec![
+                "before rotation",
+                "late write",
+                "after rotation, and longer than the old file"
+            ]
+        );
+        fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(b"next\n")
+            .unwrap();
+        assert_eq!(tail.poll().unwrap(), vec!["next"]);
+    }
+}
@@ -0,0 +1,60 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Internal diagnostics logging setup (`--verbose` and `BERTH_LOG`).
+
+use tracing_subscriber::EnvFilter;
+
+/// Environment variable holding a `tracing` env-filter directive string.
+pub const LOG_ENV: &str = "BERTH_LOG";
+
+/// Installs a stderr `tracing` subscriber for Berth internals.
+///
+/// `BERTH_LOG` (e.g. `berth_runtime=debug`) takes precedence; otherwise the
+/// `--verbose` count selects a level for all Berth crates.
+pub fn init(verbosity: u8) {
+    let directives = std::env::var(LOG_ENV)
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+        .unwrap_or_else(|| default_directives(verbosity));
+    let filter =
+        EnvFilter::try_new(&directives).unwrap_or_else(|_| EnvFilter::new(default_directives(0)));
+
+    let _ = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr)
+        .with_target(true)
+        .without_time()
+        .try_init();
+}
+
+/// Maps `--verbose` occurrences to filter directives for Berth crates.
+fn default_directives(verbosity: u8) -> String {
+    let level = match verbosity {
+        0 => "warn",
+        1 => "info",
+        2 => "debug",
+        _ => "trace",
+    };
+    format!("warn,berth={level},berth_runtime={level},berth_registry={level}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_directives_map_verbosity_to_levels() {
+        assert!(default_directives(0).contains("berth_runtime=warn"));
+        assert!(default_directives(1).contains("berth_runtime=info"));
+        assert!(default_directives(2).contains("berth=debug"));
+        assert!(default_directives(7).contains("berth_registry=trace"));
+    }
+
+    #[test]
+    fn default_directives_parse_as_env_filter() {
+        for verbosity in 0..4 {
+            assert!(EnvFilter::try_new(default_directives(verbosity)).is_ok());
+        }
+    }
+}
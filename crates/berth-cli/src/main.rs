@@ -3,28 +3,142 @@
 
 //! Berth CLI binary entrypoint.
 
+pub mod builtin_runtime;
+pub mod client_backups;
+pub mod client_compat;
 mod commands;
+pub mod credential_expiry;
+pub mod daemon;
+pub mod dependency_policy;
+pub mod disk_usage;
+pub mod dry_run;
+pub mod egress_allowlist;
+pub mod egress_monitor;
+pub mod env_isolation;
+pub mod error_budget;
+pub mod framing;
+pub mod hints;
+mod install_hooks;
+pub mod job_policy;
+pub mod jsonc;
+pub mod limits_policy;
+pub mod log_stream;
+pub mod logging;
+pub mod mcp_proxy;
+pub mod migrations;
+pub mod oauth;
+pub mod observer;
 pub mod paths;
 pub mod permission_filter;
+pub mod permission_prompt;
+pub mod platform;
+pub mod plugins;
 pub mod policy_engine;
+mod policy_scenarios;
+pub mod profiles;
+pub mod progress;
+pub mod proxy_policy;
+pub mod publish_queue;
+pub mod readiness_policy;
+pub mod receipts;
+pub mod replica_policy;
 pub mod runtime_policy;
 pub mod sandbox_policy;
 pub mod sandbox_runtime;
 pub mod secrets;
+pub mod shell_preview;
+pub mod socket_transport;
+pub mod suggest;
+pub mod time_format;
+pub mod warnings;
+pub mod wasm_runtime;
+pub mod workspace_trust;
 
-use clap::Parser;
+use clap::error::{ContextKind, ContextValue, ErrorKind};
+use clap::{CommandFactory, FromArgMatches, Parser};
+use colored::Colorize;
 use commands::Commands;
 
 /// Berth — The safe runtime & package manager for MCP servers
 #[derive(Parser)]
 #[command(name = "berth", version, about, long_about = None)]
 struct Cli {
+    /// Increase internal diagnostics on stderr (-v info, -vv debug, -vvv trace)
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    verbose: u8,
+
+    /// Use this directory as the Berth home for config, state, and cache
+    #[arg(long, global = true, value_name = "PATH", conflicts_with = "profile")]
+    home: Option<String>,
+
+    /// Use the Berth home of a profile named in profiles.toml
+    #[arg(long, global = true, value_name = "NAME")]
+    profile: Option<String>,
+
+    /// Trust a remote registry index signed by a key other than the pinned one, and pin the new key
+    #[arg(long, global = true)]
+    accept_new_key: bool,
+
+    /// Read-only mode: inspect status, logs, and audit data but refuse commands that change anything
+    #[arg(long, global = true)]
+    observer: bool,
+
     #[command(subcommand)]
     command: Commands,
 }
 
 /// Parses CLI arguments and dispatches to command handlers.
 fn main() {
-    let cli = Cli::parse();
+    platform::init_console();
+    let matches = Cli::command()
+        .try_get_matches()
+        .unwrap_or_else(|err| exit_with_parse_error(err));
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|err| exit_with_parse_error(err));
+    logging::init(cli.verbose);
+    // Read before `BERTH_HOME` is pinned, which moves where `profiles.toml` is looked up.
+    let observer = match profiles::selected_observer(cli.profile.as_deref()) {
+        Ok(profile_observer) => cli.observer || profile_observer || observer::enabled(),
+        Err(msg) => {
+            eprintln!("{} {}", "✗".red().bold(), msg);
+            std::process::exit(1);
+        }
+    };
+    match profiles::selected_home(cli.home.as_deref(), cli.profile.as_deref()) {
+        // Pinned for the whole process so spawned supervisors and proxies inherit it.
+        Ok(Some(home)) => std::env::set_var("BERTH_HOME", home),
+        Ok(None) => {}
+        Err(msg) => {
+            eprintln!("{} {}", "✗".red().bold(), msg);
+            std::process::exit(1);
+        }
+    }
+    // Process-local so the choice never reaches spawned servers.
+    berth_registry::signing::set_accept_new_key(cli.accept_new_key);
+    if observer {
+        std::env::set_var(observer::OBSERVER_ENV, "1");
+        if cli.command.mutates() {
+            observer::refuse(matches.subcommand_name().unwrap_or_default());
+        }
+    }
     commands::execute(cli.command);
+    plugins::flush();
+}
+
+/// Reports an argument error, suggesting the nearest command for unknown ones.
+fn exit_with_parse_error(err: clap::Error) -> ! {
+    let unknown = match err.get(ContextKind::InvalidSubcommand) {
+        Some(ContextValue::String(name)) if err.kind() == ErrorKind::InvalidSubcommand => name,
+        _ => err.exit(),
+    };
+    let cli = Cli::command();
+    let commands = cli
+        .get_subcommands()
+        .filter(|c| !c.is_hide_set())
+        .map(|c| c.get_name());
+    eprintln!("{} Unknown command '{}'.", "✗".red().bold(), unknown);
+    match suggest::similar(unknown, commands).first() {
+        Some(nearest) => eprintln!("  Did you mean {}?", format!("berth {nearest}").bold()),
+        None => eprintln!("  Run {} to list commands.", "berth --help".bold()),
+    }
+    std::process::exit(2);
 }
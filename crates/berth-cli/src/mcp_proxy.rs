@@ -0,0 +1,1595 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Message-level stdio relay between an MCP client and an upstream server.
+//!
+//! Messages are JSON-RPC, framed per side as newline-delimited JSON or with
+//! `Content-Length` headers (see [`crate::framing`]). Lines that are not JSON
+//! are relayed verbatim so non-conforming servers keep working through the proxy.
+
+use serde_json::{json, Value};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::process::{Child, ChildStdin, ExitStatus};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, TryRecvError};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use berth_common::redact::{is_sensitive_name, redact_url_credentials, REDACTED};
+use berth_runtime::exit_code;
+
+use crate::client_compat::{effective_client_revision, shim_server_message, CompatMode};
+use crate::framing::{read_message, Detected, FramedWriter, Framing};
+
+/// JSON-RPC internal error code used for requests the proxy could not complete.
+const INTERNAL_ERROR_CODE: i64 = -32603;
+
+/// JSON-RPC error code for messages that are not a single request object.
+const INVALID_REQUEST_CODE: i64 = -32600;
+
+/// JSON-RPC server error code for tool calls the permission gate rejects.
+const PERMISSION_DENIED_CODE: i64 = -32001;
+
+/// How often the relay loop checks queued requests for timeouts.
+const QUEUE_TICK: Duration = Duration::from_millis(50);
+
+/// How long a replaced upstream may keep answering requests it received before
+/// a blue/green switch.
+const DRAIN_WAIT: Duration = Duration::from_secs(30);
+
+/// Longest argument value kept in an audit summary, in characters.
+const ARGUMENT_SUMMARY_CHARS: usize = 64;
+
+/// One audited client request observed by the proxy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditedRequest {
+    pub action: &'static str,
+    pub target: Option<String>,
+    /// Details recorded after the target: the redacted `key=value` summary of
+    /// the request arguments, or the reason a request was denied.
+    pub arguments: Vec<String>,
+    /// Latency and result of a completed request (`mcp-*-result` only).
+    pub outcome: Option<CallOutcome>,
+}
+
+/// How a request forwarded upstream completed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CallOutcome {
+    pub duration_ms: u64,
+    /// The server answered with a JSON-RPC error or a result flagged `isError`.
+    pub error: bool,
+}
+
+/// Upstream restart behavior when the server exits while the client is connected.
+#[derive(Debug, Clone)]
+pub struct RestartPolicy {
+    pub max_restarts: u32,
+    /// Exit codes that warrant a restart; `None` restarts after any exit.
+    pub restart_on_exit_codes: Option<Vec<i32>>,
+    /// Bounded wait for a restarted server to answer the replayed `initialize`.
+    pub wait: Duration,
+}
+
+/// Per-connection protocol state tracked by the proxy.
+#[derive(Debug)]
+pub struct ProxySession {
+    subscriptions: BTreeSet<String>,
+    compat: CompatMode,
+    client_protocol: Option<String>,
+    server_protocol: Option<String>,
+    initialize: Option<(Vec<u8>, Value)>,
+    initialized: Option<Vec<u8>>,
+    pending: BTreeMap<String, Value>,
+    /// Audited requests sent upstream, keyed like `pending`, with their
+    /// result action, target, and start time.
+    calls: BTreeMap<String, (&'static str, String, Instant)>,
+    internal: BTreeSet<String>,
+    next_internal_id: u64,
+}
+
+impl Default for ProxySession {
+    fn default() -> Self {
+        ProxySession::new(CompatMode::Off)
+    }
+}
+
+impl ProxySession {
+    /// Creates a session applying the given client compatibility mode.
+    pub fn new(compat: CompatMode) -> Self {
+        ProxySession {
+            subscriptions: BTreeSet::new(),
+            compat,
+            client_protocol: None,
+            server_protocol: None,
+            initialize: None,
+            initialized: None,
+            pending: BTreeMap::new(),
+            calls: BTreeMap::new(),
+            internal: BTreeSet::new(),
+            next_internal_id: 0,
+        }
+    }
+
+    /// Inspects one client->server message, updating session state.
+    pub fn observe_client(&mut self, line: &[u8]) -> Option<AuditedRequest> {
+        let message = parse_message(line)?;
+        let method = message.get("method")?.as_str()?;
+        let params = message.get("params");
+        let uri = params
+            .and_then(|p| p.get("uri"))
+            .and_then(Value::as_str)
+            .map(ToString::to_string);
+
+        match method {
+            "initialize" => {
+                self.client_protocol = params
+                    .and_then(|p| p.get("protocolVersion"))
+                    .and_then(Value::as_str)
+                    .map(ToString::to_string);
+                if let Some(id) = message.get("id") {
+                    self.initialize = Some((line.to_vec(), id.clone()));
+                }
+            }
+            "notifications/initialized" => self.initialized = Some(line.to_vec()),
+            "resources/subscribe" => {
+                if let Some(uri) = &uri {
+                    self.subscriptions.insert(uri.clone());
+                }
+            }
+            "resources/unsubscribe" => {
+                if let Some(uri) = &uri {
+                    self.subscriptions.remove(uri);
+                }
+            }
+            _ => {}
+        }
+
+        let action = audit_action(method)?;
+        let target = match method {
+            "tools/call" | "prompts/get" => params
+                .and_then(|p| p.get("name"))
+                .and_then(Value::as_str)
+                .map(ToString::to_string),
+            _ => uri,
+        };
+        let arguments = match method {
+            "tools/call" | "prompts/get" => {
+                summarize_arguments(params.and_then(|p| p.get("arguments")))
+            }
+            _ => Vec::new(),
+        };
+        Some(AuditedRequest {
+            action,
+            target,
+            arguments,
+            outcome: None,
+        })
+    }
+
+    /// Records a client message as delivered upstream, tracking requests awaiting a response.
+    pub fn mark_sent(&mut self, line: &[u8]) {
+        let Some(message) = parse_message(line) else {
+            return;
+        };
+        let (Some(method), Some(id)) = (
+            message.get("method").and_then(Value::as_str),
+            message.get("id"),
+        ) else {
+            return;
+        };
+        let key = id.to_string();
+        if let Some(action) = result_action(method) {
+            let field = if method == "resources/read" {
+                "uri"
+            } else {
+                "name"
+            };
+            let target = message
+                .get("params")
+                .and_then(|p| p.get(field))
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            self.calls
+                .insert(key.clone(), (action, target.to_string(), Instant::now()));
+        }
+        self.pending.insert(key, id.clone());
+    }
+
+    /// Returns an `mcp-*-result` audit record when a server message answers an
+    /// audited request.
+    pub fn complete_call(&mut self, line: &[u8]) -> Option<AuditedRequest> {
+        let message = parse_message(line)?;
+        if message.get("method").is_some() {
+            return None;
+        }
+        let (action, target, started) = self.calls.remove(&message.get("id")?.to_string())?;
+        let error = message.get("error").is_some()
+            || message
+                .get("result")
+                .and_then(|r| r.get("isError"))
+                .and_then(Value::as_bool)
+                .unwrap_or(false);
+        Some(AuditedRequest {
+            action,
+            target: Some(target),
+            arguments: Vec::new(),
+            outcome: Some(CallOutcome {
+                duration_ms: started.elapsed().as_millis() as u64,
+                error,
+            }),
+        })
+    }
+
+    /// Returns the server->client bytes to deliver, or `None` to drop the message.
+    ///
+    /// Resource update notifications are only delivered for URIs the client
+    /// subscribed to through this session. Responses to proxy-internal replay
+    /// requests are consumed. Results are shimmed for the client's protocol
+    /// revision unless compatibility is off.
+    pub fn transform_server<'a>(&mut self, line: &'a [u8]) -> Option<Cow<'a, [u8]>> {
+        let Some(mut message) = parse_message(line) else {
+            return Some(Cow::Borrowed(line));
+        };
+        if message.get("method").and_then(Value::as_str) == Some("notifications/resources/updated")
+        {
+            let subscribed = message
+                .get("params")
+                .and_then(|p| p.get("uri"))
+                .and_then(Value::as_str)
+                .is_some_and(|uri| self.subscriptions.contains(uri));
+            return subscribed.then_some(Cow::Borrowed(line));
+        }
+        if message.get("method").is_none() {
+            if let Some(id) = message.get("id") {
+                let key = id.to_string();
+                if self.internal.remove(&key) {
+                    return None;
+                }
+                self.pending.remove(&key);
+            }
+        }
+
+        if let Some(version) = message
+            .get("result")
+            .filter(|r| r.get("serverInfo").is_some())
+            .and_then(|r| r.get("protocolVersion"))
+            .and_then(Value::as_str)
+        {
+            self.server_protocol = Some(version.to_string());
+        }
+
+        let Some(client_rev) =
+            effective_client_revision(&self.compat, self.client_protocol.as_deref())
+        else {
+            return Some(Cow::Borrowed(line));
+        };
+        if !shim_server_message(&mut message, &client_rev, self.server_protocol.as_deref()) {
+            return Some(Cow::Borrowed(line));
+        }
+        tracing::debug!(client_rev, "shimmed server message for client");
+        let mut rewritten = serde_json::to_vec(&message).ok()?;
+        rewritten.push(b'\n');
+        Some(Cow::Owned(rewritten))
+    }
+
+    /// Returns the number of client requests awaiting an upstream response.
+    pub fn in_flight(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Returns currently active resource subscriptions.
+    pub fn subscriptions(&self) -> &BTreeSet<String> {
+        &self.subscriptions
+    }
+
+    /// Returns the keys of client requests awaiting an upstream response.
+    pub fn pending_keys(&self) -> BTreeSet<String> {
+        self.pending.keys().cloned().collect()
+    }
+
+    /// Returns whether the client request with `key` still awaits a response.
+    pub fn is_pending(&self, key: &str) -> bool {
+        self.pending.contains_key(key)
+    }
+
+    /// Returns error responses for the given pending requests, forgetting them.
+    pub fn interrupt(&mut self, keys: &BTreeSet<String>) -> Vec<Vec<u8>> {
+        keys.iter()
+            .filter_map(|key| {
+                self.calls.remove(key);
+                self.pending.remove(key)
+            })
+            .map(|id| error_response(&id, "Upstream server replaced; request interrupted"))
+            .collect()
+    }
+
+    /// Stops waiting for a proxy-internal request whose upstream was abandoned.
+    pub fn forget_internal(&mut self, key: &str) {
+        self.internal.remove(key);
+    }
+
+    /// Returns error responses for requests the crashed upstream never answered.
+    pub fn interrupt_pending(&mut self) -> Vec<Vec<u8>> {
+        let ids: Vec<Value> = std::mem::take(&mut self.pending).into_values().collect();
+        self.calls.clear();
+        self.internal.clear();
+        ids.into_iter()
+            .map(|id| error_response(&id, "Upstream server restarted; request interrupted"))
+            .collect()
+    }
+
+    /// Returns the client's `initialize` request to replay, marking its response internal.
+    pub fn replay_initialize(&mut self) -> Option<(Vec<u8>, String)> {
+        let (line, id) = self.initialize.clone()?;
+        let key = id.to_string();
+        self.internal.insert(key.clone());
+        Some((line, key))
+    }
+
+    /// Returns whether a proxy-internal request is still awaiting its response.
+    pub fn awaiting(&self, key: &str) -> bool {
+        self.internal.contains(key)
+    }
+
+    /// Returns messages that restore session state after a replayed `initialize`.
+    pub fn replay_after_initialize(&mut self) -> Vec<Vec<u8>> {
+        let mut lines: Vec<Vec<u8>> = self.initialized.iter().cloned().collect();
+        let uris: Vec<String> = self.subscriptions.iter().cloned().collect();
+        for uri in uris {
+            self.next_internal_id += 1;
+            let id = Value::String(format!("berth-resubscribe-{}", self.next_internal_id));
+            self.internal.insert(id.to_string());
+            lines.push(message_line(&json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "method": "resources/subscribe",
+                "params": {"uri": uri},
+            })));
+        }
+        lines
+    }
+}
+
+/// Maps an MCP request method to its audit action name.
+pub fn audit_action(method: &str) -> Option<&'static str> {
+    match method {
+        "tools/call" => Some("mcp-tool-call"),
+        "resources/read" => Some("mcp-resource-read"),
+        "resources/subscribe" => Some("mcp-resource-subscribe"),
+        "resources/unsubscribe" => Some("mcp-resource-unsubscribe"),
+        "prompts/get" => Some("mcp-prompt-get"),
+        _ => None,
+    }
+}
+
+/// Maps an MCP request method to the audit action recording its response.
+pub fn result_action(method: &str) -> Option<&'static str> {
+    match method {
+        "tools/call" => Some("mcp-tool-result"),
+        "resources/read" => Some("mcp-resource-result"),
+        "prompts/get" => Some("mcp-prompt-result"),
+        _ => None,
+    }
+}
+
+/// Summarizes request arguments as `key=value` pairs for the audit log.
+///
+/// Values of credential-like keys and URL credentials are redacted, long
+/// strings are shortened, and nested values are reduced to their size so
+/// secrets inside them never reach the log.
+fn summarize_arguments(arguments: Option<&Value>) -> Vec<String> {
+    let Some(Value::Object(arguments)) = arguments else {
+        return Vec::new();
+    };
+    arguments
+        .iter()
+        .map(|(key, value)| {
+            let value = if is_sensitive_name(key) {
+                REDACTED.to_string()
+            } else {
+                match value {
+                    Value::String(text) => {
+                        let text = redact_url_credentials(text);
+                        if text.chars().count() > ARGUMENT_SUMMARY_CHARS {
+                            let kept: String = text.chars().take(ARGUMENT_SUMMARY_CHARS).collect();
+                            format!("{kept}…")
+                        } else {
+                            text
+                        }
+                    }
+                    Value::Array(items) => format!("[{} items]", items.len()),
+                    Value::Object(fields) => format!("{{{} keys}}", fields.len()),
+                    other => other.to_string(),
+                }
+            };
+            format!("{key}={value}")
+        })
+        .collect()
+}
+
+/// Upstream concurrency limit for client requests.
+#[derive(Debug, Clone, Copy)]
+pub struct InFlightLimit {
+    pub max_in_flight: usize,
+    /// How long a queued request may wait for a free slot before failing.
+    pub queue_timeout: Duration,
+}
+
+/// Relay behavior configured per server.
+#[derive(Debug, Clone)]
+pub struct RelayOptions {
+    pub compat: CompatMode,
+    pub restart: Option<RestartPolicy>,
+    pub limit: Option<InFlightLimit>,
+    /// Blue/green upgrades: how long a new upstream gets to answer the replayed
+    /// `initialize` before the switch is abandoned. `None` disables upgrades.
+    pub upgrade_wait: Option<Duration>,
+    /// Framing towards the client.
+    pub client_framing: Framing,
+    /// Framing towards the upstream server.
+    pub server_framing: Framing,
+    /// Client bytes read before relaying began, e.g. the `initialize` used to
+    /// identify the client; relayed ahead of the rest of stdin.
+    pub client_prelude: Vec<u8>,
+}
+
+/// Configured framing per side and the framing each side was seen sending.
+#[derive(Debug, Clone)]
+struct Framings {
+    client: Framing,
+    server: Framing,
+    client_seen: Detected,
+    server_seen: Detected,
+}
+
+impl Framings {
+    fn new(options: &RelayOptions) -> Self {
+        Framings {
+            client: options.client_framing,
+            server: options.server_framing,
+            client_seen: Detected::default(),
+            server_seen: Detected::default(),
+        }
+    }
+
+    fn to_client<W: Write>(&self, out: W) -> FramedWriter<W> {
+        FramedWriter::new(out, self.client, self.client_seen.clone())
+    }
+
+    /// An `auto` server is addressed in the client's framing until it answers.
+    fn to_server(&self, stdin: ChildStdin) -> FramedWriter<ChildStdin> {
+        FramedWriter::new(stdin, self.server, self.server_seen.clone())
+            .with_fallback(self.client_seen.clone())
+    }
+}
+
+/// Audit sink invoked with the current upstream pid.
+type AuditSink = Box<dyn Fn(&AuditedRequest, u32) + Send>;
+
+/// A replaced upstream finishing the requests it received before a switch.
+struct Draining {
+    child: Child,
+    lines: Receiver<Vec<u8>>,
+    keys: BTreeSet<String>,
+    deadline: Instant,
+}
+
+/// A client request waiting for an upstream slot.
+struct QueuedRequest {
+    line: Vec<u8>,
+    id: Value,
+    method: Option<String>,
+    since: Instant,
+}
+
+/// Client-facing state shared between the client reader and the relay loop.
+struct Shared {
+    session: ProxySession,
+    upstream: Option<FramedWriter<ChildStdin>>,
+    buffered: Vec<Vec<u8>>,
+    queued: VecDeque<QueuedRequest>,
+    limit: Option<InFlightLimit>,
+    client_closed: bool,
+    pid: u32,
+    audit: AuditSink,
+    /// Proxy-generated responses waiting to be written to the client.
+    replies: Vec<Vec<u8>>,
+}
+
+impl Shared {
+    fn new(
+        session: ProxySession,
+        limit: Option<InFlightLimit>,
+        pid: u32,
+        audit: AuditSink,
+    ) -> Self {
+        Shared {
+            session,
+            upstream: None,
+            buffered: Vec::new(),
+            queued: VecDeque::new(),
+            limit,
+            client_closed: false,
+            pid,
+            audit,
+            replies: Vec::new(),
+        }
+    }
+
+    fn audit(&self, request: &AuditedRequest) {
+        (self.audit)(request, self.pid);
+    }
+
+    /// Answers a JSON-RPC batch with an invalid-request error instead of
+    /// forwarding it, since the calls inside would bypass the gate and the
+    /// audit trail; MCP does not use batches. Returns whether `line` was one.
+    fn reject_batch(&mut self, line: &[u8]) -> bool {
+        if !is_batch(line) {
+            return false;
+        }
+        tracing::info!("rejected JSON-RPC batch from client");
+        self.replies.push(coded_error_response(
+            &Value::Null,
+            INVALID_REQUEST_CODE,
+            "Batched JSON-RPC requests are not supported; send one request per message.",
+        ));
+        true
+    }
+
+    /// Sends a client message on once the gate has ruled on it; a denial is
+    /// audited and answered with a JSON-RPC error instead of reaching the
+    /// upstream.
+    fn forward_client(
+        &mut self,
+        line: &[u8],
+        request: Option<&AuditedRequest>,
+        verdict: Result<(), String>,
+    ) {
+        let Some(request) = request else {
+            self.send(line);
+            return;
+        };
+        if let Err(reason) = verdict {
+            tracing::info!(tool = ?request.target, %reason, "denied tool call");
+            self.audit(&AuditedRequest {
+                action: "permission-denied",
+                target: request.target.clone(),
+                arguments: vec![reason.clone()],
+                outcome: None,
+            });
+            if let Some(id) = request_id(line) {
+                self.replies
+                    .push(coded_error_response(&id, PERMISSION_DENIED_CODE, &reason));
+            }
+            return;
+        }
+        self.audit(request);
+        self.send(line);
+    }
+
+    /// Writes proxy-generated responses to the client.
+    fn write_replies<W: Write>(&mut self, out: &mut W) -> io::Result<()> {
+        if self.replies.is_empty() {
+            return Ok(());
+        }
+        for reply in std::mem::take(&mut self.replies) {
+            out.write_all(&reply)?;
+        }
+        out.flush()
+    }
+
+    /// Sends a client message upstream, queueing requests while the in-flight limit is reached.
+    fn send(&mut self, line: &[u8]) {
+        if let (Some(limit), Some(id)) = (self.limit, request_id(line)) {
+            if self.session.in_flight() >= limit.max_in_flight || !self.queued.is_empty() {
+                let method = parse_message(line)
+                    .and_then(|m| m.get("method").and_then(Value::as_str).map(String::from));
+                tracing::debug!(method = ?method, "queued request at in-flight limit");
+                self.audit(&AuditedRequest {
+                    action: "mcp-request-queued",
+                    target: method.clone(),
+                    arguments: Vec::new(),
+                    outcome: None,
+                });
+                self.queued.push_back(QueuedRequest {
+                    line: line.to_vec(),
+                    id,
+                    method,
+                    since: Instant::now(),
+                });
+                return;
+            }
+        }
+        self.deliver(line);
+    }
+
+    /// Writes a client message upstream, buffering it while no upstream is attached.
+    fn deliver(&mut self, line: &[u8]) {
+        let delivered = match self.upstream.as_mut() {
+            Some(stdin) => stdin.write_all(line).and_then(|_| stdin.flush()).is_ok(),
+            None => false,
+        };
+        if delivered {
+            self.session.mark_sent(line);
+        } else {
+            self.upstream = None;
+            self.buffered.push(line.to_vec());
+        }
+    }
+
+    /// Releases queued requests in FIFO order while upstream slots are free.
+    fn drain_queue(&mut self) {
+        let Some(limit) = self.limit else {
+            return;
+        };
+        while self.upstream.is_some() && self.session.in_flight() < limit.max_in_flight {
+            let Some(next) = self.queued.pop_front() else {
+                return;
+            };
+            self.deliver(&next.line);
+        }
+    }
+
+    /// Fails queued requests that waited longer than the queue timeout.
+    fn expire_queue<W: Write>(&mut self, now: Instant, out: &mut W) -> io::Result<()> {
+        let Some(limit) = self.limit else {
+            return Ok(());
+        };
+        while self
+            .queued
+            .front()
+            .is_some_and(|q| now.duration_since(q.since) >= limit.queue_timeout)
+        {
+            let expired = self.queued.pop_front().expect("front checked above; qed");
+            tracing::info!(method = ?expired.method, "queued request timed out");
+            self.audit(&AuditedRequest {
+                action: "mcp-request-queue-timeout",
+                target: expired.method,
+                arguments: Vec::new(),
+                outcome: None,
+            });
+            out.write_all(&error_response(
+                &expired.id,
+                "Request timed out waiting for an upstream slot",
+            ))?;
+        }
+        out.flush()
+    }
+}
+
+/// Relays client stdio to a spawned child until the session ends.
+///
+/// The child must have piped stdin/stdout. `audit` is invoked with the current
+/// upstream pid for every audited client request, queue event, and upstream
+/// restart. `gate` is asked with the tool name of every `tools/call`; denied
+/// calls never reach the upstream, are answered with a JSON-RPC error, and are
+/// audited as `permission-denied`. With an in-flight limit, client requests beyond the limit wait in a
+/// FIFO queue and fail with a JSON-RPC error after the queue timeout.
+/// With a restart policy, an upstream exit while the client is still connected
+/// is masked: client messages are buffered, `respawn` starts a replacement, the
+/// client's `initialize` handshake and subscriptions are replayed, and relaying
+/// resumes. Requests in flight at the crash receive JSON-RPC errors.
+/// With an upgrade wait, `upgrade` is polled between messages; when it yields a
+/// new upstream, the handshake is replayed to it while the current upstream keeps
+/// serving, new requests are routed to it once it answers `initialize`, and the
+/// old upstream is stopped after answering the requests it already received.
+pub fn relay<S, U, F, G>(
+    mut child: Child,
+    mut respawn: S,
+    mut upgrade: U,
+    options: RelayOptions,
+    audit: F,
+    gate: G,
+) -> io::Result<ExitStatus>
+where
+    S: FnMut() -> io::Result<Child>,
+    U: FnMut() -> Option<io::Result<Child>>,
+    F: Fn(&AuditedRequest, u32) + Send + 'static,
+    G: Fn(&str) -> Result<(), String> + Send + 'static,
+{
+    let framings = Framings::new(&options);
+    let stdin = take_stdin(&mut child, &framings)?;
+    let mut lines = spawn_line_reader(&mut child, &framings)?;
+    let mut state = Shared::new(
+        ProxySession::new(options.compat),
+        options.limit,
+        child.id(),
+        Box::new(audit),
+    );
+    state.upstream = Some(stdin);
+    let shared = Arc::new(Mutex::new(state));
+
+    let client_shared = Arc::clone(&shared);
+    let client_seen = framings.client_seen.clone();
+    let prelude = io::Cursor::new(options.client_prelude.clone());
+    thread::spawn(move || {
+        let _ = pump_client(
+            prelude.chain(io::stdin().lock()),
+            &client_seen,
+            &client_shared,
+            &gate,
+        );
+    });
+
+    let mut out = framings.to_client(io::stdout());
+    let mut attempts = 0u32;
+    let mut draining: Option<Draining> = None;
+    loop {
+        loop {
+            match lines.recv_timeout(QUEUE_TICK) {
+                Ok(line) => {
+                    let mut state = lock(&shared)?;
+                    forward_server_line(&line, &mut state, &mut out)?;
+                    state.drain_queue();
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+            let mut state = lock(&shared)?;
+            state.expire_queue(Instant::now(), &mut out)?;
+            state.write_replies(&mut out)?;
+            drop(state);
+
+            if let Some(wait) = options.upgrade_wait {
+                if let Some(next) = upgrade() {
+                    match switch_upstream(next, &lines, &shared, &framings, wait, &mut out) {
+                        Ok((new_child, new_lines, keys)) => {
+                            if let Some(old) = draining.take() {
+                                retire(old);
+                            }
+                            draining = Some(Draining {
+                                child: std::mem::replace(&mut child, new_child),
+                                lines: std::mem::replace(&mut lines, new_lines),
+                                keys,
+                                deadline: Instant::now() + DRAIN_WAIT,
+                            });
+                            let mut state = lock(&shared)?;
+                            state.pid = child.id();
+                            state.audit(&AuditedRequest {
+                                action: "proxy-blue-green-switch",
+                                target: None,
+                                arguments: Vec::new(),
+                                outcome: None,
+                            });
+                        }
+                        Err(e) => {
+                            tracing::info!(error = %e, "blue/green upgrade abandoned");
+                            lock(&shared)?.audit(&AuditedRequest {
+                                action: "proxy-blue-green-failed",
+                                target: None,
+                                arguments: Vec::new(),
+                                outcome: None,
+                            });
+                        }
+                    }
+                }
+            }
+            if let Some(old) = draining.as_mut() {
+                if drain_upstream(old, &shared, &mut out)? {
+                    retire(draining.take().expect("checked above; qed"));
+                }
+            }
+        }
+        if let Some(old) = draining.take() {
+            retire(old);
+        }
+        let status = child.wait()?;
+
+        let mut state = lock(&shared)?;
+        state.upstream = None;
+        state.write_replies(&mut out)?;
+        for error in state.session.interrupt_pending() {
+            out.write_all(&error)?;
+        }
+        out.flush()?;
+        let code = exit_code(&status);
+        let can_restart = options.restart.as_ref().is_some_and(|policy| {
+            let failed = match (&policy.restart_on_exit_codes, code) {
+                (Some(codes), Some(code)) => codes.contains(&code),
+                _ => true,
+            };
+            failed && attempts < policy.max_restarts
+        });
+        if state.client_closed || !can_restart {
+            if !state.client_closed {
+                fail_waiting(&mut state, &mut out)?;
+            }
+            tracing::debug!(
+                subscriptions = state.session.subscriptions().len(),
+                "proxy upstream closed"
+            );
+            return Ok(status);
+        }
+        drop(state);
+
+        let policy = options
+            .restart
+            .as_ref()
+            .expect("checked can_restart above; qed");
+        attempts += 1;
+        tracing::info!(
+            attempt = attempts,
+            max = policy.max_restarts,
+            "restarting upstream"
+        );
+        thread::sleep(Duration::from_millis(100 * u64::from(attempts)));
+        match restart_upstream(&mut respawn, &shared, &framings, policy.wait, &mut out) {
+            Ok((new_child, new_lines)) => {
+                child = new_child;
+                lines = new_lines;
+                let mut state = lock(&shared)?;
+                state.pid = child.id();
+                state.audit(&AuditedRequest {
+                    action: "proxy-restart",
+                    target: None,
+                    arguments: Vec::new(),
+                    outcome: None,
+                });
+            }
+            Err(e) => {
+                tracing::info!(error = %e, "upstream restart failed");
+                let mut state = lock(&shared)?;
+                fail_waiting(&mut state, &mut out)?;
+                return Ok(status);
+            }
+        }
+    }
+}
+
+/// Spawns a replacement upstream and replays the session handshake within `wait`.
+fn restart_upstream<S, W>(
+    respawn: &mut S,
+    shared: &Mutex<Shared>,
+    framings: &Framings,
+    wait: Duration,
+    out: &mut W,
+) -> io::Result<(Child, Receiver<Vec<u8>>)>
+where
+    S: FnMut() -> io::Result<Child>,
+    W: Write,
+{
+    let mut child = respawn()?;
+    let mut stdin = take_stdin(&mut child, framings)?;
+    let lines = spawn_line_reader(&mut child, framings)?;
+
+    let replay = lock(shared)?.session.replay_initialize();
+    if let Some((init_line, key)) = replay {
+        stdin.write_all(&init_line)?;
+        stdin.flush()?;
+        let deadline = Instant::now() + wait;
+        while lock(shared)?.session.awaiting(&key) {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match lines.recv_timeout(remaining) {
+                Ok(line) => forward_server_line(&line, &mut *lock(shared)?, out)?,
+                Err(RecvTimeoutError::Timeout) => {
+                    let _ = child.kill();
+                    return Err(io::Error::new(
+                        io::ErrorKind::TimedOut,
+                        "restarted server did not answer initialize in time",
+                    ));
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    let _ = child.wait();
+                    return Err(io::Error::other("restarted server exited during replay"));
+                }
+            }
+        }
+        for line in lock(shared)?.session.replay_after_initialize() {
+            stdin.write_all(&line)?;
+        }
+        stdin.flush()?;
+    }
+
+    let mut state = lock(shared)?;
+    state.upstream = Some(stdin);
+    for line in std::mem::take(&mut state.buffered) {
+        state.send(&line);
+    }
+    state.drain_queue();
+    Ok((child, lines))
+}
+
+/// Replays the session handshake to a blue/green replacement while the current
+/// upstream keeps serving, then routes new client requests to the replacement.
+///
+/// Returns the replacement and the keys of requests still owed by the old upstream.
+fn switch_upstream<W: Write>(
+    next: io::Result<Child>,
+    current: &Receiver<Vec<u8>>,
+    shared: &Mutex<Shared>,
+    framings: &Framings,
+    wait: Duration,
+    out: &mut W,
+) -> io::Result<(Child, Receiver<Vec<u8>>, BTreeSet<String>)> {
+    let mut child = next?;
+    let mut stdin = take_stdin(&mut child, framings)?;
+    let lines = spawn_line_reader(&mut child, framings)?;
+
+    let replay = lock(shared)?.session.replay_initialize();
+    if let Some((init_line, key)) = replay {
+        stdin.write_all(&init_line)?;
+        stdin.flush()?;
+        if let Err(e) = wait_for_initialize(&key, &lines, current, shared, wait, out) {
+            lock(shared)?.session.forget_internal(&key);
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(e);
+        }
+        for line in lock(shared)?.session.replay_after_initialize() {
+            stdin.write_all(&line)?;
+        }
+        stdin.flush()?;
+    }
+
+    let mut state = lock(shared)?;
+    state.upstream = Some(stdin);
+    let keys = state.session.pending_keys();
+    state.drain_queue();
+    Ok((child, lines, keys))
+}
+
+/// Waits for a replacement to answer the replayed `initialize`, relaying the current upstream meanwhile.
+fn wait_for_initialize<W: Write>(
+    key: &str,
+    lines: &Receiver<Vec<u8>>,
+    current: &Receiver<Vec<u8>>,
+    shared: &Mutex<Shared>,
+    wait: Duration,
+    out: &mut W,
+) -> io::Result<()> {
+    let deadline = Instant::now() + wait;
+    while lock(shared)?.session.awaiting(key) {
+        while let Ok(line) = current.try_recv() {
+            let mut state = lock(shared)?;
+            forward_server_line(&line, &mut state, out)?;
+            state.drain_queue();
+        }
+        if Instant::now() >= deadline {
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "new upstream did not answer initialize in time",
+            ));
+        }
+        match lines.recv_timeout(QUEUE_TICK) {
+            Ok(line) => forward_server_line(&line, &mut *lock(shared)?, out)?,
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => {
+                return Err(io::Error::other(
+                    "new upstream exited during its health check",
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Relays a replaced upstream's remaining answers; returns whether it can be stopped.
+///
+/// Requests it has not answered by the drain deadline, or when it exits, receive
+/// JSON-RPC errors.
+fn drain_upstream<W: Write>(
+    old: &mut Draining,
+    shared: &Mutex<Shared>,
+    out: &mut W,
+) -> io::Result<bool> {
+    let mut exited = false;
+    loop {
+        match old.lines.try_recv() {
+            Ok(line) => forward_server_line(&line, &mut *lock(shared)?, out)?,
+            Err(TryRecvError::Empty) => break,
+            Err(TryRecvError::Disconnected) => {
+                exited = true;
+                break;
+            }
+        }
+    }
+    let mut state = lock(shared)?;
+    old.keys.retain(|key| state.session.is_pending(key));
+    if old.keys.is_empty() {
+        return Ok(true);
+    }
+    if !exited && Instant::now() < old.deadline {
+        return Ok(false);
+    }
+    for error in state.session.interrupt(&old.keys) {
+        out.write_all(&error)?;
+    }
+    out.flush()?;
+    Ok(true)
+}
+
+/// Stops a replaced upstream once it no longer owes the client any responses.
+fn retire(mut old: Draining) {
+    let _ = old.child.kill();
+    let _ = old.child.wait();
+}
+
+/// Forwards client messages upstream, auditing and tracking session state.
+///
+/// The gate runs without the session lock, so a permission prompt waiting on
+/// the user holds up only later client messages, not upstream responses and
+/// notifications.
+fn pump_client<R, G>(reader: R, seen: &Detected, shared: &Mutex<Shared>, gate: &G) -> io::Result<()>
+where
+    R: BufRead,
+    G: Fn(&str) -> Result<(), String>,
+{
+    for_each_message(reader, seen, |line| {
+        let request = {
+            let mut state = lock(shared)?;
+            if state.reject_batch(line) {
+                return Ok(());
+            }
+            state.session.observe_client(line)
+        };
+        if let Some(request) = &request {
+            tracing::debug!(action = request.action, target = ?request.target, "proxy request");
+        }
+        let verdict = match &request {
+            Some(request) if request.action == "mcp-tool-call" => {
+                gate(request.target.as_deref().unwrap_or_default())
+            }
+            _ => Ok(()),
+        };
+        lock(shared)?.forward_client(line, request.as_ref(), verdict);
+        Ok(())
+    })?;
+    let mut state = lock(shared)?;
+    state.client_closed = true;
+    state.upstream = None;
+    Ok(())
+}
+
+/// Delivers one upstream line to the client after session filtering.
+fn forward_server_line<W: Write>(line: &[u8], state: &mut Shared, out: &mut W) -> io::Result<()> {
+    if let Some(result) = state.session.complete_call(line) {
+        state.audit(&result);
+    }
+    let Some(forward) = state.session.transform_server(line) else {
+        tracing::debug!("dropped upstream message not meant for the client");
+        return Ok(());
+    };
+    out.write_all(&forward)?;
+    out.flush()
+}
+
+/// Answers buffered and queued client requests with errors when the upstream cannot be restored.
+fn fail_waiting<W: Write>(state: &mut Shared, out: &mut W) -> io::Result<()> {
+    let queued = std::mem::take(&mut state.queued).into_iter().map(|q| q.id);
+    let buffered = std::mem::take(&mut state.buffered)
+        .into_iter()
+        .filter_map(|line| request_id(&line));
+    for id in buffered.chain(queued) {
+        out.write_all(&error_response(&id, "Upstream server is unavailable"))?;
+    }
+    out.flush()
+}
+
+/// Reads upstream stdout on a background thread so the child never blocks on output.
+fn spawn_line_reader(child: &mut Child, framings: &Framings) -> io::Result<Receiver<Vec<u8>>> {
+    let stdout = child
+        .stdout
+        .take()
+        .ok_or_else(|| io::Error::other("child stdout is not piped"))?;
+    let (tx, rx) = mpsc::channel();
+    let seen = framings.server_seen.clone();
+    thread::spawn(move || {
+        let _ = for_each_message(BufReader::new(stdout), &seen, |line| {
+            tx.send(line.to_vec())
+                .map_err(|_| io::Error::other("relay stopped"))
+        });
+    });
+    Ok(rx)
+}
+
+fn take_stdin(child: &mut Child, framings: &Framings) -> io::Result<FramedWriter<ChildStdin>> {
+    child
+        .stdin
+        .take()
+        .map(|stdin| framings.to_server(stdin))
+        .ok_or_else(|| io::Error::other("child stdin is not piped"))
+}
+
+fn lock(shared: &Mutex<Shared>) -> io::Result<MutexGuard<'_, Shared>> {
+    shared
+        .lock()
+        .map_err(|_| io::Error::other("proxy session poisoned"))
+}
+
+/// Calls `handle` with each message, newline-terminated in either framing, until EOF.
+fn for_each_message<R, F>(mut reader: R, seen: &Detected, mut handle: F) -> io::Result<()>
+where
+    R: BufRead,
+    F: FnMut(&[u8]) -> io::Result<()>,
+{
+    while let Some(message) = read_message(&mut reader, seen)? {
+        handle(&message)?;
+    }
+    Ok(())
+}
+
+/// Parses one JSON-RPC message line, ignoring non-JSON content.
+fn parse_message(line: &[u8]) -> Option<Value> {
+    serde_json::from_slice::<Value>(line.trim_ascii())
+        .ok()
+        .filter(Value::is_object)
+}
+
+/// Returns whether a message is JSON other than a single object, such as a
+/// JSON-RPC batch.
+fn is_batch(line: &[u8]) -> bool {
+    serde_json::from_slice::<Value>(line.trim_ascii()).is_ok_and(|value| !value.is_object())
+}
+
+/// Returns the id of a JSON-RPC request (not a notification or response).
+fn request_id(line: &[u8]) -> Option<Value> {
+    let message = parse_message(line)?;
+    message.get("method")?;
+    message.get("id").cloned()
+}
+
+/// Serializes a JSON-RPC error response line for a request id.
+fn error_response(id: &Value, message: &str) -> Vec<u8> {
+    coded_error_response(id, INTERNAL_ERROR_CODE, message)
+}
+
+fn coded_error_response(id: &Value, code: i64, message: &str) -> Vec<u8> {
+    message_line(&json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": {"code": code, "message": message},
+    }))
+}
+
+fn message_line(value: &Value) -> Vec<u8> {
+    let mut line = serde_json::to_vec(value).unwrap_or_default();
+    line.push(b'\n');
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(method: &str, params: Value) -> Vec<u8> {
+        let mut line = serde_json::to_vec(&serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        }))
+        .unwrap();
+        line.push(b'\n');
+        line
+    }
+
+    fn updated(uri: &str) -> Vec<u8> {
+        serde_json::to_vec(&serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/resources/updated",
+            "params": {"uri": uri},
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn observe_client_audits_tool_resource_and_prompt_requests() {
+        let mut session = ProxySession::default();
+        assert_eq!(
+            session.observe_client(&request(
+                "tools/call",
+                serde_json::json!({"name": "search"})
+            )),
+            Some(AuditedRequest {
+                action: "mcp-tool-call",
+                target: Some("search".to_string()),
+                arguments: Vec::new(),
+                outcome: None,
+            })
+        );
+        assert_eq!(
+            session
+                .observe_client(&request(
+                    "resources/read",
+                    serde_json::json!({"uri": "file:///a"})
+                ))
+                .unwrap()
+                .target,
+            Some("file:///a".to_string())
+        );
+        assert_eq!(
+            session
+                .observe_client(&request(
+                    "prompts/get",
+                    serde_json::json!({"name": "review"})
+                ))
+                .unwrap()
+                .action,
+            "mcp-prompt-get"
+        );
+        assert!(session
+            .observe_client(&request("resources/list", serde_json::json!({})))
+            .is_none());
+        assert!(session.observe_client(b"not json\n").is_none());
+    }
+
+    #[test]
+    fn subscriptions_gate_resource_update_notifications() {
+        let mut session = ProxySession::default();
+        assert!(session.transform_server(&updated("file:///a")).is_none());
+
+        session.observe_client(&request(
+            "resources/subscribe",
+            serde_json::json!({"uri": "file:///a"}),
+        ));
+        assert!(session.transform_server(&updated("file:///a")).is_some());
+        assert!(session.transform_server(&updated("file:///b")).is_none());
+
+        session.observe_client(&request(
+            "resources/unsubscribe",
+            serde_json::json!({"uri": "file:///a"}),
+        ));
+        assert!(session.subscriptions().is_empty());
+        assert!(session.transform_server(&updated("file:///a")).is_none());
+    }
+
+    #[test]
+    fn non_json_and_other_server_messages_are_forwarded() {
+        let mut session = ProxySession::default();
+        let plain: &[u8] = b"plain output\n";
+        assert_eq!(session.transform_server(plain).as_deref(), Some(plain));
+        let response: &[u8] = br#"{"jsonrpc":"2.0","id":1,"result":{}}"#;
+        assert_eq!(
+            session.transform_server(response).as_deref(),
+            Some(response)
+        );
+    }
+
+    #[test]
+    fn results_are_shimmed_for_client_revision_from_initialize() {
+        let mut session = ProxySession::new(CompatMode::Auto);
+        session.observe_client(&request(
+            "initialize",
+            serde_json::json!({"protocolVersion": "2025-03-26"}),
+        ));
+        let result = br#"{"jsonrpc":"2.0","id":2,"result":{"structuredContent":{"a":1}}}"#;
+        let forwarded = session.transform_server(result).unwrap();
+        let value: Value = serde_json::from_slice(&forwarded).unwrap();
+        assert!(value["result"].get("structuredContent").is_none());
+        assert_eq!(value["result"]["content"][0]["text"], r#"{"a":1}"#);
+    }
+
+    #[test]
+    fn compat_off_relays_results_verbatim() {
+        let mut session = ProxySession::new(CompatMode::Off);
+        session.observe_client(&request(
+            "initialize",
+            serde_json::json!({"protocolVersion": "2024-11-05"}),
+        ));
+        let result: &[u8] = br#"{"jsonrpc":"2.0","id":2,"result":{"structuredContent":{"a":1}}}"#;
+        assert_eq!(session.transform_server(result).as_deref(), Some(result));
+    }
+
+    #[test]
+    fn forward_server_line_drops_unsubscribed_updates() {
+        let mut state = Shared::new(ProxySession::default(), None, 1, Box::new(|_, _| {}));
+        let mut out = Vec::new();
+        forward_server_line(b"hello\n", &mut state, &mut out).unwrap();
+        forward_server_line(&updated("file:///a"), &mut state, &mut out).unwrap();
+        forward_server_line(b"{\"id\":2}\n", &mut state, &mut out).unwrap();
+        assert_eq!(out, b"hello\n{\"id\":2}\n");
+    }
+
+    #[test]
+    fn requests_beyond_in_flight_limit_queue_and_time_out() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&events);
+        let limit = InFlightLimit {
+            max_in_flight: 1,
+            queue_timeout: Duration::from_secs(5),
+        };
+        let mut state = Shared::new(
+            ProxySession::default(),
+            Some(limit),
+            1,
+            Box::new(move |request: &AuditedRequest, _| {
+                sink.lock().unwrap().push(request.action);
+            }),
+        );
+        state
+            .session
+            .mark_sent(&request("tools/call", serde_json::json!({"name": "a"})));
+        state.send(b"{\"jsonrpc\":\"2.0\",\"id\":2,\"method\":\"tools/list\"}\n");
+        state.send(b"{\"jsonrpc\":\"2.0\",\"method\":\"notifications/cancelled\"}\n");
+        assert_eq!(state.queued.len(), 1);
+        assert_eq!(state.buffered.len(), 1);
+
+        let mut out = Vec::new();
+        state.expire_queue(Instant::now(), &mut out).unwrap();
+        assert!(out.is_empty());
+        state
+            .expire_queue(Instant::now() + limit.queue_timeout, &mut out)
+            .unwrap();
+        let value: Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(value["id"], 2);
+        assert!(state.queued.is_empty());
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec!["mcp-request-queued", "mcp-request-queue-timeout"]
+        );
+    }
+
+    fn recording_state(events: &Arc<Mutex<Vec<AuditedRequest>>>) -> Mutex<Shared> {
+        let sink = Arc::clone(events);
+        Mutex::new(Shared::new(
+            ProxySession::default(),
+            None,
+            1,
+            Box::new(move |request: &AuditedRequest, _| {
+                sink.lock().unwrap().push(request.clone());
+            }),
+        ))
+    }
+
+    #[test]
+    fn denied_tool_calls_are_answered_and_audited_without_reaching_upstream() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let shared = recording_state(&events);
+        let gate = |tool: &str| match tool {
+            "delete_repo" => Err("Permission denied for github: no".to_string()),
+            _ => Ok(()),
+        };
+        let mut input = request("tools/call", serde_json::json!({"name": "delete_repo"}));
+        input.extend(
+            String::from_utf8(request("tools/call", serde_json::json!({"name": "search"})))
+                .unwrap()
+                .replace("\"id\":1", "\"id\":2")
+                .into_bytes(),
+        );
+        pump_client(io::Cursor::new(input), &Detected::default(), &shared, &gate).unwrap();
+
+        let mut state = shared.lock().unwrap();
+        assert_eq!(state.buffered.len(), 1);
+        let mut out = Vec::new();
+        state.write_replies(&mut out).unwrap();
+        let value: Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(value["id"], 1);
+        assert_eq!(value["error"]["code"], PERMISSION_DENIED_CODE);
+        assert_eq!(
+            value["error"]["message"],
+            "Permission denied for github: no"
+        );
+        let events = events.lock().unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].action, "permission-denied");
+        assert_eq!(events[0].target.as_deref(), Some("delete_repo"));
+        assert_eq!(events[1].action, "mcp-tool-call");
+        assert_eq!(events[1].target.as_deref(), Some("search"));
+    }
+
+    #[test]
+    fn gate_is_asked_without_holding_the_session_lock() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let shared = Arc::new(recording_state(&events));
+        let (asked_tx, asked_rx) = std::sync::mpsc::channel();
+        let (answer_tx, answer_rx) = std::sync::mpsc::channel::<Result<(), String>>();
+        let answer_rx = Mutex::new(answer_rx);
+        let client_shared = Arc::clone(&shared);
+        let client = thread::spawn(move || {
+            let gate = |_: &str| {
+                asked_tx.send(()).unwrap();
+                answer_rx.lock().unwrap().recv().unwrap()
+            };
+            pump_client(
+                io::Cursor::new(request("tools/call", serde_json::json!({"name": "a"}))),
+                &Detected::default(),
+                &client_shared,
+                &gate,
+            )
+        });
+
+        // While the prompt waits on the user, upstream output still flows.
+        asked_rx.recv().unwrap();
+        let mut out = Vec::new();
+        {
+            let mut state = shared.try_lock().expect("session lock held during prompt");
+            forward_server_line(
+                b"{\"jsonrpc\":\"2.0\",\"method\":\"ping\"}\n",
+                &mut state,
+                &mut out,
+            )
+            .unwrap();
+        }
+        assert!(!out.is_empty());
+
+        answer_tx.send(Ok(())).unwrap();
+        client.join().unwrap().unwrap();
+        assert_eq!(shared.lock().unwrap().buffered.len(), 1);
+        assert_eq!(events.lock().unwrap()[0].action, "mcp-tool-call");
+    }
+
+    #[test]
+    fn batched_tool_calls_are_rejected_without_reaching_upstream() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let shared = recording_state(&events);
+        let gate = |_: &str| Err("Permission denied for github: no".to_string());
+        let mut batch = b"[".to_vec();
+        batch
+            .extend(request("tools/call", serde_json::json!({"name": "delete_repo"})).trim_ascii());
+        batch.extend(b"]\n");
+        pump_client(io::Cursor::new(batch), &Detected::default(), &shared, &gate).unwrap();
+
+        let mut state = shared.lock().unwrap();
+        assert!(state.buffered.is_empty());
+        assert_eq!(state.session.in_flight(), 0);
+        let mut out = Vec::new();
+        state.write_replies(&mut out).unwrap();
+        let value: Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(value["id"], Value::Null);
+        assert_eq!(value["error"]["code"], INVALID_REQUEST_CODE);
+        assert!(events.lock().unwrap().is_empty());
+        drop(state);
+
+        // A single call still goes through the gate.
+        pump_client(
+            io::Cursor::new(request(
+                "tools/call",
+                serde_json::json!({"name": "delete_repo"}),
+            )),
+            &Detected::default(),
+            &shared,
+            &gate,
+        )
+        .unwrap();
+        assert!(shared.lock().unwrap().buffered.is_empty());
+        assert_eq!(events.lock().unwrap()[0].action, "permission-denied");
+    }
+
+    #[test]
+    fn pending_requests_are_interrupted_and_answered_ones_cleared() {
+        let mut session = ProxySession::default();
+        session.mark_sent(&request("tools/call", serde_json::json!({"name": "a"})));
+        let mut second = request("tools/call", serde_json::json!({"name": "b"}));
+        second = String::from_utf8(second)
+            .unwrap()
+            .replace("\"id\":1", "\"id\":2")
+            .into_bytes();
+        session.mark_sent(&second);
+        session.transform_server(br#"{"jsonrpc":"2.0","id":1,"result":{}}"#);
+
+        let errors = session.interrupt_pending();
+        assert_eq!(errors.len(), 1);
+        let value: Value = serde_json::from_slice(&errors[0]).unwrap();
+        assert_eq!(value["id"], 2);
+        assert_eq!(value["error"]["code"], INTERNAL_ERROR_CODE);
+    }
+
+    #[test]
+    fn requests_owed_by_a_replaced_upstream_are_interrupted_selectively() {
+        let mut session = ProxySession::default();
+        session.mark_sent(&request("tools/list", Value::Null));
+        let owed = session.pending_keys();
+        let later = String::from_utf8(request("tools/list", Value::Null))
+            .unwrap()
+            .replace("\"id\":1", "\"id\":2")
+            .into_bytes();
+        session.mark_sent(&later);
+
+        let errors = session.interrupt(&owed);
+        assert_eq!(errors.len(), 1);
+        let value: Value = serde_json::from_slice(&errors[0]).unwrap();
+        assert_eq!(value["id"], 1);
+        assert!(!session.is_pending("1"));
+        assert!(session.is_pending("2"));
+    }
+
+    #[test]
+    fn complete_call_times_tool_calls_and_flags_errors() {
+        let mut session = ProxySession::default();
+        session.mark_sent(&request(
+            "tools/call",
+            serde_json::json!({"name": "search"}),
+        ));
+        assert_eq!(
+            session.complete_call(br#"{"jsonrpc":"2.0","method":"notifications/progress"}"#),
+            None
+        );
+        let result = session
+            .complete_call(br#"{"jsonrpc":"2.0","id":1,"result":{"isError":true}}"#)
+            .unwrap();
+        assert_eq!(result.action, "mcp-tool-result");
+        assert_eq!(result.target.as_deref(), Some("search"));
+        assert!(result.outcome.is_some_and(|o| o.error));
+        assert_eq!(
+            session.complete_call(br#"{"jsonrpc":"2.0","id":1,"result":{}}"#),
+            None
+        );
+
+        session.mark_sent(&request("resources/read", serde_json::json!({"uri": "x"})));
+        let result = session
+            .complete_call(br#"{"jsonrpc":"2.0","id":1,"error":{"code":-1}}"#)
+            .unwrap();
+        assert_eq!(result.action, "mcp-resource-result");
+        assert_eq!(result.target.as_deref(), Some("x"));
+        assert!(result.outcome.is_some_and(|o| o.error));
+
+        session.mark_sent(&request(
+            "resources/subscribe",
+            serde_json::json!({"uri": "x"}),
+        ));
+        assert_eq!(
+            session.complete_call(br#"{"jsonrpc":"2.0","id":1,"result":{}}"#),
+            None
+        );
+    }
+
+    #[test]
+    fn tool_call_arguments_are_summarized_with_secrets_redacted() {
+        let mut session = ProxySession::default();
+        let audited = session
+            .observe_client(&request(
+                "tools/call",
+                serde_json::json!({
+                    "name": "fetch",
+                    "arguments": {
+                        "url": "https://bob:pw@example.com/x",
+                        "apiKey": "sk-123",
+                        "query": "a".repeat(100),
+                        "limit": 5,
+                        "filters": {"state": "open", "token": "ghp_abc"},
+                    },
+                }),
+            ))
+            .unwrap();
+        assert_eq!(
+            audited.arguments,
+            [
+                "apiKey=[redacted]".to_string(),
+                "filters={2 keys}".to_string(),
+                "limit=5".to_string(),
+                format!("query={}…", "a".repeat(64)),
+                "url=https://[redacted]@example.com/x".to_string(),
+            ]
+        );
+        assert!(session
+            .observe_client(&request("resources/read", serde_json::json!({"uri": "x"})))
+            .unwrap()
+            .arguments
+            .is_empty());
+    }
+
+    #[test]
+    fn replay_consumes_initialize_and_resubscribe_responses() {
+        let mut session = ProxySession::default();
+        session.observe_client(&request(
+            "initialize",
+            serde_json::json!({"protocolVersion": "2025-06-18"}),
+        ));
+        session.observe_client(b"{\"jsonrpc\":\"2.0\",\"method\":\"notifications/initialized\"}\n");
+        session.observe_client(&request(
+            "resources/subscribe",
+            serde_json::json!({"uri": "file:///a"}),
+        ));
+
+        let (line, key) = session.replay_initialize().unwrap();
+        assert!(String::from_utf8_lossy(&line).contains("initialize"));
+        assert!(session.awaiting(&key));
+        assert!(session
+            .transform_server(br#"{"jsonrpc":"2.0","id":1,"result":{}}"#)
+            .is_none());
+        assert!(!session.awaiting(&key));
+
+        let after = session.replay_after_initialize();
+        assert_eq!(after.len(), 2);
+        assert!(String::from_utf8_lossy(&after[0]).contains("notifications/initialized"));
+        let resubscribe: Value = serde_json::from_slice(&after[1]).unwrap();
+        assert_eq!(resubscribe["params"]["uri"], "file:///a");
+        let response = format!(
+            r#"{{"jsonrpc":"2.0","id":{},"result":{{}}}}"#,
+            resubscribe["id"]
+        );
+        assert!(session.transform_server(response.as_bytes()).is_none());
+    }
+}
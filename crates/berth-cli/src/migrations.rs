@@ -0,0 +1,296 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! On-disk data format versions and the migrations between them.
+//!
+//! `<state>/format.toml` records the format of a Berth home and the release
+//! that last wrote it; homes without one predate versioning and count as
+//! format 1. A newer Berth runs the pending [`MIGRATIONS`] on first use, after
+//! copying each file they rewrite to `<state>/backups/migrations/<epoch_ms>/`.
+//! A home in a format newer than this release knows is left untouched.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::paths::BerthLayout;
+use crate::time_format::now_epoch_ms;
+
+/// Data format this release reads and writes.
+pub const CURRENT_FORMAT: u32 = 2;
+
+/// Format of homes written before `format.toml` existed.
+const UNVERSIONED_FORMAT: u32 = 1;
+
+const MARKER_FILE: &str = "format.toml";
+
+/// Contents of `format.toml`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FormatMarker {
+    pub format: u32,
+    /// Berth release that last wrote the home.
+    pub berth: String,
+}
+
+impl FormatMarker {
+    /// The marker this release writes.
+    pub fn current() -> Self {
+        FormatMarker {
+            format: CURRENT_FORMAT,
+            berth: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    }
+}
+
+/// One registered change to the on-disk format.
+pub struct Migration {
+    /// Format the home is at once this migration has run.
+    pub to: u32,
+    pub title: &'static str,
+    /// What stops working for users, if anything; shown in upgrade reports.
+    pub breaking: Option<&'static str>,
+    /// Lists the files the migration rewrites.
+    files: fn(&BerthLayout) -> Result<Vec<PathBuf>, String>,
+    /// Rewrites one of those files in place.
+    rewrite: fn(&Path) -> Result<(), String>,
+}
+
+/// Every migration, in format order.
+pub const MIGRATIONS: &[Migration] = &[Migration {
+    to: 2,
+    title: "Runtime state records millisecond timestamps",
+    breaking: Some(
+        "Berth releases before format 2 cannot read the upgraded runtime state; restore the backup before downgrading.",
+    ),
+    files: legacy_runtime_states,
+    rewrite: upgrade_runtime_state,
+}];
+
+/// A pending migration and the files it will rewrite.
+pub struct Step {
+    pub migration: &'static Migration,
+    pub files: Vec<PathBuf>,
+}
+
+/// What bringing a home to [`CURRENT_FORMAT`] involves.
+pub struct Plan {
+    /// `format.toml` as found, if any.
+    pub recorded: Option<FormatMarker>,
+    /// Format the home is at now.
+    pub from: u32,
+    pub steps: Vec<Step>,
+}
+
+impl Plan {
+    /// Returns whether the home was written in a format this release does not know.
+    pub fn is_newer(&self) -> bool {
+        self.from > CURRENT_FORMAT
+    }
+
+    /// Returns whether `format.toml` is missing or names another format or release.
+    pub fn is_outdated(&self) -> bool {
+        self.recorded.as_ref() != Some(&FormatMarker::current())
+    }
+
+    /// Number of files the steps rewrite.
+    pub fn file_count(&self) -> usize {
+        self.steps.iter().map(|step| step.files.len()).sum()
+    }
+}
+
+/// Works out which migrations `layout` still needs.
+pub fn plan(layout: &BerthLayout) -> Result<Plan, String> {
+    let recorded = read_marker(&layout.state)?;
+    let from = recorded
+        .as_ref()
+        .map_or(UNVERSIONED_FORMAT, |marker| marker.format);
+    let mut steps = Vec::new();
+    for migration in MIGRATIONS.iter().filter(|m| m.to > from) {
+        steps.push(Step {
+            migration,
+            files: (migration.files)(layout)?,
+        });
+    }
+    Ok(Plan {
+        recorded,
+        from,
+        steps,
+    })
+}
+
+/// Backs up and rewrites the files in `plan`, then records the current format.
+///
+/// Returns the backup directory when any file was rewritten.
+pub fn apply(layout: &BerthLayout, plan: &Plan) -> Result<Option<PathBuf>, String> {
+    if plan.is_newer() {
+        return Err(format!(
+            "Berth data is at format {}, newer than format {CURRENT_FORMAT} this release knows.",
+            plan.from
+        ));
+    }
+    let backup = if plan.file_count() > 0 {
+        let dir = layout
+            .state
+            .join("backups")
+            .join("migrations")
+            .join(now_epoch_ms().to_string());
+        for file in plan.steps.iter().flat_map(|step| &step.files) {
+            back_up(layout, &dir, file)?;
+        }
+        Some(dir)
+    } else {
+        None
+    };
+    for step in &plan.steps {
+        for file in &step.files {
+            (step.migration.rewrite)(file)?;
+        }
+    }
+    write_marker(&layout.state, &FormatMarker::current())?;
+    Ok(backup)
+}
+
+fn read_marker(state: &Path) -> Result<Option<FormatMarker>, String> {
+    let path = state.join(MARKER_FILE);
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(format!("Failed to read {}: {e}", path.display())),
+    };
+    toml::from_str(&content)
+        .map(Some)
+        .map_err(|e| format!("Failed to parse {}: {e}", path.display()))
+}
+
+fn write_marker(state: &Path, marker: &FormatMarker) -> Result<(), String> {
+    let path = state.join(MARKER_FILE);
+    let content = toml::to_string(marker).map_err(|e| format!("Failed to encode format: {e}"))?;
+    fs::create_dir_all(state)
+        .and_then(|()| fs::write(&path, content))
+        .map_err(|e| format!("Failed to write {}: {e}", path.display()))
+}
+
+/// Copies `file` below `dir`, keeping its path relative to the state or config directory.
+fn back_up(layout: &BerthLayout, dir: &Path, file: &Path) -> Result<(), String> {
+    let relative = if let Ok(rest) = file.strip_prefix(&layout.state) {
+        Path::new("state").join(rest)
+    } else if let Ok(rest) = file.strip_prefix(&layout.config) {
+        Path::new("config").join(rest)
+    } else {
+        PathBuf::from(file.file_name().unwrap_or_default())
+    };
+    let target = dir.join(relative);
+    if target.exists() {
+        return Ok(());
+    }
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create backup {}: {e}", parent.display()))?;
+    }
+    fs::copy(file, &target)
+        .map(|_| ())
+        .map_err(|e| format!("Failed to back up {}: {e}", file.display()))
+}
+
+/// Runtime state files still carrying whole-second `updated_at_epoch_secs`.
+fn legacy_runtime_states(layout: &BerthLayout) -> Result<Vec<PathBuf>, String> {
+    let dir = layout.state.join("runtime");
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to read {}: {e}", dir.display())),
+    };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+        .filter(|path| {
+            fs::read_to_string(path)
+                .ok()
+                .and_then(|content| content.parse::<toml::Table>().ok())
+                .is_some_and(|table| table.contains_key("updated_at_epoch_secs"))
+        })
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+fn upgrade_runtime_state(path: &Path) -> Result<(), String> {
+    let content =
+        fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    let mut table: toml::Table = content
+        .parse()
+        .map_err(|e| format!("Failed to parse {}: {e}", path.display()))?;
+    let secs = table
+        .remove("updated_at_epoch_secs")
+        .and_then(|value| value.as_integer())
+        .unwrap_or_default();
+    let has_ms = table
+        .get("updated_at_epoch_ms")
+        .and_then(toml::Value::as_integer)
+        .is_some_and(|ms| ms > 0);
+    if !has_ms {
+        table.insert(
+            "updated_at_epoch_ms".to_string(),
+            toml::Value::Integer(secs.saturating_mul(1000)),
+        );
+    }
+    let updated =
+        toml::to_string(&table).map_err(|e| format!("Failed to encode {}: {e}", path.display()))?;
+    fs::write(path, updated).map_err(|e| format!("Failed to write {}: {e}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn upgrades_unversioned_home_with_backup() {
+        let tmp = tempfile::tempdir().unwrap();
+        let layout = BerthLayout::single(tmp.path().join(".berth"));
+        let runtime = layout.state.join("runtime");
+        fs::create_dir_all(&runtime).unwrap();
+        let legacy = "status = \"running\"\nupdated_at_epoch_secs = 1700000000\npid = 42\n";
+        fs::write(runtime.join("github.toml"), legacy).unwrap();
+        fs::write(
+            runtime.join("slack.toml"),
+            "status = \"stopped\"\nupdated_at_epoch_ms = 1700000000123\n",
+        )
+        .unwrap();
+
+        let pending = plan(&layout).unwrap();
+        assert_eq!(pending.from, 1);
+        assert!(pending.is_outdated());
+        assert_eq!(pending.file_count(), 1);
+        assert_eq!(pending.steps[0].files, vec![runtime.join("github.toml")]);
+
+        let backup = apply(&layout, &pending).unwrap().unwrap();
+        let upgraded = fs::read_to_string(runtime.join("github.toml")).unwrap();
+        assert!(upgraded.contains("updated_at_epoch_ms = 1700000000000"));
+        assert!(!upgraded.contains("updated_at_epoch_secs"));
+        assert!(upgraded.contains("pid = 42"));
+        assert_eq!(
+            fs::read_to_string(backup.join("state/runtime/github.toml")).unwrap(),
+            legacy
+        );
+
+        let done = plan(&layout).unwrap();
+        assert_eq!(done.from, CURRENT_FORMAT);
+        assert!(!done.is_outdated());
+        assert!(done.steps.is_empty());
+    }
+
+    #[test]
+    fn refuses_newer_formats() {
+        let tmp = tempfile::tempdir().unwrap();
+        let layout = BerthLayout::single(tmp.path().to_path_buf());
+        fs::write(
+            tmp.path().join(MARKER_FILE),
+            format!("format = {}\nberth = \"9.0.0\"\n", CURRENT_FORMAT + 1),
+        )
+        .unwrap();
+        let newer = plan(&layout).unwrap();
+        assert!(newer.is_newer());
+        assert!(newer.steps.is_empty());
+        assert!(apply(&layout, &newer).is_err());
+    }
+}
@@ -0,0 +1,607 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! OAuth authorization-code flow for servers that need browser-issued tokens.
+//!
+//! `berth auth` sends the user to the provider, receives the code on a
+//! loopback redirect (bound to a PKCE verifier, RFC 7636, so an intercepted
+//! redirect cannot be redeemed), and keeps only the refresh token (as a secret
+//! reference in the server config). Each start exchanges it for a fresh
+//! access token that is passed to the server through the manifest's `env`.
+
+use berth_common::{now_epoch_secs, sha256};
+use berth_registry::config::InstalledServer;
+use berth_registry::http::post_form;
+use berth_registry::types::OAuthSpec;
+use berth_registry::Registry;
+use berth_runtime::ProcessSpec;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{IpAddr, TcpListener, TcpStream};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::credential_expiry::{record_expiry, refresh_at};
+use crate::permission_filter::{filter_env_map, load_permission_overrides};
+use crate::secrets::{resolve_config_value, store_secret};
+
+/// Config key holding the secret reference of the stored refresh token.
+pub const KEY_OAUTH_REFRESH_TOKEN: &str = "berth.oauth-refresh-token";
+
+/// Path the loopback listener accepts the redirect on.
+const CALLBACK_PATH: &str = "/callback";
+
+/// Client credentials read from the server's config.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OAuthClient {
+    pub id: String,
+    pub secret: Option<String>,
+}
+
+/// Token endpoint response fields Berth uses.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct TokenResponse {
+    pub access_token: String,
+    #[serde(default)]
+    pub refresh_token: Option<String>,
+    #[serde(default)]
+    pub expires_in: Option<u64>,
+}
+
+/// Error body returned by token endpoints (RFC 6749 §5.2).
+#[derive(Debug, Deserialize)]
+struct TokenError {
+    error: String,
+    #[serde(default)]
+    error_description: Option<String>,
+}
+
+/// Checks that both endpoints use https (or plain http on a loopback host).
+pub fn validate_spec(spec: &OAuthSpec) -> Result<(), String> {
+    for (label, url) in [
+        ("authorizeUrl", &spec.authorize_url),
+        ("tokenUrl", &spec.token_url),
+    ] {
+        if !is_secure_endpoint(url) {
+            return Err(format!(
+                "oauth {label} `{url}` must use https (http is only allowed for loopback hosts)"
+            ));
+        }
+    }
+    if spec.client_id_key.trim().is_empty() || spec.env.trim().is_empty() {
+        return Err("oauth clientIdKey and env must not be empty".to_string());
+    }
+    Ok(())
+}
+
+/// Reads the client ID and optional secret from the server's config.
+pub fn client_credentials(
+    server: &str,
+    installed: &InstalledServer,
+    spec: &OAuthSpec,
+) -> Result<OAuthClient, String> {
+    let read = |key: &str| -> Result<Option<String>, String> {
+        match installed.config.get(key) {
+            Some(value) if !value.trim().is_empty() => {
+                resolve_config_value(server, key, value).map(Some)
+            }
+            _ => Ok(None),
+        }
+    };
+    let id = read(&spec.client_id_key)?.ok_or_else(|| {
+        format!(
+            "OAuth client ID is not configured. Set it with `berth config {server} --set {}=<id>`.",
+            spec.client_id_key
+        )
+    })?;
+    let secret = match &spec.client_secret_key {
+        Some(key) => read(key)?,
+        None => None,
+    };
+    Ok(OAuthClient { id, secret })
+}
+
+/// Returns the redirect URI served by a loopback listener on `port`.
+pub fn redirect_uri(port: u16) -> String {
+    format!("http://127.0.0.1:{port}{CALLBACK_PATH}")
+}
+
+/// Returns the S256 PKCE challenge for `verifier`: the unpadded base64url
+/// encoding of its SHA-256 digest.
+pub fn code_challenge(verifier: &str) -> String {
+    base64url(&sha256(verifier.as_bytes()))
+}
+
+/// Builds the provider URL the user opens to grant access.
+///
+/// `code_verifier` is the PKCE secret later posted by [`exchange_code`]; only
+/// its challenge is sent here.
+pub fn authorization_url(
+    spec: &OAuthSpec,
+    client: &OAuthClient,
+    redirect_uri: &str,
+    state: &str,
+    code_verifier: &str,
+) -> String {
+    let scope = spec.scopes.join(" ");
+    let challenge = code_challenge(code_verifier);
+    let mut params = vec![
+        ("response_type", "code"),
+        ("client_id", client.id.as_str()),
+        ("redirect_uri", redirect_uri),
+        ("state", state),
+        ("code_challenge", challenge.as_str()),
+        ("code_challenge_method", "S256"),
+        // Providers such as Google only issue refresh tokens for offline access.
+        ("access_type", "offline"),
+    ];
+    if !scope.is_empty() {
+        params.push(("scope", scope.as_str()));
+    }
+    let separator = if spec.authorize_url.contains('?') {
+        '&'
+    } else {
+        '?'
+    };
+    format!("{}{separator}{}", spec.authorize_url, form_encode(&params))
+}
+
+/// Extracts the authorization code from a redirect request target.
+pub fn parse_callback(target: &str, expected_state: &str) -> Result<String, String> {
+    let query = target.split_once('?').map(|(_, q)| q).unwrap_or_default();
+    let params: BTreeMap<String, String> = query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(k, v)| (percent_decode(k), percent_decode(v)))
+        .collect();
+    if params.get("state").map(String::as_str) != Some(expected_state) {
+        return Err("OAuth redirect state did not match; aborting.".to_string());
+    }
+    if let Some(error) = params.get("error") {
+        return Err(match params.get("error_description") {
+            Some(description) => format!("Authorization failed: {error} ({description})"),
+            None => format!("Authorization failed: {error}"),
+        });
+    }
+    params
+        .get("code")
+        .filter(|code| !code.is_empty())
+        .cloned()
+        .ok_or_else(|| "OAuth redirect did not include an authorization code.".to_string())
+}
+
+/// Waits for the provider's redirect on `listener` and returns the code.
+///
+/// Requests for other paths (e.g. a browser's favicon probe) are answered with
+/// 404 and ignored.
+pub fn wait_for_code(
+    listener: &TcpListener,
+    expected_state: &str,
+    timeout: Duration,
+) -> Result<String, String> {
+    listener
+        .set_nonblocking(true)
+        .map_err(|e| format!("Failed to configure redirect listener: {e}"))?;
+    let deadline = Instant::now() + timeout;
+    loop {
+        match listener.accept() {
+            Ok((mut stream, _)) => {
+                let _ = stream.set_nonblocking(false);
+                let _ = stream.set_read_timeout(Some(Duration::from_secs(5)));
+                let target = match read_request_target(&stream) {
+                    Ok(target) => target,
+                    Err(_) => continue,
+                };
+                if target.split('?').next() != Some(CALLBACK_PATH) {
+                    let _ = respond(&mut stream, "404 Not Found", "Not found.");
+                    continue;
+                }
+                let outcome = parse_callback(&target, expected_state);
+                let message = match &outcome {
+                    Ok(_) => "Berth received the authorization. You can close this window.",
+                    Err(_) => "Berth could not complete the authorization. See the terminal.",
+                };
+                let _ = respond(&mut stream, "200 OK", message);
+                return outcome;
+            }
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                if Instant::now() >= deadline {
+                    return Err(format!(
+                        "Timed out after {}s waiting for the OAuth redirect.",
+                        timeout.as_secs()
+                    ));
+                }
+                thread::sleep(Duration::from_millis(100));
+            }
+            Err(e) => return Err(format!("Failed to accept OAuth redirect: {e}")),
+        }
+    }
+}
+
+/// Exchanges an authorization code for tokens, proving possession of the
+/// PKCE verifier the authorization URL was built with.
+pub fn exchange_code(
+    spec: &OAuthSpec,
+    client: &OAuthClient,
+    code: &str,
+    redirect_uri: &str,
+    code_verifier: &str,
+) -> Result<TokenResponse, String> {
+    let mut params = vec![
+        ("grant_type", "authorization_code"),
+        ("code", code),
+        ("redirect_uri", redirect_uri),
+        ("client_id", client.id.as_str()),
+        ("code_verifier", code_verifier),
+    ];
+    if let Some(secret) = &client.secret {
+        params.push(("client_secret", secret.as_str()));
+    }
+    request_token(&spec.token_url, &params)
+}
+
+/// Exchanges a refresh token for a new access token.
+pub fn refresh_access_token(
+    spec: &OAuthSpec,
+    client: &OAuthClient,
+    refresh_token: &str,
+) -> Result<TokenResponse, String> {
+    let mut params = vec![
+        ("grant_type", "refresh_token"),
+        ("refresh_token", refresh_token),
+        ("client_id", client.id.as_str()),
+    ];
+    if let Some(secret) = &client.secret {
+        params.push(("client_secret", secret.as_str()));
+    }
+    request_token(&spec.token_url, &params)
+}
+
+/// Stores a refresh token in the secrets backend and records its reference.
+pub fn store_refresh_token(
+    server: &str,
+    installed: &mut InstalledServer,
+    refresh_token: &str,
+) -> Result<(), String> {
+    let reference = store_secret(server, KEY_OAUTH_REFRESH_TOKEN, refresh_token)?;
+    installed
+        .config
+        .insert(KEY_OAUTH_REFRESH_TOKEN.to_string(), reference);
+    Ok(())
+}
+
+/// Adds a freshly refreshed access token to a launch spec.
+///
+/// Does nothing for servers without an OAuth spec or stored refresh token, and
+/// respects env permission revokes like any other config-derived variable.
+/// When the provider reports a lifetime, the expiry is recorded and the spec
+/// asks its supervisor to refresh before then.
+pub fn inject_access_token(
+    server: &str,
+    installed: &InstalledServer,
+    registry: &Registry,
+    process: &mut ProcessSpec,
+) -> Result<(), String> {
+    let Some(spec) = registry
+        .get(&installed.server.name)
+        .and_then(|meta| meta.oauth.as_ref())
+    else {
+        return Ok(());
+    };
+    let Some(reference) = installed
+        .config
+        .get(KEY_OAUTH_REFRESH_TOKEN)
+        .filter(|v| !v.trim().is_empty())
+    else {
+        return Ok(());
+    };
+    let refresh_token = resolve_config_value(server, KEY_OAUTH_REFRESH_TOKEN, reference)?;
+    let client = client_credentials(server, installed, spec)?;
+    let issued_at = now_epoch_secs();
+    let tokens = refresh_access_token(spec, &client, &refresh_token)
+        .map_err(|e| format!("{e} Run `berth auth {server}` to sign in again."))?;
+    // Providers that rotate refresh tokens invalidate the old one; the config
+    // keeps pointing at the same secret reference.
+    if let Some(rotated) = tokens.refresh_token.as_deref() {
+        if rotated != refresh_token {
+            store_secret(server, KEY_OAUTH_REFRESH_TOKEN, rotated)?;
+        }
+    }
+    if let Some(lifetime) = tokens.expires_in {
+        let expires_at = issued_at + lifetime;
+        record_expiry(server, expires_at)?;
+        process.refresh_at_epoch_secs = Some(refresh_at(issued_at, expires_at));
+    }
+
+    let mut granted = BTreeMap::from([(spec.env.clone(), tokens.access_token)]);
+    let overrides = load_permission_overrides(server)?;
+    filter_env_map(&mut granted, &installed.permissions.env, &overrides);
+    process.env.extend(granted);
+    Ok(())
+}
+
+/// Posts a form to a token endpoint and parses the JSON response.
+fn request_token(url: &str, params: &[(&str, &str)]) -> Result<TokenResponse, String> {
+    if !is_secure_endpoint(url) {
+        return Err(format!("OAuth token endpoint `{url}` must use https."));
+    }
+    let (status, body) = post_form(url, &form_encode(params))
+        .map_err(|e| format!("OAuth token request failed: {e}"))?;
+    parse_token_response(&body).map_err(|e| {
+        if (200..300).contains(&status) {
+            e
+        } else {
+            format!("OAuth token request to {url} returned HTTP {status}. {e}")
+        }
+    })
+}
+
+/// Parses a token endpoint response, surfacing provider errors.
+pub fn parse_token_response(body: &str) -> Result<TokenResponse, String> {
+    if let Ok(error) = serde_json::from_str::<TokenError>(body) {
+        return Err(match error.error_description {
+            Some(description) => format!(
+                "OAuth provider rejected the request: {} ({description}).",
+                error.error
+            ),
+            None => format!("OAuth provider rejected the request: {}.", error.error),
+        });
+    }
+    serde_json::from_str::<TokenResponse>(body)
+        .map_err(|e| format!("OAuth token response was not understood: {e}."))
+}
+
+fn is_secure_endpoint(url: &str) -> bool {
+    if url.starts_with("https://") {
+        return true;
+    }
+    let Some(rest) = url.strip_prefix("http://") else {
+        return false;
+    };
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host = match authority.rsplit_once(':') {
+        Some((host, port)) if port.chars().all(|c| c.is_ascii_digit()) => host,
+        _ => authority,
+    };
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    host.eq_ignore_ascii_case("localhost")
+        || host.parse::<IpAddr>().is_ok_and(|ip| ip.is_loopback())
+}
+
+fn read_request_target(stream: &TcpStream) -> io::Result<String> {
+    let mut line = String::new();
+    BufReader::new(stream).read_line(&mut line)?;
+    let mut parts = line.split_whitespace();
+    match (parts.next(), parts.next()) {
+        (Some("GET"), Some(target)) => Ok(target.to_string()),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "unexpected request",
+        )),
+    }
+}
+
+fn respond(stream: &mut TcpStream, status: &str, message: &str) -> io::Result<()> {
+    let body = format!("<!doctype html><title>Berth</title><p>{message}</p>\n");
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    stream.flush()
+}
+
+fn form_encode(params: &[(&str, &str)]) -> String {
+    params
+        .iter()
+        .map(|(k, v)| format!("{}={}", percent_encode(k), percent_encode(v)))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+fn base64url(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, b)| n | u32::from(*b) << (16 - 8 * i));
+        for i in 0..=chunk.len() {
+            out.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+        }
+    }
+    out
+}
+
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                let hex = (bytes[i + 1] as char)
+                    .to_digit(16)
+                    .zip((bytes[i + 2] as char).to_digit(16));
+                match hex {
+                    Some((hi, lo)) => {
+                        out.push((hi * 16 + lo) as u8);
+                        i += 2;
+                    }
+                    None => out.push(b'%'),
+                }
+            }
+            b => out.push(b),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec() -> OAuthSpec {
+        OAuthSpec {
+            authorize_url: "https://accounts.example.com/o/oauth2/auth".to_string(),
+            token_url: "https://oauth2.example.com/token".to_string(),
+            scopes: vec!["drive.readonly".to_string(), "email".to_string()],
+            client_id_key: "client-id".to_string(),
+            client_secret_key: Some("client-secret".to_string()),
+            env: "DRIVE_ACCESS_TOKEN".to_string(),
+        }
+    }
+
+    fn client() -> OAuthClient {
+        OAuthClient {
+            id: "abc.apps".to_string(),
+            secret: None,
+        }
+    }
+
+    /// Verifier and challenge from RFC 7636, Appendix B.
+    const VERIFIER: &str = "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk";
+    const CHALLENGE: &str = "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM";
+
+    #[test]
+    fn authorization_url_encodes_parameters() {
+        let url = authorization_url(&spec(), &client(), &redirect_uri(4711), "s1", VERIFIER);
+        assert_eq!(
+            url,
+            format!(
+                "https://accounts.example.com/o/oauth2/auth?response_type=code&client_id=abc.apps\
+                 &redirect_uri=http%3A%2F%2F127.0.0.1%3A4711%2Fcallback&state=s1\
+                 &code_challenge={CHALLENGE}&code_challenge_method=S256\
+                 &access_type=offline&scope=drive.readonly%20email"
+            )
+        );
+        assert!(!url.contains(VERIFIER));
+    }
+
+    #[test]
+    fn code_challenge_matches_rfc_7636() {
+        assert_eq!(code_challenge(VERIFIER), CHALLENGE);
+        assert_eq!(base64url(b"ab"), "YWI");
+        assert_eq!(base64url(b"a"), "YQ");
+    }
+
+    #[test]
+    fn exchange_code_posts_the_pkce_verifier() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut local = spec();
+        local.token_url = format!("http://{}/token", listener.local_addr().unwrap());
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = line.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        length = value.trim().parse().unwrap();
+                    }
+                }
+            }
+            let mut body = vec![0; length];
+            io::Read::read_exact(&mut reader, &mut body).unwrap();
+            let reply = r#"{"access_token":"at","refresh_token":"rt"}"#;
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{reply}",
+                reply.len()
+            )
+            .unwrap();
+            String::from_utf8(body).unwrap()
+        });
+
+        let tokens =
+            exchange_code(&local, &client(), "c0de", &redirect_uri(4711), VERIFIER).unwrap();
+        assert_eq!(tokens.refresh_token.as_deref(), Some("rt"));
+        let body = server.join().unwrap();
+        assert!(body.contains("grant_type=authorization_code"), "{body}");
+        assert!(
+            body.contains(&format!("code_verifier={VERIFIER}")),
+            "{body}"
+        );
+    }
+
+    #[test]
+    fn parse_callback_checks_state_and_errors() {
+        assert_eq!(
+            parse_callback("/callback?code=4%2F0Ab+c&state=s1", "s1").unwrap(),
+            "4/0Ab c"
+        );
+        assert!(parse_callback("/callback?code=x&state=other", "s1")
+            .unwrap_err()
+            .contains("state did not match"));
+        assert_eq!(
+            parse_callback("/callback?error=access_denied&state=s1", "s1").unwrap_err(),
+            "Authorization failed: access_denied"
+        );
+        assert!(parse_callback("/callback?state=s1", "s1").is_err());
+    }
+
+    #[test]
+    fn parse_token_response_surfaces_provider_errors() {
+        let tokens = parse_token_response(
+            r#"{"access_token":"ya29","refresh_token":"1//r","expires_in":3599,"token_type":"Bearer"}"#,
+        )
+        .unwrap();
+        assert_eq!(tokens.access_token, "ya29");
+        assert_eq!(tokens.refresh_token.as_deref(), Some("1//r"));
+        assert_eq!(tokens.expires_in, Some(3599));
+
+        let err = parse_token_response(
+            r#"{"error":"invalid_grant","error_description":"Token has been expired or revoked."}"#,
+        )
+        .unwrap_err();
+        assert!(err.contains("invalid_grant"));
+    }
+
+    #[test]
+    fn validate_spec_requires_https_outside_loopback() {
+        assert!(validate_spec(&spec()).is_ok());
+        let mut local = spec();
+        local.token_url = "http://127.0.0.1:8080/token".to_string();
+        assert!(validate_spec(&local).is_ok());
+        local.token_url = "http://oauth2.example.com/token".to_string();
+        assert!(validate_spec(&local).is_err());
+    }
+
+    #[test]
+    fn wait_for_code_ignores_other_paths() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let client = thread::spawn(move || {
+            for target in ["/favicon.ico", "/callback?code=c0de&state=s1"] {
+                let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+                write!(stream, "GET {target} HTTP/1.1\r\nHost: localhost\r\n\r\n").unwrap();
+                let mut response = String::new();
+                let _ = io::Read::read_to_string(&mut stream, &mut response);
+            }
+        });
+        let code = wait_for_code(&listener, "s1", Duration::from_secs(10)).unwrap();
+        client.join().unwrap();
+        assert_eq!(code, "c0de");
+    }
+}
@@ -0,0 +1,58 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Read-only observer mode for shadowing someone else's Berth environment.
+//!
+//! Observer mode is turned on with `--observer`, with `BERTH_OBSERVER=1`, or by
+//! a profile with `observer = true`. Status, logs, and audit commands keep
+//! working; commands that would change the environment are refused before
+//! they touch anything.
+
+use colored::Colorize;
+
+/// Env var that turns on observer mode; also set for child processes once on.
+pub const OBSERVER_ENV: &str = "BERTH_OBSERVER";
+
+/// Returns whether `BERTH_OBSERVER` asks for observer mode.
+pub fn enabled() -> bool {
+    std::env::var(OBSERVER_ENV).is_ok_and(|value| is_truthy(&value))
+}
+
+fn is_truthy(value: &str) -> bool {
+    matches!(
+        value.trim().to_ascii_lowercase().as_str(),
+        "1" | "true" | "yes" | "on"
+    )
+}
+
+/// Reports that observer mode refuses `berth <command>` and exits.
+pub fn refuse(command: &str) -> ! {
+    eprintln!(
+        "{} Observer mode is read-only: {} would change this Berth environment.",
+        "✗".red().bold(),
+        format!("berth {command}").bold()
+    );
+    eprintln!(
+        "  Inspect with {}, {}, {} or {}; ask the owner of the environment to run it.",
+        "berth status".bold(),
+        "berth logs".bold(),
+        "berth audit".bold(),
+        "berth history".bold()
+    );
+    std::process::exit(1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_common_truthy_values() {
+        for value in ["1", "true", "YES", " on "] {
+            assert!(is_truthy(value), "{value}");
+        }
+        for value in ["", "0", "false", "off"] {
+            assert!(!is_truthy(value), "{value}");
+        }
+    }
+}
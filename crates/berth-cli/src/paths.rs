@@ -5,21 +5,57 @@
 
 use std::path::PathBuf;
 
-/// Returns Berth home directory (`$BERTH_HOME` override or `~/.berth`).
+pub use berth_common::home::{berth_layout, legacy_berth_home, user_berth_layout, BerthLayout};
+use berth_common::storage::{check_writable, StorageProblem, MIN_FREE_BYTES};
+use berth_runtime::RuntimeManager;
+
+use crate::plugins;
+use crate::workspace_trust;
+
+/// Returns the Berth state directory that runtime state, logs, and audit live under.
 pub fn berth_home() -> Option<PathBuf> {
-    // Allow override via BERTH_HOME for testing
-    if let Ok(home) = std::env::var("BERTH_HOME") {
-        return Some(PathBuf::from(home));
+    berth_layout().map(|l| l.state)
+}
+
+/// Returns the Berth config directory.
+pub fn berth_config_dir() -> Option<PathBuf> {
+    berth_layout().map(|l| l.config)
+}
+
+/// Returns a runtime manager rooted at the state directory that reads org
+/// policy from the config directory and hands audit entries to enabled
+/// plugins, unless the home belongs to an untrusted workspace.
+pub fn runtime_manager() -> Option<RuntimeManager> {
+    berth_layout().map(|l| {
+        let manager = RuntimeManager::new(&l.state).with_config_home(&l.config);
+        if workspace_trust::is_trusted() {
+            manager.with_audit_sink(plugins::audit_sink(l))
+        } else {
+            manager
+        }
+    })
+}
+
+/// Checks that the config and state directories can take writes.
+///
+/// The cache directory is left out: losing it only costs a re-download.
+pub fn check_storage() -> Result<(), StorageProblem> {
+    let Some(layout) = berth_layout() else {
+        return Ok(());
+    };
+    check_writable(&layout.config, MIN_FREE_BYTES)?;
+    if layout.state != layout.config {
+        check_writable(&layout.state, MIN_FREE_BYTES)?;
     }
-    dirs::home_dir().map(|h| h.join(".berth"))
+    Ok(())
 }
 
-/// Returns Berth server config directory (`~/.berth/servers`).
+/// Returns Berth server config directory (`<config>/servers`).
 pub fn berth_servers_dir() -> Option<PathBuf> {
-    berth_home().map(|h| h.join("servers"))
+    berth_config_dir().map(|h| h.join("servers"))
 }
 
-/// Returns Berth binary install directory (`~/.berth/bin`).
+/// Returns Berth binary install directory (`<state>/bin`).
 pub fn berth_bin_dir() -> Option<PathBuf> {
     berth_home().map(|h| h.join("bin"))
 }
@@ -31,7 +67,7 @@ pub fn server_config_path(name: &str) -> Option<PathBuf> {
 
 /// Returns the permissions override file path for a server.
 pub fn permissions_override_path(name: &str) -> Option<PathBuf> {
-    berth_home().map(|h| h.join("permissions").join(format!("{name}.toml")))
+    berth_config_dir().map(|h| h.join("permissions").join(format!("{name}.toml")))
 }
 
 /// Returns the audit JSONL log path.
@@ -41,7 +77,37 @@ pub fn audit_log_path() -> Option<PathBuf> {
 
 /// Returns the global org-policy file path.
 pub fn policy_path() -> Option<PathBuf> {
-    berth_home().map(|h| h.join("policy.toml"))
+    berth_config_dir().map(|h| h.join("policy.toml"))
+}
+
+/// Returns the per-client protocol compatibility settings path.
+pub fn client_compat_path() -> Option<PathBuf> {
+    berth_config_dir().map(|h| h.join("compat.toml"))
+}
+
+/// Returns the egress monitoring log path for a server.
+pub fn egress_log_path(name: &str) -> Option<PathBuf> {
+    berth_home().map(|h| h.join("egress").join(format!("{name}.jsonl")))
+}
+
+/// Returns the directory holding the node and python egress hooks.
+pub fn egress_hooks_dir() -> Option<PathBuf> {
+    berth_home().map(|h| h.join("egress").join("hooks"))
+}
+
+/// Returns the directory holding config snapshots for one linked client.
+pub fn client_backups_dir(client: &str) -> Option<PathBuf> {
+    berth_home().map(|h| h.join("backups").join("clients").join(client))
+}
+
+/// Returns the directory holding install and update receipts for one server.
+pub fn receipts_dir(name: &str) -> Option<PathBuf> {
+    berth_home().map(|h| h.join("receipts").join(name))
+}
+
+/// Returns the file holding persisted per-server error budget windows.
+pub fn error_budget_path() -> Option<PathBuf> {
+    berth_home().map(|h| h.join("error-budget.json"))
 }
 
 /// Returns the local publish queue directory path.
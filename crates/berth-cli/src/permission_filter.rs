@@ -3,6 +3,10 @@
 
 //! Shared permission override and effective-permission helpers.
 
+use berth_common::atomic_write_private;
+use berth_registry::config::PermissionsInfo;
+use berth_registry::permissions::{permits, NetworkPermission, Permission};
+use berth_runtime::AuditActor;
 use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, BTreeSet};
 use std::fs;
@@ -12,6 +16,9 @@ use crate::paths;
 /// Prefix used in user-facing errors for network-permission launch denials.
 pub const NETWORK_PERMISSION_DENIED_PREFIX: &str = "Network permission denied";
 
+/// Prefix used in JSON-RPC errors for tool calls the proxy rejects.
+pub const TOOL_PERMISSION_DENIED_PREFIX: &str = "Permission denied";
+
 /// User-managed permission overrides for a server.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct PermissionOverrides {
@@ -47,7 +54,7 @@ pub fn write_permission_overrides(
     }
     let rendered = toml::to_string_pretty(overrides)
         .map_err(|e| format!("Failed to serialize overrides: {e}"))?;
-    fs::write(path, rendered).map_err(|e| format!("Failed to write overrides: {e}"))
+    atomic_write_private(&path, rendered).map_err(|e| format!("Failed to write overrides: {e}"))
 }
 
 /// Clears persisted permission overrides for a server.
@@ -62,21 +69,7 @@ pub fn clear_permission_overrides(server: &str) -> Result<(), String> {
 
 /// Validates one user-supplied permission string.
 pub fn validate_permission_syntax(permission: &str) -> Result<(), String> {
-    if let Some(value) = permission.strip_prefix("env:") {
-        return validate_env_permission(value, permission);
-    }
-    if let Some(value) = permission.strip_prefix("network:") {
-        return validate_network_permission(value, permission);
-    }
-    if let Some(value) = permission.strip_prefix("filesystem:") {
-        return validate_filesystem_permission(value, permission);
-    }
-    if let Some(value) = permission.strip_prefix("exec:") {
-        return validate_exec_permission(value, permission);
-    }
-    Err(format!(
-        "Invalid permission format `{permission}`. Use `env:<VAR>`, `env:*`, `network:<host>:<port>`, `network:*`, `filesystem:<read|write>:<path>`, `filesystem:*`, `exec:<command>`, or `exec:*`."
-    ))
+    permission.parse::<Permission>().map(|_| ())
 }
 
 /// Computes effective permissions of one prefix (`env` or `network`).
@@ -99,13 +92,15 @@ pub fn effective_permissions(
         }
     }
 
+    // Revocations win: a revoke drops every entry it covers, e.g.
+    // `network:*.github.com:443` also drops `api.github.com:443`.
     for perm in &overrides.revoke {
         if let Some(value) = perm.strip_prefix(&prefix_with_colon) {
             if value == "*" {
                 allow_all = false;
                 values.clear();
             } else {
-                values.remove(value);
+                values.retain(|entry| entry != value && !covers(prefix, value, entry));
             }
         }
     }
@@ -155,12 +150,18 @@ pub fn undeclared_network_grants(
     declared_network: &[String],
     overrides: &PermissionOverrides,
 ) -> Vec<String> {
-    let declared: BTreeSet<&str> = declared_network.iter().map(String::as_str).collect();
+    let declared: Vec<NetworkPermission> = declared_network
+        .iter()
+        .filter_map(|entry| entry.parse().ok())
+        .collect();
     let mut out: BTreeSet<String> = BTreeSet::new();
 
     for grant in &overrides.grant {
         if let Some(value) = grant.strip_prefix("network:") {
-            if value == "*" || !declared.contains(value) {
+            let covered = value
+                .parse::<NetworkPermission>()
+                .is_ok_and(|parsed| declared.iter().any(|entry| entry.covers(&parsed)));
+            if !covered {
                 out.insert(value.to_string());
             }
         }
@@ -169,110 +170,122 @@ pub fn undeclared_network_grants(
     out.into_iter().collect()
 }
 
-fn validate_env_permission(value: &str, original: &str) -> Result<(), String> {
-    if value == "*" {
-        return Ok(());
-    }
-    if value.is_empty() {
-        return Err(format!(
-            "Invalid permission format `{original}`. Environment variable name is required."
-        ));
-    }
-    let mut chars = value.chars();
-    let first = chars.next().unwrap_or_default();
-    if !(first.is_ascii_uppercase() || first == '_') {
-        return Err(format!(
-            "Invalid permission format `{original}`. Env vars must match `[A-Z_][A-Z0-9_]*`."
-        ));
-    }
-    if !chars.all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_') {
-        return Err(format!(
-            "Invalid permission format `{original}`. Env vars must match `[A-Z_][A-Z0-9_]*`."
-        ));
-    }
-    Ok(())
+/// Returns the permissions `tool` declares it needs that the effective
+/// permissions do not grant.
+///
+/// Revocations inside a broader effective grant still deny, and entries that
+/// fail to parse are always reported so the check fails closed.
+pub fn missing_tool_permissions(
+    declared: &PermissionsInfo,
+    tool: &str,
+    overrides: &PermissionOverrides,
+) -> Vec<String> {
+    let Some(needs) = declared.tools.get(tool) else {
+        return Vec::new();
+    };
+    let revoked: Vec<Permission> = overrides
+        .revoke
+        .iter()
+        .filter_map(|entry| entry.parse().ok())
+        .collect();
+    needs
+        .iter()
+        .filter(|entry| {
+            let Ok(request) = entry.parse::<Permission>() else {
+                return true;
+            };
+            let kind = request.kind();
+            let class = match kind {
+                "network" => &declared.network,
+                "env" => &declared.env,
+                "filesystem" => &declared.filesystem,
+                _ => &declared.exec,
+            };
+            let granted: Vec<Permission> = effective_permissions(kind, class, overrides)
+                .iter()
+                .filter_map(|value| format!("{kind}:{value}").parse().ok())
+                .collect();
+            !permits(&granted, &revoked, &request)
+        })
+        .cloned()
+        .collect()
 }
 
-fn validate_network_permission(value: &str, original: &str) -> Result<(), String> {
-    if value == "*" {
-        return Ok(());
-    }
-    let (host, port) = value.split_once(':').ok_or_else(|| {
-        format!(
-            "Invalid permission format `{original}`. Network permissions must be `network:<host>:<port>`."
-        )
-    })?;
-    if host.is_empty() {
-        return Err(format!(
-            "Invalid permission format `{original}`. Host is required."
-        ));
-    }
-    if host != "*"
-        && !host
-            .chars()
-            .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-')
-    {
-        return Err(format!(
-            "Invalid permission format `{original}`. Host may contain only letters, digits, `.`, or `-`."
-        ));
-    }
-    if port == "*" {
-        return Ok(());
-    }
-    let parsed_port: u16 = port.parse().map_err(|_| {
-        format!("Invalid permission format `{original}`. Port must be `*` or 1-65535.")
-    })?;
-    if parsed_port == 0 {
-        return Err(format!(
-            "Invalid permission format `{original}`. Port must be `*` or 1-65535."
-        ));
-    }
-    Ok(())
+/// Returns all effective permissions, prefixed with their kind.
+pub fn effective_permission_set(
+    declared: &PermissionsInfo,
+    overrides: &PermissionOverrides,
+) -> Vec<String> {
+    [
+        ("network", &declared.network),
+        ("env", &declared.env),
+        ("filesystem", &declared.filesystem),
+        ("exec", &declared.exec),
+    ]
+    .into_iter()
+    .flat_map(|(kind, class)| {
+        effective_permissions(kind, class, overrides)
+            .into_iter()
+            .map(move |value| format!("{kind}:{value}"))
+    })
+    .collect()
 }
 
-fn validate_filesystem_permission(value: &str, original: &str) -> Result<(), String> {
-    if value == "*" {
-        return Ok(());
-    }
-    let (mode, path) = value.split_once(':').ok_or_else(|| {
-        format!(
-            "Invalid permission format `{original}`. Filesystem permissions must be `filesystem:<read|write>:<path>`."
-        )
-    })?;
-    if !matches!(mode, "read" | "write") {
-        return Err(format!(
-            "Invalid permission format `{original}`. Mode must be `read` or `write`."
-        ));
-    }
-    if path.trim().is_empty() {
-        return Err(format!(
-            "Invalid permission format `{original}`. Filesystem path is required."
-        ));
-    }
-    Ok(())
+/// Records a change of `server`'s overrides from `before` to `after` in the
+/// audit log, with the user who made it.
+///
+/// `change` is the operation followed by the permissions it named, e.g.
+/// `["grant", "network:*:443"]`. Audit failures do not undo the change.
+pub fn audit_permission_change(
+    server: &str,
+    change: &[&str],
+    declared: &PermissionsInfo,
+    before: &PermissionOverrides,
+    after: &PermissionOverrides,
+) {
+    let Some(runtime) = paths::runtime_manager() else {
+        return;
+    };
+    let change: Vec<String> = change.iter().map(ToString::to_string).collect();
+    let _ = runtime.record_permission_change(
+        server,
+        &change,
+        AuditActor::current(),
+        effective_permission_set(declared, before),
+        effective_permission_set(declared, after),
+    );
 }
 
-fn validate_exec_permission(value: &str, original: &str) -> Result<(), String> {
-    if value == "*" {
-        return Ok(());
+/// Returns whether `pattern` covers `value`, both unprefixed entries of `prefix`.
+pub fn covers(prefix: &str, pattern: &str, value: &str) -> bool {
+    let parse = |raw: &str| format!("{prefix}:{raw}").parse::<Permission>().ok();
+    match (parse(pattern), parse(value)) {
+        (Some(pattern), Some(value)) => pattern.covers(&value),
+        _ => false,
     }
-    if value.trim().is_empty() {
-        return Err(format!(
-            "Invalid permission format `{original}`. Exec command is required."
-        ));
-    }
-    if value.contains(char::is_whitespace) {
-        return Err(format!(
-            "Invalid permission format `{original}`. Exec command must not contain whitespace."
-        ));
-    }
-    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn effective_permission_set_prefixes_every_kind() {
+        let declared = PermissionsInfo {
+            network: vec!["api.github.com:443".to_string()],
+            env: vec!["GITHUB_TOKEN".to_string()],
+            ..PermissionsInfo::default()
+        };
+        let overrides = PermissionOverrides {
+            grant: vec!["filesystem:read:/tmp".to_string()],
+            revoke: vec!["env:GITHUB_TOKEN".to_string()],
+        };
+        assert_eq!(
+            effective_permission_set(&declared, &overrides),
+            ["network:api.github.com:443", "filesystem:read:/tmp"]
+        );
+    }
+
     #[test]
     fn effective_permissions_apply_grant_and_revoke() {
         let overrides = PermissionOverrides {
@@ -339,6 +352,82 @@ mod tests {
         );
     }
 
+    #[test]
+    fn revoke_removes_entries_it_covers() {
+        let overrides = PermissionOverrides {
+            grant: vec!["network:10.1.0.0/16:5432".to_string()],
+            revoke: vec![
+                "network:*.github.com:*".to_string(),
+                "network:10.0.0.0/8:5432".to_string(),
+            ],
+        };
+        let declared = vec![
+            "api.github.com:443".to_string(),
+            "registry.npmjs.org:443".to_string(),
+        ];
+        assert_eq!(
+            effective_permissions("network", &declared, &overrides),
+            vec!["registry.npmjs.org:443".to_string()]
+        );
+    }
+
+    #[test]
+    fn undeclared_network_grants_ignores_grants_covered_by_wildcards() {
+        let declared = vec!["*.github.com:443".to_string()];
+        let overrides = PermissionOverrides {
+            grant: vec![
+                "network:api.github.com:443".to_string(),
+                "network:api.github.com:80".to_string(),
+            ],
+            revoke: vec![],
+        };
+        assert_eq!(
+            undeclared_network_grants(&declared, &overrides),
+            vec!["api.github.com:80".to_string()]
+        );
+    }
+
+    #[test]
+    fn missing_tool_permissions_respects_grants_and_narrow_revokes() {
+        let declared = PermissionsInfo {
+            network: vec!["*.github.com:443".to_string()],
+            filesystem: vec!["read:/workspace".to_string()],
+            tools: BTreeMap::from([
+                (
+                    "create_issue".to_string(),
+                    vec!["network:api.github.com:443".to_string()],
+                ),
+                (
+                    "write_file".to_string(),
+                    vec!["filesystem:write:/workspace/out".to_string()],
+                ),
+                ("broken".to_string(), vec!["network:nope".to_string()]),
+            ]),
+            ..PermissionsInfo::default()
+        };
+        let none = PermissionOverrides::default();
+        assert!(missing_tool_permissions(&declared, "create_issue", &none).is_empty());
+        assert!(missing_tool_permissions(&declared, "search", &none).is_empty());
+        assert_eq!(
+            missing_tool_permissions(&declared, "write_file", &none),
+            ["filesystem:write:/workspace/out"]
+        );
+        assert_eq!(
+            missing_tool_permissions(&declared, "broken", &none),
+            ["network:nope"]
+        );
+
+        let overrides = PermissionOverrides {
+            grant: vec!["filesystem:write:/workspace".to_string()],
+            revoke: vec!["network:api.github.com:443".to_string()],
+        };
+        assert_eq!(
+            missing_tool_permissions(&declared, "create_issue", &overrides),
+            ["network:api.github.com:443"]
+        );
+        assert!(missing_tool_permissions(&declared, "write_file", &overrides).is_empty());
+    }
+
     #[test]
     fn validate_permission_syntax_accepts_valid_formats() {
         assert!(validate_permission_syntax("env:GITHUB_TOKEN").is_ok());
@@ -346,6 +435,9 @@ mod tests {
         assert!(validate_permission_syntax("network:api.github.com:443").is_ok());
         assert!(validate_permission_syntax("network:*:443").is_ok());
         assert!(validate_permission_syntax("network:*").is_ok());
+        assert!(validate_permission_syntax("network:*.github.com:443").is_ok());
+        assert!(validate_permission_syntax("network:10.0.0.0/8:*").is_ok());
+        assert!(validate_permission_syntax("filesystem:read:~/projects/**").is_ok());
         assert!(validate_permission_syntax("filesystem:read:/tmp").is_ok());
         assert!(validate_permission_syntax("filesystem:write:/var/log").is_ok());
         assert!(validate_permission_syntax("filesystem:*").is_ok());
@@ -0,0 +1,272 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Interactive decisions for tool calls outside a server's permissions.
+//!
+//! With `berth.permission-prompt=tty`, `berth proxy` and `berth wrap` ask on
+//! the controlling terminal before rejecting such a call; with
+//! `berth.permission-prompt=hook:<program>` they ask a notification hook
+//! instead. "Allow always" is written to the server's permission overrides,
+//! the same file `berth permissions --grant` edits.
+
+use berth_registry::config::PermissionsInfo;
+use colored::Colorize;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::permission_filter::{
+    audit_permission_change, load_permission_overrides, write_permission_overrides,
+};
+use crate::platform;
+
+pub const KEY_PERMISSION_PROMPT: &str = "berth.permission-prompt";
+
+/// How long a hook may take to answer before the call is denied.
+pub const HOOK_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Where undeclared tool calls are decided.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum PermissionPrompt {
+    /// Reject without asking.
+    #[default]
+    Off,
+    /// Ask on the controlling terminal.
+    Tty,
+    /// Ask a program, which gets the request as JSON on stdin and answers on stdout.
+    Hook(String),
+}
+
+impl PermissionPrompt {
+    pub fn as_str(&self) -> String {
+        match self {
+            PermissionPrompt::Off => "off".to_string(),
+            PermissionPrompt::Tty => "tty".to_string(),
+            PermissionPrompt::Hook(program) => format!("hook:{program}"),
+        }
+    }
+}
+
+/// Answer to a prompt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    /// Allow this call only.
+    Once,
+    /// Allow this call and grant the permissions from now on.
+    Always,
+    /// Reject the call.
+    Deny,
+}
+
+impl Decision {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Decision::Once => "once",
+            Decision::Always => "always",
+            Decision::Deny => "deny",
+        }
+    }
+
+    /// Parses a terminal or hook answer; an empty answer denies.
+    pub fn parse(answer: &str) -> Option<Self> {
+        match answer.trim().to_ascii_lowercase().as_str() {
+            "o" | "once" | "allow-once" => Some(Decision::Once),
+            "a" | "always" | "allow-always" => Some(Decision::Always),
+            "" | "d" | "deny" => Some(Decision::Deny),
+            _ => None,
+        }
+    }
+}
+
+/// Returns whether a key is reserved for permission prompt settings.
+pub fn is_permission_prompt_key(key: &str) -> bool {
+    key == KEY_PERMISSION_PROMPT
+}
+
+/// Validates one key/value pair for permission prompt settings.
+pub fn validate_permission_prompt_value(key: &str, value: &str) -> Result<(), String> {
+    match key {
+        KEY_PERMISSION_PROMPT => parse_mode(value).map(|_| ()),
+        _ => Err(format!("Unknown permission prompt key: {key}")),
+    }
+}
+
+/// Parses where undeclared tool calls are decided; prompting is off by default.
+pub fn parse_permission_prompt(
+    config: &BTreeMap<String, String>,
+) -> Result<PermissionPrompt, String> {
+    match config.get(KEY_PERMISSION_PROMPT) {
+        Some(v) => parse_mode(v),
+        None => Ok(PermissionPrompt::Off),
+    }
+}
+
+fn parse_mode(value: &str) -> Result<PermissionPrompt, String> {
+    let value = value.trim();
+    if let Some(program) = value.strip_prefix("hook:") {
+        if program.trim().is_empty() {
+            return Err("Invalid value `hook:`. Name the hook program after `hook:`.".to_string());
+        }
+        return Ok(PermissionPrompt::Hook(program.trim().to_string()));
+    }
+    match value.to_ascii_lowercase().as_str() {
+        "off" => Ok(PermissionPrompt::Off),
+        "tty" => Ok(PermissionPrompt::Tty),
+        _ => Err(format!(
+            "Invalid value `{value}`. Expected `off`, `tty`, or `hook:<program>`."
+        )),
+    }
+}
+
+/// Asks whether `tool` may run although the effective permissions lack `missing`.
+///
+/// Errors mean nobody could be asked; callers deny the call.
+pub fn ask(
+    prompt: &PermissionPrompt,
+    server: &str,
+    tool: &str,
+    missing: &[String],
+) -> Result<Decision, String> {
+    match prompt {
+        PermissionPrompt::Off => Ok(Decision::Deny),
+        PermissionPrompt::Tty => ask_terminal(server, tool, missing),
+        PermissionPrompt::Hook(program) => ask_hook(program, server, tool, missing),
+    }
+}
+
+fn ask_terminal(server: &str, tool: &str, missing: &[String]) -> Result<Decision, String> {
+    let (input, mut output) =
+        platform::open_terminal().map_err(|e| format!("No terminal to ask on: {e}"))?;
+    let mut input = BufReader::new(input);
+    writeln!(
+        output,
+        "{} {} wants to call tool `{}`, which needs {}.",
+        "!".yellow().bold(),
+        server.cyan(),
+        tool,
+        missing.join(", ").bold()
+    )
+    .map_err(|e| format!("Failed to write to terminal: {e}"))?;
+    loop {
+        write!(output, "  Allow [o]nce, [a]lways, or [d]eny? [d] ")
+            .and_then(|()| output.flush())
+            .map_err(|e| format!("Failed to write to terminal: {e}"))?;
+        let mut line = String::new();
+        let read = input
+            .read_line(&mut line)
+            .map_err(|e| format!("Failed to read from terminal: {e}"))?;
+        if read == 0 {
+            return Ok(Decision::Deny);
+        }
+        if let Some(decision) = Decision::parse(&line) {
+            return Ok(decision);
+        }
+    }
+}
+
+fn ask_hook(
+    program: &str,
+    server: &str,
+    tool: &str,
+    missing: &[String],
+) -> Result<Decision, String> {
+    let request = json!({ "server": server, "tool": tool, "permissions": missing });
+    let mut child = Command::new(program)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Permission prompt hook `{program}` failed to start: {e}"))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = writeln!(stdin, "{request}");
+    }
+    let mut stdout = child.stdout.take().expect("hook stdout is piped");
+    let reader = thread::spawn(move || {
+        let mut answer = String::new();
+        let _ = stdout.read_to_string(&mut answer);
+        answer
+    });
+    let started = Instant::now();
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) => break,
+            Ok(None) if started.elapsed() < HOOK_TIMEOUT => {
+                thread::sleep(Duration::from_millis(20));
+            }
+            _ => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!(
+                    "Permission prompt hook `{program}` did not answer within {}s.",
+                    HOOK_TIMEOUT.as_secs()
+                ));
+            }
+        }
+    }
+    let answer = reader.join().unwrap_or_default();
+    let first = answer.lines().next().unwrap_or_default();
+    Decision::parse(first).ok_or_else(|| {
+        format!(
+            "Permission prompt hook `{program}` answered `{}`; expected once, always, or deny.",
+            first.trim()
+        )
+    })
+}
+
+/// Grants `missing` to `server` in its permission overrides and audits the
+/// change.
+///
+/// Exact revocations of the same entries are lifted; broader revocations stay
+/// and keep denying.
+pub fn remember(
+    server: &str,
+    declared: &PermissionsInfo,
+    missing: &[String],
+) -> Result<(), String> {
+    let before = load_permission_overrides(server)?;
+    let mut overrides = before.clone();
+    overrides.revoke.retain(|entry| !missing.contains(entry));
+    for entry in missing {
+        if !overrides.grant.contains(entry) {
+            overrides.grant.push(entry.clone());
+        }
+    }
+    write_permission_overrides(server, &overrides)?;
+    let change: Vec<&str> = std::iter::once("prompt-grant")
+        .chain(missing.iter().map(String::as_str))
+        .collect();
+    audit_permission_change(server, &change, declared, &before, &overrides);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_modes_and_answers() {
+        let cfg = BTreeMap::from([(
+            KEY_PERMISSION_PROMPT.to_string(),
+            "hook:/usr/local/bin/ask".to_string(),
+        )]);
+        assert_eq!(
+            parse_permission_prompt(&cfg).unwrap(),
+            PermissionPrompt::Hook("/usr/local/bin/ask".to_string())
+        );
+        assert_eq!(
+            parse_permission_prompt(&BTreeMap::new()).unwrap(),
+            PermissionPrompt::Off
+        );
+        assert!(validate_permission_prompt_value(KEY_PERMISSION_PROMPT, "TTY").is_ok());
+        assert!(validate_permission_prompt_value(KEY_PERMISSION_PROMPT, "hook:").is_err());
+        assert!(validate_permission_prompt_value(KEY_PERMISSION_PROMPT, "ask").is_err());
+
+        assert_eq!(Decision::parse("a\n"), Some(Decision::Always));
+        assert_eq!(Decision::parse("Once"), Some(Decision::Once));
+        assert_eq!(Decision::parse(""), Some(Decision::Deny));
+        assert_eq!(Decision::parse("yes"), None);
+    }
+}
@@ -0,0 +1,279 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Platform helpers for console setup, portable file names, and Windows integration.
+
+/// Characters Windows rejects in file names.
+const RESERVED_CHARS: [char; 9] = ['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+
+/// Device names Windows reserves regardless of extension.
+const RESERVED_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Prepares the console for colored output.
+///
+/// Legacy Windows consoles need virtual terminal processing enabled before
+/// ANSI escapes render; when that fails colors are turned off instead of
+/// printing raw escape codes.
+pub fn init_console() {
+    #[cfg(windows)]
+    if colored::control::set_virtual_terminal(true).is_err() {
+        colored::control::set_override(false);
+    }
+}
+
+/// Maps a name onto a file name that is valid on Windows, macOS, and Linux.
+///
+/// Reserved characters and control characters become `_`, trailing dots and
+/// spaces are dropped, and reserved device names get a `_` prefix.
+pub fn safe_file_name(raw: &str) -> String {
+    let mut out: String = raw
+        .chars()
+        .map(|c| {
+            if c.is_control() || RESERVED_CHARS.contains(&c) {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect();
+    while out.ends_with(['.', ' ']) {
+        out.pop();
+    }
+    if out.is_empty() {
+        return "_".to_string();
+    }
+    let stem = out.split('.').next().unwrap_or_default();
+    if RESERVED_NAMES.iter().any(|r| r.eq_ignore_ascii_case(stem)) {
+        out.insert(0, '_');
+    }
+    out
+}
+
+/// Returns whether a name can be used as-is as a file name on every platform.
+pub fn is_portable_file_name(raw: &str) -> bool {
+    safe_file_name(raw) == raw
+}
+
+/// Returns whether `url` is an `http(s)` URL that is safe to hand to a
+/// launcher as a single argument.
+fn is_browser_url(url: &str) -> bool {
+    let lower = url.to_ascii_lowercase();
+    (lower.starts_with("https://") || lower.starts_with("http://"))
+        && !url.chars().any(|c| c.is_whitespace() || c.is_control())
+}
+
+/// Opens an `http(s)` URL in the user's default browser.
+///
+/// Other schemes and file paths are refused, since manifests supply some of
+/// these URLs. Windows goes through `url.dll` rather than `cmd /C start`, so
+/// no shell ever parses the URL.
+pub fn open_url(url: &str) -> Result<(), String> {
+    if !is_browser_url(url) {
+        return Err(format!("refusing to open '{url}': not an http(s) URL"));
+    }
+    let mut command = if cfg!(windows) {
+        let mut c = std::process::Command::new("rundll32");
+        c.arg("url.dll,FileProtocolHandler");
+        c
+    } else if cfg!(target_os = "macos") {
+        std::process::Command::new("open")
+    } else {
+        std::process::Command::new("xdg-open")
+    };
+    let status = command
+        .arg(url)
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .map_err(|e| format!("failed to launch browser: {e}"))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("browser launcher exited with {status}"))
+    }
+}
+
+/// Opens the controlling terminal for reading and writing, even when stdin
+/// and stdout are pipes to an MCP client.
+pub fn open_terminal() -> std::io::Result<(std::fs::File, std::fs::File)> {
+    let (input, output) = if cfg!(windows) {
+        ("CONIN$", "CONOUT$")
+    } else {
+        ("/dev/tty", "/dev/tty")
+    };
+    let reader = std::fs::File::open(input)?;
+    let writer = std::fs::OpenOptions::new().write(true).open(output)?;
+    Ok((reader, writer))
+}
+
+/// Name of the scheduled task `berth daemon install` registers on Windows.
+pub const LOGON_TASK: &str = "Berth";
+
+/// Returns the `schtasks` arguments that register a task starting the Berth
+/// home's servers, each with its own supervisor, when the current user signs in.
+pub fn logon_task_create_args(exe: &std::path::Path) -> Vec<String> {
+    [
+        "/Create", "/TN", LOGON_TASK, "/SC", "ONLOGON", "/RL", "LIMITED", "/F", "/TR",
+    ]
+    .into_iter()
+    .map(str::to_string)
+    .chain([format!("\"{}\" start", exe.display())])
+    .collect()
+}
+
+/// Returns the `schtasks` arguments that remove the logon task.
+pub fn logon_task_delete_args() -> Vec<String> {
+    ["/Delete", "/TN", LOGON_TASK, "/F"]
+        .into_iter()
+        .map(str::to_string)
+        .collect()
+}
+
+/// Runs `schtasks` with `args`, returning its error output on failure.
+pub fn schtasks(args: &[String]) -> Result<(), String> {
+    let output = std::process::Command::new("schtasks")
+        .args(args)
+        .stdin(std::process::Stdio::null())
+        .output()
+        .map_err(|e| format!("failed to run schtasks: {e}"))?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    Err(format!(
+        "schtasks exited with {}: {}",
+        output.status,
+        stderr.trim()
+    ))
+}
+
+/// Windows-only integration with the user's DPAPI credential protection.
+#[cfg(windows)]
+pub mod windows {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    const PROTECT_SCRIPT: &str = "$s = [Console]::In.ReadToEnd(); \
+         ConvertTo-SecureString -String $s -AsPlainText -Force | ConvertFrom-SecureString";
+    const UNPROTECT_SCRIPT: &str = "$b = [Console]::In.ReadToEnd().Trim(); \
+         $ss = ConvertTo-SecureString -String $b; \
+         [Runtime.InteropServices.Marshal]::PtrToStringBSTR(\
+         [Runtime.InteropServices.Marshal]::SecureStringToBSTR($ss))";
+
+    /// Encrypts a value for the current Windows user; returns an opaque blob.
+    pub fn protect(value: &str) -> Result<String, String> {
+        run_powershell(PROTECT_SCRIPT, value)
+    }
+
+    /// Decrypts a blob produced by [`protect`] for the same Windows user.
+    pub fn unprotect(blob: &str) -> Result<String, String> {
+        run_powershell(UNPROTECT_SCRIPT, blob)
+    }
+
+    /// Runs a PowerShell script with `input` on stdin so secrets never reach argv.
+    fn run_powershell(script: &str, input: &str) -> Result<String, String> {
+        let mut child = Command::new("powershell")
+            .args(["-NoProfile", "-NonInteractive", "-Command", script])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("failed to invoke powershell: {e}"))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(input.as_bytes())
+                .map_err(|e| format!("failed to write to powershell stdin: {e}"))?;
+        }
+        let output = child
+            .wait_with_output()
+            .map_err(|e| format!("failed waiting for powershell: {e}"))?;
+        if !output.status.success() {
+            return Err(format!(
+                "DPAPI operation failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        String::from_utf8(output.stdout)
+            .map(|s| s.trim_end_matches(['\r', '\n']).to_string())
+            .map_err(|e| format!("powershell output was not valid utf-8: {e}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn safe_file_name_replaces_reserved_characters() {
+        assert_eq!(
+            safe_file_name("2026-10-16T12:00:00Z.log"),
+            "2026-10-16T12_00_00Z.log"
+        );
+        assert_eq!(safe_file_name("a/b\\c|d?e*f"), "a_b_c_d_e_f");
+        assert_eq!(safe_file_name("tab\there"), "tab_here");
+    }
+
+    #[test]
+    fn safe_file_name_handles_reserved_names_and_trailing_dots() {
+        assert_eq!(safe_file_name("con"), "_con");
+        assert_eq!(safe_file_name("NUL.log"), "_NUL.log");
+        assert_eq!(safe_file_name("console"), "console");
+        assert_eq!(safe_file_name("server. "), "server");
+        assert_eq!(safe_file_name(".."), "_");
+        assert_eq!(safe_file_name(""), "_");
+    }
+
+    #[test]
+    fn portable_file_names_round_trip() {
+        assert!(is_portable_file_name("github"));
+        assert!(is_portable_file_name("brave-search"));
+        assert!(!is_portable_file_name("aux"));
+        assert!(!is_portable_file_name("a:b"));
+        assert!(!is_portable_file_name("."));
+    }
+
+    #[test]
+    fn only_plain_http_urls_are_opened() {
+        assert!(is_browser_url(
+            "https://github.com/login/oauth/authorize?a=1&b=2"
+        ));
+        assert!(is_browser_url("HTTP://localhost:8080/"));
+        assert!(!is_browser_url("file:///etc/passwd"));
+        assert!(!is_browser_url("C:\\Windows\\System32\\calc.exe"));
+        assert!(!is_browser_url("https://example.com/ & calc"));
+        assert!(open_url("javascript:alert(1)").is_err());
+    }
+
+    #[test]
+    fn logon_task_runs_berth_start_for_the_current_user() {
+        let args =
+            logon_task_create_args(std::path::Path::new("C:\\Program Files\\Berth\\berth.exe"));
+        assert_eq!(
+            args,
+            [
+                "/Create",
+                "/TN",
+                "Berth",
+                "/SC",
+                "ONLOGON",
+                "/RL",
+                "LIMITED",
+                "/F",
+                "/TR",
+                "\"C:\\Program Files\\Berth\\berth.exe\" start",
+            ]
+        );
+        assert_eq!(logon_task_delete_args(), ["/Delete", "/TN", "Berth", "/F"]);
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn dpapi_round_trips_for_current_user() {
+        let blob = windows::protect("s3cret value").unwrap();
+        assert!(!blob.contains("s3cret"));
+        assert_eq!(windows::unprotect(&blob).unwrap(), "s3cret value");
+    }
+}
@@ -0,0 +1,395 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! External plugins that receive Berth events.
+//!
+//! A plugin is an executable in `<config>/plugins/` (`~/.berth/plugins/` in the
+//! default layout). Plugins start out disabled; `berth plugins <name> --enable`
+//! records them in `<config>/plugins.toml`. Every audit entry is then written,
+//! as one JSON line, to the stdin of a fresh plugin process:
+//!
+//! ```text
+//! {"protocol":1,"kind":"audit","event":{"timestampEpochMs":...,"server":"github","action":"start",...}}
+//! ```
+//!
+//! Deliveries run in order on a background thread so servers and proxies never
+//! wait on a plugin. A plugin that runs past its timeout is killed. Output and
+//! failures go to `<state>/plugins/<name>.log`.
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use berth_runtime::{AuditRecord, AuditSink};
+
+use crate::paths::BerthLayout;
+
+/// Version of the stdin event envelope.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Set to the plugin name inside plugin processes; audit entries written
+/// there are not delivered again, so a plugin calling `berth` cannot loop.
+pub const PLUGIN_ENV: &str = "BERTH_PLUGIN";
+
+/// How long a plugin may run per event unless configured otherwise.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+const SETTINGS_FILE: &str = "plugins.toml";
+
+/// Contents of `plugins.toml`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PluginSettings {
+    #[serde(default)]
+    pub plugins: BTreeMap<String, PluginEntry>,
+}
+
+/// Settings of one plugin.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct PluginEntry {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+}
+
+/// A discovered plugin with its settings applied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Plugin {
+    pub name: String,
+    pub path: PathBuf,
+    pub enabled: bool,
+    pub timeout: Duration,
+}
+
+/// Directory scanned for plugin executables.
+pub fn plugins_dir(layout: &BerthLayout) -> PathBuf {
+    layout.config.join("plugins")
+}
+
+/// Where a plugin's output and delivery failures are appended.
+pub fn log_path(layout: &BerthLayout, name: &str) -> PathBuf {
+    layout.state.join("plugins").join(format!("{name}.log"))
+}
+
+/// Reads `plugins.toml`; a missing file means no plugin is enabled.
+pub fn load_settings(layout: &BerthLayout) -> Result<PluginSettings, String> {
+    let path = layout.config.join(SETTINGS_FILE);
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(PluginSettings::default()),
+        Err(e) => return Err(format!("Failed to read {}: {e}", path.display())),
+    };
+    toml::from_str(&content).map_err(|e| format!("Failed to parse {}: {e}", path.display()))
+}
+
+fn write_settings(layout: &BerthLayout, settings: &PluginSettings) -> Result<(), String> {
+    let path = layout.config.join(SETTINGS_FILE);
+    let content =
+        toml::to_string(settings).map_err(|e| format!("Failed to encode plugin settings: {e}"))?;
+    fs::create_dir_all(&layout.config)
+        .and_then(|()| fs::write(&path, content))
+        .map_err(|e| format!("Failed to write {}: {e}", path.display()))
+}
+
+/// Lists the executables in the plugins directory, sorted by name.
+pub fn discover(layout: &BerthLayout) -> Result<Vec<Plugin>, String> {
+    let dir = plugins_dir(layout);
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to read {}: {e}", dir.display())),
+    };
+    let settings = load_settings(layout)?;
+    let mut plugins: Vec<Plugin> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| is_executable(path))
+        .filter_map(|path| {
+            let name = path.file_name()?.to_str()?.to_string();
+            if name.starts_with('.') {
+                return None;
+            }
+            let entry = settings.plugins.get(&name).cloned().unwrap_or_default();
+            Some(Plugin {
+                name,
+                path,
+                enabled: entry.enabled,
+                timeout: entry
+                    .timeout_secs
+                    .map_or(DEFAULT_TIMEOUT, Duration::from_secs),
+            })
+        })
+        .collect();
+    plugins.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(plugins)
+}
+
+/// Finds the discovered plugin called `name`.
+pub fn find(layout: &BerthLayout, name: &str) -> Result<Plugin, String> {
+    discover(layout)?
+        .into_iter()
+        .find(|plugin| plugin.name == name)
+        .ok_or_else(|| {
+            format!(
+                "No plugin named '{name}' in {}.",
+                plugins_dir(layout).display()
+            )
+        })
+}
+
+/// Enables or disables `name`, and sets its timeout when given.
+pub fn configure(
+    layout: &BerthLayout,
+    name: &str,
+    enabled: Option<bool>,
+    timeout_secs: Option<u64>,
+) -> Result<Plugin, String> {
+    find(layout, name)?;
+    if timeout_secs == Some(0) {
+        return Err("--timeout must be at least 1 second.".to_string());
+    }
+    let mut settings = load_settings(layout)?;
+    let entry = settings.plugins.entry(name.to_string()).or_default();
+    if let Some(enabled) = enabled {
+        entry.enabled = enabled;
+    }
+    if timeout_secs.is_some() {
+        entry.timeout_secs = timeout_secs;
+    }
+    write_settings(layout, &settings)?;
+    find(layout, name)
+}
+
+/// Wraps `event` in the stdin envelope.
+pub fn envelope(kind: &str, event: &impl Serialize) -> String {
+    json!({ "protocol": PROTOCOL_VERSION, "kind": kind, "event": event }).to_string()
+}
+
+/// Runs `plugin` once with `payload` on stdin, appending its output to its log.
+///
+/// Fails when the plugin cannot start, exits unsuccessfully, or outlives its
+/// timeout, in which case it is killed.
+pub fn deliver(layout: &BerthLayout, plugin: &Plugin, payload: &str) -> Result<(), String> {
+    let log = open_log(layout, &plugin.name)?;
+    let stderr = log
+        .try_clone()
+        .map_err(|e| format!("Failed to open plugin log: {e}"))?;
+    let mut child = Command::new(&plugin.path)
+        .env(PLUGIN_ENV, &plugin.name)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::from(log))
+        .stderr(Stdio::from(stderr))
+        .spawn()
+        .map_err(|e| format!("Plugin '{}' failed to start: {e}", plugin.name))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // A plugin that ignores its input closes the pipe early; its exit status decides.
+        let _ = writeln!(stdin, "{payload}");
+    }
+    let started = Instant::now();
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) if status.success() => return Ok(()),
+            Ok(Some(status)) => {
+                return Err(format!("Plugin '{}' exited with {status}.", plugin.name));
+            }
+            Ok(None) if started.elapsed() < plugin.timeout => {
+                thread::sleep(Duration::from_millis(20));
+            }
+            Ok(None) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!(
+                    "Plugin '{}' did not finish within {}s and was stopped.",
+                    plugin.name,
+                    plugin.timeout.as_secs()
+                ));
+            }
+            Err(e) => return Err(format!("Failed to wait for plugin '{}': {e}", plugin.name)),
+        }
+    }
+}
+
+fn open_log(layout: &BerthLayout, name: &str) -> Result<File, String> {
+    let path = log_path(layout, name);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {e}", parent.display()))?;
+    }
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("Failed to open {}: {e}", path.display()))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path).is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+        && path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| {
+                ["exe", "cmd", "bat", "com"].contains(&ext.to_ascii_lowercase().as_str())
+            })
+}
+
+/// Work for the delivery thread.
+enum Job {
+    Deliver(Vec<Plugin>, String),
+    Flush(Sender<()>),
+}
+
+static DISPATCHER: OnceLock<Mutex<Sender<Job>>> = OnceLock::new();
+
+fn dispatcher(layout: &BerthLayout) -> &'static Mutex<Sender<Job>> {
+    DISPATCHER.get_or_init(|| {
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let layout = layout.clone();
+        thread::spawn(move || {
+            for job in receiver {
+                match job {
+                    Job::Deliver(plugins, payload) => {
+                        for plugin in &plugins {
+                            if let Err(msg) = deliver(&layout, plugin, &payload) {
+                                if let Ok(mut log) = open_log(&layout, &plugin.name) {
+                                    let _ = writeln!(log, "berth: {msg}");
+                                }
+                            }
+                        }
+                    }
+                    Job::Flush(done) => {
+                        let _ = done.send(());
+                    }
+                }
+            }
+        });
+        Mutex::new(sender)
+    })
+}
+
+/// Waits until every event queued so far has been delivered.
+///
+/// Called before the CLI exits, so short-lived commands do not drop events.
+pub fn flush() {
+    let Some(sender) = DISPATCHER.get() else {
+        return;
+    };
+    let (done, wait) = mpsc::channel();
+    let sent = sender
+        .lock()
+        .map(|sender| sender.send(Job::Flush(done)).is_ok())
+        .unwrap_or(false);
+    if sent {
+        let _ = wait.recv();
+    }
+}
+
+/// Forwards audit entries to the enabled plugins.
+struct PluginAuditSink {
+    layout: BerthLayout,
+}
+
+impl AuditSink for PluginAuditSink {
+    fn record(&self, record: &AuditRecord) {
+        if std::env::var_os(PLUGIN_ENV).is_some() {
+            return;
+        }
+        // Read per event, so long-running supervisors pick up enable/disable.
+        let enabled: Vec<Plugin> = discover(&self.layout)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|plugin| plugin.enabled)
+            .collect();
+        if enabled.is_empty() {
+            return;
+        }
+        let job = Job::Deliver(enabled, envelope("audit", record));
+        if let Ok(sender) = dispatcher(&self.layout).lock() {
+            let _ = sender.send(job);
+        }
+    }
+}
+
+/// Returns the audit sink that hands entries to enabled plugins.
+pub fn audit_sink(layout: BerthLayout) -> Arc<dyn AuditSink> {
+    Arc::new(PluginAuditSink { layout })
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    fn write_plugin(layout: &BerthLayout, name: &str, script: &str) {
+        let dir = plugins_dir(layout);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        fs::write(&path, format!("#!/bin/sh\n{script}\n")).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    #[test]
+    fn discovers_executables_disabled_until_enabled() {
+        let tmp = tempfile::tempdir().unwrap();
+        let layout = BerthLayout::single(tmp.path().to_path_buf());
+        write_plugin(&layout, "notify", "cat >/dev/null");
+        fs::write(plugins_dir(&layout).join("README.md"), "not a plugin").unwrap();
+
+        let found = discover(&layout).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].name, "notify");
+        assert!(!found[0].enabled);
+        assert_eq!(found[0].timeout, DEFAULT_TIMEOUT);
+
+        let plugin = configure(&layout, "notify", Some(true), Some(3)).unwrap();
+        assert!(plugin.enabled);
+        assert_eq!(plugin.timeout, Duration::from_secs(3));
+        let plugin = configure(&layout, "notify", Some(false), None).unwrap();
+        assert!(!plugin.enabled);
+        assert_eq!(plugin.timeout, Duration::from_secs(3));
+        assert!(configure(&layout, "missing", Some(true), None).is_err());
+    }
+
+    #[test]
+    fn delivery_passes_event_and_stops_slow_plugins() {
+        let tmp = tempfile::tempdir().unwrap();
+        let layout = BerthLayout::single(tmp.path().to_path_buf());
+        let out = tmp.path().join("event.json");
+        write_plugin(&layout, "echo", &format!("cat > '{}'", out.display()));
+        write_plugin(&layout, "slow", "sleep 5");
+
+        let record = AuditRecord {
+            server: "github".to_string(),
+            action: "start".to_string(),
+            ..AuditRecord::default()
+        };
+        let echo = find(&layout, "echo").unwrap();
+        deliver(&layout, &echo, &envelope("audit", &record)).unwrap();
+        let event: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&out).unwrap()).unwrap();
+        assert_eq!(event["protocol"], PROTOCOL_VERSION);
+        assert_eq!(event["kind"], "audit");
+        assert_eq!(event["event"]["action"], "start");
+
+        let mut slow = find(&layout, "slow").unwrap();
+        slow.timeout = Duration::from_millis(200);
+        let started = Instant::now();
+        let err = deliver(&layout, &slow, "{}").unwrap_err();
+        assert!(err.contains("did not finish"), "{err}");
+        assert!(started.elapsed() < Duration::from_secs(4));
+    }
+}
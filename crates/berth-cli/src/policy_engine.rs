@@ -3,12 +3,17 @@
 
 //! Organization-wide policy enforcement for runtime launches.
 
-use berth_registry::config::PermissionsInfo;
+use berth_registry::config::InstalledServer;
+use berth_registry::permissions::{grants_filesystem_write, NetworkPermission};
+use berth_registry::Registry;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
+use std::path::Path;
 
 use crate::paths;
 use crate::permission_filter::{effective_permissions, PermissionOverrides};
+use crate::secrets::plaintext_sensitive_keys;
 
 /// Prefix used in user-facing errors for org-policy launch denials.
 pub const POLICY_DENIED_PREFIX: &str = "Policy denied";
@@ -20,6 +25,13 @@ pub struct GlobalPolicy {
     pub servers: ServerPolicy,
     #[serde(default)]
     pub permissions: PermissionPolicy,
+    #[serde(default)]
+    pub secrets: SecretPolicy,
+    #[serde(default)]
+    pub storage: StoragePolicy,
+    /// Named policies applied to every server matching their selector.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub policy: BTreeMap<String, ScopedPolicy>,
 }
 
 /// Server-scoped deny list.
@@ -42,6 +54,53 @@ pub struct PermissionPolicy {
     pub deny_exec_wildcard: bool,
 }
 
+/// Rules for how sensitive config values may be stored.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SecretPolicy {
+    /// Blocks servers whose manifest-sensitive config values are stored in
+    /// plaintext instead of the secure backend.
+    #[serde(default)]
+    pub forbid_plaintext: bool,
+}
+
+/// Limits on the disk space the Berth home may use.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StoragePolicy {
+    /// Quota for the whole Berth home in MiB; installs that would exceed it
+    /// are refused.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quota_mb: Option<u64>,
+}
+
+/// Permission classes usable in scoped policy selectors.
+pub const PERMISSION_CLASSES: [&str; 4] = ["network", "env", "filesystem", "exec"];
+
+/// Trust levels usable in `require_trust`.
+pub const TRUST_LEVELS: [&str; 4] = ["untrusted", "community", "verified", "official"];
+
+/// Rules scoped to servers by category, tag, or requested permission class.
+///
+/// Every non-empty selector list must match (any entry within a list); a scope
+/// with no selectors applies to all servers.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScopedPolicy {
+    #[serde(default)]
+    pub categories: Vec<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Permission classes the server must effectively request to match.
+    #[serde(default)]
+    pub permissions: Vec<String>,
+    /// Allowed trust levels for matching servers; empty allows any.
+    #[serde(default)]
+    pub require_trust: Vec<String>,
+    /// Blocks matching servers outright.
+    #[serde(default)]
+    pub deny: bool,
+    #[serde(flatten)]
+    pub guards: PermissionPolicy,
+}
+
 /// Loads policy file from Berth home; returns permissive defaults when missing.
 pub fn load_global_policy() -> Result<GlobalPolicy, String> {
     let Some(path) = paths::policy_path() else {
@@ -50,19 +109,90 @@ pub fn load_global_policy() -> Result<GlobalPolicy, String> {
     if !path.exists() {
         return Ok(GlobalPolicy::default());
     }
+    load_policy_file(&path)
+}
 
-    let content = fs::read_to_string(&path)
+/// Reads, parses, and validates one policy file.
+pub fn load_policy_file(path: &Path) -> Result<GlobalPolicy, String> {
+    let content = fs::read_to_string(path)
         .map_err(|e| format!("Failed to read policy file {}: {e}", path.display()))?;
-    toml::from_str::<GlobalPolicy>(&content)
-        .map_err(|e| format!("Failed to parse policy file {}: {e}", path.display()))
+    let policy = toml::from_str::<GlobalPolicy>(&content)
+        .map_err(|e| format!("Failed to parse policy file {}: {e}", path.display()))?;
+    validate_scoped_policies(&policy)
+        .map_err(|e| format!("Invalid policy file {}: {e}", path.display()))?;
+    Ok(policy)
+}
+
+/// Rejects unknown permission classes and trust levels in scoped policies.
+pub fn validate_scoped_policies(policy: &GlobalPolicy) -> Result<(), String> {
+    for (name, scope) in &policy.policy {
+        if let Some(class) = scope
+            .permissions
+            .iter()
+            .find(|c| !PERMISSION_CLASSES.contains(&c.as_str()))
+        {
+            return Err(format!(
+                "policy `{name}` has unknown permission class `{class}` (expected one of: {}).",
+                PERMISSION_CLASSES.join(", ")
+            ));
+        }
+        if let Some(level) = scope
+            .require_trust
+            .iter()
+            .find(|l| !TRUST_LEVELS.contains(&l.as_str()))
+        {
+            return Err(format!(
+                "policy `{name}` has unknown trust level `{level}` (expected one of: {}).",
+                TRUST_LEVELS.join(", ")
+            ));
+        }
+    }
+    Ok(())
 }
 
 /// Enforces global policy against effective permissions of one server launch.
 pub fn enforce_global_policy(
     server: &str,
-    declared: &PermissionsInfo,
+    installed: &InstalledServer,
     overrides: &PermissionOverrides,
     policy: &GlobalPolicy,
+    registry: &Registry,
+) -> Result<(), String> {
+    let result = evaluate_global_policy(server, installed, overrides, policy, registry);
+    match &result {
+        Ok(()) => tracing::debug!(server, "global policy allowed server"),
+        Err(reason) => tracing::info!(server, %reason, "global policy denied server"),
+    }
+    result
+}
+
+/// Effective permissions of one launch, grouped by class.
+struct EffectivePermissions {
+    network: Vec<String>,
+    env: Vec<String>,
+    filesystem: Vec<String>,
+    exec: Vec<String>,
+}
+
+impl EffectivePermissions {
+    fn class(&self, class: &str) -> &[String] {
+        match class {
+            "network" => &self.network,
+            "env" => &self.env,
+            "filesystem" => &self.filesystem,
+            "exec" => &self.exec,
+            _ => &[],
+        }
+    }
+}
+
+/// Evaluates org policy rules in order, returning the first violation.
+fn evaluate_global_policy(
+    server: &str,
+    installed: &InstalledServer,
+    overrides: &PermissionOverrides,
+    policy: &GlobalPolicy,
+    registry: &Registry,
 ) -> Result<(), String> {
     if server_denied(server, policy) {
         return Err(format!(
@@ -70,38 +200,108 @@ pub fn enforce_global_policy(
         ));
     }
 
-    let effective_network = effective_permissions("network", &declared.network, overrides);
-    let effective_env = effective_permissions("env", &declared.env, overrides);
-    let effective_filesystem = effective_permissions("filesystem", &declared.filesystem, overrides);
-    let effective_exec = effective_permissions("exec", &declared.exec, overrides);
+    if policy.secrets.forbid_plaintext {
+        let plaintext = plaintext_sensitive_keys(installed, registry);
+        if !plaintext.is_empty() {
+            return Err(format!(
+                "{POLICY_DENIED_PREFIX} for {server}: plaintext sensitive config ({}) is blocked by org policy; run `berth config migrate-secrets {server}`.",
+                plaintext.join(", ")
+            ));
+        }
+    }
+
+    let declared = &installed.permissions;
+    let effective = EffectivePermissions {
+        network: effective_permissions("network", &declared.network, overrides),
+        env: effective_permissions("env", &declared.env, overrides),
+        filesystem: effective_permissions("filesystem", &declared.filesystem, overrides),
+        exec: effective_permissions("exec", &declared.exec, overrides),
+    };
+    check_guards(server, &policy.permissions, &effective, "org policy")?;
 
-    if policy.permissions.deny_network_wildcard
-        && effective_network.iter().any(|entry| entry == "*")
+    for (name, scope) in &policy.policy {
+        if !scope_matches(scope, installed, &effective) {
+            continue;
+        }
+        let source = format!("org policy `{name}`");
+        if scope.deny {
+            return Err(format!(
+                "{POLICY_DENIED_PREFIX} for {server}: server is blocked by {source}."
+            ));
+        }
+        let trust = installed.server.trust_level.as_str();
+        if !scope.require_trust.is_empty() && !scope.require_trust.iter().any(|t| t == trust) {
+            return Err(format!(
+                "{POLICY_DENIED_PREFIX} for {server}: {source} requires trust level {} (server is {trust}).",
+                scope.require_trust.join(" or ")
+            ));
+        }
+        check_guards(server, &scope.guards, &effective, &source)?;
+    }
+
+    Ok(())
+}
+
+/// Returns whether a server falls under a scoped policy's selector.
+fn scope_matches(
+    scope: &ScopedPolicy,
+    installed: &InstalledServer,
+    effective: &EffectivePermissions,
+) -> bool {
+    let category_matches = scope.categories.is_empty()
+        || scope
+            .categories
+            .iter()
+            .any(|c| c.eq_ignore_ascii_case(&installed.server.category));
+    let tag_matches = scope.tags.is_empty()
+        || scope.tags.iter().any(|tag| {
+            installed
+                .server
+                .tags
+                .iter()
+                .any(|t| t.eq_ignore_ascii_case(tag))
+        });
+    let permission_matches = scope.permissions.is_empty()
+        || scope
+            .permissions
+            .iter()
+            .any(|class| !effective.class(class).is_empty());
+    category_matches && tag_matches && permission_matches
+}
+
+/// Applies wildcard and write-access guards, naming `source` in denials.
+fn check_guards(
+    server: &str,
+    guards: &PermissionPolicy,
+    effective: &EffectivePermissions,
+    source: &str,
+) -> Result<(), String> {
+    if guards.deny_network_wildcard
+        && effective.network.iter().any(|entry| {
+            entry
+                .parse::<NetworkPermission>()
+                .is_ok_and(|permission| permission.is_wildcard())
+        })
     {
         return Err(format!(
-            "{POLICY_DENIED_PREFIX} for {server}: network wildcard `*` is blocked by org policy."
+            "{POLICY_DENIED_PREFIX} for {server}: network wildcard `*` is blocked by {source}."
         ));
     }
-    if policy.permissions.deny_env_wildcard && effective_env.iter().any(|entry| entry == "*") {
+    if guards.deny_env_wildcard && effective.env.iter().any(|entry| entry == "*") {
         return Err(format!(
-            "{POLICY_DENIED_PREFIX} for {server}: env wildcard `*` is blocked by org policy."
+            "{POLICY_DENIED_PREFIX} for {server}: env wildcard `*` is blocked by {source}."
         ));
     }
-    if policy.permissions.deny_filesystem_write
-        && effective_filesystem
-            .iter()
-            .any(|entry| entry == "*" || entry.trim_start().starts_with("write:"))
-    {
+    if guards.deny_filesystem_write && grants_filesystem_write(&effective.filesystem) {
         return Err(format!(
-            "{POLICY_DENIED_PREFIX} for {server}: filesystem write access is blocked by org policy."
+            "{POLICY_DENIED_PREFIX} for {server}: filesystem write access is blocked by {source}."
         ));
     }
-    if policy.permissions.deny_exec_wildcard && effective_exec.iter().any(|entry| entry == "*") {
+    if guards.deny_exec_wildcard && effective.exec.iter().any(|entry| entry == "*") {
         return Err(format!(
-            "{POLICY_DENIED_PREFIX} for {server}: exec wildcard `*` is blocked by org policy."
+            "{POLICY_DENIED_PREFIX} for {server}: exec wildcard `*` is blocked by {source}."
         ));
     }
-
     Ok(())
 }
 
@@ -116,13 +316,14 @@ fn server_denied(server: &str, policy: &GlobalPolicy) -> bool {
 mod tests {
     use super::*;
 
-    fn declared() -> PermissionsInfo {
-        PermissionsInfo {
-            network: vec!["api.github.com:443".to_string()],
-            env: vec!["GITHUB_TOKEN".to_string()],
-            filesystem: vec!["read:/tmp".to_string(), "write:/tmp".to_string()],
-            exec: vec!["git".to_string()],
-        }
+    fn installed() -> InstalledServer {
+        let mut installed =
+            InstalledServer::from_metadata(Registry::from_seed().get("github").unwrap());
+        installed.permissions.network = vec!["api.github.com:443".to_string()];
+        installed.permissions.env = vec!["GITHUB_TOKEN".to_string()];
+        installed.permissions.filesystem = vec!["read:/tmp".to_string(), "write:/tmp".to_string()];
+        installed.permissions.exec = vec!["git".to_string()];
+        installed
     }
 
     #[test]
@@ -131,13 +332,14 @@ mod tests {
             servers: ServerPolicy {
                 deny: vec!["github".to_string()],
             },
-            permissions: PermissionPolicy::default(),
+            ..GlobalPolicy::default()
         };
         let err = enforce_global_policy(
             "github",
-            &declared(),
+            &installed(),
             &PermissionOverrides::default(),
             &policy,
+            &Registry::from_seed(),
         )
         .unwrap_err();
         assert!(err.contains(POLICY_DENIED_PREFIX));
@@ -147,13 +349,13 @@ mod tests {
     #[test]
     fn enforce_global_policy_blocks_wildcards_and_write_access() {
         let policy = GlobalPolicy {
-            servers: ServerPolicy::default(),
             permissions: PermissionPolicy {
                 deny_network_wildcard: true,
                 deny_env_wildcard: true,
                 deny_filesystem_write: true,
                 deny_exec_wildcard: true,
             },
+            ..GlobalPolicy::default()
         };
         let overrides = PermissionOverrides {
             grant: vec![
@@ -163,7 +365,14 @@ mod tests {
             ],
             revoke: Vec::new(),
         };
-        let err = enforce_global_policy("github", &declared(), &overrides, &policy).unwrap_err();
+        let err = enforce_global_policy(
+            "github",
+            &installed(),
+            &overrides,
+            &policy,
+            &Registry::from_seed(),
+        )
+        .unwrap_err();
         assert!(err.contains(POLICY_DENIED_PREFIX));
     }
 
@@ -171,10 +380,126 @@ mod tests {
     fn enforce_global_policy_allows_when_policy_is_permissive() {
         let result = enforce_global_policy(
             "github",
-            &declared(),
+            &installed(),
             &PermissionOverrides::default(),
             &GlobalPolicy::default(),
+            &Registry::from_seed(),
         );
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn enforce_global_policy_forbids_plaintext_sensitive_values() {
+        let policy: GlobalPolicy = toml::from_str("[secrets]\nforbid_plaintext = true\n").unwrap();
+        let mut server = installed();
+        server
+            .config
+            .insert("token".to_string(), "ghp_plain".to_string());
+        let err = enforce_global_policy(
+            "github",
+            &server,
+            &PermissionOverrides::default(),
+            &policy,
+            &Registry::from_seed(),
+        )
+        .unwrap_err();
+        assert!(err.contains("plaintext sensitive config (token)"));
+        assert!(err.contains("berth config migrate-secrets github"));
+
+        server
+            .config
+            .insert("token".to_string(), "secret://github/token".to_string());
+        assert!(enforce_global_policy(
+            "github",
+            &server,
+            &PermissionOverrides::default(),
+            &policy,
+            &Registry::from_seed()
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn scoped_policy_requires_trust_for_exec_servers() {
+        let policy: GlobalPolicy = toml::from_str(
+            r#"
+[policy.exec-servers]
+permissions = ["exec"]
+require_trust = ["official", "verified"]
+"#,
+        )
+        .unwrap();
+        let mut server = installed();
+        server.server.trust_level = "community".to_string();
+        let err = enforce_global_policy(
+            "github",
+            &server,
+            &PermissionOverrides::default(),
+            &policy,
+            &Registry::from_seed(),
+        )
+        .unwrap_err();
+        assert!(err.contains("org policy `exec-servers` requires trust level official or verified"));
+
+        server.permissions.exec.clear();
+        assert!(enforce_global_policy(
+            "github",
+            &server,
+            &PermissionOverrides::default(),
+            &policy,
+            &Registry::from_seed()
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn scoped_policy_selects_by_category_and_tag() {
+        let policy: GlobalPolicy = toml::from_str(
+            r#"
+[policy.no-dev-writes]
+categories = ["developer-tools"]
+tags = ["git"]
+deny_filesystem_write = true
+"#,
+        )
+        .unwrap();
+        let mut server = installed();
+        server.server.category = "developer-tools".to_string();
+        server.server.tags = vec!["git".to_string()];
+        let err = enforce_global_policy(
+            "github",
+            &server,
+            &PermissionOverrides::default(),
+            &policy,
+            &Registry::from_seed(),
+        )
+        .unwrap_err();
+        assert!(err.contains("filesystem write access is blocked by org policy `no-dev-writes`"));
+
+        server.server.tags = vec!["issues".to_string()];
+        assert!(enforce_global_policy(
+            "github",
+            &server,
+            &PermissionOverrides::default(),
+            &policy,
+            &Registry::from_seed()
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn validate_scoped_policies_rejects_unknown_values() {
+        let mut policy = GlobalPolicy::default();
+        policy.policy.insert(
+            "bad".to_string(),
+            ScopedPolicy {
+                permissions: vec!["sockets".to_string()],
+                ..ScopedPolicy::default()
+            },
+        );
+        assert!(validate_scoped_policies(&policy).is_err());
+        policy.policy.get_mut("bad").unwrap().permissions = vec!["exec".to_string()];
+        policy.policy.get_mut("bad").unwrap().require_trust = vec!["trusted".to_string()];
+        assert!(validate_scoped_policies(&policy).is_err());
+    }
 }
@@ -0,0 +1,358 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Fixture-based scenarios that assert org policy allow/deny outcomes.
+//!
+//! A scenario directory holds a default `policy.toml` plus one `*.toml`
+//! scenario per file. Each scenario describes a server (inline, or a
+//! `manifest` pointing at an installed-server TOML), optional permission
+//! overrides, and the expected outcome. Paths are relative to the scenario
+//! directory.
+
+use berth_registry::config::{
+    ConfigMeta, InstalledServer, PermissionsInfo, RuntimeInfo, ServerInfo, SourceInfo,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use berth_registry::Registry;
+
+use crate::permission_filter::PermissionOverrides;
+use crate::policy_engine::{enforce_global_policy, load_policy_file, GlobalPolicy};
+
+/// Policy file used by scenarios that do not name one.
+pub const DEFAULT_POLICY_FILE: &str = "policy.toml";
+
+/// Outcome a scenario expects from the policy engine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Expectation {
+    Allow,
+    Deny,
+}
+
+/// One scenario file.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Scenario {
+    #[serde(default)]
+    description: Option<String>,
+    /// Server name; defaults to the manifest's name or the file stem.
+    #[serde(default)]
+    server: Option<String>,
+    expect: Expectation,
+    /// Substring the denial reason must contain.
+    #[serde(default)]
+    reason: Option<String>,
+    #[serde(default)]
+    policy: Option<String>,
+    #[serde(default)]
+    manifest: Option<String>,
+    #[serde(default)]
+    category: Option<String>,
+    #[serde(default)]
+    trust_level: Option<String>,
+    #[serde(default)]
+    tags: Option<Vec<String>>,
+    #[serde(default)]
+    permissions: Option<PermissionsInfo>,
+    #[serde(default)]
+    overrides: PermissionOverrides,
+}
+
+/// Result of running one scenario.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScenarioResult {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub expected: Option<Expectation>,
+    /// Whether the policy allowed the server; `None` when the scenario failed to load.
+    pub allowed: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failure: Option<String>,
+}
+
+impl ScenarioResult {
+    /// Returns whether the scenario loaded and matched its expectation.
+    pub fn passed(&self) -> bool {
+        self.failure.is_none()
+    }
+}
+
+/// Runs every scenario in `dir`, sorted by file name.
+pub fn run_scenarios(dir: &Path) -> Result<Vec<ScenarioResult>, String> {
+    if !dir.is_dir() {
+        return Err(format!(
+            "Scenario directory {} does not exist.",
+            dir.display()
+        ));
+    }
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read {}: {e}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.is_file()
+                && path.extension().is_some_and(|ext| ext == "toml")
+                && path
+                    .file_name()
+                    .is_some_and(|name| name != DEFAULT_POLICY_FILE)
+        })
+        .collect();
+    files.sort();
+    if files.is_empty() {
+        return Err(format!("No scenario files found in {}.", dir.display()));
+    }
+
+    let registry = Registry::from_seed();
+    let mut policies = BTreeMap::new();
+    Ok(files
+        .iter()
+        .map(|file| run_scenario(dir, file, &mut policies, &registry))
+        .collect())
+}
+
+fn run_scenario(
+    dir: &Path,
+    file: &Path,
+    policies: &mut BTreeMap<PathBuf, Result<GlobalPolicy, String>>,
+    registry: &Registry,
+) -> ScenarioResult {
+    let name = file
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let mut result = ScenarioResult {
+        name,
+        description: None,
+        expected: None,
+        allowed: None,
+        reason: None,
+        failure: None,
+    };
+    let scenario = match load_scenario(file) {
+        Ok(scenario) => scenario,
+        Err(msg) => {
+            result.failure = Some(msg);
+            return result;
+        }
+    };
+    result.description = scenario.description.clone();
+    result.expected = Some(scenario.expect);
+
+    let policy_path = dir.join(scenario.policy.as_deref().unwrap_or(DEFAULT_POLICY_FILE));
+    let policy = policies
+        .entry(policy_path.clone())
+        .or_insert_with(|| load_policy_file(&policy_path));
+    let policy = match policy {
+        Ok(policy) => policy,
+        Err(msg) => {
+            result.failure = Some(msg.clone());
+            return result;
+        }
+    };
+    let installed = match build_server(dir, &result.name, &scenario) {
+        Ok(installed) => installed,
+        Err(msg) => {
+            result.failure = Some(msg);
+            return result;
+        }
+    };
+
+    let server = installed.server.name.clone();
+    let outcome = enforce_global_policy(&server, &installed, &scenario.overrides, policy, registry);
+    result.allowed = Some(outcome.is_ok());
+    result.reason = outcome.as_ref().err().cloned();
+    result.failure = check_expectation(&scenario, &outcome);
+    result
+}
+
+fn load_scenario(file: &Path) -> Result<Scenario, String> {
+    let content = fs::read_to_string(file)
+        .map_err(|e| format!("Failed to read scenario {}: {e}", file.display()))?;
+    toml::from_str(&content)
+        .map_err(|e| format!("Failed to parse scenario {}: {e}", file.display()))
+}
+
+/// Builds the server under test from its manifest and inline fields.
+fn build_server(dir: &Path, stem: &str, scenario: &Scenario) -> Result<InstalledServer, String> {
+    let mut installed = match &scenario.manifest {
+        Some(manifest) => {
+            let path = dir.join(manifest);
+            let content = fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read manifest {}: {e}", path.display()))?;
+            toml::from_str::<InstalledServer>(&content)
+                .map_err(|e| format!("Failed to parse manifest {}: {e}", path.display()))?
+        }
+        None => blank_server(stem),
+    };
+    if let Some(server) = &scenario.server {
+        installed.server.name = server.clone();
+    }
+    if let Some(category) = &scenario.category {
+        installed.server.category = category.clone();
+    }
+    if let Some(trust_level) = &scenario.trust_level {
+        installed.server.trust_level = trust_level.clone();
+    }
+    if let Some(tags) = &scenario.tags {
+        installed.server.tags = tags.clone();
+    }
+    if let Some(permissions) = &scenario.permissions {
+        installed.permissions = permissions.clone();
+    }
+    Ok(installed)
+}
+
+/// Returns a community server with no permissions for inline scenarios.
+fn blank_server(name: &str) -> InstalledServer {
+    InstalledServer {
+        server: ServerInfo {
+            name: name.to_string(),
+            display_name: name.to_string(),
+            version: "0.0.0".to_string(),
+            description: String::new(),
+            category: String::new(),
+            maintainer: String::new(),
+            trust_level: "community".to_string(),
+            tags: Vec::new(),
+            version_constraint: None,
+        },
+        source: SourceInfo {
+            source_type: String::new(),
+            package: String::new(),
+            repository: String::new(),
+            registry: None,
+            sha256: None,
+        },
+        runtime: RuntimeInfo {
+            runtime_type: String::new(),
+            command: String::new(),
+            args: Vec::new(),
+            transport: "stdio".to_string(),
+            syscalls: Vec::new(),
+            health_check: None,
+            schedule: None,
+            limits: None,
+            socket: None,
+        },
+        permissions: PermissionsInfo::default(),
+        config: BTreeMap::new(),
+        config_meta: ConfigMeta::default(),
+    }
+}
+
+/// Describes how an outcome differs from the scenario's expectation.
+fn check_expectation(scenario: &Scenario, outcome: &Result<(), String>) -> Option<String> {
+    match (scenario.expect, outcome) {
+        (Expectation::Allow, Ok(())) => None,
+        (Expectation::Allow, Err(reason)) => Some(format!("expected allow, got deny: {reason}")),
+        (Expectation::Deny, Ok(())) => Some("expected deny, got allow".to_string()),
+        (Expectation::Deny, Err(reason)) => match &scenario.reason {
+            Some(expected) if !reason.contains(expected.as_str()) => Some(format!(
+                "expected denial containing `{expected}`, got: {reason}"
+            )),
+            _ => None,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(dir: &Path, name: &str, content: &str) {
+        let path = dir.join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn run_scenarios_checks_inline_servers_against_default_policy() {
+        let tmp = tempfile::tempdir().unwrap();
+        write(
+            tmp.path(),
+            "policy.toml",
+            "[permissions]\ndeny_network_wildcard = true\n",
+        );
+        write(
+            tmp.path(),
+            "allow-scoped.toml",
+            "expect = \"allow\"\n[permissions]\nnetwork = [\"api.github.com:443\"]\n",
+        );
+        write(
+            tmp.path(),
+            "deny-wildcard.toml",
+            "expect = \"deny\"\nreason = \"network wildcard\"\n[permissions]\nnetwork = [\"*\"]\n",
+        );
+        write(
+            tmp.path(),
+            "wrong-expectation.toml",
+            "expect = \"deny\"\n[overrides]\nrevoke = [\"network:*\"]\n",
+        );
+
+        let results = run_scenarios(tmp.path()).unwrap();
+        let names: Vec<&str> = results.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["allow-scoped", "deny-wildcard", "wrong-expectation"]
+        );
+        assert!(results[0].passed());
+        assert!(results[1].passed());
+        assert_eq!(results[1].allowed, Some(false));
+        assert_eq!(
+            results[2].failure.as_deref(),
+            Some("expected deny, got allow")
+        );
+    }
+
+    #[test]
+    fn run_scenarios_uses_manifests_and_named_policies() {
+        let tmp = tempfile::tempdir().unwrap();
+        let manifest = InstalledServer::from_metadata(
+            berth_registry::Registry::from_seed().get("github").unwrap(),
+        );
+        write(
+            tmp.path(),
+            "servers/github.toml",
+            &toml::to_string(&manifest).unwrap(),
+        );
+        write(
+            tmp.path(),
+            "policies/trusted-only.toml",
+            "[policy.all]\nrequire_trust = [\"official\"]\n",
+        );
+        write(
+            tmp.path(),
+            "community-github.toml",
+            "expect = \"deny\"\nreason = \"requires trust level official\"\npolicy = \"policies/trusted-only.toml\"\nmanifest = \"servers/github.toml\"\ntrust_level = \"community\"\n",
+        );
+        write(
+            tmp.path(),
+            "missing-policy.toml",
+            "expect = \"allow\"\npolicy = \"nope.toml\"\n",
+        );
+
+        let results = run_scenarios(tmp.path()).unwrap();
+        assert!(results[0].passed(), "{:?}", results[0].failure);
+        assert!(results[0].reason.as_deref().unwrap().contains("github"));
+        assert!(results[1]
+            .failure
+            .as_deref()
+            .unwrap()
+            .contains("Failed to read policy file"));
+    }
+
+    #[test]
+    fn run_scenarios_rejects_empty_directories() {
+        let tmp = tempfile::tempdir().unwrap();
+        write(tmp.path(), "policy.toml", "");
+        let err = run_scenarios(tmp.path()).unwrap_err();
+        assert!(err.contains("No scenario files"));
+    }
+}
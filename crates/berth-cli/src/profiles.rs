@@ -0,0 +1,163 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Isolated Berth homes selected with `--home` or a named `--profile`.
+//!
+//! Both resolve to a single-directory home and are applied by pinning
+//! `BERTH_HOME` for the whole process, so supervisors, proxies, and client
+//! entries written by `berth link` stay inside the chosen home.
+
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::paths::berth_layout;
+
+/// File below the default config directory that names profiles.
+pub const PROFILES_FILE: &str = "profiles.toml";
+
+/// Contents of `profiles.toml`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProfilesFile {
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+/// One named profile.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    /// Berth home of the profile; `~/` expands to the user's home and
+    /// relative paths are resolved against the directory of `profiles.toml`.
+    pub home: String,
+    /// Opens the home in read-only observer mode.
+    #[serde(default)]
+    pub observer: bool,
+}
+
+/// Returns the path of `profiles.toml` in the default (non-profile) layout.
+pub fn profiles_path() -> Option<PathBuf> {
+    berth_layout().map(|layout| layout.config.join(PROFILES_FILE))
+}
+
+/// Resolves the Berth home for `--home` or `--profile`, if either is given.
+pub fn selected_home(home: Option<&str>, profile: Option<&str>) -> Result<Option<PathBuf>, String> {
+    if let Some(home) = home {
+        return Ok(Some(expand_home(
+            home,
+            &std::env::current_dir().unwrap_or_default(),
+        )));
+    }
+    let Some(profile) = profile else {
+        return Ok(None);
+    };
+    let path = profiles_path().ok_or("Could not determine home directory.")?;
+    profile_home(&path, profile).map(Some)
+}
+
+/// Returns whether `--profile` names a profile with `observer = true`.
+pub fn selected_observer(profile: Option<&str>) -> Result<bool, String> {
+    let Some(profile) = profile else {
+        return Ok(false);
+    };
+    let path = profiles_path().ok_or("Could not determine home directory.")?;
+    find_profile(&path, profile).map(|profile| profile.observer)
+}
+
+/// Looks up `name` in the profiles file at `path`.
+pub fn profile_home(path: &Path, name: &str) -> Result<PathBuf, String> {
+    let profile = find_profile(path, name)?;
+    let base = path.parent().unwrap_or(Path::new("."));
+    Ok(expand_home(&profile.home, base))
+}
+
+fn find_profile(path: &Path, name: &str) -> Result<Profile, String> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(format!("Failed to read {}: {e}", path.display())),
+    };
+    let mut file: ProfilesFile =
+        toml::from_str(&content).map_err(|e| format!("Invalid {}: {e}", path.display()))?;
+    let Some(profile) = file.profiles.remove(name) else {
+        let known: Vec<&str> = file.profiles.keys().map(String::as_str).collect();
+        return Err(format!(
+            "Profile `{name}` is not defined in {} (known: {}). Add\n\n  [profiles.{name}]\n  home = \"~/.berth-{name}\"\n",
+            path.display(),
+            if known.is_empty() {
+                "none".to_string()
+            } else {
+                known.join(", ")
+            }
+        ));
+    };
+    Ok(profile)
+}
+
+/// Expands a leading `~/` and makes relative paths absolute against `base`.
+fn expand_home(path: &str, base: &Path) -> PathBuf {
+    let expanded = match path.strip_prefix("~/").or((path == "~").then_some("")) {
+        Some(rest) => match dirs::home_dir() {
+            Some(home) => home.join(rest),
+            None => PathBuf::from(path),
+        },
+        None => PathBuf::from(path),
+    };
+    if expanded.is_absolute() {
+        expanded
+    } else {
+        base.join(expanded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_named_profiles_relative_to_the_file() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join(PROFILES_FILE);
+        fs::write(
+            &path,
+            "[profiles.experiments]\nhome = \"homes/exp\"\n\n[profiles.abs]\nhome = \"/srv/berth\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            profile_home(&path, "experiments").unwrap(),
+            tmp.path().join("homes/exp")
+        );
+        if cfg!(unix) {
+            assert_eq!(
+                profile_home(&path, "abs").unwrap(),
+                PathBuf::from("/srv/berth")
+            );
+        }
+
+        let err = profile_home(&path, "daily").unwrap_err();
+        assert!(err.contains("known: abs, experiments"), "{err}");
+        assert!(err.contains("[profiles.daily]"), "{err}");
+
+        fs::write(
+            &path,
+            "[profiles.shadow]\nhome = \"/srv/berth\"\nobserver = true\n",
+        )
+        .unwrap();
+        assert!(find_profile(&path, "shadow").unwrap().observer);
+    }
+
+    #[test]
+    fn missing_file_defines_no_profiles() {
+        let tmp = tempfile::tempdir().unwrap();
+        let err = profile_home(&tmp.path().join(PROFILES_FILE), "x").unwrap_err();
+        assert!(err.contains("known: none"), "{err}");
+    }
+
+    #[test]
+    fn expands_tilde() {
+        let home = dirs::home_dir().unwrap();
+        assert_eq!(expand_home("~/exp", Path::new("/base")), home.join("exp"));
+    }
+}
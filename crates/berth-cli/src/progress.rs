@@ -0,0 +1,232 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Machine-readable progress for long-running commands (`--progress json`).
+//!
+//! In JSON mode stdout carries one [`ProgressEvent`] per line and nothing
+//! else; the usual human-readable output moves to stderr through [`say!`].
+
+use colored::Colorize;
+use serde::Serialize;
+use std::io::Write;
+use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static JSON_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// How a command reports progress.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ProgressMode {
+    /// Colored human-readable lines on stdout.
+    #[default]
+    Text,
+    /// Line-delimited JSON events on stdout; human output goes to stderr.
+    Json,
+}
+
+/// Returns whether JSON progress events own stdout.
+pub fn json_active() -> bool {
+    JSON_ACTIVE.load(Ordering::Relaxed)
+}
+
+/// `println!` for commands that support `--progress json`: prints to stdout
+/// normally and to stderr while JSON events own stdout.
+macro_rules! say {
+    ($($arg:tt)*) => {
+        if $crate::progress::json_active() {
+            eprintln!($($arg)*)
+        } else {
+            println!($($arg)*)
+        }
+    };
+}
+pub(crate) use say;
+
+/// One line of `--progress json` output.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProgressEvent {
+    /// `started`, `completed` or `failed` for a step, `finished` once at the end.
+    pub event: &'static str,
+    pub command: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub step: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub server: Option<String>,
+    /// Share of the command's steps done, 0 to 100.
+    pub percent: u8,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Steps that completed; only on `finished`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub succeeded: Option<usize>,
+    /// Steps that failed; only on `finished`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failed: Option<usize>,
+}
+
+/// Progress of one command run through a known number of steps.
+///
+/// In text mode every method is a no-op.
+#[derive(Debug)]
+pub struct Progress {
+    json: bool,
+    command: &'static str,
+    total: usize,
+    succeeded: usize,
+    failed: usize,
+    current: Option<(String, Option<String>)>,
+}
+
+impl Progress {
+    /// Starts tracking `command` through `total` steps.
+    pub fn new(mode: ProgressMode, command: &'static str, total: usize) -> Self {
+        let json = mode == ProgressMode::Json;
+        if json {
+            JSON_ACTIVE.store(true, Ordering::Relaxed);
+        }
+        Progress {
+            json,
+            command,
+            total,
+            succeeded: 0,
+            failed: 0,
+            current: None,
+        }
+    }
+
+    /// Reports that `step` (for `server`, if any) has started.
+    pub fn start(&mut self, step: &str, server: Option<&str>) {
+        self.current = Some((step.to_string(), server.map(str::to_string)));
+        self.emit("started", None);
+    }
+
+    /// Reports that the current step completed.
+    pub fn complete(&mut self) {
+        if self.current.is_some() {
+            self.succeeded += 1;
+            self.emit("completed", None);
+            self.current = None;
+        }
+    }
+
+    /// Reports that the current step failed with `error`.
+    pub fn fail(&mut self, error: &str) {
+        if self.current.is_some() {
+            self.failed += 1;
+            self.emit("failed", Some(error));
+            self.current = None;
+        }
+    }
+
+    /// Prints `message` as an error, fails the current step, and exits 1.
+    pub fn exit_with_error(&mut self, message: &str) -> ! {
+        eprintln!("{} {}", "✗".red().bold(), message);
+        self.fail(message);
+        self.finish();
+        process::exit(1);
+    }
+
+    /// Reports the end of the command with its step tallies.
+    pub fn finish(&mut self) {
+        if self.json {
+            self.write(ProgressEvent {
+                event: "finished",
+                command: self.command,
+                step: None,
+                server: None,
+                percent: 100,
+                error: None,
+                succeeded: Some(self.succeeded),
+                failed: Some(self.failed),
+            });
+        }
+    }
+
+    fn percent(&self) -> u8 {
+        if self.total == 0 {
+            return 100;
+        }
+        ((self.succeeded + self.failed) * 100 / self.total).min(100) as u8
+    }
+
+    fn emit(&self, event: &'static str, error: Option<&str>) {
+        if !self.json {
+            return;
+        }
+        let (step, server) = self.current.clone().unzip();
+        self.write(ProgressEvent {
+            event,
+            command: self.command,
+            step,
+            server: server.flatten(),
+            percent: self.percent(),
+            error: error.map(strip_ansi),
+            succeeded: None,
+            failed: None,
+        });
+    }
+
+    fn write(&self, event: ProgressEvent) {
+        if let Ok(line) = serde_json::to_string(&event) {
+            let mut stdout = std::io::stdout().lock();
+            let _ = writeln!(stdout, "{line}");
+            let _ = stdout.flush();
+        }
+    }
+}
+
+/// Removes terminal color sequences from a message meant for JSON.
+fn strip_ansi(message: &str) -> String {
+    let mut plain = String::with_capacity(message.len());
+    let mut chars = message.chars();
+    while let Some(c) = chars.next() {
+        if c == '\u{1b}' {
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            plain.push(c);
+        }
+    }
+    plain
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percent_counts_finished_steps() {
+        let mut progress = Progress::new(ProgressMode::Text, "update", 4);
+        assert_eq!(progress.percent(), 0);
+        progress.start("update", Some("github"));
+        progress.complete();
+        progress.start("update", Some("slack"));
+        progress.fail("boom");
+        assert_eq!(progress.percent(), 50);
+        assert_eq!((progress.succeeded, progress.failed), (1, 1));
+        progress.fail("ignored without a current step");
+        assert_eq!(progress.failed, 1);
+    }
+
+    #[test]
+    fn events_serialize_without_empty_fields() {
+        let event = ProgressEvent {
+            event: "failed",
+            command: "install",
+            step: Some("fetch".to_string()),
+            server: Some("github".to_string()),
+            percent: 25,
+            error: Some(strip_ansi("\u{1b}[36mgithub\u{1b}[0m not found")),
+            succeeded: None,
+            failed: None,
+        };
+        assert_eq!(
+            serde_json::to_string(&event).unwrap(),
+            r#"{"event":"failed","command":"install","step":"fetch","server":"github","percent":25,"error":"github not found"}"#
+        );
+    }
+}
@@ -0,0 +1,145 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Helpers for parsing and validating proxy concurrency settings.
+
+use std::collections::BTreeMap;
+
+use crate::framing::Framing;
+
+pub const KEY_MAX_IN_FLIGHT: &str = "berth.max-in-flight";
+pub const KEY_QUEUE_TIMEOUT_MS: &str = "berth.queue-timeout-ms";
+pub const KEY_UPDATE_STRATEGY: &str = "berth.update-strategy";
+pub const KEY_FRAMING: &str = "berth.framing";
+pub const DEFAULT_QUEUE_TIMEOUT_MS: u64 = 30_000;
+
+/// How connected proxies pick up a new version after `berth update`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UpdateStrategy {
+    /// Keep the running upstream; the new version starts with the next session.
+    #[default]
+    Restart,
+    /// Start the new version alongside the old and switch once it is healthy.
+    BlueGreen,
+}
+
+impl UpdateStrategy {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            UpdateStrategy::Restart => "restart",
+            UpdateStrategy::BlueGreen => "blue-green",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProxyPolicy {
+    /// Maximum concurrent upstream requests; `None` means unlimited.
+    pub max_in_flight: Option<u32>,
+    pub queue_timeout_ms: u64,
+    pub update_strategy: UpdateStrategy,
+    /// Stdio framing towards the server.
+    pub framing: Framing,
+}
+
+/// Returns whether a key is reserved for Berth proxy policy settings.
+pub fn is_proxy_policy_key(key: &str) -> bool {
+    matches!(
+        key,
+        KEY_MAX_IN_FLIGHT | KEY_QUEUE_TIMEOUT_MS | KEY_UPDATE_STRATEGY | KEY_FRAMING
+    )
+}
+
+/// Validates one key/value pair for proxy policy settings.
+pub fn validate_proxy_policy_value(key: &str, value: &str) -> Result<(), String> {
+    match key {
+        KEY_MAX_IN_FLIGHT | KEY_QUEUE_TIMEOUT_MS => parse_positive(value).map(|_| ()),
+        KEY_UPDATE_STRATEGY => parse_update_strategy(value).map(|_| ()),
+        KEY_FRAMING => Framing::parse(value).map(|_| ()),
+        _ => Err(format!("Unknown proxy policy key: {key}")),
+    }
+}
+
+/// Parses proxy policy values from installed config.
+pub fn parse_proxy_policy(config: &BTreeMap<String, String>) -> Result<ProxyPolicy, String> {
+    let max_in_flight = config
+        .get(KEY_MAX_IN_FLIGHT)
+        .map(|v| parse_positive(v))
+        .transpose()?
+        .map(|v| u32::try_from(v).unwrap_or(u32::MAX));
+    let queue_timeout_ms = match config.get(KEY_QUEUE_TIMEOUT_MS) {
+        Some(v) => parse_positive(v)?,
+        None => DEFAULT_QUEUE_TIMEOUT_MS,
+    };
+    let update_strategy = config
+        .get(KEY_UPDATE_STRATEGY)
+        .map(|v| parse_update_strategy(v))
+        .transpose()?
+        .unwrap_or_default();
+    let framing = config
+        .get(KEY_FRAMING)
+        .map(|v| Framing::parse(v))
+        .transpose()?
+        .unwrap_or_default();
+    Ok(ProxyPolicy {
+        max_in_flight,
+        queue_timeout_ms,
+        update_strategy,
+        framing,
+    })
+}
+
+fn parse_update_strategy(value: &str) -> Result<UpdateStrategy, String> {
+    match value.trim() {
+        "restart" => Ok(UpdateStrategy::Restart),
+        "blue-green" => Ok(UpdateStrategy::BlueGreen),
+        _ => Err(format!(
+            "Invalid value `{value}`. Expected `restart` or `blue-green`."
+        )),
+    }
+}
+
+fn parse_positive(value: &str) -> Result<u64, String> {
+    match value.trim().parse::<u64>() {
+        Ok(parsed) if parsed > 0 => Ok(parsed),
+        _ => Err(format!(
+            "Invalid value `{value}`. Expected a positive integer (>= 1)."
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_proxy_policy_defaults_to_unlimited() {
+        let policy = parse_proxy_policy(&BTreeMap::new()).unwrap();
+        assert_eq!(policy.max_in_flight, None);
+        assert_eq!(policy.queue_timeout_ms, DEFAULT_QUEUE_TIMEOUT_MS);
+        assert_eq!(policy.update_strategy, UpdateStrategy::Restart);
+    }
+
+    #[test]
+    fn parse_proxy_policy_reads_values() {
+        let cfg = BTreeMap::from([
+            (KEY_MAX_IN_FLIGHT.to_string(), "2".to_string()),
+            (KEY_QUEUE_TIMEOUT_MS.to_string(), "500".to_string()),
+            (KEY_UPDATE_STRATEGY.to_string(), "blue-green".to_string()),
+            (KEY_FRAMING.to_string(), "content-length".to_string()),
+        ]);
+        let policy = parse_proxy_policy(&cfg).unwrap();
+        assert_eq!(policy.framing, Framing::ContentLength);
+        assert_eq!(policy.max_in_flight, Some(2));
+        assert_eq!(policy.queue_timeout_ms, 500);
+        assert_eq!(policy.update_strategy, UpdateStrategy::BlueGreen);
+    }
+
+    #[test]
+    fn validate_proxy_policy_rejects_bad_values() {
+        assert!(validate_proxy_policy_value(KEY_MAX_IN_FLIGHT, "0").is_err());
+        assert!(validate_proxy_policy_value(KEY_QUEUE_TIMEOUT_MS, "soon").is_err());
+        assert!(validate_proxy_policy_value(KEY_UPDATE_STRATEGY, "canary").is_err());
+        assert!(validate_proxy_policy_value(KEY_FRAMING, "lsp").is_err());
+    }
+}
@@ -0,0 +1,512 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Local registry review queue filled by `berth publish`.
+//!
+//! Every submission is one JSON document in `publish/queue/`. New documents
+//! carry a ULID in their `id` field and are named `<id>.json`; documents
+//! written before submissions had ids keep their file name as id. The
+//! registry API, the review website, and `berth publish --status` all address
+//! submissions by that id.
+//!
+//! `publish/queue-index.json` keeps one summary per document, so listing the
+//! queue reads the directory but not every document in it. The index is
+//! reconciled by file name on each access: documents without an entry are
+//! parsed and added, entries whose document is gone are dropped, and documents
+//! that cannot be read or parsed are logged and left out until they can. The
+//! index is only written when that changed it. Changes made through this
+//! module update their entry under `publish/queue-index.lock`.
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use berth_common::{atomic_write, jsonl};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Status of a submission nobody has reviewed yet.
+pub const PENDING_STATUS: &str = "pending-manual-review";
+
+/// Fields of a queue document needed to summarize it.
+#[derive(Debug, Deserialize)]
+struct QueueSubmissionFile {
+    #[serde(default)]
+    id: Option<String>,
+    submitted_at_epoch_secs: u64,
+    status: String,
+    manifest: QueueManifest,
+    #[serde(default)]
+    quality_checks: Vec<QueueQualityCheck>,
+}
+
+#[derive(Debug, Deserialize)]
+struct QueueManifest {
+    server: QueueServer,
+}
+
+#[derive(Debug, Deserialize)]
+struct QueueServer {
+    name: String,
+    display_name: String,
+    version: String,
+    maintainer: String,
+    category: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct QueueQualityCheck {
+    passed: bool,
+}
+
+/// What listings show about one submission.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PublishSubmissionSummary {
+    pub id: String,
+    pub submitted_at_epoch_secs: u64,
+    pub status: String,
+    pub server: PublishSubmissionServerSummary,
+    pub quality_checks_passed: usize,
+    pub quality_checks_total: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PublishSubmissionServerSummary {
+    pub name: String,
+    pub display_name: String,
+    pub version: String,
+    pub maintainer: String,
+    pub category: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IndexEntry {
+    file: String,
+    #[serde(flatten)]
+    summary: PublishSubmissionSummary,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct IndexFile {
+    submissions: Vec<IndexEntry>,
+}
+
+/// Returns whether `id` can name a submission: ULIDs and legacy file names
+/// pass, anything that could leave the queue directory does not.
+pub fn is_valid_id(id: &str) -> bool {
+    !id.is_empty()
+        && !id.starts_with('.')
+        && !id.contains("..")
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+/// Lists all submissions, newest first; submissions from the same second
+/// are ordered by id, so ULIDs keep their creation order.
+pub fn list(queue_dir: &Path) -> Result<Vec<PublishSubmissionSummary>, String> {
+    if !queue_dir.exists() {
+        return Ok(Vec::new());
+    }
+    with_index(queue_dir, |entries| {
+        Ok(entries.iter().map(|entry| entry.summary.clone()).collect())
+    })
+}
+
+/// Reads the full document and summary of submission `id`.
+pub fn read(
+    queue_dir: &Path,
+    id: &str,
+) -> Result<Option<(Value, PublishSubmissionSummary)>, String> {
+    if !is_valid_id(id) {
+        return Err("invalid submission id".to_string());
+    }
+    if !queue_dir.exists() {
+        return Ok(None);
+    }
+    let file = with_index(queue_dir, |entries| Ok(find(entries, id)))?;
+    let Some(file) = file else {
+        return Ok(None);
+    };
+    let value = read_document(&queue_dir.join(&file))?;
+    let summary = summarize(&file, value.clone(), &queue_dir.join(&file))?;
+    Ok(Some((value, summary)))
+}
+
+/// Writes a new submission document, which must carry its `id`, as
+/// `<id>.json` and records it in the index.
+pub fn add(queue_dir: &Path, document: &Value) -> Result<PathBuf, String> {
+    let id = document
+        .get("id")
+        .and_then(Value::as_str)
+        .filter(|id| is_valid_id(id))
+        .ok_or("submission document has no valid id".to_string())?;
+    fs::create_dir_all(queue_dir).map_err(|e| {
+        format!(
+            "failed to create publish queue directory {}: {e}",
+            queue_dir.display()
+        )
+    })?;
+    let file = format!("{id}.json");
+    let path = queue_dir.join(&file);
+    with_index(queue_dir, |entries| {
+        let summary = summarize(&file, document.clone(), &path)?;
+        write_document(&path, document)?;
+        upsert(entries, file.clone(), summary);
+        Ok(())
+    })?;
+    Ok(path)
+}
+
+/// Applies `edit` to the document of submission `id`, writes it back, and
+/// updates its index entry.
+pub fn update(
+    queue_dir: &Path,
+    id: &str,
+    edit: impl FnOnce(&mut Value),
+) -> Result<Option<(Value, PublishSubmissionSummary)>, String> {
+    if !is_valid_id(id) {
+        return Err("invalid submission id".to_string());
+    }
+    if !queue_dir.exists() {
+        return Ok(None);
+    }
+    with_index(queue_dir, |entries| {
+        let Some(file) = find(entries, id) else {
+            return Ok(None);
+        };
+        let path = queue_dir.join(&file);
+        let mut value = read_document(&path)?;
+        edit(&mut value);
+        let summary = summarize(&file, value.clone(), &path)?;
+        write_document(&path, &value)?;
+        upsert(entries, file, summary.clone());
+        Ok(Some((value, summary)))
+    })
+}
+
+fn index_path(queue_dir: &Path) -> PathBuf {
+    queue_dir.with_file_name("queue-index.json")
+}
+
+fn lock_path(queue_dir: &Path) -> PathBuf {
+    queue_dir.with_file_name("queue-index.lock")
+}
+
+/// Runs `f` on the reconciled index entries while holding the queue lock,
+/// saving the index afterwards when anything changed.
+fn with_index<T>(
+    queue_dir: &Path,
+    f: impl FnOnce(&mut Vec<IndexEntry>) -> Result<T, String>,
+) -> Result<T, String> {
+    let lock = lock_path(queue_dir);
+    jsonl::with_lock(&lock, |_| Ok(with_index_locked(queue_dir, f)))
+        .map_err(|e| format!("failed to lock publish queue index {}: {e}", lock.display()))?
+}
+
+fn with_index_locked<T>(
+    queue_dir: &Path,
+    f: impl FnOnce(&mut Vec<IndexEntry>) -> Result<T, String>,
+) -> Result<T, String> {
+    let path = index_path(queue_dir);
+    // The index is a cache of the documents, so an unreadable one is rebuilt.
+    let saved = match fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str::<IndexFile>(&content).ok(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Some(IndexFile::default()),
+        Err(_) => None,
+    };
+    let rebuild = saved.is_none();
+    let mut entries = saved.unwrap_or_default().submissions;
+    let before = serde_json::to_string(&entries).unwrap_or_default();
+
+    reconcile(queue_dir, &mut entries)?;
+    let result = f(&mut entries)?;
+    sort(&mut entries);
+
+    let index = IndexFile {
+        submissions: entries,
+    };
+    let after = serde_json::to_string(&index.submissions).unwrap_or_default();
+    if rebuild || after != before {
+        let payload = serde_json::to_string_pretty(&index).map_err(|e| {
+            format!(
+                "failed to serialize publish queue index {}: {e}",
+                path.display()
+            )
+        })?;
+        atomic_write(&path, payload).map_err(|e| {
+            format!(
+                "failed to write publish queue index {}: {e}",
+                path.display()
+            )
+        })?;
+    }
+    Ok(result)
+}
+
+/// Drops entries whose document is gone and adds entries for documents the
+/// index does not know yet, skipping those that cannot be read or parsed.
+fn reconcile(queue_dir: &Path, entries: &mut Vec<IndexEntry>) -> Result<(), String> {
+    let files = document_names(queue_dir)?;
+    entries.retain(|entry| files.contains(&entry.file));
+    let known: BTreeSet<String> = entries.iter().map(|entry| entry.file.clone()).collect();
+    for file in files.difference(&known) {
+        let path = queue_dir.join(file);
+        let summary = match read_document(&path).and_then(|value| summarize(file, value, &path)) {
+            Ok(summary) => summary,
+            Err(e) => {
+                tracing::warn!(reason = %e, "skipping unreadable publish queue document");
+                continue;
+            }
+        };
+        entries.push(IndexEntry {
+            file: file.clone(),
+            summary,
+        });
+    }
+    sort(entries);
+    Ok(())
+}
+
+fn document_names(queue_dir: &Path) -> Result<BTreeSet<String>, String> {
+    if !queue_dir.exists() {
+        return Ok(BTreeSet::new());
+    }
+    let entries = fs::read_dir(queue_dir).map_err(|e| {
+        format!(
+            "failed to read publish queue directory {}: {e}",
+            queue_dir.display()
+        )
+    })?;
+    let mut names = BTreeSet::new();
+    for entry in entries {
+        let entry = entry.map_err(|e| {
+            format!(
+                "failed to enumerate publish queue directory {}: {e}",
+                queue_dir.display()
+            )
+        })?;
+        let Some(name) = entry.file_name().to_str().map(str::to_string) else {
+            continue;
+        };
+        if name.ends_with(".json") && !name.starts_with('.') {
+            names.insert(name);
+        }
+    }
+    Ok(names)
+}
+
+fn sort(entries: &mut [IndexEntry]) {
+    entries.sort_by(|left, right| {
+        right
+            .summary
+            .submitted_at_epoch_secs
+            .cmp(&left.summary.submitted_at_epoch_secs)
+            .then_with(|| right.summary.id.cmp(&left.summary.id))
+    });
+}
+
+fn find(entries: &[IndexEntry], id: &str) -> Option<String> {
+    entries
+        .iter()
+        .find(|entry| entry.summary.id == id)
+        .map(|entry| entry.file.clone())
+}
+
+fn upsert(entries: &mut Vec<IndexEntry>, file: String, summary: PublishSubmissionSummary) {
+    entries.retain(|entry| entry.file != file);
+    entries.push(IndexEntry { file, summary });
+}
+
+fn read_document(path: &Path) -> Result<Value, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("failed to read queue file {}: {e}", path.display()))?;
+    serde_json::from_str::<Value>(&content)
+        .map_err(|e| format!("failed to parse queue file {}: {e}", path.display()))
+}
+
+fn write_document(path: &Path, value: &Value) -> Result<(), String> {
+    let payload = serde_json::to_string_pretty(value)
+        .map_err(|e| format!("failed to serialize queue file {}: {e}", path.display()))?;
+    atomic_write(path, payload)
+        .map_err(|e| format!("failed to write queue file {}: {e}", path.display()))
+}
+
+/// Summarizes a queue document stored as `file`, whose name doubles as the
+/// id of documents written before submissions had one.
+fn summarize(file: &str, value: Value, path: &Path) -> Result<PublishSubmissionSummary, String> {
+    let payload = serde_json::from_value::<QueueSubmissionFile>(value)
+        .map_err(|e| format!("failed to parse queue file {}: {e}", path.display()))?;
+    let quality_checks_passed = payload.quality_checks.iter().filter(|c| c.passed).count();
+    let quality_checks_total = payload.quality_checks.len();
+    Ok(PublishSubmissionSummary {
+        id: payload
+            .id
+            .filter(|id| is_valid_id(id))
+            .unwrap_or_else(|| file.to_string()),
+        submitted_at_epoch_secs: payload.submitted_at_epoch_secs,
+        status: payload.status,
+        server: PublishSubmissionServerSummary {
+            name: payload.manifest.server.name,
+            display_name: payload.manifest.server.display_name,
+            version: payload.manifest.server.version,
+            maintainer: payload.manifest.server.maintainer,
+            category: payload.manifest.server.category,
+        },
+        quality_checks_passed,
+        quality_checks_total,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn document(id: Option<&str>, submitted: u64, server: &str) -> Value {
+        let mut value = json!({
+            "submitted_at_epoch_secs": submitted,
+            "status": PENDING_STATUS,
+            "manifest": {
+                "server": {
+                    "name": server,
+                    "display_name": server,
+                    "version": "1.0.0",
+                    "maintainer": "Acme",
+                    "category": "developer-tools"
+                }
+            },
+            "quality_checks": [{"name": "schema", "passed": true}]
+        });
+        if let Some(id) = id {
+            value["id"] = json!(id);
+        }
+        value
+    }
+
+    #[test]
+    fn ids_reject_paths_and_accept_ulids_and_legacy_names() {
+        assert!(is_valid_id("01J9Z3K4M5N6P7Q8R9S0T1V2W3"));
+        assert!(is_valid_id("github-1700000000.json"));
+        for id in ["", "../x.json", "a/b.json", "a\\b", ".index.json", "a b"] {
+            assert!(!is_valid_id(id), "{id}");
+        }
+    }
+
+    #[test]
+    fn listing_orders_by_time_then_id_and_uses_the_index() {
+        let tmp = tempfile::tempdir().unwrap();
+        let queue = tmp.path().join("publish").join("queue");
+        add(
+            &queue,
+            &document(Some("01AAAAAAAAAAAAAAAAAAAAAAAA"), 100, "a"),
+        )
+        .unwrap();
+        add(
+            &queue,
+            &document(Some("01BBBBBBBBBBBBBBBBBBBBBBBB"), 100, "b"),
+        )
+        .unwrap();
+        fs::write(
+            queue.join("legacy-50.json"),
+            document(None, 50, "legacy").to_string(),
+        )
+        .unwrap();
+
+        let ids: Vec<_> = list(&queue).unwrap().into_iter().map(|s| s.id).collect();
+        assert_eq!(
+            ids,
+            [
+                "01BBBBBBBBBBBBBBBBBBBBBBBB",
+                "01AAAAAAAAAAAAAAAAAAAAAAAA",
+                "legacy-50.json"
+            ]
+        );
+        assert!(queue.join("01AAAAAAAAAAAAAAAAAAAAAAAA.json").exists());
+
+        // Listing trusts the index for known files instead of reparsing them.
+        fs::write(queue.join("01AAAAAAAAAAAAAAAAAAAAAAAA.json"), "garbage").unwrap();
+        assert_eq!(list(&queue).unwrap().len(), 3);
+
+        fs::remove_file(queue.join("legacy-50.json")).unwrap();
+        let index = fs::read_to_string(index_path(&queue)).unwrap();
+        assert!(index.contains("legacy-50.json"));
+        assert_eq!(list(&queue).unwrap().len(), 2);
+        let index = fs::read_to_string(index_path(&queue)).unwrap();
+        assert!(!index.contains("legacy-50.json"));
+    }
+
+    #[test]
+    fn updates_rewrite_the_document_and_its_index_entry() {
+        let tmp = tempfile::tempdir().unwrap();
+        let queue = tmp.path().join("publish").join("queue");
+        let id = "01CCCCCCCCCCCCCCCCCCCCCCCC";
+        add(&queue, &document(Some(id), 100, "c")).unwrap();
+
+        let (value, summary) = update(&queue, id, |value| value["status"] = json!("approved"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(value["status"], "approved");
+        assert_eq!(summary.status, "approved");
+        assert_eq!(list(&queue).unwrap()[0].status, "approved");
+        assert_eq!(read(&queue, id).unwrap().unwrap().1.status, "approved");
+
+        assert!(update(&queue, "01MISSING", |_| {}).unwrap().is_none());
+        assert!(read(&queue, "../escape.json").is_err());
+    }
+
+    #[test]
+    fn listing_skips_unreadable_documents_and_leaves_an_unchanged_index_alone() {
+        let tmp = tempfile::tempdir().unwrap();
+        let queue = tmp.path().join("publish").join("queue");
+        fs::create_dir_all(&queue).unwrap();
+        assert!(list(&queue).unwrap().is_empty());
+        assert!(!index_path(&queue).exists());
+
+        add(
+            &queue,
+            &document(Some("01EEEEEEEEEEEEEEEEEEEEEEEE"), 100, "e"),
+        )
+        .unwrap();
+        fs::write(queue.join("broken.json"), "{not json").unwrap();
+        fs::write(queue.join("partial.json"), r#"{"status": "pending"}"#).unwrap();
+        let ids: Vec<_> = list(&queue).unwrap().into_iter().map(|s| s.id).collect();
+        assert_eq!(ids, ["01EEEEEEEEEEEEEEEEEEEEEEEE"]);
+        assert!(read(&queue, "broken.json").unwrap().is_none());
+
+        // A compact copy of the same entries is not rewritten by reads.
+        let index: Value =
+            serde_json::from_str(&fs::read_to_string(index_path(&queue)).unwrap()).unwrap();
+        let compact = index.to_string();
+        fs::write(index_path(&queue), &compact).unwrap();
+        list(&queue).unwrap();
+        read(&queue, "01EEEEEEEEEEEEEEEEEEEEEEEE").unwrap().unwrap();
+        assert_eq!(fs::read_to_string(index_path(&queue)).unwrap(), compact);
+
+        fs::write(
+            queue.join("broken.json"),
+            document(None, 50, "fixed").to_string(),
+        )
+        .unwrap();
+        assert_eq!(list(&queue).unwrap().len(), 2);
+        assert_ne!(fs::read_to_string(index_path(&queue)).unwrap(), compact);
+    }
+
+    #[test]
+    fn a_corrupt_index_is_rebuilt_from_the_documents() {
+        let tmp = tempfile::tempdir().unwrap();
+        let queue = tmp.path().join("publish").join("queue");
+        add(
+            &queue,
+            &document(Some("01DDDDDDDDDDDDDDDDDDDDDDDD"), 100, "d"),
+        )
+        .unwrap();
+        fs::write(index_path(&queue), "{not json").unwrap();
+        assert_eq!(list(&queue).unwrap()[0].id, "01DDDDDDDDDDDDDDDDDDDDDDDD");
+        let index = fs::read_to_string(index_path(&queue)).unwrap();
+        assert!(index.contains("01DDDDDDDDDDDDDDDDDDDDDDDD"));
+    }
+}
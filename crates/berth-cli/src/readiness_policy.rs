@@ -0,0 +1,173 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Helpers for parsing and validating startup readiness probe settings, and
+//! for turning a manifest health check into the runtime's.
+
+use std::collections::BTreeMap;
+
+use berth_registry::config::RuntimeInfo;
+use berth_registry::types::HealthProbe as ManifestHealthProbe;
+use berth_runtime::{HealthCheck, HealthProbe, ReadinessCheck, ReadinessProbe};
+
+pub const KEY_READINESS: &str = "berth.readiness";
+pub const KEY_READINESS_TIMEOUT_MS: &str = "berth.readiness-timeout-ms";
+pub const DEFAULT_READINESS_TIMEOUT_MS: u64 = 30_000;
+
+/// Returns whether a key is reserved for readiness probe settings.
+pub fn is_readiness_key(key: &str) -> bool {
+    matches!(key, KEY_READINESS | KEY_READINESS_TIMEOUT_MS)
+}
+
+/// Validates one key/value pair for readiness probe settings.
+pub fn validate_readiness_value(key: &str, value: &str) -> Result<(), String> {
+    match key {
+        KEY_READINESS if value.trim().is_empty() => Ok(()),
+        KEY_READINESS => parse_probe(value).map(|_| ()),
+        KEY_READINESS_TIMEOUT_MS => parse_timeout_ms(value).map(|_| ()),
+        _ => Err(format!("Unknown readiness key: {key}")),
+    }
+}
+
+/// Parses the readiness check from installed config values.
+///
+/// Returns `None` when no probe is configured, in which case a server counts
+/// as running as soon as its process is spawned.
+pub fn parse_readiness(
+    config: &BTreeMap<String, String>,
+) -> Result<Option<ReadinessCheck>, String> {
+    let probe = match config.get(KEY_READINESS) {
+        Some(v) if !v.trim().is_empty() => parse_probe(v)?,
+        _ => return Ok(None),
+    };
+    let timeout_ms = match config.get(KEY_READINESS_TIMEOUT_MS) {
+        Some(v) => parse_timeout_ms(v)?,
+        None => DEFAULT_READINESS_TIMEOUT_MS,
+    };
+    Ok(Some(ReadinessCheck { probe, timeout_ms }))
+}
+
+/// Parses `log:<text>`, `tcp:<port>` or `initialize`.
+fn parse_probe(value: &str) -> Result<ReadinessProbe, String> {
+    let invalid =
+        || format!("Invalid value `{value}`. Expected `log:<text>`, `tcp:<port>` or `initialize`.");
+    let trimmed = value.trim();
+    if trimmed == "initialize" {
+        return Ok(ReadinessProbe::McpInitialize);
+    }
+    match trimmed.split_once(':') {
+        Some(("log", pattern)) if !pattern.is_empty() => Ok(ReadinessProbe::LogPattern {
+            pattern: pattern.to_string(),
+        }),
+        Some(("tcp", port)) => match port.trim().parse::<u16>() {
+            Ok(port) if port > 0 => Ok(ReadinessProbe::TcpPort { port }),
+            _ => Err(invalid()),
+        },
+        _ => Err(invalid()),
+    }
+}
+
+/// Returns the health check the supervisor runs for an installed server, if
+/// its manifest declares one.
+pub fn health_check(runtime: &RuntimeInfo) -> Option<HealthCheck> {
+    let check = runtime.health_check.as_ref()?;
+    let probe = match &check.probe {
+        ManifestHealthProbe::Exec { command, args } => HealthProbe::Exec {
+            command: command.clone(),
+            args: args.clone(),
+        },
+        ManifestHealthProbe::Tcp { port } => HealthProbe::Tcp { port: *port },
+        ManifestHealthProbe::McpPing => HealthProbe::McpPing,
+    };
+    Some(HealthCheck {
+        probe,
+        interval_secs: check.interval_secs.max(1),
+        timeout_ms: check.timeout_ms.max(1),
+        failure_threshold: check.failure_threshold.max(1),
+    })
+}
+
+fn parse_timeout_ms(value: &str) -> Result<u64, String> {
+    match value.trim().parse::<u64>() {
+        Ok(ms) if ms > 0 => Ok(ms),
+        _ => Err(format!(
+            "Invalid value `{value}`. Expected a timeout in milliseconds (>= 1)."
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_readiness_is_none_when_unset() {
+        assert_eq!(parse_readiness(&BTreeMap::new()).unwrap(), None);
+        let cfg = BTreeMap::from([(KEY_READINESS.to_string(), String::new())]);
+        assert_eq!(parse_readiness(&cfg).unwrap(), None);
+    }
+
+    #[test]
+    fn parse_readiness_reads_each_probe_kind() {
+        let cfg = BTreeMap::from([
+            (KEY_READINESS.to_string(), "log:Server ready".to_string()),
+            (KEY_READINESS_TIMEOUT_MS.to_string(), "5000".to_string()),
+        ]);
+        assert_eq!(
+            parse_readiness(&cfg).unwrap(),
+            Some(ReadinessCheck {
+                probe: ReadinessProbe::LogPattern {
+                    pattern: "Server ready".to_string()
+                },
+                timeout_ms: 5_000,
+            })
+        );
+        assert_eq!(
+            parse_probe("tcp:8080").unwrap(),
+            ReadinessProbe::TcpPort { port: 8080 }
+        );
+        assert_eq!(
+            parse_probe("initialize").unwrap(),
+            ReadinessProbe::McpInitialize
+        );
+    }
+
+    #[test]
+    fn validate_readiness_rejects_malformed_values() {
+        assert!(validate_readiness_value(KEY_READINESS, "log:").is_err());
+        assert!(validate_readiness_value(KEY_READINESS, "tcp:0").is_err());
+        assert!(validate_readiness_value(KEY_READINESS, "tcp:http").is_err());
+        assert!(validate_readiness_value(KEY_READINESS, "ping").is_err());
+        assert!(validate_readiness_value(KEY_READINESS_TIMEOUT_MS, "0").is_err());
+        assert!(validate_readiness_value(KEY_READINESS, "").is_ok());
+    }
+
+    #[test]
+    fn health_check_comes_from_the_manifest() {
+        let mut runtime = RuntimeInfo {
+            runtime_type: "node".to_string(),
+            command: "npx".to_string(),
+            args: vec![],
+            transport: "stdio".to_string(),
+            syscalls: vec![],
+            health_check: None,
+            schedule: None,
+            limits: None,
+            socket: None,
+        };
+        assert_eq!(health_check(&runtime), None);
+
+        runtime.health_check = Some(
+            serde_json::from_str(r#"{"probe": "tcp", "port": 8080, "intervalSecs": 0}"#).unwrap(),
+        );
+        assert_eq!(
+            health_check(&runtime),
+            Some(HealthCheck {
+                probe: HealthProbe::Tcp { port: 8080 },
+                interval_secs: 1,
+                timeout_ms: 5_000,
+                failure_threshold: 3,
+            })
+        );
+    }
+}
@@ -0,0 +1,235 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Structured receipts written by `berth install` and `berth update`.
+//!
+//! Each receipt records where a server came from, the version it resolved to,
+//! the files Berth wrote for it with their SHA-256 digests, and the policy
+//! decisions taken along the way. Receipts live in
+//! `<state>/receipts/<server>/<epoch_ms>-<action>.json` and outlive the server,
+//! so `berth receipts` still answers after an uninstall. `berth uninstall`
+//! reads them to remove exactly the artifacts an install created.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use berth_common::sha256_file;
+use berth_registry::config::SourceInfo;
+use serde::{Deserialize, Serialize};
+
+use crate::paths;
+use crate::time_format::now_epoch_ms;
+
+/// What an install or update did, as recorded at the time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Receipt {
+    pub server: String,
+    /// `install` or `update`.
+    pub action: String,
+    pub timestamp_epoch_ms: u64,
+    pub version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_version: Option<String>,
+    pub source: SourceInfo,
+    /// Mirror artifact the server was installed from, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub artifact: Option<FileRecord>,
+    #[serde(default)]
+    pub files: Vec<FileRecord>,
+    #[serde(default)]
+    pub policy: Vec<PolicyDecision>,
+}
+
+/// One file an install wrote or read.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileRecord {
+    pub path: PathBuf,
+    /// `config`, `binary`, or `artifact`.
+    pub kind: String,
+    pub sha256: String,
+}
+
+impl FileRecord {
+    /// Records a file together with its current digest.
+    pub fn hashed(path: &Path, kind: &str) -> Result<Self, String> {
+        let sha256 =
+            sha256_file(path).map_err(|e| format!("Failed to hash {}: {e}", path.display()))?;
+        Ok(FileRecord {
+            path: path.to_path_buf(),
+            kind: kind.to_string(),
+            sha256,
+        })
+    }
+
+    /// Returns whether the file still has the recorded contents.
+    pub fn unchanged(&self) -> bool {
+        sha256_file(&self.path).is_ok_and(|actual| actual == self.sha256)
+    }
+}
+
+/// A policy check and its outcome, e.g. `global` / `allowed at start`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicyDecision {
+    pub check: String,
+    pub decision: String,
+}
+
+impl PolicyDecision {
+    pub fn new(check: &str, decision: impl Into<String>) -> Self {
+        PolicyDecision {
+            check: check.to_string(),
+            decision: decision.into(),
+        }
+    }
+}
+
+impl Receipt {
+    /// Starts a receipt stamped with the current time; callers add files and decisions.
+    pub fn new(server: &str, action: &str, version: &str, source: &SourceInfo) -> Self {
+        Receipt {
+            server: server.to_string(),
+            action: action.to_string(),
+            timestamp_epoch_ms: now_epoch_ms(),
+            version: version.to_string(),
+            previous_version: None,
+            source: source.clone(),
+            artifact: None,
+            files: Vec::new(),
+            policy: Vec::new(),
+        }
+    }
+}
+
+/// Writes a receipt for its server; returns the receipt path.
+pub fn write(receipt: &Receipt) -> Result<PathBuf, String> {
+    write_in(&receipts_dir(&receipt.server)?, receipt)
+}
+
+/// Returns a server's receipts, oldest first.
+pub fn list(server: &str) -> Result<Vec<Receipt>, String> {
+    list_in(&receipts_dir(server)?)
+}
+
+/// Returns the binaries recorded in `receipts` that still have their recorded
+/// contents, i.e. the ones safe to remove on uninstall.
+pub fn removable_files(receipts: &[Receipt]) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = receipts
+        .iter()
+        .flat_map(|r| &r.files)
+        .filter(|f| f.kind == "binary" && f.unchanged())
+        .map(|f| f.path.clone())
+        .collect();
+    files.sort();
+    files.dedup();
+    files
+}
+
+fn receipts_dir(server: &str) -> Result<PathBuf, String> {
+    paths::receipts_dir(server).ok_or("Could not determine home directory.".to_string())
+}
+
+fn write_in(dir: &Path, receipt: &Receipt) -> Result<PathBuf, String> {
+    fs::create_dir_all(dir)
+        .map_err(|e| format!("Failed to create receipt directory {}: {e}", dir.display()))?;
+    let rendered = serde_json::to_string_pretty(receipt)
+        .map_err(|e| format!("Failed to serialize receipt: {e}"))?;
+    // Two receipts within one millisecond still need distinct, ordered names.
+    let stamp = receipt_paths(dir)?
+        .last()
+        .map_or(receipt.timestamp_epoch_ms, |(last, _)| {
+            receipt.timestamp_epoch_ms.max(last + 1)
+        });
+    let path = dir.join(format!("{stamp}-{}.json", receipt.action));
+    fs::write(&path, rendered)
+        .map_err(|e| format!("Failed to write receipt {}: {e}", path.display()))?;
+    Ok(path)
+}
+
+fn list_in(dir: &Path) -> Result<Vec<Receipt>, String> {
+    receipt_paths(dir)?
+        .into_iter()
+        .map(|(_, path)| {
+            let content = fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read receipt {}: {e}", path.display()))?;
+            serde_json::from_str(&content)
+                .map_err(|e| format!("Failed to parse receipt {}: {e}", path.display()))
+        })
+        .collect()
+}
+
+/// Returns receipt files with their stamps, oldest first.
+fn receipt_paths(dir: &Path) -> Result<Vec<(u64, PathBuf)>, String> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to read {}: {e}", dir.display())),
+    };
+    let mut paths: Vec<(u64, PathBuf)> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|path| {
+            let stem = path.file_stem()?.to_str()?;
+            let stamp = stem.split_once('-')?.0.parse().ok()?;
+            Some((stamp, path))
+        })
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source() -> SourceInfo {
+        SourceInfo {
+            source_type: "npm".to_string(),
+            package: "@example/server".to_string(),
+            repository: "https://github.com/example/server".to_string(),
+            registry: None,
+            sha256: None,
+        }
+    }
+
+    #[test]
+    fn receipts_round_trip_in_order() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("receipts");
+        let mut install = Receipt::new("github", "install", "1.2.0", &source());
+        install.timestamp_epoch_ms = 1_000;
+        install
+            .policy
+            .push(PolicyDecision::new("global", "allowed at start"));
+        let mut update = Receipt::new("github", "update", "1.3.0", &source());
+        update.timestamp_epoch_ms = 1_000;
+        update.previous_version = Some("1.2.0".to_string());
+
+        write_in(&dir, &install).unwrap();
+        write_in(&dir, &update).unwrap();
+        let receipts = list_in(&dir).unwrap();
+        assert_eq!(receipts.len(), 2);
+        assert_eq!(receipts[0].action, "install");
+        assert_eq!(receipts[0].policy[0].decision, "allowed at start");
+        assert_eq!(receipts[1].previous_version.as_deref(), Some("1.2.0"));
+        assert!(list_in(&tmp.path().join("missing")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn only_unchanged_binaries_are_removable() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (kept, edited) = (tmp.path().join("kept"), tmp.path().join("edited"));
+        fs::write(&kept, "bin").unwrap();
+        fs::write(&edited, "bin").unwrap();
+        let mut receipt = Receipt::new("github", "install", "1.0.0", &source());
+        receipt.files = vec![
+            FileRecord::hashed(&kept, "binary").unwrap(),
+            FileRecord::hashed(&edited, "binary").unwrap(),
+        ];
+        fs::write(&edited, "patched").unwrap();
+        assert_eq!(removable_files(&[receipt]), vec![kept]);
+    }
+}
@@ -0,0 +1,398 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Helpers for parsing and validating the port and replica count of servers
+//! on the `http` transport, and for expanding such a server into the
+//! processes Berth runs for it.
+//!
+//! An `http` server listens on `berth.port`, passed in `BERTH_PORT` and in
+//! place of `{port}` in its arguments. With `berth.replicas` above 1, Berth
+//! runs that many copies on the ports after `berth.port`, named `<server>@1`,
+//! `<server>@2`, ..., and under the server's own name a gateway on
+//! `berth.port` (the hidden `__gateway` command) that hands each connection
+//! to the next replica that accepts it.
+
+use std::collections::BTreeMap;
+
+use berth_registry::config::InstalledServer;
+use berth_runtime::{
+    replica_name, replica_of, ProcessSpec, ReadinessCheck, ReadinessProbe, RuntimeManager,
+    ServerStatus, HTTP, PORT_ENV, PORT_PLACEHOLDER,
+};
+use colored::Colorize;
+
+use crate::readiness_policy::DEFAULT_READINESS_TIMEOUT_MS;
+
+pub const KEY_PORT: &str = "berth.port";
+pub const KEY_REPLICAS: &str = "berth.replicas";
+pub const MAX_REPLICAS: u16 = 16;
+
+/// Hidden subcommand that balances connections across replicas.
+pub const GATEWAY_COMMAND: &str = "__gateway";
+
+/// Returns whether a key is reserved for port and replica settings.
+pub fn is_replica_key(key: &str) -> bool {
+    matches!(key, KEY_PORT | KEY_REPLICAS)
+}
+
+/// Validates one key/value pair for port and replica settings.
+pub fn validate_replica_value(key: &str, value: &str) -> Result<(), String> {
+    match key {
+        KEY_PORT => parse_port(value).map(|_| ()),
+        KEY_REPLICAS => parse_replicas(value).map(|_| ()),
+        _ => Err(format!("Unknown replica key: {key}")),
+    }
+}
+
+/// Where an `http` server listens and how many copies of it run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReplicaPolicy {
+    pub port: u16,
+    pub replicas: u16,
+}
+
+/// Parses the port and replica count of an installed `http` server; `None`
+/// for servers on other transports and `http` servers without `berth.port`,
+/// which run as a single process listening where they are configured to.
+pub fn parse_replica_policy(installed: &InstalledServer) -> Result<Option<ReplicaPolicy>, String> {
+    let name = &installed.server.name;
+    let replicas = match installed.config.get(KEY_REPLICAS) {
+        Some(v) => parse_replicas(v)?,
+        None => 1,
+    };
+    let transport = installed.runtime.transport.trim();
+    if transport != HTTP {
+        if replicas > 1 {
+            return Err(format!(
+                "`{KEY_REPLICAS}` needs transport `{HTTP}`; {} uses `{transport}`.",
+                name.cyan()
+            ));
+        }
+        return Ok(None);
+    }
+    let port = match installed.config.get(KEY_PORT) {
+        Some(v) => parse_port(v)?,
+        None if replicas > 1 => {
+            return Err(format!(
+                "{} runs {replicas} replicas. Set `{KEY_PORT}` to the port their gateway should listen on.",
+                name.cyan()
+            ))
+        }
+        None => return Ok(None),
+    };
+    // Replicas take the ports after the gateway's; a single copy uses `port`.
+    if replicas > 1 && u32::from(port) + u32::from(replicas) > u32::from(u16::MAX) {
+        return Err(format!(
+            "`{KEY_PORT}` {port} leaves no room for {replicas} replica ports after it."
+        ));
+    }
+    Ok(Some(ReplicaPolicy { port, replicas }))
+}
+
+/// What an [`Instance`] runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    /// The server itself, the only process of an unreplicated server.
+    Server,
+    /// One copy of a replicated server, counting from 1.
+    Replica(u16),
+    /// The load balancer in front of a replicated server's copies.
+    Gateway,
+}
+
+/// One process Berth runs for an installed server, under its own runtime
+/// state, log and supervisor.
+#[derive(Debug, Clone)]
+pub struct Instance {
+    pub name: String,
+    pub role: Role,
+    /// Port it listens on; `None` off the `http` transport.
+    pub port: Option<u16>,
+    pub spec: ProcessSpec,
+}
+
+/// Expands the launch spec of `server` into its instances: the server
+/// itself, or its replicas followed by their gateway.
+///
+/// An `http` instance without a readiness probe counts as ready once its port
+/// accepts connections.
+pub fn instances(
+    server: &str,
+    installed: &InstalledServer,
+    spec: ProcessSpec,
+) -> Result<Vec<Instance>, String> {
+    let Some(policy) = parse_replica_policy(installed)? else {
+        return Ok(vec![Instance {
+            name: server.to_string(),
+            role: Role::Server,
+            port: None,
+            spec,
+        }]);
+    };
+    if policy.replicas == 1 {
+        return Ok(vec![Instance {
+            name: server.to_string(),
+            role: Role::Server,
+            port: Some(policy.port),
+            spec: apply_port(spec, policy.port),
+        }]);
+    }
+
+    let mut instances: Vec<Instance> = (1..=policy.replicas)
+        .map(|replica| {
+            let port = policy.port + replica;
+            Instance {
+                name: replica_name(server, replica),
+                role: Role::Replica(replica),
+                port: Some(port),
+                spec: apply_port(spec.clone(), port),
+            }
+        })
+        .collect();
+    let exe = std::env::current_exe().map_err(|e| format!("failed to locate current exe: {e}"))?;
+    let mut args = vec![GATEWAY_COMMAND.to_string(), policy.port.to_string()];
+    args.extend(
+        instances
+            .iter()
+            .filter_map(|i| i.port)
+            .map(|p| p.to_string()),
+    );
+    instances.push(Instance {
+        name: server.to_string(),
+        role: Role::Gateway,
+        port: Some(policy.port),
+        spec: ProcessSpec {
+            command: exe.display().to_string(),
+            args,
+            env: BTreeMap::new(),
+            auto_restart: spec.auto_restart,
+            clean_env: spec.clean_env,
+            readiness: Some(listening(policy.port)),
+            ..ProcessSpec::default()
+        },
+    });
+    Ok(instances)
+}
+
+/// Hands `port` to an `http` server's launch.
+fn apply_port(mut spec: ProcessSpec, port: u16) -> ProcessSpec {
+    let value = port.to_string();
+    for arg in &mut spec.args {
+        *arg = arg.replace(PORT_PLACEHOLDER, &value);
+    }
+    spec.env.insert(PORT_ENV.to_string(), value);
+    spec.readiness.get_or_insert_with(|| listening(port));
+    spec
+}
+
+fn listening(port: u16) -> ReadinessCheck {
+    ReadinessCheck {
+        probe: ReadinessProbe::TcpPort { port },
+        timeout_ms: DEFAULT_READINESS_TIMEOUT_MS,
+    }
+}
+
+/// Picks the spec of one instance, e.g. `github@2`, from the expanded spec
+/// of the server it belongs to.
+pub fn instance_spec(
+    instance: &str,
+    installed: &InstalledServer,
+    spec: ProcessSpec,
+) -> Result<ProcessSpec, String> {
+    instances(base_server(instance), installed, spec)?
+        .into_iter()
+        .find(|i| i.name == instance)
+        .map(|i| i.spec)
+        .ok_or_else(|| format!("{} is no longer configured", instance.cyan()))
+}
+
+/// Returns the installed server an instance name belongs to.
+pub fn base_server(instance: &str) -> &str {
+    replica_of(instance).map_or(instance, |(server, _)| server)
+}
+
+/// Lists replica instances of `server` that are not stopped, by replica
+/// number, whatever `berth.replicas` says now.
+pub fn running_replicas(runtime: &RuntimeManager, server: &str) -> Vec<String> {
+    let mut replicas: Vec<(u16, String)> = runtime
+        .status_snapshot()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|(_, status)| *status != ServerStatus::Stopped)
+        .filter_map(|(instance, _)| {
+            let (owner, replica) = replica_of(&instance)?;
+            (owner == server).then_some((replica, instance.clone()))
+        })
+        .collect();
+    replicas.sort();
+    replicas.into_iter().map(|(_, instance)| instance).collect()
+}
+
+fn parse_port(value: &str) -> Result<u16, String> {
+    match value.trim().parse::<u16>() {
+        Ok(port) if port > 0 => Ok(port),
+        _ => Err(format!(
+            "Invalid value `{value}`. Expected a TCP port (1-65535)."
+        )),
+    }
+}
+
+fn parse_replicas(value: &str) -> Result<u16, String> {
+    match value.trim().parse::<u16>() {
+        Ok(n) if (1..=MAX_REPLICAS).contains(&n) => Ok(n),
+        _ => Err(format!(
+            "Invalid value `{value}`. Expected a replica count from 1 to {MAX_REPLICAS}."
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use berth_registry::config::{
+        ConfigMeta, PermissionsInfo, RuntimeInfo, ServerInfo, SourceInfo,
+    };
+
+    fn installed(transport: &str, config: &[(&str, &str)]) -> InstalledServer {
+        InstalledServer {
+            server: ServerInfo {
+                name: "search".to_string(),
+                display_name: "Search".to_string(),
+                version: "1.0.0".to_string(),
+                description: "d".to_string(),
+                category: "search".to_string(),
+                maintainer: "m".to_string(),
+                trust_level: "community".to_string(),
+                tags: Vec::new(),
+                version_constraint: None,
+            },
+            source: SourceInfo {
+                source_type: "npm".to_string(),
+                package: "search".to_string(),
+                repository: "https://example.com".to_string(),
+                registry: None,
+                sha256: None,
+            },
+            runtime: RuntimeInfo {
+                runtime_type: "node".to_string(),
+                command: "node".to_string(),
+                args: vec!["server.js".to_string(), "--port={port}".to_string()],
+                transport: transport.to_string(),
+                syscalls: vec![],
+                health_check: None,
+                schedule: None,
+                limits: None,
+                socket: None,
+            },
+            permissions: PermissionsInfo::default(),
+            config: config
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            config_meta: ConfigMeta::default(),
+        }
+    }
+
+    fn spec(installed: &InstalledServer) -> ProcessSpec {
+        ProcessSpec {
+            command: installed.runtime.command.clone(),
+            args: installed.runtime.args.clone(),
+            clean_env: true,
+            ..ProcessSpec::default()
+        }
+    }
+
+    #[test]
+    fn validate_replica_value_rejects_bad_ports_and_counts() {
+        assert!(validate_replica_value(KEY_PORT, "8080").is_ok());
+        assert!(validate_replica_value(KEY_PORT, "0").is_err());
+        assert!(validate_replica_value(KEY_PORT, "70000").is_err());
+        assert!(validate_replica_value(KEY_REPLICAS, "3").is_ok());
+        assert!(validate_replica_value(KEY_REPLICAS, "0").is_err());
+        assert!(validate_replica_value(KEY_REPLICAS, "17").is_err());
+    }
+
+    #[test]
+    fn replicas_need_the_http_transport_and_a_port() {
+        assert_eq!(
+            parse_replica_policy(&installed("stdio", &[])).unwrap(),
+            None
+        );
+        assert!(parse_replica_policy(&installed("stdio", &[(KEY_REPLICAS, "2")])).is_err());
+        assert_eq!(parse_replica_policy(&installed(HTTP, &[])).unwrap(), None);
+        assert!(parse_replica_policy(&installed(HTTP, &[(KEY_REPLICAS, "2")])).is_err());
+        assert!(parse_replica_policy(&installed(
+            HTTP,
+            &[(KEY_PORT, "65534"), (KEY_REPLICAS, "2")]
+        ))
+        .is_err());
+        assert_eq!(
+            parse_replica_policy(&installed(HTTP, &[(KEY_PORT, "8080")])).unwrap(),
+            Some(ReplicaPolicy {
+                port: 8080,
+                replicas: 1
+            })
+        );
+    }
+
+    #[test]
+    fn a_single_copy_may_use_the_highest_port() {
+        let http = installed(HTTP, &[(KEY_PORT, "65535"), (KEY_REPLICAS, "1")]);
+        assert_eq!(
+            parse_replica_policy(&http).unwrap(),
+            Some(ReplicaPolicy {
+                port: 65535,
+                replicas: 1
+            })
+        );
+        let only = instances("search", &http, spec(&http)).unwrap();
+        assert_eq!(only[0].port, Some(65535));
+    }
+
+    #[test]
+    fn unreplicated_servers_run_as_themselves() {
+        let stdio = installed("stdio", &[]);
+        let only = instances("search", &stdio, spec(&stdio)).unwrap();
+        assert_eq!(only.len(), 1);
+        assert_eq!((only[0].role, only[0].port), (Role::Server, None));
+        assert_eq!(only[0].spec.args, ["server.js", "--port={port}"]);
+
+        let http = installed(HTTP, &[(KEY_PORT, "8080")]);
+        let only = instances("search", &http, spec(&http)).unwrap();
+        assert_eq!(only.len(), 1);
+        assert_eq!(only[0].name, "search");
+        assert_eq!(only[0].spec.args, ["server.js", "--port=8080"]);
+        assert_eq!(only[0].spec.env[PORT_ENV], "8080");
+    }
+
+    #[test]
+    fn replicas_get_consecutive_ports_behind_a_gateway() {
+        let http = installed(HTTP, &[(KEY_PORT, "8080"), (KEY_REPLICAS, "3")]);
+        let all = instances("search", &http, spec(&http)).unwrap();
+        let names: Vec<_> = all.iter().map(|i| i.name.as_str()).collect();
+        assert_eq!(names, ["search@1", "search@2", "search@3", "search"]);
+        assert_eq!(all[1].role, Role::Replica(2));
+        assert_eq!(all[1].spec.args, ["server.js", "--port=8082"]);
+        assert_eq!(
+            all[1].spec.readiness.as_ref().map(|r| &r.probe),
+            Some(&ReadinessProbe::TcpPort { port: 8082 })
+        );
+
+        let gateway = &all[3];
+        assert_eq!((gateway.role, gateway.port), (Role::Gateway, Some(8080)));
+        assert_eq!(
+            gateway.spec.args,
+            [GATEWAY_COMMAND, "8080", "8081", "8082", "8083"]
+        );
+        assert!(gateway.spec.env.is_empty());
+        assert!(gateway.spec.clean_env);
+
+        let replica = instance_spec("search@3", &http, spec(&http)).unwrap();
+        assert_eq!(replica.env[PORT_ENV], "8083");
+        assert_eq!(
+            instance_spec("search", &http, spec(&http)).unwrap().args[0],
+            GATEWAY_COMMAND
+        );
+        assert!(instance_spec("search@4", &http, spec(&http)).is_err());
+    }
+}
@@ -5,22 +5,38 @@
 
 use std::collections::BTreeMap;
 
-use berth_runtime::AutoRestartPolicy;
+use berth_registry::config::InstalledServer;
+use berth_runtime::{AutoRestartPolicy, ShutdownSignal};
 
 pub const KEY_AUTO_RESTART: &str = "berth.auto-restart";
 pub const KEY_MAX_RESTARTS: &str = "berth.max-restarts";
+pub const KEY_RESTART_ON_EXIT_CODES: &str = "berth.restart-on-exit-codes";
+pub const KEY_CAPTURE_CORE: &str = "berth.capture-core";
+pub const KEY_SHUTDOWN_SIGNAL: &str = "berth.shutdown-signal";
+pub const KEY_SHUTDOWN_GRACE_PERIOD_SECS: &str = "berth.shutdown-grace-period-secs";
 pub const DEFAULT_MAX_RESTARTS: u32 = 3;
 
 /// Returns whether a key is reserved for Berth runtime policy settings.
 pub fn is_runtime_policy_key(key: &str) -> bool {
-    matches!(key, KEY_AUTO_RESTART | KEY_MAX_RESTARTS)
+    matches!(
+        key,
+        KEY_AUTO_RESTART
+            | KEY_MAX_RESTARTS
+            | KEY_RESTART_ON_EXIT_CODES
+            | KEY_CAPTURE_CORE
+            | KEY_SHUTDOWN_SIGNAL
+            | KEY_SHUTDOWN_GRACE_PERIOD_SECS
+    )
 }
 
 /// Validates one key/value pair for runtime policy settings.
 pub fn validate_runtime_policy_value(key: &str, value: &str) -> Result<(), String> {
     match key {
-        KEY_AUTO_RESTART => parse_bool(value).map(|_| ()),
+        KEY_AUTO_RESTART | KEY_CAPTURE_CORE => parse_bool(value).map(|_| ()),
         KEY_MAX_RESTARTS => parse_max_restarts(value).map(|_| ()),
+        KEY_RESTART_ON_EXIT_CODES => parse_exit_codes(value).map(|_| ()),
+        KEY_SHUTDOWN_SIGNAL => ShutdownSignal::parse(value).map(|_| ()),
+        KEY_SHUTDOWN_GRACE_PERIOD_SECS => parse_grace_period(value).map(|_| ()),
         _ => Err(format!("Unknown runtime policy key: {key}")),
     }
 }
@@ -37,12 +53,46 @@ pub fn parse_runtime_policy(
         Some(v) => parse_max_restarts(v)?,
         None => DEFAULT_MAX_RESTARTS,
     };
+    let restart_on_exit_codes = match config.get(KEY_RESTART_ON_EXIT_CODES) {
+        Some(v) if !v.trim().is_empty() => Some(parse_exit_codes(v)?),
+        _ => None,
+    };
     Ok(AutoRestartPolicy {
         enabled,
         max_restarts,
+        restart_on_exit_codes,
     })
 }
 
+/// Parses the shutdown signal and grace period used when stopping a server.
+pub fn parse_shutdown_settings(
+    config: &BTreeMap<String, String>,
+) -> Result<(Option<ShutdownSignal>, Option<u64>), String> {
+    let signal = match config.get(KEY_SHUTDOWN_SIGNAL) {
+        Some(v) if !v.trim().is_empty() => Some(ShutdownSignal::parse(v)?),
+        _ => None,
+    };
+    let grace_period_secs = match config.get(KEY_SHUTDOWN_GRACE_PERIOD_SECS) {
+        Some(v) if !v.trim().is_empty() => Some(parse_grace_period(v)?),
+        _ => None,
+    };
+    Ok((signal, grace_period_secs))
+}
+
+/// Returns whether crash capture is enabled for a server.
+///
+/// Only native `binary` runtimes qualify; interpreters such as node or python
+/// produce their own stack traces on crashes.
+pub fn capture_core_enabled(installed: &InstalledServer) -> Result<bool, String> {
+    if installed.runtime.runtime_type != "binary" {
+        return Ok(false);
+    }
+    match installed.config.get(KEY_CAPTURE_CORE) {
+        Some(v) => parse_bool(v),
+        None => Ok(false),
+    }
+}
+
 fn parse_bool(value: &str) -> Result<bool, String> {
     match value.trim().to_ascii_lowercase().as_str() {
         "true" => Ok(true),
@@ -66,6 +116,36 @@ fn parse_max_restarts(value: &str) -> Result<u32, String> {
     Ok(parsed)
 }
 
+fn parse_grace_period(value: &str) -> Result<u64, String> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| format!("Invalid value `{value}`. Expected a whole number of seconds (>= 0)."))
+}
+
+/// Parses a comma-separated exit code list such as `1,137` or `[1, 137]`.
+fn parse_exit_codes(value: &str) -> Result<Vec<i32>, String> {
+    let inner = value.trim().trim_start_matches('[').trim_end_matches(']');
+    let codes = inner
+        .split(',')
+        .map(str::trim)
+        .filter(|code| !code.is_empty())
+        .map(|code| {
+            code.parse::<i32>().map_err(|_| {
+                format!(
+                    "Invalid value `{value}`. Expected comma-separated exit codes, e.g. `1,137`."
+                )
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if codes.is_empty() {
+        return Err(format!(
+            "Invalid value `{value}`. Expected comma-separated exit codes, e.g. `1,137`."
+        ));
+    }
+    Ok(codes)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -87,11 +167,45 @@ mod tests {
         let policy = parse_runtime_policy(&cfg).unwrap();
         assert!(policy.enabled);
         assert_eq!(policy.max_restarts, 5);
+        assert_eq!(policy.restart_on_exit_codes, None);
+    }
+
+    #[test]
+    fn parse_runtime_policy_reads_restart_exit_codes() {
+        let cfg = BTreeMap::from([(
+            KEY_RESTART_ON_EXIT_CODES.to_string(),
+            "[1, 137]".to_string(),
+        )]);
+        let policy = parse_runtime_policy(&cfg).unwrap();
+        assert_eq!(policy.restart_on_exit_codes, Some(vec![1, 137]));
+        assert!(validate_runtime_policy_value(KEY_RESTART_ON_EXIT_CODES, "1,x").is_err());
+        assert!(validate_runtime_policy_value(KEY_RESTART_ON_EXIT_CODES, "[]").is_err());
     }
 
     #[test]
     fn validate_runtime_policy_rejects_bad_values() {
         assert!(validate_runtime_policy_value(KEY_AUTO_RESTART, "maybe").is_err());
         assert!(validate_runtime_policy_value(KEY_MAX_RESTARTS, "0").is_err());
+        assert!(validate_runtime_policy_value(KEY_CAPTURE_CORE, "yes").is_err());
+        assert!(validate_runtime_policy_value(KEY_CAPTURE_CORE, "true").is_ok());
+    }
+
+    #[test]
+    fn parse_shutdown_settings_reads_signal_and_grace_period() {
+        assert_eq!(
+            parse_shutdown_settings(&BTreeMap::new()).unwrap(),
+            (None, None)
+        );
+        let cfg = BTreeMap::from([
+            (KEY_SHUTDOWN_SIGNAL.to_string(), "sigint".to_string()),
+            (KEY_SHUTDOWN_GRACE_PERIOD_SECS.to_string(), "30".to_string()),
+        ]);
+        assert_eq!(
+            parse_shutdown_settings(&cfg).unwrap(),
+            (Some(ShutdownSignal::Sigint), Some(30))
+        );
+        assert!(validate_runtime_policy_value(KEY_SHUTDOWN_SIGNAL, "INT").is_ok());
+        assert!(validate_runtime_policy_value(KEY_SHUTDOWN_SIGNAL, "SIGKILL").is_err());
+        assert!(validate_runtime_policy_value(KEY_SHUTDOWN_GRACE_PERIOD_SECS, "-1").is_err());
     }
 }
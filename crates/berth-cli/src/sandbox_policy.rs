@@ -5,18 +5,47 @@
 
 use std::collections::BTreeMap;
 
+use berth_runtime::SeccompProfile;
+
 pub const KEY_SANDBOX: &str = "berth.sandbox";
 pub const KEY_SANDBOX_NETWORK: &str = "berth.sandbox-network";
+pub const KEY_RUN_AS: &str = "berth.run-as";
+pub const KEY_SECCOMP: &str = "berth.seccomp";
+
+/// Network access a server gets, from `berth.sandbox-network`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SandboxNetwork {
+    /// Unrestricted, as the user running Berth.
+    #[default]
+    Inherit,
+    /// Refuse to launch the server at all.
+    DenyAll,
+    /// Only the hosts in the effective `network:` permissions.
+    Allowlist,
+}
+
+impl SandboxNetwork {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SandboxNetwork::Inherit => "inherit",
+            SandboxNetwork::DenyAll => "deny-all",
+            SandboxNetwork::Allowlist => "allowlist",
+        }
+    }
+}
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct SandboxPolicy {
     pub enabled: bool,
-    pub network_deny_all: bool,
+    pub network: SandboxNetwork,
 }
 
 /// Returns whether a key is reserved for Berth sandbox policy settings.
 pub fn is_sandbox_policy_key(key: &str) -> bool {
-    matches!(key, KEY_SANDBOX | KEY_SANDBOX_NETWORK)
+    matches!(
+        key,
+        KEY_SANDBOX | KEY_SANDBOX_NETWORK | KEY_RUN_AS | KEY_SECCOMP
+    )
 }
 
 /// Validates one key/value pair for sandbox policy settings.
@@ -24,6 +53,9 @@ pub fn validate_sandbox_policy_value(key: &str, value: &str) -> Result<(), Strin
     match key {
         KEY_SANDBOX => parse_sandbox_mode(value).map(|_| ()),
         KEY_SANDBOX_NETWORK => parse_network_mode(value).map(|_| ()),
+        KEY_RUN_AS if value.trim().is_empty() => Ok(()),
+        KEY_RUN_AS => parse_user_name(value).map(|_| ()),
+        KEY_SECCOMP => parse_seccomp_mode(value).map(|_| ()),
         _ => Err(format!("Unknown sandbox policy key: {key}")),
     }
 }
@@ -34,14 +66,77 @@ pub fn parse_sandbox_policy(config: &BTreeMap<String, String>) -> Result<Sandbox
         Some(v) => parse_sandbox_mode(v)?,
         None => false,
     };
-    let network_deny_all = match config.get(KEY_SANDBOX_NETWORK) {
+    let network = match config.get(KEY_SANDBOX_NETWORK) {
         Some(v) => parse_network_mode(v)?,
+        None => SandboxNetwork::default(),
+    };
+    Ok(SandboxPolicy { enabled, network })
+}
+
+/// Parses the OS user a server should run as, if configured.
+///
+/// Switching users is only implemented on Linux; elsewhere a configured user
+/// is an error rather than a silent downgrade in isolation.
+pub fn parse_run_as(config: &BTreeMap<String, String>) -> Result<Option<String>, String> {
+    let user = match config.get(KEY_RUN_AS) {
+        Some(v) if !v.trim().is_empty() => parse_user_name(v)?,
+        _ => return Ok(None),
+    };
+    if !cfg!(target_os = "linux") {
+        return Err(format!(
+            "`{KEY_RUN_AS}` is only supported on Linux; unset it to run as the current user."
+        ));
+    }
+    Ok(Some(user))
+}
+
+/// Parses the seccomp profile for a server, if filtering is enabled.
+///
+/// `syscalls` are the ones its manifest declares as required; they are lifted
+/// from the default deny list.
+pub fn parse_seccomp(
+    config: &BTreeMap<String, String>,
+    syscalls: &[String],
+) -> Result<Option<SeccompProfile>, String> {
+    let enabled = match config.get(KEY_SECCOMP) {
+        Some(v) => parse_seccomp_mode(v)?,
         None => false,
     };
-    Ok(SandboxPolicy {
-        enabled,
-        network_deny_all,
-    })
+    if !enabled {
+        return Ok(None);
+    }
+    if !cfg!(target_os = "linux") {
+        return Err(format!(
+            "`{KEY_SECCOMP}` is only supported on Linux; set it to `off` to start without a filter."
+        ));
+    }
+    Ok(Some(SeccompProfile {
+        allow: syscalls.to_vec(),
+    }))
+}
+
+/// Accepts POSIX-style user names such as `mcp-github` or `_mcp`.
+fn parse_user_name(value: &str) -> Result<String, String> {
+    let name = value.trim();
+    let valid = name.len() <= 32
+        && name
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_lowercase() || c == '_')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-');
+    if !valid {
+        return Err(format!(
+            "Invalid value `{value}`. Expected an OS user name such as `mcp-github`."
+        ));
+    }
+    if name == "root" {
+        return Err(format!(
+            "`{KEY_RUN_AS}` must name an unprivileged user, not `root`."
+        ));
+    }
+    Ok(name.to_string())
 }
 
 fn parse_sandbox_mode(value: &str) -> Result<bool, String> {
@@ -54,12 +149,23 @@ fn parse_sandbox_mode(value: &str) -> Result<bool, String> {
     }
 }
 
-fn parse_network_mode(value: &str) -> Result<bool, String> {
+fn parse_seccomp_mode(value: &str) -> Result<bool, String> {
     match value.trim().to_ascii_lowercase().as_str() {
-        "deny-all" => Ok(true),
-        "inherit" => Ok(false),
+        "default" => Ok(true),
+        "off" => Ok(false),
         _ => Err(format!(
-            "Invalid value `{value}`. Expected `inherit` or `deny-all`."
+            "Invalid value `{value}`. Expected `default` or `off`."
+        )),
+    }
+}
+
+fn parse_network_mode(value: &str) -> Result<SandboxNetwork, String> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "deny-all" => Ok(SandboxNetwork::DenyAll),
+        "inherit" => Ok(SandboxNetwork::Inherit),
+        "allowlist" => Ok(SandboxNetwork::Allowlist),
+        _ => Err(format!(
+            "Invalid value `{value}`. Expected `inherit`, `deny-all`, or `allowlist`."
         )),
     }
 }
@@ -73,7 +179,7 @@ mod tests {
         let cfg = BTreeMap::new();
         let policy = parse_sandbox_policy(&cfg).unwrap();
         assert!(!policy.enabled);
-        assert!(!policy.network_deny_all);
+        assert_eq!(policy.network, SandboxNetwork::Inherit);
     }
 
     #[test]
@@ -84,7 +190,12 @@ mod tests {
         ]);
         let policy = parse_sandbox_policy(&cfg).unwrap();
         assert!(policy.enabled);
-        assert!(policy.network_deny_all);
+        assert_eq!(policy.network, SandboxNetwork::DenyAll);
+        let cfg = BTreeMap::from([(KEY_SANDBOX_NETWORK.to_string(), "allowlist".to_string())]);
+        assert_eq!(
+            parse_sandbox_policy(&cfg).unwrap().network,
+            SandboxNetwork::Allowlist
+        );
     }
 
     #[test]
@@ -92,4 +203,33 @@ mod tests {
         assert!(validate_sandbox_policy_value(KEY_SANDBOX, "on").is_err());
         assert!(validate_sandbox_policy_value(KEY_SANDBOX_NETWORK, "deny").is_err());
     }
+
+    #[test]
+    fn run_as_accepts_plain_user_names_only() {
+        assert!(validate_sandbox_policy_value(KEY_RUN_AS, "mcp-github").is_ok());
+        assert!(validate_sandbox_policy_value(KEY_RUN_AS, "_mcp").is_ok());
+        assert!(validate_sandbox_policy_value(KEY_RUN_AS, "root").is_err());
+        assert!(validate_sandbox_policy_value(KEY_RUN_AS, "-u").is_err());
+        assert!(validate_sandbox_policy_value(KEY_RUN_AS, "a b").is_err());
+        assert_eq!(parse_run_as(&BTreeMap::new()).unwrap(), None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn parse_run_as_reads_user() {
+        let cfg = BTreeMap::from([(KEY_RUN_AS.to_string(), "mcp-github".to_string())]);
+        assert_eq!(parse_run_as(&cfg).unwrap(), Some("mcp-github".to_string()));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn parse_seccomp_lifts_declared_syscalls() {
+        assert_eq!(parse_seccomp(&BTreeMap::new(), &[]).unwrap(), None);
+        let cfg = BTreeMap::from([(KEY_SECCOMP.to_string(), "default".to_string())]);
+        let profile = parse_seccomp(&cfg, &["ptrace".to_string()])
+            .unwrap()
+            .unwrap();
+        assert_eq!(profile.allow, vec!["ptrace".to_string()]);
+        assert!(validate_sandbox_policy_value(KEY_SECCOMP, "strict").is_err());
+    }
 }
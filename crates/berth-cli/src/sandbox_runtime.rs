@@ -3,12 +3,57 @@
 
 //! Runtime helpers that adapt process launch for sandbox policies.
 
-use std::collections::BTreeMap;
+use berth_registry::permissions::{FsMode, FsPermission, NetworkPermission};
+use berth_runtime::SOCKET_ENV;
+use std::collections::{BTreeMap, BTreeSet};
 use std::env;
+use std::fs;
 use std::path::Path;
 use std::path::PathBuf;
-
-use crate::sandbox_policy::SandboxPolicy;
+use std::process::{Command, Stdio};
+
+use crate::egress_monitor::EGRESS_LOG_ENV;
+use crate::paths::{self, berth_layout, BerthLayout};
+use crate::sandbox_policy::{SandboxNetwork, SandboxPolicy};
+use crate::wasm_runtime::WASM_MODULE_ENV;
+
+/// Env var naming the backends a sandboxed server runs under, joined by `+`.
+pub const SANDBOX_BACKEND_ENV: &str = "BERTH_SANDBOX_BACKEND";
+
+/// Backend name for bubblewrap mount namespaces.
+pub const BWRAP_BACKEND: &str = "linux-bwrap";
+
+/// Env vars listing declared read and write paths for servers that scope
+/// themselves, separated like `PATH`.
+pub const SANDBOX_FS_READ_ENV: &str = "BERTH_SANDBOX_FS_READ";
+pub const SANDBOX_FS_WRITE_ENV: &str = "BERTH_SANDBOX_FS_WRITE";
+
+/// System directories mounted read-only into a bubblewrap sandbox.
+const BWRAP_SYSTEM_DIRS: [&str; 9] = [
+    "/usr", "/bin", "/sbin", "/lib", "/lib32", "/lib64", "/etc", "/opt", "/nix",
+];
+
+/// Places under `/run` that `/etc/resolv.conf` commonly links into; the rest
+/// of `/run` (sockets of the session bus, the container runtime, ...) stays
+/// out of the sandbox.
+const BWRAP_RESOLVER_PATHS: [&str; 3] = [
+    "/run/systemd/resolve",
+    "/run/resolvconf",
+    "/run/NetworkManager/resolv.conf",
+];
+
+/// Package cache variables of `npx` and `uvx`, each pointed at a directory of
+/// the server's own, so a sandboxed server cannot poison the caches that
+/// other servers and the user's own launches read.
+const RUNTIME_CACHE_VARS: [(&str, &str); 3] = [
+    ("npm_config_cache", "npm"),
+    ("UV_CACHE_DIR", "uv"),
+    ("UV_PYTHON_INSTALL_DIR", "uv-python"),
+];
+
+/// Directories next to a command's `bin/` that are mounted with it, so a
+/// node or python install finds its interpreter and modules.
+const INSTALL_DIRS: [&str; 4] = ["bin", "lib", "lib64", "libexec"];
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum HostPlatform {
@@ -23,26 +68,44 @@ struct RuntimeProbes {
     has_setpriv: bool,
     has_sandbox_exec: bool,
     has_landlock_restrict: bool,
+    has_bwrap: bool,
 }
 
 impl RuntimeProbes {
     fn detect() -> Self {
+        let platform = host_platform();
         Self {
-            platform: host_platform(),
+            platform,
             has_setpriv: path_has_binary("setpriv"),
             has_sandbox_exec: path_has_binary("sandbox-exec"),
             has_landlock_restrict: path_has_binary("landlock-restrict"),
+            has_bwrap: platform == HostPlatform::Linux && bwrap_usable(),
         }
     }
 }
 
+/// Returns whether `bwrap` is installed and may create namespaces here; hosts
+/// that disable unprivileged user namespaces fall back to Landlock.
+fn bwrap_usable() -> bool {
+    path_has_binary("bwrap")
+        && Command::new("bwrap")
+            .args(["--ro-bind", "/", "/", "true"])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success())
+}
+
 /// Applies sandbox runtime adaptation to command/args/env.
 pub fn apply_sandbox_runtime(
+    server: &str,
     command: &str,
     args: &[String],
     env_map: &mut BTreeMap<String, String>,
     policy: SandboxPolicy,
     filesystem_permissions: &[String],
+    network_permissions: &[String],
 ) -> (String, Vec<String>) {
     apply_sandbox_runtime_with_probes(
         command,
@@ -50,17 +113,24 @@ pub fn apply_sandbox_runtime(
         env_map,
         policy,
         filesystem_permissions,
+        network_permissions,
         RuntimeProbes::detect(),
+        berth_layout().map(|layout| (layout, server)),
     )
 }
 
+/// `home` is the Berth layout that is kept out of the sandbox, with the name
+/// of the server whose package caches live under it.
+#[allow(clippy::too_many_arguments)]
 fn apply_sandbox_runtime_with_probes(
     command: &str,
     args: &[String],
     env_map: &mut BTreeMap<String, String>,
     policy: SandboxPolicy,
     filesystem_permissions: &[String],
+    network_permissions: &[String],
     probes: RuntimeProbes,
+    home: Option<(BerthLayout, &str)>,
 ) -> (String, Vec<String>) {
     if !policy.enabled {
         return (command.to_string(), args.to_vec());
@@ -69,12 +139,9 @@ fn apply_sandbox_runtime_with_probes(
     env_map.insert("BERTH_SANDBOX_MODE".to_string(), "basic".to_string());
     env_map.insert(
         "BERTH_SANDBOX_NETWORK".to_string(),
-        if policy.network_deny_all {
-            "deny-all".to_string()
-        } else {
-            "inherit".to_string()
-        },
+        policy.network.as_str().to_string(),
     );
+    insert_path_policy(env_map, filesystem_permissions);
 
     match probes.platform {
         HostPlatform::Linux => {
@@ -82,25 +149,39 @@ fn apply_sandbox_runtime_with_probes(
             let mut wrapped_args = args.to_vec();
             let mut backend = Vec::new();
 
-            if probes.has_landlock_restrict {
+            if probes.has_bwrap {
+                let mut protected = Vec::new();
+                if let Some((layout, server)) = &home {
+                    insert_cache_dirs(env_map, &layout.state, server);
+                    protected.extend([layout.state.clone(), layout.config.clone()]);
+                    protected.dedup();
+                }
+                let mut bwrap_args =
+                    bwrap_mounts(command, env_map, filesystem_permissions, &protected);
+                if allows_no_network(policy, network_permissions) {
+                    bwrap_args.push("--unshare-net".to_string());
+                }
+                bwrap_args.push("--".to_string());
+                bwrap_args.push(wrapped_command);
+                bwrap_args.extend(wrapped_args);
+                wrapped_command = "bwrap".to_string();
+                wrapped_args = bwrap_args;
+                backend.push(BWRAP_BACKEND);
+            } else if probes.has_landlock_restrict {
                 let mut landlock_args = vec!["--best-effort".to_string()];
                 for permission in filesystem_permissions {
-                    if let Some((mode, path)) = parse_filesystem_permission(permission) {
-                        if path == "*" || path.is_empty() {
-                            continue;
-                        }
-                        match mode {
-                            "read" => {
-                                landlock_args.push("--ro".to_string());
-                                landlock_args.push(path.to_string());
-                            }
-                            "write" => {
-                                landlock_args.push("--rw".to_string());
-                                landlock_args.push(path.to_string());
-                            }
-                            _ => {}
-                        }
+                    let Some(permission) = parse_filesystem_permission(permission) else {
+                        continue;
+                    };
+                    if permission.is_wildcard() {
+                        continue;
                     }
+                    let flag = match permission.mode {
+                        FsMode::Read => "--ro",
+                        FsMode::Write => "--rw",
+                    };
+                    landlock_args.push(flag.to_string());
+                    landlock_args.push(permission.base_path());
                 }
                 landlock_args.push("--".to_string());
                 landlock_args.push(wrapped_command);
@@ -123,30 +204,50 @@ fn apply_sandbox_runtime_with_probes(
             }
 
             if backend.is_empty() {
-                env_map.insert("BERTH_SANDBOX_BACKEND".to_string(), "none".to_string());
+                env_map.insert(SANDBOX_BACKEND_ENV.to_string(), "none".to_string());
                 return (command.to_string(), args.to_vec());
             }
 
-            env_map.insert("BERTH_SANDBOX_BACKEND".to_string(), backend.join("+"));
+            env_map.insert(SANDBOX_BACKEND_ENV.to_string(), backend.join("+"));
             (wrapped_command, wrapped_args)
         }
         HostPlatform::MacOs if probes.has_sandbox_exec => {
             env_map.insert(
-                "BERTH_SANDBOX_BACKEND".to_string(),
+                SANDBOX_BACKEND_ENV.to_string(),
                 "macos-sandbox-exec".to_string(),
             );
-            let profile = build_macos_profile(policy, filesystem_permissions);
+            let profile = build_macos_profile(policy, filesystem_permissions, network_permissions);
             let mut wrapped = vec!["-p".to_string(), profile, command.to_string()];
             wrapped.extend(args.iter().cloned());
             ("sandbox-exec".to_string(), wrapped)
         }
+        // No enforcing backend: only the advisory path policy in the env remains.
         _ => {
-            env_map.insert("BERTH_SANDBOX_BACKEND".to_string(), "none".to_string());
+            env_map.insert(SANDBOX_BACKEND_ENV.to_string(), "path-policy".to_string());
             (command.to_string(), args.to_vec())
         }
     }
 }
 
+/// Returns whether a policy leaves the server no network access at all, so
+/// bubblewrap can give it an empty network namespace.
+///
+/// A non-empty allowlist keeps the host network: it is enforced by the egress
+/// hooks and proxy, which only a server that honors them is held to.
+fn allows_no_network(policy: SandboxPolicy, network_permissions: &[String]) -> bool {
+    match policy.network {
+        SandboxNetwork::Inherit => false,
+        SandboxNetwork::DenyAll => true,
+        SandboxNetwork::Allowlist => !network_permissions.iter().any(|permission| {
+            permission
+                .strip_prefix("network:")
+                .unwrap_or(permission)
+                .parse::<NetworkPermission>()
+                .is_ok()
+        }),
+    }
+}
+
 fn host_platform() -> HostPlatform {
     if cfg!(target_os = "linux") {
         HostPlatform::Linux
@@ -157,7 +258,217 @@ fn host_platform() -> HostPlatform {
     }
 }
 
-fn path_has_binary(name: &str) -> bool {
+/// Lists declared read and write paths in the env, for servers that scope
+/// their own file access where no backend enforces it.
+fn insert_path_policy(env_map: &mut BTreeMap<String, String>, filesystem_permissions: &[String]) {
+    let mut read = Vec::new();
+    let mut write = Vec::new();
+    for permission in filesystem_permissions {
+        let Some(permission) = parse_filesystem_permission(permission) else {
+            continue;
+        };
+        let path = if permission.is_wildcard() {
+            "/".to_string()
+        } else {
+            permission.base_path()
+        };
+        match permission.mode {
+            FsMode::Read => read.push(path),
+            FsMode::Write => write.push(path),
+        }
+    }
+    for (key, paths) in [(SANDBOX_FS_READ_ENV, read), (SANDBOX_FS_WRITE_ENV, write)] {
+        if let Ok(joined) = env::join_paths(paths) {
+            env_map.insert(key.to_string(), joined.to_string_lossy().into_owned());
+        }
+    }
+}
+
+/// Points the package caches of `npx` and `uvx` at directories of the
+/// server's own under `<state>/sandbox-cache/<server>`.
+fn insert_cache_dirs(env_map: &mut BTreeMap<String, String>, state: &Path, server: &str) {
+    let base = state.join("sandbox-cache").join(server);
+    for (var, dir) in RUNTIME_CACHE_VARS {
+        let dir = base.join(dir);
+        // Created up front, since bubblewrap only mounts what exists.
+        let _ = fs::create_dir_all(&dir);
+        env_map.insert(var.to_string(), dir.display().to_string());
+    }
+}
+
+/// Builds the mount options of a bubblewrap sandbox.
+///
+/// The server sees read-only system directories, a private `/tmp`, its
+/// command with the install directories next to it, and its declared paths;
+/// everything else in the user's filesystem is absent. `--die-with-parent`
+/// ties it to the process that owns it, because bubblewrap does not forward
+/// stop signals.
+///
+/// `protected` lists the Berth state and config directories, which hold
+/// credentials, other servers' configs, and their logs. Whatever mount would
+/// reveal them is shadowed with an empty `tmpfs`, and only the files Berth
+/// itself hands the server (its binary, package caches, egress log, socket
+/// directory, ...) are mounted back inside.
+fn bwrap_mounts(
+    command: &str,
+    env_map: &BTreeMap<String, String>,
+    filesystem_permissions: &[String],
+    protected: &[PathBuf],
+) -> Vec<String> {
+    let inside_protected = |path: &Path| protected.iter().any(|dir| path.starts_with(dir));
+    let mut read = BTreeSet::new();
+    let mut write = BTreeSet::new();
+    let (mut read_all, mut write_all) = (false, false);
+    for permission in filesystem_permissions {
+        let Some(permission) = parse_filesystem_permission(permission) else {
+            continue;
+        };
+        match (permission.is_wildcard(), permission.mode) {
+            (true, FsMode::Read) => read_all = true,
+            (true, FsMode::Write) => write_all = true,
+            (false, mode) => {
+                let path = permission.base_path();
+                if inside_protected(Path::new(&path)) {
+                    tracing::warn!(path, "not mounting a declared path inside the Berth home");
+                    continue;
+                }
+                match mode {
+                    FsMode::Read => read.insert(path),
+                    FsMode::Write => write.insert(path),
+                };
+            }
+        }
+    }
+
+    let mut args = [
+        "--die-with-parent",
+        "--unshare-pid",
+        "--unshare-ipc",
+        "--new-session",
+    ]
+    .map(String::from)
+    .to_vec();
+    let mut base: Vec<(&str, &str)> = Vec::new();
+    if write_all {
+        base.push(("--bind", "/"));
+    } else if read_all {
+        base.push(("--ro-bind", "/"));
+    } else {
+        for dir in BWRAP_SYSTEM_DIRS.iter().chain(&BWRAP_RESOLVER_PATHS) {
+            base.push(("--ro-bind-try", dir));
+        }
+    }
+    let declared = read
+        .iter()
+        .map(|path| ("--ro-bind-try", path.as_str()))
+        .chain(write.iter().map(|path| ("--bind-try", path.as_str())))
+        .collect::<Vec<_>>();
+    let mount = |args: &mut Vec<String>, binds: &[(&str, &str)]| {
+        for (flag, path) in binds {
+            args.extend([flag.to_string(), path.to_string(), path.to_string()]);
+        }
+    };
+    mount(&mut args, &base);
+    args.extend(["--dev", "/dev", "--proc", "/proc", "--tmpfs", "/tmp"].map(String::from));
+    mount(&mut args, &declared);
+    for dir in protected {
+        let revealed = base
+            .iter()
+            .chain(&declared)
+            .any(|(_, source)| dir.starts_with(source));
+        if revealed {
+            args.extend(["--tmpfs".to_string(), dir.display().to_string()]);
+        }
+    }
+
+    // Mounted after the shadowing, so they stay visible inside the Berth home.
+    let mut bind_back = |flag: &str, path: &str| {
+        args.extend([flag.to_string(), path.to_string(), path.to_string()]);
+    };
+    for path in command_mounts(command) {
+        let reveals_protected = protected.iter().any(|dir| dir.starts_with(&path));
+        let is_binary = path.is_file();
+        if reveals_protected || (!is_binary && inside_protected(&path)) {
+            continue;
+        }
+        if !BWRAP_SYSTEM_DIRS.iter().any(|dir| path.starts_with(dir)) {
+            bind_back("--ro-bind-try", &path.display().to_string());
+        }
+    }
+    for (var, _) in RUNTIME_CACHE_VARS {
+        if let Some(dir) = env_map.get(var) {
+            bind_back("--bind-try", dir);
+        }
+    }
+    if let Some(log) = env_map.get(EGRESS_LOG_ENV) {
+        // The hooks append to the log, which must exist to be mounted.
+        let _ = fs::OpenOptions::new().create(true).append(true).open(log);
+        if let Some(hooks) = paths::egress_hooks_dir() {
+            bind_back("--ro-bind-try", &hooks.display().to_string());
+        }
+        bind_back("--bind-try", log);
+    }
+    if let Some(module) = env_map.get(WASM_MODULE_ENV) {
+        bind_back("--ro-bind-try", module);
+    }
+    if let Some(dir) = env_map
+        .get(SOCKET_ENV)
+        .and_then(|socket| Path::new(socket).parent())
+    {
+        // Socket servers bind their endpoint there.
+        bind_back("--bind-try", &dir.display().to_string());
+    }
+    args
+}
+
+/// Returns what to mount for `command`: the file it resolves to and, for a
+/// binary in a `bin/` directory, the [`INSTALL_DIRS`] next to that `bin/`.
+///
+/// A symlinked command is followed, and both the link and its target count,
+/// so `npx` in a node install brings the install's `bin/` and `lib/` along.
+fn command_mounts(command: &str) -> Vec<PathBuf> {
+    let Some(found) = find_command(command) else {
+        return Vec::new();
+    };
+    let mut mounts = Vec::new();
+    for binary in [Some(found.clone()), fs::canonicalize(&found).ok()]
+        .into_iter()
+        .flatten()
+    {
+        if let Some(prefix) = install_prefix(&binary) {
+            mounts.extend(INSTALL_DIRS.iter().map(|dir| prefix.join(dir)));
+        }
+        mounts.push(binary);
+    }
+    mounts.dedup();
+    mounts
+}
+
+/// Finds the file a command name runs through `PATH`, without following
+/// symlinks.
+fn find_command(command: &str) -> Option<PathBuf> {
+    let path = Path::new(command);
+    if path.is_absolute() {
+        return path.is_file().then(|| path.to_path_buf());
+    }
+    let search = env::var_os("PATH")?;
+    env::split_paths(&search)
+        .flat_map(|dir| candidate_paths(&dir, command))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Returns the install prefix of a binary that lives in a `bin/` directory.
+fn install_prefix(binary: &Path) -> Option<PathBuf> {
+    let dir = binary.parent()?;
+    if dir.file_name().is_some_and(|name| name == "bin") {
+        dir.parent().map(Path::to_path_buf)
+    } else {
+        None
+    }
+}
+
+/// Returns whether `name` resolves to a file on `PATH`.
+pub(crate) fn path_has_binary(name: &str) -> bool {
     let path = match env::var_os("PATH") {
         Some(p) => p,
         None => return false,
@@ -177,25 +488,26 @@ fn candidate_paths(dir: &Path, name: &str) -> Vec<PathBuf> {
     }
 }
 
-fn build_macos_profile(policy: SandboxPolicy, filesystem_permissions: &[String]) -> String {
+fn build_macos_profile(
+    policy: SandboxPolicy,
+    filesystem_permissions: &[String],
+    network_permissions: &[String],
+) -> String {
     let mut write_paths = vec!["/tmp".to_string(), "/private/tmp".to_string()];
     let mut allow_all_writes = false;
 
     for permission in filesystem_permissions {
-        let (mode, path) = match parse_filesystem_permission(permission) {
-            Some(parts) => parts,
-            None => continue,
+        let Some(permission) = parse_filesystem_permission(permission) else {
+            continue;
         };
-        if mode != "write" {
+        if !permission.allows_write() {
             continue;
         }
-        if path == "*" {
+        if permission.is_wildcard() {
             allow_all_writes = true;
             break;
         }
-        if !path.is_empty() {
-            write_paths.push(path.to_string());
-        }
+        write_paths.push(permission.base_path());
     }
 
     write_paths.sort();
@@ -209,8 +521,10 @@ fn build_macos_profile(policy: SandboxPolicy, filesystem_permissions: &[String])
         "(allow file-read*)".to_string(),
     ];
 
-    if !policy.network_deny_all {
-        profile.push("(allow network*)".to_string());
+    match policy.network {
+        SandboxNetwork::Inherit => profile.push("(allow network*)".to_string()),
+        SandboxNetwork::DenyAll => {}
+        SandboxNetwork::Allowlist => profile.extend(allowlist_network_rules(network_permissions)),
     }
 
     if allow_all_writes {
@@ -229,10 +543,54 @@ fn build_macos_profile(policy: SandboxPolicy, filesystem_permissions: &[String])
     profile.join("\n")
 }
 
-fn parse_filesystem_permission(permission: &str) -> Option<(&str, &str)> {
+/// Network rules for `allowlist` mode.
+///
+/// Seatbelt filters remote addresses by port only, so hosts are enforced by
+/// the egress proxy and hooks; the profile keeps everything else to loopback,
+/// DNS, and the declared ports.
+fn allowlist_network_rules(network_permissions: &[String]) -> Vec<String> {
+    let mut ports = Vec::new();
+    let mut any_port = false;
+    for permission in network_permissions {
+        let value = permission.strip_prefix("network:").unwrap_or(permission);
+        let Ok(parsed) = value.parse::<NetworkPermission>() else {
+            continue;
+        };
+        match parsed.port {
+            Some(port) => ports.push(port),
+            None => any_port = true,
+        }
+    }
+    ports.sort_unstable();
+    ports.dedup();
+
+    let mut rules = vec![
+        "(allow network-bind (local ip \"localhost:*\"))".to_string(),
+        "(allow network-inbound (local ip \"localhost:*\"))".to_string(),
+        "(allow network-outbound (remote ip \"localhost:*\"))".to_string(),
+        "(allow network-outbound (literal \"/private/var/run/mDNSResponder\"))".to_string(),
+    ];
+    if any_port {
+        rules.push("(allow network-outbound (remote ip \"*:*\"))".to_string());
+    } else {
+        for port in ports {
+            rules.push(format!("(allow network-outbound (remote ip \"*:{port}\"))"));
+        }
+    }
+    rules
+}
+
+/// Parses a filesystem permission with `~` expanded to the user's home.
+///
+/// Backends only take concrete paths, so globs are later widened to their
+/// literal base directory.
+pub(crate) fn parse_filesystem_permission(permission: &str) -> Option<FsPermission> {
     let value = permission.strip_prefix("filesystem:").unwrap_or(permission);
-    let (mode, path) = value.split_once(':')?;
-    Some((mode, path))
+    let parsed: FsPermission = value.parse().ok()?;
+    match dirs::home_dir() {
+        Some(home) => Some(parsed.expand_home(&home.to_string_lossy())),
+        None => Some(parsed),
+    }
 }
 
 fn escape_sandbox_string(value: &str) -> String {
@@ -242,7 +600,6 @@ fn escape_sandbox_string(value: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::sandbox_policy::SandboxPolicy;
 
     #[test]
     fn sandbox_disabled_keeps_original_command() {
@@ -253,15 +610,18 @@ mod tests {
             &mut env_map,
             SandboxPolicy {
                 enabled: false,
-                network_deny_all: false,
+                network: SandboxNetwork::Inherit,
             },
             &[],
+            &[],
             RuntimeProbes {
                 platform: HostPlatform::Linux,
                 has_setpriv: true,
                 has_sandbox_exec: false,
                 has_landlock_restrict: false,
+                has_bwrap: false,
             },
+            None,
         );
         assert_eq!(cmd, "npx");
         assert_eq!(args, vec!["-y".to_string()]);
@@ -277,15 +637,18 @@ mod tests {
             &mut env_map,
             SandboxPolicy {
                 enabled: true,
-                network_deny_all: false,
+                network: SandboxNetwork::Inherit,
             },
             &[],
+            &[],
             RuntimeProbes {
                 platform: HostPlatform::Linux,
                 has_setpriv: true,
                 has_sandbox_exec: false,
                 has_landlock_restrict: false,
+                has_bwrap: false,
             },
+            None,
         );
         assert_eq!(cmd, "setpriv");
         assert_eq!(
@@ -313,15 +676,18 @@ mod tests {
             &mut env_map,
             SandboxPolicy {
                 enabled: true,
-                network_deny_all: false,
+                network: SandboxNetwork::Inherit,
             },
             &[],
+            &[],
             RuntimeProbes {
                 platform: HostPlatform::Linux,
                 has_setpriv: false,
                 has_sandbox_exec: false,
                 has_landlock_restrict: false,
+                has_bwrap: false,
             },
+            None,
         );
         assert_eq!(cmd, "npx");
         assert_eq!(args, vec!["-y".to_string()]);
@@ -340,18 +706,21 @@ mod tests {
             &mut env_map,
             SandboxPolicy {
                 enabled: true,
-                network_deny_all: false,
+                network: SandboxNetwork::Inherit,
             },
             &[
                 "read:/workspace".to_string(),
                 "write:/workspace".to_string(),
             ],
+            &[],
             RuntimeProbes {
                 platform: HostPlatform::MacOs,
                 has_setpriv: false,
                 has_sandbox_exec: true,
                 has_landlock_restrict: false,
+                has_bwrap: false,
             },
+            None,
         );
 
         assert_eq!(cmd, "sandbox-exec");
@@ -377,19 +746,22 @@ mod tests {
             &mut env_map,
             SandboxPolicy {
                 enabled: true,
-                network_deny_all: false,
+                network: SandboxNetwork::Inherit,
             },
             &[
                 "read:/workspace".to_string(),
                 "write:/tmp".to_string(),
                 "exec:git".to_string(),
             ],
+            &[],
             RuntimeProbes {
                 platform: HostPlatform::Linux,
                 has_setpriv: false,
                 has_sandbox_exec: false,
                 has_landlock_restrict: true,
+                has_bwrap: false,
             },
+            None,
         );
 
         assert_eq!(cmd, "landlock-restrict");
@@ -403,4 +775,230 @@ mod tests {
             Some(&"linux-landlock".to_string())
         );
     }
+
+    #[test]
+    fn macos_allowlist_profile_limits_outbound_ports() {
+        let mut env_map = BTreeMap::new();
+        let (_, args) = apply_sandbox_runtime_with_probes(
+            "npx",
+            &[],
+            &mut env_map,
+            SandboxPolicy {
+                enabled: true,
+                network: SandboxNetwork::Allowlist,
+            },
+            &[],
+            &[
+                "api.github.com:443".to_string(),
+                "network:*.github.com:443".to_string(),
+                "10.0.0.0/8:5432".to_string(),
+            ],
+            RuntimeProbes {
+                platform: HostPlatform::MacOs,
+                has_setpriv: false,
+                has_sandbox_exec: true,
+                has_landlock_restrict: false,
+                has_bwrap: false,
+            },
+            None,
+        );
+
+        assert!(!args[1].contains("(allow network*)"));
+        assert!(args[1].contains("(remote ip \"localhost:*\")"));
+        assert!(args[1].contains("(remote ip \"*:443\")"));
+        assert!(args[1].contains("(remote ip \"*:5432\")"));
+        assert!(!args[1].contains("(remote ip \"*:*\")"));
+        assert_eq!(
+            env_map.get("BERTH_SANDBOX_NETWORK"),
+            Some(&"allowlist".to_string())
+        );
+    }
+
+    #[test]
+    fn linux_bwrap_mounts_declared_paths_in_place_of_landlock() {
+        let mut env_map = BTreeMap::new();
+        let (cmd, args) = apply_sandbox_runtime_with_probes(
+            "/opt/tools/bin/server",
+            &["--stdio".to_string()],
+            &mut env_map,
+            SandboxPolicy {
+                enabled: true,
+                network: SandboxNetwork::Inherit,
+            },
+            &[
+                "read:/workspace".to_string(),
+                "write:/srv/data/**".to_string(),
+            ],
+            &[],
+            RuntimeProbes {
+                platform: HostPlatform::Linux,
+                has_setpriv: false,
+                has_sandbox_exec: false,
+                has_landlock_restrict: true,
+                has_bwrap: true,
+            },
+            None,
+        );
+
+        assert_eq!(cmd, "bwrap");
+        let joined = args.join(" ");
+        assert!(joined.starts_with(
+            "--die-with-parent --unshare-pid --unshare-ipc --new-session --ro-bind-try /usr /usr"
+        ));
+        assert!(joined.contains("--ro-bind-try /run/systemd/resolve /run/systemd/resolve"));
+        assert!(!joined.contains("/run /run"));
+        assert!(joined.contains("--tmpfs /tmp"));
+        assert!(joined.contains("--ro-bind-try /workspace /workspace"));
+        assert!(joined.contains("--bind-try /srv/data /srv/data"));
+        assert!(!joined.contains("--bind / /"));
+        assert!(!joined.contains("--unshare-net"));
+        assert!(joined.ends_with("-- /opt/tools/bin/server --stdio"));
+        assert_eq!(
+            env_map.get(SANDBOX_BACKEND_ENV),
+            Some(&BWRAP_BACKEND.to_string())
+        );
+        assert_eq!(
+            env_map.get(SANDBOX_FS_READ_ENV),
+            Some(&"/workspace".to_string())
+        );
+        assert_eq!(
+            env_map.get(SANDBOX_FS_WRITE_ENV),
+            Some(&"/srv/data".to_string())
+        );
+    }
+
+    #[test]
+    fn linux_bwrap_unshares_the_network_when_nothing_is_allowed() {
+        let probes = RuntimeProbes {
+            platform: HostPlatform::Linux,
+            has_setpriv: false,
+            has_sandbox_exec: false,
+            has_landlock_restrict: false,
+            has_bwrap: true,
+        };
+        let bwrap_args = |network, permissions: &[String]| {
+            let (_, args) = apply_sandbox_runtime_with_probes(
+                "server",
+                &[],
+                &mut BTreeMap::new(),
+                SandboxPolicy {
+                    enabled: true,
+                    network,
+                },
+                &[],
+                permissions,
+                probes,
+                None,
+            );
+            args
+        };
+        let unshares = |args: Vec<String>| args.iter().any(|arg| arg == "--unshare-net");
+
+        assert!(unshares(bwrap_args(SandboxNetwork::DenyAll, &[])));
+        assert!(unshares(bwrap_args(SandboxNetwork::Allowlist, &[])));
+        assert!(!unshares(bwrap_args(
+            SandboxNetwork::Allowlist,
+            &["network:api.github.com:443".to_string()]
+        )));
+        assert!(!unshares(bwrap_args(SandboxNetwork::Inherit, &[])));
+    }
+
+    fn install(root: &Path, binary: &str) -> PathBuf {
+        let path = root.join(binary);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "").unwrap();
+        path
+    }
+
+    fn bind_sources(args: &[String]) -> Vec<PathBuf> {
+        args.windows(2)
+            .filter(|pair| pair[0].contains("bind"))
+            .map(|pair| PathBuf::from(&pair[1]))
+            .collect()
+    }
+
+    #[test]
+    fn command_mounts_bring_install_dirs_but_not_the_prefix() {
+        let tmp = tempfile::tempdir().unwrap();
+        let binary = install(tmp.path(), ".cargo/bin/tool");
+        install(tmp.path(), ".cargo/credentials.toml");
+        let mounts = command_mounts(&binary.display().to_string());
+        assert!(mounts.contains(&binary));
+        assert!(mounts.contains(&tmp.path().join(".cargo/lib")));
+        assert!(!mounts.contains(&tmp.path().join(".cargo")));
+
+        let loose = install(tmp.path(), "tools/server");
+        assert_eq!(command_mounts(&loose.display().to_string()), [loose]);
+    }
+
+    #[test]
+    fn bwrap_keeps_the_berth_home_out_of_the_sandbox() {
+        let tmp = tempfile::tempdir().unwrap();
+        let home = tmp.path().join("berth");
+        let binary = install(&home, "bin/server");
+        install(&home, "credentials/secrets.toml");
+        let protected = [home.clone()];
+
+        let args = bwrap_mounts(
+            &binary.display().to_string(),
+            &BTreeMap::new(),
+            &[],
+            &protected,
+        );
+        let sources = bind_sources(&args);
+        assert!(sources.contains(&binary));
+        assert!(!sources.iter().any(|source| home.starts_with(source)));
+        assert!(!sources.contains(&home.join("bin")));
+
+        // A declared read of a parent directory is shadowed over the Berth home.
+        let read_parent = format!("read:{}", tmp.path().display());
+        let args = bwrap_mounts(
+            &binary.display().to_string(),
+            &BTreeMap::new(),
+            &[read_parent],
+            &protected,
+        );
+        let joined = args.join(" ");
+        let shadow = format!("--tmpfs {}", home.display());
+        let rebind = format!("--ro-bind-try {0} {0}", binary.display());
+        assert!(joined.find(&shadow).unwrap() < joined.find(&rebind).unwrap());
+
+        let inside = format!("read:{}", home.join("credentials").display());
+        let args = bwrap_mounts("server", &BTreeMap::new(), &[inside], &protected);
+        assert!(!bind_sources(&args)
+            .iter()
+            .any(|source| source.starts_with(&home)));
+    }
+
+    #[test]
+    fn bwrap_gives_each_server_its_own_package_caches() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut env_map = BTreeMap::new();
+        let (_, args) = apply_sandbox_runtime_with_probes(
+            "server",
+            &[],
+            &mut env_map,
+            SandboxPolicy {
+                enabled: true,
+                network: SandboxNetwork::Inherit,
+            },
+            &[],
+            &[],
+            RuntimeProbes {
+                platform: HostPlatform::Linux,
+                has_setpriv: false,
+                has_sandbox_exec: false,
+                has_landlock_restrict: false,
+                has_bwrap: true,
+            },
+            Some((BerthLayout::single(tmp.path().to_path_buf()), "github")),
+        );
+        let npm = tmp.path().join("sandbox-cache/github/npm");
+        assert!(npm.is_dir());
+        assert_eq!(env_map["npm_config_cache"], npm.display().to_string());
+        assert!(args
+            .join(" ")
+            .contains(&format!("--bind-try {0} {0}", npm.display())));
+        assert!(!args.iter().any(|arg| arg.ends_with(".npm")));
+    }
 }
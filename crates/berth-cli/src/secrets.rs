@@ -10,6 +10,9 @@ use std::io::Write;
 use std::path::Path;
 use std::process::{Command, Stdio};
 
+use berth_registry::config::InstalledServer;
+use berth_registry::Registry;
+
 use crate::paths;
 
 const SECRET_REF_PREFIX: &str = "secret://";
@@ -53,6 +56,74 @@ pub fn resolve_config_value(_server: &str, _key: &str, raw_value: &str) -> Resul
     Ok(raw_value.to_string())
 }
 
+/// Returns whether a persisted config value is a secret reference.
+pub fn is_secret_ref(value: &str) -> bool {
+    parse_secret_ref(value).is_some()
+}
+
+/// Returns the keys the manifest marks sensitive, in manifest order.
+///
+/// Configs installed before `sensitive_keys` was recorded fall back to the
+/// server's entry in `registry`.
+pub fn sensitive_config_keys(installed: &InstalledServer, registry: &Registry) -> Vec<String> {
+    if !installed.config_meta.sensitive_keys.is_empty() {
+        return installed.config_meta.sensitive_keys.clone();
+    }
+    let Some(meta) = registry.get(&installed.server.name) else {
+        return Vec::new();
+    };
+    meta.config
+        .required
+        .iter()
+        .chain(meta.config.optional.iter())
+        .filter(|field| field.sensitive)
+        .map(|field| field.key.clone())
+        .collect()
+}
+
+/// Returns sensitive keys whose configured values are stored in plaintext.
+pub fn plaintext_sensitive_keys(installed: &InstalledServer, registry: &Registry) -> Vec<String> {
+    sensitive_config_keys(installed, registry)
+        .into_iter()
+        .filter(|key| {
+            installed
+                .config
+                .get(key)
+                .is_some_and(|value| !value.trim().is_empty() && !is_secret_ref(value))
+        })
+        .collect()
+}
+
+/// Describes the secret backend in use, or why the OS keychain is unavailable.
+pub fn backend_status() -> Result<String, String> {
+    if secret_backend() == SecretBackend::File {
+        return Ok(format!("file backend ({SECRET_BACKEND_ENV}=file)"));
+    }
+    #[cfg(target_os = "macos")]
+    {
+        if crate::sandbox_runtime::path_has_binary("security") {
+            return Ok("macOS keychain".to_string());
+        }
+        return Err("the macOS `security` tool is not on PATH".to_string());
+    }
+    #[cfg(target_os = "linux")]
+    {
+        if crate::sandbox_runtime::path_has_binary("secret-tool") {
+            Ok("libsecret keychain via secret-tool".to_string())
+        } else {
+            Err("`secret-tool` (libsecret) is not on PATH".to_string())
+        }
+    }
+    #[cfg(target_os = "windows")]
+    {
+        Ok("Windows DPAPI".to_string())
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+    {
+        Err("OS keychain backend is not supported on this platform".to_string())
+    }
+}
+
 fn secret_backend() -> SecretBackend {
     match std::env::var(SECRET_BACKEND_ENV)
         .ok()
@@ -138,7 +209,16 @@ fn store_keyring_secret(server: &str, key: &str, value: &str) -> Result<(), Stri
         Err("failed to store secret via secret-tool (libsecret keychain unavailable?)".to_string())
     }
 
-    #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+    #[cfg(target_os = "windows")]
+    {
+        let blob = crate::platform::windows::protect(value)?;
+        let path = dpapi_file_path()?;
+        let mut secrets = read_file_secrets_from(&path)?;
+        secrets.secrets.insert(account, blob);
+        write_file_secrets_to(&path, &secrets)
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
     {
         let _ = value;
         Err(
@@ -189,7 +269,17 @@ fn get_keyring_secret(server: &str, key: &str) -> Result<String, String> {
             .map_err(|e| format!("secret-tool output was not valid utf-8: {e}"))
     }
 
-    #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+    #[cfg(target_os = "windows")]
+    {
+        let secrets = read_file_secrets_from(&dpapi_file_path()?)?;
+        let blob = secrets
+            .secrets
+            .get(&account)
+            .ok_or_else(|| format!("secret not found for {server}/{key}"))?;
+        crate::platform::windows::unprotect(blob)
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
     {
         Err(
             "OS keychain backend is not supported on this platform; use BERTH_SECRET_BACKEND=file"
@@ -199,11 +289,17 @@ fn get_keyring_secret(server: &str, key: &str) -> Result<String, String> {
 }
 
 fn secrets_file_path() -> Result<std::path::PathBuf, String> {
-    paths::berth_home()
+    paths::berth_config_dir()
         .map(|p| p.join("credentials").join("secrets.toml"))
         .ok_or("Could not determine home directory.".to_string())
 }
 
+/// DPAPI-protected secret blobs used as the Windows keychain backend.
+#[cfg(target_os = "windows")]
+fn dpapi_file_path() -> Result<std::path::PathBuf, String> {
+    secrets_file_path().map(|p| p.with_file_name("dpapi.toml"))
+}
+
 fn read_file_secrets() -> Result<FileSecrets, String> {
     let path = secrets_file_path()?;
     read_file_secrets_from(&path)
@@ -281,4 +377,32 @@ mod tests {
             Some(&"secret-value".to_string())
         );
     }
+
+    #[test]
+    fn plaintext_sensitive_keys_skip_references_and_empty_values() {
+        let registry = Registry::from_seed();
+        let mut installed = InstalledServer::from_metadata(registry.get("github").unwrap());
+        assert!(plaintext_sensitive_keys(&installed, &registry).is_empty());
+
+        installed
+            .config
+            .insert("token".to_string(), "ghp_plain".to_string());
+        assert_eq!(
+            plaintext_sensitive_keys(&installed, &registry),
+            vec!["token"]
+        );
+
+        installed
+            .config
+            .insert("token".to_string(), secret_ref("github", "token"));
+        assert!(plaintext_sensitive_keys(&installed, &registry).is_empty());
+    }
+
+    #[test]
+    fn sensitive_keys_fall_back_to_seed_registry_for_older_configs() {
+        let registry = Registry::from_seed();
+        let mut installed = InstalledServer::from_metadata(registry.get("github").unwrap());
+        installed.config_meta.sensitive_keys.clear();
+        assert_eq!(sensitive_config_keys(&installed, &registry), vec!["token"]);
+    }
 }
@@ -0,0 +1,99 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! POSIX shell quoting for copy-pasteable command previews.
+
+use std::collections::BTreeMap;
+
+/// Env var prefix for Berth-injected, non-secret launch values.
+const BERTH_ENV_PREFIX: &str = "BERTH_";
+
+/// Quotes one word for a POSIX shell, leaving safe words unquoted.
+pub fn quote(word: &str) -> String {
+    if !word.is_empty()
+        && word
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:@%+,".contains(c))
+    {
+        return word.to_string();
+    }
+    format!("'{}'", word.replace('\'', r"'\''"))
+}
+
+/// Renders a command and its args as one shell-safe line.
+pub fn command_line(command: &str, args: &[String]) -> String {
+    std::iter::once(command)
+        .chain(args.iter().map(String::as_str))
+        .map(quote)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Renders an `env ... command` line that references secret values by variable name.
+///
+/// Berth-injected `BERTH_*` values are inlined; every other value is written as
+/// `"$NAME"` so the preview never contains config or secret values. `clean`
+/// renders `env -i` for launches that do not inherit the parent environment.
+pub fn env_command_line(
+    env: &BTreeMap<String, String>,
+    clean: bool,
+    command: &str,
+    args: &[String],
+) -> String {
+    if env.is_empty() && !clean {
+        return command_line(command, args);
+    }
+    let assignments: Vec<String> = env
+        .iter()
+        .map(|(key, value)| {
+            if key.starts_with(BERTH_ENV_PREFIX) {
+                format!("{key}={}", quote(value))
+            } else {
+                format!("{key}=\"${key}\"")
+            }
+        })
+        .collect();
+    let prefix = if clean { "env -i" } else { "env" };
+    std::iter::once(prefix.to_string())
+        .chain(assignments)
+        .chain(std::iter::once(command_line(command, args)))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quote_leaves_safe_words_and_wraps_others() {
+        assert_eq!(quote("npx"), "npx");
+        assert_eq!(quote("@scope/pkg"), "@scope/pkg");
+        assert_eq!(quote("sleep 60"), "'sleep 60'");
+        assert_eq!(quote(""), "''");
+        assert_eq!(quote("it's"), r"'it'\''s'");
+        assert_eq!(quote("$HOME"), "'$HOME'");
+    }
+
+    #[test]
+    fn command_line_quotes_each_word() {
+        let args = vec!["-c".to_string(), "sleep 60".to_string()];
+        assert_eq!(command_line("sh", &args), "sh -c 'sleep 60'");
+    }
+
+    #[test]
+    fn env_command_line_hides_values_except_berth_keys() {
+        let env = BTreeMap::from([
+            ("GITHUB_TOKEN".to_string(), "secret".to_string()),
+            ("BERTH_SANDBOX_MODE".to_string(), "basic".to_string()),
+        ]);
+        let line = env_command_line(&env, false, "npx", &["-y".to_string()]);
+        assert_eq!(
+            line,
+            "env BERTH_SANDBOX_MODE=basic GITHUB_TOKEN=\"$GITHUB_TOKEN\" npx -y"
+        );
+        assert!(!line.contains("secret"));
+        let isolated = env_command_line(&env, true, "npx", &[]);
+        assert!(isolated.starts_with("env -i BERTH_SANDBOX_MODE=basic "));
+    }
+}
@@ -0,0 +1,100 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Launch wiring for servers declaring `unix-socket` or `named-pipe` transport.
+//!
+//! Every launch resolves the server's endpoint, passes it in `BERTH_SOCKET`
+//! and in place of `{socket}` in the arguments. `berth proxy` and `berth wrap`
+//! additionally launch through the hidden `__socket-bridge` command, which
+//! connects to the endpoint (starting the server first when nothing listens
+//! there yet) and relays MCP between its stdio and the socket, so the proxy
+//! itself keeps talking stdio.
+
+use std::collections::BTreeMap;
+
+use berth_registry::config::RuntimeInfo;
+use berth_runtime::{Endpoint, SOCKET_ENV, SOCKET_PLACEHOLDER};
+use colored::Colorize;
+
+use crate::paths;
+
+/// Hidden subcommand that relays stdio to a socket-transport server.
+pub const BRIDGE_COMMAND: &str = "__socket-bridge";
+
+/// Resolves the endpoint of a socket-transport server and hands it to the
+/// launch env and `args`; stdio servers are left untouched.
+pub fn apply_socket_endpoint(
+    server: &str,
+    runtime: &RuntimeInfo,
+    env: &mut BTreeMap<String, String>,
+    args: &mut [String],
+) -> Result<Option<Endpoint>, String> {
+    let state = paths::berth_home().ok_or("Could not determine home directory.")?;
+    let endpoint = Endpoint::resolve(
+        runtime.transport.trim(),
+        runtime.socket.as_deref(),
+        server,
+        &state,
+    )
+    .map_err(|e| format!("Invalid endpoint for {}: {e}.", server.cyan()))?;
+    let Some(endpoint) = endpoint else {
+        return Ok(None);
+    };
+    endpoint.prepare().map_err(|e| {
+        format!(
+            "Failed to create socket directory for {}: {e}",
+            server.cyan()
+        )
+    })?;
+    let value = endpoint.to_string();
+    for arg in args.iter_mut() {
+        *arg = arg.replace(SOCKET_PLACEHOLDER, &value);
+    }
+    env.insert(SOCKET_ENV.to_string(), value);
+    Ok(Some(endpoint))
+}
+
+/// Wraps a launch command so its MCP traffic is relayed over stdio.
+pub fn bridge_command(
+    endpoint: &Endpoint,
+    command: String,
+    args: Vec<String>,
+) -> Result<(String, Vec<String>), String> {
+    let exe = std::env::current_exe().map_err(|e| format!("failed to locate current exe: {e}"))?;
+    let mut bridged = vec![
+        BRIDGE_COMMAND.to_string(),
+        endpoint.transport().to_string(),
+        endpoint.to_string(),
+        "--".to_string(),
+        command,
+    ];
+    bridged.extend(args);
+    Ok((exe.display().to_string(), bridged))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bridges_through_the_hidden_command() {
+        let endpoint = Endpoint::UnixSocket("/run/berth/db.sock".into());
+        let (command, args) =
+            bridge_command(&endpoint, "node".to_string(), vec!["server.js".to_string()]).unwrap();
+        assert_eq!(
+            command,
+            std::env::current_exe().unwrap().display().to_string()
+        );
+        assert_eq!(
+            args,
+            [
+                BRIDGE_COMMAND,
+                "unix-socket",
+                "/run/berth/db.sock",
+                "--",
+                "node",
+                "server.js"
+            ]
+        );
+    }
+}
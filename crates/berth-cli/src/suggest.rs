@@ -0,0 +1,105 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! "Did you mean" suggestions for mistyped command and server names.
+
+use berth_registry::Registry;
+use colored::Colorize;
+
+/// Maximum number of suggestions offered for one unknown name.
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Returns the candidates closest to `input`, best match first.
+///
+/// Matching is case-insensitive and tolerates roughly one edit per three
+/// characters, counting a swap of adjacent characters as a single edit.
+pub fn similar<'a, I>(input: &str, candidates: I) -> Vec<&'a str>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let input = input.to_lowercase();
+    let max_distance = (input.chars().count() / 3).max(1);
+    let mut scored = candidates
+        .into_iter()
+        .filter_map(|candidate| {
+            let distance = edit_distance(&input, &candidate.to_lowercase());
+            (distance <= max_distance).then_some((distance, candidate))
+        })
+        .collect::<Vec<_>>();
+    scored.sort();
+    scored.dedup();
+    scored
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, candidate)| candidate)
+        .collect()
+}
+
+/// Prints registry servers whose names are close to an unknown `name`.
+pub fn print_server_suggestions(name: &str, registry: &Registry) {
+    let names = registry.list_all().iter().map(|s| s.name.as_str());
+    let matches = similar(name, names);
+    if matches.is_empty() {
+        return;
+    }
+    let rendered = matches
+        .iter()
+        .map(|m| m.cyan().to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    eprintln!("  Did you mean {rendered}?");
+}
+
+/// Optimal string alignment distance between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a = a.chars().collect::<Vec<_>>();
+    let b = b.chars().collect::<Vec<_>>();
+    let mut rows = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut best = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = best;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edit_distance_counts_transpositions_once() {
+        assert_eq!(edit_distance("isntall", "install"), 1);
+        assert_eq!(edit_distance("gihub", "github"), 1);
+        assert_eq!(edit_distance("start", "start"), 0);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+
+    #[test]
+    fn similar_ranks_nearest_candidates_first() {
+        let commands = ["install", "uninstall", "list", "info"];
+        assert_eq!(similar("isntall", commands), vec!["install"]);
+        assert_eq!(similar("INSTAL", commands), vec!["install"]);
+        assert!(similar("zzzz", commands).is_empty());
+    }
+
+    #[test]
+    fn similar_finds_registry_servers() {
+        let registry = Registry::from_seed();
+        let names = registry.list_all().iter().map(|s| s.name.as_str());
+        assert_eq!(similar("githb", names).first(), Some(&"github"));
+    }
+}
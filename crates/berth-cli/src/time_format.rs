@@ -0,0 +1,100 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Millisecond timestamps and RFC3339 rendering for CLI output.
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+
+pub use berth_common::time::{format_rfc3339, now_epoch_ms};
+
+/// Formats timestamps in UTC or the local timezone, caching offsets per hour.
+pub struct TimestampFormatter {
+    utc: bool,
+    offsets: RefCell<BTreeMap<u64, i32>>,
+}
+
+impl TimestampFormatter {
+    /// Creates a formatter; `utc` skips local offset lookup entirely.
+    pub fn new(utc: bool) -> Self {
+        TimestampFormatter {
+            utc,
+            offsets: RefCell::new(BTreeMap::new()),
+        }
+    }
+
+    /// Renders one timestamp as RFC3339.
+    pub fn format(&self, epoch_ms: u64) -> String {
+        if self.utc {
+            return format_rfc3339(epoch_ms, 0);
+        }
+        let secs = epoch_ms / 1_000;
+        let offset = *self
+            .offsets
+            .borrow_mut()
+            .entry(secs / 3_600)
+            .or_insert_with(|| local_offset_secs(secs).unwrap_or(0));
+        format_rfc3339(epoch_ms, offset)
+    }
+}
+
+/// Looks up the local UTC offset at an instant using the system `date` tool.
+#[cfg(unix)]
+fn local_offset_secs(epoch_secs: u64) -> Option<i32> {
+    use std::process::Command;
+
+    let at = epoch_secs.to_string();
+    // GNU date takes `-d @<secs>`; BSD/macOS date takes `-r <secs>`.
+    let output = Command::new("date")
+        .args(["-d", &format!("@{at}"), "+%z"])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .or_else(|| {
+            Command::new("date")
+                .args(["-r", &at, "+%z"])
+                .output()
+                .ok()
+                .filter(|o| o.status.success())
+        })?;
+    parse_utc_offset(String::from_utf8_lossy(&output.stdout).trim())
+}
+
+#[cfg(not(unix))]
+fn local_offset_secs(_epoch_secs: u64) -> Option<i32> {
+    None
+}
+
+/// Parses `date +%z` output like `+0200` or `-0530` into seconds.
+fn parse_utc_offset(raw: &str) -> Option<i32> {
+    let (sign, digits) = match raw.split_at_checked(1)? {
+        ("+", rest) => (1, rest),
+        ("-", rest) => (-1, rest),
+        _ => return None,
+    };
+    if digits.len() != 4 || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let hours: i32 = digits[..2].parse().ok()?;
+    let minutes: i32 = digits[2..].parse().ok()?;
+    Some(sign * (hours * 3_600 + minutes * 60))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_utc_offset_reads_date_output() {
+        assert_eq!(parse_utc_offset("+0200"), Some(7_200));
+        assert_eq!(parse_utc_offset("-0530"), Some(-19_800));
+        assert_eq!(parse_utc_offset("0200"), None);
+        assert_eq!(parse_utc_offset(""), None);
+    }
+
+    #[test]
+    fn utc_formatter_ignores_local_timezone() {
+        let formatter = TimestampFormatter::new(true);
+        assert_eq!(formatter.format(1_500), "1970-01-01T00:00:01.500Z");
+    }
+}
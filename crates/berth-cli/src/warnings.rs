@@ -0,0 +1,263 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Warnings that would otherwise repeat on every invocation.
+//!
+//! Each warning has an ID from [`CATALOG`] and an optional subject, such as
+//! the server it is about. A warning prints at most once per interval for the
+//! same ID and subject, and not at all while its ID is snoozed with
+//! `berth warnings snooze <id> <duration>`. When it was last shown, how often
+//! it was held back since, and snoozes are kept in `warnings.toml` in the state
+//! directory; a home that cannot take the write still gets every warning.
+
+use berth_common::atomic_write;
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::paths;
+use crate::time_format::now_epoch_ms;
+
+/// File in the state directory that records shown and snoozed warnings.
+pub const WARNINGS_FILE: &str = "warnings.toml";
+
+const HOUR_MS: u64 = 3_600_000;
+const DAY_MS: u64 = 24 * HOUR_MS;
+
+/// A kind of warning Berth rate-limits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WarningKind {
+    pub id: &'static str,
+    pub description: &'static str,
+    /// Shortest time between two prints for the same subject.
+    pub interval_ms: u64,
+}
+
+pub const PLAINTEXT_SECRETS: &str = "plaintext-secrets";
+pub const UNDECLARED_NETWORK: &str = "undeclared-network";
+pub const REGISTRY_CACHE_AGE: &str = "registry-cache-age";
+pub const UNENFORCED_EGRESS: &str = "unenforced-egress";
+
+/// Every warning ID `berth warnings` knows.
+pub const CATALOG: &[WarningKind] = &[
+    WarningKind {
+        id: PLAINTEXT_SECRETS,
+        description: "A server stores sensitive config in plaintext",
+        interval_ms: DAY_MS,
+    },
+    WarningKind {
+        id: REGISTRY_CACHE_AGE,
+        description: "The registry index comes from an old cache",
+        interval_ms: DAY_MS,
+    },
+    WarningKind {
+        id: UNDECLARED_NETWORK,
+        description: "A server has network grants its manifest does not declare",
+        interval_ms: DAY_MS,
+    },
+    WarningKind {
+        id: UNENFORCED_EGRESS,
+        description: "A server's egress allow-list holds only if it honors the proxy variables",
+        interval_ms: DAY_MS,
+    },
+];
+
+/// Contents of `warnings.toml`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WarningsFile {
+    /// Snooze deadlines by warning ID.
+    #[serde(default)]
+    pub snoozed: BTreeMap<String, u64>,
+    /// Bookkeeping by `id` or `id:subject`.
+    #[serde(default)]
+    pub shown: BTreeMap<String, ShownWarning>,
+}
+
+/// When a warning last printed for one subject.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ShownWarning {
+    pub last_shown_epoch_ms: u64,
+    /// Times it was held back since it last printed.
+    #[serde(default)]
+    pub suppressed: u64,
+}
+
+/// Returns the catalog entry for `id`.
+pub fn kind(id: &str) -> Option<&'static WarningKind> {
+    CATALOG.iter().find(|kind| kind.id == id)
+}
+
+/// Returns the path of `warnings.toml`.
+pub fn warnings_path() -> Option<PathBuf> {
+    paths::berth_home().map(|state| state.join(WARNINGS_FILE))
+}
+
+/// Reads the warnings file at `path`; a missing file records nothing.
+pub fn load(path: &Path) -> Result<WarningsFile, String> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(WarningsFile::default()),
+        Err(e) => return Err(format!("Failed to read {}: {e}", path.display())),
+    };
+    toml::from_str(&content).map_err(|e| format!("Failed to parse {}: {e}", path.display()))
+}
+
+fn save(path: &Path, file: &WarningsFile) -> Result<(), String> {
+    let content = toml::to_string(file).map_err(|e| format!("Failed to encode warnings: {e}"))?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+    }
+    atomic_write(path, content).map_err(|e| format!("Failed to write {}: {e}", path.display()))
+}
+
+/// Decides whether a warning prints at `now`, updating its bookkeeping.
+pub fn decide(file: &mut WarningsFile, id: &str, subject: Option<&str>, now: u64) -> bool {
+    if file.snoozed.get(id).is_some_and(|until| *until > now) {
+        return false;
+    }
+    file.snoozed.remove(id);
+    let interval_ms = kind(id).map_or(0, |kind| kind.interval_ms);
+    let key = match subject {
+        Some(subject) => format!("{id}:{subject}"),
+        None => id.to_string(),
+    };
+    let shown = file.shown.entry(key).or_default();
+    if shown.last_shown_epoch_ms > 0 && now < shown.last_shown_epoch_ms + interval_ms {
+        shown.suppressed += 1;
+        return false;
+    }
+    *shown = ShownWarning {
+        last_shown_epoch_ms: now,
+        suppressed: 0,
+    };
+    true
+}
+
+/// Returns whether a warning should print now and records that it did.
+///
+/// For callers that print through their own channel, such as `berth start`'s
+/// progress output; others use [`emit`].
+pub fn due(id: &str, subject: Option<&str>) -> bool {
+    let Some(path) = warnings_path() else {
+        return true;
+    };
+    let Ok(mut file) = load(&path) else {
+        return true;
+    };
+    let show = decide(&mut file, id, subject, now_epoch_ms());
+    let _ = save(&path, &file);
+    show
+}
+
+/// Prints a warning to stderr unless it is rate-limited or snoozed.
+pub fn emit(id: &str, subject: Option<&str>, message: &str) {
+    if due(id, subject) {
+        eprintln!("{} {message} {}", "!".yellow().bold(), snooze_hint(id));
+    }
+}
+
+/// Tells how to silence a warning, for appending to it.
+pub fn snooze_hint(id: &str) -> String {
+    format!("(silence with `berth warnings snooze {id} 7d`)")
+        .dimmed()
+        .to_string()
+}
+
+/// Snoozes `id` until `until`.
+pub fn snooze(path: &Path, id: &str, until: u64) -> Result<(), String> {
+    let mut file = load(path)?;
+    file.snoozed.insert(id.to_string(), until);
+    save(path, &file)
+}
+
+/// Lifts a snooze; returns whether there was one.
+pub fn unsnooze(path: &Path, id: &str) -> Result<bool, String> {
+    let mut file = load(path)?;
+    if file.snoozed.remove(id).is_none() {
+        return Ok(false);
+    }
+    save(path, &file)?;
+    Ok(true)
+}
+
+/// Parses a snooze length such as `30m`, `12h`, `7d`, or `2w` into milliseconds.
+pub fn parse_duration(raw: &str) -> Result<u64, String> {
+    let invalid = || format!("Invalid duration `{raw}`. Use <number><m|h|d|w>, e.g. 7d.");
+    let unit_at = raw.len().checked_sub(1).ok_or_else(invalid)?;
+    let (number, unit) = raw.split_at(unit_at);
+    let number: u64 = number.parse().map_err(|_| invalid())?;
+    let unit_ms = match unit {
+        "m" => 60_000,
+        "h" => HOUR_MS,
+        "d" => DAY_MS,
+        "w" => 7 * DAY_MS,
+        _ => return Err(invalid()),
+    };
+    if number == 0 {
+        return Err(invalid());
+    }
+    Ok(number.saturating_mul(unit_ms))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rate_limits_per_subject_and_honours_snoozes() {
+        let mut file = WarningsFile::default();
+        let start = 1_000;
+        assert!(decide(&mut file, PLAINTEXT_SECRETS, Some("github"), start));
+        assert!(!decide(
+            &mut file,
+            PLAINTEXT_SECRETS,
+            Some("github"),
+            start + HOUR_MS
+        ));
+        assert!(decide(
+            &mut file,
+            PLAINTEXT_SECRETS,
+            Some("slack"),
+            start + HOUR_MS
+        ));
+        assert_eq!(file.shown["plaintext-secrets:github"].suppressed, 1);
+        assert!(decide(
+            &mut file,
+            PLAINTEXT_SECRETS,
+            Some("github"),
+            start + DAY_MS
+        ));
+        assert_eq!(file.shown["plaintext-secrets:github"].suppressed, 0);
+
+        file.snoozed
+            .insert(PLAINTEXT_SECRETS.to_string(), start + 10 * DAY_MS);
+        assert!(!decide(
+            &mut file,
+            PLAINTEXT_SECRETS,
+            Some("slack"),
+            start + 5 * DAY_MS
+        ));
+        assert!(decide(
+            &mut file,
+            PLAINTEXT_SECRETS,
+            Some("slack"),
+            start + 10 * DAY_MS
+        ));
+        assert!(file.snoozed.is_empty());
+    }
+
+    #[test]
+    fn parses_snooze_lengths() {
+        assert_eq!(parse_duration("7d"), Ok(7 * DAY_MS));
+        assert_eq!(parse_duration("30m"), Ok(30 * 60_000));
+        assert_eq!(parse_duration("2w"), Ok(14 * DAY_MS));
+        assert!(parse_duration("d").is_err());
+        assert!(parse_duration("0h").is_err());
+        assert!(parse_duration("7y").is_err());
+        assert!(parse_duration("").is_err());
+    }
+}
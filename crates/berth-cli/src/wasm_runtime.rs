@@ -0,0 +1,180 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Helpers for `wasm` runtimes: WASI modules run inside `wasmtime`.
+//!
+//! The installed module path is the runtime command. Launch turns it into a
+//! `wasmtime run` invocation whose capabilities come from the effective
+//! permissions: directories granted for writing are preopened, env vars are
+//! forwarded by name, and WASI sockets are only enabled when network access
+//! is granted. Everything else on the host is out of reach of the guest.
+//!
+//! `wasmtime` preopens are always writable, so read-only grants are not
+//! preopened at all. Its sockets cannot be narrowed to hosts either: any
+//! network grant gives the guest the whole network, and the egress allow-list
+//! does not reach it.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+use berth_registry::config::{InstalledServer, RuntimeInfo};
+use berth_registry::permissions::FsMode;
+
+use crate::sandbox_runtime::parse_filesystem_permission;
+
+/// Runtime type of WASI modules run by `wasmtime`.
+pub const WASM_RUNTIME: &str = "wasm";
+/// Executable that hosts `wasm` runtimes.
+pub const WASMTIME: &str = "wasmtime";
+/// Env var naming the module a `wasmtime` launch runs, so sandboxes can mount it.
+pub const WASM_MODULE_ENV: &str = "BERTH_WASM_MODULE";
+
+/// Returns whether an installed server is a WASI module.
+pub fn is_wasm(installed: &InstalledServer) -> bool {
+    installed.runtime.runtime_type == WASM_RUNTIME
+}
+
+/// Returns the installed file name of a server's module.
+pub fn module_file_name(server: &str) -> String {
+    format!("{server}.wasm")
+}
+
+/// Wraps a `wasm` runtime's module in `wasmtime run`; other runtimes are
+/// returned unchanged.
+///
+/// Only existing directories granted for writing are preopened, at the same
+/// path inside the guest. `--env NAME` makes `wasmtime` copy values from its own env, so
+/// config secrets never appear in the process arguments.
+pub fn apply_wasm_runtime(
+    runtime: &RuntimeInfo,
+    args: &[String],
+    env_map: &mut BTreeMap<String, String>,
+    filesystem_permissions: &[String],
+    network_permissions: &[String],
+) -> (String, Vec<String>) {
+    if runtime.runtime_type != WASM_RUNTIME {
+        return (runtime.command.clone(), args.to_vec());
+    }
+
+    let mut wrapped = vec!["run".to_string()];
+    for key in env_map.keys() {
+        wrapped.extend(["--env".to_string(), key.clone()]);
+    }
+    let dirs: BTreeSet<String> = filesystem_permissions
+        .iter()
+        .filter_map(|permission| parse_filesystem_permission(permission))
+        .filter(|permission| permission.mode == FsMode::Write)
+        .map(|permission| {
+            if permission.is_wildcard() {
+                "/".to_string()
+            } else {
+                permission.base_path()
+            }
+        })
+        .filter(|dir| Path::new(dir).is_dir())
+        .collect();
+    for dir in dirs {
+        wrapped.extend(["--dir".to_string(), format!("{dir}::{dir}")]);
+    }
+    // Widens every network grant to the whole network; see the module docs.
+    if !network_permissions.is_empty() {
+        for option in ["inherit-network=y", "allow-ip-name-lookup=y"] {
+            wrapped.extend(["-S".to_string(), option.to_string()]);
+        }
+    }
+    wrapped.push(runtime.command.clone());
+    wrapped.extend(args.iter().cloned());
+    env_map.insert(WASM_MODULE_ENV.to_string(), runtime.command.clone());
+    (WASMTIME.to_string(), wrapped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn runtime(runtime_type: &str) -> RuntimeInfo {
+        RuntimeInfo {
+            runtime_type: runtime_type.to_string(),
+            command: "/home/me/.berth/bin/files.wasm".to_string(),
+            args: Vec::new(),
+            transport: "stdio".to_string(),
+            syscalls: Vec::new(),
+            health_check: None,
+            schedule: None,
+            limits: None,
+            socket: None,
+        }
+    }
+
+    #[test]
+    fn leaves_other_runtimes_alone() {
+        let mut env = BTreeMap::new();
+        let (command, args) = apply_wasm_runtime(
+            &runtime("binary"),
+            &["--verbose".to_string()],
+            &mut env,
+            &["filesystem:read:/".to_string()],
+            &[],
+        );
+        assert_eq!(command, "/home/me/.berth/bin/files.wasm");
+        assert_eq!(args, ["--verbose"]);
+        assert!(env.is_empty());
+    }
+
+    #[test]
+    fn preopens_writable_directories_and_forwards_env_by_name() {
+        let tmp = tempfile::tempdir().unwrap();
+        let data = tmp.path().display().to_string();
+        let docs = tmp.path().join("docs");
+        std::fs::create_dir(&docs).unwrap();
+        let docs = docs.display().to_string();
+        let mut env = BTreeMap::from([("API_TOKEN".to_string(), "secret".to_string())]);
+        let (command, args) = apply_wasm_runtime(
+            &runtime(WASM_RUNTIME),
+            &["--root".to_string(), data.clone()],
+            &mut env,
+            &[
+                format!("filesystem:write:{data}"),
+                format!("filesystem:read:{docs}"),
+                "filesystem:write:/does/not/exist".to_string(),
+            ],
+            &[],
+        );
+        assert_eq!(command, WASMTIME);
+        assert_eq!(
+            args,
+            [
+                "run".to_string(),
+                "--env".to_string(),
+                "API_TOKEN".to_string(),
+                "--dir".to_string(),
+                format!("{data}::{data}"),
+                "/home/me/.berth/bin/files.wasm".to_string(),
+                "--root".to_string(),
+                data,
+            ]
+        );
+        assert!(!args.iter().any(|arg| arg.contains("secret")));
+        assert!(!args.iter().any(|arg| arg.contains(&docs)));
+        assert_eq!(
+            env.get(WASM_MODULE_ENV).map(String::as_str),
+            Some("/home/me/.berth/bin/files.wasm")
+        );
+    }
+
+    #[test]
+    fn enables_sockets_only_with_network_grants() {
+        let mut env = BTreeMap::new();
+        let (_, offline) = apply_wasm_runtime(&runtime(WASM_RUNTIME), &[], &mut env, &[], &[]);
+        assert!(!offline.iter().any(|arg| arg == "-S"));
+
+        let (_, online) = apply_wasm_runtime(
+            &runtime(WASM_RUNTIME),
+            &[],
+            &mut env,
+            &[],
+            &["api.github.com:443".to_string()],
+        );
+        assert!(online.windows(2).any(|w| w == ["-S", "inherit-network=y"]));
+    }
+}
@@ -0,0 +1,299 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Trust decisions for Berth homes that live inside project checkouts.
+//!
+//! A home selected with `--home`, a profile, or `BERTH_HOME` that sits in a
+//! version-controlled checkout (such as a cloned repo's `.berth/`) holds
+//! server commands and plugins written by whoever committed them. Berth only
+//! launches servers or runs plugins from such a workspace once the user has
+//! trusted it. Decisions are kept in `trust.toml` of the user's own layout,
+//! which the workspace cannot supply, keyed by the checkout root.
+
+use berth_common::atomic_write_private;
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+
+use crate::paths::{berth_layout, user_berth_layout};
+use crate::time_format::now_epoch_ms;
+
+/// File in the user's config directory that records trust decisions.
+pub const TRUST_FILE: &str = "trust.toml";
+
+/// Entries that mark the root of a checkout.
+const VCS_MARKERS: [&str; 3] = [".git", ".hg", ".svn"];
+
+/// Contents of `trust.toml`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrustFile {
+    #[serde(default)]
+    pub workspaces: BTreeMap<String, TrustDecision>,
+}
+
+/// The decision recorded for one workspace.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct TrustDecision {
+    pub trusted: bool,
+    pub decided_at_epoch_ms: u64,
+}
+
+/// Returns the path of `trust.toml`.
+pub fn trust_path() -> Option<PathBuf> {
+    user_berth_layout().map(|layout| layout.config.join(TRUST_FILE))
+}
+
+/// Reads the trust file at `path`; a missing file records no decisions.
+pub fn load(path: &Path) -> Result<TrustFile, String> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(TrustFile::default()),
+        Err(e) => return Err(format!("Failed to read {}: {e}", path.display())),
+    };
+    toml::from_str(&content).map_err(|e| format!("Failed to parse {}: {e}", path.display()))
+}
+
+fn save(path: &Path, file: &TrustFile) -> Result<(), String> {
+    let content =
+        toml::to_string(file).map_err(|e| format!("Failed to encode trust decisions: {e}"))?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+    }
+    atomic_write_private(path, content)
+        .map_err(|e| format!("Failed to write {}: {e}", path.display()))
+}
+
+/// Records a decision for `workspace`.
+pub fn decide(path: &Path, workspace: &Path, trusted: bool) -> Result<(), String> {
+    let mut file = load(path)?;
+    file.workspaces.insert(
+        workspace.display().to_string(),
+        TrustDecision {
+            trusted,
+            decided_at_epoch_ms: now_epoch_ms(),
+        },
+    );
+    save(path, &file)
+}
+
+/// Forgets the decision for `workspace`; returns whether there was one.
+pub fn revoke(path: &Path, workspace: &Path) -> Result<bool, String> {
+    let mut file = load(path)?;
+    if file
+        .workspaces
+        .remove(&workspace.display().to_string())
+        .is_none()
+    {
+        return Ok(false);
+    }
+    save(path, &file)?;
+    Ok(true)
+}
+
+/// Returns the checkout root the active Berth home lives in, or `None` for
+/// the user's own home and homes outside any checkout.
+pub fn current_workspace() -> Option<PathBuf> {
+    let layout = berth_layout()?;
+    if Some(&layout) == user_berth_layout().as_ref() {
+        return None;
+    }
+    workspace_root(&layout.config, dirs::home_dir().as_deref())
+}
+
+/// Finds the closest checkout containing `path`.
+///
+/// The search stops below `user_home`, so a dotfiles repository in the home
+/// directory does not turn every Berth home into a workspace.
+pub fn workspace_root(path: &Path, user_home: Option<&Path>) -> Option<PathBuf> {
+    let path = canonical(path);
+    let user_home = user_home.map(canonical);
+    for dir in path.ancestors() {
+        if user_home.as_deref() == Some(dir) {
+            return None;
+        }
+        if VCS_MARKERS.iter().any(|marker| dir.join(marker).exists()) {
+            return Some(dir.to_path_buf());
+        }
+    }
+    None
+}
+
+/// Resolves the workspace `berth trust` acts on: the checkout containing
+/// `path`, or the one of the active home or current directory.
+pub fn resolve_workspace(path: Option<&str>) -> Result<PathBuf, String> {
+    let user_home = dirs::home_dir();
+    if let Some(path) = path {
+        let path = Path::new(path);
+        return Ok(workspace_root(path, user_home.as_deref()).unwrap_or_else(|| canonical(path)));
+    }
+    current_workspace()
+        .or_else(|| {
+            let cwd = std::env::current_dir().ok()?;
+            workspace_root(&cwd, user_home.as_deref())
+        })
+        .ok_or_else(|| {
+            "Not inside a workspace. Name one, e.g. `berth trust grant ~/src/repo`.".to_string()
+        })
+}
+
+/// Resolves symlinks in the longest existing prefix of `path`.
+fn canonical(path: &Path) -> PathBuf {
+    let mut missing = Vec::new();
+    let mut existing = path;
+    loop {
+        if let Ok(resolved) = fs::canonicalize(existing) {
+            return missing
+                .iter()
+                .rev()
+                .fold(resolved, |dir: PathBuf, part| dir.join(part));
+        }
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                missing.push(name.to_os_string());
+                existing = parent;
+            }
+            _ => return path.to_path_buf(),
+        }
+    }
+}
+
+/// Checks that the active home may launch servers: it is no workspace, or a
+/// trusted one.
+pub fn check() -> Result<(), String> {
+    let Some(workspace) = current_workspace() else {
+        return Ok(());
+    };
+    let path = trust_path().ok_or("Could not determine home directory.")?;
+    let decision = load(&path)?
+        .workspaces
+        .remove(&workspace.display().to_string());
+    match decision {
+        Some(decision) if decision.trusted => Ok(()),
+        Some(_) => Err(format!(
+            "Workspace {} is not trusted, so its servers are not launched. Run `berth trust grant {}` to trust it.",
+            workspace.display(),
+            workspace.display()
+        )),
+        None => Err(format!(
+            "Workspace {} has not been trusted yet. Its Berth home defines commands from that checkout; run `berth trust grant {}` if you trust them.",
+            workspace.display(),
+            workspace.display()
+        )),
+    }
+}
+
+/// Returns whether the active home may launch servers and run plugins.
+pub fn is_trusted() -> bool {
+    check().is_ok()
+}
+
+/// Asks for a trust decision on a terminal when the active workspace has
+/// none, then exits unless the workspace is trusted.
+///
+/// Commands whose stdin is not a terminal, such as `berth proxy` run by an MCP
+/// client, are refused without asking.
+pub fn ensure() {
+    let Some(workspace) = current_workspace() else {
+        return;
+    };
+    let Some(path) = trust_path() else {
+        return;
+    };
+    let undecided = load(&path)
+        .map(|file| {
+            !file
+                .workspaces
+                .contains_key(&workspace.display().to_string())
+        })
+        .unwrap_or(false);
+    if undecided && io::stdin().is_terminal() && io::stderr().is_terminal() {
+        if let Some(trusted) = prompt(&workspace) {
+            if let Err(msg) = decide(&path, &workspace, trusted) {
+                eprintln!("{} {msg}", "✗".red().bold());
+                std::process::exit(1);
+            }
+        }
+    }
+    if let Err(msg) = check() {
+        eprintln!("{} {msg}", "✗".red().bold());
+        std::process::exit(1);
+    }
+}
+
+fn prompt(workspace: &Path) -> Option<bool> {
+    let home = berth_layout().map(|layout| layout.config.display().to_string());
+    eprintln!(
+        "{} The Berth home {} belongs to the workspace {}.",
+        "!".yellow().bold(),
+        home.unwrap_or_default(),
+        workspace.display().to_string().bold()
+    );
+    eprintln!("  Its servers and plugins run commands defined by whoever wrote that checkout.");
+    eprint!("  Trust this workspace? [y/N] ");
+    io::stderr().flush().ok()?;
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer).ok()?;
+    Some(matches!(
+        answer.trim().to_ascii_lowercase().as_str(),
+        "y" | "yes"
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_the_closest_checkout_below_the_user_home() {
+        let tmp = tempfile::tempdir().unwrap();
+        let user_home = tmp.path().join("me");
+        let repo = user_home.join("src/repo");
+        fs::create_dir_all(repo.join(".git")).unwrap();
+        fs::create_dir_all(user_home.join(".git")).unwrap();
+        let root = canonical(&repo);
+
+        assert_eq!(
+            workspace_root(&repo.join(".berth"), Some(&user_home)),
+            Some(root)
+        );
+        assert_eq!(
+            workspace_root(&user_home.join("homes/exp"), Some(&user_home)),
+            None
+        );
+        assert_eq!(
+            workspace_root(&tmp.path().join("elsewhere"), Some(&user_home)),
+            None
+        );
+    }
+
+    #[test]
+    fn records_and_revokes_decisions() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join(TRUST_FILE);
+        let workspace = Path::new("/src/repo");
+
+        decide(&path, workspace, false).unwrap();
+        assert!(!load(&path).unwrap().workspaces["/src/repo"].trusted);
+        decide(&path, workspace, true).unwrap();
+        assert!(load(&path).unwrap().workspaces["/src/repo"].trusted);
+
+        assert!(revoke(&path, workspace).unwrap());
+        assert!(!revoke(&path, workspace).unwrap());
+        assert!(load(&path).unwrap().workspaces.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn trust_file_is_private() {
+        use std::os::unix::fs::PermissionsExt;
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join(TRUST_FILE);
+        decide(&path, Path::new("/src/repo"), true).unwrap();
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+}
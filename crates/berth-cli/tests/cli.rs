@@ -1,6 +1,7 @@
 // SPDX-License-Identifier: Apache-2.0
 // Copyright (c) 2026 Schwimmbeck Dominik
 
+use berth_testkit::{FakeServer, Violation};
 use std::collections::BTreeSet;
 use std::io::{BufRead, BufReader, Read, Write};
 use std::net::TcpStream;
@@ -92,6 +93,74 @@ fn write_registry_override(path: &std::path::Path, servers: serde_json::Value) {
     std::fs::write(path, rendered).unwrap();
 }
 
+/// Registry entry for a binary server installed from a local file.
+fn binary_server(name: &str, package: &std::path::Path) -> serde_json::Value {
+    serde_json::json!({
+      "name": name,
+      "displayName": name,
+      "description": "Local binary test server",
+      "version": "0.1.0",
+      "source": {
+        "type": "binary",
+        "package": package.to_string_lossy(),
+        "repository": format!("https://example.com/{name}")
+      },
+      "runtime": { "type": "binary", "command": name, "args": [] },
+      "transport": "stdio",
+      "permissions": { "network": [], "env": [], "filesystem": [], "exec": [] },
+      "config": { "required": [], "optional": [] },
+      "compatibility": { "clients": ["generic"], "platforms": ["linux", "macos", "windows"] },
+      "quality": {
+        "securityScan": "pass",
+        "healthCheck": true,
+        "lastVerified": "2026-02-21",
+        "downloads": 1
+      },
+      "category": "developer-tools",
+      "tags": ["binary"],
+      "maintainer": "Test",
+      "trustLevel": "community"
+    })
+}
+
+/// Serves one canned JSON response per connection and returns the request bodies.
+fn serve_token_endpoint(responses: Vec<&'static str>) -> (String, thread::JoinHandle<Vec<String>>) {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/token", listener.local_addr().unwrap());
+    let handle = thread::spawn(move || {
+        let mut bodies = Vec::new();
+        for response in responses {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = line.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        content_length = value.trim().parse().unwrap();
+                    }
+                }
+            }
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+            bodies.push(String::from_utf8(body).unwrap());
+            let mut stream = reader.into_inner();
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{response}",
+                response.len()
+            )
+            .unwrap();
+        }
+        bodies
+    });
+    (url, handle)
+}
+
 fn write_publish_manifest(path: &std::path::Path) {
     let manifest = r#"
 [server]
@@ -256,6 +325,25 @@ fn patch_runtime_to_long_running(tmp: &std::path::Path, server: &str) {
     std::fs::write(&config_path, rendered).unwrap();
 }
 
+fn patch_runtime_to_fake_server(tmp: &std::path::Path, server: &str, fake: &FakeServer) {
+    let config_path = tmp.join(".berth/servers").join(format!("{server}.toml"));
+    let content = std::fs::read_to_string(&config_path).unwrap();
+    let mut value: toml::Value = toml::from_str(&content).unwrap();
+    let runtime = value
+        .get_mut("runtime")
+        .and_then(toml::Value::as_table_mut)
+        .unwrap();
+    runtime.insert(
+        "command".to_string(),
+        toml::Value::String(env!("CARGO_BIN_EXE_berth").to_string()),
+    );
+    runtime.insert(
+        "args".to_string(),
+        toml::Value::Array(fake.args().into_iter().map(toml::Value::String).collect()),
+    );
+    std::fs::write(config_path, toml::to_string_pretty(&value).unwrap()).unwrap();
+}
+
 fn patch_installed_version(tmp: &std::path::Path, server: &str, version: &str) {
     let config_path = tmp.join(".berth/servers").join(format!("{server}.toml"));
     let content = std::fs::read_to_string(&config_path).unwrap();
@@ -312,6 +400,24 @@ fn patch_runtime_to_echo(tmp: &std::path::Path, server: &str) {
     std::fs::write(&config_path, rendered).unwrap();
 }
 
+#[cfg(unix)]
+fn patch_runtime_to_cat(tmp: &std::path::Path, server: &str) {
+    let config_path = tmp.join(".berth/servers").join(format!("{server}.toml"));
+    let content = std::fs::read_to_string(&config_path).unwrap();
+    let mut value: toml::Value = toml::from_str(&content).unwrap();
+    let runtime = value
+        .get_mut("runtime")
+        .and_then(toml::Value::as_table_mut)
+        .unwrap();
+    runtime.insert(
+        "command".to_string(),
+        toml::Value::String("cat".to_string()),
+    );
+    runtime.insert("args".to_string(), toml::Value::Array(vec![]));
+    let rendered = toml::to_string_pretty(&value).unwrap();
+    std::fs::write(&config_path, rendered).unwrap();
+}
+
 fn patch_runtime_to_print_env_var(tmp: &std::path::Path, server: &str, env_var: &str) {
     let config_path = tmp.join(".berth/servers").join(format!("{server}.toml"));
     let content = std::fs::read_to_string(&config_path).unwrap();
@@ -487,6 +593,46 @@ fn search_multiple_results() {
     assert!(stdout.contains("filesystem"));
 }
 
+#[test]
+fn search_pages_with_limit_and_cursor() {
+    let all = berth().args(["search", "", "--json"]).output().unwrap();
+    let all: serde_json::Value = serde_json::from_slice(&all.stdout).unwrap();
+
+    let mut names = Vec::new();
+    let mut cursor: Option<String> = None;
+    loop {
+        let mut args = vec!["search", "", "--json", "--limit", "2"];
+        if let Some(cursor) = &cursor {
+            args.extend(["--cursor", cursor.as_str()]);
+        }
+        let output = berth().args(&args).output().unwrap();
+        assert!(output.status.success());
+        let page: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        for hit in page["results"].as_array().unwrap() {
+            names.push(hit["name"].clone());
+        }
+        match page["nextCursor"].as_str() {
+            Some(next) => cursor = Some(next.to_string()),
+            None => break,
+        }
+    }
+    let expected: Vec<serde_json::Value> = all
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|hit| hit["name"].clone())
+        .collect();
+    assert!(expected.len() > 2);
+    assert_eq!(names, expected);
+
+    let output = berth()
+        .args(["search", "github", "--cursor", "nope"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("invalid cursor"));
+}
+
 // --- info ---
 
 #[test]
@@ -499,6 +645,24 @@ fn info_shows_details() {
     assert!(stdout.contains("official"));
 }
 
+#[test]
+fn search_and_info_print_json() {
+    let search = berth()
+        .args(["search", "github", "--json"])
+        .output()
+        .unwrap();
+    assert!(search.status.success());
+    let hits: serde_json::Value = serde_json::from_slice(&search.stdout).unwrap();
+    assert_eq!(hits[0]["name"], "github");
+    assert_eq!(hits[0]["trustLevel"], "official");
+
+    let info = berth().args(["info", "github", "--json"]).output().unwrap();
+    assert!(info.status.success());
+    let meta: serde_json::Value = serde_json::from_slice(&info.stdout).unwrap();
+    assert_eq!(meta["displayName"], "GitHub MCP Server");
+    assert_eq!(meta["runtime"]["type"], "node");
+}
+
 #[test]
 fn info_not_found() {
     let output = berth().args(["info", "nonexistent"]).output().unwrap();
@@ -507,6 +671,24 @@ fn info_not_found() {
     assert!(stderr.contains("not found"));
 }
 
+#[test]
+fn info_suggests_close_server_names() {
+    let output = berth().args(["info", "githb"]).output().unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Did you mean"));
+    assert!(stderr.contains("github"));
+}
+
+#[test]
+fn mistyped_command_suggests_nearest() {
+    let output = berth().args(["isntall", "github"]).output().unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Unknown command 'isntall'"));
+    assert!(stderr.contains("Did you mean berth install?"));
+}
+
 // --- list ---
 
 #[test]
@@ -530,6 +712,43 @@ fn help_shows_subcommands() {
     assert!(stdout.contains("start"));
 }
 
+#[test]
+fn berth_log_env_emits_internal_diagnostics_on_stderr() {
+    let tmp = tempfile::tempdir().unwrap();
+    let output = berth_with_home(tmp.path())
+        .env("BERTH_LOG", "berth_registry=debug")
+        .env_remove("BERTH_REGISTRY_INDEX_FILE")
+        .env_remove("BERTH_REGISTRY_INDEX_URL")
+        .args(["search", "github"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("falling back to embedded seed registry"));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!stdout.contains("falling back"));
+}
+
+#[test]
+fn verbose_flag_enables_debug_diagnostics() {
+    let tmp = tempfile::tempdir().unwrap();
+    let quiet = berth_with_home(tmp.path())
+        .env_remove("BERTH_LOG")
+        .args(["search", "github"])
+        .output()
+        .unwrap();
+    assert!(quiet.status.success());
+    assert!(!String::from_utf8_lossy(&quiet.stderr).contains("DEBUG"));
+
+    let verbose = berth_with_home(tmp.path())
+        .env_remove("BERTH_LOG")
+        .args(["-vv", "search", "github"])
+        .output()
+        .unwrap();
+    assert!(verbose.status.success());
+    assert!(String::from_utf8_lossy(&verbose.stderr).contains("DEBUG"));
+}
+
 #[test]
 fn readme_command_list_matches_cli_help_commands() {
     let output = berth().arg("--help").output().unwrap();
@@ -620,6 +839,69 @@ fn version_flag() {
     assert!(stdout.contains("0.1.0"));
 }
 
+#[test]
+fn registry_api_installs_and_runs_as_a_managed_server() {
+    let tmp = tempfile::tempdir().unwrap();
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let addr = format!("127.0.0.1:{port}");
+    let server = "berth-registry-api";
+
+    let output = berth_with_home(tmp.path())
+        .args(["install", server])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let config =
+        std::fs::read_to_string(tmp.path().join(".berth/servers/berth-registry-api.toml")).unwrap();
+    let installed: toml::Value = toml::from_str(&config).unwrap();
+    assert_eq!(
+        installed["runtime"]["command"].as_str(),
+        Some(env!("CARGO_BIN_EXE_berth"))
+    );
+    for setting in [
+        format!("bind={addr}"),
+        format!("berth.readiness=tcp:{port}"),
+    ] {
+        let output = berth_with_home(tmp.path())
+            .args(["config", server, "--set", &setting])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+    }
+
+    let output = berth_with_home(tmp.path())
+        .args(["start", server])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let (status, body) = http_get(&addr, "/health");
+    assert_eq!(status, 200, "{body}");
+
+    let proxy = berth_with_home(tmp.path())
+        .args(["proxy", server])
+        .output()
+        .unwrap();
+    assert_eq!(proxy.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&proxy.stderr).contains("not an MCP server"));
+
+    let output = berth_with_home(tmp.path())
+        .args(["stop", server])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let audit = std::fs::read_to_string(tmp.path().join(".berth/audit/audit.jsonl")).unwrap();
+    assert!(audit.contains("\"server\":\"berth-registry-api\",\"action\":\"start\""));
+    assert!(audit.contains("\"server\":\"berth-registry-api\",\"action\":\"stop\""));
+}
+
 #[test]
 fn registry_api_serves_health_search_and_downloads() {
     let tmp = tempfile::tempdir().unwrap();
@@ -1260,13 +1542,75 @@ fn publish_writes_submission_queue_entry() {
     assert_eq!(entries.len(), 1);
 
     let entry_path = entries[0].as_ref().unwrap().path();
-    let payload = std::fs::read_to_string(entry_path).unwrap();
+    let payload = std::fs::read_to_string(&entry_path).unwrap();
     let submission: serde_json::Value = serde_json::from_str(&payload).unwrap();
     assert_eq!(
         submission["manifest"]["server"]["name"].as_str(),
         Some("acme-github")
     );
     assert_eq!(submission["status"].as_str(), Some("pending-manual-review"));
+
+    let id = submission["id"].as_str().unwrap();
+    assert_eq!(id.len(), 26);
+    assert_eq!(
+        entry_path.file_name().unwrap().to_str(),
+        Some(format!("{id}.json").as_str())
+    );
+    assert!(stdout.contains(&format!("Submission ID: {id}")));
+    let index =
+        std::fs::read_to_string(tmp.path().join(".berth/publish/queue-index.json")).unwrap();
+    assert!(index.contains(id));
+}
+
+#[test]
+fn publish_status_shows_review_progress_by_submission_id() {
+    let tmp = tempfile::tempdir().unwrap();
+    write_publish_manifest(&tmp.path().join("berth.toml"));
+    let output = berth_with_home(tmp.path())
+        .current_dir(tmp.path())
+        .args(["publish"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let queue_dir = tmp.path().join(".berth").join("publish").join("queue");
+    let entry = std::fs::read_dir(&queue_dir)
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap();
+    let id = entry
+        .path()
+        .file_stem()
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .to_string();
+
+    let output = berth_with_home(tmp.path())
+        .args(["publish", "--status", &id])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(&id));
+    assert!(stdout.contains("acme-github"));
+    assert!(stdout.contains("pending-manual-review"));
+    assert!(stdout.contains("Not reviewed yet."));
+
+    write_publish_submission(tmp.path(), "legacy-100.json", 100, "approved", "legacy");
+    let output = berth_with_home(tmp.path())
+        .args(["publish", "--status", "legacy-100.json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("approved"));
+
+    let output = berth_with_home(tmp.path())
+        .args(["publish", "--status", "01NOTASUBMISSION"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("No publish submission"));
 }
 
 #[test]
@@ -1358,6 +1702,41 @@ fn install_creates_config_file() {
     assert!(config_path.exists());
 }
 
+#[test]
+fn install_dry_run_prints_plan_without_writing_config() {
+    let tmp = tempfile::tempdir().unwrap();
+    let output = berth_with_home(tmp.path())
+        .args(["install", "github", "--dry-run"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("would install v1.2.0"));
+    assert!(stdout.contains("required config: token"));
+    assert!(stdout.contains("policy global: allowed at start"));
+    assert!(stdout.contains("servers/github.toml"));
+    assert!(!tmp.path().join(".berth/servers/github.toml").exists());
+}
+
+#[test]
+fn install_refuses_unwritable_home_but_list_still_works() {
+    let tmp = tempfile::tempdir().unwrap();
+    // A file where the home directory should be makes every write fail.
+    std::fs::write(tmp.path().join(".berth"), "not a directory").unwrap();
+
+    let output = berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr.matches("Cannot write Berth files").count(), 1);
+    assert!(stderr.contains("still work"));
+
+    let list = berth_with_home(tmp.path()).arg("list").output().unwrap();
+    assert!(list.status.success());
+}
+
 #[test]
 fn install_already_installed_warns() {
     let tmp = tempfile::tempdir().unwrap();
@@ -1377,134 +1756,358 @@ fn install_already_installed_warns() {
     assert!(stdout.contains("already installed"));
 }
 
+/// A registry entry for `name` published from `package` in the servers monorepo.
+fn lookalike_entry(name: &str, package: &str) -> serde_json::Value {
+    serde_json::json!({
+      "name": name,
+      "displayName": "GitHub",
+      "description": "Look-alike GitHub server",
+      "version": "9.9.9",
+      "source": {
+        "type": "npm",
+        "package": package,
+        "repository": "https://github.com/modelcontextprotocol/servers"
+      },
+      "runtime": { "type": "node", "command": "npx", "args": ["-y", package] },
+      "transport": "stdio",
+      "permissions": { "network": [], "env": [], "filesystem": [], "exec": [] },
+      "config": { "required": [], "optional": [] },
+      "compatibility": { "clients": ["generic"], "platforms": ["linux", "macos", "windows"] },
+      "quality": {
+        "securityScan": "pass",
+        "healthCheck": true,
+        "lastVerified": "2026-02-21",
+        "downloads": 1
+      },
+      "category": "developer-tools",
+      "tags": ["test"],
+      "maintainer": "Test",
+      "trustLevel": "untrusted"
+    })
+}
+
 #[test]
-fn install_not_found_exits_1() {
+fn install_refuses_same_name_from_different_source_without_replace() {
     let tmp = tempfile::tempdir().unwrap();
+    let registry_file = tmp.path().join("registry.json");
+    write_registry_override(
+        &registry_file,
+        serde_json::json!([lookalike_entry("github", "@evil/server-github")]),
+    );
     let output = berth_with_home(tmp.path())
-        .args(["install", "nonexistent"])
+        .args(["install", "github"])
         .output()
         .unwrap();
-    assert!(!output.status.success());
-    let stderr = String::from_utf8_lossy(&output.stderr);
-    assert!(stderr.contains("not found"));
-}
+    assert!(output.status.success());
 
-#[test]
-fn install_suggests_config() {
-    let tmp = tempfile::tempdir().unwrap();
     let output = berth_with_home(tmp.path())
+        .env("BERTH_REGISTRY_INDEX_FILE", &registry_file)
         .args(["install", "github"])
         .output()
         .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("collides with an installed server"));
+    assert!(stderr.contains("@modelcontextprotocol/server-github"));
+    assert!(stderr.contains("@evil/server-github"));
+    assert!(stderr.contains("from bundled seed"));
+    assert!(stderr.contains("--replace"));
+
+    let config_path = tmp.path().join(".berth/servers/github.toml");
+    let config = std::fs::read_to_string(&config_path).unwrap();
+    assert!(config.contains("@modelcontextprotocol/server-github"));
+
+    let output = berth_with_home(tmp.path())
+        .env("BERTH_REGISTRY_INDEX_FILE", &registry_file)
+        .args(["install", "github", "--replace"])
+        .output()
+        .unwrap();
     assert!(output.status.success());
     let stdout = String::from_utf8_lossy(&output.stdout);
-    assert!(stdout.contains("berth config"));
-    assert!(stdout.contains("token"));
+    assert!(stdout.contains("Replaced"));
+    let config = std::fs::read_to_string(&config_path).unwrap();
+    assert!(config.contains("@evil/server-github"));
+    assert!(config.contains(&registry_file.display().to_string()));
 }
 
 #[test]
-fn install_specific_version_succeeds() {
+fn install_replace_removes_duplicate_from_other_registry() {
     let tmp = tempfile::tempdir().unwrap();
+    let registry_file = tmp.path().join("registry.json");
+    write_registry_override(
+        &registry_file,
+        serde_json::json!([
+            lookalike_entry("gh-copy", "@modelcontextprotocol/server-github"),
+            lookalike_entry("gh-tools", "@other/server-gh-tools")
+        ]),
+    );
     let output = berth_with_home(tmp.path())
-        .args(["install", "github@1.2.0"])
+        .args(["install", "github"])
         .output()
         .unwrap();
     assert!(output.status.success());
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    assert!(stdout.contains("Installed"));
-    assert!(stdout.contains("v1.2.0"));
-}
 
-#[test]
-fn install_unavailable_version_exits_1() {
-    let tmp = tempfile::tempdir().unwrap();
+    // A sibling package from the same monorepo is not a duplicate.
     let output = berth_with_home(tmp.path())
-        .args(["install", "github@9.9.9"])
+        .env("BERTH_REGISTRY_INDEX_FILE", &registry_file)
+        .args(["install", "gh-tools"])
         .output()
         .unwrap();
-    assert!(!output.status.success());
-    let stderr = String::from_utf8_lossy(&output.stderr);
-    assert!(stderr.contains("not available"));
-}
+    assert!(output.status.success());
 
-#[test]
-fn install_invalid_server_spec_exits_1() {
-    let tmp = tempfile::tempdir().unwrap();
     let output = berth_with_home(tmp.path())
-        .args(["install", "github@"])
+        .env("BERTH_REGISTRY_INDEX_FILE", &registry_file)
+        .args(["install", "gh-copy"])
         .output()
         .unwrap();
-    assert!(!output.status.success());
+    assert_eq!(output.status.code(), Some(1));
     let stderr = String::from_utf8_lossy(&output.stderr);
-    assert!(stderr.contains("Invalid server format"));
+    assert!(stderr.contains("installed"));
+    assert!(stderr.contains("github"));
+
+    let output = berth_with_home(tmp.path())
+        .env("BERTH_REGISTRY_INDEX_FILE", &registry_file)
+        .args(["install", "gh-copy", "--replace"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let servers = tmp.path().join(".berth/servers");
+    assert!(!servers.join("github.toml").exists());
+    assert!(servers.join("gh-copy.toml").exists());
+    assert!(servers.join("gh-tools.toml").exists());
 }
 
 #[test]
-fn install_python_runtime_server_uses_uvx() {
+fn install_as_alias_keeps_instances_separate() {
     let tmp = tempfile::tempdir().unwrap();
     let output = berth_with_home(tmp.path())
-        .args(["install", "sequential-thinking"])
+        .args(["install", "github"])
         .output()
         .unwrap();
     assert!(output.status.success());
-
-    let config_path = tmp.path().join(".berth/servers/sequential-thinking.toml");
-    let content = std::fs::read_to_string(config_path).unwrap();
-    let value: toml::Value = toml::from_str(&content).unwrap();
-    let runtime = value
-        .get("runtime")
-        .and_then(toml::Value::as_table)
+    let output = berth_with_home(tmp.path())
+        .args(["install", "github", "--as", "github-work"])
+        .output()
         .unwrap();
-    assert_eq!(
-        runtime.get("type").and_then(toml::Value::as_str),
-        Some("python")
-    );
-    assert_eq!(
-        runtime.get("command").and_then(toml::Value::as_str),
-        Some("uvx")
-    );
-}
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("as github-work"));
 
-#[test]
-fn install_binary_runtime_server_copies_local_artifact() {
+    let servers = tmp.path().join(".berth/servers");
+    let config = std::fs::read_to_string(servers.join("github-work.toml")).unwrap();
+    assert!(config.contains("name = \"github\""));
+
+    berth_with_home(tmp.path())
+        .args(["config", "github-work", "--set", "token=work-token"])
+        .output()
+        .unwrap();
+    let personal = std::fs::read_to_string(servers.join("github.toml")).unwrap();
+    assert!(!personal.contains("work-token"));
+    patch_runtime_to_long_running(tmp.path(), "github-work");
+
+    let output = berth_with_home(tmp.path())
+        .args(["show-command", "github-work"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("# env: GITHUB_TOKEN"));
+
+    let output = berth_with_home(tmp.path())
+        .args(["config", "github-work", "--env"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("GITHUB_TOKEN"));
+
+    let output = berth_with_home(tmp.path())
+        .args(["install", "github", "--as", "Work Account"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Invalid alias"));
+}
+
+#[test]
+fn install_not_found_exits_1() {
     let tmp = tempfile::tempdir().unwrap();
-    let source_binary = tmp.path().join("source-binary");
-    std::fs::write(&source_binary, "#!/bin/sh\necho binary-demo\n").unwrap();
+    let output = berth_with_home(tmp.path())
+        .args(["install", "nonexistent"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("not found"));
+    assert!(!stderr.contains("Did you mean"));
+}
+
+#[test]
+fn install_suggests_close_server_names() {
+    let tmp = tempfile::tempdir().unwrap();
+    let output = berth_with_home(tmp.path())
+        .args(["install", "gihub"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Did you mean"));
+    assert!(stderr.contains("github"));
+}
+
+#[test]
+fn install_suggests_config() {
+    let tmp = tempfile::tempdir().unwrap();
+    let output = berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("berth config"));
+    assert!(stdout.contains("token"));
+}
+
+#[test]
+fn install_specific_version_succeeds() {
+    let tmp = tempfile::tempdir().unwrap();
+    let output = berth_with_home(tmp.path())
+        .args(["install", "github@1.2.0"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Installed"));
+    assert!(stdout.contains("v1.2.0"));
+}
+
+#[test]
+fn install_unavailable_version_exits_1() {
+    let tmp = tempfile::tempdir().unwrap();
+    let output = berth_with_home(tmp.path())
+        .args(["install", "github@9.9.9"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("not available"));
+}
+
+#[test]
+fn install_range_pins_updates_within_it() {
+    let tmp = tempfile::tempdir().unwrap();
+    let output = berth_with_home(tmp.path())
+        .args(["install", "github@~1.1"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("v1.1.0"));
+    let config = tmp.path().join(".berth/servers/github.toml");
+    assert!(std::fs::read_to_string(&config)
+        .unwrap()
+        .contains("version_constraint = \"~1.1\""));
+
+    let pinned = berth_with_home(tmp.path())
+        .args(["update", "github"])
+        .output()
+        .unwrap();
+    assert!(pinned.status.success());
+    assert!(String::from_utf8_lossy(&pinned.stdout).contains("up to date (v1.1.0)"));
+
+    let widened = berth_with_home(tmp.path())
+        .args(["update", "github@>=1,<2"])
+        .output()
+        .unwrap();
+    assert!(widened.status.success());
+    assert!(String::from_utf8_lossy(&widened.stdout).contains("1.1.0 -> 1.2.0"));
+    assert!(std::fs::read_to_string(&config)
+        .unwrap()
+        .contains("version_constraint = \">=1,<2\""));
+}
+
+#[test]
+fn info_versions_lists_published_versions() {
+    let tmp = tempfile::tempdir().unwrap();
+    let output = berth_with_home(tmp.path())
+        .args(["info", "github", "--versions", "--json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let versions: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let names: Vec<&str> = versions
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|v| v["version"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, vec!["1.2.0", "1.1.0", "1.0.0"]);
+    assert_eq!(versions[0]["current"], true);
+    assert_eq!(versions[2]["permissions"]["exec"], serde_json::json!([]));
+}
+
+#[test]
+fn install_invalid_server_spec_exits_1() {
+    let tmp = tempfile::tempdir().unwrap();
+    let output = berth_with_home(tmp.path())
+        .args(["install", "github@"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Invalid server format"));
+}
+
+#[test]
+fn install_python_runtime_server_uses_uvx() {
+    let tmp = tempfile::tempdir().unwrap();
+    let output = berth_with_home(tmp.path())
+        .args(["install", "sequential-thinking"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let config_path = tmp.path().join(".berth/servers/sequential-thinking.toml");
+    let content = std::fs::read_to_string(config_path).unwrap();
+    let value: toml::Value = toml::from_str(&content).unwrap();
+    let runtime = value
+        .get("runtime")
+        .and_then(toml::Value::as_table)
+        .unwrap();
+    assert_eq!(
+        runtime.get("type").and_then(toml::Value::as_str),
+        Some("python")
+    );
+    assert_eq!(
+        runtime.get("command").and_then(toml::Value::as_str),
+        Some("uvx")
+    );
+}
 
+#[test]
+fn install_runs_permitted_post_install_steps_only() {
+    let tmp = tempfile::tempdir().unwrap();
+    let data_dir = tmp.path().join("hook-data");
     let registry_file = tmp.path().join("registry.json");
     write_registry_override(
         &registry_file,
         serde_json::json!([
           {
-            "name": "binary-demo",
-            "displayName": "Binary Demo Server",
-            "description": "Local binary runtime test server",
+            "name": "hook-demo",
+            "displayName": "Hook Demo",
+            "description": "Server with post-install steps",
             "version": "0.1.0",
             "source": {
-              "type": "binary",
-              "package": source_binary.to_string_lossy(),
-              "repository": "https://example.com/binary-demo"
-            },
-            "runtime": {
-              "type": "binary",
-              "command": "binary-demo",
-              "args": []
+              "type": "npm",
+              "package": "hook-demo",
+              "repository": "https://example.com/hook-demo"
             },
+            "runtime": { "type": "node", "command": "npx", "args": ["-y", "hook-demo"] },
             "transport": "stdio",
             "permissions": {
-              "network": [],
+              "network": ["*.example.com:443"],
               "env": [],
-              "filesystem": [],
+              "filesystem": [format!("write:{}", data_dir.display())],
               "exec": []
             },
-            "config": {
-              "required": [],
-              "optional": []
-            },
-            "compatibility": {
-              "clients": ["generic"],
-              "platforms": ["linux", "macos", "windows"]
-            },
+            "config": { "required": [], "optional": [] },
+            "compatibility": { "clients": ["generic"], "platforms": ["linux", "macos", "windows"] },
             "quality": {
               "securityScan": "pass",
               "healthCheck": true,
@@ -1512,517 +2115,4695 @@ fn install_binary_runtime_server_copies_local_artifact() {
               "downloads": 1
             },
             "category": "developer-tools",
-            "tags": ["binary", "test"],
+            "tags": ["test"],
             "maintainer": "Test",
-            "trustLevel": "community"
+            "trustLevel": "community",
+            "postInstall": [
+              { "type": "createDir", "path": data_dir.join("cache").to_string_lossy() },
+              { "type": "createDir", "path": tmp.path().join("outside").to_string_lossy() },
+              { "type": "openUrl", "url": "https://apps.example.com/oauth/new", "description": "Register an OAuth app" },
+              { "type": "print", "message": "Then run berth config hook-demo" }
+            ]
           }
         ]),
     );
 
-    let mut cmd = berth_with_home(tmp.path());
-    cmd.env("BERTH_REGISTRY_INDEX_FILE", &registry_file);
-    let output = cmd.args(["install", "binary-demo"]).output().unwrap();
-    assert!(output.status.success());
+    let dry_run = berth_with_home(tmp.path())
+        .env("BERTH_REGISTRY_INDEX_FILE", &registry_file)
+        .args(["install", "hook-demo", "--dry-run"])
+        .output()
+        .unwrap();
+    assert!(dry_run.status.success());
+    let stdout = String::from_utf8_lossy(&dry_run.stdout);
+    assert!(stdout.contains("post-install: open https://apps.example.com/oauth/new [would run]"));
+    assert!(stdout.contains("not covered by a declared filesystem write permission"));
+    assert!(!data_dir.exists());
 
-    let binary_name = if cfg!(windows) {
-        "binary-demo.exe"
-    } else {
-        "binary-demo"
-    };
-    let installed_binary = tmp.path().join(".berth/bin").join(binary_name);
-    assert!(installed_binary.exists());
+    let output = berth_with_home(tmp.path())
+        .env("BERTH_REGISTRY_INDEX_FILE", &registry_file)
+        .args(["install", "hook-demo"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(data_dir.join("cache").is_dir());
+    assert!(!tmp.path().join("outside").exists());
+    assert!(stdout.contains("Skipped post-install step"));
+    assert!(stdout.contains("Register an OAuth app: https://apps.example.com/oauth/new"));
+    assert!(stdout.contains("Then run berth config hook-demo"));
+}
 
-    let config_path = tmp.path().join(".berth/servers/binary-demo.toml");
-    let content = std::fs::read_to_string(config_path).unwrap();
-    let value: toml::Value = toml::from_str(&content).unwrap();
-    let runtime = value
-        .get("runtime")
-        .and_then(toml::Value::as_table)
+#[test]
+fn registry_init_scaffolds_private_registry_and_prints_setup() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = tmp.path().join("team-registry");
+    let output = berth_with_home(tmp.path())
+        .arg("registry-init")
+        .arg(&root)
+        .output()
         .unwrap();
-    let command = runtime
-        .get("command")
-        .and_then(toml::Value::as_str)
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Initialized private registry"));
+    assert!(stdout.contains("BERTH_REGISTRY_API_TOKEN="));
+    assert!(stdout.contains("BERTH_REGISTRY_INDEX_URL=http://<host>:8787/index.json"));
+
+    let index: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(root.join("index.json")).unwrap()).unwrap();
+    assert!(!index.as_array().unwrap().is_empty());
+    assert!(root.join("registry/community").is_dir());
+    assert!(root.join("publish/queue").is_dir());
+    let token = std::fs::read_to_string(root.join("auth-token")).unwrap();
+    assert!(!stdout.contains(token.trim()));
+
+    let again = berth_with_home(tmp.path())
+        .arg("registry-init")
+        .arg(&root)
+        .output()
         .unwrap();
-    assert_eq!(command, installed_binary.to_string_lossy());
+    assert!(!again.status.success());
+    assert!(String::from_utf8_lossy(&again.stderr).contains("already exists"));
+}
 
-    #[cfg(unix)]
+#[test]
+fn registry_api_requires_token_for_post_when_configured() {
+    let tmp = tempfile::tempdir().unwrap();
+    let mut child = berth_with_home(tmp.path())
+        .env("BERTH_REGISTRY_API_TOKEN", "team-secret")
+        .args([
+            "registry-api",
+            "--bind",
+            "127.0.0.1:0",
+            "--max-requests",
+            "2",
+        ])
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut ready_line = String::new();
     {
-        use std::os::unix::fs::PermissionsExt;
-        let mode = std::fs::metadata(installed_binary)
-            .unwrap()
-            .permissions()
-            .mode();
-        assert_ne!(mode & 0o111, 0);
+        let stdout = child.stdout.as_mut().unwrap();
+        let mut reader = BufReader::new(stdout);
+        reader.read_line(&mut ready_line).unwrap();
     }
+    let addr = ready_line
+        .trim()
+        .split("http://")
+        .nth(1)
+        .unwrap()
+        .to_string();
+
+    let (status, body) = http_post_json(&addr, "/servers/github/star", "{}");
+    assert_eq!(status, 401);
+    assert!(body.contains("unauthorized"));
+    let (status, body) = http_get(&addr, "/index.json");
+    assert_eq!(status, 200);
+    let index: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert!(index.as_array().is_some_and(|a| !a.is_empty()));
+    assert!(child.wait().unwrap().success());
+}
+
+#[test]
+fn registry_api_accepts_report_batches_beyond_the_single_request_limit() {
+    let tmp = tempfile::tempdir().unwrap();
+    let mut child = berth_with_home(tmp.path())
+        .args([
+            "registry-api",
+            "--bind",
+            "127.0.0.1:0",
+            "--max-requests",
+            "2",
+        ])
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut ready_line = String::new();
+    {
+        let stdout = child.stdout.as_mut().unwrap();
+        let mut reader = BufReader::new(stdout);
+        reader.read_line(&mut ready_line).unwrap();
+    }
+    let addr = ready_line
+        .trim()
+        .split("http://")
+        .nth(1)
+        .unwrap()
+        .to_string();
+
+    let details = "x".repeat(400);
+    let reports: Vec<_> = (0..100)
+        .map(|_| serde_json::json!({"server": "github", "reason": "spam", "details": details}))
+        .collect();
+    let body = serde_json::json!({ "reports": reports }).to_string();
+    assert!(body.len() > 16 * 1024);
+    let (status, response) = http_post_json(&addr, "/reports/batch", &body);
+    assert_eq!(status, 200, "{response}");
+    let response: serde_json::Value = serde_json::from_str(&response).unwrap();
+    assert_eq!(response["accepted"].as_u64(), Some(100));
+    let log = std::fs::read_to_string(
+        tmp.path()
+            .join(".berth/registry/community/reports/github.jsonl"),
+    )
+    .unwrap();
+    assert_eq!(log.lines().count(), 100);
+
+    // The declared length alone is enough to refuse the upload, even one
+    // that would overflow once the head is added.
+    let mut stream = TcpStream::connect(&addr).unwrap();
+    let request = format!(
+        "POST /reports/batch HTTP/1.1\r\nHost: {addr}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        usize::MAX
+    );
+    stream.write_all(request.as_bytes()).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    assert!(response.starts_with("HTTP/1.1 413"), "{response}");
+    assert!(response.contains("request too large"));
+    assert!(child.wait().unwrap().success());
+}
+
+#[test]
+fn registry_promote_records_reviewer_and_updates_served_index() {
+    let tmp = tempfile::tempdir().unwrap();
+    let output = berth_with_home(tmp.path())
+        .args([
+            "registry-promote",
+            "github",
+            "--to",
+            "verified",
+            "--reviewer",
+            "alice",
+            "--reason",
+            "security review passed",
+        ])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{stdout}");
+    assert!(stdout.contains("to verified (reviewer alice)"), "{stdout}");
+    let events = std::fs::read_to_string(
+        tmp.path()
+            .join(".berth/registry/community/trust-events.jsonl"),
+    )
+    .unwrap();
+    assert!(events.contains("\"reason\":\"security review passed\""));
+
+    let again = berth_with_home(tmp.path())
+        .args([
+            "registry-promote",
+            "github",
+            "--to",
+            "verified",
+            "--reviewer",
+            "alice",
+            "--reason",
+            "again",
+        ])
+        .output()
+        .unwrap();
+    assert!(!again.status.success());
+    assert!(String::from_utf8_lossy(&again.stderr).contains("already verified"));
+
+    let mut child = berth_with_home(tmp.path())
+        .args([
+            "registry-api",
+            "--bind",
+            "127.0.0.1:0",
+            "--max-requests",
+            "1",
+        ])
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut ready_line = String::new();
+    {
+        let stdout = child.stdout.as_mut().unwrap();
+        let mut reader = BufReader::new(stdout);
+        reader.read_line(&mut ready_line).unwrap();
+    }
+    let addr = ready_line
+        .trim()
+        .split("http://")
+        .nth(1)
+        .unwrap()
+        .to_string();
+    let (status, body) = http_get(&addr, "/index.json");
+    assert_eq!(status, 200);
+    let index: serde_json::Value = serde_json::from_str(&body).unwrap();
+    let github = index
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|server| server["name"] == "github")
+        .unwrap();
+    assert_eq!(github["trustLevel"], "verified");
+    assert!(child.wait().unwrap().success());
+}
+
+#[test]
+fn registry_mirror_enables_offline_binary_install() {
+    let tmp = tempfile::tempdir().unwrap();
+    let source_binary = tmp.path().join("source-binary");
+    std::fs::write(&source_binary, "#!/bin/sh\necho mirror-demo\n").unwrap();
+    let registry_file = tmp.path().join("registry.json");
+    write_registry_override(
+        &registry_file,
+        serde_json::json!([
+          {
+            "name": "mirror-demo",
+            "displayName": "Mirror Demo Server",
+            "description": "Local binary runtime mirror test server",
+            "version": "0.1.0",
+            "source": {
+              "type": "binary",
+              "package": source_binary.to_string_lossy(),
+              "repository": "https://example.com/mirror-demo"
+            },
+            "runtime": {
+              "type": "binary",
+              "command": "mirror-demo",
+              "args": []
+            },
+            "transport": "stdio",
+            "permissions": {
+              "network": [],
+              "env": [],
+              "filesystem": [],
+              "exec": []
+            },
+            "config": {
+              "required": [],
+              "optional": []
+            },
+            "compatibility": {
+              "clients": ["generic"],
+              "platforms": ["linux", "macos", "windows"]
+            },
+            "quality": {
+              "securityScan": "pass",
+              "healthCheck": true,
+              "lastVerified": "2026-02-21",
+              "downloads": 1
+            },
+            "category": "developer-tools",
+            "tags": ["binary", "test"],
+            "maintainer": "Test",
+            "trustLevel": "community"
+          }
+        ]),
+    );
+
+    let mirror = tmp.path().join("mirror");
+    let output = berth_with_home(&tmp.path().join("maintainer"))
+        .env("BERTH_REGISTRY_INDEX_FILE", &registry_file)
+        .args(["registry-mirror", "--dest"])
+        .arg(&mirror)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("with 1 artifact(s)"));
+    assert!(mirror.join("index.json").exists());
+    assert!(mirror.join("artifacts/bin/mirror-demo").exists());
+    let manifest: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(mirror.join("mirror.json")).unwrap())
+            .unwrap();
+    assert_eq!(
+        manifest["servers"]["mirror-demo"]["path"],
+        "artifacts/bin/mirror-demo"
+    );
+
+    // The original artifact is gone; install must resolve from the mirror alone.
+    std::fs::remove_file(&source_binary).unwrap();
+    let output = berth_with_home(tmp.path())
+        .args(["install", "mirror-demo", "--mirror"])
+        .arg(&mirror)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let binary_name = if cfg!(windows) {
+        "mirror-demo.exe"
+    } else {
+        "mirror-demo"
+    };
+    assert!(tmp.path().join(".berth/bin").join(binary_name).exists());
+}
+
+#[test]
+fn install_with_missing_mirror_exits_1() {
+    let tmp = tempfile::tempdir().unwrap();
+    let output = berth_with_home(tmp.path())
+        .args(["install", "github", "--mirror"])
+        .arg(tmp.path().join("nope"))
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("failed reading registry index"));
+}
+
+#[test]
+fn install_binary_runtime_server_copies_local_artifact() {
+    let tmp = tempfile::tempdir().unwrap();
+    let source_binary = tmp.path().join("source-binary");
+    std::fs::write(&source_binary, "#!/bin/sh\necho binary-demo\n").unwrap();
+
+    let registry_file = tmp.path().join("registry.json");
+    write_registry_override(
+        &registry_file,
+        serde_json::json!([
+          {
+            "name": "binary-demo",
+            "displayName": "Binary Demo Server",
+            "description": "Local binary runtime test server",
+            "version": "0.1.0",
+            "source": {
+              "type": "binary",
+              "package": source_binary.to_string_lossy(),
+              "repository": "https://example.com/binary-demo"
+            },
+            "runtime": {
+              "type": "binary",
+              "command": "binary-demo",
+              "args": []
+            },
+            "transport": "stdio",
+            "permissions": {
+              "network": [],
+              "env": [],
+              "filesystem": [],
+              "exec": []
+            },
+            "config": {
+              "required": [],
+              "optional": []
+            },
+            "compatibility": {
+              "clients": ["generic"],
+              "platforms": ["linux", "macos", "windows"]
+            },
+            "quality": {
+              "securityScan": "pass",
+              "healthCheck": true,
+              "lastVerified": "2026-02-21",
+              "downloads": 1
+            },
+            "category": "developer-tools",
+            "tags": ["binary", "test"],
+            "maintainer": "Test",
+            "trustLevel": "community"
+          }
+        ]),
+    );
+
+    let mut cmd = berth_with_home(tmp.path());
+    cmd.env("BERTH_REGISTRY_INDEX_FILE", &registry_file);
+    let output = cmd.args(["install", "binary-demo"]).output().unwrap();
+    assert!(output.status.success());
+
+    let binary_name = if cfg!(windows) {
+        "binary-demo.exe"
+    } else {
+        "binary-demo"
+    };
+    let installed_binary = tmp.path().join(".berth/bin").join(binary_name);
+    assert!(installed_binary.exists());
+
+    let config_path = tmp.path().join(".berth/servers/binary-demo.toml");
+    let content = std::fs::read_to_string(config_path).unwrap();
+    let value: toml::Value = toml::from_str(&content).unwrap();
+    let runtime = value
+        .get("runtime")
+        .and_then(toml::Value::as_table)
+        .unwrap();
+    let command = runtime
+        .get("command")
+        .and_then(toml::Value::as_str)
+        .unwrap();
+    assert_eq!(command, installed_binary.to_string_lossy());
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&installed_binary)
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_ne!(mode & 0o111, 0);
+    }
+
+    let output = berth_with_home(tmp.path())
+        .args(["receipts", "binary-demo", "--json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let receipts: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let receipt = &receipts[0];
+    assert_eq!(receipt["action"], "install");
+    assert_eq!(receipt["version"], "0.1.0");
+    assert_eq!(receipt["source"]["type"], "binary");
+    let files = receipt["files"].as_array().unwrap();
+    let binary = files.iter().find(|f| f["kind"] == "binary").unwrap();
+    assert_eq!(binary["path"], installed_binary.to_string_lossy().as_ref());
+    // sha256 of "#!/bin/sh\necho binary-demo\n"
+    assert_eq!(
+        binary["sha256"],
+        "2cc084aac8786da5f10da6e1b01e34b88f174b1c3867530f0f193aa6803178bc"
+    );
+    assert!(receipt["policy"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|p| p["check"] == "global" && p["decision"] == "allowed at start"));
+
+    let output = berth_with_home(tmp.path())
+        .args(["uninstall", "binary-demo"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(!installed_binary.exists());
+    let output = berth_with_home(tmp.path())
+        .args(["receipts", "binary-demo"])
+        .output()
+        .unwrap();
+    assert!(String::from_utf8_lossy(&output.stdout).contains("install v0.1.0"));
+}
+
+// --- uninstall ---
+
+#[test]
+fn uninstall_removes_config_file() {
+    let tmp = tempfile::tempdir().unwrap();
+    // Install first
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+
+    let config_path = tmp.path().join(".berth/servers/github.toml");
+    assert!(config_path.exists());
+
+    // Uninstall
+    let output = berth_with_home(tmp.path())
+        .args(["uninstall", "github"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(!config_path.exists());
+}
+
+#[test]
+fn workspace_homes_launch_only_after_trust_is_granted() {
+    let tmp = tempfile::tempdir().unwrap();
+    let user_home = tmp.path().join("me");
+    let repo = tmp.path().join("repo");
+    std::fs::create_dir_all(&user_home).unwrap();
+    std::fs::create_dir_all(repo.join(".git")).unwrap();
+    let berth = |args: &[&str]| {
+        berth_with_home(&repo)
+            .env("HOME", &user_home)
+            .env_remove("XDG_CONFIG_HOME")
+            .args(args)
+            .output()
+            .unwrap()
+    };
+    assert!(berth(&["install", "github"]).status.success());
+    berth(&["config", "github", "--set", "token=abc123"]);
+    patch_runtime_to_long_running(&repo, "github");
+
+    let refused = berth(&["start", "github"]);
+    assert!(!refused.status.success());
+    let stderr = String::from_utf8_lossy(&refused.stderr);
+    assert!(stderr.contains("has not been trusted yet"), "{stderr}");
+    assert!(!repo.join(".berth/runtime/github.toml").exists());
+
+    let granted = berth(&["trust", "grant", &repo.to_string_lossy()]);
+    assert!(granted.status.success());
+    let trust_file = user_home.join(".config/berth/trust.toml");
+    assert!(trust_file.exists());
+    assert!(!repo.join(".berth/trust.toml").exists());
+    let list = berth(&["trust", "list"]);
+    let stdout = String::from_utf8_lossy(&list.stdout);
+    assert!(stdout.contains("(trusted)"), "{stdout}");
+
+    let started = berth(&["start", "github"]);
+    assert!(
+        started.status.success(),
+        "{}",
+        String::from_utf8_lossy(&started.stderr)
+    );
+    berth(&["stop", "github"]);
+
+    assert!(berth(&["trust", "revoke"]).status.success());
+    assert!(!berth(&["start", "github"]).status.success());
+}
+
+#[test]
+fn uninstall_removes_runtime_state_and_logs() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
+    patch_runtime_to_long_running(tmp.path(), "github");
+    berth_with_home(tmp.path())
+        .args(["start", "github"])
+        .output()
+        .unwrap();
+
+    let output = berth_with_home(tmp.path())
+        .args(["uninstall", "github"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("is running"));
+
+    berth_with_home(tmp.path())
+        .args(["stop", "github"])
+        .output()
+        .unwrap();
+    let state_path = tmp.path().join(".berth/runtime/github.toml");
+    let log_path = tmp.path().join(".berth/logs/github.log");
+    assert!(state_path.exists());
+    assert!(log_path.exists());
+
+    let output = berth_with_home(tmp.path())
+        .args(["uninstall", "github"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(!state_path.exists());
+    assert!(!log_path.exists());
+}
+
+#[test]
+fn gc_prunes_artifacts_of_uninstalled_servers() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    let home = tmp.path().join(".berth");
+    std::fs::create_dir_all(home.join("runtime")).unwrap();
+    std::fs::create_dir_all(home.join("logs")).unwrap();
+    for server in ["github", "slack"] {
+        std::fs::write(
+            home.join("runtime").join(format!("{server}.toml")),
+            "status = \"stopped\"\n",
+        )
+        .unwrap();
+        std::fs::write(home.join("logs").join(format!("{server}.log")), "START\n").unwrap();
+    }
+
+    let output = berth_with_home(tmp.path())
+        .args(["gc", "--dry-run"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("slack.toml"));
+    assert!(!stdout.contains("github.toml"));
+    assert!(home.join("runtime/slack.toml").exists());
+
+    let output = berth_with_home(tmp.path())
+        .args(["gc", "--archive"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(!home.join("runtime/slack.toml").exists());
+    assert!(!home.join("logs/slack.log").exists());
+    assert!(home.join("runtime/github.toml").exists());
+    let archived: Vec<_> = std::fs::read_dir(home.join("archive"))
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .collect();
+    assert_eq!(archived.len(), 1);
+    assert!(archived[0].join("runtime/slack.toml").exists());
+    assert!(archived[0].join("logs/slack.log").exists());
+}
+
+#[test]
+fn signed_registry_index_pins_key_and_refuses_another_without_accept_new_key() {
+    use std::sync::atomic::{AtomicU8, Ordering};
+    use std::sync::Arc;
+
+    let tmp = tempfile::tempdir().unwrap();
+    let index = serde_json::to_string(&vec![binary_server(
+        "signed-demo",
+        &tmp.path().join("signed-demo"),
+    )])
+    .unwrap();
+    let secret = Arc::new(AtomicU8::new(1));
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/index.json", listener.local_addr().unwrap());
+    let serving = Arc::clone(&secret);
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+            let body = if request_line.contains("index.json.sig") {
+                let key = [serving.load(Ordering::SeqCst); 32];
+                serde_json::to_string(&berth_registry::signing::sign_index(index.as_bytes(), &key))
+                    .unwrap()
+            } else {
+                index.clone()
+            };
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            let _ = stream.write_all(response.as_bytes());
+        }
+    });
+    let search = |extra: &[&str]| {
+        berth_with_home(tmp.path())
+            .env("BERTH_REGISTRY_INDEX_URL", &url)
+            .args(extra)
+            .args(["search", "signed-demo"])
+            .output()
+            .unwrap()
+    };
+
+    let output = search(&[]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("signed-demo"));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Pinned signing key"));
+    let pins = std::fs::read_to_string(tmp.path().join(".berth/registry-keys.toml")).unwrap();
+    assert!(pins.contains(&url), "{pins}");
+
+    secret.store(2, Ordering::SeqCst);
+    let output = search(&[]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("--accept-new-key"), "{stderr}");
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("signed-demo"));
+
+    let output = search(&["--accept-new-key"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Replaced the pinned signing key"));
+    assert!(search(&[]).status.success());
+}
+
+#[test]
+fn prune_removes_orphans_expired_audit_and_reviewed_submissions() {
+    let tmp = tempfile::tempdir().unwrap();
+    let home = tmp.path().join(".berth");
+    for dir in ["runtime", "logs", "audit", "publish/queue"] {
+        std::fs::create_dir_all(home.join(dir)).unwrap();
+    }
+    std::fs::write(home.join("runtime/slack.toml"), "status = \"stopped\"\n").unwrap();
+    std::fs::write(home.join("logs/slack.log"), "START\n").unwrap();
+    let now_ms = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64;
+    std::fs::write(
+        home.join("audit/audit.jsonl"),
+        format!(
+            "{{\"timestampEpochMs\":1000,\"server\":\"github\",\"action\":\"start\"}}\n{{\"timestampEpochMs\":{now_ms},\"server\":\"github\",\"action\":\"stop\"}}\n"
+        ),
+    )
+    .unwrap();
+    std::fs::write(
+        home.join("publish/queue/old.json"),
+        r#"{"status": "approved", "submitted_at_epoch_secs": 1}"#,
+    )
+    .unwrap();
+    std::fs::write(
+        home.join("publish/queue/waiting.json"),
+        r#"{"status": "pending-manual-review", "submitted_at_epoch_secs": 1}"#,
+    )
+    .unwrap();
+
+    let output = berth_with_home(tmp.path())
+        .args(["prune", "--older-than", "7d", "--dry-run"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("slack.toml"), "{stdout}");
+    assert!(stdout.contains("old.json"), "{stdout}");
+    assert!(!stdout.contains("waiting.json"), "{stdout}");
+    assert!(stdout.contains("Would free"), "{stdout}");
+    assert!(stdout.contains("1 audit entry"), "{stdout}");
+    assert!(home.join("runtime/slack.toml").exists());
+    assert!(home.join("publish/queue/old.json").exists());
+
+    let output = berth_with_home(tmp.path())
+        .args(["prune", "--older-than", "7d"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Freed"), "{stdout}");
+    assert!(!home.join("runtime/slack.toml").exists());
+    assert!(!home.join("logs/slack.log").exists());
+    assert!(!home.join("publish/queue/old.json").exists());
+    assert!(home.join("publish/queue/waiting.json").exists());
+    let audit = std::fs::read_to_string(home.join("audit/audit.jsonl")).unwrap();
+    assert_eq!(audit.lines().count(), 1);
+    assert!(audit.contains("\"stop\""));
+
+    let output = berth_with_home(tmp.path())
+        .args(["prune", "--older-than", "soon"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--older-than"));
+}
+
+#[test]
+fn doctor_reports_stale_state_and_fails_on_broken_registry_cache() {
+    let tmp = tempfile::tempdir().unwrap();
+    let home = tmp.path().join(".berth");
+    std::fs::create_dir_all(home.join("runtime")).unwrap();
+    std::fs::create_dir_all(home.join("logs")).unwrap();
+    std::fs::write(
+        home.join("runtime/ghost.toml"),
+        "status = \"running\"\npid = 4294967\n",
+    )
+    .unwrap();
+    std::fs::write(home.join("logs/ghost.log"), "START\n").unwrap();
+
+    let doctor = || {
+        berth_with_home(tmp.path())
+            .env("BERTH_SECRET_BACKEND", "file")
+            .args(["doctor", "--json"])
+            .output()
+            .unwrap()
+    };
+    let output = doctor();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["ok"], true);
+    let check = |name: &str| -> Vec<serde_json::Value> {
+        report["checks"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|c| c["name"] == name)
+            .cloned()
+            .collect()
+    };
+    let state = check("runtime state");
+    assert_eq!(state[0]["status"], "warn");
+    assert!(state[0]["detail"]
+        .as_str()
+        .unwrap()
+        .contains("pid 4294967 is gone"));
+    let orphaned = check("orphaned logs");
+    assert_eq!(orphaned[0]["status"], "warn");
+    assert_eq!(orphaned[0]["fix"], "berth gc");
+    assert_eq!(check("keyring")[0]["status"], "pass");
+
+    std::fs::create_dir_all(home.join("registry")).unwrap();
+    std::fs::write(home.join("registry/index.json"), "{ not json").unwrap();
+    let output = doctor();
+    assert_eq!(output.status.code(), Some(1));
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["ok"], false);
+    let cache = report["checks"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|c| c["name"] == "registry cache")
+        .unwrap();
+    assert_eq!(cache["status"], "fail");
+    assert!(cache["fix"].as_str().unwrap().contains("index.json"));
+}
+
+#[test]
+fn migrate_layout_moves_legacy_home_into_xdg_dirs() {
+    let tmp = tempfile::tempdir().unwrap();
+    let legacy = tmp.path().join(".berth");
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    let legacy_config = legacy.join("servers/github.toml");
+    let config = std::fs::read_to_string(&legacy_config).unwrap().replace(
+        "command = \"npx\"",
+        &format!("command = \"{}\"", legacy.join("bin/github").display()),
+    );
+    std::fs::write(&legacy_config, config).unwrap();
+    std::fs::create_dir_all(legacy.join("bin")).unwrap();
+    std::fs::write(legacy.join("bin/github"), "").unwrap();
+    std::fs::create_dir_all(legacy.join("registry")).unwrap();
+    std::fs::write(legacy.join("registry/index.json"), "[]").unwrap();
+
+    let xdg = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_berth"))
+            .env_remove("BERTH_HOME")
+            .env("HOME", tmp.path())
+            .env("XDG_CONFIG_HOME", tmp.path().join("xdg-config"))
+            .env("XDG_STATE_HOME", tmp.path().join("xdg-state"))
+            .env("XDG_CACHE_HOME", tmp.path().join("xdg-cache"))
+            .args(args)
+            .output()
+            .unwrap()
+    };
+
+    let output = xdg(&["migrate-layout", "--dry-run"]);
+    assert!(output.status.success());
+    assert!(legacy.exists());
+
+    let output = xdg(&["migrate-layout"]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(!legacy.exists());
+    let config_path = tmp.path().join("xdg-config/berth/servers/github.toml");
+    let state_bin = tmp.path().join("xdg-state/berth/bin/github");
+    assert!(state_bin.exists());
+    assert!(tmp
+        .path()
+        .join("xdg-cache/berth/registry/index.json")
+        .exists());
+    let migrated = std::fs::read_to_string(&config_path).unwrap();
+    assert!(migrated.contains(&state_bin.display().to_string()));
+
+    let output = xdg(&["list"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("github"));
+}
+
+#[test]
+fn older_data_is_migrated_with_backup_and_newer_data_refused() {
+    let tmp = tempfile::tempdir().unwrap();
+    let runtime = tmp.path().join(".berth/runtime");
+    std::fs::create_dir_all(&runtime).unwrap();
+    let legacy = "status = \"stopped\"\nupdated_at_epoch_secs = 1700000000\n";
+    std::fs::write(runtime.join("github.toml"), legacy).unwrap();
+
+    let preview = berth_with_home(tmp.path())
+        .args(["migrate", "--dry-run"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&preview.stdout);
+    assert!(preview.status.success());
+    assert!(stdout.contains("from format 1 to 2"), "{stdout}");
+    assert!(stdout.contains("github.toml"));
+    assert!(stdout.contains("Breaking:"));
+    assert!(!tmp.path().join(".berth/format.toml").exists());
+
+    let list = berth_with_home(tmp.path()).args(["list"]).output().unwrap();
+    let stderr = String::from_utf8_lossy(&list.stderr);
+    assert!(list.status.success());
+    assert!(
+        stderr.contains("Upgraded Berth data from format 1 to 2"),
+        "{stderr}"
+    );
+    assert!(stderr.contains("Backup:"));
+    let upgraded = std::fs::read_to_string(runtime.join("github.toml")).unwrap();
+    assert!(upgraded.contains("updated_at_epoch_ms = 1700000000000"));
+    let backups = std::fs::read_dir(tmp.path().join(".berth/backups/migrations"))
+        .unwrap()
+        .count();
+    assert_eq!(backups, 1);
+
+    let again = berth_with_home(tmp.path()).args(["list"]).output().unwrap();
+    assert!(!String::from_utf8_lossy(&again.stderr).contains("Upgraded"));
+
+    std::fs::write(
+        tmp.path().join(".berth/format.toml"),
+        "format = 99\nberth = \"9.0.0\"\n",
+    )
+    .unwrap();
+    let install = berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    assert!(!install.status.success());
+    assert!(
+        String::from_utf8_lossy(&install.stderr).contains("written by berth 9.0.0 in format 99")
+    );
+    let list = berth_with_home(tmp.path()).args(["list"]).output().unwrap();
+    assert!(list.status.success());
+}
+
+#[test]
+fn uninstall_not_installed_exits_1() {
+    let tmp = tempfile::tempdir().unwrap();
+    let output = berth_with_home(tmp.path())
+        .args(["uninstall", "github"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("not installed"));
+}
+
+// --- config ---
+
+#[test]
+fn config_not_installed_exits_1() {
+    let tmp = tempfile::tempdir().unwrap();
+    let output = berth_with_home(tmp.path())
+        .args(["config", "github"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("not installed"));
+}
+
+#[test]
+fn config_show_lists_required() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+
+    let output = berth_with_home(tmp.path())
+        .args(["config", "github"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("token"));
+    assert!(stdout.contains("NOT SET"));
+}
+
+#[test]
+fn config_set_updates_value() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+
+    let output = berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let config_path = tmp.path().join(".berth/servers/github.toml");
+    let content = std::fs::read_to_string(config_path).unwrap();
+    assert!(content.contains("abc123"));
+}
+
+#[test]
+fn config_set_secure_stores_secret_reference() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+
+    let mut cmd = berth_with_home(tmp.path());
+    cmd.env("BERTH_SECRET_BACKEND", "file");
+    let output = cmd
+        .args(["config", "github", "--set", "token=abc123", "--secure"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let config_path = tmp.path().join(".berth/servers/github.toml");
+    let content = std::fs::read_to_string(config_path).unwrap();
+    assert!(content.contains("secret://github/token"));
+    assert!(!content.contains("abc123"));
+}
+
+#[test]
+fn config_migrate_secrets_moves_plaintext_values_and_policy_forbids_them() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    let set = berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
+    assert!(set.status.success());
+    assert!(String::from_utf8_lossy(&set.stderr).contains("stored in plaintext"));
+
+    let report = berth_with_home(tmp.path())
+        .args(["config", "migrate-secrets"])
+        .output()
+        .unwrap();
+    assert!(report.status.success());
+    let stdout = String::from_utf8_lossy(&report.stdout);
+    assert!(stdout.contains("github"), "{stdout}");
+    assert!(stdout.contains("token"), "{stdout}");
+
+    std::fs::write(
+        tmp.path().join(".berth/policy.toml"),
+        "[secrets]\nforbid_plaintext = true\n",
+    )
+    .unwrap();
+    let start = berth_with_home(tmp.path())
+        .args(["start", "github"])
+        .output()
+        .unwrap();
+    assert!(!start.status.success());
+    assert!(String::from_utf8_lossy(&start.stderr).contains("plaintext sensitive config"));
+    let refused = berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=other"])
+        .output()
+        .unwrap();
+    assert!(!refused.status.success());
+
+    let mut migrate = berth_with_home(tmp.path());
+    migrate.env("BERTH_SECRET_BACKEND", "file");
+    let output = migrate
+        .args(["config", "migrate-secrets", "github"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let content = std::fs::read_to_string(tmp.path().join(".berth/servers/github.toml")).unwrap();
+    assert!(content.contains("secret://github/token"));
+    assert!(!content.contains("abc123"));
+}
+
+#[test]
+fn config_secure_without_set_exits_1() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+
+    let output = berth_with_home(tmp.path())
+        .args(["config", "github", "--secure"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+}
+
+#[test]
+fn config_interactive_updates_value() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+
+    let mut child = berth_with_home(tmp.path())
+        .args(["config", "github", "--interactive"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .as_mut()
+        .unwrap()
+        .write_all(b"interactive-token\n\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    assert!(output.status.success());
+    let config_path = tmp.path().join(".berth/servers/github.toml");
+    let content = std::fs::read_to_string(config_path).unwrap();
+    assert!(content.contains("interactive-token"));
+}
+
+#[test]
+fn config_interactive_conflicts_with_set() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+
+    let output = berth_with_home(tmp.path())
+        .args(["config", "github", "--interactive", "--set", "token=abc123"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+}
+
+#[test]
+fn config_set_unknown_key_exits_1() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+
+    let output = berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "unknown_key=value"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+}
+
+#[test]
+fn config_set_runtime_policy_keys_updates_value() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+
+    let out1 = berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "berth.auto-restart=true"])
+        .output()
+        .unwrap();
+    assert!(out1.status.success());
+    let out2 = berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "berth.max-restarts=2"])
+        .output()
+        .unwrap();
+    assert!(out2.status.success());
+
+    let config = std::fs::read_to_string(tmp.path().join(".berth/servers/github.toml")).unwrap();
+    assert!(config.contains("\"berth.auto-restart\" = \"true\""));
+    assert!(config.contains("\"berth.max-restarts\" = \"2\""));
+}
+
+#[test]
+fn config_set_sandbox_policy_keys_updates_value() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+
+    let out1 = berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "berth.sandbox=basic"])
+        .output()
+        .unwrap();
+    assert!(out1.status.success());
+    let out2 = berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "berth.sandbox-network=inherit"])
+        .output()
+        .unwrap();
+    assert!(out2.status.success());
+
+    let config = std::fs::read_to_string(tmp.path().join(".berth/servers/github.toml")).unwrap();
+    assert!(config.contains("\"berth.sandbox\" = \"basic\""));
+    assert!(config.contains("\"berth.sandbox-network\" = \"inherit\""));
+}
+
+#[test]
+fn status_flags_servers_over_their_error_budget() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    let audit_path = tmp.path().join(".berth/audit/audit.jsonl");
+    let mut audit = std::fs::read_to_string(&audit_path).unwrap();
+    let now_ms = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64;
+    for i in 0..4 {
+        let ms = now_ms - 60_000 + i;
+        audit.push_str(&format!(
+            "{{\"timestampEpochMs\":{ms},\"timestampEpochSecs\":{},\"server\":\"github\",\"action\":\"auto-restart\"}}\n",
+            ms / 1_000
+        ));
+    }
+    std::fs::write(&audit_path, audit).unwrap();
+
+    let output = berth_with_home(tmp.path())
+        .args(["status"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("flaky"), "{stdout}");
+    assert!(stdout.contains("4 restarts (budget 3)"), "{stdout}");
+    assert!(stdout.contains("POST /servers/github/report"), "{stdout}");
+    assert!(tmp.path().join(".berth/error-budget.json").exists());
+
+    let raised = berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "berth.error-budget-restarts=5"])
+        .output()
+        .unwrap();
+    assert!(raised.status.success());
+    // The persisted window still holds the restarts; the larger budget absorbs them.
+    let output = berth_with_home(tmp.path())
+        .args(["status"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!stdout.contains("flaky"), "{stdout}");
+}
+
+#[test]
+fn status_flags_expired_configured_credentials() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+
+    let invalid = berth_with_home(tmp.path())
+        .args([
+            "config",
+            "github",
+            "--set",
+            "berth.credential-expires-at=soon",
+        ])
+        .output()
+        .unwrap();
+    assert!(!invalid.status.success());
+    assert!(String::from_utf8_lossy(&invalid.stderr).contains("RFC3339"));
+
+    let set = berth_with_home(tmp.path())
+        .args([
+            "config",
+            "github",
+            "--set",
+            "berth.credential-expires-at=2020-01-01T00:00:00Z",
+        ])
+        .output()
+        .unwrap();
+    assert!(set.status.success());
+
+    let output = berth_with_home(tmp.path())
+        .args(["status"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("credentials expired"));
+    assert!(stdout.contains("berth.credential-expires-at"));
+}
+
+#[test]
+fn config_env_shows_variables() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+
+    let output = berth_with_home(tmp.path())
+        .args(["config", "github", "--env"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("GITHUB_TOKEN"));
+}
+
+#[test]
+fn config_export_import_round_trip() {
+    let source = tempfile::tempdir().unwrap();
+    berth_with_home(source.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    berth_with_home(source.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
+
+    let export_file = source.path().join("team-berth.toml");
+    let export = berth_with_home(source.path())
+        .args(["config", "export", export_file.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert!(export.status.success());
+    assert!(export_file.exists());
+
+    let target = tempfile::tempdir().unwrap();
+    berth_with_home(target.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+
+    let import = berth_with_home(target.path())
+        .args(["config", "import", export_file.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert!(import.status.success());
+
+    let target_config =
+        std::fs::read_to_string(target.path().join(".berth/servers/github.toml")).unwrap();
+    assert!(target_config.contains("abc123"));
+}
+
+#[test]
+fn config_import_requires_file() {
+    let tmp = tempfile::tempdir().unwrap();
+    let output = berth_with_home(tmp.path())
+        .args(["config", "import"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Missing import file"));
+}
+
+#[test]
+fn wasm_runtime_installs_module_and_launches_it_in_wasmtime() {
+    let tmp = tempfile::tempdir().unwrap();
+    let module = tmp.path().join("files.wasm");
+    std::fs::write(&module, b"\0asm\x01\0\0\0").unwrap();
+    let data = tmp.path().join("data");
+    std::fs::create_dir_all(&data).unwrap();
+    let data = data.to_string_lossy().to_string();
+    let mut server = binary_server("files", &module);
+    server["runtime"]["type"] = serde_json::json!("wasm");
+    server["permissions"]["filesystem"] = serde_json::json!([format!("write:{data}")]);
+    let registry_file = tmp.path().join("registry.json");
+    write_registry_override(&registry_file, serde_json::json!([server]));
+
+    let output = berth_with_home(tmp.path())
+        .env("BERTH_REGISTRY_INDEX_FILE", &registry_file)
+        .args(["install", "files"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let installed = tmp.path().join(".berth/bin/files.wasm");
+    assert_eq!(std::fs::read(&installed).unwrap(), b"\0asm\x01\0\0\0");
+
+    let output = berth_with_home(tmp.path())
+        .env("BERTH_REGISTRY_INDEX_FILE", &registry_file)
+        .args(["show-command", "files"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("wasmtime run"), "{stdout}");
+    assert!(stdout.contains(&format!("{data}::{data}")), "{stdout}");
+    assert!(
+        stdout.contains(installed.to_string_lossy().as_ref()),
+        "{stdout}"
+    );
+    assert!(!stdout.contains("inherit-network"), "{stdout}");
+}
+
+#[test]
+fn install_verifies_binary_sha256_and_records_it() {
+    let tmp = tempfile::tempdir().unwrap();
+    let source_binary = tmp.path().join("source-binary");
+    std::fs::write(&source_binary, "#!/bin/sh\necho binary-demo\n").unwrap();
+    // sha256 of "#!/bin/sh\necho binary-demo\n"
+    let digest = "2cc084aac8786da5f10da6e1b01e34b88f174b1c3867530f0f193aa6803178bc";
+    let registry_file = tmp.path().join("registry.json");
+    let mut server = binary_server("checked", &source_binary);
+    server["source"]["sha256"] = serde_json::json!(format!("sha256:{}", "0".repeat(64)));
+    write_registry_override(&registry_file, serde_json::json!([server.clone()]));
+
+    let plan = berth_with_home(tmp.path())
+        .env("BERTH_REGISTRY_INDEX_FILE", &registry_file)
+        .args(["install", "checked", "--dry-run"])
+        .output()
+        .unwrap();
+    assert!(String::from_utf8_lossy(&plan.stdout).contains("verify sha256"));
+
+    let mismatch = berth_with_home(tmp.path())
+        .env("BERTH_REGISTRY_INDEX_FILE", &registry_file)
+        .args(["install", "checked"])
+        .output()
+        .unwrap();
+    assert!(!mismatch.status.success());
+    let stderr = String::from_utf8_lossy(&mismatch.stderr);
+    assert!(stderr.contains("Integrity check failed"), "{stderr}");
+    assert!(stderr.contains(digest), "{stderr}");
+    assert!(!tmp.path().join(".berth/servers/checked.toml").exists());
+    let bin_dir = tmp.path().join(".berth/bin");
+    assert_eq!(std::fs::read_dir(&bin_dir).unwrap().count(), 0);
+
+    server["source"]["sha256"] = serde_json::json!(digest.to_uppercase());
+    write_registry_override(&registry_file, serde_json::json!([server]));
+    let output = berth_with_home(tmp.path())
+        .env("BERTH_REGISTRY_INDEX_FILE", &registry_file)
+        .args(["install", "checked"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Verified sha256"));
+    let content = std::fs::read_to_string(tmp.path().join(".berth/servers/checked.toml")).unwrap();
+    let value: toml::Value = toml::from_str(&content).unwrap();
+    assert_eq!(value["source"]["sha256"].as_str(), Some(digest));
+
+    let mut newer = binary_server("checked", &source_binary);
+    newer["version"] = serde_json::json!("0.2.0");
+    newer["source"]["sha256"] = serde_json::json!("f".repeat(64));
+    write_registry_override(&registry_file, serde_json::json!([newer]));
+    let update = berth_with_home(tmp.path())
+        .env("BERTH_REGISTRY_INDEX_FILE", &registry_file)
+        .args(["update", "checked"])
+        .output()
+        .unwrap();
+    assert!(!update.status.success());
+    assert!(String::from_utf8_lossy(&update.stderr).contains("Integrity check failed"));
+    let content = std::fs::read_to_string(tmp.path().join(".berth/servers/checked.toml")).unwrap();
+    assert!(content.contains("version = \"0.1.0\""), "{content}");
+}
+
+#[test]
+fn install_enforces_home_quota_and_list_reports_sizes() {
+    let tmp = tempfile::tempdir().unwrap();
+    let source_binary = tmp.path().join("source-binary");
+    std::fs::write(&source_binary, vec![b'x'; 64 * 1024]).unwrap();
+    let registry_file = tmp.path().join("registry.json");
+    write_registry_override(
+        &registry_file,
+        serde_json::json!([binary_server("sized", &source_binary)]),
+    );
+    let berth_dir = tmp.path().join(".berth");
+    std::fs::create_dir_all(&berth_dir).unwrap();
+    std::fs::write(berth_dir.join("filler"), vec![0u8; 1024 * 1024]).unwrap();
+    std::fs::write(berth_dir.join("policy.toml"), "[storage]\nquota_mb = 1\n").unwrap();
+
+    let plan = berth_with_home(tmp.path())
+        .env("BERTH_REGISTRY_INDEX_FILE", &registry_file)
+        .args(["install", "sized", "--dry-run"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&plan.stdout);
+    assert!(stdout.contains("64.0 KiB download"), "{stdout}");
+    assert!(stdout.contains("would be refused"), "{stdout}");
+
+    let refused = berth_with_home(tmp.path())
+        .env("BERTH_REGISTRY_INDEX_FILE", &registry_file)
+        .args(["install", "sized"])
+        .output()
+        .unwrap();
+    assert!(!refused.status.success());
+    let stderr = String::from_utf8_lossy(&refused.stderr);
+    assert!(stderr.contains("quota is 1.0 MiB"), "{stderr}");
+    assert!(!berth_dir.join("servers/sized.toml").exists());
+
+    std::fs::write(berth_dir.join("policy.toml"), "[storage]\nquota_mb = 4\n").unwrap();
+    let installed = berth_with_home(tmp.path())
+        .env("BERTH_REGISTRY_INDEX_FILE", &registry_file)
+        .args(["install", "sized"])
+        .output()
+        .unwrap();
+    assert!(installed.status.success());
+    assert!(String::from_utf8_lossy(&installed.stdout).contains("Estimated size"));
+
+    let list = berth_with_home(tmp.path())
+        .args(["list", "--sizes", "--json"])
+        .output()
+        .unwrap();
+    let servers: serde_json::Value = serde_json::from_slice(&list.stdout).unwrap();
+    assert!(servers[0]["diskBytes"].as_u64().unwrap() > 64 * 1024);
+
+    let table = berth_with_home(tmp.path())
+        .args(["list", "--sizes"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&table.stdout);
+    assert!(
+        stdout.contains("SIZE") && stdout.contains("Total:"),
+        "{stdout}"
+    );
+
+    let plain = berth_with_home(tmp.path())
+        .args(["list", "--json"])
+        .output()
+        .unwrap();
+    let servers: serde_json::Value = serde_json::from_slice(&plain.stdout).unwrap();
+    assert!(servers[0].get("diskBytes").is_none());
+}
+
+#[test]
+fn profiles_and_home_flag_isolate_installs() {
+    let tmp = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(tmp.path().join(".berth")).unwrap();
+    std::fs::write(
+        tmp.path().join(".berth/profiles.toml"),
+        "[profiles.experiments]\nhome = \"../experiments\"\n",
+    )
+    .unwrap();
+
+    let install = berth_with_home(tmp.path())
+        .args(["--profile", "experiments", "install", "github"])
+        .output()
+        .unwrap();
+    assert!(install.status.success());
+    assert!(tmp.path().join("experiments/servers/github.toml").exists());
+    assert!(!tmp.path().join(".berth/servers/github.toml").exists());
+
+    let list = berth_with_home(tmp.path())
+        .args(["list", "--json", "--profile", "experiments"])
+        .output()
+        .unwrap();
+    let servers: serde_json::Value = serde_json::from_slice(&list.stdout).unwrap();
+    assert_eq!(servers[0]["name"], "github");
+    let daily = berth_with_home(tmp.path())
+        .args(["list", "--json"])
+        .output()
+        .unwrap();
+    assert_eq!(String::from_utf8_lossy(&daily.stdout).trim(), "[]");
+
+    let other = tmp.path().join("other");
+    let install = berth_with_home(tmp.path())
+        .args(["--home", other.to_str().unwrap(), "install", "github"])
+        .output()
+        .unwrap();
+    assert!(install.status.success());
+    assert!(other.join("servers/github.toml").exists());
+
+    let unknown = berth_with_home(tmp.path())
+        .args(["--profile", "daily", "list"])
+        .output()
+        .unwrap();
+    assert!(!unknown.status.success());
+    let stderr = String::from_utf8_lossy(&unknown.stderr);
+    assert!(
+        stderr.contains("Profile `daily` is not defined"),
+        "{stderr}"
+    );
+    assert!(stderr.contains("known: experiments"), "{stderr}");
+}
+
+#[test]
+fn observer_mode_allows_inspection_and_refuses_changes() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    for setting in ["token=abc123", "berth.auto-restart=true"] {
+        berth_with_home(tmp.path())
+            .args(["config", "github", "--set", setting])
+            .output()
+            .unwrap();
+    }
+    // A crashed server that a non-observer status check would relaunch.
+    write_runtime_state_for_status_autorestart(tmp.path(), "github");
+    let state_path = tmp.path().join(".berth/runtime/github.toml");
+    let state_before = std::fs::read_to_string(&state_path).unwrap();
+    std::fs::write(
+        tmp.path().join(".berth/profiles.toml"),
+        "[profiles.shadow]\nhome = \".\"\nobserver = true\n",
+    )
+    .unwrap();
+    let config_path = tmp.path().join(".berth/servers/github.toml");
+    let before = std::fs::read_to_string(&config_path).unwrap();
+
+    for args in [
+        vec!["--observer", "status"],
+        vec!["--observer", "list"],
+        vec!["--observer", "config", "github"],
+        vec!["--observer", "permissions", "github"],
+        vec!["--observer", "audit", "github"],
+        vec!["--observer", "history", "github"],
+    ] {
+        let output = berth_with_home(tmp.path()).args(&args).output().unwrap();
+        assert!(output.status.success(), "{args:?}");
+    }
+    let status = berth_with_home(tmp.path())
+        .args(["--observer", "status", "--json"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&status.stdout);
+    assert!(stdout.contains("\"stopped\""), "{stdout}");
+    thread::sleep(Duration::from_millis(300));
+    assert_eq!(std::fs::read_to_string(&state_path).unwrap(), state_before);
+    assert!(!tmp.path().join(".berth/logs/github.log").exists());
+
+    let refused = [
+        (
+            berth_with_home(tmp.path())
+                .args(["--observer", "start", "github"])
+                .output(),
+            "berth start",
+        ),
+        (
+            berth_with_home(tmp.path())
+                .args(["config", "github", "--set", "token=abc123"])
+                .env("BERTH_OBSERVER", "1")
+                .output(),
+            "berth config",
+        ),
+        (
+            berth_with_home(tmp.path())
+                .args([
+                    "--profile",
+                    "shadow",
+                    "permissions",
+                    "github",
+                    "--revoke",
+                    "network:*",
+                ])
+                .output(),
+            "berth permissions",
+        ),
+        (
+            berth_with_home(tmp.path())
+                .args(["uninstall", "github", "--observer"])
+                .output(),
+            "berth uninstall",
+        ),
+    ];
+    for (output, command) in refused {
+        let output = output.unwrap();
+        assert_eq!(output.status.code(), Some(1), "{command}");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("Observer mode is read-only"), "{stderr}");
+        assert!(stderr.contains(command), "{stderr}");
+    }
+    assert_eq!(std::fs::read_to_string(&config_path).unwrap(), before);
+    assert!(!tmp.path().join(".berth/permissions/github.toml").exists());
+}
+
+// --- list with version ---
+
+#[test]
+fn list_shows_version_after_install() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+
+    let output = berth_with_home(tmp.path()).args(["list"]).output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("VERSION"));
+    assert!(stdout.contains("1.2.0"));
+}
+
+/// Parses every stdout line of a `--progress json` run as one event.
+fn progress_events(stdout: &[u8]) -> Vec<serde_json::Value> {
+    String::from_utf8_lossy(stdout)
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap_or_else(|_| panic!("not JSON: {line}")))
+        .collect()
+}
+
+#[test]
+fn progress_json_emits_step_events_for_install_update_and_start() {
+    let tmp = tempfile::tempdir().unwrap();
+    let install = berth_with_home(tmp.path())
+        .args(["install", "github", "--progress", "json"])
+        .output()
+        .unwrap();
+    assert!(install.status.success());
+    assert!(String::from_utf8_lossy(&install.stderr).contains("Installed"));
+    let events = progress_events(&install.stdout);
+    let steps: Vec<(&str, &str)> = events
+        .iter()
+        .filter(|e| e["event"] != "finished")
+        .map(|e| (e["event"].as_str().unwrap(), e["step"].as_str().unwrap()))
+        .collect();
+    assert_eq!(
+        steps,
+        [
+            ("started", "resolve"),
+            ("completed", "resolve"),
+            ("started", "fetch"),
+            ("completed", "fetch"),
+            ("started", "configure"),
+            ("completed", "configure"),
+            ("started", "hooks"),
+            ("completed", "hooks"),
+        ]
+    );
+    assert_eq!(events[3]["percent"], 50);
+    let finished = events.last().unwrap();
+    assert_eq!(finished["event"], "finished");
+    assert_eq!(finished["percent"], 100);
+    assert_eq!(finished["failed"], 0);
+
+    let update = berth_with_home(tmp.path())
+        .args(["update", "--all", "--progress", "json"])
+        .output()
+        .unwrap();
+    assert!(update.status.success());
+    let events = progress_events(&update.stdout);
+    assert_eq!(events[0]["event"], "started");
+    assert_eq!(events[0]["server"], "github");
+    assert_eq!(events[1]["event"], "completed");
+    assert_eq!(events[1]["percent"], 100);
+
+    let start = berth_with_home(tmp.path())
+        .args(["start", "--progress", "json"])
+        .output()
+        .unwrap();
+    assert!(!start.status.success());
+    let events = progress_events(&start.stdout);
+    assert_eq!(events[1]["event"], "failed");
+    assert_eq!(events[1]["server"], "github");
+    assert!(events[1]["error"]
+        .as_str()
+        .unwrap()
+        .contains("Missing required config"));
+    assert_eq!(events[2]["failed"], 1);
+
+    let missing = berth_with_home(tmp.path())
+        .args(["install", "no-such-server", "--progress", "json"])
+        .output()
+        .unwrap();
+    assert!(!missing.status.success());
+    let events = progress_events(&missing.stdout);
+    assert_eq!(events[1]["event"], "failed");
+    assert_eq!(events[1]["step"], "resolve");
+}
+
+#[test]
+fn list_and_status_print_json() {
+    let tmp = tempfile::tempdir().unwrap();
+    let empty = berth_with_home(tmp.path())
+        .args(["status", "--json"])
+        .output()
+        .unwrap();
+    assert_eq!(String::from_utf8_lossy(&empty.stdout).trim(), "[]");
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+
+    let list = berth_with_home(tmp.path())
+        .args(["list", "--json"])
+        .output()
+        .unwrap();
+    assert!(list.status.success());
+    let servers: serde_json::Value = serde_json::from_slice(&list.stdout).unwrap();
+    assert_eq!(servers[0]["name"], "github");
+    assert_eq!(servers[0]["version"], "1.2.0");
+    assert_eq!(servers[0]["status"], "stopped");
+
+    let status = berth_with_home(tmp.path())
+        .args(["status", "--json"])
+        .output()
+        .unwrap();
+    let rows: serde_json::Value = serde_json::from_slice(&status.stdout).unwrap();
+    assert_eq!(rows[0]["status"], "stopped");
+    assert!(rows[0]["pid"].is_null());
+    assert_eq!(rows[0]["flaky"], false);
+}
+
+// --- auth ---
+
+#[test]
+fn auth_stores_refresh_token_and_start_injects_access_token() {
+    let tmp = tempfile::tempdir().unwrap();
+    let (token_url, token_server) = serve_token_endpoint(vec![
+        r#"{"access_token":"first","refresh_token":"r-token","expires_in":3600}"#,
+        r#"{"access_token":"second","expires_in":3600}"#,
+    ]);
+    let registry_file = tmp.path().join("registry.json");
+    write_registry_override(
+        &registry_file,
+        serde_json::json!([
+          {
+            "name": "oauth-demo",
+            "displayName": "OAuth Demo",
+            "description": "Server with an OAuth flow",
+            "version": "0.1.0",
+            "source": {
+              "type": "npm",
+              "package": "oauth-demo",
+              "repository": "https://example.com/oauth-demo"
+            },
+            "runtime": { "type": "node", "command": "npx", "args": ["-y", "oauth-demo"] },
+            "transport": "stdio",
+            "permissions": {
+              "network": ["*.example.com:443"],
+              "env": ["DEMO_ACCESS_TOKEN"],
+              "filesystem": [],
+              "exec": []
+            },
+            "config": {
+              "required": [{ "key": "client-id", "description": "OAuth client ID" }],
+              "optional": []
+            },
+            "compatibility": { "clients": ["generic"], "platforms": ["linux", "macos", "windows"] },
+            "quality": {
+              "securityScan": "pass",
+              "healthCheck": true,
+              "lastVerified": "2026-02-21",
+              "downloads": 1
+            },
+            "category": "productivity",
+            "tags": ["test"],
+            "maintainer": "Test",
+            "trustLevel": "community",
+            "oauth": {
+              "authorizeUrl": "https://accounts.example.com/auth",
+              "tokenUrl": token_url,
+              "scopes": ["files.read"],
+              "clientIdKey": "client-id",
+              "env": "DEMO_ACCESS_TOKEN"
+            }
+          }
+        ]),
+    );
+    let berth_oauth = || {
+        let mut cmd = berth_with_home(tmp.path());
+        cmd.env("BERTH_REGISTRY_INDEX_FILE", &registry_file)
+            .env("BERTH_SECRET_BACKEND", "file");
+        cmd
+    };
+
+    let unconfigured = berth_oauth()
+        .args(["install", "oauth-demo"])
+        .output()
+        .unwrap();
+    assert!(unconfigured.status.success());
+    let output = berth_oauth().args(["auth", "oauth-demo"]).output().unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("client-id=<id>"));
+    berth_oauth()
+        .args(["config", "oauth-demo", "--set", "client-id=demo-app"])
+        .output()
+        .unwrap();
+
+    let mut child = berth_oauth()
+        .args(["auth", "oauth-demo", "--no-browser"])
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut lines = BufReader::new(child.stdout.take().unwrap()).lines();
+    let url = lines
+        .by_ref()
+        .map(Result::unwrap)
+        .find(|line| line.contains("redirect_uri="))
+        .unwrap();
+    let param = |name: &str| {
+        let start = url.find(&format!("{name}=")).unwrap() + name.len() + 1;
+        url[start..].split('&').next().unwrap().to_string()
+    };
+    assert!(param("client_id").contains("demo-app"));
+    let port = param("redirect_uri")
+        .trim_start_matches("http%3A%2F%2F127.0.0.1%3A")
+        .split('%')
+        .next()
+        .unwrap()
+        .to_string();
+    let (status, _) = http_get(
+        &format!("127.0.0.1:{port}"),
+        &format!("/callback?code=c0de&state={}", param("state")),
+    );
+    assert_eq!(status, 200);
+    let rest: Vec<String> = lines.map(Result::unwrap).collect();
+    assert!(child.wait().unwrap().success());
+    assert!(rest.iter().any(|line| line.contains("Authorized")));
+
+    let config =
+        std::fs::read_to_string(tmp.path().join(".berth/servers/oauth-demo.toml")).unwrap();
+    assert!(config.contains("secret://oauth-demo/berth.oauth-refresh-token"));
+    assert!(!config.contains("r-token"));
+
+    patch_runtime_to_print_env_var(tmp.path(), "oauth-demo", "DEMO_ACCESS_TOKEN");
+    let proxy = berth_oauth()
+        .args(["proxy", "oauth-demo"])
+        .output()
+        .unwrap();
+    assert!(proxy.status.success());
+    assert!(String::from_utf8_lossy(&proxy.stdout).contains("env-present"));
+
+    let requests = token_server.join().unwrap();
+    assert!(requests[0].contains("grant_type=authorization_code"));
+    assert!(requests[0].contains("code=c0de"));
+    assert!(requests[1].contains("grant_type=refresh_token"));
+    assert!(requests[1].contains("refresh_token=r-token"));
+
+    let config = berth_oauth()
+        .args(["config", "oauth-demo"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&config.stdout);
+    assert!(stdout.contains("Credentials:"));
+    assert!(stdout.contains("expires in"));
+}
+
+// --- runtime lifecycle ---
+
+#[test]
+fn start_not_installed_exits_1() {
+    let tmp = tempfile::tempdir().unwrap();
+    let output = berth_with_home(tmp.path())
+        .args(["start", "github"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("not installed"));
+}
+
+#[test]
+fn start_dry_run_prints_plan_without_side_effects() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
+    patch_runtime_to_long_running(tmp.path(), "github");
+
+    let output = berth_with_home(tmp.path())
+        .args(["start", "github", "--dry-run"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Dry run"));
+    assert!(stdout.contains("would start"));
+    assert!(stdout.contains("env: GITHUB_TOKEN"));
+    assert!(!stdout.contains("abc123"));
+    assert!(stdout.contains("permission network: api.github.com:443"));
+    assert!(stdout.contains("runtime/github.toml"));
+    assert!(!tmp.path().join(".berth/runtime").exists());
+    // Only the install is audited.
+    let audit =
+        std::fs::read_to_string(tmp.path().join(".berth/audit/audit.jsonl")).unwrap_or_default();
+    assert_eq!(audit.lines().count(), 1);
+    assert!(audit.contains("\"action\":\"install\""));
+}
+
+#[test]
+fn start_dry_run_reports_policy_denial_without_audit() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
+    write_global_policy(tmp.path(), "[servers]\ndeny = [\"github\"]\n");
+
+    let output = berth_with_home(tmp.path())
+        .args(["start", "github", "--dry-run"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Policy denied"));
+    let audit =
+        std::fs::read_to_string(tmp.path().join(".berth/audit/audit.jsonl")).unwrap_or_default();
+    assert!(!audit.contains("policy-denied"));
+}
+
+#[test]
+fn start_failures_print_remediation_hints() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
+
+    let config_path = tmp.path().join(".berth/servers/github.toml");
+    let content = std::fs::read_to_string(&config_path).unwrap();
+    let mut value: toml::Value = toml::from_str(&content).unwrap();
+    value["runtime"].as_table_mut().unwrap().insert(
+        "command".to_string(),
+        toml::Value::String("berth-test-missing-command".to_string()),
+    );
+    std::fs::write(&config_path, toml::to_string(&value).unwrap()).unwrap();
+
+    let output = berth_with_home(tmp.path())
+        .args(["start", "github"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("berth-test-missing-command was not found"),
+        "{stderr}"
+    );
+
+    write_global_policy(tmp.path(), "[servers]\ndeny = [\"github\"]\n");
+    let output = berth_with_home(tmp.path())
+        .args(["start", "github"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Policy denied"));
+    assert!(stderr.contains("berth policy github"), "{stderr}");
+}
+
+#[test]
+fn stop_dry_run_leaves_running_server_untouched() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
+    patch_runtime_to_long_running(tmp.path(), "github");
+    let start = berth_with_home(tmp.path())
+        .args(["start", "github"])
+        .output()
+        .unwrap();
+    assert!(start.status.success());
+
+    let dry = berth_with_home(tmp.path())
+        .args(["stop", "github", "--dry-run"])
+        .output()
+        .unwrap();
+    assert!(dry.status.success());
+    let stdout = String::from_utf8_lossy(&dry.stdout);
+    assert!(stdout.contains("would stop"));
+
+    let status = berth_with_home(tmp.path())
+        .args(["status"])
+        .output()
+        .unwrap();
+    assert!(String::from_utf8_lossy(&status.stdout).contains("running"));
+
+    berth_with_home(tmp.path())
+        .args(["stop", "github"])
+        .output()
+        .unwrap();
+}
+
+#[test]
+fn show_command_prints_shell_safe_invocation_without_secrets() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
+    patch_runtime_to_long_running(tmp.path(), "github");
+
+    let output = berth_with_home(tmp.path())
+        .args(["show-command", "github"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("# cwd: "));
+    assert!(stdout.contains("# env: GITHUB_TOKEN"));
+    assert!(stdout.contains("# sandbox: off (wrapper: none)"));
+    assert!(stdout.contains("# env isolation: on"));
+    #[cfg(unix)]
+    {
+        assert!(stdout.contains("env -i GITHUB_TOKEN=\"$GITHUB_TOKEN\" "));
+        assert!(stdout.contains("PATH=\"$PATH\""));
+        assert!(stdout.contains(" sh -c 'sleep 60'"));
+    }
+    assert!(!stdout.contains("abc123"));
+    assert!(!tmp.path().join(".berth/runtime").exists());
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn run_as_user_is_validated_and_shown_in_launch_preview() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
+
+    let root = berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "berth.run-as=root"])
+        .output()
+        .unwrap();
+    assert!(!root.status.success());
+    let set = berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "berth.run-as=mcp-github"])
+        .output()
+        .unwrap();
+    assert!(set.status.success());
+
+    let output = berth_with_home(tmp.path())
+        .args(["show-command", "github"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("# run as: mcp-github"));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn seccomp_profile_is_validated_and_shown_in_launch_preview() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
+
+    let strict = berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "berth.seccomp=strict"])
+        .output()
+        .unwrap();
+    assert!(!strict.status.success());
+    let set = berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "berth.seccomp=default"])
+        .output()
+        .unwrap();
+    assert!(set.status.success());
+
+    let output = berth_with_home(tmp.path())
+        .args(["show-command", "github"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("# seccomp: blocks "));
+    assert!(stdout.contains("ptrace"));
+}
+
+#[test]
+fn show_command_not_installed_exits_1() {
+    let tmp = tempfile::tempdir().unwrap();
+    let output = berth_with_home(tmp.path())
+        .args(["show-command", "github"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("not installed"));
+}
+
+#[test]
+fn start_requires_config_before_running() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+
+    let output = berth_with_home(tmp.path())
+        .args(["start", "github"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Missing required config"));
+}
+
+#[test]
+fn start_blocks_when_sandbox_network_deny_all_and_audits() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "berth.sandbox=basic"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args([
+            "config",
+            "github",
+            "--set",
+            "berth.sandbox-network=deny-all",
+        ])
+        .output()
+        .unwrap();
+
+    let start = berth_with_home(tmp.path())
+        .args(["start", "github"])
+        .output()
+        .unwrap();
+    assert!(!start.status.success());
+    let stderr = String::from_utf8_lossy(&start.stderr);
+    assert!(stderr.contains("blocked by sandbox policy"));
+
+    let audit = berth_with_home(tmp.path())
+        .args(["audit", "github"])
+        .output()
+        .unwrap();
+    assert!(audit.status.success());
+    let audit_out = String::from_utf8_lossy(&audit.stdout);
+    assert!(audit_out.contains("permission-network-denied"));
+}
+
+#[test]
+fn start_blocks_when_org_policy_denies_network_wildcard_and_audits() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["permissions", "github", "--grant", "network:*"])
+        .output()
+        .unwrap();
+    write_global_policy(
+        tmp.path(),
+        r#"
+[permissions]
+deny_network_wildcard = true
+"#,
+    );
+
+    let start = berth_with_home(tmp.path())
+        .args(["start", "github"])
+        .output()
+        .unwrap();
+    assert!(!start.status.success());
+    let stderr = String::from_utf8_lossy(&start.stderr);
+    assert!(stderr.contains("Policy denied"));
+    assert!(stderr.contains("network wildcard"));
+
+    let audit = berth_with_home(tmp.path())
+        .args(["audit", "github"])
+        .output()
+        .unwrap();
+    assert!(audit.status.success());
+    let audit_out = String::from_utf8_lossy(&audit.stdout);
+    assert!(audit_out.contains("policy-denied"));
+}
+
+#[test]
+fn proxy_sets_sandbox_env_when_basic_enabled() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "berth.sandbox=basic"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "berth.sandbox-network=inherit"])
+        .output()
+        .unwrap();
+    patch_runtime_to_print_env_var(tmp.path(), "github", "BERTH_SANDBOX_MODE");
+
+    let output = berth_with_home(tmp.path())
+        .args(["proxy", "github"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("env-present"));
+}
+
+#[cfg(unix)]
+#[test]
+fn sandbox_scopes_effective_filesystem_permissions() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "berth.sandbox=basic"])
+        .output()
+        .unwrap();
+    let grant = berth_with_home(tmp.path())
+        .args([
+            "permissions",
+            "github",
+            "--grant",
+            "filesystem:read:/srv/shared",
+        ])
+        .output()
+        .unwrap();
+    assert!(grant.status.success());
+
+    let dry_run = berth_with_home(tmp.path())
+        .args(["start", "github", "--dry-run"])
+        .output()
+        .unwrap();
+    assert!(String::from_utf8_lossy(&dry_run.stdout).contains("policy sandbox: basic ("));
+
+    patch_runtime_to_script(tmp.path(), "github", "echo \"read=$BERTH_SANDBOX_FS_READ\"");
+    let output = berth_with_home(tmp.path())
+        .args(["proxy", "github"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let read = stdout
+        .lines()
+        .find_map(|line| line.strip_prefix("read="))
+        .unwrap_or_default();
+    assert!(
+        read.split(':').any(|path| path == "/srv/shared"),
+        "{stdout}"
+    );
+}
+
+#[cfg(all(
+    target_os = "linux",
+    any(target_arch = "x86_64", target_arch = "aarch64")
+))]
+#[test]
+fn seccomp_filter_is_installed_by_bwrap_in_the_basic_sandbox() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    for setting in [
+        "token=abc123",
+        "berth.sandbox=basic",
+        "berth.sandbox-network=inherit",
+        "berth.seccomp=default",
+    ] {
+        let set = berth_with_home(tmp.path())
+            .args(["config", "github", "--set", setting])
+            .output()
+            .unwrap();
+        assert!(set.status.success());
+    }
+    patch_runtime_to_script(tmp.path(), "github", "echo server-ran");
+
+    // A stand-in bubblewrap that passes the usability probe, reports how it
+    // was launched, then runs the command after `--` like the real one.
+    let bin = tmp.path().join("bin");
+    std::fs::create_dir_all(&bin).unwrap();
+    std::fs::write(
+        bin.join("bwrap"),
+        "#!/bin/sh\n[ \"$1\" = --ro-bind ] && exit 0\n\
+         echo \"bwrap-flag=$1\"\ngrep '^Seccomp:' /proc/self/status\n\
+         while [ \"$1\" != -- ]; do shift; done\nshift\nexec \"$@\"\n",
+    )
+    .unwrap();
+    std::fs::set_permissions(bin.join("bwrap"), std::fs::Permissions::from_mode(0o755)).unwrap();
+    let path = format!(
+        "{}:{}",
+        bin.display(),
+        std::env::var("PATH").unwrap_or_default()
+    );
+
+    let output = berth_with_home(tmp.path())
+        .env("PATH", path)
+        .args(["proxy", "github"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{stdout}");
+    assert!(stdout.contains("bwrap-flag=--seccomp"), "{stdout}");
+    assert!(stdout.contains("Seccomp:\t0"), "{stdout}");
+    assert!(stdout.contains("server-ran"), "{stdout}");
+}
+
+#[test]
+fn start_warns_on_undeclared_network_grant_and_audits() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args([
+            "permissions",
+            "github",
+            "--grant",
+            "network:example.com:443",
+        ])
+        .output()
+        .unwrap();
+    patch_runtime_to_long_running(tmp.path(), "github");
+
+    let start = berth_with_home(tmp.path())
+        .args(["start", "github"])
+        .output()
+        .unwrap();
+    assert!(start.status.success());
+    let stdout = String::from_utf8_lossy(&start.stdout);
+    assert!(stdout.contains("undeclared network grant override"));
+
+    let audit = berth_with_home(tmp.path())
+        .args(["audit", "github"])
+        .output()
+        .unwrap();
+    assert!(audit.status.success());
+    let audit_out = String::from_utf8_lossy(&audit.stdout);
+    assert!(audit_out.contains("permission-network-warning"));
+
+    let stop = berth_with_home(tmp.path())
+        .args(["stop", "github"])
+        .output()
+        .unwrap();
+    assert!(stop.status.success());
+}
+
+#[test]
+fn start_then_status_shows_running() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
+    patch_runtime_to_long_running(tmp.path(), "github");
+
+    let start = berth_with_home(tmp.path())
+        .args(["start", "github"])
+        .output()
+        .unwrap();
+    assert!(start.status.success());
+
+    let status = berth_with_home(tmp.path())
+        .args(["status"])
+        .output()
+        .unwrap();
+    assert!(status.status.success());
+    let stdout = String::from_utf8_lossy(&status.stdout);
+    assert!(stdout.contains("github"));
+    assert!(stdout.contains("running"));
+}
+
+#[test]
+fn status_summary_file_counts_running_servers() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
+    patch_runtime_to_long_running(tmp.path(), "github");
+    berth_with_home(tmp.path())
+        .args(["start", "github"])
+        .output()
+        .unwrap();
+
+    let output = berth_with_home(tmp.path())
+        .args(["status", "--summary-file"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    assert_eq!(
+        std::path::Path::new(&path),
+        tmp.path().join(".berth/status-summary.json")
+    );
+    let summary: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(summary["version"], 1);
+    assert_eq!(summary["running"], 1);
+    assert_eq!(summary["servers"]["github"], "running");
+
+    berth_with_home(tmp.path())
+        .args(["stop", "github"])
+        .output()
+        .unwrap();
+    let summary: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(summary["running"], 0);
+}
+
+#[cfg(unix)]
+#[test]
+fn manifest_limits_apply_to_started_server_and_show_in_status() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
+    let config_path = tmp.path().join(".berth/servers/github.toml");
+    let mut value: toml::Value =
+        toml::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    let runtime = value
+        .get_mut("runtime")
+        .and_then(toml::Value::as_table_mut)
+        .unwrap();
+    runtime.insert("command".to_string(), toml::Value::String("sh".to_string()));
+    runtime.insert(
+        "args".to_string(),
+        toml::Value::Array(vec![
+            toml::Value::String("-c".to_string()),
+            toml::Value::String("echo \"nofile=$(ulimit -n)\"; sleep 30".to_string()),
+        ]),
+    );
+    runtime.insert(
+        "limits".to_string(),
+        toml::Value::Table(toml::map::Map::from_iter([(
+            "maxOpenFiles".to_string(),
+            toml::Value::Integer(64),
+        )])),
+    );
+    std::fs::write(&config_path, toml::to_string_pretty(&value).unwrap()).unwrap();
+
+    let start = berth_with_home(tmp.path())
+        .args(["start", "github"])
+        .output()
+        .unwrap();
+    assert!(start.status.success());
+
+    let mut logged = String::new();
+    for _ in 0..50 {
+        logged = berth_with_home(tmp.path())
+            .args(["logs", "github"])
+            .output()
+            .map(|o| String::from_utf8_lossy(&o.stdout).to_string())
+            .unwrap();
+        if logged.contains("nofile=") {
+            break;
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+    assert!(logged.contains("nofile=64"), "{logged}");
+
+    let status = berth_with_home(tmp.path())
+        .args(["status"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&status.stdout);
+    assert!(stdout.contains("LIMITS"));
+    assert!(stdout.contains("/64"), "{stdout}");
+
+    let json = berth_with_home(tmp.path())
+        .args(["status", "--json"])
+        .output()
+        .unwrap();
+    let rows: serde_json::Value = serde_json::from_slice(&json.stdout).unwrap();
+    assert_eq!(rows[0]["limits"]["maxOpenFiles"], 64);
+
+    berth_with_home(tmp.path())
+        .args(["stop", "github"])
+        .output()
+        .unwrap();
+}
+
+#[test]
+fn status_shows_pid_for_running_server() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
+    patch_runtime_to_long_running(tmp.path(), "github");
+    berth_with_home(tmp.path())
+        .args(["start", "github"])
+        .output()
+        .unwrap();
+
+    let state_path = tmp.path().join(".berth/runtime/github.toml");
+    let state_content = std::fs::read_to_string(state_path).unwrap();
+    let state: toml::Value = toml::from_str(&state_content).unwrap();
+    let pid = state["pid"].as_integer().unwrap().to_string();
+
+    let status = berth_with_home(tmp.path())
+        .args(["status"])
+        .output()
+        .unwrap();
+    assert!(status.status.success());
+    let stdout = String::from_utf8_lossy(&status.stdout);
+    assert!(stdout.contains("PID"));
+    assert!(stdout.contains("MEMORY"));
+    assert!(stdout.contains(&pid));
+}
+
+#[test]
+fn stop_after_start_shows_stopped() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
+    patch_runtime_to_long_running(tmp.path(), "github");
+    berth_with_home(tmp.path())
+        .args(["start", "github"])
+        .output()
+        .unwrap();
+
+    let stop = berth_with_home(tmp.path())
+        .args(["stop", "github"])
+        .output()
+        .unwrap();
+    assert!(stop.status.success());
+
+    let status = berth_with_home(tmp.path())
+        .args(["status"])
+        .output()
+        .unwrap();
+    assert!(status.status.success());
+    let stdout = String::from_utf8_lossy(&status.stdout);
+    assert!(stdout.contains("stopped"));
+}
+
+#[test]
+fn status_malformed_runtime_state_exits_1() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    let runtime_dir = tmp.path().join(".berth/runtime");
+    std::fs::create_dir_all(&runtime_dir).unwrap();
+    std::fs::write(runtime_dir.join("github.toml"), "not = [valid").unwrap();
+
+    let status = berth_with_home(tmp.path())
+        .args(["status"])
+        .output()
+        .unwrap();
+    assert!(!status.status.success());
+    let stdout = String::from_utf8_lossy(&status.stdout);
+    assert!(stdout.contains("error"));
+}
+
+#[test]
+fn restart_sets_running_state() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
+    patch_runtime_to_long_running(tmp.path(), "github");
+
+    let restart = berth_with_home(tmp.path())
+        .args(["restart", "github"])
+        .output()
+        .unwrap();
+    assert!(restart.status.success());
+
+    let status = berth_with_home(tmp.path())
+        .args(["status"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&status.stdout);
+    assert!(stdout.contains("running"));
+}
+
+#[cfg(unix)]
+fn patch_runtime_to_script(tmp: &std::path::Path, server: &str, script: &str) {
+    let config_path = tmp.join(".berth/servers").join(format!("{server}.toml"));
+    let content = std::fs::read_to_string(&config_path).unwrap();
+    let mut value: toml::Value = toml::from_str(&content).unwrap();
+    let runtime = value
+        .get_mut("runtime")
+        .and_then(toml::Value::as_table_mut)
+        .unwrap();
+    runtime.insert("command".to_string(), toml::Value::String("sh".to_string()));
+    runtime.insert(
+        "args".to_string(),
+        toml::Value::Array(vec![
+            toml::Value::String("-c".to_string()),
+            toml::Value::String(script.to_string()),
+        ]),
+    );
+    std::fs::write(config_path, toml::to_string(&value).unwrap()).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn permissions_usage_flags_hosts_outside_declared_network() {
+    if Command::new("python3").arg("--version").output().is_err() {
+        return;
+    }
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
+
+    let empty = berth_with_home(tmp.path())
+        .args(["permissions", "github", "--usage"])
+        .output()
+        .unwrap();
+    assert!(empty.status.success());
+    assert!(String::from_utf8_lossy(&empty.stdout).contains("berth.egress-monitor=log"));
+
+    let set = berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "berth.egress-monitor=log"])
+        .output()
+        .unwrap();
+    assert!(set.status.success());
+    patch_runtime_to_script(
+        tmp.path(),
+        "github",
+        "python3 -c \"import socket\nfor host in ('api.github.com', 'exfil.invalid'):\n  try: socket.getaddrinfo(host, 443)\n  except OSError: pass\"; sleep 60",
+    );
+    let start = berth_with_home(tmp.path())
+        .args(["start", "github"])
+        .output()
+        .unwrap();
+    assert!(start.status.success());
+
+    let log = tmp.path().join(".berth/egress/github.jsonl");
+    let deadline = std::time::Instant::now() + Duration::from_secs(10);
+    while !std::fs::read_to_string(&log).is_ok_and(|c| c.contains("exfil.invalid")) {
+        assert!(std::time::Instant::now() < deadline, "no egress recorded");
+        thread::sleep(Duration::from_millis(100));
+    }
+    berth_with_home(tmp.path())
+        .args(["stop", "github"])
+        .output()
+        .unwrap();
+
+    let usage = berth_with_home(tmp.path())
+        .args(["permissions", "github", "--usage"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&usage.stdout);
+    assert!(usage.status.success());
+    assert!(stdout.contains("api.github.com:443"));
+    assert!(stdout.contains("exfil.invalid:443"));
+    assert!(stdout.contains("1 destination(s) not covered"));
+}
+
+#[cfg(unix)]
+#[test]
+fn sandbox_network_allowlist_blocks_undeclared_hosts() {
+    if Command::new("python3").arg("--version").output().is_err() {
+        return;
+    }
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
+    let set = berth_with_home(tmp.path())
+        .args([
+            "config",
+            "github",
+            "--set",
+            "berth.sandbox-network=allowlist",
+        ])
+        .output()
+        .unwrap();
+    assert!(set.status.success());
+    let marker = tmp.path().join("outcome.txt");
+    patch_runtime_to_script(
+        tmp.path(),
+        "github",
+        &format!(
+            "python3 -c \"import os, socket\nout = open('{}', 'w')\nout.write(os.environ.get('HTTPS_PROXY', '') + '\\n')\ntry: socket.getaddrinfo('exfil.invalid', 443)\nexcept PermissionError: out.write('blocked\\n')\nout.close()\"; sleep 60",
+            marker.display()
+        ),
+    );
+    let start = berth_with_home(tmp.path())
+        .args(["start", "github"])
+        .output()
+        .unwrap();
+    assert!(
+        start.status.success(),
+        "{}",
+        String::from_utf8_lossy(&start.stderr)
+    );
+
+    let deadline = std::time::Instant::now() + Duration::from_secs(10);
+    while !std::fs::read_to_string(&marker)
+        .is_ok_and(|c| c.ends_with("\n") && c.lines().count() == 2)
+    {
+        assert!(
+            std::time::Instant::now() < deadline,
+            "script did not finish"
+        );
+        thread::sleep(Duration::from_millis(100));
+    }
+    berth_with_home(tmp.path())
+        .args(["stop", "github"])
+        .output()
+        .unwrap();
+
+    let outcome = std::fs::read_to_string(&marker).unwrap();
+    assert!(outcome.starts_with("http://127.0.0.1:"), "{outcome}");
+    assert!(outcome.contains("blocked"), "{outcome}");
+    let log = std::fs::read_to_string(tmp.path().join(".berth/egress/github.jsonl")).unwrap();
+    assert!(log
+        .lines()
+        .any(|l| l.contains("exfil.invalid") && l.contains("\"blocked\":true")));
+
+    let usage = berth_with_home(tmp.path())
+        .args(["permissions", "github", "--usage"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&usage.stdout);
+    assert!(stdout
+        .lines()
+        .any(|l| l.contains("exfil.invalid:443") && l.contains("blocked")));
+}
+
+#[cfg(unix)]
+#[test]
+fn start_waits_for_readiness_log_line_before_reporting_running() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
+    let set = berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "berth.readiness=log:listening"])
+        .output()
+        .unwrap();
+    assert!(set.status.success());
+    patch_runtime_to_script(tmp.path(), "github", "sleep 0.5; echo listening; sleep 60");
+
+    let start = berth_with_home(tmp.path())
+        .args(["start", "github"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&start.stdout);
+    assert!(start.status.success());
+    assert!(stdout.contains("is ready after"));
+
+    let status = berth_with_home(tmp.path())
+        .args(["status"])
+        .output()
+        .unwrap();
+    assert!(String::from_utf8_lossy(&status.stdout).contains("running"));
+    berth_with_home(tmp.path())
+        .args(["stop", "github"])
+        .output()
+        .unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn start_stops_server_that_misses_readiness_timeout() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "berth.readiness=log:listening"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args([
+            "config",
+            "github",
+            "--set",
+            "berth.readiness-timeout-ms=300",
+        ])
+        .output()
+        .unwrap();
+    patch_runtime_to_long_running(tmp.path(), "github");
+
+    let start = berth_with_home(tmp.path())
+        .args(["start", "github"])
+        .output()
+        .unwrap();
+    assert!(!start.status.success());
+    assert!(String::from_utf8_lossy(&start.stderr).contains("did not become ready"));
+
+    let status = berth_with_home(tmp.path())
+        .args(["status"])
+        .output()
+        .unwrap();
+    assert!(String::from_utf8_lossy(&status.stdout).contains("stopped"));
+}
+
+#[cfg(unix)]
+#[test]
+fn status_reports_servers_failing_their_health_check() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
+    patch_runtime_to_long_running(tmp.path(), "github");
+    let config_path = tmp.path().join(".berth/servers/github.toml");
+    let mut value: toml::Value =
+        toml::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    let check: toml::Value = toml::from_str(
+        "probe = \"exec\"\ncommand = \"false\"\nintervalSecs = 1\nfailureThreshold = 1\n",
+    )
+    .unwrap();
+    value["runtime"]
+        .as_table_mut()
+        .unwrap()
+        .insert("health_check".to_string(), check);
+    std::fs::write(&config_path, toml::to_string(&value).unwrap()).unwrap();
+
+    let dry_run = berth_with_home(tmp.path())
+        .args(["start", "github", "--dry-run"])
+        .output()
+        .unwrap();
+    assert!(String::from_utf8_lossy(&dry_run.stdout).contains("`false` succeeds every 1s"));
+    let start = berth_with_home(tmp.path())
+        .args(["start", "github"])
+        .output()
+        .unwrap();
+    assert!(start.status.success());
+
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+    let stdout = loop {
+        let status = berth_with_home(tmp.path())
+            .args(["status"])
+            .output()
+            .unwrap();
+        let stdout = String::from_utf8_lossy(&status.stdout).into_owned();
+        if stdout.contains("unhealthy") || std::time::Instant::now() > deadline {
+            break stdout;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(200));
+    };
+    berth_with_home(tmp.path())
+        .args(["stop", "github"])
+        .output()
+        .unwrap();
+    assert!(
+        stdout.contains("keeps failing its health check"),
+        "{stdout}"
+    );
+    let history = berth_with_home(tmp.path())
+        .args(["history", "github"])
+        .output()
+        .unwrap();
+    assert!(String::from_utf8_lossy(&history.stdout).contains("failed its health check"));
+}
+
+#[cfg(unix)]
+#[test]
+fn start_all_follows_dependencies_and_waits_for_health() {
+    let tmp = tempfile::tempdir().unwrap();
+    for server in ["github", "sequential-thinking"] {
+        berth_with_home(tmp.path())
+            .args(["install", server])
+            .output()
+            .unwrap();
+        patch_runtime_to_long_running(tmp.path(), server);
+    }
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
+    let set = berth_with_home(tmp.path())
+        .args([
+            "config",
+            "github",
+            "--set",
+            "berth.depends-on=sequential-thinking",
+        ])
+        .output()
+        .unwrap();
+    assert!(set.status.success());
+    let set_health_check = |command: &str| {
+        let config_path = tmp.path().join(".berth/servers/sequential-thinking.toml");
+        let mut value: toml::Value =
+            toml::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+        let check: toml::Value = toml::from_str(&format!(
+            "probe = \"exec\"\ncommand = \"{command}\"\nintervalSecs = 1\nfailureThreshold = 1\n"
+        ))
+        .unwrap();
+        value["runtime"]
+            .as_table_mut()
+            .unwrap()
+            .insert("health_check".to_string(), check);
+        std::fs::write(&config_path, toml::to_string(&value).unwrap()).unwrap();
+    };
+
+    set_health_check("false");
+    let failing = berth_with_home(tmp.path())
+        .args(["start"])
+        .output()
+        .unwrap();
+    assert!(!failing.status.success());
+    let stderr = String::from_utf8_lossy(&failing.stderr);
+    assert!(stderr.contains("is unhealthy"), "{stderr}");
+    assert!(stderr.contains("Skipped github: its dependency sequential-thinking did not start."));
+    berth_with_home(tmp.path()).args(["stop"]).output().unwrap();
+
+    set_health_check("true");
+    let start = berth_with_home(tmp.path())
+        .args(["start"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&start.stdout).into_owned();
+    let stop = berth_with_home(tmp.path()).args(["stop"]).output().unwrap();
+    assert!(start.status.success(), "{stdout}");
+    let started_dep = stdout.find("Started sequential-thinking").unwrap();
+    let healthy = stdout.find("sequential-thinking is healthy").unwrap();
+    let started_github = stdout.find("Started github").unwrap();
+    assert!(
+        started_dep < healthy && healthy < started_github,
+        "{stdout}"
+    );
+
+    let stdout = String::from_utf8_lossy(&stop.stdout);
+    let stopped_github = stdout.find("Stopped github").unwrap();
+    let stopped_dep = stdout.find("Stopped sequential-thinking").unwrap();
+    assert!(stopped_github < stopped_dep, "{stdout}");
+}
+
+#[test]
+fn repeated_warnings_are_rate_limited_and_can_be_snoozed() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
+    patch_runtime_to_long_running(tmp.path(), "github");
+    let start_stderr = || {
+        let start = berth_with_home(tmp.path())
+            .args(["start", "github"])
+            .output()
+            .unwrap();
+        assert!(start.status.success());
+        berth_with_home(tmp.path())
+            .args(["stop", "github"])
+            .output()
+            .unwrap();
+        String::from_utf8_lossy(&start.stderr).into_owned()
+    };
+
+    let first = start_stderr();
+    assert!(
+        first.contains("stores sensitive config in plaintext"),
+        "{first}"
+    );
+    assert!(first.contains("berth warnings snooze plaintext-secrets 7d"));
+    assert!(!start_stderr().contains("in plaintext"));
+
+    let list = berth_with_home(tmp.path())
+        .args(["warnings"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&list.stdout);
+    assert!(stdout.contains("plaintext-secrets"));
+    assert!(stdout.contains("registry-cache-age"));
+    assert!(stdout.contains("1 repeat(s) held back"), "{stdout}");
+
+    let snooze = berth_with_home(tmp.path())
+        .args(["warnings", "snooze", "plaintext-secrets", "7d"])
+        .output()
+        .unwrap();
+    assert!(snooze.status.success());
+    assert!(String::from_utf8_lossy(&snooze.stdout).contains("Snoozed plaintext-secrets until"));
+    let list = berth_with_home(tmp.path())
+        .args(["warnings", "list"])
+        .output()
+        .unwrap();
+    assert!(String::from_utf8_lossy(&list.stdout).contains("snoozed until"));
+
+    let unknown = berth_with_home(tmp.path())
+        .args(["warnings", "snooze", "nope", "7d"])
+        .output()
+        .unwrap();
+    assert!(!unknown.status.success());
+    assert!(String::from_utf8_lossy(&unknown.stderr).contains("Unknown warning ID `nope`"));
+    let invalid = berth_with_home(tmp.path())
+        .args(["warnings", "snooze", "plaintext-secrets", "soon"])
+        .output()
+        .unwrap();
+    assert!(String::from_utf8_lossy(&invalid.stderr).contains("Invalid duration `soon`"));
+
+    let unsnooze = berth_with_home(tmp.path())
+        .args(["warnings", "unsnooze", "plaintext-secrets"])
+        .output()
+        .unwrap();
+    assert!(String::from_utf8_lossy(&unsnooze.stdout).contains("no longer snoozed"));
+}
+
+#[cfg(unix)]
+#[test]
+fn daemon_supervises_started_servers_and_answers_lifecycle_commands() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
+    patch_runtime_to_long_running(tmp.path(), "github");
+    let daemon_status = || {
+        let output = berth_with_home(tmp.path())
+            .args(["daemon", "status"])
+            .output()
+            .unwrap();
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+    assert!(daemon_status().contains("No daemon runs"));
+
+    let started = berth_with_home(tmp.path())
+        .args(["daemon", "start"])
+        .output()
+        .unwrap();
+    assert!(
+        started.status.success(),
+        "{}",
+        String::from_utf8_lossy(&started.stderr)
+    );
+    assert!(String::from_utf8_lossy(&started.stdout).contains("Started the Berth daemon"));
+    assert!(daemon_status().contains("Supervising no servers."));
+
+    let start = berth_with_home(tmp.path())
+        .args(["start", "github"])
+        .output()
+        .unwrap();
+    assert!(
+        start.status.success(),
+        "{}",
+        String::from_utf8_lossy(&start.stderr)
+    );
+    assert!(daemon_status().contains("Supervising github."));
+    let status = berth_with_home(tmp.path())
+        .args(["status"])
+        .output()
+        .unwrap();
+    assert!(String::from_utf8_lossy(&status.stdout).contains("running"));
+    let logs = berth_with_home(tmp.path())
+        .args(["logs", "github"])
+        .output()
+        .unwrap();
+    assert!(String::from_utf8_lossy(&logs.stdout).contains("START"));
+
+    let stop = berth_with_home(tmp.path())
+        .args(["stop", "github"])
+        .output()
+        .unwrap();
+    assert!(String::from_utf8_lossy(&stop.stdout).contains("Stopped github."));
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+    while !daemon_status().contains("Supervising no servers.") {
+        assert!(std::time::Instant::now() < deadline, "{}", daemon_status());
+        std::thread::sleep(std::time::Duration::from_millis(50));
+    }
+
+    berth_with_home(tmp.path())
+        .args(["start", "github"])
+        .output()
+        .unwrap();
+    let shutdown = berth_with_home(tmp.path())
+        .args(["daemon", "stop"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&shutdown.stdout);
+    assert!(stdout.contains("Stopped github."), "{stdout}");
+    assert!(stdout.contains("Stopped the Berth daemon."));
+    let status = berth_with_home(tmp.path())
+        .args(["status"])
+        .output()
+        .unwrap();
+    assert!(String::from_utf8_lossy(&status.stdout).contains("stopped"));
+    assert!(daemon_status().contains("No daemon runs"));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn adopt_finds_external_server_captures_output_and_stops_it() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    let script = format!(
+        ": {}; while true; do echo tick; sleep 0.1; done",
+        tmp.path().display()
+    );
+    patch_runtime_to_script(tmp.path(), "github", &script);
+    let output = tmp.path().join("external.log");
+    let mut external = Command::new("sh")
+        .args(["-c", &script])
+        .stdout(std::fs::File::create(&output).unwrap())
+        .spawn()
+        .unwrap();
+    let pid = external.id();
+    while !std::fs::read_to_string(format!("/proc/{pid}/cmdline"))
+        .unwrap_or_default()
+        .contains("tick")
+    {
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+
+    let adopt = berth_with_home(tmp.path())
+        .args(["adopt", "github"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&adopt.stdout);
+    assert!(adopt.status.success(), "{stdout}");
+    assert!(stdout.contains(&format!("Adopted github (pid {pid})")));
+    assert!(stdout.contains("Capturing output from"));
+
+    let again = berth_with_home(tmp.path())
+        .args(["adopt", "github", "--pid", &pid.to_string()])
+        .output()
+        .unwrap();
+    assert!(String::from_utf8_lossy(&again.stdout).contains("already manages"));
+
+    let status = berth_with_home(tmp.path())
+        .args(["status"])
+        .output()
+        .unwrap();
+    assert!(String::from_utf8_lossy(&status.stdout).contains("running"));
+
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+    let logs = loop {
+        let logs = berth_with_home(tmp.path())
+            .args(["logs", "github"])
+            .output()
+            .unwrap();
+        let logs = String::from_utf8_lossy(&logs.stdout).into_owned();
+        if logs.contains("tick") || std::time::Instant::now() > deadline {
+            break logs;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    };
+    let stop = berth_with_home(tmp.path())
+        .args(["stop", "github"])
+        .output()
+        .unwrap();
+    assert!(stop.status.success());
+    assert!(external.wait().is_ok());
+    assert!(logs.contains("ADOPT pid="), "{logs}");
+    assert!(logs.contains("tick"), "{logs}");
+
+    let missing = berth_with_home(tmp.path())
+        .args(["adopt", "github"])
+        .output()
+        .unwrap();
+    assert!(!missing.status.success());
+    assert!(String::from_utf8_lossy(&missing.stderr).contains("No running process looks like"));
+}
+
+#[cfg(unix)]
+#[test]
+fn scheduled_jobs_run_and_are_listed_separately() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
+    patch_runtime_to_script(tmp.path(), "github", "echo refreshed-cache");
+    let config_path = tmp.path().join(".berth/servers/github.toml");
+    let mut value: toml::Value =
+        toml::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    let runtime = value["runtime"].as_table_mut().unwrap();
+    runtime.insert("type".to_string(), toml::Value::String("job".to_string()));
+    runtime.insert(
+        "schedule".to_string(),
+        toml::Value::String("every 1s".to_string()),
+    );
+    std::fs::write(&config_path, toml::to_string(&value).unwrap()).unwrap();
+
+    let proxy = berth_with_home(tmp.path())
+        .args(["proxy", "github"])
+        .output()
+        .unwrap();
+    assert!(!proxy.status.success());
+    assert!(String::from_utf8_lossy(&proxy.stderr).contains("scheduled job"));
+
+    let start = berth_with_home(tmp.path())
+        .args(["start", "github"])
+        .output()
+        .unwrap();
+    assert!(start.status.success());
+    assert!(String::from_utf8_lossy(&start.stdout).contains("Scheduled github (every 1s)"));
+
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+    let jobs = loop {
+        let status = berth_with_home(tmp.path())
+            .args(["status", "--jobs"])
+            .output()
+            .unwrap();
+        let stdout = String::from_utf8_lossy(&status.stdout).into_owned();
+        if stdout.contains(" ok") || std::time::Instant::now() > deadline {
+            break stdout;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(200));
+    };
+    let servers = berth_with_home(tmp.path())
+        .args(["status"])
+        .output()
+        .unwrap();
+    let logs = berth_with_home(tmp.path())
+        .args(["logs", "github"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["stop", "github"])
+        .output()
+        .unwrap();
+
+    assert!(jobs.contains("every 1s") && jobs.contains(" ok"), "{jobs}");
+    let servers = String::from_utf8_lossy(&servers.stdout);
+    assert!(
+        servers.contains("1 scheduled job(s) not shown"),
+        "{servers}"
+    );
+    assert!(String::from_utf8_lossy(&logs.stdout).contains("refreshed-cache"));
+    let history = berth_with_home(tmp.path())
+        .args(["history", "github"])
+        .output()
+        .unwrap();
+    assert!(String::from_utf8_lossy(&history.stdout).contains("job ran successfully"));
+}
+
+#[test]
+fn config_set_readiness_rejects_unknown_probe() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+
+    let output = berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "berth.readiness=ping"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+}
+
+#[test]
+fn status_auto_restart_recovers_crash_when_enabled() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "berth.auto-restart=true"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "berth.max-restarts=1"])
+        .output()
+        .unwrap();
+    patch_runtime_to_fail_once_then_run(tmp.path(), "github");
+
+    berth_with_home(tmp.path())
+        .args(["start", "github"])
+        .output()
+        .unwrap();
+
+    let mut running_seen = false;
+    for _ in 0..80 {
+        let status = berth_with_home(tmp.path())
+            .args(["status"])
+            .output()
+            .unwrap();
+        assert!(status.status.success());
+        let stdout = String::from_utf8_lossy(&status.stdout);
+        if stdout.contains("running") {
+            running_seen = true;
+            break;
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+    assert!(running_seen);
+
+    let mut restart_seen = false;
+    for _ in 0..80 {
+        let audit = berth_with_home(tmp.path())
+            .args(["audit", "github", "--action", "auto-restart"])
+            .output()
+            .unwrap();
+        assert!(audit.status.success());
+        let audit_out = String::from_utf8_lossy(&audit.stdout);
+        if audit_out.contains("auto-restart") {
+            restart_seen = true;
+            break;
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+    assert!(restart_seen);
+}
+
+#[cfg(unix)]
+#[test]
+fn status_auto_restart_keeps_inherited_env() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "berth.auto-restart=true"])
+        .output()
+        .unwrap();
+    patch_runtime_to_script(
+        tmp.path(),
+        "github",
+        "echo \"parent-marker=${BERTH_TEST_PARENT_MARKER:-unset}\"",
+    );
+    // A server started with `--inherit-env` whose process has gone.
+    write_runtime_state_for_status_autorestart(tmp.path(), "github");
+    let state_path = tmp.path().join(".berth/runtime/github.toml");
+    let state = std::fs::read_to_string(&state_path).unwrap();
+    std::fs::write(&state_path, format!("{state}clean_env = false\n")).unwrap();
+
+    berth_with_home(tmp.path())
+        .env("BERTH_TEST_PARENT_MARKER", "inherited")
+        .args(["status"])
+        .output()
+        .unwrap();
+
+    let log_path = tmp.path().join(".berth/logs/github.log");
+    let mut log = String::new();
+    for _ in 0..80 {
+        log = std::fs::read_to_string(&log_path).unwrap_or_default();
+        if log.contains("parent-marker=") {
+            break;
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+    assert!(log.contains("parent-marker=inherited"), "{log}");
+}
+
+#[test]
+fn status_does_not_restart_when_org_policy_denies_server() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
+    patch_runtime_to_long_running(tmp.path(), "github");
+    write_runtime_state_for_status_autorestart(tmp.path(), "github");
+    write_global_policy(
+        tmp.path(),
+        r#"
+[servers]
+deny = ["github"]
+"#,
+    );
+
+    let status = berth_with_home(tmp.path())
+        .args(["status"])
+        .output()
+        .unwrap();
+    assert!(!status.status.success());
+
+    let audit = berth_with_home(tmp.path())
+        .args(["audit", "github", "--action", "auto-restart"])
+        .output()
+        .unwrap();
+    assert!(audit.status.success());
+    let audit_out = String::from_utf8_lossy(&audit.stdout);
+    assert!(!audit_out.contains("auto-restart"));
+}
+
+#[test]
+fn auto_restart_happens_without_status_polling() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "berth.auto-restart=true"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "berth.max-restarts=1"])
+        .output()
+        .unwrap();
+    patch_runtime_to_fail_once_then_run(tmp.path(), "github");
+
+    let start = berth_with_home(tmp.path())
+        .args(["start", "github"])
+        .output()
+        .unwrap();
+    assert!(start.status.success());
+
+    let mut saw_restart = false;
+    for _ in 0..80 {
+        let audit = berth_with_home(tmp.path())
+            .args(["audit", "github", "--action", "auto-restart"])
+            .output()
+            .unwrap();
+        if audit.status.success() && String::from_utf8_lossy(&audit.stdout).contains("auto-restart")
+        {
+            saw_restart = true;
+            break;
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+    assert!(saw_restart);
+
+    let stop = berth_with_home(tmp.path())
+        .args(["stop", "github"])
+        .output()
+        .unwrap();
+    assert!(stop.status.success());
+}
+
+#[test]
+fn auto_restart_respects_max_restarts_setting() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "berth.auto-restart=true"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "berth.max-restarts=1"])
+        .output()
+        .unwrap();
+    patch_runtime_to_fail_immediately(tmp.path(), "github");
+
+    berth_with_home(tmp.path())
+        .args(["start", "github"])
+        .output()
+        .unwrap();
+
+    let mut count = 0usize;
+    for _ in 0..80 {
+        let audit = berth_with_home(tmp.path())
+            .args(["audit", "github", "--action", "auto-restart"])
+            .output()
+            .unwrap();
+        assert!(audit.status.success());
+        let audit_out = String::from_utf8_lossy(&audit.stdout);
+        count = audit_out
+            .lines()
+            .filter(|l| l.split_whitespace().nth(1) == Some("auto-restart"))
+            .count();
+        if count >= 1 {
+            break;
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+    assert_eq!(count, 1);
+}
+
+#[test]
+fn status_reports_crash_looping_once_restart_budget_is_spent() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    for setting in [
+        "token=abc123",
+        "berth.auto-restart=true",
+        "berth.max-restarts=1",
+    ] {
+        let output = berth_with_home(tmp.path())
+            .args(["config", "github", "--set", setting])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+    }
+    patch_runtime_to_fail_immediately(tmp.path(), "github");
+    berth_with_home(tmp.path())
+        .args(["start", "github"])
+        .output()
+        .unwrap();
+
+    let mut stdout = String::new();
+    for _ in 0..80 {
+        let status = berth_with_home(tmp.path())
+            .args(["status"])
+            .output()
+            .unwrap();
+        stdout = String::from_utf8_lossy(&status.stdout).to_string();
+        if stdout.contains("crash-looping") {
+            break;
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+    assert!(stdout.contains("crash-looping"));
+    assert!(stdout.contains("restart budget ran out"));
+
+    let list = berth_with_home(tmp.path()).args(["list"]).output().unwrap();
+    assert!(String::from_utf8_lossy(&list.stdout).contains("crash-looping"));
+}
+
+#[test]
+fn auto_restart_skips_exit_codes_not_listed_as_failures() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    for setting in [
+        "token=abc123",
+        "berth.auto-restart=true",
+        "berth.restart-on-exit-codes=137",
+    ] {
+        let output = berth_with_home(tmp.path())
+            .args(["config", "github", "--set", setting])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+    }
+    patch_runtime_to_fail_immediately(tmp.path(), "github");
+
+    let start = berth_with_home(tmp.path())
+        .args(["start", "github"])
+        .output()
+        .unwrap();
+    assert!(start.status.success());
+
+    let mut clean_exit_seen = false;
+    for _ in 0..80 {
+        let logs = berth_with_home(tmp.path())
+            .args(["logs", "github", "--tail", "20"])
+            .output()
+            .unwrap();
+        if String::from_utf8_lossy(&logs.stdout).contains("CLEAN_EXIT code=1") {
+            clean_exit_seen = true;
+            break;
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+    assert!(clean_exit_seen);
+
+    let audit = berth_with_home(tmp.path())
+        .args(["audit", "github", "--action", "auto-restart"])
+        .output()
+        .unwrap();
+    assert!(!String::from_utf8_lossy(&audit.stdout).contains("auto-restart"));
+}
+
+#[test]
+fn logs_show_lifecycle_events() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
+    patch_runtime_to_long_running(tmp.path(), "github");
+    berth_with_home(tmp.path())
+        .args(["start", "github"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["stop", "github"])
+        .output()
+        .unwrap();
+
+    let logs = berth_with_home(tmp.path())
+        .args(["logs", "github", "--tail", "10"])
+        .output()
+        .unwrap();
+    assert!(logs.status.success());
+    let stdout = String::from_utf8_lossy(&logs.stdout);
+    assert!(stdout.contains("START"));
+    assert!(stdout.contains("STOP"));
+}
+
+#[test]
+fn logs_sanitize_binary_output_and_raw_exports_original_bytes() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    let log_dir = tmp.path().join(".berth/logs");
+    std::fs::create_dir_all(&log_dir).unwrap();
+    let content = b"[1] START pid=1\nbinary \xff\xfe\x1b[2J\n";
+    std::fs::write(log_dir.join("github.log"), content).unwrap();
+
+    let logs = berth_with_home(tmp.path())
+        .args(["logs", "github"])
+        .output()
+        .unwrap();
+    assert!(logs.status.success());
+    let stdout = std::str::from_utf8(&logs.stdout).unwrap();
+    assert!(
+        stdout.contains("binary \u{fffd}\u{fffd}\u{fffd}[2J"),
+        "{stdout}"
+    );
+
+    let raw = berth_with_home(tmp.path())
+        .args(["logs", "github", "--raw", "--tail", "1"])
+        .output()
+        .unwrap();
+    assert!(raw.status.success());
+    assert_eq!(raw.stdout, b"binary \xff\xfe\x1b[2J\n");
+}
+
+#[test]
+fn logs_follow_streams_appended_lines() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    let log_dir = tmp.path().join(".berth/logs");
+    std::fs::create_dir_all(&log_dir).unwrap();
+    let log_path = log_dir.join("github.log");
+    std::fs::write(&log_path, "[1] START pid=1\n").unwrap();
+
+    let mut child = berth_with_home(tmp.path())
+        .args(["logs", "github", "--follow"])
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let stdout = child.stdout.take().unwrap();
+    let (tx, rx) = std::sync::mpsc::channel();
+    thread::spawn(move || {
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            if tx.send(line).is_err() {
+                break;
+            }
+        }
+    });
+
+    let wait_for = |needle: &str| {
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while let Some(left) = deadline.checked_duration_since(std::time::Instant::now()) {
+            match rx.recv_timeout(left) {
+                Ok(line) if line.contains(needle) => return true,
+                Ok(_) => {}
+                Err(_) => return false,
+            }
+        }
+        false
+    };
+    assert!(wait_for("Following"));
+    let mut file = std::fs::OpenOptions::new()
+        .append(true)
+        .open(&log_path)
+        .unwrap();
+    writeln!(file, "[2] appended-after-follow").unwrap();
+    let streamed = wait_for("appended-after-follow");
+    std::fs::rename(&log_path, log_dir.join("github.log.1")).unwrap();
+    writeln!(file, "[3] written-to-rotated-file").unwrap();
+    std::fs::write(&log_path, "[4] written-after-rotation\n").unwrap();
+    let drained = wait_for("written-to-rotated-file");
+    let rotated = wait_for("written-after-rotation");
+    child.kill().unwrap();
+    let _ = child.wait();
+    assert!(streamed);
+    assert!(drained);
+    assert!(rotated);
+}
+
+// --- client linking ---
+
+#[test]
+fn link_claude_desktop_writes_config() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
+
+    let output = berth_with_home(tmp.path())
+        .args(["link", "claude-desktop"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let config_path = tmp
+        .path()
+        .join(".berth/clients/claude-desktop/claude_desktop_config.json");
+    assert!(config_path.exists());
+
+    let content = std::fs::read_to_string(config_path).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&content).unwrap();
+    let github = &json["mcpServers"]["github"];
+    assert_eq!(github["command"], "npx");
+    assert_eq!(github["env"]["GITHUB_TOKEN"], "abc123");
+}
+
+#[test]
+fn link_with_compat_routes_entries_through_proxy() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
+
+    let output = berth_with_home(tmp.path())
+        .args(["link", "cursor", "--compat", "2024-11-05"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let content = std::fs::read_to_string(
+        tmp.path()
+            .join(".berth/clients/cursor/cursor_mcp_config.json"),
+    )
+    .unwrap();
+    let json: serde_json::Value = serde_json::from_str(&content).unwrap();
+    let github = &json["mcpServers"]["github"];
+    assert_eq!(
+        github["args"],
+        serde_json::json!(["proxy", "github", "--client", "cursor"])
+    );
+    assert!(github["env"].get("GITHUB_TOKEN").is_none());
+
+    let compat = std::fs::read_to_string(tmp.path().join(".berth/compat.toml")).unwrap();
+    assert!(compat.contains("cursor = \"2024-11-05\""));
+}
+
+#[test]
+fn link_with_client_overrides_routes_entries_through_proxy() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
+
+    let unknown = berth_with_home(tmp.path())
+        .args(["link", "cursor", "--set", "github.nope=1"])
+        .output()
+        .unwrap();
+    assert!(!unknown.status.success());
+    assert!(String::from_utf8_lossy(&unknown.stderr).contains("Unknown config key"));
+
+    let output = berth_with_home(tmp.path())
+        .args(["link", "cursor", "--set", "github.token=cursor-token"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Overrides:"));
+
+    let content = std::fs::read_to_string(
+        tmp.path()
+            .join(".berth/clients/cursor/cursor_mcp_config.json"),
+    )
+    .unwrap();
+    let json: serde_json::Value = serde_json::from_str(&content).unwrap();
+    assert_eq!(
+        json["mcpServers"]["github"]["args"],
+        serde_json::json!(["proxy", "github", "--client", "cursor"])
+    );
+    let compat = std::fs::read_to_string(tmp.path().join(".berth/compat.toml")).unwrap();
+    assert!(compat.contains("cursor-token"));
+}
+
+#[cfg(unix)]
+#[test]
+fn proxy_applies_overrides_for_client_named_in_initialize() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["link", "cursor", "--set", "github.token=cursor-token"])
+        .output()
+        .unwrap();
+    let config_path = tmp.path().join(".berth/servers/github.toml");
+    let mut value: toml::Value =
+        toml::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    let runtime = value
+        .get_mut("runtime")
+        .and_then(toml::Value::as_table_mut)
+        .unwrap();
+    runtime.insert("command".to_string(), toml::Value::String("sh".to_string()));
+    runtime.insert(
+        "args".to_string(),
+        toml::Value::Array(vec![
+            toml::Value::String("-c".to_string()),
+            toml::Value::String("head -n 1; echo \"token=$GITHUB_TOKEN\"".to_string()),
+        ]),
+    );
+    std::fs::write(&config_path, toml::to_string_pretty(&value).unwrap()).unwrap();
+
+    let run = |client_name: &str| {
+        let mut child = berth_with_home(tmp.path())
+            .args(["proxy", "github"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let initialize = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "initialize",
+            "params": {"clientInfo": {"name": client_name, "version": "1.0"}}
+        });
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(format!("{initialize}\n").as_bytes())
+            .unwrap();
+        let output = child.wait_with_output().unwrap();
+        assert!(output.status.success());
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+
+    let cursor = run("Cursor");
+    assert!(cursor.contains("\"method\":\"initialize\""));
+    assert!(cursor.contains("token=cursor-token"));
+    let other = run("some-other-client");
+    assert!(other.contains("token=abc123"));
 }
 
-// --- uninstall ---
+#[test]
+fn link_with_invalid_compat_exits_1() {
+    let tmp = tempfile::tempdir().unwrap();
+    let output = berth_with_home(tmp.path())
+        .args(["link", "cursor", "--compat", "newest"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Invalid compat mode"));
+}
 
 #[test]
-fn uninstall_removes_config_file() {
+fn link_claude_desktop_creates_backup_when_file_exists() {
     let tmp = tempfile::tempdir().unwrap();
-    // Install first
     berth_with_home(tmp.path())
         .args(["install", "github"])
         .output()
         .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
 
-    let config_path = tmp.path().join(".berth/servers/github.toml");
-    assert!(config_path.exists());
-
-    // Uninstall
+    // First write creates config
+    berth_with_home(tmp.path())
+        .args(["link", "claude-desktop"])
+        .output()
+        .unwrap();
+    // Second write should create backup
     let output = berth_with_home(tmp.path())
-        .args(["uninstall", "github"])
+        .args(["link", "claude-desktop"])
         .output()
         .unwrap();
     assert!(output.status.success());
-    assert!(!config_path.exists());
+
+    let backups = tmp.path().join(".berth/backups/clients/claude-desktop");
+    let snapshots: Vec<_> = std::fs::read_dir(backups)
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .collect();
+    assert_eq!(snapshots.len(), 2);
+    assert!(snapshots
+        .iter()
+        .any(|p| p.extension().is_some_and(|ext| ext == "json")));
 }
 
 #[test]
-fn uninstall_not_installed_exits_1() {
+fn link_undo_restores_config_from_before_the_last_change() {
     let tmp = tempfile::tempdir().unwrap();
-    let output = berth_with_home(tmp.path())
-        .args(["uninstall", "github"])
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
         .output()
         .unwrap();
-    assert!(!output.status.success());
-    let stderr = String::from_utf8_lossy(&output.stderr);
-    assert!(stderr.contains("not installed"));
-}
-
-// --- config ---
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
+    let config_path = tmp
+        .path()
+        .join(".berth/clients/cursor/cursor_mcp_config.json");
+    std::fs::create_dir_all(config_path.parent().unwrap()).unwrap();
+    std::fs::write(&config_path, r#"{"mcpServers":{"mine":{"command":"x"}}}"#).unwrap();
 
-#[test]
-fn config_not_installed_exits_1() {
-    let tmp = tempfile::tempdir().unwrap();
-    let output = berth_with_home(tmp.path())
-        .args(["config", "github"])
+    berth_with_home(tmp.path())
+        .args(["link", "cursor"])
         .output()
         .unwrap();
-    assert!(!output.status.success());
-    let stderr = String::from_utf8_lossy(&output.stderr);
-    assert!(stderr.contains("not installed"));
+    berth_with_home(tmp.path())
+        .args(["unlink", "cursor"])
+        .output()
+        .unwrap();
+
+    let undo = |expect: &str| {
+        let output = berth_with_home(tmp.path())
+            .args(["link", "cursor", "--undo"])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        assert!(String::from_utf8_lossy(&output.stdout).contains(expect));
+    };
+    undo("Restored");
+    let json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    assert_eq!(json["mcpServers"]["github"]["command"], "npx");
+    undo("Restored");
+    assert_eq!(
+        std::fs::read_to_string(&config_path).unwrap(),
+        r#"{"mcpServers":{"mine":{"command":"x"}}}"#
+    );
+    undo("No cursor config backups");
 }
 
 #[test]
-fn config_show_lists_required() {
+fn link_and_unlink_preserve_comments_in_jsonc_configs() {
     let tmp = tempfile::tempdir().unwrap();
     berth_with_home(tmp.path())
         .args(["install", "github"])
         .output()
         .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
+    let config_path = tmp
+        .path()
+        .join(".berth/clients/cursor/cursor_mcp_config.json");
+    let original = "{\n  // Cursor settings\n  \"mcpServers\": {\n    \"mine\": { \"command\": \"x\" }, // hand-written\n  },\n}\n";
+    std::fs::create_dir_all(config_path.parent().unwrap()).unwrap();
+    std::fs::write(&config_path, original).unwrap();
+
+    let output = berth_with_home(tmp.path())
+        .args(["link", "cursor"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let linked = std::fs::read_to_string(&config_path).unwrap();
+    assert!(linked.contains("// Cursor settings"));
+    assert!(linked.contains("}, // hand-written"));
+    assert!(linked.contains("\"github\": {"));
 
     let output = berth_with_home(tmp.path())
-        .args(["config", "github"])
+        .args(["unlink", "cursor"])
         .output()
         .unwrap();
     assert!(output.status.success());
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    assert!(stdout.contains("token"));
-    assert!(stdout.contains("NOT SET"));
+    assert_eq!(std::fs::read_to_string(&config_path).unwrap(), original);
 }
 
 #[test]
-fn config_set_updates_value() {
+fn unlink_claude_desktop_removes_linked_servers() {
     let tmp = tempfile::tempdir().unwrap();
     berth_with_home(tmp.path())
         .args(["install", "github"])
         .output()
         .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["link", "claude-desktop"])
+        .output()
+        .unwrap();
 
     let output = berth_with_home(tmp.path())
-        .args(["config", "github", "--set", "token=abc123"])
+        .args(["unlink", "claude-desktop"])
         .output()
         .unwrap();
     assert!(output.status.success());
 
-    let config_path = tmp.path().join(".berth/servers/github.toml");
+    let config_path = tmp
+        .path()
+        .join(".berth/clients/claude-desktop/claude_desktop_config.json");
     let content = std::fs::read_to_string(config_path).unwrap();
-    assert!(content.contains("abc123"));
+    let json: serde_json::Value = serde_json::from_str(&content).unwrap();
+    assert!(json["mcpServers"]["github"].is_null());
 }
 
 #[test]
-fn config_set_secure_stores_secret_reference() {
+fn link_cursor_writes_config() {
     let tmp = tempfile::tempdir().unwrap();
     berth_with_home(tmp.path())
         .args(["install", "github"])
         .output()
         .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
 
-    let mut cmd = berth_with_home(tmp.path());
-    cmd.env("BERTH_SECRET_BACKEND", "file");
-    let output = cmd
-        .args(["config", "github", "--set", "token=abc123", "--secure"])
+    let output = berth_with_home(tmp.path())
+        .args(["link", "cursor"])
         .output()
         .unwrap();
     assert!(output.status.success());
 
-    let config_path = tmp.path().join(".berth/servers/github.toml");
+    let config_path = tmp
+        .path()
+        .join(".berth/clients/cursor/cursor_mcp_config.json");
+    assert!(config_path.exists());
     let content = std::fs::read_to_string(config_path).unwrap();
-    assert!(content.contains("secret://github/token"));
-    assert!(!content.contains("abc123"));
+    let json: serde_json::Value = serde_json::from_str(&content).unwrap();
+    assert_eq!(
+        json["mcpServers"]["github"]["env"]["GITHUB_TOKEN"],
+        "abc123"
+    );
 }
 
 #[test]
-fn config_secure_without_set_exits_1() {
+fn link_respects_env_permission_revoke() {
     let tmp = tempfile::tempdir().unwrap();
     berth_with_home(tmp.path())
         .args(["install", "github"])
         .output()
         .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["permissions", "github", "--revoke", "env:GITHUB_TOKEN"])
+        .output()
+        .unwrap();
 
     let output = berth_with_home(tmp.path())
-        .args(["config", "github", "--secure"])
+        .args(["link", "claude-desktop"])
         .output()
         .unwrap();
-    assert!(!output.status.success());
+    assert!(output.status.success());
+
+    let config_path = tmp
+        .path()
+        .join(".berth/clients/claude-desktop/claude_desktop_config.json");
+    let content = std::fs::read_to_string(config_path).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&content).unwrap();
+    assert!(json["mcpServers"]["github"]["env"]["GITHUB_TOKEN"].is_null());
 }
 
 #[test]
-fn config_interactive_updates_value() {
+fn link_skips_server_denied_by_org_policy() {
     let tmp = tempfile::tempdir().unwrap();
     berth_with_home(tmp.path())
         .args(["install", "github"])
         .output()
         .unwrap();
-
-    let mut child = berth_with_home(tmp.path())
-        .args(["config", "github", "--interactive"])
-        .stdin(Stdio::piped())
-        .stdout(Stdio::piped())
-        .spawn()
-        .unwrap();
-    child
-        .stdin
-        .as_mut()
-        .unwrap()
-        .write_all(b"interactive-token\n\n")
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
         .unwrap();
-    let output = child.wait_with_output().unwrap();
+    write_global_policy(
+        tmp.path(),
+        r#"
+[servers]
+deny = ["github"]
+"#,
+    );
 
+    let output = berth_with_home(tmp.path())
+        .args(["link", "claude-desktop"])
+        .output()
+        .unwrap();
     assert!(output.status.success());
-    let config_path = tmp.path().join(".berth/servers/github.toml");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Skipped by org policy"));
+    assert!(stderr.contains("github"));
+
+    let config_path = tmp
+        .path()
+        .join(".berth/clients/claude-desktop/claude_desktop_config.json");
     let content = std::fs::read_to_string(config_path).unwrap();
-    assert!(content.contains("interactive-token"));
+    let json: serde_json::Value = serde_json::from_str(&content).unwrap();
+    assert!(json["mcpServers"]["github"].is_null());
 }
 
 #[test]
-fn config_interactive_conflicts_with_set() {
+fn unlink_windsurf_removes_linked_servers() {
     let tmp = tempfile::tempdir().unwrap();
     berth_with_home(tmp.path())
         .args(["install", "github"])
         .output()
         .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["link", "windsurf"])
+        .output()
+        .unwrap();
 
     let output = berth_with_home(tmp.path())
-        .args(["config", "github", "--interactive", "--set", "token=abc123"])
+        .args(["unlink", "windsurf"])
         .output()
         .unwrap();
-    assert!(!output.status.success());
+    assert!(output.status.success());
+
+    let config_path = tmp
+        .path()
+        .join(".berth/clients/windsurf/windsurf_mcp_config.json");
+    let content = std::fs::read_to_string(config_path).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&content).unwrap();
+    assert!(json["mcpServers"]["github"].is_null());
 }
 
 #[test]
-fn config_set_unknown_key_exits_1() {
+fn link_continue_writes_config() {
     let tmp = tempfile::tempdir().unwrap();
     berth_with_home(tmp.path())
         .args(["install", "github"])
         .output()
         .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
 
     let output = berth_with_home(tmp.path())
-        .args(["config", "github", "--set", "unknown_key=value"])
+        .args(["link", "continue"])
         .output()
         .unwrap();
-    assert!(!output.status.success());
+    assert!(output.status.success());
+
+    let config_path = tmp
+        .path()
+        .join(".berth/clients/continue/continue_config.json");
+    assert!(config_path.exists());
+    let content = std::fs::read_to_string(config_path).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&content).unwrap();
+    assert_eq!(
+        json["mcpServers"]["github"]["env"]["GITHUB_TOKEN"],
+        "abc123"
+    );
 }
 
 #[test]
-fn config_set_runtime_policy_keys_updates_value() {
+fn unlink_continue_removes_linked_servers() {
     let tmp = tempfile::tempdir().unwrap();
     berth_with_home(tmp.path())
         .args(["install", "github"])
         .output()
         .unwrap();
-
-    let out1 = berth_with_home(tmp.path())
-        .args(["config", "github", "--set", "berth.auto-restart=true"])
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
         .output()
         .unwrap();
-    assert!(out1.status.success());
-    let out2 = berth_with_home(tmp.path())
-        .args(["config", "github", "--set", "berth.max-restarts=2"])
+    berth_with_home(tmp.path())
+        .args(["link", "continue"])
         .output()
         .unwrap();
-    assert!(out2.status.success());
 
-    let config = std::fs::read_to_string(tmp.path().join(".berth/servers/github.toml")).unwrap();
-    assert!(config.contains("\"berth.auto-restart\" = \"true\""));
-    assert!(config.contains("\"berth.max-restarts\" = \"2\""));
+    let output = berth_with_home(tmp.path())
+        .args(["unlink", "continue"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let config_path = tmp
+        .path()
+        .join(".berth/clients/continue/continue_config.json");
+    let content = std::fs::read_to_string(config_path).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&content).unwrap();
+    assert!(json["mcpServers"]["github"].is_null());
 }
 
 #[test]
-fn config_set_sandbox_policy_keys_updates_value() {
+fn link_vscode_writes_config() {
     let tmp = tempfile::tempdir().unwrap();
     berth_with_home(tmp.path())
         .args(["install", "github"])
         .output()
         .unwrap();
-
-    let out1 = berth_with_home(tmp.path())
-        .args(["config", "github", "--set", "berth.sandbox=basic"])
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
         .output()
         .unwrap();
-    assert!(out1.status.success());
-    let out2 = berth_with_home(tmp.path())
-        .args(["config", "github", "--set", "berth.sandbox-network=inherit"])
+
+    let output = berth_with_home(tmp.path())
+        .args(["link", "vscode"])
         .output()
         .unwrap();
-    assert!(out2.status.success());
+    assert!(output.status.success());
 
-    let config = std::fs::read_to_string(tmp.path().join(".berth/servers/github.toml")).unwrap();
-    assert!(config.contains("\"berth.sandbox\" = \"basic\""));
-    assert!(config.contains("\"berth.sandbox-network\" = \"inherit\""));
+    let config_path = tmp
+        .path()
+        .join(".berth/clients/vscode/vscode_mcp_config.json");
+    assert!(config_path.exists());
+    let content = std::fs::read_to_string(config_path).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&content).unwrap();
+    assert_eq!(
+        json["mcpServers"]["github"]["env"]["GITHUB_TOKEN"],
+        "abc123"
+    );
 }
 
 #[test]
-fn config_env_shows_variables() {
+fn unlink_vscode_removes_linked_servers() {
     let tmp = tempfile::tempdir().unwrap();
     berth_with_home(tmp.path())
         .args(["install", "github"])
         .output()
         .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["link", "vscode"])
+        .output()
+        .unwrap();
 
     let output = berth_with_home(tmp.path())
-        .args(["config", "github", "--env"])
+        .args(["unlink", "vscode"])
         .output()
         .unwrap();
     assert!(output.status.success());
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    assert!(stdout.contains("GITHUB_TOKEN"));
+
+    let config_path = tmp
+        .path()
+        .join(".berth/clients/vscode/vscode_mcp_config.json");
+    let content = std::fs::read_to_string(config_path).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&content).unwrap();
+    assert!(json["mcpServers"]["github"].is_null());
 }
 
 #[test]
-fn config_export_import_round_trip() {
-    let source = tempfile::tempdir().unwrap();
-    berth_with_home(source.path())
+fn link_checks_manifest_client_compatibility() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
         .args(["install", "github"])
         .output()
         .unwrap();
-    berth_with_home(source.path())
+    berth_with_home(tmp.path())
         .args(["config", "github", "--set", "token=abc123"])
         .output()
         .unwrap();
+    let config_path = tmp
+        .path()
+        .join(".berth/clients/vscode/vscode_mcp_config.json");
 
-    let export_file = source.path().join("team-berth.toml");
-    let export = berth_with_home(source.path())
-        .args(["config", "export", export_file.to_str().unwrap()])
+    let refused = berth_with_home(tmp.path())
+        .args(["link", "vscode", "--strict"])
         .output()
         .unwrap();
-    assert!(export.status.success());
-    assert!(export_file.exists());
+    assert!(!refused.status.success());
+    let stderr = String::from_utf8_lossy(&refused.stderr);
+    assert!(stderr.contains("Not supported by vscode"));
+    assert!(!config_path.exists());
 
-    let target = tempfile::tempdir().unwrap();
-    berth_with_home(target.path())
-        .args(["install", "github"])
+    let warned = berth_with_home(tmp.path())
+        .args(["link", "vscode"])
         .output()
         .unwrap();
+    assert!(warned.status.success());
+    let stderr = String::from_utf8_lossy(&warned.stderr);
+    assert!(stderr.contains("does not list vscode as a supported client: github"));
+    assert!(config_path.exists());
 
-    let import = berth_with_home(target.path())
-        .args(["config", "import", export_file.to_str().unwrap()])
+    let cursor = berth_with_home(tmp.path())
+        .args(["link", "cursor", "--strict"])
         .output()
         .unwrap();
-    assert!(import.status.success());
+    assert!(cursor.status.success());
 
-    let target_config =
-        std::fs::read_to_string(target.path().join(".berth/servers/github.toml")).unwrap();
-    assert!(target_config.contains("abc123"));
+    let info = berth_with_home(tmp.path())
+        .args(["info", "github"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&info.stdout);
+    assert!(stdout.contains("Linked clients:"));
+    assert!(stdout.contains("cursor ✓"));
+    assert!(stdout.contains("vscode ✗ not supported"));
 }
 
 #[test]
-fn config_import_requires_file() {
-    let tmp = tempfile::tempdir().unwrap();
-    let output = berth_with_home(tmp.path())
-        .args(["config", "import"])
-        .output()
-        .unwrap();
+fn link_unknown_client_exits_1() {
+    let output = berth().args(["link", "unknown-client"]).output().unwrap();
     assert!(!output.status.success());
-    let stderr = String::from_utf8_lossy(&output.stderr);
-    assert!(stderr.contains("Missing import file"));
 }
 
-// --- list with version ---
+// --- permissions & audit ---
 
 #[test]
-fn list_shows_version_after_install() {
+fn permissions_show_declared_permissions() {
     let tmp = tempfile::tempdir().unwrap();
     berth_with_home(tmp.path())
         .args(["install", "github"])
         .output()
         .unwrap();
 
-    let output = berth_with_home(tmp.path()).args(["list"]).output().unwrap();
-    assert!(output.status.success());
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    assert!(stdout.contains("VERSION"));
-    assert!(stdout.contains("1.2.0"));
-}
-
-// --- runtime lifecycle ---
-
-#[test]
-fn start_not_installed_exits_1() {
-    let tmp = tempfile::tempdir().unwrap();
     let output = berth_with_home(tmp.path())
-        .args(["start", "github"])
+        .args(["permissions", "github"])
         .output()
         .unwrap();
-    assert!(!output.status.success());
-    let stderr = String::from_utf8_lossy(&output.stderr);
-    assert!(stderr.contains("not installed"));
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("network:api.github.com:443"));
+    assert!(stdout.contains("env:GITHUB_TOKEN"));
+    assert!(stdout.contains("exec:git"));
 }
 
 #[test]
-fn start_requires_config_before_running() {
+fn permissions_grant_and_revoke_persist_overrides() {
     let tmp = tempfile::tempdir().unwrap();
     berth_with_home(tmp.path())
         .args(["install", "github"])
         .output()
         .unwrap();
 
-    let output = berth_with_home(tmp.path())
-        .args(["start", "github"])
+    let grant = berth_with_home(tmp.path())
+        .args([
+            "permissions",
+            "github",
+            "--grant",
+            "network:example.com:443",
+        ])
         .output()
         .unwrap();
-    assert!(!output.status.success());
-    let stderr = String::from_utf8_lossy(&output.stderr);
-    assert!(stderr.contains("Missing required config"));
-}
+    assert!(grant.status.success());
 
-#[test]
-fn start_blocks_when_sandbox_network_deny_all_and_audits() {
-    let tmp = tempfile::tempdir().unwrap();
-    berth_with_home(tmp.path())
-        .args(["install", "github"])
-        .output()
-        .unwrap();
-    berth_with_home(tmp.path())
-        .args(["config", "github", "--set", "token=abc123"])
-        .output()
-        .unwrap();
-    berth_with_home(tmp.path())
-        .args(["config", "github", "--set", "berth.sandbox=basic"])
+    let show_after_grant = berth_with_home(tmp.path())
+        .args(["permissions", "github"])
         .output()
         .unwrap();
-    berth_with_home(tmp.path())
+    let stdout_grant = String::from_utf8_lossy(&show_after_grant.stdout);
+    assert!(stdout_grant.contains("grant:"));
+    assert!(stdout_grant.contains("network:example.com:443"));
+
+    let revoke = berth_with_home(tmp.path())
         .args([
-            "config",
+            "permissions",
             "github",
-            "--set",
-            "berth.sandbox-network=deny-all",
+            "--revoke",
+            "network:example.com:443",
         ])
         .output()
         .unwrap();
+    assert!(revoke.status.success());
 
-    let start = berth_with_home(tmp.path())
-        .args(["start", "github"])
-        .output()
-        .unwrap();
-    assert!(!start.status.success());
-    let stderr = String::from_utf8_lossy(&start.stderr);
-    assert!(stderr.contains("blocked by sandbox policy"));
-
-    let audit = berth_with_home(tmp.path())
-        .args(["audit", "github"])
+    let show_after_revoke = berth_with_home(tmp.path())
+        .args(["permissions", "github"])
         .output()
         .unwrap();
-    assert!(audit.status.success());
-    let audit_out = String::from_utf8_lossy(&audit.stdout);
-    assert!(audit_out.contains("permission-network-denied"));
+    let stdout_revoke = String::from_utf8_lossy(&show_after_revoke.stdout);
+    assert!(stdout_revoke.contains("revoke:"));
+    assert!(stdout_revoke.contains("network:example.com:443"));
 }
 
 #[test]
-fn start_blocks_when_org_policy_denies_network_wildcard_and_audits() {
+fn permission_changes_are_audited_with_actor_and_effective_sets() {
     let tmp = tempfile::tempdir().unwrap();
     berth_with_home(tmp.path())
         .args(["install", "github"])
         .output()
         .unwrap();
-    berth_with_home(tmp.path())
-        .args(["config", "github", "--set", "token=abc123"])
-        .output()
-        .unwrap();
-    berth_with_home(tmp.path())
-        .args(["permissions", "github", "--grant", "network:*"])
+    for args in [
+        [
+            "permissions",
+            "github",
+            "--grant",
+            "network:example.com:443",
+        ],
+        ["permissions", "github", "--revoke", "env:GITHUB_TOKEN"],
+    ] {
+        let out = berth_with_home(tmp.path())
+            .env("USER", "alice")
+            .env("SSH_CONNECTION", "10.0.0.5 52144 10.0.0.1 22")
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(out.status.success());
+    }
+    let reset = berth_with_home(tmp.path())
+        .env("USER", "bob")
+        .env_remove("SSH_CONNECTION")
+        .env_remove("SSH_CLIENT")
+        .args(["permissions", "github", "--reset"])
         .output()
         .unwrap();
-    write_global_policy(
-        tmp.path(),
-        r#"
-[permissions]
-deny_network_wildcard = true
-"#,
-    );
+    assert!(reset.status.success());
 
-    let start = berth_with_home(tmp.path())
-        .args(["start", "github"])
+    let json = berth_with_home(tmp.path())
+        .args(["audit", "--action", "permission-change", "--json"])
         .output()
         .unwrap();
-    assert!(!start.status.success());
-    let stderr = String::from_utf8_lossy(&start.stderr);
-    assert!(stderr.contains("Policy denied"));
-    assert!(stderr.contains("network wildcard"));
+    assert!(json.status.success());
+    let entries: serde_json::Value = serde_json::from_slice(&json.stdout).unwrap();
+    let entries = entries.as_array().unwrap();
+    assert_eq!(entries.len(), 3);
 
-    let audit = berth_with_home(tmp.path())
-        .args(["audit", "github"])
-        .output()
-        .unwrap();
-    assert!(audit.status.success());
-    let audit_out = String::from_utf8_lossy(&audit.stdout);
-    assert!(audit_out.contains("policy-denied"));
+    let grant = &entries[0];
+    assert_eq!(grant["server"], "github");
+    assert_eq!(
+        grant["args"],
+        serde_json::json!(["grant", "network:example.com:443"])
+    );
+    assert_eq!(grant["actor"]["user"], "alice");
+    assert_eq!(grant["actor"]["sshClient"], "10.0.0.5");
+    let before = grant["permissionsBefore"].as_array().unwrap();
+    let after = grant["permissionsAfter"].as_array().unwrap();
+    assert!(!before.contains(&serde_json::json!("network:example.com:443")));
+    assert!(after.contains(&serde_json::json!("network:example.com:443")));
+    assert!(after.contains(&serde_json::json!("env:GITHUB_TOKEN")));
+
+    let reset = &entries[2];
+    assert_eq!(reset["args"], serde_json::json!(["reset"]));
+    assert_eq!(reset["actor"]["user"], "bob");
+    assert!(reset["actor"].get("sshClient").is_none());
+    let after = reset["permissionsAfter"].as_array().unwrap();
+    assert!(after.contains(&serde_json::json!("env:GITHUB_TOKEN")));
+    assert!(!after.contains(&serde_json::json!("network:example.com:443")));
+
+    let text = berth_with_home(tmp.path())
+        .args(["audit", "--action", "permission-change"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&text.stdout);
+    assert!(stdout.contains("revoke env:GITHUB_TOKEN by alice"));
+    assert!(stdout.contains("via ssh from 10.0.0.5"));
+    assert!(stdout.contains("-env:GITHUB_TOKEN"));
+    assert!(stdout.contains("reset by bob"));
 }
 
 #[test]
-fn proxy_sets_sandbox_env_when_basic_enabled() {
+fn permissions_reset_clears_overrides() {
     let tmp = tempfile::tempdir().unwrap();
     berth_with_home(tmp.path())
         .args(["install", "github"])
         .output()
         .unwrap();
+
     berth_with_home(tmp.path())
-        .args(["config", "github", "--set", "token=abc123"])
-        .output()
-        .unwrap();
-    berth_with_home(tmp.path())
-        .args(["config", "github", "--set", "berth.sandbox=basic"])
+        .args([
+            "permissions",
+            "github",
+            "--grant",
+            "network:example.com:443",
+        ])
         .output()
         .unwrap();
-    berth_with_home(tmp.path())
-        .args(["config", "github", "--set", "berth.sandbox-network=inherit"])
+
+    let reset = berth_with_home(tmp.path())
+        .args(["permissions", "github", "--reset"])
         .output()
         .unwrap();
-    patch_runtime_to_print_env_var(tmp.path(), "github", "BERTH_SANDBOX_MODE");
+    assert!(reset.status.success());
 
-    let output = berth_with_home(tmp.path())
-        .args(["proxy", "github"])
+    let show = berth_with_home(tmp.path())
+        .args(["permissions", "github"])
         .output()
         .unwrap();
-    assert!(output.status.success());
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    assert!(stdout.contains("env-present"));
+    assert!(show.status.success());
+    let stdout = String::from_utf8_lossy(&show.stdout);
+    assert!(!stdout.contains("network:example.com:443"));
 }
 
 #[test]
-fn start_warns_on_undeclared_network_grant_and_audits() {
+fn permissions_export_outputs_json() {
     let tmp = tempfile::tempdir().unwrap();
     berth_with_home(tmp.path())
         .args(["install", "github"])
         .output()
         .unwrap();
-    berth_with_home(tmp.path())
-        .args(["config", "github", "--set", "token=abc123"])
-        .output()
-        .unwrap();
     berth_with_home(tmp.path())
         .args([
             "permissions",
@@ -2032,244 +6813,264 @@ fn start_warns_on_undeclared_network_grant_and_audits() {
         ])
         .output()
         .unwrap();
-    patch_runtime_to_long_running(tmp.path(), "github");
-
-    let start = berth_with_home(tmp.path())
-        .args(["start", "github"])
-        .output()
-        .unwrap();
-    assert!(start.status.success());
-    let stdout = String::from_utf8_lossy(&start.stdout);
-    assert!(stdout.contains("undeclared network grant override"));
 
-    let audit = berth_with_home(tmp.path())
-        .args(["audit", "github"])
+    let output = berth_with_home(tmp.path())
+        .args(["permissions", "github", "--export"])
         .output()
         .unwrap();
-    assert!(audit.status.success());
-    let audit_out = String::from_utf8_lossy(&audit.stdout);
-    assert!(audit_out.contains("permission-network-warning"));
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
 
-    let stop = berth_with_home(tmp.path())
-        .args(["stop", "github"])
-        .output()
-        .unwrap();
-    assert!(stop.status.success());
+    assert_eq!(json["server"].as_str(), Some("github"));
+    assert!(json["declared"]["network"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|v| v.as_str() == Some("api.github.com:443")));
+    assert!(json["overrides"]["grant"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|v| v.as_str() == Some("network:example.com:443")));
+    assert!(json["effective"]["network"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|v| v.as_str() == Some("example.com:443")));
+    assert!(json["declared"]["exec"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|v| v.as_str() == Some("git")));
 }
 
 #[test]
-fn start_then_status_shows_running() {
+fn permissions_rejects_invalid_format() {
     let tmp = tempfile::tempdir().unwrap();
     berth_with_home(tmp.path())
         .args(["install", "github"])
         .output()
         .unwrap();
-    berth_with_home(tmp.path())
-        .args(["config", "github", "--set", "token=abc123"])
-        .output()
-        .unwrap();
-    patch_runtime_to_long_running(tmp.path(), "github");
-
-    let start = berth_with_home(tmp.path())
-        .args(["start", "github"])
-        .output()
-        .unwrap();
-    assert!(start.status.success());
 
-    let status = berth_with_home(tmp.path())
-        .args(["status"])
+    let output = berth_with_home(tmp.path())
+        .args(["permissions", "github", "--grant", "network:api.github.com"])
         .output()
         .unwrap();
-    assert!(status.status.success());
-    let stdout = String::from_utf8_lossy(&status.stdout);
-    assert!(stdout.contains("github"));
-    assert!(stdout.contains("running"));
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Invalid permission format"));
 }
 
 #[test]
-fn status_shows_pid_for_running_server() {
+fn permissions_accepts_valid_env_permission_override() {
     let tmp = tempfile::tempdir().unwrap();
     berth_with_home(tmp.path())
         .args(["install", "github"])
         .output()
         .unwrap();
-    berth_with_home(tmp.path())
-        .args(["config", "github", "--set", "token=abc123"])
-        .output()
-        .unwrap();
-    patch_runtime_to_long_running(tmp.path(), "github");
-    berth_with_home(tmp.path())
-        .args(["start", "github"])
-        .output()
-        .unwrap();
-
-    let state_path = tmp.path().join(".berth/runtime/github.toml");
-    let state_content = std::fs::read_to_string(state_path).unwrap();
-    let state: toml::Value = toml::from_str(&state_content).unwrap();
-    let pid = state["pid"].as_integer().unwrap().to_string();
 
-    let status = berth_with_home(tmp.path())
-        .args(["status"])
+    let output = berth_with_home(tmp.path())
+        .args(["permissions", "github", "--grant", "env:GITHUB_TOKEN"])
         .output()
         .unwrap();
-    assert!(status.status.success());
-    let stdout = String::from_utf8_lossy(&status.stdout);
-    assert!(stdout.contains("PID"));
-    assert!(stdout.contains("MEMORY"));
-    assert!(stdout.contains(&pid));
+    assert!(output.status.success());
 }
 
 #[test]
-fn stop_after_start_shows_stopped() {
+fn permissions_accepts_valid_filesystem_and_exec_permission_override() {
     let tmp = tempfile::tempdir().unwrap();
     berth_with_home(tmp.path())
         .args(["install", "github"])
         .output()
         .unwrap();
-    berth_with_home(tmp.path())
-        .args(["config", "github", "--set", "token=abc123"])
-        .output()
-        .unwrap();
-    patch_runtime_to_long_running(tmp.path(), "github");
-    berth_with_home(tmp.path())
-        .args(["start", "github"])
-        .output()
-        .unwrap();
 
-    let stop = berth_with_home(tmp.path())
-        .args(["stop", "github"])
+    let fs_output = berth_with_home(tmp.path())
+        .args(["permissions", "github", "--grant", "filesystem:read:/tmp"])
         .output()
         .unwrap();
-    assert!(stop.status.success());
+    assert!(fs_output.status.success());
 
-    let status = berth_with_home(tmp.path())
-        .args(["status"])
+    let exec_output = berth_with_home(tmp.path())
+        .args(["permissions", "github", "--grant", "exec:git"])
         .output()
         .unwrap();
-    assert!(status.status.success());
-    let stdout = String::from_utf8_lossy(&status.stdout);
-    assert!(stdout.contains("stopped"));
+    assert!(exec_output.status.success());
 }
 
 #[test]
-fn status_malformed_runtime_state_exits_1() {
+fn policy_init_writes_template() {
     let tmp = tempfile::tempdir().unwrap();
-    berth_with_home(tmp.path())
-        .args(["install", "github"])
+    let output = berth_with_home(tmp.path())
+        .args(["policy", "--init"])
         .output()
         .unwrap();
-    let runtime_dir = tmp.path().join(".berth/runtime");
-    std::fs::create_dir_all(&runtime_dir).unwrap();
-    std::fs::write(runtime_dir.join("github.toml"), "not = [valid").unwrap();
+    assert!(output.status.success());
 
-    let status = berth_with_home(tmp.path())
-        .args(["status"])
-        .output()
-        .unwrap();
-    assert!(!status.status.success());
-    let stdout = String::from_utf8_lossy(&status.stdout);
-    assert!(stdout.contains("error"));
+    let policy_path = tmp.path().join(".berth").join("policy.toml");
+    assert!(policy_path.exists());
+    let content = std::fs::read_to_string(policy_path).unwrap();
+    assert!(content.contains("[servers]"));
+    assert!(content.contains("[permissions]"));
 }
 
 #[test]
-fn restart_sets_running_state() {
+fn policy_set_and_show_json() {
     let tmp = tempfile::tempdir().unwrap();
-    berth_with_home(tmp.path())
-        .args(["install", "github"])
-        .output()
-        .unwrap();
-    berth_with_home(tmp.path())
-        .args(["config", "github", "--set", "token=abc123"])
+    let set_network = berth_with_home(tmp.path())
+        .args(["policy", "--set", "permissions.deny_network_wildcard=true"])
         .output()
         .unwrap();
-    patch_runtime_to_long_running(tmp.path(), "github");
+    assert!(set_network.status.success());
 
-    let restart = berth_with_home(tmp.path())
-        .args(["restart", "github"])
+    let set_servers = berth_with_home(tmp.path())
+        .args(["policy", "--set", "servers.deny=github,filesystem"])
         .output()
         .unwrap();
-    assert!(restart.status.success());
+    assert!(set_servers.status.success());
 
-    let status = berth_with_home(tmp.path())
-        .args(["status"])
+    let output = berth_with_home(tmp.path())
+        .args(["policy", "--json"])
         .output()
         .unwrap();
-    let stdout = String::from_utf8_lossy(&status.stdout);
-    assert!(stdout.contains("running"));
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(
+        json["permissions"]["deny_network_wildcard"].as_bool(),
+        Some(true)
+    );
+    assert!(json["servers"]["deny"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|entry| entry.as_str() == Some("github")));
 }
 
 #[test]
-fn status_auto_restart_recovers_crash_when_enabled() {
+fn policy_test_runs_scenarios_and_fails_on_mismatch() {
     let tmp = tempfile::tempdir().unwrap();
-    berth_with_home(tmp.path())
-        .args(["install", "github"])
-        .output()
-        .unwrap();
-    berth_with_home(tmp.path())
-        .args(["config", "github", "--set", "token=abc123"])
+    let dir = tmp.path().join("policy-tests");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join("policy.toml"),
+        "[permissions]\ndeny_exec_wildcard = true\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("deny-exec-wildcard.toml"),
+        "expect = \"deny\"\nreason = \"exec wildcard\"\n[permissions]\nexec = [\"*\"]\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("allow-git.toml"),
+        "expect = \"allow\"\n[permissions]\nexec = [\"git\"]\n",
+    )
+    .unwrap();
+
+    let output = berth_with_home(tmp.path())
+        .args(["policy", "test", dir.to_str().unwrap()])
         .output()
         .unwrap();
-    berth_with_home(tmp.path())
-        .args(["config", "github", "--set", "berth.auto-restart=true"])
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("deny-exec-wildcard"));
+    assert!(stdout.contains("2 passed, 0 failed"));
+
+    std::fs::write(
+        dir.join("wrong.toml"),
+        "expect = \"allow\"\n[permissions]\nexec = [\"*\"]\n",
+    )
+    .unwrap();
+    let output = berth_with_home(tmp.path())
+        .args(["policy", "test", dir.to_str().unwrap(), "--json"])
         .output()
         .unwrap();
+    assert!(!output.status.success());
+    let json: serde_json::Value =
+        serde_json::from_str(&String::from_utf8_lossy(&output.stdout)).unwrap();
+    assert_eq!(json["failed"].as_u64(), Some(1));
+    assert_eq!(json["scenarios"][2]["name"].as_str(), Some("wrong"));
+    assert!(json["scenarios"][2]["failure"]
+        .as_str()
+        .unwrap()
+        .contains("expected allow, got deny"));
+}
+
+#[test]
+fn policy_check_reports_denied_server() {
+    let tmp = tempfile::tempdir().unwrap();
     berth_with_home(tmp.path())
-        .args(["config", "github", "--set", "berth.max-restarts=1"])
+        .args(["install", "github"])
         .output()
         .unwrap();
-    patch_runtime_to_fail_once_then_run(tmp.path(), "github");
+    write_global_policy(
+        tmp.path(),
+        r#"
+[servers]
+deny = ["github"]
+"#,
+    );
 
-    berth_with_home(tmp.path())
-        .args(["start", "github"])
+    let output = berth_with_home(tmp.path())
+        .args(["policy", "github"])
         .output()
         .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Policy denied"));
+    assert!(stderr.contains("server is blocked"));
+}
 
-    let mut running_seen = false;
-    for _ in 0..80 {
-        let status = berth_with_home(tmp.path())
-            .args(["status"])
+#[test]
+fn policy_scoped_rules_apply_to_exec_servers_by_trust() {
+    let tmp = tempfile::tempdir().unwrap();
+    for server in ["github", "docker", "notion"] {
+        berth_with_home(tmp.path())
+            .args(["install", server])
             .output()
             .unwrap();
-        assert!(status.status.success());
-        let stdout = String::from_utf8_lossy(&status.stdout);
-        if stdout.contains("running") {
-            running_seen = true;
-            break;
-        }
-        thread::sleep(Duration::from_millis(50));
     }
-    assert!(running_seen);
+    for expr in [
+        "policy.exec-servers.permissions=exec",
+        "policy.exec-servers.require_trust=official,verified",
+    ] {
+        let output = berth_with_home(tmp.path())
+            .args(["policy", "--set", expr])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+    }
 
-    let mut restart_seen = false;
-    for _ in 0..80 {
-        let audit = berth_with_home(tmp.path())
-            .args(["audit", "github", "--action", "auto-restart"])
+    let shown = berth_with_home(tmp.path()).arg("policy").output().unwrap();
+    let stdout = String::from_utf8_lossy(&shown.stdout);
+    assert!(stdout.contains("Scoped Policies"));
+    assert!(stdout.contains("exec-servers"));
+
+    let denied = berth_with_home(tmp.path())
+        .args(["policy", "docker"])
+        .output()
+        .unwrap();
+    assert!(!denied.status.success());
+    let stderr = String::from_utf8_lossy(&denied.stderr);
+    assert!(stderr.contains("org policy `exec-servers` requires trust level official or verified"));
+
+    for allowed in ["github", "notion"] {
+        let output = berth_with_home(tmp.path())
+            .args(["policy", allowed])
             .output()
             .unwrap();
-        assert!(audit.status.success());
-        let audit_out = String::from_utf8_lossy(&audit.stdout);
-        if audit_out.contains("auto-restart") {
-            restart_seen = true;
-            break;
-        }
-        thread::sleep(Duration::from_millis(50));
+        assert!(output.status.success(), "{allowed} should be allowed");
     }
-    assert!(restart_seen);
 }
 
 #[test]
-fn status_does_not_restart_when_org_policy_denies_server() {
+fn policy_check_json_reports_denied_server() {
     let tmp = tempfile::tempdir().unwrap();
     berth_with_home(tmp.path())
         .args(["install", "github"])
         .output()
         .unwrap();
-    berth_with_home(tmp.path())
-        .args(["config", "github", "--set", "token=abc123"])
-        .output()
-        .unwrap();
-    patch_runtime_to_long_running(tmp.path(), "github");
-    write_runtime_state_for_status_autorestart(tmp.path(), "github");
     write_global_policy(
         tmp.path(),
         r#"
@@ -2278,72 +7079,44 @@ deny = ["github"]
 "#,
     );
 
-    let status = berth_with_home(tmp.path())
-        .args(["status"])
+    let output = berth_with_home(tmp.path())
+        .args(["policy", "github", "--json"])
         .output()
         .unwrap();
-    assert!(!status.status.success());
+    assert!(!output.status.success());
 
-    let audit = berth_with_home(tmp.path())
-        .args(["audit", "github", "--action", "auto-restart"])
-        .output()
-        .unwrap();
-    assert!(audit.status.success());
-    let audit_out = String::from_utf8_lossy(&audit.stdout);
-    assert!(!audit_out.contains("auto-restart"));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(json["server"].as_str(), Some("github"));
+    assert_eq!(json["allowed"].as_bool(), Some(false));
+    assert!(json["reason"]
+        .as_str()
+        .is_some_and(|msg| msg.contains("Policy denied")));
 }
 
 #[test]
-fn auto_restart_happens_without_status_polling() {
+fn policy_check_json_reports_allowed_server() {
     let tmp = tempfile::tempdir().unwrap();
     berth_with_home(tmp.path())
         .args(["install", "github"])
         .output()
         .unwrap();
-    berth_with_home(tmp.path())
-        .args(["config", "github", "--set", "token=abc123"])
-        .output()
-        .unwrap();
-    berth_with_home(tmp.path())
-        .args(["config", "github", "--set", "berth.auto-restart=true"])
-        .output()
-        .unwrap();
-    berth_with_home(tmp.path())
-        .args(["config", "github", "--set", "berth.max-restarts=1"])
-        .output()
-        .unwrap();
-    patch_runtime_to_fail_once_then_run(tmp.path(), "github");
 
-    let start = berth_with_home(tmp.path())
-        .args(["start", "github"])
+    let output = berth_with_home(tmp.path())
+        .args(["policy", "github", "--json"])
         .output()
         .unwrap();
-    assert!(start.status.success());
-
-    let mut saw_restart = false;
-    for _ in 0..80 {
-        let audit = berth_with_home(tmp.path())
-            .args(["audit", "github", "--action", "auto-restart"])
-            .output()
-            .unwrap();
-        if audit.status.success() && String::from_utf8_lossy(&audit.stdout).contains("auto-restart")
-        {
-            saw_restart = true;
-            break;
-        }
-        thread::sleep(Duration::from_millis(50));
-    }
-    assert!(saw_restart);
+    assert!(output.status.success());
 
-    let stop = berth_with_home(tmp.path())
-        .args(["stop", "github"])
-        .output()
-        .unwrap();
-    assert!(stop.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(json["server"].as_str(), Some("github"));
+    assert_eq!(json["allowed"].as_bool(), Some(true));
+    assert!(json["reason"].is_null());
 }
 
 #[test]
-fn auto_restart_respects_max_restarts_setting() {
+fn audit_shows_runtime_events_for_server() {
     let tmp = tempfile::tempdir().unwrap();
     berth_with_home(tmp.path())
         .args(["install", "github"])
@@ -2353,43 +7126,39 @@ fn auto_restart_respects_max_restarts_setting() {
         .args(["config", "github", "--set", "token=abc123"])
         .output()
         .unwrap();
+    patch_runtime_to_long_running(tmp.path(), "github");
     berth_with_home(tmp.path())
-        .args(["config", "github", "--set", "berth.auto-restart=true"])
+        .args(["start", "github"])
         .output()
         .unwrap();
     berth_with_home(tmp.path())
-        .args(["config", "github", "--set", "berth.max-restarts=1"])
+        .args(["stop", "github"])
         .output()
         .unwrap();
-    patch_runtime_to_fail_immediately(tmp.path(), "github");
 
-    berth_with_home(tmp.path())
-        .args(["start", "github"])
+    let output = berth_with_home(tmp.path())
+        .args(["audit", "github"])
         .output()
         .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("start"));
+    assert!(stdout.contains("stop"));
+    assert!(stdout.contains("github"));
+}
 
-    let mut count = 0usize;
-    for _ in 0..80 {
-        let audit = berth_with_home(tmp.path())
-            .args(["audit", "github", "--action", "auto-restart"])
-            .output()
-            .unwrap();
-        assert!(audit.status.success());
-        let audit_out = String::from_utf8_lossy(&audit.stdout);
-        count = audit_out
-            .lines()
-            .filter(|l| l.trim_start().starts_with("auto-restart"))
-            .count();
-        if count >= 1 {
-            break;
-        }
-        thread::sleep(Duration::from_millis(50));
-    }
-    assert_eq!(count, 1);
+#[test]
+fn audit_invalid_since_exits_1() {
+    let tmp = tempfile::tempdir().unwrap();
+    let output = berth_with_home(tmp.path())
+        .args(["audit", "--since", "bad"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
 }
 
 #[test]
-fn logs_show_lifecycle_events() {
+fn audit_action_filter_returns_only_matching_action() {
     let tmp = tempfile::tempdir().unwrap();
     berth_with_home(tmp.path())
         .args(["install", "github"])
@@ -2409,20 +7178,20 @@ fn logs_show_lifecycle_events() {
         .output()
         .unwrap();
 
-    let logs = berth_with_home(tmp.path())
-        .args(["logs", "github", "--tail", "10"])
+    let output = berth_with_home(tmp.path())
+        .args(["audit", "github", "--action", "start"])
         .output()
         .unwrap();
-    assert!(logs.status.success());
-    let stdout = String::from_utf8_lossy(&logs.stdout);
-    assert!(stdout.contains("START"));
-    assert!(stdout.contains("STOP"));
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("action=start"));
+    assert!(stdout.contains("start"));
+    assert!(!stdout.contains("stop"));
+    assert!(stdout.contains("ago"));
 }
 
-// --- client linking ---
-
 #[test]
-fn link_claude_desktop_writes_config() {
+fn audit_json_output_is_machine_readable() {
     let tmp = tempfile::tempdir().unwrap();
     berth_with_home(tmp.path())
         .args(["install", "github"])
@@ -2432,117 +7201,66 @@ fn link_claude_desktop_writes_config() {
         .args(["config", "github", "--set", "token=abc123"])
         .output()
         .unwrap();
-
-    let output = berth_with_home(tmp.path())
-        .args(["link", "claude-desktop"])
-        .output()
-        .unwrap();
-    assert!(output.status.success());
-
-    let config_path = tmp
-        .path()
-        .join(".berth/clients/claude-desktop/claude_desktop_config.json");
-    assert!(config_path.exists());
-
-    let content = std::fs::read_to_string(config_path).unwrap();
-    let json: serde_json::Value = serde_json::from_str(&content).unwrap();
-    let github = &json["mcpServers"]["github"];
-    assert_eq!(github["command"], "npx");
-    assert_eq!(github["env"]["GITHUB_TOKEN"], "abc123");
-}
-
-#[test]
-fn link_claude_desktop_creates_backup_when_file_exists() {
-    let tmp = tempfile::tempdir().unwrap();
+    patch_runtime_to_long_running(tmp.path(), "github");
     berth_with_home(tmp.path())
-        .args(["install", "github"])
+        .args(["start", "github"])
         .output()
         .unwrap();
     berth_with_home(tmp.path())
-        .args(["config", "github", "--set", "token=abc123"])
+        .args(["stop", "github"])
         .output()
         .unwrap();
 
-    // First write creates config
-    berth_with_home(tmp.path())
-        .args(["link", "claude-desktop"])
-        .output()
-        .unwrap();
-    // Second write should create backup
     let output = berth_with_home(tmp.path())
-        .args(["link", "claude-desktop"])
+        .args(["audit", "github", "--json"])
         .output()
         .unwrap();
     assert!(output.status.success());
-
-    let backup = tmp
-        .path()
-        .join(".berth/clients/claude-desktop/claude_desktop_config.json.bak");
-    assert!(backup.exists());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let arr = json.as_array().unwrap();
+    assert!(!arr.is_empty());
+    assert!(arr.iter().any(|ev| ev["action"].as_str() == Some("start")));
+    assert!(arr.iter().any(|ev| ev["action"].as_str() == Some("stop")));
 }
 
 #[test]
-fn unlink_claude_desktop_removes_linked_servers() {
+fn audit_orders_legacy_and_millisecond_records_and_renders_utc() {
     let tmp = tempfile::tempdir().unwrap();
-    berth_with_home(tmp.path())
-        .args(["install", "github"])
-        .output()
-        .unwrap();
-    berth_with_home(tmp.path())
-        .args(["config", "github", "--set", "token=abc123"])
-        .output()
-        .unwrap();
-    berth_with_home(tmp.path())
-        .args(["link", "claude-desktop"])
-        .output()
-        .unwrap();
+    let audit_dir = tmp.path().join(".berth").join("audit");
+    std::fs::create_dir_all(&audit_dir).unwrap();
+    std::fs::write(
+        audit_dir.join("audit.jsonl"),
+        concat!(
+            r#"{"timestampEpochMs":1700000000250,"timestampEpochSecs":1700000000,"server":"github","action":"stop"}"#,
+            "\n",
+            r#"{"timestampEpochSecs":1700000000,"server":"github","action":"start"}"#,
+            "\n",
+        ),
+    )
+    .unwrap();
 
     let output = berth_with_home(tmp.path())
-        .args(["unlink", "claude-desktop"])
+        .args(["audit", "github", "--utc"])
         .output()
         .unwrap();
     assert!(output.status.success());
-
-    let config_path = tmp
-        .path()
-        .join(".berth/clients/claude-desktop/claude_desktop_config.json");
-    let content = std::fs::read_to_string(config_path).unwrap();
-    let json: serde_json::Value = serde_json::from_str(&content).unwrap();
-    assert!(json["mcpServers"]["github"].is_null());
-}
-
-#[test]
-fn link_cursor_writes_config() {
-    let tmp = tempfile::tempdir().unwrap();
-    berth_with_home(tmp.path())
-        .args(["install", "github"])
-        .output()
-        .unwrap();
-    berth_with_home(tmp.path())
-        .args(["config", "github", "--set", "token=abc123"])
-        .output()
-        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let start = stdout.find("2023-11-14T22:13:20.000Z").unwrap();
+    let stop = stdout.find("2023-11-14T22:13:20.250Z").unwrap();
+    assert!(start < stop);
 
     let output = berth_with_home(tmp.path())
-        .args(["link", "cursor"])
+        .args(["analytics", "--utc"])
         .output()
         .unwrap();
     assert!(output.status.success());
-
-    let config_path = tmp
-        .path()
-        .join(".berth/clients/cursor/cursor_mcp_config.json");
-    assert!(config_path.exists());
-    let content = std::fs::read_to_string(config_path).unwrap();
-    let json: serde_json::Value = serde_json::from_str(&content).unwrap();
-    assert_eq!(
-        json["mcpServers"]["github"]["env"]["GITHUB_TOKEN"],
-        "abc123"
-    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("time range: 2023-11-14T22:13:20.000Z .. 2023-11-14T22:13:20.250Z"));
 }
 
 #[test]
-fn link_respects_env_permission_revoke() {
+fn audit_export_json_output_writes_array_file() {
     let tmp = tempfile::tempdir().unwrap();
     berth_with_home(tmp.path())
         .args(["install", "github"])
@@ -2552,63 +7270,40 @@ fn link_respects_env_permission_revoke() {
         .args(["config", "github", "--set", "token=abc123"])
         .output()
         .unwrap();
+    patch_runtime_to_long_running(tmp.path(), "github");
     berth_with_home(tmp.path())
-        .args(["permissions", "github", "--revoke", "env:GITHUB_TOKEN"])
-        .output()
-        .unwrap();
-
-    let output = berth_with_home(tmp.path())
-        .args(["link", "claude-desktop"])
-        .output()
-        .unwrap();
-    assert!(output.status.success());
-
-    let config_path = tmp
-        .path()
-        .join(".berth/clients/claude-desktop/claude_desktop_config.json");
-    let content = std::fs::read_to_string(config_path).unwrap();
-    let json: serde_json::Value = serde_json::from_str(&content).unwrap();
-    assert!(json["mcpServers"]["github"]["env"]["GITHUB_TOKEN"].is_null());
-}
-
-#[test]
-fn link_skips_server_denied_by_org_policy() {
-    let tmp = tempfile::tempdir().unwrap();
-    berth_with_home(tmp.path())
-        .args(["install", "github"])
+        .args(["start", "github"])
         .output()
         .unwrap();
     berth_with_home(tmp.path())
-        .args(["config", "github", "--set", "token=abc123"])
+        .args(["stop", "github"])
         .output()
         .unwrap();
-    write_global_policy(
-        tmp.path(),
-        r#"
-[servers]
-deny = ["github"]
-"#,
-    );
 
+    let export_file = tmp.path().join("exports").join("audit.json");
     let output = berth_with_home(tmp.path())
-        .args(["link", "claude-desktop"])
+        .args([
+            "audit",
+            "github",
+            "--json",
+            "--export",
+            export_file.to_str().unwrap(),
+        ])
         .output()
         .unwrap();
     assert!(output.status.success());
-    let stderr = String::from_utf8_lossy(&output.stderr);
-    assert!(stderr.contains("Skipped by org policy"));
-    assert!(stderr.contains("github"));
+    assert!(export_file.exists());
 
-    let config_path = tmp
-        .path()
-        .join(".berth/clients/claude-desktop/claude_desktop_config.json");
-    let content = std::fs::read_to_string(config_path).unwrap();
+    let content = std::fs::read_to_string(export_file).unwrap();
     let json: serde_json::Value = serde_json::from_str(&content).unwrap();
-    assert!(json["mcpServers"]["github"].is_null());
+    let arr = json.as_array().unwrap();
+    assert!(!arr.is_empty());
+    assert!(arr.iter().any(|ev| ev["action"].as_str() == Some("start")));
+    assert!(arr.iter().any(|ev| ev["action"].as_str() == Some("stop")));
 }
 
 #[test]
-fn unlink_windsurf_removes_linked_servers() {
+fn audit_export_jsonl_output_writes_line_delimited_file() {
     let tmp = tempfile::tempdir().unwrap();
     berth_with_home(tmp.path())
         .args(["install", "github"])
@@ -2618,117 +7313,241 @@ fn unlink_windsurf_removes_linked_servers() {
         .args(["config", "github", "--set", "token=abc123"])
         .output()
         .unwrap();
+    patch_runtime_to_long_running(tmp.path(), "github");
     berth_with_home(tmp.path())
-        .args(["link", "windsurf"])
+        .args(["start", "github"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["stop", "github"])
         .output()
         .unwrap();
 
+    let export_file = tmp.path().join("exports").join("audit.jsonl");
     let output = berth_with_home(tmp.path())
-        .args(["unlink", "windsurf"])
+        .args([
+            "audit",
+            "github",
+            "--action",
+            "start",
+            "--export",
+            export_file.to_str().unwrap(),
+        ])
         .output()
         .unwrap();
     assert!(output.status.success());
+    assert!(export_file.exists());
 
-    let config_path = tmp
-        .path()
-        .join(".berth/clients/windsurf/windsurf_mcp_config.json");
-    let content = std::fs::read_to_string(config_path).unwrap();
-    let json: serde_json::Value = serde_json::from_str(&content).unwrap();
-    assert!(json["mcpServers"]["github"].is_null());
+    let content = std::fs::read_to_string(export_file).unwrap();
+    let lines: Vec<&str> = content.lines().filter(|line| !line.is_empty()).collect();
+    assert!(!lines.is_empty());
+    for line in lines {
+        let parsed: serde_json::Value = serde_json::from_str(line).unwrap();
+        assert_eq!(parsed["action"].as_str(), Some("start"));
+        assert_eq!(parsed["server"].as_str(), Some("github"));
+    }
 }
 
 #[test]
-fn link_continue_writes_config() {
+fn audit_filters_by_pid() {
     let tmp = tempfile::tempdir().unwrap();
-    berth_with_home(tmp.path())
-        .args(["install", "github"])
+    let audit = tmp.path().join(".berth/audit");
+    std::fs::create_dir_all(&audit).unwrap();
+    std::fs::write(
+        audit.join("audit.jsonl"),
+        concat!(
+            "{\"timestampEpochMs\":2000,\"timestampEpochSecs\":2,\"server\":\"github\",\"action\":\"stop\",\"pid\":41,\"exitCode\":0}\n",
+            "{\"timestampEpochMs\":1000,\"timestampEpochSecs\":1,\"server\":\"github\",\"action\":\"start\",\"pid\":41}\n",
+            "{\"timestampEpochMs\":3000,\"timestampEpochSecs\":3,\"server\":\"github\",\"action\":\"start\",\"pid\":42}\n",
+        ),
+    )
+    .unwrap();
+
+    let output = berth_with_home(tmp.path())
+        .args(["audit", "github", "--pid", "41", "--json"])
         .output()
         .unwrap();
-    berth_with_home(tmp.path())
-        .args(["config", "github", "--set", "token=abc123"])
+    assert!(output.status.success());
+    let entries: Vec<serde_json::Value> = serde_json::from_slice(&output.stdout).unwrap();
+    let actions: Vec<&str> = entries
+        .iter()
+        .map(|e| e["action"].as_str().unwrap())
+        .collect();
+    assert_eq!(actions, vec!["start", "stop"]);
+    assert_eq!(entries[1]["exitCode"].as_i64(), Some(0));
+}
+
+#[test]
+fn audit_verify_reports_the_first_broken_hash_link() {
+    let tmp = tempfile::tempdir().unwrap();
+    let berth_dir = tmp.path().join(".berth");
+    std::fs::create_dir_all(&berth_dir).unwrap();
+    std::fs::write(
+        berth_dir.join("policy.toml"),
+        "[audit]\nhash_chain = true\n",
+    )
+    .unwrap();
+
+    let output = berth_with_home(tmp.path())
+        .args(["audit", "--verify"])
         .output()
         .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("No audit entry is hash-chained"));
 
+    for server in ["github", "sequential-thinking"] {
+        let output = berth_with_home(tmp.path())
+            .args(["install", server])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+    }
     let output = berth_with_home(tmp.path())
-        .args(["link", "continue"])
+        .args(["audit", "--verify"])
         .output()
         .unwrap();
     assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("hash chain intact: 2 chained entries"),
+        "{stdout}"
+    );
 
-    let config_path = tmp
-        .path()
-        .join(".berth/clients/continue/continue_config.json");
-    assert!(config_path.exists());
-    let content = std::fs::read_to_string(config_path).unwrap();
-    let json: serde_json::Value = serde_json::from_str(&content).unwrap();
-    assert_eq!(
-        json["mcpServers"]["github"]["env"]["GITHUB_TOKEN"],
-        "abc123"
+    let audit_path = berth_dir.join("audit/audit.jsonl");
+    let audit = std::fs::read_to_string(&audit_path).unwrap();
+    assert!(audit.lines().all(|line| line.contains("\"prevHash\":")));
+    std::fs::write(&audit_path, audit.replacen("github", "gitlab", 1)).unwrap();
+    let output = berth_with_home(tmp.path())
+        .args(["audit", "--verify"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("audit.jsonl:2: prevHash does not match"),
+        "{stderr}"
     );
 }
 
 #[test]
-fn unlink_continue_removes_linked_servers() {
+fn explain_audit_narrates_an_entry_with_neighbours_and_log_lines() {
     let tmp = tempfile::tempdir().unwrap();
-    berth_with_home(tmp.path())
-        .args(["install", "github"])
-        .output()
-        .unwrap();
-    berth_with_home(tmp.path())
-        .args(["config", "github", "--set", "token=abc123"])
+    let berth_dir = tmp.path().join(".berth");
+    std::fs::create_dir_all(berth_dir.join("audit")).unwrap();
+    std::fs::create_dir_all(berth_dir.join("logs")).unwrap();
+    std::fs::write(
+        berth_dir.join("audit/audit.jsonl"),
+        concat!(
+            "{\"timestampEpochMs\":1000000,\"timestampEpochSecs\":1000,\"server\":\"github\",\"action\":\"start\",\"pid\":41,\"command\":\"npx\"}\n",
+            "{\"timestampEpochMs\":1500000,\"timestampEpochSecs\":1500,\"server\":\"fetch\",\"action\":\"start\",\"pid\":50}\n",
+            "{\"timestampEpochMs\":2000000,\"timestampEpochSecs\":2000,\"server\":\"github\",\"action\":\"exit\",\"pid\":41,\"exitCode\":1}\n",
+            "{\"timestampEpochMs\":2001000,\"timestampEpochSecs\":2001,\"server\":\"github\",\"action\":\"crash-loop\",\"pid\":41}\n",
+        ),
+    )
+    .unwrap();
+    std::fs::write(
+        berth_dir.join("logs/github.log"),
+        "[1000] START pid=41\n[1999] GITHUB_TOKEN environment variable is required\n[2000] EXIT code=1\n",
+    )
+    .unwrap();
+
+    let output = berth_with_home(tmp.path())
+        .args(["explain", "audit", "3", "--utc"])
         .output()
         .unwrap();
-    berth_with_home(tmp.path())
-        .args(["link", "continue"])
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Audit entry #3"), "{stdout}");
+    assert!(stdout.contains("github exited with code 1."), "{stdout}");
+    assert!(stdout.contains("Berth supervisor"), "{stdout}");
+    assert!(stdout.contains("PID:") && stdout.contains("41"), "{stdout}");
+    assert!(stdout.contains("#1") && stdout.contains("#4"), "{stdout}");
+    assert!(!stdout.contains("#2 "), "{stdout}");
+    assert!(stdout.contains("github.log:2-3"), "{stdout}");
+    assert!(stdout.contains("GITHUB_TOKEN environment variable is required"));
+
+    let json = berth_with_home(tmp.path())
+        .args(["audit", "github", "--json"])
         .output()
         .unwrap();
+    let entries: Vec<serde_json::Value> = serde_json::from_slice(&json.stdout).unwrap();
+    let id = entries[2]["id"].as_str().unwrap().to_string();
+    assert_eq!(entries[2]["action"].as_str(), Some("crash-loop"));
+    for entry in [id[..6].to_string(), entries[2].to_string()] {
+        let output = berth_with_home(tmp.path())
+            .args(["explain", "audit", &entry])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("Audit entry #4"), "{stdout}");
+        assert!(stdout.contains(&id), "{stdout}");
+    }
 
     let output = berth_with_home(tmp.path())
-        .args(["unlink", "continue"])
+        .args(["explain", "audit", "#9"])
         .output()
         .unwrap();
-    assert!(output.status.success());
-
-    let config_path = tmp
-        .path()
-        .join(".berth/clients/continue/continue_config.json");
-    let content = std::fs::read_to_string(config_path).unwrap();
-    let json: serde_json::Value = serde_json::from_str(&content).unwrap();
-    assert!(json["mcpServers"]["github"].is_null());
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("the log holds 4 entries"));
 }
 
 #[test]
-fn link_vscode_writes_config() {
+fn history_renders_lifecycle_timeline_with_folded_crash_loops() {
     let tmp = tempfile::tempdir().unwrap();
     berth_with_home(tmp.path())
         .args(["install", "github"])
         .output()
         .unwrap();
-    berth_with_home(tmp.path())
-        .args(["config", "github", "--set", "token=abc123"])
-        .output()
-        .unwrap();
+    let audit_path = tmp.path().join(".berth/audit/audit.jsonl");
+    let mut audit = std::fs::read_to_string(&audit_path).unwrap();
+    assert!(audit.contains("\"action\":\"install\""));
+    let now_ms = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64;
+    for (offset, action, extra) in [
+        (1_000, "start", ",\"pid\":42"),
+        (2_000, "mcp-tool-call", ""),
+        (3_000, "exit", ",\"exitCode\":1"),
+        (3_100, "auto-restart", ""),
+        (4_000, "exit", ",\"exitCode\":1"),
+        (4_100, "auto-restart", ""),
+        (5_000, "stop", ""),
+    ] {
+        let ms = now_ms + offset;
+        audit.push_str(&format!(
+            "{{\"timestampEpochMs\":{ms},\"timestampEpochSecs\":{},\"server\":\"github\",\"action\":\"{action}\"{extra}}}\n",
+            ms / 1_000
+        ));
+    }
+    std::fs::write(&audit_path, audit).unwrap();
 
     let output = berth_with_home(tmp.path())
-        .args(["link", "vscode"])
+        .args(["history", "github", "--utc"])
         .output()
         .unwrap();
     assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("installed v"));
+    assert!(stdout.contains("started (pid 42)"));
+    assert!(stdout.contains("crashed (exit code 1), auto-restarted ×2"));
+    assert!(stdout.contains("stopped"));
+    assert!(!stdout.contains("mcp-tool-call"));
 
-    let config_path = tmp
-        .path()
-        .join(".berth/clients/vscode/vscode_mcp_config.json");
-    assert!(config_path.exists());
-    let content = std::fs::read_to_string(config_path).unwrap();
-    let json: serde_json::Value = serde_json::from_str(&content).unwrap();
-    assert_eq!(
-        json["mcpServers"]["github"]["env"]["GITHUB_TOKEN"],
-        "abc123"
-    );
+    let output = berth_with_home(tmp.path())
+        .args(["history", "github", "--json"])
+        .output()
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["status"], "stopped");
+    let events = json["events"].as_array().unwrap();
+    assert_eq!(events.len(), 4);
+    assert_eq!(events[2]["count"], 2);
 }
 
 #[test]
-fn unlink_vscode_removes_linked_servers() {
+fn analytics_summarizes_audit_events_for_server() {
     let tmp = tempfile::tempdir().unwrap();
     berth_with_home(tmp.path())
         .args(["install", "github"])
@@ -2738,339 +7557,529 @@ fn unlink_vscode_removes_linked_servers() {
         .args(["config", "github", "--set", "token=abc123"])
         .output()
         .unwrap();
+    patch_runtime_to_long_running(tmp.path(), "github");
     berth_with_home(tmp.path())
-        .args(["link", "vscode"])
+        .args(["start", "github"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["stop", "github"])
         .output()
         .unwrap();
 
     let output = berth_with_home(tmp.path())
-        .args(["unlink", "vscode"])
+        .args(["analytics", "github", "--top", "3"])
         .output()
         .unwrap();
     assert!(output.status.success());
-
-    let config_path = tmp
-        .path()
-        .join(".berth/clients/vscode/vscode_mcp_config.json");
-    let content = std::fs::read_to_string(config_path).unwrap();
-    let json: serde_json::Value = serde_json::from_str(&content).unwrap();
-    assert!(json["mcpServers"]["github"].is_null());
-}
-
-#[test]
-fn link_unknown_client_exits_1() {
-    let output = berth().args(["link", "unknown-client"]).output().unwrap();
-    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Audit analytics"));
+    assert!(stdout.contains("total events"));
+    assert!(stdout.contains("Top actions"));
+    assert!(stdout.contains("start"));
+    assert!(stdout.contains("stop"));
 }
 
-// --- permissions & audit ---
-
 #[test]
-fn permissions_show_declared_permissions() {
+fn top_ranks_tools_with_latency_and_error_rates() {
     let tmp = tempfile::tempdir().unwrap();
-    berth_with_home(tmp.path())
-        .args(["install", "github"])
+    let audit_dir = tmp.path().join(".berth").join("audit");
+    std::fs::create_dir_all(&audit_dir).unwrap();
+    std::fs::write(
+        audit_dir.join("audit.jsonl"),
+        concat!(
+            r#"{"timestampEpochMs":1700000000000,"timestampEpochSecs":1700000000,"server":"github","action":"mcp-tool-call","args":["search"]}"#,
+            "\n",
+            r#"{"timestampEpochMs":1700000000100,"timestampEpochSecs":1700000000,"server":"github","action":"mcp-tool-result","args":["search"],"durationMs":100,"error":false}"#,
+            "\n",
+            r#"{"timestampEpochMs":1700000001000,"timestampEpochSecs":1700000001,"server":"github","action":"mcp-tool-call","args":["search"]}"#,
+            "\n",
+            r#"{"timestampEpochMs":1700000001300,"timestampEpochSecs":1700000001,"server":"github","action":"mcp-tool-result","args":["search"],"durationMs":300,"error":true}"#,
+            "\n",
+            r#"{"timestampEpochMs":1700000002000,"timestampEpochSecs":1700000002,"server":"slack","action":"mcp-tool-call","args":["post"]}"#,
+            "\n",
+        ),
+    )
+    .unwrap();
+
+    let output = berth_with_home(tmp.path())
+        .args(["top", "--json"])
         .output()
         .unwrap();
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["totalToolCalls"].as_u64(), Some(3));
+    assert_eq!(json["tools"][0]["tool"].as_str(), Some("search"));
+    assert_eq!(json["tools"][0]["avgLatencyMs"].as_f64(), Some(200.0));
+    assert_eq!(json["tools"][0]["errorRate"].as_f64(), Some(0.5));
+    assert_eq!(json["servers"][0]["server"].as_str(), Some("github"));
+
+    let output = berth_with_home(tmp.path()).args(["top"]).output().unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Most-called tools"));
+    assert!(stdout.contains("Busiest servers"));
+    assert!(stdout.contains("50.0%"));
+    assert!(stdout.contains("200ms"));
 
     let output = berth_with_home(tmp.path())
-        .args(["permissions", "github"])
+        .args(["top", "--since", "1h"])
         .output()
         .unwrap();
     assert!(output.status.success());
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    assert!(stdout.contains("network:api.github.com:443"));
-    assert!(stdout.contains("env:GITHUB_TOKEN"));
-    assert!(stdout.contains("exec:git"));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("No proxied requests recorded"));
 }
 
+#[cfg(unix)]
 #[test]
-fn permissions_grant_and_revoke_persist_overrides() {
+fn proxy_rejects_tool_calls_outside_effective_permissions() {
     let tmp = tempfile::tempdir().unwrap();
     berth_with_home(tmp.path())
         .args(["install", "github"])
         .output()
         .unwrap();
-
-    let grant = berth_with_home(tmp.path())
-        .args([
-            "permissions",
-            "github",
-            "--grant",
-            "network:example.com:443",
-        ])
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
         .output()
         .unwrap();
-    assert!(grant.status.success());
+    patch_runtime_to_cat(tmp.path(), "github");
+    let config_path = tmp.path().join(".berth/servers/github.toml");
+    let mut config = std::fs::read_to_string(&config_path).unwrap();
+    config.push_str(concat!(
+        "\n[permissions.tools]\n",
+        "search_repositories = [\"network:api.github.com:443\"]\n",
+        "delete_repository = [\"filesystem:write:~/.ssh\"]\n",
+    ));
+    std::fs::write(&config_path, config).unwrap();
 
-    let show_after_grant = berth_with_home(tmp.path())
-        .args(["permissions", "github"])
-        .output()
+    let mut child = berth_with_home(tmp.path())
+        .args(["proxy", "github"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
         .unwrap();
-    let stdout_grant = String::from_utf8_lossy(&show_after_grant.stdout);
-    assert!(stdout_grant.contains("grant:"));
-    assert!(stdout_grant.contains("network:example.com:443"));
-
-    let revoke = berth_with_home(tmp.path())
-        .args([
-            "permissions",
-            "github",
-            "--revoke",
-            "network:example.com:443",
-        ])
-        .output()
+    let input = concat!(
+        r#"{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{"name":"search_repositories"}}"#,
+        "\n",
+        r#"{"jsonrpc":"2.0","id":2,"method":"tools/call","params":{"name":"delete_repository"}}"#,
+        "\n",
+    );
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
         .unwrap();
-    assert!(revoke.status.success());
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
 
-    let show_after_revoke = berth_with_home(tmp.path())
-        .args(["permissions", "github"])
-        .output()
-        .unwrap();
-    let stdout_revoke = String::from_utf8_lossy(&show_after_revoke.stdout);
-    assert!(stdout_revoke.contains("revoke:"));
-    assert!(stdout_revoke.contains("network:example.com:443"));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let responses: Vec<serde_json::Value> = stdout
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    // `cat` echoes the allowed call back; the denied one never reaches it.
+    assert!(responses
+        .iter()
+        .any(|r| r["id"] == 1 && r["method"] == "tools/call"));
+    assert!(!responses
+        .iter()
+        .any(|r| r["id"] == 2 && r["method"] == "tools/call"));
+    let denied = responses.iter().find(|r| r["id"] == 2).unwrap();
+    assert_eq!(denied["error"]["code"], -32001);
+    let message = denied["error"]["message"].as_str().unwrap();
+    assert!(
+        message.starts_with("Permission denied for github"),
+        "{message}"
+    );
+    assert!(message.contains("filesystem:write:~/.ssh"), "{message}");
+
+    let audit = std::fs::read_to_string(tmp.path().join(".berth/audit/audit.jsonl")).unwrap();
+    assert!(audit.contains(r#""action":"permission-denied""#), "{audit}");
+    assert!(
+        !audit
+            .lines()
+            .any(|line| line.contains("mcp-tool-call") && line.contains("delete_repository")),
+        "{audit}"
+    );
 }
 
+#[cfg(unix)]
 #[test]
-fn permissions_reset_clears_overrides() {
+fn proxy_relays_unix_socket_servers_through_bridge() {
+    if Command::new("python3").arg("--version").output().is_err() {
+        return;
+    }
     let tmp = tempfile::tempdir().unwrap();
     berth_with_home(tmp.path())
         .args(["install", "github"])
         .output()
         .unwrap();
-
     berth_with_home(tmp.path())
-        .args([
-            "permissions",
-            "github",
-            "--grant",
-            "network:example.com:443",
-        ])
+        .args(["config", "github", "--set", "token=abc123"])
         .output()
         .unwrap();
+    patch_runtime_to_script(
+        tmp.path(),
+        "github",
+        "exec python3 -c \"import os, socket\ns = socket.socket(socket.AF_UNIX)\ns.bind(os.environ['BERTH_SOCKET'])\ns.listen(1)\nconn, _ = s.accept()\nstream = conn.makefile('rwb')\nfor line in stream:\n  stream.write(line)\n  stream.flush()\"",
+    );
+    let config_path = tmp.path().join(".berth/servers/github.toml");
+    let content = std::fs::read_to_string(&config_path).unwrap();
+    let mut value: toml::Value = toml::from_str(&content).unwrap();
+    value["runtime"].as_table_mut().unwrap().insert(
+        "transport".to_string(),
+        toml::Value::String("unix-socket".to_string()),
+    );
+    std::fs::write(&config_path, toml::to_string(&value).unwrap()).unwrap();
 
-    let reset = berth_with_home(tmp.path())
-        .args(["permissions", "github", "--reset"])
-        .output()
+    let mut child = berth_with_home(tmp.path())
+        .args(["proxy", "github"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
         .unwrap();
-    assert!(reset.status.success());
-
-    let show = berth_with_home(tmp.path())
-        .args(["permissions", "github"])
-        .output()
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"ping\"}\n")
         .unwrap();
-    assert!(show.status.success());
-    let stdout = String::from_utf8_lossy(&show.stdout);
-    assert!(!stdout.contains("network:example.com:443"));
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(r#""method":"ping""#), "{stdout}");
+}
+
+/// Returns a port where it and the `count` ports after it are free.
+fn free_port_run(count: u16) -> u16 {
+    loop {
+        let base = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        if base.checked_add(count).is_some()
+            && (base..=base + count).all(|p| std::net::TcpListener::bind(("127.0.0.1", p)).is_ok())
+        {
+            return base;
+        }
+    }
 }
 
+#[cfg(unix)]
 #[test]
-fn permissions_export_outputs_json() {
+fn start_balances_http_replicas_and_restart_rolls_them() {
+    if Command::new("python3").arg("--version").output().is_err() {
+        return;
+    }
     let tmp = tempfile::tempdir().unwrap();
     berth_with_home(tmp.path())
         .args(["install", "github"])
         .output()
         .unwrap();
     berth_with_home(tmp.path())
-        .args([
-            "permissions",
-            "github",
-            "--grant",
-            "network:example.com:443",
-        ])
+        .args(["config", "github", "--set", "token=abc123"])
         .output()
         .unwrap();
-
+    // Answers every connection with the port it listens on.
+    patch_runtime_to_script(
+        tmp.path(),
+        "github",
+        "exec python3 -c \"import os, socket\ns = socket.socket()\ns.setsockopt(socket.SOL_SOCKET, socket.SO_REUSEADDR, 1)\ns.bind(('127.0.0.1', int(os.environ['BERTH_PORT'])))\ns.listen(8)\nwhile True:\n  conn, _ = s.accept()\n  conn.sendall(os.environ['BERTH_PORT'].encode())\n  conn.close()\"",
+    );
+    let config_path = tmp.path().join(".berth/servers/github.toml");
+    let content = std::fs::read_to_string(&config_path).unwrap();
+    let mut value: toml::Value = toml::from_str(&content).unwrap();
+    value["runtime"].as_table_mut().unwrap().insert(
+        "transport".to_string(),
+        toml::Value::String("http".to_string()),
+    );
+    std::fs::write(&config_path, toml::to_string(&value).unwrap()).unwrap();
+
+    let port = free_port_run(3);
+    for setting in ["berth.replicas=3".to_string(), format!("berth.port={port}")] {
+        if setting.starts_with("berth.port") {
+            let output = berth_with_home(tmp.path())
+                .args(["start", "github"])
+                .output()
+                .unwrap();
+            assert!(!output.status.success());
+            assert!(String::from_utf8_lossy(&output.stderr).contains("berth.port"));
+        }
+        let output = berth_with_home(tmp.path())
+            .args(["config", "github", "--set", &setting])
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{setting}");
+    }
     let output = berth_with_home(tmp.path())
-        .args(["permissions", "github", "--export"])
+        .args(["start", "github"])
         .output()
         .unwrap();
-    assert!(output.status.success());
     let stdout = String::from_utf8_lossy(&output.stdout);
-    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
-
-    assert_eq!(json["server"].as_str(), Some("github"));
-    assert!(json["declared"]["network"]
-        .as_array()
-        .unwrap()
-        .iter()
-        .any(|v| v.as_str() == Some("api.github.com:443")));
-    assert!(json["overrides"]["grant"]
-        .as_array()
-        .unwrap()
-        .iter()
-        .any(|v| v.as_str() == Some("network:example.com:443")));
-    assert!(json["effective"]["network"]
-        .as_array()
-        .unwrap()
-        .iter()
-        .any(|v| v.as_str() == Some("example.com:443")));
-    assert!(json["declared"]["exec"]
-        .as_array()
-        .unwrap()
-        .iter()
-        .any(|v| v.as_str() == Some("git")));
-}
+    assert!(output.status.success(), "{stdout}");
+    assert!(stdout.contains(&format!("Started github@3 on port {}", port + 3)));
+
+    let ask = || {
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        let mut reply = String::new();
+        stream.read_to_string(&mut reply).unwrap();
+        reply.parse::<u16>().unwrap()
+    };
+    // The gateway's readiness probe took a turn too, so the cycle may start
+    // at any replica.
+    let mut replies: Vec<u16> = (0..4).map(|_| ask()).collect();
+    assert_eq!(replies[3], replies[0]);
+    replies.truncate(3);
+    replies.sort();
+    assert_eq!(replies, [port + 1, port + 2, port + 3]);
+
+    let rows = |tmp: &std::path::Path| -> Vec<serde_json::Value> {
+        let output = berth_with_home(tmp)
+            .args(["status", "--json"])
+            .output()
+            .unwrap();
+        serde_json::from_slice(&output.stdout).unwrap()
+    };
+    let before = rows(tmp.path());
+    let names: Vec<&str> = before.iter().map(|r| r["name"].as_str().unwrap()).collect();
+    assert_eq!(names, ["github", "github@1", "github@2", "github@3"]);
+    assert_eq!(before[0]["port"], port);
+    assert!(before[0].get("replicaOf").is_none());
+    assert_eq!(before[2]["replicaOf"], "github");
+    assert_eq!(before[2]["port"], port + 2);
+    assert!(
+        before.iter().all(|r| r["status"] == "running"),
+        "{before:?}"
+    );
 
-#[test]
-fn permissions_rejects_invalid_format() {
-    let tmp = tempfile::tempdir().unwrap();
-    berth_with_home(tmp.path())
-        .args(["install", "github"])
+    let output = berth_with_home(tmp.path())
+        .args(["restart", "github"])
         .output()
         .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{stdout}");
+    assert!(stdout.contains("Restarted github@1.") && stdout.contains("Restarted github@3."));
+    assert!(!stdout.contains("Restarted github."), "{stdout}");
+    let after = rows(tmp.path());
+    assert_eq!(after[0]["pid"], before[0]["pid"]);
+    assert_ne!(after[1]["pid"], before[1]["pid"]);
+    assert!(after.iter().all(|r| r["status"] == "running"), "{after:?}");
+    assert!((port + 1..=port + 3).contains(&ask()));
 
     let output = berth_with_home(tmp.path())
-        .args(["permissions", "github", "--grant", "network:api.github.com"])
+        .args(["stop", "github"])
         .output()
         .unwrap();
-    assert!(!output.status.success());
-    let stderr = String::from_utf8_lossy(&output.stderr);
-    assert!(stderr.contains("Invalid permission format"));
+    assert!(output.status.success());
+    assert!(rows(tmp.path()).iter().all(|r| r["status"] == "stopped"));
 }
 
+#[cfg(unix)]
 #[test]
-fn permissions_accepts_valid_env_permission_override() {
+fn proxy_asks_permission_prompt_hook_and_remembers_always() {
+    use std::os::unix::fs::PermissionsExt;
+
     let tmp = tempfile::tempdir().unwrap();
     berth_with_home(tmp.path())
         .args(["install", "github"])
         .output()
         .unwrap();
-
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
+    let hook = tmp.path().join("ask.sh");
+    std::fs::write(
+        &hook,
+        "#!/bin/sh\nread request\ncase \"$request\" in *delete_repository*) echo always ;; *) echo deny ;; esac\n",
+    )
+    .unwrap();
+    std::fs::set_permissions(&hook, std::fs::Permissions::from_mode(0o755)).unwrap();
     let output = berth_with_home(tmp.path())
-        .args(["permissions", "github", "--grant", "env:GITHUB_TOKEN"])
+        .args([
+            "config",
+            "github",
+            "--set",
+            &format!("berth.permission-prompt=hook:{}", hook.display()),
+        ])
         .output()
         .unwrap();
     assert!(output.status.success());
+    patch_runtime_to_cat(tmp.path(), "github");
+    let config_path = tmp.path().join(".berth/servers/github.toml");
+    let mut config = std::fs::read_to_string(&config_path).unwrap();
+    config.push_str(concat!(
+        "\n[permissions.tools]\n",
+        "delete_repository = [\"filesystem:write:~/.ssh\"]\n",
+        "exfiltrate = [\"network:evil.example.com:443\"]\n",
+    ));
+    std::fs::write(&config_path, config).unwrap();
+
+    let mut child = berth_with_home(tmp.path())
+        .args(["proxy", "github"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let input = concat!(
+        r#"{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{"name":"delete_repository"}}"#,
+        "\n",
+        r#"{"jsonrpc":"2.0","id":2,"method":"tools/call","params":{"name":"exfiltrate"}}"#,
+        "\n",
+    );
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let responses: Vec<serde_json::Value> = stdout
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert!(responses
+        .iter()
+        .any(|r| r["id"] == 1 && r["method"] == "tools/call"));
+    let denied = responses.iter().find(|r| r["id"] == 2).unwrap();
+    assert_eq!(denied["error"]["code"], -32001);
+
+    let overrides =
+        std::fs::read_to_string(tmp.path().join(".berth/permissions/github.toml")).unwrap();
+    assert!(overrides.contains("filesystem:write:~/.ssh"), "{overrides}");
+    assert!(!overrides.contains("evil.example.com"), "{overrides}");
+    let audit = std::fs::read_to_string(tmp.path().join(".berth/audit/audit.jsonl")).unwrap();
+    assert!(
+        audit.contains(r#""args":["delete_repository","always","filesystem:write:~/.ssh"]"#),
+        "{audit}"
+    );
+    assert!(
+        audit.contains(r#""args":["exfiltrate","deny","network:evil.example.com:443"]"#),
+        "{audit}"
+    );
 }
 
+#[cfg(unix)]
 #[test]
-fn permissions_accepts_valid_filesystem_and_exec_permission_override() {
+fn proxy_audits_tool_call_latency_for_top() {
     let tmp = tempfile::tempdir().unwrap();
     berth_with_home(tmp.path())
         .args(["install", "github"])
         .output()
         .unwrap();
-
-    let fs_output = berth_with_home(tmp.path())
-        .args(["permissions", "github", "--grant", "filesystem:read:/tmp"])
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
         .output()
         .unwrap();
-    assert!(fs_output.status.success());
+    patch_runtime_to_cat(tmp.path(), "github");
 
-    let exec_output = berth_with_home(tmp.path())
-        .args(["permissions", "github", "--grant", "exec:git"])
-        .output()
+    let mut child = berth_with_home(tmp.path())
+        .args(["proxy", "github"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
         .unwrap();
-    assert!(exec_output.status.success());
-}
+    // `cat` echoes the request, which carries an id but also a method, so a
+    // separate response line stands in for the server's answer.
+    let input = concat!(
+        r#"{"jsonrpc":"2.0","id":7,"method":"tools/call","params":{"name":"search","arguments":{"q":"berth","token":"ghp_secret"}}}"#,
+        "\n",
+        r#"{"jsonrpc":"2.0","id":7,"error":{"code":-1,"message":"boom"}}"#,
+        "\n",
+    );
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+
+    let audit = std::fs::read_to_string(tmp.path().join(".berth/audit/audit.jsonl")).unwrap();
+    assert!(audit.contains("\"action\":\"mcp-tool-result\""), "{audit}");
+    assert!(audit.contains("\"error\":true"), "{audit}");
+    assert!(
+        audit.contains(r#""args":["search","q=berth","token=[redacted]"]"#),
+        "{audit}"
+    );
+    assert!(!audit.contains("ghp_secret"), "{audit}");
 
-#[test]
-fn policy_init_writes_template() {
-    let tmp = tempfile::tempdir().unwrap();
     let output = berth_with_home(tmp.path())
-        .args(["policy", "--init"])
+        .args(["top", "github", "--json"])
         .output()
         .unwrap();
-    assert!(output.status.success());
-
-    let policy_path = tmp.path().join(".berth").join("policy.toml");
-    assert!(policy_path.exists());
-    let content = std::fs::read_to_string(policy_path).unwrap();
-    assert!(content.contains("[servers]"));
-    assert!(content.contains("[permissions]"));
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["tools"][0]["calls"].as_u64(), Some(1));
+    assert_eq!(json["tools"][0]["errors"].as_u64(), Some(1));
 }
 
 #[test]
-fn policy_set_and_show_json() {
+fn analytics_json_output_is_machine_readable() {
     let tmp = tempfile::tempdir().unwrap();
-    let set_network = berth_with_home(tmp.path())
-        .args(["policy", "--set", "permissions.deny_network_wildcard=true"])
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
         .output()
         .unwrap();
-    assert!(set_network.status.success());
-
-    let set_servers = berth_with_home(tmp.path())
-        .args(["policy", "--set", "servers.deny=github,filesystem"])
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
+    patch_runtime_to_long_running(tmp.path(), "github");
+    berth_with_home(tmp.path())
+        .args(["start", "github"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["stop", "github"])
         .output()
         .unwrap();
-    assert!(set_servers.status.success());
 
     let output = berth_with_home(tmp.path())
-        .args(["policy", "--json"])
+        .args(["analytics", "github", "--json"])
         .output()
         .unwrap();
     assert!(output.status.success());
     let stdout = String::from_utf8_lossy(&output.stdout);
     let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
-    assert_eq!(
-        json["permissions"]["deny_network_wildcard"].as_bool(),
-        Some(true)
-    );
-    assert!(json["servers"]["deny"]
+    assert!(json["totalEvents"].as_u64().unwrap_or(0) >= 2);
+    assert_eq!(json["uniqueServers"].as_u64(), Some(1));
+    assert!(json["estimatedCostUsd"].as_f64().unwrap_or(0.0) > 0.0);
+    assert!(json["topActions"]
         .as_array()
         .unwrap()
         .iter()
-        .any(|entry| entry.as_str() == Some("github")));
+        .any(|item| item["value"].as_str() == Some("start")));
 }
 
 #[test]
-fn policy_check_reports_denied_server() {
+fn analytics_invalid_since_exits_1() {
     let tmp = tempfile::tempdir().unwrap();
-    berth_with_home(tmp.path())
-        .args(["install", "github"])
-        .output()
-        .unwrap();
-    write_global_policy(
-        tmp.path(),
-        r#"
-[servers]
-deny = ["github"]
-"#,
-    );
-
     let output = berth_with_home(tmp.path())
-        .args(["policy", "github"])
+        .args(["analytics", "--since", "bad"])
         .output()
         .unwrap();
     assert!(!output.status.success());
-    let stderr = String::from_utf8_lossy(&output.stderr);
-    assert!(stderr.contains("Policy denied"));
-    assert!(stderr.contains("server is blocked"));
 }
 
+// --- proxy ---
+
 #[test]
-fn policy_check_json_reports_denied_server() {
+fn proxy_not_installed_exits_1() {
     let tmp = tempfile::tempdir().unwrap();
-    berth_with_home(tmp.path())
-        .args(["install", "github"])
-        .output()
-        .unwrap();
-    write_global_policy(
-        tmp.path(),
-        r#"
-[servers]
-deny = ["github"]
-"#,
-    );
-
     let output = berth_with_home(tmp.path())
-        .args(["policy", "github", "--json"])
+        .args(["proxy", "github"])
         .output()
         .unwrap();
     assert!(!output.status.success());
-
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
-    assert_eq!(json["server"].as_str(), Some("github"));
-    assert_eq!(json["allowed"].as_bool(), Some(false));
-    assert!(json["reason"]
-        .as_str()
-        .is_some_and(|msg| msg.contains("Policy denied")));
 }
 
 #[test]
-fn policy_check_json_reports_allowed_server() {
+fn proxy_requires_config_before_running() {
     let tmp = tempfile::tempdir().unwrap();
     berth_with_home(tmp.path())
         .args(["install", "github"])
@@ -3078,20 +8087,16 @@ fn policy_check_json_reports_allowed_server() {
         .unwrap();
 
     let output = berth_with_home(tmp.path())
-        .args(["policy", "github", "--json"])
+        .args(["proxy", "github"])
         .output()
         .unwrap();
-    assert!(output.status.success());
-
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
-    assert_eq!(json["server"].as_str(), Some("github"));
-    assert_eq!(json["allowed"].as_bool(), Some(true));
-    assert!(json["reason"].is_null());
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Missing required config"));
 }
 
 #[test]
-fn audit_shows_runtime_events_for_server() {
+fn proxy_executes_child_and_records_audit() {
     let tmp = tempfile::tempdir().unwrap();
     berth_with_home(tmp.path())
         .args(["install", "github"])
@@ -3101,72 +8106,47 @@ fn audit_shows_runtime_events_for_server() {
         .args(["config", "github", "--set", "token=abc123"])
         .output()
         .unwrap();
-    patch_runtime_to_long_running(tmp.path(), "github");
-    berth_with_home(tmp.path())
-        .args(["start", "github"])
-        .output()
-        .unwrap();
-    berth_with_home(tmp.path())
-        .args(["stop", "github"])
-        .output()
-        .unwrap();
+    patch_runtime_to_echo(tmp.path(), "github");
 
     let output = berth_with_home(tmp.path())
-        .args(["audit", "github"])
+        .args(["proxy", "github"])
         .output()
         .unwrap();
     assert!(output.status.success());
     let stdout = String::from_utf8_lossy(&output.stdout);
-    assert!(stdout.contains("start"));
-    assert!(stdout.contains("stop"));
-    assert!(stdout.contains("github"));
-}
+    assert!(stdout.contains("proxy-ok"));
 
-#[test]
-fn audit_invalid_since_exits_1() {
-    let tmp = tempfile::tempdir().unwrap();
-    let output = berth_with_home(tmp.path())
-        .args(["audit", "--since", "bad"])
+    let audit = berth_with_home(tmp.path())
+        .args(["audit", "github"])
         .output()
         .unwrap();
-    assert!(!output.status.success());
+    assert!(audit.status.success());
+    let audit_out = String::from_utf8_lossy(&audit.stdout);
+    assert!(audit_out.contains("proxy-start"));
+    assert!(audit_out.contains("proxy-end"));
 }
 
 #[test]
-fn audit_action_filter_returns_only_matching_action() {
+fn wrap_requires_config_before_running() {
     let tmp = tempfile::tempdir().unwrap();
     berth_with_home(tmp.path())
         .args(["install", "github"])
         .output()
         .unwrap();
-    berth_with_home(tmp.path())
-        .args(["config", "github", "--set", "token=abc123"])
-        .output()
-        .unwrap();
-    patch_runtime_to_long_running(tmp.path(), "github");
-    berth_with_home(tmp.path())
-        .args(["start", "github"])
-        .output()
-        .unwrap();
-    berth_with_home(tmp.path())
-        .args(["stop", "github"])
-        .output()
-        .unwrap();
 
     let output = berth_with_home(tmp.path())
-        .args(["audit", "github", "--action", "start"])
+        .args(["wrap", "github"])
         .output()
         .unwrap();
-    assert!(output.status.success());
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    assert!(stdout.contains("action=start"));
-    assert!(stdout.contains("start"));
-    assert!(!stdout.contains("stop"));
-    assert!(stdout.contains("ago"));
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Cannot wrap"), "{stderr}");
+    assert!(stderr.contains("Missing required config"), "{stderr}");
 }
 
+#[cfg(unix)]
 #[test]
-fn audit_json_output_is_machine_readable() {
+fn wrap_relays_and_audits_without_restarting_the_server() {
     let tmp = tempfile::tempdir().unwrap();
     berth_with_home(tmp.path())
         .args(["install", "github"])
@@ -3176,31 +8156,57 @@ fn audit_json_output_is_machine_readable() {
         .args(["config", "github", "--set", "token=abc123"])
         .output()
         .unwrap();
-    patch_runtime_to_long_running(tmp.path(), "github");
+    // Wrapped servers belong to the client, so auto-restart must not apply.
     berth_with_home(tmp.path())
-        .args(["start", "github"])
+        .args(["config", "github", "--set", "berth.auto-restart=true"])
         .output()
         .unwrap();
-    berth_with_home(tmp.path())
-        .args(["stop", "github"])
-        .output()
+    let config_path = tmp.path().join(".berth/servers/github.toml");
+    let mut value: toml::Value =
+        toml::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    let runtime = value
+        .get_mut("runtime")
+        .and_then(toml::Value::as_table_mut)
         .unwrap();
+    runtime.insert("command".to_string(), toml::Value::String("sh".to_string()));
+    runtime.insert(
+        "args".to_string(),
+        toml::Value::Array(vec![
+            toml::Value::String("-c".to_string()),
+            toml::Value::String(
+                r#"read -r line; printf '{"jsonrpc":"2.0","id":1,"result":{"content":[]}}\n'; exit 3"#
+                    .to_string(),
+            ),
+        ]),
+    );
+    std::fs::write(&config_path, toml::to_string_pretty(&value).unwrap()).unwrap();
 
-    let output = berth_with_home(tmp.path())
-        .args(["audit", "github", "--json"])
-        .output()
+    let mut child = berth_with_home(tmp.path())
+        .args(["wrap", "github"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
         .unwrap();
-    assert!(output.status.success());
+    let stdin = child.stdin.as_mut().unwrap();
+    stdin
+        .write_all(br#"{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{"name":"search"}}"#)
+        .unwrap();
+    stdin.write_all(b"\n").unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert_eq!(output.status.code(), Some(3));
     let stdout = String::from_utf8_lossy(&output.stdout);
-    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
-    let arr = json.as_array().unwrap();
-    assert!(!arr.is_empty());
-    assert!(arr.iter().any(|ev| ev["action"].as_str() == Some("start")));
-    assert!(arr.iter().any(|ev| ev["action"].as_str() == Some("stop")));
+    assert!(stdout.contains(r#""content":[]"#), "{stdout}");
+
+    let audit = std::fs::read_to_string(tmp.path().join(".berth/audit/audit.jsonl")).unwrap();
+    assert!(audit.contains(r#""action":"wrap-start""#), "{audit}");
+    assert!(audit.contains(r#""action":"wrap-end""#), "{audit}");
+    assert!(audit.contains(r#""action":"mcp-tool-call""#), "{audit}");
+    assert!(!audit.contains("proxy-restart"), "{audit}");
 }
 
+#[cfg(unix)]
 #[test]
-fn audit_export_json_output_writes_array_file() {
+fn proxy_relays_resources_and_prompts_with_subscription_filtering_and_audit() {
     let tmp = tempfile::tempdir().unwrap();
     berth_with_home(tmp.path())
         .args(["install", "github"])
@@ -3210,40 +8216,59 @@ fn audit_export_json_output_writes_array_file() {
         .args(["config", "github", "--set", "token=abc123"])
         .output()
         .unwrap();
-    patch_runtime_to_long_running(tmp.path(), "github");
-    berth_with_home(tmp.path())
-        .args(["start", "github"])
-        .output()
-        .unwrap();
-    berth_with_home(tmp.path())
-        .args(["stop", "github"])
-        .output()
-        .unwrap();
+    // `cat` echoes every client message back, standing in for server output.
+    patch_runtime_to_cat(tmp.path(), "github");
 
-    let export_file = tmp.path().join("exports").join("audit.json");
-    let output = berth_with_home(tmp.path())
-        .args([
-            "audit",
-            "github",
-            "--json",
-            "--export",
-            export_file.to_str().unwrap(),
-        ])
-        .output()
+    let mut child = berth_with_home(tmp.path())
+        .args(["proxy", "github"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let input = concat!(
+        r#"{"jsonrpc":"2.0","id":1,"method":"resources/list"}"#,
+        "\n",
+        r#"{"jsonrpc":"2.0","id":2,"method":"resources/read","params":{"uri":"file:///notes"}}"#,
+        "\n",
+        r#"{"jsonrpc":"2.0","method":"notifications/resources/updated","params":{"uri":"file:///other"}}"#,
+        "\n",
+        r#"{"jsonrpc":"2.0","id":3,"method":"resources/subscribe","params":{"uri":"file:///notes"}}"#,
+        "\n",
+        r#"{"jsonrpc":"2.0","method":"notifications/resources/updated","params":{"uri":"file:///notes"}}"#,
+        "\n",
+        r#"{"jsonrpc":"2.0","id":4,"method":"prompts/get","params":{"name":"review"}}"#,
+        "\n",
+    );
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
         .unwrap();
+    let output = child.wait_with_output().unwrap();
     assert!(output.status.success());
-    assert!(export_file.exists());
 
-    let content = std::fs::read_to_string(export_file).unwrap();
-    let json: serde_json::Value = serde_json::from_str(&content).unwrap();
-    let arr = json.as_array().unwrap();
-    assert!(!arr.is_empty());
-    assert!(arr.iter().any(|ev| ev["action"].as_str() == Some("start")));
-    assert!(arr.iter().any(|ev| ev["action"].as_str() == Some("stop")));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!stdout.contains("file:///other"), "{stdout}");
+    assert_eq!(
+        stdout.matches("notifications/resources/updated").count(),
+        1,
+        "{stdout}"
+    );
+    assert!(stdout.contains("resources/list"));
+    assert!(stdout.contains("prompts/get"));
+
+    let audit = std::fs::read_to_string(tmp.path().join(".berth/audit/audit.jsonl")).unwrap();
+    assert!(audit.contains("\"action\":\"mcp-resource-read\""));
+    assert!(audit.contains("\"action\":\"mcp-resource-subscribe\""));
+    assert!(audit.contains("\"action\":\"mcp-prompt-get\""));
+    assert!(audit.contains("file:///notes"));
+    assert!(!audit.contains("resources/list"));
 }
 
+#[cfg(unix)]
 #[test]
-fn audit_export_jsonl_output_writes_line_delimited_file() {
+fn proxy_shims_structured_results_for_pinned_client_revision() {
     let tmp = tempfile::tempdir().unwrap();
     berth_with_home(tmp.path())
         .args(["install", "github"])
@@ -3253,43 +8278,36 @@ fn audit_export_jsonl_output_writes_line_delimited_file() {
         .args(["config", "github", "--set", "token=abc123"])
         .output()
         .unwrap();
-    patch_runtime_to_long_running(tmp.path(), "github");
-    berth_with_home(tmp.path())
-        .args(["start", "github"])
-        .output()
-        .unwrap();
     berth_with_home(tmp.path())
-        .args(["stop", "github"])
+        .args(["link", "cursor", "--compat", "2024-11-05"])
         .output()
         .unwrap();
+    patch_runtime_to_cat(tmp.path(), "github");
 
-    let export_file = tmp.path().join("exports").join("audit.jsonl");
-    let output = berth_with_home(tmp.path())
-        .args([
-            "audit",
-            "github",
-            "--action",
-            "start",
-            "--export",
-            export_file.to_str().unwrap(),
-        ])
-        .output()
+    let mut child = berth_with_home(tmp.path())
+        .args(["proxy", "github", "--client", "cursor"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"{\"jsonrpc\":\"2.0\",\"id\":7,\"result\":{\"structuredContent\":{\"a\":1}}}\n")
         .unwrap();
+    let output = child.wait_with_output().unwrap();
     assert!(output.status.success());
-    assert!(export_file.exists());
 
-    let content = std::fs::read_to_string(export_file).unwrap();
-    let lines: Vec<&str> = content.lines().filter(|line| !line.is_empty()).collect();
-    assert!(!lines.is_empty());
-    for line in lines {
-        let parsed: serde_json::Value = serde_json::from_str(line).unwrap();
-        assert_eq!(parsed["action"].as_str(), Some("start"));
-        assert_eq!(parsed["server"].as_str(), Some("github"));
-    }
+    let line = String::from_utf8_lossy(&output.stdout);
+    let value: serde_json::Value = serde_json::from_str(line.trim()).unwrap();
+    assert!(value["result"].get("structuredContent").is_none());
+    assert_eq!(value["result"]["content"][0]["text"], "{\"a\":1}");
 }
 
+#[cfg(unix)]
 #[test]
-fn analytics_summarizes_audit_events_for_server() {
+fn proxy_bridges_content_length_client_to_line_delimited_server() {
     let tmp = tempfile::tempdir().unwrap();
     berth_with_home(tmp.path())
         .args(["install", "github"])
@@ -3299,31 +8317,42 @@ fn analytics_summarizes_audit_events_for_server() {
         .args(["config", "github", "--set", "token=abc123"])
         .output()
         .unwrap();
-    patch_runtime_to_long_running(tmp.path(), "github");
-    berth_with_home(tmp.path())
-        .args(["start", "github"])
-        .output()
-        .unwrap();
     berth_with_home(tmp.path())
-        .args(["stop", "github"])
+        .args(["config", "github", "--set", "berth.framing=line"])
         .output()
         .unwrap();
+    patch_runtime_to_cat(tmp.path(), "github");
 
-    let output = berth_with_home(tmp.path())
-        .args(["analytics", "github", "--top", "3"])
-        .output()
+    let mut child = berth_with_home(tmp.path())
+        .args(["proxy", "github"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let body = "{\"jsonrpc\":\"2.0\",\n\"id\":1,\"method\":\"tools/list\"}";
+    let framed = format!("Content-Length: {}\r\n\r\n{body}", body.len());
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(framed.as_bytes())
         .unwrap();
+    let output = child.wait_with_output().unwrap();
     assert!(output.status.success());
+
+    // `cat` only understands lines, so it got the message compacted onto one
+    // line; the client gets the echo back with its own framing.
     let stdout = String::from_utf8_lossy(&output.stdout);
-    assert!(stdout.contains("Audit analytics"));
-    assert!(stdout.contains("total events"));
-    assert!(stdout.contains("Top actions"));
-    assert!(stdout.contains("start"));
-    assert!(stdout.contains("stop"));
+    let echoed = r#"{"id":1,"jsonrpc":"2.0","method":"tools/list"}"#;
+    assert!(
+        stdout.starts_with(&format!("Content-Length: {}\r\n\r\n{echoed}", echoed.len())),
+        "{stdout}"
+    );
 }
 
+#[cfg(unix)]
 #[test]
-fn analytics_json_output_is_machine_readable() {
+fn proxy_masks_upstream_crash_with_restart_and_initialize_replay() {
     let tmp = tempfile::tempdir().unwrap();
     berth_with_home(tmp.path())
         .args(["install", "github"])
@@ -3333,101 +8362,268 @@ fn analytics_json_output_is_machine_readable() {
         .args(["config", "github", "--set", "token=abc123"])
         .output()
         .unwrap();
-    patch_runtime_to_long_running(tmp.path(), "github");
     berth_with_home(tmp.path())
-        .args(["start", "github"])
+        .args(["config", "github", "--set", "berth.auto-restart=true"])
         .output()
         .unwrap();
-    berth_with_home(tmp.path())
-        .args(["stop", "github"])
-        .output()
+
+    // Answers numeric ids with its run number and crashes once on request 2.
+    let marker = tmp.path().join("crashed");
+    let script = format!(
+        r#"while IFS= read -r line; do
+  id=$(printf '%s' "$line" | sed -n 's/.*"id":\([0-9][0-9]*\).*/\1/p')
+  if [ ! -f '{marker}' ] && [ "$id" = 2 ]; then touch '{marker}'; exit 1; fi
+  run=1; [ -f '{marker}' ] && run=2
+  [ -n "$id" ] && printf '{{"jsonrpc":"2.0","id":%s,"result":{{"run":%s}}}}\n' "$id" "$run"
+done"#,
+        marker = marker.display()
+    );
+    let config_path = tmp.path().join(".berth/servers/github.toml");
+    let mut value: toml::Value =
+        toml::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    let runtime = value
+        .get_mut("runtime")
+        .and_then(toml::Value::as_table_mut)
         .unwrap();
+    runtime.insert("command".to_string(), toml::Value::String("sh".to_string()));
+    runtime.insert(
+        "args".to_string(),
+        toml::Value::Array(vec![
+            toml::Value::String("-c".to_string()),
+            toml::Value::String(script),
+        ]),
+    );
+    std::fs::write(&config_path, toml::to_string_pretty(&value).unwrap()).unwrap();
 
-    let output = berth_with_home(tmp.path())
-        .args(["analytics", "github", "--json"])
-        .output()
+    let mut child = berth_with_home(tmp.path())
+        .args(["proxy", "github"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
         .unwrap();
-    assert!(output.status.success());
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
-    assert!(json["totalEvents"].as_u64().unwrap_or(0) >= 2);
-    assert_eq!(json["uniqueServers"].as_u64(), Some(1));
-    assert!(json["estimatedCostUsd"].as_f64().unwrap_or(0.0) > 0.0);
-    assert!(json["topActions"]
-        .as_array()
+    let mut stdin = child.stdin.take().unwrap();
+    let mut stdout = BufReader::new(child.stdout.take().unwrap());
+    let mut exchange = |request: &str| -> serde_json::Value {
+        stdin.write_all(request.as_bytes()).unwrap();
+        stdin.write_all(b"\n").unwrap();
+        stdin.flush().unwrap();
+        let mut line = String::new();
+        stdout.read_line(&mut line).unwrap();
+        serde_json::from_str(line.trim()).unwrap()
+    };
+
+    let init = exchange(
+        r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":"2025-06-18"}}"#,
+    );
+    assert_eq!(init["result"]["run"], 1);
+    let interrupted = exchange(r#"{"jsonrpc":"2.0","id":2,"method":"tools/list"}"#);
+    assert_eq!(interrupted["id"], 2);
+    assert!(interrupted["error"]["message"]
+        .as_str()
         .unwrap()
-        .iter()
-        .any(|item| item["value"].as_str() == Some("start")));
+        .contains("restarted"));
+    let resumed = exchange(r#"{"jsonrpc":"2.0","id":3,"method":"tools/list"}"#);
+    assert_eq!(resumed["id"], 3);
+    assert_eq!(resumed["result"]["run"], 2);
+
+    drop(stdin);
+    let status = child.wait().unwrap();
+    assert!(status.success());
+
+    let audit = berth_with_home(tmp.path())
+        .args(["audit", "github", "--action", "proxy-restart"])
+        .output()
+        .unwrap();
+    assert!(String::from_utf8_lossy(&audit.stdout).contains("proxy-restart"));
 }
 
+#[cfg(unix)]
 #[test]
-fn analytics_invalid_since_exits_1() {
+fn proxy_blue_green_switches_to_updated_version_without_errors() {
     let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
     let output = berth_with_home(tmp.path())
-        .args(["analytics", "--since", "bad"])
+        .args([
+            "config",
+            "github",
+            "--set",
+            "berth.update-strategy=blue-green",
+        ])
         .output()
         .unwrap();
-    assert!(!output.status.success());
-}
+    assert!(output.status.success());
 
-// --- proxy ---
+    // Answers every request with the generation passed as `$0`.
+    let script = r#"while IFS= read -r line; do
+  id=$(printf '%s' "$line" | sed -n 's/.*"id":\([0-9][0-9]*\).*/\1/p')
+  [ -n "$id" ] && printf '{"jsonrpc":"2.0","id":%s,"result":{"gen":"%s"}}\n' "$id" "$0"
+done"#;
+    let config_path = tmp.path().join(".berth/servers/github.toml");
+    let set_generation = |version: &str, generation: &str| {
+        let mut value: toml::Value =
+            toml::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+        value["server"]
+            .as_table_mut()
+            .unwrap()
+            .insert("version".to_string(), toml::Value::from(version));
+        let runtime = value["runtime"].as_table_mut().unwrap();
+        runtime.insert("command".to_string(), toml::Value::from("sh"));
+        runtime.insert(
+            "args".to_string(),
+            toml::Value::Array(vec!["-c".into(), script.into(), generation.into()]),
+        );
+        std::fs::write(&config_path, toml::to_string_pretty(&value).unwrap()).unwrap();
+    };
+    set_generation("1.0.0", "blue");
 
-#[test]
-fn proxy_not_installed_exits_1() {
-    let tmp = tempfile::tempdir().unwrap();
-    let output = berth_with_home(tmp.path())
+    let mut child = berth_with_home(tmp.path())
         .args(["proxy", "github"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    let mut stdout = BufReader::new(child.stdout.take().unwrap());
+    let mut exchange = |request: &str| -> serde_json::Value {
+        stdin.write_all(request.as_bytes()).unwrap();
+        stdin.write_all(b"\n").unwrap();
+        stdin.flush().unwrap();
+        let mut line = String::new();
+        stdout.read_line(&mut line).unwrap();
+        serde_json::from_str(line.trim()).unwrap()
+    };
+
+    let init = exchange(
+        r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":"2025-06-18"}}"#,
+    );
+    assert_eq!(init["result"]["gen"], "blue");
+
+    // Simulates `berth update` rewriting the config with a new version.
+    std::thread::sleep(Duration::from_millis(50));
+    set_generation("2.0.0", "green");
+
+    let mut switched = false;
+    for id in 2..80 {
+        let response = exchange(&format!(
+            r#"{{"jsonrpc":"2.0","id":{id},"method":"tools/list"}}"#
+        ));
+        assert_eq!(response["id"], id);
+        assert!(response.get("error").is_none(), "{response}");
+        if response["result"]["gen"] == "green" {
+            switched = true;
+            break;
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+    assert!(switched, "proxy never switched to the updated upstream");
+
+    drop(stdin);
+    child.wait().unwrap();
+
+    let audit = berth_with_home(tmp.path())
+        .args(["audit", "github", "--action", "proxy-blue-green-switch"])
         .output()
         .unwrap();
-    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&audit.stdout).contains("proxy-blue-green-switch"));
 }
 
+#[cfg(unix)]
 #[test]
-fn proxy_requires_config_before_running() {
+fn proxy_queues_requests_beyond_max_in_flight_and_times_out() {
     let tmp = tempfile::tempdir().unwrap();
     berth_with_home(tmp.path())
         .args(["install", "github"])
         .output()
         .unwrap();
+    for kv in [
+        "token=abc123",
+        "berth.max-in-flight=1",
+        "berth.queue-timeout-ms=200",
+    ] {
+        let output = berth_with_home(tmp.path())
+            .args(["config", "github", "--set", kv])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+    }
+    // A server that accepts requests but never answers keeps the only slot busy.
+    let config_path = tmp.path().join(".berth/servers/github.toml");
+    let mut value: toml::Value =
+        toml::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+    let runtime = value
+        .get_mut("runtime")
+        .and_then(toml::Value::as_table_mut)
+        .unwrap();
+    runtime.insert("command".to_string(), toml::Value::String("sh".to_string()));
+    runtime.insert(
+        "args".to_string(),
+        toml::Value::Array(vec![
+            toml::Value::String("-c".to_string()),
+            toml::Value::String("cat >/dev/null".to_string()),
+        ]),
+    );
+    std::fs::write(&config_path, toml::to_string_pretty(&value).unwrap()).unwrap();
 
-    let output = berth_with_home(tmp.path())
+    let mut child = berth_with_home(tmp.path())
         .args(["proxy", "github"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    let mut stdout = BufReader::new(child.stdout.take().unwrap());
+    stdin
+        .write_all(
+            concat!(
+                r#"{"jsonrpc":"2.0","id":1,"method":"tools/list"}"#,
+                "\n",
+                r#"{"jsonrpc":"2.0","id":2,"method":"tools/list"}"#,
+                "\n"
+            )
+            .as_bytes(),
+        )
+        .unwrap();
+    stdin.flush().unwrap();
+    let mut line = String::new();
+    stdout.read_line(&mut line).unwrap();
+    let timed_out: serde_json::Value = serde_json::from_str(line.trim()).unwrap();
+    assert_eq!(timed_out["id"], 2);
+    assert!(timed_out["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("timed out"));
+    drop(stdin);
+    child.wait().unwrap();
+
+    let analytics = berth_with_home(tmp.path())
+        .args(["analytics", "github", "--json"])
         .output()
         .unwrap();
-    assert!(!output.status.success());
-    let stderr = String::from_utf8_lossy(&output.stderr);
-    assert!(stderr.contains("Missing required config"));
+    let json: serde_json::Value = serde_json::from_slice(&analytics.stdout).unwrap();
+    assert_eq!(json["proxyQueuedRequests"], 1);
+    assert_eq!(json["proxyQueueTimeouts"], 1);
 }
 
 #[test]
-fn proxy_executes_child_and_records_audit() {
+fn config_rejects_invalid_max_in_flight() {
     let tmp = tempfile::tempdir().unwrap();
     berth_with_home(tmp.path())
         .args(["install", "github"])
         .output()
         .unwrap();
-    berth_with_home(tmp.path())
-        .args(["config", "github", "--set", "token=abc123"])
-        .output()
-        .unwrap();
-    patch_runtime_to_echo(tmp.path(), "github");
-
     let output = berth_with_home(tmp.path())
-        .args(["proxy", "github"])
-        .output()
-        .unwrap();
-    assert!(output.status.success());
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    assert!(stdout.contains("proxy-ok"));
-
-    let audit = berth_with_home(tmp.path())
-        .args(["audit", "github"])
+        .args(["config", "github", "--set", "berth.max-in-flight=0"])
         .output()
         .unwrap();
-    assert!(audit.status.success());
-    let audit_out = String::from_utf8_lossy(&audit.stdout);
-    assert!(audit_out.contains("proxy-start"));
-    assert!(audit_out.contains("proxy-end"));
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("positive integer"));
 }
 
 #[test]
@@ -3528,6 +8724,83 @@ fn proxy_applies_env_permission_revoke() {
     assert!(stdout.contains("env-missing"));
 }
 
+#[test]
+fn proxy_isolates_env_unless_inherit_env_is_set() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
+    patch_runtime_to_print_env_var(tmp.path(), "github", "BERTH_TEST_PARENT_ONLY");
+
+    let isolated = berth_with_home(tmp.path())
+        .env("BERTH_TEST_PARENT_ONLY", "1")
+        .args(["proxy", "github"])
+        .output()
+        .unwrap();
+    assert!(isolated.status.success());
+    assert!(String::from_utf8_lossy(&isolated.stdout).contains("env-missing"));
+
+    let inherited = berth_with_home(tmp.path())
+        .env("BERTH_TEST_PARENT_ONLY", "1")
+        .args(["proxy", "github", "--inherit-env"])
+        .output()
+        .unwrap();
+    assert!(inherited.status.success());
+    assert!(String::from_utf8_lossy(&inherited.stdout).contains("env-present"));
+}
+
+#[test]
+fn proxy_isolated_env_passes_granted_parent_vars() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["permissions", "github", "--grant", "env:BERTH_TEST_GRANTED"])
+        .output()
+        .unwrap();
+    patch_runtime_to_print_env_var(tmp.path(), "github", "BERTH_TEST_GRANTED");
+
+    let output = berth_with_home(tmp.path())
+        .env("BERTH_TEST_GRANTED", "from-shell")
+        .args(["proxy", "github"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("env-present"));
+}
+
+#[test]
+fn config_env_shows_isolated_launch_environment() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+
+    let output = berth_with_home(tmp.path())
+        .env("BERTH_TEST_PARENT_ONLY", "1")
+        .args(["config", "github", "--env"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Launch environment: isolated"));
+    assert!(stdout.contains("PATH"));
+    assert!(stdout.contains("BERTH_TEST_PARENT_ONLY"));
+    assert!(stdout.contains("--inherit-env"));
+}
+
 #[test]
 fn proxy_resolves_secure_secret_reference() {
     let tmp = tempfile::tempdir().unwrap();
@@ -3590,6 +8863,33 @@ fn update_specific_server_updates_and_preserves_config() {
     assert_eq!(parsed["config"]["token"].as_str(), Some("abc123"));
 }
 
+#[test]
+fn update_dry_run_reports_change_without_writing() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
+    patch_installed_version(tmp.path(), "github", "0.9.0");
+
+    let output = berth_with_home(tmp.path())
+        .args(["update", "github", "--dry-run"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("would update 0.9.0 -> 1.2.0"));
+    assert!(stdout.contains("config preserved: token"));
+
+    let content = std::fs::read_to_string(tmp.path().join(".berth/servers/github.toml")).unwrap();
+    let parsed: toml::Value = toml::from_str(&content).unwrap();
+    assert_eq!(parsed["server"]["version"].as_str(), Some("0.9.0"));
+}
+
 #[test]
 fn update_specific_server_up_to_date_succeeds() {
     let tmp = tempfile::tempdir().unwrap();
@@ -3631,7 +8931,8 @@ fn update_all_mixed_results_exits_1_and_reports_summary() {
     // Add an installed server entry that does not exist in the registry.
     let servers_dir = tmp.path().join(".berth/servers");
     let github_cfg = std::fs::read_to_string(servers_dir.join("github.toml")).unwrap();
-    std::fs::write(servers_dir.join("missing.toml"), github_cfg).unwrap();
+    let missing_cfg = github_cfg.replace("name = \"github\"", "name = \"missing\"");
+    std::fs::write(servers_dir.join("missing.toml"), missing_cfg).unwrap();
 
     let output = berth_with_home(tmp.path())
         .args(["update", "--all"])
@@ -3644,3 +8945,229 @@ fn update_all_mixed_results_exits_1_and_reports_summary() {
     assert!(stdout.contains("failed"));
     assert!(stderr.contains("not found in the registry"));
 }
+
+#[test]
+fn registry_seed_check_validates_seed_and_flags_stale_entries() {
+    let tmp = tempfile::tempdir().unwrap();
+    let output = berth_with_home(tmp.path())
+        .args(["registry-seed-check", "--max-age-days", "100000"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Seed registry: 31 servers valid."));
+
+    let stale = berth_with_home(tmp.path())
+        .args(["registry-seed-check", "--max-age-days", "0"])
+        .output()
+        .unwrap();
+    assert!(stale.status.success());
+    let stdout = String::from_utf8_lossy(&stale.stdout);
+    assert!(stdout.contains("last verified"));
+    assert!(stdout.contains("31 stale"));
+}
+
+#[test]
+fn registry_seed_check_diffs_against_index_file() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = tmp.path().join("team-registry");
+    let init = berth_with_home(tmp.path())
+        .arg("registry-init")
+        .arg(&root)
+        .output()
+        .unwrap();
+    assert!(init.status.success());
+
+    let index_path = root.join("index.json");
+    let mut index: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&index_path).unwrap()).unwrap();
+    let servers = index.as_array_mut().unwrap();
+    servers.retain(|s| s["name"] != "slack");
+    let github = servers.iter_mut().find(|s| s["name"] == "github").unwrap();
+    github["version"] = serde_json::json!("9.9.9");
+    let mut extra = github.clone();
+    extra["name"] = serde_json::json!("team-tools");
+    servers.push(extra);
+    std::fs::write(&index_path, serde_json::to_string(&index).unwrap()).unwrap();
+
+    let output = berth_with_home(tmp.path())
+        .args(["registry-seed-check", "--max-age-days", "100000", "--diff"])
+        .arg(&index_path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("+ team-tools"));
+    assert!(stdout.contains("- slack"));
+    assert!(stdout.contains("~ github: version"));
+    assert!(stdout.contains("-> 9.9.9"));
+}
+
+#[cfg(unix)]
+#[test]
+fn enabled_plugins_receive_audit_events_and_time_out() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let tmp = tempfile::tempdir().unwrap();
+    let plugins = tmp.path().join(".berth/plugins");
+    std::fs::create_dir_all(&plugins).unwrap();
+    let received = tmp.path().join("received.jsonl");
+    for (name, script) in [
+        ("notify", format!("cat >> '{}'", received.display())),
+        ("slow", "sleep 30".to_string()),
+    ] {
+        let path = plugins.join(name);
+        std::fs::write(&path, format!("#!/bin/sh\n{script}\n")).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    let list = berth_with_home(tmp.path()).arg("plugins").output().unwrap();
+    assert!(list.status.success());
+    let stdout = String::from_utf8_lossy(&list.stdout);
+    assert!(stdout.contains("notify"));
+    assert!(stdout.contains("disabled"));
+
+    // Disabled plugins get nothing.
+    let install = berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    assert!(install.status.success());
+    assert!(!received.exists());
+
+    let enable = berth_with_home(tmp.path())
+        .args(["plugins", "notify", "--enable"])
+        .output()
+        .unwrap();
+    assert!(enable.status.success());
+    let settings = std::fs::read_to_string(tmp.path().join(".berth/plugins.toml")).unwrap();
+    assert!(settings.contains("[plugins.notify]"));
+    assert!(settings.contains("enabled = true"));
+
+    let uninstall = berth_with_home(tmp.path())
+        .args(["uninstall", "github"])
+        .output()
+        .unwrap();
+    assert!(uninstall.status.success());
+    let line = std::fs::read_to_string(&received).unwrap();
+    let event: serde_json::Value = serde_json::from_str(line.lines().last().unwrap()).unwrap();
+    assert_eq!(event["protocol"], 1);
+    assert_eq!(event["kind"], "audit");
+    assert_eq!(event["event"]["server"], "github");
+    assert_eq!(event["event"]["action"], "uninstall");
+
+    let test = berth_with_home(tmp.path())
+        .args(["plugins", "notify", "--test"])
+        .output()
+        .unwrap();
+    assert!(test.status.success());
+    assert!(std::fs::read_to_string(&received)
+        .unwrap()
+        .contains("\"kind\":\"test\""));
+
+    let started = std::time::Instant::now();
+    let slow = berth_with_home(tmp.path())
+        .args(["plugins", "slow", "--timeout", "1", "--test"])
+        .output()
+        .unwrap();
+    assert!(!slow.status.success());
+    assert!(String::from_utf8_lossy(&slow.stderr).contains("did not finish within 1s"));
+    assert!(started.elapsed() < Duration::from_secs(20));
+
+    let disable = berth_with_home(tmp.path())
+        .args(["plugins", "notify", "--disable"])
+        .output()
+        .unwrap();
+    assert!(disable.status.success());
+    let missing = berth_with_home(tmp.path())
+        .args(["plugins", "absent", "--enable"])
+        .output()
+        .unwrap();
+    assert!(!missing.status.success());
+    assert!(String::from_utf8_lossy(&missing.stderr).contains("No plugin named 'absent'"));
+}
+
+#[test]
+fn test_server_answers_requests_and_crashes_after_configured_calls() {
+    let fake = FakeServer::new().with_name("fixture").crashing_after(1, 4);
+    let mut child = Command::new(env!("CARGO_BIN_EXE_berth"))
+        .args(fake.args())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let stdin = child.stdin.as_mut().unwrap();
+    for request in [
+        r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":"2025-06-18"}}"#,
+        r#"{"jsonrpc":"2.0","id":2,"method":"tools/call","params":{"name":"echo","arguments":{"q":"berth"}}}"#,
+        r#"{"jsonrpc":"2.0","id":3,"method":"tools/call","params":{"name":"echo"}}"#,
+    ] {
+        writeln!(stdin, "{request}").unwrap();
+    }
+    let output = child.wait_with_output().unwrap();
+    assert_eq!(output.status.code(), Some(4));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let answers: Vec<serde_json::Value> = stdout
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(answers.len(), 2, "{stdout}");
+    assert_eq!(answers[0]["result"]["serverInfo"]["name"], "fixture");
+    assert_eq!(
+        answers[1]["result"]["content"][0]["text"],
+        r#"{"q":"berth"}"#
+    );
+
+    let invalid = Command::new(env!("CARGO_BIN_EXE_berth"))
+        .args(["test-server", "--violate", "loud"])
+        .stdin(Stdio::null())
+        .output()
+        .unwrap();
+    assert!(!invalid.status.success());
+    assert!(String::from_utf8_lossy(&invalid.stderr).contains("Invalid violation"));
+}
+
+#[test]
+fn wrap_relays_large_outputs_and_stdout_noise_from_a_fake_server() {
+    let tmp = tempfile::tempdir().unwrap();
+    berth_with_home(tmp.path())
+        .args(["install", "github"])
+        .output()
+        .unwrap();
+    berth_with_home(tmp.path())
+        .args(["config", "github", "--set", "token=abc123"])
+        .output()
+        .unwrap();
+    let fake = FakeServer::new()
+        .with_tools(&["search"])
+        .with_startup_delay(Duration::from_millis(200))
+        .with_output_bytes(1 << 20)
+        .violating(Violation::StdoutNoise);
+    patch_runtime_to_fake_server(tmp.path(), "github", &fake);
+
+    let mut child = berth_with_home(tmp.path())
+        .args(["wrap", "github"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let stdin = child.stdin.as_mut().unwrap();
+    writeln!(
+        stdin,
+        r#"{{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{{"name":"search"}}}}"#
+    )
+    .unwrap();
+    let output = child.wait_with_output().unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut lines = stdout.lines();
+    assert_eq!(
+        lines.next(),
+        Some("berth-test-server: handling tools/call"),
+        "{}",
+        &stdout[..stdout.len().min(200)]
+    );
+    let answer: serde_json::Value = serde_json::from_str(lines.next().unwrap()).unwrap();
+    assert_eq!(answer["id"], 1);
+    let text = answer["result"]["content"][0]["text"].as_str().unwrap();
+    assert_eq!(text.len(), 1 << 20);
+}
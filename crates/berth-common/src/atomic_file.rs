@@ -0,0 +1,137 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Whole-file replacement that never leaves a partly written file behind.
+//!
+//! State, caches, and queue files are rewritten in full on every change. A
+//! plain `fs::write` truncates first, so a crash or full disk mid-write
+//! leaves a truncated file that later fails to parse. [`atomic_write`]
+//! writes a temporary file next to the target, flushes it to disk, and
+//! renames it over the target, so readers see either the old or the new
+//! contents.
+
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Replaces `path` with `contents` through a temporary file and a rename.
+///
+/// The temporary file sits in the same directory, which must exist, and is
+/// named `.<file name>.tmp-<pid>-<n>`; the leading dot keeps it out of
+/// directory listings that match on extension or server name. It is removed
+/// again when writing or renaming fails.
+pub fn atomic_write(path: &Path, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    replace_file(path, contents.as_ref(), false)
+}
+
+/// Like [`atomic_write`], but the new file is only readable by its owner.
+///
+/// On Unix the temporary file is created with mode 0600, so the contents are
+/// never exposed under the default umask, not even between write and rename.
+pub fn atomic_write_private(path: &Path, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    replace_file(path, contents.as_ref(), true)
+}
+
+fn replace_file(path: &Path, contents: &[u8], private: bool) -> io::Result<()> {
+    static NEXT_TMP: AtomicU64 = AtomicU64::new(0);
+    let Some(file_name) = path.file_name() else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} does not name a file", path.display()),
+        ));
+    };
+    let mut tmp_name = std::ffi::OsString::from(".");
+    tmp_name.push(file_name);
+    tmp_name.push(format!(
+        ".tmp-{}-{}",
+        std::process::id(),
+        NEXT_TMP.fetch_add(1, Ordering::Relaxed)
+    ));
+    let tmp = path.with_file_name(tmp_name);
+
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    if private {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    #[cfg(not(unix))]
+    let _ = private;
+    let written = options
+        .open(&tmp)
+        .and_then(|mut file| {
+            file.write_all(contents)?;
+            file.sync_all()
+        })
+        .and_then(|()| fs::rename(&tmp, path));
+    if written.is_err() {
+        let _ = fs::remove_file(&tmp);
+        return written;
+    }
+    sync_parent(path);
+    Ok(())
+}
+
+/// Flushes the rename itself to disk; best effort, since not every
+/// filesystem supports syncing a directory.
+#[cfg(unix)]
+fn sync_parent(path: &Path) {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    if let Ok(dir) = fs::File::open(dir) {
+        let _ = dir.sync_all();
+    }
+}
+
+#[cfg(not(unix))]
+fn sync_parent(_path: &Path) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replaces_the_file_and_leaves_no_temporary_behind() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("state.toml");
+        atomic_write(&path, "first").unwrap();
+        atomic_write(&path, "second").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second");
+        let names: Vec<_> = fs::read_dir(tmp.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(names, ["state.toml"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn private_writes_are_owner_only() {
+        use std::os::unix::fs::PermissionsExt;
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("secret.toml");
+        atomic_write(&path, "public").unwrap();
+        atomic_write_private(&path, "token").unwrap();
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "token");
+    }
+
+    #[test]
+    fn failed_writes_keep_the_old_contents() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("cache.json");
+        atomic_write(&path, "old").unwrap();
+        // A directory in the way makes the rename fail.
+        let blocked = tmp.path().join("blocked");
+        fs::create_dir_all(blocked.join("inner")).unwrap();
+        assert!(atomic_write(&blocked, "new").is_err());
+        assert!(atomic_write(&tmp.path().join("missing").join("x"), "new").is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "old");
+        assert_eq!(fs::read_dir(tmp.path()).unwrap().count(), 2);
+    }
+}
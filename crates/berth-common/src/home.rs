@@ -0,0 +1,87 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Resolution of the directories Berth keeps its files in.
+
+use std::path::{Path, PathBuf};
+
+/// Directory name used below each XDG base directory.
+const XDG_APP_DIR: &str = "berth";
+
+/// Where Berth keeps config, state, and cache data.
+///
+/// The single-directory layout (`$BERTH_HOME` or a legacy `~/.berth`) uses the
+/// same root for all three.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BerthLayout {
+    /// Server configs, permission overrides, org policy, credentials.
+    pub config: PathBuf,
+    /// Runtime state, logs, audit log, installed binaries, publish queue.
+    pub state: PathBuf,
+    /// Re-downloadable data such as the registry index cache.
+    pub cache: PathBuf,
+}
+
+impl BerthLayout {
+    /// Uses one directory for config, state, and cache.
+    pub fn single(root: PathBuf) -> Self {
+        BerthLayout {
+            config: root.clone(),
+            state: root.clone(),
+            cache: root,
+        }
+    }
+
+    /// Uses `$XDG_CONFIG_HOME`, `$XDG_STATE_HOME`, and `$XDG_CACHE_HOME`.
+    ///
+    /// Unset or relative variables fall back to `~/.config`, `~/.local/state`,
+    /// and `~/.cache` as the XDG base directory spec requires.
+    pub fn xdg(home: &Path) -> Self {
+        let base = |var: &str, default: &[&str]| {
+            std::env::var_os(var)
+                .map(PathBuf::from)
+                .filter(|p| p.is_absolute())
+                .unwrap_or_else(|| default.iter().fold(home.to_path_buf(), |p, c| p.join(c)))
+                .join(XDG_APP_DIR)
+        };
+        BerthLayout {
+            config: base("XDG_CONFIG_HOME", &[".config"]),
+            state: base("XDG_STATE_HOME", &[".local", "state"]),
+            cache: base("XDG_CACHE_HOME", &[".cache"]),
+        }
+    }
+
+    /// Returns whether config, state, and cache share one directory.
+    pub fn is_single(&self) -> bool {
+        self.config == self.state && self.state == self.cache
+    }
+}
+
+/// Returns the legacy single-directory home (`~/.berth`).
+pub fn legacy_berth_home() -> Option<PathBuf> {
+    dirs::home_dir().map(|h| h.join(".berth"))
+}
+
+/// Resolves the active layout.
+///
+/// `$BERTH_HOME` pins a single directory. Otherwise an existing `~/.berth` is
+/// used as-is until `berth migrate-layout` moves it; fresh installs use XDG.
+pub fn berth_layout() -> Option<BerthLayout> {
+    if let Ok(home) = std::env::var("BERTH_HOME") {
+        return Some(BerthLayout::single(PathBuf::from(home)));
+    }
+    user_berth_layout()
+}
+
+/// Resolves the user's own layout, ignoring `$BERTH_HOME`.
+///
+/// Settings that must not be supplied by whichever home is active, such as
+/// workspace trust decisions, live here.
+pub fn user_berth_layout() -> Option<BerthLayout> {
+    let home = dirs::home_dir()?;
+    let legacy = home.join(".berth");
+    if legacy.is_dir() {
+        return Some(BerthLayout::single(legacy));
+    }
+    Some(BerthLayout::xdg(&home))
+}
@@ -0,0 +1,95 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! ULID generation for records that need sortable, collision-free ids.
+//!
+//! A ULID is a 48-bit millisecond timestamp followed by 80 random bits,
+//! rendered as 26 Crockford base32 characters, so ids sort by creation time.
+//! Ids made within the same millisecond in one process increment the random
+//! part instead of drawing a new one, which keeps them ordered too.
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Mutex;
+
+use crate::time::now_epoch_ms;
+
+/// Crockford's base32 alphabet (no I, L, O, U).
+const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+const RANDOM_BITS: u32 = 80;
+const RANDOM_MASK: u128 = (1 << RANDOM_BITS) - 1;
+
+/// Last id handed out in this process, as `(timestamp_ms, random)`.
+static LAST: Mutex<(u64, u128)> = Mutex::new((0, 0));
+
+/// Returns a new ULID for the current time.
+pub fn new_ulid() -> String {
+    let now = now_epoch_ms();
+    let mut last = LAST.lock().unwrap_or_else(|e| e.into_inner());
+    let (ms, random) = if now <= last.0 && last.1 < RANDOM_MASK {
+        (last.0, last.1 + 1)
+    } else {
+        (now.max(last.0), random_bits())
+    };
+    *last = (ms, random);
+    ulid_from_parts(ms, random)
+}
+
+/// Encodes a timestamp and the low 80 bits of `random` as a ULID.
+pub fn ulid_from_parts(timestamp_ms: u64, random: u128) -> String {
+    let value =
+        (u128::from(timestamp_ms & 0xFFFF_FFFF_FFFF) << RANDOM_BITS) | (random & RANDOM_MASK);
+    (0..26)
+        .rev()
+        .map(|i| ALPHABET[((value >> (i * 5)) & 0x1F) as usize] as char)
+        .collect()
+}
+
+/// Returns the creation time encoded in a ULID, or `None` if it is not one.
+pub fn ulid_timestamp_ms(id: &str) -> Option<u64> {
+    if id.len() != 26 {
+        return None;
+    }
+    let mut value: u128 = 0;
+    for c in id.bytes() {
+        let digit = ALPHABET.iter().position(|a| *a == c.to_ascii_uppercase())?;
+        value = value.checked_mul(32)? | digit as u128;
+    }
+    Some((value >> RANDOM_BITS) as u64)
+}
+
+/// Draws 80 bits from std's randomly keyed hasher.
+fn random_bits() -> u128 {
+    let word = |salt: u8| {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u64(now_epoch_ms());
+        hasher.write_u32(std::process::id());
+        hasher.write_u8(salt);
+        u128::from(hasher.finish())
+    };
+    ((word(0) << 64) | word(1)) & RANDOM_MASK
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ulid_encodes_timestamp_first() {
+        assert_eq!(ulid_from_parts(0, 0), "00000000000000000000000000");
+        let id = ulid_from_parts(1_469_918_176_385, 0);
+        assert_eq!(&id[..10], "01ARYZ6S41");
+        assert_eq!(ulid_timestamp_ms(&id), Some(1_469_918_176_385));
+        assert_eq!(ulid_timestamp_ms("not-a-ulid"), None);
+    }
+
+    #[test]
+    fn new_ulids_are_unique_and_sorted() {
+        let ids: Vec<String> = (0..1_000).map(|_| new_ulid()).collect();
+        let mut sorted = ids.clone();
+        sorted.sort();
+        sorted.dedup();
+        assert_eq!(sorted, ids);
+    }
+}
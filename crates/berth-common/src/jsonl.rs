@@ -0,0 +1,319 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Append-only JSON Lines files shared between Berth processes.
+//!
+//! Appends take an exclusive advisory lock so lines written by concurrent
+//! `berth` invocations and supervisors never interleave; reads take a shared
+//! lock and skip lines that do not parse. Retention and [`with_lock`] callers
+//! that append based on what is already there work under the same exclusive
+//! lock.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Appends `record` as one line, creating the file and its directory if needed.
+pub fn append<T: Serialize>(path: &Path, record: &T) -> io::Result<()> {
+    append_all(path, std::slice::from_ref(record)).map(drop)
+}
+
+/// Appends `records` one per line in a single write, so readers see either
+/// none or all of them; nothing is written if any record fails to serialize.
+///
+/// Returns the file's length before the batch, which [`truncate`] takes to
+/// roll it back.
+pub fn append_all<T: Serialize>(path: &Path, records: &[T]) -> io::Result<u64> {
+    let mut lines = String::new();
+    for record in records {
+        lines.push_str(
+            &serde_json::to_string(record)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?,
+        );
+        lines.push('\n');
+    }
+    if lines.is_empty() {
+        return match fs::metadata(path) {
+            Ok(metadata) => Ok(metadata.len()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(0),
+            Err(e) => Err(e),
+        };
+    }
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.lock()?;
+    let written = file.metadata().and_then(|metadata| {
+        // One write call per batch, so readers without a lock see whole lines too.
+        file.write_all(lines.as_bytes())?;
+        Ok(metadata.len())
+    });
+    file.unlock()?;
+    written
+}
+
+/// Cuts `path` back to `len` bytes under the exclusive lock, undoing the
+/// [`append_all`] that returned `len`.
+pub fn truncate(path: &Path, len: u64) -> io::Result<()> {
+    let file = OpenOptions::new().write(true).open(path)?;
+    file.lock()?;
+    let truncated = file.set_len(len);
+    file.unlock()?;
+    truncated
+}
+
+/// Reads every record that parses as `T`; a missing file reads as empty.
+///
+/// Malformed lines, e.g. a partly written last line, are skipped so they
+/// cannot hide the rest of the file.
+pub fn read<T: DeserializeOwned>(path: &Path) -> io::Result<Vec<T>> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    file.lock_shared()?;
+    let mut records = Vec::new();
+    for line in BufReader::new(&file).lines() {
+        let line = line?;
+        if let Ok(record) = serde_json::from_str(line.trim()) {
+            records.push(record);
+        }
+    }
+    file.unlock()?;
+    Ok(records)
+}
+
+/// Lines dropped by [`retain`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Dropped {
+    pub lines: usize,
+    pub bytes: u64,
+}
+
+/// Drops the lines for which `keep` returns false, holding the exclusive lock
+/// so no append lands in between; with `apply` false only counts them.
+///
+/// A missing file drops nothing.
+pub fn retain(path: &Path, apply: bool, mut keep: impl FnMut(&str) -> bool) -> io::Result<Dropped> {
+    let mut file = match OpenOptions::new().read(true).write(true).open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Dropped::default()),
+        Err(e) => return Err(e),
+    };
+    file.lock()?;
+    let result = (|| {
+        let mut content = String::new();
+        file.read_to_string(&mut content)?;
+        let mut kept = String::with_capacity(content.len());
+        let mut dropped = Dropped::default();
+        for line in content.split_inclusive('\n') {
+            if line.trim().is_empty() || keep(line.trim()) {
+                kept.push_str(line);
+            } else {
+                dropped.lines += 1;
+                dropped.bytes += line.len() as u64;
+            }
+        }
+        if apply && dropped.lines > 0 {
+            file.set_len(0)?;
+            file.rewind()?;
+            file.write_all(kept.as_bytes())?;
+        }
+        Ok(dropped)
+    })();
+    file.unlock()?;
+    result
+}
+
+/// Runs `f` on `path` opened for reading and appending, holding the
+/// exclusive lock throughout; creates the file and its directory if needed.
+pub fn with_lock<R>(path: &Path, f: impl FnOnce(&mut File) -> io::Result<R>) -> io::Result<R> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut file = OpenOptions::new()
+        .read(true)
+        .append(true)
+        .create(true)
+        .open(path)?;
+    file.lock()?;
+    let result = f(&mut file);
+    file.unlock()?;
+    result
+}
+
+/// Returns the last non-blank line of `file` without its line ending,
+/// reading backwards from the end so large files stay cheap.
+pub fn last_line(file: &mut File) -> io::Result<Option<Vec<u8>>> {
+    const CHUNK: u64 = 8 * 1024;
+    let mut pos = file.seek(SeekFrom::End(0))?;
+    let mut tail = Vec::new();
+    while pos > 0 {
+        let step = pos.min(CHUNK);
+        pos -= step;
+        let mut chunk = vec![0; step as usize];
+        file.seek(SeekFrom::Start(pos))?;
+        file.read_exact(&mut chunk)?;
+        chunk.extend_from_slice(&tail);
+        tail = chunk;
+        let text = tail.trim_ascii_end();
+        if let Some(newline) = text.iter().rposition(|&b| b == b'\n') {
+            return Ok(Some(text[newline + 1..].trim_ascii().to_vec()));
+        }
+    }
+    let text = tail.trim_ascii();
+    Ok((!text.is_empty()).then(|| text.to_vec()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Event {
+        n: u32,
+        server: String,
+    }
+
+    #[test]
+    fn append_then_read_round_trips_and_skips_malformed_lines() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("audit").join("audit.jsonl");
+        append(
+            &path,
+            &Event {
+                n: 1,
+                server: "github".to_string(),
+            },
+        )
+        .unwrap();
+        fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(b"{\"n\":2,\"serv\n")
+            .unwrap();
+        append(
+            &path,
+            &Event {
+                n: 3,
+                server: "slack".to_string(),
+            },
+        )
+        .unwrap();
+
+        let events: Vec<Event> = read(&path).unwrap();
+        assert_eq!(events.iter().map(|e| e.n).collect::<Vec<_>>(), [1, 3]);
+        assert!(read::<Event>(&tmp.path().join("none.jsonl"))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn append_all_writes_a_batch_that_truncate_rolls_back() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("reports").join("github.jsonl");
+        append_all::<Event>(&path, &[]).unwrap();
+        assert!(!path.exists());
+
+        let batch: Vec<Event> = (1..=3)
+            .map(|n| Event {
+                n,
+                server: "github".to_string(),
+            })
+            .collect();
+        assert_eq!(append_all(&path, &batch).unwrap(), 0);
+        let len = append_all(&path, &batch[..1]).unwrap();
+        let events: Vec<Event> = read(&path).unwrap();
+        assert_eq!(events.iter().map(|e| e.n).collect::<Vec<_>>(), [1, 2, 3, 1]);
+
+        truncate(&path, len).unwrap();
+        let events: Vec<Event> = read(&path).unwrap();
+        assert_eq!(events.iter().map(|e| e.n).collect::<Vec<_>>(), [1, 2, 3]);
+        assert_eq!(append_all::<Event>(&path, &[]).unwrap(), len);
+    }
+
+    #[test]
+    fn retain_drops_rejected_lines_only_when_applied() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("audit.jsonl");
+        for n in 1..=4 {
+            append(
+                &path,
+                &Event {
+                    n,
+                    server: "github".to_string(),
+                },
+            )
+            .unwrap();
+        }
+        let old = |line: &str| !line.contains("\"n\":1") && !line.contains("\"n\":2");
+
+        let counted = retain(&path, false, old).unwrap();
+        assert_eq!(counted.lines, 2);
+        assert_eq!(read::<Event>(&path).unwrap().len(), 4);
+
+        let dropped = retain(&path, true, old).unwrap();
+        assert_eq!(dropped, counted);
+        let left: Vec<Event> = read(&path).unwrap();
+        assert_eq!(left.iter().map(|e| e.n).collect::<Vec<_>>(), [3, 4]);
+        assert_eq!(
+            fs::metadata(&path).unwrap().len() + dropped.bytes,
+            4 * fs::metadata(&path).unwrap().len() / 2
+        );
+        assert_eq!(
+            retain(&tmp.path().join("none.jsonl"), true, |_| false).unwrap(),
+            Dropped::default()
+        );
+    }
+
+    #[test]
+    fn last_line_skips_trailing_blank_lines() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("audit").join("audit.jsonl");
+        let long = "x".repeat(20_000);
+        let last = with_lock(&path, |file| {
+            assert_eq!(last_line(file)?, None);
+            file.write_all(format!("first\n{long}\n\n").as_bytes())?;
+            last_line(file)
+        })
+        .unwrap();
+        assert_eq!(last, Some(long.into_bytes()));
+
+        fs::write(&path, "only").unwrap();
+        let last = with_lock(&path, last_line).unwrap();
+        assert_eq!(last, Some(b"only".to_vec()));
+    }
+
+    #[test]
+    fn concurrent_appends_keep_lines_whole() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = Arc::new(tmp.path().join("events.jsonl"));
+        let writers: Vec<_> = (0..8)
+            .map(|t| {
+                let path = Arc::clone(&path);
+                thread::spawn(move || {
+                    for i in 0..50 {
+                        let event = Event {
+                            n: t * 100 + i,
+                            server: "x".repeat(512),
+                        };
+                        append(&path, &event).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+        assert_eq!(read::<Event>(&path).unwrap().len(), 400);
+    }
+}
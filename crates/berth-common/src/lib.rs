@@ -0,0 +1,19 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Foundation helpers shared by the Berth CLI, runtime, and registry crates.
+
+pub mod atomic_file;
+pub mod digest;
+pub mod home;
+pub mod id;
+pub mod jsonl;
+pub mod redact;
+pub mod storage;
+pub mod time;
+
+pub use atomic_file::{atomic_write, atomic_write_private};
+pub use digest::{sha256, sha256_file, sha256_hex};
+pub use home::{berth_layout, BerthLayout};
+pub use id::new_ulid;
+pub use time::{format_rfc3339, now_epoch_ms, now_epoch_secs};
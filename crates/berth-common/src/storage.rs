@@ -0,0 +1,280 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Preflight checks that a Berth directory can take writes.
+//!
+//! A read-only mount or a full disk otherwise surfaces as whichever io error
+//! the first write of an operation happens to hit, often after part of the
+//! operation has already run. Mutating commands check up front instead and
+//! report one [`StorageProblem`].
+
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Free space below which a directory counts as full for state writes.
+pub const MIN_FREE_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Why a directory cannot take writes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StorageProblem {
+    /// The directory is on a read-only filesystem.
+    ReadOnly { path: PathBuf },
+    /// Less than `required` bytes are free.
+    Full {
+        path: PathBuf,
+        available: u64,
+        required: u64,
+    },
+    /// Writing failed for another reason, e.g. missing permissions.
+    Unwritable { path: PathBuf, reason: String },
+}
+
+impl StorageProblem {
+    /// Directory the problem was found in.
+    pub fn path(&self) -> &Path {
+        match self {
+            StorageProblem::ReadOnly { path }
+            | StorageProblem::Full { path, .. }
+            | StorageProblem::Unwritable { path, .. } => path,
+        }
+    }
+}
+
+impl fmt::Display for StorageProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageProblem::ReadOnly { path } => {
+                write!(f, "{} is on a read-only filesystem", path.display())
+            }
+            StorageProblem::Full {
+                path,
+                available,
+                required,
+            } => write!(
+                f,
+                "{} is out of disk space ({} free, {} needed)",
+                path.display(),
+                format_bytes(*available),
+                format_bytes(*required)
+            ),
+            StorageProblem::Unwritable { path, reason } => {
+                write!(f, "{} is not writable: {reason}", path.display())
+            }
+        }
+    }
+}
+
+impl std::error::Error for StorageProblem {}
+
+impl From<StorageProblem> for io::Error {
+    fn from(problem: StorageProblem) -> Self {
+        let kind = match problem {
+            StorageProblem::ReadOnly { .. } => io::ErrorKind::ReadOnlyFilesystem,
+            StorageProblem::Full { .. } => io::ErrorKind::StorageFull,
+            StorageProblem::Unwritable { .. } => io::ErrorKind::PermissionDenied,
+        };
+        io::Error::new(kind, problem)
+    }
+}
+
+/// Checks that `dir` exists or can be created, accepts a new file, and has
+/// at least `required` bytes free.
+///
+/// Free space is only checked where the platform reports it.
+pub fn check_writable(dir: &Path, required: u64) -> Result<(), StorageProblem> {
+    let classify = |e: io::Error| classify_error(dir, &e);
+    fs::create_dir_all(dir).map_err(classify)?;
+    let probe = dir.join(format!(".berth-write-probe-{}", std::process::id()));
+    OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(&probe)
+        .map_err(classify)?;
+    let _ = fs::remove_file(&probe);
+
+    match available_bytes(dir) {
+        Some(available) if available < required => Err(StorageProblem::Full {
+            path: dir.to_path_buf(),
+            available,
+            required,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Maps a failed write below `dir` to the storage problem it indicates.
+pub fn classify_error(dir: &Path, error: &io::Error) -> StorageProblem {
+    let path = dir.to_path_buf();
+    match error.kind() {
+        io::ErrorKind::ReadOnlyFilesystem => StorageProblem::ReadOnly { path },
+        io::ErrorKind::StorageFull | io::ErrorKind::QuotaExceeded => StorageProblem::Full {
+            available: available_bytes(dir).unwrap_or(0),
+            required: MIN_FREE_BYTES,
+            path,
+        },
+        _ => StorageProblem::Unwritable {
+            path,
+            reason: error.to_string(),
+        },
+    }
+}
+
+/// Returns whether `error` means the filesystem cannot take writes at all,
+/// as opposed to a problem with one file.
+pub fn is_storage_error(error: &io::Error) -> bool {
+    matches!(
+        error.kind(),
+        io::ErrorKind::ReadOnlyFilesystem
+            | io::ErrorKind::StorageFull
+            | io::ErrorKind::QuotaExceeded
+    )
+}
+
+/// Returns the bytes available to unprivileged writers below `dir`.
+#[cfg(unix)]
+pub fn available_bytes(dir: &Path) -> Option<u64> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let path = CString::new(dir.as_os_str().as_bytes()).ok()?;
+    // SAFETY: an all-zero statvfs is valid and is only read after success.
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    // SAFETY: `path` is NUL-terminated and `stat` is a writable statvfs.
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    #[allow(clippy::unnecessary_cast)]
+    Some((stat.f_bavail as u64).saturating_mul(stat.f_frsize as u64))
+}
+
+/// Returns the bytes available to the current user below `dir`.
+#[cfg(windows)]
+pub fn available_bytes(dir: &Path) -> Option<u64> {
+    use std::os::windows::ffi::OsStrExt;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetDiskFreeSpaceExW(
+            directory: *const u16,
+            free_to_caller: *mut u64,
+            total: *mut u64,
+            total_free: *mut u64,
+        ) -> i32;
+    }
+
+    let wide: Vec<u16> = dir.as_os_str().encode_wide().chain([0]).collect();
+    let mut free = 0u64;
+    // SAFETY: `wide` is NUL-terminated; null is allowed for the totals.
+    let ok = unsafe {
+        GetDiskFreeSpaceExW(
+            wide.as_ptr(),
+            &mut free,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+        )
+    };
+    (ok != 0).then_some(free)
+}
+
+#[cfg(not(any(unix, windows)))]
+pub fn available_bytes(_dir: &Path) -> Option<u64> {
+    None
+}
+
+/// Returns the total size of the files below `path`; a missing path is empty.
+///
+/// Symbolic links are counted as links, not followed.
+pub fn dir_size(path: &Path) -> io::Result<u64> {
+    let metadata = match fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e),
+    };
+    if !metadata.is_dir() {
+        return Ok(metadata.len());
+    }
+    let mut total = 0u64;
+    for entry in fs::read_dir(path)? {
+        total = total.saturating_add(dir_size(&entry?.path())?);
+    }
+    Ok(total)
+}
+
+/// Formats a byte count with a binary unit for diagnostics.
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writable_directory_passes_and_leaves_no_probe() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("state");
+        check_writable(&dir, 0).unwrap();
+        assert!(dir.is_dir());
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn reports_full_disk_when_space_is_short() {
+        let tmp = tempfile::tempdir().unwrap();
+        let problem = check_writable(tmp.path(), u64::MAX).unwrap_err();
+        assert!(matches!(problem, StorageProblem::Full { .. }));
+        assert!(problem.to_string().contains("out of disk space"));
+        let error = io::Error::from(problem);
+        assert!(is_storage_error(&error));
+    }
+
+    #[test]
+    fn classifies_io_errors() {
+        let dir = Path::new("/berth");
+        let read_only = io::Error::from(io::ErrorKind::ReadOnlyFilesystem);
+        assert_eq!(
+            classify_error(dir, &read_only),
+            StorageProblem::ReadOnly {
+                path: dir.to_path_buf()
+            }
+        );
+        let denied = io::Error::from(io::ErrorKind::PermissionDenied);
+        assert!(matches!(
+            classify_error(dir, &denied),
+            StorageProblem::Unwritable { .. }
+        ));
+        assert!(!is_storage_error(&denied));
+    }
+
+    #[test]
+    fn dir_size_sums_nested_files() {
+        let tmp = tempfile::tempdir().unwrap();
+        fs::create_dir_all(tmp.path().join("a/b")).unwrap();
+        fs::write(tmp.path().join("a/one"), [0u8; 10]).unwrap();
+        fs::write(tmp.path().join("a/b/two"), [0u8; 32]).unwrap();
+        assert_eq!(dir_size(tmp.path()).unwrap(), 42);
+        assert_eq!(dir_size(&tmp.path().join("a/one")).unwrap(), 10);
+        assert_eq!(dir_size(&tmp.path().join("missing")).unwrap(), 0);
+    }
+
+    #[test]
+    fn formats_byte_counts() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(16 * 1024 * 1024), "16.0 MiB");
+    }
+}
@@ -0,0 +1,143 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Unix timestamps, their RFC3339 rendering, and civil dates.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Returns current unix timestamp in milliseconds.
+pub fn now_epoch_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// Returns current unix timestamp in seconds.
+pub fn now_epoch_secs() -> u64 {
+    now_epoch_ms() / 1_000
+}
+
+/// Renders epoch milliseconds as RFC3339 with millisecond precision.
+///
+/// `offset_secs` of zero renders a `Z` suffix.
+pub fn format_rfc3339(epoch_ms: u64, offset_secs: i32) -> String {
+    let local_ms = epoch_ms as i64 + i64::from(offset_secs) * 1_000;
+    let (days, ms_of_day) = (
+        local_ms.div_euclid(86_400_000),
+        local_ms.rem_euclid(86_400_000),
+    );
+    let (year, month, day) = civil_from_days(days);
+    let secs = ms_of_day / 1_000;
+    let stamp = format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:03}",
+        secs / 3_600,
+        secs % 3_600 / 60,
+        secs % 60,
+        ms_of_day % 1_000
+    );
+    if offset_secs == 0 {
+        return format!("{stamp}Z");
+    }
+    let sign = if offset_secs < 0 { '-' } else { '+' };
+    let abs = offset_secs.unsigned_abs();
+    format!("{stamp}{sign}{:02}:{:02}", abs / 3_600, abs % 3_600 / 60)
+}
+
+/// Parses a `YYYY-MM-DD` date into days since the Unix epoch, rejecting
+/// days the month does not have.
+pub fn parse_date(date: &str) -> Option<i64> {
+    let mut parts = date.split('-');
+    let (Some(y), Some(m), Some(d), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return None;
+    };
+    if y.len() != 4 || m.len() != 2 || d.len() != 2 {
+        return None;
+    }
+    let (year, month, day): (i64, u32, u32) = (y.parse().ok()?, m.parse().ok()?, d.parse().ok()?);
+    let leap = (year % 4 == 0 && year % 100 != 0) || year % 400 == 0;
+    let month_days = match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        1..=12 => 31,
+        _ => return None,
+    };
+    if !(1..=month_days).contains(&day) {
+        return None;
+    }
+    Some(days_from_civil(year, month, day))
+}
+
+/// Days since 1970-01-01 for a proleptic Gregorian date.
+pub fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    // March is the first month of each 400-year era, so leap days fall last.
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = i64::from(month);
+    let day_of_year =
+        (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Converts days since the Unix epoch into a `(year, month, day)` civil date.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_rfc3339_renders_utc_with_millis() {
+        assert_eq!(format_rfc3339(0, 0), "1970-01-01T00:00:00.000Z");
+        assert_eq!(
+            format_rfc3339(1_709_210_096_789, 0),
+            "2024-02-29T12:34:56.789Z"
+        );
+    }
+
+    #[test]
+    fn format_rfc3339_applies_offsets_across_midnight() {
+        assert_eq!(
+            format_rfc3339(1_709_164_800_000, 7_200),
+            "2024-02-29T02:00:00.000+02:00"
+        );
+        assert_eq!(
+            format_rfc3339(1_709_164_800_000, -19_800),
+            "2024-02-28T18:30:00.000-05:30"
+        );
+    }
+
+    #[test]
+    fn parse_date_matches_known_dates_and_round_trips() {
+        assert_eq!(parse_date("1970-01-01"), Some(0));
+        assert_eq!(parse_date("2000-03-01"), Some(11_017));
+        assert_eq!(parse_date("2024-02-29"), Some(19_782));
+        assert_eq!(civil_from_days(19_782), (2024, 2, 29));
+        assert_eq!(parse_date("1969-12-31"), Some(-1));
+        for bad in [
+            "2023-02-29",
+            "2026-04-31",
+            "2026-13-01",
+            "2026-2-1",
+            "2026-01-01-01",
+        ] {
+            assert_eq!(parse_date(bad), None, "{bad}");
+        }
+    }
+}
@@ -6,7 +6,7 @@
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 
-use crate::types::ServerMetadata;
+use crate::types::{HealthCheck, ResourceLimits, ServerMetadata};
 
 /// On-disk server configuration written to `~/.berth/servers/<name>.toml`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -23,6 +23,8 @@ pub struct InstalledServer {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ServerInfo {
+    /// Registry name; the local instance name is the config file stem, which
+    /// differs for servers installed with `berth install --as <alias>`.
     pub name: String,
     pub display_name: String,
     pub version: String,
@@ -30,6 +32,11 @@ pub struct ServerInfo {
     pub category: String,
     pub maintainer: String,
     pub trust_level: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Constraint given at install (`^1.2`, `1.2.0`); `berth update` stays within it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version_constraint: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,6 +45,13 @@ pub struct SourceInfo {
     pub source_type: String,
     pub package: String,
     pub repository: String,
+    /// Registry index the server was installed from (see `Registry::origin`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub registry: Option<String>,
+    /// SHA-256 the installed artifact was verified against, when the
+    /// registry declared one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -47,6 +61,21 @@ pub struct RuntimeInfo {
     pub command: String,
     pub args: Vec<String>,
     pub transport: String,
+    /// Syscalls the server needs beyond Berth's default seccomp profile.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub syscalls: Vec<String>,
+    /// Health check declared by the server's manifest.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub health_check: Option<HealthCheck>,
+    /// Schedule of a `job` runtime, as declared by its manifest.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<String>,
+    /// Resource limits declared by the server's manifest.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limits: Option<ResourceLimits>,
+    /// Endpoint template of a `unix-socket` or `named-pipe` server.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub socket: Option<String>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -59,12 +88,18 @@ pub struct PermissionsInfo {
     pub filesystem: Vec<String>,
     #[serde(default)]
     pub exec: Vec<String>,
+    /// Permissions each listed tool needs, in prefixed form.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tools: BTreeMap<String, Vec<String>>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ConfigMeta {
     pub required_keys: Vec<String>,
     pub optional_keys: Vec<String>,
+    /// Keys the manifest marks `sensitive`; their values belong in the secure backend.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sensitive_keys: Vec<String>,
 }
 
 impl InstalledServer {
@@ -85,6 +120,14 @@ impl InstalledServer {
 
         let required_keys = meta.config.required.iter().map(|f| f.key.clone()).collect();
         let optional_keys = meta.config.optional.iter().map(|f| f.key.clone()).collect();
+        let sensitive_keys = meta
+            .config
+            .required
+            .iter()
+            .chain(meta.config.optional.iter())
+            .filter(|f| f.sensitive)
+            .map(|f| f.key.clone())
+            .collect();
 
         InstalledServer {
             server: ServerInfo {
@@ -95,28 +138,39 @@ impl InstalledServer {
                 category: meta.category.clone(),
                 maintainer: meta.maintainer.clone(),
                 trust_level: meta.trust_level.to_string(),
+                tags: meta.tags.clone(),
+                version_constraint: None,
             },
             source: SourceInfo {
                 source_type: meta.source.source_type.clone(),
                 package: meta.source.package.clone(),
                 repository: meta.source.repository.clone(),
+                registry: None,
+                sha256: None,
             },
             runtime: RuntimeInfo {
                 runtime_type: meta.runtime.runtime_type.clone(),
                 command: meta.runtime.command.clone(),
                 args: meta.runtime.args.clone(),
                 transport: meta.transport.clone(),
+                syscalls: meta.runtime.syscalls.clone(),
+                health_check: meta.runtime.health_check.clone(),
+                schedule: meta.runtime.schedule.clone(),
+                limits: meta.runtime.limits,
+                socket: meta.runtime.socket.clone(),
             },
             permissions: PermissionsInfo {
                 network: meta.permissions.network.clone(),
                 env: meta.permissions.env.clone(),
                 filesystem: meta.permissions.filesystem.clone(),
                 exec: meta.permissions.exec.clone(),
+                tools: meta.permissions.tools.clone(),
             },
             config,
             config_meta: ConfigMeta {
                 required_keys,
                 optional_keys,
+                sensitive_keys,
             },
         }
     }
@@ -165,6 +219,7 @@ mod tests {
             .config_meta
             .required_keys
             .contains(&"token".to_string()));
+        assert_eq!(installed.config_meta.sensitive_keys, vec!["token"]);
     }
 
     #[test]
@@ -194,4 +249,27 @@ mod tests {
             installed.config_meta.required_keys
         );
     }
+
+    #[test]
+    fn health_check_survives_manifest_and_toml_round_trip() {
+        let mut meta = github_metadata();
+        meta.runtime.health_check = Some(
+            serde_json::from_str(r#"{"probe": "exec", "command": "curl", "args": ["-f", "localhost"], "timeoutMs": 2000}"#)
+                .unwrap(),
+        );
+        let installed = InstalledServer::from_metadata(&meta);
+
+        let toml_str = toml::to_string_pretty(&installed).unwrap();
+        let deserialized: InstalledServer = toml::from_str(&toml_str).unwrap();
+        let check = deserialized.runtime.health_check.unwrap();
+        assert_eq!(
+            check.probe,
+            crate::types::HealthProbe::Exec {
+                command: "curl".to_string(),
+                args: vec!["-f".to_string(), "localhost".to_string()],
+            }
+        );
+        assert_eq!((check.interval_secs, check.timeout_ms), (30, 2_000));
+        assert!(check.problems().is_empty());
+    }
 }
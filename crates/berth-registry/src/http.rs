@@ -0,0 +1,316 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Fetching remote registry indexes over HTTP(S), checking that links in
+//! them resolve, and posting forms such as OAuth token requests.
+//!
+//! With the default `http-client` feature requests go through a built-in
+//! client that honors `HTTPS_PROXY`/`HTTP_PROXY`/`ALL_PROXY` and `NO_PROXY`
+//! and revalidates cached indexes with `If-None-Match`. Builds without it
+//! fall back to `curl` or `wget` and always download the full index.
+
+use std::time::Duration;
+
+/// Longest a whole index request may take, including the body.
+pub const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+/// Longest to wait for a connection to the registry host.
+pub const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Result of fetching an index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fetched {
+    /// The server sent the index, with the entity tag to revalidate it later.
+    Body { body: String, etag: Option<String> },
+    /// The index matches the entity tag that was sent; use the cached copy.
+    NotModified,
+}
+
+#[cfg(feature = "http-client")]
+fn agent() -> ureq::Agent {
+    ureq::Agent::config_builder()
+        .timeout_global(Some(REQUEST_TIMEOUT))
+        .timeout_connect(Some(CONNECT_TIMEOUT))
+        .http_status_as_error(false)
+        .user_agent(concat!("berth/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .into()
+}
+
+/// Fetches `url` as text, revalidating against `etag` when one is given.
+#[cfg(feature = "http-client")]
+pub fn fetch_text(url: &str, etag: Option<&str>) -> Result<Fetched, String> {
+    let mut request = agent().get(url).header("Accept", "application/json");
+    if let Some(etag) = etag {
+        request = request.header("If-None-Match", etag);
+    }
+    let mut response = request.call().map_err(|e| describe_error(url, &e))?;
+
+    let status = response.status().as_u16();
+    tracing::debug!(url, status, "registry index response");
+    if status == 304 && etag.is_some() {
+        return Ok(Fetched::NotModified);
+    }
+    if !(200..300).contains(&status) {
+        return Err(format!("registry index {url} returned HTTP {status}"));
+    }
+    let etag = response
+        .headers()
+        .get("etag")
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    let body = response
+        .body_mut()
+        .read_to_string()
+        .map_err(|e| format!("failed reading registry index from {url}: {e}"))?;
+    Ok(Fetched::Body { body, etag })
+}
+
+/// Returns whether a `HEAD` request for `url`, following redirects, ends in a
+/// 2xx response.
+#[cfg(feature = "http-client")]
+pub fn url_resolves(url: &str) -> bool {
+    match agent().head(url).call() {
+        Ok(response) => {
+            let status = response.status().as_u16();
+            tracing::debug!(url, status, "link check response");
+            (200..300).contains(&status)
+        }
+        Err(e) => {
+            tracing::debug!(url, error = %e, "link check failed");
+            false
+        }
+    }
+}
+
+/// Posts a form-encoded `body` to `url` and returns the status and response
+/// text.
+///
+/// Non-2xx responses are returned rather than treated as errors, since
+/// endpoints such as OAuth token URLs explain failures in the body.
+#[cfg(feature = "http-client")]
+pub fn post_form(url: &str, body: &str) -> Result<(u16, String), String> {
+    let mut response = agent()
+        .post(url)
+        .header("Accept", "application/json")
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .send(body)
+        .map_err(|e| format!("request to {url} failed: {e}"))?;
+    let status = response.status().as_u16();
+    tracing::debug!(url, status, "form post response");
+    let text = response
+        .body_mut()
+        .read_to_string()
+        .map_err(|e| format!("failed reading response from {url}: {e}"))?;
+    Ok((status, text))
+}
+
+/// Turns a transport error into a message that says what to check.
+#[cfg(feature = "http-client")]
+fn describe_error(url: &str, error: &ureq::Error) -> String {
+    match error {
+        ureq::Error::Timeout(_) => format!(
+            "registry index {url} did not respond within {}s",
+            REQUEST_TIMEOUT.as_secs()
+        ),
+        ureq::Error::HostNotFound => {
+            format!("could not resolve the host of registry index {url}")
+        }
+        ureq::Error::ConnectionFailed => format!(
+            "could not connect to registry index {url} (check the URL and any HTTPS_PROXY/HTTP_PROXY settings)"
+        ),
+        other => format!("failed to fetch registry index from {url}: {other}"),
+    }
+}
+
+/// Fetches `url` with `curl` or `wget`; entity tags are not supported.
+#[cfg(not(feature = "http-client"))]
+pub fn fetch_text(url: &str, _etag: Option<&str>) -> Result<Fetched, String> {
+    use std::process::Command;
+
+    let timeout = REQUEST_TIMEOUT.as_secs().to_string();
+    let attempts = [
+        Command::new("curl")
+            .args(["-fsSL", "--max-time", &timeout, url])
+            .output(),
+        Command::new("wget")
+            .args(["-q", "-O", "-", &format!("--timeout={timeout}"), url])
+            .output(),
+    ];
+    for output in attempts.into_iter().flatten() {
+        if output.status.success() {
+            let body = String::from_utf8(output.stdout)
+                .map_err(|e| format!("registry response was not utf-8: {e}"))?;
+            return Ok(Fetched::Body { body, etag: None });
+        }
+    }
+    Err(format!(
+        "failed to fetch registry index from {url} (curl/wget unavailable or request failed)"
+    ))
+}
+
+/// Returns whether `curl` reaches `url` with a 2xx `HEAD` response, following
+/// redirects.
+#[cfg(not(feature = "http-client"))]
+pub fn url_resolves(url: &str) -> bool {
+    let timeout = REQUEST_TIMEOUT.as_secs().to_string();
+    std::process::Command::new("curl")
+        .args(["-fsIL", "--max-time", &timeout, "-o", "/dev/null", url])
+        .status()
+        .is_ok_and(|s| s.success())
+}
+
+/// Posts a form-encoded `body` to `url` with `curl` and returns the status
+/// and response text.
+///
+/// The body goes over curl's stdin so secrets never appear in the process
+/// list.
+#[cfg(not(feature = "http-client"))]
+pub fn post_form(url: &str, body: &str) -> Result<(u16, String), String> {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let mut child = Command::new("curl")
+        .args([
+            "-sS",
+            "--max-time",
+            &REQUEST_TIMEOUT.as_secs().to_string(),
+            "-H",
+            "Accept: application/json",
+            "--data-binary",
+            "@-",
+            "-w",
+            "\n%{http_code}",
+            url,
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("failed to invoke curl for {url}: {e}"))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(body.as_bytes())
+            .map_err(|e| format!("failed to send request to {url}: {e}"))?;
+    }
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("failed waiting for request to {url}: {e}"))?;
+    if !output.status.success() {
+        return Err(format!(
+            "request to {url} failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let (text, status) = stdout.rsplit_once('\n').unwrap_or(("", &stdout));
+    let status = status
+        .trim()
+        .parse()
+        .map_err(|_| format!("request to {url} returned no HTTP status"))?;
+    Ok((status, text.to_string()))
+}
+
+#[cfg(all(test, feature = "http-client"))]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    /// Serves one request per response, returning each request's header lines.
+    fn serve(responses: Vec<&'static str>) -> (String, thread::JoinHandle<Vec<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/index.json", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            let mut requests = Vec::new();
+            for response in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut headers = Vec::new();
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    headers.push(line.trim().to_string());
+                }
+                let length = headers
+                    .iter()
+                    .filter_map(|h| h.split_once(':'))
+                    .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+                    .and_then(|(_, value)| value.trim().parse().ok())
+                    .unwrap_or(0);
+                let mut body = vec![0; length];
+                std::io::Read::read_exact(&mut reader, &mut body).unwrap();
+                stream.write_all(response.as_bytes()).unwrap();
+                requests.push(headers);
+            }
+            requests
+        });
+        (url, handle)
+    }
+
+    #[test]
+    fn fetch_returns_body_with_etag_then_not_modified() {
+        let (url, server) = serve(vec![
+            "HTTP/1.1 200 OK\r\nETag: \"v1\"\r\nContent-Length: 2\r\nConnection: close\r\n\r\n[]",
+            "HTTP/1.1 304 Not Modified\r\nETag: \"v1\"\r\nConnection: close\r\n\r\n",
+        ]);
+
+        let first = fetch_text(&url, None).unwrap();
+        assert_eq!(
+            first,
+            Fetched::Body {
+                body: "[]".to_string(),
+                etag: Some("\"v1\"".to_string())
+            }
+        );
+        assert_eq!(
+            fetch_text(&url, Some("\"v1\"")).unwrap(),
+            Fetched::NotModified
+        );
+
+        let requests = server.join().unwrap();
+        assert!(requests[1]
+            .iter()
+            .any(|h| h.eq_ignore_ascii_case("if-none-match: \"v1\"")));
+    }
+
+    #[test]
+    fn fetch_reports_http_status() {
+        let (url, server) = serve(vec![
+            "HTTP/1.1 503 Service Unavailable\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+        ]);
+        let err = fetch_text(&url, None).unwrap_err();
+        assert!(err.contains("returned HTTP 503"), "{err}");
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn url_resolves_only_on_success_status() {
+        let (url, server) = serve(vec![
+            "HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+            "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+        ]);
+        assert!(url_resolves(&url));
+        assert!(!url_resolves(&url));
+        let requests = server.join().unwrap();
+        assert!(requests[0][0].starts_with("HEAD "), "{:?}", requests[0]);
+    }
+
+    #[test]
+    fn post_form_returns_error_bodies() {
+        let (url, server) = serve(vec![
+            "HTTP/1.1 400 Bad Request\r\nContent-Length: 25\r\nConnection: close\r\n\r\n{\"error\":\"invalid_grant\"}",
+        ]);
+        let (status, body) = post_form(&url, "grant_type=x").unwrap();
+        assert_eq!(status, 400);
+        assert_eq!(body, r#"{"error":"invalid_grant"}"#);
+        let requests = server.join().unwrap();
+        assert!(requests[0][0].starts_with("POST "), "{:?}", requests[0]);
+        assert!(requests[0]
+            .iter()
+            .any(|h| h.eq_ignore_ascii_case("content-type: application/x-www-form-urlencoded")));
+    }
+}
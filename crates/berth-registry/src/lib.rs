@@ -4,49 +4,172 @@
 //! Registry loading and query APIs for Berth.
 
 pub mod config;
+pub mod http;
+pub mod mirrors;
+pub mod permissions;
 pub mod search;
 pub mod seed;
+pub mod signing;
 pub mod types;
+pub mod versions;
 
-use search::{search_servers, SearchResult};
+use berth_common::atomic_write;
+use http::Fetched;
+use mirrors::{
+    fetch_from_mirrors, parse_index_urls, MirrorHealth, HEDGE_DELAY, MIRROR_HEALTH_FILE,
+};
+use search::{search_page, search_servers, Cursor, Page, SearchResult};
 use seed::load_seed_registry;
+use signing::{IndexTrust, PinnedKeys, SIGNATURE_SUFFIX};
 use std::env;
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::process::Command;
 use types::ServerMetadata;
 
+/// Env var pointing at a directory produced by `berth registry-mirror`.
+pub const MIRROR_ENV: &str = "BERTH_REGISTRY_MIRROR";
+/// Index file name inside a registry mirror directory.
+pub const MIRROR_INDEX_FILE: &str = "index.json";
+
+/// Origin reported for the registry compiled into the binary.
+pub const SEED_ORIGIN: &str = "bundled seed";
+
 /// In-memory registry loaded from the embedded seed dataset.
 pub struct Registry {
     servers: Vec<ServerMetadata>,
+    origin: String,
+    trust: IndexTrust,
+}
+
+/// Why the configured index could not be used.
+#[derive(Debug)]
+enum LoadError {
+    /// Nothing configured or reachable; the seed registry stands in.
+    Unavailable(String),
+    /// A remote index failed signature or key-pin checks; never replaced
+    /// silently by callers that can report it.
+    Untrusted(String),
+}
+
+impl From<String> for LoadError {
+    fn from(reason: String) -> Self {
+        LoadError::Unavailable(reason)
+    }
+}
+
+/// Index servers with where they came from and how they were verified.
+struct Loaded {
+    servers: Vec<ServerMetadata>,
+    origin: String,
+    trust: IndexTrust,
+}
+
+/// Where registry keys are pinned and whether a new key may replace one.
+#[derive(Debug, Clone, Copy, Default)]
+struct KeyPins<'a> {
+    path: Option<&'a Path>,
+    accept_new_key: bool,
 }
 
 impl Registry {
-    /// Builds a registry from embedded seed JSON.
+    /// Builds a registry from the configured index, falling back to the
+    /// embedded seed JSON when none is available or it fails verification.
     pub fn from_seed() -> Self {
-        let index_file = env::var_os("BERTH_REGISTRY_INDEX_FILE").map(PathBuf::from);
-        let index_url = env::var("BERTH_REGISTRY_INDEX_URL")
-            .ok()
-            .filter(|v| !v.trim().is_empty());
-        let cache_path = default_cache_path();
-        if let Ok(servers) = load_registry_servers(
+        Self::load().unwrap_or_else(|e| {
+            tracing::warn!(reason = %e, "refusing untrusted registry index; using embedded seed registry");
+            Self::seed()
+        })
+    }
+
+    /// Builds a registry like [`Registry::from_seed`], but returns an error
+    /// instead of the seed when a remote index fails signature or key-pin
+    /// checks (see [`signing`]).
+    pub fn load() -> Result<Self, String> {
+        let index_file = resolve_index_file(
+            env::var_os("BERTH_REGISTRY_INDEX_FILE").map(PathBuf::from),
+            mirror_dir(),
+        );
+        let index_urls = env::var("BERTH_REGISTRY_INDEX_URL")
+            .map(|v| parse_index_urls(&v))
+            .unwrap_or_default();
+        let cache_path = cache_path();
+        let keys_path = signing::pinned_keys_path();
+        let pins = KeyPins {
+            path: keys_path.as_deref(),
+            accept_new_key: signing::accept_new_key(),
+        };
+        match load_registry_servers(
             index_file.as_deref(),
             cache_path.as_deref(),
-            index_url.as_deref(),
+            &index_urls,
+            pins,
         ) {
-            return Registry { servers };
+            Ok(loaded) => Ok(Registry {
+                servers: loaded.servers,
+                origin: loaded.origin,
+                trust: loaded.trust,
+            }),
+            Err(LoadError::Untrusted(e)) => Err(e),
+            Err(LoadError::Unavailable(e)) => {
+                tracing::debug!(reason = %e, "falling back to embedded seed registry");
+                Ok(Self::seed())
+            }
         }
+    }
 
+    fn seed() -> Self {
         Registry {
             servers: load_seed_registry(),
+            origin: SEED_ORIGIN.to_string(),
+            trust: IndexTrust::Unsigned,
         }
     }
 
+    /// Builds a registry from an index file without touching the local cache.
+    pub fn from_index_file(path: &Path) -> Result<Self, String> {
+        let data = fs::read_to_string(path)
+            .map_err(|e| format!("failed reading registry index {}: {e}", path.display()))?;
+        Ok(Registry {
+            servers: parse_registry_json(&data)?,
+            origin: path.display().to_string(),
+            trust: IndexTrust::Unsigned,
+        })
+    }
+
+    /// Builds a registry from a remote JSON index URL without touching the
+    /// local cache; its signature is checked against the key pinned for `url`.
+    pub fn from_index_url(url: &str) -> Result<Self, String> {
+        let Fetched::Body { body: data, .. } = http::fetch_text(url, None)? else {
+            return Err(format!("registry index {url} answered not modified"));
+        };
+        let keys_path = signing::pinned_keys_path();
+        let pins = KeyPins {
+            path: keys_path.as_deref(),
+            accept_new_key: signing::accept_new_key(),
+        };
+        let trust = verify_remote_index(&[url.to_string()], url, &data, pins)?;
+        Ok(Registry {
+            servers: parse_registry_json(&data)?,
+            origin: url.to_string(),
+            trust,
+        })
+    }
+
     /// Searches servers by keyword and relevance.
     pub fn search(&self, query: &str) -> Vec<SearchResult<'_>> {
         search_servers(&self.servers, query)
     }
 
+    /// Returns up to `limit` search results after `after`; see [`search_page`].
+    pub fn search_page(
+        &self,
+        query: &str,
+        after: Option<&Cursor>,
+        limit: usize,
+    ) -> Result<Page<SearchResult<'_>>, String> {
+        search_page(&self.servers, query, after, limit)
+    }
+
     /// Returns a server by exact name.
     pub fn get(&self, name: &str) -> Option<&ServerMetadata> {
         search::find_server(&self.servers, name)
@@ -56,13 +179,38 @@ impl Registry {
     pub fn list_all(&self) -> &[ServerMetadata] {
         &self.servers
     }
+
+    /// Describes where the index was loaded from: a file path, a URL, a
+    /// cache, or [`SEED_ORIGIN`].
+    pub fn origin(&self) -> &str {
+        &self.origin
+    }
+
+    /// Returns how the index was verified against its registry's pinned key.
+    pub fn trust(&self) -> &IndexTrust {
+        &self.trust
+    }
 }
 
+/// Returns the configured registry mirror directory, if any.
+pub fn mirror_dir() -> Option<PathBuf> {
+    env::var_os(MIRROR_ENV)
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Picks the index override file; an explicit file wins over a mirror's index.
+fn resolve_index_file(index_file: Option<PathBuf>, mirror: Option<PathBuf>) -> Option<PathBuf> {
+    index_file.or_else(|| mirror.map(|dir| dir.join(MIRROR_INDEX_FILE)))
+}
+
+/// Loads the first available index, returning its servers and origin.
 fn load_registry_servers(
     index_file: Option<&Path>,
     cache_path: Option<&Path>,
-    index_url: Option<&str>,
-) -> Result<Vec<ServerMetadata>, String> {
+    index_urls: &[String],
+    pins: KeyPins<'_>,
+) -> Result<Loaded, LoadError> {
     if let Some(path) = index_file {
         let data = fs::read_to_string(path).map_err(|e| {
             format!(
@@ -71,19 +219,19 @@ fn load_registry_servers(
             )
         })?;
         let servers = parse_registry_json(&data)?;
+        tracing::debug!(path = %path.display(), count = servers.len(), "loaded registry index override");
         if let Some(cache) = cache_path {
             let _ = write_cache(cache, &data);
         }
-        return Ok(servers);
+        return Ok(Loaded {
+            servers,
+            origin: path.display().to_string(),
+            trust: IndexTrust::Unsigned,
+        });
     }
 
-    if let Some(url) = index_url {
-        let data = fetch_registry_json(url)?;
-        let servers = parse_registry_json(&data)?;
-        if let Some(cache) = cache_path {
-            let _ = write_cache(cache, &data);
-        }
-        return Ok(servers);
+    if !index_urls.is_empty() {
+        return load_from_mirrors(index_urls, cache_path, pins);
     }
 
     if let Some(cache) = cache_path {
@@ -95,11 +243,151 @@ fn load_registry_servers(
                 )
             })?;
             let servers = parse_registry_json(&data)?;
-            return Ok(servers);
+            tracing::debug!(path = %cache.display(), count = servers.len(), "loaded cached registry index");
+            return Ok(Loaded {
+                servers,
+                origin: format!("cache {}", cache.display()),
+                trust: IndexTrust::Unsigned,
+            });
         }
     }
 
-    Err("no registry override or cache available".to_string())
+    Err(LoadError::Unavailable(
+        "no registry override or cache available".to_string(),
+    ))
+}
+
+/// Fetches the index from the healthiest mirror, revalidating the cache and
+/// recording mirror health beside it.
+///
+/// A fresh index is verified before it is cached; all mirrors share the key
+/// pinned for the registry, whichever of them it was pinned under.
+fn load_from_mirrors(
+    index_urls: &[String],
+    cache_path: Option<&Path>,
+    pins: KeyPins<'_>,
+) -> Result<Loaded, LoadError> {
+    let cached = cache_path.filter(|cache| cache.exists());
+    let etag = cached.and_then(|cache| fs::read_to_string(etag_path(cache)).ok());
+    let health_path = cache_path.map(|cache| cache.with_file_name(MIRROR_HEALTH_FILE));
+    let mut health = health_path
+        .as_deref()
+        .map(MirrorHealth::load)
+        .unwrap_or_default();
+    let fetched = fetch_from_mirrors(
+        index_urls,
+        etag.as_deref().map(str::trim),
+        &mut health,
+        HEDGE_DELAY,
+        http::fetch_text,
+    );
+
+    let result = fetched.map_err(LoadError::from).and_then(|(url, fetched)| {
+        // A fresh body carries the entity tag to store with it.
+        let (data, fresh_etag) = match (fetched, cached) {
+            (Fetched::NotModified, None) => {
+                return Err(LoadError::Unavailable(format!(
+                    "registry index {url} answered not modified without a cached copy"
+                )));
+            }
+            (Fetched::NotModified, Some(cache)) => {
+                tracing::debug!(url, "registry index not modified; using cache");
+                let data = fs::read_to_string(cache).map_err(|e| {
+                    format!(
+                        "failed reading cached registry index {}: {e}",
+                        cache.display()
+                    )
+                })?;
+                (data, None)
+            }
+            (Fetched::Body { body, etag }, _) => (body, Some(etag)),
+        };
+        // A revalidated cache was verified when it was fetched.
+        let trust = match fresh_etag {
+            Some(_) => {
+                verify_remote_index(index_urls, &url, &data, pins).map_err(LoadError::Untrusted)?
+            }
+            None => IndexTrust::Unsigned,
+        };
+        let servers = parse_registry_json(&data)?;
+        if let (Some(etag), Some(cache)) = (fresh_etag, cache_path) {
+            if write_cache(cache, &data).is_ok() {
+                let _ = match etag {
+                    Some(etag) => atomic_write(&etag_path(cache), etag),
+                    None => fs::remove_file(etag_path(cache)),
+                };
+                health.cached_from = Some(url.clone());
+            }
+        }
+        tracing::debug!(url, count = servers.len(), "loaded registry index from url");
+        Ok(Loaded {
+            servers,
+            origin: url,
+            trust,
+        })
+    });
+
+    if let Some(path) = &health_path {
+        if let Err(e) = health.save(path) {
+            tracing::debug!(reason = %e, "could not record registry mirror health");
+        }
+    }
+    result
+}
+
+/// Checks the index fetched from `url` against its detached signature and the
+/// key pinned for the registry served from `mirrors`, pinning a new key when
+/// one is accepted and the verified key under mirrors that lack it.
+fn verify_remote_index(
+    mirrors: &[String],
+    url: &str,
+    data: &str,
+    pins: KeyPins<'_>,
+) -> Result<IndexTrust, String> {
+    let signature = match http::fetch_text(&format!("{url}{SIGNATURE_SUFFIX}"), None) {
+        Ok(Fetched::Body { body, .. }) => Some(body),
+        Ok(Fetched::NotModified) => None,
+        Err(e) => {
+            tracing::debug!(url, reason = %e, "no registry index signature");
+            None
+        }
+    };
+    let mut pinned = match pins.path {
+        Some(path) => PinnedKeys::load(path)?,
+        None => PinnedKeys::default(),
+    };
+    let current = match pinned.for_mirrors(mirrors).as_slice() {
+        [] => None,
+        [key] => Some(key.to_string()),
+        // A rotation re-pins every mirror, settling the disagreement.
+        [key, ..] if pins.accept_new_key => Some(key.to_string()),
+        keys => {
+            return Err(format!(
+                "the mirrors of registry index {url} have different keys pinned ({}); confirm the right key out of band and rerun with --accept-new-key",
+                keys.join(", ")
+            ))
+        }
+    };
+    let trust = signing::check_index(
+        url,
+        data.as_bytes(),
+        signature.as_deref(),
+        current.as_deref(),
+        pins.accept_new_key,
+    )?;
+    let key = match &trust {
+        IndexTrust::Pinned { key } | IndexTrust::NewlyPinned { key, .. } => key,
+        IndexTrust::Unsigned => return Ok(trust),
+    };
+    if let Some(path) = pins.path {
+        if pinned.pin(mirrors, key) {
+            pinned.save(path)?;
+        }
+    }
+    if matches!(trust, IndexTrust::NewlyPinned { .. }) {
+        tracing::info!(url, key, "pinned registry signing key");
+    }
+    Ok(trust)
 }
 
 fn parse_registry_json(data: &str) -> Result<Vec<ServerMetadata>, String> {
@@ -117,41 +405,24 @@ fn write_cache(path: &Path, data: &str) -> Result<(), String> {
             })?;
         }
     }
-    fs::write(path, data)
+    atomic_write(path, data)
         .map_err(|e| format!("failed writing registry cache {}: {e}", path.display()))
 }
 
-fn fetch_registry_json(url: &str) -> Result<String, String> {
-    let curl_output = Command::new("curl")
-        .args(["-fsSL", "--max-time", "5", url])
-        .output();
-    if let Ok(output) = curl_output {
-        if output.status.success() {
-            return String::from_utf8(output.stdout)
-                .map_err(|e| format!("registry response was not utf-8: {e}"));
-        }
-    }
-
-    let wget_output = Command::new("wget")
-        .args(["-q", "-O", "-", "--timeout=5", url])
-        .output();
-    if let Ok(output) = wget_output {
-        if output.status.success() {
-            return String::from_utf8(output.stdout)
-                .map_err(|e| format!("registry response was not utf-8: {e}"));
-        }
-    }
-
-    Err(format!(
-        "failed to fetch registry index from {url} (curl/wget unavailable or request failed)"
-    ))
+/// Entity tag of the cached index, kept next to it.
+fn etag_path(cache: &Path) -> PathBuf {
+    let mut name = cache.as_os_str().to_owned();
+    name.push(".etag");
+    PathBuf::from(name)
 }
 
-fn default_cache_path() -> Option<PathBuf> {
+/// Returns the local registry index cache path (`BERTH_REGISTRY_CACHE`
+/// overrides it).
+pub fn cache_path() -> Option<PathBuf> {
     if let Some(path) = env::var_os("BERTH_REGISTRY_CACHE") {
         return Some(PathBuf::from(path));
     }
-    env::var_os("BERTH_HOME").map(|home| PathBuf::from(home).join("registry").join("index.json"))
+    berth_common::berth_layout().map(|layout| layout.cache.join("registry").join("index.json"))
 }
 
 #[cfg(test)]
@@ -159,10 +430,14 @@ mod tests {
     use super::*;
     use std::fs;
 
+    #[cfg(feature = "http-client")]
+    const NOT_FOUND: &str =
+        "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+
     #[test]
     fn registry_from_seed() {
         let registry = Registry::from_seed();
-        assert_eq!(registry.list_all().len(), 30);
+        assert_eq!(registry.list_all().len(), 31);
     }
 
     #[test]
@@ -179,6 +454,33 @@ mod tests {
         assert!(registry.get("nonexistent").is_none());
     }
 
+    #[test]
+    fn index_file_override_wins_over_mirror_index() {
+        let explicit = PathBuf::from("/tmp/explicit.json");
+        let mirror = PathBuf::from("/tmp/mirror");
+        assert_eq!(
+            resolve_index_file(Some(explicit.clone()), Some(mirror.clone())),
+            Some(explicit)
+        );
+        assert_eq!(
+            resolve_index_file(None, Some(mirror.clone())),
+            Some(mirror.join(MIRROR_INDEX_FILE))
+        );
+        assert_eq!(resolve_index_file(None, None), None);
+    }
+
+    #[test]
+    fn registry_loads_from_index_file_without_cache() {
+        let temp = tempfile::tempdir().unwrap();
+        let source = temp.path().join("index.json");
+        let server = load_seed_registry()[0].clone();
+        fs::write(&source, serde_json::to_string(&vec![server]).unwrap()).unwrap();
+
+        let registry = Registry::from_index_file(&source).unwrap();
+        assert_eq!(registry.list_all().len(), 1);
+        assert!(Registry::from_index_file(&temp.path().join("missing.json")).is_err());
+    }
+
     #[test]
     fn registry_loads_from_override_file_and_writes_cache() {
         let temp = tempfile::tempdir().unwrap();
@@ -190,12 +492,211 @@ mod tests {
         server.display_name = "Override Server".to_string();
         fs::write(&source, serde_json::to_string(&vec![server]).unwrap()).unwrap();
 
-        let servers = load_registry_servers(Some(&source), Some(&cache), None).unwrap();
+        let Loaded {
+            servers, origin, ..
+        } = load_registry_servers(Some(&source), Some(&cache), &[], KeyPins::default()).unwrap();
+        assert_eq!(origin, source.display().to_string());
         assert_eq!(servers.len(), 1);
         assert_eq!(servers[0].name, "override-server");
         assert!(cache.exists());
     }
 
+    #[cfg(feature = "http-client")]
+    #[test]
+    fn registry_url_revalidates_cache_with_etag() {
+        use std::io::{BufRead, BufReader, Write};
+
+        let temp = tempfile::tempdir().unwrap();
+        let cache = temp.path().join("cache/index.json");
+        let mut server = load_seed_registry()[0].clone();
+        server.name = "remote-server".to_string();
+        let index = serde_json::to_string(&vec![server]).unwrap();
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/index.json", listener.local_addr().unwrap());
+        let responses = [
+            format!(
+                "HTTP/1.1 200 OK\r\nETag: \"abc\"\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{index}",
+                index.len()
+            ),
+            "HTTP/1.1 304 Not Modified\r\nConnection: close\r\n\r\n".to_string(),
+        ];
+        let handle = std::thread::spawn(move || {
+            let mut conditional = Vec::new();
+            let mut responses = responses.into_iter().peekable();
+            while let Some(response) = responses.peek() {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut sent_etag = false;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    sent_etag |= line
+                        .to_ascii_lowercase()
+                        .starts_with("if-none-match: \"abc\"");
+                }
+                // The index is unsigned.
+                if request_line.contains(SIGNATURE_SUFFIX) {
+                    stream.write_all(NOT_FOUND.as_bytes()).unwrap();
+                    continue;
+                }
+                stream.write_all(response.as_bytes()).unwrap();
+                responses.next();
+                conditional.push(sent_etag);
+            }
+            conditional
+        });
+
+        for _ in 0..2 {
+            let Loaded {
+                servers, origin, ..
+            } = load_registry_servers(
+                None,
+                Some(&cache),
+                std::slice::from_ref(&url),
+                KeyPins::default(),
+            )
+            .unwrap();
+            assert_eq!(origin, url);
+            assert_eq!(servers[0].name, "remote-server");
+        }
+        assert_eq!(handle.join().unwrap(), vec![false, true]);
+        assert_eq!(fs::read_to_string(etag_path(&cache)).unwrap(), "\"abc\"");
+    }
+
+    #[cfg(feature = "http-client")]
+    #[test]
+    fn registry_url_fails_over_to_next_mirror_and_records_health() {
+        use std::io::{BufRead, BufReader, Write};
+
+        let temp = tempfile::tempdir().unwrap();
+        let cache = temp.path().join("cache/index.json");
+        let index = serde_json::to_string(&load_seed_registry()[..1]).unwrap();
+
+        let closed = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let down = format!("http://{}/index.json", closed.local_addr().unwrap());
+        drop(closed);
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let up = format!("http://{}/index.json", listener.local_addr().unwrap());
+        let handle = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{index}",
+                index.len()
+            );
+            stream.write_all(response.as_bytes()).unwrap();
+        });
+
+        let Loaded {
+            servers, origin, ..
+        } = load_registry_servers(
+            None,
+            Some(&cache),
+            &[down.clone(), up.clone()],
+            KeyPins::default(),
+        )
+        .unwrap();
+        handle.join().unwrap();
+        assert_eq!(origin, up);
+        assert_eq!(servers.len(), 1);
+
+        let health = MirrorHealth::load(&cache.with_file_name(MIRROR_HEALTH_FILE));
+        assert_eq!(health.cached_from.as_deref(), Some(up.as_str()));
+        assert_eq!(health.mirrors[&down].consecutive_failures, 1);
+        assert_eq!(health.rank(&[down.clone(), up.clone()]), vec![up, down]);
+    }
+
+    #[cfg(feature = "http-client")]
+    #[test]
+    fn registry_url_pins_signing_key_and_rejects_a_different_one() {
+        use std::io::{BufRead, BufReader, Write};
+
+        let temp = tempfile::tempdir().unwrap();
+        let cache = temp.path().join("cache/index.json");
+        let keys = temp.path().join(signing::PINNED_KEYS_FILE);
+        let index = serde_json::to_string(&load_seed_registry()[..1]).unwrap();
+        let signatures: Vec<String> = [[1u8; 32], [2u8; 32], [2u8; 32]]
+            .iter()
+            .map(|secret| {
+                serde_json::to_string(&signing::sign_index(index.as_bytes(), secret)).unwrap()
+            })
+            .collect();
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/index.json", listener.local_addr().unwrap());
+        let served = index.clone();
+        let handle = std::thread::spawn(move || {
+            for signature in signatures {
+                for _ in 0..2 {
+                    let (mut stream, _) = listener.accept().unwrap();
+                    let mut reader = BufReader::new(stream.try_clone().unwrap());
+                    let mut request_line = String::new();
+                    reader.read_line(&mut request_line).unwrap();
+                    let mut line = String::new();
+                    while reader.read_line(&mut line).unwrap() > 2 {
+                        line.clear();
+                    }
+                    let body = if request_line.contains(SIGNATURE_SUFFIX) {
+                        &signature
+                    } else {
+                        &served
+                    };
+                    let response = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                        body.len()
+                    );
+                    stream.write_all(response.as_bytes()).unwrap();
+                }
+            }
+        });
+        let urls = std::slice::from_ref(&url);
+        let pins = KeyPins {
+            path: Some(&keys),
+            accept_new_key: false,
+        };
+
+        let first = load_registry_servers(None, Some(&cache), urls, pins).unwrap();
+        assert!(matches!(
+            first.trust,
+            IndexTrust::NewlyPinned { previous: None, .. }
+        ));
+        let pinned = PinnedKeys::load(&keys).unwrap();
+        assert!(pinned.keys.contains_key(&url));
+
+        let Err(LoadError::Untrusted(reason)) =
+            load_registry_servers(None, Some(&cache), urls, pins)
+        else {
+            panic!("an index signed by another key must be refused");
+        };
+        assert!(reason.contains("--accept-new-key"), "{reason}");
+        assert_eq!(PinnedKeys::load(&keys).unwrap(), pinned);
+
+        let accepted = KeyPins {
+            accept_new_key: true,
+            ..pins
+        };
+        let rotated = load_registry_servers(None, Some(&cache), urls, accepted).unwrap();
+        handle.join().unwrap();
+        assert!(matches!(
+            rotated.trust,
+            IndexTrust::NewlyPinned {
+                previous: Some(_),
+                ..
+            }
+        ));
+        assert_ne!(PinnedKeys::load(&keys).unwrap(), pinned);
+    }
+
     #[test]
     fn registry_loads_from_cache_when_available() {
         let temp = tempfile::tempdir().unwrap();
@@ -206,7 +707,10 @@ mod tests {
         server.display_name = "Cached Server".to_string();
         write_cache(&cache, &serde_json::to_string(&vec![server]).unwrap()).unwrap();
 
-        let servers = load_registry_servers(None, Some(&cache), None).unwrap();
+        let Loaded {
+            servers, origin, ..
+        } = load_registry_servers(None, Some(&cache), &[], KeyPins::default()).unwrap();
+        assert!(origin.starts_with("cache "));
         assert_eq!(servers.len(), 1);
         assert_eq!(servers[0].name, "cached-server");
     }
@@ -0,0 +1,326 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Fetching one index from several mirrors with health-ranked failover.
+//!
+//! `BERTH_REGISTRY_INDEX_URL` may list several mirrors of the same index.
+//! They are tried healthiest first; a mirror that fails hands over at once,
+//! and one that has not answered within [`HEDGE_DELAY`] gets the next mirror
+//! started alongside it, so one slow endpoint costs seconds rather than the
+//! full request timeout. Outcomes are remembered in [`MIRROR_HEALTH_FILE`]
+//! next to the index cache and decide the order on the next run.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::http::Fetched;
+
+/// File name of the mirror health record, kept beside the index cache.
+pub const MIRROR_HEALTH_FILE: &str = "mirrors.json";
+
+/// How long a mirror may stay silent before the next one is started too.
+pub const HEDGE_DELAY: Duration = Duration::from_secs(2);
+
+/// Remembered outcomes of one mirror.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MirrorStats {
+    pub successes: u64,
+    pub failures: u64,
+    /// Failures since the last success; the primary ranking key.
+    pub consecutive_failures: u32,
+    /// Smoothed response time of recent requests.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+impl MirrorStats {
+    fn observe_latency(&mut self, elapsed: Duration) {
+        let sample = elapsed.as_millis().min(u128::from(u64::MAX)) as u64;
+        self.latency_ms = Some(match self.latency_ms {
+            Some(previous) => (previous.saturating_mul(3).saturating_add(sample)) / 4,
+            None => sample,
+        });
+    }
+}
+
+/// Health of every mirror seen so far and which one filled the cache.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MirrorHealth {
+    /// Mirror whose response is in the index cache; only it is sent the
+    /// cached entity tag.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cached_from: Option<String>,
+    #[serde(default)]
+    pub mirrors: BTreeMap<String, MirrorStats>,
+}
+
+impl MirrorHealth {
+    /// Reads the record at `path`; a missing or unreadable file starts fresh.
+    pub fn load(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Writes the record to `path`.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let serialized = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("failed serializing mirror health: {e}"))?;
+        fs::write(path, serialized)
+            .map_err(|e| format!("failed writing mirror health {}: {e}", path.display()))
+    }
+
+    /// Orders `urls` healthiest first: fewest consecutive failures, then
+    /// lowest latency; mirrors without history keep their configured order
+    /// after the proven ones.
+    pub fn rank(&self, urls: &[String]) -> Vec<String> {
+        let mut ranked = urls.to_vec();
+        ranked.sort_by_key(|url| match self.mirrors.get(url) {
+            Some(stats) => (
+                stats.consecutive_failures,
+                stats.latency_ms.unwrap_or(u64::MAX),
+            ),
+            None => (0, u64::MAX),
+        });
+        ranked
+    }
+
+    fn record_success(&mut self, url: &str, elapsed: Duration) {
+        let stats = self.mirrors.entry(url.to_string()).or_default();
+        stats.successes += 1;
+        stats.consecutive_failures = 0;
+        stats.last_error = None;
+        stats.observe_latency(elapsed);
+    }
+
+    fn record_failure(&mut self, url: &str, error: &str) {
+        let stats = self.mirrors.entry(url.to_string()).or_default();
+        stats.failures += 1;
+        stats.consecutive_failures = stats.consecutive_failures.saturating_add(1);
+        stats.last_error = Some(error.to_string());
+    }
+
+    /// Counts a mirror that was still silent when another one answered; the
+    /// elapsed time is a lower bound of its latency.
+    fn record_pending(&mut self, url: &str, elapsed: Duration) {
+        self.mirrors
+            .entry(url.to_string())
+            .or_default()
+            .observe_latency(elapsed);
+    }
+}
+
+/// Splits a `BERTH_REGISTRY_INDEX_URL` value into mirror URLs.
+///
+/// Entries are separated by commas or whitespace; duplicates are dropped.
+pub fn parse_index_urls(value: &str) -> Vec<String> {
+    let mut urls: Vec<String> = Vec::new();
+    for url in value.split(|c: char| c == ',' || c.is_whitespace()) {
+        if !url.is_empty() && !urls.iter().any(|u| u == url) {
+            urls.push(url.to_string());
+        }
+    }
+    urls
+}
+
+/// Fetches the index from the healthiest answering mirror.
+///
+/// `etag` is sent only to the mirror recorded in `health` as the source of
+/// the cache. Returns the winning mirror with its response and updates
+/// `health` with every outcome observed on the way.
+pub fn fetch_from_mirrors<F>(
+    urls: &[String],
+    etag: Option<&str>,
+    health: &mut MirrorHealth,
+    hedge_delay: Duration,
+    fetch: F,
+) -> Result<(String, Fetched), String>
+where
+    F: Fn(&str, Option<&str>) -> Result<Fetched, String> + Send + Sync + Clone + 'static,
+{
+    let ranked = health.rank(urls);
+    if ranked.is_empty() {
+        return Err("no registry index URL configured".to_string());
+    }
+
+    let (tx, rx) = mpsc::channel();
+    let mut pending: Vec<(String, Instant)> = Vec::new();
+    let mut next = ranked.into_iter();
+    let mut errors = Vec::new();
+    let cached_from = health.cached_from.clone();
+
+    let mut start_next = |pending: &mut Vec<(String, Instant)>| {
+        let Some(url) = next.next() else {
+            return;
+        };
+        let sent_etag = etag
+            .filter(|_| cached_from.as_deref().is_none_or(|from| from == url))
+            .map(str::to_string);
+        let tx = tx.clone();
+        let fetch = fetch.clone();
+        let thread_url = url.clone();
+        thread::spawn(move || {
+            let result = fetch(&thread_url, sent_etag.as_deref());
+            let _ = tx.send((thread_url, result));
+        });
+        tracing::debug!(url, "fetching registry index from mirror");
+        pending.push((url, Instant::now()));
+    };
+
+    start_next(&mut pending);
+    while !pending.is_empty() {
+        let (url, result) = match rx.recv_timeout(hedge_delay) {
+            Ok(received) => received,
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                start_next(&mut pending);
+                continue;
+            }
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        };
+        let Some(position) = pending.iter().position(|(u, _)| *u == url) else {
+            continue;
+        };
+        let (_, started) = pending.swap_remove(position);
+        match result {
+            Ok(fetched) => {
+                health.record_success(&url, started.elapsed());
+                for (slow, started) in &pending {
+                    health.record_pending(slow, started.elapsed());
+                }
+                return Ok((url, fetched));
+            }
+            Err(error) => {
+                tracing::debug!(url, %error, "registry mirror failed");
+                health.record_failure(&url, &error);
+                errors.push(error);
+                start_next(&mut pending);
+            }
+        }
+    }
+
+    Err(errors.join("; "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    fn body(text: &str) -> Fetched {
+        Fetched::Body {
+            body: text.to_string(),
+            etag: None,
+        }
+    }
+
+    fn urls(list: &[&str]) -> Vec<String> {
+        list.iter().map(|u| u.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_comma_and_space_separated_urls() {
+        assert_eq!(
+            parse_index_urls("https://a/index.json, https://b/index.json https://a/index.json"),
+            urls(&["https://a/index.json", "https://b/index.json"])
+        );
+    }
+
+    #[test]
+    fn failing_mirror_fails_over_and_is_ranked_last() {
+        let mut health = MirrorHealth::default();
+        let mirrors = urls(&["bad", "good"]);
+        let (winner, fetched) =
+            fetch_from_mirrors(&mirrors, None, &mut health, HEDGE_DELAY, |url, _| {
+                if url == "bad" {
+                    Err("connection refused".to_string())
+                } else {
+                    Ok(body("[]"))
+                }
+            })
+            .unwrap();
+        assert_eq!(winner, "good");
+        assert_eq!(fetched, body("[]"));
+        assert_eq!(health.mirrors["bad"].consecutive_failures, 1);
+        assert_eq!(health.mirrors["good"].successes, 1);
+        assert_eq!(health.rank(&mirrors), urls(&["good", "bad"]));
+    }
+
+    #[test]
+    fn slow_mirror_is_hedged_and_remembered() {
+        let mut health = MirrorHealth::default();
+        let mirrors = urls(&["slow", "fast"]);
+        let (winner, _) = fetch_from_mirrors(
+            &mirrors,
+            None,
+            &mut health,
+            Duration::from_millis(20),
+            |url, _| {
+                if url == "slow" {
+                    thread::sleep(Duration::from_millis(500));
+                }
+                Ok(body(url))
+            },
+        )
+        .unwrap();
+        assert_eq!(winner, "fast");
+        assert!(health.mirrors["slow"].latency_ms.unwrap() >= 20);
+        assert_eq!(health.rank(&mirrors), urls(&["fast", "slow"]));
+    }
+
+    #[test]
+    fn etag_goes_only_to_the_mirror_that_filled_the_cache() {
+        let mut health = MirrorHealth {
+            cached_from: Some("b".to_string()),
+            ..MirrorHealth::default()
+        };
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let record = Arc::clone(&seen);
+        let err = fetch_from_mirrors(
+            &urls(&["a", "b"]),
+            Some("\"v1\""),
+            &mut health,
+            HEDGE_DELAY,
+            move |url, etag| {
+                record
+                    .lock()
+                    .unwrap()
+                    .push((url.to_string(), etag.map(str::to_string)));
+                Err(format!("{url} down"))
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, "a down; b down");
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec![
+                ("a".to_string(), None),
+                ("b".to_string(), Some("\"v1\"".to_string()))
+            ]
+        );
+    }
+
+    #[test]
+    fn health_round_trips_through_file() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join(MIRROR_HEALTH_FILE);
+        let mut health = MirrorHealth::default();
+        health.record_success("a", Duration::from_millis(40));
+        health.record_failure("b", "timed out");
+        health.save(&path).unwrap();
+        assert_eq!(MirrorHealth::load(&path), health);
+        assert_eq!(
+            MirrorHealth::load(&tmp.path().join("missing.json")),
+            MirrorHealth::default()
+        );
+    }
+}
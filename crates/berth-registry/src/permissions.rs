@@ -0,0 +1,809 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Typed permission models shared by the registry and the CLI.
+//!
+//! Server metadata declares permissions per kind without a prefix
+//! (`"api.github.com:443"`, `"read:/workspace"`); user overrides carry one
+//! (`"network:api.github.com:443"`). Each model parses the unprefixed value,
+//! and [`Permission`] parses the prefixed form.
+//!
+//! Matching semantics:
+//! - network hosts are exact names or IP literals, `*` for any host,
+//!   `*.example.com` for any subdomain (not `example.com` itself), or CIDR
+//!   ranges such as `10.0.0.0/8` and `[fd00::/8]` that match IP literals only;
+//!   a port of `*` matches any port
+//! - filesystem paths without glob characters cover the path and everything
+//!   beneath it; `*` and `?` match within one path segment and `**` matches any
+//!   number of segments; a leading `~` is the user's home directory
+//! - write access implies read access
+//! - revocations win over grants: a request is permitted only when some grant
+//!   matches it and no revocation does (see [`permits`])
+
+use std::fmt;
+use std::net::IpAddr;
+use std::str::FromStr;
+
+/// Host side of a network permission.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum HostPattern {
+    /// `*`: any host.
+    Any,
+    /// One lowercased host name or IP address.
+    Exact(String),
+    /// `*.example.com`: any subdomain of the stored suffix.
+    Subdomains(String),
+    /// `10.0.0.0/8`: any IP literal inside the range.
+    Cidr { network: IpAddr, prefix_len: u8 },
+}
+
+/// Outbound network access to a host and port.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NetworkPermission {
+    pub host: HostPattern,
+    /// Port number; `None` means any port.
+    pub port: Option<u16>,
+}
+
+/// Access mode of a filesystem permission.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FsMode {
+    Read,
+    Write,
+}
+
+/// Filesystem access to a path.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FsPermission {
+    pub mode: FsMode,
+    /// Path as declared, or `*` for any path.
+    pub path: String,
+}
+
+/// Permission to spawn a command.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ExecPermission {
+    /// Command name, or `*` for any command.
+    pub command: String,
+}
+
+/// Permission to receive an environment variable.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EnvPermission {
+    /// Variable name, or `*` for the full environment.
+    pub name: String,
+}
+
+/// One prefixed permission such as `network:api.github.com:443`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Permission {
+    Network(NetworkPermission),
+    Filesystem(FsPermission),
+    Exec(ExecPermission),
+    Env(EnvPermission),
+}
+
+impl HostPattern {
+    /// Returns whether a concrete host name or IP literal matches.
+    pub fn matches(&self, host: &str) -> bool {
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        match self {
+            HostPattern::Any => true,
+            HostPattern::Exact(exact) => exact.eq_ignore_ascii_case(host),
+            HostPattern::Subdomains(suffix) => is_subdomain_of(host, suffix),
+            HostPattern::Cidr {
+                network,
+                prefix_len,
+            } => host
+                .parse::<IpAddr>()
+                .is_ok_and(|ip| cidr_contains(*network, *prefix_len, ip)),
+        }
+    }
+
+    /// Returns whether every host matched by `other` is matched by `self`.
+    pub fn covers(&self, other: &HostPattern) -> bool {
+        match (self, other) {
+            (HostPattern::Any, _) => true,
+            (_, HostPattern::Any) => false,
+            (_, HostPattern::Exact(host)) => self.matches(host),
+            (HostPattern::Subdomains(ours), HostPattern::Subdomains(theirs)) => {
+                ours == theirs || is_subdomain_of(theirs, ours)
+            }
+            (
+                HostPattern::Cidr {
+                    network,
+                    prefix_len,
+                },
+                HostPattern::Cidr {
+                    network: inner,
+                    prefix_len: inner_len,
+                },
+            ) => inner_len >= prefix_len && cidr_contains(*network, *prefix_len, *inner),
+            _ => false,
+        }
+    }
+}
+
+impl NetworkPermission {
+    /// Returns whether this grants any host on any port.
+    pub fn is_wildcard(&self) -> bool {
+        self.host == HostPattern::Any && self.port.is_none()
+    }
+
+    /// Returns whether this spans more than one host or port.
+    pub fn is_broad(&self) -> bool {
+        !matches!(self.host, HostPattern::Exact(_)) || self.port.is_none()
+    }
+
+    /// Returns whether a connection to `host:port` is covered.
+    pub fn matches(&self, host: &str, port: u16) -> bool {
+        self.port.is_none_or(|p| p == port) && self.host.matches(host)
+    }
+
+    /// Returns whether every connection `other` allows is also allowed by `self`.
+    pub fn covers(&self, other: &NetworkPermission) -> bool {
+        let port_covered = match (self.port, other.port) {
+            (None, _) => true,
+            (Some(ours), Some(theirs)) => ours == theirs,
+            (Some(_), None) => false,
+        };
+        port_covered && self.host.covers(&other.host)
+    }
+}
+
+impl FsPermission {
+    /// Returns whether this grants access to any path.
+    pub fn is_wildcard(&self) -> bool {
+        self.path == "*"
+    }
+
+    /// Returns whether this grants write access.
+    pub fn allows_write(&self) -> bool {
+        self.mode == FsMode::Write
+    }
+
+    /// Returns whether the path contains glob characters.
+    pub fn has_glob(&self) -> bool {
+        !self.is_wildcard() && self.path.contains(['*', '?'])
+    }
+
+    /// Returns whether this spans unknown paths or is a glob.
+    pub fn is_broad(&self) -> bool {
+        self.is_wildcard() || self.has_glob()
+    }
+
+    /// Returns the literal leading directories before the first glob segment.
+    ///
+    /// Sandboxes that only understand concrete paths grant this directory.
+    pub fn base_path(&self) -> String {
+        if !self.has_glob() {
+            return self.path.clone();
+        }
+        let mut base = String::new();
+        for (i, segment) in self.path.split(['/', '\\']).enumerate() {
+            if segment.contains(['*', '?']) {
+                break;
+            }
+            if i > 0 {
+                base.push('/');
+            }
+            base.push_str(segment);
+        }
+        if base.is_empty() {
+            "/".to_string()
+        } else {
+            base
+        }
+    }
+
+    /// Replaces a leading `~` with `home`.
+    pub fn expand_home(&self, home: &str) -> FsPermission {
+        let path = match self.path.strip_prefix('~') {
+            Some(rest) if rest.is_empty() || rest.starts_with(['/', '\\']) => {
+                format!("{}{rest}", home.trim_end_matches(['/', '\\']))
+            }
+            _ => self.path.clone(),
+        };
+        FsPermission {
+            mode: self.mode,
+            path,
+        }
+    }
+
+    /// Returns whether access to `path` in `mode` is covered.
+    pub fn matches(&self, mode: FsMode, path: &str) -> bool {
+        mode <= self.mode && (self.is_wildcard() || path_matches(&self.path, path))
+    }
+
+    /// Returns whether every access `other` allows is also allowed by `self`.
+    pub fn covers(&self, other: &FsPermission) -> bool {
+        if other.mode > self.mode {
+            return false;
+        }
+        if self.is_wildcard() || self.path == other.path {
+            return true;
+        }
+        if other.is_wildcard() {
+            return false;
+        }
+        if !other.has_glob() {
+            return path_matches(&self.path, &other.path);
+        }
+        // A glob is only known to be covered by an ancestor literal or `**` tree.
+        let base = self.path.strip_suffix("/**").unwrap_or(&self.path);
+        !base.contains(['*', '?']) && path_matches(base, &other.base_path())
+    }
+}
+
+impl ExecPermission {
+    /// Returns whether this grants any command.
+    pub fn is_wildcard(&self) -> bool {
+        self.command == "*"
+    }
+}
+
+impl EnvPermission {
+    /// Returns whether this grants the full environment.
+    pub fn is_wildcard(&self) -> bool {
+        self.name == "*"
+    }
+}
+
+impl Permission {
+    /// Returns the prefix naming this permission's kind.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Permission::Network(_) => "network",
+            Permission::Filesystem(_) => "filesystem",
+            Permission::Exec(_) => "exec",
+            Permission::Env(_) => "env",
+        }
+    }
+
+    /// Returns whether every access `other` allows is also allowed by `self`.
+    ///
+    /// Permissions of different kinds never cover each other.
+    pub fn covers(&self, other: &Permission) -> bool {
+        match (self, other) {
+            (Permission::Network(ours), Permission::Network(theirs)) => ours.covers(theirs),
+            (Permission::Filesystem(ours), Permission::Filesystem(theirs)) => ours.covers(theirs),
+            (Permission::Exec(ours), Permission::Exec(theirs)) => {
+                ours.is_wildcard() || ours == theirs
+            }
+            (Permission::Env(ours), Permission::Env(theirs)) => {
+                ours.is_wildcard() || ours == theirs
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Applies allow/deny precedence to one requested access.
+///
+/// The request is permitted when a grant covers it and no revocation
+/// overlaps it: a narrow revocation inside a broad grant still denies.
+pub fn permits(granted: &[Permission], revoked: &[Permission], request: &Permission) -> bool {
+    granted.iter().any(|grant| grant.covers(request))
+        && !revoked
+            .iter()
+            .any(|revoke| revoke.covers(request) || request.covers(revoke))
+}
+
+/// Returns whether any declared filesystem entry may grant write access.
+///
+/// Entries that fail to parse count as writes so guards fail closed.
+pub fn grants_filesystem_write(entries: &[String]) -> bool {
+    entries.iter().any(|entry| {
+        entry
+            .trim()
+            .parse::<FsPermission>()
+            .map_or(true, |permission| permission.allows_write())
+    })
+}
+
+impl FromStr for NetworkPermission {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, String> {
+        let original = format!("network:{value}");
+        if value == "*" {
+            return Ok(NetworkPermission {
+                host: HostPattern::Any,
+                port: None,
+            });
+        }
+        let split = match value.strip_prefix('[') {
+            Some(rest) => rest.split_once("]:").map(|(host, port)| (host, port, true)),
+            None => value
+                .split_once(':')
+                .map(|(host, port)| (host, port, false)),
+        };
+        let (host, port, bracketed) = split.ok_or_else(|| {
+            format!(
+                "Invalid permission format `{original}`. Network permissions must be `network:<host>:<port>`."
+            )
+        })?;
+        if host.is_empty() {
+            return Err(format!(
+                "Invalid permission format `{original}`. Host is required."
+            ));
+        }
+        let host = parse_host_pattern(host, bracketed, &original)?;
+        let port = match port {
+            "*" => None,
+            _ => match port.parse::<u16>() {
+                Ok(parsed) if parsed > 0 => Some(parsed),
+                _ => {
+                    return Err(format!(
+                        "Invalid permission format `{original}`. Port must be `*` or 1-65535."
+                    ))
+                }
+            },
+        };
+        Ok(NetworkPermission { host, port })
+    }
+}
+
+fn parse_host_pattern(host: &str, bracketed: bool, original: &str) -> Result<HostPattern, String> {
+    if host == "*" {
+        return Ok(HostPattern::Any);
+    }
+    if let Some((addr, prefix_len)) = host.split_once('/') {
+        let network: IpAddr = addr.parse().ok().filter(|ip: &IpAddr| ip.is_ipv6() == bracketed).ok_or_else(|| {
+            format!(
+                "Invalid permission format `{original}`. CIDR ranges must look like `10.0.0.0/8` or `[fd00::/8]`."
+            )
+        })?;
+        let max_len = if network.is_ipv4() { 32 } else { 128 };
+        let prefix_len = prefix_len
+            .parse::<u8>()
+            .ok()
+            .filter(|len| *len <= max_len)
+            .ok_or_else(|| {
+                format!(
+                    "Invalid permission format `{original}`. CIDR prefix length must be 0-{max_len}."
+                )
+            })?;
+        return Ok(HostPattern::Cidr {
+            network: mask_ip(network, prefix_len),
+            prefix_len,
+        });
+    }
+    if bracketed {
+        return match host.parse::<IpAddr>() {
+            Ok(IpAddr::V6(ip)) => Ok(HostPattern::Exact(ip.to_string())),
+            _ => Err(format!(
+                "Invalid permission format `{original}`. Brackets may only wrap IPv6 addresses."
+            )),
+        };
+    }
+    let (suffix, subdomains) = match host.strip_prefix("*.") {
+        Some(suffix) => (suffix, true),
+        None => (host, false),
+    };
+    if suffix.contains('*') {
+        return Err(format!(
+            "Invalid permission format `{original}`. Host wildcards must be `*` or a leading `*.` label."
+        ));
+    }
+    if suffix.is_empty()
+        || !suffix
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-')
+    {
+        return Err(format!(
+            "Invalid permission format `{original}`. Host may contain only letters, digits, `.`, or `-`."
+        ));
+    }
+    let suffix = suffix.to_ascii_lowercase();
+    Ok(if subdomains {
+        HostPattern::Subdomains(suffix)
+    } else {
+        HostPattern::Exact(suffix)
+    })
+}
+
+fn is_subdomain_of(host: &str, suffix: &str) -> bool {
+    host.len() > suffix.len() + 1
+        && host[host.len() - suffix.len()..].eq_ignore_ascii_case(suffix)
+        && host.as_bytes()[host.len() - suffix.len() - 1] == b'.'
+}
+
+fn mask_ip(ip: IpAddr, prefix_len: u8) -> IpAddr {
+    match ip {
+        IpAddr::V4(v4) => {
+            let mask = u32::MAX
+                .checked_shl(32 - u32::from(prefix_len))
+                .unwrap_or(0);
+            IpAddr::V4((u32::from(v4) & mask).into())
+        }
+        IpAddr::V6(v6) => {
+            let mask = u128::MAX
+                .checked_shl(128 - u32::from(prefix_len))
+                .unwrap_or(0);
+            IpAddr::V6((u128::from(v6) & mask).into())
+        }
+    }
+}
+
+fn cidr_contains(network: IpAddr, prefix_len: u8, ip: IpAddr) -> bool {
+    network.is_ipv4() == ip.is_ipv4() && mask_ip(ip, prefix_len) == network
+}
+
+/// Matches a concrete path against a literal directory or glob pattern.
+fn path_matches(pattern: &str, path: &str) -> bool {
+    let split = |raw: &str| -> Vec<String> {
+        raw.split(['/', '\\'])
+            .filter(|segment| !segment.is_empty())
+            .map(str::to_string)
+            .collect()
+    };
+    let pattern_segments = split(pattern);
+    let path_segments = split(path);
+    if !pattern.contains(['*', '?']) {
+        return path_segments.starts_with(&pattern_segments);
+    }
+    segments_match(&pattern_segments, &path_segments)
+}
+
+fn segments_match(pattern: &[String], path: &[String]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((first, rest)) if first == "**" => {
+            (0..=path.len()).any(|skip| segments_match(rest, &path[skip..]))
+        }
+        Some((first, rest)) => path.split_first().is_some_and(|(segment, path_rest)| {
+            segment_matches(first.as_bytes(), segment.as_bytes()) && segments_match(rest, path_rest)
+        }),
+    }
+}
+
+fn segment_matches(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => text.is_empty(),
+        Some((b'*', rest)) => (0..=text.len()).any(|skip| segment_matches(rest, &text[skip..])),
+        Some((b'?', rest)) => !text.is_empty() && segment_matches(rest, &text[1..]),
+        Some((c, rest)) => text.first() == Some(c) && segment_matches(rest, &text[1..]),
+    }
+}
+
+impl FromStr for FsPermission {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, String> {
+        let original = format!("filesystem:{value}");
+        if value == "*" {
+            return Ok(FsPermission {
+                mode: FsMode::Write,
+                path: "*".to_string(),
+            });
+        }
+        let (mode, path) = value.split_once(':').ok_or_else(|| {
+            format!(
+                "Invalid permission format `{original}`. Filesystem permissions must be `filesystem:<read|write>:<path>`."
+            )
+        })?;
+        let mode = match mode {
+            "read" => FsMode::Read,
+            "write" => FsMode::Write,
+            _ => {
+                return Err(format!(
+                    "Invalid permission format `{original}`. Mode must be `read` or `write`."
+                ))
+            }
+        };
+        if path.trim().is_empty() {
+            return Err(format!(
+                "Invalid permission format `{original}`. Filesystem path is required."
+            ));
+        }
+        Ok(FsPermission {
+            mode,
+            path: path.to_string(),
+        })
+    }
+}
+
+impl FromStr for ExecPermission {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, String> {
+        let original = format!("exec:{value}");
+        if value.trim().is_empty() {
+            return Err(format!(
+                "Invalid permission format `{original}`. Exec command is required."
+            ));
+        }
+        if value.contains(char::is_whitespace) {
+            return Err(format!(
+                "Invalid permission format `{original}`. Exec command must not contain whitespace."
+            ));
+        }
+        Ok(ExecPermission {
+            command: value.to_string(),
+        })
+    }
+}
+
+impl FromStr for EnvPermission {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, String> {
+        let original = format!("env:{value}");
+        if value == "*" {
+            return Ok(EnvPermission {
+                name: value.to_string(),
+            });
+        }
+        let mut chars = value.chars();
+        let Some(first) = chars.next() else {
+            return Err(format!(
+                "Invalid permission format `{original}`. Environment variable name is required."
+            ));
+        };
+        if !(first.is_ascii_uppercase() || first == '_')
+            || !chars.all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+        {
+            return Err(format!(
+                "Invalid permission format `{original}`. Env vars must match `[A-Z_][A-Z0-9_]*`."
+            ));
+        }
+        Ok(EnvPermission {
+            name: value.to_string(),
+        })
+    }
+}
+
+impl FromStr for Permission {
+    type Err = String;
+
+    fn from_str(permission: &str) -> Result<Self, String> {
+        if let Some(value) = permission.strip_prefix("env:") {
+            return value.parse().map(Permission::Env);
+        }
+        if let Some(value) = permission.strip_prefix("network:") {
+            return value.parse().map(Permission::Network);
+        }
+        if let Some(value) = permission.strip_prefix("filesystem:") {
+            return value.parse().map(Permission::Filesystem);
+        }
+        if let Some(value) = permission.strip_prefix("exec:") {
+            return value.parse().map(Permission::Exec);
+        }
+        Err(format!(
+            "Invalid permission format `{permission}`. Use `env:<VAR>`, `env:*`, `network:<host>:<port>`, `network:*`, `filesystem:<read|write>:<path>`, `filesystem:*`, `exec:<command>`, or `exec:*`."
+        ))
+    }
+}
+
+impl fmt::Display for HostPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HostPattern::Any => f.write_str("*"),
+            HostPattern::Exact(host) if host.contains(':') => write!(f, "[{host}]"),
+            HostPattern::Exact(host) => f.write_str(host),
+            HostPattern::Subdomains(suffix) => write!(f, "*.{suffix}"),
+            HostPattern::Cidr {
+                network: IpAddr::V4(ip),
+                prefix_len,
+            } => write!(f, "{ip}/{prefix_len}"),
+            HostPattern::Cidr {
+                network: IpAddr::V6(ip),
+                prefix_len,
+            } => write!(f, "[{ip}/{prefix_len}]"),
+        }
+    }
+}
+
+impl fmt::Display for NetworkPermission {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.port {
+            _ if self.is_wildcard() => f.write_str("*"),
+            Some(port) => write!(f, "{}:{port}", self.host),
+            None => write!(f, "{}:*", self.host),
+        }
+    }
+}
+
+impl fmt::Display for FsMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            FsMode::Read => "read",
+            FsMode::Write => "write",
+        })
+    }
+}
+
+impl fmt::Display for FsPermission {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_wildcard() && self.allows_write() {
+            return f.write_str("*");
+        }
+        write!(f, "{}:{}", self.mode, self.path)
+    }
+}
+
+impl fmt::Display for ExecPermission {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.command)
+    }
+}
+
+impl fmt::Display for EnvPermission {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+impl fmt::Display for Permission {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Permission::Network(p) => write!(f, "network:{p}"),
+            Permission::Filesystem(p) => write!(f, "filesystem:{p}"),
+            Permission::Exec(p) => write!(f, "exec:{p}"),
+            Permission::Env(p) => write!(f, "env:{p}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn network_permission_parses_host_and_port() {
+        let parsed: NetworkPermission = "API.GitHub.com:443".parse().unwrap();
+        assert_eq!(
+            parsed.host,
+            HostPattern::Exact("api.github.com".to_string())
+        );
+        assert_eq!(parsed.port, Some(443));
+        assert_eq!(parsed.to_string(), "api.github.com:443");
+
+        let any_port: NetworkPermission = "example.com:*".parse().unwrap();
+        assert_eq!(any_port.port, None);
+        assert!(!any_port.is_wildcard());
+        assert!("*".parse::<NetworkPermission>().unwrap().is_wildcard());
+
+        let err = "example.com:0".parse::<NetworkPermission>().unwrap_err();
+        assert!(err.contains("`network:example.com:0`"));
+        assert!("example.com".parse::<NetworkPermission>().is_err());
+        assert!("exa mple.com:443".parse::<NetworkPermission>().is_err());
+    }
+
+    #[test]
+    fn fs_permission_parses_mode_and_path() {
+        let parsed: FsPermission = "read:/workspace".parse().unwrap();
+        assert_eq!(parsed.mode, FsMode::Read);
+        assert_eq!(parsed.path, "/workspace");
+        assert!(!parsed.allows_write());
+
+        let windows: FsPermission = "write:C:\\Users\\dev".parse().unwrap();
+        assert_eq!(windows.path, "C:\\Users\\dev");
+
+        let wildcard: FsPermission = "*".parse().unwrap();
+        assert!(wildcard.is_wildcard() && wildcard.allows_write());
+        assert_eq!(wildcard.to_string(), "*");
+
+        assert!("run:/tmp".parse::<FsPermission>().is_err());
+        assert!("write: ".parse::<FsPermission>().is_err());
+    }
+
+    #[test]
+    fn permission_round_trips_prefixed_strings() {
+        for raw in [
+            "env:GITHUB_TOKEN",
+            "env:*",
+            "network:api.github.com:443",
+            "network:*:443",
+            "network:*",
+            "filesystem:write:/var/log",
+            "filesystem:*",
+            "exec:git",
+        ] {
+            let parsed: Permission = raw.parse().unwrap();
+            assert_eq!(parsed.to_string(), raw);
+        }
+        assert_eq!("exec:git".parse::<Permission>().unwrap().kind(), "exec");
+        assert!("shell:ls".parse::<Permission>().is_err());
+        assert!("env:lower".parse::<Permission>().is_err());
+    }
+
+    #[test]
+    fn network_wildcards_and_cidr_ranges_match_hosts() {
+        let subdomains: NetworkPermission = "*.GitHub.com:443".parse().unwrap();
+        assert_eq!(subdomains.to_string(), "*.github.com:443");
+        assert!(subdomains.matches("api.github.com", 443));
+        assert!(subdomains.matches("uploads.api.github.com", 443));
+        assert!(!subdomains.matches("github.com", 443));
+        assert!(!subdomains.matches("evilgithub.com", 443));
+        assert!(!subdomains.matches("api.github.com", 80));
+
+        let range: NetworkPermission = "10.1.2.3/8:*".parse().unwrap();
+        assert_eq!(range.to_string(), "10.0.0.0/8:*");
+        assert!(range.matches("10.200.0.1", 5432));
+        assert!(!range.matches("11.0.0.1", 5432));
+        assert!(!range.matches("internal.example", 5432));
+
+        let v6: NetworkPermission = "[fd00::/8]:443".parse().unwrap();
+        assert!(v6.matches("[fd12::1]", 443));
+        assert!(!v6.matches("10.0.0.1", 443));
+
+        assert!("api.*.com:443".parse::<NetworkPermission>().is_err());
+        assert!("10.0.0.0/33:443".parse::<NetworkPermission>().is_err());
+        assert!("fd00::/8:443".parse::<NetworkPermission>().is_err());
+    }
+
+    #[test]
+    fn network_covers_follows_host_and_port_breadth() {
+        let parse = |raw: &str| raw.parse::<NetworkPermission>().unwrap();
+        assert!(parse("*.github.com:443").covers(&parse("api.github.com:443")));
+        assert!(parse("*.github.com:*").covers(&parse("*.api.github.com:443")));
+        assert!(!parse("*.github.com:443").covers(&parse("*.github.com:*")));
+        assert!(parse("10.0.0.0/8:443").covers(&parse("10.2.0.0/16:443")));
+        assert!(!parse("10.2.0.0/16:443").covers(&parse("10.0.0.0/8:443")));
+        assert!(parse("*:443").covers(&parse("10.0.0.1:443")));
+        assert!(!parse("api.github.com:443").is_broad());
+        assert!(parse("*.github.com:443").is_broad());
+    }
+
+    #[test]
+    fn fs_globs_match_segments_and_literal_paths_cover_descendants() {
+        let parse = |raw: &str| raw.parse::<FsPermission>().unwrap();
+        let tree = parse("read:~/projects/**").expand_home("/home/dev/");
+        assert_eq!(tree.path, "/home/dev/projects/**");
+        assert_eq!(tree.base_path(), "/home/dev/projects");
+        assert!(tree.matches(FsMode::Read, "/home/dev/projects/berth/src/main.rs"));
+        assert!(!tree.matches(FsMode::Write, "/home/dev/projects/berth"));
+        assert!(!tree.matches(FsMode::Read, "/home/dev/other"));
+
+        let children = parse("write:/srv/*.log");
+        assert!(children.matches(FsMode::Read, "/srv/app.log"));
+        assert!(!children.matches(FsMode::Write, "/srv/nested/app.log"));
+
+        let literal = parse("write:/workspace");
+        assert!(literal.matches(FsMode::Write, "/workspace/src/lib.rs"));
+        assert!(!literal.matches(FsMode::Write, "/workspace-other"));
+
+        assert!(literal.covers(&parse("read:/workspace/docs/**")));
+        assert!(parse("read:/srv/**").covers(&parse("read:/srv/*.log")));
+        assert!(!parse("read:/srv/*").covers(&parse("read:/srv/**")));
+        assert!(!parse("read:/workspace").covers(&parse("write:/workspace/tmp")));
+    }
+
+    #[test]
+    fn permits_lets_revocations_win_over_overlapping_grants() {
+        let parse = |raw: &str| raw.parse::<Permission>().unwrap();
+        let granted = vec![parse("network:*.github.com:443"), parse("env:*")];
+        let revoked = vec![parse("network:uploads.github.com:443")];
+        assert!(permits(
+            &granted,
+            &revoked,
+            &parse("network:api.github.com:443")
+        ));
+        assert!(!permits(
+            &granted,
+            &revoked,
+            &parse("network:uploads.github.com:443")
+        ));
+        assert!(!permits(
+            &granted,
+            &revoked,
+            &parse("network:*.github.com:443")
+        ));
+        assert!(!permits(
+            &granted,
+            &revoked,
+            &parse("network:example.com:443")
+        ));
+        assert!(permits(&granted, &revoked, &parse("env:HOME")));
+    }
+
+    #[test]
+    fn grants_filesystem_write_fails_closed() {
+        assert!(!grants_filesystem_write(&["read:/tmp".to_string()]));
+        assert!(grants_filesystem_write(&[" write:/tmp".to_string()]));
+        assert!(grants_filesystem_write(&["*".to_string()]));
+        assert!(grants_filesystem_write(&["/tmp".to_string()]));
+    }
+}
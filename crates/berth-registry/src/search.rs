@@ -1,7 +1,10 @@
 // SPDX-License-Identifier: Apache-2.0
 // Copyright (c) 2026 Schwimmbeck Dominik
 
-//! Search and ranking helpers for registry servers.
+//! Search and ranking helpers for registry servers, and cursor pagination
+//! over ranked results.
+
+use std::cmp::Ordering;
 
 use crate::types::ServerMetadata;
 
@@ -12,7 +15,124 @@ pub struct SearchResult<'a> {
     pub score: u32,
 }
 
-/// Performs keyword search and returns descending relevance results.
+/// Position of an item in a sorted listing, handed out as an opaque
+/// continuation token.
+///
+/// A page resumes after the item with this sort key and name rather than at
+/// an offset, so servers added or removed between requests neither repeat
+/// nor skip results.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cursor {
+    /// Ordering the cursor was issued for, e.g. `relevance` or `stars:desc`.
+    pub ordering: String,
+    /// Primary sort key of the item; `0` when sorting by name only.
+    pub key: u64,
+    /// Name of the item, which breaks ties.
+    pub name: String,
+}
+
+impl Cursor {
+    /// Encodes the cursor as an opaque token.
+    pub fn encode(&self) -> String {
+        format!("v1\n{}\n{}\n{}", self.ordering, self.key, self.name)
+            .bytes()
+            .map(|b| format!("{b:02x}"))
+            .collect()
+    }
+
+    /// Decodes a token produced by [`Cursor::encode`].
+    pub fn decode(token: &str) -> Result<Self, String> {
+        let invalid = || format!("invalid cursor `{token}`");
+        let token = token.trim();
+        if !token.len().is_multiple_of(2) || !token.is_ascii() {
+            return Err(invalid());
+        }
+        let bytes = (0..token.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&token[i..i + 2], 16))
+            .collect::<Result<Vec<u8>, _>>()
+            .map_err(|_| invalid())?;
+        let text = String::from_utf8(bytes).map_err(|_| invalid())?;
+        let mut parts = text.splitn(4, '\n');
+        if parts.next() != Some("v1") {
+            return Err(invalid());
+        }
+        let (Some(ordering), Some(key), Some(name)) = (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid());
+        };
+        Ok(Cursor {
+            ordering: ordering.to_string(),
+            key: key.parse().map_err(|_| invalid())?,
+            name: name.to_string(),
+        })
+    }
+}
+
+/// One page of a sorted listing.
+#[derive(Debug)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Where the next page starts; `None` on the last page.
+    pub next_cursor: Option<Cursor>,
+}
+
+/// Returns up to `limit` items of `sorted` that come after `after`.
+///
+/// `position` gives an item's cursor and `compare` orders cursors the same
+/// way `sorted` is ordered.
+pub fn paginate<T>(
+    sorted: Vec<T>,
+    after: Option<&Cursor>,
+    limit: usize,
+    position: impl Fn(&T) -> Cursor,
+    compare: impl Fn(&Cursor, &Cursor) -> Ordering,
+) -> Page<T> {
+    let mut remaining = sorted
+        .into_iter()
+        .filter(|item| after.is_none_or(|after| compare(&position(item), after).is_gt()))
+        .peekable();
+    let items: Vec<T> = remaining.by_ref().take(limit).collect();
+    let next_cursor = match (remaining.peek(), items.last()) {
+        (Some(_), Some(last)) => Some(position(last)),
+        _ => None,
+    };
+    Page { items, next_cursor }
+}
+
+/// Ordering name of relevance-ranked search results in cursors.
+const RELEVANCE_ORDERING: &str = "relevance";
+
+/// Returns one page of [`search_servers`] results after `after`.
+pub fn search_page<'a>(
+    servers: &'a [ServerMetadata],
+    query: &str,
+    after: Option<&Cursor>,
+    limit: usize,
+) -> Result<Page<SearchResult<'a>>, String> {
+    if after.is_some_and(|after| after.ordering != RELEVANCE_ORDERING) {
+        return Err("cursor was issued for a different ordering".to_string());
+    }
+    Ok(paginate(
+        search_servers(servers, query),
+        after,
+        limit,
+        |result| Cursor {
+            ordering: RELEVANCE_ORDERING.to_string(),
+            key: u64::from(result.score),
+            name: result.server.name.clone(),
+        },
+        |left, right| {
+            right
+                .key
+                .cmp(&left.key)
+                .then_with(|| left.name.cmp(&right.name))
+        },
+    ))
+}
+
+/// Performs keyword search and returns descending relevance results, ties
+/// in name order.
 pub fn search_servers<'a>(servers: &'a [ServerMetadata], query: &str) -> Vec<SearchResult<'a>> {
     let query_lower = query.to_lowercase();
 
@@ -28,7 +148,11 @@ pub fn search_servers<'a>(servers: &'a [ServerMetadata], query: &str) -> Vec<Sea
         })
         .collect();
 
-    results.sort_by(|a, b| b.score.cmp(&a.score));
+    results.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then_with(|| a.server.name.cmp(&b.server.name))
+    });
     results
 }
 
@@ -126,6 +250,34 @@ mod tests {
         assert!(results.is_empty());
     }
 
+    #[test]
+    fn search_pages_resume_after_cursor() {
+        let servers = load_seed_registry();
+        let all = search_servers(&servers, "");
+        let first = search_page(&servers, "", None, 2).unwrap();
+        assert_eq!(first.items.len(), 2);
+        let cursor = first.next_cursor.unwrap();
+        assert_eq!(Cursor::decode(&cursor.encode()).unwrap(), cursor);
+
+        let second = search_page(&servers, "", Some(&cursor), all.len()).unwrap();
+        assert!(second.next_cursor.is_none());
+        let names: Vec<&str> = first
+            .items
+            .iter()
+            .chain(&second.items)
+            .map(|r| r.server.name.as_str())
+            .collect();
+        let expected: Vec<&str> = all.iter().map(|r| r.server.name.as_str()).collect();
+        assert_eq!(names, expected);
+
+        assert!(Cursor::decode("zz").is_err());
+        let other = Cursor {
+            ordering: "name:asc".to_string(),
+            ..cursor
+        };
+        assert!(search_page(&servers, "", Some(&other), 2).is_err());
+    }
+
     #[test]
     fn find_server_exact() {
         let servers = load_seed_registry();
@@ -7,6 +7,11 @@ use crate::types::ServerMetadata;
 
 const SEED_DATA: &str = include_str!("../data/seed_registry.json");
 
+/// Returns the raw embedded seed registry JSON.
+pub fn seed_json() -> &'static str {
+    SEED_DATA
+}
+
 /// Parses the embedded seed registry JSON into typed metadata.
 pub fn load_seed_registry() -> Vec<ServerMetadata> {
     serde_json::from_str(SEED_DATA).expect("embedded seed registry should be valid JSON")
@@ -19,7 +24,7 @@ mod tests {
     #[test]
     fn seed_registry_parses() {
         let servers = load_seed_registry();
-        assert_eq!(servers.len(), 30);
+        assert_eq!(servers.len(), 31);
     }
 
     #[test]
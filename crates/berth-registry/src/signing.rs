@@ -0,0 +1,289 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Ed25519 signatures over remote registry indexes and the keys pinned for them.
+//!
+//! A signing registry serves a detached signature next to its index at
+//! `<index url>.sig`: `{"publicKey": "<hex>", "signature": "<hex>"}` over the
+//! exact index bytes. The first signed index seen from a registry pins its key
+//! in [`PINNED_KEYS_FILE`] under every mirror URL of the registry (trust on
+//! first use), so reordering or adding mirrors keeps the pin. From then on
+//! every index must carry a valid signature by that key; a different key is
+//! only accepted after [`set_accept_new_key`], which `berth --accept-new-key`
+//! calls for the one command instead of exporting anything to child processes.
+
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use berth_common::atomic_write;
+
+/// Appended to an index URL to get its detached signature.
+pub const SIGNATURE_SUFFIX: &str = ".sig";
+/// File in the config directory mapping registries to their pinned keys.
+pub const PINNED_KEYS_FILE: &str = "registry-keys.toml";
+
+/// Whether a registry may replace its pinned key during this process.
+static ACCEPT_NEW_KEY: AtomicBool = AtomicBool::new(false);
+
+/// Detached signature document served at `<index url>.sig`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexSignature {
+    /// Hex Ed25519 public key (32 bytes).
+    pub public_key: String,
+    /// Hex Ed25519 signature over the index bytes (64 bytes).
+    pub signature: String,
+}
+
+/// Contents of [`PINNED_KEYS_FILE`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PinnedKeys {
+    /// Hex public key per registry, keyed by each of its index URLs.
+    #[serde(default)]
+    pub keys: BTreeMap<String, String>,
+}
+
+impl PinnedKeys {
+    /// Reads the pins at `path`; a missing file pins nothing.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        match fs::read_to_string(path) {
+            Ok(content) => toml::from_str(&content)
+                .map_err(|e| format!("invalid pinned registry keys {}: {e}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(format!(
+                "failed reading pinned registry keys {}: {e}",
+                path.display()
+            )),
+        }
+    }
+
+    /// Returns the distinct keys pinned under any of a registry's mirror `urls`.
+    pub fn for_mirrors(&self, urls: &[String]) -> Vec<&str> {
+        let keys: BTreeSet<&str> = urls
+            .iter()
+            .filter_map(|url| self.keys.get(url).map(String::as_str))
+            .collect();
+        keys.into_iter().collect()
+    }
+
+    /// Pins `key` under every mirror URL; returns whether anything changed.
+    pub fn pin(&mut self, urls: &[String], key: &str) -> bool {
+        let mut changed = false;
+        for url in urls {
+            if self.keys.get(url).map(String::as_str) != Some(key) {
+                self.keys.insert(url.clone(), key.to_string());
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// Writes the pins to `path`.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("failed creating {}: {e}", parent.display()))?;
+        }
+        let content = toml::to_string(self)
+            .map_err(|e| format!("failed encoding pinned registry keys: {e}"))?;
+        atomic_write(path, content).map_err(|e| {
+            format!(
+                "failed writing pinned registry keys {}: {e}",
+                path.display()
+            )
+        })
+    }
+}
+
+/// How a loaded index relates to the key pinned for its registry.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum IndexTrust {
+    /// A local or cached index, or a registry that does not sign.
+    #[default]
+    Unsigned,
+    /// Signed by the key already pinned for the registry.
+    Pinned { key: String },
+    /// Signed by a key pinned during this load, replacing `previous` if any.
+    NewlyPinned {
+        key: String,
+        previous: Option<String>,
+    },
+}
+
+/// Returns the pinned registry keys path in the config directory.
+pub fn pinned_keys_path() -> Option<PathBuf> {
+    berth_common::berth_layout().map(|layout| layout.config.join(PINNED_KEYS_FILE))
+}
+
+/// Lets a new registry key replace the pinned one for the rest of this process.
+pub fn set_accept_new_key(accept: bool) {
+    ACCEPT_NEW_KEY.store(accept, Ordering::Relaxed);
+}
+
+/// Returns whether a new registry key may replace the pinned one.
+pub fn accept_new_key() -> bool {
+    ACCEPT_NEW_KEY.load(Ordering::Relaxed)
+}
+
+/// Checks `index` from `registry` against its detached `signature` and the
+/// `pinned` key.
+///
+/// Unsigned indexes pass only while no key is pinned; a signature that does
+/// not verify never passes.
+pub fn check_index(
+    registry: &str,
+    index: &[u8],
+    signature: Option<&str>,
+    pinned: Option<&str>,
+    accept_new_key: bool,
+) -> Result<IndexTrust, String> {
+    let Some(signature) = signature else {
+        return match pinned {
+            Some(pinned) => Err(format!(
+                "registry index {registry} is not signed, but key {pinned} is pinned for it; refusing the unsigned index"
+            )),
+            None => Ok(IndexTrust::Unsigned),
+        };
+    };
+    let document: IndexSignature = serde_json::from_str(signature)
+        .map_err(|e| format!("invalid signature for registry index {registry}: {e}"))?;
+    let key = document.public_key.trim().to_ascii_lowercase();
+    let key_bytes = decode_hex::<32>("public key", &key)
+        .map_err(|e| format!("invalid signature for registry index {registry}: {e}"))?;
+    let signature_bytes = decode_hex::<64>("signature", &document.signature)
+        .map_err(|e| format!("invalid signature for registry index {registry}: {e}"))?;
+    let verifying_key = VerifyingKey::from_bytes(&key_bytes)
+        .map_err(|e| format!("invalid signing key for registry index {registry}: {e}"))?;
+    verifying_key
+        .verify_strict(index, &Signature::from_bytes(&signature_bytes))
+        .map_err(|_| {
+            format!("signature of registry index {registry} does not verify against key {key}")
+        })?;
+
+    match pinned.map(str::to_ascii_lowercase) {
+        None => Ok(IndexTrust::NewlyPinned {
+            key,
+            previous: None,
+        }),
+        Some(pinned) if pinned == key => Ok(IndexTrust::Pinned { key }),
+        Some(pinned) if accept_new_key => Ok(IndexTrust::NewlyPinned {
+            key,
+            previous: Some(pinned),
+        }),
+        Some(pinned) => Err(format!(
+            "registry index {registry} is signed by key {key}, but key {pinned} is pinned for it. If the registry rotated its key, confirm the new key out of band and rerun with --accept-new-key"
+        )),
+    }
+}
+
+/// Signs `index` with a 32-byte Ed25519 secret key.
+pub fn sign_index(index: &[u8], secret_key: &[u8; 32]) -> IndexSignature {
+    let signing_key = SigningKey::from_bytes(secret_key);
+    IndexSignature {
+        public_key: encode_hex(signing_key.verifying_key().as_bytes()),
+        signature: encode_hex(&signing_key.sign(index).to_bytes()),
+    }
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn decode_hex<const N: usize>(what: &str, text: &str) -> Result<[u8; N], String> {
+    let text = text.trim();
+    if text.len() != N * 2 || !text.is_ascii() {
+        return Err(format!("{what} must be {} hex characters", N * 2));
+    }
+    let mut bytes = [0u8; N];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&text[2 * i..2 * i + 2], 16)
+            .map_err(|_| format!("{what} is not valid hex"))?;
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const URL: &str = "https://registry.example.com/index.json";
+
+    #[test]
+    fn first_signed_index_pins_and_later_keys_must_match() {
+        let index = br#"[{"name":"github"}]"#;
+        let signed = serde_json::to_string(&sign_index(index, &[7; 32])).unwrap();
+        let key = sign_index(index, &[7; 32]).public_key;
+
+        assert_eq!(
+            check_index(URL, index, Some(&signed), None, false).unwrap(),
+            IndexTrust::NewlyPinned {
+                key: key.clone(),
+                previous: None
+            }
+        );
+        assert_eq!(
+            check_index(URL, index, Some(&signed), Some(&key), false).unwrap(),
+            IndexTrust::Pinned { key: key.clone() }
+        );
+
+        let rotated = serde_json::to_string(&sign_index(index, &[9; 32])).unwrap();
+        let err = check_index(URL, index, Some(&rotated), Some(&key), false).unwrap_err();
+        assert!(err.contains("--accept-new-key"), "{err}");
+        assert!(matches!(
+            check_index(URL, index, Some(&rotated), Some(&key), true).unwrap(),
+            IndexTrust::NewlyPinned { previous: Some(previous), .. } if previous == key
+        ));
+    }
+
+    #[test]
+    fn tampered_or_unsigned_indexes_fail_once_a_key_is_pinned() {
+        let index = br#"[{"name":"github"}]"#;
+        let signature = sign_index(index, &[7; 32]);
+        let signed = serde_json::to_string(&signature).unwrap();
+
+        let err = check_index(URL, b"[]", Some(&signed), None, true).unwrap_err();
+        assert!(err.contains("does not verify"), "{err}");
+        let err = check_index(URL, index, None, Some(&signature.public_key), true).unwrap_err();
+        assert!(err.contains("not signed"), "{err}");
+        assert_eq!(
+            check_index(URL, index, None, None, false).unwrap(),
+            IndexTrust::Unsigned
+        );
+    }
+
+    #[test]
+    fn pinned_keys_round_trip() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join(PINNED_KEYS_FILE);
+        assert_eq!(PinnedKeys::load(&path).unwrap(), PinnedKeys::default());
+        let mut pins = PinnedKeys::default();
+        pins.keys.insert(URL.to_string(), "ab".repeat(32));
+        pins.save(&path).unwrap();
+        assert_eq!(PinnedKeys::load(&path).unwrap(), pins);
+    }
+
+    #[test]
+    fn pins_follow_the_registry_across_mirror_order() {
+        let mirror = "https://mirror.example.com/index.json".to_string();
+        let mut pins = PinnedKeys::default();
+        pins.keys.insert(URL.to_string(), "ab".repeat(32));
+
+        let reordered = [mirror.clone(), URL.to_string()];
+        assert_eq!(pins.for_mirrors(&reordered), ["ab".repeat(32)]);
+        assert!(pins.pin(&reordered, &"ab".repeat(32)));
+        assert_eq!(pins.keys.get(&mirror), Some(&"ab".repeat(32)));
+        assert!(!pins.pin(&reordered, &"ab".repeat(32)));
+        assert_eq!(pins.for_mirrors(std::slice::from_ref(&mirror)).len(), 1);
+
+        pins.keys.insert(mirror.clone(), "cd".repeat(32));
+        assert_eq!(pins.for_mirrors(&reordered).len(), 2);
+        assert!(pins
+            .for_mirrors(&["https://other.example.com".to_string()])
+            .is_empty());
+    }
+}
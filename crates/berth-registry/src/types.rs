@@ -4,6 +4,7 @@
 //! Core registry metadata types parsed from seed JSON.
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fmt;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -24,6 +25,65 @@ pub struct ServerMetadata {
     pub tags: Vec<String>,
     pub maintainer: String,
     pub trust_level: TrustLevel,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub post_install: Vec<PostInstallStep>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub oauth: Option<OAuthSpec>,
+    /// Other published versions; the top-level fields describe `version`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub versions: Vec<PublishedVersion>,
+}
+
+/// One published version of a server; unset fields match the top-level manifest.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PublishedVersion {
+    pub version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<ServerSource>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub runtime: Option<ServerRuntime>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub permissions: Option<ServerPermissions>,
+}
+
+/// Authorization-code flow a server needs for browser-issued tokens.
+///
+/// Client credentials live in the server's config so each user can register
+/// their own OAuth app; the access token reaches the server through `env`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OAuthSpec {
+    pub authorize_url: String,
+    pub token_url: String,
+    #[serde(default)]
+    pub scopes: Vec<String>,
+    /// Config key holding the OAuth client ID.
+    pub client_id_key: String,
+    /// Config key holding the client secret, for providers that require one.
+    #[serde(default)]
+    pub client_secret_key: Option<String>,
+    /// Environment variable that receives a fresh access token at start.
+    pub env: String,
+}
+
+/// One restricted setup step `berth install` runs after writing the config.
+///
+/// Steps are interpreted by Berth itself; manifests cannot run commands.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum PostInstallStep {
+    /// Creates a directory covered by a declared filesystem write permission.
+    CreateDir { path: String },
+    /// Prints a next-step instruction.
+    Print { message: String },
+    /// Shows an `https` URL, e.g. to register an OAuth app, and opens it on a terminal.
+    #[serde(rename_all = "camelCase")]
+    OpenUrl {
+        url: String,
+        #[serde(default)]
+        description: Option<String>,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -33,6 +93,16 @@ pub struct ServerSource {
     pub source_type: String,
     pub package: String,
     pub repository: String,
+    /// Expected SHA-256 of the artifact Berth downloads, as lowercase hex
+    /// with an optional `sha256:` prefix.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    /// Publisher's estimate of the download size, shown before install.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub download_bytes: Option<u64>,
+    /// Publisher's estimate of the size once unpacked.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub installed_bytes: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -42,6 +112,119 @@ pub struct ServerRuntime {
     pub runtime_type: String,
     pub command: String,
     pub args: Vec<String>,
+    /// Syscalls blocked by Berth's default seccomp profile that this server needs.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub syscalls: Vec<String>,
+    /// Probe a supervisor runs periodically while the server is up.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub health_check: Option<HealthCheck>,
+    /// When a `job` runtime runs, e.g. `every 1h` or `daily`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<String>,
+    /// CPU, memory and open file caps the server runs under.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limits: Option<ResourceLimits>,
+    /// Endpoint template of a `unix-socket` or `named-pipe` server, e.g.
+    /// `{state}/sockets/{server}.sock`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub socket: Option<String>,
+}
+
+/// Resource caps declared by a manifest; unset fields are unlimited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceLimits {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_mb: Option<u64>,
+    /// Percent of one CPU core; above 100 allows several cores.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_percent: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_open_files: Option<u64>,
+}
+
+impl ResourceLimits {
+    /// Returns what is wrong with the limits, if anything.
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.memory_mb == Some(0) {
+            problems.push("memoryMb must be at least 1".to_string());
+        }
+        if self.cpu_percent == Some(0) {
+            problems.push("cpuPercent must be at least 1".to_string());
+        }
+        if self.max_open_files == Some(0) {
+            problems.push("maxOpenFiles must be at least 1".to_string());
+        }
+        problems
+    }
+}
+
+/// Periodic liveness probe that tells a working server from a wedged one.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthCheck {
+    #[serde(flatten)]
+    pub probe: HealthProbe,
+    #[serde(default = "default_health_interval_secs")]
+    pub interval_secs: u64,
+    #[serde(default = "default_health_timeout_ms")]
+    pub timeout_ms: u64,
+    /// Consecutive failed probes before the server counts as unhealthy.
+    #[serde(default = "default_health_failure_threshold")]
+    pub failure_threshold: u32,
+}
+
+/// What a health check does.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "probe", rename_all = "kebab-case")]
+pub enum HealthProbe {
+    /// Runs a command; exit code 0 is healthy.
+    Exec {
+        command: String,
+        #[serde(default)]
+        args: Vec<String>,
+    },
+    /// Connects to a TCP port on the loopback interface.
+    Tcp { port: u16 },
+    /// Sends an MCP `ping` over the server's stdin and waits for the answer.
+    McpPing,
+}
+
+fn default_health_interval_secs() -> u64 {
+    30
+}
+
+fn default_health_timeout_ms() -> u64 {
+    5_000
+}
+
+fn default_health_failure_threshold() -> u32 {
+    3
+}
+
+impl HealthCheck {
+    /// Returns what is wrong with the check, if anything.
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        match &self.probe {
+            HealthProbe::Exec { command, .. } if command.trim().is_empty() => {
+                problems.push("exec probe needs a command".to_string());
+            }
+            HealthProbe::Tcp { port: 0 } => problems.push("tcp probe needs a port".to_string()),
+            _ => {}
+        }
+        if self.interval_secs == 0 {
+            problems.push("intervalSecs must be at least 1".to_string());
+        }
+        if self.timeout_ms == 0 {
+            problems.push("timeoutMs must be at least 1".to_string());
+        }
+        if self.failure_threshold == 0 {
+            problems.push("failureThreshold must be at least 1".to_string());
+        }
+        problems
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -55,6 +238,11 @@ pub struct ServerPermissions {
     pub filesystem: Vec<String>,
     #[serde(default)]
     pub exec: Vec<String>,
+    /// Permissions individual tools need, in prefixed form
+    /// (`network:api.github.com:443`); `berth proxy` rejects calls to a listed
+    /// tool unless the effective permissions cover every entry.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tools: BTreeMap<String, Vec<String>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -84,6 +272,14 @@ pub struct ServerCompatibility {
     pub platforms: Vec<String>,
 }
 
+impl ServerCompatibility {
+    /// Returns whether the manifest lists `client`; an empty list places no
+    /// restriction.
+    pub fn supports_client(&self, client: &str) -> bool {
+        self.clients.is_empty() || self.clients.iter().any(|c| c == client)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ServerQuality {
@@ -93,7 +289,8 @@ pub struct ServerQuality {
     pub downloads: u64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Trust levels from least to most trusted.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum TrustLevel {
     Untrusted,
@@ -112,3 +309,19 @@ impl fmt::Display for TrustLevel {
         }
     }
 }
+
+impl std::str::FromStr for TrustLevel {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "untrusted" => Ok(TrustLevel::Untrusted),
+            "community" => Ok(TrustLevel::Community),
+            "verified" => Ok(TrustLevel::Verified),
+            "official" => Ok(TrustLevel::Official),
+            _ => Err(format!(
+                "unknown trust level `{value}` (expected untrusted, community, verified, or official)"
+            )),
+        }
+    }
+}
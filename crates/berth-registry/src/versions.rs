@@ -0,0 +1,147 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Published versions of a server and version-constraint resolution.
+//!
+//! A manifest describes its current version at the top level and may list
+//! further [`PublishedVersion`]s that override the source, runtime or
+//! permissions. Constraints are either one exact version (`1.2.0`) or a
+//! semver range (`^1.2`, `~1.1`, `>=2, <3`) that resolves to the newest
+//! matching version.
+
+use semver::{Version, VersionReq};
+
+use crate::types::{PublishedVersion, ServerMetadata};
+
+/// Returns every published version of `meta`, newest first.
+///
+/// Versions that are not valid semver sort after the ones that are.
+pub fn published_versions(meta: &ServerMetadata) -> Vec<&str> {
+    let mut versions: Vec<&str> = vec![meta.version.as_str()];
+    for published in &meta.versions {
+        if !versions.contains(&published.version.as_str()) {
+            versions.push(&published.version);
+        }
+    }
+    versions.sort_by(|a, b| match (Version::parse(a), Version::parse(b)) {
+        (Ok(a), Ok(b)) => b.cmp(&a),
+        (Ok(_), Err(_)) => std::cmp::Ordering::Less,
+        (Err(_), Ok(_)) => std::cmp::Ordering::Greater,
+        (Err(_), Err(_)) => b.cmp(a),
+    });
+    versions
+}
+
+/// Returns `meta` as published at `version`, or `None` if it was not.
+pub fn at_version(meta: &ServerMetadata, version: &str) -> Option<ServerMetadata> {
+    if meta.version == version {
+        return Some(meta.clone());
+    }
+    let published: &PublishedVersion = meta.versions.iter().find(|v| v.version == version)?;
+    let mut resolved = meta.clone();
+    resolved.version = published.version.clone();
+    if let Some(source) = &published.source {
+        resolved.source = source.clone();
+    }
+    if let Some(runtime) = &published.runtime {
+        resolved.runtime = runtime.clone();
+    }
+    if let Some(permissions) = &published.permissions {
+        resolved.permissions = permissions.clone();
+    }
+    Some(resolved)
+}
+
+/// Returns whether `constraint` names one exact version rather than a range.
+pub fn is_exact(constraint: &str) -> bool {
+    Version::parse(constraint.trim()).is_ok()
+}
+
+/// Resolves `constraint` to the newest matching published version of `meta`.
+pub fn resolve_version(meta: &ServerMetadata, constraint: &str) -> Result<ServerMetadata, String> {
+    let constraint = constraint.trim();
+    let published = published_versions(meta);
+    let not_found = || {
+        format!(
+            "no published version of {} matches `{constraint}` (available: {})",
+            meta.name,
+            published.join(", ")
+        )
+    };
+
+    if published.contains(&constraint) {
+        return at_version(meta, constraint).ok_or_else(not_found);
+    }
+    if is_exact(constraint) {
+        return Err(format!(
+            "version {constraint} of {} is not available (available: {})",
+            meta.name,
+            published.join(", ")
+        ));
+    }
+    let req = VersionReq::parse(constraint)
+        .map_err(|e| format!("invalid version constraint `{constraint}`: {e}"))?;
+    let newest = published
+        .iter()
+        .find(|v| Version::parse(v).is_ok_and(|version| req.matches(&version)))
+        .ok_or_else(not_found)?;
+    at_version(meta, newest).ok_or_else(not_found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Registry;
+
+    fn github() -> ServerMetadata {
+        let mut meta = Registry::from_seed().get("github").unwrap().clone();
+        meta.version = "2.1.0".to_string();
+        meta.versions = serde_json::from_str(
+            r#"[
+                {"version": "1.4.2", "permissions": {"network": ["api.github.com:443"]}},
+                {"version": "2.0.0"},
+                {"version": "1.3.0"}
+            ]"#,
+        )
+        .unwrap();
+        meta
+    }
+
+    #[test]
+    fn lists_versions_newest_first() {
+        assert_eq!(
+            published_versions(&github()),
+            vec!["2.1.0", "2.0.0", "1.4.2", "1.3.0"]
+        );
+    }
+
+    #[test]
+    fn resolves_ranges_to_the_newest_match() {
+        let meta = github();
+        assert_eq!(resolve_version(&meta, "^1.2").unwrap().version, "1.4.2");
+        assert_eq!(resolve_version(&meta, ">=2,<3").unwrap().version, "2.1.0");
+        assert_eq!(resolve_version(&meta, "~1.3").unwrap().version, "1.3.0");
+        assert_eq!(resolve_version(&meta, "2.0.0").unwrap().version, "2.0.0");
+    }
+
+    #[test]
+    fn older_versions_apply_their_overrides() {
+        let resolved = resolve_version(&github(), "1.4.2").unwrap();
+        assert!(resolved.permissions.exec.is_empty());
+        assert_eq!(resolved.source.package, github().source.package);
+    }
+
+    #[test]
+    fn reports_unmatched_and_invalid_constraints() {
+        let meta = github();
+        let err = resolve_version(&meta, "1.9.0").unwrap_err();
+        assert!(
+            err.contains("available: 2.1.0, 2.0.0, 1.4.2, 1.3.0"),
+            "{err}"
+        );
+        assert!(resolve_version(&meta, "^3").is_err());
+        assert!(resolve_version(&meta, ">>1")
+            .unwrap_err()
+            .contains("invalid version constraint"));
+    }
+}
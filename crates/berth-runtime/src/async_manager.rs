@@ -0,0 +1,224 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Async adapter over the blocking [`RuntimeManager`] for embedding in tokio
+//! applications.
+//!
+//! This is not a tokio-native runtime: processes are spawned with
+//! `std::process` through the manager's spawner, and state, log, and audit
+//! files go through `std::fs`. Each state transition (`start`, `stop`,
+//! `status`, ...) runs the blocking call with `spawn_blocking` and holds one
+//! blocking-pool thread until it returns, readiness waits and stop grace
+//! periods included.
+//!
+//! Supervision is what scales: [`AsyncRuntimeManager::supervise`] waits on
+//! tokio timers on the caller's runtime, so dozens of servers share its
+//! threads instead of each needing a thread and runtime as
+//! [`RuntimeManager::run_supervisor`] does. State reads, health probes, and
+//! restarts inside the loop run on the blocking pool, so a slow probe holds a
+//! pool thread but never stalls the executor.
+
+use std::io;
+use std::path::PathBuf;
+
+use crate::{
+    supervision_policy, ProcessSpec, RuntimeManager, ServerStatus, StartOutcome, StopOutcome,
+};
+
+/// Async adapter over [`RuntimeManager`], sharing its state files; see the
+/// module docs for what still blocks.
+#[derive(Clone)]
+pub struct AsyncRuntimeManager {
+    manager: RuntimeManager,
+}
+
+impl AsyncRuntimeManager {
+    /// Creates a manager rooted at a Berth home directory.
+    pub fn new<P: Into<PathBuf>>(berth_home: P) -> Self {
+        Self::from_manager(RuntimeManager::new(berth_home))
+    }
+
+    /// Wraps a configured manager, keeping its clock, spawner, and config home.
+    pub fn from_manager(manager: RuntimeManager) -> Self {
+        AsyncRuntimeManager { manager }
+    }
+
+    /// Returns the blocking manager this wraps.
+    pub fn manager(&self) -> &RuntimeManager {
+        &self.manager
+    }
+
+    /// Returns current persisted status for a server.
+    pub async fn status(&self, server: &str) -> io::Result<ServerStatus> {
+        let server = server.to_string();
+        self.blocking(move |manager| manager.status(&server)).await
+    }
+
+    /// Starts a server subprocess and records runtime state.
+    pub async fn start(&self, server: &str, spec: &ProcessSpec) -> io::Result<StartOutcome> {
+        let (server, spec) = (server.to_string(), spec.clone());
+        self.blocking(move |manager| manager.start(&server, &spec))
+            .await
+    }
+
+    /// Stops a running server, escalating to a forced kill after the grace period.
+    pub async fn stop(&self, server: &str) -> io::Result<StopOutcome> {
+        let server = server.to_string();
+        self.blocking(move |manager| manager.stop(&server)).await
+    }
+
+    /// Restarts a server by stopping then starting with the same process spec.
+    pub async fn restart(&self, server: &str, spec: &ProcessSpec) -> io::Result<()> {
+        let (server, spec) = (server.to_string(), spec.clone());
+        self.blocking(move |manager| manager.restart(&server, &spec))
+            .await
+    }
+
+    /// Supervises an already running server until it is stopped.
+    ///
+    /// Like [`RuntimeManager::run_supervisor`], but as a future on the
+    /// caller's runtime. Returns at once if `spec` needs no supervision.
+    pub async fn supervise(&self, server: &str, spec: &ProcessSpec) -> io::Result<()> {
+        let Some(policy) = supervision_policy(spec) else {
+            return Ok(());
+        };
+        self.manager
+            .run_supervisor_loop(server, spec.clone(), policy, no_refresh, None)
+            .await
+    }
+
+    /// Starts a server as a child of this process and supervises it until it
+    /// is stopped, seeing exit codes for `restart_on_exit_codes`.
+    ///
+    /// Returns at once if the server was already running, since its own
+    /// supervisor is in charge of it.
+    pub async fn start_supervised(&self, server: &str, spec: &ProcessSpec) -> io::Result<()> {
+        // The supervisor handles restarts; status reads must not race it.
+        let mut launch_spec = spec.clone();
+        launch_spec.auto_restart = None;
+        let name = server.to_string();
+        let Some(child) = self
+            .blocking(move |manager| manager.start_child(&name, &launch_spec))
+            .await?
+        else {
+            return Ok(());
+        };
+        let Some(policy) = supervision_policy(spec) else {
+            return Ok(());
+        };
+        self.manager
+            .run_supervisor_loop(server, spec.clone(), policy, no_refresh, Some(child))
+            .await
+    }
+
+    /// Runs a blocking manager call on tokio's blocking pool, occupying one of
+    /// its threads until the call returns.
+    async fn blocking<T, F>(&self, call: F) -> io::Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(RuntimeManager) -> io::Result<T> + Send + 'static,
+    {
+        let manager = self.manager.clone();
+        tokio::task::spawn_blocking(move || call(manager))
+            .await
+            .map_err(|e| io::Error::other(format!("runtime task failed: {e}")))?
+    }
+}
+
+fn no_refresh() -> Result<ProcessSpec, String> {
+    Err("no refresh handler configured".to_string())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::{process_is_alive, AutoRestartPolicy, HealthCheck, HealthProbe};
+    use std::time::Duration;
+
+    /// Exits once with code 1, then keeps running after the restart, answering
+    /// a slow health probe every second.
+    fn fail_once_spec(marker: &str) -> ProcessSpec {
+        ProcessSpec {
+            command: "sh".to_string(),
+            args: vec![
+                "-c".to_string(),
+                format!("if [ -f \"{marker}\" ]; then sleep 60; else : > \"{marker}\"; exit 1; fi"),
+            ],
+            auto_restart: Some(AutoRestartPolicy {
+                enabled: true,
+                max_restarts: 1,
+                restart_on_exit_codes: None,
+            }),
+            health_check: Some(HealthCheck {
+                probe: HealthProbe::Exec {
+                    command: "sleep".to_string(),
+                    args: vec!["0.3".to_string()],
+                },
+                interval_secs: 1,
+                timeout_ms: 2_000,
+                failure_threshold: 1,
+            }),
+            ..ProcessSpec::default()
+        }
+    }
+
+    #[test]
+    fn supervises_many_servers_on_one_thread() {
+        let tmp = tempfile::tempdir().unwrap();
+        let manager = AsyncRuntimeManager::new(tmp.path().join(".berth"));
+        let servers: Vec<String> = (0..12).map(|i| format!("server-{i}")).collect();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap();
+
+        runtime.block_on(async {
+            // Measures how long the executor goes without polling a task; a
+            // blocking probe or restart on the runtime thread shows up here.
+            let ticker = tokio::spawn(async {
+                let mut longest = Duration::ZERO;
+                for _ in 0..150 {
+                    let before = std::time::Instant::now();
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                    longest = longest.max(before.elapsed());
+                }
+                longest
+            });
+            let supervisors: Vec<_> = servers
+                .iter()
+                .map(|server| {
+                    let marker = tmp.path().join(format!("{server}.flag"));
+                    let spec = fail_once_spec(&marker.to_string_lossy());
+                    let (manager, server) = (manager.clone(), server.clone());
+                    tokio::spawn(async move { manager.start_supervised(&server, &spec).await })
+                })
+                .collect();
+
+            let recovered = |server: &str| {
+                let state = manager.manager().read_state(server).unwrap();
+                state.restart_attempts == 1 && state.pid.is_some_and(process_is_alive)
+            };
+            for _ in 0..250 {
+                if servers.iter().all(|server| recovered(server)) {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+            assert!(servers.iter().all(|server| recovered(server)));
+            // Twelve 300ms probes run each second from here on.
+            let longest_stall = ticker.await.unwrap();
+            assert!(
+                longest_stall < Duration::from_millis(250),
+                "executor stalled for {longest_stall:?}"
+            );
+
+            for server in &servers {
+                assert_eq!(manager.status(server).await.unwrap(), ServerStatus::Running);
+                manager.stop(server).await.unwrap();
+            }
+            for supervisor in supervisors {
+                supervisor.await.unwrap().unwrap();
+            }
+        });
+    }
+}
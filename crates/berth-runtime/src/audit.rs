@@ -0,0 +1,642 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! The audit JSONL that [`RuntimeManager`](crate::RuntimeManager) appends
+//! lifecycle and request events to: writing, rotation, hash chaining, and
+//! queries.
+//!
+//! Appends go to `audit.jsonl`. When the `[audit]` section of `policy.toml`
+//! sets a size or age limit, a full file is moved to `audit-<epoch ms>.jsonl`
+//! next to it before the next append. With `hash_chain` on, every entry
+//! carries `prevHash`, the hex SHA-256 of the entry line written before it,
+//! so editing or deleting an entry breaks the chain at the following one.
+
+use berth_common::{jsonl, sha256_hex};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// Prefix of rotated segment files in the audit directory.
+const SEGMENT_PREFIX: &str = "audit-";
+
+/// Hex digits kept in an entry id.
+const ENTRY_ID_LEN: usize = 12;
+
+/// `[audit]` section of `policy.toml`; the default never rotates or chains.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditSettings {
+    /// Rotates `audit.jsonl` once it reaches this size in MiB.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_file_mb: Option<u64>,
+    /// Rotates `audit.jsonl` once its oldest entry is this many days old.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_file_age_days: Option<u64>,
+    /// Links every new entry to the one before it with `prevHash`.
+    #[serde(default)]
+    pub hash_chain: bool,
+}
+
+impl AuditSettings {
+    fn rotation_due(&self, file: &mut File, now_ms: u64) -> io::Result<bool> {
+        let len = file.metadata()?.len();
+        if len == 0 {
+            return Ok(false);
+        }
+        if self
+            .max_file_mb
+            .is_some_and(|mb| len >= mb.saturating_mul(1024 * 1024))
+        {
+            return Ok(true);
+        }
+        let Some(days) = self.max_file_age_days else {
+            return Ok(false);
+        };
+        file.seek(SeekFrom::Start(0))?;
+        let mut first = String::new();
+        BufReader::new(&*file).read_line(&mut first)?;
+        let Ok(record) = serde_json::from_str::<AuditRecord>(first.trim()) else {
+            return Ok(false);
+        };
+        Ok(record.epoch_ms() <= now_ms.saturating_sub(days.saturating_mul(86_400_000)))
+    }
+}
+
+/// One parsed audit entry.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditRecord {
+    /// Missing in records written before millisecond timestamps; query
+    /// results always carry it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp_epoch_ms: Option<u64>,
+    #[serde(default)]
+    pub timestamp_epoch_secs: u64,
+    pub server: String,
+    pub action: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pid: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub args: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub actor: Option<AuditActor>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub permissions_before: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub permissions_after: Option<Vec<String>>,
+    /// Hex SHA-256 of the previous entry line, when hash chaining is on.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prev_hash: Option<String>,
+    /// Short id of the entry: the leading hex digits of the SHA-256 of its
+    /// line, which the next entry's `prevHash` starts with. Filled in by
+    /// [`AuditLog::query`], never stored.
+    #[serde(default, skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+}
+
+impl AuditRecord {
+    /// Event time in milliseconds, upgrading whole-second legacy records.
+    pub fn epoch_ms(&self) -> u64 {
+        self.timestamp_epoch_ms
+            .unwrap_or(self.timestamp_epoch_secs.saturating_mul(1_000))
+    }
+}
+
+/// Who made a change recorded in the audit log, and from where.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditActor {
+    /// OS user name from the environment.
+    pub user: String,
+    /// Effective uid, which unlike the name the caller cannot choose.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uid: Option<u32>,
+    /// User who ran the command through `sudo`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sudo_user: Option<String>,
+    /// Terminal on stdin, e.g. `/dev/pts/3`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tty: Option<String>,
+    /// Client address of the SSH session the command runs in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ssh_client: Option<String>,
+}
+
+impl AuditActor {
+    /// Describes the current process.
+    pub fn current() -> Self {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.trim().is_empty());
+        AuditActor {
+            user: var("USER")
+                .or_else(|| var("LOGNAME"))
+                .or_else(|| var("USERNAME"))
+                .unwrap_or_else(|| "unknown".to_string()),
+            uid: current_uid(),
+            sudo_user: var("SUDO_USER"),
+            tty: stdin_tty(),
+            ssh_client: var("SSH_CONNECTION")
+                .or_else(|| var("SSH_CLIENT"))
+                .and_then(|v| v.split_whitespace().next().map(str::to_string)),
+        }
+    }
+
+    /// One line such as `alice (uid 1000) via ssh from 10.0.0.5 on /dev/pts/3`.
+    pub fn describe(&self) -> String {
+        let mut out = self.user.clone();
+        if let Some(uid) = self.uid {
+            out.push_str(&format!(" (uid {uid})"));
+        }
+        if let Some(sudo_user) = &self.sudo_user {
+            out.push_str(&format!(" via sudo from {sudo_user}"));
+        }
+        match &self.ssh_client {
+            Some(client) => out.push_str(&format!(" via ssh from {client}")),
+            None if self.tty.is_none() => out.push_str(" without a terminal"),
+            None => {}
+        }
+        if let Some(tty) = &self.tty {
+            out.push_str(&format!(" on {tty}"));
+        }
+        out
+    }
+}
+
+#[cfg(unix)]
+fn current_uid() -> Option<u32> {
+    // SAFETY: geteuid cannot fail and has no side effects.
+    Some(unsafe { libc::geteuid() })
+}
+
+#[cfg(not(unix))]
+fn current_uid() -> Option<u32> {
+    None
+}
+
+#[cfg(unix)]
+fn stdin_tty() -> Option<String> {
+    let mut buf = [0 as libc::c_char; 256];
+    // SAFETY: the buffer outlives the call and its length is passed along;
+    // on success ttyname_r writes a NUL-terminated name into it.
+    let rc = unsafe { libc::ttyname_r(libc::STDIN_FILENO, buf.as_mut_ptr(), buf.len()) };
+    if rc != 0 {
+        return None;
+    }
+    // SAFETY: ttyname_r succeeded, so `buf` holds a NUL-terminated string.
+    let name = unsafe { std::ffi::CStr::from_ptr(buf.as_ptr()) };
+    Some(name.to_string_lossy().into_owned())
+}
+
+#[cfg(not(unix))]
+fn stdin_tty() -> Option<String> {
+    use std::io::IsTerminal;
+    io::stdin().is_terminal().then(|| "console".to_string())
+}
+
+/// Filters and page of an [`AuditLog::query`]; the default matches everything.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AuditQuery {
+    pub server: Option<String>,
+    pub action: Option<String>,
+    pub pid: Option<u32>,
+    /// Inclusive lower bound.
+    pub since_epoch_ms: Option<u64>,
+    /// Exclusive upper bound.
+    pub until_epoch_ms: Option<u64>,
+    /// Matching entries to skip, oldest first.
+    pub offset: usize,
+    /// Most entries to return; `None` returns the rest.
+    pub limit: Option<usize>,
+}
+
+impl AuditQuery {
+    fn matches(&self, record: &AuditRecord) -> bool {
+        let at = record.epoch_ms();
+        self.server.as_ref().is_none_or(|s| *s == record.server)
+            && self.action.as_ref().is_none_or(|a| *a == record.action)
+            && self.pid.is_none_or(|pid| record.pid == Some(pid))
+            && self.since_epoch_ms.is_none_or(|since| at >= since)
+            && self.until_epoch_ms.is_none_or(|until| at < until)
+    }
+}
+
+/// One page of matching entries.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AuditPage {
+    /// Entries in time order; ties keep file order.
+    pub entries: Vec<AuditRecord>,
+    /// Entries matching the filters across all pages.
+    pub total: usize,
+    /// Lines that could not be parsed as audit records.
+    pub malformed_lines: usize,
+}
+
+/// Result of [`AuditLog::verify`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChainReport {
+    /// Entry lines checked, across all segments.
+    pub entries: usize,
+    /// Entries that carry a `prevHash`.
+    pub chained: usize,
+    /// Files read, rotated segments first.
+    pub segments: usize,
+    /// First entry whose link to its predecessor does not hold.
+    pub first_break: Option<ChainBreak>,
+}
+
+/// A broken link in the audit hash chain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainBreak {
+    pub path: PathBuf,
+    /// 1-based line number within `path`.
+    pub line: usize,
+    pub reason: String,
+}
+
+/// Receives each audit entry once it is written, e.g. to forward it elsewhere.
+///
+/// Called on the writing thread, so implementations should hand slow work off.
+pub trait AuditSink: Send + Sync {
+    fn record(&self, record: &AuditRecord);
+}
+
+/// The audit log: the active JSONL file plus its rotated segments.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditLog {
+    path: PathBuf,
+}
+
+impl AuditLog {
+    /// Opens the audit log whose active file is `path`; a missing file reads
+    /// as empty.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        AuditLog { path: path.into() }
+    }
+
+    /// Path of the active JSONL file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Rotated segments oldest first, followed by the active file.
+    pub fn segments(&self) -> io::Result<Vec<PathBuf>> {
+        let mut rotated = Vec::new();
+        let dir = self.path.parent().unwrap_or(Path::new("."));
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(vec![self.path.clone()]),
+            Err(e) => return Err(e),
+        };
+        for entry in entries {
+            let path = entry?.path();
+            let rotated_at = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_prefix(SEGMENT_PREFIX))
+                .and_then(|rest| rest.strip_suffix(".jsonl"))
+                .and_then(|ms| ms.parse::<u64>().ok());
+            if let Some(rotated_at) = rotated_at {
+                rotated.push((rotated_at, path));
+            }
+        }
+        rotated.sort();
+        let mut segments: Vec<PathBuf> = rotated.into_iter().map(|(_, path)| path).collect();
+        segments.push(self.path.clone());
+        Ok(segments)
+    }
+
+    /// Appends `record`, rotating the active file first when `settings` say
+    /// it is full and adding `prevHash` when they enable hash chaining.
+    ///
+    /// `record` must serialize to a non-empty JSON object.
+    pub fn append<T: Serialize>(
+        &self,
+        record: &T,
+        settings: &AuditSettings,
+        now_ms: u64,
+    ) -> io::Result<()> {
+        let line = serde_json::to_string(record)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        jsonl::with_lock(&self.path, |file| {
+            let mut previous = jsonl::last_line(file)?;
+            if settings.rotation_due(file, now_ms)? {
+                self.rotate(file, now_ms)?;
+            }
+            let mut line = line;
+            if settings.hash_chain {
+                if previous.is_none() {
+                    previous = self.last_rotated_line()?;
+                }
+                let hash = sha256_hex(previous.as_deref().unwrap_or_default());
+                line.pop();
+                line.push_str(&format!(",\"prevHash\":\"{hash}\"}}"));
+            }
+            line.push('\n');
+            // One write call per line, so readers without a lock see whole lines too.
+            file.write_all(line.as_bytes())
+        })
+    }
+
+    /// Moves the content of the locked active file into a new segment.
+    ///
+    /// The active file is truncated rather than renamed, so appenders
+    /// waiting on its lock still write to the live file.
+    fn rotate(&self, file: &mut File, now_ms: u64) -> io::Result<()> {
+        let mut content = Vec::new();
+        file.seek(SeekFrom::Start(0))?;
+        file.read_to_end(&mut content)?;
+        let dir = self.path.parent().unwrap_or(Path::new("."));
+        let mut rotated_at = now_ms;
+        let mut segment = loop {
+            let path = dir.join(format!("{SEGMENT_PREFIX}{rotated_at}.jsonl"));
+            match fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&path)
+            {
+                Ok(segment) => break segment,
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => rotated_at += 1,
+                Err(e) => return Err(e),
+            }
+        };
+        segment.write_all(&content)?;
+        segment.sync_all()?;
+        file.set_len(0)?;
+        tracing::info!(rotated_at, bytes = content.len(), "rotated audit log");
+        Ok(())
+    }
+
+    /// Last entry line of the newest rotated segment.
+    fn last_rotated_line(&self) -> io::Result<Option<Vec<u8>>> {
+        let segments = self.segments()?;
+        let Some(newest) = segments.iter().rev().nth(1) else {
+            return Ok(None);
+        };
+        jsonl::last_line(&mut File::open(newest)?)
+    }
+
+    /// Checks the hash chain across all segments, stopping at the first
+    /// broken link.
+    ///
+    /// Entries before the first one with a `prevHash` predate chaining and
+    /// are not checked. The oldest remaining entry anchors the chain, since
+    /// `berth prune` may have removed its predecessor; after it, every entry
+    /// must carry the hash of the line before it.
+    pub fn verify(&self) -> io::Result<ChainReport> {
+        let mut report = ChainReport::default();
+        let mut previous: Option<Vec<u8>> = None;
+        let mut chain_started = false;
+        for path in self.segments()? {
+            let content = match fs::read(&path) {
+                Ok(content) => content,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e),
+            };
+            report.segments += 1;
+            for (index, line) in content.split(|&b| b == b'\n').enumerate() {
+                let line = line.trim_ascii();
+                if line.is_empty() {
+                    continue;
+                }
+                report.entries += 1;
+                let prev_hash = serde_json::from_slice::<serde_json::Value>(line)
+                    .ok()
+                    .and_then(|value| value.get("prevHash")?.as_str().map(str::to_string));
+                let broken = match (&prev_hash, &previous) {
+                    (Some(hash), Some(previous)) if *hash != sha256_hex(previous) => {
+                        Some("prevHash does not match the entry before it")
+                    }
+                    (Some(_), _) => None,
+                    (None, _) if chain_started => Some("entry has no prevHash"),
+                    (None, _) => None,
+                };
+                if let Some(reason) = broken {
+                    report.first_break = Some(ChainBreak {
+                        path,
+                        line: index + 1,
+                        reason: reason.to_string(),
+                    });
+                    return Ok(report);
+                }
+                if prev_hash.is_some() {
+                    report.chained += 1;
+                    chain_started = true;
+                }
+                previous = Some(line.to_vec());
+            }
+        }
+        Ok(report)
+    }
+
+    /// Returns the entries matching `query`, sorted by time and paginated.
+    pub fn query(&self, query: &AuditQuery) -> io::Result<AuditPage> {
+        let mut page = AuditPage::default();
+        let mut matching = Vec::new();
+        for path in self.segments()? {
+            let content = match fs::read_to_string(&path) {
+                Ok(content) => content,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e),
+            };
+            for line in content.lines().filter(|l| !l.trim().is_empty()) {
+                match serde_json::from_str::<AuditRecord>(line) {
+                    Ok(mut record) if query.matches(&record) => {
+                        record.timestamp_epoch_ms = Some(record.epoch_ms());
+                        record.id = Some(entry_id(line.trim()));
+                        matching.push(record);
+                    }
+                    Ok(_) => {}
+                    Err(_) => page.malformed_lines += 1,
+                }
+            }
+        }
+        // Concurrent writers can append slightly out of order.
+        matching.sort_by_key(AuditRecord::epoch_ms);
+        page.total = matching.len();
+        page.entries = matching
+            .into_iter()
+            .skip(query.offset)
+            .take(query.limit.unwrap_or(usize::MAX))
+            .collect();
+        Ok(page)
+    }
+}
+
+/// Short id of an entry line; see [`AuditRecord::id`].
+fn entry_id(line: &str) -> String {
+    let mut hash = sha256_hex(line.as_bytes());
+    hash.truncate(ENTRY_ID_LEN);
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_filters_sorts_and_paginates() {
+        let tmp = tempfile::tempdir().unwrap();
+        let log = AuditLog::new(tmp.path().join("audit.jsonl"));
+        assert_eq!(log.query(&AuditQuery::default()).unwrap().total, 0);
+
+        fs::write(
+            log.path(),
+            [
+                r#"{"timestampEpochMs":3000,"timestampEpochSecs":3,"server":"github","action":"stop","pid":7}"#,
+                r#"{"timestampEpochSecs":1,"server":"github","action":"start","pid":7}"#,
+                "not json",
+                r#"{"timestampEpochMs":2000,"timestampEpochSecs":2,"server":"slack","action":"start","pid":9}"#,
+                r#"{"timestampEpochMs":4000,"timestampEpochSecs":4,"server":"github","action":"start","pid":8}"#,
+            ]
+            .join("\n"),
+        )
+        .unwrap();
+
+        let all = log.query(&AuditQuery::default()).unwrap();
+        assert_eq!(all.total, 4);
+        assert_eq!(all.malformed_lines, 1);
+        let times: Vec<u64> = all.entries.iter().map(AuditRecord::epoch_ms).collect();
+        assert_eq!(times, vec![1000, 2000, 3000, 4000]);
+        assert_eq!(all.entries[0].timestamp_epoch_ms, Some(1000));
+        let id =
+            sha256_hex(br#"{"timestampEpochSecs":1,"server":"github","action":"start","pid":7}"#);
+        assert_eq!(all.entries[0].id.as_deref(), Some(&id[..ENTRY_ID_LEN]));
+
+        let github = AuditQuery {
+            server: Some("github".to_string()),
+            pid: Some(7),
+            ..AuditQuery::default()
+        };
+        assert_eq!(log.query(&github).unwrap().total, 2);
+
+        let window = AuditQuery {
+            action: Some("start".to_string()),
+            since_epoch_ms: Some(1500),
+            until_epoch_ms: Some(4000),
+            ..AuditQuery::default()
+        };
+        let page = log.query(&window).unwrap();
+        assert_eq!(page.total, 1);
+        assert_eq!(page.entries[0].server, "slack");
+
+        let second = AuditQuery {
+            offset: 1,
+            limit: Some(2),
+            ..AuditQuery::default()
+        };
+        let page = log.query(&second).unwrap();
+        assert_eq!(page.total, 4);
+        let times: Vec<u64> = page.entries.iter().map(AuditRecord::epoch_ms).collect();
+        assert_eq!(times, vec![2000, 3000]);
+    }
+
+    fn event(at_ms: u64, action: &str) -> AuditRecord {
+        AuditRecord {
+            timestamp_epoch_ms: Some(at_ms),
+            timestamp_epoch_secs: at_ms / 1_000,
+            server: "github".to_string(),
+            action: action.to_string(),
+            ..AuditRecord::default()
+        }
+    }
+
+    #[test]
+    fn rotates_by_age_and_size_and_queries_every_segment() {
+        let tmp = tempfile::tempdir().unwrap();
+        let log = AuditLog::new(tmp.path().join("audit").join("audit.jsonl"));
+        let settings = AuditSettings {
+            max_file_mb: Some(1),
+            max_file_age_days: Some(1),
+            hash_chain: false,
+        };
+        let day_ms = 86_400_000;
+        log.append(&event(1_000, "start"), &settings, 1_000)
+            .unwrap();
+        log.append(&event(2_000, "stop"), &settings, 2_000).unwrap();
+        assert_eq!(log.segments().unwrap(), vec![log.path().to_path_buf()]);
+
+        let later = 1_000 + day_ms;
+        log.append(&event(later, "start"), &settings, later)
+            .unwrap();
+        let segments = log.segments().unwrap();
+        assert_eq!(segments.len(), 2);
+        assert!(segments[0].ends_with(format!("audit-{later}.jsonl")));
+        assert_eq!(fs::read_to_string(&segments[0]).unwrap().lines().count(), 2);
+
+        let mut big = event(later, "stop");
+        big.command = Some("x".repeat(1024 * 1024));
+        log.append(&big, &settings, later).unwrap();
+        log.append(&event(later + 1, "start"), &settings, later + 1)
+            .unwrap();
+        let segments = log.segments().unwrap();
+        assert_eq!(segments.len(), 3);
+        assert!(segments[1].ends_with(format!("audit-{}.jsonl", later + 1)));
+
+        let page = log.query(&AuditQuery::default()).unwrap();
+        assert_eq!(page.total, 5);
+        assert_eq!(page.entries[0].epoch_ms(), 1_000);
+    }
+
+    #[test]
+    fn verify_follows_the_chain_across_rotation_and_finds_edits() {
+        let tmp = tempfile::tempdir().unwrap();
+        let log = AuditLog::new(tmp.path().join("audit.jsonl"));
+        fs::write(
+            log.path(),
+            "{\"timestampEpochSecs\":1,\"server\":\"github\",\"action\":\"start\"}\n",
+        )
+        .unwrap();
+        let settings = AuditSettings {
+            max_file_age_days: Some(1),
+            hash_chain: true,
+            ..AuditSettings::default()
+        };
+        let day_ms = 86_400_000;
+        for at in [2_000, 3_000, day_ms + 2_000, day_ms + 3_000] {
+            log.append(&event(at, "start"), &settings, at).unwrap();
+        }
+
+        let report = log.verify().unwrap();
+        assert_eq!(report.first_break, None);
+        assert_eq!((report.entries, report.chained, report.segments), (5, 4, 2));
+        let entries = log.query(&AuditQuery::default()).unwrap().entries;
+        assert_eq!(entries[0].prev_hash, None);
+        assert!(entries[1..].iter().all(|e| e.prev_hash.is_some()));
+
+        let segment = log.segments().unwrap()[0].clone();
+        let tampered = fs::read_to_string(&segment)
+            .unwrap()
+            .replace("\"timestampEpochMs\":3000", "\"timestampEpochMs\":3001");
+        fs::write(&segment, tampered).unwrap();
+        let broken = log.verify().unwrap().first_break.unwrap();
+        assert_eq!((broken.path, broken.line), (log.path().to_path_buf(), 1));
+        assert!(
+            broken.reason.contains("does not match"),
+            "{}",
+            broken.reason
+        );
+
+        fs::remove_file(&segment).unwrap();
+        assert_eq!(log.verify().unwrap().first_break, None);
+        let mut file = fs::OpenOptions::new()
+            .append(true)
+            .open(log.path())
+            .unwrap();
+        file.write_all(b"{\"server\":\"github\",\"action\":\"forged\"}\n")
+            .unwrap();
+        let broken = log.verify().unwrap().first_break.unwrap();
+        assert_eq!(broken.line, 3);
+        assert_eq!(broken.reason, "entry has no prevHash");
+    }
+}
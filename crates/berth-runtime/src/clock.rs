@@ -0,0 +1,75 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Time source used by the runtime manager and its supervisor.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Future returned by [`Clock::sleep`].
+pub type Sleep = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// Source of wall-clock time and supervisor sleeps.
+///
+/// [`SystemClock`] is the default; [`ManualClock`] lets tests step through
+/// restart and refresh timing without waiting.
+pub trait Clock: Send + Sync {
+    /// Returns the current unix time in milliseconds.
+    fn now_epoch_ms(&self) -> u64;
+
+    /// Returns the current unix time in seconds.
+    fn now_epoch_secs(&self) -> u64 {
+        self.now_epoch_ms() / 1_000
+    }
+
+    /// Waits for `duration` to pass on this clock.
+    fn sleep(&self, duration: Duration) -> Sleep;
+}
+
+/// The real clock, sleeping on the tokio timer.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_epoch_ms(&self) -> u64 {
+        berth_common::now_epoch_ms()
+    }
+
+    fn sleep(&self, duration: Duration) -> Sleep {
+        Box::pin(tokio::time::sleep(duration))
+    }
+}
+
+/// A clock that only moves when told to; sleeping advances it instantly.
+#[derive(Debug, Default)]
+pub struct ManualClock {
+    now_ms: AtomicU64,
+}
+
+impl ManualClock {
+    /// Creates a clock reading `start_epoch_ms`.
+    pub fn new(start_epoch_ms: u64) -> Self {
+        ManualClock {
+            now_ms: AtomicU64::new(start_epoch_ms),
+        }
+    }
+
+    /// Moves the clock forward.
+    pub fn advance(&self, duration: Duration) {
+        self.now_ms
+            .fetch_add(duration.as_millis() as u64, Ordering::SeqCst);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now_epoch_ms(&self) -> u64 {
+        self.now_ms.load(Ordering::SeqCst)
+    }
+
+    fn sleep(&self, duration: Duration) -> Sleep {
+        self.advance(duration);
+        Box::pin(std::future::ready(()))
+    }
+}
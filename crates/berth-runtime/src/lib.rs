@@ -1,40 +1,241 @@
 // SPDX-License-Identifier: Apache-2.0
 // Copyright (c) 2026 Schwimmbeck Dominik
 
-//! Synchronous runtime state manager for installed MCP servers.
+//! Runtime state manager for installed MCP servers, with a blocking front end
+//! and an async adapter over it ([`AsyncRuntimeManager`]).
 
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use std::fmt;
-use std::fs::{self, OpenOptions};
-use std::io::{self, Write};
-use std::path::PathBuf;
-use std::process::{Command, Stdio};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpStream};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::Arc;
 use std::thread;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, UNIX_EPOCH};
+
+use berth_common::atomic_write;
+use berth_common::jsonl;
+use berth_common::now_epoch_ms;
+use berth_common::redact::redact_args;
+use berth_common::storage;
+
+mod async_manager;
+mod audit;
+mod clock;
+mod limits;
+mod log_text;
+mod process;
+mod seccomp;
+mod spawn;
+mod state_lock;
+mod summary;
+#[cfg(test)]
+mod supervisor_harness;
+mod transport;
+
+pub use async_manager::AsyncRuntimeManager;
+pub use audit::{
+    AuditActor, AuditLog, AuditPage, AuditQuery, AuditRecord, AuditSettings, AuditSink, ChainBreak,
+    ChainReport,
+};
+pub use clock::{Clock, ManualClock, Sleep, SystemClock};
+pub use limits::{attach_limits, ResourceLimits};
+pub use log_text::{sanitize_log_line, MAX_LOG_LINE_BYTES};
+pub use process::{
+    output_files, process_command_line, process_table, ChildHandle, PidHandle, ProcessExit,
+    ProcessHandle, ShutdownSignal, DEFAULT_SHUTDOWN_GRACE_PERIOD,
+};
+use process::{process_is_alive, terminate_process, Shutdown};
+pub use seccomp::{is_filtered_syscall, SeccompProfile, SIGSYS};
+pub use spawn::{CommandSpawner, Spawner};
+use state_lock::StateLock;
+pub use summary::{
+    read_status_summary, StatusSummary, STATUS_SUMMARY_FILE, STATUS_SUMMARY_VERSION,
+};
+pub use transport::{
+    bind_to_parent, replica_name, replica_of, template_problems, Connection, Endpoint, HTTP,
+    NAMED_PIPE, PORT_ENV, PORT_PLACEHOLDER, SOCKET_ENV, SOCKET_PLACEHOLDER, STDIO, UNIX_SOCKET,
+};
 
 /// Returns crate version for runtime diagnostics/tests.
 pub fn version() -> &'static str {
     env!("CARGO_PKG_VERSION")
 }
 
-/// Runtime status persisted for a server.
+/// Runtime status of a server.
+///
+/// Only `Running`, `Stopped` and `CrashLooping` are persisted; the other states
+/// are derived from a running process or job scheduler when status is read. Older state files
+/// hold `running` / `stopped`, which keep their meaning.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "lowercase")]
+#[serde(rename_all = "kebab-case")]
 pub enum ServerStatus {
+    /// Spawned but its readiness probe has not passed yet.
+    Starting,
     Running,
+    /// Alive but its readiness probe did not pass in time, or its health
+    /// check keeps failing.
+    Unhealthy,
+    /// Kept exiting until the auto-restart budget was spent.
+    CrashLooping,
+    /// A scheduled job waiting for its next run.
+    Scheduled,
     Stopped,
 }
 
 impl fmt::Display for ServerStatus {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
+            ServerStatus::Starting => write!(f, "starting"),
             ServerStatus::Running => write!(f, "running"),
+            ServerStatus::Unhealthy => write!(f, "unhealthy"),
+            ServerStatus::CrashLooping => write!(f, "crash-looping"),
+            ServerStatus::Scheduled => write!(f, "scheduled"),
             ServerStatus::Stopped => write!(f, "stopped"),
         }
     }
 }
 
+/// Condition a started server must meet before it is reported as running.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum ReadinessProbe {
+    /// A line containing `pattern` appears in the server log after spawn.
+    LogPattern { pattern: String },
+    /// A TCP connection to `port` on the loopback interface succeeds.
+    TcpPort { port: u16 },
+    /// The server answers an MCP `initialize` request written to its stdin.
+    McpInitialize,
+}
+
+impl fmt::Display for ReadinessProbe {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReadinessProbe::LogPattern { pattern } => write!(f, "log line containing `{pattern}`"),
+            ReadinessProbe::TcpPort { port } => write!(f, "TCP port {port} open"),
+            ReadinessProbe::McpInitialize => write!(f, "MCP initialize answered"),
+        }
+    }
+}
+
+/// A readiness probe and how long a server may take to pass it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReadinessCheck {
+    pub probe: ReadinessProbe,
+    pub timeout_ms: u64,
+}
+
+/// Probe a supervisor runs periodically against a server that is up.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum HealthProbe {
+    /// `command` with `args` exits with code 0.
+    Exec { command: String, args: Vec<String> },
+    /// A TCP connection to `port` on the loopback interface succeeds.
+    Tcp { port: u16 },
+    /// The server answers an MCP `ping` written to its stdin.
+    McpPing,
+}
+
+impl fmt::Display for HealthProbe {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HealthProbe::Exec { command, .. } => write!(f, "`{command}` succeeds"),
+            HealthProbe::Tcp { port } => write!(f, "TCP port {port} open"),
+            HealthProbe::McpPing => write!(f, "MCP ping answered"),
+        }
+    }
+}
+
+/// A health probe, how often it runs, and when failures make a server unhealthy.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HealthCheck {
+    pub probe: HealthProbe,
+    pub interval_secs: u64,
+    pub timeout_ms: u64,
+    /// Consecutive failed probes before the server is recorded as unhealthy.
+    pub failure_threshold: u32,
+}
+
+/// Latest health check verdict for a running server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Health {
+    Healthy,
+    Unhealthy,
+}
+
+impl fmt::Display for Health {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Health::Healthy => write!(f, "healthy"),
+            Health::Unhealthy => write!(f, "unhealthy"),
+        }
+    }
+}
+
+/// When a scheduled job runs.
+///
+/// Runs start at whole multiples of `every_secs` since the Unix epoch, so
+/// `3600` runs at the top of every hour (UTC). Runs never overlap: slots that
+/// pass while a run is still going are skipped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobSchedule {
+    pub every_secs: u64,
+}
+
+impl JobSchedule {
+    /// Returns the first run time after `epoch_ms`.
+    pub fn next_after(&self, epoch_ms: u64) -> u64 {
+        let every_ms = self.every_secs.max(1).saturating_mul(1_000);
+        (epoch_ms / every_ms + 1).saturating_mul(every_ms)
+    }
+}
+
+impl fmt::Display for JobSchedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let secs = self.every_secs;
+        match secs {
+            s if s > 0 && s % 86_400 == 0 => write!(f, "every {}d", s / 86_400),
+            s if s > 0 && s % 3_600 == 0 => write!(f, "every {}h", s / 3_600),
+            s if s > 0 && s % 60 == 0 => write!(f, "every {}m", s / 60),
+            s => write!(f, "every {s}s"),
+        }
+    }
+}
+
+/// One finished run of a scheduled job.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobRun {
+    pub started_at_epoch_ms: u64,
+    pub duration_ms: u64,
+    pub pid: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+}
+
+impl JobRun {
+    pub fn succeeded(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+}
+
+/// Result of waiting for a started server to become ready.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadinessOutcome {
+    Ready {
+        after_ms: u64,
+    },
+    /// The process exited before the probe passed.
+    Exited,
+    /// The probe did not pass in time; the server was stopped.
+    TimedOut,
+}
+
 /// Result of attempting to start a server.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum StartOutcome {
@@ -49,6 +250,89 @@ pub enum StopOutcome {
     AlreadyStopped,
 }
 
+/// What [`RuntimeManager::prune`] does with artifacts of unknown servers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PruneMode {
+    /// Deletes state, log, and crash files.
+    Remove,
+    /// Moves files under `archive/<epoch-ms>/` in the Berth home.
+    Archive,
+}
+
+/// Outcome of a prune pass.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PruneReport {
+    /// Servers whose artifacts were pruned.
+    pub servers: Vec<String>,
+    /// Files removed, or their archived locations.
+    pub files: Vec<PathBuf>,
+    /// Unknown servers left alone because a tracked process is still alive.
+    pub skipped_running: Vec<String>,
+    /// Archive directory used by [`PruneMode::Archive`].
+    pub archive_dir: Option<PathBuf>,
+}
+
+/// How long [`RuntimeManager::prune_stale`] keeps logs and audit entries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    /// Log and crash files untouched this long, and audit entries older than
+    /// this, are removed.
+    pub max_age: Duration,
+    /// Reports what would be removed without changing anything.
+    pub dry_run: bool,
+}
+
+/// Outcome of a [`RuntimeManager::prune_stale`] pass.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct StalePruneReport {
+    /// State, log, and crash files of servers no longer installed.
+    pub orphaned_files: Vec<PathBuf>,
+    /// Log and crash files of installed servers past the retention period.
+    pub expired_logs: Vec<PathBuf>,
+    /// Audit entries past the retention period.
+    pub expired_audit_entries: usize,
+    /// Servers left alone because a tracked process is still alive.
+    pub skipped_running: Vec<String>,
+    /// Bytes freed, or that a dry run would free.
+    pub freed_bytes: u64,
+}
+
+/// Runtime state that no longer matches the machine; see
+/// [`RuntimeManager::stale_states`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StaleState {
+    /// The state records a running process whose pid is gone.
+    DeadPid { server: String, pid: u32 },
+    /// The state file cannot be read or parsed.
+    Unreadable { server: String, error: String },
+}
+
+/// Audit action of grants, revocations and resets of a server's permissions.
+pub const PERMISSION_CHANGE_ACTION: &str = "permission-change";
+
+/// Seconds a supervisor waits before retrying a failed spec refresh.
+pub const REFRESH_RETRY_SECS: u64 = 60;
+
+/// How often [`RuntimeManager::await_ready`] re-runs a readiness probe.
+const READINESS_POLL: Duration = Duration::from_millis(100);
+
+/// Request id of the `initialize` sent by [`ReadinessProbe::McpInitialize`].
+const READINESS_REQUEST_ID: &str = "berth-readiness";
+
+/// How often [`RuntimeManager::relay_output`] looks for new output.
+const RELAY_POLL: Duration = Duration::from_millis(200);
+
+/// How often a running health probe re-checks its command or the log.
+const HEALTH_POLL: Duration = Duration::from_millis(50);
+
+/// Log lines copied into a crash report.
+const CRASH_REPORT_LOG_LINES: usize = 20;
+
+/// Raises the soft core limit as far as the hard limit allows, then becomes the server.
+#[cfg(unix)]
+const CORE_LIMIT_WRAPPER: &str =
+    r#"ulimit -c unlimited 2>/dev/null || ulimit -c "$(ulimit -H -c)"; exec "$0" "$@""#;
+
 /// Runtime process specification for launching a server.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ProcessSpec {
@@ -56,19 +340,88 @@ pub struct ProcessSpec {
     pub args: Vec<String>,
     pub env: BTreeMap<String, String>,
     pub auto_restart: Option<AutoRestartPolicy>,
+    /// Starts the child with only `env` instead of inheriting the parent environment.
+    #[serde(default)]
+    pub clean_env: bool,
+    /// Unix time (seconds) after which a supervisor relaunches the server with
+    /// a fresh spec, e.g. shortly before credentials in `env` expire.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_at_epoch_secs: Option<u64>,
+    /// Lifts the core file size limit for the child and writes a crash report
+    /// when a supervised child dies by signal.
+    #[serde(default)]
+    pub capture_core: bool,
+    /// Probe that must pass before a started server is reported as running.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub readiness: Option<ReadinessCheck>,
+    /// OS user the server runs as (Linux only).
+    ///
+    /// A privileged Berth switches users with `runuser`; otherwise the server
+    /// is launched as a transient unit with `systemd-run --uid`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run_as: Option<String>,
+    /// Syscall filter installed before the server is exec'd (Linux only).
+    ///
+    /// Supervised servers killed by the filter get a crash report.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seccomp: Option<SeccompProfile>,
+    /// Signal sent to ask the server to exit when it is stopped (default `SIGTERM`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shutdown_signal: Option<ShutdownSignal>,
+    /// Seconds the server gets to exit after its shutdown signal before it is
+    /// force-killed (default 1).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shutdown_grace_period_secs: Option<u64>,
+    /// Probe a supervisor runs while the server is up; its verdict shows in
+    /// [`RuntimeManager::status`] and [`RuntimeManager::health`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub health_check: Option<HealthCheck>,
+    /// Runs the spec as a scheduled job instead of a long-running server; see
+    /// [`RuntimeManager::schedule_job`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<JobSchedule>,
+    /// CPU, memory and open file caps; see [`attach_limits`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limits: Option<ResourceLimits>,
 }
 
 /// Auto-restart policy applied to supervised server processes.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct AutoRestartPolicy {
     pub enabled: bool,
     pub max_restarts: u32,
+    /// Exit codes that count as failures; any other exit is clean and ends
+    /// supervision. `None` restarts after every exit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub restart_on_exit_codes: Option<Vec<i32>>,
+}
+
+impl AutoRestartPolicy {
+    /// Returns whether an exit with `code` should trigger a restart.
+    ///
+    /// An unknown code (a process the supervisor did not spawn itself) always
+    /// counts as a failure.
+    pub fn restarts_on(&self, code: Option<i32>) -> bool {
+        match (&self.restart_on_exit_codes, code) {
+            (Some(codes), Some(code)) => codes.contains(&code),
+            _ => true,
+        }
+    }
+}
+
+/// Returns a process exit code, mapping death by signal `n` to `128 + n` as shells do.
+pub fn exit_code(status: &ExitStatus) -> Option<i32> {
+    ProcessExit::from(*status).exit_code()
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 struct RuntimeState {
     status: ServerStatus,
-    updated_at_epoch_secs: u64,
+    #[serde(default)]
+    updated_at_epoch_ms: u64,
+    /// Whole-second timestamp written by older releases; read once and upgraded.
+    #[serde(default, skip_serializing)]
+    updated_at_epoch_secs: Option<u64>,
     #[serde(default)]
     pid: Option<u32>,
     #[serde(default)]
@@ -77,16 +430,45 @@ struct RuntimeState {
     args: Vec<String>,
     #[serde(default)]
     auto_restart_enabled: bool,
+    /// Whether the running spec cleared the inherited env, so specs rebuilt
+    /// for a restart keep `--inherit-env`; unknown for older states.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    clean_env: Option<bool>,
     #[serde(default)]
     max_restarts: u32,
     #[serde(default)]
     restart_attempts: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    last_exit_code: Option<i32>,
+    /// Log length at spawn while the server awaits its readiness probe.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    readiness_log_offset: Option<u64>,
+    /// When a server still awaiting its readiness probe turns unhealthy.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    readiness_deadline_epoch_ms: Option<u64>,
+    /// Shutdown settings of the running spec, so `stop` needs no spec.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    shutdown_signal: Option<ShutdownSignal>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    shutdown_grace_period_secs: Option<u64>,
+    /// Latest health check verdict, written by the supervisor.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    health: Option<Health>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    health_checked_at_epoch_ms: Option<u64>,
+    /// Process driving a scheduled job; `pid` is then the current run, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    scheduler_pid: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    next_run_epoch_ms: Option<u64>,
 }
 
 #[derive(Debug, Default, Deserialize)]
 struct RuntimePolicyFile {
     #[serde(default)]
     servers: RuntimePolicyServers,
+    #[serde(default)]
+    audit: AuditSettings,
 }
 
 #[derive(Debug, Default, Deserialize)]
@@ -98,6 +480,8 @@ struct RuntimePolicyServers {
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct AuditEvent {
+    timestamp_epoch_ms: u64,
+    /// Whole seconds, kept so readers of older audit records keep working.
     timestamp_epoch_secs: u64,
     server: String,
     action: String,
@@ -107,196 +491,432 @@ struct AuditEvent {
     command: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     args: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    exit_code: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    duration_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<bool>,
+    /// Server version after an install or update.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    version: Option<String>,
+    /// Server version an update replaced.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    previous_version: Option<String>,
+    /// Who made a configuration change.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    actor: Option<AuditActor>,
+    /// Effective permissions before and after a permission change.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    permissions_before: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    permissions_after: Option<Vec<String>>,
+}
+
+impl AuditEvent {
+    /// Returns an event stamped with the current time; callers fill in the rest.
+    fn now() -> Self {
+        let timestamp_epoch_ms = now_epoch_ms();
+        AuditEvent {
+            timestamp_epoch_ms,
+            timestamp_epoch_secs: timestamp_epoch_ms / 1_000,
+            server: String::new(),
+            action: String::new(),
+            pid: None,
+            command: None,
+            args: None,
+            exit_code: None,
+            duration_ms: None,
+            error: None,
+            version: None,
+            previous_version: None,
+            actor: None,
+            permissions_before: None,
+            permissions_after: None,
+        }
+    }
 }
 
 impl Default for RuntimeState {
     fn default() -> Self {
         RuntimeState {
             status: ServerStatus::Stopped,
-            updated_at_epoch_secs: now_epoch_secs(),
+            updated_at_epoch_ms: now_epoch_ms(),
+            updated_at_epoch_secs: None,
             pid: None,
             command: None,
             args: Vec::new(),
             auto_restart_enabled: false,
+            clean_env: None,
             max_restarts: 0,
             restart_attempts: 0,
+            last_exit_code: None,
+            readiness_log_offset: None,
+            readiness_deadline_epoch_ms: None,
+            shutdown_signal: None,
+            shutdown_grace_period_secs: None,
+            health: None,
+            health_checked_at_epoch_ms: None,
+            scheduler_pid: None,
+            next_run_epoch_ms: None,
         }
     }
 }
 
+impl RuntimeState {
+    /// Records the settings of the spec now running; a new process starts
+    /// without a health verdict.
+    fn record_spec(&mut self, spec: &ProcessSpec) {
+        self.command = Some(spec.command.clone());
+        self.args = spec.args.clone();
+        self.clean_env = Some(spec.clean_env);
+        self.shutdown_signal = spec.shutdown_signal;
+        self.shutdown_grace_period_secs = spec.shutdown_grace_period_secs;
+        self.health = None;
+        self.health_checked_at_epoch_ms = None;
+    }
+
+    fn shutdown(&self) -> Shutdown {
+        Shutdown::new(self.shutdown_signal, self.shutdown_grace_period_secs)
+    }
+
+    /// Returns whether `scheduler` still drives this job.
+    fn scheduled_by(&self, scheduler: u32) -> bool {
+        self.status == ServerStatus::Running && self.scheduler_pid == Some(scheduler)
+    }
+}
+
+#[derive(Clone)]
 pub struct RuntimeManager {
     berth_home: PathBuf,
+    config_home: PathBuf,
+    clock: Arc<dyn Clock>,
+    spawner: Arc<dyn Spawner>,
+    audit_sink: Option<Arc<dyn AuditSink>>,
 }
 
 impl RuntimeManager {
     /// Creates a manager rooted at a Berth home directory.
     pub fn new<P: Into<PathBuf>>(berth_home: P) -> Self {
+        let berth_home = berth_home.into();
         RuntimeManager {
-            berth_home: berth_home.into(),
+            config_home: berth_home.clone(),
+            berth_home,
+            clock: Arc::new(SystemClock),
+            spawner: Arc::new(CommandSpawner),
+            audit_sink: None,
         }
     }
 
+    /// Reads org policy from a separate config directory (split layouts).
+    pub fn with_config_home<P: Into<PathBuf>>(mut self, config_home: P) -> Self {
+        self.config_home = config_home.into();
+        self
+    }
+
+    /// Uses `clock` for state timestamps and supervisor timing instead of the system clock.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Launches servers through `spawner` instead of as local child processes.
+    pub fn with_spawner(mut self, spawner: Arc<dyn Spawner>) -> Self {
+        self.spawner = spawner;
+        self
+    }
+
+    /// Hands every audit entry this manager writes to `sink` as well.
+    pub fn with_audit_sink(mut self, sink: Arc<dyn AuditSink>) -> Self {
+        self.audit_sink = Some(sink);
+        self
+    }
+
+    /// Checks that the Berth home can take state, log, and audit writes.
+    ///
+    /// Commands that change server state call this first, so a read-only or
+    /// full filesystem fails before anything is spawned or signaled.
+    pub fn preflight(&self) -> io::Result<()> {
+        storage::check_writable(&self.berth_home, storage::MIN_FREE_BYTES).map_err(io::Error::from)
+    }
+
     /// Returns current persisted status for a server.
     pub fn status(&self, server: &str) -> io::Result<ServerStatus> {
         self.status_with_spec(server, None)
     }
 
+    /// Returns a server's status without recording anything.
+    ///
+    /// A process that exited reads as stopped, but the state file, log, and
+    /// audit trail are left for the next status check that may write, and no
+    /// auto-restart is attempted.
+    pub fn peek_status(&self, server: &str) -> io::Result<ServerStatus> {
+        let state = self.read_state(server)?;
+        Ok(self
+            .live_status(server, &state)
+            .unwrap_or(ServerStatus::Stopped))
+    }
+
     /// Returns current persisted status for a server with optional restart spec.
     pub fn status_with_spec(
         &self,
         server: &str,
         spec: Option<&ProcessSpec>,
     ) -> io::Result<ServerStatus> {
-        let mut state = self.read_state(server)?;
-
-        if state.status == ServerStatus::Running {
-            let old_pid = state.pid;
-            let old_command = state.command.clone();
-            let old_args = state.args.clone();
-
-            if let Some(pid) = state.pid {
-                if process_is_alive(pid) {
-                    return Ok(ServerStatus::Running);
-                }
-            }
+        let state = self.read_state(server)?;
+        if let Some(status) = self.live_status(server, &state) {
+            return Ok(status);
+        }
 
-            let expects_external_supervisor = spec
-                .and_then(|s| s.auto_restart)
-                .is_some_and(|policy| policy.enabled)
-                && !state.auto_restart_enabled;
-            if expects_external_supervisor
-                && self.wait_for_supervisor_replacement(server, old_pid)?
-            {
-                return Ok(ServerStatus::Running);
+        // The state says running but the process is gone.
+        let expects_external_supervisor = spec
+            .and_then(|s| s.auto_restart.as_ref())
+            .is_some_and(|policy| policy.enabled)
+            && !state.auto_restart_enabled;
+        if expects_external_supervisor {
+            if let Some(status) = self.wait_for_supervisor_replacement(server, state.pid)? {
+                return Ok(status);
             }
+        }
 
-            // Record that a previously running process exited.
-            state.status = ServerStatus::Stopped;
-            state.pid = None;
-            state.updated_at_epoch_secs = now_epoch_secs();
-            self.write_state(server, &state)?;
-            self.append_log(server, "EXIT")?;
-            self.append_audit_event(AuditEvent {
-                timestamp_epoch_secs: now_epoch_secs(),
-                server: server.to_string(),
-                action: "exit".to_string(),
-                pid: old_pid,
-                command: old_command.clone(),
-                args: if old_args.is_empty() {
-                    None
-                } else {
-                    Some(old_args.clone())
-                },
-            })?;
+        // Another writer may have recorded the exit or a replacement meanwhile.
+        let _lock = self.lock_state(server)?;
+        let mut state = self.read_state(server)?;
+        if let Some(status) = self.live_status(server, &state) {
+            return Ok(status);
+        }
+        let old_pid = state.pid;
+        let old_command = state.command.clone();
+        let old_args = state.args.clone();
 
-            // Attempt bounded auto-restart when policy is enabled.
-            if state.auto_restart_enabled && state.restart_attempts < state.max_restarts {
-                if let Some(spec) = spec {
-                    if self.server_denied_by_policy(server)? {
-                        state.status = ServerStatus::Stopped;
-                        state.pid = None;
-                        state.updated_at_epoch_secs = now_epoch_secs();
-                        self.write_state(server, &state)?;
-                        self.append_log(server, "POLICY_DENIED_AUTO_RESTART")?;
-                        self.append_audit_event(AuditEvent {
-                            timestamp_epoch_secs: now_epoch_secs(),
-                            server: server.to_string(),
-                            action: "policy-denied".to_string(),
-                            pid: old_pid,
-                            command: old_command,
-                            args: if old_args.is_empty() {
-                                None
-                            } else {
-                                Some(old_args)
-                            },
-                        })?;
-                        return Ok(ServerStatus::Stopped);
-                    }
+        // Record that a previously running process exited.
+        tracing::info!(server, pid = ?old_pid, "running -> stopped (process exited)");
+        state.status = ServerStatus::Stopped;
+        state.pid = None;
+        state.readiness_log_offset = None;
+        state.readiness_deadline_epoch_ms = None;
+        state.updated_at_epoch_ms = self.clock.now_epoch_ms();
+        match self.write_state(server, &state) {
+            // Status stays readable on a read-only or full home; the exit
+            // is recorded by the next status check that can write.
+            Err(e) if storage::is_storage_error(&e) => {
+                tracing::warn!(server, error = %e, "cannot record process exit");
+                return Ok(ServerStatus::Stopped);
+            }
+            result => result?,
+        }
+        self.append_log(server, "EXIT")?;
+        self.append_audit_event(AuditEvent {
+            server: server.to_string(),
+            action: "exit".to_string(),
+            pid: old_pid,
+            command: old_command.clone(),
+            args: if old_args.is_empty() {
+                None
+            } else {
+                Some(old_args.clone())
+            },
+            ..AuditEvent::now()
+        })?;
 
-                    let child = Command::new(&spec.command)
-                        .args(&spec.args)
-                        .envs(&spec.env)
-                        .stdin(Stdio::null())
-                        .stdout(Stdio::from(self.open_log_append(server)?))
-                        .stderr(Stdio::from(self.open_log_append(server)?))
-                        .spawn()
-                        .map_err(|e| {
-                            io::Error::new(e.kind(), format!("failed to spawn process: {e}"))
-                        })?;
-                    let pid = child.id();
-                    drop(child);
-
-                    state.status = ServerStatus::Running;
-                    state.pid = Some(pid);
-                    state.command = Some(spec.command.clone());
-                    state.args = spec.args.clone();
-                    state.restart_attempts += 1;
-                    state.updated_at_epoch_secs = now_epoch_secs();
+        // Attempt bounded auto-restart when policy is enabled.
+        if state.auto_restart_enabled && state.restart_attempts < state.max_restarts {
+            if let Some(spec) = spec {
+                if self.server_denied_by_policy(server)? {
+                    tracing::info!(server, "auto-restart denied by policy");
+                    state.status = ServerStatus::Stopped;
+                    state.pid = None;
+                    state.updated_at_epoch_ms = self.clock.now_epoch_ms();
                     self.write_state(server, &state)?;
-                    self.append_log(
-                        server,
-                        &format!(
-                            "AUTO_RESTART pid={pid} attempt={}/{}",
-                            state.restart_attempts, state.max_restarts
-                        ),
-                    )?;
+                    self.append_log(server, "POLICY_DENIED_AUTO_RESTART")?;
                     self.append_audit_event(AuditEvent {
-                        timestamp_epoch_secs: now_epoch_secs(),
                         server: server.to_string(),
-                        action: "auto-restart".to_string(),
-                        pid: Some(pid),
-                        command: Some(spec.command.clone()),
-                        args: if spec.args.is_empty() {
+                        action: "policy-denied".to_string(),
+                        pid: old_pid,
+                        command: old_command,
+                        args: if old_args.is_empty() {
                             None
                         } else {
-                            Some(spec.args.clone())
+                            Some(old_args)
                         },
+                        ..AuditEvent::now()
                     })?;
-                    return Ok(ServerStatus::Running);
+                    return Ok(ServerStatus::Stopped);
                 }
+
+                let pid = self.spawn_logged(server, spec)?.id();
+
+                state.status = ServerStatus::Running;
+                state.pid = Some(pid);
+                state.record_spec(spec);
+                state.restart_attempts += 1;
+                state.updated_at_epoch_ms = self.clock.now_epoch_ms();
+                self.write_state(server, &state)?;
+                tracing::info!(
+                    server,
+                    pid,
+                    attempt = state.restart_attempts,
+                    max = state.max_restarts,
+                    "stopped -> running (auto-restart)"
+                );
+                self.append_log(
+                    server,
+                    &format!(
+                        "AUTO_RESTART pid={pid} attempt={}/{}",
+                        state.restart_attempts, state.max_restarts
+                    ),
+                )?;
+                self.append_audit_event(AuditEvent {
+                    server: server.to_string(),
+                    action: "auto-restart".to_string(),
+                    pid: Some(pid),
+                    command: Some(spec.command.clone()),
+                    args: if spec.args.is_empty() {
+                        None
+                    } else {
+                        Some(spec.args.clone())
+                    },
+                    ..AuditEvent::now()
+                })?;
+                return Ok(ServerStatus::Running);
             }
         }
-
+        if state.auto_restart_enabled && state.restart_attempts >= state.max_restarts {
+            self.mark_crash_looping(server, state)?;
+            return Ok(ServerStatus::CrashLooping);
+        }
         Ok(ServerStatus::Stopped)
     }
 
-    /// Waits briefly for an external supervisor to replace a dead pid in state.
+    /// Derives a server's status from its state and live processes, or
+    /// `None` when the state says running but the process has exited.
+    fn live_status(&self, server: &str, state: &RuntimeState) -> Option<ServerStatus> {
+        if let (ServerStatus::Running, Some(scheduler)) = (state.status, state.scheduler_pid) {
+            return Some(if !process_is_alive(scheduler) {
+                ServerStatus::Stopped
+            } else if state.pid.is_some_and(process_is_alive) {
+                ServerStatus::Running
+            } else {
+                ServerStatus::Scheduled
+            });
+        }
+        if state.status != ServerStatus::Running {
+            return Some(state.status);
+        }
+
+        let pid = state.pid?;
+        let alive = process_is_alive(pid);
+        tracing::debug!(server, pid, alive, "liveness check");
+        if !alive {
+            return None;
+        }
+        if state.readiness_log_offset.is_some() {
+            let overdue = state
+                .readiness_deadline_epoch_ms
+                .is_some_and(|deadline| self.clock.now_epoch_ms() > deadline);
+            return Some(if overdue {
+                ServerStatus::Unhealthy
+            } else {
+                ServerStatus::Starting
+            });
+        }
+        if state.health == Some(Health::Unhealthy) {
+            return Some(ServerStatus::Unhealthy);
+        }
+        Some(ServerStatus::Running)
+    }
+
+    /// Records that a server exhausted its auto-restart budget.
+    fn mark_crash_looping(&self, server: &str, mut state: RuntimeState) -> io::Result<()> {
+        tracing::info!(
+            server,
+            attempts = state.restart_attempts,
+            "stopped -> crash-looping (restart budget exhausted)"
+        );
+        let attempts = state.restart_attempts;
+        state.status = ServerStatus::CrashLooping;
+        state.pid = None;
+        state.updated_at_epoch_ms = self.clock.now_epoch_ms();
+        self.write_state(server, &state)?;
+        self.append_log(server, &format!("CRASH_LOOP attempts={attempts}"))?;
+        self.append_audit_event(AuditEvent {
+            server: server.to_string(),
+            action: "crash-loop".to_string(),
+            command: state.command,
+            exit_code: state.last_exit_code,
+            ..AuditEvent::now()
+        })
+    }
+
+    /// Returns the latest health check verdict for a live server, if it has one.
+    pub fn health(&self, server: &str) -> io::Result<Option<Health>> {
+        let state = self.read_state(server)?;
+        let alive =
+            state.status == ServerStatus::Running && state.pid.is_some_and(process_is_alive);
+        Ok(state.health.filter(|_| alive))
+    }
+
+    /// Returns the pid of a live tracked process without mutating state.
+    pub fn running_pid(&self, server: &str) -> io::Result<Option<u32>> {
+        let state = self.read_state(server)?;
+        Ok(state.pid.filter(|pid| process_is_alive(*pid)))
+    }
+
+    /// Waits briefly for an external supervisor to replace a dead pid in state,
+    /// returning the status it settled on, if any.
     fn wait_for_supervisor_replacement(
         &self,
         server: &str,
         old_pid: Option<u32>,
-    ) -> io::Result<bool> {
+    ) -> io::Result<Option<ServerStatus>> {
         for _ in 0..10 {
             thread::sleep(Duration::from_millis(50));
             let state = self.read_state(server)?;
             if state.status != ServerStatus::Running {
-                return Ok(false);
+                // The supervisor already recorded why it stopped.
+                return Ok(Some(state.status));
             }
             if let Some(pid) = state.pid {
                 if Some(pid) != old_pid && process_is_alive(pid) {
-                    return Ok(true);
+                    return Ok(Some(ServerStatus::Running));
                 }
             }
         }
-        Ok(false)
+        Ok(None)
     }
 
     /// Starts a server subprocess and records runtime state.
     pub fn start(&self, server: &str, spec: &ProcessSpec) -> io::Result<StartOutcome> {
+        Ok(match self.start_child(server, spec)? {
+            Some(_) => StartOutcome::Started,
+            None => StartOutcome::AlreadyRunning,
+        })
+    }
+
+    /// Starts a server like [`Self::start`], returning the process handle so the
+    /// caller can collect its exit status, or `None` if it was already running.
+    pub fn start_child(
+        &self,
+        server: &str,
+        spec: &ProcessSpec,
+    ) -> io::Result<Option<Box<dyn ProcessHandle>>> {
         if spec.command.trim().is_empty() {
             return Err(io::Error::new(
                 io::ErrorKind::InvalidInput,
                 "process command must not be empty",
             ));
         }
+        self.preflight()?;
 
+        let _lock = self.lock_state(server)?;
         let mut state = self.read_state(server)?;
         if let Some(pid) = state.pid {
             if process_is_alive(pid) {
+                tracing::debug!(server, pid, "start skipped: process already running");
                 state.status = ServerStatus::Running;
-                state.updated_at_epoch_secs = now_epoch_secs();
+                state.updated_at_epoch_ms = self.clock.now_epoch_ms();
                 self.write_state(server, &state)?;
-                return Ok(StartOutcome::AlreadyRunning);
+                return Ok(None);
             }
 
             state.status = ServerStatus::Stopped;
@@ -305,31 +925,33 @@ impl RuntimeManager {
 
         fs::create_dir_all(self.logs_dir())?;
         let log_file = self.open_log_append(server)?;
-        let err_file = log_file.try_clone()?;
+        let log_offset = log_file.metadata()?.len();
 
-        let child = Command::new(&spec.command)
-            .args(&spec.args)
-            .envs(&spec.env)
-            .stdin(Stdio::null())
-            .stdout(Stdio::from(log_file))
-            .stderr(Stdio::from(err_file))
-            .spawn()
-            .map_err(|e| io::Error::new(e.kind(), format!("failed to spawn process: {e}")))?;
+        tracing::debug!(server, command = %spec.command, args = ?spec.args, "spawning process");
+        let child = self
+            .spawner
+            .spawn(spec, log_file, startup_input(spec).as_deref())?;
         let pid = child.id();
-        drop(child);
 
         state.status = ServerStatus::Running;
         state.pid = Some(pid);
-        state.command = Some(spec.command.clone());
-        state.args = spec.args.clone();
-        state.auto_restart_enabled = spec.auto_restart.map(|p| p.enabled).unwrap_or(false);
-        state.max_restarts = spec.auto_restart.map(|p| p.max_restarts).unwrap_or(0);
+        state.scheduler_pid = None;
+        state.next_run_epoch_ms = None;
+        state.record_spec(spec);
+        state.auto_restart_enabled = spec.auto_restart.as_ref().is_some_and(|p| p.enabled);
+        state.max_restarts = spec.auto_restart.as_ref().map_or(0, |p| p.max_restarts);
         state.restart_attempts = 0;
-        state.updated_at_epoch_secs = now_epoch_secs();
+        state.last_exit_code = None;
+        state.readiness_log_offset = spec.readiness.as_ref().map(|_| log_offset);
+        state.readiness_deadline_epoch_ms = spec
+            .readiness
+            .as_ref()
+            .map(|check| self.clock.now_epoch_ms() + check.timeout_ms);
+        state.updated_at_epoch_ms = self.clock.now_epoch_ms();
         self.write_state(server, &state)?;
         self.append_log(server, &format!("START pid={pid}"))?;
+        tracing::info!(server, pid, "stopped -> running");
         self.append_audit_event(AuditEvent {
-            timestamp_epoch_secs: now_epoch_secs(),
             server: server.to_string(),
             action: "start".to_string(),
             pid: Some(pid),
@@ -339,57 +961,166 @@ impl RuntimeManager {
             } else {
                 Some(spec.args.clone())
             },
+            ..AuditEvent::now()
         })?;
-        Ok(StartOutcome::Started)
+        Ok(Some(child))
+    }
+
+    /// Waits for a started server to pass its readiness probe, then records it as running.
+    ///
+    /// A server that does not pass within the check's timeout is stopped. Works
+    /// from any process, so it also covers servers launched by a supervisor.
+    pub fn await_ready(
+        &self,
+        server: &str,
+        check: &ReadinessCheck,
+    ) -> io::Result<ReadinessOutcome> {
+        let started = Instant::now();
+        let timeout = Duration::from_millis(check.timeout_ms);
+        loop {
+            let lock = self.lock_state(server)?;
+            let mut state = self.read_state(server)?;
+            let Some(pid) = state.pid.filter(|pid| process_is_alive(*pid)) else {
+                tracing::info!(server, "process exited before becoming ready");
+                self.append_log(server, "READINESS_FAILED reason=exited")?;
+                return Ok(ReadinessOutcome::Exited);
+            };
+            let offset = state.readiness_log_offset.unwrap_or_default();
+            if self.probe_passes(server, &check.probe, offset)? {
+                let after_ms = started.elapsed().as_millis() as u64;
+                state.readiness_log_offset = None;
+                state.readiness_deadline_epoch_ms = None;
+                state.updated_at_epoch_ms = self.clock.now_epoch_ms();
+                self.write_state(server, &state)?;
+                tracing::info!(server, pid, after_ms, "starting -> running");
+                self.append_log(server, &format!("READY after_ms={after_ms}"))?;
+                self.append_audit_event(AuditEvent {
+                    server: server.to_string(),
+                    action: "ready".to_string(),
+                    pid: Some(pid),
+                    duration_ms: Some(after_ms),
+                    ..AuditEvent::now()
+                })?;
+                return Ok(ReadinessOutcome::Ready { after_ms });
+            }
+            if started.elapsed() >= timeout {
+                tracing::info!(server, pid, "readiness probe timed out");
+                self.append_log(
+                    server,
+                    &format!("READINESS_TIMEOUT after_ms={}", check.timeout_ms),
+                )?;
+                self.append_audit_event(AuditEvent {
+                    server: server.to_string(),
+                    action: "readiness-timeout".to_string(),
+                    pid: Some(pid),
+                    duration_ms: Some(check.timeout_ms),
+                    ..AuditEvent::now()
+                })?;
+                drop(lock);
+                self.stop(server)?;
+                return Ok(ReadinessOutcome::TimedOut);
+            }
+            drop(lock);
+            thread::sleep(READINESS_POLL);
+        }
+    }
+
+    /// Runs one readiness probe against a server whose output starts at `log_offset`.
+    fn probe_passes(
+        &self,
+        server: &str,
+        probe: &ReadinessProbe,
+        log_offset: u64,
+    ) -> io::Result<bool> {
+        match probe {
+            ReadinessProbe::TcpPort { port } => {
+                let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, *port));
+                Ok(TcpStream::connect_timeout(&addr, READINESS_POLL).is_ok())
+            }
+            ReadinessProbe::LogPattern { pattern } => Ok(self
+                .log_since(server, log_offset)?
+                .lines()
+                .any(|line| line.contains(pattern.as_str()))),
+            ReadinessProbe::McpInitialize => Ok(self
+                .log_since(server, log_offset)?
+                .lines()
+                .any(answers_readiness_request)),
+        }
+    }
+
+    /// Returns log content written after `offset`.
+    fn log_since(&self, server: &str, offset: u64) -> io::Result<String> {
+        let content = match fs::read(self.log_path(server)) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(String::new()),
+            Err(e) => return Err(e),
+        };
+        let start = usize::try_from(offset)
+            .unwrap_or(usize::MAX)
+            .min(content.len());
+        Ok(String::from_utf8_lossy(&content[start..]).into_owned())
     }
 
     /// Stops a running server subprocess and records runtime state.
+    ///
+    /// The server gets the shutdown signal and grace period recorded when it
+    /// was started, then is force-killed.
     pub fn stop(&self, server: &str) -> io::Result<StopOutcome> {
+        self.preflight()?;
+        let lock = self.lock_state(server)?;
         let mut state = self.read_state(server)?;
         let old_pid = state.pid;
         let old_command = state.command.clone();
         let old_args = state.args.clone();
+        let shutdown = state.shutdown();
         let mut outcome = StopOutcome::AlreadyStopped;
         let pid_to_stop = state.pid.filter(|pid| process_is_alive(*pid));
 
         if pid_to_stop.is_some() || state.status == ServerStatus::Running {
             outcome = StopOutcome::Stopped;
         }
+        tracing::debug!(server, pid = ?pid_to_stop, ?outcome, "stop requested");
 
         // Mark stopped before signaling so a background supervisor can observe intent and exit.
         state.status = ServerStatus::Stopped;
         state.pid = None;
         state.restart_attempts = 0;
-        state.updated_at_epoch_secs = now_epoch_secs();
+        state.readiness_log_offset = None;
+        state.readiness_deadline_epoch_ms = None;
+        state.scheduler_pid = None;
+        state.next_run_epoch_ms = None;
+        state.updated_at_epoch_ms = self.clock.now_epoch_ms();
         self.write_state(server, &state)?;
         self.append_log(server, "STOP")?;
+        // Signaling can take the whole grace period; others may read and write meanwhile.
+        drop(lock);
 
         if let Some(pid) = pid_to_stop {
-            terminate_process(pid)?;
+            terminate_process(pid, shutdown)?;
         }
 
-        // Close a narrow race where a supervisor could spawn a replacement pid concurrently.
+        // A supervisor that spawned a replacement just before the stopped
+        // state was written records its pid afterwards; stop that one too.
         for _ in 0..5 {
+            let lock = self.lock_state(server)?;
             let latest = self.read_state(server)?;
-            let Some(pid) = latest.pid else {
+            let Some(pid) = latest.pid.filter(|pid| process_is_alive(*pid)) else {
                 break;
             };
-            if !process_is_alive(pid) {
-                break;
-            }
-            terminate_process(pid)?;
+            let shutdown = latest.shutdown();
             let mut reset = latest;
             reset.status = ServerStatus::Stopped;
             reset.pid = None;
             reset.restart_attempts = 0;
-            reset.updated_at_epoch_secs = now_epoch_secs();
+            reset.updated_at_epoch_ms = self.clock.now_epoch_ms();
             self.write_state(server, &reset)?;
+            drop(lock);
+            terminate_process(pid, shutdown)?;
             thread::sleep(Duration::from_millis(20));
         }
 
         if outcome == StopOutcome::Stopped {
             self.append_audit_event(AuditEvent {
-                timestamp_epoch_secs: now_epoch_secs(),
                 server: server.to_string(),
                 action: "stop".to_string(),
                 pid: old_pid,
@@ -399,6 +1130,7 @@ impl RuntimeManager {
                 } else {
                     Some(old_args)
                 },
+                ..AuditEvent::now()
             })?;
         }
         Ok(outcome)
@@ -410,7 +1142,6 @@ impl RuntimeManager {
         let _ = self.start(server, spec)?;
         let state = self.read_state(server)?;
         self.append_audit_event(AuditEvent {
-            timestamp_epoch_secs: now_epoch_secs(),
             server: server.to_string(),
             action: "restart".to_string(),
             pid: state.pid,
@@ -420,193 +1151,1012 @@ impl RuntimeManager {
             } else {
                 Some(state.args)
             },
+            ..AuditEvent::now()
         })?;
         Ok(())
     }
 
-    /// Runs a tokio-backed supervision loop for one server until stopped.
-    pub fn run_supervisor(&self, server: &str, spec: &ProcessSpec) -> io::Result<()> {
-        let policy = match spec.auto_restart {
-            Some(policy) if policy.enabled => policy,
-            _ => return Ok(()),
-        };
-
-        let runtime = tokio::runtime::Builder::new_current_thread()
-            .enable_time()
-            .build()
-            .map_err(|e| io::Error::other(format!("failed to build tokio runtime: {e}")))?;
-
-        runtime.block_on(self.run_supervisor_loop(server, spec, policy))
-    }
-
-    /// Async supervision loop that monitors pid transitions and performs bounded restarts.
-    async fn run_supervisor_loop(
+    /// Records an already running process as `server`'s, so `stop`, `restart`
+    /// and `status` manage it like a process Berth started.
+    ///
+    /// Returns `AlreadyRunning` when Berth already tracks `pid` for the server.
+    /// A dead pid, a server running under another pid, or a pid tracked for
+    /// another server is an error.
+    pub fn adopt(
         &self,
         server: &str,
-        spec: &ProcessSpec,
-        policy: AutoRestartPolicy,
-    ) -> io::Result<()> {
-        let poll_interval = Duration::from_millis(100);
-        let mut restart_attempts = self.read_state(server)?.restart_attempts;
-
-        loop {
-            let state = self.read_state(server)?;
-            if state.status != ServerStatus::Running {
-                return Ok(());
+        pid: u32,
+        shutdown_signal: Option<ShutdownSignal>,
+        shutdown_grace_period_secs: Option<u64>,
+    ) -> io::Result<StartOutcome> {
+        self.preflight()?;
+        if !process_is_alive(pid) {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no running process with pid {pid}"),
+            ));
+        }
+        if let Some(owner) = self.managed_pids()?.remove(&pid) {
+            if owner == server {
+                return Ok(StartOutcome::AlreadyRunning);
             }
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("pid {pid} is already managed as {owner}"),
+            ));
+        }
+        let _lock = self.lock_state(server)?;
+        let mut state = self.read_state(server)?;
+        if let Some(current) = state.pid.filter(|pid| process_is_alive(*pid)) {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{server} is already running as pid {current}"),
+            ));
+        }
 
-            let monitored_pid = match state.pid {
-                Some(pid) => pid,
-                None => {
-                    tokio::time::sleep(poll_interval).await;
-                    continue;
-                }
-            };
+        let mut command_line = process_command_line(pid).unwrap_or_default().into_iter();
+        state.status = ServerStatus::Running;
+        state.pid = Some(pid);
+        state.command = command_line.next();
+        state.args = command_line.collect();
+        state.shutdown_signal = shutdown_signal;
+        state.shutdown_grace_period_secs = shutdown_grace_period_secs;
+        state.auto_restart_enabled = false;
+        state.max_restarts = 0;
+        state.restart_attempts = 0;
+        state.last_exit_code = None;
+        state.readiness_log_offset = None;
+        state.readiness_deadline_epoch_ms = None;
+        state.health = None;
+        state.health_checked_at_epoch_ms = None;
+        state.scheduler_pid = None;
+        state.next_run_epoch_ms = None;
+        state.updated_at_epoch_ms = self.clock.now_epoch_ms();
+        self.write_state(server, &state)?;
+        self.append_log(server, &format!("ADOPT pid={pid}"))?;
+        tracing::info!(server, pid, "adopted external process");
+        self.append_audit_event(AuditEvent {
+            server: server.to_string(),
+            action: "adopt".to_string(),
+            pid: Some(pid),
+            command: state.command,
+            args: (!state.args.is_empty()).then_some(state.args),
+            ..AuditEvent::now()
+        })?;
+        Ok(StartOutcome::Started)
+    }
 
+    /// Copies what an adopted process appends to `source` (its redirected
+    /// output) into the server log, until the process exits or Berth no
+    /// longer tracks it as the server.
+    ///
+    /// Output written before the call is left out; a truncated source is read
+    /// again from its start.
+    pub fn relay_output(&self, server: &str, pid: u32, source: &Path) -> io::Result<()> {
+        let mut input = File::open(source)?;
+        let mut position = input.seek(SeekFrom::End(0))?;
+        let mut buf = vec![0u8; 8192];
+        loop {
+            if input.metadata()?.len() < position {
+                position = input.seek(SeekFrom::Start(0))?;
+            }
+            let mut copied = false;
             loop {
-                if !process_is_alive(monitored_pid) {
+                let read = input.read(&mut buf)?;
+                if read == 0 {
                     break;
                 }
-                tokio::time::sleep(poll_interval).await;
-                let latest = self.read_state(server)?;
-                if latest.status != ServerStatus::Running {
-                    return Ok(());
-                }
-                if latest.pid != Some(monitored_pid) {
-                    // Another process took ownership; this supervisor exits.
-                    return Ok(());
+                self.open_log_append(server)?.write_all(&buf[..read])?;
+                position += read as u64;
+                copied = true;
+            }
+            if copied {
+                continue;
+            }
+            let tracked = self.read_state(server)?.pid == Some(pid);
+            if !tracked || !process_is_alive(pid) {
+                return Ok(());
+            }
+            thread::sleep(RELAY_POLL);
+        }
+    }
+
+    /// Maps the pid of every live process Berth tracks to its server.
+    pub fn managed_pids(&self) -> io::Result<BTreeMap<u32, String>> {
+        let mut pids = BTreeMap::new();
+        let dir = self.runtime_dir();
+        if !dir.exists() {
+            return Ok(pids);
+        }
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|ext| ext != "toml") {
+                continue;
+            }
+            let Some(server) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+            let Ok(state) = self.read_state(server) else {
+                continue;
+            };
+            for pid in [state.pid, state.scheduler_pid].into_iter().flatten() {
+                if process_is_alive(pid) {
+                    pids.insert(pid, server.to_string());
                 }
             }
+        }
+        Ok(pids)
+    }
+
+    /// Arms a scheduled job with this process as its scheduler.
+    ///
+    /// Returns `AlreadyRunning` when a live scheduler or process already owns
+    /// the name. The caller then drives the runs with [`Self::run_job_schedule`].
+    pub fn schedule_job(&self, server: &str, spec: &ProcessSpec) -> io::Result<StartOutcome> {
+        let Some(schedule) = spec.schedule else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "process spec has no schedule",
+            ));
+        };
+        if spec.command.trim().is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "process command must not be empty",
+            ));
+        }
+        self.preflight()?;
 
-            let state_after_exit = self.read_state(server)?;
-            if state_after_exit.status != ServerStatus::Running {
+        let _lock = self.lock_state(server)?;
+        let mut state = self.read_state(server)?;
+        let owned = state.status == ServerStatus::Running
+            && (state.scheduler_pid.is_some_and(process_is_alive)
+                || state.pid.is_some_and(process_is_alive));
+        if owned {
+            tracing::debug!(server, "schedule skipped: already running");
+            return Ok(StartOutcome::AlreadyRunning);
+        }
+
+        let now = self.clock.now_epoch_ms();
+        let next_run = schedule.next_after(now);
+        let scheduler = std::process::id();
+        state.status = ServerStatus::Running;
+        state.pid = None;
+        state.scheduler_pid = Some(scheduler);
+        state.next_run_epoch_ms = Some(next_run);
+        state.record_spec(spec);
+        state.auto_restart_enabled = false;
+        state.restart_attempts = 0;
+        state.last_exit_code = None;
+        state.readiness_log_offset = None;
+        state.readiness_deadline_epoch_ms = None;
+        state.updated_at_epoch_ms = now;
+        self.write_state(server, &state)?;
+        self.append_log(
+            server,
+            &format!("SCHEDULE {schedule} next_run_epoch_ms={next_run}"),
+        )?;
+        tracing::info!(server, scheduler, next_run, "stopped -> scheduled");
+        self.append_audit_event(AuditEvent {
+            server: server.to_string(),
+            action: "schedule".to_string(),
+            pid: Some(scheduler),
+            command: Some(spec.command.clone()),
+            args: if spec.args.is_empty() {
+                None
+            } else {
+                Some(spec.args.clone())
+            },
+            ..AuditEvent::now()
+        })?;
+        Ok(StartOutcome::Started)
+    }
+
+    /// Runs a job armed by [`Self::schedule_job`] in this process each time it
+    /// is due, until it is stopped.
+    ///
+    /// Each run appends its output to the job's log and is recorded in its run
+    /// history (see [`Self::job_runs`]) and the audit log.
+    pub fn run_job_schedule(&self, server: &str, spec: &ProcessSpec) -> io::Result<()> {
+        let Some(schedule) = spec.schedule else {
+            return Ok(());
+        };
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .map_err(|e| io::Error::other(format!("failed to build tokio runtime: {e}")))?;
+        runtime.block_on(self.run_job_loop(server, spec, schedule))
+    }
+
+    async fn run_job_loop(
+        &self,
+        server: &str,
+        spec: &ProcessSpec,
+        schedule: JobSchedule,
+    ) -> io::Result<()> {
+        let poll_interval = Duration::from_millis(100);
+        let scheduler = std::process::id();
+        loop {
+            let state = self.read_state(server)?;
+            if !state.scheduled_by(scheduler) {
                 return Ok(());
             }
-            if state_after_exit.pid != Some(monitored_pid) {
-                return Ok(());
+            let due = state.next_run_epoch_ms.unwrap_or_default();
+            if self.clock.now_epoch_ms() < due {
+                self.clock.sleep(poll_interval).await;
+                continue;
             }
 
-            self.append_log(server, "EXIT")?;
+            let started_at = self.clock.now_epoch_ms();
+            let mut child = self.spawn_logged(server, spec)?;
+            let pid = child.id();
+            {
+                let _lock = self.lock_state(server)?;
+                let mut state = self.read_state(server)?;
+                if !state.scheduled_by(scheduler) {
+                    let _ = child.terminate();
+                    return Ok(());
+                }
+                state.pid = Some(pid);
+                state.updated_at_epoch_ms = started_at;
+                self.write_state(server, &state)?;
+            }
+            self.append_log(server, &format!("JOB_START pid={pid}"))?;
+            tracing::info!(server, pid, "scheduled -> running");
             self.append_audit_event(AuditEvent {
-                timestamp_epoch_secs: now_epoch_secs(),
                 server: server.to_string(),
-                action: "exit".to_string(),
-                pid: Some(monitored_pid),
-                command: state_after_exit.command.clone(),
-                args: if state_after_exit.args.is_empty() {
+                action: "job-start".to_string(),
+                pid: Some(pid),
+                command: Some(spec.command.clone()),
+                args: if spec.args.is_empty() {
                     None
                 } else {
-                    Some(state_after_exit.args.clone())
+                    Some(spec.args.clone())
                 },
+                ..AuditEvent::now()
             })?;
 
-            if restart_attempts >= policy.max_restarts {
-                let mut stopped_state = state_after_exit;
-                stopped_state.status = ServerStatus::Stopped;
-                stopped_state.pid = None;
-                stopped_state.updated_at_epoch_secs = now_epoch_secs();
-                stopped_state.restart_attempts = restart_attempts;
-                self.write_state(server, &stopped_state)?;
-                return Ok(());
-            }
-
-            if self.server_denied_by_policy(server)? {
-                let mut stopped_state = self.read_state(server)?;
-                stopped_state.status = ServerStatus::Stopped;
-                stopped_state.pid = None;
-                stopped_state.updated_at_epoch_secs = now_epoch_secs();
-                self.write_state(server, &stopped_state)?;
-                self.append_log(server, "POLICY_DENIED_AUTO_RESTART")?;
-                self.append_audit_event(AuditEvent {
-                    timestamp_epoch_secs: now_epoch_secs(),
-                    server: server.to_string(),
-                    action: "policy-denied".to_string(),
-                    pid: Some(monitored_pid),
-                    command: stopped_state.command.clone(),
-                    args: if stopped_state.args.is_empty() {
-                        None
-                    } else {
-                        Some(stopped_state.args.clone())
-                    },
-                })?;
-                return Ok(());
-            }
+            let exit = loop {
+                if let Some(exit) = child.try_wait()? {
+                    break exit;
+                }
+                self.clock.sleep(poll_interval).await;
+                // A stop terminates the run itself.
+                if !self.read_state(server)?.scheduled_by(scheduler) {
+                    return Ok(());
+                }
+            };
 
-            let control_state = self.read_state(server)?;
-            if control_state.status != ServerStatus::Running
-                || control_state.pid != Some(monitored_pid)
+            let finished_at = self.clock.now_epoch_ms();
+            let run = JobRun {
+                started_at_epoch_ms: started_at,
+                duration_ms: finished_at.saturating_sub(started_at),
+                pid,
+                exit_code: exit.and_then(|exit| exit.exit_code()),
+            };
+            jsonl::append(&self.job_runs_path(server), &run)?;
+            let next_run = schedule.next_after(finished_at);
             {
-                return Ok(());
+                let _lock = self.lock_state(server)?;
+                let mut state = self.read_state(server)?;
+                if !state.scheduled_by(scheduler) {
+                    return Ok(());
+                }
+                state.pid = None;
+                state.last_exit_code = run.exit_code;
+                state.next_run_epoch_ms = Some(next_run);
+                state.updated_at_epoch_ms = finished_at;
+                self.write_state(server, &state)?;
             }
+            let code = run
+                .exit_code
+                .map(|code| format!(" code={code}"))
+                .unwrap_or_default();
+            self.append_log(
+                server,
+                &format!("JOB_EXIT{code} duration_ms={}", run.duration_ms),
+            )?;
+            tracing::info!(server, pid, code = ?run.exit_code, next_run, "running -> scheduled");
+            self.append_audit_event(AuditEvent {
+                server: server.to_string(),
+                action: "job-exit".to_string(),
+                pid: Some(pid),
+                exit_code: run.exit_code,
+                duration_ms: Some(run.duration_ms),
+                error: Some(!run.succeeded()),
+                ..AuditEvent::now()
+            })?;
+        }
+    }
 
-            let log_file = self.open_log_append(server)?;
-            let err_file = log_file.try_clone()?;
-            let child = Command::new(&spec.command)
-                .args(&spec.args)
-                .envs(&spec.env)
-                .stdin(Stdio::null())
-                .stdout(Stdio::from(log_file))
-                .stderr(Stdio::from(err_file))
-                .spawn()
-                .map_err(|e| io::Error::new(e.kind(), format!("failed to spawn process: {e}")))?;
-            let pid = child.id();
-            drop(child);
+    /// Returns up to `limit` of a job's most recent runs, oldest first.
+    pub fn job_runs(&self, server: &str, limit: usize) -> io::Result<Vec<JobRun>> {
+        let mut runs: Vec<JobRun> = jsonl::read(&self.job_runs_path(server))?;
+        let skip = runs.len().saturating_sub(limit);
+        runs.drain(..skip);
+        Ok(runs)
+    }
+
+    /// Returns when a scheduled job runs next, while its scheduler is alive.
+    pub fn next_job_run(&self, server: &str) -> io::Result<Option<u64>> {
+        let state = self.read_state(server)?;
+        let scheduled = state.status == ServerStatus::Running
+            && state.scheduler_pid.is_some_and(process_is_alive);
+        Ok(state.next_run_epoch_ms.filter(|_| scheduled))
+    }
+
+    /// Runs a tokio-backed supervision loop for one server until stopped.
+    pub fn run_supervisor(&self, server: &str, spec: &ProcessSpec) -> io::Result<()> {
+        self.run_supervisor_with_refresh(server, spec, || {
+            Err("no refresh handler configured".to_string())
+        })
+    }
+
+    /// Runs the supervision loop, relaunching the server with the spec returned
+    /// by `refresh` once `refresh_at_epoch_secs` passes.
+    ///
+    /// A failed refresh is logged and retried after [`REFRESH_RETRY_SECS`]
+    /// while the current process keeps running.
+    pub fn run_supervisor_with_refresh<F>(
+        &self,
+        server: &str,
+        spec: &ProcessSpec,
+        refresh: F,
+    ) -> io::Result<()>
+    where
+        F: FnMut() -> Result<ProcessSpec, String>,
+    {
+        self.supervise(server, spec, refresh, None)
+    }
 
-            // Stop could have raced with this spawn; terminate immediately if so.
-            if self.read_state(server)?.status != ServerStatus::Running {
-                let _ = terminate_process(pid);
+    /// Supervises a server launched by [`Self::start_child`] in this process.
+    ///
+    /// Unlike [`Self::run_supervisor_with_refresh`], which watches a pid started
+    /// elsewhere, the supervisor then owns every process it watches and sees
+    /// their exit codes, which `restart_on_exit_codes` needs.
+    pub fn supervise_child<F>(
+        &self,
+        server: &str,
+        spec: &ProcessSpec,
+        child: Box<dyn ProcessHandle>,
+        refresh: F,
+    ) -> io::Result<()>
+    where
+        F: FnMut() -> Result<ProcessSpec, String>,
+    {
+        self.supervise(server, spec, refresh, Some(child))
+    }
+
+    /// Like [`Self::supervise_child`], but also watches servers that need no
+    /// supervision, recording their exit instead of restarting them.
+    ///
+    /// For a process that outlives the command that started the server, such
+    /// as `berth daemon`, so it reaps every server it launched.
+    pub fn watch_child<F>(
+        &self,
+        server: &str,
+        spec: &ProcessSpec,
+        child: Box<dyn ProcessHandle>,
+        refresh: F,
+    ) -> io::Result<()>
+    where
+        F: FnMut() -> Result<ProcessSpec, String>,
+    {
+        let policy = supervision_policy(spec).unwrap_or(AutoRestartPolicy {
+            enabled: false,
+            max_restarts: 0,
+            restart_on_exit_codes: None,
+        });
+        self.run_supervision(server, spec, policy, refresh, Some(child))
+    }
+
+    fn supervise<F>(
+        &self,
+        server: &str,
+        spec: &ProcessSpec,
+        refresh: F,
+        owned: Option<Box<dyn ProcessHandle>>,
+    ) -> io::Result<()>
+    where
+        F: FnMut() -> Result<ProcessSpec, String>,
+    {
+        let Some(policy) = supervision_policy(spec) else {
+            return Ok(());
+        };
+        self.run_supervision(server, spec, policy, refresh, owned)
+    }
+
+    fn run_supervision<F>(
+        &self,
+        server: &str,
+        spec: &ProcessSpec,
+        policy: AutoRestartPolicy,
+        refresh: F,
+        owned: Option<Box<dyn ProcessHandle>>,
+    ) -> io::Result<()>
+    where
+        F: FnMut() -> Result<ProcessSpec, String>,
+    {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .map_err(|e| io::Error::other(format!("failed to build tokio runtime: {e}")))?;
+
+        runtime.block_on(self.run_supervisor_loop(server, spec.clone(), policy, refresh, owned))
+    }
+
+    /// Async supervision loop that monitors pid transitions and performs bounded restarts.
+    ///
+    /// `owned` is the process the caller spawned, if any; replacements come
+    /// from the manager's spawner and timing goes through its clock.
+    async fn run_supervisor_loop<F>(
+        &self,
+        server: &str,
+        mut spec: ProcessSpec,
+        policy: AutoRestartPolicy,
+        mut refresh: F,
+        mut owned: Option<Box<dyn ProcessHandle>>,
+    ) -> io::Result<()>
+    where
+        F: FnMut() -> Result<ProcessSpec, String>,
+    {
+        let poll_interval = Duration::from_millis(100);
+        let mut restart_attempts = self.read_state_off_executor(server).await?.restart_attempts;
+
+        loop {
+            let state = self.read_state_off_executor(server).await?;
+            if state.status != ServerStatus::Running {
                 return Ok(());
             }
 
+            let Some(pid) = state.pid else {
+                self.clock.sleep(poll_interval).await;
+                continue;
+            };
+            let mut process = match owned.take() {
+                Some(process) if process.id() == pid => process,
+                _ => Box::new(PidHandle(pid)),
+            };
+            // The first probe runs one interval in, giving the server time to start.
+            let mut next_health_check_ms = self.clock.now_epoch_ms();
+            let mut health_failures = 0;
+            if let Some(check) = &spec.health_check {
+                next_health_check_ms += check.interval_secs.saturating_mul(1_000);
+            }
+
+            let exit = loop {
+                if let Some(exit) = process.try_wait()? {
+                    break exit;
+                }
+                if let Some(check) = &spec.health_check {
+                    let now = self.clock.now_epoch_ms();
+                    if now >= next_health_check_ms {
+                        next_health_check_ms = now + check.interval_secs.saturating_mul(1_000);
+                        let (name, probe_spec, check) =
+                            (server.to_string(), spec.clone(), check.clone());
+                        (process, health_failures) = self
+                            .off_executor(move |manager| {
+                                let mut failures = health_failures;
+                                manager.check_health(
+                                    &name,
+                                    &probe_spec,
+                                    &check,
+                                    process.as_mut(),
+                                    &mut failures,
+                                )?;
+                                Ok((process, failures))
+                            })
+                            .await?;
+                    }
+                }
+                if spec
+                    .refresh_at_epoch_secs
+                    .is_some_and(|at| self.clock.now_epoch_secs() >= at)
+                {
+                    match refresh() {
+                        Ok(fresh) => {
+                            let (name, next_spec) = (server.to_string(), fresh.clone());
+                            let relaunched = self
+                                .off_executor(move |manager| {
+                                    manager.relaunch(&name, process.as_mut(), &next_spec)
+                                })
+                                .await?;
+                            let Some(next) = relaunched else {
+                                return Ok(());
+                            };
+                            process = next;
+                            spec = fresh;
+                            health_failures = 0;
+                        }
+                        Err(msg) => {
+                            tracing::warn!(server, error = %msg, "supervisor refresh failed");
+                            let (name, line) =
+                                (server.to_string(), format!("REFRESH_FAILED {msg}"));
+                            self.off_executor(move |manager| manager.append_log(&name, &line))
+                                .await?;
+                            spec.refresh_at_epoch_secs =
+                                Some(self.clock.now_epoch_secs() + REFRESH_RETRY_SECS);
+                        }
+                    }
+                }
+                self.clock.sleep(poll_interval).await;
+                let latest = self.read_state_off_executor(server).await?;
+                if latest.status != ServerStatus::Running {
+                    return Ok(());
+                }
+                if latest.pid != Some(process.id()) {
+                    // Another process took ownership; this supervisor exits.
+                    return Ok(());
+                }
+            };
+            let monitored_pid = process.id();
+
+            let replacement = {
+                let (server, spec, policy) = (server.to_string(), spec.clone(), policy.clone());
+                self.off_executor(move |manager| {
+                    manager.handle_supervised_exit(
+                        &server,
+                        &spec,
+                        &policy,
+                        monitored_pid,
+                        exit,
+                        restart_attempts,
+                    )
+                })
+                .await?
+            };
+            let Some(child) = replacement else {
+                return Ok(());
+            };
+            owned = Some(child);
             restart_attempts += 1;
-            let mut restarted_state = self.read_state(server)?;
-            restarted_state.status = ServerStatus::Running;
-            restarted_state.pid = Some(pid);
-            restarted_state.command = Some(spec.command.clone());
-            restarted_state.args = spec.args.clone();
-            restarted_state.updated_at_epoch_secs = now_epoch_secs();
-            restarted_state.restart_attempts = restart_attempts;
-            self.write_state(server, &restarted_state)?;
+        }
+    }
+
+    /// Runs a blocking step of the supervision loop on tokio's blocking pool,
+    /// so probes, restarts, and state file access do not stall the other
+    /// futures sharing the runtime.
+    async fn off_executor<T, F>(&self, call: F) -> io::Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(RuntimeManager) -> io::Result<T> + Send + 'static,
+    {
+        let manager = self.clone();
+        tokio::task::spawn_blocking(move || call(manager))
+            .await
+            .map_err(|e| io::Error::other(format!("runtime task failed: {e}")))?
+    }
+
+    /// Reads a server's state file on the blocking pool.
+    async fn read_state_off_executor(&self, server: &str) -> io::Result<RuntimeState> {
+        let server = server.to_string();
+        self.off_executor(move |manager| manager.read_state(&server))
+            .await
+    }
+
+    /// Records a supervised process's exit and restarts it if the policy
+    /// allows, holding the state lock until any replacement is running.
+    ///
+    /// Returns the replacement, or `None` when supervision should end.
+    fn handle_supervised_exit(
+        &self,
+        server: &str,
+        spec: &ProcessSpec,
+        policy: &AutoRestartPolicy,
+        monitored_pid: u32,
+        exit: Option<ProcessExit>,
+        restart_attempts: u32,
+    ) -> io::Result<Option<Box<dyn ProcessHandle>>> {
+        // Held until the exit is recorded and any replacement is running.
+        let _lock = self.lock_state(server)?;
+        let mut state_after_exit = self.read_state(server)?;
+        if state_after_exit.status != ServerStatus::Running {
+            return Ok(None);
+        }
+        if state_after_exit.pid != Some(monitored_pid) {
+            return Ok(None);
+        }
+
+        let exit_code = exit.and_then(|exit| exit.exit_code());
+        state_after_exit.last_exit_code = exit_code;
+        self.write_state(server, &state_after_exit)?;
+        match exit_code {
+            Some(code) => self.append_log(server, &format!("EXIT code={code}"))?,
+            None => self.append_log(server, "EXIT")?,
+        }
+        let crash = exit.and_then(|exit| exit.crash());
+        let seccomp_violation =
+            spec.seccomp.is_some() && crash.is_some_and(|(signal, _)| signal == SIGSYS);
+        if seccomp_violation {
+            tracing::warn!(server, "server killed by its seccomp filter");
+            self.append_log(server, "SECCOMP_VIOLATION signal=SIGSYS")?;
+        }
+        if let (true, Some((signal, core_dumped))) = (spec.capture_core || seccomp_violation, crash)
+        {
+            let report =
+                self.write_crash_report(server, monitored_pid, spec, signal, core_dumped)?;
+            tracing::warn!(server, signal, report = %report.display(), "server crashed");
             self.append_log(
                 server,
-                &format!(
-                    "AUTO_RESTART pid={pid} attempt={}/{}",
-                    restart_attempts, policy.max_restarts
-                ),
+                &format!("CRASH signal={signal} report={}", report.display()),
             )?;
+        }
+        self.append_audit_event(AuditEvent {
+            server: server.to_string(),
+            action: "exit".to_string(),
+            pid: Some(monitored_pid),
+            command: state_after_exit.command.clone(),
+            args: if state_after_exit.args.is_empty() {
+                None
+            } else {
+                Some(state_after_exit.args.clone())
+            },
+            exit_code,
+            ..AuditEvent::now()
+        })?;
+
+        if !policy.restarts_on(exit_code) {
+            tracing::info!(server, code = ?exit_code, "supervisor stopping: clean exit");
+            let mut stopped_state = state_after_exit;
+            stopped_state.status = ServerStatus::Stopped;
+            stopped_state.pid = None;
+            stopped_state.updated_at_epoch_ms = self.clock.now_epoch_ms();
+            stopped_state.restart_attempts = restart_attempts;
+            self.write_state(server, &stopped_state)?;
+            if let Some(code) = exit_code {
+                self.append_log(server, &format!("CLEAN_EXIT code={code}"))?;
+            }
+            return Ok(None);
+        }
+
+        if restart_attempts >= policy.max_restarts {
+            tracing::info!(
+                server,
+                attempts = restart_attempts,
+                "supervisor giving up: restart budget exhausted"
+            );
+            let mut exhausted_state = state_after_exit;
+            exhausted_state.restart_attempts = restart_attempts;
+            if policy.enabled {
+                self.mark_crash_looping(server, exhausted_state)?;
+            } else {
+                exhausted_state.status = ServerStatus::Stopped;
+                exhausted_state.pid = None;
+                exhausted_state.updated_at_epoch_ms = self.clock.now_epoch_ms();
+                self.write_state(server, &exhausted_state)?;
+            }
+            return Ok(None);
+        }
+
+        if self.server_denied_by_policy(server)? {
+            tracing::info!(server, "supervisor restart denied by policy");
+            let mut stopped_state = self.read_state(server)?;
+            stopped_state.status = ServerStatus::Stopped;
+            stopped_state.pid = None;
+            stopped_state.updated_at_epoch_ms = self.clock.now_epoch_ms();
+            self.write_state(server, &stopped_state)?;
+            self.append_log(server, "POLICY_DENIED_AUTO_RESTART")?;
             self.append_audit_event(AuditEvent {
-                timestamp_epoch_secs: now_epoch_secs(),
                 server: server.to_string(),
-                action: "auto-restart".to_string(),
-                pid: Some(pid),
-                command: Some(spec.command.clone()),
-                args: if spec.args.is_empty() {
+                action: "policy-denied".to_string(),
+                pid: Some(monitored_pid),
+                command: stopped_state.command.clone(),
+                args: if stopped_state.args.is_empty() {
                     None
                 } else {
-                    Some(spec.args.clone())
+                    Some(stopped_state.args.clone())
                 },
+                ..AuditEvent::now()
             })?;
+            return Ok(None);
+        }
+
+        let control_state = self.read_state(server)?;
+        if control_state.status != ServerStatus::Running || control_state.pid != Some(monitored_pid)
+        {
+            return Ok(None);
+        }
+
+        let mut child = self.spawn_logged(server, spec)?;
+        let pid = child.id();
+
+        // Stop could have raced with this spawn; terminate immediately if so.
+        if self.read_state(server)?.status != ServerStatus::Running {
+            let _ = child.terminate();
+            return Ok(None);
+        }
+        let restart_attempts = restart_attempts + 1;
+
+        tracing::info!(
+            server,
+            pid,
+            attempt = restart_attempts,
+            max = policy.max_restarts,
+            "supervisor restarted process"
+        );
+        let mut restarted_state = self.read_state(server)?;
+        restarted_state.status = ServerStatus::Running;
+        restarted_state.pid = Some(pid);
+        restarted_state.record_spec(spec);
+        restarted_state.updated_at_epoch_ms = self.clock.now_epoch_ms();
+        restarted_state.restart_attempts = restart_attempts;
+        self.write_state(server, &restarted_state)?;
+        self.append_log(
+            server,
+            &format!(
+                "AUTO_RESTART pid={pid} attempt={}/{}",
+                restart_attempts, policy.max_restarts
+            ),
+        )?;
+        self.append_audit_event(AuditEvent {
+            server: server.to_string(),
+            action: "auto-restart".to_string(),
+            pid: Some(pid),
+            command: Some(spec.command.clone()),
+            args: if spec.args.is_empty() {
+                None
+            } else {
+                Some(spec.args.clone())
+            },
+            ..AuditEvent::now()
+        })?;
+        Ok(Some(child))
+    }
+
+    /// Runs one health probe against a supervised process and records the
+    /// verdict: healthy after a pass, unhealthy after `failure_threshold`
+    /// consecutive failures. Changes of verdict are logged and audited.
+    fn check_health(
+        &self,
+        server: &str,
+        spec: &ProcessSpec,
+        check: &HealthCheck,
+        process: &mut dyn ProcessHandle,
+        failures: &mut u32,
+    ) -> io::Result<()> {
+        let passed = match self.health_probe_passes(server, spec, check, process) {
+            Ok(passed) => passed,
+            // A process this supervisor did not spawn has no stdin to ping.
+            Err(e) if e.kind() == io::ErrorKind::Unsupported => return Ok(()),
+            Err(e) => {
+                tracing::debug!(server, error = %e, "health probe failed to run");
+                false
+            }
+        };
+        *failures = if passed { 0 } else { *failures + 1 };
+        let verdict = if passed {
+            Some(Health::Healthy)
+        } else if *failures >= check.failure_threshold {
+            Some(Health::Unhealthy)
+        } else {
+            None
+        };
+
+        let lock = self.lock_state(server)?;
+        let mut state = self.read_state(server)?;
+        if state.status != ServerStatus::Running || state.pid != Some(process.id()) {
+            return Ok(());
         }
+        let previous = state.health;
+        state.health = verdict.or(previous);
+        state.health_checked_at_epoch_ms = Some(self.clock.now_epoch_ms());
+        self.write_state(server, &state)?;
+        drop(lock);
+
+        let action = match (previous, verdict) {
+            (Some(Health::Unhealthy), Some(Health::Healthy)) => "health-recovered",
+            (Some(Health::Healthy) | None, Some(Health::Unhealthy)) => "health-failed",
+            _ => return Ok(()),
+        };
+        tracing::info!(server, failures = *failures, "{action}");
+        match verdict {
+            Some(Health::Unhealthy) => self.append_log(
+                server,
+                &format!("HEALTH_FAILED probe={} failures={failures}", check.probe),
+            )?,
+            _ => self.append_log(server, "HEALTH_RECOVERED")?,
+        }
+        self.append_audit_event(AuditEvent {
+            server: server.to_string(),
+            action: action.to_string(),
+            pid: Some(process.id()),
+            error: Some(!passed),
+            ..AuditEvent::now()
+        })
     }
 
-    /// Returns the last `lines` log lines for a server.
-    pub fn tail_logs(&self, server: &str, lines: usize) -> io::Result<Vec<String>> {
-        if lines == 0 {
-            return Ok(Vec::new());
+    /// Runs a health probe, waiting at most the check's timeout.
+    fn health_probe_passes(
+        &self,
+        server: &str,
+        spec: &ProcessSpec,
+        check: &HealthCheck,
+        process: &mut dyn ProcessHandle,
+    ) -> io::Result<bool> {
+        let timeout = Duration::from_millis(check.timeout_ms);
+        let started = Instant::now();
+        match &check.probe {
+            HealthProbe::Tcp { port } => {
+                let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, *port));
+                Ok(TcpStream::connect_timeout(&addr, timeout).is_ok())
+            }
+            HealthProbe::Exec { command, args } => {
+                let mut probe = Command::new(command);
+                probe
+                    .args(args)
+                    .stdin(Stdio::null())
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::null());
+                if spec.clean_env {
+                    probe.env_clear();
+                }
+                let mut child = probe.envs(&spec.env).spawn()?;
+                loop {
+                    if let Some(status) = child.try_wait()? {
+                        return Ok(status.success());
+                    }
+                    if started.elapsed() >= timeout {
+                        let _ = child.kill();
+                        let _ = child.wait();
+                        return Ok(false);
+                    }
+                    thread::sleep(HEALTH_POLL);
+                }
+            }
+            HealthProbe::McpPing => {
+                let offset = fs::metadata(self.log_path(server)).map_or(0, |m| m.len());
+                let id = format!("berth-health-{}", self.clock.now_epoch_ms());
+                process.write_stdin(health_ping_request(&id).as_bytes())?;
+                loop {
+                    if self
+                        .log_since(server, offset)?
+                        .lines()
+                        .any(|line| answers_request(line, &id))
+                    {
+                        return Ok(true);
+                    }
+                    if started.elapsed() >= timeout {
+                        return Ok(false);
+                    }
+                    thread::sleep(HEALTH_POLL);
+                }
+            }
         }
+    }
 
-        let path = self.log_path(server);
-        if !path.exists() {
-            return Ok(Vec::new());
+    /// Replaces a supervised process with one launched from a refreshed spec.
+    ///
+    /// Returns the new child, or `None` when the server was stopped or taken
+    /// over in the meantime. Restart attempts are not counted.
+    fn relaunch(
+        &self,
+        server: &str,
+        current: &mut dyn ProcessHandle,
+        spec: &ProcessSpec,
+    ) -> io::Result<Option<Box<dyn ProcessHandle>>> {
+        let old_pid = current.id();
+        let _lock = self.lock_state(server)?;
+        let state = self.read_state(server)?;
+        if state.status != ServerStatus::Running || state.pid != Some(old_pid) {
+            return Ok(None);
+        }
+        current.terminate()?;
+        let mut child = self.spawn_logged(server, spec)?;
+        let pid = child.id();
+        let mut state = self.read_state(server)?;
+        if state.status != ServerStatus::Running || state.pid != Some(old_pid) {
+            let _ = child.terminate();
+            return Ok(None);
         }
 
-        let content = fs::read_to_string(path)?;
-        let all: Vec<String> = content.lines().map(ToString::to_string).collect();
-        if all.len() <= lines {
-            return Ok(all);
+        tracing::info!(
+            server,
+            old_pid,
+            pid,
+            "supervisor relaunched process with refreshed spec"
+        );
+        state.pid = Some(pid);
+        state.record_spec(spec);
+        state.updated_at_epoch_ms = self.clock.now_epoch_ms();
+        self.write_state(server, &state)?;
+        self.append_log(server, &format!("REFRESH pid={pid}"))?;
+        self.append_audit_event(AuditEvent {
+            server: server.to_string(),
+            action: "refresh".to_string(),
+            pid: Some(pid),
+            command: Some(spec.command.clone()),
+            args: if spec.args.is_empty() {
+                None
+            } else {
+                Some(spec.args.clone())
+            },
+            ..AuditEvent::now()
+        })?;
+        Ok(Some(child))
+    }
+
+    /// Writes a crash report for a child killed by `signal` and returns its path.
+    ///
+    /// Reports land next to the server log as `<server>.<epoch-ms>.crash` and
+    /// include a backtrace when the core file and a debugger are available.
+    fn write_crash_report(
+        &self,
+        server: &str,
+        pid: u32,
+        spec: &ProcessSpec,
+        signal: i32,
+        core_dumped: bool,
+    ) -> io::Result<PathBuf> {
+        let recent_log = self.tail_logs(server, CRASH_REPORT_LOG_LINES)?;
+        let mut report = format!(
+            "server: {server}\npid: {pid}\nsignal: {signal}\ncommand: {}\n",
+            std::iter::once(spec.command.clone())
+                .chain(redact_args(&spec.args))
+                .collect::<Vec<_>>()
+                .join(" ")
+        );
+        if core_dumped {
+            let core = locate_core(pid);
+            match &core {
+                Ok(path) => report.push_str(&format!("core: {}\n", path.display())),
+                Err(reason) => report.push_str(&format!("core: {reason}\n")),
+            }
+            match capture_backtrace(&spec.command, pid, &core) {
+                Some(backtrace) => report.push_str(&format!("\nbacktrace:\n{backtrace}\n")),
+                None => report.push_str("\nbacktrace: unavailable (no core file or debugger)\n"),
+            }
+        } else {
+            report.push_str("core: not dumped\n");
+        }
+        if let (Some(profile), SIGSYS) = (&spec.seccomp, signal) {
+            report.push_str(&format!(
+                "seccomp: killed for a blocked syscall; the kernel audit log (dmesg) names it.\n\
+                 seccomp: blocked syscalls: {}\n",
+                profile.denied().join(", ")
+            ));
+        }
+        report.push_str("\nrecent log:\n");
+        for line in recent_log {
+            report.push_str(&format!("  {line}\n"));
         }
 
-        Ok(all[all.len() - lines..].to_vec())
+        fs::create_dir_all(self.logs_dir())?;
+        let path = self
+            .logs_dir()
+            .join(format!("{server}.{}.crash", self.clock.now_epoch_ms()));
+        fs::write(&path, report)?;
+        Ok(path)
+    }
+
+    /// Spawns a server process with output appended to its log file.
+    fn spawn_logged(&self, server: &str, spec: &ProcessSpec) -> io::Result<Box<dyn ProcessHandle>> {
+        self.spawner.spawn(
+            spec,
+            self.open_log_append(server)?,
+            startup_input(spec).as_deref(),
+        )
+    }
+
+    /// Returns the last `lines` log lines for a server, made safe to display
+    /// by [`sanitize_log_line`].
+    pub fn tail_logs(&self, server: &str, lines: usize) -> io::Result<Vec<String>> {
+        let raw = self.tail_logs_raw(server, lines)?;
+        Ok(raw
+            .strip_suffix(b"\n")
+            .unwrap_or(&raw)
+            .split(|b| *b == b'\n')
+            .filter(|_| !raw.is_empty())
+            .map(sanitize_log_line)
+            .collect())
+    }
+
+    /// Returns the last `lines` log lines for a server as the bytes it wrote.
+    pub fn tail_logs_raw(&self, server: &str, lines: usize) -> io::Result<Vec<u8>> {
+        match fs::read(self.log_path(server)) {
+            Ok(content) => Ok(log_text::last_lines(&content, lines).to_vec()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(e) => Err(e),
+        }
     }
 
     /// Appends a custom audit event for non-lifecycle runtime actions.
@@ -626,15 +2176,379 @@ impl RuntimeManager {
         }
 
         self.append_audit_event(AuditEvent {
-            timestamp_epoch_secs: now_epoch_secs(),
             server: server.to_string(),
             action: action.to_string(),
             pid,
             command: command.map(ToString::to_string),
             args: args.filter(|v| !v.is_empty()).map(|v| v.to_vec()),
+            ..AuditEvent::now()
+        })
+    }
+
+    /// Appends an audit event for a completed request with its latency and outcome.
+    pub fn record_timed_audit_event(
+        &self,
+        server: &str,
+        action: &str,
+        pid: Option<u32>,
+        args: Option<&[String]>,
+        duration_ms: u64,
+        error: bool,
+    ) -> io::Result<()> {
+        if action.trim().is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "audit action must not be empty",
+            ));
+        }
+
+        self.append_audit_event(AuditEvent {
+            server: server.to_string(),
+            action: action.to_string(),
+            pid,
+            args: args.filter(|v| !v.is_empty()).map(|v| v.to_vec()),
+            duration_ms: Some(duration_ms),
+            error: Some(error),
+            ..AuditEvent::now()
+        })
+    }
+
+    /// Appends an audit event for an install, update, or uninstall of `version`.
+    pub fn record_version_event(
+        &self,
+        server: &str,
+        action: &str,
+        version: &str,
+        previous_version: Option<&str>,
+    ) -> io::Result<()> {
+        if action.trim().is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "audit action must not be empty",
+            ));
+        }
+
+        self.append_audit_event(AuditEvent {
+            server: server.to_string(),
+            action: action.to_string(),
+            version: Some(version.to_string()),
+            previous_version: previous_version.map(ToString::to_string),
+            ..AuditEvent::now()
+        })
+    }
+
+    /// Appends a [`PERMISSION_CHANGE_ACTION`] event: `change` names the
+    /// operation and the permissions it touched, `before` and `after` are the
+    /// server's effective permissions around it.
+    pub fn record_permission_change(
+        &self,
+        server: &str,
+        change: &[String],
+        actor: AuditActor,
+        before: Vec<String>,
+        after: Vec<String>,
+    ) -> io::Result<()> {
+        self.append_audit_event(AuditEvent {
+            server: server.to_string(),
+            action: PERMISSION_CHANGE_ACTION.to_string(),
+            args: Some(change.to_vec()),
+            actor: Some(actor),
+            permissions_before: Some(before),
+            permissions_after: Some(after),
+            ..AuditEvent::now()
         })
     }
 
+    /// Lists runtime artifacts per server for servers not in `known_servers`.
+    ///
+    /// Files in the runtime and log directories belong to the server named by
+    /// their prefix before the first `.` (`github.toml`, `github.log`, ...).
+    pub fn orphaned_artifacts(
+        &self,
+        known_servers: &[String],
+    ) -> io::Result<BTreeMap<String, Vec<PathBuf>>> {
+        let mut orphaned = BTreeMap::<String, Vec<PathBuf>>::new();
+        for (server, path) in self.artifact_files()? {
+            if !known_servers.contains(&server) {
+                orphaned.entry(server).or_default().push(path);
+            }
+        }
+        Ok(orphaned)
+    }
+
+    /// Returns the bytes of state, log, and crash files kept per server.
+    pub fn artifact_usage(&self) -> io::Result<BTreeMap<String, u64>> {
+        let mut usage = BTreeMap::<String, u64>::new();
+        for (server, path) in self.artifact_files()? {
+            let size = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+            *usage.entry(server).or_default() += size;
+        }
+        Ok(usage)
+    }
+
+    /// Removes or archives state, logs, and crash files of servers no longer installed.
+    ///
+    /// Servers with a live tracked process are skipped so a running child is
+    /// never detached from its state.
+    pub fn prune(&self, known_servers: &[String], mode: PruneMode) -> io::Result<PruneReport> {
+        let mut report = PruneReport::default();
+        for (server, files) in self.orphaned_artifacts(known_servers)? {
+            if self.running_pid(&server).ok().flatten().is_some() {
+                report.skipped_running.push(server);
+                continue;
+            }
+            for path in files {
+                let pruned = match mode {
+                    PruneMode::Remove => {
+                        fs::remove_file(&path)?;
+                        path
+                    }
+                    PruneMode::Archive => {
+                        let dir = report
+                            .archive_dir
+                            .get_or_insert_with(|| {
+                                self.berth_home
+                                    .join("archive")
+                                    .join(self.clock.now_epoch_ms().to_string())
+                            })
+                            .clone();
+                        self.archive_file(&path, &dir)?
+                    }
+                };
+                report.files.push(pruned);
+            }
+            tracing::info!(server, ?mode, "pruned runtime artifacts");
+            report.servers.push(server);
+        }
+        if !report.servers.is_empty() {
+            self.refresh_status_summary();
+        }
+        Ok(report)
+    }
+
+    /// Removes state of uninstalled servers and logs and audit entries older
+    /// than `policy.max_age`.
+    ///
+    /// Servers with a live tracked process keep all their files; audit entries
+    /// without a timestamp are kept.
+    pub fn prune_stale(
+        &self,
+        known_servers: &[String],
+        policy: RetentionPolicy,
+    ) -> io::Result<StalePruneReport> {
+        let mut report = StalePruneReport::default();
+        let now_ms = self.clock.now_epoch_ms();
+        let cutoff_ms = now_ms.saturating_sub(policy.max_age.as_millis() as u64);
+        let logs_dir = self.logs_dir();
+
+        let mut live = BTreeMap::<String, bool>::new();
+        let mut is_live = |server: &str| {
+            *live
+                .entry(server.to_string())
+                .or_insert_with(|| self.running_pid(server).ok().flatten().is_some())
+        };
+        for (server, path) in self.artifact_files()? {
+            let orphaned = !known_servers.contains(&server);
+            let expired = path.starts_with(&logs_dir) && modified_before(&path, cutoff_ms);
+            if !orphaned && !expired {
+                continue;
+            }
+            if is_live(&server) {
+                if orphaned && !report.skipped_running.contains(&server) {
+                    report.skipped_running.push(server);
+                }
+                continue;
+            }
+            report.freed_bytes += fs::metadata(&path).map_or(0, |m| m.len());
+            if !policy.dry_run {
+                fs::remove_file(&path)?;
+            }
+            if orphaned {
+                report.orphaned_files.push(path);
+            } else {
+                report.expired_logs.push(path);
+            }
+        }
+
+        // Rotated segments go as a whole once their newest entry expired,
+        // which keeps the hash chain of the remaining entries intact.
+        let audit_path = self.audit_log_path();
+        for segment in self.audit_log().segments()? {
+            if segment == audit_path {
+                continue;
+            }
+            let newest = jsonl::last_line(&mut fs::File::open(&segment)?)?
+                .and_then(|line| audit_line_epoch_ms(&String::from_utf8_lossy(&line)));
+            if newest.is_none_or(|ts| ts >= cutoff_ms) {
+                continue;
+            }
+            let content = fs::read_to_string(&segment)?;
+            report.expired_audit_entries +=
+                content.lines().filter(|l| !l.trim().is_empty()).count();
+            report.freed_bytes += content.len() as u64;
+            if !policy.dry_run {
+                fs::remove_file(&segment)?;
+            }
+        }
+        let dropped = jsonl::retain(&audit_path, !policy.dry_run, |line| {
+            audit_line_epoch_ms(line).is_none_or(|ts| ts >= cutoff_ms)
+        })?;
+        report.expired_audit_entries += dropped.lines;
+        report.freed_bytes += dropped.bytes;
+
+        if !policy.dry_run && !report.orphaned_files.is_empty() {
+            self.refresh_status_summary();
+        }
+        tracing::info!(
+            orphaned = report.orphaned_files.len(),
+            expired_logs = report.expired_logs.len(),
+            audit = report.expired_audit_entries,
+            dry_run = policy.dry_run,
+            "pruned stale runtime data"
+        );
+        Ok(report)
+    }
+
+    /// Removes every runtime artifact of one server, returning the removed paths.
+    ///
+    /// Refuses while a tracked process is alive.
+    pub fn remove_server_artifacts(&self, server: &str) -> io::Result<Vec<PathBuf>> {
+        if let Some(pid) = self.running_pid(server)? {
+            return Err(io::Error::other(format!(
+                "server {server} is still running (pid {pid})"
+            )));
+        }
+        let mut removed = Vec::new();
+        for (owner, path) in self.artifact_files()? {
+            if owner == server {
+                fs::remove_file(&path)?;
+                removed.push(path);
+            }
+        }
+        if !removed.is_empty() {
+            self.refresh_status_summary();
+        }
+        Ok(removed)
+    }
+
+    /// Returns the status of every server with runtime state, without
+    /// recording exits the way [`Self::status`] does.
+    pub fn status_snapshot(&self) -> io::Result<BTreeMap<String, ServerStatus>> {
+        let mut statuses = BTreeMap::new();
+        let dir = self.runtime_dir();
+        if !dir.exists() {
+            return Ok(statuses);
+        }
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|ext| ext != "toml") {
+                continue;
+            }
+            let Some(server) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+            let Ok(state) = self.read_state(server) else {
+                continue;
+            };
+            let status = self
+                .live_status(server, &state)
+                .unwrap_or(ServerStatus::Stopped);
+            statuses.insert(server.to_string(), status);
+        }
+        Ok(statuses)
+    }
+
+    /// Lists state files that record a dead process or cannot be parsed,
+    /// without reconciling them the way [`Self::status`] does.
+    pub fn stale_states(&self) -> io::Result<Vec<StaleState>> {
+        let mut stale = Vec::new();
+        let dir = self.runtime_dir();
+        if !dir.exists() {
+            return Ok(stale);
+        }
+        let mut servers = Vec::new();
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|ext| ext != "toml") {
+                continue;
+            }
+            if let Some(server) = path.file_stem().and_then(|stem| stem.to_str()) {
+                servers.push(server.to_string());
+            }
+        }
+        servers.sort();
+        for server in servers {
+            match self.read_state(&server) {
+                Ok(state) => {
+                    // A scheduled job's child is legitimately gone between runs.
+                    let tracked = state.scheduler_pid.or(state.pid);
+                    if let (ServerStatus::Running, Some(pid)) = (state.status, tracked) {
+                        if !process_is_alive(pid) {
+                            stale.push(StaleState::DeadPid { server, pid });
+                        }
+                    }
+                }
+                Err(e) => stale.push(StaleState::Unreadable {
+                    server,
+                    error: e.to_string(),
+                }),
+            }
+        }
+        Ok(stale)
+    }
+
+    /// Rewrites the machine-wide [`StatusSummary`] file from current state.
+    ///
+    /// Called after every state change; failures are logged rather than
+    /// returned because the summary is only a convenience for readers.
+    pub fn refresh_status_summary(&self) {
+        let result = self.status_snapshot().and_then(|servers| {
+            let summary = StatusSummary::new(servers, self.clock.now_epoch_ms());
+            summary::write_summary(&self.status_summary_path(), &summary)
+        });
+        if let Err(e) = result {
+            tracing::debug!(error = %e, "failed to refresh status summary");
+        }
+    }
+
+    /// Lists `(server, path)` for every file in the runtime and log directories.
+    ///
+    /// Files of replica instances such as `github@2` are listed under `github`.
+    fn artifact_files(&self) -> io::Result<Vec<(String, PathBuf)>> {
+        let mut files = Vec::new();
+        for dir in [self.runtime_dir(), self.logs_dir()] {
+            if !dir.exists() {
+                continue;
+            }
+            for entry in fs::read_dir(&dir)? {
+                let path = entry?.path();
+                if !path.is_file() {
+                    continue;
+                }
+                let name = path.file_name().unwrap_or_default().to_string_lossy();
+                let instance = name.split('.').next().unwrap_or_default();
+                // Replicas' files belong to the server they are a copy of.
+                let server = replica_of(instance).map_or(instance, |(server, _)| server);
+                if !server.is_empty() {
+                    files.push((server.to_string(), path));
+                }
+            }
+        }
+        files.sort();
+        Ok(files)
+    }
+
+    /// Moves one artifact below `archive_dir`, keeping its parent directory name.
+    fn archive_file(&self, path: &Path, archive_dir: &Path) -> io::Result<PathBuf> {
+        let relative = path.strip_prefix(&self.berth_home).unwrap_or(path);
+        let destination = archive_dir.join(relative);
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::rename(path, &destination)?;
+        Ok(destination)
+    }
+
     /// Runtime state directory path.
     fn runtime_dir(&self) -> PathBuf {
         self.berth_home.join("runtime")
@@ -650,45 +2564,78 @@ impl RuntimeManager {
         self.berth_home.join("audit")
     }
 
+    /// Machine-wide status summary file path; see [`StatusSummary`].
+    pub fn status_summary_path(&self) -> PathBuf {
+        self.berth_home.join(STATUS_SUMMARY_FILE)
+    }
+
     /// Per-server state file path.
-    fn state_path(&self, server: &str) -> PathBuf {
+    pub fn state_path(&self, server: &str) -> PathBuf {
         self.runtime_dir().join(format!("{server}.toml"))
     }
 
+    /// Per-server lock file guarding read-modify-write of its state.
+    fn state_lock_path(&self, server: &str) -> PathBuf {
+        self.runtime_dir().join(format!("{server}.lock"))
+    }
+
+    /// Takes the server's state lock for one read-modify-write step; see
+    /// [`StateLock`].
+    fn lock_state(&self, server: &str) -> io::Result<StateLock> {
+        StateLock::acquire(&self.state_lock_path(server))
+    }
+
+    /// Run history of a scheduled job.
+    fn job_runs_path(&self, server: &str) -> PathBuf {
+        self.runtime_dir().join(format!("{server}.runs.jsonl"))
+    }
+
     /// Per-server log file path.
-    fn log_path(&self, server: &str) -> PathBuf {
+    pub fn log_path(&self, server: &str) -> PathBuf {
         self.logs_dir().join(format!("{server}.log"))
     }
 
     /// JSONL audit log file path.
-    fn audit_log_path(&self) -> PathBuf {
+    pub fn audit_log_path(&self) -> PathBuf {
         self.audit_dir().join("audit.jsonl")
     }
 
+    /// Query access to the audit log.
+    pub fn audit_log(&self) -> AuditLog {
+        AuditLog::new(self.audit_log_path())
+    }
+
     /// Org policy file path.
     fn policy_path(&self) -> PathBuf {
-        self.berth_home.join("policy.toml")
+        self.config_home.join("policy.toml")
     }
 
-    /// Returns true when org policy denies this server by name or wildcard.
-    fn server_denied_by_policy(&self, server: &str) -> io::Result<bool> {
+    /// Reads the parts of org policy the runtime enforces; a missing file
+    /// enforces nothing.
+    fn read_policy(&self) -> io::Result<RuntimePolicyFile> {
         let policy_path = self.policy_path();
         if !policy_path.exists() {
-            return Ok(false);
+            return Ok(RuntimePolicyFile::default());
         }
 
         let content = fs::read_to_string(&policy_path)?;
-        let policy: RuntimePolicyFile = toml::from_str(&content).map_err(|e| {
+        toml::from_str(&content).map_err(|e| {
             io::Error::new(
                 io::ErrorKind::InvalidData,
                 format!("failed to parse policy file {}: {e}", policy_path.display()),
             )
-        })?;
+        })
+    }
 
-        Ok(policy.servers.deny.iter().any(|entry| {
+    /// Returns true when org policy denies this server by name or wildcard.
+    fn server_denied_by_policy(&self, server: &str) -> io::Result<bool> {
+        let policy = self.read_policy()?;
+        let denied = policy.servers.deny.iter().any(|entry| {
             let normalized = entry.trim();
             normalized == "*" || normalized.eq_ignore_ascii_case(server)
-        }))
+        });
+        tracing::debug!(server, denied, "evaluated runtime server policy");
+        Ok(denied)
     }
 
     /// Reads persisted state, defaulting to stopped when missing.
@@ -699,21 +2646,33 @@ impl RuntimeManager {
         }
 
         let content = fs::read_to_string(path)?;
-        toml::from_str(&content).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+        let mut state: RuntimeState =
+            toml::from_str(&content).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        if let Some(secs) = state.updated_at_epoch_secs.take() {
+            if state.updated_at_epoch_ms == 0 {
+                state.updated_at_epoch_ms = secs.saturating_mul(1_000);
+            }
+        }
+        Ok(state)
     }
 
     /// Persists a server runtime state as TOML.
+    ///
+    /// The file is replaced atomically, so lock-free readers never see a
+    /// partial write.
     fn write_state(&self, server: &str, state: &RuntimeState) -> io::Result<()> {
         fs::create_dir_all(self.runtime_dir())?;
         let serialized = toml::to_string_pretty(state)
             .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
-        fs::write(self.state_path(server), serialized)
+        atomic_write(&self.state_path(server), serialized)?;
+        self.refresh_status_summary();
+        Ok(())
     }
 
     /// Appends one lifecycle event line to a server log file.
     fn append_log(&self, server: &str, event: &str) -> io::Result<()> {
         let mut file = self.open_log_append(server)?;
-        writeln!(file, "[{}] {}", now_epoch_secs(), event)
+        writeln!(file, "[{}] {}", self.clock.now_epoch_secs(), event)
     }
 
     /// Opens the server log file in append mode, creating it if needed.
@@ -725,153 +2684,335 @@ impl RuntimeManager {
             .open(self.log_path(server))
     }
 
-    /// Appends one audit event as JSONL.
+    /// Appends one audit event as JSONL, rotating and chaining as the
+    /// `[audit]` policy asks.
     fn append_audit_event(&self, event: AuditEvent) -> io::Result<()> {
-        fs::create_dir_all(self.audit_dir())?;
-        let json = serde_json::to_string(&event)
-            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
-        let mut file = OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(self.audit_log_path())?;
-        writeln!(file, "{json}")
+        let settings = self.read_policy()?.audit;
+        self.audit_log()
+            .append(&event, &settings, self.clock.now_epoch_ms())?;
+        if let Some(sink) = &self.audit_sink {
+            let record =
+                serde_json::to_value(&event).and_then(serde_json::from_value::<AuditRecord>);
+            if let Ok(record) = record {
+                sink.record(&record);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns whether `path` was last modified before `cutoff_ms`.
+fn modified_before(path: &Path, cutoff_ms: u64) -> bool {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .is_some_and(|age| (age.as_millis() as u64) < cutoff_ms)
+}
+
+/// Reads the timestamp of one audit line, preferring milliseconds.
+fn audit_line_epoch_ms(line: &str) -> Option<u64> {
+    let value: serde_json::Value = serde_json::from_str(line).ok()?;
+    value
+        .get("timestampEpochMs")
+        .and_then(serde_json::Value::as_u64)
+        .or_else(|| {
+            value
+                .get("timestampEpochSecs")
+                .and_then(serde_json::Value::as_u64)
+                .map(|secs| secs.saturating_mul(1_000))
+        })
+}
+
+/// Finds the core file written for `pid`, or describes where the kernel sent it.
+///
+/// Only the default `core`/`core.<pid>` names in the working directory are
+/// searched; piped `core_pattern` handlers such as systemd-coredump are named
+/// so the report can point at them.
+fn locate_core(pid: u32) -> Result<PathBuf, String> {
+    let pattern = fs::read_to_string("/proc/sys/kernel/core_pattern").unwrap_or_default();
+    let pattern = pattern.trim();
+    if let Some(handler) = pattern.strip_prefix('|') {
+        return Err(format!("handed to `{handler}`"));
     }
+    let dir = std::env::current_dir().map_err(|e| format!("working directory unknown: {e}"))?;
+    [format!("core.{pid}"), "core".to_string()]
+        .into_iter()
+        .map(|name| dir.join(name))
+        .find(|path| path.is_file())
+        .ok_or_else(|| format!("not found in {}", dir.display()))
 }
 
-/// Returns current unix timestamp in seconds.
-fn now_epoch_secs() -> u64 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_secs()
+/// Collects a backtrace with gdb or coredumpctl, whichever can reach the core.
+fn capture_backtrace(command: &str, pid: u32, core: &Result<PathBuf, String>) -> Option<String> {
+    let output = match core {
+        Ok(path) => Command::new("gdb")
+            .args(["-batch", "-nx", "-ex", "thread apply all bt", command])
+            .arg(path)
+            .stdin(Stdio::null())
+            .output(),
+        Err(reason) if reason.contains("systemd-coredump") => Command::new("coredumpctl")
+            .args(["--no-pager", "info", &pid.to_string()])
+            .stdin(Stdio::null())
+            .output(),
+        Err(_) => return None,
+    };
+    let output = output.ok().filter(|out| out.status.success())?;
+    let text = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!text.is_empty()).then_some(text)
 }
 
-/// Returns whether a process is currently alive.
-#[cfg(unix)]
-fn process_is_alive(pid: u32) -> bool {
-    let pid_str = pid.to_string();
-    if let Ok(out) = Command::new("ps")
-        .args(["-o", "stat=", "-p", &pid_str])
-        .output()
-    {
-        if !out.status.success() {
-            return false;
+/// Returns the policy a supervisor applies to `spec`, or `None` when the spec
+/// needs no supervisor.
+///
+/// Refresh, crash capture, seccomp reporting and health checks need one even
+/// without auto-restart; it then never restarts.
+fn supervision_policy(spec: &ProcessSpec) -> Option<AutoRestartPolicy> {
+    match &spec.auto_restart {
+        Some(policy) if policy.enabled => Some(policy.clone()),
+        _ if spec.refresh_at_epoch_secs.is_some()
+            || spec.capture_core
+            || spec.seccomp.is_some()
+            || spec.health_check.is_some() =>
+        {
+            Some(AutoRestartPolicy {
+                enabled: false,
+                max_restarts: 0,
+                restart_on_exit_codes: None,
+            })
         }
-        let stat = String::from_utf8_lossy(&out.stdout).trim().to_string();
-        if stat.is_empty() {
-            return false;
-        }
-        // Zombie processes are dead for supervision purposes.
-        if stat.starts_with('Z') {
-            return false;
-        }
-        return true;
+        _ => None,
     }
+}
 
-    Command::new("kill")
-        .arg("-0")
-        .arg(&pid_str)
-        .status()
-        .is_ok_and(|s| s.success())
+/// Builds the command for a spec, clearing inherited env when requested.
+///
+/// With `capture_core` the server is started through `/bin/sh`, which lifts
+/// the core limit and `exec`s it, so the tracked pid stays the server's. With
+/// `run_as` the whole invocation is wrapped in the user switch. With `seccomp`
+/// the filter is installed in the child before exec, so it covers every
+/// wrapper as well as the server; a server sandboxed by bubblewrap instead
+/// hands the filter to `bwrap --seccomp`, which needs the blocked mount
+/// syscalls itself and installs the filter once the sandbox is set up. Limits
+/// that need the server's pid are applied by the caller with
+/// [`attach_limits`], as is stdio.
+pub fn process_command(spec: &ProcessSpec) -> Command {
+    let mut argv = match &spec.run_as {
+        Some(user) => run_as_prefix(user, spec),
+        None => Vec::new(),
+    };
+    #[cfg(unix)]
+    if spec.capture_core {
+        argv.extend(["/bin/sh", "-c", CORE_LIMIT_WRAPPER].map(String::from));
+    }
+    let server_at = argv.len();
+    argv.push(spec.command.clone());
+    argv.extend(spec.args.iter().cloned());
+    #[cfg(target_os = "linux")]
+    let filter = spec
+        .seccomp
+        .as_ref()
+        .and_then(|profile| seccomp_filter(profile, &mut argv, server_at));
+    #[cfg(not(target_os = "linux"))]
+    if spec.seccomp.is_some() {
+        let _ = server_at;
+        tracing::warn!("seccomp filtering is only supported on Linux; ignoring");
+    }
+    let mut command = Command::new(&argv[0]);
+    command.args(&argv[1..]);
+    if spec.clean_env {
+        command.env_clear();
+    }
+    command.envs(&spec.env);
+    #[cfg(target_os = "linux")]
+    if let Some(filter) = filter {
+        filter.attach(&mut command);
+    }
+    if let Some(limits) = &spec.limits {
+        limits::configure(&mut command, limits);
+    }
+    command
 }
 
-/// Returns whether a process is currently alive.
-#[cfg(windows)]
-fn process_is_alive(pid: u32) -> bool {
-    let output = Command::new("cmd")
-        .args(["/C", "tasklist", "/FI", &format!("PID eq {pid}")])
-        .output();
-    match output {
-        Ok(out) if out.status.success() => {
-            String::from_utf8_lossy(&out.stdout).contains(&pid.to_string())
+/// Compiles the filter for a launch, passing it to bubblewrap on the command
+/// line when the server at `argv[server_at..]` is sandboxed by it.
+#[cfg(target_os = "linux")]
+fn seccomp_filter(
+    profile: &SeccompProfile,
+    argv: &mut Vec<String>,
+    server_at: usize,
+) -> Option<seccomp::Filter> {
+    let Some(program) = seccomp::compile(profile) else {
+        tracing::warn!("seccomp filtering is not supported on this architecture; ignoring");
+        return None;
+    };
+    let Some(offset) = bwrap_offset(&argv[server_at..]) else {
+        return Some(seccomp::Filter::PreExec(program));
+    };
+    if argv[0] == "systemd-run" {
+        // The unit does not inherit our fds; `run_as_prefix_for` has warned.
+        return None;
+    }
+    match seccomp::to_memfd(&program) {
+        Ok(fd) => {
+            use std::os::fd::AsRawFd;
+            let at = server_at + offset + 1;
+            argv.splice(
+                at..at,
+                ["--seccomp".to_string(), fd.as_raw_fd().to_string()],
+            );
+            Some(seccomp::Filter::Bwrap(fd))
         }
-        _ => false,
+        Err(e) => {
+            tracing::warn!("could not pass the seccomp filter to bwrap: {e}; ignoring");
+            None
+        }
+    }
+}
+
+/// Returns where `bwrap` sits in a server's argv when the sandbox wraps the
+/// server in it, either directly or behind `setpriv ... --`.
+#[cfg(target_os = "linux")]
+fn bwrap_offset(argv: &[String]) -> Option<usize> {
+    let named = |arg: &String, name: &str| Path::new(arg).file_name().is_some_and(|f| f == name);
+    let mut at = 0;
+    if named(argv.first()?, "setpriv") {
+        at = argv.iter().position(|arg| arg == "--")? + 1;
     }
+    named(argv.get(at)?, "bwrap").then_some(at)
 }
 
-/// Returns whether a process is currently alive.
-#[cfg(not(any(unix, windows)))]
-fn process_is_alive(_pid: u32) -> bool {
-    false
+/// Returns the command prefix that switches to `user` before running the server.
+#[cfg(target_os = "linux")]
+fn run_as_prefix(user: &str, spec: &ProcessSpec) -> Vec<String> {
+    run_as_prefix_for(user, spec, effective_uid() == Some(0))
 }
 
-/// Sends a termination signal to a process.
-#[cfg(unix)]
-fn terminate_process(pid: u32) -> io::Result<()> {
-    let pid_str = pid.to_string();
-    let status = Command::new("kill").arg(&pid_str).status()?;
-    if !status.success() {
-        return Err(io::Error::new(
-            io::ErrorKind::PermissionDenied,
-            format!("failed to signal process {pid}"),
+#[cfg(target_os = "linux")]
+fn run_as_prefix_for(user: &str, spec: &ProcessSpec, privileged: bool) -> Vec<String> {
+    if privileged {
+        return ["runuser", "-u", user, "--"].map(String::from).to_vec();
+    }
+    let mut prefix = [
+        "systemd-run",
+        "--pipe",
+        "--quiet",
+        "--wait",
+        "--collect",
+        "--same-dir",
+        "--no-ask-password",
+    ]
+    .map(String::from)
+    .to_vec();
+    prefix.push(format!("--uid={user}"));
+    // Transient units start with an empty environment; pass names only so
+    // values never show up in the process list.
+    prefix.extend(spec.env.keys().map(|key| format!("--setenv={key}")));
+    // The unit is spawned by the service manager, not by us, so the filter
+    // installed before exec would not reach it. A filter on the unit would
+    // also hold for bubblewrap, which cannot set up its sandbox under it.
+    let server_argv: Vec<String> = std::iter::once(spec.command.clone())
+        .chain(spec.args.iter().cloned())
+        .collect();
+    if spec.seccomp.is_some() && bwrap_offset(&server_argv).is_some() {
+        tracing::warn!(
+            "seccomp filtering is not supported for bubblewrap-sandboxed servers run as another user without root; ignoring"
+        );
+    } else if let Some(profile) = &spec.seccomp {
+        prefix.push(format!(
+            "--property=SystemCallFilter=~{}",
+            profile.denied().join(" ")
         ));
     }
-
-    if wait_for_process_exit(pid, 50, Duration::from_millis(20)) {
-        return Ok(());
+    // Likewise, the unit gets its limits from the service manager.
+    if let Some(limits) = &spec.limits {
+        if let Some(mb) = limits.memory_mb {
+            prefix.push(format!("--property=MemoryMax={mb}M"));
+        }
+        if let Some(percent) = limits.cpu_percent {
+            prefix.push(format!("--property=CPUQuota={percent}%"));
+        }
+        if let Some(files) = limits.max_open_files {
+            prefix.push(format!("--property=LimitNOFILE={files}"));
+        }
     }
+    prefix.push("--".to_string());
+    prefix
+}
 
-    // Escalate if the process does not exit after TERM.
-    let kill_status = Command::new("kill").args(["-9", &pid_str]).status()?;
-    if kill_status.success() {
-        Ok(())
-    } else {
-        Err(io::Error::new(
-            io::ErrorKind::PermissionDenied,
-            format!("failed to force terminate process {pid}"),
-        ))
-    }
+#[cfg(not(target_os = "linux"))]
+fn run_as_prefix(user: &str, _spec: &ProcessSpec) -> Vec<String> {
+    tracing::warn!(user, "run_as is only supported on Linux; ignoring");
+    Vec::new()
 }
 
-/// Sends a termination signal to a process.
-#[cfg(windows)]
-fn terminate_process(pid: u32) -> io::Result<()> {
-    // Try graceful shutdown first, then force-kill if needed.
-    let status = Command::new("taskkill")
-        .args(["/PID", &pid.to_string(), "/T"])
-        .status()?;
-    if !status.success() {
-        return Err(io::Error::new(
-            io::ErrorKind::PermissionDenied,
-            format!("failed to terminate process {pid}"),
-        ));
-    }
+/// Returns the effective uid of this process.
+#[cfg(target_os = "linux")]
+fn effective_uid() -> Option<u32> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("Uid:"))?
+        .split_whitespace()
+        .nth(1)?
+        .parse()
+        .ok()
+}
 
-    if wait_for_process_exit(pid, 50, Duration::from_millis(20)) {
-        return Ok(());
-    }
+/// Returns what a new server process gets on stdin, if anything.
+///
+/// Servers probed over MCP get an `initialize` request, whose answer lands in
+/// the log where [`RuntimeManager::await_ready`] looks for it. Their stdin then
+/// stays open so health pings can follow.
+fn startup_input(spec: &ProcessSpec) -> Option<Vec<u8>> {
+    let initialize = spec
+        .readiness
+        .as_ref()
+        .is_some_and(|check| check.probe == ReadinessProbe::McpInitialize)
+        || spec
+            .health_check
+            .as_ref()
+            .is_some_and(|check| check.probe == HealthProbe::McpPing);
+    initialize.then(|| readiness_initialize_request().into_bytes())
+}
 
-    let force_status = Command::new("taskkill")
-        .args(["/PID", &pid.to_string(), "/T", "/F"])
-        .status()?;
-    if force_status.success() {
-        Ok(())
-    } else {
-        Err(io::Error::new(
-            io::ErrorKind::PermissionDenied,
-            format!("failed to force terminate process {pid}"),
-        ))
-    }
+/// Returns the newline-terminated `initialize` request used as a readiness probe.
+fn readiness_initialize_request() -> String {
+    let request = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": READINESS_REQUEST_ID,
+        "method": "initialize",
+        "params": {
+            "protocolVersion": "2025-06-18",
+            "capabilities": {},
+            "clientInfo": {"name": "berth", "version": version()},
+        },
+    });
+    format!("{request}\n")
 }
 
-/// Sends a termination signal to a process.
-#[cfg(not(any(unix, windows)))]
-fn terminate_process(_pid: u32) -> io::Result<()> {
-    Err(io::Error::new(
-        io::ErrorKind::Unsupported,
-        "process termination is not supported on this platform",
-    ))
+/// Returns a newline-terminated MCP `ping` request with `id`.
+fn health_ping_request(id: &str) -> String {
+    let request = serde_json::json!({"jsonrpc": "2.0", "id": id, "method": "ping"});
+    format!("{request}\n")
 }
 
-/// Waits for a process to exit, checking liveness repeatedly.
-fn wait_for_process_exit(pid: u32, attempts: u32, interval: Duration) -> bool {
-    for _ in 0..attempts {
-        if !process_is_alive(pid) {
-            return true;
-        }
-        thread::sleep(interval);
-    }
-    !process_is_alive(pid)
+/// Returns whether a log line answers the request with `id`, with a result or an error.
+///
+/// An error still shows the server reads and answers requests.
+fn answers_request(line: &str, id: &str) -> bool {
+    serde_json::from_str::<serde_json::Value>(line.trim()).is_ok_and(|message| {
+        message.get("id").and_then(serde_json::Value::as_str) == Some(id)
+            && (message.get("result").is_some() || message.get("error").is_some())
+    })
+}
+
+/// Returns whether a log line is a successful answer to the readiness `initialize`.
+fn answers_readiness_request(line: &str) -> bool {
+    serde_json::from_str::<serde_json::Value>(line.trim()).is_ok_and(|message| {
+        message.get("id").and_then(serde_json::Value::as_str) == Some(READINESS_REQUEST_ID)
+            && message.get("result").is_some()
+    })
 }
 
 #[cfg(test)]
@@ -912,6 +3053,17 @@ mod tests {
             args: vec!["-c".to_string(), "sleep 60".to_string()],
             env: BTreeMap::new(),
             auto_restart: None,
+            clean_env: false,
+            refresh_at_epoch_secs: None,
+            capture_core: false,
+            readiness: None,
+            run_as: None,
+            seccomp: None,
+            shutdown_signal: None,
+            shutdown_grace_period_secs: None,
+            health_check: None,
+            schedule: None,
+            limits: None,
         }
     }
 
@@ -928,6 +3080,17 @@ mod tests {
             ],
             env: BTreeMap::new(),
             auto_restart: None,
+            clean_env: false,
+            refresh_at_epoch_secs: None,
+            capture_core: false,
+            readiness: None,
+            run_as: None,
+            seccomp: None,
+            shutdown_signal: None,
+            shutdown_grace_period_secs: None,
+            health_check: None,
+            schedule: None,
+            limits: None,
         }
     }
 
@@ -938,6 +3101,17 @@ mod tests {
             args: vec![],
             env: BTreeMap::new(),
             auto_restart: None,
+            clean_env: false,
+            refresh_at_epoch_secs: None,
+            capture_core: false,
+            readiness: None,
+            run_as: None,
+            seccomp: None,
+            shutdown_signal: None,
+            shutdown_grace_period_secs: None,
+            health_check: None,
+            schedule: None,
+            limits: None,
         }
     }
 
@@ -951,6 +3125,17 @@ mod tests {
             ],
             env: BTreeMap::new(),
             auto_restart: None,
+            clean_env: false,
+            refresh_at_epoch_secs: None,
+            capture_core: false,
+            readiness: None,
+            run_as: None,
+            seccomp: None,
+            shutdown_signal: None,
+            shutdown_grace_period_secs: None,
+            health_check: None,
+            schedule: None,
+            limits: None,
         }
     }
 
@@ -963,7 +3148,19 @@ mod tests {
             auto_restart: Some(AutoRestartPolicy {
                 enabled: true,
                 max_restarts,
+                restart_on_exit_codes: None,
             }),
+            clean_env: false,
+            refresh_at_epoch_secs: None,
+            capture_core: false,
+            readiness: None,
+            run_as: None,
+            seccomp: None,
+            shutdown_signal: None,
+            shutdown_grace_period_secs: None,
+            health_check: None,
+            schedule: None,
+            limits: None,
         }
     }
 
@@ -976,7 +3173,19 @@ mod tests {
             auto_restart: Some(AutoRestartPolicy {
                 enabled: true,
                 max_restarts,
+                restart_on_exit_codes: None,
             }),
+            clean_env: false,
+            refresh_at_epoch_secs: None,
+            capture_core: false,
+            readiness: None,
+            run_as: None,
+            seccomp: None,
+            shutdown_signal: None,
+            shutdown_grace_period_secs: None,
+            health_check: None,
+            schedule: None,
+            limits: None,
         }
     }
 
@@ -989,7 +3198,19 @@ mod tests {
             auto_restart: Some(AutoRestartPolicy {
                 enabled: true,
                 max_restarts,
+                restart_on_exit_codes: None,
             }),
+            clean_env: false,
+            refresh_at_epoch_secs: None,
+            capture_core: false,
+            readiness: None,
+            run_as: None,
+            seccomp: None,
+            shutdown_signal: None,
+            shutdown_grace_period_secs: None,
+            health_check: None,
+            schedule: None,
+            limits: None,
         }
     }
 
@@ -1007,7 +3228,19 @@ mod tests {
             auto_restart: Some(AutoRestartPolicy {
                 enabled: true,
                 max_restarts,
+                restart_on_exit_codes: None,
             }),
+            clean_env: false,
+            refresh_at_epoch_secs: None,
+            capture_core: false,
+            readiness: None,
+            run_as: None,
+            seccomp: None,
+            shutdown_signal: None,
+            shutdown_grace_period_secs: None,
+            health_check: None,
+            schedule: None,
+            limits: None,
         }
     }
 
@@ -1025,119 +3258,878 @@ mod tests {
             auto_restart: Some(AutoRestartPolicy {
                 enabled: true,
                 max_restarts,
+                restart_on_exit_codes: None,
+            }),
+            clean_env: false,
+            refresh_at_epoch_secs: None,
+            capture_core: false,
+            readiness: None,
+            run_as: None,
+            seccomp: None,
+            shutdown_signal: None,
+            shutdown_grace_period_secs: None,
+            health_check: None,
+            schedule: None,
+            limits: None,
+        }
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    fn fail_once_then_run_spec(_marker_path: &str, max_restarts: u32) -> ProcessSpec {
+        ProcessSpec {
+            command: "unsupported".to_string(),
+            args: vec![],
+            env: BTreeMap::new(),
+            auto_restart: Some(AutoRestartPolicy {
+                enabled: true,
+                max_restarts,
+                restart_on_exit_codes: None,
             }),
+            clean_env: false,
+            refresh_at_epoch_secs: None,
+            capture_core: false,
+            readiness: None,
+            run_as: None,
+            seccomp: None,
+            shutdown_signal: None,
+            shutdown_grace_period_secs: None,
+            health_check: None,
+            schedule: None,
+            limits: None,
+        }
+    }
+
+    #[test]
+    fn version_is_set() {
+        assert!(!version().is_empty());
+    }
+
+    #[test]
+    fn missing_state_defaults_to_stopped() {
+        let (_tmp, manager) = manager();
+        let status = manager.status("github").unwrap();
+        assert_eq!(status, ServerStatus::Stopped);
+    }
+
+    #[test]
+    fn start_transitions_to_running() {
+        let (_tmp, manager) = manager();
+        let spec = long_running_spec();
+        let outcome = manager.start("github", &spec).unwrap();
+        assert_eq!(outcome, StartOutcome::Started);
+        assert_eq!(manager.status("github").unwrap(), ServerStatus::Running);
+        let _ = manager.stop("github");
+    }
+
+    #[test]
+    fn start_launches_through_the_configured_spawner() {
+        #[derive(Default)]
+        struct Recording(std::sync::Mutex<Vec<(String, Option<Vec<u8>>)>>);
+
+        impl Spawner for Recording {
+            fn spawn(
+                &self,
+                spec: &ProcessSpec,
+                _log: fs::File,
+                input: Option<&[u8]>,
+            ) -> io::Result<Box<dyn ProcessHandle>> {
+                let mut calls = self.0.lock().unwrap();
+                calls.push((spec.command.clone(), input.map(<[u8]>::to_vec)));
+                Ok(Box::new(PidHandle(4_000 + calls.len() as u32)))
+            }
+        }
+
+        let (_tmp, manager) = manager();
+        let spawner = Arc::new(Recording::default());
+        let manager = manager.with_spawner(spawner.clone());
+        let mut spec = long_running_spec();
+        spec.readiness = Some(ReadinessCheck {
+            probe: ReadinessProbe::McpInitialize,
+            timeout_ms: 5_000,
+        });
+        let child = manager.start_child("github", &spec).unwrap().unwrap();
+
+        assert_eq!(child.id(), 4_001);
+        assert_eq!(manager.read_state("github").unwrap().pid, Some(4_001));
+        let calls = spawner.0.lock().unwrap();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].0, spec.command);
+        assert_eq!(
+            calls[0].1.as_deref(),
+            Some(readiness_initialize_request().as_bytes())
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn readiness_probe_holds_server_in_starting_until_log_line() {
+        let (_tmp, manager) = manager();
+        let mut spec = long_running_spec();
+        spec.args = vec![
+            "-c".to_string(),
+            "sleep 0.3; echo listening; sleep 60".to_string(),
+        ];
+        let check = ReadinessCheck {
+            probe: ReadinessProbe::LogPattern {
+                pattern: "listening".to_string(),
+            },
+            timeout_ms: 5_000,
+        };
+        spec.readiness = Some(check.clone());
+        manager.start("github", &spec).unwrap();
+        assert_eq!(manager.status("github").unwrap(), ServerStatus::Starting);
+
+        let outcome = manager.await_ready("github", &check).unwrap();
+        assert!(matches!(outcome, ReadinessOutcome::Ready { .. }));
+        assert_eq!(manager.status("github").unwrap(), ServerStatus::Running);
+        let _ = manager.stop("github");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn readiness_timeout_stops_the_server() {
+        let (_tmp, manager) = manager();
+        let mut spec = long_running_spec();
+        let check = ReadinessCheck {
+            probe: ReadinessProbe::LogPattern {
+                pattern: "never printed".to_string(),
+            },
+            timeout_ms: 300,
+        };
+        spec.readiness = Some(check.clone());
+        manager.start("github", &spec).unwrap();
+
+        let outcome = manager.await_ready("github", &check).unwrap();
+        assert_eq!(outcome, ReadinessOutcome::TimedOut);
+        assert_eq!(manager.status("github").unwrap(), ServerStatus::Stopped);
+        let logs = manager.tail_logs("github", 10).unwrap();
+        assert!(logs.iter().any(|l| l.contains("READINESS_TIMEOUT")));
+    }
+
+    #[test]
+    fn readiness_request_answer_is_matched_by_id() {
+        assert!(answers_readiness_request(
+            r#"{"jsonrpc":"2.0","id":"berth-readiness","result":{}}"#
+        ));
+        assert!(!answers_readiness_request(
+            r#"{"jsonrpc":"2.0","id":"berth-readiness","error":{"code":-1}}"#
+        ));
+        assert!(!answers_readiness_request(
+            r#"{"jsonrpc":"2.0","id":1,"result":{}}"#
+        ));
+        assert!(!answers_readiness_request("berth-readiness"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn run_as_switches_user_with_runuser_or_systemd_run() {
+        let mut spec = long_running_spec();
+        spec.env = BTreeMap::from([("API_TOKEN".to_string(), "secret".to_string())]);
+
+        assert_eq!(
+            run_as_prefix_for("mcp-github", &spec, true),
+            vec!["runuser", "-u", "mcp-github", "--"]
+        );
+        let unprivileged = run_as_prefix_for("mcp-github", &spec, false);
+        assert_eq!(unprivileged[0], "systemd-run");
+        assert!(unprivileged.contains(&"--uid=mcp-github".to_string()));
+        assert!(unprivileged.contains(&"--setenv=API_TOKEN".to_string()));
+        assert!(!unprivileged.iter().any(|arg| arg.contains("secret")));
+        assert_eq!(unprivileged.last().map(String::as_str), Some("--"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn process_command_clears_inherited_env_when_requested() {
+        let mut spec = ProcessSpec {
+            command: "sh".to_string(),
+            args: vec![
+                "-c".to_string(),
+                "echo \"${HOME:-unset} $MARKER\"".to_string(),
+            ],
+            env: BTreeMap::from([("MARKER".to_string(), "set".to_string())]),
+            auto_restart: None,
+            clean_env: true,
+            refresh_at_epoch_secs: None,
+            capture_core: false,
+            readiness: None,
+            run_as: None,
+            seccomp: None,
+            shutdown_signal: None,
+            shutdown_grace_period_secs: None,
+            health_check: None,
+            schedule: None,
+            limits: None,
+        };
+        let isolated = process_command(&spec).output().unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&isolated.stdout).trim(),
+            "unset set"
+        );
+
+        spec.clean_env = false;
+        let inherited = process_command(&spec).output().unwrap();
+        assert!(!String::from_utf8_lossy(&inherited.stdout).starts_with("unset"));
+    }
+
+    #[cfg(all(
+        target_os = "linux",
+        any(target_arch = "x86_64", target_arch = "aarch64")
+    ))]
+    #[test]
+    fn seccomp_filter_is_handed_to_bwrap_instead_of_filtering_it() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tmp = tempfile::tempdir().unwrap();
+        // Stands in for bubblewrap: reports whether it runs filtered itself
+        // and how many bytes of filter it was handed.
+        let bwrap = tmp.path().join("bwrap");
+        fs::write(
+            &bwrap,
+            "#!/bin/sh\n[ \"$1\" = --seccomp ] || exit 3\n\
+             grep '^Seccomp:' /proc/self/status\nwc -c < /proc/self/fd/$2\n",
+        )
+        .unwrap();
+        fs::set_permissions(&bwrap, fs::Permissions::from_mode(0o755)).unwrap();
+        let program_bytes = seccomp::compile(&SeccompProfile::default()).unwrap().len() * 8;
+
+        let mut spec = long_running_spec();
+        spec.seccomp = Some(SeccompProfile::default());
+        spec.command = "setpriv".to_string();
+        spec.args = [
+            "--no-new-privs",
+            "--",
+            bwrap.to_str().unwrap(),
+            "--die-with-parent",
+            "--",
+            "true",
+        ]
+        .map(String::from)
+        .to_vec();
+        let output = process_command(&spec).output().unwrap();
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(output.status.success(), "{stdout}");
+        assert!(stdout.contains("Seccomp:\t0"), "{stdout}");
+        assert_eq!(
+            stdout.lines().last().map(str::trim),
+            Some(program_bytes.to_string().as_str())
+        );
+
+        // Anything else is filtered itself.
+        spec.command = "sh".to_string();
+        spec.args = ["-c", "grep '^Seccomp:' /proc/self/status"]
+            .map(String::from)
+            .to_vec();
+        let output = process_command(&spec).output().unwrap();
+        assert!(String::from_utf8_lossy(&output.stdout).contains("Seccomp:\t2"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn bwrap_is_found_directly_or_behind_setpriv() {
+        let argv = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            bwrap_offset(&argv(&["/usr/bin/bwrap", "--", "node"])),
+            Some(0)
+        );
+        assert_eq!(
+            bwrap_offset(&argv(&[
+                "setpriv",
+                "--no-new-privs",
+                "--",
+                "bwrap",
+                "--",
+                "node"
+            ])),
+            Some(3)
+        );
+        assert_eq!(bwrap_offset(&argv(&["node", "bwrap"])), None);
+        assert_eq!(bwrap_offset(&argv(&["setpriv", "--", "node"])), None);
+
+        let mut spec = long_running_spec();
+        spec.seccomp = Some(SeccompProfile::default());
+        assert!(run_as_prefix_for("mcp", &spec, false)
+            .iter()
+            .any(|arg| arg.starts_with("--property=SystemCallFilter=")));
+        spec.command = "bwrap".to_string();
+        assert!(!run_as_prefix_for("mcp", &spec, false)
+            .iter()
+            .any(|arg| arg.starts_with("--property=SystemCallFilter=")));
+    }
+
+    #[test]
+    fn running_pid_reports_live_process_without_writing_state() {
+        let (_tmp, manager) = manager();
+        assert_eq!(manager.running_pid("github").unwrap(), None);
+        assert!(!manager.state_path("github").exists());
+
+        manager.start("github", &long_running_spec()).unwrap();
+        let pid = manager.read_state("github").unwrap().pid;
+        assert!(pid.is_some());
+        assert_eq!(manager.running_pid("github").unwrap(), pid);
+        let _ = manager.stop("github");
+        assert_eq!(manager.running_pid("github").unwrap(), None);
+    }
+
+    #[test]
+    fn starting_running_server_reports_already_running() {
+        let (_tmp, manager) = manager();
+        let spec = long_running_spec();
+        manager.start("github", &spec).unwrap();
+        let outcome = manager.start("github", &spec).unwrap();
+        assert_eq!(outcome, StartOutcome::AlreadyRunning);
+        let _ = manager.stop("github");
+    }
+
+    #[test]
+    fn stop_transitions_to_stopped() {
+        let (_tmp, manager) = manager();
+        let spec = long_running_spec();
+        manager.start("github", &spec).unwrap();
+        let outcome = manager.stop("github").unwrap();
+        assert_eq!(outcome, StopOutcome::Stopped);
+        assert_eq!(manager.status("github").unwrap(), ServerStatus::Stopped);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn stop_escalates_when_process_ignores_term() {
+        let (_tmp, manager) = manager();
+        let spec = ignores_term_spec();
+        manager.start("github", &spec).unwrap();
+        let outcome = manager.stop("github").unwrap();
+        assert_eq!(outcome, StopOutcome::Stopped);
+        assert_eq!(manager.status("github").unwrap(), ServerStatus::Stopped);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn stop_uses_the_recorded_shutdown_signal() {
+        let (tmp, manager) = manager();
+        let spec = ProcessSpec {
+            args: vec![
+                "-c".to_string(),
+                "trap '' TERM; trap 'echo drained; exit 0' INT; echo trapped; \
+                 while true; do sleep 0.1; done"
+                    .to_string(),
+            ],
+            shutdown_signal: Some(ShutdownSignal::Sigint),
+            shutdown_grace_period_secs: Some(10),
+            health_check: None,
+            schedule: None,
+            limits: None,
+            ..ignores_term_spec()
+        };
+        manager.start("github", &spec).unwrap();
+        let state = fs::read_to_string(tmp.path().join(".berth/runtime/github.toml")).unwrap();
+        assert!(state.contains("shutdown_signal = \"SIGINT\""));
+        // Signals sent before the traps are installed would kill the shell.
+        for _ in 0..250 {
+            let log = fs::read_to_string(manager.log_path("github")).unwrap_or_default();
+            if log.contains("trapped") {
+                break;
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+
+        let started = Instant::now();
+        assert_eq!(manager.stop("github").unwrap(), StopOutcome::Stopped);
+        assert!(started.elapsed() < Duration::from_secs(10));
+        let log = fs::read_to_string(manager.log_path("github")).unwrap();
+        assert!(log.contains("drained"));
+    }
+
+    #[test]
+    fn stopping_stopped_server_reports_already_stopped() {
+        let (_tmp, manager) = manager();
+        let outcome = manager.stop("github").unwrap();
+        assert_eq!(outcome, StopOutcome::AlreadyStopped);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn concurrent_commands_keep_state_whole_and_one_process_tracked() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        let (tmp, manager) = manager();
+        let home = tmp.path().join(".berth");
+        let done = Arc::new(AtomicBool::new(false));
+        let reader = {
+            let path = manager.state_path("github");
+            let done = Arc::clone(&done);
+            thread::spawn(move || {
+                while !done.load(Ordering::SeqCst) {
+                    if let Ok(content) = fs::read_to_string(&path) {
+                        toml::from_str::<RuntimeState>(&content).unwrap();
+                    }
+                }
+            })
+        };
+        let workers: Vec<_> = (0..8)
+            .map(|worker| {
+                let manager = RuntimeManager::new(&home);
+                thread::spawn(move || {
+                    let spec = long_running_spec();
+                    for round in 0..10 {
+                        match (worker + round) % 3 {
+                            0 => drop(manager.start("github", &spec).unwrap()),
+                            1 => drop(manager.status_with_spec("github", Some(&spec)).unwrap()),
+                            _ => drop(manager.stop("github").unwrap()),
+                        }
+                    }
+                })
+            })
+            .collect();
+        for worker in workers {
+            worker.join().unwrap();
         }
+        done.store(true, Ordering::SeqCst);
+        reader.join().unwrap();
+
+        // Every process a start spawned is either the tracked one or stopped.
+        let log = fs::read_to_string(manager.log_path("github")).unwrap();
+        let started: Vec<u32> = log
+            .lines()
+            .filter_map(|line| line.split_once("START pid=")?.1.parse().ok())
+            .collect();
+        assert!(!started.is_empty());
+        let tracked = manager.running_pid("github").unwrap();
+        let alive: Vec<u32> = started
+            .iter()
+            .copied()
+            .filter(|pid| process_is_alive(*pid))
+            .collect();
+        assert_eq!(alive, tracked.into_iter().collect::<Vec<_>>());
+
+        manager.stop("github").unwrap();
+        assert!(!started.iter().any(|pid| process_is_alive(*pid)));
+    }
+
+    #[test]
+    fn restart_ends_in_running_state() {
+        let (_tmp, manager) = manager();
+        let spec = long_running_spec();
+        manager.restart("github", &spec).unwrap();
+        assert_eq!(manager.status("github").unwrap(), ServerStatus::Running);
+        let _ = manager.stop("github");
+    }
+
+    #[test]
+    fn tail_logs_returns_last_lines() {
+        let (_tmp, manager) = manager();
+        let spec = long_running_spec();
+        manager.start("github", &spec).unwrap();
+        manager.stop("github").unwrap();
+        manager.start("github", &spec).unwrap();
+        let _ = manager.stop("github");
+
+        let lines = manager.tail_logs("github", 2).unwrap();
+        assert_eq!(lines.len(), 2);
+        assert!(lines.iter().any(|l| l.contains("STOP")));
+        assert!(lines.iter().any(|l| l.contains("START")));
+    }
+
+    #[test]
+    fn state_changes_refresh_status_summary() {
+        let (_tmp, manager) = manager();
+        let spec = long_running_spec();
+        manager.start("github", &spec).unwrap();
+        let summary = read_status_summary(&manager.status_summary_path()).unwrap();
+        assert_eq!(summary.running, 1);
+        assert_eq!(summary.servers["github"], ServerStatus::Running);
+
+        manager.stop("github").unwrap();
+        let summary = read_status_summary(&manager.status_summary_path()).unwrap();
+        assert_eq!(summary.running, 0);
+        assert_eq!(summary.servers["github"], ServerStatus::Stopped);
+
+        manager.remove_server_artifacts("github").unwrap();
+        let summary = read_status_summary(&manager.status_summary_path()).unwrap();
+        assert!(summary.servers.is_empty());
+    }
+
+    #[test]
+    fn start_stop_writes_audit_events() {
+        let (_tmp, manager) = manager();
+        let spec = long_running_spec();
+        manager.start("github", &spec).unwrap();
+        manager.stop("github").unwrap();
+
+        let audit_path = manager.audit_log_path();
+        let content = fs::read_to_string(audit_path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert!(lines.iter().any(|l| l.contains("\"action\":\"start\"")));
+        assert!(lines.iter().any(|l| l.contains("\"action\":\"stop\"")));
+    }
+
+    #[test]
+    fn timed_audit_events_record_duration_and_error() {
+        let (_tmp, manager) = manager();
+        manager
+            .record_timed_audit_event(
+                "github",
+                "mcp-tool-result",
+                Some(42),
+                Some(&["search".to_string()]),
+                120,
+                true,
+            )
+            .unwrap();
+        let content = fs::read_to_string(manager.audit_log_path()).unwrap();
+        assert!(content.contains("\"durationMs\":120"));
+        assert!(content.contains("\"error\":true"));
+        assert!(content.contains("\"args\":[\"search\"]"));
     }
 
-    #[cfg(not(any(unix, windows)))]
-    fn fail_once_then_run_spec(_marker_path: &str, max_restarts: u32) -> ProcessSpec {
-        ProcessSpec {
-            command: "unsupported".to_string(),
-            args: vec![],
-            env: BTreeMap::new(),
-            auto_restart: Some(AutoRestartPolicy {
-                enabled: true,
-                max_restarts,
-            }),
+    #[test]
+    fn version_audit_events_record_both_versions() {
+        let (_tmp, manager) = manager();
+        manager
+            .record_version_event("github", "update", "1.3.0", Some("1.2.0"))
+            .unwrap();
+        let content = fs::read_to_string(manager.audit_log_path()).unwrap();
+        assert!(content.contains("\"version\":\"1.3.0\""));
+        assert!(content.contains("\"previousVersion\":\"1.2.0\""));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn adopted_processes_are_tracked_and_stopped_like_started_ones() {
+        let (_tmp, manager) = manager();
+        let mut child = Command::new("sleep").arg("30").spawn().unwrap();
+        let pid = child.id();
+        while process_command_line(pid).is_none_or(|args| args[0] != "sleep") {
+            thread::sleep(Duration::from_millis(10));
         }
+
+        assert_eq!(
+            manager.adopt("github", pid, None, Some(0)).unwrap(),
+            StartOutcome::Started
+        );
+        assert_eq!(manager.status("github").unwrap(), ServerStatus::Running);
+        assert_eq!(manager.running_pid("github").unwrap(), Some(pid));
+        assert_eq!(
+            manager.adopt("github", pid, None, None).unwrap(),
+            StartOutcome::AlreadyRunning
+        );
+        let err = manager.adopt("other", pid, None, None).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+        let audit = fs::read_to_string(manager.audit_log_path()).unwrap();
+        assert!(audit.contains("\"action\":\"adopt\""));
+        assert!(audit.contains("\"command\":\"sleep\""));
+
+        assert_eq!(manager.stop("github").unwrap(), StopOutcome::Stopped);
+        child.wait().unwrap();
+        assert_eq!(
+            manager.adopt("github", pid, None, None).unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
     }
 
+    #[cfg(unix)]
     #[test]
-    fn version_is_set() {
-        assert!(!version().is_empty());
+    fn relays_output_of_adopted_processes_until_they_exit() {
+        let (tmp, manager) = manager();
+        let out = tmp.path().join("external.log");
+        fs::write(&out, "before adoption\n").unwrap();
+        let mut child = Command::new("sh")
+            .args(["-c", "sleep 0.5; echo relayed; sleep 0.3"])
+            .stdout(OpenOptions::new().append(true).open(&out).unwrap())
+            .spawn()
+            .unwrap();
+        let pid = child.id();
+        manager.adopt("github", pid, None, None).unwrap();
+
+        let reaper = thread::spawn(move || child.wait());
+        manager.relay_output("github", pid, &out).unwrap();
+        reaper.join().unwrap().unwrap();
+        let log = fs::read_to_string(manager.log_path("github")).unwrap();
+        assert!(log.contains("ADOPT pid="));
+        assert!(log.contains("relayed"));
+        assert!(!log.contains("before adoption"));
     }
 
     #[test]
-    fn missing_state_defaults_to_stopped() {
+    fn permission_change_events_record_actor_and_both_sets() {
         let (_tmp, manager) = manager();
-        let status = manager.status("github").unwrap();
-        assert_eq!(status, ServerStatus::Stopped);
+        let actor = AuditActor {
+            user: "alice".to_string(),
+            uid: Some(1000),
+            ssh_client: Some("10.0.0.5".to_string()),
+            ..AuditActor::default()
+        };
+        manager
+            .record_permission_change(
+                "github",
+                &["grant".to_string(), "network:*:443".to_string()],
+                actor.clone(),
+                vec!["env:GITHUB_TOKEN".to_string()],
+                vec!["env:GITHUB_TOKEN".to_string(), "network:*:443".to_string()],
+            )
+            .unwrap();
+        let record = AuditLog::new(manager.audit_log_path())
+            .query(&AuditQuery::default())
+            .unwrap()
+            .entries
+            .pop()
+            .unwrap();
+        assert_eq!(record.action, PERMISSION_CHANGE_ACTION);
+        assert_eq!(record.actor, Some(actor));
+        assert_eq!(record.permissions_before.unwrap().len(), 1);
+        assert_eq!(record.permissions_after.unwrap().len(), 2);
+        assert_eq!(
+            record.args.unwrap(),
+            ["grant".to_string(), "network:*:443".to_string()]
+        );
     }
 
     #[test]
-    fn start_transitions_to_running() {
+    fn audit_sink_receives_written_entries() {
+        struct Collect(std::sync::Mutex<Vec<AuditRecord>>);
+        impl AuditSink for Collect {
+            fn record(&self, record: &AuditRecord) {
+                self.0.lock().unwrap().push(record.clone());
+            }
+        }
         let (_tmp, manager) = manager();
-        let spec = long_running_spec();
-        let outcome = manager.start("github", &spec).unwrap();
-        assert_eq!(outcome, StartOutcome::Started);
-        assert_eq!(manager.status("github").unwrap(), ServerStatus::Running);
-        let _ = manager.stop("github");
+        let sink = Arc::new(Collect(std::sync::Mutex::new(Vec::new())));
+        let manager = manager.with_audit_sink(sink.clone());
+        manager
+            .record_version_event("github", "install", "1.3.0", None)
+            .unwrap();
+        let records = sink.0.lock().unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].server, "github");
+        assert_eq!(records[0].action, "install");
+        assert_eq!(records[0].version.as_deref(), Some("1.3.0"));
+        assert!(records[0].timestamp_epoch_ms.is_some());
     }
 
     #[test]
-    fn starting_running_server_reports_already_running() {
+    fn audit_events_record_millisecond_and_legacy_second_timestamps() {
         let (_tmp, manager) = manager();
-        let spec = long_running_spec();
-        manager.start("github", &spec).unwrap();
-        let outcome = manager.start("github", &spec).unwrap();
-        assert_eq!(outcome, StartOutcome::AlreadyRunning);
-        let _ = manager.stop("github");
+        manager
+            .record_audit_event("github", "start", None, None, None)
+            .unwrap();
+        let line = fs::read_to_string(manager.audit_log_path()).unwrap();
+        let event: serde_json::Value = serde_json::from_str(line.trim()).unwrap();
+        let ms = event["timestampEpochMs"].as_u64().unwrap();
+        assert_eq!(event["timestampEpochSecs"].as_u64().unwrap(), ms / 1_000);
     }
 
     #[test]
-    fn stop_transitions_to_stopped() {
-        let (_tmp, manager) = manager();
-        let spec = long_running_spec();
-        manager.start("github", &spec).unwrap();
-        let outcome = manager.stop("github").unwrap();
-        assert_eq!(outcome, StopOutcome::Stopped);
-        assert_eq!(manager.status("github").unwrap(), ServerStatus::Stopped);
+    fn legacy_second_state_is_upgraded_to_milliseconds() {
+        let (tmp, manager) = manager();
+        let runtime_dir = tmp.path().join(".berth/runtime");
+        fs::create_dir_all(&runtime_dir).unwrap();
+        fs::write(
+            runtime_dir.join("github.toml"),
+            "status = \"stopped\"\nupdated_at_epoch_secs = 1700000000\n",
+        )
+        .unwrap();
+
+        let state = manager.read_state("github").unwrap();
+        assert_eq!(state.updated_at_epoch_ms, 1_700_000_000_000);
+        manager.write_state("github", &state).unwrap();
+        let rewritten = fs::read_to_string(runtime_dir.join("github.toml")).unwrap();
+        assert!(rewritten.contains("updated_at_epoch_ms = 1700000000000"));
+        assert!(!rewritten.contains("updated_at_epoch_secs"));
+    }
+
+    #[test]
+    fn status_values_keep_their_persisted_names() {
+        let (tmp, manager) = manager();
+        let runtime_dir = tmp.path().join(".berth/runtime");
+        fs::create_dir_all(&runtime_dir).unwrap();
+        fs::write(runtime_dir.join("github.toml"), "status = \"running\"\n").unwrap();
+        assert_eq!(
+            manager.read_state("github").unwrap().status,
+            ServerStatus::Running
+        );
+
+        let state = RuntimeState {
+            status: ServerStatus::CrashLooping,
+            ..RuntimeState::default()
+        };
+        manager.write_state("github", &state).unwrap();
+        let rewritten = fs::read_to_string(runtime_dir.join("github.toml")).unwrap();
+        assert!(rewritten.contains("status = \"crash-looping\""));
+        assert_eq!(ServerStatus::CrashLooping.to_string(), "crash-looping");
     }
 
     #[cfg(unix)]
     #[test]
-    fn stop_escalates_when_process_ignores_term() {
+    fn overdue_readiness_probe_reports_unhealthy() {
         let (_tmp, manager) = manager();
-        let spec = ignores_term_spec();
+        let mut spec = long_running_spec();
+        spec.readiness = Some(ReadinessCheck {
+            probe: ReadinessProbe::LogPattern {
+                pattern: "never printed".to_string(),
+            },
+            timeout_ms: 1,
+        });
         manager.start("github", &spec).unwrap();
-        let outcome = manager.stop("github").unwrap();
-        assert_eq!(outcome, StopOutcome::Stopped);
+        thread::sleep(Duration::from_millis(20));
+        assert_eq!(manager.status("github").unwrap(), ServerStatus::Unhealthy);
+        let _ = manager.stop("github");
         assert_eq!(manager.status("github").unwrap(), ServerStatus::Stopped);
     }
 
+    /// Writes state and log artifacts for `servers` under the manager home.
+    fn seed_artifacts(tmp: &tempfile::TempDir, servers: &[&str]) {
+        let home = tmp.path().join(".berth");
+        fs::create_dir_all(home.join("runtime")).unwrap();
+        fs::create_dir_all(home.join("logs")).unwrap();
+        for server in servers {
+            fs::write(
+                home.join("runtime").join(format!("{server}.toml")),
+                "status = \"stopped\"\n",
+            )
+            .unwrap();
+            fs::write(home.join("logs").join(format!("{server}.log")), "START\n").unwrap();
+        }
+    }
+
     #[test]
-    fn stopping_stopped_server_reports_already_stopped() {
-        let (_tmp, manager) = manager();
-        let outcome = manager.stop("github").unwrap();
-        assert_eq!(outcome, StopOutcome::AlreadyStopped);
+    fn prune_removes_artifacts_of_unknown_servers_only() {
+        let (tmp, manager) = manager();
+        seed_artifacts(&tmp, &["github", "github@2", "slack", "slack@1"]);
+        fs::write(tmp.path().join(".berth/runtime/slack.restart-flag"), "").unwrap();
+
+        let orphaned = manager.orphaned_artifacts(&["github".to_string()]).unwrap();
+        assert_eq!(orphaned.keys().collect::<Vec<_>>(), vec!["slack"]);
+        assert_eq!(orphaned["slack"].len(), 5);
+
+        let report = manager
+            .prune(&["github".to_string()], PruneMode::Remove)
+            .unwrap();
+        assert_eq!(report.servers, vec!["slack".to_string()]);
+        assert_eq!(report.files.len(), 5);
+        assert!(!manager.state_path("slack").exists());
+        assert!(!manager.log_path("slack@1").exists());
+        assert!(manager.state_path("github").exists());
+        assert!(manager.log_path("github@2").exists());
     }
 
     #[test]
-    fn restart_ends_in_running_state() {
-        let (_tmp, manager) = manager();
-        let spec = long_running_spec();
-        manager.restart("github", &spec).unwrap();
-        assert_eq!(manager.status("github").unwrap(), ServerStatus::Running);
-        let _ = manager.stop("github");
+    fn artifact_usage_sums_files_per_server() {
+        let (tmp, manager) = manager();
+        seed_artifacts(&tmp, &["github", "slack"]);
+        fs::write(tmp.path().join(".berth/logs/github.log.1"), "x".repeat(100)).unwrap();
+
+        let usage = manager.artifact_usage().unwrap();
+        let seeded = ("status = \"stopped\"\n".len() + "START\n".len()) as u64;
+        assert_eq!(usage["slack"], seeded);
+        assert_eq!(usage["github"], seeded + 100);
     }
 
     #[test]
-    fn tail_logs_returns_last_lines() {
-        let (_tmp, manager) = manager();
-        let spec = long_running_spec();
-        manager.start("github", &spec).unwrap();
-        manager.stop("github").unwrap();
-        manager.start("github", &spec).unwrap();
-        let _ = manager.stop("github");
+    fn prune_archive_moves_artifacts_under_archive_dir() {
+        let (tmp, manager) = manager();
+        seed_artifacts(&tmp, &["slack"]);
 
-        let lines = manager.tail_logs("github", 2).unwrap();
-        assert_eq!(lines.len(), 2);
-        assert!(lines.iter().any(|l| l.contains("STOP")));
-        assert!(lines.iter().any(|l| l.contains("START")));
+        let report = manager.prune(&[], PruneMode::Archive).unwrap();
+        let archive_dir = report.archive_dir.unwrap();
+        assert!(archive_dir.join("runtime/slack.toml").exists());
+        assert!(archive_dir.join("logs/slack.log").exists());
+        assert!(!manager.state_path("slack").exists());
     }
 
     #[test]
-    fn start_stop_writes_audit_events() {
+    fn prune_skips_servers_with_live_process() {
         let (_tmp, manager) = manager();
-        let spec = long_running_spec();
-        manager.start("github", &spec).unwrap();
+        manager.start("github", &long_running_spec()).unwrap();
+
+        let report = manager.prune(&[], PruneMode::Remove).unwrap();
+        assert_eq!(report.skipped_running, vec!["github".to_string()]);
+        assert!(manager.state_path("github").exists());
+        assert!(manager.remove_server_artifacts("github").is_err());
+
         manager.stop("github").unwrap();
+        let removed = manager.remove_server_artifacts("github").unwrap();
+        assert!(removed.contains(&manager.state_path("github")));
+        assert!(!manager.log_path("github").exists());
+    }
 
-        let audit_path = manager.audit_log_path();
-        let content = fs::read_to_string(audit_path).unwrap();
-        let lines: Vec<&str> = content.lines().collect();
-        assert!(lines.iter().any(|l| l.contains("\"action\":\"start\"")));
-        assert!(lines.iter().any(|l| l.contains("\"action\":\"stop\"")));
+    #[test]
+    fn prune_stale_removes_orphans_and_expired_logs_and_audit_entries() {
+        let (tmp, manager) = manager();
+        seed_artifacts(&tmp, &["github", "slack"]);
+        let logs = tmp.path().join(".berth/logs");
+        let crash = logs.join("github.1700000000000.crash");
+        fs::write(&crash, "report\n").unwrap();
+        let old = std::time::SystemTime::now() - Duration::from_secs(40 * 86_400);
+        fs::File::options()
+            .write(true)
+            .open(&crash)
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+        let now_ms = now_epoch_ms();
+        let audit = manager.audit_log_path();
+        fs::create_dir_all(audit.parent().unwrap()).unwrap();
+        fs::write(
+            &audit,
+            format!(
+                "{{\"timestampEpochSecs\":1000,\"server\":\"github\",\"action\":\"start\"}}\n{{\"timestampEpochMs\":{now_ms},\"server\":\"github\",\"action\":\"stop\"}}\n"
+            ),
+        )
+        .unwrap();
+
+        let policy = RetentionPolicy {
+            max_age: Duration::from_secs(30 * 86_400),
+            dry_run: true,
+        };
+        let known = ["github".to_string()];
+        let planned = manager.prune_stale(&known, policy).unwrap();
+        assert_eq!(planned.orphaned_files.len(), 2);
+        assert_eq!(planned.expired_logs, vec![crash.clone()]);
+        assert_eq!(planned.expired_audit_entries, 1);
+        assert!(planned.freed_bytes > 0);
+        assert!(crash.exists());
+        assert!(manager.state_path("slack").exists());
+
+        let report = manager
+            .prune_stale(
+                &known,
+                RetentionPolicy {
+                    dry_run: false,
+                    ..policy
+                },
+            )
+            .unwrap();
+        assert_eq!(report, planned);
+        assert!(!crash.exists());
+        assert!(!manager.state_path("slack").exists());
+        assert!(manager.log_path("github").exists());
+        let remaining = fs::read_to_string(&audit).unwrap();
+        assert_eq!(remaining.lines().count(), 1);
+        assert!(remaining.contains("stop"));
+    }
+
+    #[test]
+    fn stale_states_report_dead_pids_and_unreadable_files() {
+        let (tmp, manager) = manager();
+        seed_artifacts(&tmp, &["github"]);
+        let runtime_dir = tmp.path().join(".berth/runtime");
+        fs::write(runtime_dir.join("broken.toml"), "not = [valid").unwrap();
+        fs::write(
+            runtime_dir.join("slack.toml"),
+            "status = \"running\"\npid = 4294967\n",
+        )
+        .unwrap();
+
+        let stale = manager.stale_states().unwrap();
+        assert_eq!(stale.len(), 2, "{stale:?}");
+        assert!(matches!(&stale[0], StaleState::Unreadable { server, .. } if server == "broken"));
+        assert_eq!(
+            stale[1],
+            StaleState::DeadPid {
+                server: "slack".to_string(),
+                pid: 4294967
+            }
+        );
+        assert!(manager.state_path("slack").exists());
     }
 
     #[test]
@@ -1194,7 +4186,11 @@ mod tests {
         let _ = manager.status_with_spec("github", Some(&crash)).unwrap();
         wait_until_process_exits(&manager, "github");
         let second = manager.status_with_spec("github", Some(&crash)).unwrap();
-        assert_eq!(second, ServerStatus::Stopped);
+        assert_eq!(second, ServerStatus::CrashLooping);
+        assert_eq!(
+            manager.status("github").unwrap(),
+            ServerStatus::CrashLooping
+        );
 
         let audit = fs::read_to_string(manager.audit_log_path()).unwrap();
         let count = audit
@@ -1237,6 +4233,398 @@ mod tests {
         handle.join().unwrap().unwrap();
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn supervisor_relaunches_with_refreshed_spec_when_due() {
+        let (tmp, manager) = manager();
+        let mut due = long_running_spec();
+        due.refresh_at_epoch_secs = Some(0);
+        manager.start("github", &due).unwrap();
+        let first_pid = manager.read_state("github").unwrap().pid.unwrap();
+
+        let supervisor_manager = RuntimeManager::new(tmp.path().join(".berth"));
+        let handle = thread::spawn(move || {
+            supervisor_manager
+                .run_supervisor_with_refresh("github", &due, || Ok(long_running_spec()))
+        });
+
+        let mut relaunched = None;
+        for _ in 0..200 {
+            let state = manager.read_state("github").unwrap();
+            if state.pid.is_some_and(|pid| pid != first_pid) {
+                relaunched = state.pid;
+                break;
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+        let relaunched = relaunched.expect("supervisor should relaunch the process");
+        assert!(process_is_alive(relaunched));
+        assert!(!process_is_alive(first_pid));
+        let log = fs::read_to_string(manager.log_path("github")).unwrap();
+        assert!(log.contains(&format!("REFRESH pid={relaunched}")));
+
+        let _ = manager.stop("github");
+        handle.join().unwrap().unwrap();
+        let audit = fs::read_to_string(manager.audit_log_path()).unwrap();
+        assert!(audit.contains("\"action\":\"refresh\""));
+        assert!(!audit.contains("\"action\":\"auto-restart\""));
+    }
+
+    #[test]
+    fn restart_on_exit_codes_limits_which_exits_restart() {
+        let mut policy = AutoRestartPolicy {
+            enabled: true,
+            max_restarts: 3,
+            restart_on_exit_codes: None,
+        };
+        assert!(policy.restarts_on(Some(0)));
+        assert!(policy.restarts_on(None));
+        policy.restart_on_exit_codes = Some(vec![1, 137]);
+        assert!(policy.restarts_on(Some(137)));
+        assert!(!policy.restarts_on(Some(0)));
+        assert!(!policy.restarts_on(Some(2)));
+        assert!(policy.restarts_on(None));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn supervisor_marks_crash_loop_when_restart_budget_is_spent() {
+        let (_tmp, manager) = manager();
+        let spec = crash_spec_with_policy(2);
+        let mut launch_spec = spec.clone();
+        launch_spec.auto_restart = None;
+        let child = manager
+            .start_child("github", &launch_spec)
+            .unwrap()
+            .unwrap();
+        manager
+            .supervise_child("github", &spec, child, || Err("unused".to_string()))
+            .unwrap();
+
+        assert_eq!(
+            manager.status("github").unwrap(),
+            ServerStatus::CrashLooping
+        );
+        let log = fs::read_to_string(manager.log_path("github")).unwrap();
+        assert!(log.contains("CRASH_LOOP attempts=2"));
+        let audit = fs::read_to_string(manager.audit_log_path()).unwrap();
+        assert!(audit.contains("\"action\":\"crash-loop\""));
+
+        manager.start("github", &long_running_spec()).unwrap();
+        assert_eq!(manager.status("github").unwrap(), ServerStatus::Running);
+        let _ = manager.stop("github");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn launched_supervisor_records_exit_code_and_skips_clean_exits() {
+        let (_tmp, manager) = manager();
+        let mut spec = crash_spec_with_policy(3);
+        spec.args = vec!["-c".to_string(), "exit 0".to_string()];
+        if let Some(policy) = spec.auto_restart.as_mut() {
+            policy.restart_on_exit_codes = Some(vec![1]);
+        }
+        let mut launch_spec = spec.clone();
+        launch_spec.auto_restart = None;
+        let child = manager
+            .start_child("github", &launch_spec)
+            .unwrap()
+            .unwrap();
+        manager
+            .supervise_child("github", &spec, child, || Err("unused".to_string()))
+            .unwrap();
+
+        let state = manager.read_state("github").unwrap();
+        assert_eq!(state.status, ServerStatus::Stopped);
+        assert_eq!(state.last_exit_code, Some(0));
+        assert_eq!(state.restart_attempts, 0);
+        let log = fs::read_to_string(manager.log_path("github")).unwrap();
+        assert!(log.contains("CLEAN_EXIT code=0"));
+        let audit = fs::read_to_string(manager.audit_log_path()).unwrap();
+        assert!(audit.contains("\"exitCode\":0"));
+        assert!(!audit.contains("\"action\":\"auto-restart\""));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn watched_servers_without_supervision_record_their_exit() {
+        let (_tmp, manager) = manager();
+        let mut spec = crash_spec_with_policy(0);
+        spec.auto_restart = None;
+        spec.args = vec!["-c".to_string(), "exit 3".to_string()];
+        let child = manager.start_child("github", &spec).unwrap().unwrap();
+        manager
+            .watch_child("github", &spec, child, || Err("unused".to_string()))
+            .unwrap();
+
+        let state = manager.read_state("github").unwrap();
+        assert_eq!(state.status, ServerStatus::Stopped);
+        assert_eq!(state.last_exit_code, Some(3));
+        let log = fs::read_to_string(manager.log_path("github")).unwrap();
+        assert!(log.contains("EXIT code=3"));
+        assert!(!log.contains("AUTO_RESTART"));
+    }
+
+    /// Supervises a started server on a background thread.
+    fn supervise_in_background(
+        manager: &RuntimeManager,
+        spec: &ProcessSpec,
+    ) -> thread::JoinHandle<io::Result<()>> {
+        let child = manager.start_child("github", spec).unwrap().unwrap();
+        let (manager, spec) = (manager.clone(), spec.clone());
+        thread::spawn(move || {
+            manager.supervise_child("github", &spec, child, || Err("unused".to_string()))
+        })
+    }
+
+    fn wait_for_health(manager: &RuntimeManager, expected: Health) {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while manager.health("github").unwrap() != Some(expected) {
+            assert!(Instant::now() < deadline, "server never became {expected}");
+            thread::sleep(Duration::from_millis(50));
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn supervisor_records_health_check_verdicts() {
+        let (tmp, manager) = manager();
+        let marker = tmp.path().join("healthy");
+        let mut spec = long_running_spec();
+        spec.health_check = Some(HealthCheck {
+            probe: HealthProbe::Exec {
+                command: "test".to_string(),
+                args: vec!["-f".to_string(), marker.display().to_string()],
+            },
+            interval_secs: 1,
+            timeout_ms: 1_000,
+            failure_threshold: 2,
+        });
+        let supervisor = supervise_in_background(&manager, &spec);
+
+        wait_for_health(&manager, Health::Unhealthy);
+        assert_eq!(manager.status("github").unwrap(), ServerStatus::Unhealthy);
+        fs::write(&marker, "").unwrap();
+        wait_for_health(&manager, Health::Healthy);
+        assert_eq!(manager.status("github").unwrap(), ServerStatus::Running);
+
+        manager.stop("github").unwrap();
+        supervisor.join().unwrap().unwrap();
+        assert_eq!(manager.health("github").unwrap(), None);
+        let log = fs::read_to_string(manager.log_path("github")).unwrap();
+        assert!(log.contains("HEALTH_FAILED probe=`test` succeeds failures=2"));
+        assert!(log.contains("HEALTH_RECOVERED"));
+        let audit = fs::read_to_string(manager.audit_log_path()).unwrap();
+        assert!(audit.contains("\"action\":\"health-failed\""));
+        assert!(audit.contains("\"action\":\"health-recovered\""));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn mcp_ping_health_check_is_answered_over_stdin() {
+        let (_tmp, manager) = manager();
+        let mut spec = long_running_spec();
+        // Answers pings and ignores everything else, like the `initialize`.
+        spec.args = vec![
+            "-c".to_string(),
+            r#"while read -r line; do printf '%s\n' "$line" | sed -n 's/.*"id":"\(berth-health-[0-9]*\)".*/{"jsonrpc":"2.0","id":"\1","result":{}}/p'; done"#
+                .to_string(),
+        ];
+        spec.health_check = Some(HealthCheck {
+            probe: HealthProbe::McpPing,
+            interval_secs: 1,
+            timeout_ms: 2_000,
+            failure_threshold: 1,
+        });
+        let supervisor = supervise_in_background(&manager, &spec);
+
+        wait_for_health(&manager, Health::Healthy);
+        assert_eq!(manager.status("github").unwrap(), ServerStatus::Running);
+        manager.stop("github").unwrap();
+        supervisor.join().unwrap().unwrap();
+    }
+
+    #[test]
+    fn job_schedule_runs_on_interval_boundaries() {
+        let hourly = JobSchedule { every_secs: 3_600 };
+        assert_eq!(hourly.next_after(0), 3_600_000);
+        assert_eq!(hourly.next_after(3_599_999), 3_600_000);
+        assert_eq!(hourly.next_after(3_600_000), 7_200_000);
+        assert_eq!(hourly.to_string(), "every 1h");
+        assert_eq!(JobSchedule { every_secs: 90 }.to_string(), "every 90s");
+        assert_eq!(
+            JobSchedule {
+                every_secs: 172_800
+            }
+            .to_string(),
+            "every 2d"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn scheduled_job_runs_when_due_and_records_history() {
+        let (_tmp, manager) = manager();
+        let mut spec = long_running_spec();
+        spec.args = vec!["-c".to_string(), "echo ran; exit 3".to_string()];
+        spec.schedule = Some(JobSchedule { every_secs: 1 });
+
+        assert_eq!(
+            manager.schedule_job("nightly", &spec).unwrap(),
+            StartOutcome::Started
+        );
+        assert_eq!(manager.status("nightly").unwrap(), ServerStatus::Scheduled);
+        assert!(manager.next_job_run("nightly").unwrap().is_some());
+        let scheduler = {
+            let (manager, spec) = (manager.clone(), spec.clone());
+            thread::spawn(move || manager.run_job_schedule("nightly", &spec))
+        };
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while manager.job_runs("nightly", 10).unwrap().len() < 2 {
+            assert!(Instant::now() < deadline, "job never ran twice");
+            thread::sleep(Duration::from_millis(50));
+        }
+        assert_eq!(
+            manager.schedule_job("nightly", &spec).unwrap(),
+            StartOutcome::AlreadyRunning
+        );
+
+        manager.stop("nightly").unwrap();
+        scheduler.join().unwrap().unwrap();
+        assert_eq!(manager.status("nightly").unwrap(), ServerStatus::Stopped);
+        assert_eq!(manager.next_job_run("nightly").unwrap(), None);
+        let runs = manager.job_runs("nightly", 1).unwrap();
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].exit_code, Some(3));
+        assert!(!runs[0].succeeded());
+        let log = fs::read_to_string(manager.log_path("nightly")).unwrap();
+        assert!(log.contains("ran"));
+        assert!(log.contains("JOB_EXIT code=3"));
+        let audit = fs::read_to_string(manager.audit_log_path()).unwrap();
+        assert!(audit.contains("\"action\":\"schedule\""));
+        assert!(audit.contains("\"action\":\"job-exit\""));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn capture_core_lifts_core_limit_and_writes_crash_report() {
+        let (_tmp, manager) = manager();
+        let spec = ProcessSpec {
+            command: "sh".to_string(),
+            args: vec!["-c".to_string(), "ulimit -c; kill -KILL $$".to_string()],
+            capture_core: true,
+            ..ProcessSpec::default()
+        };
+        let hard_limit = Command::new("sh")
+            .args(["-c", "ulimit -H -c"])
+            .output()
+            .unwrap();
+        let child = manager.start_child("github", &spec).unwrap().unwrap();
+        manager
+            .supervise_child("github", &spec, child, || Err("unused".to_string()))
+            .unwrap();
+
+        let state = manager.read_state("github").unwrap();
+        assert_eq!(state.status, ServerStatus::Stopped);
+        assert_eq!(state.last_exit_code, Some(137));
+        let log = fs::read_to_string(manager.log_path("github")).unwrap();
+        assert!(log.contains(String::from_utf8_lossy(&hard_limit.stdout).trim()));
+        assert!(log.contains("CRASH signal=9 report="));
+        let report = fs::read_dir(manager.logs_dir())
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .find(|path| path.extension().is_some_and(|ext| ext == "crash"))
+            .unwrap();
+        let report = fs::read_to_string(report).unwrap();
+        assert!(report.contains("signal: 9"));
+        assert!(report.contains("core: not dumped"));
+        assert!(report.contains("recent log:"));
+    }
+
+    #[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+    #[test]
+    fn seccomp_violation_is_logged_with_a_crash_report() {
+        let (_tmp, manager) = manager();
+        // ptrace(PTRACE_TRACEME) is harmless but on the default deny list.
+        let mut spec = ProcessSpec {
+            command: "perl".to_string(),
+            args: vec![
+                "-e".to_string(),
+                "syscall(101, 0, 0, 0, 0); exit 0".to_string(),
+            ],
+            seccomp: Some(SeccompProfile::default()),
+            ..ProcessSpec::default()
+        };
+        if Command::new("perl").arg("-v").output().is_err() {
+            return;
+        }
+        let child = manager.start_child("github", &spec).unwrap().unwrap();
+        manager
+            .supervise_child("github", &spec, child, || Err("unused".to_string()))
+            .unwrap();
+
+        let state = manager.read_state("github").unwrap();
+        assert_eq!(state.last_exit_code, Some(128 + SIGSYS));
+        let log = fs::read_to_string(manager.log_path("github")).unwrap();
+        assert!(log.contains("SECCOMP_VIOLATION signal=SIGSYS"));
+        let report = fs::read_dir(manager.logs_dir())
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .find(|path| path.extension().is_some_and(|ext| ext == "crash"))
+            .unwrap();
+        let report = fs::read_to_string(report).unwrap();
+        assert!(report.contains("seccomp: blocked syscalls: "));
+
+        spec.seccomp = Some(SeccompProfile {
+            allow: vec!["ptrace".to_string()],
+        });
+        let child = manager.start_child("github", &spec).unwrap().unwrap();
+        manager
+            .supervise_child("github", &spec, child, || Err("unused".to_string()))
+            .unwrap();
+        assert_eq!(
+            manager.read_state("github").unwrap().last_exit_code,
+            Some(0)
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn launched_supervisor_restarts_on_listed_failure_codes() {
+        let (_tmp, manager) = manager();
+        let mut spec = crash_spec_with_policy(2);
+        spec.args = vec!["-c".to_string(), "exit 3".to_string()];
+        if let Some(policy) = spec.auto_restart.as_mut() {
+            policy.restart_on_exit_codes = Some(vec![3]);
+        }
+        let mut launch_spec = spec.clone();
+        launch_spec.auto_restart = None;
+        let child = manager
+            .start_child("github", &launch_spec)
+            .unwrap()
+            .unwrap();
+        manager
+            .supervise_child("github", &spec, child, || Err("unused".to_string()))
+            .unwrap();
+
+        let state = manager.read_state("github").unwrap();
+        assert_eq!(state.status, ServerStatus::CrashLooping);
+        assert_eq!(state.restart_attempts, 2);
+        assert_eq!(state.last_exit_code, Some(3));
+        let log = fs::read_to_string(manager.log_path("github")).unwrap();
+        assert_eq!(log.matches("EXIT code=3").count(), 3);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn exit_code_maps_signals_like_a_shell() {
+        let status = Command::new("sh")
+            .args(["-c", "kill -9 $$"])
+            .status()
+            .unwrap();
+        assert_eq!(exit_code(&status), Some(137));
+    }
+
     #[cfg(any(unix, windows))]
     #[test]
     fn tokio_supervisor_does_not_restart_when_server_denied_by_policy() {
@@ -0,0 +1,381 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Per-server CPU, memory and open file limits.
+//!
+//! On Linux a server with a memory or CPU limit is moved into its own cgroup
+//! v2 right after launch. Where Berth cannot create one (no cgroup2 mount, or
+//! its cgroup has no delegated `memory`/`cpu` controllers), memory falls back
+//! to `RLIMIT_DATA` and the CPU limit is not enforced. `RLIMIT_AS` is not used
+//! because V8 reserves far more address space than it ever touches, so node
+//! servers would fail to start under it. Other unix systems do not enforce the
+//! memory limit. The open file limit is always `RLIMIT_NOFILE`. On Windows the
+//! server is assigned to a Job Object carrying the memory and CPU caps; open
+//! files are not limited there.
+
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+
+/// Resource limits for a server process.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceLimits {
+    /// Memory cap in MiB, covering the server and its children.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_mb: Option<u64>,
+    /// CPU cap in percent of one core; above 100 allows several cores.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_percent: Option<u32>,
+    /// Maximum number of open file descriptors.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_open_files: Option<u64>,
+}
+
+impl ResourceLimits {
+    /// Returns whether no limit is set.
+    pub fn is_empty(&self) -> bool {
+        self.memory_mb.is_none() && self.cpu_percent.is_none() && self.max_open_files.is_none()
+    }
+}
+
+/// Sets up the limits that must be in place before the server is exec'd.
+#[cfg(unix)]
+pub(crate) fn configure(command: &mut Command, limits: &ResourceLimits) {
+    use std::os::unix::process::CommandExt;
+
+    let mut rlimits = Vec::new();
+    if let Some(files) = limits.max_open_files {
+        rlimits.push((libc::RLIMIT_NOFILE, files as libc::rlim_t));
+    }
+    // Elsewhere `attach` reports the memory and CPU limits as not enforced.
+    if cfg!(target_os = "linux") && needs_cgroup(limits) && cgroup::parent(limits).is_none() {
+        if let Some(mb) = limits.memory_mb {
+            tracing::warn!("no cgroup available; limiting memory with RLIMIT_DATA instead");
+            rlimits.push((libc::RLIMIT_DATA, (mb * 1024 * 1024) as libc::rlim_t));
+        }
+        if limits.cpu_percent.is_some() {
+            tracing::warn!("no cgroup available; the CPU limit is not enforced");
+        }
+    }
+    if rlimits.is_empty() {
+        return;
+    }
+    // SAFETY: the closure only calls `setrlimit`, which is async-signal-safe,
+    // on values computed before the fork.
+    unsafe {
+        command.pre_exec(move || {
+            for (resource, value) in &rlimits {
+                let limit = libc::rlimit {
+                    rlim_cur: *value,
+                    rlim_max: *value,
+                };
+                if libc::setrlimit(*resource, &limit) != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+            }
+            Ok(())
+        });
+    }
+}
+
+#[cfg(not(unix))]
+pub(crate) fn configure(_command: &mut Command, limits: &ResourceLimits) {
+    if limits.max_open_files.is_some() {
+        tracing::warn!("open file limits are only supported on unix; ignoring");
+    }
+}
+
+/// Places a launched server under the limits that need its pid: a cgroup on
+/// Linux, a Job Object on Windows.
+///
+/// Failures are logged and leave the server running without those limits.
+pub fn attach_limits(limits: &ResourceLimits, pid: u32) {
+    if !needs_cgroup(limits) {
+        return;
+    }
+    if let Err(e) = attach(limits, pid) {
+        tracing::warn!(pid, error = %e, "could not apply resource limits");
+    }
+}
+
+/// Returns whether the memory or CPU limit is set, which needs a cgroup or
+/// Job Object.
+fn needs_cgroup(limits: &ResourceLimits) -> bool {
+    limits.memory_mb.is_some() || limits.cpu_percent.is_some()
+}
+
+#[cfg(target_os = "linux")]
+fn attach(limits: &ResourceLimits, pid: u32) -> std::io::Result<()> {
+    // Without a cgroup, `configure` already fell back to rlimits.
+    let Some(parent) = cgroup::parent(limits) else {
+        return Ok(());
+    };
+    cgroup::remove_stale(&parent);
+    cgroup::create(&parent, limits, pid)
+}
+
+#[cfg(windows)]
+fn attach(limits: &ResourceLimits, pid: u32) -> std::io::Result<()> {
+    job_object::assign(limits, pid)
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+fn attach(_limits: &ResourceLimits, _pid: u32) -> std::io::Result<()> {
+    tracing::warn!("memory and CPU limits are only supported on Linux and Windows; ignoring");
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+mod cgroup {
+    use super::ResourceLimits;
+
+    /// Returns where server cgroups go; never available off Linux.
+    #[cfg_attr(not(unix), allow(dead_code))]
+    pub fn parent(_limits: &ResourceLimits) -> Option<std::path::PathBuf> {
+        None
+    }
+}
+
+/// Server cgroups, created next to Berth's own.
+#[cfg(target_os = "linux")]
+mod cgroup {
+    use std::fs;
+    use std::io;
+    use std::path::{Path, PathBuf};
+
+    use super::ResourceLimits;
+
+    const ROOT: &str = "/sys/fs/cgroup";
+    /// Leaf cgroup Berth moves itself into, so its own cgroup may delegate.
+    const LEAF: &str = "berth";
+    /// `cpu.max` period in microseconds.
+    const CPU_PERIOD_US: u64 = 100_000;
+
+    /// Returns Berth's cgroup when it can hold server cgroups with the
+    /// controllers `limits` needs, enabling them if necessary.
+    ///
+    /// A cgroup other than the root may only enable controllers for its
+    /// children while no process lives in it directly, so Berth first moves
+    /// itself into the `berth` leaf below it. Processes Berth cannot move,
+    /// such as the shell that started it, leave the controllers off.
+    pub fn parent(limits: &ResourceLimits) -> Option<PathBuf> {
+        let own = fs::read_to_string("/proc/self/cgroup").ok()?;
+        let relative = own.lines().find_map(|line| line.strip_prefix("0::"))?;
+        let mut dir = Path::new(ROOT).join(relative.trim().trim_start_matches('/'));
+        if dir.file_name().is_some_and(|name| name == LEAF) {
+            dir.pop();
+        }
+        let control = dir.join("cgroup.subtree_control");
+        let enabled = fs::read_to_string(&control).ok()?;
+        let wanted: Vec<&str> = [
+            limits.memory_mb.map(|_| "memory"),
+            limits.cpu_percent.map(|_| "cpu"),
+        ]
+        .into_iter()
+        .flatten()
+        .filter(|controller| !enabled.split_whitespace().any(|e| e == *controller))
+        .collect();
+        if !wanted.is_empty() {
+            if dir != Path::new(ROOT) {
+                let leaf = dir.join(LEAF);
+                fs::create_dir_all(&leaf).ok()?;
+                fs::write(leaf.join("cgroup.procs"), std::process::id().to_string()).ok()?;
+            }
+            // Fails with EBUSY while other processes still live in `dir`.
+            let request: Vec<String> = wanted.iter().map(|c| format!("+{c}")).collect();
+            fs::write(&control, request.join(" ")).ok()?;
+        }
+        Some(dir)
+    }
+
+    /// Creates `berth-<pid>` under `parent` with `limits` and moves `pid` in.
+    pub fn create(parent: &Path, limits: &ResourceLimits, pid: u32) -> io::Result<()> {
+        let dir = parent.join(format!("berth-{pid}"));
+        fs::create_dir_all(&dir)?;
+        if let Some(mb) = limits.memory_mb {
+            fs::write(dir.join("memory.max"), (mb * 1024 * 1024).to_string())?;
+        }
+        if let Some(percent) = limits.cpu_percent {
+            fs::write(dir.join("cpu.max"), cpu_max(percent))?;
+        }
+        fs::write(dir.join("cgroup.procs"), pid.to_string())?;
+        tracing::debug!(pid, cgroup = %dir.display(), "applied resource limits");
+        Ok(())
+    }
+
+    /// Removes cgroups of servers that have exited; populated ones stay.
+    pub fn remove_stale(parent: &Path) {
+        let Ok(entries) = fs::read_dir(parent) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            if entry.file_name().to_string_lossy().starts_with("berth-") {
+                let _ = fs::remove_dir(entry.path());
+            }
+        }
+    }
+
+    /// Renders a `cpu.max` value for `percent` of one core.
+    pub fn cpu_max(percent: u32) -> String {
+        let quota = (CPU_PERIOD_US * u64::from(percent) / 100).max(1_000);
+        format!("{quota} {CPU_PERIOD_US}")
+    }
+}
+
+/// Job Objects carrying a server's memory and CPU caps.
+#[cfg(windows)]
+#[allow(non_snake_case)]
+mod job_object {
+    use std::ffi::c_void;
+    use std::io;
+
+    use super::ResourceLimits;
+
+    const PROCESS_TERMINATE: u32 = 0x0001;
+    const PROCESS_SET_QUOTA: u32 = 0x0100;
+    const JOB_OBJECT_LIMIT_JOB_MEMORY: u32 = 0x0000_0200;
+    const JOB_OBJECT_CPU_RATE_CONTROL_ENABLE: u32 = 0x1;
+    const JOB_OBJECT_CPU_RATE_CONTROL_HARD_CAP: u32 = 0x4;
+    const EXTENDED_LIMIT_INFORMATION: i32 = 9;
+    const CPU_RATE_CONTROL_INFORMATION: i32 = 15;
+
+    #[repr(C)]
+    #[derive(Default)]
+    struct BasicLimitInformation {
+        per_process_user_time_limit: i64,
+        per_job_user_time_limit: i64,
+        limit_flags: u32,
+        minimum_working_set_size: usize,
+        maximum_working_set_size: usize,
+        active_process_limit: u32,
+        affinity: usize,
+        priority_class: u32,
+        scheduling_class: u32,
+    }
+
+    #[repr(C)]
+    #[derive(Default)]
+    struct ExtendedLimitInformation {
+        basic: BasicLimitInformation,
+        io_counters: [u64; 6],
+        process_memory_limit: usize,
+        job_memory_limit: usize,
+        peak_process_memory_used: usize,
+        peak_job_memory_used: usize,
+    }
+
+    #[repr(C)]
+    struct CpuRateControlInformation {
+        control_flags: u32,
+        cpu_rate: u32,
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn CreateJobObjectW(attributes: *mut c_void, name: *const u16) -> *mut c_void;
+        fn SetInformationJobObject(
+            job: *mut c_void,
+            class: i32,
+            info: *const c_void,
+            length: u32,
+        ) -> i32;
+        fn AssignProcessToJobObject(job: *mut c_void, process: *mut c_void) -> i32;
+        fn OpenProcess(access: u32, inherit: i32, pid: u32) -> *mut c_void;
+        fn CloseHandle(handle: *mut c_void) -> i32;
+    }
+
+    /// A handle closed on drop.
+    struct Handle(*mut c_void);
+
+    impl Drop for Handle {
+        fn drop(&mut self) {
+            // SAFETY: the handle is valid and closed once.
+            unsafe { CloseHandle(self.0) };
+        }
+    }
+
+    /// Creates a Job Object with `limits` and assigns `pid` to it.
+    ///
+    /// The job is not killed when its handle closes, so it lives exactly as
+    /// long as the server and its children.
+    pub fn assign(limits: &ResourceLimits, pid: u32) -> io::Result<()> {
+        // SAFETY: null attributes and name create an anonymous job.
+        let job = unsafe { CreateJobObjectW(std::ptr::null_mut(), std::ptr::null()) };
+        if job.is_null() {
+            return Err(io::Error::last_os_error());
+        }
+        let job = Handle(job);
+        if let Some(mb) = limits.memory_mb {
+            let mut info = ExtendedLimitInformation::default();
+            info.basic.limit_flags = JOB_OBJECT_LIMIT_JOB_MEMORY;
+            info.job_memory_limit = (mb * 1024 * 1024) as usize;
+            set(&job, EXTENDED_LIMIT_INFORMATION, &info)?;
+        }
+        if let Some(percent) = limits.cpu_percent {
+            let cores = std::thread::available_parallelism().map_or(1, |n| n.get() as u32);
+            // The rate is in hundredths of a percent of the whole machine.
+            let info = CpuRateControlInformation {
+                control_flags: JOB_OBJECT_CPU_RATE_CONTROL_ENABLE
+                    | JOB_OBJECT_CPU_RATE_CONTROL_HARD_CAP,
+                cpu_rate: (percent.saturating_mul(100) / cores).clamp(1, 10_000),
+            };
+            set(&job, CPU_RATE_CONTROL_INFORMATION, &info)?;
+        }
+        // SAFETY: OpenProcess takes plain integers and returns null on failure.
+        let process = unsafe { OpenProcess(PROCESS_SET_QUOTA | PROCESS_TERMINATE, 0, pid) };
+        if process.is_null() {
+            return Err(io::Error::last_os_error());
+        }
+        let process = Handle(process);
+        // SAFETY: both handles are valid for the duration of the call.
+        if unsafe { AssignProcessToJobObject(job.0, process.0) } == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    fn set<T>(job: &Handle, class: i32, info: &T) -> io::Result<()> {
+        // SAFETY: `info` is a correctly laid out struct for `class`.
+        let ok = unsafe {
+            SetInformationJobObject(
+                job.0,
+                class,
+                (info as *const T).cast(),
+                std::mem::size_of::<T>() as u32,
+            )
+        };
+        if ok == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn open_file_limit_is_applied_before_exec() {
+        let limits = ResourceLimits {
+            max_open_files: Some(64),
+            ..ResourceLimits::default()
+        };
+        let mut command = Command::new("sh");
+        command.args(["-c", "ulimit -n"]);
+        configure(&mut command, &limits);
+        let output = command.output().unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "64");
+        assert!(!limits.is_empty());
+        assert!(ResourceLimits::default().is_empty());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn cpu_percent_maps_to_cpu_max_quota() {
+        assert_eq!(cgroup::cpu_max(50), "50000 100000");
+        assert_eq!(cgroup::cpu_max(200), "200000 100000");
+        assert_eq!(cgroup::cpu_max(0), "1000 100000");
+    }
+}
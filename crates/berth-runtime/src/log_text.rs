@@ -0,0 +1,84 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Turning raw server log bytes into text that is safe to display.
+//!
+//! Server output is logged byte for byte, so binary or mixed-encoding output
+//! survives on disk for `berth logs --raw`. Everything that reads logs as text
+//! goes through [`sanitize_log_line`] instead of assuming UTF-8.
+
+/// Longest line, in bytes, shown before the rest is cut off.
+pub const MAX_LOG_LINE_BYTES: usize = 8 * 1024;
+
+/// Decodes one log line (without its newline) for display.
+///
+/// Invalid UTF-8 and control characters other than tab become U+FFFD, so
+/// binary output cannot garble a terminal, and lines over
+/// [`MAX_LOG_LINE_BYTES`] are truncated with a note of how much was dropped.
+pub fn sanitize_log_line(raw: &[u8]) -> String {
+    let raw = raw.strip_suffix(b"\r").unwrap_or(raw);
+    let (kept, dropped) = match raw.len().checked_sub(MAX_LOG_LINE_BYTES) {
+        Some(dropped) if dropped > 0 => (&raw[..MAX_LOG_LINE_BYTES], dropped),
+        _ => (raw, 0),
+    };
+    let mut line: String = String::from_utf8_lossy(kept)
+        .chars()
+        .map(|c| {
+            if c.is_control() && c != '\t' {
+                char::REPLACEMENT_CHARACTER
+            } else {
+                c
+            }
+        })
+        .collect();
+    if dropped > 0 {
+        line.push_str(&format!(" … [{dropped} bytes truncated]"));
+    }
+    line
+}
+
+/// Returns the last `lines` lines of `content`, as the original bytes.
+pub(crate) fn last_lines(content: &[u8], lines: usize) -> &[u8] {
+    if lines == 0 {
+        return &[];
+    }
+    let body = content.strip_suffix(b"\n").unwrap_or(content);
+    let start = body
+        .iter()
+        .enumerate()
+        .rev()
+        .filter(|(_, b)| **b == b'\n')
+        .nth(lines - 1)
+        .map_or(0, |(i, _)| i + 1);
+    &content[start..]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sanitize_replaces_invalid_bytes_and_controls() {
+        assert_eq!(sanitize_log_line(b"ok\tfine\r"), "ok\tfine");
+        assert_eq!(
+            sanitize_log_line(b"bin\xff\x00\x1b[2J"),
+            "bin\u{fffd}\u{fffd}\u{fffd}[2J"
+        );
+    }
+
+    #[test]
+    fn sanitize_caps_line_length() {
+        let line = sanitize_log_line(&vec![b'a'; MAX_LOG_LINE_BYTES + 10]);
+        assert!(line.starts_with(&"a".repeat(MAX_LOG_LINE_BYTES)));
+        assert!(line.ends_with(" … [10 bytes truncated]"));
+    }
+
+    #[test]
+    fn last_lines_keeps_original_bytes() {
+        let content = b"one\ntwo\xff\nthree\n";
+        assert_eq!(last_lines(content, 2), b"two\xff\nthree\n");
+        assert_eq!(last_lines(content, 5), content);
+        assert_eq!(last_lines(b"a\nb", 1), b"b");
+        assert!(last_lines(content, 0).is_empty());
+    }
+}
@@ -0,0 +1,644 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Handles to server processes as seen by the supervisor, and the native
+//! liveness checks and signals behind them.
+//!
+//! Liveness and termination go straight to the OS (`kill(2)` on unix,
+//! `OpenProcess`/`TerminateProcess` on windows) rather than through `ps`,
+//! `kill`, or `taskkill`, which are slow in polling loops and missing from
+//! minimal containers.
+
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::process::{Child, ExitStatus};
+use std::thread;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// How long a server gets to exit after its shutdown signal by default.
+pub const DEFAULT_SHUTDOWN_GRACE_PERIOD: Duration = Duration::from_secs(1);
+
+/// Signal that asks a server to shut down before it is force-killed.
+///
+/// Only meaningful on unix; windows terminates processes outright.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum ShutdownSignal {
+    #[default]
+    Sigterm,
+    Sigint,
+    Sighup,
+    Sigquit,
+}
+
+impl ShutdownSignal {
+    /// Parses a signal name such as `SIGINT`, `sigint`, or `INT`.
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let name = value.trim().to_ascii_uppercase();
+        match name.strip_prefix("SIG").unwrap_or(&name) {
+            "TERM" => Ok(ShutdownSignal::Sigterm),
+            "INT" => Ok(ShutdownSignal::Sigint),
+            "HUP" => Ok(ShutdownSignal::Sighup),
+            "QUIT" => Ok(ShutdownSignal::Sigquit),
+            _ => Err(format!(
+                "Invalid signal `{value}`. Expected `SIGTERM`, `SIGINT`, `SIGHUP`, or `SIGQUIT`."
+            )),
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ShutdownSignal::Sigterm => "SIGTERM",
+            ShutdownSignal::Sigint => "SIGINT",
+            ShutdownSignal::Sighup => "SIGHUP",
+            ShutdownSignal::Sigquit => "SIGQUIT",
+        }
+    }
+
+    #[cfg(unix)]
+    fn as_raw(self) -> libc::c_int {
+        match self {
+            ShutdownSignal::Sigterm => libc::SIGTERM,
+            ShutdownSignal::Sigint => libc::SIGINT,
+            ShutdownSignal::Sighup => libc::SIGHUP,
+            ShutdownSignal::Sigquit => libc::SIGQUIT,
+        }
+    }
+}
+
+/// How a server is asked to stop before being force-killed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Shutdown {
+    pub signal: ShutdownSignal,
+    pub grace_period: Duration,
+}
+
+impl Shutdown {
+    pub fn new(signal: Option<ShutdownSignal>, grace_period_secs: Option<u64>) -> Self {
+        Shutdown {
+            signal: signal.unwrap_or_default(),
+            grace_period: grace_period_secs
+                .map_or(DEFAULT_SHUTDOWN_GRACE_PERIOD, Duration::from_secs),
+        }
+    }
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        Shutdown::new(None, None)
+    }
+}
+
+/// How a process ended.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProcessExit {
+    /// Exit code of a process that exited normally.
+    pub code: Option<i32>,
+    /// Signal that killed the process.
+    pub signal: Option<i32>,
+    /// Whether the kernel dumped core for a signal death.
+    pub core_dumped: bool,
+}
+
+impl ProcessExit {
+    /// A normal exit with `code`.
+    pub fn code(code: i32) -> Self {
+        ProcessExit {
+            code: Some(code),
+            ..ProcessExit::default()
+        }
+    }
+
+    /// A death by `signal`.
+    pub fn signaled(signal: i32, core_dumped: bool) -> Self {
+        ProcessExit {
+            code: None,
+            signal: Some(signal),
+            core_dumped,
+        }
+    }
+
+    /// Returns the shell-style exit code, mapping signal `n` to `128 + n`.
+    pub fn exit_code(&self) -> Option<i32> {
+        self.signal.map(|signal| 128 + signal).or(self.code)
+    }
+
+    /// Returns the terminating signal and whether core was dumped.
+    pub fn crash(&self) -> Option<(i32, bool)> {
+        self.signal.map(|signal| (signal, self.core_dumped))
+    }
+}
+
+impl From<ExitStatus> for ProcessExit {
+    #[cfg(unix)]
+    fn from(status: ExitStatus) -> Self {
+        use std::os::unix::process::ExitStatusExt;
+        match status.signal() {
+            Some(signal) => ProcessExit::signaled(signal, status.core_dumped()),
+            None => ProcessExit {
+                code: status.code(),
+                ..ProcessExit::default()
+            },
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn from(status: ExitStatus) -> Self {
+        ProcessExit {
+            code: status.code(),
+            ..ProcessExit::default()
+        }
+    }
+}
+
+/// A running server process the supervisor watches.
+pub trait ProcessHandle: Send {
+    /// Returns the OS process id.
+    fn id(&self) -> u32;
+
+    /// Returns `Some` once the process is gone, with its exit when known.
+    ///
+    /// Processes this side did not spawn can only be seen to disappear, so
+    /// their exit is `None`.
+    fn try_wait(&mut self) -> io::Result<Option<Option<ProcessExit>>>;
+
+    /// Asks the process to stop, escalating if it does not.
+    fn terminate(&mut self) -> io::Result<()>;
+
+    /// Writes `bytes` to the process's stdin.
+    ///
+    /// Only processes spawned with input keep their stdin open; others
+    /// return [`io::ErrorKind::Unsupported`].
+    fn write_stdin(&mut self, _bytes: &[u8]) -> io::Result<()> {
+        Err(io::Error::from(io::ErrorKind::Unsupported))
+    }
+}
+
+/// A process spawned by this manager, whose exit status can be collected.
+pub struct ChildHandle(pub Child);
+
+impl ProcessHandle for ChildHandle {
+    fn id(&self) -> u32 {
+        self.0.id()
+    }
+
+    fn try_wait(&mut self) -> io::Result<Option<Option<ProcessExit>>> {
+        Ok(self.0.try_wait()?.map(|status| Some(status.into())))
+    }
+
+    fn terminate(&mut self) -> io::Result<()> {
+        terminate_process(self.0.id(), Shutdown::default())
+    }
+
+    fn write_stdin(&mut self, bytes: &[u8]) -> io::Result<()> {
+        let Some(stdin) = self.0.stdin.as_mut() else {
+            return Err(io::Error::from(io::ErrorKind::Unsupported));
+        };
+        stdin.write_all(bytes)?;
+        stdin.flush()
+    }
+}
+
+/// A child let go while still running is reaped in the background, so a
+/// long-lived supervisor such as `berth daemon` does not collect zombies.
+#[cfg(unix)]
+impl Drop for ChildHandle {
+    fn drop(&mut self) {
+        if !matches!(self.0.try_wait(), Ok(None)) {
+            return;
+        }
+        let Ok(pid) = libc::pid_t::try_from(self.0.id()) else {
+            return;
+        };
+        thread::spawn(move || {
+            // SAFETY: waits for one child that nothing else waits for any more.
+            unsafe { libc::waitpid(pid, std::ptr::null_mut(), 0) };
+        });
+    }
+}
+
+/// A process known only by pid, e.g. one started by another Berth invocation.
+pub struct PidHandle(pub u32);
+
+impl ProcessHandle for PidHandle {
+    fn id(&self) -> u32 {
+        self.0
+    }
+
+    fn try_wait(&mut self) -> io::Result<Option<Option<ProcessExit>>> {
+        Ok((!process_is_alive(self.0)).then_some(None))
+    }
+
+    fn terminate(&mut self) -> io::Result<()> {
+        terminate_process(self.0, Shutdown::default())
+    }
+}
+
+/// Returns whether a process is currently alive.
+///
+/// Zombies count as dead: they have exited and only wait to be reaped.
+#[cfg(unix)]
+pub(crate) fn process_is_alive(pid: u32) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    if pid <= 0 {
+        return false;
+    }
+    // Signal 0 only checks that the pid exists; EPERM means it does but
+    // belongs to another user.
+    // SAFETY: kill(2) with signal 0 delivers nothing.
+    let exists = unsafe { libc::kill(pid, 0) } == 0
+        || io::Error::last_os_error().raw_os_error() == Some(libc::EPERM);
+    exists && !is_zombie(pid)
+}
+
+#[cfg(target_os = "linux")]
+fn is_zombie(pid: libc::pid_t) -> bool {
+    // The state follows the parenthesized command name, which may itself
+    // contain spaces or parentheses.
+    std::fs::read_to_string(format!("/proc/{pid}/stat"))
+        .ok()
+        .and_then(|stat| {
+            let (_, rest) = stat.rsplit_once(')')?;
+            Some(rest.trim_start().starts_with('Z'))
+        })
+        .unwrap_or(false)
+}
+
+#[cfg(target_os = "macos")]
+fn is_zombie(pid: libc::pid_t) -> bool {
+    let mut info: libc::proc_bsdinfo = unsafe { std::mem::zeroed() };
+    let size = std::mem::size_of::<libc::proc_bsdinfo>() as libc::c_int;
+    // SAFETY: the buffer is a zeroed `proc_bsdinfo` of the size passed.
+    let read = unsafe {
+        libc::proc_pidinfo(
+            pid,
+            libc::PROC_PIDTBSDINFO,
+            0,
+            (&mut info as *mut libc::proc_bsdinfo).cast(),
+            size,
+        )
+    };
+    read == size && info.pbi_status == libc::SZOMB
+}
+
+#[cfg(all(unix, not(any(target_os = "linux", target_os = "macos"))))]
+fn is_zombie(_pid: libc::pid_t) -> bool {
+    false
+}
+
+/// Returns whether a process is currently alive.
+#[cfg(windows)]
+pub(crate) fn process_is_alive(pid: u32) -> bool {
+    let Some(handle) = win32::open(pid, win32::SYNCHRONIZE) else {
+        return false;
+    };
+    // SAFETY: `handle` is a valid process handle owned by `handle`.
+    unsafe { win32::WaitForSingleObject(handle.0, 0) == win32::WAIT_TIMEOUT }
+}
+
+/// Returns whether a process is currently alive.
+#[cfg(not(any(unix, windows)))]
+pub(crate) fn process_is_alive(_pid: u32) -> bool {
+    false
+}
+
+/// Sends the shutdown signal, escalating to SIGKILL if the process does not
+/// exit within the grace period.
+#[cfg(unix)]
+pub(crate) fn terminate_process(pid: u32, shutdown: Shutdown) -> io::Result<()> {
+    signal(pid, shutdown.signal.as_raw())
+        .map_err(|e| io::Error::new(e.kind(), format!("failed to signal process {pid}: {e}")))?;
+
+    if wait_for_process_exit(pid, shutdown.grace_period) {
+        return Ok(());
+    }
+
+    // Escalate if the process does not exit after the shutdown signal.
+    match signal(pid, libc::SIGKILL) {
+        Ok(()) => Ok(()),
+        // It exited between the last check and the kill.
+        Err(e) if e.raw_os_error() == Some(libc::ESRCH) => Ok(()),
+        Err(e) => Err(io::Error::new(
+            e.kind(),
+            format!("failed to force terminate process {pid}: {e}"),
+        )),
+    }
+}
+
+#[cfg(unix)]
+fn signal(pid: u32, signal: libc::c_int) -> io::Result<()> {
+    let pid = libc::pid_t::try_from(pid)
+        .ok()
+        .filter(|pid| *pid > 0)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "invalid pid"))?;
+    // SAFETY: kill(2) takes plain integers; a positive pid targets one process.
+    if unsafe { libc::kill(pid, signal) } == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+/// Terminates a process and its descendants, so `npx`-spawned servers do
+/// not linger.
+///
+/// Windows has no SIGTERM for console processes, so this is immediate and
+/// the shutdown signal is ignored; the grace period bounds the wait for exit.
+#[cfg(windows)]
+pub(crate) fn terminate_process(pid: u32, shutdown: Shutdown) -> io::Result<()> {
+    for child in win32::descendants(pid).into_iter().rev() {
+        if let Some(handle) = win32::open(child, win32::PROCESS_TERMINATE) {
+            // SAFETY: `handle` was opened with PROCESS_TERMINATE.
+            unsafe { win32::TerminateProcess(handle.0, 1) };
+        }
+    }
+    let handle =
+        win32::open(pid, win32::PROCESS_TERMINATE | win32::SYNCHRONIZE).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!(
+                    "failed to open process {pid}: {}",
+                    io::Error::last_os_error()
+                ),
+            )
+        })?;
+    // SAFETY: `handle` was opened with PROCESS_TERMINATE.
+    if unsafe { win32::TerminateProcess(handle.0, 1) } == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!(
+                "failed to terminate process {pid}: {}",
+                io::Error::last_os_error()
+            ),
+        ));
+    }
+    if wait_for_process_exit(pid, shutdown.grace_period) {
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::TimedOut,
+            format!("process {pid} did not exit after termination"),
+        ))
+    }
+}
+
+/// Terminates a process.
+#[cfg(not(any(unix, windows)))]
+pub(crate) fn terminate_process(_pid: u32, _shutdown: Shutdown) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "process termination is not supported on this platform",
+    ))
+}
+
+/// Waits up to `timeout` for a process to exit, checking liveness repeatedly.
+fn wait_for_process_exit(pid: u32, timeout: Duration) -> bool {
+    const INTERVAL: Duration = Duration::from_millis(20);
+    let attempts = (timeout.as_millis() / INTERVAL.as_millis()).max(1);
+    for _ in 0..attempts {
+        if !process_is_alive(pid) {
+            return true;
+        }
+        thread::sleep(INTERVAL);
+    }
+    !process_is_alive(pid)
+}
+
+/// Returns the pid and arguments of every process this user can see, for
+/// matching servers started outside Berth.
+#[cfg(target_os = "linux")]
+pub fn process_table() -> Vec<(u32, Vec<String>)> {
+    let Ok(entries) = std::fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+    let mut table: Vec<(u32, Vec<String>)> = entries
+        .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse::<u32>().ok())
+        .filter_map(|pid| Some((pid, process_command_line(pid)?)))
+        .collect();
+    table.sort_by_key(|(pid, _)| *pid);
+    table
+}
+
+/// Returns the pid and arguments of every process this user can see, for
+/// matching servers started outside Berth.
+///
+/// Runs `ps` once, which splits arguments on whitespace.
+#[cfg(all(unix, not(target_os = "linux")))]
+pub fn process_table() -> Vec<(u32, Vec<String>)> {
+    let Ok(output) = std::process::Command::new("ps")
+        .args(["-axww", "-o", "pid=", "-o", "args="])
+        .output()
+    else {
+        return Vec::new();
+    };
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let pid = parts.next()?.parse().ok()?;
+            let args: Vec<String> = parts.map(str::to_string).collect();
+            (!args.is_empty()).then_some((pid, args))
+        })
+        .collect()
+}
+
+/// Returns the pid and arguments of every process this user can see.
+#[cfg(not(unix))]
+pub fn process_table() -> Vec<(u32, Vec<String>)> {
+    Vec::new()
+}
+
+/// Returns the arguments a live process was started with; `None` for kernel
+/// threads, exited processes, and processes the table cannot see.
+pub fn process_command_line(pid: u32) -> Option<Vec<String>> {
+    #[cfg(target_os = "linux")]
+    {
+        let raw = std::fs::read(format!("/proc/{pid}/cmdline")).ok()?;
+        let args: Vec<String> = raw
+            .split(|byte| *byte == 0)
+            .filter(|arg| !arg.is_empty())
+            .map(|arg| String::from_utf8_lossy(arg).into_owned())
+            .collect();
+        (!args.is_empty() && process_is_alive(pid)).then_some(args)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        process_table()
+            .into_iter()
+            .find(|(candidate, _)| *candidate == pid)
+            .map(|(_, args)| args)
+    }
+}
+
+/// Returns the regular files a process writes its stdout and stderr to.
+///
+/// Output going to a terminal or pipe cannot be read by anyone else, so it is
+/// left out; only Linux exposes where a process's descriptors point.
+pub fn output_files(pid: u32) -> Vec<PathBuf> {
+    #[cfg(target_os = "linux")]
+    {
+        let mut files: Vec<PathBuf> = Vec::new();
+        for fd in [1, 2] {
+            let Ok(target) = std::fs::read_link(format!("/proc/{pid}/fd/{fd}")) else {
+                continue;
+            };
+            if target.is_file() && !files.contains(&target) {
+                files.push(target);
+            }
+        }
+        files
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = pid;
+        Vec::new()
+    }
+}
+
+/// The few kernel32 calls needed, declared here to avoid a bindings crate.
+#[cfg(windows)]
+#[allow(non_snake_case)]
+mod win32 {
+    use std::ffi::c_void;
+
+    pub const PROCESS_TERMINATE: u32 = 0x0001;
+    pub const SYNCHRONIZE: u32 = 0x0010_0000;
+    pub const WAIT_TIMEOUT: u32 = 0x0000_0102;
+    const TH32CS_SNAPPROCESS: u32 = 0x0000_0002;
+    const INVALID_HANDLE_VALUE: *mut c_void = -1isize as *mut c_void;
+
+    #[repr(C)]
+    struct ProcessEntry32W {
+        size: u32,
+        usage: u32,
+        process_id: u32,
+        default_heap_id: usize,
+        module_id: u32,
+        threads: u32,
+        parent_process_id: u32,
+        priority_class_base: i32,
+        flags: u32,
+        exe_file: [u16; 260],
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn OpenProcess(access: u32, inherit: i32, pid: u32) -> *mut c_void;
+        fn CloseHandle(handle: *mut c_void) -> i32;
+        fn CreateToolhelp32Snapshot(flags: u32, pid: u32) -> *mut c_void;
+        fn Process32FirstW(snapshot: *mut c_void, entry: *mut ProcessEntry32W) -> i32;
+        fn Process32NextW(snapshot: *mut c_void, entry: *mut ProcessEntry32W) -> i32;
+        pub fn TerminateProcess(process: *mut c_void, exit_code: u32) -> i32;
+        pub fn WaitForSingleObject(handle: *mut c_void, millis: u32) -> u32;
+    }
+
+    /// A process handle closed on drop.
+    pub struct Handle(pub *mut c_void);
+
+    impl Drop for Handle {
+        fn drop(&mut self) {
+            // SAFETY: the handle came from OpenProcess and is closed once.
+            unsafe { CloseHandle(self.0) };
+        }
+    }
+
+    /// Opens `pid` with `access`; `None` if it does not exist or is denied.
+    pub fn open(pid: u32, access: u32) -> Option<Handle> {
+        // SAFETY: OpenProcess takes plain integers and returns null on failure.
+        let handle = unsafe { OpenProcess(access, 0, pid) };
+        (!handle.is_null()).then_some(Handle(handle))
+    }
+
+    /// Returns the descendants of `pid`, parents before their children.
+    pub fn descendants(pid: u32) -> Vec<u32> {
+        // SAFETY: a process snapshot takes plain integers.
+        let snapshot = unsafe { CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0) };
+        if snapshot == INVALID_HANDLE_VALUE {
+            return Vec::new();
+        }
+        let snapshot = Handle(snapshot);
+        // SAFETY: an all-zero entry is valid; `size` is set before use.
+        let mut entry: ProcessEntry32W = unsafe { std::mem::zeroed() };
+        entry.size = std::mem::size_of::<ProcessEntry32W>() as u32;
+        let mut parents = Vec::new();
+        // SAFETY: `entry` is a correctly sized PROCESSENTRY32W.
+        let mut more = unsafe { Process32FirstW(snapshot.0, &mut entry) } != 0;
+        while more {
+            parents.push((entry.process_id, entry.parent_process_id));
+            // SAFETY: as above.
+            more = unsafe { Process32NextW(snapshot.0, &mut entry) } != 0;
+        }
+
+        let mut tree = vec![pid];
+        let mut next = 0;
+        while next < tree.len() {
+            let parent = tree[next];
+            tree.extend(
+                parents
+                    .iter()
+                    .filter(|(child, of)| *of == parent && *child != pid && !tree.contains(child))
+                    .map(|(child, _)| *child)
+                    .collect::<Vec<_>>(),
+            );
+            next += 1;
+        }
+        tree.remove(0);
+        tree
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    #[test]
+    fn liveness_and_termination_use_native_signals() {
+        let mut child = Command::new("sleep").arg("30").spawn().unwrap();
+        let pid = child.id();
+        assert!(process_is_alive(pid));
+
+        terminate_process(pid, Shutdown::default()).unwrap();
+        // Unreaped, the child is a zombie, which already counts as dead.
+        assert!(!process_is_alive(pid));
+        child.wait().unwrap();
+        assert!(!process_is_alive(pid));
+        assert!(!process_is_alive(0));
+        assert!(!process_is_alive(u32::MAX));
+    }
+
+    #[test]
+    fn reads_command_lines_and_output_files_of_other_processes() {
+        let tmp = tempfile::tempdir().unwrap();
+        let out = tmp.path().join("out.log");
+        let mut child = Command::new("sleep")
+            .arg("30")
+            .stdout(std::fs::File::create(&out).unwrap())
+            .spawn()
+            .unwrap();
+        let pid = child.id();
+        // Until the child execs, it still carries the test's arguments.
+        while process_command_line(pid).is_none_or(|args| args[0] != "sleep") {
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        assert_eq!(
+            process_command_line(pid).unwrap(),
+            ["sleep".to_string(), "30".to_string()]
+        );
+        assert!(process_table()
+            .iter()
+            .any(|(candidate, _)| *candidate == pid));
+        if cfg!(target_os = "linux") {
+            assert_eq!(output_files(pid), [out.canonicalize().unwrap()]);
+        }
+
+        child.kill().unwrap();
+        child.wait().unwrap();
+        assert_eq!(process_command_line(pid), None);
+    }
+}
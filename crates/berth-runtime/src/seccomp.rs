@@ -0,0 +1,251 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Seccomp syscall filtering for spawned servers (Linux only).
+//!
+//! The default profile is a deny list: syscalls that node and python MCP
+//! servers have no business making (kernel modules, mounts, tracing other
+//! processes, ...) kill the process with `SIGSYS`. Servers that genuinely need
+//! one of them declare it in their manifest's `runtime.syscalls`.
+
+use serde::{Deserialize, Serialize};
+
+/// Signal the kernel sends when a process makes a syscall its filter kills.
+pub const SIGSYS: i32 = 31;
+
+/// Syscalls blocked by the default profile as `(name, x86_64, aarch64)` numbers.
+const DEFAULT_DENIED: &[(&str, u32, u32)] = &[
+    ("acct", 163, 89),
+    ("adjtimex", 159, 171),
+    ("bpf", 321, 280),
+    ("clock_settime", 227, 112),
+    ("delete_module", 176, 106),
+    ("finit_module", 313, 273),
+    ("fsmount", 432, 432),
+    ("fsopen", 430, 430),
+    ("init_module", 175, 105),
+    ("kexec_file_load", 320, 294),
+    ("kexec_load", 246, 104),
+    ("mount", 165, 40),
+    ("move_mount", 429, 429),
+    ("open_by_handle_at", 304, 265),
+    ("open_tree", 428, 428),
+    ("perf_event_open", 298, 241),
+    ("pivot_root", 155, 41),
+    ("process_vm_readv", 310, 270),
+    ("process_vm_writev", 311, 271),
+    ("ptrace", 101, 117),
+    ("quotactl", 179, 60),
+    ("reboot", 169, 142),
+    ("settimeofday", 164, 170),
+    ("swapoff", 168, 225),
+    ("swapon", 167, 224),
+    ("umount2", 166, 39),
+    ("userfaultfd", 323, 282),
+];
+
+/// Syscall filter applied to a server process at spawn.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeccompProfile {
+    /// Syscalls from the default deny list that this server may use anyway.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allow: Vec<String>,
+}
+
+impl SeccompProfile {
+    /// Returns the names of the syscalls this profile blocks.
+    pub fn denied(&self) -> Vec<&'static str> {
+        DEFAULT_DENIED
+            .iter()
+            .map(|(name, _, _)| *name)
+            .filter(|name| !self.allow.iter().any(|allowed| allowed == name))
+            .collect()
+    }
+}
+
+/// Returns whether the default profile blocks a syscall, i.e. whether listing
+/// it as required changes anything.
+pub fn is_filtered_syscall(name: &str) -> bool {
+    DEFAULT_DENIED.iter().any(|(denied, _, _)| *denied == name)
+}
+
+/// Compiles a profile into a classic BPF program for `SECCOMP_MODE_FILTER`.
+///
+/// Returns `None` on architectures without a syscall table here, so callers
+/// can fall back to an unfiltered launch.
+#[cfg(target_os = "linux")]
+pub(crate) fn compile(profile: &SeccompProfile) -> Option<Vec<libc::sock_filter>> {
+    const LD_W_ABS: u16 = (libc::BPF_LD | libc::BPF_W | libc::BPF_ABS) as u16;
+    const JEQ_K: u16 = (libc::BPF_JMP | libc::BPF_JEQ | libc::BPF_K) as u16;
+    const JGE_K: u16 = (libc::BPF_JMP | libc::BPF_JGE | libc::BPF_K) as u16;
+    const RET_K: u16 = (libc::BPF_RET | libc::BPF_K) as u16;
+    // Offsets into `struct seccomp_data`.
+    const NR_OFFSET: u32 = 0;
+    const ARCH_OFFSET: u32 = 4;
+    // Syscall numbers at or above this bit select the x32 ABI on x86_64.
+    const X32_SYSCALL_BIT: u32 = 0x4000_0000;
+    // `AUDIT_ARCH_*` values from <linux/audit.h>.
+    const AUDIT_ARCH_X86_64: u32 = 0xC000_003E;
+    const AUDIT_ARCH_AARCH64: u32 = 0xC000_00B7;
+
+    let stmt = |code, k| libc::sock_filter {
+        code,
+        jt: 0,
+        jf: 0,
+        k,
+    };
+    let jump = |code, k, jt, jf| libc::sock_filter { code, jt, jf, k };
+
+    let (arch, x86) = if cfg!(target_arch = "x86_64") {
+        (AUDIT_ARCH_X86_64, true)
+    } else if cfg!(target_arch = "aarch64") {
+        (AUDIT_ARCH_AARCH64, false)
+    } else {
+        return None;
+    };
+
+    let mut program = vec![
+        stmt(LD_W_ABS, ARCH_OFFSET),
+        jump(JEQ_K, arch, 1, 0),
+        stmt(RET_K, libc::SECCOMP_RET_KILL_PROCESS),
+        stmt(LD_W_ABS, NR_OFFSET),
+    ];
+    if x86 {
+        program.push(jump(JGE_K, X32_SYSCALL_BIT, 0, 1));
+        program.push(stmt(RET_K, libc::SECCOMP_RET_KILL_PROCESS));
+    }
+    for (name, x86_64, aarch64) in DEFAULT_DENIED {
+        if profile.allow.iter().any(|allowed| allowed == name) {
+            continue;
+        }
+        let nr = if x86 { *x86_64 } else { *aarch64 };
+        program.push(jump(JEQ_K, nr, 0, 1));
+        program.push(stmt(RET_K, libc::SECCOMP_RET_KILL_PROCESS));
+    }
+    program.push(stmt(RET_K, libc::SECCOMP_RET_ALLOW));
+    Some(program)
+}
+
+/// Installs `program` in the calling process; meant for `pre_exec`.
+///
+/// Sets `no_new_privs` first, as unprivileged filters require.
+#[cfg(target_os = "linux")]
+pub(crate) fn install(program: &[libc::sock_filter]) -> std::io::Result<()> {
+    let fprog = libc::sock_fprog {
+        len: program.len() as u16,
+        filter: program.as_ptr().cast_mut(),
+    };
+    // SAFETY: both calls only read their arguments; `fprog` points at a
+    // program that outlives the call, and neither allocates, so this is safe
+    // between fork and exec.
+    unsafe {
+        if libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        if libc::prctl(
+            libc::PR_SET_SECCOMP,
+            libc::SECCOMP_MODE_FILTER,
+            &fprog as *const libc::sock_fprog,
+        ) != 0
+        {
+            return Err(std::io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+/// A compiled filter on its way into a spawned server.
+#[cfg(target_os = "linux")]
+pub(crate) enum Filter {
+    /// Installed in the child between fork and exec.
+    PreExec(Vec<libc::sock_filter>),
+    /// Inherited by bubblewrap, which reads it from `--seccomp <fd>`.
+    Bwrap(std::os::fd::OwnedFd),
+}
+
+#[cfg(target_os = "linux")]
+impl Filter {
+    /// Makes `command` apply the filter when it spawns.
+    pub(crate) fn attach(self, command: &mut std::process::Command) {
+        use std::os::fd::AsRawFd;
+        use std::os::unix::process::CommandExt;
+
+        match self {
+            // SAFETY: the closure only calls `prctl` on a program compiled
+            // before the fork, which is async-signal-safe.
+            Filter::PreExec(program) => unsafe {
+                command.pre_exec(move || install(&program));
+            },
+            // SAFETY: `fcntl` is async-signal-safe. The closure owns the fd,
+            // so it stays open until the command is dropped, and only the
+            // child clears close-on-exec on its copy.
+            Filter::Bwrap(fd) => unsafe {
+                command.pre_exec(move || {
+                    if libc::fcntl(fd.as_raw_fd(), libc::F_SETFD, 0) != 0 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                    Ok(())
+                });
+            },
+        }
+    }
+}
+
+/// Writes `program` to an anonymous file in the raw `struct sock_filter`
+/// layout `bwrap --seccomp` reads, rewound to the start.
+#[cfg(target_os = "linux")]
+pub(crate) fn to_memfd(program: &[libc::sock_filter]) -> std::io::Result<std::os::fd::OwnedFd> {
+    use std::io::{Seek, Write};
+    use std::os::fd::FromRawFd;
+
+    // SAFETY: the name is a valid C string; the result is checked below.
+    let fd = unsafe { libc::memfd_create(c"berth-seccomp".as_ptr(), libc::MFD_CLOEXEC) };
+    if fd < 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    // SAFETY: `fd` was just created and nothing else owns it.
+    let mut file = unsafe { std::fs::File::from_raw_fd(fd) };
+    let mut bytes = Vec::with_capacity(program.len() * 8);
+    for insn in program {
+        bytes.extend(insn.code.to_ne_bytes());
+        bytes.extend([insn.jt, insn.jf]);
+        bytes.extend(insn.k.to_ne_bytes());
+    }
+    file.write_all(&bytes)?;
+    file.rewind()?;
+    Ok(file.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allowed_syscalls_are_removed_from_the_deny_list() {
+        let profile = SeccompProfile {
+            allow: vec!["ptrace".to_string()],
+        };
+        assert!(!profile.denied().contains(&"ptrace"));
+        assert!(profile.denied().contains(&"mount"));
+        assert!(is_filtered_syscall("bpf"));
+        assert!(!is_filtered_syscall("read"));
+    }
+
+    #[cfg(all(
+        target_os = "linux",
+        any(target_arch = "x86_64", target_arch = "aarch64")
+    ))]
+    #[test]
+    fn compiled_program_checks_each_denied_syscall() {
+        let default = compile(&SeccompProfile::default()).unwrap();
+        let relaxed = compile(&SeccompProfile {
+            allow: vec!["ptrace".to_string(), "mount".to_string()],
+        })
+        .unwrap();
+        assert_eq!(default.len() - relaxed.len(), 4);
+        assert_eq!(
+            default.last().map(|insn| insn.k),
+            Some(libc::SECCOMP_RET_ALLOW)
+        );
+    }
+}
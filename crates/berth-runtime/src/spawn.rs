@@ -0,0 +1,64 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! How the runtime manager turns a [`ProcessSpec`] into a running process.
+//!
+//! Start, auto-restart and the supervisor all launch through a [`Spawner`], so
+//! other backends and test doubles can replace the local `Command` launch
+//! without touching the state machine around it.
+
+use std::fs::File;
+use std::io::{self, Write};
+use std::process::Stdio;
+
+use crate::process::{ChildHandle, ProcessHandle};
+use crate::{attach_limits, process_command, ProcessSpec};
+
+/// Launches server processes.
+pub trait Spawner: Send + Sync {
+    /// Starts `spec` with stdout and stderr appended to `log`.
+    ///
+    /// `input`, when given, is written to the server's stdin right after
+    /// launch; otherwise stdin is closed.
+    fn spawn(
+        &self,
+        spec: &ProcessSpec,
+        log: File,
+        input: Option<&[u8]>,
+    ) -> io::Result<Box<dyn ProcessHandle>>;
+}
+
+/// Spawns servers as local child processes via [`process_command`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CommandSpawner;
+
+impl Spawner for CommandSpawner {
+    fn spawn(
+        &self,
+        spec: &ProcessSpec,
+        log: File,
+        input: Option<&[u8]>,
+    ) -> io::Result<Box<dyn ProcessHandle>> {
+        let err = log.try_clone()?;
+        let stdin = if input.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        };
+        let mut child = process_command(spec)
+            .stdin(stdin)
+            .stdout(Stdio::from(log))
+            .stderr(Stdio::from(err))
+            .spawn()
+            .map_err(|e| io::Error::new(e.kind(), format!("failed to spawn process: {e}")))?;
+        if let Some(limits) = &spec.limits {
+            attach_limits(limits, child.id());
+        }
+        if let (Some(input), Some(stdin)) = (input, child.stdin.as_mut()) {
+            // A server that exits right away closes the pipe; its exit is
+            // reported through the handle instead.
+            let _ = stdin.write_all(input).and_then(|()| stdin.flush());
+        }
+        Ok(Box::new(ChildHandle(child)))
+    }
+}
@@ -0,0 +1,113 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Advisory locks that serialize read-modify-write of runtime state.
+//!
+//! Each server has a `runtime/<server>.lock` file next to its state. Code that
+//! reads state, decides, and writes it back holds the lock for that whole
+//! step, so two `berth` invocations or a command and a supervisor cannot
+//! clobber each other's changes. Plain reads take no lock; state files are
+//! replaced by rename, so a reader always sees a complete file.
+//!
+//! Locks are re-entrant per thread: a locked step may call another one for the
+//! same server, such as `stop` from a readiness timeout.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+
+thread_local! {
+    /// Lock files this thread holds, with how many guards share each.
+    static HELD: RefCell<HashMap<PathBuf, (File, usize)>> = RefCell::new(HashMap::new());
+}
+
+/// Holds a server's state lock until dropped.
+///
+/// Not `Send`: the lock belongs to the thread that took it.
+#[derive(Debug)]
+pub(crate) struct StateLock {
+    path: PathBuf,
+    _thread_bound: PhantomData<*const ()>,
+}
+
+impl StateLock {
+    /// Blocks until this thread holds the lock file at `path`, creating it
+    /// if needed.
+    pub(crate) fn acquire(path: &Path) -> io::Result<StateLock> {
+        let reentered = HELD.with(|held| {
+            let mut held = held.borrow_mut();
+            held.get_mut(path).map(|(_, count)| *count += 1).is_some()
+        });
+        if !reentered {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            let file = OpenOptions::new()
+                .create(true)
+                .truncate(false)
+                .write(true)
+                .open(path)?;
+            file.lock()?;
+            HELD.with(|held| held.borrow_mut().insert(path.to_path_buf(), (file, 1)));
+        }
+        Ok(StateLock {
+            path: path.to_path_buf(),
+            _thread_bound: PhantomData,
+        })
+    }
+}
+
+impl Drop for StateLock {
+    fn drop(&mut self) {
+        HELD.with(|held| {
+            let mut held = held.borrow_mut();
+            let Some((_, count)) = held.get_mut(&self.path) else {
+                return;
+            };
+            *count -= 1;
+            if *count == 0 {
+                if let Some((file, _)) = held.remove(&self.path) {
+                    let _ = file.unlock();
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn serializes_threads_and_reenters_on_one() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("runtime").join("github.lock");
+        let inside = Arc::new(AtomicUsize::new(0));
+        let workers: Vec<_> = (0..4)
+            .map(|_| {
+                let path = path.clone();
+                let inside = Arc::clone(&inside);
+                thread::spawn(move || {
+                    for _ in 0..10 {
+                        let _outer = StateLock::acquire(&path).unwrap();
+                        let _inner = StateLock::acquire(&path).unwrap();
+                        assert_eq!(inside.fetch_add(1, Ordering::SeqCst), 0);
+                        thread::sleep(Duration::from_millis(1));
+                        inside.fetch_sub(1, Ordering::SeqCst);
+                    }
+                })
+            })
+            .collect();
+        for worker in workers {
+            worker.join().unwrap();
+        }
+        HELD.with(|held| assert!(held.borrow().is_empty()));
+    }
+}
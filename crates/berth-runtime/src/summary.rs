@@ -0,0 +1,110 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Machine-wide status summary for shell prompts and editor integrations.
+//!
+//! Every state change rewrites one small JSON file, so a prompt can show
+//! "3 MCP servers running" by reading it instead of spawning `berth` or
+//! reading per-server state. The file is replaced by rename, so readers never
+//! see a partial write and need no lock.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use berth_common::atomic_write;
+
+use crate::ServerStatus;
+
+/// File name of the summary below the Berth state directory.
+pub const STATUS_SUMMARY_FILE: &str = "status-summary.json";
+
+/// Schema version written to [`StatusSummary::version`]; bumped only for
+/// changes that break existing readers.
+pub const STATUS_SUMMARY_VERSION: u32 = 1;
+
+/// Contents of the status summary file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatusSummary {
+    pub version: u32,
+    pub updated_at_epoch_ms: u64,
+    /// Servers with a live process, including starting and unhealthy ones.
+    pub running: usize,
+    pub unhealthy: usize,
+    pub crash_looping: usize,
+    pub scheduled: usize,
+    /// Status of every server with runtime state, by name.
+    pub servers: BTreeMap<String, ServerStatus>,
+}
+
+impl StatusSummary {
+    /// Tallies `servers` into a summary stamped `updated_at_epoch_ms`.
+    pub fn new(servers: BTreeMap<String, ServerStatus>, updated_at_epoch_ms: u64) -> Self {
+        let count = |wanted: &[ServerStatus]| {
+            servers
+                .values()
+                .filter(|status| wanted.contains(status))
+                .count()
+        };
+        StatusSummary {
+            version: STATUS_SUMMARY_VERSION,
+            updated_at_epoch_ms,
+            running: count(&[
+                ServerStatus::Running,
+                ServerStatus::Starting,
+                ServerStatus::Unhealthy,
+            ]),
+            unhealthy: count(&[ServerStatus::Unhealthy]),
+            crash_looping: count(&[ServerStatus::CrashLooping]),
+            scheduled: count(&[ServerStatus::Scheduled]),
+            servers,
+        }
+    }
+}
+
+/// Replaces `path` with `summary` through a temporary file and a rename.
+pub(crate) fn write_summary(path: &Path, summary: &StatusSummary) -> io::Result<()> {
+    let serialized = serde_json::to_vec_pretty(summary)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    atomic_write(path, serialized)
+}
+
+/// Reads the summary at `path`.
+pub fn read_status_summary(path: &Path) -> io::Result<StatusSummary> {
+    let content = fs::read(path)?;
+    serde_json::from_slice(&content).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summary_tallies_live_servers_and_round_trips() {
+        let servers = BTreeMap::from([
+            ("github".to_string(), ServerStatus::Running),
+            ("slack".to_string(), ServerStatus::Unhealthy),
+            ("fetch".to_string(), ServerStatus::Stopped),
+            ("backup".to_string(), ServerStatus::Scheduled),
+        ]);
+        let summary = StatusSummary::new(servers, 42);
+        assert_eq!(summary.running, 2);
+        assert_eq!(summary.unhealthy, 1);
+        assert_eq!(summary.scheduled, 1);
+        assert_eq!(summary.crash_looping, 0);
+
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join(STATUS_SUMMARY_FILE);
+        write_summary(&path, &summary).unwrap();
+        assert_eq!(read_status_summary(&path).unwrap(), summary);
+        assert_eq!(fs::read_dir(tmp.path()).unwrap().count(), 1);
+
+        let json: serde_json::Value = serde_json::from_slice(&fs::read(&path).unwrap()).unwrap();
+        assert_eq!(json["version"], 1);
+        assert_eq!(json["servers"]["slack"], "unhealthy");
+        assert_eq!(json["updatedAtEpochMs"], 42);
+    }
+}
@@ -0,0 +1,454 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Deterministic harness for the supervisor state machine.
+//!
+//! Runs the real supervisor loop against a [`ManualClock`] and a [`Spawner`]
+//! that hands out scripted fake processes, so restart, refresh and crash-loop transitions are exercised
+//! without spawning anything or waiting on timers.
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::sync::Mutex;
+
+use super::*;
+
+const SERVER: &str = "github";
+const START_MS: u64 = 1_800_000_000_000;
+const FIRST_PID: u32 = 1_000;
+
+/// How one scripted process behaves.
+#[derive(Debug, Clone, Copy)]
+struct Run {
+    /// How long the process lives; `None` runs until terminated.
+    lasts: Option<Duration>,
+    /// How it ends; `None` models an exit whose status is unknown.
+    exit: Option<ProcessExit>,
+}
+
+impl Run {
+    fn exits(after_ms: u64, code: i32) -> Self {
+        Run {
+            lasts: Some(Duration::from_millis(after_ms)),
+            exit: Some(ProcessExit::code(code)),
+        }
+    }
+
+    fn killed(after_ms: u64, signal: i32) -> Self {
+        Run {
+            lasts: Some(Duration::from_millis(after_ms)),
+            exit: Some(ProcessExit::signaled(signal, false)),
+        }
+    }
+
+    fn forever() -> Self {
+        Run {
+            lasts: None,
+            exit: None,
+        }
+    }
+}
+
+/// Shared bookkeeping the fake processes write to.
+struct World {
+    clock: Arc<ManualClock>,
+    home: PathBuf,
+    /// Later runs, consumed in launch order; an empty queue runs forever.
+    queue: Mutex<VecDeque<Run>>,
+    next_pid: Mutex<u32>,
+    /// `spawn <pid> +<ms>` / `terminate <pid> +<ms>` in order.
+    events: Mutex<Vec<String>>,
+    /// Offset at which the harness plays `berth stop`.
+    stop_after: Duration,
+}
+
+impl World {
+    fn elapsed_ms(&self) -> u64 {
+        self.clock.now_epoch_ms() - START_MS
+    }
+
+    fn record(&self, event: &str, pid: u32) {
+        let line = format!("{event} {pid} +{}", self.elapsed_ms());
+        self.events.lock().unwrap().push(line);
+    }
+
+    fn launch(self: &Arc<Self>, run: Run) -> Box<dyn ProcessHandle> {
+        let pid = {
+            let mut next = self.next_pid.lock().unwrap();
+            let pid = *next;
+            *next += 1;
+            pid
+        };
+        self.record("spawn", pid);
+        Box::new(FakeProcess {
+            pid,
+            ends_at_ms: run
+                .lasts
+                .map(|lasts| self.clock.now_epoch_ms() + lasts.as_millis() as u64),
+            exit: run.exit,
+            terminated: false,
+            world: Arc::clone(self),
+        })
+    }
+
+    /// Marks the server stopped once the scenario's time is up, as `berth stop` would.
+    fn stop_if_due(&self) {
+        if self.elapsed_ms() < self.stop_after.as_millis() as u64 {
+            return;
+        }
+        let manager = RuntimeManager::new(&self.home);
+        let mut state = manager.read_state(SERVER).unwrap();
+        if state.status == ServerStatus::Running {
+            state.status = ServerStatus::Stopped;
+            state.pid = None;
+            manager.write_state(SERVER, &state).unwrap();
+        }
+    }
+}
+
+/// Launches the next scripted run for every replacement.
+struct FakeSpawner(Arc<World>);
+
+impl Spawner for FakeSpawner {
+    fn spawn(
+        &self,
+        _spec: &ProcessSpec,
+        _log: File,
+        _input: Option<&[u8]>,
+    ) -> io::Result<Box<dyn ProcessHandle>> {
+        let run = self
+            .0
+            .queue
+            .lock()
+            .unwrap()
+            .pop_front()
+            .unwrap_or(Run::forever());
+        Ok(self.0.launch(run))
+    }
+}
+
+struct FakeProcess {
+    pid: u32,
+    ends_at_ms: Option<u64>,
+    exit: Option<ProcessExit>,
+    terminated: bool,
+    world: Arc<World>,
+}
+
+impl ProcessHandle for FakeProcess {
+    fn id(&self) -> u32 {
+        self.pid
+    }
+
+    fn try_wait(&mut self) -> io::Result<Option<Option<ProcessExit>>> {
+        self.world.stop_if_due();
+        if self.terminated {
+            return Ok(Some(Some(ProcessExit::signaled(15, false))));
+        }
+        let ended = self
+            .ends_at_ms
+            .is_some_and(|at| self.world.clock.now_epoch_ms() >= at);
+        Ok(ended.then_some(self.exit))
+    }
+
+    fn terminate(&mut self) -> io::Result<()> {
+        self.terminated = true;
+        self.world.record("terminate", self.pid);
+        Ok(())
+    }
+}
+
+/// A supervisor run over scripted processes.
+struct Harness {
+    _tmp: tempfile::TempDir,
+    manager: RuntimeManager,
+    world: Arc<World>,
+}
+
+impl Harness {
+    /// Creates a scenario whose replacement processes follow `later`; the
+    /// server is stopped after `stop_after`.
+    fn new(later: &[Run], stop_after: Duration) -> Self {
+        let tmp = tempfile::tempdir().unwrap();
+        let home = tmp.path().join(".berth");
+        let clock = Arc::new(ManualClock::new(START_MS));
+        let world = Arc::new(World {
+            clock: clock.clone(),
+            home: home.clone(),
+            queue: Mutex::new(later.iter().copied().collect()),
+            next_pid: Mutex::new(FIRST_PID),
+            events: Mutex::new(Vec::new()),
+            stop_after,
+        });
+        let manager = RuntimeManager::new(&home)
+            .with_clock(clock)
+            .with_spawner(Arc::new(FakeSpawner(Arc::clone(&world))));
+        Harness {
+            _tmp: tmp,
+            manager,
+            world,
+        }
+    }
+
+    /// Runs the supervisor until it exits, starting from `first`.
+    fn run<F>(&self, spec: &ProcessSpec, first: Run, refresh: F) -> io::Result<()>
+    where
+        F: FnMut() -> Result<ProcessSpec, String>,
+    {
+        let policy = supervision_policy(spec).expect("spec needs a supervisor");
+        let process = self.world.launch(first);
+        self.manager.write_state(
+            SERVER,
+            &RuntimeState {
+                status: ServerStatus::Running,
+                pid: Some(process.id()),
+                command: Some(spec.command.clone()),
+                auto_restart_enabled: policy.enabled,
+                max_restarts: policy.max_restarts,
+                updated_at_epoch_ms: START_MS,
+                ..RuntimeState::default()
+            },
+        )?;
+        tokio::runtime::Builder::new_current_thread()
+            .build()?
+            .block_on(self.manager.run_supervisor_loop(
+                SERVER,
+                spec.clone(),
+                policy,
+                refresh,
+                Some(process),
+            ))
+    }
+
+    fn state(&self) -> RuntimeState {
+        self.manager.read_state(SERVER).unwrap()
+    }
+
+    fn events(&self) -> Vec<String> {
+        self.world.events.lock().unwrap().clone()
+    }
+
+    fn log(&self) -> String {
+        fs::read_to_string(self.manager.log_path(SERVER)).unwrap_or_default()
+    }
+}
+
+fn spec(max_restarts: u32, restart_on: Option<Vec<i32>>) -> ProcessSpec {
+    ProcessSpec {
+        command: "server".to_string(),
+        auto_restart: Some(AutoRestartPolicy {
+            enabled: true,
+            max_restarts,
+            restart_on_exit_codes: restart_on,
+        }),
+        ..ProcessSpec::default()
+    }
+}
+
+fn no_refresh() -> Result<ProcessSpec, String> {
+    Err("unused".to_string())
+}
+
+const ONE_MINUTE: Duration = Duration::from_secs(60);
+
+#[test]
+fn restarts_until_the_budget_is_spent_then_crash_loops() {
+    let harness = Harness::new(&[Run::exits(500, 1), Run::exits(500, 1)], ONE_MINUTE);
+    harness
+        .run(&spec(2, None), Run::exits(500, 1), no_refresh)
+        .unwrap();
+
+    let state = harness.state();
+    assert_eq!(state.status, ServerStatus::CrashLooping);
+    assert_eq!(state.restart_attempts, 2);
+    assert_eq!(state.last_exit_code, Some(1));
+    assert_eq!(
+        harness.events(),
+        ["spawn 1000 +0", "spawn 1001 +500", "spawn 1002 +1000"]
+    );
+    assert!(harness.log().contains("AUTO_RESTART pid=1002 attempt=2/2"));
+    assert!(harness.log().contains("CRASH_LOOP attempts=2"));
+}
+
+#[test]
+fn restart_attempts_and_final_status_follow_the_budget() {
+    for max_restarts in 0..8 {
+        for crashes in 1..10usize {
+            let later = vec![Run::exits(100, 1); crashes - 1];
+            let harness = Harness::new(&later, ONE_MINUTE);
+            harness
+                .run(&spec(max_restarts, None), Run::exits(100, 1), no_refresh)
+                .unwrap();
+
+            let state = harness.state();
+            let restarts = (crashes as u32).min(max_restarts);
+            assert_eq!(
+                state.restart_attempts, restarts,
+                "max={max_restarts} crashes={crashes}"
+            );
+            assert_eq!(harness.events().len(), restarts as usize + 1);
+            if crashes as u32 > max_restarts {
+                assert_eq!(state.status, ServerStatus::CrashLooping);
+            } else {
+                // The last replacement keeps running until the scenario stops it.
+                assert_eq!(state.status, ServerStatus::Stopped);
+            }
+        }
+    }
+}
+
+#[test]
+fn listed_exit_codes_decide_between_restart_and_clean_stop() {
+    let listed = vec![1, 137];
+    for code in [0, 1, 2, 3, 42, 137, 143] {
+        let harness = Harness::new(&[], ONE_MINUTE);
+        harness
+            .run(
+                &spec(3, Some(listed.clone())),
+                Run::exits(200, code),
+                no_refresh,
+            )
+            .unwrap();
+
+        let state = harness.state();
+        if listed.contains(&code) {
+            assert_eq!(state.restart_attempts, 1, "code {code}");
+            assert_eq!(harness.events()[1], "spawn 1001 +200");
+        } else {
+            assert_eq!(state.status, ServerStatus::Stopped, "code {code}");
+            assert_eq!(state.restart_attempts, 0);
+            assert_eq!(state.last_exit_code, Some(code));
+            assert!(harness.log().contains(&format!("CLEAN_EXIT code={code}")));
+        }
+    }
+}
+
+#[test]
+fn signal_deaths_map_to_shell_exit_codes() {
+    let harness = Harness::new(&[], ONE_MINUTE);
+    harness
+        .run(&spec(0, Some(vec![137])), Run::killed(300, 9), no_refresh)
+        .unwrap();
+
+    let state = harness.state();
+    assert_eq!(state.last_exit_code, Some(137));
+    assert_eq!(state.status, ServerStatus::CrashLooping);
+    assert!(harness.log().contains("EXIT code=137"));
+}
+
+#[test]
+fn unknown_exit_status_always_counts_as_a_failure() {
+    let harness = Harness::new(&[], ONE_MINUTE);
+    let first = Run {
+        lasts: Some(Duration::from_millis(100)),
+        exit: None,
+    };
+    harness
+        .run(&spec(1, Some(vec![1])), first, no_refresh)
+        .unwrap();
+
+    let state = harness.state();
+    assert_eq!(state.restart_attempts, 1);
+    assert!(harness.log().lines().any(|line| line.ends_with("] EXIT")));
+}
+
+#[test]
+fn stop_ends_supervision_without_restarting() {
+    let harness = Harness::new(&[], Duration::from_secs(5));
+    harness
+        .run(&spec(3, None), Run::forever(), no_refresh)
+        .unwrap();
+
+    let state = harness.state();
+    assert_eq!(state.status, ServerStatus::Stopped);
+    assert_eq!(state.restart_attempts, 0);
+    assert_eq!(harness.events(), ["spawn 1000 +0"]);
+}
+
+#[test]
+fn refresh_relaunches_at_the_deadline_without_counting_a_restart() {
+    let harness = Harness::new(&[], ONE_MINUTE);
+    let mut spec = spec(3, None);
+    spec.refresh_at_epoch_secs = Some(START_MS / 1_000 + 10);
+    let fresh = ProcessSpec {
+        refresh_at_epoch_secs: None,
+        ..spec.clone()
+    };
+    harness
+        .run(&spec, Run::forever(), || Ok(fresh.clone()))
+        .unwrap();
+
+    assert_eq!(
+        harness.events(),
+        [
+            "spawn 1000 +0",
+            "terminate 1000 +10000",
+            "spawn 1001 +10000"
+        ]
+    );
+    let state = harness.state();
+    assert_eq!(state.restart_attempts, 0);
+    assert!(harness.log().contains("REFRESH pid=1001"));
+}
+
+#[test]
+fn failed_refresh_is_retried_a_minute_later() {
+    let harness = Harness::new(&[], Duration::from_secs(120));
+    let mut spec = spec(3, None);
+    spec.refresh_at_epoch_secs = Some(START_MS / 1_000 + 10);
+    let fresh = ProcessSpec {
+        refresh_at_epoch_secs: None,
+        ..spec.clone()
+    };
+    let mut calls = 0;
+    harness
+        .run(&spec, Run::forever(), || {
+            calls += 1;
+            if calls == 1 {
+                Err("token endpoint unreachable".to_string())
+            } else {
+                Ok(fresh.clone())
+            }
+        })
+        .unwrap();
+
+    assert!(harness
+        .log()
+        .contains("REFRESH_FAILED token endpoint unreachable"));
+    assert_eq!(
+        harness.events(),
+        [
+            "spawn 1000 +0",
+            "terminate 1000 +70000",
+            "spawn 1001 +70000"
+        ]
+    );
+}
+
+#[test]
+fn org_policy_denial_stops_instead_of_restarting() {
+    let harness = Harness::new(&[], ONE_MINUTE);
+    fs::create_dir_all(&harness.world.home).unwrap();
+    fs::write(
+        harness.world.home.join("policy.toml"),
+        "[servers]\ndeny = [\"github\"]\n",
+    )
+    .unwrap();
+    harness
+        .run(&spec(3, None), Run::exits(100, 1), no_refresh)
+        .unwrap();
+
+    assert_eq!(harness.state().status, ServerStatus::Stopped);
+    assert_eq!(harness.events(), ["spawn 1000 +0"]);
+    assert!(harness.log().contains("POLICY_DENIED_AUTO_RESTART"));
+}
+
+#[test]
+fn state_timestamps_come_from_the_injected_clock() {
+    let harness = Harness::new(&[Run::exits(2_000, 1)], ONE_MINUTE);
+    harness
+        .run(&spec(1, None), Run::exits(1_500, 1), no_refresh)
+        .unwrap();
+
+    assert_eq!(harness.state().updated_at_epoch_ms, START_MS + 3_500);
+}
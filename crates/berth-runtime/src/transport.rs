@@ -0,0 +1,480 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Local endpoints for servers that speak MCP over a unix socket or a
+//! Windows named pipe instead of stdio.
+//!
+//! Berth still launches such servers, but hands them an endpoint to listen on:
+//! a per-server path expanded from a template (see [`Endpoint::resolve`]),
+//! passed in [`SOCKET_ENV`] and in place of [`SOCKET_PLACEHOLDER`] in their
+//! arguments. [`Endpoint::connect`] refuses unix sockets that another user
+//! owns or could have swapped in.
+//!
+//! Servers on the `http` transport get a TCP port the same way, in
+//! [`PORT_ENV`] and in place of [`PORT_PLACEHOLDER`]. Replicas of one server
+//! run as separate instances named like `github@2`; see [`replica_name`].
+
+use std::fmt;
+#[cfg(windows)]
+use std::fs::OpenOptions;
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+
+pub const STDIO: &str = "stdio";
+pub const UNIX_SOCKET: &str = "unix-socket";
+pub const NAMED_PIPE: &str = "named-pipe";
+pub const HTTP: &str = "http";
+
+/// Environment variable that tells a server which endpoint to listen on.
+pub const SOCKET_ENV: &str = "BERTH_SOCKET";
+
+/// Replaced with the endpoint in a server's arguments.
+pub const SOCKET_PLACEHOLDER: &str = "{socket}";
+
+/// Environment variable that tells an `http` server which port to listen on.
+pub const PORT_ENV: &str = "BERTH_PORT";
+
+/// Replaced with the port in an `http` server's arguments.
+pub const PORT_PLACEHOLDER: &str = "{port}";
+
+/// Separates a server name from the replica number in instance names.
+const REPLICA_SEPARATOR: char = '@';
+
+const DEFAULT_UNIX_TEMPLATE: &str = "{state}/sockets/{server}.sock";
+const DEFAULT_PIPE_TEMPLATE: &str = r"\\.\pipe\berth-{server}";
+const PIPE_PREFIX: &str = r"\\.\pipe\";
+
+/// Longest unix socket path that fits `sun_path` on both Linux (108 bytes)
+/// and macOS (104 bytes), leaving room for the terminating NUL.
+const MAX_SOCKET_PATH: usize = 103;
+
+/// Problems with a manifest's transport and endpoint template, if any.
+pub fn template_problems(transport: &str, template: Option<&str>) -> Vec<String> {
+    match transport {
+        STDIO | HTTP if template.is_some() => {
+            vec!["runtime.socket needs transport `unix-socket` or `named-pipe`.".to_string()]
+        }
+        STDIO | HTTP => Vec::new(),
+        UNIX_SOCKET | NAMED_PIPE => {
+            match Endpoint::resolve(transport, template, "server", Path::new("/state")) {
+                Ok(_) => Vec::new(),
+                Err(msg) => vec![format!("runtime.socket: {msg}")],
+            }
+        }
+        other => vec![format!(
+            "runtime.transport `{other}` is not one of `{STDIO}`, `{UNIX_SOCKET}`, `{NAMED_PIPE}`, `{HTTP}`."
+        )],
+    }
+}
+
+/// Names replica `replica` (counting from 1) of `server`, e.g. `github@2`.
+pub fn replica_name(server: &str, replica: u16) -> String {
+    format!("{server}{REPLICA_SEPARATOR}{replica}")
+}
+
+/// Splits an instance name from [`replica_name`] into server and replica;
+/// `None` for plain server names.
+pub fn replica_of(instance: &str) -> Option<(&str, u16)> {
+    let (server, replica) = instance.rsplit_once(REPLICA_SEPARATOR)?;
+    let replica = replica.parse().ok().filter(|n| *n > 0)?;
+    (!server.is_empty()).then_some((server, replica))
+}
+
+/// Where a socket-transport server listens.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Endpoint {
+    UnixSocket(PathBuf),
+    /// Full pipe name, `\\.\pipe\...`.
+    NamedPipe(String),
+}
+
+impl Endpoint {
+    /// Expands the endpoint of `server` for `transport`; `None` unless the
+    /// transport listens on a unix socket or named pipe.
+    ///
+    /// `template` may use `{server}`, `{state}` (the Berth state directory)
+    /// and `{tmp}`. Without one, unix sockets live at
+    /// `{state}/sockets/{server}.sock` and pipes are named `berth-{server}`.
+    pub fn resolve(
+        transport: &str,
+        template: Option<&str>,
+        server: &str,
+        state_dir: &Path,
+    ) -> Result<Option<Endpoint>, String> {
+        let default = match transport {
+            UNIX_SOCKET => DEFAULT_UNIX_TEMPLATE,
+            NAMED_PIPE => DEFAULT_PIPE_TEMPLATE,
+            _ => return Ok(None),
+        };
+        let expanded = expand(template.unwrap_or(default), server, state_dir)?;
+        Endpoint::parse(transport, &expanded).map(Some)
+    }
+
+    /// Checks an already expanded endpoint.
+    pub fn parse(transport: &str, endpoint: &str) -> Result<Endpoint, String> {
+        match transport {
+            UNIX_SOCKET => {
+                let path = PathBuf::from(endpoint);
+                if !path.is_absolute() {
+                    return Err(format!("socket path `{endpoint}` is not absolute"));
+                }
+                if endpoint.len() > MAX_SOCKET_PATH {
+                    return Err(format!(
+                        "socket path `{endpoint}` is longer than {MAX_SOCKET_PATH} bytes"
+                    ));
+                }
+                Ok(Endpoint::UnixSocket(path))
+            }
+            NAMED_PIPE => {
+                let name = endpoint.get(PIPE_PREFIX.len()..).unwrap_or_default();
+                let prefixed = endpoint
+                    .get(..PIPE_PREFIX.len())
+                    .is_some_and(|prefix| prefix.eq_ignore_ascii_case(PIPE_PREFIX));
+                if !prefixed || name.is_empty() {
+                    return Err(format!(
+                        "pipe name `{endpoint}` does not start with `{PIPE_PREFIX}`"
+                    ));
+                }
+                Ok(Endpoint::NamedPipe(endpoint.to_string()))
+            }
+            other => Err(format!("transport `{other}` has no local endpoint")),
+        }
+    }
+
+    pub fn transport(&self) -> &'static str {
+        match self {
+            Endpoint::UnixSocket(_) => UNIX_SOCKET,
+            Endpoint::NamedPipe(_) => NAMED_PIPE,
+        }
+    }
+
+    /// Checks that the endpoint belongs to the current user.
+    ///
+    /// A unix socket must be a socket owned by the current user, in a
+    /// directory owned by that user or root that other users cannot write to
+    /// (unless it is sticky, like `/tmp`). Named pipes keep their creator's
+    /// default ACL and are not checked further.
+    pub fn check_owner(&self) -> io::Result<()> {
+        match self {
+            #[cfg(unix)]
+            Endpoint::UnixSocket(path) => check_socket_owner(path),
+            _ => Ok(()),
+        }
+    }
+
+    /// Connects after [`Endpoint::check_owner`] passes.
+    pub fn connect(&self) -> io::Result<Connection> {
+        self.check_owner()?;
+        match self {
+            #[cfg(unix)]
+            Endpoint::UnixSocket(path) => UnixStream::connect(path).map(Connection::Unix),
+            #[cfg(not(unix))]
+            Endpoint::UnixSocket(_) => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "unix socket transport is not supported on this platform",
+            )),
+            #[cfg(windows)]
+            Endpoint::NamedPipe(name) => OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open(name)
+                .map(Connection::Pipe),
+            #[cfg(not(windows))]
+            Endpoint::NamedPipe(_) => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "named pipe transport is only supported on Windows",
+            )),
+        }
+    }
+
+    /// Creates the directory a unix socket goes in, private to the current user.
+    pub fn prepare(&self) -> io::Result<()> {
+        let Endpoint::UnixSocket(path) = self else {
+            return Ok(());
+        };
+        let Some(dir) = path.parent() else {
+            return Ok(());
+        };
+        if dir.exists() {
+            return Ok(());
+        }
+        fs::create_dir_all(dir)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(dir, fs::Permissions::from_mode(0o700))?;
+        }
+        Ok(())
+    }
+
+    /// Removes a unix socket file left behind by a server that exited, so a
+    /// new one can bind it. Sockets failing [`Endpoint::check_owner`] are kept.
+    pub fn remove_stale(&self) -> io::Result<()> {
+        let Endpoint::UnixSocket(path) = self else {
+            return Ok(());
+        };
+        if fs::symlink_metadata(path).is_err() {
+            return Ok(());
+        }
+        self.check_owner()?;
+        match self.connect() {
+            Ok(_) => Ok(()),
+            Err(_) => fs::remove_file(path),
+        }
+    }
+}
+
+impl fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Endpoint::UnixSocket(path) => write!(f, "{}", path.display()),
+            Endpoint::NamedPipe(name) => f.write_str(name),
+        }
+    }
+}
+
+/// An open connection to an [`Endpoint`].
+#[derive(Debug)]
+pub enum Connection {
+    #[cfg(unix)]
+    Unix(UnixStream),
+    Pipe(File),
+}
+
+impl Connection {
+    /// Another handle to the same connection, e.g. for a writer thread.
+    pub fn try_clone(&self) -> io::Result<Connection> {
+        match self {
+            #[cfg(unix)]
+            Connection::Unix(stream) => stream.try_clone().map(Connection::Unix),
+            Connection::Pipe(file) => file.try_clone().map(Connection::Pipe),
+        }
+    }
+
+    /// Tells the server no more requests follow, where the endpoint supports it.
+    pub fn shutdown_write(&self) -> io::Result<()> {
+        match self {
+            #[cfg(unix)]
+            Connection::Unix(stream) => stream.shutdown(std::net::Shutdown::Write),
+            Connection::Pipe(_) => Ok(()),
+        }
+    }
+}
+
+impl Read for Connection {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            #[cfg(unix)]
+            Connection::Unix(stream) => stream.read(buf),
+            Connection::Pipe(file) => file.read(buf),
+        }
+    }
+}
+
+impl Write for Connection {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            #[cfg(unix)]
+            Connection::Unix(stream) => stream.write(buf),
+            Connection::Pipe(file) => file.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            #[cfg(unix)]
+            Connection::Unix(stream) => stream.flush(),
+            Connection::Pipe(file) => file.flush(),
+        }
+    }
+}
+
+/// Stops `command` from outliving the process that spawns it, where the
+/// platform supports it (Linux); a killed bridge then takes its server along.
+pub fn bind_to_parent(command: &mut Command) {
+    #[cfg(target_os = "linux")]
+    {
+        use std::os::unix::process::CommandExt;
+        // SAFETY: prctl is async-signal-safe and touches no shared state.
+        unsafe {
+            command.pre_exec(|| {
+                if libc::prctl(libc::PR_SET_PDEATHSIG, libc::SIGTERM) != 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+    }
+    #[cfg(not(target_os = "linux"))]
+    let _ = command;
+}
+
+fn expand(template: &str, server: &str, state_dir: &Path) -> Result<String, String> {
+    let mut out = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let Some(len) = rest[start..].find('}') else {
+            return Err(format!("unclosed `{{` in `{template}`"));
+        };
+        let value = match &rest[start + 1..start + len] {
+            "server" => server.to_string(),
+            "state" => state_dir.display().to_string(),
+            "tmp" => std::env::temp_dir()
+                .display()
+                .to_string()
+                .trim_end_matches(['/', '\\'])
+                .to_string(),
+            other => {
+                return Err(format!(
+                    "unknown placeholder `{{{other}}}` in `{template}`; use {{server}}, {{state}} or {{tmp}}"
+                ))
+            }
+        };
+        out.push_str(&value);
+        rest = &rest[start + len + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+#[cfg(unix)]
+fn check_socket_owner(path: &Path) -> io::Result<()> {
+    use std::os::unix::fs::{FileTypeExt, MetadataExt};
+
+    let denied = |reason: String| io::Error::new(io::ErrorKind::PermissionDenied, reason);
+    // SAFETY: geteuid cannot fail and has no side effects.
+    let uid = unsafe { libc::geteuid() };
+    let meta = fs::symlink_metadata(path)?;
+    if !meta.file_type().is_socket() {
+        return Err(denied(format!("{} is not a socket", path.display())));
+    }
+    if meta.uid() != uid {
+        return Err(denied(format!(
+            "{} is owned by uid {}, not the current user (uid {uid})",
+            path.display(),
+            meta.uid()
+        )));
+    }
+    let dir = path.parent().unwrap_or(Path::new("/"));
+    let dir_meta = fs::metadata(dir)?;
+    if dir_meta.uid() != uid && dir_meta.uid() != 0 {
+        return Err(denied(format!(
+            "{} is owned by uid {}, so the socket in it could be replaced",
+            dir.display(),
+            dir_meta.uid()
+        )));
+    }
+    let others_write = dir_meta.mode() & 0o022 != 0;
+    let sticky = dir_meta.mode() & 0o1000 != 0;
+    if others_write && !sticky {
+        return Err(denied(format!(
+            "{} is writable by other users, so the socket in it could be replaced",
+            dir.display()
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_templates_and_defaults() {
+        let state = Path::new("/home/me/.berth");
+        assert_eq!(
+            Endpoint::resolve(STDIO, None, "github", state).unwrap(),
+            None
+        );
+        assert_eq!(
+            Endpoint::resolve(UNIX_SOCKET, None, "github", state).unwrap(),
+            Some(Endpoint::UnixSocket(PathBuf::from(
+                "/home/me/.berth/sockets/github.sock"
+            )))
+        );
+        assert_eq!(
+            Endpoint::resolve(NAMED_PIPE, None, "github", state).unwrap(),
+            Some(Endpoint::NamedPipe(r"\\.\pipe\berth-github".to_string()))
+        );
+        assert_eq!(
+            Endpoint::resolve(UNIX_SOCKET, Some("/run/mcp/{server}.sock"), "db", state).unwrap(),
+            Some(Endpoint::UnixSocket(PathBuf::from("/run/mcp/db.sock")))
+        );
+
+        assert!(Endpoint::resolve(UNIX_SOCKET, Some("{home}/x.sock"), "db", state).is_err());
+        assert!(Endpoint::resolve(UNIX_SOCKET, Some("relative.sock"), "db", state).is_err());
+        let long = format!("/{}.sock", "a".repeat(120));
+        assert!(Endpoint::resolve(UNIX_SOCKET, Some(&long), "db", state).is_err());
+        assert!(Endpoint::resolve(NAMED_PIPE, Some(r"C:\pipes\{server}"), "db", state).is_err());
+    }
+
+    #[test]
+    fn validates_manifest_transports() {
+        assert!(template_problems(STDIO, None).is_empty());
+        assert!(template_problems(UNIX_SOCKET, Some("{tmp}/{server}.sock")).is_empty());
+        assert!(template_problems(HTTP, None).is_empty());
+        assert_eq!(template_problems(STDIO, Some("/x.sock")).len(), 1);
+        assert_eq!(template_problems(HTTP, Some("/x.sock")).len(), 1);
+        assert_eq!(template_problems("sse", None).len(), 1);
+        assert_eq!(template_problems(NAMED_PIPE, Some("berth")).len(), 1);
+    }
+
+    #[test]
+    fn replica_names_round_trip() {
+        assert_eq!(replica_name("github", 2), "github@2");
+        assert_eq!(replica_of("github@2"), Some(("github", 2)));
+        assert_eq!(replica_of("github"), None);
+        assert_eq!(replica_of("github@0"), None);
+        assert_eq!(replica_of("github@latest"), None);
+        assert_eq!(replica_of("@1"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn connects_only_to_sockets_owned_in_private_directories() {
+        use std::os::unix::fs::PermissionsExt;
+        use std::os::unix::net::UnixListener;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let endpoint = Endpoint::resolve(UNIX_SOCKET, None, "echo", tmp.path())
+            .unwrap()
+            .unwrap();
+        endpoint.prepare().unwrap();
+        let Endpoint::UnixSocket(path) = &endpoint else {
+            unreachable!()
+        };
+        let listener = UnixListener::bind(path).unwrap();
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut line = [0u8; 5];
+            stream.read_exact(&mut line).unwrap();
+            stream.write_all(&line).unwrap();
+        });
+        let mut conn = endpoint.connect().unwrap();
+        conn.write_all(b"ping\n").unwrap();
+        let mut reply = [0u8; 5];
+        conn.read_exact(&mut reply).unwrap();
+        assert_eq!(&reply, b"ping\n");
+        server.join().unwrap();
+
+        let dir = path.parent().unwrap();
+        fs::set_permissions(dir, fs::Permissions::from_mode(0o777)).unwrap();
+        let err = endpoint.connect().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+        assert!(err.to_string().contains("writable by other users"));
+        fs::set_permissions(dir, fs::Permissions::from_mode(0o700)).unwrap();
+
+        endpoint.remove_stale().unwrap();
+        assert!(!path.exists());
+
+        fs::write(path, "").unwrap();
+        let err = endpoint.connect().unwrap_err();
+        assert!(err.to_string().contains("is not a socket"));
+    }
+}
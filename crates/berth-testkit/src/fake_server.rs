@@ -0,0 +1,448 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! A fake MCP server speaking newline-delimited JSON-RPC over stdio.
+//!
+//! It answers `initialize`, `ping`, `tools/list`, and `tools/call`; other
+//! methods get a method-not-found error and notifications are ignored. Each
+//! tool echoes its arguments back as text, or returns
+//! [`FakeServer::output_bytes`] bytes of filler when that is set.
+
+use serde_json::{json, Value};
+use std::io::{self, BufRead, Write};
+use std::thread;
+use std::time::Duration;
+
+/// Server name reported in `serverInfo` unless [`FakeServer::with_name`] sets one.
+pub const DEFAULT_NAME: &str = "berth-test-server";
+
+/// Protocol revision answered when `initialize` does not name one.
+const PROTOCOL_VERSION: &str = "2025-06-18";
+
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// A protocol rule the server breaks in its `tools/call` answers.
+///
+/// `initialize` and `tools/list` stay well-formed, so a client gets far
+/// enough to call a tool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Violation {
+    /// The answer is cut off before its closing brace.
+    MalformedJson,
+    /// The answer carries an id the client never sent.
+    WrongId,
+    /// The answer has no `"jsonrpc": "2.0"` member.
+    MissingVersion,
+    /// A plain-text log line is written to stdout before the answer.
+    StdoutNoise,
+}
+
+impl Violation {
+    /// Every violation, in the order `berth test-server --help` lists them.
+    pub const ALL: [Violation; 4] = [
+        Violation::MalformedJson,
+        Violation::WrongId,
+        Violation::MissingVersion,
+        Violation::StdoutNoise,
+    ];
+
+    /// Parses a violation name such as `malformed-json`.
+    pub fn parse(value: &str) -> Result<Self, String> {
+        Violation::ALL
+            .into_iter()
+            .find(|violation| violation.as_str() == value.trim())
+            .ok_or_else(|| {
+                let names: Vec<&str> = Violation::ALL.iter().map(|v| v.as_str()).collect();
+                format!(
+                    "Invalid violation `{value}`. Expected one of: {}.",
+                    names.join(", ")
+                )
+            })
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Violation::MalformedJson => "malformed-json",
+            Violation::WrongId => "wrong-id",
+            Violation::MissingVersion => "missing-version",
+            Violation::StdoutNoise => "stdout-noise",
+        }
+    }
+}
+
+/// Why [`FakeServer::serve`] returned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Exit {
+    /// The client closed stdin.
+    InputClosed,
+    /// The configured number of tool calls was answered; the process should
+    /// exit with `exit_code`.
+    Crashed { exit_code: i32 },
+}
+
+/// How a fake server behaves.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FakeServer {
+    /// Name reported in `serverInfo`.
+    pub name: String,
+    /// Tools listed by `tools/list` and accepted by `tools/call`.
+    pub tools: Vec<String>,
+    /// Time spent before reading the first request.
+    pub startup_delay: Duration,
+    /// Tool calls answered before the server crashes; `None` never crashes.
+    pub crash_after_calls: Option<u32>,
+    /// Exit code of a crash.
+    pub exit_code: i32,
+    /// Size of each tool result's text; `0` echoes the arguments instead.
+    pub output_bytes: usize,
+    pub violation: Option<Violation>,
+}
+
+impl Default for FakeServer {
+    fn default() -> Self {
+        FakeServer {
+            name: DEFAULT_NAME.to_string(),
+            tools: vec!["echo".to_string()],
+            startup_delay: Duration::ZERO,
+            crash_after_calls: None,
+            exit_code: 1,
+            output_bytes: 0,
+            violation: None,
+        }
+    }
+}
+
+impl FakeServer {
+    /// A well-behaved server with one `echo` tool.
+    pub fn new() -> Self {
+        FakeServer::default()
+    }
+
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    pub fn with_tools(mut self, tools: &[&str]) -> Self {
+        self.tools = tools.iter().map(|tool| tool.to_string()).collect();
+        self
+    }
+
+    /// Waits `delay` before answering anything, like a server that loads slowly.
+    pub fn with_startup_delay(mut self, delay: Duration) -> Self {
+        self.startup_delay = delay;
+        self
+    }
+
+    /// Exits with `exit_code` right after answering the `calls`-th tool call,
+    /// or at startup when `calls` is `0`.
+    pub fn crashing_after(mut self, calls: u32, exit_code: i32) -> Self {
+        self.crash_after_calls = Some(calls);
+        self.exit_code = exit_code;
+        self
+    }
+
+    /// Answers every tool call with `bytes` bytes of text.
+    pub fn with_output_bytes(mut self, bytes: usize) -> Self {
+        self.output_bytes = bytes;
+        self
+    }
+
+    pub fn violating(mut self, violation: Violation) -> Self {
+        self.violation = Some(violation);
+        self
+    }
+
+    /// Returns the `berth test-server` arguments that run this server.
+    pub fn args(&self) -> Vec<String> {
+        let mut args = vec!["test-server".to_string()];
+        if self.name != DEFAULT_NAME {
+            args.extend(["--name".to_string(), self.name.clone()]);
+        }
+        for tool in &self.tools {
+            args.extend(["--tool".to_string(), tool.clone()]);
+        }
+        if !self.startup_delay.is_zero() {
+            let ms = self.startup_delay.as_millis().to_string();
+            args.extend(["--startup-delay-ms".to_string(), ms]);
+        }
+        if let Some(calls) = self.crash_after_calls {
+            args.extend(["--crash-after".to_string(), calls.to_string()]);
+            args.extend(["--exit-code".to_string(), self.exit_code.to_string()]);
+        }
+        if self.output_bytes > 0 {
+            let bytes = self.output_bytes.to_string();
+            args.extend(["--output-bytes".to_string(), bytes]);
+        }
+        if let Some(violation) = self.violation {
+            args.extend(["--violate".to_string(), violation.as_str().to_string()]);
+        }
+        args
+    }
+
+    /// Answers requests from `input` on `output` until the input closes or
+    /// the server crashes.
+    pub fn serve(&self, input: impl BufRead, mut output: impl Write) -> io::Result<Exit> {
+        thread::sleep(self.startup_delay);
+        let crash = Exit::Crashed {
+            exit_code: self.exit_code,
+        };
+        if self.crash_after_calls == Some(0) {
+            return Ok(crash);
+        }
+        let mut calls = 0;
+        for line in input.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let Ok(request) = serde_json::from_str::<Value>(&line) else {
+                let answer = error(Value::Null, PARSE_ERROR, "Parse error".to_string());
+                write_line(&mut output, &answer.to_string())?;
+                continue;
+            };
+            // Notifications and answers to our (nonexistent) requests get no reply.
+            let (Some(method), Some(id)) = (
+                request.get("method").and_then(Value::as_str),
+                request.get("id").cloned(),
+            ) else {
+                continue;
+            };
+            let params = request.get("params").cloned().unwrap_or(Value::Null);
+            match method {
+                "initialize" => write_line(&mut output, &self.initialize(id, &params).to_string())?,
+                "ping" => write_line(&mut output, &result(id, json!({})).to_string())?,
+                "tools/list" => write_line(&mut output, &self.list_tools(id).to_string())?,
+                "tools/call" => {
+                    calls += 1;
+                    let answer = self.call_tool(id, &params);
+                    self.write_call_answer(&mut output, answer)?;
+                    if self.crash_after_calls == Some(calls) {
+                        return Ok(crash);
+                    }
+                }
+                _ => {
+                    let message = format!("Method not found: {method}");
+                    write_line(
+                        &mut output,
+                        &error(id, METHOD_NOT_FOUND, message).to_string(),
+                    )?
+                }
+            }
+        }
+        Ok(Exit::InputClosed)
+    }
+
+    fn initialize(&self, id: Value, params: &Value) -> Value {
+        let protocol = params
+            .get("protocolVersion")
+            .and_then(Value::as_str)
+            .unwrap_or(PROTOCOL_VERSION);
+        result(
+            id,
+            json!({
+                "protocolVersion": protocol,
+                "capabilities": {"tools": {}},
+                "serverInfo": {"name": self.name, "version": env!("CARGO_PKG_VERSION")},
+            }),
+        )
+    }
+
+    fn list_tools(&self, id: Value) -> Value {
+        let tools: Vec<Value> = self
+            .tools
+            .iter()
+            .map(|name| {
+                json!({
+                    "name": name,
+                    "description": format!("Fake tool `{name}`"),
+                    "inputSchema": {"type": "object"},
+                })
+            })
+            .collect();
+        result(id, json!({ "tools": tools }))
+    }
+
+    fn call_tool(&self, id: Value, params: &Value) -> Value {
+        let name = params
+            .get("name")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        if !self.tools.iter().any(|tool| tool == name) {
+            return error(id, INVALID_PARAMS, format!("Unknown tool: {name}"));
+        }
+        let text = if self.output_bytes > 0 {
+            "x".repeat(self.output_bytes)
+        } else {
+            params
+                .get("arguments")
+                .cloned()
+                .unwrap_or_else(|| json!({}))
+                .to_string()
+        };
+        result(id, json!({"content": [{"type": "text", "text": text}]}))
+    }
+
+    fn write_call_answer(&self, output: &mut impl Write, mut answer: Value) -> io::Result<()> {
+        match self.violation {
+            None => write_line(output, &answer.to_string()),
+            Some(Violation::MalformedJson) => {
+                let mut line = answer.to_string();
+                line.pop();
+                write_line(output, &line)
+            }
+            Some(Violation::WrongId) => {
+                answer["id"] = json!("berth-test-server-unknown-id");
+                write_line(output, &answer.to_string())
+            }
+            Some(Violation::MissingVersion) => {
+                if let Some(object) = answer.as_object_mut() {
+                    object.remove("jsonrpc");
+                }
+                write_line(output, &answer.to_string())
+            }
+            Some(Violation::StdoutNoise) => {
+                write_line(output, "berth-test-server: handling tools/call")?;
+                write_line(output, &answer.to_string())
+            }
+        }
+    }
+}
+
+fn result(id: Value, result: Value) -> Value {
+    json!({"jsonrpc": "2.0", "id": id, "result": result})
+}
+
+fn error(id: Value, code: i64, message: String) -> Value {
+    json!({"jsonrpc": "2.0", "id": id, "error": {"code": code, "message": message}})
+}
+
+fn write_line(output: &mut impl Write, line: &str) -> io::Result<()> {
+    output.write_all(line.as_bytes())?;
+    output.write_all(b"\n")?;
+    output.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exchange(server: &FakeServer, requests: &[&str]) -> (Exit, Vec<String>) {
+        let input = requests.join("\n");
+        let mut output = Vec::new();
+        let exit = server.serve(input.as_bytes(), &mut output).unwrap();
+        let lines = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(str::to_string)
+            .collect();
+        (exit, lines)
+    }
+
+    fn parse(line: &str) -> Value {
+        serde_json::from_str(line).unwrap()
+    }
+
+    #[test]
+    fn answers_the_basic_methods_and_ignores_notifications() {
+        let server = FakeServer::new().with_name("fixture");
+        let (exit, lines) = exchange(
+            &server,
+            &[
+                r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":"2025-03-26"}}"#,
+                r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#,
+                r#"{"jsonrpc":"2.0","id":2,"method":"tools/list"}"#,
+                r#"{"jsonrpc":"2.0","id":3,"method":"tools/call","params":{"name":"echo","arguments":{"q":"berth"}}}"#,
+                r#"{"jsonrpc":"2.0","id":4,"method":"tools/call","params":{"name":"missing"}}"#,
+                r#"{"jsonrpc":"2.0","id":5,"method":"resources/list"}"#,
+                "not json",
+            ],
+        );
+        assert_eq!(exit, Exit::InputClosed);
+        assert_eq!(lines.len(), 6);
+        let init = parse(&lines[0]);
+        assert_eq!(init["result"]["protocolVersion"], "2025-03-26");
+        assert_eq!(init["result"]["serverInfo"]["name"], "fixture");
+        assert_eq!(parse(&lines[1])["result"]["tools"][0]["name"], "echo");
+        assert_eq!(
+            parse(&lines[2])["result"]["content"][0]["text"],
+            r#"{"q":"berth"}"#
+        );
+        assert_eq!(parse(&lines[3])["error"]["code"], INVALID_PARAMS);
+        assert_eq!(parse(&lines[4])["error"]["code"], METHOD_NOT_FOUND);
+        assert_eq!(parse(&lines[5])["error"]["code"], PARSE_ERROR);
+    }
+
+    #[test]
+    fn crashes_after_the_configured_calls_with_large_outputs() {
+        let server = FakeServer::new()
+            .with_output_bytes(100_000)
+            .crashing_after(2, 7);
+        let call = r#"{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{"name":"echo"}}"#;
+        let (exit, lines) = exchange(&server, &[call, call, call]);
+        assert_eq!(exit, Exit::Crashed { exit_code: 7 });
+        assert_eq!(lines.len(), 2);
+        let text = parse(&lines[1])["result"]["content"][0]["text"].clone();
+        assert_eq!(text.as_str().unwrap().len(), 100_000);
+
+        let (exit, lines) = exchange(&FakeServer::new().crashing_after(0, 3), &[call]);
+        assert_eq!(exit, Exit::Crashed { exit_code: 3 });
+        assert!(lines.is_empty());
+    }
+
+    #[test]
+    fn breaks_only_tool_call_answers() {
+        let init = r#"{"jsonrpc":"2.0","id":1,"method":"initialize"}"#;
+        let call = r#"{"jsonrpc":"2.0","id":2,"method":"tools/call","params":{"name":"echo"}}"#;
+        for violation in Violation::ALL {
+            let server = FakeServer::new().violating(violation);
+            let (_, lines) = exchange(&server, &[init, call]);
+            assert_eq!(parse(&lines[0])["id"], 1, "{violation:?}");
+            let answer = lines.last().unwrap();
+            match violation {
+                Violation::MalformedJson => {
+                    assert!(serde_json::from_str::<Value>(answer).is_err())
+                }
+                Violation::WrongId => assert_ne!(parse(answer)["id"], 2),
+                Violation::MissingVersion => assert!(parse(answer).get("jsonrpc").is_none()),
+                Violation::StdoutNoise => {
+                    assert_eq!(lines.len(), 3);
+                    assert!(serde_json::from_str::<Value>(&lines[1]).is_err());
+                    assert_eq!(parse(answer)["id"], 2);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn renders_test_server_arguments() {
+        assert_eq!(FakeServer::new().args(), ["test-server", "--tool", "echo"]);
+        let server = FakeServer::new()
+            .with_tools(&["a", "b"])
+            .with_startup_delay(Duration::from_millis(250))
+            .crashing_after(3, 9)
+            .violating(Violation::WrongId);
+        assert_eq!(
+            server.args(),
+            [
+                "test-server",
+                "--tool",
+                "a",
+                "--tool",
+                "b",
+                "--startup-delay-ms",
+                "250",
+                "--crash-after",
+                "3",
+                "--exit-code",
+                "9",
+                "--violate",
+                "wrong-id",
+            ]
+        );
+        assert_eq!(Violation::parse("stdout-noise"), Ok(Violation::StdoutNoise));
+        assert!(Violation::parse("loud").is_err());
+    }
+}
@@ -0,0 +1,15 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright (c) 2026 Schwimmbeck Dominik
+
+//! Test fixtures for exercising Berth against misbehaving MCP servers.
+//!
+//! [`FakeServer`] is a configurable stdio MCP server: it can start slowly,
+//! crash after a number of tool calls, answer with very large outputs, or
+//! break the protocol in specific ways. `berth test-server` runs one, so a
+//! server entry can point at it and go through `berth start`, `berth proxy`,
+//! or `berth wrap` like any other server. Server authors can use the same
+//! fixtures to see how Berth treats a given failure before shipping.
+
+pub mod fake_server;
+
+pub use fake_server::{Exit, FakeServer, Violation, DEFAULT_NAME};